@@ -1,18 +1,21 @@
 use std::collections::{HashMap, HashSet};
 
-use anyhow::Context;
+use anyhow::{Context, anyhow};
 use colored::Colorize;
 use comfy_table::{Attribute, Cell, Color};
 use futures_util::{
   FutureExt, TryStreamExt, stream::FuturesUnordered,
 };
 use komodo_client::{
-  api::read::{
-    InspectDockerContainer, ListAllDockerContainers, ListServers,
+  api::{
+    log::FollowContainerLogBody,
+    read::{
+      InspectDockerContainer, ListAllDockerContainers, ListServers,
+    },
   },
   entities::{
     config::cli::args::container::{
-      Container, ContainerCommand, InspectContainer,
+      Container, ContainerCommand, FollowLog, InspectContainer,
     },
     docker::{
       self,
@@ -133,6 +136,59 @@ async fn list_containers(
   Ok(())
 }
 
+pub async fn follow_log(follow: &FollowLog) -> anyhow::Result<()> {
+  let client = super::komodo_client().await?;
+
+  let server = match &follow.server {
+    Some(server) => server.clone(),
+    None => {
+      let mut servers = client
+        .read(ListAllDockerContainers {
+          servers: Default::default(),
+        })
+        .await?
+        .into_iter()
+        .filter(|c| c.name == follow.container)
+        .filter_map(|c| c.server_id)
+        .collect::<HashSet<_>>()
+        .into_iter();
+      match (servers.next(), servers.next()) {
+        (Some(server), None) => server,
+        (None, _) => {
+          return Err(anyhow!(
+            "Did not find any container named '{}'",
+            follow.container
+          ));
+        }
+        (Some(_), Some(_)) => {
+          return Err(anyhow!(
+            "Found containers named '{}' on multiple servers, select one with --server",
+            follow.container
+          ));
+        }
+      }
+    }
+  };
+
+  let res = client
+    .follow_container_log(FollowContainerLogBody {
+      server,
+      container: follow.container.clone(),
+      tail: follow.tail,
+      timestamps: follow.timestamps,
+      filter: follow.filter.clone().unwrap_or_default(),
+    })
+    .await?;
+  let mut lines = std::pin::pin!(res.into_line_stream());
+  while let Some(line) =
+    lines.try_next().await.context("Failed to read log line")?
+  {
+    println!("{line}");
+  }
+
+  Ok(())
+}
+
 pub async fn inspect_container(
   inspect: &InspectContainer,
 ) -> anyhow::Result<()> {
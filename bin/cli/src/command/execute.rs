@@ -1,21 +1,13 @@
 use std::time::Duration;
 
 use colored::Colorize;
-use futures_util::{StreamExt, stream::FuturesUnordered};
 use komodo_client::{
-  api::execute::{
-    BatchExecutionResponse, BatchExecutionResponseItem, Execution,
-  },
+  api::execute::Execution,
   entities::{resource_link, update::Update},
 };
 
 use crate::config::cli_config;
 
-enum ExecutionResult {
-  Single(Box<Update>),
-  Batch(BatchExecutionResponse),
-}
-
 pub async fn handle(
   execution: &Execution,
   yes: bool,
@@ -83,6 +75,15 @@ pub async fn handle(
     Execution::BatchDestroyDeployment(data) => {
       println!("{}: {data:?}", "Data".dimmed())
     }
+    Execution::DeploySwarmService(data) => {
+      println!("{}: {data:?}", "Data".dimmed())
+    }
+    Execution::ScaleSwarmService(data) => {
+      println!("{}: {data:?}", "Data".dimmed())
+    }
+    Execution::RemoveSwarmService(data) => {
+      println!("{}: {data:?}", "Data".dimmed())
+    }
     Execution::CloneRepo(data) => {
       println!("{}: {data:?}", "Data".dimmed())
     }
@@ -95,6 +96,9 @@ pub async fn handle(
     Execution::BatchPullRepo(data) => {
       println!("{}: {data:?}", "Data".dimmed())
     }
+    Execution::RunRepoPipeline(data) => {
+      println!("{}: {data:?}", "Data".dimmed())
+    }
     Execution::BuildRepo(data) => {
       println!("{}: {data:?}", "Data".dimmed())
     }
@@ -167,6 +171,9 @@ pub async fn handle(
     Execution::PruneSystem(data) => {
       println!("{}: {data:?}", "Data".dimmed())
     }
+    Execution::RunScheduledCommand(data) => {
+      println!("{}: {data:?}", "Data".dimmed())
+    }
     Execution::RunSync(data) => {
       println!("{}: {data:?}", "Data".dimmed())
     }
@@ -215,6 +222,9 @@ pub async fn handle(
     Execution::RunStackService(data) => {
       println!("{}: {data:?}", "Data".dimmed())
     }
+    Execution::ScaleStackService(data) => {
+      println!("{}: {data:?}", "Data".dimmed())
+    }
     Execution::TestAlerter(data) => {
       println!("{}: {data:?}", "Data".dimmed())
     }
@@ -242,258 +252,168 @@ pub async fn handle(
   let client = super::komodo_client().await?;
 
   let res = match execution.clone() {
-    Execution::RunAction(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
+    Execution::RunAction(request) => client.execute(request).await,
     Execution::BatchRunAction(request) => {
-      client.execute(request).await.map(ExecutionResult::Batch)
+      client.execute(request).await
     }
-    Execution::RunProcedure(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
+    Execution::RunProcedure(request) => client.execute(request).await,
     Execution::BatchRunProcedure(request) => {
-      client.execute(request).await.map(ExecutionResult::Batch)
+      client.execute(request).await
     }
-    Execution::RunBuild(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
+    Execution::RunBuild(request) => client.execute(request).await,
     Execution::BatchRunBuild(request) => {
-      client.execute(request).await.map(ExecutionResult::Batch)
-    }
-    Execution::CancelBuild(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::Deploy(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::BatchDeploy(request) => {
-      client.execute(request).await.map(ExecutionResult::Batch)
-    }
-    Execution::PullDeployment(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::StartDeployment(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::RestartDeployment(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::PauseDeployment(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::UnpauseDeployment(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::StopDeployment(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::DestroyDeployment(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
+      client.execute(request).await
+    }
+    Execution::CancelBuild(request) => client.execute(request).await,
+    Execution::Deploy(request) => client.execute(request).await,
+    Execution::BatchDeploy(request) => client.execute(request).await,
+    Execution::PullDeployment(request) => {
+      client.execute(request).await
+    }
+    Execution::StartDeployment(request) => {
+      client.execute(request).await
+    }
+    Execution::RestartDeployment(request) => {
+      client.execute(request).await
+    }
+    Execution::PauseDeployment(request) => {
+      client.execute(request).await
+    }
+    Execution::UnpauseDeployment(request) => {
+      client.execute(request).await
+    }
+    Execution::StopDeployment(request) => {
+      client.execute(request).await
+    }
+    Execution::DestroyDeployment(request) => {
+      client.execute(request).await
+    }
     Execution::BatchDestroyDeployment(request) => {
-      client.execute(request).await.map(ExecutionResult::Batch)
+      client.execute(request).await
+    }
+    Execution::DeploySwarmService(request) => {
+      client.execute(request).await
+    }
+    Execution::ScaleSwarmService(request) => {
+      client.execute(request).await
     }
-    Execution::CloneRepo(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
+    Execution::RemoveSwarmService(request) => {
+      client.execute(request).await
+    }
+    Execution::CloneRepo(request) => client.execute(request).await,
     Execution::BatchCloneRepo(request) => {
-      client.execute(request).await.map(ExecutionResult::Batch)
+      client.execute(request).await
     }
-    Execution::PullRepo(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
+    Execution::PullRepo(request) => client.execute(request).await,
     Execution::BatchPullRepo(request) => {
-      client.execute(request).await.map(ExecutionResult::Batch)
+      client.execute(request).await
+    }
+    Execution::RunRepoPipeline(request) => {
+      client.execute(request).await
     }
-    Execution::BuildRepo(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
+    Execution::BuildRepo(request) => client.execute(request).await,
     Execution::BatchBuildRepo(request) => {
-      client.execute(request).await.map(ExecutionResult::Batch)
-    }
-    Execution::CancelRepoBuild(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::StartContainer(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::RestartContainer(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::PauseContainer(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::UnpauseContainer(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::StopContainer(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::DestroyContainer(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::StartAllContainers(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::RestartAllContainers(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::PauseAllContainers(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::UnpauseAllContainers(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::StopAllContainers(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::PruneContainers(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::DeleteNetwork(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::PruneNetworks(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::DeleteImage(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::PruneImages(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::DeleteVolume(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::PruneVolumes(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::PruneDockerBuilders(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::PruneBuildx(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::PruneSystem(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::RunSync(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::CommitSync(request) => client
-      .write(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::DeployStack(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
+      client.execute(request).await
+    }
+    Execution::CancelRepoBuild(request) => {
+      client.execute(request).await
+    }
+    Execution::StartContainer(request) => {
+      client.execute(request).await
+    }
+    Execution::RestartContainer(request) => {
+      client.execute(request).await
+    }
+    Execution::PauseContainer(request) => {
+      client.execute(request).await
+    }
+    Execution::UnpauseContainer(request) => {
+      client.execute(request).await
+    }
+    Execution::StopContainer(request) => {
+      client.execute(request).await
+    }
+    Execution::DestroyContainer(request) => {
+      client.execute(request).await
+    }
+    Execution::StartAllContainers(request) => {
+      client.execute(request).await
+    }
+    Execution::RestartAllContainers(request) => {
+      client.execute(request).await
+    }
+    Execution::PauseAllContainers(request) => {
+      client.execute(request).await
+    }
+    Execution::UnpauseAllContainers(request) => {
+      client.execute(request).await
+    }
+    Execution::StopAllContainers(request) => {
+      client.execute(request).await
+    }
+    Execution::PruneContainers(request) => {
+      client.execute(request).await
+    }
+    Execution::DeleteNetwork(request) => {
+      client.execute(request).await
+    }
+    Execution::PruneNetworks(request) => {
+      client.execute(request).await
+    }
+    Execution::DeleteImage(request) => client.execute(request).await,
+    Execution::PruneImages(request) => client.execute(request).await,
+    Execution::DeleteVolume(request) => client.execute(request).await,
+    Execution::PruneVolumes(request) => client.execute(request).await,
+    Execution::PruneDockerBuilders(request) => {
+      client.execute(request).await
+    }
+    Execution::PruneBuildx(request) => client.execute(request).await,
+    Execution::PruneSystem(request) => client.execute(request).await,
+    Execution::RunScheduledCommand(request) => {
+      client.execute(request).await
+    }
+    Execution::RunSync(request) => client.execute(request).await,
+    Execution::CommitSync(request) => client.write(request).await,
+    Execution::DeployStack(request) => client.execute(request).await,
     Execution::BatchDeployStack(request) => {
-      client.execute(request).await.map(ExecutionResult::Batch)
+      client.execute(request).await
+    }
+    Execution::DeployStackIfChanged(request) => {
+      client.execute(request).await
     }
-    Execution::DeployStackIfChanged(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
     Execution::BatchDeployStackIfChanged(request) => {
-      client.execute(request).await.map(ExecutionResult::Batch)
+      client.execute(request).await
     }
-    Execution::PullStack(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
+    Execution::PullStack(request) => client.execute(request).await,
     Execution::BatchPullStack(request) => {
-      client.execute(request).await.map(ExecutionResult::Batch)
-    }
-    Execution::StartStack(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::RestartStack(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::PauseStack(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::UnpauseStack(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::StopStack(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::DestroyStack(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
+      client.execute(request).await
+    }
+    Execution::StartStack(request) => client.execute(request).await,
+    Execution::RestartStack(request) => client.execute(request).await,
+    Execution::PauseStack(request) => client.execute(request).await,
+    Execution::UnpauseStack(request) => client.execute(request).await,
+    Execution::StopStack(request) => client.execute(request).await,
+    Execution::DestroyStack(request) => client.execute(request).await,
     Execution::BatchDestroyStack(request) => {
-      client.execute(request).await.map(ExecutionResult::Batch)
-    }
-    Execution::RunStackService(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::TestAlerter(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::SendAlert(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::ClearRepoCache(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::BackupCoreDatabase(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
-    Execution::GlobalAutoUpdate(request) => client
-      .execute(request)
-      .await
-      .map(|u| ExecutionResult::Single(u.into())),
+      client.execute(request).await
+    }
+    Execution::RunStackService(request) => {
+      client.execute(request).await
+    }
+    Execution::ScaleStackService(request) => {
+      client.execute(request).await
+    }
+    Execution::TestAlerter(request) => client.execute(request).await,
+    Execution::SendAlert(request) => client.execute(request).await,
+    Execution::ClearRepoCache(request) => {
+      client.execute(request).await
+    }
+    Execution::BackupCoreDatabase(request) => {
+      client.execute(request).await
+    }
+    Execution::GlobalAutoUpdate(request) => {
+      client.execute(request).await
+    }
     Execution::Sleep(request) => {
       let duration =
         Duration::from_millis(request.duration_ms as u64);
@@ -505,34 +425,7 @@ pub async fn handle(
   };
 
   match res {
-    Ok(ExecutionResult::Single(update)) => {
-      poll_update_until_complete(&update).await
-    }
-    Ok(ExecutionResult::Batch(updates)) => {
-      let mut handles = updates
-        .iter()
-        .map(|update| async move {
-          match update {
-            BatchExecutionResponseItem::Ok(update) => {
-              poll_update_until_complete(update).await
-            }
-            BatchExecutionResponseItem::Err(e) => {
-              error!("{e:#?}");
-              Ok(())
-            }
-          }
-        })
-        .collect::<FuturesUnordered<_>>();
-      while let Some(res) = handles.next().await {
-        match res {
-          Ok(()) => {}
-          Err(e) => {
-            error!("{e:#?}");
-          }
-        }
-      }
-      Ok(())
-    }
+    Ok(update) => poll_update_until_complete(&update).await,
     Err(e) => {
       error!("{e:#?}");
       Ok(())
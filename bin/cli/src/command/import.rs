@@ -0,0 +1,737 @@
+use std::{
+  collections::{HashMap, HashSet},
+  path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use colored::Colorize;
+use comfy_table::{Attribute, Cell, Color};
+use komodo_client::{
+  api::{
+    read::{ListServers, ListStacks},
+    write::{CreateServer, CreateStack},
+  },
+  entities::{
+    config::cli::args::{CliFormat, import::ImportCommand},
+    server::PartialServerConfig,
+    stack::PartialStackConfig,
+  },
+};
+use serde::{Deserialize, Serialize};
+
+use crate::command::{PrintTable, print_items};
+
+/// The compose file names picked up by `docker compose`, in priority order.
+const COMPOSE_FILE_NAMES: [&str; 4] = [
+  "compose.yaml",
+  "compose.yml",
+  "docker-compose.yaml",
+  "docker-compose.yml",
+];
+
+const COMPOSE_OVERRIDE_FILE_NAMES: [&str; 4] = [
+  "compose.override.yaml",
+  "compose.override.yml",
+  "docker-compose.override.yaml",
+  "docker-compose.override.yml",
+];
+
+pub async fn handle(command: &ImportCommand) -> anyhow::Result<()> {
+  match command {
+    ImportCommand::Compose {
+      path,
+      server,
+      dry_run,
+      format,
+      yes,
+    } => {
+      println!("\n{}: Import Compose Projects\n", "Mode".dimmed());
+      println!(" - {}: {path:?}", "Path".dimmed());
+      println!(" - {}: {server}", "Server".dimmed());
+      let plan = compose_import_plan(path, server)?;
+      run_import(plan, *dry_run, *format, *yes).await
+    }
+    ImportCommand::Portainer {
+      path,
+      stacks,
+      endpoints,
+      server,
+      dry_run,
+      format,
+      yes,
+    } => {
+      println!("\n{}: Import Portainer Stacks\n", "Mode".dimmed());
+      println!(" - {}: {path:?}", "Path".dimmed());
+      if let Some(stacks) = stacks {
+        println!(" - {}: {stacks:?}", "Stacks".dimmed());
+      }
+      if let Some(endpoints) = endpoints {
+        println!(" - {}: {endpoints:?}", "Endpoints".dimmed());
+      }
+      if let Some(server) = server {
+        println!(" - {}: {server}", "Server".dimmed());
+      }
+      let plan = portainer_import_plan(
+        path,
+        stacks.as_deref(),
+        endpoints.as_deref(),
+        server.as_deref(),
+      )?;
+      run_import(plan, *dry_run, *format, *yes).await
+    }
+  }
+}
+
+#[derive(Default)]
+struct ImportPlan {
+  servers: Vec<ServerImport>,
+  stacks: Vec<StackImport>,
+  /// Sources which could not be translated at all.
+  skipped: Vec<ImportReportItem>,
+}
+
+struct ServerImport {
+  source: String,
+  name: String,
+}
+
+struct StackImport {
+  source: String,
+  name: String,
+  config: PartialStackConfig,
+  notes: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct ImportReportItem {
+  source: String,
+  resource: &'static str,
+  name: String,
+  status: ImportStatus,
+  notes: Vec<String>,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum ImportStatus {
+  /// Will be created (dry run / before confirmation).
+  Create,
+  Created,
+  /// A resource with the same name already exists.
+  Exists,
+  Skipped,
+  Failed,
+}
+
+impl ImportStatus {
+  fn as_str(&self) -> &'static str {
+    match self {
+      ImportStatus::Create => "Create",
+      ImportStatus::Created => "Created",
+      ImportStatus::Exists => "Exists",
+      ImportStatus::Skipped => "Skipped",
+      ImportStatus::Failed => "Failed",
+    }
+  }
+}
+
+impl PrintTable for ImportReportItem {
+  fn header(_links: bool) -> &'static [&'static str] {
+    &["Source", "Resource", "Name", "Status", "Notes"]
+  }
+  fn row(self, _links: bool) -> Vec<Cell> {
+    let color = match self.status {
+      ImportStatus::Create | ImportStatus::Created => Color::Green,
+      ImportStatus::Exists => Color::Blue,
+      ImportStatus::Skipped => Color::DarkYellow,
+      ImportStatus::Failed => Color::Red,
+    };
+    vec![
+      Cell::new(self.source),
+      Cell::new(self.resource),
+      Cell::new(self.name).add_attribute(Attribute::Bold),
+      Cell::new(self.status.as_str())
+        .fg(color)
+        .add_attribute(Attribute::Bold),
+      Cell::new(self.notes.join("\n")),
+    ]
+  }
+}
+
+async fn run_import(
+  plan: ImportPlan,
+  dry_run: bool,
+  format: CliFormat,
+  yes: bool,
+) -> anyhow::Result<()> {
+  let client = super::komodo_client().await?;
+
+  let (servers, stacks) = tokio::try_join!(
+    client.read(ListServers::default()),
+    client.read(ListStacks::default()),
+  )?;
+  let existing_servers =
+    servers.into_iter().map(|s| s.name).collect::<HashSet<_>>();
+  let existing_stacks =
+    stacks.into_iter().map(|s| s.name).collect::<HashSet<_>>();
+
+  let mut servers = Vec::new();
+  let mut report = Vec::new();
+
+  for server in plan.servers {
+    let status = if existing_servers.contains(&server.name) {
+      ImportStatus::Exists
+    } else {
+      ImportStatus::Create
+    };
+    report.push(ImportReportItem {
+      source: server.source.clone(),
+      resource: "Server",
+      name: server.name.clone(),
+      status,
+      notes: if status == ImportStatus::Create {
+        vec![String::from(
+          "Created disabled, configure the Periphery address and enable",
+        )]
+      } else {
+        Vec::new()
+      },
+    });
+    if status == ImportStatus::Create {
+      servers.push((report.len() - 1, server));
+    }
+  }
+
+  let mut stacks = Vec::new();
+
+  for stack in plan.stacks {
+    let status = if existing_stacks.contains(&stack.name) {
+      ImportStatus::Exists
+    } else {
+      ImportStatus::Create
+    };
+    report.push(ImportReportItem {
+      source: stack.source.clone(),
+      resource: "Stack",
+      name: stack.name.clone(),
+      status,
+      notes: stack.notes.clone(),
+    });
+    if status == ImportStatus::Create {
+      stacks.push((report.len() - 1, stack));
+    }
+  }
+
+  report.extend(plan.skipped);
+
+  if dry_run || (servers.is_empty() && stacks.is_empty()) {
+    print_items(report, format, false)?;
+    if !dry_run {
+      info!("Nothing to import");
+    }
+    return Ok(());
+  }
+
+  println!(
+    " - {}: {} Servers, {} Stacks\n",
+    "Create".dimmed(),
+    servers.len(),
+    stacks.len()
+  );
+
+  super::wait_for_enter("import resources", yes)?;
+
+  for (index, server) in servers {
+    let res = client
+      .write(CreateServer {
+        name: server.name,
+        config: PartialServerConfig {
+          enabled: Some(false),
+          ..Default::default()
+        },
+      })
+      .await;
+    let item = &mut report[index];
+    match res {
+      Ok(_) => item.status = ImportStatus::Created,
+      Err(e) => {
+        item.status = ImportStatus::Failed;
+        item.notes = vec![format!("{e:#}")];
+      }
+    }
+  }
+
+  for (index, stack) in stacks {
+    let res = client
+      .write(CreateStack {
+        name: stack.name,
+        config: stack.config,
+      })
+      .await;
+    let item = &mut report[index];
+    match res {
+      Ok(_) => item.status = ImportStatus::Created,
+      Err(e) => {
+        item.status = ImportStatus::Failed;
+        item.notes.push(format!("{e:#}"));
+      }
+    }
+  }
+
+  print_items(report, format, false)
+}
+
+// ==========
+//  COMPOSE
+// ==========
+
+fn compose_import_plan(
+  root: &Path,
+  server: &str,
+) -> anyhow::Result<ImportPlan> {
+  let mut projects = Vec::new();
+  find_compose_projects(root, &mut projects)?;
+
+  let mut plan = ImportPlan::default();
+
+  for (project, file) in projects {
+    let source = project.display().to_string();
+    let Some(name) = project
+      .canonicalize()
+      .ok()
+      .and_then(|path| Some(path.file_name()?.to_str()?.to_string()))
+    else {
+      plan.skipped.push(skipped(
+        source,
+        String::new(),
+        "Could not get a Stack name from the directory",
+      ));
+      continue;
+    };
+    let contents = match std::fs::read_to_string(&file) {
+      Ok(contents) => contents,
+      Err(e) => {
+        plan.skipped.push(skipped(
+          source,
+          name,
+          format!("Failed to read {file:?} | {e}"),
+        ));
+        continue;
+      }
+    };
+
+    let mut notes = compose_notes(&contents);
+    if let Some(file) = COMPOSE_OVERRIDE_FILE_NAMES
+      .iter()
+      .find(|file| project.join(file).is_file())
+    {
+      notes.push(format!("{file} is not imported"));
+    }
+    let environment = read_env_file(&project, &mut notes);
+
+    plan.stacks.push(StackImport {
+      source,
+      name,
+      config: PartialStackConfig {
+        server_id: Some(server.to_string()),
+        file_contents: Some(contents),
+        environment,
+        ..Default::default()
+      },
+      notes,
+    });
+  }
+
+  Ok(plan)
+}
+
+/// Finds the directories containing a compose file,
+/// without searching inside found projects.
+fn find_compose_projects(
+  dir: &Path,
+  projects: &mut Vec<(PathBuf, PathBuf)>,
+) -> anyhow::Result<()> {
+  if let Some(file) = find_compose_file(dir) {
+    projects.push((dir.to_path_buf(), file));
+    return Ok(());
+  }
+  let mut dirs = std::fs::read_dir(dir)
+    .with_context(|| format!("Failed to read directory {dir:?}"))?
+    .filter_map(|entry| {
+      let path = entry.ok()?.path();
+      let hidden = path.file_name()?.to_str()?.starts_with('.');
+      (path.is_dir() && !hidden).then_some(path)
+    })
+    .collect::<Vec<_>>();
+  dirs.sort();
+  for dir in dirs {
+    find_compose_projects(&dir, projects)?;
+  }
+  Ok(())
+}
+
+fn find_compose_file(dir: &Path) -> Option<PathBuf> {
+  COMPOSE_FILE_NAMES
+    .iter()
+    .map(|file| dir.join(file))
+    .find(|path| path.is_file())
+}
+
+/// Imports the `.env` file in the directory as the Stack environment.
+fn read_env_file(
+  dir: &Path,
+  notes: &mut Vec<String>,
+) -> Option<String> {
+  let path = dir.join(".env");
+  if !path.is_file() {
+    return None;
+  }
+  match std::fs::read_to_string(&path) {
+    Ok(environment) => Some(environment),
+    Err(e) => {
+      notes.push(format!("Failed to read .env | {e}"));
+      None
+    }
+  }
+}
+
+/// Notes on parts of the compose file which
+/// depend on files that are not imported.
+fn compose_notes(contents: &str) -> Vec<String> {
+  let mut notes = Vec::new();
+  if contents.contains("build:") {
+    notes.push(String::from(
+      "Builds from the project directory, which is not imported",
+    ));
+  }
+  if contents.contains("env_file") {
+    notes.push(String::from("env_file references are not imported"));
+  }
+  if contents.contains("./") {
+    notes.push(String::from(
+      "Relative paths reference files which are not imported",
+    ));
+  }
+  notes
+}
+
+fn skipped(
+  source: String,
+  name: String,
+  note: impl Into<String>,
+) -> ImportReportItem {
+  ImportReportItem {
+    source,
+    resource: "Stack",
+    name,
+    status: ImportStatus::Skipped,
+    notes: vec![note.into()],
+  }
+}
+
+// ===========
+//  PORTAINER
+// ===========
+
+/// An item of the Portainer API `GET /api/stacks` response.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct PortainerStack {
+  id: i64,
+  name: String,
+  /// 1: Swarm, 2: Compose, 3: Kubernetes
+  #[serde(rename = "Type")]
+  stack_type: i64,
+  #[serde(default)]
+  endpoint_id: i64,
+  #[serde(default)]
+  entry_point: String,
+  #[serde(default)]
+  env: Option<Vec<PortainerEnv>>,
+  #[serde(default)]
+  additional_files: Option<Vec<String>>,
+  #[serde(default)]
+  git_config: Option<PortainerGitConfig>,
+  #[serde(default)]
+  auto_update: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct PortainerEnv {
+  name: String,
+  #[serde(default)]
+  value: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct PortainerGitConfig {
+  #[serde(rename = "URL")]
+  url: String,
+  #[serde(default)]
+  reference_name: String,
+  #[serde(default)]
+  config_file_path: String,
+  #[serde(default)]
+  authentication: Option<serde_json::Value>,
+}
+
+/// An item of the Portainer API `GET /api/endpoints` response.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct PortainerEndpoint {
+  id: i64,
+  name: String,
+}
+
+fn portainer_import_plan(
+  root: &Path,
+  stacks: Option<&Path>,
+  endpoints: Option<&Path>,
+  server: Option<&str>,
+) -> anyhow::Result<ImportPlan> {
+  let Some(stacks) = stacks else {
+    return portainer_compose_dirs_plan(root, server);
+  };
+
+  let stacks = read_json::<Vec<PortainerStack>>(stacks)?;
+  let endpoints = match endpoints {
+    Some(endpoints) => {
+      read_json::<Vec<PortainerEndpoint>>(endpoints)?
+        .into_iter()
+        .map(|endpoint| (endpoint.id, endpoint.name))
+        .collect()
+    }
+    None => HashMap::new(),
+  };
+
+  let mut plan = ImportPlan::default();
+  let mut servers = HashSet::new();
+
+  for stack in stacks {
+    let source = format!("Portainer stack {}", stack.id);
+    match stack.stack_type {
+      1 => {
+        plan.skipped.push(skipped(
+          source,
+          stack.name,
+          "Swarm stacks are not imported, they can be created as Swarm Services",
+        ));
+        continue;
+      }
+      3 => {
+        plan.skipped.push(skipped(
+          source,
+          stack.name,
+          "Kubernetes stacks are not supported",
+        ));
+        continue;
+      }
+      _ => {}
+    }
+
+    let mut notes = Vec::new();
+
+    let server_name = match endpoints.get(&stack.endpoint_id) {
+      Some(endpoint) => {
+        if servers.insert(endpoint.clone()) {
+          plan.servers.push(ServerImport {
+            source: format!(
+              "Portainer environment {}",
+              stack.endpoint_id
+            ),
+            name: endpoint.clone(),
+          });
+        }
+        Some(endpoint.clone())
+      }
+      None => server.map(str::to_string),
+    };
+    if server_name.is_none() {
+      notes.push(String::from("No Server attached"));
+    }
+
+    let environment =
+      stack.env.filter(|env| !env.is_empty()).map(|env| {
+        env
+          .into_iter()
+          .map(|var| format!("{}={}\n", var.name, var.value))
+          .collect::<String>()
+      });
+
+    if stack.auto_update.is_some_and(|auto| !auto.is_null()) {
+      notes.push(String::from(
+        "Auto update is not imported, use Stack auto update or webhooks",
+      ));
+    }
+
+    let additional_files = stack.additional_files.unwrap_or_default();
+
+    let config = if let Some(git) = stack.git_config {
+      let Some((https, provider, repo)) = parse_git_url(&git.url)
+      else {
+        plan.skipped.push(skipped(
+          source,
+          stack.name,
+          format!("Could not parse git url {}", git.url),
+        ));
+        continue;
+      };
+      if git.authentication.is_some_and(|auth| !auth.is_null()) {
+        notes.push(String::from(
+          "Git credentials are not imported, attach a git account",
+        ));
+      }
+      let file_path = if git.config_file_path.is_empty() {
+        stack.entry_point
+      } else {
+        git.config_file_path
+      };
+      PartialStackConfig {
+        server_id: server_name,
+        git_provider: Some(provider),
+        git_https: Some(https),
+        repo: Some(repo),
+        branch: git
+          .reference_name
+          .strip_prefix("refs/heads/")
+          .map(str::to_string)
+          .filter(|branch| !branch.is_empty()),
+        file_paths: Some(
+          [file_path].into_iter().chain(additional_files).collect(),
+        ),
+        environment,
+        ..Default::default()
+      }
+    } else {
+      let dir = root.join("compose").join(stack.id.to_string());
+      let file = if stack.entry_point.is_empty() {
+        find_compose_file(&dir)
+      } else {
+        Some(dir.join(&stack.entry_point))
+      };
+      let contents = file
+        .as_ref()
+        .and_then(|file| std::fs::read_to_string(file).ok());
+      let Some(contents) = contents else {
+        plan.skipped.push(skipped(
+          source,
+          stack.name,
+          format!("Compose file not found in {dir:?}"),
+        ));
+        continue;
+      };
+      notes.extend(compose_notes(&contents));
+      if !additional_files.is_empty() {
+        notes.push(format!(
+          "Additional files are not imported: {}",
+          additional_files.join(", ")
+        ));
+      }
+      PartialStackConfig {
+        server_id: server_name,
+        file_contents: Some(contents),
+        environment,
+        ..Default::default()
+      }
+    };
+
+    plan.stacks.push(StackImport {
+      source,
+      name: stack.name,
+      config,
+      notes,
+    });
+  }
+
+  Ok(plan)
+}
+
+/// Without the Portainer stack list, only the compose files
+/// stored in the data directory are available.
+fn portainer_compose_dirs_plan(
+  root: &Path,
+  server: Option<&str>,
+) -> anyhow::Result<ImportPlan> {
+  let compose_dir = root.join("compose");
+  let mut dirs = std::fs::read_dir(&compose_dir)
+    .with_context(|| {
+      format!("Failed to read directory {compose_dir:?}")
+    })?
+    .filter_map(|entry| {
+      let path = entry.ok()?.path();
+      path.is_dir().then_some(path)
+    })
+    .collect::<Vec<_>>();
+  dirs.sort();
+
+  let mut plan = ImportPlan::default();
+
+  for dir in dirs {
+    let Some(id) = dir.file_name().and_then(|id| id.to_str()) else {
+      continue;
+    };
+    let source = format!("Portainer stack {id}");
+    let name = format!("portainer-{id}");
+    let contents = find_compose_file(&dir)
+      .and_then(|file| std::fs::read_to_string(file).ok());
+    let Some(contents) = contents else {
+      plan.skipped.push(skipped(
+        source,
+        name,
+        format!("Compose file not found in {dir:?}"),
+      ));
+      continue;
+    };
+    let mut notes = compose_notes(&contents);
+    notes.push(String::from(
+      "The name and environment are stored in the Portainer database, pass --stacks to import them",
+    ));
+    if server.is_none() {
+      notes.push(String::from("No Server attached"));
+    }
+    plan.stacks.push(StackImport {
+      source,
+      name,
+      config: PartialStackConfig {
+        server_id: server.map(str::to_string),
+        file_contents: Some(contents),
+        ..Default::default()
+      },
+      notes,
+    });
+  }
+
+  Ok(plan)
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(
+  path: &Path,
+) -> anyhow::Result<T> {
+  let contents = std::fs::read_to_string(path)
+    .with_context(|| format!("Failed to read {path:?}"))?;
+  serde_json::from_str(&contents)
+    .with_context(|| format!("Failed to parse {path:?}"))
+}
+
+/// Parses `https://github.com/owner/repo.git`
+/// into (https, provider, repo).
+fn parse_git_url(url: &str) -> Option<(bool, String, String)> {
+  let (https, rest) = if let Some(rest) = url.strip_prefix("https://")
+  {
+    (true, rest)
+  } else if let Some(rest) = url.strip_prefix("http://") {
+    (false, rest)
+  } else {
+    return None;
+  };
+  // Remove any credentials
+  let rest =
+    rest.rsplit_once('@').map(|(_, rest)| rest).unwrap_or(rest);
+  let (provider, repo) = rest.split_once('/')?;
+  let repo = repo.trim_end_matches('/').trim_end_matches(".git");
+  if provider.is_empty() || repo.is_empty() {
+    return None;
+  }
+  Some((https, provider.to_string(), repo.to_string()))
+}
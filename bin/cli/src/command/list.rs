@@ -421,14 +421,18 @@ impl ListResources for DeploymentListItem {
         } else if filters.down {
           !matches!(
             deployment.info.state,
-            DeploymentState::Running | DeploymentState::Deploying
+            DeploymentState::Running
+              | DeploymentState::Unhealthy
+              | DeploymentState::Deploying
           )
         } else if filters.in_progress {
           matches!(deployment.info.state, DeploymentState::Deploying)
         } else {
           matches!(
             deployment.info.state,
-            DeploymentState::Running | DeploymentState::Deploying
+            DeploymentState::Running
+              | DeploymentState::Unhealthy
+              | DeploymentState::Deploying
           )
         };
         state_check
@@ -547,7 +551,9 @@ impl ListResources for RepoListItem {
         } else if minimal || filters.in_progress {
           matches!(
             repo.info.state,
-            RepoState::Building | RepoState::Cloning
+            RepoState::Building
+              | RepoState::Cloning
+              | RepoState::RunningPipeline
           )
         } else {
           true
@@ -935,7 +941,8 @@ impl PrintTable for ResourceListItem<RepoListItemInfo> {
       RepoState::Ok => Color::Green,
       RepoState::Building
       | RepoState::Cloning
-      | RepoState::Pulling => Color::DarkYellow,
+      | RepoState::Pulling
+      | RepoState::RunningPipeline => Color::DarkYellow,
       RepoState::Unknown => Color::Magenta,
       RepoState::Failed => Color::Red,
     };
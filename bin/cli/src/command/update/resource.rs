@@ -59,6 +59,7 @@ impl ResourceUpdate for PartialBuildConfig {
       .write(UpdateBuild {
         id: resource.to_string(),
         config: self,
+        revision: None,
       })
       .await
       .context("Failed to update build config")?;
@@ -76,6 +77,7 @@ impl ResourceUpdate for PartialDeploymentConfig {
       .write(UpdateDeployment {
         id: resource.to_string(),
         config: self,
+        revision: None,
       })
       .await
       .context("Failed to update deployment config")?;
@@ -93,6 +95,7 @@ impl ResourceUpdate for PartialRepoConfig {
       .write(UpdateRepo {
         id: resource.to_string(),
         config: self,
+        revision: None,
       })
       .await
       .context("Failed to update repo config")?;
@@ -110,6 +113,7 @@ impl ResourceUpdate for PartialServerConfig {
       .write(UpdateServer {
         id: resource.to_string(),
         config: self,
+        revision: None,
       })
       .await
       .context("Failed to update server config")?;
@@ -127,6 +131,7 @@ impl ResourceUpdate for PartialStackConfig {
       .write(UpdateStack {
         id: resource.to_string(),
         config: self,
+        revision: None,
       })
       .await
       .context("Failed to update stack config")?;
@@ -144,6 +149,7 @@ impl ResourceUpdate for PartialResourceSyncConfig {
       .write(UpdateResourceSync {
         id: resource.to_string(),
         config: self,
+        revision: None,
       })
       .await
       .context("Failed to update sync config")?;
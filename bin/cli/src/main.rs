@@ -47,6 +47,9 @@ async fn app() -> anyhow::Result<()> {
     args::Command::Inspect(inspect) => {
       command::container::inspect_container(inspect).await
     }
+    args::Command::Logs(follow) => {
+      command::container::follow_log(follow).await
+    }
     args::Command::List(list) => command::list::handle(list).await,
     args::Command::Execute(args) => {
       command::execute::handle(&args.execution, args.yes).await
@@ -57,6 +60,9 @@ async fn app() -> anyhow::Result<()> {
     args::Command::Database { command } => {
       command::database::handle(command).await
     }
+    args::Command::Import { command } => {
+      command::import::handle(command).await
+    }
   }
 }
 
@@ -3,7 +3,7 @@
 ## Core deps installer
 
 apt-get update
-apt-get install -y git curl ca-certificates iproute2
+apt-get install -y git curl ca-certificates iproute2 openssh-client
 
 rm -rf /var/lib/apt/lists/*
 
@@ -0,0 +1,374 @@
+use std::{collections::HashMap, str::FromStr};
+
+use async_timing_util::{
+  ONE_DAY_MS, Timelength, wait_until_timelength,
+};
+use chrono::{Datelike, Local, Timelike};
+use database::mungos::mongodb::{
+  bson::{Document, doc},
+  options::FindOneOptions,
+};
+use komodo_client::entities::{
+  DayOfWeek, Operation, ResourceTarget,
+  alert::{DigestDiskUsage, DigestSection},
+  resource::Resource,
+  tag::Tag,
+};
+
+use crate::{
+  helpers::maintenance::convert_day_of_week,
+  state::{deployment_status_cache, stack_status_cache},
+};
+
+use super::*;
+
+pub fn spawn_digest_loop() {
+  tokio::spawn(async move {
+    loop {
+      wait_until_timelength(Timelength::OneHour, 0).await;
+      send_digests(komodo_timestamp()).await;
+    }
+  });
+}
+
+/// Sends the weekly digest through the Alerters
+/// with the digest scheduled for this hour.
+async fn send_digests(ts: i64) {
+  let alerters = match find_collect(
+    &db_client().alerters,
+    doc! { "config.enabled": true, "config.digest.enabled": true },
+    None,
+  )
+  .await
+  {
+    Ok(alerters) => alerters,
+    Err(e) => {
+      error!(
+        "Failed to get alerters from db for weekly digest | {e:#}"
+      );
+      return;
+    }
+  };
+
+  for alerter in alerters {
+    match digest_due(&alerter.config.digest, ts) {
+      Ok(true) => {}
+      Ok(false) => continue,
+      Err(e) => {
+        warn!(
+          "Failed to check weekly digest schedule for Alerter {} | {e:#}",
+          alerter.name
+        );
+        continue;
+      }
+    }
+    let since = ts - 7 * ONE_DAY_MS as i64;
+    let sections =
+      match digest_sections(&alerter.config.digest.tags, since).await
+      {
+        Ok(sections) => sections,
+        Err(e) => {
+          error!(
+            "Failed to generate weekly digest for Alerter {} | {e:#}",
+            alerter.name
+          );
+          continue;
+        }
+      };
+    let alert = Alert {
+      id: Default::default(),
+      ts,
+      resolved: true,
+      resolved_ts: Some(ts),
+      level: SeverityLevel::Ok,
+      target: ResourceTarget::Alerter(alerter.id.clone()),
+      data: AlertData::WeeklyDigest {
+        id: alerter.id.clone(),
+        name: alerter.name.clone(),
+        since,
+        sections,
+      },
+      notifications: Default::default(),
+    };
+    if let Err(e) = send_alert_to_alerter(&alerter, &alert).await {
+      error!("Failed to send weekly digest | {e:#}");
+    }
+  }
+}
+
+/// Whether `ts` is in the hour the digest is scheduled for.
+fn digest_due(digest: &AlertDigest, ts: i64) -> anyhow::Result<bool> {
+  let dt = chrono::DateTime::from_timestamp_millis(ts)
+    .context("Invalid timestamp")?;
+  let (weekday, hour) =
+    match (digest.timezone.as_str(), core_config().timezone.as_str())
+    {
+      ("", "") => {
+        let local_dt = dt.with_timezone(&Local);
+        (local_dt.weekday(), local_dt.hour())
+      }
+      ("", timezone) | (timezone, _) => {
+        let tz: chrono_tz::Tz =
+          timezone.parse().context("Failed to parse timezone")?;
+        let local_dt = dt.with_timezone(&tz);
+        (local_dt.weekday(), local_dt.hour())
+      }
+    };
+  let day_of_week =
+    DayOfWeek::from_str(&digest.day_of_week).unwrap_or_default();
+  Ok(
+    convert_day_of_week(weekday) == day_of_week
+      && hour == digest.hour as u32,
+  )
+}
+
+/// One section per tag, or a single section
+/// covering all resources if no tags are given.
+async fn digest_sections(
+  tags: &[String],
+  since: i64,
+) -> anyhow::Result<Vec<DigestSection>> {
+  if tags.is_empty() {
+    let section = digest_section(None, since).await?;
+    return Ok(vec![section]);
+  }
+  let all_tags = find_collect(&db_client().tags, None, None)
+    .await
+    .context("Failed to get tags from db")?;
+  let mut sections = Vec::with_capacity(tags.len());
+  for entry in tags {
+    let entry = entry.strip_prefix("tag:").unwrap_or(entry);
+    let Some(tag) = all_tags
+      .iter()
+      .find(|tag| tag.id == entry || tag.name == entry)
+    else {
+      warn!("Weekly digest tag {entry} not found");
+      continue;
+    };
+    sections.push(digest_section(Some(tag), since).await?);
+  }
+  Ok(sections)
+}
+
+async fn digest_section(
+  tag: Option<&Tag>,
+  since: i64,
+) -> anyhow::Result<DigestSection> {
+  let targets = TaggedTargets::new(tag);
+  let db = db_client();
+
+  let mut deploys_query = doc! {
+    "start_ts": { "$gte": since },
+    "operation": { "$in": [
+      Operation::Deploy.to_string(),
+      Operation::DeployStack.to_string(),
+      Operation::DeployStackService.to_string(),
+      Operation::DeploySwarmService.to_string(),
+    ] },
+  };
+  let mut failures_query = doc! {
+    "start_ts": { "$gte": since },
+    "success": false,
+  };
+  let mut open_alerts_query = doc! { "resolved": false };
+  if let Some(filter) = targets.filter() {
+    deploys_query.extend(filter.clone());
+    failures_query.extend(filter.clone());
+    open_alerts_query.extend(filter);
+  }
+
+  let (deploys, failures, open_alerts) = tokio::try_join!(
+    db.updates.count_documents(deploys_query),
+    db.updates.count_documents(failures_query),
+    db.alerts.count_documents(open_alerts_query),
+  )
+  .context("Failed to count digest updates / alerts")?;
+
+  let mut stale_images = 0;
+  for id in &targets.deployments {
+    if deployment_status_cache()
+      .get(id)
+      .await
+      .map(|status| status.curr.update_available)
+      .unwrap_or_default()
+    {
+      stale_images += 1;
+    }
+  }
+  for id in &targets.stacks {
+    if let Some(status) = stack_status_cache().get(id).await {
+      stale_images += status
+        .curr
+        .services
+        .iter()
+        .filter(|service| service.update_available)
+        .count() as i64;
+    }
+  }
+
+  let mut disks = Vec::with_capacity(targets.servers.len());
+  for (server_id, server_name) in targets.servers {
+    match disk_usage(server_id, server_name, since).await {
+      Ok(Some(disk)) => disks.push(disk),
+      Ok(None) => {}
+      Err(e) => warn!("Failed to get digest disk usage | {e:#}"),
+    }
+  }
+
+  Ok(DigestSection {
+    tag: tag.map(|tag| tag.name.clone()).unwrap_or_default(),
+    deploys: deploys as i64,
+    failures: failures as i64,
+    open_alerts: open_alerts as i64,
+    stale_images,
+    disks,
+  })
+}
+
+/// The current disk usage, compared to the first
+/// stats recorded since the start of the week.
+async fn disk_usage(
+  server_id: String,
+  server_name: String,
+  since: i64,
+) -> anyhow::Result<Option<DigestDiskUsage>> {
+  let stats = &db_client().stats;
+  let (latest, first) = tokio::try_join!(
+    stats.find_one(doc! { "sid": &server_id }).with_options(
+      FindOneOptions::builder().sort(doc! { "ts": -1 }).build()
+    ),
+    stats
+      .find_one(doc! { "sid": &server_id, "ts": { "$gte": since } })
+      .with_options(
+        FindOneOptions::builder().sort(doc! { "ts": 1 }).build()
+      ),
+  )
+  .with_context(|| {
+    format!("Failed to get stats for Server {server_name}")
+  })?;
+  let Some(latest) = latest else {
+    return Ok(None);
+  };
+  let used_perc = |used: f64, total: f64| {
+    if total > 0.0 {
+      used / total * 100.0
+    } else {
+      0.0
+    }
+  };
+  let current = used_perc(latest.disk_used_gb, latest.disk_total_gb);
+  let start = first
+    .map(|first| used_perc(first.disk_used_gb, first.disk_total_gb))
+    .unwrap_or(current);
+  Ok(Some(DigestDiskUsage {
+    server_id,
+    server_name,
+    used_perc: current,
+    change_perc: current - start,
+  }))
+}
+
+/// The resources included in a digest section.
+/// Collected up front so the resources cache isn't held across awaits.
+struct TaggedTargets {
+  /// The tagged resource ids by type.
+  /// None if the section covers all resources.
+  ids: Option<Vec<(ResourceTargetVariant, Vec<String>)>>,
+  deployments: Vec<String>,
+  stacks: Vec<String>,
+  /// (id, name)
+  servers: Vec<(String, String)>,
+}
+
+impl TaggedTargets {
+  fn new(tag: Option<&Tag>) -> TaggedTargets {
+    let all = all_resources_cache().load();
+    let deployments = tagged_ids(&all.deployments, tag);
+    let stacks = tagged_ids(&all.stacks, tag);
+    let servers = all
+      .servers
+      .values()
+      .filter(|server| is_tagged(&server.tags, tag))
+      .map(|server| (server.id.clone(), server.name.clone()))
+      .collect::<Vec<_>>();
+    let ids = tag.map(|tag| {
+      let tag = Some(tag);
+      vec![
+        (
+          ResourceTargetVariant::Server,
+          servers.iter().map(|(id, _)| id.clone()).collect(),
+        ),
+        (ResourceTargetVariant::Deployment, deployments.clone()),
+        (ResourceTargetVariant::Stack, stacks.clone()),
+        (
+          ResourceTargetVariant::SwarmService,
+          tagged_ids(&all.swarm_services, tag),
+        ),
+        (ResourceTargetVariant::Build, tagged_ids(&all.builds, tag)),
+        (ResourceTargetVariant::Repo, tagged_ids(&all.repos, tag)),
+        (
+          ResourceTargetVariant::Procedure,
+          tagged_ids(&all.procedures, tag),
+        ),
+        (
+          ResourceTargetVariant::Action,
+          tagged_ids(&all.actions, tag),
+        ),
+        (
+          ResourceTargetVariant::Builder,
+          tagged_ids(&all.builders, tag),
+        ),
+        (
+          ResourceTargetVariant::Alerter,
+          tagged_ids(&all.alerters, tag),
+        ),
+        (
+          ResourceTargetVariant::ResourceSync,
+          tagged_ids(&all.syncs, tag),
+        ),
+      ]
+    });
+    TaggedTargets {
+      ids,
+      deployments,
+      stacks,
+      servers,
+    }
+  }
+
+  /// Mongo filter on `target`, for Updates and Alerts.
+  fn filter(&self) -> Option<Document> {
+    let ids = self.ids.as_ref()?;
+    let filters = ids
+      .iter()
+      .filter(|(_, ids)| !ids.is_empty())
+      .map(|(variant, ids)| {
+        doc! {
+          "target.type": variant.as_ref(),
+          "target.id": { "$in": ids },
+        }
+      })
+      .collect::<Vec<_>>();
+    if filters.is_empty() {
+      // No resources have the tag, match nothing
+      Some(doc! { "_id": { "$exists": false } })
+    } else {
+      Some(doc! { "$or": filters })
+    }
+  }
+}
+
+fn is_tagged(tags: &[String], tag: Option<&Tag>) -> bool {
+  tag.map(|tag| tags.contains(&tag.id)).unwrap_or(true)
+}
+
+fn tagged_ids<C: Default, I: Default>(
+  resources: &HashMap<String, Resource<C, I>>,
+  tag: Option<&Tag>,
+) -> Vec<String> {
+  resources
+    .values()
+    .filter(|resource| is_tagged(&resource.tags, tag))
+    .map(|resource| resource.id.clone())
+    .collect()
+}
@@ -106,6 +106,32 @@ pub async fn send_alert(
         "{level} | **{name}**{region} disk usage at **{percentage:.1}%** 💿\nmount point: `{path:?}`\nusing **{used_gb:.1} GiB** / **{total_gb:.1} GiB**\n{link}"
       )
     }
+    AlertData::ServerNetwork {
+      id,
+      name,
+      region,
+      ingress_mb_per_sec,
+      egress_mb_per_sec,
+    } => {
+      let region = fmt_region(region);
+      let link = resource_link(ResourceTargetVariant::Server, id);
+      format!(
+        "{level} | **{name}**{region} network throughput at **{ingress_mb_per_sec:.1} MB/s** in / **{egress_mb_per_sec:.1} MB/s** out 🌐\n{link}"
+      )
+    }
+    AlertData::ServerDiskIo {
+      id,
+      name,
+      region,
+      read_mb_per_sec,
+      write_mb_per_sec,
+    } => {
+      let region = fmt_region(region);
+      let link = resource_link(ResourceTargetVariant::Server, id);
+      format!(
+        "{level} | **{name}**{region} disk IO at **{read_mb_per_sec:.1} MB/s** read / **{write_mb_per_sec:.1} MB/s** write 💿\n{link}"
+      )
+    }
     AlertData::ContainerStateChange {
       id,
       name,
@@ -120,6 +146,17 @@ pub async fn send_alert(
         "📦 Deployment **{name}** is now **{to}**\nserver: **{server_name}**\nprevious: **{from}**\n{link}"
       )
     }
+    AlertData::ContainerUnhealthy {
+      id,
+      name,
+      server_id: _server_id,
+      server_name,
+    } => {
+      let link = resource_link(ResourceTargetVariant::Deployment, id);
+      format!(
+        "{level} | 📦 Deployment **{name}** is **unhealthy** 🩺\nserver: **{server_name}**\n{link}"
+      )
+    }
     AlertData::DeploymentImageUpdateAvailable {
       id,
       name,
@@ -158,6 +195,30 @@ pub async fn send_alert(
         "🥞 Stack **{name}** is now {to}\nserver: **{server_name}**\nprevious: **{from}**\n{link}"
       )
     }
+    AlertData::StackDrifted {
+      id,
+      name,
+      server_id: _server_id,
+      server_name,
+    } => {
+      let link = resource_link(ResourceTargetVariant::Stack, id);
+      format!(
+        "🥞 Stack **{name}** files have changed since deploy\nserver: **{server_name}**\n{link}"
+      )
+    }
+    AlertData::StackRuntimeDrift {
+      id,
+      name,
+      server_id: _server_id,
+      server_name,
+      services,
+    } => {
+      let link = resource_link(ResourceTargetVariant::Stack, id);
+      let services = services.join(", ");
+      format!(
+        "🥞 Stack **{name}** containers no longer match the deployed compose file\nserver: **{server_name}**\nservices: **{services}**\n{link}"
+      )
+    }
     AlertData::StackImageUpdateAvailable {
       id,
       name,
@@ -186,6 +247,20 @@ pub async fn send_alert(
         "⬆ Stack **{name}** was updated automatically ⏫\nserver: **{server_name}**\n{images_label}: **{images}**\n{link}"
       )
     }
+    AlertData::StackServiceReplicasLow {
+      id,
+      name,
+      server_id: _server_id,
+      server_name,
+      service,
+      running,
+      desired,
+    } => {
+      let link = resource_link(ResourceTargetVariant::Stack, id);
+      format!(
+        "{level} | Stack **{name}** service **{service}** has **{running}/{desired}** replicas running\nserver: **{server_name}**\n{link}"
+      )
+    }
     AlertData::AwsBuilderTerminationFailed {
       instance_id,
       message,
@@ -215,10 +290,58 @@ pub async fn send_alert(
       let link = resource_link(ResourceTargetVariant::Procedure, id);
       format!("{level} | Procedure **{name}** failed\n{link}")
     }
+    AlertData::ProcedureApprovalRequired {
+      id,
+      name,
+      stage,
+      approval_id,
+      message,
+    } => {
+      let link = resource_link(ResourceTargetVariant::Procedure, id);
+      let message = if message.is_empty() {
+        String::new()
+      } else {
+        format!("\n{message}")
+      };
+      format!(
+        "{level} | Procedure **{name}** stage **{stage}** is waiting for approval ✋{message}\nApproval id: {approval_id}\n{link}"
+      )
+    }
     AlertData::ActionFailed { id, name } => {
       let link = resource_link(ResourceTargetVariant::Action, id);
       format!("{level} | Action **{name}** failed\n{link}")
     }
+    AlertData::UpdateFailed {
+      resource_type,
+      id,
+      name,
+      operation,
+      ..
+    } => {
+      let link = resource_link(*resource_type, id);
+      format!(
+        "{level} | **{operation}** on {resource_type} **{name}** failed\n{link}"
+      )
+    }
+    AlertData::ScheduledCommandFailed { id, name, command } => {
+      let link = resource_link(ResourceTargetVariant::Server, id);
+      format!(
+        "{level} | Scheduled command **{command}** on **{name}** failed\n{link}"
+      )
+    }
+    AlertData::AutoStopPending {
+      resource_type,
+      id,
+      name,
+      mode,
+      minutes,
+    } => {
+      let link = resource_link(*resource_type, id);
+      format!(
+        "{level} | **{name}** ({resource_type}) | Auto {} in {minutes} min 🕝\n{link}",
+        auto_stop_action(*mode)
+      )
+    }
     AlertData::ScheduleRun {
       resource_type,
       id,
@@ -229,6 +352,84 @@ pub async fn send_alert(
         "{level} | **{name}** ({resource_type}) | Scheduled run started 🕝\n{link}"
       )
     }
+    AlertData::ScheduleMissed {
+      resource_type,
+      id,
+      name,
+      scheduled_at,
+      run_late,
+    } => {
+      let link = schedule_link(*resource_type, id);
+      format!(
+        "{level} | **{name}** ({}) | {}\n{link}",
+        fmt_schedule_type(*resource_type),
+        fmt_schedule_missed(*scheduled_at, *run_late)
+      )
+    }
+    AlertData::ProviderAccountCheckFailed {
+      provider_type,
+      domain,
+      username,
+      message,
+      ..
+    } => {
+      format!(
+        "{level} | **{username}** @ {domain} ({provider_type}) | Token check failed 🔑\n{message}"
+      )
+    }
+    AlertData::ProviderTokenExpiring {
+      provider_type,
+      domain,
+      username,
+      days,
+      ..
+    } => {
+      format!(
+        "{level} | **{username}** @ {domain} ({provider_type}) | Token expires in {days} days 🔑"
+      )
+    }
+    AlertData::ExternalStatus {
+      name,
+      state,
+      message,
+    } => {
+      format!(
+        "{level} | **{name}** (External) is **{state}**{}",
+        if message.is_empty() {
+          format_args!("")
+        } else {
+          format_args!("\n{message}")
+        }
+      )
+    }
+    AlertData::Prometheus {
+      name,
+      summary,
+      description,
+      generator_url,
+      ..
+    } => {
+      let details = [summary, description, generator_url]
+        .into_iter()
+        .filter(|line| !line.is_empty())
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join("\n");
+      format!("{level} | **{name}** (Prometheus) 🔥\n{details}")
+    }
+    AlertData::WeeklyDigest {
+      id,
+      name,
+      since,
+      sections,
+    } => {
+      let link = resource_link(ResourceTargetVariant::Alerter, id);
+      let since = fmt_date(*since);
+      let sections = fmt_digest_sections(sections);
+      format!(
+        "📊 Weekly digest from Alerter **{name}** since {since}\n{sections}\n{link}"
+      )
+    }
     AlertData::Custom { message, details } => {
       format!(
         "{level} | {message}{}",
@@ -242,22 +443,12 @@ pub async fn send_alert(
     AlertData::None {} => Default::default(),
   };
   if !content.is_empty() {
-    let VariablesAndSecrets { variables, secrets } =
-      get_variables_and_secrets().await?;
-    let mut url_interpolated = url.to_string();
-
-    let mut interpolator =
-      Interpolator::new(Some(&variables), &secrets);
-
-    interpolator.interpolate_string(&mut url_interpolated)?;
+    let (url_interpolated, replacers) =
+      interpolate_alerter_string(url, alert).await?;
 
     send_message(&url_interpolated, &content)
       .await
       .map_err(|e| {
-        let replacers = interpolator
-          .secret_replacers
-          .into_iter()
-          .collect::<Vec<_>>();
         let sanitized_error =
           svi::replace_in_string(&format!("{e:?}"), &replacers);
         anyhow::Error::msg(format!(
@@ -0,0 +1,76 @@
+use lettre::{
+  AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+  message::{Mailbox, header::ContentType},
+  transport::smtp::authentication::Credentials,
+};
+
+use super::*;
+
+#[instrument(level = "debug", skip(endpoint), fields(host = %endpoint.host))]
+pub async fn send_alert(
+  endpoint: &EmailAlerterEndpoint,
+  alert: &Alert,
+) -> anyhow::Result<()> {
+  let content = standard_alert_content(alert);
+  if content.is_empty() {
+    return Ok(());
+  }
+
+  if endpoint.to.is_empty() {
+    return Err(anyhow!("No recipient addresses configured"));
+  }
+
+  let mut message = Message::builder()
+    .from(
+      endpoint
+        .from
+        .parse::<Mailbox>()
+        .context("Invalid 'from' address")?,
+    )
+    .subject(format!("Komodo | {}", alert_summary(&content, 200)))
+    .header(ContentType::TEXT_PLAIN);
+  for to in &endpoint.to {
+    message = message.to(
+      to.parse::<Mailbox>()
+        .with_context(|| format!("Invalid 'to' address: {to}"))?,
+    );
+  }
+  let message =
+    message.body(content).context("Failed to build email")?;
+
+  let mut transport = match endpoint.security {
+    SmtpSecurity::StartTls => {
+      AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(
+        &endpoint.host,
+      )
+      .context("Failed to initialize SMTP STARTTLS transport")?
+    }
+    SmtpSecurity::Tls => {
+      AsyncSmtpTransport::<Tokio1Executor>::relay(&endpoint.host)
+        .context("Failed to initialize SMTP TLS transport")?
+    }
+    SmtpSecurity::None => {
+      AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(
+        &endpoint.host,
+      )
+    }
+  }
+  .port(endpoint.port);
+
+  if !endpoint.username.is_empty() {
+    let (password, _) =
+      interpolate_alerter_string(&endpoint.password, alert).await?;
+    transport = transport.credentials(Credentials::new(
+      endpoint.username.clone(),
+      password,
+    ));
+  }
+
+  transport
+    .build()
+    .send(message)
+    .await
+    .context("Failed to send email")?;
+
+  Ok(())
+}
@@ -0,0 +1,175 @@
+use database::mungos::{
+  by_id::update_one_by_id, mongodb::bson::to_bson,
+};
+use komodo_client::entities::alert::{
+  AlertNotification, AlertNotificationKind,
+};
+
+use super::*;
+
+/// Re-sends and escalates the open alerts
+/// according to the Alerter escalation policies.
+#[instrument(level = "debug")]
+pub async fn escalate_open_alerts(ts: i64) {
+  let alerters = match find_collect(
+    &db_client().alerters,
+    doc! { "config.enabled": true },
+    None,
+  )
+  .await
+  {
+    Ok(alerters) => alerters,
+    Err(e) => {
+      error!(
+        "Failed to get alerters from db for alert escalation | {e:#}"
+      );
+      return;
+    }
+  };
+
+  if alerters
+    .iter()
+    .all(|alerter| alerter.config.escalation.is_empty())
+  {
+    return;
+  }
+
+  let alerts = match find_collect(
+    &db_client().alerts,
+    doc! { "resolved": false },
+    None,
+  )
+  .await
+  {
+    Ok(alerts) => alerts,
+    Err(e) => {
+      error!(
+        "Failed to get open alerts from db for alert escalation | {e:#}"
+      );
+      return;
+    }
+  };
+
+  let silences = find_collect(
+    &db_client().silences,
+    doc! {
+      "start_ts": { "$lte": ts },
+      "end_ts": { "$gt": ts },
+    },
+    None,
+  )
+  .await
+  .inspect_err(|e| {
+    warn!("Failed to get alert silences from db | {e:#}")
+  })
+  .unwrap_or_default();
+
+  for alert in alerts {
+    if silences.iter().any(|silence| silence.matches(&alert)) {
+      continue;
+    }
+    let notifications = escalate_alert(&alerters, &alert, ts).await;
+    if notifications.is_empty() {
+      continue;
+    }
+    let notifications = match to_bson(&notifications) {
+      Ok(notifications) => notifications,
+      Err(e) => {
+        warn!("Failed to serialize alert notifications | {e:#}");
+        continue;
+      }
+    };
+    update_one_by_id(
+      &db_client().alerts,
+      &alert.id,
+      doc! {
+        "$push": { "notifications": { "$each": notifications } }
+      },
+      None,
+    )
+    .await
+    .context("Failed to record alert notifications")
+    .inspect_err(|e| warn!("{e:#}"))
+    .ok();
+  }
+}
+
+/// Sends the due repeat / escalation notifications for the alert.
+async fn escalate_alert(
+  alerters: &[Alerter],
+  alert: &Alert,
+  ts: i64,
+) -> Vec<AlertNotification> {
+  let mut notifications = Vec::new();
+  for alerter in alerters {
+    let escalation = &alerter.config.escalation;
+    if escalation.is_empty()
+      || !alerter_accepts_alert(alerter, alert).await
+    {
+      continue;
+    }
+
+    let sent = alert
+      .notifications
+      .iter()
+      .filter(|notification| notification.alerter_id == alerter.id);
+
+    // REPEAT
+    if escalation.repeat_interval_minutes > 0 {
+      let last_sent = sent
+        .clone()
+        .filter(|notification| {
+          notification.kind == AlertNotificationKind::Repeat
+        })
+        .map(|notification| notification.ts)
+        .max()
+        .unwrap_or(alert.ts);
+      if ts - last_sent
+        >= minutes_to_ms(escalation.repeat_interval_minutes)
+      {
+        match send_alert_to_alerter(alerter, alert).await {
+          Ok(_) => notifications.push(AlertNotification {
+            alerter_id: alerter.id.clone(),
+            ts,
+            kind: AlertNotificationKind::Repeat,
+          }),
+          Err(e) => warn!("Failed to repeat alert | {e:#}"),
+        }
+      }
+    }
+
+    // ESCALATE
+    if escalation.escalate_after_minutes > 0
+      && !escalation.escalate_to.is_empty()
+      && ts - alert.ts
+        >= minutes_to_ms(escalation.escalate_after_minutes)
+      && !sent.clone().any(|notification| {
+        notification.kind == AlertNotificationKind::Escalation
+      })
+    {
+      let Some(escalate_to) = alerters.iter().find(|escalate_to| {
+        escalate_to.id == escalation.escalate_to
+          || escalate_to.name == escalation.escalate_to
+      }) else {
+        warn!(
+          "Alerter {} escalates to {}, which is not an enabled Alerter",
+          alerter.name, escalation.escalate_to
+        );
+        continue;
+      };
+      match send_alert_to_alerter(escalate_to, alert).await {
+        Ok(_) => notifications.push(AlertNotification {
+          alerter_id: alerter.id.clone(),
+          ts,
+          kind: AlertNotificationKind::Escalation,
+        }),
+        Err(e) => warn!("Failed to escalate alert | {e:#}"),
+      }
+    }
+  }
+  notifications
+}
+
+fn minutes_to_ms(minutes: u64) -> i64 {
+  (minutes as i64).saturating_mul(60_000)
+}
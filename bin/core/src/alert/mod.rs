@@ -5,8 +5,10 @@ use derive_variants::ExtractVariant;
 use futures::future::join_all;
 use interpolate::Interpolator;
 use komodo_client::entities::{
-  ResourceTargetVariant,
-  alert::{Alert, AlertData, AlertDataVariant, SeverityLevel},
+  AutoStopMode, ResourceTargetVariant,
+  alert::{
+    Alert, AlertData, AlertDataVariant, DigestSection, SeverityLevel,
+  },
   alerter::*,
   deployment::DeploymentState,
   komodo_timestamp,
@@ -18,13 +20,28 @@ use crate::helpers::query::get_variables_and_secrets;
 use crate::helpers::{
   maintenance::is_in_maintenance, query::VariablesAndSecrets,
 };
-use crate::{config::core_config, state::db_client};
+use crate::{
+  config::core_config,
+  state::{all_resources_cache, db_client},
+};
 
+mod digest;
 mod discord;
+mod email;
+mod escalation;
 mod ntfy;
+mod opsgenie;
+mod pagerduty;
+mod periphery_hooks;
 mod pushover;
+mod routing;
 mod slack;
 
+pub use digest::spawn_digest_loop;
+pub use escalation::escalate_open_alerts;
+
+use periphery_hooks::send_alert_to_periphery_hooks;
+
 #[instrument(level = "debug")]
 pub async fn send_alerts(alerts: &[Alert]) {
   if alerts.is_empty() {
@@ -48,9 +65,34 @@ pub async fn send_alerts(alerts: &[Alert]) {
       return;
     };
 
+    let now = komodo_timestamp();
+    let silences = find_collect(
+      &db_client().silences,
+      doc! {
+        "start_ts": { "$lte": now },
+        "end_ts": { "$gt": now },
+      },
+      None,
+    )
+    .await
+    .inspect_err(|e| {
+      warn!("Failed to get alert silences from db | {e:#}")
+    })
+    .unwrap_or_default();
+
     let handles = alerts
       .iter()
-      .map(|alert| send_alert_to_alerters(&alerters, alert));
+      .filter(|alert| {
+        // Tests are never silenced
+        alert.data.extract_variant() == AlertDataVariant::Test
+          || !silences.iter().any(|silence| silence.matches(alert))
+      })
+      .map(|alert| async {
+        tokio::join!(
+          send_alert_to_alerters(&alerters, alert),
+          send_alert_to_periphery_hooks(alert),
+        );
+      });
 
     join_all(handles).await;
   }
@@ -79,43 +121,10 @@ pub async fn send_alert_to_alerter(
   alerter: &Alerter,
   alert: &Alert,
 ) -> anyhow::Result<()> {
-  // Don't send if not enabled
-  if !alerter.config.enabled {
-    return Ok(());
-  }
-
-  if is_in_maintenance(
-    &alerter.config.maintenance_windows,
-    komodo_timestamp(),
-  ) {
+  if !alerter_accepts_alert(alerter, alert).await {
     return Ok(());
   }
 
-  let alert_type = alert.data.extract_variant();
-
-  // In the test case, we don't want the filters inside this
-  // block to stop the test from being sent to the alerting endpoint.
-  if alert_type != AlertDataVariant::Test {
-    // Don't send if alert type not configured on the alerter
-    if !alerter.config.alert_types.is_empty()
-      && !alerter.config.alert_types.contains(&alert_type)
-    {
-      return Ok(());
-    }
-
-    // Don't send if resource is in the blacklist
-    if alerter.config.except_resources.contains(&alert.target) {
-      return Ok(());
-    }
-
-    // Don't send if whitelist configured and target is not included
-    if !alerter.config.resources.is_empty()
-      && !alerter.config.resources.contains(&alert.target)
-    {
-      return Ok(());
-    }
-  }
-
   match &alerter.config.endpoint {
     AlerterEndpoint::Custom(CustomAlerterEndpoint { url }) => {
       send_custom_alert(url, alert).await.with_context(|| {
@@ -159,7 +168,92 @@ pub async fn send_alert_to_alerter(
         )
       })
     }
+    AlerterEndpoint::PagerDuty(PagerDutyAlerterEndpoint {
+      url,
+      routing_key,
+    }) => pagerduty::send_alert(url, routing_key, alert)
+      .await
+      .with_context(|| {
+        format!(
+          "Failed to send alert to PagerDuty Alerter {}",
+          alerter.name
+        )
+      }),
+    AlerterEndpoint::Opsgenie(OpsgenieAlerterEndpoint {
+      url,
+      api_key,
+    }) => opsgenie::send_alert(url, api_key, alert)
+      .await
+      .with_context(|| {
+        format!(
+          "Failed to send alert to Opsgenie Alerter {}",
+          alerter.name
+        )
+      }),
+    AlerterEndpoint::Email(endpoint) => {
+      email::send_alert(endpoint, alert).await.with_context(|| {
+        format!(
+          "Failed to send alert to Email Alerter {}",
+          alerter.name
+        )
+      })
+    }
+  }
+}
+
+/// Whether the Alerter is enabled, not in maintenance,
+/// and the alert passes the Alerter filters.
+async fn alerter_accepts_alert(
+  alerter: &Alerter,
+  alert: &Alert,
+) -> bool {
+  // Don't send if not enabled
+  if !alerter.config.enabled {
+    return false;
+  }
+
+  if is_in_maintenance(
+    &alerter.config.maintenance_windows,
+    komodo_timestamp(),
+  ) {
+    return false;
+  }
+
+  let alert_type = alert.data.extract_variant();
+
+  // In the test / digest case, we don't want the filters inside this
+  // block to stop them from being sent to the alerting endpoint.
+  if !matches!(
+    alert_type,
+    AlertDataVariant::Test | AlertDataVariant::WeeklyDigest
+  ) {
+    // Don't send if alert type not configured on the alerter
+    if !alerter.config.alert_types.is_empty()
+      && !alerter.config.alert_types.contains(&alert_type)
+    {
+      return false;
+    }
+
+    // Don't send if resource is in the blacklist
+    if alerter.config.except_resources.contains(&alert.target) {
+      return false;
+    }
+
+    // Don't send if whitelist configured and target is not included
+    if !alerter.config.resources.is_empty()
+      && !alerter.config.resources.contains(&alert.target)
+    {
+      return false;
+    }
+
+    // Don't send if the alert doesn't pass the routing rules
+    if !routing::alert_is_routed(&alerter.config.routing, alert).await
+    {
+      return false;
+    }
   }
+
+  true
 }
 
 #[instrument(level = "debug")]
@@ -167,14 +261,8 @@ async fn send_custom_alert(
   url: &str,
   alert: &Alert,
 ) -> anyhow::Result<()> {
-  let VariablesAndSecrets { variables, secrets } =
-    get_variables_and_secrets().await?;
-  let mut url_interpolated = url.to_string();
-
-  let mut interpolator =
-    Interpolator::new(Some(&variables), &secrets);
-
-  interpolator.interpolate_string(&mut url_interpolated)?;
+  let (url_interpolated, replacers) =
+    interpolate_alerter_string(url, alert).await?;
 
   let res = reqwest::Client::new()
     .post(url_interpolated)
@@ -182,10 +270,6 @@ async fn send_custom_alert(
     .send()
     .await
     .map_err(|e| {
-      let replacers = interpolator
-        .secret_replacers
-        .into_iter()
-        .collect::<Vec<_>>();
       let sanitized_error =
         svi::replace_in_string(&format!("{e:?}"), &replacers);
       anyhow::Error::msg(format!(
@@ -206,6 +290,32 @@ async fn send_custom_alert(
   Ok(())
 }
 
+/// Interpolates Variables and Secrets into the alerter url / key,
+/// as well as `${resource.KEY}` from the alerting resource metadata.
+/// Also returns the secret replacers to sanitize errors.
+async fn interpolate_alerter_string(
+  target: &str,
+  alert: &Alert,
+) -> anyhow::Result<(String, Vec<(String, String)>)> {
+  let VariablesAndSecrets { variables, secrets } =
+    get_variables_and_secrets().await?;
+  let mut interpolated = target.to_string();
+
+  let all = all_resources_cache().load();
+  let mut interpolator =
+    Interpolator::new(Some(&variables), &secrets);
+  if let Some(meta) = all.resource_meta(&alert.target) {
+    interpolator = interpolator.with_resource_metadata(meta.metadata);
+  }
+
+  interpolator.interpolate_string(&mut interpolated)?;
+
+  Ok((
+    interpolated,
+    interpolator.secret_replacers.into_iter().collect(),
+  ))
+}
+
 fn fmt_region(region: &Option<String>) -> String {
   match region {
     Some(region) => format!(" ({region})"),
@@ -233,6 +343,92 @@ fn fmt_stack_state(state: &StackState) -> String {
   }
 }
 
+fn auto_stop_action(mode: AutoStopMode) -> &'static str {
+  match mode {
+    AutoStopMode::Stop => "stop",
+    AutoStopMode::Destroy => "destroy",
+  }
+}
+
+/// Execution Schedules are sent with the System resource type,
+/// as they aren't resources.
+fn fmt_schedule_type(resource_type: ResourceTargetVariant) -> String {
+  match resource_type {
+    ResourceTargetVariant::System => {
+      String::from("Execution Schedule")
+    }
+    _ => resource_type.to_string(),
+  }
+}
+
+fn schedule_link(
+  resource_type: ResourceTargetVariant,
+  id: &str,
+) -> String {
+  match resource_type {
+    ResourceTargetVariant::System => String::new(),
+    _ => resource_link(resource_type, id),
+  }
+}
+
+fn fmt_schedule_missed(scheduled_at: i64, run_late: bool) -> String {
+  let scheduled_at =
+    chrono::DateTime::from_timestamp_millis(scheduled_at)
+      .map(|dt| dt.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+      .unwrap_or_else(|| scheduled_at.to_string());
+  if run_late {
+    format!("Scheduled run for {scheduled_at} started late ⏰")
+  } else {
+    format!("Scheduled run for {scheduled_at} was skipped ⏰")
+  }
+}
+
+fn fmt_date(ts: i64) -> String {
+  chrono::DateTime::from_timestamp_millis(ts)
+    .map(|dt| dt.format("%Y-%m-%d").to_string())
+    .unwrap_or_else(|| ts.to_string())
+}
+
+/// One block per digest section, separated by an empty line.
+fn fmt_digest_sections(sections: &[DigestSection]) -> String {
+  sections
+    .iter()
+    .map(|section| {
+      let tag = if section.tag.is_empty() {
+        "All resources"
+      } else {
+        section.tag.as_str()
+      };
+      let mut lines = vec![
+        format!("🏷️ {tag}"),
+        format!(
+          "Deploys: {} | Failures: {} | Open alerts: {} | Stale images: {}",
+          section.deploys,
+          section.failures,
+          section.open_alerts,
+          section.stale_images
+        ),
+      ];
+      if !section.disks.is_empty() {
+        let disks = section
+          .disks
+          .iter()
+          .map(|disk| {
+            format!(
+              "{} {:.1}% ({:+.1}%)",
+              disk.server_name, disk.used_perc, disk.change_perc
+            )
+          })
+          .collect::<Vec<_>>()
+          .join(", ");
+        lines.push(format!("Disk: {disks}"));
+      }
+      lines.join("\n")
+    })
+    .collect::<Vec<_>>()
+    .join("\n\n")
+}
+
 fn fmt_level(level: SeverityLevel) -> &'static str {
   match level {
     SeverityLevel::Critical => "CRITICAL 🚨",
@@ -252,8 +448,21 @@ fn resource_link(
   )
 }
 
+/// The first line of the alert content,
+/// truncated to `max` characters.
+fn alert_summary(content: &str, max: usize) -> String {
+  let line = content.lines().next().unwrap_or_default();
+  if line.chars().count() <= max {
+    return line.to_string();
+  }
+  let mut summary =
+    line.chars().take(max.saturating_sub(3)).collect::<String>();
+  summary.push_str("...");
+  summary
+}
+
 /// Standard message content format
-/// used by Ntfy, Pushover.
+/// used by Ntfy, Pushover, PagerDuty, Opsgenie, and Email.
 fn standard_alert_content(alert: &Alert) -> String {
   let level = fmt_level(alert.level);
   match &alert.data {
@@ -350,6 +559,32 @@ fn standard_alert_content(alert: &Alert) -> String {
         "{level} | {name}{region} disk usage at {percentage:.1}%💿\nmount point: {path:?}\nusing {used_gb:.1} GiB / {total_gb:.1} GiB\n{link}",
       )
     }
+    AlertData::ServerNetwork {
+      id,
+      name,
+      region,
+      ingress_mb_per_sec,
+      egress_mb_per_sec,
+    } => {
+      let region = fmt_region(region);
+      let link = resource_link(ResourceTargetVariant::Server, id);
+      format!(
+        "{level} | {name}{region} network throughput at {ingress_mb_per_sec:.1} MB/s in / {egress_mb_per_sec:.1} MB/s out 🌐\n{link}",
+      )
+    }
+    AlertData::ServerDiskIo {
+      id,
+      name,
+      region,
+      read_mb_per_sec,
+      write_mb_per_sec,
+    } => {
+      let region = fmt_region(region);
+      let link = resource_link(ResourceTargetVariant::Server, id);
+      format!(
+        "{level} | {name}{region} disk IO at {read_mb_per_sec:.1} MB/s read / {write_mb_per_sec:.1} MB/s write 💿\n{link}",
+      )
+    }
     AlertData::ContainerStateChange {
       id,
       name,
@@ -364,6 +599,17 @@ fn standard_alert_content(alert: &Alert) -> String {
         "📦Deployment {name} is now {to_state}\nserver: {server_name}\nprevious: {from}\n{link}",
       )
     }
+    AlertData::ContainerUnhealthy {
+      id,
+      name,
+      server_id: _server_id,
+      server_name,
+    } => {
+      let link = resource_link(ResourceTargetVariant::Deployment, id);
+      format!(
+        "{level} | 📦Deployment {name} is unhealthy 🩺\nserver: {server_name}\n{link}",
+      )
+    }
     AlertData::DeploymentImageUpdateAvailable {
       id,
       name,
@@ -402,6 +648,30 @@ fn standard_alert_content(alert: &Alert) -> String {
         "🥞 Stack {name} is now {to_state}\nserver: {server_name}\nprevious: {from}\n{link}",
       )
     }
+    AlertData::StackDrifted {
+      id,
+      name,
+      server_id: _server_id,
+      server_name,
+    } => {
+      let link = resource_link(ResourceTargetVariant::Stack, id);
+      format!(
+        "🥞 Stack {name} files have changed since deploy\nserver: {server_name}\n{link}",
+      )
+    }
+    AlertData::StackRuntimeDrift {
+      id,
+      name,
+      server_id: _server_id,
+      server_name,
+      services,
+    } => {
+      let link = resource_link(ResourceTargetVariant::Stack, id);
+      let services = services.join(", ");
+      format!(
+        "🥞 Stack {name} containers no longer match the deployed compose file\nserver: {server_name}\nservices: {services}\n{link}",
+      )
+    }
     AlertData::StackImageUpdateAvailable {
       id,
       name,
@@ -430,6 +700,20 @@ fn standard_alert_content(alert: &Alert) -> String {
         "⬆ Stack {name} was updated automatically ⏫\nserver: {server_name}\n{images_label}: {images_str}\n{link}",
       )
     }
+    AlertData::StackServiceReplicasLow {
+      id,
+      name,
+      server_id: _server_id,
+      server_name,
+      service,
+      running,
+      desired,
+    } => {
+      let link = resource_link(ResourceTargetVariant::Stack, id);
+      format!(
+        "{level} | Stack {name} service {service} has {running}/{desired} replicas running\nserver: {server_name}\n{link}",
+      )
+    }
     AlertData::AwsBuilderTerminationFailed {
       instance_id,
       message,
@@ -459,10 +743,58 @@ fn standard_alert_content(alert: &Alert) -> String {
       let link = resource_link(ResourceTargetVariant::Procedure, id);
       format!("{level} | Procedure {name} failed\n{link}")
     }
+    AlertData::ProcedureApprovalRequired {
+      id,
+      name,
+      stage,
+      approval_id,
+      message,
+    } => {
+      let link = resource_link(ResourceTargetVariant::Procedure, id);
+      let message = if message.is_empty() {
+        String::new()
+      } else {
+        format!("\n{message}")
+      };
+      format!(
+        "{level} | Procedure {name} stage {stage} is waiting for approval ✋{message}\nApproval id: {approval_id}\n{link}"
+      )
+    }
     AlertData::ActionFailed { id, name } => {
       let link = resource_link(ResourceTargetVariant::Action, id);
       format!("{level} | Action {name} failed\n{link}")
     }
+    AlertData::UpdateFailed {
+      resource_type,
+      id,
+      name,
+      operation,
+      ..
+    } => {
+      let link = resource_link(*resource_type, id);
+      format!(
+        "{level} | {operation} on {resource_type} {name} failed\n{link}"
+      )
+    }
+    AlertData::ScheduledCommandFailed { id, name, command } => {
+      let link = resource_link(ResourceTargetVariant::Server, id);
+      format!(
+        "{level} | Scheduled command {command} on {name} failed\n{link}"
+      )
+    }
+    AlertData::AutoStopPending {
+      resource_type,
+      id,
+      name,
+      mode,
+      minutes,
+    } => {
+      let link = resource_link(*resource_type, id);
+      format!(
+        "{level} | {name} ({resource_type}) | Auto {} in {minutes} min 🕝\n{link}",
+        auto_stop_action(*mode)
+      )
+    }
     AlertData::ScheduleRun {
       resource_type,
       id,
@@ -473,6 +805,84 @@ fn standard_alert_content(alert: &Alert) -> String {
         "{level} | {name} ({resource_type}) | Scheduled run started 🕝\n{link}"
       )
     }
+    AlertData::ScheduleMissed {
+      resource_type,
+      id,
+      name,
+      scheduled_at,
+      run_late,
+    } => {
+      let link = schedule_link(*resource_type, id);
+      format!(
+        "{level} | {name} ({}) | {}\n{link}",
+        fmt_schedule_type(*resource_type),
+        fmt_schedule_missed(*scheduled_at, *run_late)
+      )
+    }
+    AlertData::ProviderAccountCheckFailed {
+      provider_type,
+      domain,
+      username,
+      message,
+      ..
+    } => {
+      format!(
+        "{level} | {username} @ {domain} ({provider_type}) | Token check failed 🔑\n{message}"
+      )
+    }
+    AlertData::ProviderTokenExpiring {
+      provider_type,
+      domain,
+      username,
+      days,
+      ..
+    } => {
+      format!(
+        "{level} | {username} @ {domain} ({provider_type}) | Token expires in {days} days 🔑"
+      )
+    }
+    AlertData::ExternalStatus {
+      name,
+      state,
+      message,
+    } => {
+      format!(
+        "{level} | {name} (External) is {state}{}",
+        if message.is_empty() {
+          format_args!("")
+        } else {
+          format_args!("\n{message}")
+        }
+      )
+    }
+    AlertData::Prometheus {
+      name,
+      summary,
+      description,
+      generator_url,
+      ..
+    } => {
+      let details = [summary, description, generator_url]
+        .into_iter()
+        .filter(|line| !line.is_empty())
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join("\n");
+      format!("{level} | {name} (Prometheus) 🔥\n{details}")
+    }
+    AlertData::WeeklyDigest {
+      id,
+      name,
+      since,
+      sections,
+    } => {
+      let link = resource_link(ResourceTargetVariant::Alerter, id);
+      let since = fmt_date(*since);
+      let sections = fmt_digest_sections(sections);
+      format!(
+        "📊 Weekly digest from Alerter {name} since {since}\n{sections}\n{link}"
+      )
+    }
     AlertData::Custom { message, details } => {
       format!(
         "{level} | {message}{}",
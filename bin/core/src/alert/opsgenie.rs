@@ -0,0 +1,103 @@
+use std::sync::OnceLock;
+
+use serde::Serialize;
+
+use super::*;
+
+#[instrument(level = "debug", skip(api_key))]
+pub async fn send_alert(
+  url: &str,
+  api_key: &str,
+  alert: &Alert,
+) -> anyhow::Result<()> {
+  let content = standard_alert_content(alert);
+  if content.is_empty() {
+    return Ok(());
+  }
+
+  let (api_key, replacers) =
+    interpolate_alerter_string(api_key, alert).await?;
+  let url = url.trim_end_matches('/');
+
+  // Open alerts are identified by the alert id,
+  // so the Opsgenie alert is closed when the alert closes.
+  let alias = (!alert.id.is_empty()).then_some(alert.id.as_str());
+
+  let request = match alias {
+    Some(alias)
+      if alert.resolved && alert.level == SeverityLevel::Ok =>
+    {
+      http_client()
+        .post(format!(
+          "{url}/v2/alerts/{alias}/close?identifierType=alias"
+        ))
+        .json(&CloseAlert { source: "Komodo" })
+    }
+    alias => http_client().post(format!("{url}/v2/alerts")).json(
+      &CreateAlert {
+        message: alert_summary(&content, 130),
+        alias,
+        description: &content,
+        priority: priority(alert.level),
+        source: "Komodo",
+      },
+    ),
+  };
+
+  let response = request
+    .header("Authorization", format!("GenieKey {api_key}"))
+    .send()
+    .await
+    .map_err(|e| {
+      let sanitized_error =
+        svi::replace_in_string(&format!("{e:?}"), &replacers);
+      anyhow::Error::msg(format!(
+        "Error with Opsgenie request: {sanitized_error}"
+      ))
+    })?;
+
+  let status = response.status();
+  if status.is_success() {
+    debug!("Opsgenie alert sent successfully: {}", status);
+    Ok(())
+  } else {
+    let text = response.text().await.with_context(|| {
+      format!(
+        "Failed to send alert to Opsgenie | {status} | failed to get response text"
+      )
+    })?;
+    Err(anyhow!(
+      "Failed to send alert to Opsgenie | {} | {}",
+      status,
+      text
+    ))
+  }
+}
+
+fn priority(level: SeverityLevel) -> &'static str {
+  match level {
+    SeverityLevel::Critical => "P1",
+    SeverityLevel::Warning => "P3",
+    SeverityLevel::Ok => "P5",
+  }
+}
+
+#[derive(Serialize)]
+struct CreateAlert<'a> {
+  message: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  alias: Option<&'a str>,
+  description: &'a str,
+  priority: &'static str,
+  source: &'static str,
+}
+
+#[derive(Serialize)]
+struct CloseAlert {
+  source: &'static str,
+}
+
+fn http_client() -> &'static reqwest::Client {
+  static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+  CLIENT.get_or_init(reqwest::Client::new)
+}
@@ -0,0 +1,107 @@
+use std::sync::OnceLock;
+
+use serde::Serialize;
+
+use super::*;
+
+#[instrument(level = "debug", skip(routing_key))]
+pub async fn send_alert(
+  url: &str,
+  routing_key: &str,
+  alert: &Alert,
+) -> anyhow::Result<()> {
+  let content = standard_alert_content(alert);
+  if content.is_empty() {
+    return Ok(());
+  }
+
+  let (routing_key, replacers) =
+    interpolate_alerter_string(routing_key, alert).await?;
+
+  // Open alerts are identified by the alert id,
+  // so the incident is resolved when the alert closes.
+  let dedup_key = (!alert.id.is_empty()).then_some(alert.id.as_str());
+
+  let event = if alert.resolved
+    && alert.level == SeverityLevel::Ok
+    && dedup_key.is_some()
+  {
+    Event {
+      routing_key: &routing_key,
+      event_action: "resolve",
+      dedup_key,
+      payload: None,
+    }
+  } else {
+    Event {
+      routing_key: &routing_key,
+      event_action: "trigger",
+      dedup_key,
+      payload: Some(Payload {
+        summary: alert_summary(&content, 1024),
+        source: "komodo",
+        severity: severity(alert.level),
+        custom_details: &content,
+      }),
+    }
+  };
+
+  let response =
+    http_client().post(url).json(&event).send().await.map_err(
+      |e| {
+        let sanitized_error =
+          svi::replace_in_string(&format!("{e:?}"), &replacers);
+        anyhow::Error::msg(format!(
+          "Error with PagerDuty request: {sanitized_error}"
+        ))
+      },
+    )?;
+
+  let status = response.status();
+  if status.is_success() {
+    debug!("PagerDuty alert sent successfully: {}", status);
+    Ok(())
+  } else {
+    let text = response.text().await.with_context(|| {
+      format!(
+        "Failed to send event to PagerDuty | {status} | failed to get response text"
+      )
+    })?;
+    Err(anyhow!(
+      "Failed to send event to PagerDuty | {} | {}",
+      status,
+      text
+    ))
+  }
+}
+
+fn severity(level: SeverityLevel) -> &'static str {
+  match level {
+    SeverityLevel::Critical => "critical",
+    SeverityLevel::Warning => "warning",
+    SeverityLevel::Ok => "info",
+  }
+}
+
+#[derive(Serialize)]
+struct Event<'a> {
+  routing_key: &'a str,
+  event_action: &'static str,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  dedup_key: Option<&'a str>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  payload: Option<Payload<'a>>,
+}
+
+#[derive(Serialize)]
+struct Payload<'a> {
+  summary: String,
+  source: &'static str,
+  severity: &'static str,
+  custom_details: &'a str,
+}
+
+fn http_client() -> &'static reqwest::Client {
+  static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+  CLIENT.get_or_init(reqwest::Client::new)
+}
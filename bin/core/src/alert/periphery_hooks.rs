@@ -0,0 +1,67 @@
+use komodo_client::entities::{
+  ResourceTarget,
+  alert::Alert,
+  server::{Server, ServerState},
+};
+use periphery_client::api::RunAlertHooks;
+
+use crate::{
+  helpers::periphery_client,
+  state::{all_resources_cache, server_status_cache},
+};
+
+/// Passes the alert to the `alert_condition` hooks on the
+/// Periphery of the Server the alert is about, if any.
+pub async fn send_alert_to_periphery_hooks(alert: &Alert) {
+  let Some(server) = alert_server(&alert.target) else {
+    return;
+  };
+  // Eg. the alert is that the server is unreachable.
+  let reachable = server_status_cache()
+    .get(&server.id)
+    .await
+    .is_some_and(|status| status.state == ServerState::Ok);
+  if !reachable {
+    return;
+  }
+  let logs = async {
+    periphery_client(&server)
+      .await?
+      .request(RunAlertHooks {
+        alert: alert.clone(),
+      })
+      .await
+  }
+  .await;
+  match logs {
+    Ok(logs) => {
+      for log in logs.into_iter().filter(|log| !log.success) {
+        warn!(
+          "{} failed on Server {} | {}",
+          log.stage,
+          server.name,
+          log.stderr.trim()
+        );
+      }
+    }
+    Err(e) => debug!(
+      "Failed to run alert hooks on Server {} | {e:#}",
+      server.name
+    ),
+  }
+}
+
+fn alert_server(target: &ResourceTarget) -> Option<Server> {
+  let cache = all_resources_cache().load();
+  let server_id = match target {
+    ResourceTarget::Server(id) => id,
+    ResourceTarget::Stack(id) => {
+      &cache.stacks.get(id)?.config.server_id
+    }
+    ResourceTarget::Deployment(id) => {
+      &cache.deployments.get(id)?.config.server_id
+    }
+    _ => return None,
+  };
+  cache.servers.get(server_id).cloned()
+}
@@ -0,0 +1,111 @@
+use database::mungos::find::find_collect;
+use komodo_client::entities::{
+  ResourceTarget, alert::Alert, alerter::AlertRouting,
+};
+
+use crate::{
+  helpers::all_resources::AllResourcesById,
+  state::{all_resources_cache, db_client},
+};
+
+/// Whether the alert passes the Alerter routing rules.
+pub async fn alert_is_routed(
+  routing: &AlertRouting,
+  alert: &Alert,
+) -> bool {
+  if routing.is_empty() {
+    return true;
+  }
+
+  if !included(&routing.levels, &routing.except_levels, |level| {
+    *level == alert.level
+  }) {
+    return false;
+  }
+
+  let (variant, _) = alert.target.extract_variant_id();
+  if !included(
+    &routing.resource_types,
+    &routing.except_resource_types,
+    |resource_type| *resource_type == variant,
+  ) {
+    return false;
+  }
+
+  let tag_rules =
+    !routing.tags.is_empty() || !routing.except_tags.is_empty();
+  // Load before the resources cache, so it isn't held across await.
+  let tags = if tag_rules {
+    find_collect(&db_client().tags, None, None)
+      .await
+      .inspect_err(|e| {
+        warn!("Failed to get tags from db for alert routing | {e:#}")
+      })
+      .unwrap_or_default()
+  } else {
+    Vec::new()
+  };
+
+  let all = all_resources_cache().load();
+
+  if !routing.servers.is_empty() || !routing.except_servers.is_empty()
+  {
+    let server = alert_server_id(&all, &alert.target)
+      .and_then(|id| all.servers.get(id));
+    if !included(&routing.servers, &routing.except_servers, |entry| {
+      server
+        .map(|server| entry == &server.id || entry == &server.name)
+        .unwrap_or_default()
+    }) {
+      return false;
+    }
+  }
+
+  if tag_rules {
+    let resource_tags = all
+      .resource_meta(&alert.target)
+      .map(|meta| meta.tags.as_slice())
+      .unwrap_or_default();
+    let tags = tags
+      .iter()
+      .filter(|tag| resource_tags.contains(&tag.id))
+      .collect::<Vec<_>>();
+    if !included(&routing.tags, &routing.except_tags, |entry| {
+      // Also accept entries like `tag:team-a`
+      let entry = entry.strip_prefix("tag:").unwrap_or(entry);
+      tags.iter().any(|tag| entry == tag.id || entry == tag.name)
+    }) {
+      return false;
+    }
+  }
+
+  true
+}
+
+/// The include list must be empty or have a match,
+/// and the except list can't have a match.
+fn included<T>(
+  include: &[T],
+  except: &[T],
+  matches: impl Fn(&T) -> bool,
+) -> bool {
+  (include.is_empty() || include.iter().any(&matches))
+    && !except.iter().any(matches)
+}
+
+/// The Server the alerting resource is on.
+fn alert_server_id<'a>(
+  all: &'a AllResourcesById,
+  target: &'a ResourceTarget,
+) -> Option<&'a String> {
+  match target {
+    ResourceTarget::Server(id) => Some(id),
+    ResourceTarget::Deployment(id) => {
+      all.deployments.get(id).map(|d| &d.config.server_id)
+    }
+    ResourceTarget::Stack(id) => {
+      all.stacks.get(id).map(|s| &s.config.server_id)
+    }
+    _ => None,
+  }
+}
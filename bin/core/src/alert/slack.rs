@@ -236,6 +236,52 @@ pub async fn send_alert(
         }
       }
     }
+    AlertData::ServerNetwork {
+      id,
+      name,
+      region,
+      ingress_mb_per_sec,
+      egress_mb_per_sec,
+    } => {
+      let region = fmt_region(region);
+      let text = format!(
+        "{level} | *{name}*{region} network throughput at *{ingress_mb_per_sec:.1} MB/s* in / *{egress_mb_per_sec:.1} MB/s* out 🌐"
+      );
+      let blocks = vec![
+        Block::header(level),
+        Block::section(format!(
+          "*{name}*{region} network throughput at *{ingress_mb_per_sec:.1} MB/s* in / *{egress_mb_per_sec:.1} MB/s* out 🌐"
+        )),
+        Block::section(resource_link(
+          ResourceTargetVariant::Server,
+          id,
+        )),
+      ];
+      (text, blocks.into())
+    }
+    AlertData::ServerDiskIo {
+      id,
+      name,
+      region,
+      read_mb_per_sec,
+      write_mb_per_sec,
+    } => {
+      let region = fmt_region(region);
+      let text = format!(
+        "{level} | *{name}*{region} disk IO at *{read_mb_per_sec:.1} MB/s* read / *{write_mb_per_sec:.1} MB/s* write 💿"
+      );
+      let blocks = vec![
+        Block::header(level),
+        Block::section(format!(
+          "*{name}*{region} disk IO at *{read_mb_per_sec:.1} MB/s* read / *{write_mb_per_sec:.1} MB/s* write 💿"
+        )),
+        Block::section(resource_link(
+          ResourceTargetVariant::Server,
+          id,
+        )),
+      ];
+      (text, blocks.into())
+    }
     AlertData::ContainerStateChange {
       name,
       server_name,
@@ -258,6 +304,26 @@ pub async fn send_alert(
       ];
       (text, blocks.into())
     }
+    AlertData::ContainerUnhealthy {
+      id,
+      name,
+      server_name,
+      server_id: _server_id,
+    } => {
+      let text =
+        format!("{level} | 📦 Deployment *{name}* is *unhealthy* 🩺");
+      let blocks = vec![
+        Block::header(level),
+        Block::section(format!(
+          "*{name}* healthcheck is failing\nserver: *{server_name}*",
+        )),
+        Block::section(resource_link(
+          ResourceTargetVariant::Deployment,
+          id,
+        )),
+      ];
+      (text, blocks.into())
+    }
     AlertData::DeploymentImageUpdateAvailable {
       id,
       name,
@@ -322,6 +388,48 @@ pub async fn send_alert(
       ];
       (text, blocks.into())
     }
+    AlertData::StackDrifted {
+      id,
+      name,
+      server_id: _server_id,
+      server_name,
+    } => {
+      let text =
+        format!("🥞 Stack *{name}* files have changed since deploy");
+      let blocks = vec![
+        Block::header(text.clone()),
+        Block::section(format!("server: *{server_name}*")),
+        Block::section(resource_link(
+          ResourceTargetVariant::Stack,
+          id,
+        )),
+      ];
+      (text, blocks.into())
+    }
+    AlertData::StackRuntimeDrift {
+      id,
+      name,
+      server_id: _server_id,
+      server_name,
+      services,
+    } => {
+      let text = format!(
+        "🥞 Stack *{name}* containers no longer match the deployed compose file"
+      );
+      let blocks = vec![
+        Block::header(text.clone()),
+        Block::section(format!("server: *{server_name}*")),
+        Block::section(format!(
+          "services: *{}*",
+          services.join(", ")
+        )),
+        Block::section(resource_link(
+          ResourceTargetVariant::Stack,
+          id,
+        )),
+      ];
+      (text, blocks.into())
+    }
     AlertData::StackImageUpdateAvailable {
       id,
       name,
@@ -367,6 +475,30 @@ pub async fn send_alert(
       ];
       (text, blocks.into())
     }
+    AlertData::StackServiceReplicasLow {
+      id,
+      name,
+      server_id: _server_id,
+      server_name,
+      service,
+      running,
+      desired,
+    } => {
+      let text = format!(
+        "{level} | Stack *{name}* service *{service}* has *{running}/{desired}* replicas running"
+      );
+      let blocks = vec![
+        Block::header(level),
+        Block::section(format!(
+          "server: *{server_name}*\nservice: *{service}*\nreplicas: *{running}/{desired}*",
+        )),
+        Block::section(resource_link(
+          ResourceTargetVariant::Stack,
+          id,
+        )),
+      ];
+      (text, blocks.into())
+    }
     AlertData::AwsBuilderTerminationFailed {
       instance_id,
       message,
@@ -433,6 +565,28 @@ pub async fn send_alert(
       ];
       (text, blocks.into())
     }
+    AlertData::ProcedureApprovalRequired {
+      id,
+      name,
+      stage,
+      approval_id,
+      message,
+    } => {
+      let text = format!(
+        "{level} | Procedure *{name}* stage *{stage}* is waiting for approval ✋"
+      );
+      let mut blocks = vec![Block::header(text.clone())];
+      if !message.is_empty() {
+        blocks.push(Block::section(message.clone()));
+      }
+      blocks
+        .push(Block::section(format!("Approval id: {approval_id}")));
+      blocks.push(Block::section(resource_link(
+        ResourceTargetVariant::Procedure,
+        id,
+      )));
+      (text, blocks.into())
+    }
     AlertData::ActionFailed { id, name } => {
       let text = format!("{level} | Action *{name}* has *failed*");
       let blocks = vec![
@@ -444,6 +598,52 @@ pub async fn send_alert(
       ];
       (text, blocks.into())
     }
+    AlertData::UpdateFailed {
+      resource_type,
+      id,
+      name,
+      operation,
+      ..
+    } => {
+      let text = format!(
+        "{level} | *{operation}* on {resource_type} *{name}* has *failed*"
+      );
+      let blocks = vec![
+        Block::header(text.clone()),
+        Block::section(resource_link(*resource_type, id)),
+      ];
+      (text, blocks.into())
+    }
+    AlertData::ScheduledCommandFailed { id, name, command } => {
+      let text = format!(
+        "{level} | Scheduled command *{command}* on *{name}* has *failed*"
+      );
+      let blocks = vec![
+        Block::header(text.clone()),
+        Block::section(resource_link(
+          ResourceTargetVariant::Server,
+          id,
+        )),
+      ];
+      (text, blocks.into())
+    }
+    AlertData::AutoStopPending {
+      resource_type,
+      id,
+      name,
+      mode,
+      minutes,
+    } => {
+      let text = format!(
+        "{level} | *{name}* ({resource_type}) | Auto {} in *{minutes}* min 🕝",
+        auto_stop_action(*mode)
+      );
+      let blocks = vec![
+        Block::header(text.clone()),
+        Block::section(resource_link(*resource_type, id)),
+      ];
+      (text, blocks.into())
+    }
     AlertData::ScheduleRun {
       resource_type,
       id,
@@ -458,6 +658,103 @@ pub async fn send_alert(
       ];
       (text, blocks.into())
     }
+    AlertData::ScheduleMissed {
+      resource_type,
+      id,
+      name,
+      scheduled_at,
+      run_late,
+    } => {
+      let text = format!(
+        "{level} | *{name}* ({}) | {}",
+        fmt_schedule_type(*resource_type),
+        fmt_schedule_missed(*scheduled_at, *run_late)
+      );
+      let mut blocks = vec![Block::header(text.clone())];
+      let link = schedule_link(*resource_type, id);
+      if !link.is_empty() {
+        blocks.push(Block::section(link));
+      }
+      (text, blocks.into())
+    }
+    AlertData::ProviderAccountCheckFailed {
+      provider_type,
+      domain,
+      username,
+      message,
+      ..
+    } => {
+      let text = format!(
+        "{level} | *{username}* @ {domain} ({provider_type}) | Token check failed 🔑"
+      );
+      let blocks =
+        vec![Block::header(text.clone()), Block::section(message)];
+      (text, blocks.into())
+    }
+    AlertData::ProviderTokenExpiring {
+      provider_type,
+      domain,
+      username,
+      days,
+      ..
+    } => {
+      let text = format!(
+        "{level} | *{username}* @ {domain} ({provider_type}) | Token expires in *{days}* days 🔑"
+      );
+      let blocks = vec![Block::header(text.clone())];
+      (text, blocks.into())
+    }
+    AlertData::ExternalStatus {
+      name,
+      state,
+      message,
+    } => {
+      let text =
+        format!("{level} | *{name}* (External) is *{state}*");
+      let mut blocks = vec![Block::header(text.clone())];
+      if !message.is_empty() {
+        blocks.push(Block::section(message));
+      }
+      (text, blocks.into())
+    }
+    AlertData::Prometheus {
+      name,
+      summary,
+      description,
+      generator_url,
+      ..
+    } => {
+      let text = format!("{level} | *{name}* (Prometheus) 🔥");
+      let mut blocks = vec![Block::header(text.clone())];
+      for line in [summary, description, generator_url] {
+        if !line.is_empty() {
+          blocks.push(Block::section(line));
+        }
+      }
+      (text, blocks.into())
+    }
+    AlertData::WeeklyDigest {
+      id,
+      name,
+      since,
+      sections,
+    } => {
+      let since = fmt_date(*since);
+      let text = format!(
+        "📊 Weekly digest from Alerter *{name}* since {since}"
+      );
+      let mut blocks = vec![Block::header(text.clone())];
+      blocks.extend(
+        fmt_digest_sections(sections)
+          .split("\n\n")
+          .map(Block::section),
+      );
+      blocks.push(Block::section(resource_link(
+        ResourceTargetVariant::Alerter,
+        id,
+      )));
+      (text, blocks.into())
+    }
     AlertData::Custom { message, details } => {
       let text = format!("{level} | {message}");
       let blocks =
@@ -467,21 +764,11 @@ pub async fn send_alert(
     AlertData::None {} => Default::default(),
   };
   if !text.is_empty() {
-    let VariablesAndSecrets { variables, secrets } =
-      get_variables_and_secrets().await?;
-    let mut url_interpolated = url.to_string();
-
-    let mut interpolator =
-      Interpolator::new(Some(&variables), &secrets);
-
-    interpolator.interpolate_string(&mut url_interpolated)?;
+    let (url_interpolated, replacers) =
+      interpolate_alerter_string(url, alert).await?;
 
     let slack = ::slack::Client::new(url_interpolated);
     slack.send_message(text, blocks).await.map_err(|e| {
-      let replacers = interpolator
-        .secret_replacers
-        .into_iter()
-        .collect::<Vec<_>>();
       let sanitized_error =
         svi::replace_in_string(&format!("{e:?}"), &replacers);
       anyhow::Error::msg(format!(
@@ -0,0 +1,75 @@
+use anyhow::anyhow;
+use axum::{
+  Extension, Router,
+  http::header,
+  middleware,
+  response::{IntoResponse, Response},
+  routing::post,
+};
+use komodo_client::{
+  api::{artifact::DownloadArtifactBody, read::GetUpdate},
+  entities::user::User,
+};
+use resolver_api::Resolve;
+use serror::Json;
+
+use crate::{
+  api::read::ReadArgs, auth::auth_request,
+  helpers::artifact::read_artifact,
+};
+
+pub fn router() -> Router {
+  Router::new()
+    .route("/download", post(download_artifact))
+    .layer(middleware::from_fn(auth_request))
+}
+
+#[instrument(
+  name = "DownloadArtifact",
+  skip(user),
+  fields(
+    user_id = user.id,
+  )
+)]
+async fn download_artifact(
+  Extension(user): Extension<User>,
+  Json(DownloadArtifactBody { update, name }): Json<
+    DownloadArtifactBody,
+  >,
+) -> serror::Result<Response> {
+  info!("/artifact/download request | user: {}", user.username);
+
+  // Checks the user has permission to read the Update
+  let update =
+    GetUpdate { id: update }.resolve(&ReadArgs { user }).await?;
+
+  if !update
+    .artifacts
+    .iter()
+    .any(|artifact| artifact.name == name)
+  {
+    return Err(
+      anyhow!("No artifact named {name} on update {}", update.id)
+        .into(),
+    );
+  }
+
+  let contents = read_artifact(&update.id, &name).await?;
+
+  Ok(
+    (
+      [
+        (
+          header::CONTENT_TYPE,
+          String::from("application/octet-stream"),
+        ),
+        (
+          header::CONTENT_DISPOSITION,
+          format!("attachment; filename=\"{name}\""),
+        ),
+      ],
+      contents,
+    )
+      .into_response(),
+  )
+}
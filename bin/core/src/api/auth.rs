@@ -2,7 +2,10 @@ use std::{sync::OnceLock, time::Instant};
 
 use axum::{Router, extract::Path, http::HeaderMap, routing::post};
 use derive_variants::{EnumVariants, ExtractVariant};
-use komodo_client::{api::auth::*, entities::user::User};
+use komodo_client::{
+  api::auth::*,
+  entities::{audit::AuditLogEntry, komodo_timestamp, user::User},
+};
 use reqwest::StatusCode;
 use resolver_api::Resolve;
 use response::Response;
@@ -20,7 +23,10 @@ use crate::{
     oidc::{self, client::oidc_client},
   },
   config::core_config,
-  helpers::query::get_user,
+  helpers::{
+    audit::{RequestSource, record_audit},
+    query::get_user,
+  },
   state::jwt_client,
 };
 
@@ -29,6 +35,7 @@ use super::Variant;
 #[derive(Default)]
 pub struct AuthArgs {
   pub headers: HeaderMap,
+  pub source: RequestSource,
 }
 
 #[typeshare]
@@ -45,6 +52,7 @@ pub enum AuthRequest {
   GetLoginOptions(GetLoginOptions),
   SignUpLocalUser(SignUpLocalUser),
   LoginLocalUser(LoginLocalUser),
+  LoginLdapUser(LoginLdapUser),
   ExchangeForJwt(ExchangeForJwt),
   GetUser(GetUser),
 }
@@ -58,6 +66,10 @@ pub fn router() -> Router {
     info!("🔑 Local Login Enabled");
   }
 
+  if core_config().ldap_enabled {
+    info!("🔑 LDAP Login Enabled");
+  }
+
   if github_oauth_client().is_some() {
     info!("🔑 Github Login Enabled");
     router = router.nest("/github", github::router())
@@ -78,6 +90,7 @@ pub fn router() -> Router {
 
 async fn variant_handler(
   headers: HeaderMap,
+  source: RequestSource,
   Path(Variant { variant }): Path<Variant>,
   Json(params): Json<serde_json::Value>,
 ) -> serror::Result<axum::response::Response> {
@@ -85,12 +98,17 @@ async fn variant_handler(
     "type": variant,
     "params": params,
   }))?;
-  handler(headers, Json(req)).await
+  handler(headers, source, Json(req)).await
 }
 
-#[instrument(name = "AuthHandler", level = "debug", skip(headers))]
+#[instrument(
+  name = "AuthHandler",
+  level = "debug",
+  skip(headers, source)
+)]
 async fn handler(
   headers: HeaderMap,
+  source: RequestSource,
   Json(request): Json<AuthRequest>,
 ) -> serror::Result<axum::response::Response> {
   let timer = Instant::now();
@@ -99,7 +117,7 @@ async fn handler(
     "/auth request {req_id} | METHOD: {:?}",
     request.extract_variant()
   );
-  let res = request.resolve(&AuthArgs { headers }).await;
+  let res = request.resolve(&AuthArgs { headers, source }).await;
   if let Err(e) = &res {
     debug!("/auth request {req_id} | error: {:#}", e.error);
   }
@@ -108,6 +126,30 @@ async fn handler(
   res.map(|res| res.0)
 }
 
+/// Records a login / sign up attempt in the audit log.
+/// Failed attempts have no user id.
+pub fn record_login(
+  AuthArgs { source, .. }: &AuthArgs,
+  operation: &str,
+  username: String,
+  res: &serror::Result<JwtResponse>,
+) {
+  let entry = AuditLogEntry {
+    ts: komodo_timestamp(),
+    user_id: res
+      .as_ref()
+      .map(|jwt| jwt.user_id.clone())
+      .unwrap_or_default(),
+    username,
+    api_key: source.api_key.clone(),
+    ip: source.ip.clone(),
+    api: String::from("auth"),
+    operation: operation.to_string(),
+    ..Default::default()
+  };
+  record_audit(entry, None, res);
+}
+
 fn login_options_reponse() -> &'static GetLoginOptionsResponse {
   static GET_LOGIN_OPTIONS_RESPONSE: OnceLock<
     GetLoginOptionsResponse,
@@ -119,6 +161,7 @@ fn login_options_reponse() -> &'static GetLoginOptionsResponse {
       github: github_oauth_client().is_some(),
       google: google_oauth_client().is_some(),
       oidc: oidc_client().load().is_some(),
+      ldap: config.ldap_enabled,
       registration_disabled: config.disable_user_registration,
     }
   })
@@ -138,12 +181,15 @@ impl Resolve<AuthArgs> for ExchangeForJwt {
   #[instrument(name = "ExchangeForJwt", level = "debug", skip(self))]
   async fn resolve(
     self,
-    _: &AuthArgs,
+    args: &AuthArgs,
   ) -> serror::Result<ExchangeForJwtResponse> {
-    jwt_client()
+    let res = jwt_client()
       .redeem_exchange_token(&self.token)
       .await
-      .map_err(Into::into)
+      .map_err(Into::into);
+    // Completes the OAuth / OIDC login flows
+    record_login(args, "ExchangeForJwt", String::new(), &res);
+    res
   }
 }
 
@@ -151,7 +197,7 @@ impl Resolve<AuthArgs> for GetUser {
   #[instrument(name = "GetUser", level = "debug", skip(self))]
   async fn resolve(
     self,
-    AuthArgs { headers }: &AuthArgs,
+    AuthArgs { headers, .. }: &AuthArgs,
   ) -> serror::Result<User> {
     let user_id = get_user_id_from_headers(headers)
       .await
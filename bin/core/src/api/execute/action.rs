@@ -14,7 +14,7 @@ use database::mungos::{
 use interpolate::Interpolator;
 use komodo_client::{
   api::{
-    execute::{BatchExecutionResponse, BatchRunAction, RunAction},
+    execute::{BatchRunAction, RunAction},
     user::{CreateApiKey, CreateApiKeyResponse, DeleteApiKey},
   },
   entities::{
@@ -39,6 +39,8 @@ use crate::{
   helpers::{
     query::{VariablesAndSecrets, get_variables_and_secrets},
     random_string,
+    resource_lock::acquire_resource_lock,
+    stage_outputs::ACTION_OUTPUT_PREFIX,
     update::update_update,
   },
   permission::get_check_permissions,
@@ -59,14 +61,20 @@ impl super::BatchExecute for BatchRunAction {
 }
 
 impl Resolve<ExecuteArgs> for BatchRunAction {
-  #[instrument(name = "BatchRunAction", skip(self, user), fields(user_id = user.id))]
+  #[instrument(name = "BatchRunAction", skip(self, user, update), fields(user_id = user.id, update_id = update.id))]
   async fn resolve(
     self,
-    ExecuteArgs { user, .. }: &ExecuteArgs,
-  ) -> serror::Result<BatchExecutionResponse> {
+    ExecuteArgs { user, update }: &ExecuteArgs,
+  ) -> serror::Result<Update> {
     Ok(
-      super::batch_execute::<BatchRunAction>(&self.pattern, user)
-        .await?,
+      super::batch_execute::<BatchRunAction>(
+        &self.pattern,
+        self.parallelism,
+        self.abort_on_failure,
+        user,
+        update,
+      )
+      .await?,
     )
   }
 }
@@ -84,6 +92,9 @@ impl Resolve<ExecuteArgs> for RunAction {
     )
     .await?;
 
+    let _resource_lock =
+      acquire_resource_lock(&action, &update).await?;
+
     // get the action state for the action (or insert default).
     let action_state = action_states()
       .action
@@ -122,6 +133,7 @@ impl Resolve<ExecuteArgs> for RunAction {
     let CreateApiKeyResponse { key, secret } = CreateApiKey {
       name: update.id.clone(),
       expires: 0,
+      scope: Default::default(),
     }
     .resolve(&UserArgs {
       user: action_user().to_owned(),
@@ -231,6 +243,7 @@ impl Resolve<ExecuteArgs> for RunAction {
             id: action.id,
             name: action.name,
           },
+          notifications: Default::default(),
         };
         send_alerts(&[alert]).await
       });
@@ -297,6 +310,11 @@ const TOML = {{
 
 const ARGS = {args};
 
+/** Publish an output for later Procedure stages to reference */
+const OUTPUT = (name: string, value: string | number | boolean) => {{
+  console.log('{ACTION_OUTPUT_PREFIX}' + JSON.stringify({{ name, value: String(value) }}));
+}}
+
 const komodo = KomodoClient('{base_url}', {{
   type: 'api-key',
   params: {{ key: '{key}', secret: '{secret}' }}
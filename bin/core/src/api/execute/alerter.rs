@@ -61,6 +61,7 @@ impl Resolve<ExecuteArgs> for TestAlerter {
         name: alerter.name.clone(),
       },
       resolved_ts: Some(ts),
+      notifications: Default::default(),
     };
 
     if let Err(e) = send_alert_to_alerter(&alerter, &alert).await {
@@ -123,6 +124,7 @@ impl Resolve<ExecuteArgs> for SendAlert {
         details: self.details,
       },
       resolved_ts: Some(ts),
+      notifications: Default::default(),
     };
 
     update.push_simple_log(
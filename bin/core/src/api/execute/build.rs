@@ -1,6 +1,7 @@
 use std::{
   collections::{HashMap, HashSet},
   future::IntoFuture,
+  path::Path,
   time::Duration,
 };
 
@@ -17,16 +18,15 @@ use formatting::format_serror;
 use futures::future::join_all;
 use interpolate::Interpolator;
 use komodo_client::{
-  api::execute::{
-    BatchExecutionResponse, BatchRunBuild, CancelBuild, Deploy,
-    RunBuild,
-  },
+  api::execute::{BatchRunBuild, CancelBuild, Deploy, RunBuild},
   entities::{
     alert::{Alert, AlertData, SeverityLevel},
     all_logs_success,
-    build::{Build, BuildConfig},
+    build::{
+      Build, BuildConfig, BuildMatrixVariant, ImageScan,
+      MANIFEST_DIGEST_LOG_STAGE,
+    },
     builder::{Builder, BuilderConfig},
-    deployment::DeploymentState,
     komodo_timestamp, optional_string,
     permission::PermissionLevel,
     repo::Repo,
@@ -34,21 +34,30 @@ use komodo_client::{
     user::auto_redeploy_user,
   },
 };
-use periphery_client::api;
+use periphery_client::{PeripheryClient, api};
 use resolver_api::Resolve;
-use tokio_util::sync::CancellationToken;
 
 use crate::{
   alert::send_alerts,
+  cloud::BuildCleanupData,
   helpers::{
-    build_git_token,
-    builder::{cleanup_builder_instance, get_builder_periphery},
+    TokenRequester, build_git_token,
+    builder::{
+      check_spot_interruption, cleanup_builder_instance,
+      get_builder_periphery, wait_for_spot_interruption,
+    },
+    cancel::execution_cancel_token,
     channel::build_cancel_channel,
+    execution_environment::capture_execution_environment,
+    image_retention::prune_registry_images,
+    junit::add_junit_report,
     query::{
       VariablesAndSecrets, get_deployment_state,
       get_variables_and_secrets,
     },
     registry_token,
+    rerun::pin_rerun_commit,
+    resource_lock::acquire_build_lock,
     update::{init_execution_update, update_update},
   },
   permission::get_check_permissions,
@@ -66,14 +75,20 @@ impl super::BatchExecute for BatchRunBuild {
 }
 
 impl Resolve<ExecuteArgs> for BatchRunBuild {
-  #[instrument(name = "BatchRunBuild", skip(user), fields(user_id = user.id))]
+  #[instrument(name = "BatchRunBuild", skip(self, user, update), fields(user_id = user.id, update_id = update.id))]
   async fn resolve(
     self,
-    ExecuteArgs { user, .. }: &ExecuteArgs,
-  ) -> serror::Result<BatchExecutionResponse> {
+    ExecuteArgs { user, update }: &ExecuteArgs,
+  ) -> serror::Result<Update> {
     Ok(
-      super::batch_execute::<BatchRunBuild>(&self.pattern, user)
-        .await?,
+      super::batch_execute::<BatchRunBuild>(
+        &self.pattern,
+        self.parallelism,
+        self.abort_on_failure,
+        user,
+        update,
+      )
+      .await?,
     )
   }
 }
@@ -91,6 +106,14 @@ impl Resolve<ExecuteArgs> for RunBuild {
     )
     .await?;
 
+    let mut update = update.clone();
+
+    // Acquired first, so builds in a concurrency group
+    // wait in line before the build is prepared.
+    let _resource_lock = acquire_build_lock(&build, &update).await?;
+    // The build may have been updated while waiting, eg the version.
+    build = resource::get::<Build>(&build.id).await?;
+
     let mut repo = if !build.config.files_on_host
       && !build.config.linked_repo.is_empty()
     {
@@ -101,6 +124,17 @@ impl Resolve<ExecuteArgs> for RunBuild {
       None
     };
 
+    // Reruns build the recorded commit.
+    match repo.as_mut() {
+      Some(repo) => {
+        pin_rerun_commit(&mut repo.config.commit, &mut update)
+      }
+      None if !build.config.repo.is_empty() => {
+        pin_rerun_commit(&mut build.config.commit, &mut update)
+      }
+      None => {}
+    }
+
     let VariablesAndSecrets {
       mut variables,
       secrets,
@@ -116,6 +150,8 @@ impl Resolve<ExecuteArgs> for RunBuild {
       return Err(anyhow!("Must attach builder to RunBuild").into());
     }
 
+    validate_matrix(&build.config.matrix)?;
+
     // get the action state for the build (or insert default).
     let action_state =
       action_states().build.get_or_insert_default(&build.id).await;
@@ -129,8 +165,6 @@ impl Resolve<ExecuteArgs> for RunBuild {
       build.config.version.increment();
     }
 
-    let mut update = update.clone();
-
     update.version = build.config.version;
     update_update(update.clone()).await?;
 
@@ -140,7 +174,8 @@ impl Resolve<ExecuteArgs> for RunBuild {
     let registry_tokens =
       validate_account_extract_registry_tokens(&build).await?;
 
-    let cancel = CancellationToken::new();
+    // Also cancelled by CancelUpdate.
+    let cancel = execution_cancel_token().child_token();
     let cancel_clone = cancel.clone();
     let mut cancel_recv =
       build_cancel_channel().receiver.resubscribe();
@@ -160,19 +195,13 @@ impl Resolve<ExecuteArgs> for RunBuild {
             id = cancel_recv.recv() => id?
           };
           if incoming_build_id == build_id {
-            if is_server_builder {
-              update.push_error_log("Cancel acknowledged", "Build cancellation is not possible on server builders at this time. Use an AWS builder to enable this feature.");
-            } else {
-              update.push_simple_log("Cancel acknowledged", "The build cancellation has been queued, it may still take some time.");
-            }
+            update.push_simple_log("Cancel acknowledged", "The build cancellation has been queued, it may still take some time.");
             update.finalize();
             let id = update.id.clone();
             if let Err(e) = update_update(update).await {
               warn!("failed to modify Update {id} on db | {e:#}");
             }
-            if !is_server_builder {
-              cancel_clone.cancel();
-            }
+            cancel_clone.cancel();
             return Ok(());
           }
         }
@@ -185,145 +214,326 @@ impl Resolve<ExecuteArgs> for RunBuild {
       }
     });
 
-    // GET BUILDER PERIPHERY
-    let (periphery, cleanup_data) = match get_builder_periphery(
-      build.name.clone(),
-      Some(build.config.version),
-      builder,
-      &mut update,
-    )
-    .await
-    {
-      Ok(builder) => builder,
-      Err(e) => {
-        warn!(
-          "failed to get builder for build {} | {e:#}",
-          build.name
-        );
-        update.logs.push(Log::error(
-          "get builder",
-          format_serror(&e.context("failed to get builder").into()),
-        ));
-        return handle_early_return(
-          update, build.id, build.name, false,
-        )
-        .await;
+    // Spot instances may be interrupted during the build,
+    // in which case the build is restarted on a new instance.
+    let (spot, spot_retries) = match &builder.config {
+      BuilderConfig::Aws(config) if config.use_spot => {
+        (true, config.spot_interruption_retries.max(0))
       }
+      _ => (false, 0),
     };
+    if spot {
+      add_registry_cache_args(&mut build);
+    }
 
     // INTERPOLATE VARIABLES
-    let secret_replacers = if !build.config.skip_secret_interp {
-      let mut interpolator =
-        Interpolator::new(Some(&variables), &secrets);
+    let (secret_replacers, interpolated) =
+      if !build.config.skip_secret_interp {
+        let mut interpolator =
+          Interpolator::new(Some(&variables), &secrets);
 
-      interpolator.interpolate_build(&mut build)?;
-
-      if let Some(repo) = repo.as_mut() {
-        interpolator.interpolate_repo(repo)?;
-      }
+        interpolator.interpolate_build(&mut build)?;
 
-      interpolator.push_logs(&mut update.logs);
+        if let Some(repo) = repo.as_mut() {
+          interpolator.interpolate_repo(repo)?;
+        }
 
-      interpolator.secret_replacers
-    } else {
-      Default::default()
-    };
+        interpolator.push_logs(&mut update.logs);
 
-    let commit_message = if !build.config.files_on_host
-      && (!build.config.repo.is_empty()
-        || !build.config.linked_repo.is_empty())
-    {
-      // PULL OR CLONE REPO
-      let res = tokio::select! {
-        res = periphery
-          .request(api::git::PullOrCloneRepo {
-            args: repo.as_ref().map(Into::into).unwrap_or((&build).into()),
-            git_token,
-            environment: Default::default(),
-            env_file_path: Default::default(),
-            on_clone: None,
-            on_pull: None,
-            skip_secret_interp: Default::default(),
-            replacers: Default::default(),
-          }) => res,
-        _ = cancel.cancelled() => {
-          debug!("build cancelled during clone, cleaning up builder");
-          update.push_error_log("build cancelled", String::from("user cancelled build during repo clone"));
-          cleanup_builder_instance(cleanup_data, &mut update)
-            .await;
-          info!("builder cleaned up");
-          return handle_early_return(update, build.id, build.name, true).await
-        },
+        let interpolated = interpolator.interpolated_names();
+        (interpolator.secret_replacers, interpolated)
+      } else {
+        Default::default()
       };
 
-      let commit_message = match res {
-        Ok(res) => {
-          debug!("finished repo clone");
-          update.logs.extend(res.res.logs);
-          update.commit_hash =
-            res.res.commit_hash.unwrap_or_default().to_string();
-          res.res.commit_message.unwrap_or_default()
-        }
+    let mut interruptions = 0;
+    let mut image_scans = Vec::new();
+    let mut environment = None;
+
+    let (cleanup_data, commit_message) = loop {
+      // GET BUILDER PERIPHERY
+      let (periphery, cleanup_data) = match get_builder_periphery(
+        build.name.clone(),
+        Some(build.config.version),
+        builder.clone(),
+        &mut update,
+      )
+      .await
+      {
+        Ok(builder) => builder,
         Err(e) => {
-          warn!("Failed build at clone repo | {e:#}");
-          update.push_error_log(
-            "Clone Repo",
-            format_serror(&e.context("Failed to clone repo").into()),
+          warn!(
+            "failed to get builder for build {} | {e:#}",
+            build.name
           );
-          Default::default()
+          update.logs.push(Log::error(
+            "get builder",
+            format_serror(&e.context("failed to get builder").into()),
+          ));
+          return handle_early_return(
+            update,
+            build.id,
+            build.name,
+            false,
+            build.config.send_failure_alerts,
+          )
+          .await;
         }
       };
 
-      update_update(update.clone()).await?;
+      // Ok with the commit message, or Err with the spot interruption.
+      let attempt = 'attempt: {
+        let commit_message = if !build.config.files_on_host
+          && (!build.config.repo.is_empty()
+            || !build.config.linked_repo.is_empty())
+        {
+          // PULL OR CLONE REPO
+          let res = tokio::select! {
+            res = periphery
+              .request(api::git::PullOrCloneRepo {
+                args: repo.as_ref().map(Into::into).unwrap_or((&build).into()),
+                git_token: git_token.clone(),
+                environment: Default::default(),
+                env_file_path: Default::default(),
+                on_clone: None,
+                on_pull: None,
+                skip_secret_interp: Default::default(),
+                replacers: Default::default(),
+              }) => res,
+            _ = cancel.cancelled() => {
+              debug!("build cancelled during clone, cleaning up builder");
+              update.push_error_log("build cancelled", String::from("user cancelled build during repo clone"));
+              cleanup_builder_instance(cleanup_data, &mut update)
+                .await;
+              info!("builder cleaned up");
+              return handle_early_return(update, build.id, build.name, true, false).await
+            },
+            interruption = wait_for_spot_interruption(&periphery, spot) => {
+              break 'attempt Err(interruption)
+            },
+          };
 
-      Some(commit_message)
-    } else {
-      None
-    };
+          let commit_message = match res {
+            Ok(res) => {
+              debug!("finished repo clone");
+              update.logs.extend(res.res.logs);
+              update.commit_hash =
+                res.res.commit_hash.unwrap_or_default().to_string();
+              res.res.commit_message.unwrap_or_default()
+            }
+            Err(e) => {
+              if let Some(interruption) = check_spot_interruption(
+                &periphery,
+                &cleanup_data,
+                spot,
+              )
+              .await
+              {
+                break 'attempt Err(interruption);
+              }
+              warn!("Failed build at clone repo | {e:#}");
+              update.push_error_log(
+                "Clone Repo",
+                format_serror(
+                  &e.context("Failed to clone repo").into(),
+                ),
+              );
+              Default::default()
+            }
+          };
+
+          update_update(update.clone()).await?;
+
+          Some(commit_message)
+        } else {
+          None
+        };
+
+        if all_logs_success(&update.logs) {
+          // RUN BUILD
+          let res = tokio::select! {
+            res = periphery_build(&periphery, api::build::Build {
+                build: build.clone(),
+                repo: repo.clone(),
+                registry_tokens: registry_tokens.clone(),
+                replacers: secret_replacers.iter().cloned().collect(),
+                // To push a commit hash tagged image
+                commit_hash: optional_string(&update.commit_hash),
+                // Unused for now
+                additional_tags: Default::default(),
+                step: Default::default(),
+              }) => res,
+            _ = cancel.cancelled() => {
+              info!("build cancelled during build, cleaning up builder");
+              update.push_error_log("build cancelled", String::from("user cancelled build during docker build"));
+              // Server builders keep running the docker build otherwise.
+              if is_server_builder {
+                match periphery
+                  .request(api::build::KillBuild { name: build.name.clone() })
+                  .await
+                {
+                  Ok(log) => update.logs.push(log),
+                  Err(e) => update.push_error_log(
+                    "Kill Build",
+                    format_serror(&e.context("Failed to kill docker build on server builder").into()),
+                  ),
+                }
+              }
+              cleanup_builder_instance(cleanup_data, &mut update)
+                .await;
+              return handle_early_return(update, build.id, build.name, true, false).await
+            },
+            interruption = wait_for_spot_interruption(&periphery, spot) => {
+              break 'attempt Err(interruption)
+            },
+          };
 
-    if all_logs_success(&update.logs) {
-      // RUN BUILD
-      let res = tokio::select! {
-        res = periphery
-          .request(api::build::Build {
-            build: build.clone(),
-            repo,
-            registry_tokens,
-            replacers: secret_replacers.into_iter().collect(),
-            // To push a commit hash tagged image
-            commit_hash: optional_string(&update.commit_hash),
-            // Unused for now
-            additional_tags: Default::default(),
-          }) => res.context("failed at call to periphery to build"),
-        _ = cancel.cancelled() => {
-          info!("build cancelled during build, cleaning up builder");
-          update.push_error_log("build cancelled", String::from("user cancelled build during docker build"));
-          cleanup_builder_instance(cleanup_data, &mut update)
+          match res {
+            Ok(logs) => {
+              debug!("finished build");
+              update.logs.extend(logs);
+            }
+            Err(e) => {
+              if let Some(interruption) = check_spot_interruption(
+                &periphery,
+                &cleanup_data,
+                spot,
+              )
+              .await
+              {
+                break 'attempt Err(interruption);
+              }
+              warn!("error in build | {e:#}");
+              update.push_error_log(
+                "build",
+                format_serror(&e.context("failed to build").into()),
+              )
+            }
+          };
+
+          if !build.config.junit_report.is_empty() {
+            add_junit_report(
+              &periphery,
+              build.name.clone(),
+              api::build::GetJunitReport {
+                path: Path::new(&build.config.build_path)
+                  .join(&build.config.junit_report)
+                  .display()
+                  .to_string(),
+                name: repo
+                  .as_ref()
+                  .map(|repo| repo.name.clone())
+                  .unwrap_or_else(|| build.name.clone()),
+                linked_repo: repo.is_some(),
+              },
+              &mut update,
+            )
             .await;
-          return handle_early_return(update, build.id, build.name, true).await
-        },
-      };
+          }
 
-      match res {
-        Ok(logs) => {
-          debug!("finished build");
-          update.logs.extend(logs);
+          if build.config.scan_image && all_logs_success(&update.logs)
+          {
+            image_scans =
+              scan_images(&periphery, &build, &mut update).await;
+          }
+
+          if all_logs_success(&update.logs) {
+            environment = Some(
+              capture_execution_environment(
+                &periphery,
+                built_images(&build),
+                interpolated.clone(),
+              )
+              .await,
+            );
+          }
         }
-        Err(e) => {
-          warn!("error in build | {e:#}");
-          update.push_error_log(
-            "build",
-            format_serror(&e.context("failed to build").into()),
-          )
+
+        Ok(commit_message)
+      };
+
+      let interruption = match attempt {
+        Ok(commit_message) => break (cleanup_data, commit_message),
+        Err(interruption) => interruption,
+      };
+
+      warn!(
+        "spot builder interrupted during build {} | {interruption}",
+        build.name
+      );
+
+      // Interrupted instances can't be returned to the warm pool.
+      let cleanup_data = match cleanup_data {
+        BuildCleanupData::AwsWarmPool { instance, .. } => {
+          BuildCleanupData::Aws {
+            instance_id: instance.instance_id,
+            region: instance.region,
+          }
         }
+        cleanup_data => cleanup_data,
       };
+      cleanup_builder_instance(cleanup_data, &mut update).await;
+
+      if interruptions >= spot_retries {
+        update.push_error_log(
+          "spot interruption",
+          format!(
+            "{interruption}\nbuild interrupted {} times, not restarting",
+            interruptions + 1
+          ),
+        );
+        return handle_early_return(
+          update,
+          build.id,
+          build.name,
+          false,
+          build.config.send_failure_alerts,
+        )
+        .await;
+      }
+
+      interruptions += 1;
+      update.push_simple_log(
+        "spot interruption",
+        format!(
+          "{interruption}\nrestarting build on a new instance ({interruptions} / {spot_retries})"
+        ),
+      );
+      update_update(update.clone()).await?;
+    };
+
+    if all_logs_success(&update.logs)
+      && let Some(log) =
+        prune_registry_images(&build, &registry_tokens).await
+    {
+      update.logs.push(log);
     }
 
+    // The pushed manifest digest is what the tag resolves to,
+    // prefer it over the local image digest.
+    if let Some(environment) = environment.as_mut()
+      && let [image] = environment.images.as_mut_slice()
+      && let Some(digest) = update
+        .logs
+        .iter()
+        .find(|log| log.stage == MANIFEST_DIGEST_LOG_STAGE)
+        .map(|log| log.stdout.trim())
+    {
+      image.digest = digest.to_string();
+    }
+    update.environment = environment;
+
     update.finalize();
 
     let db = db_client();
 
     if update.success {
+      // All tags point to the multi-platform manifest pushed by periphery.
+      let built_digest = update
+        .logs
+        .iter()
+        .find(|log| log.stage == MANIFEST_DIGEST_LOG_STAGE)
+        .map(|log| log.stdout.trim().to_string());
       let _ = db
         .builds
         .update_one(
@@ -333,7 +543,23 @@ impl Resolve<ExecuteArgs> for RunBuild {
               .context("failed at converting version to bson")?,
             "info.last_built_at": komodo_timestamp(),
             "info.built_hash": &update.commit_hash,
-            "info.built_message": commit_message
+            "info.built_message": commit_message,
+            "info.built_digest": built_digest,
+            "info.built_platforms": &build.config.platforms,
+          }},
+        )
+        .await;
+    }
+
+    // Store the scans even if they failed the build.
+    if !image_scans.is_empty() {
+      let _ = db
+        .builds
+        .update_one(
+          doc! { "name": &build.name },
+          doc! { "$set": {
+            "info.image_scans": to_bson(&image_scans)
+              .context("failed at converting image scans to bson")?,
           }},
         )
         .await;
@@ -368,7 +594,7 @@ impl Resolve<ExecuteArgs> for RunBuild {
       tokio::spawn(async move {
         handle_post_build_redeploy(&build.id).await;
       });
-    } else {
+    } else if build.config.send_failure_alerts {
       warn!("build unsuccessful, alerting...");
       let target = update.target.clone();
       let version = update.version;
@@ -385,6 +611,7 @@ impl Resolve<ExecuteArgs> for RunBuild {
             name: build.name,
             version,
           },
+          notifications: Default::default(),
         };
         send_alerts(&[alert]).await
       });
@@ -394,12 +621,162 @@ impl Resolve<ExecuteArgs> for RunBuild {
   }
 }
 
+/// Matrix builds run the shared steps once, then build the variants
+/// in parallel. The variant name is added to the stage of its logs.
+async fn periphery_build(
+  periphery: &PeripheryClient,
+  request: api::build::Build,
+) -> anyhow::Result<Vec<Log>> {
+  if request.build.config.matrix.is_empty() {
+    return periphery
+      .request(request)
+      .await
+      .context("failed at call to periphery to build");
+  }
+
+  let mut logs = periphery
+    .request(api::build::Build {
+      step: api::build::BuildStep::Prepare,
+      ..request.clone()
+    })
+    .await
+    .context("failed at call to periphery to prepare matrix build")?;
+  if !all_logs_success(&logs) {
+    return Ok(logs);
+  }
+
+  let variants = request.build.config.matrix.iter().map(|variant| {
+    let request = api::build::Build {
+      build: request.build.matrix_variant(variant),
+      step: api::build::BuildStep::Variant(variant.name.clone()),
+      ..request.clone()
+    };
+    async move { (&variant.name, periphery.request(request).await) }
+  });
+
+  for (name, res) in join_all(variants).await {
+    match res {
+      Ok(variant_logs) => {
+        logs.extend(variant_logs.into_iter().map(|mut log| {
+          log.stage = format!("{} ({name})", log.stage);
+          log
+        }))
+      }
+      Err(e) => logs.push(Log::error(
+        &format!("Build ({name})"),
+        format_serror(
+          &e.context("failed at call to periphery to build variant")
+            .into(),
+        ),
+      )),
+    }
+  }
+
+  Ok(logs)
+}
+
+/// The image tag used to deploy the build,
+/// one for each matrix variant.
+fn built_images(build: &Build) -> Vec<String> {
+  let builds = if build.config.matrix.is_empty() {
+    vec![build.clone()]
+  } else {
+    build
+      .config
+      .matrix
+      .iter()
+      .map(|variant| build.matrix_variant(variant))
+      .collect()
+  };
+  builds
+    .into_iter()
+    .filter_map(|build| {
+      let image_name = build.get_image_names().into_iter().next()?;
+      let image = if build.config.image_tag.is_empty() {
+        format!("{image_name}:{}", build.config.version)
+      } else {
+        format!(
+          "{image_name}:{}-{}",
+          build.config.version, build.config.image_tag
+        )
+      };
+      Some(image)
+    })
+    .collect()
+}
+
+/// Scans the built images for vulnerabilities, one for each
+/// matrix variant. Fails the build if a scan finds
+/// vulnerabilities at or above the configured severity.
+async fn scan_images(
+  periphery: &PeripheryClient,
+  build: &Build,
+  update: &mut Update,
+) -> Vec<ImageScan> {
+  let mut scans = Vec::new();
+  for image in built_images(build) {
+    match periphery.request(api::build::ScanImage { image }).await {
+      Ok(res) => {
+        update.logs.push(res.log);
+        scans.extend(res.scan);
+      }
+      Err(e) => update.push_error_log(
+        "Image Scan",
+        format_serror(
+          &e.context("failed at call to periphery to scan image")
+            .into(),
+        ),
+      ),
+    }
+  }
+
+  let threshold = build.config.scan_fail_severity;
+  for scan in &scans {
+    let count = threshold.count(scan);
+    if count > 0 {
+      update.push_error_log(
+        "Image Scan Threshold",
+        format!(
+          "{} has {count} vulnerabilities at or above {threshold:?} severity",
+          scan.image
+        ),
+      );
+    }
+  }
+
+  scans
+}
+
+/// The variant names are used in the image tags,
+/// so they must be unique and valid in a tag.
+fn validate_matrix(
+  matrix: &[BuildMatrixVariant],
+) -> anyhow::Result<()> {
+  let mut names = HashSet::new();
+  for BuildMatrixVariant { name, .. } in matrix {
+    if name.is_empty()
+      || !name.chars().all(|c| {
+        c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')
+      })
+    {
+      return Err(anyhow!(
+        "Invalid matrix variant name '{name}'. Can only contain alphanumerics, '-', '_', and '.'"
+      ));
+    }
+    if !names.insert(name) {
+      return Err(anyhow!("Duplicate matrix variant name '{name}'"));
+    }
+  }
+  Ok(())
+}
+
 #[instrument(skip(update))]
 async fn handle_early_return(
   mut update: Update,
   build_id: String,
   build_name: String,
   is_cancel: bool,
+  send_failure_alert: bool,
 ) -> serror::Result<Update> {
   update.finalize();
   // Need to manually update the update before cache refresh,
@@ -417,7 +794,7 @@ async fn handle_early_return(
     refresh_build_state_cache().await;
   }
   update_update(update.clone()).await?;
-  if !update.success && !is_cancel {
+  if !update.success && !is_cancel && send_failure_alert {
     warn!("build unsuccessful, alerting...");
     let target = update.target.clone();
     let version = update.version;
@@ -434,6 +811,7 @@ async fn handle_early_return(
           name: build_name,
           version,
         },
+        notifications: Default::default(),
       };
       send_alerts(&[alert]).await
     });
@@ -570,11 +948,12 @@ async fn handle_post_build_redeploy(build_id: &str) {
         let state = get_deployment_state(&deployment.id)
           .await
           .unwrap_or_default();
-        if state == DeploymentState::Running {
+        if state.is_running() {
           let req = super::ExecuteRequest::Deploy(Deploy {
             deployment: deployment.id.clone(),
             stop_signal: None,
             stop_time: None,
+            inputs: None,
           });
           let user = auto_redeploy_user().to_owned();
           let res = async {
@@ -583,6 +962,7 @@ async fn handle_post_build_redeploy(build_id: &str) {
               deployment: deployment.id.clone(),
               stop_signal: None,
               stop_time: None,
+              inputs: None,
             }
             .resolve(&ExecuteArgs { user, update })
             .await
@@ -611,12 +991,11 @@ async fn handle_post_build_redeploy(build_id: &str) {
 /// and will check the core config for a token matching requirements.
 /// Otherwise it is left to periphery.
 async fn validate_account_extract_registry_tokens(
-  Build {
-    config: BuildConfig { image_registry, .. },
-    ..
-  }: &Build,
+  build: &Build,
   // Maps (domain, account) -> token
 ) -> serror::Result<Vec<(String, String, String)>> {
+  let image_registry = &build.config.image_registry;
+  let requester = TokenRequester::from(build);
   let mut res = HashMap::with_capacity(image_registry.capacity());
 
   for (domain, account) in image_registry
@@ -636,7 +1015,7 @@ async fn validate_account_extract_registry_tokens(
         .into(),
       );
     }
-    let Some(registry_token) = registry_token(domain, account).await.with_context(
+    let Some(registry_token) = registry_token(domain, account, &requester).await.with_context(
       || format!("Failed to get registry token in call to db. Stopping run. | {domain} | {account}"),
     )? else {
       continue;
@@ -655,3 +1034,36 @@ async fn validate_account_extract_registry_tokens(
       .collect(),
   )
 }
+
+/// Builds on spot instances share layers through a build cache in the registry,
+/// so a build restarted after an interruption doesn't start from scratch.
+/// Requires buildx, and is skipped if the build already configures a cache,
+/// with `cache_from` / `cache_to` or in the extra args.
+fn add_registry_cache_args(build: &mut Build) {
+  let BuildConfig {
+    use_buildx,
+    image_registry,
+    extra_args,
+    cache_from,
+    cache_to,
+    ..
+  } = &build.config;
+  if !*use_buildx
+    || !image_registry.iter().any(|r| !r.domain.is_empty())
+    || !cache_from.is_empty()
+    || !cache_to.is_empty()
+    || extra_args.iter().any(|arg| arg.contains("--cache-"))
+  {
+    return;
+  }
+  let Some(image_name) = build.get_image_names().into_iter().next()
+  else {
+    return;
+  };
+  let cache_ref =
+    format!("type=registry,ref={image_name}:buildcache");
+  build.config.extra_args.extend([
+    format!("--cache-from {cache_ref}"),
+    format!("--cache-to {cache_ref},mode=max"),
+  ]);
+}
@@ -1,13 +1,14 @@
-use std::sync::OnceLock;
+use std::{collections::HashSet, sync::OnceLock};
 
 use anyhow::{Context, anyhow};
 use cache::TimeoutCache;
+use database::mungos::{find::find_collect, mongodb::bson::doc};
 use formatting::format_serror;
 use interpolate::Interpolator;
 use komodo_client::{
   api::execute::*,
   entities::{
-    Version,
+    Version, all_logs_success,
     build::{Build, ImageRegistryConfig},
     deployment::{
       Deployment, DeploymentImage, extract_registry_domain,
@@ -16,7 +17,7 @@ use komodo_client::{
     permission::PermissionLevel,
     server::Server,
     update::{Log, Update},
-    user::User,
+    user::{User, auto_redeploy_user},
   },
 };
 use periphery_client::api;
@@ -24,15 +25,23 @@ use resolver_api::Resolve;
 
 use crate::{
   helpers::{
+    container_dns::apply_container_dns_defaults,
+    execution_environment::capture_execution_environment,
     periphery_client,
-    query::{VariablesAndSecrets, get_variables_and_secrets},
+    query::{
+      VariablesAndSecrets, get_deployment_state,
+      get_variables_and_secrets,
+    },
     registry_token,
-    update::update_update,
+    rerun::{pinned_image, rerun_inputs},
+    resource_lock::acquire_resource_lock,
+    runtime_inputs::RuntimeInputs,
+    update::{init_execution_update, update_update},
   },
   monitor::update_cache_for_server,
   permission::get_check_permissions,
   resource,
-  state::action_states,
+  state::{action_states, db_client},
 };
 
 use super::{ExecuteArgs, ExecuteRequest};
@@ -44,19 +53,26 @@ impl super::BatchExecute for BatchDeploy {
       deployment,
       stop_signal: None,
       stop_time: None,
+      inputs: None,
     })
   }
 }
 
 impl Resolve<ExecuteArgs> for BatchDeploy {
-  #[instrument(name = "BatchDeploy", skip(user), fields(user_id = user.id))]
+  #[instrument(name = "BatchDeploy", skip(self, user, update), fields(user_id = user.id, update_id = update.id))]
   async fn resolve(
     self,
-    ExecuteArgs { user, .. }: &ExecuteArgs,
-  ) -> serror::Result<BatchExecutionResponse> {
+    ExecuteArgs { user, update }: &ExecuteArgs,
+  ) -> serror::Result<Update> {
     Ok(
-      super::batch_execute::<BatchDeploy>(&self.pattern, user)
-        .await?,
+      super::batch_execute::<BatchDeploy>(
+        &self.pattern,
+        self.parallelism,
+        self.abort_on_failure,
+        user,
+        update,
+      )
+      .await?,
     )
   }
 }
@@ -95,6 +111,12 @@ impl Resolve<ExecuteArgs> for Deploy {
     let (mut deployment, server) =
       setup_deployment_execution(&self.deployment, user).await?;
 
+    let inputs =
+      RuntimeInputs::resolve(&deployment.config.inputs, self.inputs)?;
+
+    let _resource_lock =
+      acquire_resource_lock(&deployment, &update).await?;
+
     // get the action state for the deployment (or insert default).
     let action_state = action_states()
       .deployment
@@ -132,10 +154,25 @@ impl Resolve<ExecuteArgs> for Deploy {
         } else {
           format!("{version_str}-{}", build.config.image_tag)
         };
+        let image = format!("{image_name}:{version_str}");
+        if build.config.scan_block_deploy
+          && let Some(scan) = build
+            .info
+            .image_scans
+            .iter()
+            .find(|scan| scan.image == image)
+          && scan.critical > 0
+        {
+          return Err(
+            anyhow!(
+              "Image {image} has {} Critical vulnerabilities, and the Build blocks deploying it",
+              scan.critical
+            )
+            .into(),
+          );
+        }
         // replace image with corresponding build image.
-        deployment.config.image = DeploymentImage::Image {
-          image: format!("{image_name}:{version_str}"),
-        };
+        deployment.config.image = DeploymentImage::Image { image };
         let first_registry = build
           .config
           .image_registry
@@ -156,7 +193,7 @@ impl Resolve<ExecuteArgs> for Deploy {
             .image_registry_account
             .is_empty()
           {
-            registry_token(domain, &deployment.config.image_registry_account).await.with_context(
+            registry_token(domain, &deployment.config.image_registry_account, &(&deployment).into()).await.with_context(
               || format!("Failed to get git token in call to db. Stopping run. | {domain} | {}", deployment.config.image_registry_account),
             )?
           } else {
@@ -172,7 +209,7 @@ impl Resolve<ExecuteArgs> for Deploy {
           .image_registry_account
           .is_empty()
         {
-          registry_token(&domain, &deployment.config.image_registry_account).await.with_context(
+          registry_token(&domain, &deployment.config.image_registry_account, &(&deployment).into()).await.with_context(
             || format!("Failed to get git token in call to db. Stopping run. | {domain} | {}", deployment.config.image_registry_account),
           )?
         } else {
@@ -182,28 +219,81 @@ impl Resolve<ExecuteArgs> for Deploy {
       }
     };
 
+    // Reruns deploy the recorded image, pinned to the recorded digest.
+    let version = match rerun_inputs() {
+      Some(rerun) => {
+        let recorded = rerun
+          .environment
+          .images
+          .into_iter()
+          .next()
+          .context("Update being rerun has no recorded image")?;
+        let image = if recorded.digest.is_empty() {
+          recorded.image
+        } else {
+          pinned_image(&recorded.image, &recorded.digest)
+        };
+        update.push_simple_log(
+          "Rerun",
+          format!(
+            "Deploying image {image} of Update {}",
+            rerun.update_id
+          ),
+        );
+        deployment.config.image = DeploymentImage::Image { image };
+        rerun.version
+      }
+      None => version,
+    };
+
     // interpolate variables / secrets, returning the sanitizing replacers to send to
     // periphery so it may sanitize the final command for safe logging (avoids exposing secret values)
-    let secret_replacers = if !deployment.config.skip_secret_interp {
-      let VariablesAndSecrets { variables, secrets } =
-        get_variables_and_secrets().await?;
+    let (secret_replacers, interpolated) =
+      if !deployment.config.skip_secret_interp {
+        let VariablesAndSecrets {
+          mut variables,
+          mut secrets,
+        } = get_variables_and_secrets().await?;
+
+        // Runtime inputs take precedence over global variables / secrets
+        inputs.push_log(&mut update.logs);
+        variables.extend(inputs.variables);
+        secrets.extend(inputs.secrets);
+
+        let mut interpolator =
+          Interpolator::new(Some(&variables), &secrets)
+            .with_server(&server)?;
+
+        interpolator
+          .interpolate_deployment(&mut deployment)?
+          .push_logs(&mut update.logs);
+
+        let interpolated = interpolator.interpolated_names();
+        (interpolator.secret_replacers, interpolated)
+      } else {
+        Default::default()
+      };
 
-      let mut interpolator =
-        Interpolator::new(Some(&variables), &secrets);
+    apply_container_dns_defaults(
+      &server,
+      &mut deployment.config.dns,
+      &mut deployment.config.extra_hosts,
+    )
+    .await;
 
-      interpolator
-        .interpolate_deployment(&mut deployment)?
-        .push_logs(&mut update.logs);
+    update.version = version;
+    update_update(update.clone()).await?;
 
-      interpolator.secret_replacers
-    } else {
-      Default::default()
+    let deployment_id = deployment.id.clone();
+    // Build images have already been replaced with the image to deploy.
+    let image = match &deployment.config.image {
+      DeploymentImage::Image { image } => image.clone(),
+      DeploymentImage::Build { .. } => String::new(),
     };
 
-    update.version = version;
-    update_update(update.clone()).await?;
+    let periphery = periphery_client(&server).await?;
 
-    match periphery_client(&server)?
+    match periphery
       .request(api::container::Deploy {
         deployment,
         stop_signal: self.stop_signal,
@@ -222,15 +312,129 @@ impl Resolve<ExecuteArgs> for Deploy {
       }
     };
 
+    if all_logs_success(&update.logs) {
+      update.environment = Some(
+        capture_execution_environment(
+          &periphery,
+          [image],
+          interpolated,
+        )
+        .await,
+      );
+    }
+
     update_cache_for_server(&server, true).await;
 
     update.finalize();
     update_update(update.clone()).await?;
 
+    if update.success {
+      tokio::spawn(handle_post_deploy_restart(
+        deployment_id,
+        server.id,
+      ));
+    }
+
     Ok(update)
   }
 }
 
+/// Restarts the running Deployments on the same Server which are
+/// configured to `restart_after` the redeployed Deployment,
+/// including those further down the chain, in dependency order.
+#[instrument]
+async fn handle_post_deploy_restart(
+  deployment_id: String,
+  server_id: String,
+) {
+  let dependents = match find_collect(
+    &db_client().deployments,
+    doc! {
+      "config.server_id": &server_id,
+      "config.restart_after.0": { "$exists": true },
+    },
+    None,
+  )
+  .await
+  {
+    Ok(dependents) => dependents,
+    Err(e) => {
+      warn!(
+        "Failed to query Deployments to restart after {deployment_id} | {e:#}"
+      );
+      return;
+    }
+  };
+
+  // Collect all the Deployments downstream of the redeployed one.
+  let mut affected = HashSet::<&str>::new();
+  let mut stack = vec![deployment_id.as_str()];
+  while let Some(id) = stack.pop() {
+    for dependent in &dependents {
+      if dependent.id != deployment_id
+        && dependent.config.restart_after.iter().any(|d| d == id)
+        && affected.insert(&dependent.id)
+      {
+        stack.push(&dependent.id);
+      }
+    }
+  }
+
+  // A Deployment is only restarted after all of
+  // its affected dependencies have been handled.
+  let mut pending = dependents
+    .iter()
+    .filter(|d| affected.contains(d.id.as_str()))
+    .collect::<Vec<_>>();
+  let mut handled = HashSet::<&str>::new();
+
+  while !pending.is_empty() {
+    let Some(index) = pending.iter().position(|d| {
+      d.config.restart_after.iter().all(|dependency| {
+        !affected.contains(dependency.as_str())
+          || handled.contains(dependency.as_str())
+      })
+    }) else {
+      warn!(
+        "Found cycle in Deployment restart_after chain, skipping restart for {:?}",
+        pending.iter().map(|d| &d.name).collect::<Vec<_>>()
+      );
+      return;
+    };
+
+    let dependent = pending.remove(index);
+    handled.insert(&dependent.id);
+
+    let state = get_deployment_state(&dependent.id)
+      .await
+      .unwrap_or_default();
+    if !state.is_running() {
+      continue;
+    }
+
+    let req = ExecuteRequest::RestartDeployment(RestartDeployment {
+      deployment: dependent.id.clone(),
+    });
+    let user = auto_redeploy_user().to_owned();
+    let res = async {
+      let update = init_execution_update(&req, &user).await?;
+      RestartDeployment {
+        deployment: dependent.id.clone(),
+      }
+      .resolve(&ExecuteArgs { user, update })
+      .await
+    }
+    .await;
+
+    if let Err(e) = res {
+      warn!(
+        "Failed to restart Deployment {} after dependency redeploy | {:#}",
+        dependent.name, e.error
+      );
+    }
+  }
+}
+
 /// Wait this long after a pull to allow another pull through
 const PULL_TIMEOUT: i64 = 5_000;
 type ServerId = String;
@@ -285,7 +489,7 @@ pub async fn pull_deployment_inner(
             &deployment.config.image_registry_account
           };
         let token = if !account.is_empty() {
-          registry_token(domain, account).await.with_context(
+          registry_token(domain, account, &(&deployment).into()).await.with_context(
               || format!("Failed to get git token in call to db. Stopping run. | {domain} | {account}"),
             )?
         } else {
@@ -301,7 +505,7 @@ pub async fn pull_deployment_inner(
         .image_registry_account
         .is_empty()
       {
-        registry_token(&domain, &deployment.config.image_registry_account).await.with_context(
+        registry_token(&domain, &deployment.config.image_registry_account, &(&deployment).into()).await.with_context(
             || format!("Failed to get git token in call to db. Stopping run. | {domain} | {}", deployment.config.image_registry_account),
           )?
       } else {
@@ -331,7 +535,8 @@ pub async fn pull_deployment_inner(
   }
 
   let res = async {
-    let log = match periphery_client(server)?
+    let log = match periphery_client(server)
+      .await?
       .request(api::image::PullImage {
         name: image,
         account,
@@ -364,6 +569,9 @@ impl Resolve<ExecuteArgs> for PullDeployment {
     let (deployment, server) =
       setup_deployment_execution(&self.deployment, user).await?;
 
+    let _resource_lock =
+      acquire_resource_lock(&deployment, &update).await?;
+
     // get the action state for the deployment (or insert default).
     let action_state = action_states()
       .deployment
@@ -398,6 +606,9 @@ impl Resolve<ExecuteArgs> for StartDeployment {
     let (deployment, server) =
       setup_deployment_execution(&self.deployment, user).await?;
 
+    let _resource_lock =
+      acquire_resource_lock(&deployment, &update).await?;
+
     // get the action state for the deployment (or insert default).
     let action_state = action_states()
       .deployment
@@ -414,7 +625,8 @@ impl Resolve<ExecuteArgs> for StartDeployment {
     // Send update after setting action state, this way frontend gets correct state.
     update_update(update.clone()).await?;
 
-    let log = match periphery_client(&server)?
+    let log = match periphery_client(&server)
+      .await?
       .request(api::container::StartContainer {
         name: deployment.name,
       })
@@ -445,6 +657,9 @@ impl Resolve<ExecuteArgs> for RestartDeployment {
     let (deployment, server) =
       setup_deployment_execution(&self.deployment, user).await?;
 
+    let _resource_lock =
+      acquire_resource_lock(&deployment, &update).await?;
+
     // get the action state for the deployment (or insert default).
     let action_state = action_states()
       .deployment
@@ -461,7 +676,8 @@ impl Resolve<ExecuteArgs> for RestartDeployment {
     // Send update after setting action state, this way frontend gets correct state.
     update_update(update.clone()).await?;
 
-    let log = match periphery_client(&server)?
+    let log = match periphery_client(&server)
+      .await?
       .request(api::container::RestartContainer {
         name: deployment.name,
       })
@@ -494,6 +710,9 @@ impl Resolve<ExecuteArgs> for PauseDeployment {
     let (deployment, server) =
       setup_deployment_execution(&self.deployment, user).await?;
 
+    let _resource_lock =
+      acquire_resource_lock(&deployment, &update).await?;
+
     // get the action state for the deployment (or insert default).
     let action_state = action_states()
       .deployment
@@ -510,7 +729,8 @@ impl Resolve<ExecuteArgs> for PauseDeployment {
     // Send update after setting action state, this way frontend gets correct state.
     update_update(update.clone()).await?;
 
-    let log = match periphery_client(&server)?
+    let log = match periphery_client(&server)
+      .await?
       .request(api::container::PauseContainer {
         name: deployment.name,
       })
@@ -541,6 +761,9 @@ impl Resolve<ExecuteArgs> for UnpauseDeployment {
     let (deployment, server) =
       setup_deployment_execution(&self.deployment, user).await?;
 
+    let _resource_lock =
+      acquire_resource_lock(&deployment, &update).await?;
+
     // get the action state for the deployment (or insert default).
     let action_state = action_states()
       .deployment
@@ -557,7 +780,8 @@ impl Resolve<ExecuteArgs> for UnpauseDeployment {
     // Send update after setting action state, this way frontend gets correct state.
     update_update(update.clone()).await?;
 
-    let log = match periphery_client(&server)?
+    let log = match periphery_client(&server)
+      .await?
       .request(api::container::UnpauseContainer {
         name: deployment.name,
       })
@@ -590,6 +814,9 @@ impl Resolve<ExecuteArgs> for StopDeployment {
     let (deployment, server) =
       setup_deployment_execution(&self.deployment, user).await?;
 
+    let _resource_lock =
+      acquire_resource_lock(&deployment, &update).await?;
+
     // get the action state for the deployment (or insert default).
     let action_state = action_states()
       .deployment
@@ -606,7 +833,8 @@ impl Resolve<ExecuteArgs> for StopDeployment {
     // Send update after setting action state, this way frontend gets correct state.
     update_update(update.clone()).await?;
 
-    let log = match periphery_client(&server)?
+    let log = match periphery_client(&server)
+      .await?
       .request(api::container::StopContainer {
         name: deployment.name,
         signal: self
@@ -648,15 +876,18 @@ impl super::BatchExecute for BatchDestroyDeployment {
 }
 
 impl Resolve<ExecuteArgs> for BatchDestroyDeployment {
-  #[instrument(name = "BatchDestroyDeployment", skip(user), fields(user_id = user.id))]
+  #[instrument(name = "BatchDestroyDeployment", skip(self, user, update), fields(user_id = user.id, update_id = update.id))]
   async fn resolve(
     self,
-    ExecuteArgs { user, .. }: &ExecuteArgs,
-  ) -> serror::Result<BatchExecutionResponse> {
+    ExecuteArgs { user, update }: &ExecuteArgs,
+  ) -> serror::Result<Update> {
     Ok(
       super::batch_execute::<BatchDestroyDeployment>(
         &self.pattern,
+        self.parallelism,
+        self.abort_on_failure,
         user,
+        update,
       )
       .await?,
     )
@@ -672,6 +903,11 @@ impl Resolve<ExecuteArgs> for DestroyDeployment {
     let (deployment, server) =
       setup_deployment_execution(&self.deployment, user).await?;
 
+    resource::check_not_protected::<Deployment>(&deployment)?;
+
+    let _resource_lock =
+      acquire_resource_lock(&deployment, &update).await?;
+
     // get the action state for the deployment (or insert default).
     let action_state = action_states()
       .deployment
@@ -688,7 +924,8 @@ impl Resolve<ExecuteArgs> for DestroyDeployment {
     // Send update after setting action state, this way frontend gets correct state.
     update_update(update.clone()).await?;
 
-    let log = match periphery_client(&server)?
+    let log = match periphery_client(&server)
+      .await?
       .request(api::container::RemoveContainer {
         name: deployment.name,
         signal: self
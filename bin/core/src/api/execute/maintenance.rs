@@ -8,10 +8,7 @@ use komodo_client::{
   api::execute::{
     BackupCoreDatabase, ClearRepoCache, GlobalAutoUpdate,
   },
-  entities::{
-    deployment::DeploymentState, server::ServerState,
-    stack::StackState,
-  },
+  entities::{server::ServerState, stack::StackState},
 };
 use reqwest::StatusCode;
 use resolver_api::Resolve;
@@ -278,7 +275,7 @@ impl Resolve<ExecuteArgs> for GlobalAutoUpdate {
         continue;
       };
       // Only pull running deployments.
-      if !matches!(status.curr.state, DeploymentState::Running) {
+      if !status.curr.state.is_running() {
         continue;
       }
       if let Some(server) =
@@ -1,4 +1,8 @@
-use std::{pin::Pin, time::Instant};
+use std::{
+  pin::Pin,
+  sync::atomic::{AtomicBool, Ordering},
+  time::Instant,
+};
 
 use anyhow::Context;
 use axum::{
@@ -7,30 +11,39 @@ use axum::{
 use axum_extra::{TypedHeader, headers::ContentType};
 use database::mungos::by_id::find_one_by_id;
 use derive_variants::{EnumVariants, ExtractVariant};
-use formatting::format_serror;
-use futures::future::join_all;
+use formatting::{Color, colored, format_serror, muted};
+use futures::{StreamExt, stream};
 use komodo_client::{
   api::execute::*,
   entities::{
-    Operation,
+    Operation, ResourceTarget,
+    alert::{Alert, AlertData, SeverityLevel},
+    komodo_timestamp,
     permission::PermissionLevel,
-    update::{Log, Update},
+    update::{Log, Update, UpdateStatus},
     user::User,
   },
 };
+use periphery_client::audit::AuditContext;
 use resolver_api::Resolve;
 use response::JsonString;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use serror::Json;
+use tokio::sync::Mutex;
 use typeshare::typeshare;
 use uuid::Uuid;
 
 use crate::{
+  alert::send_alerts,
   auth::auth_request,
-  helpers::update::{init_execution_update, update_update},
+  helpers::{
+    cancel::run_cancellable,
+    execution_queue,
+    update::{init_execution_update, update_update},
+  },
   resource::{KomodoResource, list_full_for_user_using_pattern},
-  state::db_client,
+  state::{all_resources_cache, db_client},
 };
 
 mod action;
@@ -38,10 +51,13 @@ mod alerter;
 mod build;
 mod deployment;
 mod maintenance;
+mod plan;
 mod procedure;
 mod repo;
+mod rerun;
 mod server;
 mod stack;
+mod swarm;
 mod sync;
 
 use super::Variant;
@@ -87,6 +103,7 @@ pub enum ExecuteRequest {
   PruneDockerBuilders(PruneDockerBuilders),
   PruneBuildx(PruneBuildx),
   PruneSystem(PruneSystem),
+  RunScheduledCommand(RunScheduledCommand),
 
   // ==== STACK ====
   DeployStack(DeployStack),
@@ -103,6 +120,7 @@ pub enum ExecuteRequest {
   DestroyStack(DestroyStack),
   BatchDestroyStack(BatchDestroyStack),
   RunStackService(RunStackService),
+  ScaleStackService(ScaleStackService),
 
   // ==== DEPLOYMENT ====
   Deploy(Deploy),
@@ -116,6 +134,11 @@ pub enum ExecuteRequest {
   DestroyDeployment(DestroyDeployment),
   BatchDestroyDeployment(BatchDestroyDeployment),
 
+  // ==== SWARM SERVICE ====
+  DeploySwarmService(DeploySwarmService),
+  ScaleSwarmService(ScaleSwarmService),
+  RemoveSwarmService(RemoveSwarmService),
+
   // ==== BUILD ====
   RunBuild(RunBuild),
   BatchRunBuild(BatchRunBuild),
@@ -126,6 +149,7 @@ pub enum ExecuteRequest {
   BatchCloneRepo(BatchCloneRepo),
   PullRepo(PullRepo),
   BatchPullRepo(BatchPullRepo),
+  RunRepoPipeline(RunRepoPipeline),
   BuildRepo(BuildRepo),
   BatchBuildRepo(BatchBuildRepo),
   CancelRepoBuild(CancelRepoBuild),
@@ -145,6 +169,12 @@ pub enum ExecuteRequest {
   // ==== SYNC ====
   RunSync(RunSync),
 
+  // ==== PLAN ====
+  Plan(Plan),
+
+  // ==== RERUN ====
+  RerunUpdate(RerunUpdate),
+
   // ==== MAINTENANCE ====
   ClearRepoCache(ClearRepoCache),
   BackupCoreDatabase(BackupCoreDatabase),
@@ -174,30 +204,18 @@ async fn handler(
   Extension(user): Extension<User>,
   Json(request): Json<ExecuteRequest>,
 ) -> serror::Result<(TypedHeader<ContentType>, String)> {
-  let res = match inner_handler(request, user).await? {
-    ExecutionResult::Single(update) => serde_json::to_string(&update)
-      .context("Failed to serialize Update")?,
-    ExecutionResult::Batch(res) => res,
-  };
+  let update = inner_handler(request, user).await?;
+  let res = serde_json::to_string(&update)
+    .context("Failed to serialize Update")?;
   Ok((TypedHeader(ContentType::json()), res))
 }
 
-#[typeshare(serialized_as = "Update")]
-type BoxUpdate = Box<Update>;
-
-pub enum ExecutionResult {
-  Single(BoxUpdate),
-  /// The batch contents will be pre serialized here
-  Batch(String),
-}
-
 pub fn inner_handler(
   request: ExecuteRequest,
   user: User,
 ) -> Pin<
   Box<
-    dyn std::future::Future<Output = anyhow::Result<ExecutionResult>>
-      + Send,
+    dyn std::future::Future<Output = anyhow::Result<Update>> + Send,
   >,
 > {
   Box::pin(async move {
@@ -210,21 +228,17 @@ pub fn inner_handler(
 
     let update = init_execution_update(&request, &user).await?;
 
-    // This will be the case for the Batch exections,
-    // they don't have their own updates.
-    // The batch calls also call "inner_handler" themselves,
-    // and in their case will spawn tasks, so that isn't necessary
-    // here either.
-    if update.operation == Operation::None {
-      return Ok(ExecutionResult::Batch(
-        task(req_id, request, user, update).await?,
-      ));
-    }
-
     // Spawn a task for the execution which continues
     // running after this method returns.
-    let handle =
-      tokio::spawn(task(req_id, request, user, update.clone()));
+    // Periphery requests made by the execution are audited
+    // with the user and Update.
+    let audit = AuditContext::new(&user.username, &update.id);
+    let handle = tokio::spawn(audit.scope(task(
+      req_id,
+      request,
+      user,
+      update.clone(),
+    )));
 
     // Spawns another task to monitor the first for failures,
     // and add the log to Update about it (which primary task can't do because it errored out)
@@ -255,7 +269,9 @@ pub fn inner_handler(
               .context("no update exists with given id")?;
           update.logs.push(log);
           update.finalize();
-          update_update(update).await
+          update_update(update.clone()).await?;
+          alert_if_failed(&update).await;
+          anyhow::Ok(())
         }
         .await;
 
@@ -267,7 +283,7 @@ pub fn inner_handler(
       }
     });
 
-    Ok(ExecutionResult::Single(update.into()))
+    Ok(update)
   })
 }
 
@@ -289,34 +305,103 @@ async fn task(
   info!("/execute request {req_id} | user: {}", user.username);
   let timer = Instant::now();
 
-  let res = match request.resolve(&ExecuteArgs { user, update }).await
-  {
-    Err(e) => Err(e.error),
-    Ok(JsonString::Err(e)) => Err(
-      anyhow::Error::from(e).context("failed to serialize response"),
-    ),
-    Ok(JsonString::Ok(res)) => Ok(res),
-  };
+  let variant = format!("{:?}", request.extract_variant());
+  let update_id = update.id.clone();
+  let res = run_cancellable(&variant, &update_id, async {
+    match request.resolve(&ExecuteArgs { user, update }).await {
+      Err(e) => Err(e.error),
+      Ok(JsonString::Err(e)) => Err(
+        anyhow::Error::from(e)
+          .context("failed to serialize response"),
+      ),
+      Ok(JsonString::Ok(res)) => Ok(res),
+    }
+  })
+  .await;
 
   if let Err(e) = &res {
     warn!("/execute request {req_id} error: {e:#}");
   }
 
+  // On error, the monitoring task finalizes the Update and alerts.
+  if res.is_ok() && !update_id.is_empty() {
+    match find_one_by_id(&db_client().updates, &update_id).await {
+      Ok(Some(update)) => alert_if_failed(&update).await,
+      Ok(None) => {}
+      Err(e) => {
+        warn!("Failed to get Update to check for failure | {e:#}")
+      }
+    }
+  }
+
   let elapsed = timer.elapsed();
   debug!("/execute request {req_id} | resolve time: {elapsed:?}");
 
   res
 }
 
+/// Sends an UpdateFailed alert if the execution failed,
+/// unless the execution sends its own failure alert.
+async fn alert_if_failed(update: &Update) {
+  if update.success || update.status != UpdateStatus::Complete {
+    return;
+  }
+  if matches!(
+    update.operation,
+    Operation::RunBuild
+      | Operation::BuildRepo
+      | Operation::RunProcedure
+      | Operation::RunAction
+  ) || update.operation.as_ref().starts_with("Batch")
+  {
+    // Batch executions fail along with the individual executions.
+    return;
+  }
+  if let ResourceTarget::System(_) = &update.target {
+    return;
+  }
+  let (resource_type, id) = update.target.extract_variant_id();
+  let name = all_resources_cache()
+    .load()
+    .resource_meta(&update.target)
+    .map(|meta| meta.name.clone())
+    .unwrap_or_default();
+  let ts = komodo_timestamp();
+  let alert = Alert {
+    id: Default::default(),
+    ts,
+    resolved: true,
+    level: SeverityLevel::Warning,
+    target: update.target.clone(),
+    data: AlertData::UpdateFailed {
+      resource_type,
+      id: id.clone(),
+      name,
+      operation: update.operation,
+      update_id: update.id.clone(),
+    },
+    resolved_ts: Some(ts),
+    notifications: Default::default(),
+  };
+  send_alerts(&[alert]).await
+}
+
 trait BatchExecute {
   type Resource: KomodoResource;
   fn single_request(name: String) -> ExecuteRequest;
 }
 
+/// Runs the execution for each resource matching the pattern,
+/// at most `parallelism` at once. The batch Update tracks the
+/// progress, and links the Update of each execution.
 async fn batch_execute<E: BatchExecute>(
   pattern: &str,
+  parallelism: Option<i32>,
+  abort_on_failure: Option<bool>,
   user: &User,
-) -> anyhow::Result<BatchExecutionResponse> {
+  update: &Update,
+) -> anyhow::Result<Update> {
+  let mut update = update.clone();
   let resources = list_full_for_user_using_pattern::<E::Resource>(
     pattern,
     Default::default(),
@@ -325,23 +410,166 @@ async fn batch_execute<E: BatchExecute>(
     &[],
   )
   .await?;
-  let futures = resources.into_iter().map(|resource| {
-    let user = user.clone();
-    async move {
-      inner_handler(E::single_request(resource.name.clone()), user)
-        .await
-        .map(|r| {
-          let ExecutionResult::Single(update) = r else {
-            unreachable!()
+  let total = resources.len();
+  let parallelism = match parallelism {
+    Some(parallelism) if parallelism > 0 => parallelism as usize,
+    _ => total.max(1),
+  };
+  let abort_on_failure = abort_on_failure.unwrap_or_default();
+
+  update.push_simple_log(
+    "Batch Execution",
+    format!(
+      "{}: {pattern}\n{}: {parallelism}\n{}: {abort_on_failure}\n\nMatched {total} {}: {}",
+      muted("pattern"),
+      muted("parallelism"),
+      muted("abort on failure"),
+      E::Resource::resource_type(),
+      resources
+        .iter()
+        .map(|resource| resource.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+    ),
+  );
+  let progress = BatchProgress {
+    total,
+    ..Default::default()
+  };
+  update.push_simple_log(BatchProgress::STAGE, progress.log());
+  update_update(update.clone()).await?;
+
+  let state = Mutex::new((update, progress));
+  let failed = AtomicBool::new(false);
+
+  stream::iter(resources)
+    .map(|resource| {
+      let (state, failed) = (&state, &failed);
+      async move {
+        let result =
+          if abort_on_failure && failed.load(Ordering::Relaxed) {
+            BatchItemResult::Aborted
+          } else {
+            let res = execution_queue::run_execution(
+              E::single_request(resource.name.clone()),
+              user.clone(),
+              "Batch Execution",
+            )
+            .await;
+            match res {
+              Ok(update) if update.success => {
+                BatchItemResult::Complete(update.id)
+              }
+              Ok(update) => {
+                failed.store(true, Ordering::Relaxed);
+                BatchItemResult::Failed(update.id, None)
+              }
+              Err(e) => {
+                failed.store(true, Ordering::Relaxed);
+                BatchItemResult::Failed(String::new(), Some(e))
+              }
+            }
           };
-          update
-        })
-        .map_err(|e| BatchExecutionResponseItemErr {
-          name: resource.name,
-          error: e.into(),
-        })
-        .into()
+        let mut state = state.lock().await;
+        let (update, progress) = &mut *state;
+        progress.record(&resource.name, result, update);
+        if let Err(e) = update_update(update.clone()).await {
+          warn!("Failed to update batch execution progress | {e:#}");
+        }
+      }
+    })
+    .buffer_unordered(parallelism)
+    .collect::<Vec<_>>()
+    .await;
+
+  let (mut update, progress) = state.into_inner();
+  if progress.failed > 0 || progress.aborted > 0 {
+    update.push_error_log(
+      "Batch Execution",
+      format!(
+        "{} of {total} executions failed, {} aborted",
+        progress.failed, progress.aborted
+      ),
+    );
+  }
+  update.finalize();
+  update_update(update.clone()).await?;
+  Ok(update)
+}
+
+enum BatchItemResult {
+  /// Contains the Update id
+  Complete(String),
+  /// Contains the Update id, if it was created,
+  /// and the error if the execution couldn't run.
+  Failed(String, Option<anyhow::Error>),
+  /// Not run after another execution failed.
+  Aborted,
+}
+
+#[derive(Default)]
+struct BatchProgress {
+  total: usize,
+  complete: usize,
+  failed: usize,
+  aborted: usize,
+  /// A line for each finished execution
+  lines: Vec<String>,
+}
+
+impl BatchProgress {
+  const STAGE: &str = "Progress";
+
+  fn record(
+    &mut self,
+    name: &str,
+    result: BatchItemResult,
+    update: &mut Update,
+  ) {
+    let line = match result {
+      BatchItemResult::Complete(update_id) => {
+        self.complete += 1;
+        format!(
+          "{}: {name} | update: {update_id}",
+          colored("Complete", Color::Green)
+        )
+      }
+      BatchItemResult::Failed(update_id, error) => {
+        self.failed += 1;
+        if let Some(e) = error {
+          update.push_error_log(
+            &format!("Batch Execution: {name}"),
+            format_serror(&e.into()),
+          );
+        }
+        format!(
+          "{}: {name} | update: {update_id}",
+          colored("Failed", Color::Red)
+        )
+      }
+      BatchItemResult::Aborted => {
+        self.aborted += 1;
+        format!("{}: {name}", muted("Aborted"))
+      }
+    };
+    self.lines.push(line);
+    if let Some(log) =
+      update.logs.iter_mut().find(|log| log.stage == Self::STAGE)
+    {
+      log.stdout = self.log();
+    }
+  }
+
+  fn log(&self) -> String {
+    let finished = self.complete + self.failed + self.aborted;
+    let mut log = format!(
+      "{finished} of {} finished | {} complete | {} failed | {} aborted",
+      self.total, self.complete, self.failed, self.aborted
+    );
+    for line in &self.lines {
+      log.push('\n');
+      log.push_str(line);
     }
-  });
-  Ok(join_all(futures).await)
+    log
+  }
 }
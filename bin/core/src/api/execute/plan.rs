@@ -0,0 +1,134 @@
+use formatting::{Color, bold, colored, muted};
+use komodo_client::{
+  api::execute::{Plan, PlanExecution},
+  entities::{
+    plan::{ExecutionPlan, StackPlan, SyncPlan},
+    sync::DiffData,
+    update::Update,
+  },
+};
+use resolver_api::Resolve;
+
+use crate::helpers::update::update_update;
+
+use super::{ExecuteArgs, stack::plan_deploy_stack, sync::plan_sync};
+
+impl Resolve<ExecuteArgs> for Plan {
+  #[instrument(name = "Plan", skip(user, update), fields(user_id = user.id, update_id = update.id))]
+  async fn resolve(
+    self,
+    ExecuteArgs { user, update }: &ExecuteArgs,
+  ) -> serror::Result<Update> {
+    let mut update = update.clone();
+
+    // Nothing is applied, so the action state isn't set.
+    update_update(update.clone()).await?;
+
+    let plan = match self.execution {
+      PlanExecution::RunSync(request) => ExecutionPlan::RunSync(
+        plan_sync(request, user, &mut update).await?,
+      ),
+      PlanExecution::DeployStack(request) => {
+        ExecutionPlan::DeployStack(
+          plan_deploy_stack(request, user).await?,
+        )
+      }
+    };
+
+    let log = match &plan {
+      ExecutionPlan::RunSync(plan) => sync_plan_log(plan),
+      ExecutionPlan::DeployStack(plan) => stack_plan_log(plan),
+    };
+    update.push_simple_log("Plan", log);
+    update.plan = Some(plan);
+
+    update.finalize();
+    update_update(update.clone()).await?;
+
+    Ok(update)
+  }
+}
+
+fn sync_plan_log(plan: &SyncPlan) -> String {
+  let mut lines = Vec::new();
+  for diff in &plan.resource_updates {
+    let (variant, id) = diff.target.extract_variant_id();
+    let name = match &diff.data {
+      DiffData::Create { name, .. } => name.clone(),
+      DiffData::Update { current, .. }
+      | DiffData::Delete { current } => {
+        toml_resource_name(current).unwrap_or_else(|| id.clone())
+      }
+    };
+    lines.push(diff_line(&diff.data, &format!("{variant} {name}")));
+  }
+  for diff in &plan.variable_updates {
+    lines.push(diff_line(diff, "Variable"));
+  }
+  for diff in &plan.user_group_updates {
+    lines.push(diff_line(diff, "User Group"));
+  }
+  if plan.deploy_updates.to_deploy > 0 {
+    lines.push(format!(
+      "{} {} resources",
+      colored("deploy", Color::Blue),
+      plan.deploy_updates.to_deploy
+    ));
+  }
+  summary(lines)
+}
+
+fn stack_plan_log(plan: &StackPlan) -> String {
+  let mut lines = Vec::new();
+  if plan.destroy_first {
+    lines.push(format!(
+      "{} existing containers before deploying",
+      colored("destroy", Color::Red)
+    ));
+  }
+  for diff in &plan.files {
+    lines.push(diff_line(&diff.data, &format!("file {}", diff.path)));
+  }
+  for diff in &plan.services {
+    lines.push(diff_line(
+      &diff.data,
+      &format!("service {}", diff.service),
+    ));
+  }
+  summary(lines)
+}
+
+fn diff_line(data: &DiffData, item: &str) -> String {
+  let action = match data {
+    DiffData::Create { .. } => colored("create", Color::Green),
+    DiffData::Update { .. } => colored("update", Color::Blue),
+    DiffData::Delete { .. } => colored("delete", Color::Red),
+  };
+  format!("{action} {}", bold(item))
+}
+
+/// The resource TOML looks like `[[stack]]\nname = "..."`.
+fn toml_resource_name(toml: &str) -> Option<String> {
+  let table = toml::from_str::<toml::Table>(toml).ok()?;
+  let (_, resources) = table.into_iter().next()?;
+  resources
+    .as_array()?
+    .first()?
+    .get("name")?
+    .as_str()
+    .map(str::to_string)
+}
+
+fn summary(lines: Vec<String>) -> String {
+  if lines.is_empty() {
+    return format!(
+      "{}. the execution would not change anything.",
+      colored("no changes", Color::Green)
+    );
+  }
+  format!(
+    "{}\n\n{}",
+    muted(format!("{} changes:", lines.len())),
+    lines.join("\n")
+  )
+}
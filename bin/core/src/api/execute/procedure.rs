@@ -1,13 +1,11 @@
-use std::pin::Pin;
+use std::{collections::HashMap, pin::Pin};
 
 use database::mungos::{
   by_id::update_one_by_id, mongodb::bson::to_document,
 };
 use formatting::{Color, bold, colored, format_serror, muted};
 use komodo_client::{
-  api::execute::{
-    BatchExecutionResponse, BatchRunProcedure, RunProcedure,
-  },
+  api::execute::{BatchRunProcedure, RunProcedure},
   entities::{
     alert::{Alert, AlertData, SeverityLevel},
     komodo_timestamp,
@@ -22,7 +20,11 @@ use tokio::sync::Mutex;
 
 use crate::{
   alert::send_alerts,
-  helpers::{procedure::execute_procedure, update::update_update},
+  helpers::{
+    procedure::execute_procedure,
+    resource_lock::acquire_resource_lock,
+    runtime_inputs::RuntimeInputs, update::update_update,
+  },
   permission::get_check_permissions,
   resource::refresh_procedure_state_cache,
   state::{action_states, db_client},
@@ -33,19 +35,28 @@ use super::{ExecuteArgs, ExecuteRequest};
 impl super::BatchExecute for BatchRunProcedure {
   type Resource = Procedure;
   fn single_request(procedure: String) -> ExecuteRequest {
-    ExecuteRequest::RunProcedure(RunProcedure { procedure })
+    ExecuteRequest::RunProcedure(RunProcedure {
+      procedure,
+      inputs: None,
+    })
   }
 }
 
 impl Resolve<ExecuteArgs> for BatchRunProcedure {
-  #[instrument(name = "BatchRunProcedure", skip(user), fields(user_id = user.id))]
+  #[instrument(name = "BatchRunProcedure", skip(self, user, update), fields(user_id = user.id, update_id = update.id))]
   async fn resolve(
     self,
-    ExecuteArgs { user, .. }: &ExecuteArgs,
-  ) -> serror::Result<BatchExecutionResponse> {
+    ExecuteArgs { user, update }: &ExecuteArgs,
+  ) -> serror::Result<Update> {
     Ok(
-      super::batch_execute::<BatchRunProcedure>(&self.pattern, user)
-        .await?,
+      super::batch_execute::<BatchRunProcedure>(
+        &self.pattern,
+        self.parallelism,
+        self.abort_on_failure,
+        user,
+        update,
+      )
+      .await?,
     )
   }
 }
@@ -57,14 +68,20 @@ impl Resolve<ExecuteArgs> for RunProcedure {
     ExecuteArgs { user, update }: &ExecuteArgs,
   ) -> serror::Result<Update> {
     Ok(
-      resolve_inner(self.procedure, user.clone(), update.clone())
-        .await?,
+      resolve_inner(
+        self.procedure,
+        self.inputs,
+        user.clone(),
+        update.clone(),
+      )
+      .await?,
     )
   }
 }
 
 fn resolve_inner(
   procedure: String,
+  inputs: Option<HashMap<String, String>>,
   user: User,
   mut update: Update,
 ) -> Pin<
@@ -80,6 +97,9 @@ fn resolve_inner(
     )
     .await?;
 
+    let inputs =
+      RuntimeInputs::resolve(&procedure.config.inputs, inputs)?;
+
     // Need to push the initial log, as execute_procedure
     // assumes first log is already created
     // and will panic otherwise.
@@ -91,6 +111,10 @@ fn resolve_inner(
         bold(&procedure.name)
       ),
     );
+    inputs.push_log(&mut update.logs);
+
+    let _resource_lock =
+      acquire_resource_lock(&procedure, &update).await?;
 
     // get the action state for the procedure (or insert default).
     let action_state = action_states()
@@ -107,7 +131,7 @@ fn resolve_inner(
 
     let update = Mutex::new(update);
 
-    let res = execute_procedure(&procedure, &update).await;
+    let res = execute_procedure(&procedure, &inputs, &update).await;
 
     let mut update = update.into_inner();
 
@@ -160,6 +184,7 @@ fn resolve_inner(
             id: procedure.id,
             name: procedure.name,
           },
+          notifications: Default::default(),
         };
         send_alerts(&[alert]).await
       });
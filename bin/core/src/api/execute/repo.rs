@@ -14,6 +14,7 @@ use komodo_client::{
   api::{execute::*, write::RefreshRepoCache},
   entities::{
     alert::{Alert, AlertData, SeverityLevel},
+    all_logs_success,
     builder::{Builder, BuilderConfig},
     komodo_timestamp,
     permission::PermissionLevel,
@@ -24,16 +25,19 @@ use komodo_client::{
 };
 use periphery_client::api;
 use resolver_api::Resolve;
-use tokio_util::sync::CancellationToken;
 
 use crate::{
   alert::send_alerts,
   api::write::WriteArgs,
   helpers::{
     builder::{cleanup_builder_instance, get_builder_periphery},
+    cancel::execution_cancel_token,
     channel::repo_cancel_channel,
-    git_token, periphery_client,
+    git_token,
+    junit::add_junit_report,
+    periphery_client,
     query::{VariablesAndSecrets, get_variables_and_secrets},
+    resource_lock::acquire_resource_lock,
     update::update_update,
   },
   permission::get_check_permissions,
@@ -51,14 +55,20 @@ impl super::BatchExecute for BatchCloneRepo {
 }
 
 impl Resolve<ExecuteArgs> for BatchCloneRepo {
-  #[instrument(name = "BatchCloneRepo", skip( user), fields(user_id = user.id))]
+  #[instrument(name = "BatchCloneRepo", skip(self, user, update), fields(user_id = user.id, update_id = update.id))]
   async fn resolve(
     self,
     ExecuteArgs { user, update }: &ExecuteArgs,
-  ) -> serror::Result<BatchExecutionResponse> {
+  ) -> serror::Result<Update> {
     Ok(
-      super::batch_execute::<BatchCloneRepo>(&self.pattern, user)
-        .await?,
+      super::batch_execute::<BatchCloneRepo>(
+        &self.pattern,
+        self.parallelism,
+        self.abort_on_failure,
+        user,
+        update,
+      )
+      .await?,
     )
   }
 }
@@ -76,6 +86,9 @@ impl Resolve<ExecuteArgs> for CloneRepo {
     )
     .await?;
 
+    let _resource_lock =
+      acquire_resource_lock(&repo, &update).await?;
+
     // get the action state for the repo (or insert default).
     let action_state =
       action_states().repo.get_or_insert_default(&repo.id).await;
@@ -95,6 +108,7 @@ impl Resolve<ExecuteArgs> for CloneRepo {
     let git_token = git_token(
       &repo.config.git_provider,
       &repo.config.git_account,
+      &(&repo).into(),
       |https| repo.config.git_https = https,
     )
     .await
@@ -105,7 +119,7 @@ impl Resolve<ExecuteArgs> for CloneRepo {
     let server =
       resource::get::<Server>(&repo.config.server_id).await?;
 
-    let periphery = periphery_client(&server)?;
+    let periphery = periphery_client(&server).await?;
 
     // interpolate variables / secrets, returning the sanitizing replacers to send to
     // periphery so it may sanitize the final command for safe logging (avoids exposing secret values)
@@ -165,14 +179,20 @@ impl super::BatchExecute for BatchPullRepo {
 }
 
 impl Resolve<ExecuteArgs> for BatchPullRepo {
-  #[instrument(name = "BatchPullRepo", skip(user), fields(user_id = user.id))]
+  #[instrument(name = "BatchPullRepo", skip(self, user, update), fields(user_id = user.id, update_id = update.id))]
   async fn resolve(
     self,
-    ExecuteArgs { user, .. }: &ExecuteArgs,
-  ) -> serror::Result<BatchExecutionResponse> {
+    ExecuteArgs { user, update }: &ExecuteArgs,
+  ) -> serror::Result<Update> {
     Ok(
-      super::batch_execute::<BatchPullRepo>(&self.pattern, user)
-        .await?,
+      super::batch_execute::<BatchPullRepo>(
+        &self.pattern,
+        self.parallelism,
+        self.abort_on_failure,
+        user,
+        update,
+      )
+      .await?,
     )
   }
 }
@@ -190,6 +210,9 @@ impl Resolve<ExecuteArgs> for PullRepo {
     )
     .await?;
 
+    let _resource_lock =
+      acquire_resource_lock(&repo, &update).await?;
+
     // get the action state for the repo (or insert default).
     let action_state =
       action_states().repo.get_or_insert_default(&repo.id).await;
@@ -210,6 +233,7 @@ impl Resolve<ExecuteArgs> for PullRepo {
     let git_token = git_token(
       &repo.config.git_provider,
       &repo.config.git_account,
+      &(&repo).into(),
       |https| repo.config.git_https = https,
     )
     .await
@@ -220,7 +244,7 @@ impl Resolve<ExecuteArgs> for PullRepo {
     let server =
       resource::get::<Server>(&repo.config.server_id).await?;
 
-    let periphery = periphery_client(&server)?;
+    let periphery = periphery_client(&server).await?;
 
     // interpolate variables / secrets, returning the sanitizing replacers to send to
     // periphery so it may sanitize the final command for safe logging (avoids exposing secret values)
@@ -275,6 +299,158 @@ impl Resolve<ExecuteArgs> for PullRepo {
   }
 }
 
+impl Resolve<ExecuteArgs> for RunRepoPipeline {
+  #[instrument(name = "RunRepoPipeline", skip(user, update), fields(user_id = user.id, update_id = update.id))]
+  async fn resolve(
+    self,
+    ExecuteArgs { user, update }: &ExecuteArgs,
+  ) -> serror::Result<Update> {
+    let mut repo = get_check_permissions::<Repo>(
+      &self.repo,
+      user,
+      PermissionLevel::Execute.into(),
+    )
+    .await?;
+
+    let _resource_lock =
+      acquire_resource_lock(&repo, &update).await?;
+
+    // get the action state for the repo (or insert default).
+    let action_state =
+      action_states().repo.get_or_insert_default(&repo.id).await;
+
+    // This will set action state back to default when dropped.
+    // Will also check to ensure repo not already busy before updating.
+    let _action_guard =
+      action_state.update(|state| state.running_pipeline = true)?;
+
+    let mut update = update.clone();
+
+    update_update(update.clone()).await?;
+
+    if repo.config.server_id.is_empty() {
+      return Err(anyhow!("repo has no server attached").into());
+    }
+
+    if repo.config.pipeline.is_empty() {
+      return Err(anyhow!("repo has no pipeline steps").into());
+    }
+
+    let git_token = git_token(
+      &repo.config.git_provider,
+      &repo.config.git_account,
+      &(&repo).into(),
+      |https| repo.config.git_https = https,
+    )
+    .await
+    .with_context(
+      || format!("Failed to get git token in call to db. This is a database error, not a token exisitence error. Stopping run. | {} | {}", repo.config.git_provider, repo.config.git_account),
+    )?;
+
+    let server =
+      resource::get::<Server>(&repo.config.server_id).await?;
+
+    let periphery = periphery_client(&server).await?;
+
+    // interpolate variables / secrets, returning the sanitizing replacers to send to
+    // periphery so it may sanitize the final command for safe logging (avoids exposing secret values)
+    let secret_replacers = interpolate(&mut repo, &mut update)
+      .await?
+      .into_iter()
+      .collect::<Vec<_>>();
+
+    let path = match periphery
+      .request(api::git::PullOrCloneRepo {
+        args: (&repo).into(),
+        git_token,
+        environment: repo.config.env_vars()?,
+        env_file_path: repo.config.env_file_path.clone(),
+        on_clone: repo.config.on_clone.into(),
+        on_pull: repo.config.on_pull.into(),
+        skip_secret_interp: repo.config.skip_secret_interp,
+        replacers: secret_replacers.clone(),
+      })
+      .await
+    {
+      Ok(res) => {
+        update.commit_hash = res.res.commit_hash.unwrap_or_default();
+        update.logs.extend(res.res.logs);
+        Some(res.res.path)
+      }
+      Err(e) => {
+        update.push_error_log(
+          "Pull Repo",
+          format_serror(&e.context("Failed to pull repo").into()),
+        );
+        None
+      }
+    };
+
+    if let Some(path) = path
+      && all_logs_success(&update.logs)
+    {
+      update_last_pulled_time(&repo.name).await;
+      match periphery
+        .request(api::git::RunRepoPipeline {
+          name: repo.name.clone(),
+          path: path.clone(),
+          steps: repo.config.pipeline.clone(),
+          env_file_path: repo.config.env_file_path,
+          replacers: secret_replacers,
+        })
+        .await
+      {
+        Ok(logs) => update.logs.extend(logs),
+        Err(e) => update.push_error_log(
+          "Run Pipeline",
+          format_serror(
+            &e.context("Failed to run repo pipeline").into(),
+          ),
+        ),
+      };
+      // Only collect the reports of steps which ran
+      for step in repo.config.pipeline {
+        if step.junit_report.is_empty()
+          || !update.logs.iter().any(|log| log.stage == step.name)
+        {
+          continue;
+        }
+        add_junit_report(
+          &periphery,
+          step.name,
+          api::build::GetJunitReport {
+            path: path
+              .join(&step.path)
+              .join(&step.junit_report)
+              .display()
+              .to_string(),
+            name: Default::default(),
+            linked_repo: false,
+          },
+          &mut update,
+        )
+        .await;
+      }
+    }
+
+    update.finalize();
+
+    if let Err(e) = (RefreshRepoCache { repo: repo.id })
+      .resolve(&WriteArgs { user: user.clone() })
+      .await
+      .map_err(|e| e.error)
+      .context("Failed to refresh repo cache")
+    {
+      update.push_error_log(
+        "Refresh Repo cache",
+        format_serror(&e.into()),
+      );
+    };
+
+    handle_repo_update_return(update).await
+  }
+}
+
 #[instrument(skip_all, fields(update_id = update.id))]
 async fn handle_repo_update_return(
   update: Update,
@@ -321,14 +497,20 @@ impl super::BatchExecute for BatchBuildRepo {
 }
 
 impl Resolve<ExecuteArgs> for BatchBuildRepo {
-  #[instrument(name = "BatchBuildRepo", skip(user), fields(user_id = user.id))]
+  #[instrument(name = "BatchBuildRepo", skip(self, user, update), fields(user_id = user.id, update_id = update.id))]
   async fn resolve(
     self,
-    ExecuteArgs { user, .. }: &ExecuteArgs,
-  ) -> serror::Result<BatchExecutionResponse> {
+    ExecuteArgs { user, update }: &ExecuteArgs,
+  ) -> serror::Result<Update> {
     Ok(
-      super::batch_execute::<BatchBuildRepo>(&self.pattern, user)
-        .await?,
+      super::batch_execute::<BatchBuildRepo>(
+        &self.pattern,
+        self.parallelism,
+        self.abort_on_failure,
+        user,
+        update,
+      )
+      .await?,
     )
   }
 }
@@ -349,6 +531,8 @@ impl Resolve<ExecuteArgs> for BuildRepo {
     if repo.config.builder_id.is_empty() {
       return Err(anyhow!("Must attach builder to BuildRepo").into());
     }
+    let _resource_lock =
+      acquire_resource_lock(&repo, &update).await?;
 
     // get the action state for the repo (or insert default).
     let action_state =
@@ -365,6 +549,7 @@ impl Resolve<ExecuteArgs> for BuildRepo {
     let git_token = git_token(
       &repo.config.git_provider,
       &repo.config.git_account,
+      &(&repo).into(),
       |https| repo.config.git_https = https,
     )
     .await
@@ -372,7 +557,8 @@ impl Resolve<ExecuteArgs> for BuildRepo {
       || format!("Failed to get git token in call to db. This is a database error, not a token exisitence error. Stopping run. | {} | {}", repo.config.git_provider, repo.config.git_account),
     )?;
 
-    let cancel = CancellationToken::new();
+    // Also cancelled by CancelUpdate.
+    let cancel = execution_cancel_token().child_token();
     let cancel_clone = cancel.clone();
     let mut cancel_recv =
       repo_cancel_channel().receiver.resubscribe();
@@ -544,6 +730,7 @@ impl Resolve<ExecuteArgs> for BuildRepo {
             id: repo.id,
             name: repo.name,
           },
+          notifications: Default::default(),
         };
         send_alerts(&[alert]).await
       });
@@ -591,6 +778,7 @@ async fn handle_builder_early_return(
           id: repo_id,
           name: repo_name,
         },
+        notifications: Default::default(),
       };
       send_alerts(&[alert]).await
     });
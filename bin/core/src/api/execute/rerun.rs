@@ -0,0 +1,79 @@
+use anyhow::anyhow;
+use komodo_client::{
+  api::execute::{Deploy, DeployStack, RerunUpdate, RunBuild},
+  entities::{Operation, ResourceTarget, update::Update},
+};
+use resolver_api::Resolve;
+
+use crate::helpers::rerun::{
+  RerunInputs, get_rerun_update, run_rerun,
+};
+
+use super::ExecuteArgs;
+
+impl Resolve<ExecuteArgs> for RerunUpdate {
+  #[instrument(name = "RerunUpdate", skip(user, update), fields(user_id = user.id, update_id = update.id))]
+  async fn resolve(
+    self,
+    ExecuteArgs { user, update }: &ExecuteArgs,
+  ) -> serror::Result<Update> {
+    let original = get_rerun_update(&self.update).await?;
+
+    let mut update = update.clone();
+    update.push_simple_log(
+      "Rerun",
+      format!(
+        "Rerunning {} of Update {}",
+        original.operation, original.id
+      ),
+    );
+    let args = ExecuteArgs {
+      user: user.clone(),
+      update,
+    };
+
+    let environment = original.environment.unwrap_or_default();
+    let services = environment.services.clone();
+    let inputs = RerunInputs {
+      update_id: original.id,
+      commit_hash: original.commit_hash,
+      version: original.version,
+      environment,
+    };
+
+    // Permissions are checked by the execution as usual.
+    match (original.operation, original.target) {
+      (Operation::Deploy, ResourceTarget::Deployment(deployment)) => {
+        run_rerun(
+          inputs,
+          Deploy {
+            deployment,
+            stop_signal: None,
+            stop_time: None,
+            inputs: self.inputs,
+          }
+          .resolve(&args),
+        )
+        .await
+      }
+      (Operation::DeployStack, ResourceTarget::Stack(stack)) => {
+        run_rerun(
+          inputs,
+          DeployStack {
+            stack,
+            services,
+            stop_time: None,
+          }
+          .resolve(&args),
+        )
+        .await
+      }
+      (Operation::RunBuild, ResourceTarget::Build(build)) => {
+        run_rerun(inputs, RunBuild { build }.resolve(&args)).await
+      }
+      (operation, target) => {
+        Err(anyhow!("Cannot rerun {operation} on {target:?}").into())
+      }
+    }
+  }
+}
@@ -3,7 +3,8 @@ use formatting::format_serror;
 use komodo_client::{
   api::execute::*,
   entities::{
-    all_logs_success,
+    alert::{Alert, AlertData, SeverityLevel},
+    all_logs_success, komodo_timestamp,
     permission::PermissionLevel,
     server::Server,
     update::{Log, Update},
@@ -13,10 +14,12 @@ use periphery_client::api;
 use resolver_api::Resolve;
 
 use crate::{
+  alert::send_alerts,
   helpers::{periphery_client, update::update_update},
   monitor::update_cache_for_server,
   permission::get_check_permissions,
-  state::action_states,
+  resource::check_not_protected,
+  state::{action_states, db_client},
 };
 
 use super::ExecuteArgs;
@@ -50,7 +53,7 @@ impl Resolve<ExecuteArgs> for StartContainer {
     // Send update after setting action state, this way frontend gets correct state.
     update_update(update.clone()).await?;
 
-    let periphery = periphery_client(&server)?;
+    let periphery = periphery_client(&server).await?;
 
     let log = match periphery
       .request(api::container::StartContainer {
@@ -104,7 +107,7 @@ impl Resolve<ExecuteArgs> for RestartContainer {
     // Send update after setting action state, this way frontend gets correct state.
     update_update(update.clone()).await?;
 
-    let periphery = periphery_client(&server)?;
+    let periphery = periphery_client(&server).await?;
 
     let log = match periphery
       .request(api::container::RestartContainer {
@@ -160,7 +163,7 @@ impl Resolve<ExecuteArgs> for PauseContainer {
     // Send update after setting action state, this way frontend gets correct state.
     update_update(update.clone()).await?;
 
-    let periphery = periphery_client(&server)?;
+    let periphery = periphery_client(&server).await?;
 
     let log = match periphery
       .request(api::container::PauseContainer {
@@ -214,7 +217,7 @@ impl Resolve<ExecuteArgs> for UnpauseContainer {
     // Send update after setting action state, this way frontend gets correct state.
     update_update(update.clone()).await?;
 
-    let periphery = periphery_client(&server)?;
+    let periphery = periphery_client(&server).await?;
 
     let log = match periphery
       .request(api::container::UnpauseContainer {
@@ -270,7 +273,7 @@ impl Resolve<ExecuteArgs> for StopContainer {
     // Send update after setting action state, this way frontend gets correct state.
     update_update(update.clone()).await?;
 
-    let periphery = periphery_client(&server)?;
+    let periphery = periphery_client(&server).await?;
 
     let log = match periphery
       .request(api::container::StopContainer {
@@ -316,6 +319,8 @@ impl Resolve<ExecuteArgs> for DestroyContainer {
     )
     .await?;
 
+    check_not_protected::<Server>(&server)?;
+
     // get the action state for the server (or insert default).
     let action_state = action_states()
       .server
@@ -332,7 +337,7 @@ impl Resolve<ExecuteArgs> for DestroyContainer {
     // Send update after setting action state, this way frontend gets correct state.
     update_update(update.clone()).await?;
 
-    let periphery = periphery_client(&server)?;
+    let periphery = periphery_client(&server).await?;
 
     let log = match periphery
       .request(api::container::RemoveContainer {
@@ -387,7 +392,8 @@ impl Resolve<ExecuteArgs> for StartAllContainers {
 
     update_update(update.clone()).await?;
 
-    let logs = periphery_client(&server)?
+    let logs = periphery_client(&server)
+      .await?
       .request(api::container::StartAllContainers {})
       .await
       .context("failed to start all containers on host")?;
@@ -437,7 +443,8 @@ impl Resolve<ExecuteArgs> for RestartAllContainers {
 
     update_update(update.clone()).await?;
 
-    let logs = periphery_client(&server)?
+    let logs = periphery_client(&server)
+      .await?
       .request(api::container::RestartAllContainers {})
       .await
       .context("failed to restart all containers on host")?;
@@ -489,7 +496,8 @@ impl Resolve<ExecuteArgs> for PauseAllContainers {
 
     update_update(update.clone()).await?;
 
-    let logs = periphery_client(&server)?
+    let logs = periphery_client(&server)
+      .await?
       .request(api::container::PauseAllContainers {})
       .await
       .context("failed to pause all containers on host")?;
@@ -539,7 +547,8 @@ impl Resolve<ExecuteArgs> for UnpauseAllContainers {
 
     update_update(update.clone()).await?;
 
-    let logs = periphery_client(&server)?
+    let logs = periphery_client(&server)
+      .await?
       .request(api::container::UnpauseAllContainers {})
       .await
       .context("failed to unpause all containers on host")?;
@@ -591,7 +600,8 @@ impl Resolve<ExecuteArgs> for StopAllContainers {
 
     update_update(update.clone()).await?;
 
-    let logs = periphery_client(&server)?
+    let logs = periphery_client(&server)
+      .await?
       .request(api::container::StopAllContainers {})
       .await
       .context("failed to stop all containers on host")?;
@@ -626,6 +636,8 @@ impl Resolve<ExecuteArgs> for PruneContainers {
     )
     .await?;
 
+    check_not_protected::<Server>(&server)?;
+
     // get the action state for the server (or insert default).
     let action_state = action_states()
       .server
@@ -641,7 +653,7 @@ impl Resolve<ExecuteArgs> for PruneContainers {
 
     update_update(update.clone()).await?;
 
-    let periphery = periphery_client(&server)?;
+    let periphery = periphery_client(&server).await?;
 
     let log = match periphery
       .request(api::container::PruneContainers {})
@@ -682,11 +694,13 @@ impl Resolve<ExecuteArgs> for DeleteNetwork {
     )
     .await?;
 
+    check_not_protected::<Server>(&server)?;
+
     let mut update = update.clone();
 
     update_update(update.clone()).await?;
 
-    let periphery = periphery_client(&server)?;
+    let periphery = periphery_client(&server).await?;
 
     let log = match periphery
       .request(api::network::DeleteNetwork {
@@ -733,6 +747,8 @@ impl Resolve<ExecuteArgs> for PruneNetworks {
     )
     .await?;
 
+    check_not_protected::<Server>(&server)?;
+
     // get the action state for the server (or insert default).
     let action_state = action_states()
       .server
@@ -748,7 +764,7 @@ impl Resolve<ExecuteArgs> for PruneNetworks {
 
     update_update(update.clone()).await?;
 
-    let periphery = periphery_client(&server)?;
+    let periphery = periphery_client(&server).await?;
 
     let log = match periphery
       .request(api::network::PruneNetworks {})
@@ -787,11 +803,13 @@ impl Resolve<ExecuteArgs> for DeleteImage {
     )
     .await?;
 
+    check_not_protected::<Server>(&server)?;
+
     let mut update = update.clone();
 
     update_update(update.clone()).await?;
 
-    let periphery = periphery_client(&server)?;
+    let periphery = periphery_client(&server).await?;
 
     let log = match periphery
       .request(api::image::DeleteImage {
@@ -835,6 +853,8 @@ impl Resolve<ExecuteArgs> for PruneImages {
     )
     .await?;
 
+    check_not_protected::<Server>(&server)?;
+
     // get the action state for the server (or insert default).
     let action_state = action_states()
       .server
@@ -850,7 +870,7 @@ impl Resolve<ExecuteArgs> for PruneImages {
 
     update_update(update.clone()).await?;
 
-    let periphery = periphery_client(&server)?;
+    let periphery = periphery_client(&server).await?;
 
     let log =
       match periphery.request(api::image::PruneImages {}).await {
@@ -887,11 +907,13 @@ impl Resolve<ExecuteArgs> for DeleteVolume {
     )
     .await?;
 
+    check_not_protected::<Server>(&server)?;
+
     let mut update = update.clone();
 
     update_update(update.clone()).await?;
 
-    let periphery = periphery_client(&server)?;
+    let periphery = periphery_client(&server).await?;
 
     let log = match periphery
       .request(api::volume::DeleteVolume {
@@ -938,6 +960,8 @@ impl Resolve<ExecuteArgs> for PruneVolumes {
     )
     .await?;
 
+    check_not_protected::<Server>(&server)?;
+
     // get the action state for the server (or insert default).
     let action_state = action_states()
       .server
@@ -953,7 +977,7 @@ impl Resolve<ExecuteArgs> for PruneVolumes {
 
     update_update(update.clone()).await?;
 
-    let periphery = periphery_client(&server)?;
+    let periphery = periphery_client(&server).await?;
 
     let log =
       match periphery.request(api::volume::PruneVolumes {}).await {
@@ -990,6 +1014,8 @@ impl Resolve<ExecuteArgs> for PruneDockerBuilders {
     )
     .await?;
 
+    check_not_protected::<Server>(&server)?;
+
     // get the action state for the server (or insert default).
     let action_state = action_states()
       .server
@@ -1005,7 +1031,7 @@ impl Resolve<ExecuteArgs> for PruneDockerBuilders {
 
     update_update(update.clone()).await?;
 
-    let periphery = periphery_client(&server)?;
+    let periphery = periphery_client(&server).await?;
 
     let log =
       match periphery.request(api::build::PruneBuilders {}).await {
@@ -1042,6 +1068,8 @@ impl Resolve<ExecuteArgs> for PruneBuildx {
     )
     .await?;
 
+    check_not_protected::<Server>(&server)?;
+
     // get the action state for the server (or insert default).
     let action_state = action_states()
       .server
@@ -1057,7 +1085,7 @@ impl Resolve<ExecuteArgs> for PruneBuildx {
 
     update_update(update.clone()).await?;
 
-    let periphery = periphery_client(&server)?;
+    let periphery = periphery_client(&server).await?;
 
     let log =
       match periphery.request(api::build::PruneBuildx {}).await {
@@ -1094,6 +1122,8 @@ impl Resolve<ExecuteArgs> for PruneSystem {
     )
     .await?;
 
+    check_not_protected::<Server>(&server)?;
+
     // get the action state for the server (or insert default).
     let action_state = action_states()
       .server
@@ -1109,7 +1139,7 @@ impl Resolve<ExecuteArgs> for PruneSystem {
 
     update_update(update.clone()).await?;
 
-    let periphery = periphery_client(&server)?;
+    let periphery = periphery_client(&server).await?;
 
     let log = match periphery.request(api::PruneSystem {}).await {
       Ok(log) => log,
@@ -1131,3 +1161,94 @@ impl Resolve<ExecuteArgs> for PruneSystem {
     Ok(update)
   }
 }
+
+impl Resolve<ExecuteArgs> for RunScheduledCommand {
+  #[instrument(name = "RunScheduledCommand", skip(user, update), fields(user_id = user.id, update_id = update.id))]
+  async fn resolve(
+    self,
+    ExecuteArgs { user, update }: &ExecuteArgs,
+  ) -> serror::Result<Update> {
+    // Also checked for the creator when run on schedule.
+    let server = get_check_permissions::<Server>(
+      &self.server,
+      user,
+      PermissionLevel::Execute.terminal(),
+    )
+    .await?;
+
+    let command = server
+      .config
+      .scheduled_commands
+      .iter()
+      .find(|command| command.name == self.command)
+      .cloned()
+      .with_context(|| {
+        format!(
+          "No scheduled command named {} on Server {}",
+          self.command, server.name
+        )
+      })?;
+
+    let mut update = update.clone();
+
+    update_update(update.clone()).await?;
+
+    let periphery = periphery_client(&server).await?;
+
+    let log = match periphery
+      .request(api::RunScheduledCommand {
+        name: command.name.clone(),
+        command: command.command,
+        path: command.path,
+        user: command.user,
+      })
+      .await
+    {
+      Ok(log) => log,
+      Err(e) => Log::error(
+        &command.name,
+        format_serror(
+          &e.context("Failed to run scheduled command").into(),
+        ),
+      ),
+    };
+
+    update.logs.push(log);
+    update.finalize();
+    update_update(update.clone()).await?;
+
+    if !update.success && command.alert {
+      warn!(
+        "scheduled command {} on server {} failed, alerting...",
+        command.name, server.name
+      );
+      let target = update.target.clone();
+      tokio::spawn(async move {
+        let ts = komodo_timestamp();
+        let alert = Alert {
+          id: Default::default(),
+          target,
+          ts,
+          resolved_ts: Some(ts),
+          resolved: true,
+          level: SeverityLevel::Warning,
+          data: AlertData::ScheduledCommandFailed {
+            id: server.id,
+            name: server.name,
+            command: command.name,
+          },
+          notifications: Default::default(),
+        };
+        let res = db_client().alerts.insert_one(&alert).await;
+        if let Err(e) = res {
+          error!(
+            "Failed to record ScheduledCommandFailed to db | {e:#}"
+          );
+        }
+        send_alerts(&[alert]).await
+      });
+    }
+
+    Ok(update)
+  }
+}
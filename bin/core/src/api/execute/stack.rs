@@ -1,21 +1,25 @@
-use std::{collections::HashSet, str::FromStr};
-
-use anyhow::Context;
-use database::mungos::mongodb::bson::{
-  doc, oid::ObjectId, to_bson, to_document,
+use std::{
+  collections::{BTreeMap, HashSet},
+  str::FromStr,
 };
+
+use anyhow::{Context, anyhow};
+use database::mungos::mongodb::bson::{doc, oid::ObjectId, to_bson};
 use formatting::format_serror;
 use interpolate::Interpolator;
 use komodo_client::{
   api::{execute::*, write::RefreshStackCache},
   entities::{
-    FileContents,
+    FileContents, all_logs_success,
     permission::PermissionLevel,
+    plan::{StackFileDiff, StackPlan, StackServiceDiff},
     repo::Repo,
     server::Server,
     stack::{
-      Stack, StackFileRequires, StackInfo, StackRemoteFileContents,
+      BlueGreenColor, PartialStackConfig, Stack, StackFileRequires,
+      StackInfo, StackRemoteFileContents, StackServiceReplicas,
     },
+    sync::DiffData,
     update::{Log, Update},
     user::User,
   },
@@ -26,9 +30,16 @@ use resolver_api::Resolve;
 use crate::{
   api::write::WriteArgs,
   helpers::{
+    artifact::attach_artifact,
+    container_dns::apply_container_dns_defaults,
+    execution_environment::{
+      capture_execution_environment, compose_hash,
+    },
     periphery_client,
     query::{VariablesAndSecrets, get_variables_and_secrets},
-    stack_git_token,
+    rerun::{pin_rerun_commit, rerun_inputs},
+    resource_lock::acquire_resource_lock,
+    stack_git_token, stack_oci_token,
     update::{
       add_update_without_send, init_execution_update, update_update,
     },
@@ -36,7 +47,21 @@ use crate::{
   monitor::update_cache_for_server,
   permission::get_check_permissions,
   resource,
-  stack::{execute::execute_compose, get_stack_and_server},
+  stack::{
+    execute::execute_compose,
+    get_project_name_conflicts, get_stack_and_server,
+    health::probe_stack_health,
+    includes::{
+      resolve_remote_includes, restore_include_urls,
+      stack_is_ui_defined,
+    },
+    latest_stack_files,
+    overrides::{
+      is_compose_override_path, resolve_compose_overrides,
+    },
+    runtime_drift::clear_stack_runtime_drift,
+    update_stack_info,
+  },
   state::{action_states, db_client},
 };
 
@@ -54,14 +79,20 @@ impl super::BatchExecute for BatchDeployStack {
 }
 
 impl Resolve<ExecuteArgs> for BatchDeployStack {
-  #[instrument(name = "BatchDeployStack", skip(user), fields(user_id = user.id))]
+  #[instrument(name = "BatchDeployStack", skip(self, user, update), fields(user_id = user.id, update_id = update.id))]
   async fn resolve(
     self,
-    ExecuteArgs { user, .. }: &ExecuteArgs,
-  ) -> serror::Result<BatchExecutionResponse> {
+    ExecuteArgs { user, update }: &ExecuteArgs,
+  ) -> serror::Result<Update> {
     Ok(
-      super::batch_execute::<BatchDeployStack>(&self.pattern, user)
-        .await?,
+      super::batch_execute::<BatchDeployStack>(
+        &self.pattern,
+        self.parallelism,
+        self.abort_on_failure,
+        user,
+        update,
+      )
+      .await?,
     )
   }
 }
@@ -90,6 +121,9 @@ impl Resolve<ExecuteArgs> for DeployStack {
       None
     };
 
+    let _resource_lock =
+      acquire_resource_lock(&stack, &update).await?;
+
     // get the action state for the stack (or insert default).
     let action_state =
       action_states().stack.get_or_insert_default(&stack.id).await;
@@ -113,41 +147,157 @@ impl Resolve<ExecuteArgs> for DeployStack {
       ))
     }
 
+    // Reruns deploy the recorded commit.
+    match repo.as_mut() {
+      Some(repo) => {
+        pin_rerun_commit(&mut repo.config.commit, &mut update)
+      }
+      None if !stack.config.repo.is_empty() => {
+        pin_rerun_commit(&mut stack.config.commit, &mut update)
+      }
+      None => {}
+    }
+
     let git_token =
       stack_git_token(&mut stack, repo.as_mut()).await?;
 
     let registry_token = crate::helpers::registry_token(
       &stack.config.registry_provider,
       &stack.config.registry_account,
+      &(&stack).into(),
     ).await.with_context(
       || format!("Failed to get registry token in call to db. Stopping run. | {} | {}", stack.config.registry_provider, stack.config.registry_account),
     )?;
 
+    let oci_token = stack_oci_token(&stack).await?;
+
     // interpolate variables / secrets, returning the sanitizing replacers to send to
     // periphery so it may sanitize the final command for safe logging (avoids exposing secret values)
-    let secret_replacers = if !stack.config.skip_secret_interp {
-      let VariablesAndSecrets { variables, secrets } =
-        get_variables_and_secrets().await?;
+    let (secret_replacers, interpolated) =
+      if !stack.config.skip_secret_interp {
+        let VariablesAndSecrets { variables, secrets } =
+          get_variables_and_secrets().await?;
+
+        let mut interpolator =
+          Interpolator::new(Some(&variables), &secrets)
+            .with_server(&server)?;
+
+        interpolator.interpolate_stack(&mut stack)?;
+        if let Some(repo) = repo.as_mut()
+          && !repo.config.skip_secret_interp
+        {
+          interpolator.interpolate_repo(repo)?;
+        }
+        interpolator.push_logs(&mut update.logs);
 
-      let mut interpolator =
-        Interpolator::new(Some(&variables), &secrets);
+        let interpolated = interpolator.interpolated_names();
+        (interpolator.secret_replacers, interpolated)
+      } else {
+        Default::default()
+      };
 
-      interpolator.interpolate_stack(&mut stack)?;
-      if let Some(repo) = repo.as_mut()
-        && !repo.config.skip_secret_interp
+    apply_container_dns_defaults(
+      &server,
+      &mut stack.config.dns,
+      &mut stack.config.extra_hosts,
+    )
+    .await;
+
+    // Full deploys of blue / green Stacks bring up the next color
+    // alongside the active project. Service deploys go to the active project.
+    let mut up_stack = stack.clone();
+    let blue_green = if stack.config.blue_green
+      && self.services.is_empty()
+    {
+      let color = stack
+        .info
+        .blue_green_color
+        .map(BlueGreenColor::next)
+        .unwrap_or(BlueGreenColor::Blue);
+      up_stack.config.project_name =
+        format!("{}-{color}", stack.project_name(true));
+      up_stack.config.destroy_before_deploy = false;
+      // Otherwise periphery takes down the active project
+      // after seeing the project name changed.
+      up_stack.info.deployed_project_name = None;
+      Some(color)
+    } else {
+      if stack.config.blue_green
+        && let Some(project_name) = &stack.info.deployed_project_name
       {
-        interpolator.interpolate_repo(repo)?;
+        up_stack.config.project_name = project_name.clone();
       }
-      interpolator.push_logs(&mut update.logs);
-
-      interpolator.secret_replacers
+      None
+    };
+    // This ensures to get the latest project name,
+    // as it may have changed since the last deploy.
+    let project_name = up_stack.project_name(true);
+
+    let includes = if !stack.config.cache_remote_includes {
+      Vec::new()
+    } else if stack_is_ui_defined(&stack) {
+      resolve_remote_includes(&mut up_stack, &mut update.logs)
+        .await
+        .context("Failed to cache remote includes")?
     } else {
-      Default::default()
+      update.logs.push(Log::simple(
+        "Remote Includes",
+        String::from(
+          "Remote includes are only cached for Stacks with the compose file defined in the UI",
+        ),
+      ));
+      Vec::new()
     };
 
+    let overrides =
+      resolve_compose_overrides(&mut up_stack, &mut update.logs)
+        .await
+        .context("Failed to get compose overrides")?;
+
+    match get_project_name_conflicts(&stack, &project_name).await {
+      Ok(conflicts) if !conflicts.is_empty() => {
+        update.logs.push(Log::simple(
+          "Project Ownership",
+          format!(
+            "WARNING: Compose project '{project_name}' is also managed by Stack/s {} on the same Server. \
+            Deploying will take over their containers.",
+            conflicts.join(", ")
+          ),
+        ));
+      }
+      Ok(_) => {}
+      Err(e) => warn!(
+        "Failed to check project name conflicts for Stack {} | {e:#}",
+        stack.name
+      ),
+    }
+
+    let periphery = periphery_client(&server).await?;
+
+    // Reruns only deploy the same rendered compose config.
+    // Blue / green deploys alternate the project name in the config,
+    // so it can't match.
+    let expected_compose_hash = match rerun_inputs() {
+      Some(rerun)
+        if !rerun.environment.compose_hash.is_empty()
+          && blue_green.is_none() =>
+      {
+        update.push_simple_log(
+          "Rerun",
+          format!(
+            "Deploying only if the rendered compose config matches Update {}",
+            rerun.update_id
+          ),
+        );
+        Some(rerun.environment.compose_hash)
+      }
+      _ => None,
+    };
+    let requested_services = self.services.clone();
+
     let ComposeUpResponse {
       logs,
-      deployed,
+      mut deployed,
       services,
       file_contents,
       missing_files,
@@ -155,19 +305,78 @@ impl Resolve<ExecuteArgs> for DeployStack {
       compose_config,
       commit_hash,
       commit_message,
-    } = periphery_client(&server)?
+    } = periphery
       .request(ComposeUp {
-        stack: stack.clone(),
+        stack: up_stack,
         services: self.services,
         repo,
         git_token,
         registry_token,
+        oci_token,
         replacers: secret_replacers.into_iter().collect(),
+        include_files: includes
+          .iter()
+          .map(|include| include.file.clone())
+          .chain(overrides)
+          .collect(),
+        expected_compose_hash,
       })
       .await?;
 
     update.logs.extend(logs);
 
+    // The overrides aren't Stack files, so they aren't diffed.
+    let file_contents = file_contents
+      .into_iter()
+      .filter(|file| !is_compose_override_path(&file.path))
+      .collect::<Vec<_>>();
+
+    if deployed && blue_green.is_some() {
+      deployed = match swap_blue_green(
+        &server,
+        &stack,
+        &project_name,
+        &mut update.logs,
+      )
+      .await
+      {
+        Ok(swapped) => swapped,
+        Err(e) => {
+          update
+            .push_error_log("Blue / Green", format_serror(&e.into()));
+          false
+        }
+      };
+    }
+
+    // Keep the rendered (sanitized) compose config with the deploy.
+    if deployed
+      && let Some(config) = &compose_config
+      && let Err(e) = attach_artifact(
+        &mut update,
+        "compose.config.yaml",
+        config.clone().into_bytes(),
+      )
+      .await
+    {
+      warn!("Failed to attach compose config artifact | {e:#}");
+    }
+
+    if deployed {
+      update.commit_hash = commit_hash.clone().unwrap_or_default();
+      let mut environment = capture_execution_environment(
+        &periphery,
+        services.iter().map(|service| service.image.clone()),
+        interpolated,
+      )
+      .await;
+      if let Some(config) = &compose_config {
+        environment.compose_hash = compose_hash(config);
+      }
+      environment.services = requested_services;
+      update.environment = Some(environment);
+    }
+
     let update_info = async {
       let latest_services = if services.is_empty() {
         // maybe better to do something else here for services.
@@ -176,9 +385,19 @@ impl Resolve<ExecuteArgs> for DeployStack {
         services
       };
 
-      // This ensures to get the latest project name,
-      // as it may have changed since the last deploy.
-      let project_name = stack.project_name(true);
+      // A failed blue / green deploy leaves the active project serving.
+      let (deployed_project_name, blue_green_color) = match blue_green
+      {
+        Some(color) if deployed => (Some(project_name), Some(color)),
+        Some(_) => (
+          stack.info.deployed_project_name,
+          stack.info.blue_green_color,
+        ),
+        None if stack.config.blue_green => {
+          (Some(project_name), stack.info.blue_green_color)
+        }
+        None => (Some(project_name), None),
+      };
 
       let (
         deployed_services,
@@ -194,7 +413,10 @@ impl Resolve<ExecuteArgs> for DeployStack {
               .iter()
               .map(|f| FileContents {
                 path: f.path.clone(),
-                contents: f.contents.clone(),
+                contents: restore_include_urls(
+                  &f.contents,
+                  &includes,
+                ),
               })
               .collect(),
           ),
@@ -214,7 +436,8 @@ impl Resolve<ExecuteArgs> for DeployStack {
 
       let info = StackInfo {
         missing_files,
-        deployed_project_name: project_name.into(),
+        deployed_project_name,
+        blue_green_color,
         deployed_services,
         deployed_contents,
         deployed_config,
@@ -233,19 +456,12 @@ impl Resolve<ExecuteArgs> for DeployStack {
           .then_some(remote_errors),
         latest_hash: commit_hash,
         latest_message: commit_message,
+        stored_file_contents: stack.info.stored_file_contents.clone(),
       };
 
-      let info = to_document(&info)
-        .context("failed to serialize stack info to bson")?;
-
-      db_client()
-        .stacks
-        .update_one(
-          doc! { "name": &stack.name },
-          doc! { "$set": { "info": info } },
-        )
-        .await
-        .context("failed to update stack info on db")?;
+      update_stack_info(&stack.name, &info).await?;
+      // The next drift check compares against the new deployment.
+      clear_stack_runtime_drift(&stack.id);
       anyhow::Ok(())
     };
 
@@ -262,6 +478,12 @@ impl Resolve<ExecuteArgs> for DeployStack {
     // Ensure cached stack state up to date by updating server cache
     update_cache_for_server(&server, true).await;
 
+    if !stack.config.health_checks.is_empty() {
+      tokio::spawn(async move {
+        probe_stack_health(&stack).await;
+      });
+    }
+
     update.finalize();
     update_update(update.clone()).await?;
 
@@ -269,6 +491,82 @@ impl Resolve<ExecuteArgs> for DeployStack {
   }
 }
 
+/// Waits for the new blue / green project to be healthy,
+/// moves the network alias over, and takes down the previous project.
+/// If the new project fails, it is taken down instead,
+/// leaving the previous project serving.
+/// Returns whether the new project is now active.
+async fn swap_blue_green(
+  server: &Server,
+  stack: &Stack,
+  project: &str,
+  logs: &mut Vec<Log>,
+) -> anyhow::Result<bool> {
+  let periphery = periphery_client(server).await?;
+
+  let log = periphery
+    .request(ComposeWaitHealthy {
+      project: project.to_string(),
+      ignore_services: stack.config.ignore_services.clone(),
+      timeout: stack.config.blue_green_timeout,
+    })
+    .await
+    .context("Failed to wait on blue / green project health")?;
+  let mut success = log.success;
+  logs.push(log);
+
+  let previous = stack
+    .info
+    .deployed_project_name
+    .clone()
+    .filter(|previous| previous != project);
+
+  if success && !stack.config.blue_green_service.is_empty() {
+    if stack.config.blue_green_network.is_empty()
+      || stack.config.blue_green_alias.is_empty()
+    {
+      logs.push(Log::error(
+        "Blue / Green",
+        String::from(
+          "Blue / green service is set without a network and alias",
+        ),
+      ));
+      success = false;
+    } else {
+      let swap_logs = periphery
+        .request(ComposeSwapNetworkAlias {
+          project: project.to_string(),
+          previous_project: previous.clone(),
+          service: stack.config.blue_green_service.clone(),
+          network: stack.config.blue_green_network.clone(),
+          alias: stack.config.blue_green_alias.clone(),
+        })
+        .await
+        .context("Failed to swap blue / green network alias")?;
+      success = all_logs_success(&swap_logs);
+      logs.extend(swap_logs);
+    }
+  }
+
+  let down = if success {
+    previous
+  } else {
+    Some(project.to_string())
+  };
+  if let Some(down) = down {
+    let log = periphery
+      .request(ComposeExecution {
+        project: down,
+        command: String::from("down"),
+      })
+      .await
+      .context("Failed to take down blue / green project")?;
+    logs.push(log);
+  }
+
+  Ok(success)
+}
+
 impl super::BatchExecute for BatchDeployStackIfChanged {
   type Resource = Stack;
   fn single_request(stack: String) -> ExecuteRequest {
@@ -280,15 +578,18 @@ impl super::BatchExecute for BatchDeployStackIfChanged {
 }
 
 impl Resolve<ExecuteArgs> for BatchDeployStackIfChanged {
-  #[instrument(name = "BatchDeployStackIfChanged", skip(user), fields(user_id = user.id))]
+  #[instrument(name = "BatchDeployStackIfChanged", skip(self, user, update), fields(user_id = user.id, update_id = update.id))]
   async fn resolve(
     self,
-    ExecuteArgs { user, .. }: &ExecuteArgs,
-  ) -> serror::Result<BatchExecutionResponse> {
+    ExecuteArgs { user, update }: &ExecuteArgs,
+  ) -> serror::Result<Update> {
     Ok(
       super::batch_execute::<BatchDeployStackIfChanged>(
         &self.pattern,
+        self.parallelism,
+        self.abort_on_failure,
         user,
+        update,
       )
       .await?,
     )
@@ -651,6 +952,154 @@ fn resolve_deploy_if_changed_action(
   }
 }
 
+/// Computes what [DeployStack] would change compared to
+/// the last successful deploy, without applying anything.
+pub async fn plan_deploy_stack(
+  DeployStack {
+    stack, services, ..
+  }: DeployStack,
+  user: &User,
+) -> serror::Result<StackPlan> {
+  let stack = get_check_permissions::<Stack>(
+    &stack,
+    user,
+    PermissionLevel::Execute.into(),
+  )
+  .await?;
+
+  // Get the latest file contents
+  RefreshStackCache {
+    stack: stack.id.clone(),
+  }
+  .resolve(&WriteArgs { user: user.clone() })
+  .await?;
+
+  let stack = resource::get::<Stack>(&stack.id).await?;
+
+  let latest_files = latest_stack_files(&stack);
+  let deployed_files =
+    stack.info.deployed_contents.clone().unwrap_or_default();
+
+  let mut plan = StackPlan {
+    // Blue / green deploys bring up a new project alongside the old one.
+    destroy_first: !stack.config.blue_green
+      && stack.info.deployed_contents.is_some()
+      && (stack.config.destroy_before_deploy
+        || stack.project_name(false) != stack.project_name(true)),
+    ..Default::default()
+  };
+
+  for file in &latest_files {
+    let data =
+      match deployed_files.iter().find(|f| f.path == file.path) {
+        None => DiffData::Create {
+          name: file.path.clone(),
+          proposed: file.contents.clone(),
+        },
+        Some(deployed) if deployed.contents != file.contents => {
+          DiffData::Update {
+            proposed: file.contents.clone(),
+            current: deployed.contents.clone(),
+          }
+        }
+        Some(_) => continue,
+      };
+    plan.files.push(StackFileDiff {
+      path: file.path.clone(),
+      data,
+    });
+  }
+
+  // UI defined stacks only have the compose file in the latest files.
+  if stack.info.remote_contents.is_some() {
+    for file in deployed_files
+      .iter()
+      .filter(|f| !latest_files.iter().any(|l| l.path == f.path))
+    {
+      plan.files.push(StackFileDiff {
+        path: file.path.clone(),
+        data: DiffData::Delete {
+          current: file.contents.clone(),
+        },
+      });
+    }
+  }
+
+  let latest_services = compose_services(&stack, &latest_files)?;
+  let deployed_services = compose_services(&stack, &deployed_files)?;
+  let included = |service: &String| {
+    services.is_empty() || services.contains(service)
+  };
+
+  for (service, proposed) in &latest_services {
+    if !included(service) {
+      continue;
+    }
+    let data = match deployed_services.get(service) {
+      None => DiffData::Create {
+        name: service.clone(),
+        proposed: to_yaml(proposed)?,
+      },
+      Some(current) if current != proposed => DiffData::Update {
+        proposed: to_yaml(proposed)?,
+        current: to_yaml(current)?,
+      },
+      Some(_) => continue,
+    };
+    plan.services.push(StackServiceDiff {
+      service: service.clone(),
+      data,
+    });
+  }
+
+  for (service, current) in &deployed_services {
+    if !included(service) || latest_services.contains_key(service) {
+      continue;
+    }
+    plan.services.push(StackServiceDiff {
+      service: service.clone(),
+      data: DiffData::Delete {
+        current: to_yaml(current)?,
+      },
+    });
+  }
+
+  Ok(plan)
+}
+
+/// Gets the service definitions in the compose files.
+/// Services in later files replace those in earlier files.
+fn compose_services(
+  stack: &Stack,
+  files: &[FileContents],
+) -> anyhow::Result<BTreeMap<String, serde_yaml_ng::Value>> {
+  let mut services = BTreeMap::new();
+  for file in files.iter().filter(|f| stack.is_compose_file(&f.path))
+  {
+    let compose =
+      serde_yaml_ng::from_str::<serde_yaml_ng::Value>(&file.contents)
+        .with_context(|| {
+          format!("Failed to parse compose file at {}", file.path)
+        })?;
+    let Some(file_services) =
+      compose.get("services").and_then(|s| s.as_mapping())
+    else {
+      continue;
+    };
+    for (name, service) in file_services {
+      if let Some(name) = name.as_str() {
+        services.insert(name.to_string(), service.clone());
+      }
+    }
+  }
+  Ok(services)
+}
+
+fn to_yaml(service: &serde_yaml_ng::Value) -> anyhow::Result<String> {
+  serde_yaml_ng::to_string(service)
+    .context("Failed to serialize compose service")
+}
+
 impl super::BatchExecute for BatchPullStack {
   type Resource = Stack;
   fn single_request(stack: String) -> ExecuteRequest {
@@ -662,14 +1111,20 @@ impl super::BatchExecute for BatchPullStack {
 }
 
 impl Resolve<ExecuteArgs> for BatchPullStack {
-  #[instrument(name = "BatchPullStack", skip(user), fields(user_id = user.id))]
+  #[instrument(name = "BatchPullStack", skip(self, user, update), fields(user_id = user.id, update_id = update.id))]
   async fn resolve(
     self,
-    ExecuteArgs { user, .. }: &ExecuteArgs,
-  ) -> serror::Result<BatchExecutionResponse> {
+    ExecuteArgs { user, update }: &ExecuteArgs,
+  ) -> serror::Result<Update> {
     Ok(
-      super::batch_execute::<BatchPullStack>(&self.pattern, user)
-        .await?,
+      super::batch_execute::<BatchPullStack>(
+        &self.pattern,
+        self.parallelism,
+        self.abort_on_failure,
+        user,
+        update,
+      )
+      .await?,
     )
   }
 }
@@ -680,9 +1135,10 @@ async fn maybe_pull_stack(
 ) -> anyhow::Result<()> {
   if stack.config.files_on_host
     || (stack.config.repo.is_empty()
-      && stack.config.linked_repo.is_empty())
+      && stack.config.linked_repo.is_empty()
+      && stack.config.oci_artifact.is_empty())
   {
-    // Not repo based, no pull necessary
+    // Not repo or artifact based, no pull necessary
     return Ok(());
   }
   let server =
@@ -723,17 +1179,21 @@ pub async fn pull_stack_inner(
   let registry_token = crate::helpers::registry_token(
       &stack.config.registry_provider,
       &stack.config.registry_account,
+      &(&stack).into(),
     ).await.with_context(
       || format!("Failed to get registry token in call to db. Stopping run. | {} | {}", stack.config.registry_provider, stack.config.registry_account),
     )?;
 
+  let oci_token = stack_oci_token(&stack).await?;
+
   // interpolate variables / secrets
   let secret_replacers = if !stack.config.skip_secret_interp {
     let VariablesAndSecrets { variables, secrets } =
       get_variables_and_secrets().await?;
 
     let mut interpolator =
-      Interpolator::new(Some(&variables), &secrets);
+      Interpolator::new(Some(&variables), &secrets)
+        .with_server(server)?;
 
     interpolator.interpolate_stack(&mut stack)?;
     if let Some(repo) = repo.as_mut()
@@ -741,7 +1201,7 @@ pub async fn pull_stack_inner(
     {
       interpolator.interpolate_repo(repo)?;
     }
-    if let Some(update) = update {
+    if let Some(update) = update.as_mut() {
       interpolator.push_logs(&mut update.logs);
     }
     interpolator.secret_replacers
@@ -749,14 +1209,26 @@ pub async fn pull_stack_inner(
     Default::default()
   };
 
-  let res = periphery_client(server)?
+  let mut override_logs = Vec::new();
+  let overrides =
+    resolve_compose_overrides(&mut stack, &mut override_logs)
+      .await
+      .context("Failed to get compose overrides")?;
+  if let Some(update) = update {
+    update.logs.extend(override_logs);
+  }
+
+  let res = periphery_client(server)
+    .await?
     .request(ComposePull {
       stack,
       services,
       repo,
       git_token,
       registry_token,
+      oci_token,
       replacers: secret_replacers.into_iter().collect(),
+      include_files: overrides,
     })
     .await?;
 
@@ -790,6 +1262,9 @@ impl Resolve<ExecuteArgs> for PullStack {
       None
     };
 
+    let _resource_lock =
+      acquire_resource_lock(&stack, &update).await?;
+
     // get the action state for the stack (or insert default).
     let action_state =
       action_states().stack.get_or_insert_default(&stack.id).await;
@@ -929,14 +1404,21 @@ impl super::BatchExecute for BatchDestroyStack {
 }
 
 impl Resolve<ExecuteArgs> for BatchDestroyStack {
-  #[instrument(name = "BatchDestroyStack", skip(user), fields(user_id = user.id))]
+  #[instrument(name = "BatchDestroyStack", skip(self, user, update), fields(user_id = user.id, update_id = update.id))]
   async fn resolve(
     self,
-    ExecuteArgs { user, .. }: &ExecuteArgs,
-  ) -> serror::Result<BatchExecutionResponse> {
-    super::batch_execute::<BatchDestroyStack>(&self.pattern, user)
-      .await
-      .map_err(Into::into)
+    ExecuteArgs { user, update }: &ExecuteArgs,
+  ) -> serror::Result<Update> {
+    Ok(
+      super::batch_execute::<BatchDestroyStack>(
+        &self.pattern,
+        self.parallelism,
+        self.abort_on_failure,
+        user,
+        update,
+      )
+      .await?,
+    )
   }
 }
 
@@ -983,6 +1465,9 @@ impl Resolve<ExecuteArgs> for RunStackService {
       None
     };
 
+    let _resource_lock =
+      acquire_resource_lock(&stack, &update).await?;
+
     let action_state =
       action_states().stack.get_or_insert_default(&stack.id).await;
 
@@ -998,16 +1483,20 @@ impl Resolve<ExecuteArgs> for RunStackService {
     let registry_token = crate::helpers::registry_token(
       &stack.config.registry_provider,
       &stack.config.registry_account,
+      &(&stack).into(),
     ).await.with_context(
       || format!("Failed to get registry token in call to db. Stopping run. | {} | {}", stack.config.registry_provider, stack.config.registry_account),
     )?;
 
+    let oci_token = stack_oci_token(&stack).await?;
+
     let secret_replacers = if !stack.config.skip_secret_interp {
       let VariablesAndSecrets { variables, secrets } =
         get_variables_and_secrets().await?;
 
       let mut interpolator =
-        Interpolator::new(Some(&variables), &secrets);
+        Interpolator::new(Some(&variables), &secrets)
+          .with_server(&server)?;
 
       interpolator.interpolate_stack(&mut stack)?;
       if let Some(repo) = repo.as_mut()
@@ -1022,12 +1511,14 @@ impl Resolve<ExecuteArgs> for RunStackService {
       Default::default()
     };
 
-    let log = periphery_client(&server)?
+    let log = periphery_client(&server)
+      .await?
       .request(ComposeRun {
         stack,
         repo,
         git_token,
         registry_token,
+        oci_token,
         replacers: secret_replacers.into_iter().collect(),
         service: self.service,
         command: self.command,
@@ -1050,3 +1541,95 @@ impl Resolve<ExecuteArgs> for RunStackService {
     Ok(update)
   }
 }
+
+impl Resolve<ExecuteArgs> for ScaleStackService {
+  #[instrument(name = "ScaleStackService", skip(user, update), fields(user_id = user.id, update_id = update.id))]
+  async fn resolve(
+    self,
+    ExecuteArgs { user, update }: &ExecuteArgs,
+  ) -> serror::Result<Update> {
+    // Scaling persists the replicas to the Stack config,
+    // so requires the same permission as any other config update.
+    let (stack, _) = get_stack_and_server(
+      &self.stack,
+      user,
+      PermissionLevel::Write.into(),
+      true,
+    )
+    .await?;
+
+    if self.replicas < 0 {
+      return Err(
+        anyhow!("Replicas must be a non-negative number").into(),
+      );
+    }
+
+    if !stack
+      .info
+      .latest_services
+      .iter()
+      .any(|s| s.service_name == self.service)
+    {
+      return Err(
+        anyhow!(
+          "Service {} not found in Stack {}",
+          self.service,
+          stack.name
+        )
+        .into(),
+      );
+    }
+
+    // Don't persist the new replicas if the deploy can't happen.
+    if action_states()
+      .stack
+      .get_or_insert_default(&stack.id)
+      .await
+      .busy()?
+    {
+      return Err(anyhow!("Stack is busy").into());
+    }
+
+    let mut replicas = stack.config.replicas.clone();
+    match replicas.iter_mut().find(|r| r.service == self.service) {
+      Some(existing) => existing.replicas = self.replicas,
+      None => replicas.push(StackServiceReplicas {
+        service: self.service.clone(),
+        replicas: self.replicas,
+      }),
+    }
+
+    // Goes through the normal update path,
+    // recording the config diff in an UpdateStack Update.
+    resource::update::<Stack>(
+      &stack.id,
+      PartialStackConfig {
+        replicas: Some(replicas),
+        ..Default::default()
+      },
+      None,
+      user,
+    )
+    .await?;
+
+    let mut update = update.clone();
+    update.logs.push(Log::simple(
+      "Scale Service",
+      format!(
+        "Set service {} replicas to {}",
+        self.service, self.replicas
+      ),
+    ));
+
+    DeployStack {
+      stack: stack.id,
+      services: vec![self.service],
+      stop_time: None,
+    }
+    .resolve(&ExecuteArgs {
+      user: user.clone(),
+      update,
+    })
+    .await
+  }
+}
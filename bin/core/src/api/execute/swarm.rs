@@ -0,0 +1,301 @@
+use anyhow::{Context, anyhow};
+use formatting::format_serror;
+use interpolate::Interpolator;
+use komodo_client::{
+  api::execute::*,
+  entities::{
+    deployment::extract_registry_domain,
+    permission::PermissionLevel,
+    server::Server,
+    swarm::SwarmService,
+    update::{Log, Update},
+    user::User,
+  },
+};
+use periphery_client::api;
+use resolver_api::Resolve;
+
+use crate::{
+  helpers::{
+    periphery_client,
+    query::{VariablesAndSecrets, get_variables_and_secrets},
+    registry_token,
+    resource_lock::acquire_resource_lock,
+    update::update_update,
+  },
+  monitor::update_cache_for_server,
+  permission::get_check_permissions,
+  resource,
+  state::{action_states, swarm_service_status_cache},
+};
+
+use super::ExecuteArgs;
+
+async fn setup_swarm_service_execution(
+  swarm_service: &str,
+  user: &User,
+) -> anyhow::Result<(SwarmService, Server)> {
+  let swarm_service = get_check_permissions::<SwarmService>(
+    swarm_service,
+    user,
+    PermissionLevel::Execute.into(),
+  )
+  .await?;
+
+  if swarm_service.config.server_id.is_empty() {
+    return Err(anyhow!("Swarm Service has no Server configured"));
+  }
+
+  let server =
+    resource::get::<Server>(&swarm_service.config.server_id).await?;
+
+  if !server.config.enabled {
+    return Err(anyhow!("Attached Server is not enabled"));
+  }
+
+  Ok((swarm_service, server))
+}
+
+impl Resolve<ExecuteArgs> for DeploySwarmService {
+  #[instrument(name = "DeploySwarmService", skip(user, update), fields(user_id = user.id, update_id = update.id))]
+  async fn resolve(
+    self,
+    ExecuteArgs { user, update }: &ExecuteArgs,
+  ) -> serror::Result<Update> {
+    let (mut swarm_service, server) =
+      setup_swarm_service_execution(&self.swarm_service, user)
+        .await?;
+
+    let _resource_lock =
+      acquire_resource_lock(&swarm_service, &update).await?;
+
+    // get the action state for the swarm service (or insert default).
+    let action_state = action_states()
+      .swarm_service
+      .get_or_insert_default(&swarm_service.id)
+      .await;
+
+    // Will check to ensure swarm service not already busy before updating, and return Err if so.
+    // The returned guard will set the action state back to default when dropped.
+    let _action_guard =
+      action_state.update(|state| state.deploying = true)?;
+
+    let mut update = update.clone();
+
+    // Send update after setting action state, this way frontend gets correct state.
+    update_update(update.clone()).await?;
+
+    let registry_token = if swarm_service
+      .config
+      .file_contents
+      .is_empty()
+      && !swarm_service.config.image_registry_account.is_empty()
+    {
+      let domain =
+        extract_registry_domain(&swarm_service.config.image)?;
+      registry_token(
+        &domain,
+        &swarm_service.config.image_registry_account,
+        &(&swarm_service).into(),
+      )
+      .await
+      .with_context(|| {
+        format!(
+          "Failed to get registry token in call to db. Stopping run. | {domain} | {}",
+          swarm_service.config.image_registry_account
+        )
+      })?
+    } else {
+      None
+    };
+
+    // interpolate variables / secrets, returning the sanitizing replacers to send to
+    // periphery so it may sanitize the final command for safe logging (avoids exposing secret values)
+    let secret_replacers = if !swarm_service.config.skip_secret_interp
+    {
+      let VariablesAndSecrets { variables, secrets } =
+        get_variables_and_secrets().await?;
+
+      let mut interpolator =
+        Interpolator::new(Some(&variables), &secrets);
+
+      interpolator
+        .interpolate_swarm_service(&mut swarm_service)?
+        .push_logs(&mut update.logs);
+
+      interpolator.secret_replacers
+    } else {
+      Default::default()
+    };
+
+    update_update(update.clone()).await?;
+
+    match periphery_client(&server)
+      .await?
+      .request(api::swarm::DeploySwarmService {
+        swarm_service,
+        registry_token,
+        replacers: secret_replacers.into_iter().collect(),
+      })
+      .await
+    {
+      Ok(log) => update.logs.push(log),
+      Err(e) => {
+        update.push_error_log(
+          "Deploy Swarm Service",
+          format_serror(&e.into()),
+        );
+      }
+    };
+
+    update_cache_for_server(&server, true).await;
+
+    update.finalize();
+    update_update(update.clone()).await?;
+
+    Ok(update)
+  }
+}
+
+impl Resolve<ExecuteArgs> for ScaleSwarmService {
+  #[instrument(name = "ScaleSwarmService", skip(user, update), fields(user_id = user.id, update_id = update.id))]
+  async fn resolve(
+    self,
+    ExecuteArgs { user, update }: &ExecuteArgs,
+  ) -> serror::Result<Update> {
+    if self.replicas < 0 {
+      return Err(anyhow!("Replicas cannot be negative").into());
+    }
+
+    let (swarm_service, server) =
+      setup_swarm_service_execution(&self.swarm_service, user)
+        .await?;
+
+    let _resource_lock =
+      acquire_resource_lock(&swarm_service, &update).await?;
+
+    // get the action state for the swarm service (or insert default).
+    let action_state = action_states()
+      .swarm_service
+      .get_or_insert_default(&swarm_service.id)
+      .await;
+
+    // Will check to ensure swarm service not already busy before updating, and return Err if so.
+    // The returned guard will set the action state back to default when dropped.
+    let _action_guard =
+      action_state.update(|state| state.scaling = true)?;
+
+    let mut update = update.clone();
+
+    // Send update after setting action state, this way frontend gets correct state.
+    update_update(update.clone()).await?;
+
+    // Services in the stack are named `<stack>_<service>`
+    let target = self
+      .service
+      .as_ref()
+      .map(|service| format!("{}_{service}", swarm_service.name));
+
+    let services = swarm_service_status_cache()
+      .get(&swarm_service.id)
+      .await
+      .unwrap_or_default()
+      .curr
+      .services
+      .iter()
+      .filter(|service| {
+        service.mode == "replicated"
+          && target
+            .as_ref()
+            .map(|target| &service.name == target)
+            .unwrap_or(true)
+      })
+      .map(|service| service.name.clone())
+      .collect::<Vec<_>>();
+
+    if services.is_empty() {
+      return Err(
+        anyhow!(
+          "No replicated services found to scale. Is the Swarm Service deployed?"
+        )
+        .into(),
+      );
+    }
+
+    let log = match periphery_client(&server)
+      .await?
+      .request(api::swarm::ScaleSwarmServices {
+        services,
+        replicas: self.replicas,
+      })
+      .await
+    {
+      Ok(log) => log,
+      Err(e) => Log::error(
+        "Scale Services",
+        format_serror(&e.context("Failed to scale services").into()),
+      ),
+    };
+
+    update.logs.push(log);
+    update_cache_for_server(&server, true).await;
+    update.finalize();
+    update_update(update.clone()).await?;
+
+    Ok(update)
+  }
+}
+
+impl Resolve<ExecuteArgs> for RemoveSwarmService {
+  #[instrument(name = "RemoveSwarmService", skip(user, update), fields(user_id = user.id, update_id = update.id))]
+  async fn resolve(
+    self,
+    ExecuteArgs { user, update }: &ExecuteArgs,
+  ) -> serror::Result<Update> {
+    let (swarm_service, server) =
+      setup_swarm_service_execution(&self.swarm_service, user)
+        .await?;
+
+    resource::check_not_protected::<SwarmService>(&swarm_service)?;
+
+    let _resource_lock =
+      acquire_resource_lock(&swarm_service, &update).await?;
+
+    // get the action state for the swarm service (or insert default).
+    let action_state = action_states()
+      .swarm_service
+      .get_or_insert_default(&swarm_service.id)
+      .await;
+
+    // Will check to ensure swarm service not already busy before updating, and return Err if so.
+    // The returned guard will set the action state back to default when dropped.
+    let _action_guard =
+      action_state.update(|state| state.removing = true)?;
+
+    let mut update = update.clone();
+
+    // Send update after setting action state, this way frontend gets correct state.
+    update_update(update.clone()).await?;
+
+    let log = match periphery_client(&server)
+      .await?
+      .request(api::swarm::RemoveSwarmStack {
+        stack: swarm_service.name,
+      })
+      .await
+    {
+      Ok(log) => log,
+      Err(e) => Log::error(
+        "Remove Stack",
+        format_serror(&e.context("Failed to remove stack").into()),
+      ),
+    };
+
+    update.logs.push(log);
+    update_cache_for_server(&server, true).await;
+    update.finalize();
+    update_update(update.clone()).await?;
+
+    Ok(update)
+  }
+}
@@ -17,13 +17,14 @@ use komodo_client::{
     deployment::Deployment,
     komodo_timestamp,
     permission::PermissionLevel,
+    plan::SyncPlan,
     procedure::Procedure,
     repo::Repo,
     server::Server,
     stack::Stack,
     sync::ResourceSync,
     update::{Log, Update},
-    user::sync_user,
+    user::{User, sync_user},
   },
 };
 use resolver_api::Resolve;
@@ -32,7 +33,7 @@ use crate::{
   api::write::WriteArgs,
   helpers::{
     all_resources::AllResourcesById, query::get_id_to_tags,
-    update::update_update,
+    resource_lock::acquire_resource_lock, update::update_update,
   },
   permission::get_check_permissions,
   state::{action_states, db_client},
@@ -43,6 +44,7 @@ use crate::{
     },
     execute::{ExecuteResourceSync, get_updates_for_execution},
     remote::RemoteResources,
+    view::push_updates_for_view,
   },
 };
 
@@ -76,6 +78,9 @@ impl Resolve<ExecuteArgs> for RunSync {
       None
     };
 
+    let _resource_lock =
+      acquire_resource_lock(&sync, &update).await?;
+
     // get the action state for the sync (or insert default).
     let action_state =
       action_states().sync.get_or_insert_default(&sync.id).await;
@@ -115,63 +120,11 @@ impl Resolve<ExecuteArgs> for RunSync {
 
     let id_to_tags = get_id_to_tags(None).await?;
     let all_resources = AllResourcesById::load().await?;
-    // Convert all match_resources to names
-    let match_resources = match_resources.map(|resources| {
-      resources
-        .into_iter()
-        .filter_map(|name_or_id| {
-          let Some(resource_type) = match_resource_type else {
-            return Some(name_or_id);
-          };
-          match ObjectId::from_str(&name_or_id) {
-            Ok(_) => match resource_type {
-              ResourceTargetVariant::Alerter => all_resources
-                .alerters
-                .get(&name_or_id)
-                .map(|a| a.name.clone()),
-              ResourceTargetVariant::Build => all_resources
-                .builds
-                .get(&name_or_id)
-                .map(|b| b.name.clone()),
-              ResourceTargetVariant::Builder => all_resources
-                .builders
-                .get(&name_or_id)
-                .map(|b| b.name.clone()),
-              ResourceTargetVariant::Deployment => all_resources
-                .deployments
-                .get(&name_or_id)
-                .map(|d| d.name.clone()),
-              ResourceTargetVariant::Procedure => all_resources
-                .procedures
-                .get(&name_or_id)
-                .map(|p| p.name.clone()),
-              ResourceTargetVariant::Action => all_resources
-                .actions
-                .get(&name_or_id)
-                .map(|p| p.name.clone()),
-              ResourceTargetVariant::Repo => all_resources
-                .repos
-                .get(&name_or_id)
-                .map(|r| r.name.clone()),
-              ResourceTargetVariant::Server => all_resources
-                .servers
-                .get(&name_or_id)
-                .map(|s| s.name.clone()),
-              ResourceTargetVariant::Stack => all_resources
-                .stacks
-                .get(&name_or_id)
-                .map(|s| s.name.clone()),
-              ResourceTargetVariant::ResourceSync => all_resources
-                .syncs
-                .get(&name_or_id)
-                .map(|s| s.name.clone()),
-              ResourceTargetVariant::System => None,
-            },
-            Err(_) => Some(name_or_id),
-          }
-        })
-        .collect::<Vec<_>>()
-    });
+    let match_resources = match_resource_names(
+      match_resource_type,
+      match_resources,
+      &all_resources,
+    );
 
     let deployments_by_name = all_resources
       .deployments
@@ -533,8 +486,309 @@ impl Resolve<ExecuteArgs> for RunSync {
   }
 }
 
+/// Computes what [RunSync] would change, without applying anything.
+/// Resources are matched the same way as RunSync.
+pub async fn plan_sync(
+  RunSync {
+    sync,
+    resource_type: match_resource_type,
+    resources: match_resources,
+  }: RunSync,
+  user: &User,
+  update: &mut Update,
+) -> serror::Result<SyncPlan> {
+  let sync = get_check_permissions::<entities::sync::ResourceSync>(
+    &sync,
+    user,
+    PermissionLevel::Execute.into(),
+  )
+  .await?;
+
+  let repo = if !sync.config.files_on_host
+    && !sync.config.linked_repo.is_empty()
+  {
+    crate::resource::get::<Repo>(&sync.config.linked_repo)
+      .await?
+      .into()
+  } else {
+    None
+  };
+
+  let RemoteResources {
+    resources,
+    logs,
+    file_errors,
+    ..
+  } = crate::sync::remote::get_remote_resources(&sync, repo.as_ref())
+    .await
+    .context("failed to get remote resources")?;
+
+  update.logs.extend(logs);
+
+  if !file_errors.is_empty() {
+    return Err(
+      anyhow!("Found file errors. Cannot plan sync.").into(),
+    );
+  }
+
+  let resources = resources?;
+  let delete = sync.config.managed || sync.config.delete;
+
+  let id_to_tags = get_id_to_tags(None).await?;
+  let all_resources = AllResourcesById::load().await?;
+  let match_resources = match_resource_names(
+    match_resource_type,
+    match_resources,
+    &all_resources,
+  );
+  let match_resources = match_resources.as_deref();
+
+  let mut plan = SyncPlan::default();
+
+  if sync.config.include_resources {
+    let deployments_by_name = all_resources
+      .deployments
+      .values()
+      .filter(|deployment| {
+        Deployment::include_resource(
+          &deployment.name,
+          &deployment.config,
+          match_resource_type,
+          match_resources,
+          &deployment.tags,
+          &id_to_tags,
+          &sync.config.match_tags,
+        )
+      })
+      .map(|deployment| (deployment.name.clone(), deployment.clone()))
+      .collect::<HashMap<_, _>>();
+    let stacks_by_name = all_resources
+      .stacks
+      .values()
+      .filter(|stack| {
+        Stack::include_resource(
+          &stack.name,
+          &stack.config,
+          match_resource_type,
+          match_resources,
+          &stack.tags,
+          &id_to_tags,
+          &sync.config.match_tags,
+        )
+      })
+      .map(|stack| (stack.name.clone(), stack.clone()))
+      .collect::<HashMap<_, _>>();
+
+    plan.deploy_updates =
+      crate::sync::deploy::get_updates_for_view(SyncDeployParams {
+        deployments: &resources.deployments,
+        deployment_map: &deployments_by_name,
+        stacks: &resources.stacks,
+        stack_map: &stacks_by_name,
+      })
+      .await;
+
+    let diffs = &mut plan.resource_updates;
+    let match_tags = &sync.config.match_tags;
+
+    push_updates_for_view::<Server>(
+      resources.servers,
+      delete,
+      match_resource_type,
+      match_resources,
+      &id_to_tags,
+      match_tags,
+      diffs,
+    )
+    .await?;
+    push_updates_for_view::<Stack>(
+      resources.stacks,
+      delete,
+      match_resource_type,
+      match_resources,
+      &id_to_tags,
+      match_tags,
+      diffs,
+    )
+    .await?;
+    push_updates_for_view::<Deployment>(
+      resources.deployments,
+      delete,
+      match_resource_type,
+      match_resources,
+      &id_to_tags,
+      match_tags,
+      diffs,
+    )
+    .await?;
+    push_updates_for_view::<Build>(
+      resources.builds,
+      delete,
+      match_resource_type,
+      match_resources,
+      &id_to_tags,
+      match_tags,
+      diffs,
+    )
+    .await?;
+    push_updates_for_view::<Repo>(
+      resources.repos,
+      delete,
+      match_resource_type,
+      match_resources,
+      &id_to_tags,
+      match_tags,
+      diffs,
+    )
+    .await?;
+    push_updates_for_view::<Procedure>(
+      resources.procedures,
+      delete,
+      match_resource_type,
+      match_resources,
+      &id_to_tags,
+      match_tags,
+      diffs,
+    )
+    .await?;
+    push_updates_for_view::<Action>(
+      resources.actions,
+      delete,
+      match_resource_type,
+      match_resources,
+      &id_to_tags,
+      match_tags,
+      diffs,
+    )
+    .await?;
+    push_updates_for_view::<Builder>(
+      resources.builders,
+      delete,
+      match_resource_type,
+      match_resources,
+      &id_to_tags,
+      match_tags,
+      diffs,
+    )
+    .await?;
+    push_updates_for_view::<Alerter>(
+      resources.alerters,
+      delete,
+      match_resource_type,
+      match_resources,
+      &id_to_tags,
+      match_tags,
+      diffs,
+    )
+    .await?;
+    push_updates_for_view::<ResourceSync>(
+      resources.resource_syncs,
+      delete,
+      match_resource_type,
+      match_resources,
+      &id_to_tags,
+      match_tags,
+      diffs,
+    )
+    .await?;
+  }
+
+  // Like RunSync, variables and user groups
+  // are skipped when matching specific resources.
+  let match_all =
+    match_resource_type.is_none() && match_resources.is_none();
+
+  if match_all && sync.config.include_variables {
+    plan.variable_updates =
+      crate::sync::variables::get_updates_for_view(
+        &resources.variables,
+        delete,
+      )
+      .await?;
+  }
+
+  if match_all && sync.config.include_user_groups {
+    plan.user_group_updates =
+      crate::sync::user_groups::get_updates_for_view(
+        resources.user_groups,
+        delete,
+      )
+      .await?;
+  }
+
+  Ok(plan)
+}
+
 fn maybe_extend(logs: &mut Vec<Log>, log: Option<Log>) {
   if let Some(log) = log {
     logs.push(log);
   }
 }
+
+/// Converts the resource ids in `match_resources` to names.
+fn match_resource_names(
+  match_resource_type: Option<ResourceTargetVariant>,
+  match_resources: Option<Vec<String>>,
+  all_resources: &AllResourcesById,
+) -> Option<Vec<String>> {
+  match_resources.map(|resources| {
+    resources
+      .into_iter()
+      .filter_map(|name_or_id| {
+        let Some(resource_type) = match_resource_type else {
+          return Some(name_or_id);
+        };
+        match ObjectId::from_str(&name_or_id) {
+          Ok(_) => match resource_type {
+            ResourceTargetVariant::Alerter => all_resources
+              .alerters
+              .get(&name_or_id)
+              .map(|a| a.name.clone()),
+            ResourceTargetVariant::Build => all_resources
+              .builds
+              .get(&name_or_id)
+              .map(|b| b.name.clone()),
+            ResourceTargetVariant::Builder => all_resources
+              .builders
+              .get(&name_or_id)
+              .map(|b| b.name.clone()),
+            ResourceTargetVariant::Deployment => all_resources
+              .deployments
+              .get(&name_or_id)
+              .map(|d| d.name.clone()),
+            ResourceTargetVariant::SwarmService => all_resources
+              .swarm_services
+              .get(&name_or_id)
+              .map(|s| s.name.clone()),
+            ResourceTargetVariant::Procedure => all_resources
+              .procedures
+              .get(&name_or_id)
+              .map(|p| p.name.clone()),
+            ResourceTargetVariant::Action => all_resources
+              .actions
+              .get(&name_or_id)
+              .map(|p| p.name.clone()),
+            ResourceTargetVariant::Repo => all_resources
+              .repos
+              .get(&name_or_id)
+              .map(|r| r.name.clone()),
+            ResourceTargetVariant::Server => all_resources
+              .servers
+              .get(&name_or_id)
+              .map(|s| s.name.clone()),
+            ResourceTargetVariant::Stack => all_resources
+              .stacks
+              .get(&name_or_id)
+              .map(|s| s.name.clone()),
+            ResourceTargetVariant::ResourceSync => all_resources
+              .syncs
+              .get(&name_or_id)
+              .map(|s| s.name.clone()),
+            ResourceTargetVariant::System => None,
+          },
+          Err(_) => Some(name_or_id),
+        }
+      })
+      .collect::<Vec<_>>()
+  })
+}
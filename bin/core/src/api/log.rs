@@ -0,0 +1,162 @@
+use anyhow::Context;
+use axum::{Extension, Router, middleware, routing::post};
+use futures::TryStreamExt;
+use komodo_client::{
+  api::log::*,
+  entities::{
+    deployment::Deployment, permission::PermissionLevel,
+    server::Server, stack::Stack, user::User,
+  },
+};
+use periphery_client::{
+  api::container::FollowContainerLogBody as PeripheryFollowContainerLogBody,
+  audit::AuditContext,
+};
+use serror::Json;
+
+use crate::{
+  auth::auth_request, helpers::periphery_client,
+  permission::get_check_permissions, resource::get,
+  stack::get_stack_service_container,
+};
+
+pub fn router() -> Router {
+  Router::new()
+    .route("/follow/container", post(follow_container_log))
+    .route("/follow/deployment", post(follow_deployment_log))
+    .route("/follow/stack", post(follow_stack_log))
+    .layer(middleware::from_fn(auth_request))
+}
+
+#[instrument(
+  name = "FollowContainerLog",
+  skip(user),
+  fields(
+    user_id = user.id,
+  )
+)]
+async fn follow_container_log(
+  Extension(user): Extension<User>,
+  Json(FollowContainerLogBody {
+    server,
+    container,
+    tail,
+    timestamps,
+    filter,
+  }): Json<FollowContainerLogBody>,
+) -> serror::Result<axum::body::Body> {
+  info!("/log/follow/container request | user: {}", user.username);
+  let server = get_check_permissions::<Server>(
+    &server,
+    &user,
+    PermissionLevel::Read.logs(),
+  )
+  .await?;
+  follow_log(
+    &user,
+    &server,
+    PeripheryFollowContainerLogBody {
+      name: container,
+      tail,
+      timestamps,
+      filter,
+    },
+  )
+  .await
+}
+
+#[instrument(
+  name = "FollowDeploymentLog",
+  skip(user),
+  fields(
+    user_id = user.id,
+  )
+)]
+async fn follow_deployment_log(
+  Extension(user): Extension<User>,
+  Json(FollowDeploymentLogBody {
+    deployment,
+    tail,
+    timestamps,
+    filter,
+  }): Json<FollowDeploymentLogBody>,
+) -> serror::Result<axum::body::Body> {
+  info!("/log/follow/deployment request | user: {}", user.username);
+  let deployment = get_check_permissions::<Deployment>(
+    &deployment,
+    &user,
+    PermissionLevel::Read.logs(),
+  )
+  .await?;
+  let server = get::<Server>(&deployment.config.server_id).await?;
+  follow_log(
+    &user,
+    &server,
+    PeripheryFollowContainerLogBody {
+      name: deployment.name,
+      tail,
+      timestamps,
+      filter,
+    },
+  )
+  .await
+}
+
+#[instrument(
+  name = "FollowStackLog",
+  skip(user),
+  fields(
+    user_id = user.id,
+  )
+)]
+async fn follow_stack_log(
+  Extension(user): Extension<User>,
+  Json(FollowStackLogBody {
+    stack,
+    service,
+    tail,
+    timestamps,
+    filter,
+  }): Json<FollowStackLogBody>,
+) -> serror::Result<axum::body::Body> {
+  info!("/log/follow/stack request | user: {}", user.username);
+  let stack = get_check_permissions::<Stack>(
+    &stack,
+    &user,
+    PermissionLevel::Read.logs(),
+  )
+  .await?;
+  let server = get::<Server>(&stack.config.server_id).await?;
+  let container =
+    get_stack_service_container(&stack, &server, &service).await?;
+  follow_log(
+    &user,
+    &server,
+    PeripheryFollowContainerLogBody {
+      name: container,
+      tail,
+      timestamps,
+      filter,
+    },
+  )
+  .await
+}
+
+/// Proxies the log stream from Periphery. Lines are only read
+/// from Periphery as they are sent on to the client.
+async fn follow_log(
+  user: &User,
+  server: &Server,
+  body: PeripheryFollowContainerLogBody,
+) -> serror::Result<axum::body::Body> {
+  let periphery = periphery_client(server).await?;
+  let stream = AuditContext::new(&user.username, "")
+    .scope(periphery.follow_container_log(body))
+    .await
+    .context("Failed to follow container log on periphery")?;
+  // The line stream strips the line endings, add them back
+  // so clients can read the body line by line.
+  Ok(axum::body::Body::from_stream(
+    stream.into_line_stream().map_ok(|line| line + "\n"),
+  ))
+}
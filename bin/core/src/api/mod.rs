@@ -1,6 +1,9 @@
+pub mod artifact;
 pub mod auth;
 pub mod execute;
+pub mod log;
 pub mod read;
+pub mod stack_file;
 pub mod terminal;
 pub mod user;
 pub mod write;
@@ -9,3 +12,15 @@ pub mod write;
 struct Variant {
   variant: String,
 }
+
+/// Adds the Core version to responses, so clients can point out
+/// version mismatches when they fail to parse a response.
+pub async fn core_version_header(
+  mut res: axum::response::Response,
+) -> axum::response::Response {
+  res.headers_mut().insert(
+    komodo_client::compatibility::CORE_VERSION_HEADER,
+    axum::http::HeaderValue::from_static(env!("CARGO_PKG_VERSION")),
+  );
+  res
+}
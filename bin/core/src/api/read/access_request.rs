@@ -0,0 +1,36 @@
+use anyhow::Context;
+use database::mungos::{
+  find::find_collect,
+  mongodb::{bson::doc, options::FindOptions},
+};
+use komodo_client::api::read::*;
+use resolver_api::Resolve;
+
+use crate::state::db_client;
+
+use super::ReadArgs;
+
+impl Resolve<ReadArgs> for ListAccessRequests {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<ListAccessRequestsResponse> {
+    let mut filter = doc! {};
+    if !user.admin {
+      filter.insert("user_id", &user.id);
+    }
+    if !self.include_resolved {
+      filter.insert("status", "Pending");
+    }
+    let requests = find_collect(
+      &db_client().access_requests,
+      filter,
+      FindOptions::builder()
+        .sort(doc! { "requested_at": -1 })
+        .build(),
+    )
+    .await
+    .context("Failed to query db for access requests")?;
+    Ok(requests)
+  }
+}
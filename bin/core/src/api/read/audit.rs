@@ -0,0 +1,49 @@
+use anyhow::{Context, anyhow};
+use database::mungos::{
+  find::find_collect,
+  mongodb::{bson::doc, options::FindOptions},
+};
+use komodo_client::api::read::{ListAuditLog, ListAuditLogResponse};
+use reqwest::StatusCode;
+use resolver_api::Resolve;
+use serror::AddStatusCodeError;
+
+use crate::state::db_client;
+
+use super::ReadArgs;
+
+const NUM_ENTRIES_PER_PAGE: u64 = 100;
+
+impl Resolve<ReadArgs> for ListAuditLog {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<ListAuditLogResponse> {
+    if !user.admin {
+      return Err(
+        anyhow!("Only admins can view the audit log")
+          .status_code(StatusCode::FORBIDDEN),
+      );
+    }
+
+    let entries = find_collect(
+      &db_client().audit_log,
+      self.query,
+      FindOptions::builder()
+        .sort(doc! { "ts": -1 })
+        .limit(NUM_ENTRIES_PER_PAGE as i64)
+        .skip(self.page * NUM_ENTRIES_PER_PAGE)
+        .build(),
+    )
+    .await
+    .context("failed to get audit log from db")?;
+
+    let next_page = if entries.len() < NUM_ENTRIES_PER_PAGE as usize {
+      None
+    } else {
+      Some((self.page + 1) as i64)
+    };
+
+    Ok(ListAuditLogResponse { entries, next_page })
+  }
+}
@@ -0,0 +1,73 @@
+use anyhow::Context;
+use database::mungos::{
+  by_id::find_one_by_id,
+  find::find_collect,
+  mongodb::{bson::doc, options::FindOptions},
+};
+use komodo_client::{
+  api::read::*,
+  entities::{permission::PermissionLevel, procedure::Procedure},
+};
+use resolver_api::Resolve;
+
+use crate::{
+  permission::{get_check_permissions, get_resource_ids_for_user},
+  state::db_client,
+};
+
+use super::ReadArgs;
+
+impl Resolve<ReadArgs> for GetExecutionApproval {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<GetExecutionApprovalResponse> {
+    let approval =
+      find_one_by_id(&db_client().execution_approvals, &self.id)
+        .await
+        .context("Failed to query db for execution approval")?
+        .context("No execution approval found with given id")?;
+    get_check_permissions::<Procedure>(
+      &approval.procedure_id,
+      user,
+      PermissionLevel::Read.into(),
+    )
+    .await?;
+    Ok(approval)
+  }
+}
+
+impl Resolve<ReadArgs> for ListExecutionApprovals {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<ListExecutionApprovalsResponse> {
+    let mut filter = doc! {};
+    if let Some(procedure) = &self.procedure {
+      let procedure = get_check_permissions::<Procedure>(
+        procedure,
+        user,
+        PermissionLevel::Read.into(),
+      )
+      .await?;
+      filter.insert("procedure_id", procedure.id);
+    } else if let Some(ids) =
+      get_resource_ids_for_user::<Procedure>(user).await?
+    {
+      filter.insert("procedure_id", doc! { "$in": ids });
+    }
+    if !self.include_resolved {
+      filter.insert("status", "Pending");
+    }
+    let approvals = find_collect(
+      &db_client().execution_approvals,
+      filter,
+      FindOptions::builder()
+        .sort(doc! { "requested_at": -1 })
+        .build(),
+    )
+    .await
+    .context("Failed to query db for execution approvals")?;
+    Ok(approvals)
+  }
+}
@@ -0,0 +1,78 @@
+use anyhow::{Context, anyhow};
+use database::mungos::{
+  find::find_collect,
+  mongodb::{bson::doc, options::FindOptions},
+};
+use komodo_client::api::read::*;
+use reqwest::StatusCode;
+use resolver_api::Resolve;
+use serror::AddStatusCodeError;
+
+use crate::{
+  helpers::query::get_execution_schedule, state::db_client,
+};
+
+use super::ReadArgs;
+
+impl Resolve<ReadArgs> for GetExecutionSchedule {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<GetExecutionScheduleResponse> {
+    let schedule = get_execution_schedule(&self.schedule).await?;
+    if !user.admin && schedule.created_by != user.id {
+      return Err(
+        anyhow!("User does not have permission to view this execution schedule")
+          .status_code(StatusCode::FORBIDDEN),
+      );
+    }
+    Ok(schedule)
+  }
+}
+
+impl Resolve<ReadArgs> for ListExecutionScheduleRuns {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<ListExecutionScheduleRunsResponse> {
+    let schedule = get_execution_schedule(&self.schedule).await?;
+    if !user.admin && schedule.created_by != user.id {
+      return Err(
+        anyhow!("User does not have permission to view this execution schedule")
+          .status_code(StatusCode::FORBIDDEN),
+      );
+    }
+    let runs = find_collect(
+      &db_client().execution_schedule_runs,
+      doc! { "schedule_id": &schedule.id },
+      FindOptions::builder()
+        .sort(doc! { "run_at": -1 })
+        .limit(self.limit.filter(|limit| *limit > 0))
+        .build(),
+    )
+    .await
+    .context("Failed to query db for execution schedule runs")?;
+    Ok(runs)
+  }
+}
+
+impl Resolve<ReadArgs> for ListExecutionSchedules {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<ListExecutionSchedulesResponse> {
+    let filter = if user.admin {
+      doc! {}
+    } else {
+      doc! { "created_by": &user.id }
+    };
+    let schedules = find_collect(
+      &db_client().execution_schedules,
+      filter,
+      FindOptions::builder().sort(doc! { "name": 1 }).build(),
+    )
+    .await
+    .context("Failed to query db for execution schedules")?;
+    Ok(schedules)
+  }
+}
@@ -0,0 +1,463 @@
+use anyhow::Context;
+use indexmap::IndexMap;
+use komodo_client::{
+  api::read::*,
+  entities::{
+    EnvironmentVar, TerminationSignal,
+    build::Build,
+    deployment::{
+      Deployment, DeploymentConfig, DeploymentImage,
+      DeploymentNetworkMode, DeploymentQuerySpecifics, RestartMode,
+      conversions_from_str,
+    },
+    environment_vars_from_str,
+    permission::PermissionLevel,
+    resource::{ResourceQuery, TemplatesQueryBehavior},
+    server::{ContainerRuntime, Server},
+    stack::{Stack, StackQuerySpecifics},
+  },
+  parsers::QUOTE_PATTERN,
+};
+use resolver_api::Resolve;
+use serde::Serialize;
+
+use crate::{
+  permission::get_check_permissions, resource,
+  stack::storage::load_stored_file_contents,
+};
+
+use super::ReadArgs;
+
+/// The networks which are always available on the host.
+const BUILTIN_NETWORKS: [&str; 3] = ["host", "bridge", "none"];
+
+impl Resolve<ReadArgs> for ExportDeploymentToDockerRun {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<ExportDeploymentToDockerRunResponse> {
+    let deployment = get_check_permissions::<Deployment>(
+      &self.deployment,
+      user,
+      PermissionLevel::Read.into(),
+    )
+    .await?;
+    let container_runtime = if deployment.config.server_id.is_empty()
+    {
+      ContainerRuntime::Docker
+    } else {
+      resource::get::<Server>(&deployment.config.server_id)
+        .await
+        .map(|server| server.config.container_runtime)
+        .unwrap_or_default()
+    };
+    let image = deployment_image(&deployment).await?;
+    let command =
+      docker_run_command(&deployment, &image, container_runtime)?;
+    Ok(ExportDeploymentToDockerRunResponse { command })
+  }
+}
+
+impl Resolve<ReadArgs> for ExportDeploymentToCompose {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<ComposeExportResponse> {
+    let deployment = get_check_permissions::<Deployment>(
+      &self.deployment,
+      user,
+      PermissionLevel::Read.into(),
+    )
+    .await?;
+    let compose = deployments_compose_file(&[deployment]).await?;
+    Ok(ComposeExportResponse {
+      compose,
+      stacks: Vec::new(),
+    })
+  }
+}
+
+impl Resolve<ReadArgs> for ExportServerToCompose {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<ComposeExportResponse> {
+    let server = get_check_permissions::<Server>(
+      &self.server,
+      user,
+      PermissionLevel::Read.into(),
+    )
+    .await?;
+    let (deployments, mut stacks) = tokio::try_join!(
+      resource::list_full_for_user::<Deployment>(
+        ResourceQuery {
+          templates: TemplatesQueryBehavior::Exclude,
+          specific: DeploymentQuerySpecifics {
+            server_ids: vec![server.id.clone()],
+            ..Default::default()
+          },
+          ..Default::default()
+        },
+        user,
+        PermissionLevel::Read.into(),
+        &[],
+      ),
+      resource::list_full_for_user::<Stack>(
+        ResourceQuery {
+          templates: TemplatesQueryBehavior::Exclude,
+          specific: StackQuerySpecifics {
+            server_ids: vec![server.id.clone()],
+            ..Default::default()
+          },
+          ..Default::default()
+        },
+        user,
+        PermissionLevel::Read.into(),
+        &[],
+      ),
+    )?;
+    let compose = if deployments.is_empty() {
+      String::new()
+    } else {
+      deployments_compose_file(&deployments).await?
+    };
+    for stack in &mut stacks {
+      load_stored_file_contents(stack).await?;
+    }
+    let stacks = stacks
+      .into_iter()
+      // Only the UI defined compose files are stored in Core
+      .filter(|stack| {
+        !stack.config.files_on_host
+          && stack.config.repo.is_empty()
+          && stack.config.linked_repo.is_empty()
+          && stack.config.oci_artifact.is_empty()
+          && !stack.config.file_contents.trim().is_empty()
+      })
+      .map(|stack| StackComposeExport {
+        project_name: stack.project_name(true),
+        name: stack.name,
+        contents: stack.config.file_contents,
+      })
+      .collect();
+    Ok(ComposeExportResponse { compose, stacks })
+  }
+}
+
+/// Gets the image the Deployment runs,
+/// resolving the image name for attached Builds.
+async fn deployment_image(
+  deployment: &Deployment,
+) -> anyhow::Result<String> {
+  match &deployment.config.image {
+    DeploymentImage::Image { image } => Ok(image.clone()),
+    DeploymentImage::Build { build_id, version } => {
+      let build = resource::get::<Build>(build_id).await?;
+      let image_name = build
+        .get_image_names()
+        .into_iter()
+        .next()
+        .context("No image name could be created")?;
+      let version = if version.is_none() {
+        build.config.version.to_string()
+      } else {
+        version.to_string()
+      };
+      // Potentially add the build image_tag postfix
+      let version = if build.config.image_tag.is_empty() {
+        version
+      } else {
+        format!("{version}-{}", build.config.image_tag)
+      };
+      Ok(format!("{image_name}:{version}"))
+    }
+  }
+}
+
+fn docker_run_command(
+  deployment: &Deployment,
+  image: &str,
+  container_runtime: ContainerRuntime,
+) -> anyhow::Result<String> {
+  let Deployment {
+    name,
+    config:
+      DeploymentConfig {
+        volumes,
+        ports,
+        ip_address,
+        mac_address,
+        dns,
+        extra_hosts,
+        command,
+        restart,
+        termination_signal,
+        termination_timeout,
+        environment,
+        labels,
+        extra_args,
+        ..
+      },
+    ..
+  } = deployment;
+  let mut res = format!("{container_runtime} run -d --name {name}");
+  for port in conversions_from_str(ports).context("Invalid ports")? {
+    res.push_str(&format!(" -p {}:{}", port.local, port.container));
+  }
+  for volume in
+    conversions_from_str(volumes).context("Invalid volumes")?
+  {
+    res.push_str(&format!(
+      " -v {}:{}",
+      volume.local, volume.container
+    ));
+  }
+  res.push_str(&format!(
+    " --network {}",
+    deployment.config.network_name()
+  ));
+  if !ip_address.is_empty() {
+    if ip_address.contains(':') {
+      res.push_str(&format!(" --ip6 {ip_address}"));
+    } else {
+      res.push_str(&format!(" --ip {ip_address}"));
+    }
+  }
+  if !mac_address.is_empty() {
+    res.push_str(&format!(" --mac-address {mac_address}"));
+  }
+  for dns in dns {
+    res.push_str(&format!(" --dns {dns}"));
+  }
+  for host in extra_hosts {
+    res.push_str(&format!(" --add-host {host}"));
+  }
+  let restart = match restart {
+    RestartMode::OnFailure => String::from("on-failure:10"),
+    _ => restart.to_string(),
+  };
+  res.push_str(&format!(" --restart {restart}"));
+  if *termination_signal != TerminationSignal::SigTerm {
+    res.push_str(&format!(" --stop-signal {termination_signal}"));
+  }
+  if *termination_timeout != 10 {
+    res.push_str(&format!(" --stop-timeout {termination_timeout}"));
+  }
+  for var in environment_vars_from_str(environment)
+    .context("Invalid environment")?
+  {
+    res.push_str(&format!(" --env {}", quoted_var(&var)));
+  }
+  for label in
+    environment_vars_from_str(labels).context("Invalid labels")?
+  {
+    res.push_str(&format!(" --label {}", quoted_var(&label)));
+  }
+  for arg in extra_args {
+    res.push(' ');
+    res.push_str(arg);
+  }
+  res.push(' ');
+  res.push_str(image);
+  if !command.is_empty() {
+    res.push(' ');
+    res.push_str(command);
+  }
+  Ok(res)
+}
+
+fn quoted_var(var: &EnvironmentVar) -> String {
+  if var.value.starts_with(QUOTE_PATTERN)
+    && var.value.ends_with(QUOTE_PATTERN)
+  {
+    // If the value already wrapped in quotes, don't wrap it again
+    format!("{}={}", var.variable, var.value)
+  } else {
+    format!("{}=\"{}\"", var.variable, var.value)
+  }
+}
+
+#[derive(Serialize)]
+struct ComposeFile {
+  services: IndexMap<String, ComposeService>,
+  #[serde(skip_serializing_if = "IndexMap::is_empty")]
+  networks: IndexMap<String, ComposeNetwork>,
+}
+
+#[derive(Serialize)]
+struct ComposeService {
+  image: String,
+  container_name: String,
+  #[serde(skip_serializing_if = "String::is_empty")]
+  command: String,
+  restart: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  network_mode: Option<String>,
+  #[serde(skip_serializing_if = "IndexMap::is_empty")]
+  networks: IndexMap<String, ComposeServiceNetwork>,
+  #[serde(skip_serializing_if = "String::is_empty")]
+  mac_address: String,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  ports: Vec<String>,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  volumes: Vec<String>,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  dns: Vec<String>,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  extra_hosts: Vec<String>,
+  #[serde(skip_serializing_if = "IndexMap::is_empty")]
+  environment: IndexMap<String, String>,
+  #[serde(skip_serializing_if = "IndexMap::is_empty")]
+  labels: IndexMap<String, String>,
+  stop_signal: String,
+  stop_grace_period: String,
+}
+
+#[derive(Serialize, Default)]
+struct ComposeServiceNetwork {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  ipv4_address: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  ipv6_address: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ComposeNetwork {
+  external: bool,
+}
+
+/// Renders the Deployments as services in a single compose file.
+async fn deployments_compose_file(
+  deployments: &[Deployment],
+) -> anyhow::Result<String> {
+  let mut compose = ComposeFile {
+    services: IndexMap::new(),
+    networks: IndexMap::new(),
+  };
+  // Extra args can't be represented in the compose file,
+  // so they are noted at the top of the file.
+  let mut extra_args = Vec::new();
+  for deployment in deployments {
+    let image =
+      deployment_image(deployment).await.with_context(|| {
+        format!(
+          "Failed to get image for Deployment {}",
+          deployment.name
+        )
+      })?;
+    let service =
+      compose_service(deployment, image).with_context(|| {
+        format!(
+          "Failed to create compose service for Deployment {}",
+          deployment.name
+        )
+      })?;
+    if let Some(network) = service.networks.keys().next() {
+      compose
+        .networks
+        .insert(network.clone(), ComposeNetwork { external: true });
+    }
+    compose.services.insert(deployment.name.clone(), service);
+    if !deployment.config.extra_args.is_empty() {
+      extra_args.push(format!(
+        "# {}: {}",
+        deployment.name,
+        deployment.config.extra_args.join(" ")
+      ));
+    }
+  }
+  let contents = serde_yaml_ng::to_string(&compose)
+    .context("Failed to serialize compose file")?;
+  if extra_args.is_empty() {
+    Ok(contents)
+  } else {
+    Ok(format!(
+      "# Extra args not included in the compose file:\n{}\n\n{contents}",
+      extra_args.join("\n")
+    ))
+  }
+}
+
+fn compose_service(
+  deployment: &Deployment,
+  image: String,
+) -> anyhow::Result<ComposeService> {
+  let config = &deployment.config;
+  let network = config.network_name();
+  let (network_mode, networks) = match config.network_mode {
+    DeploymentNetworkMode::Bridge | DeploymentNetworkMode::Host => {
+      (Some(network.to_string()), IndexMap::new())
+    }
+    DeploymentNetworkMode::Custom
+      if BUILTIN_NETWORKS.contains(&network) =>
+    {
+      (Some(network.to_string()), IndexMap::new())
+    }
+    DeploymentNetworkMode::Custom
+    | DeploymentNetworkMode::Macvlan => {
+      let mut service_network = ComposeServiceNetwork::default();
+      if config.ip_address.contains(':') {
+        service_network.ipv6_address =
+          Some(config.ip_address.clone());
+      } else if !config.ip_address.is_empty() {
+        service_network.ipv4_address =
+          Some(config.ip_address.clone());
+      }
+      (
+        None,
+        [(network.to_string(), service_network)]
+          .into_iter()
+          .collect(),
+      )
+    }
+  };
+  let restart = match config.restart {
+    RestartMode::OnFailure => String::from("on-failure:10"),
+    restart => restart.to_string(),
+  };
+  Ok(ComposeService {
+    image,
+    container_name: deployment.name.clone(),
+    command: config.command.clone(),
+    restart,
+    network_mode,
+    networks,
+    mac_address: config.mac_address.clone(),
+    ports: conversions_from_str(&config.ports)
+      .context("Invalid ports")?
+      .into_iter()
+      .map(|port| format!("{}:{}", port.local, port.container))
+      .collect(),
+    volumes: conversions_from_str(&config.volumes)
+      .context("Invalid volumes")?
+      .into_iter()
+      .map(|volume| format!("{}:{}", volume.local, volume.container))
+      .collect(),
+    dns: config.dns.clone(),
+    extra_hosts: config.extra_hosts.clone(),
+    environment: environment_vars_from_str(&config.environment)
+      .context("Invalid environment")?
+      .into_iter()
+      .map(|var| (var.variable, strip_quotes(var.value)))
+      .collect(),
+    labels: environment_vars_from_str(&config.labels)
+      .context("Invalid labels")?
+      .into_iter()
+      .map(|label| (label.variable, strip_quotes(label.value)))
+      .collect(),
+    stop_signal: config.termination_signal.to_string(),
+    stop_grace_period: format!("{}s", config.termination_timeout),
+  })
+}
+
+/// Quotes are only needed when passing values through the shell.
+fn strip_quotes(value: String) -> String {
+  if value.len() > 1
+    && value.starts_with(QUOTE_PATTERN)
+    && value.ends_with(QUOTE_PATTERN)
+  {
+    value[1..value.len() - 1].to_string()
+  } else {
+    value
+  }
+}
@@ -0,0 +1,63 @@
+use anyhow::Context;
+use database::mungos::{
+  find::find_collect,
+  mongodb::{bson::doc, options::FindOptions},
+};
+use komodo_client::{
+  api::read::*,
+  entities::{external_status::ExternalState, komodo_timestamp},
+};
+use resolver_api::Resolve;
+
+use crate::{helpers::query::get_external_status, state::db_client};
+
+use super::ReadArgs;
+
+impl Resolve<ReadArgs> for GetExternalStatus {
+  async fn resolve(
+    self,
+    _: &ReadArgs,
+  ) -> serror::Result<GetExternalStatusResponse> {
+    Ok(get_external_status(&self.name).await?)
+  }
+}
+
+impl Resolve<ReadArgs> for ListExternalStatuses {
+  async fn resolve(
+    self,
+    _: &ReadArgs,
+  ) -> serror::Result<ListExternalStatusesResponse> {
+    let statuses = find_collect(
+      &db_client().external_statuses,
+      None,
+      FindOptions::builder().sort(doc! { "name": 1 }).build(),
+    )
+    .await
+    .context("Failed to query db for external statuses")?;
+    Ok(statuses)
+  }
+}
+
+impl Resolve<ReadArgs> for GetExternalStatusesSummary {
+  async fn resolve(
+    self,
+    _: &ReadArgs,
+  ) -> serror::Result<GetExternalStatusesSummaryResponse> {
+    let statuses =
+      find_collect(&db_client().external_statuses, None, None)
+        .await
+        .context("Failed to query db for external statuses")?;
+    let ts = komodo_timestamp();
+    let mut res = GetExternalStatusesSummaryResponse::default();
+    for status in statuses {
+      res.total += 1;
+      match status.current_state(ts) {
+        ExternalState::Ok => res.ok += 1,
+        ExternalState::Warning => res.warning += 1,
+        ExternalState::Critical => res.critical += 1,
+        ExternalState::Unknown => res.unknown += 1,
+      }
+    }
+    Ok(res)
+  }
+}
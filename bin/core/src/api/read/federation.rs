@@ -0,0 +1,106 @@
+use anyhow::{Context, anyhow};
+use database::mungos::{
+  find::find_collect,
+  mongodb::{bson::doc, options::FindOptions},
+};
+use komodo_client::{
+  api::read::*, entities::federation::FederationPeerStatus,
+};
+use reqwest::StatusCode;
+use resolver_api::Resolve;
+use serror::AddStatusCodeError;
+
+use crate::{
+  helpers::federation::{federated_peers, federation_cache},
+  state::db_client,
+};
+
+use super::ReadArgs;
+
+impl Resolve<ReadArgs> for ListFederationPeers {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<ListFederationPeersResponse> {
+    if !user.admin {
+      return Err(
+        anyhow!("Only admins can list federation peers")
+          .status_code(StatusCode::FORBIDDEN),
+      );
+    }
+    let peers = find_collect(
+      &db_client().federation_peers,
+      None,
+      FindOptions::builder().sort(doc! { "name": 1 }).build(),
+    )
+    .await
+    .context("Failed to query db for federation peers")?;
+    let mut res = Vec::with_capacity(peers.len());
+    for peer in peers {
+      // Peers which haven't been read yet.
+      let status = match federation_cache().get(&peer.id).await {
+        Some(federated) => FederationPeerStatus {
+          name: peer.name,
+          address: peer.address,
+          enabled: peer.enabled,
+          ..federated.status
+        },
+        None => FederationPeerStatus {
+          id: peer.id,
+          name: peer.name,
+          address: peer.address,
+          enabled: peer.enabled,
+          ..Default::default()
+        },
+      };
+      res.push(status);
+    }
+    Ok(res)
+  }
+}
+
+impl Resolve<ReadArgs> for ListFederatedResources {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<ListFederatedResourcesResponse> {
+    if !user.admin {
+      return Err(
+        anyhow!("Only admins can list federated resources")
+          .status_code(StatusCode::FORBIDDEN),
+      );
+    }
+    let resources = federated_peers(self.peer.as_deref())
+      .await
+      .into_iter()
+      .flat_map(|federated| federated.resources)
+      .filter(|resource| {
+        self.resource_type.is_none_or(|resource_type| {
+          resource.resource_type == resource_type
+        })
+      })
+      .collect();
+    Ok(resources)
+  }
+}
+
+impl Resolve<ReadArgs> for ListFederatedAlerts {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<ListFederatedAlertsResponse> {
+    if !user.admin {
+      return Err(
+        anyhow!("Only admins can list federated alerts")
+          .status_code(StatusCode::FORBIDDEN),
+      );
+    }
+    let mut alerts = federated_peers(self.peer.as_deref())
+      .await
+      .into_iter()
+      .flat_map(|federated| federated.alerts)
+      .collect::<Vec<_>>();
+    alerts.sort_by(|a, b| b.alert.ts.cmp(&a.alert.ts));
+    Ok(alerts)
+  }
+}
@@ -6,8 +6,9 @@ use axum::{
 };
 use komodo_client::{
   api::read::*,
+  compatibility::{check_compatible, supported_client_versions},
   entities::{
-    ResourceTarget,
+    ResourceTarget, Version,
     build::Build,
     builder::{Builder, BuilderConfig},
     config::{DockerRegistry, GitProvider},
@@ -18,40 +19,60 @@ use komodo_client::{
     user::User,
   },
 };
+use reqwest::StatusCode;
 use resolver_api::Resolve;
 use response::Response;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use serror::Json;
+use serror::{AddStatusCodeError, Json};
 use typeshare::typeshare;
 use uuid::Uuid;
 
 use crate::{
-  auth::auth_request, config::core_config, helpers::periphery_client,
+  auth::auth_request,
+  config::core_config,
+  helpers::{
+    periphery_client, secret_providers::get_provider_secret_names,
+  },
   resource,
 };
 
 use super::Variant;
 
+mod access_request;
 mod action;
 mod alert;
 mod alerter;
+mod audit;
 mod build;
 mod builder;
 mod deployment;
+mod execution_approval;
+mod execution_schedule;
+mod export;
+mod external_status;
+mod federation;
+mod pattern;
 mod permission;
 mod procedure;
 mod provider;
+mod queued_execution;
 mod repo;
 mod schedule;
+mod schema;
 mod server;
+mod server_profile;
+mod silence;
+mod snapshot;
 mod stack;
+mod swarm;
 mod sync;
 mod tag;
 mod toml;
 mod update;
 mod user;
 mod user_group;
+mod validate;
 mod variable;
 
 pub struct ReadArgs {
@@ -66,6 +87,7 @@ pub struct ReadArgs {
 #[serde(tag = "type", content = "params")]
 enum ReadRequest {
   GetVersion(GetVersion),
+  NegotiateVersion(NegotiateVersion),
   GetCoreInfo(GetCoreInfo),
   ListSecrets(ListSecrets),
   ListGitProvidersFromConfig(ListGitProvidersFromConfig),
@@ -107,12 +129,14 @@ enum ReadRequest {
   GetServer(GetServer),
   GetServerState(GetServerState),
   GetPeripheryVersion(GetPeripheryVersion),
+  GetPeripheryPublicKey(GetPeripheryPublicKey),
   GetServerActionState(GetServerActionState),
   GetHistoricalServerStats(GetHistoricalServerStats),
   ListServers(ListServers),
   ListFullServers(ListFullServers),
   InspectDockerContainer(InspectDockerContainer),
   GetResourceMatchingContainer(GetResourceMatchingContainer),
+  DiffContainerConfig(DiffContainerConfig),
   GetContainerLog(GetContainerLog),
   SearchContainerLog(SearchContainerLog),
   InspectDockerNetwork(InspectDockerNetwork),
@@ -128,6 +152,10 @@ enum ReadRequest {
   ListComposeProjects(ListComposeProjects),
   ListTerminals(ListTerminals),
 
+  // ==== SERVER PROFILE ====
+  GetServerProfile(GetServerProfile),
+  ListServerProfiles(ListServerProfiles),
+
   // ==== SERVER STATS ====
   GetSystemInformation(GetSystemInformation),
   GetSystemStats(GetSystemStats),
@@ -141,6 +169,8 @@ enum ReadRequest {
   GetStackLog(GetStackLog),
   SearchStackLog(SearchStackLog),
   InspectStackContainer(InspectStackContainer),
+  RenderStackCompose(RenderStackCompose),
+  DiffStackFile(DiffStackFile),
   ListStacks(ListStacks),
   ListFullStacks(ListFullStacks),
   ListStackServices(ListStackServices),
@@ -160,6 +190,13 @@ enum ReadRequest {
   ListFullDeployments(ListFullDeployments),
   ListCommonDeploymentExtraArgs(ListCommonDeploymentExtraArgs),
 
+  // ==== SWARM SERVICE ====
+  GetSwarmService(GetSwarmService),
+  GetSwarmServiceActionState(GetSwarmServiceActionState),
+  ListSwarmServices(ListSwarmServices),
+  ListFullSwarmServices(ListFullSwarmServices),
+  ListSwarmStackServices(ListSwarmStackServices),
+
   // ==== BUILD ====
   GetBuildsSummary(GetBuildsSummary),
   GetBuild(GetBuild),
@@ -203,6 +240,11 @@ enum ReadRequest {
   ExportAllResourcesToToml(ExportAllResourcesToToml),
   ExportResourcesToToml(ExportResourcesToToml),
 
+  // ==== EXPORT ====
+  ExportDeploymentToDockerRun(ExportDeploymentToDockerRun),
+  ExportDeploymentToCompose(ExportDeploymentToCompose),
+  ExportServerToCompose(ExportServerToCompose),
+
   // ==== TAG ====
   GetTag(GetTag),
   ListTags(ListTags),
@@ -210,20 +252,79 @@ enum ReadRequest {
   // ==== UPDATE ====
   GetUpdate(GetUpdate),
   ListUpdates(ListUpdates),
+  ListTestReports(ListTestReports),
+  GetResourceMetrics(GetResourceMetrics),
 
   // ==== ALERT ====
   ListAlerts(ListAlerts),
   GetAlert(GetAlert),
 
+  // ==== AUDIT LOG ====
+  ListAuditLog(ListAuditLog),
+
   // ==== VARIABLE ====
   GetVariable(GetVariable),
   ListVariables(ListVariables),
 
+  // ==== EXTERNAL STATUS ====
+  GetExternalStatus(GetExternalStatus),
+  ListExternalStatuses(ListExternalStatuses),
+  GetExternalStatusesSummary(GetExternalStatusesSummary),
+
+  // ==== FEDERATION ====
+  ListFederationPeers(ListFederationPeers),
+  ListFederatedResources(ListFederatedResources),
+  ListFederatedAlerts(ListFederatedAlerts),
+
+  // ==== QUEUED EXECUTION ====
+  GetQueuedExecution(GetQueuedExecution),
+  ListQueuedExecutions(ListQueuedExecutions),
+
+  // ==== EXECUTION APPROVAL ====
+  GetExecutionApproval(GetExecutionApproval),
+  ListExecutionApprovals(ListExecutionApprovals),
+
+  // ==== ACCESS REQUEST ====
+  ListAccessRequests(ListAccessRequests),
+
+  // ==== EXECUTION SCHEDULE ====
+  GetExecutionSchedule(GetExecutionSchedule),
+  ListExecutionSchedules(ListExecutionSchedules),
+  ListExecutionScheduleRuns(ListExecutionScheduleRuns),
+
+  // ==== SILENCE ====
+  ListSilences(ListSilences),
+
+  // ==== SNAPSHOT ====
+  ListSnapshots(ListSnapshots),
+  GetSnapshot(GetSnapshot),
+  DiffSnapshots(DiffSnapshots),
+
   // ==== PROVIDER ====
   GetGitProviderAccount(GetGitProviderAccount),
   ListGitProviderAccounts(ListGitProviderAccounts),
   GetDockerRegistryAccount(GetDockerRegistryAccount),
   ListDockerRegistryAccounts(ListDockerRegistryAccounts),
+
+  // ==== SCHEMA ====
+  GetResourceConfigSchema(GetResourceConfigSchema),
+  ListResourceConfigSchemas(ListResourceConfigSchemas),
+
+  // ==== PATTERN ====
+  PreviewMatches(PreviewMatches),
+
+  // ==== VALIDATE ====
+  ValidateServerConfig(ValidateServerConfig),
+  ValidateStackConfig(ValidateStackConfig),
+  ValidateDeploymentConfig(ValidateDeploymentConfig),
+  ValidateSwarmServiceConfig(ValidateSwarmServiceConfig),
+  ValidateBuildConfig(ValidateBuildConfig),
+  ValidateRepoConfig(ValidateRepoConfig),
+  ValidateProcedureConfig(ValidateProcedureConfig),
+  ValidateActionConfig(ValidateActionConfig),
+  ValidateResourceSyncConfig(ValidateResourceSyncConfig),
+  ValidateBuilderConfig(ValidateBuilderConfig),
+  ValidateAlerterConfig(ValidateAlerterConfig),
 }
 
 pub fn router() -> Router {
@@ -273,6 +374,40 @@ impl Resolve<ReadArgs> for GetVersion {
   }
 }
 
+impl Resolve<ReadArgs> for NegotiateVersion {
+  async fn resolve(
+    self,
+    _: &ReadArgs,
+  ) -> serror::Result<NegotiateVersionResponse> {
+    let core_version = env!("CARGO_PKG_VERSION");
+    Version::try_from(self.client_version.as_str())
+      .context("Invalid client version")
+      .status_code(StatusCode::BAD_REQUEST)?;
+    let core = Version::try_from(core_version)?;
+    let (min_client_version, max_client_version) =
+      supported_client_versions(core);
+    let message =
+      check_compatible(core_version, &self.client_version)
+        .err()
+        .map(|e| format!("{e:#}"));
+    Ok(NegotiateVersionResponse {
+      core_version: core,
+      min_client_version,
+      max_client_version,
+      compatible: message.is_none(),
+      message,
+      deprecations: deprecated_endpoints(),
+    })
+  }
+}
+
+/// Endpoints which still work, but will be removed.
+/// Add endpoints here when they are deprecated,
+/// so clients can move off them before they are removed.
+fn deprecated_endpoints() -> Vec<DeprecatedEndpoint> {
+  Vec::new()
+}
+
 fn core_info() -> &'static GetCoreInfoResponse {
   static CORE_INFO: OnceLock<GetCoreInfoResponse> = OnceLock::new();
   CORE_INFO.get_or_init(|| {
@@ -321,6 +456,7 @@ impl Resolve<ReadArgs> for ListSecrets {
       .keys()
       .cloned()
       .collect::<HashSet<_>>();
+    secrets.extend(get_provider_secret_names().await?);
 
     if let Some(target) = self.target {
       let server_id = match target {
@@ -343,7 +479,8 @@ impl Resolve<ReadArgs> for ListSecrets {
       };
       if let Some(id) = server_id {
         let server = resource::get::<Server>(&id).await?;
-        let more = periphery_client(&server)?
+        let more = periphery_client(&server)
+          .await?
           .request(periphery_client::api::ListSecrets {})
           .await
           .with_context(|| {
@@ -515,7 +652,8 @@ async fn merge_git_providers_for_server(
   server_id: &str,
 ) -> serror::Result<()> {
   let server = resource::get::<Server>(server_id).await?;
-  let more = periphery_client(&server)?
+  let more = periphery_client(&server)
+    .await?
     .request(periphery_client::api::ListGitProviders {})
     .await
     .with_context(|| {
@@ -553,7 +691,8 @@ async fn merge_docker_registries_for_server(
   server_id: &str,
 ) -> serror::Result<()> {
   let server = resource::get::<Server>(server_id).await?;
-  let more = periphery_client(&server)?
+  let more = periphery_client(&server)
+    .await?
     .request(periphery_client::api::ListDockerRegistries {})
     .await
     .with_context(|| {
@@ -0,0 +1,94 @@
+use anyhow::anyhow;
+use komodo_client::{
+  api::read::*,
+  entities::{
+    ResourceTargetVariant, action::Action, alerter::Alerter,
+    build::Build, builder::Builder, deployment::Deployment,
+    permission::PermissionLevel, procedure::Procedure, repo::Repo,
+    server::Server, stack::Stack, swarm::SwarmService,
+    sync::ResourceSync, user::User,
+  },
+};
+use reqwest::StatusCode;
+use resolver_api::Resolve;
+use serror::AddStatusCodeError;
+
+use crate::resource::{
+  KomodoResource, list_full_for_user_using_pattern,
+};
+
+use super::ReadArgs;
+
+impl Resolve<ReadArgs> for PreviewMatches {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<PreviewMatchesResponse> {
+    let pattern = &self.pattern;
+    let matches = match self.resource_type {
+      ResourceTargetVariant::System => {
+        return Err(
+          anyhow!("System can't be matched by pattern")
+            .status_code(StatusCode::BAD_REQUEST),
+        );
+      }
+      ResourceTargetVariant::Server => {
+        preview_matches::<Server>(pattern, user).await
+      }
+      ResourceTargetVariant::Stack => {
+        preview_matches::<Stack>(pattern, user).await
+      }
+      ResourceTargetVariant::Deployment => {
+        preview_matches::<Deployment>(pattern, user).await
+      }
+      ResourceTargetVariant::SwarmService => {
+        preview_matches::<SwarmService>(pattern, user).await
+      }
+      ResourceTargetVariant::Build => {
+        preview_matches::<Build>(pattern, user).await
+      }
+      ResourceTargetVariant::Repo => {
+        preview_matches::<Repo>(pattern, user).await
+      }
+      ResourceTargetVariant::Procedure => {
+        preview_matches::<Procedure>(pattern, user).await
+      }
+      ResourceTargetVariant::Action => {
+        preview_matches::<Action>(pattern, user).await
+      }
+      ResourceTargetVariant::Builder => {
+        preview_matches::<Builder>(pattern, user).await
+      }
+      ResourceTargetVariant::Alerter => {
+        preview_matches::<Alerter>(pattern, user).await
+      }
+      ResourceTargetVariant::ResourceSync => {
+        preview_matches::<ResourceSync>(pattern, user).await
+      }
+    }?;
+    Ok(matches)
+  }
+}
+
+/// Uses the same permission level as batch executions,
+/// so the preview matches exactly what would run.
+async fn preview_matches<T: KomodoResource>(
+  pattern: &str,
+  user: &User,
+) -> anyhow::Result<Vec<PatternMatch>> {
+  let matches = list_full_for_user_using_pattern::<T>(
+    pattern,
+    Default::default(),
+    user,
+    PermissionLevel::Execute.into(),
+    &[],
+  )
+  .await?
+  .into_iter()
+  .map(|resource| PatternMatch {
+    id: resource.id,
+    name: resource.name,
+  })
+  .collect();
+  Ok(matches)
+}
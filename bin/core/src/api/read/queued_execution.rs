@@ -0,0 +1,71 @@
+use anyhow::Context;
+use database::mungos::{
+  by_id::find_one_by_id,
+  find::find_collect,
+  mongodb::{bson::doc, options::FindOptions},
+};
+use komodo_client::{
+  api::read::*,
+  entities::{permission::PermissionLevel, server::Server},
+};
+use resolver_api::Resolve;
+
+use crate::{
+  permission::{get_check_permissions, get_resource_ids_for_user},
+  state::db_client,
+};
+
+use super::ReadArgs;
+
+impl Resolve<ReadArgs> for GetQueuedExecution {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<GetQueuedExecutionResponse> {
+    let queued =
+      find_one_by_id(&db_client().queued_executions, &self.id)
+        .await
+        .context("Failed to query db for queued execution")?
+        .context("No queued execution found with given id")?;
+    get_check_permissions::<Server>(
+      &queued.server_id,
+      user,
+      PermissionLevel::Read.into(),
+    )
+    .await?;
+    Ok(queued)
+  }
+}
+
+impl Resolve<ReadArgs> for ListQueuedExecutions {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<ListQueuedExecutionsResponse> {
+    let mut filter = doc! {};
+    if let Some(server) = &self.server {
+      let server = get_check_permissions::<Server>(
+        server,
+        user,
+        PermissionLevel::Read.into(),
+      )
+      .await?;
+      filter.insert("server_id", server.id);
+    } else if let Some(ids) =
+      get_resource_ids_for_user::<Server>(user).await?
+    {
+      filter.insert("server_id", doc! { "$in": ids });
+    }
+    if !self.include_finished {
+      filter.insert("status", doc! { "$in": ["Queued", "Running"] });
+    }
+    let queued = find_collect(
+      &db_client().queued_executions,
+      filter,
+      FindOptions::builder().sort(doc! { "queued_at": 1 }).build(),
+    )
+    .await
+    .context("Failed to query db for queued executions")?;
+    Ok(queued)
+  }
+}
@@ -140,13 +140,17 @@ impl Resolve<ReadArgs> for GetReposSummary {
         (_, action_states) if action_states.building => {
           res.building += 1;
         }
+        (_, action_states) if action_states.running_pipeline => {
+          res.running_pipeline += 1;
+        }
         (RepoState::Ok, _) => res.ok += 1,
         (RepoState::Failed, _) => res.failed += 1,
         (RepoState::Unknown, _) => res.unknown += 1,
         // will never come off the cache in the building state, since that comes from action states
         (RepoState::Cloning, _)
         | (RepoState::Pulling, _)
-        | (RepoState::Building, _) => {
+        | (RepoState::Building, _)
+        | (RepoState::RunningPipeline, _) => {
           unreachable!()
         }
       }
@@ -167,6 +171,7 @@ impl Resolve<ReadArgs> for GetRepoWebhooksEnabled {
         clone_enabled: false,
         pull_enabled: false,
         build_enabled: false,
+        pipeline_enabled: false,
       });
     };
 
@@ -185,6 +190,7 @@ impl Resolve<ReadArgs> for GetRepoWebhooksEnabled {
         clone_enabled: false,
         pull_enabled: false,
         build_enabled: false,
+        pipeline_enabled: false,
       });
     }
 
@@ -197,6 +203,7 @@ impl Resolve<ReadArgs> for GetRepoWebhooksEnabled {
         clone_enabled: false,
         pull_enabled: false,
         build_enabled: false,
+        pipeline_enabled: false,
       });
     };
 
@@ -228,10 +235,13 @@ impl Resolve<ReadArgs> for GetRepoWebhooksEnabled {
       format!("{host}/listener/github/repo/{}/pull", repo.id);
     let build_url =
       format!("{host}/listener/github/repo/{}/build", repo.id);
+    let pipeline_url =
+      format!("{host}/listener/github/repo/{}/pipeline", repo.id);
 
     let mut clone_enabled = false;
     let mut pull_enabled = false;
     let mut build_enabled = false;
+    let mut pipeline_enabled = false;
 
     for webhook in webhooks {
       if !webhook.active {
@@ -246,6 +256,9 @@ impl Resolve<ReadArgs> for GetRepoWebhooksEnabled {
       if webhook.config.url == build_url {
         build_enabled = true
       }
+      if webhook.config.url == pipeline_url {
+        pipeline_enabled = true
+      }
     }
 
     Ok(GetRepoWebhooksEnabledResponse {
@@ -253,6 +266,7 @@ impl Resolve<ReadArgs> for GetRepoWebhooksEnabled {
       clone_enabled,
       pull_enabled,
       build_enabled,
+      pipeline_enabled,
     })
   }
 }
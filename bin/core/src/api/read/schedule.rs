@@ -4,6 +4,7 @@ use komodo_client::{
   entities::{
     ResourceTarget,
     action::Action,
+    build::Build,
     permission::PermissionLevel,
     procedure::Procedure,
     resource::{ResourceQuery, TemplatesQueryBehavior},
@@ -26,12 +27,13 @@ impl Resolve<ReadArgs> for ListSchedules {
     args: &ReadArgs,
   ) -> serror::Result<Vec<Schedule>> {
     let all_tags = get_all_tags(None).await?;
-    let (actions, procedures) = tokio::try_join!(
+    let (actions, procedures, builds) = tokio::try_join!(
       list_full_for_user::<Action>(
         ResourceQuery {
           names: Default::default(),
           templates: TemplatesQueryBehavior::Include,
           tag_behavior: self.tag_behavior,
+          metadata: Default::default(),
           tags: self.tags.clone(),
           specific: Default::default(),
         },
@@ -44,6 +46,20 @@ impl Resolve<ReadArgs> for ListSchedules {
           names: Default::default(),
           templates: TemplatesQueryBehavior::Include,
           tag_behavior: self.tag_behavior,
+          metadata: Default::default(),
+          tags: self.tags.clone(),
+          specific: Default::default(),
+        },
+        &args.user,
+        PermissionLevel::Read.into(),
+        &all_tags,
+      ),
+      list_full_for_user::<Build>(
+        ResourceQuery {
+          names: Default::default(),
+          templates: TemplatesQueryBehavior::Include,
+          tag_behavior: self.tag_behavior,
+          metadata: Default::default(),
           tags: self.tags.clone(),
           specific: Default::default(),
         },
@@ -93,13 +109,37 @@ impl Resolve<ReadArgs> for ListSchedules {
         schedule_error,
       }
     });
-    let (actions, procedures) =
-      tokio::join!(join_all(actions), join_all(procedures));
+    let builds = builds.into_iter().map(async |build| {
+      let (next_scheduled_run, schedule_error) =
+        get_schedule_item_info(&ResourceTarget::Build(
+          build.id.clone(),
+        ));
+      let last_run_at =
+        get_last_run_at::<Build>(&build.id).await.unwrap_or(None);
+      Schedule {
+        target: ResourceTarget::Build(build.id),
+        name: build.name,
+        enabled: build.config.schedule_enabled,
+        schedule_format: build.config.schedule_format,
+        schedule: build.config.schedule,
+        schedule_timezone: build.config.schedule_timezone,
+        tags: build.tags,
+        last_run_at,
+        next_scheduled_run,
+        schedule_error,
+      }
+    });
+    let (actions, procedures, builds) = tokio::join!(
+      join_all(actions),
+      join_all(procedures),
+      join_all(builds)
+    );
 
     Ok(
       actions
         .into_iter()
         .chain(procedures)
+        .chain(builds)
         .filter(|s| !s.schedule.is_empty())
         .collect(),
     )
@@ -0,0 +1,49 @@
+use anyhow::anyhow;
+use komodo_client::{
+  api::read::*,
+  entities::ResourceTargetVariant,
+  schema::{RESOURCE_TYPES, resource_config_schema},
+};
+use reqwest::StatusCode;
+use resolver_api::Resolve;
+use serror::AddStatusCodeError;
+
+use super::ReadArgs;
+
+impl Resolve<ReadArgs> for GetResourceConfigSchema {
+  async fn resolve(
+    self,
+    _: &ReadArgs,
+  ) -> serror::Result<GetResourceConfigSchemaResponse> {
+    if self.resource_type == ResourceTargetVariant::System {
+      return Err(
+        anyhow!("System has no config")
+          .status_code(StatusCode::BAD_REQUEST),
+      );
+    }
+    Ok(config_schema(self.resource_type)?)
+  }
+}
+
+impl Resolve<ReadArgs> for ListResourceConfigSchemas {
+  async fn resolve(
+    self,
+    _: &ReadArgs,
+  ) -> serror::Result<ListResourceConfigSchemasResponse> {
+    let schemas = RESOURCE_TYPES
+      .into_iter()
+      .map(config_schema)
+      .collect::<anyhow::Result<_>>()?;
+    Ok(schemas)
+  }
+}
+
+fn config_schema(
+  resource_type: ResourceTargetVariant,
+) -> anyhow::Result<ResourceConfigSchema> {
+  Ok(ResourceConfigSchema {
+    resource_type,
+    version: env!("CARGO_PKG_VERSION").to_string(),
+    schema: resource_config_schema(resource_type)?,
+  })
+}
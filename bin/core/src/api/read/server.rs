@@ -16,6 +16,7 @@ use komodo_client::{
   api::read::*,
   entities::{
     ResourceTarget,
+    builder::{Builder, BuilderConfig},
     deployment::Deployment,
     docker::{
       container::{
@@ -36,19 +37,25 @@ use komodo_client::{
     update::Log,
   },
 };
-use periphery_client::api::{
-  self as periphery,
-  container::InspectContainer,
-  image::{ImageHistory, InspectImage},
-  network::InspectNetwork,
-  volume::InspectVolume,
+use periphery_client::{
+  api::{
+    self as periphery,
+    container::InspectContainer,
+    image::{ImageHistory, InspectImage},
+    network::InspectNetwork,
+    volume::InspectVolume,
+  },
+  signing::encode_public_key,
 };
 use resolver_api::Resolve;
 use tokio::sync::Mutex;
 
 use crate::{
   helpers::{
-    periphery_client,
+    container_diff::{
+      diff_deployment_container, diff_stack_container,
+    },
+    periphery_client, periphery_signing_key,
     query::{get_all_tags, get_system_info},
   },
   permission::get_check_permissions,
@@ -122,6 +129,55 @@ impl Resolve<ReadArgs> for GetPeripheryVersion {
   }
 }
 
+impl Resolve<ReadArgs> for GetPeripheryPublicKey {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<GetPeripheryPublicKeyResponse> {
+    let id = match self.target {
+      ResourceTarget::Server(server) => {
+        get_check_permissions::<Server>(
+          &server,
+          user,
+          PermissionLevel::Read.into(),
+        )
+        .await?
+        .id
+      }
+      ResourceTarget::Builder(builder) => {
+        let builder = get_check_permissions::<Builder>(
+          &builder,
+          user,
+          PermissionLevel::Read.into(),
+        )
+        .await?;
+        // Server Builders use the key of the attached Server.
+        match builder.config {
+          BuilderConfig::Url(_) | BuilderConfig::Aws(_) => builder.id,
+          BuilderConfig::Server(_) => {
+            return Err(
+              anyhow!(
+                "Server Builders sign requests with the key of the attached Server"
+              )
+              .into(),
+            );
+          }
+        }
+      }
+      _ => {
+        return Err(
+          anyhow!("Target must be a Server or Builder").into(),
+        );
+      }
+    };
+    let signing_key = periphery_signing_key(&id)
+      .context("Core has no periphery_signing_secret configured")?;
+    Ok(GetPeripheryPublicKeyResponse {
+      public_key: encode_public_key(&signing_key.verifying_key()),
+    })
+  }
+}
+
 impl Resolve<ReadArgs> for GetServer {
   async fn resolve(
     self,
@@ -289,7 +345,8 @@ impl Resolve<ReadArgs> for ListSystemProcesses {
         cached.0.clone()
       }
       _ => {
-        let stats = periphery_client(&server)?
+        let stats = periphery_client(&server)
+          .await?
           .request(periphery::stats::GetSystemProcesses {})
           .await?;
         lock.insert(
@@ -478,7 +535,8 @@ impl Resolve<ReadArgs> for InspectDockerContainer {
         .into(),
       );
     }
-    let res = periphery_client(&server)?
+    let res = periphery_client(&server)
+      .await?
       .request(InspectContainer {
         name: self.container,
       })
@@ -506,7 +564,8 @@ impl Resolve<ReadArgs> for GetContainerLog {
       PermissionLevel::Read.logs(),
     )
     .await?;
-    let res = periphery_client(&server)?
+    let res = periphery_client(&server)
+      .await?
       .request(periphery::container::GetContainerLog {
         name: container,
         tail: cmp::min(tail, MAX_LOG_LENGTH),
@@ -537,7 +596,8 @@ impl Resolve<ReadArgs> for SearchContainerLog {
       PermissionLevel::Read.logs(),
     )
     .await?;
-    let res = periphery_client(&server)?
+    let res = periphery_client(&server)
+      .await?
       .request(periphery::container::GetContainerLogSearch {
         name: container,
         terms,
@@ -612,6 +672,49 @@ impl Resolve<ReadArgs> for GetResourceMatchingContainer {
   }
 }
 
+impl Resolve<ReadArgs> for DiffContainerConfig {
+  async fn resolve(
+    self,
+    args: &ReadArgs,
+  ) -> serror::Result<DiffContainerConfigResponse> {
+    match self.target {
+      ResourceTarget::Deployment(deployment) => {
+        // Inspect handles the permission check
+        let container = InspectDeploymentContainer {
+          deployment: deployment.clone(),
+        }
+        .resolve(args)
+        .await?;
+        let deployment =
+          resource::get::<Deployment>(&deployment).await?;
+        diff_deployment_container(deployment, &container)
+          .await
+          .map_err(Into::into)
+      }
+      ResourceTarget::Stack(stack) => {
+        let service = self
+          .service
+          .context("Must provide 'service' to diff a Stack container")?;
+        // Inspect handles the permission check
+        let container = InspectStackContainer {
+          stack: stack.clone(),
+          service: service.clone(),
+        }
+        .resolve(args)
+        .await?;
+        let stack = resource::get::<Stack>(&stack).await?;
+        Ok(diff_stack_container(&stack, &service, &container))
+      }
+      _ => Err(
+        anyhow!(
+          "DiffContainerConfig only supports Deployment and Stack targets"
+        )
+        .into(),
+      ),
+    }
+  }
+}
+
 impl Resolve<ReadArgs> for ListDockerNetworks {
   async fn resolve(
     self,
@@ -657,7 +760,8 @@ impl Resolve<ReadArgs> for InspectDockerNetwork {
         .into(),
       );
     }
-    let res = periphery_client(&server)?
+    let res = periphery_client(&server)
+      .await?
       .request(InspectNetwork { name: self.network })
       .await?;
     Ok(res)
@@ -706,7 +810,8 @@ impl Resolve<ReadArgs> for InspectDockerImage {
           .into(),
       );
     }
-    let res = periphery_client(&server)?
+    let res = periphery_client(&server)
+      .await?
       .request(InspectImage { name: self.image })
       .await?;
     Ok(res)
@@ -736,7 +841,8 @@ impl Resolve<ReadArgs> for ListDockerImageHistory {
         .into(),
       );
     }
-    let res = periphery_client(&server)?
+    let res = periphery_client(&server)
+      .await?
       .request(ImageHistory { name: self.image })
       .await?;
     Ok(res)
@@ -785,7 +891,8 @@ impl Resolve<ReadArgs> for InspectDockerVolume {
           .into(),
       );
     }
-    let res = periphery_client(&server)?
+    let res = periphery_client(&server)
+      .await?
       .request(InspectVolume { name: self.volume })
       .await?;
     Ok(res)
@@ -865,7 +972,8 @@ impl Resolve<ReadArgs> for ListTerminals {
     let cache = terminals_cache().get_or_insert(server.id.clone());
     let mut cache = cache.lock().await;
     if self.fresh || komodo_timestamp() > cache.ttl {
-      cache.list = periphery_client(&server)?
+      cache.list = periphery_client(&server)
+        .await?
         .request(periphery_client::api::terminal::ListTerminals {})
         .await
         .context("Failed to get fresh terminal list")?;
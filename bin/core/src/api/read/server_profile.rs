@@ -0,0 +1,36 @@
+use anyhow::Context;
+use database::mungos::{
+  find::find_collect,
+  mongodb::{bson::doc, options::FindOptions},
+};
+use komodo_client::api::read::*;
+use resolver_api::Resolve;
+
+use crate::{helpers::query::get_server_profile, state::db_client};
+
+use super::ReadArgs;
+
+impl Resolve<ReadArgs> for GetServerProfile {
+  async fn resolve(
+    self,
+    _: &ReadArgs,
+  ) -> serror::Result<GetServerProfileResponse> {
+    Ok(get_server_profile(&self.profile).await?)
+  }
+}
+
+impl Resolve<ReadArgs> for ListServerProfiles {
+  async fn resolve(
+    self,
+    _: &ReadArgs,
+  ) -> serror::Result<ListServerProfilesResponse> {
+    let profiles = find_collect(
+      &db_client().server_profiles,
+      None,
+      FindOptions::builder().sort(doc! { "name": 1 }).build(),
+    )
+    .await
+    .context("Failed to query db for server profiles")?;
+    Ok(profiles)
+  }
+}
@@ -0,0 +1,29 @@
+use anyhow::Context;
+use database::mungos::{
+  find::find_collect,
+  mongodb::{bson::doc, options::FindOptions},
+};
+use komodo_client::{api::read::*, entities::komodo_timestamp};
+use resolver_api::Resolve;
+
+use crate::state::db_client;
+
+use super::ReadArgs;
+
+impl Resolve<ReadArgs> for ListSilences {
+  async fn resolve(
+    self,
+    _: &ReadArgs,
+  ) -> serror::Result<ListSilencesResponse> {
+    let filter = (!self.include_expired)
+      .then(|| doc! { "end_ts": { "$gt": komodo_timestamp() } });
+    let silences = find_collect(
+      &db_client().silences,
+      filter,
+      FindOptions::builder().sort(doc! { "start_ts": -1 }).build(),
+    )
+    .await
+    .context("Failed to query db for silences")?;
+    Ok(silences)
+  }
+}
@@ -0,0 +1,110 @@
+use anyhow::{Context, anyhow};
+use database::mungos::{
+  by_id::find_one_by_id,
+  find::find_collect,
+  mongodb::{bson::doc, options::FindOptions},
+};
+use komodo_client::{
+  api::read::*,
+  entities::{komodo_timestamp, snapshot::Snapshot},
+};
+use reqwest::StatusCode;
+use resolver_api::Resolve;
+use serror::AddStatusCodeError;
+
+use crate::{
+  state::db_client,
+  sync::snapshot::{
+    SnapshotDiff, current_state_toml, diff_snapshots,
+  },
+};
+
+use super::ReadArgs;
+
+impl Resolve<ReadArgs> for ListSnapshots {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<ListSnapshotsResponse> {
+    if !user.admin {
+      return Err(
+        anyhow!("Only admins can list snapshots")
+          .status_code(StatusCode::FORBIDDEN),
+      );
+    }
+    let snapshots = find_collect(
+      &db_client().snapshots,
+      None,
+      FindOptions::builder()
+        .sort(doc! { "ts": -1 })
+        // The contents can be large, and aren't needed for the list
+        .projection(doc! { "toml": 0 })
+        .build(),
+    )
+    .await
+    .context("Failed to query db for snapshots")?
+    .into_iter()
+    .map(Into::into)
+    .collect();
+    Ok(snapshots)
+  }
+}
+
+impl Resolve<ReadArgs> for GetSnapshot {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<GetSnapshotResponse> {
+    if !user.admin {
+      return Err(
+        anyhow!("Only admins can get snapshots")
+          .status_code(StatusCode::FORBIDDEN),
+      );
+    }
+    Ok(get_snapshot(&self.id).await?)
+  }
+}
+
+impl Resolve<ReadArgs> for DiffSnapshots {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<DiffSnapshotsResponse> {
+    if !user.admin {
+      return Err(
+        anyhow!("Only admins can diff snapshots")
+          .status_code(StatusCode::FORBIDDEN),
+      );
+    }
+
+    let from = get_snapshot(&self.from).await?;
+    let (to_ts, to_toml) = match &self.to {
+      Some(to) => {
+        let to = get_snapshot(to).await?;
+        (to.ts, to.toml)
+      }
+      None => (komodo_timestamp(), current_state_toml(user).await?),
+    };
+
+    let SnapshotDiff {
+      resource_updates,
+      variable_updates,
+      user_group_updates,
+    } = diff_snapshots(&from.toml, &to_toml)?;
+
+    Ok(DiffSnapshotsResponse {
+      from_ts: from.ts,
+      to_ts,
+      resource_updates,
+      variable_updates,
+      user_group_updates,
+    })
+  }
+}
+
+async fn get_snapshot(id: &str) -> anyhow::Result<Snapshot> {
+  find_one_by_id(&db_client().snapshots, id)
+    .await
+    .context("Failed to query db for snapshot")?
+    .with_context(|| format!("No snapshot found with id {id}"))
+}
@@ -1,28 +1,45 @@
 use std::collections::HashSet;
 
 use anyhow::{Context, anyhow};
+use interpolate::Interpolator;
 use komodo_client::{
   api::read::*,
   entities::{
     config::core::CoreConfig,
     docker::container::Container,
     permission::PermissionLevel,
+    repo::Repo,
     server::{Server, ServerState},
     stack::{Stack, StackActionState, StackListItem, StackState},
   },
 };
 use periphery_client::api::{
-  compose::{GetComposeLog, GetComposeLogSearch},
+  compose::{
+    ComposeRender, ComposeRenderResponse, GetComposeLog,
+    GetComposeLogSearch,
+  },
   container::InspectContainer,
 };
 use resolver_api::Resolve;
+use similar::TextDiff;
 
 use crate::{
   config::core_config,
-  helpers::{periphery_client, query::get_all_tags},
+  helpers::{
+    periphery_client,
+    query::{
+      VariablesAndSecrets, get_all_tags, get_variables_and_secrets,
+    },
+    stack_git_token, stack_oci_token,
+  },
   permission::get_check_permissions,
   resource,
-  stack::get_stack_and_server,
+  stack::{
+    get_stack_and_server,
+    includes::{resolve_remote_includes, stack_is_ui_defined},
+    latest_stack_files,
+    overrides::resolve_compose_overrides,
+  },
   state::{
     action_states, github_client, server_status_cache,
     stack_status_cache,
@@ -89,7 +106,8 @@ impl Resolve<ReadArgs> for GetStackLog {
       true,
     )
     .await?;
-    let res = periphery_client(&server)?
+    let res = periphery_client(&server)
+      .await?
       .request(GetComposeLog {
         project: stack.project_name(false),
         services,
@@ -122,7 +140,8 @@ impl Resolve<ReadArgs> for SearchStackLog {
       true,
     )
     .await?;
-    let res = periphery_client(&server)?
+    let res = periphery_client(&server)
+      .await?
       .request(GetComposeLogSearch {
         project: stack.project_name(false),
         services,
@@ -184,13 +203,211 @@ impl Resolve<ReadArgs> for InspectStackContainer {
         "No service found matching '{service}'. Was the stack last deployed manually?"
       ).into());
     };
-    let res = periphery_client(&server)?
+    let res = periphery_client(&server)
+      .await?
       .request(InspectContainer { name })
       .await?;
     Ok(res)
   }
 }
 
+impl Resolve<ReadArgs> for RenderStackCompose {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<RenderStackComposeResponse> {
+    // Execute, as the stack files are fetched on the server.
+    let (mut stack, server) = get_stack_and_server(
+      &self.stack,
+      user,
+      PermissionLevel::Execute.into(),
+      true,
+    )
+    .await?;
+
+    let mut repo = if !stack.config.files_on_host
+      && !stack.config.linked_repo.is_empty()
+    {
+      resource::get::<Repo>(&stack.config.linked_repo)
+        .await?
+        .into()
+    } else {
+      None
+    };
+
+    let git_token =
+      stack_git_token(&mut stack, repo.as_mut()).await?;
+    let oci_token = stack_oci_token(&stack).await?;
+
+    let mut logs = Vec::new();
+
+    let secret_replacers = if !stack.config.skip_secret_interp {
+      let VariablesAndSecrets { variables, secrets } =
+        get_variables_and_secrets().await?;
+
+      let mut interpolator =
+        Interpolator::new(Some(&variables), &secrets)
+          .with_server(&server)?;
+
+      interpolator.interpolate_stack(&mut stack)?;
+      if let Some(repo) = repo.as_mut()
+        && !repo.config.skip_secret_interp
+      {
+        interpolator.interpolate_repo(repo)?;
+      }
+      interpolator.push_logs(&mut logs);
+
+      interpolator.secret_replacers
+    } else {
+      Default::default()
+    };
+
+    let includes = if stack.config.cache_remote_includes
+      && stack_is_ui_defined(&stack)
+    {
+      resolve_remote_includes(&mut stack, &mut logs)
+        .await
+        .context("Failed to cache remote includes")?
+    } else {
+      Vec::new()
+    };
+
+    let overrides = resolve_compose_overrides(&mut stack, &mut logs)
+      .await
+      .context("Failed to get compose overrides")?;
+
+    let ComposeRenderResponse {
+      logs: render_logs,
+      compose_config,
+      command,
+    } = periphery_client(&server)
+      .await?
+      .request(ComposeRender {
+        stack,
+        services: self.services,
+        repo,
+        git_token,
+        oci_token,
+        replacers: secret_replacers.into_iter().collect(),
+        include_files: includes
+          .iter()
+          .map(|include| include.file.clone())
+          .chain(overrides)
+          .collect(),
+      })
+      .await
+      .context("Failed to render compose config on periphery")?;
+
+    logs.extend(render_logs);
+
+    let errors = logs
+      .iter()
+      .filter(|log| !log.success)
+      .map(|log| {
+        if log.stderr.trim().is_empty() {
+          log.stdout.trim().to_string()
+        } else {
+          log.stderr.trim().to_string()
+        }
+      })
+      .collect();
+
+    Ok(RenderStackComposeResponse {
+      valid: compose_config.is_some(),
+      compose_config,
+      command,
+      errors,
+      logs,
+    })
+  }
+}
+
+impl Resolve<ReadArgs> for DiffStackFile {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<DiffStackFileResponse> {
+    let stack = get_check_permissions::<Stack>(
+      &self.stack,
+      user,
+      PermissionLevel::Read.into(),
+    )
+    .await?;
+
+    let latest_files = latest_stack_files(&stack);
+    let deployed_files =
+      stack.info.deployed_contents.clone().unwrap_or_default();
+
+    if let Some(path) = &self.path
+      && !latest_files
+        .iter()
+        .chain(&deployed_files)
+        .any(|file| &file.path == path)
+    {
+      return Err(anyhow!("Stack has no file at path {path}").into());
+    }
+    let included = |file_path: &String| {
+      self.path.as_ref().is_none_or(|path| path == file_path)
+    };
+
+    let mut diffs = Vec::new();
+
+    for file in latest_files.iter().filter(|f| included(&f.path)) {
+      let deployed =
+        deployed_files.iter().find(|f| f.path == file.path);
+      if deployed.is_some_and(|d| d.contents == file.contents) {
+        continue;
+      }
+      diffs.push(unified_diff(
+        &file.path,
+        deployed.map(|d| d.contents.as_str()),
+        Some(&file.contents),
+      ));
+    }
+
+    // UI defined stacks only have the compose file in the latest files.
+    if stack.info.remote_contents.is_some() {
+      for file in deployed_files.iter().filter(|f| {
+        included(&f.path)
+          && !latest_files.iter().any(|l| l.path == f.path)
+      }) {
+        diffs.push(unified_diff(
+          &file.path,
+          Some(&file.contents),
+          None,
+        ));
+      }
+    }
+
+    Ok(diffs)
+  }
+}
+
+/// Missing contents are diffed as empty, with a `/dev/null` header.
+fn unified_diff(
+  path: &str,
+  deployed: Option<&str>,
+  latest: Option<&str>,
+) -> StackFileUnifiedDiff {
+  let header = |prefix: &str, contents: Option<&str>| {
+    contents
+      .map(|_| format!("{prefix}/{path}"))
+      .unwrap_or_else(|| String::from("/dev/null"))
+  };
+  let diff = TextDiff::from_lines(
+    deployed.unwrap_or_default(),
+    latest.unwrap_or_default(),
+  )
+  .unified_diff()
+  .context_radius(3)
+  .header(&header("a", deployed), &header("b", latest))
+  .to_string();
+  StackFileUnifiedDiff {
+    path: path.to_string(),
+    diff,
+  }
+}
+
 impl Resolve<ReadArgs> for ListCommonStackExtraArgs {
   async fn resolve(
     self,
@@ -232,7 +232,8 @@ impl Resolve<ReadArgs> for ExportResourcesToToml {
           if sync.config.file_contents.is_empty()
             && (sync.config.files_on_host
               || !sync.config.repo.is_empty()
-              || !sync.config.linked_repo.is_empty())
+              || !sync.config.linked_repo.is_empty()
+              || sync.config.artifact_source())
           {
             ResourceSync::replace_ids(&mut sync);
             res.resource_syncs.push(convert_resource::<ResourceSync>(
@@ -363,7 +364,9 @@ impl Resolve<ReadArgs> for ExportResourcesToToml {
             &id_to_tags,
           ));
         }
-        ResourceTarget::System(_) => continue,
+        // Swarm Services are not yet supported in Resource Syncs
+        ResourceTarget::SwarmService(_)
+        | ResourceTarget::System(_) => continue,
       };
     }
 
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use anyhow::{Context, anyhow};
 use database::mungos::{
@@ -7,7 +7,11 @@ use database::mungos::{
   mongodb::{bson::doc, options::FindOptions},
 };
 use komodo_client::{
-  api::read::{GetUpdate, ListUpdates, ListUpdatesResponse},
+  api::read::{
+    FlakyTestCase, GetResourceMetrics, GetResourceMetricsResponse,
+    GetUpdate, ListTestReports, ListTestReportsResponse, ListUpdates,
+    ListUpdatesResponse, TestReportRun,
+  },
   entities::{
     ResourceTarget,
     action::Action,
@@ -20,6 +24,7 @@ use komodo_client::{
     repo::Repo,
     server::Server,
     stack::Stack,
+    swarm::SwarmService,
     sync::ResourceSync,
     update::{Update, UpdateListItem},
     user::User,
@@ -29,6 +34,7 @@ use resolver_api::Resolve;
 
 use crate::{
   config::core_config,
+  helpers::execution_metrics::aggregate_execution_metrics,
   permission::{get_check_permissions, get_resource_ids_for_user},
   state::db_client,
 };
@@ -36,6 +42,7 @@ use crate::{
 use super::ReadArgs;
 
 const UPDATES_PER_PAGE: i64 = 100;
+const DEFAULT_TEST_REPORT_RUNS: i64 = 20;
 
 impl Resolve<ReadArgs> for ListUpdates {
   async fn resolve(
@@ -64,6 +71,17 @@ impl Resolve<ReadArgs> for ListUpdates {
           })
           .unwrap_or_else(|| doc! { "target.type": "Deployment" });
 
+      let swarm_service_query = get_resource_ids_for_user::<
+        SwarmService,
+      >(user)
+      .await?
+      .map(|ids| {
+        doc! {
+          "target.type": "SwarmService", "target.id": { "$in": ids }
+        }
+      })
+      .unwrap_or_else(|| doc! { "target.type": "SwarmService" });
+
       let stack_query = get_resource_ids_for_user::<Stack>(user)
         .await?
         .map(|ids| {
@@ -144,6 +162,7 @@ impl Resolve<ReadArgs> for ListUpdates {
         "$or": [
           server_query,
           deployment_query,
+          swarm_service_query,
           stack_query,
           build_query,
           repo_query,
@@ -152,6 +171,7 @@ impl Resolve<ReadArgs> for ListUpdates {
           alerter_query,
           builder_query,
           resource_sync_query,
+          doc! { "target.type": "System", "operator": &user.id },
         ]
       });
       query.into()
@@ -196,6 +216,7 @@ impl Resolve<ReadArgs> for ListUpdates {
         status: u.status,
         version: u.version,
         other_data: u.other_data,
+        rerun_of: u.rerun_of,
       }
     })
     .collect::<Vec<_>>();
@@ -223,6 +244,9 @@ impl Resolve<ReadArgs> for GetUpdate {
       return Ok(update);
     }
     match &update.target {
+      // Users can view the system updates they started,
+      // such as batch executions.
+      ResourceTarget::System(_) if update.operator == user.id => {}
       ResourceTarget::System(_) => {
         return Err(
           anyhow!("user must be admin to view system updates").into(),
@@ -284,6 +308,14 @@ impl Resolve<ReadArgs> for GetUpdate {
         )
         .await?;
       }
+      ResourceTarget::SwarmService(id) => {
+        get_check_permissions::<SwarmService>(
+          id,
+          user,
+          PermissionLevel::Read.into(),
+        )
+        .await?;
+      }
       ResourceTarget::Action(id) => {
         get_check_permissions::<Action>(
           id,
@@ -312,3 +344,160 @@ impl Resolve<ReadArgs> for GetUpdate {
     Ok(update)
   }
 }
+
+impl Resolve<ReadArgs> for ListTestReports {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<ListTestReportsResponse> {
+    let (target_type, id) = match &self.target {
+      ResourceTarget::Build(id) => {
+        get_check_permissions::<Build>(
+          id,
+          user,
+          PermissionLevel::Read.into(),
+        )
+        .await?;
+        ("Build", id)
+      }
+      ResourceTarget::Repo(id) => {
+        get_check_permissions::<Repo>(
+          id,
+          user,
+          PermissionLevel::Read.into(),
+        )
+        .await?;
+        ("Repo", id)
+      }
+      _ => {
+        return Err(
+          anyhow!("Only Builds and Repos have test reports").into(),
+        );
+      }
+    };
+
+    let runs = find_collect(
+      &db_client().updates,
+      doc! {
+        "target.type": target_type,
+        "target.id": id,
+        "test_reports.0": { "$exists": true },
+      },
+      FindOptions::builder()
+        .sort(doc! { "start_ts": -1 })
+        .limit(self.limit.unwrap_or(DEFAULT_TEST_REPORT_RUNS))
+        .build(),
+    )
+    .await
+    .context("failed to pull updates from db")?
+    .into_iter()
+    .map(|update| TestReportRun {
+      update_id: update.id,
+      operation: update.operation,
+      start_ts: update.start_ts,
+      commit_hash: update.commit_hash,
+      reports: update.test_reports,
+    })
+    .collect::<Vec<_>>();
+
+    // report name -> (runs, test case name -> failures)
+    let mut cases = HashMap::<&str, (i64, HashMap<&str, i64>)>::new();
+    for report in runs.iter().flat_map(|run| &run.reports) {
+      let (report_runs, failures) =
+        cases.entry(report.name.as_str()).or_default();
+      *report_runs += 1;
+      // Only count each test case once per report
+      let failed = report
+        .failures
+        .iter()
+        .map(|failure| failure.name.as_str())
+        .collect::<HashSet<_>>();
+      for name in failed {
+        *failures.entry(name).or_default() += 1;
+      }
+    }
+
+    let mut flaky = cases
+      .into_iter()
+      .flat_map(|(report, (runs, failures))| {
+        failures
+          .into_iter()
+          .filter(move |(_, failures)| *failures < runs)
+          .map(move |(name, failures)| FlakyTestCase {
+            report: report.to_string(),
+            name: name.to_string(),
+            failures,
+            runs,
+          })
+      })
+      .collect::<Vec<_>>();
+    flaky.sort_by(|a, b| {
+      b.failures
+        .cmp(&a.failures)
+        .then_with(|| a.report.cmp(&b.report))
+        .then_with(|| a.name.cmp(&b.name))
+    });
+
+    Ok(ListTestReportsResponse { runs, flaky })
+  }
+}
+
+impl Resolve<ReadArgs> for GetResourceMetrics {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<GetResourceMetricsResponse> {
+    let read = PermissionLevel::Read.into();
+    // Normalize to the resource id, names are also accepted
+    let target = match &self.target {
+      ResourceTarget::Deployment(id) => ResourceTarget::Deployment(
+        get_check_permissions::<Deployment>(id, user, read)
+          .await?
+          .id,
+      ),
+      ResourceTarget::Stack(id) => ResourceTarget::Stack(
+        get_check_permissions::<Stack>(id, user, read).await?.id,
+      ),
+      ResourceTarget::SwarmService(id) => {
+        ResourceTarget::SwarmService(
+          get_check_permissions::<SwarmService>(id, user, read)
+            .await?
+            .id,
+        )
+      }
+      ResourceTarget::Build(id) => ResourceTarget::Build(
+        get_check_permissions::<Build>(id, user, read).await?.id,
+      ),
+      ResourceTarget::Repo(id) => ResourceTarget::Repo(
+        get_check_permissions::<Repo>(id, user, read).await?.id,
+      ),
+      ResourceTarget::Procedure(id) => ResourceTarget::Procedure(
+        get_check_permissions::<Procedure>(id, user, read).await?.id,
+      ),
+      ResourceTarget::Action(id) => ResourceTarget::Action(
+        get_check_permissions::<Action>(id, user, read).await?.id,
+      ),
+      ResourceTarget::ResourceSync(id) => {
+        ResourceTarget::ResourceSync(
+          get_check_permissions::<ResourceSync>(id, user, read)
+            .await?
+            .id,
+        )
+      }
+      _ => {
+        return Err(
+          anyhow!(
+            "Only Deployments, Stacks, Swarm Services, Builds, Repos, Procedures, Actions and Resource Syncs have execution metrics"
+          )
+          .into(),
+        );
+      }
+    };
+    let metrics =
+      aggregate_execution_metrics(Some(&target), self.days)
+        .await?
+        .remove(&target)
+        .unwrap_or_default();
+    Ok(metrics)
+  }
+}
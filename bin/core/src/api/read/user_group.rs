@@ -1,6 +1,6 @@
 use std::str::FromStr;
 
-use anyhow::Context;
+use anyhow::{Context, anyhow};
 use database::mungos::{
   find::find_collect,
   mongodb::{
@@ -11,7 +11,9 @@ use database::mungos::{
 use komodo_client::api::read::*;
 use resolver_api::Resolve;
 
-use crate::state::db_client;
+use crate::{
+  helpers::query::get_user_user_group_ids, state::db_client,
+};
 
 use super::ReadArgs;
 
@@ -20,21 +22,25 @@ impl Resolve<ReadArgs> for GetUserGroup {
     self,
     ReadArgs { user }: &ReadArgs,
   ) -> serror::Result<GetUserGroupResponse> {
-    let mut filter = match ObjectId::from_str(&self.user_group) {
+    let filter = match ObjectId::from_str(&self.user_group) {
       Ok(id) => doc! { "_id": id },
       Err(_) => doc! { "name": &self.user_group },
     };
-    // Don't allow non admin users to get UserGroups they aren't a part of.
-    if !user.admin {
-      // Filter for only UserGroups which contain the users id
-      filter.insert("users", &user.id);
-    }
     let res = db_client()
       .user_groups
       .find_one(filter)
       .await
       .context("failed to query db for user groups")?
       .context("no UserGroup found with given name or id")?;
+    // Don't allow non admin users to get UserGroups they aren't a part of,
+    // directly or through a nested group.
+    if !user.admin
+      && !get_user_user_group_ids(&user.id).await?.contains(&res.id)
+    {
+      return Err(
+        anyhow!("no UserGroup found with given name or id").into(),
+      );
+    }
     Ok(res)
   }
 }
@@ -46,7 +52,12 @@ impl Resolve<ReadArgs> for ListUserGroups {
   ) -> serror::Result<ListUserGroupsResponse> {
     let mut filter = Document::new();
     if !user.admin {
-      filter.insert("users", &user.id);
+      let ids = get_user_user_group_ids(&user.id)
+        .await?
+        .into_iter()
+        .filter_map(|id| ObjectId::from_str(&id).ok())
+        .collect::<Vec<_>>();
+      filter.insert("_id", doc! { "$in": ids });
     }
     let res = find_collect(
       &db_client().user_groups,
@@ -0,0 +1,477 @@
+use std::sync::OnceLock;
+
+use anyhow::{Context, anyhow};
+use komodo_client::{
+  api::read::*,
+  entities::{
+    ScheduleFormat,
+    action::Action,
+    alerter::Alerter,
+    build::Build,
+    builder::Builder,
+    deployment::{
+      Conversion, Deployment, DeploymentImage, conversions_from_str,
+    },
+    permission::PermissionLevel,
+    procedure::Procedure,
+    repo::Repo,
+    server::Server,
+    stack::{Stack, StackServiceHealthCheck},
+    swarm::SwarmService,
+    sync::ResourceSync,
+    user::User,
+  },
+};
+use regex::Regex;
+use resolver_api::Resolve;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value, json};
+
+use crate::{
+  permission::get_check_permissions,
+  resource::KomodoResource,
+  schedule::next_occurrence,
+  stack::{
+    includes::check_include_checksum,
+    overrides::check_compose_override,
+  },
+};
+
+use super::ReadArgs;
+
+impl Resolve<ReadArgs> for ValidateServerConfig {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<ValidateConfigResponse> {
+    let mut errors =
+      validate_fields::<Server>(&self.id, &self.config, user).await?;
+    for command in self.config.scheduled_commands.iter().flatten() {
+      if let Err(e) = check_schedule(
+        Some(command.schedule_format),
+        Some(command.schedule.as_str()),
+        Some(command.schedule_timezone.as_str()),
+      ) {
+        errors.push(ConfigFieldError {
+          field: String::from("scheduled_commands"),
+          error: format!("{} | {e:#}", command.name),
+        });
+      }
+    }
+    Ok(ValidateConfigResponse { errors })
+  }
+}
+
+impl Resolve<ReadArgs> for ValidateStackConfig {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<ValidateConfigResponse> {
+    let mut errors =
+      validate_fields::<Stack>(&self.id, &self.config, user).await?;
+    push_error(
+      &mut errors,
+      "auto_stop_schedule",
+      check_schedule(
+        self.config.auto_stop_schedule_format,
+        self.config.auto_stop_schedule.as_deref(),
+        self.config.auto_stop_schedule_timezone.as_deref(),
+      ),
+    );
+    for check in self.config.health_checks.iter().flatten() {
+      push_error(
+        &mut errors,
+        "health_checks",
+        check_stack_health_check(check),
+      );
+    }
+    for pin in self.config.include_checksums.iter().flatten() {
+      push_error(
+        &mut errors,
+        "include_checksums",
+        check_include_checksum(&pin.url, &pin.sha256),
+      );
+    }
+    for compose_override in
+      self.config.compose_overrides.iter().flatten()
+    {
+      push_error(
+        &mut errors,
+        "compose_overrides",
+        check_compose_override(compose_override),
+      );
+    }
+    Ok(ValidateConfigResponse { errors })
+  }
+}
+
+impl Resolve<ReadArgs> for ValidateDeploymentConfig {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<ValidateConfigResponse> {
+    let mut errors =
+      validate_fields::<Deployment>(&self.id, &self.config, user)
+        .await?;
+    if let Some(DeploymentImage::Image { image }) = &self.config.image
+    {
+      push_error(&mut errors, "image", check_image(image));
+    }
+    if let Some(ports) = &self.config.ports {
+      push_error(&mut errors, "ports", check_ports(ports));
+    }
+    push_error(
+      &mut errors,
+      "auto_stop_schedule",
+      check_schedule(
+        self.config.auto_stop_schedule_format,
+        self.config.auto_stop_schedule.as_deref(),
+        self.config.auto_stop_schedule_timezone.as_deref(),
+      ),
+    );
+    Ok(ValidateConfigResponse { errors })
+  }
+}
+
+impl Resolve<ReadArgs> for ValidateSwarmServiceConfig {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<ValidateConfigResponse> {
+    let mut errors =
+      validate_fields::<SwarmService>(&self.id, &self.config, user)
+        .await?;
+    if let Some(image) = &self.config.image {
+      push_error(&mut errors, "image", check_image(image));
+    }
+    if let Some(ports) = &self.config.ports {
+      push_error(&mut errors, "ports", check_ports(ports));
+    }
+    Ok(ValidateConfigResponse { errors })
+  }
+}
+
+impl Resolve<ReadArgs> for ValidateBuildConfig {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<ValidateConfigResponse> {
+    let mut errors =
+      validate_fields::<Build>(&self.id, &self.config, user).await?;
+    if let Some(image_name) = &self.config.image_name {
+      push_error(&mut errors, "image_name", check_image(image_name));
+    }
+    push_error(
+      &mut errors,
+      "schedule",
+      check_schedule(
+        self.config.schedule_format,
+        self.config.schedule.as_deref(),
+        self.config.schedule_timezone.as_deref(),
+      ),
+    );
+    Ok(ValidateConfigResponse { errors })
+  }
+}
+
+impl Resolve<ReadArgs> for ValidateRepoConfig {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<ValidateConfigResponse> {
+    let errors =
+      validate_fields::<Repo>(&self.id, &self.config, user).await?;
+    Ok(ValidateConfigResponse { errors })
+  }
+}
+
+impl Resolve<ReadArgs> for ValidateProcedureConfig {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<ValidateConfigResponse> {
+    let mut errors =
+      validate_fields::<Procedure>(&self.id, &self.config, user)
+        .await?;
+    push_error(
+      &mut errors,
+      "schedule",
+      check_schedule(
+        self.config.schedule_format,
+        self.config.schedule.as_deref(),
+        self.config.schedule_timezone.as_deref(),
+      ),
+    );
+    Ok(ValidateConfigResponse { errors })
+  }
+}
+
+impl Resolve<ReadArgs> for ValidateActionConfig {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<ValidateConfigResponse> {
+    let mut errors =
+      validate_fields::<Action>(&self.id, &self.config, user).await?;
+    push_error(
+      &mut errors,
+      "schedule",
+      check_schedule(
+        self.config.schedule_format,
+        self.config.schedule.as_deref(),
+        self.config.schedule_timezone.as_deref(),
+      ),
+    );
+    Ok(ValidateConfigResponse { errors })
+  }
+}
+
+impl Resolve<ReadArgs> for ValidateResourceSyncConfig {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<ValidateConfigResponse> {
+    let errors =
+      validate_fields::<ResourceSync>(&self.id, &self.config, user)
+        .await?;
+    Ok(ValidateConfigResponse { errors })
+  }
+}
+
+impl Resolve<ReadArgs> for ValidateBuilderConfig {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<ValidateConfigResponse> {
+    let errors =
+      validate_fields::<Builder>(&self.id, &self.config, user)
+        .await?;
+    Ok(ValidateConfigResponse { errors })
+  }
+}
+
+impl Resolve<ReadArgs> for ValidateAlerterConfig {
+  async fn resolve(
+    self,
+    ReadArgs { user }: &ReadArgs,
+  ) -> serror::Result<ValidateConfigResponse> {
+    let errors =
+      validate_fields::<Alerter>(&self.id, &self.config, user)
+        .await?;
+    Ok(ValidateConfigResponse { errors })
+  }
+}
+
+/// Runs the same validation as create / update on each
+/// field of the config separately, so each error is
+/// scoped to the field which caused it.
+async fn validate_fields<T: KomodoResource>(
+  id: &str,
+  config: &T::PartialConfig,
+  user: &User,
+) -> serror::Result<Vec<ConfigFieldError>>
+where
+  T::PartialConfig: DeserializeOwned,
+{
+  let id = if id.is_empty() {
+    None
+  } else {
+    let resource = get_check_permissions::<T>(
+      id,
+      user,
+      PermissionLevel::Read.into(),
+    )
+    .await?;
+    Some(resource.id)
+  };
+  let config = serde_json::to_value(config)
+    .context("Failed to serialize config")?;
+  let mut errors = Vec::new();
+  for (field, partial) in field_partials(config) {
+    let mut partial =
+      match serde_json::from_value::<T::PartialConfig>(partial) {
+        Ok(partial) => partial,
+        Err(e) => {
+          errors.push(ConfigFieldError {
+            field,
+            error: e.to_string(),
+          });
+          continue;
+        }
+      };
+    let res = match &id {
+      Some(id) => {
+        T::validate_update_config(id, &mut partial, user).await
+      }
+      None => T::validate_create_config(&mut partial, user).await,
+    };
+    if let Err(e) = res {
+      errors.push(ConfigFieldError {
+        field,
+        error: format!("{e:#}"),
+      });
+    }
+  }
+  Ok(errors)
+}
+
+/// Splits the serialized config into partials with a single field.
+/// Enum configs (`{ type, params }`) are split on the params.
+fn field_partials(config: Value) -> Vec<(String, Value)> {
+  let Value::Object(mut config) = config else {
+    return Vec::new();
+  };
+  if let Some(variant) = config.get("type").cloned()
+    && let Some(Value::Object(params)) = config.remove("params")
+  {
+    return params
+      .into_iter()
+      .map(|(field, value)| {
+        let mut params = Map::new();
+        params.insert(field.clone(), value);
+        (field, json!({ "type": variant, "params": params }))
+      })
+      .collect();
+  }
+  config
+    .into_iter()
+    .map(|(field, value)| {
+      let mut partial = Map::new();
+      partial.insert(field.clone(), value);
+      (field, Value::Object(partial))
+    })
+    .collect()
+}
+
+fn push_error(
+  errors: &mut Vec<ConfigFieldError>,
+  field: &str,
+  res: anyhow::Result<()>,
+) {
+  if let Err(e) = res {
+    errors.push(ConfigFieldError {
+      field: field.to_string(),
+      error: format!("{e:#}"),
+    });
+  }
+}
+
+fn check_schedule(
+  format: Option<ScheduleFormat>,
+  schedule: Option<&str>,
+  timezone: Option<&str>,
+) -> anyhow::Result<()> {
+  let Some(schedule) = schedule.filter(|s| !s.is_empty()) else {
+    return Ok(());
+  };
+  next_occurrence(
+    format.unwrap_or_default(),
+    schedule,
+    timezone.unwrap_or_default(),
+  )
+  .map(|_| ())
+}
+
+/// Checks the image is a well formed docker image reference,
+/// eg. `ghcr.io/owner/image:tag`.
+fn check_image(image: &str) -> anyhow::Result<()> {
+  // Interpolated images can only be checked at deploy time.
+  if image.is_empty() || image.contains("[[") {
+    return Ok(());
+  }
+  if image_reference_regex().is_match(image) {
+    Ok(())
+  } else {
+    Err(anyhow!("'{image}' is not a valid image reference"))
+  }
+}
+
+fn image_reference_regex() -> &'static Regex {
+  static IMAGE_REFERENCE_REGEX: OnceLock<Regex> = OnceLock::new();
+  IMAGE_REFERENCE_REGEX.get_or_init(|| {
+    let label = r"[a-zA-Z0-9](?:[a-zA-Z0-9-]*[a-zA-Z0-9])?";
+    let domain = format!(r"{label}(?:\.{label})*(?::[0-9]+)?");
+    let component = r"[a-z0-9]+(?:(?:[._]|__|-+)[a-z0-9]+)*";
+    let tag = r"[a-zA-Z0-9_][a-zA-Z0-9_.-]{0,127}";
+    let digest = r"[a-zA-Z][a-zA-Z0-9]*(?:[-_+.][a-zA-Z][a-zA-Z0-9]*)*:[0-9a-fA-F]{32,}";
+    Regex::new(&format!(
+      "^(?:{domain}/)?{component}(?:/{component})*(?::{tag})?(?:@{digest})?$"
+    ))
+    .expect("Invalid image reference regex")
+  })
+}
+
+fn check_stack_health_check(
+  check: &StackServiceHealthCheck,
+) -> anyhow::Result<()> {
+  if check.service.is_empty() {
+    return Err(anyhow!("Health check is missing the service"));
+  }
+  if check.url.is_empty() {
+    if check.port == 0 {
+      return Err(anyhow!(
+        "Health check for {} needs a url or port",
+        check.service
+      ));
+    }
+    return Ok(());
+  }
+  let url = reqwest::Url::parse(&check.url).with_context(|| {
+    format!("Invalid health check url for {}", check.service)
+  })?;
+  if !matches!(url.scheme(), "http" | "https") {
+    return Err(anyhow!(
+      "Health check url for {} must be http or https",
+      check.service
+    ));
+  }
+  Ok(())
+}
+
+/// Checks each mapping is `[ip:][published:]container[/protocol]`,
+/// where the ports may be ranges.
+fn check_ports(ports: &str) -> anyhow::Result<()> {
+  // Interpolated ports can only be checked at deploy time.
+  if ports.contains("[[") {
+    return Ok(());
+  }
+  for Conversion { local, container } in conversions_from_str(ports)?
+  {
+    let mapping = format!("{local}:{container}");
+    check_port_mapping(&mapping)
+      .with_context(|| format!("Invalid port mapping '{mapping}'"))?;
+  }
+  Ok(())
+}
+
+fn check_port_mapping(mapping: &str) -> anyhow::Result<()> {
+  let (published, container) =
+    mapping.rsplit_once(':').context("Missing container port")?;
+  let (container, protocol) =
+    container.split_once('/').unwrap_or((container, "tcp"));
+  if !matches!(protocol, "tcp" | "udp" | "sctp") {
+    return Err(anyhow!("Unknown protocol '{protocol}'"));
+  }
+  let published = published
+    .rsplit_once(':')
+    .map(|(_, port)| port)
+    .unwrap_or(published);
+  if !published.is_empty() {
+    check_port_range(published)?;
+  }
+  check_port_range(container)
+}
+
+fn check_port_range(ports: &str) -> anyhow::Result<()> {
+  let (start, end) = ports.split_once('-').unwrap_or((ports, ports));
+  let parse = |port: &str| {
+    port
+      .trim()
+      .parse::<u16>()
+      .with_context(|| format!("'{port}' is not a valid port"))
+  };
+  if parse(start)? > parse(end)? {
+    return Err(anyhow!("Port range '{ports}' is reversed"));
+  }
+  Ok(())
+}
@@ -0,0 +1,200 @@
+use anyhow::{Context, anyhow};
+use axum::{
+  Extension, Router,
+  body::Body,
+  extract::Query,
+  http::header,
+  middleware,
+  response::{IntoResponse, Response},
+  routing::post,
+};
+use database::mungos::mongodb::bson::{doc, oid::ObjectId, to_bson};
+use formatting::format_serror;
+use futures::TryStreamExt;
+use komodo_client::{
+  api::{
+    stack_file::{DownloadStackFileBody, UploadStackFileQuery},
+    write::RefreshStackCache,
+  },
+  entities::{
+    Operation,
+    permission::PermissionLevel,
+    stack::Stack,
+    update::Update,
+    user::{User, stack_user},
+  },
+};
+use resolver_api::Resolve;
+use serror::Json;
+use tokio_util::io::{ReaderStream, StreamReader};
+
+use crate::{
+  api::write::WriteArgs,
+  auth::auth_request,
+  helpers::update::{add_update, make_update},
+  permission::check_permissions,
+  resource::{KomodoResource, get_unloaded},
+  stack::{
+    includes::stack_is_ui_defined,
+    storage::{
+      delete_stored_file, read_stored_file, stored_file_key,
+      write_stored_file,
+    },
+  },
+  state::db_client,
+};
+
+pub fn router() -> Router {
+  Router::new()
+    .route("/download", post(download_stack_file))
+    .route("/upload", post(upload_stack_file))
+    .layer(middleware::from_fn(auth_request))
+}
+
+#[instrument(
+  name = "DownloadStackFile",
+  skip(user),
+  fields(
+    user_id = user.id,
+  )
+)]
+async fn download_stack_file(
+  Extension(user): Extension<User>,
+  Json(DownloadStackFileBody { stack }): Json<DownloadStackFileBody>,
+) -> serror::Result<Response> {
+  info!("/stack_file/download request | user: {}", user.username);
+
+  // The stored contents are streamed below, rather than loaded.
+  let stack = check_permissions::<Stack>(
+    get_unloaded::<Stack>(&stack).await?,
+    &user,
+    PermissionLevel::Read.into(),
+  )
+  .await?;
+
+  let body = match &stack.info.stored_file_contents {
+    Some(stored) if stack.config.file_contents.is_empty() => {
+      Body::from_stream(ReaderStream::new(
+        read_stored_file(stored).await?,
+      ))
+    }
+    _ => Body::from(stack.config.file_contents),
+  };
+
+  Ok(
+    (
+      [
+        (header::CONTENT_TYPE, String::from("application/yaml")),
+        (
+          header::CONTENT_DISPOSITION,
+          format!(
+            "attachment; filename=\"{}.compose.yaml\"",
+            stack.name
+          ),
+        ),
+      ],
+      body,
+    )
+      .into_response(),
+  )
+}
+
+#[instrument(
+  name = "UploadStackFile",
+  skip(user, body),
+  fields(
+    user_id = user.id,
+  )
+)]
+async fn upload_stack_file(
+  Extension(user): Extension<User>,
+  Query(UploadStackFileQuery { stack }): Query<UploadStackFileQuery>,
+  body: Body,
+) -> serror::Result<axum::Json<Update>> {
+  info!("/stack_file/upload request | user: {}", user.username);
+
+  // The previous contents are replaced, so there's no need to load them.
+  let stack = check_permissions::<Stack>(
+    get_unloaded::<Stack>(&stack).await?,
+    &user,
+    PermissionLevel::Write.into(),
+  )
+  .await?;
+
+  if !stack_is_ui_defined(&stack) {
+    return Err(
+      anyhow!(
+        "Stack does not use UI defined file contents, can't upload file contents"
+      )
+      .into(),
+    );
+  }
+
+  if Stack::busy(&stack.id).await? {
+    return Err(anyhow!("Stack busy").into());
+  }
+
+  let reader = StreamReader::new(
+    body.into_data_stream().map_err(std::io::Error::other),
+  );
+  let stored =
+    write_stored_file(&stored_file_key(&stack.id), reader).await?;
+
+  db_client()
+    .stacks
+    .update_one(
+      doc! {
+        "_id": ObjectId::parse_str(&stack.id)
+          .context("Stack id is not valid ObjectId")?
+      },
+      doc! {
+        "$set": {
+          "config.file_contents": "",
+          "info.stored_file_contents": to_bson(&stored)
+            .context("Failed to serialize stored file contents")?,
+        },
+        "$inc": { "revision": 1 },
+      },
+    )
+    .await
+    .context("Failed to update Stack file contents on db")?;
+
+  let mut update = make_update(&stack, Operation::UpdateStack, &user);
+  update.push_simple_log(
+    "Upload File Contents",
+    format!(
+      "Stored {} bytes in {} storage\nsha256: {}",
+      stored.size, stored.storage, stored.sha256
+    ),
+  );
+
+  if let Some(previous) = &stack.info.stored_file_contents
+    && (previous.storage != stored.storage
+      || previous.key != stored.key)
+    && let Err(e) = delete_stored_file(previous).await
+  {
+    update.push_error_log(
+      "Delete previous file contents",
+      format_serror(&e.into()),
+    );
+  }
+
+  if let Err(e) = (RefreshStackCache {
+    stack: stack.id.clone(),
+  })
+  .resolve(&WriteArgs {
+    user: stack_user().to_owned(),
+  })
+  .await
+  {
+    update.push_error_log(
+      "Refresh stack cache",
+      format_serror(&e.error.into()),
+    );
+  }
+
+  update.finalize();
+  update.id = add_update(update.clone()).await?;
+
+  Ok(axum::Json(update))
+}
@@ -7,13 +7,22 @@ use komodo_client::{
     server::Server, stack::Stack, user::User,
   },
 };
+use periphery_client::audit::AuditContext;
 use serror::Json;
 use uuid::Uuid;
 
 use crate::{
-  auth::auth_request, helpers::periphery_client,
-  permission::get_check_permissions, resource::get,
-  state::stack_status_cache,
+  auth::auth_request,
+  helpers::{
+    periphery_client,
+    terminal::{
+      check_container_exec_enabled, check_terminal_access,
+      check_unrestricted_terminal_access,
+    },
+  },
+  permission::get_check_permissions,
+  resource::get,
+  stack::get_stack_service_container,
 };
 
 pub fn router() -> Router {
@@ -33,7 +42,9 @@ async fn execute_terminal(
   Extension(user): Extension<User>,
   Json(request): Json<ExecuteTerminalBody>,
 ) -> serror::Result<axum::body::Body> {
-  execute_terminal_inner(Uuid::new_v4(), request, user).await
+  AuditContext::new(&user.username, "")
+    .scope(execute_terminal_inner(Uuid::new_v4(), request, user))
+    .await
 }
 
 #[instrument(
@@ -58,11 +69,13 @@ async fn execute_terminal_inner(
     let server = get_check_permissions::<Server>(
       &server,
       &user,
-      PermissionLevel::Read.terminal(),
+      PermissionLevel::Execute.terminal(),
     )
     .await?;
 
-    let periphery = periphery_client(&server)?;
+    check_terminal_access(&server, &user, &terminal).await?;
+
+    let periphery = periphery_client(&server).await?;
 
     let stream = periphery
       .execute_terminal(terminal, command)
@@ -92,7 +105,13 @@ async fn execute_container_exec(
   Extension(user): Extension<User>,
   Json(request): Json<ExecuteContainerExecBody>,
 ) -> serror::Result<axum::body::Body> {
-  execute_container_exec_inner(Uuid::new_v4(), request, user).await
+  AuditContext::new(&user.username, "")
+    .scope(execute_container_exec_inner(
+      Uuid::new_v4(),
+      request,
+      user,
+    ))
+    .await
 }
 
 #[instrument(
@@ -121,11 +140,14 @@ async fn execute_container_exec_inner(
     let server = get_check_permissions::<Server>(
       &server,
       &user,
-      PermissionLevel::Read.terminal(),
+      PermissionLevel::Execute.terminal(),
     )
     .await?;
 
-    let periphery = periphery_client(&server)?;
+    check_unrestricted_terminal_access(&server, &user)?;
+    check_container_exec_enabled(&server).await?;
+
+    let periphery = periphery_client(&server).await?;
 
     let stream = periphery
       .execute_container_exec(container, shell, command)
@@ -159,7 +181,13 @@ async fn execute_deployment_exec(
   Extension(user): Extension<User>,
   Json(request): Json<ExecuteDeploymentExecBody>,
 ) -> serror::Result<axum::body::Body> {
-  execute_deployment_exec_inner(Uuid::new_v4(), request, user).await
+  AuditContext::new(&user.username, "")
+    .scope(execute_deployment_exec_inner(
+      Uuid::new_v4(),
+      request,
+      user,
+    ))
+    .await
 }
 
 #[instrument(
@@ -187,13 +215,16 @@ async fn execute_deployment_exec_inner(
     let deployment = get_check_permissions::<Deployment>(
       &deployment,
       &user,
-      PermissionLevel::Read.terminal(),
+      PermissionLevel::Execute.terminal(),
     )
     .await?;
 
     let server = get::<Server>(&deployment.config.server_id).await?;
 
-    let periphery = periphery_client(&server)?;
+    check_unrestricted_terminal_access(&server, &user)?;
+    check_container_exec_enabled(&server).await?;
+
+    let periphery = periphery_client(&server).await?;
 
     let stream = periphery
       .execute_container_exec(deployment.name, shell, command)
@@ -227,7 +258,9 @@ async fn execute_stack_exec(
   Extension(user): Extension<User>,
   Json(request): Json<ExecuteStackExecBody>,
 ) -> serror::Result<axum::body::Body> {
-  execute_stack_exec_inner(Uuid::new_v4(), request, user).await
+  AuditContext::new(&user.username, "")
+    .scope(execute_stack_exec_inner(Uuid::new_v4(), request, user))
+    .await
 }
 
 #[instrument(
@@ -253,28 +286,19 @@ async fn execute_stack_exec_inner(
     let stack = get_check_permissions::<Stack>(
       &stack,
       &user,
-      PermissionLevel::Read.terminal(),
+      PermissionLevel::Execute.terminal(),
     )
     .await?;
 
     let server = get::<Server>(&stack.config.server_id).await?;
 
-    let container = stack_status_cache()
-      .get(&stack.id)
-      .await
-      .context("could not get stack status")?
-      .curr
-      .services
-      .iter()
-      .find(|s| s.service == service)
-      .context("could not find service")?
-      .container
-      .as_ref()
-      .context("could not find service container")?
-      .name
-      .clone();
-
-    let periphery = periphery_client(&server)?;
+    check_unrestricted_terminal_access(&server, &user)?;
+    check_container_exec_enabled(&server).await?;
+
+    let container =
+      get_stack_service_container(&stack, &server, &service).await?;
+
+    let periphery = periphery_client(&server).await?;
 
     let stream = periphery
       .execute_container_exec(container, shell, command)
@@ -21,8 +21,12 @@ use typeshare::typeshare;
 use uuid::Uuid;
 
 use crate::{
-  auth::auth_request,
-  helpers::{query::get_user, random_string},
+  auth::{auth_request, normalize_api_key_scope},
+  helpers::{
+    audit::{RequestSource, audit_entry, record_audit},
+    query::get_user,
+    random_string,
+  },
   state::db_client,
 };
 
@@ -56,6 +60,7 @@ pub fn router() -> Router {
 
 async fn variant_handler(
   user: Extension<User>,
+  source: RequestSource,
   Path(Variant { variant }): Path<Variant>,
   Json(params): Json<serde_json::Value>,
 ) -> serror::Result<axum::response::Response> {
@@ -63,12 +68,17 @@ async fn variant_handler(
     "type": variant,
     "params": params,
   }))?;
-  handler(user, Json(req)).await
+  handler(user, source, Json(req)).await
 }
 
-#[instrument(name = "UserHandler", level = "debug", skip(user))]
+#[instrument(
+  name = "UserHandler",
+  level = "debug",
+  skip(user, source)
+)]
 async fn handler(
   Extension(user): Extension<User>,
+  source: RequestSource,
   Json(request): Json<UserRequest>,
 ) -> serror::Result<axum::response::Response> {
   let timer = Instant::now();
@@ -77,10 +87,20 @@ async fn handler(
     "/user request {req_id} | user: {} ({})",
     user.username, user.id
   );
-  let res = request.resolve(&UserArgs { user }).await;
+  // Recently viewed / last seen are just ui state
+  let entry = matches!(
+    request,
+    UserRequest::CreateApiKey(_) | UserRequest::DeleteApiKey(_)
+  )
+  .then(|| audit_entry("user", &source, &request));
+  let args = UserArgs { user };
+  let res = request.resolve(&args).await;
   if let Err(e) = &res {
     warn!("/user request {req_id} error: {:#}", e.error);
   }
+  if let Some(entry) = entry {
+    record_audit(entry, Some(&args.user), &res);
+  }
   let elapsed = timer.elapsed();
   debug!("/user request {req_id} | resolve time: {elapsed:?}");
   res.map(|res| res.0)
@@ -165,6 +185,9 @@ impl Resolve<UserArgs> for CreateApiKey {
   ) -> serror::Result<CreateApiKeyResponse> {
     let user = get_user(&user.id).await?;
 
+    let mut scope = self.scope;
+    normalize_api_key_scope(&mut scope)?;
+
     let key = format!("K-{}", random_string(SECRET_LENGTH));
     let secret = format!("S-{}", random_string(SECRET_LENGTH));
     let secret_hash = bcrypt::hash(&secret, BCRYPT_COST)
@@ -177,6 +200,7 @@ impl Resolve<UserArgs> for CreateApiKey {
       user_id: user.id.clone(),
       created_at: komodo_timestamp(),
       expires: self.expires,
+      scope,
     };
     db_client()
       .api_keys
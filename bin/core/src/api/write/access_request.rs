@@ -0,0 +1,367 @@
+use anyhow::{Context, anyhow};
+use database::mungos::{
+  by_id::find_one_by_id,
+  mongodb::{
+    bson::{doc, oid::ObjectId, to_bson},
+    options::UpdateOptions,
+  },
+};
+use komodo_client::{
+  api::write::*,
+  entities::{
+    Operation, ResourceTargetVariant,
+    access_request::{AccessRequest, AccessRequestStatus},
+    komodo_timestamp,
+    permission::{PermissionLevel, PermissionLevelAndSpecifics},
+  },
+};
+use resolver_api::Resolve;
+
+use crate::{
+  helpers::{
+    access_request::schedule_access_request_expiry,
+    query::get_user,
+    update::{add_update, make_update},
+  },
+  resource::resource_target_from_variant,
+  state::db_client,
+};
+
+use super::{
+  WriteArgs, permissions::extract_resource_target_with_validation,
+};
+
+impl Resolve<WriteArgs> for RequestAccess {
+  #[instrument(name = "RequestAccess", skip(user))]
+  async fn resolve(
+    self,
+    WriteArgs { user }: &WriteArgs,
+  ) -> serror::Result<RequestAccessResponse> {
+    if user.admin {
+      return Err(anyhow!("Admins already have full access").into());
+    }
+    if self.duration_ms <= 0 {
+      return Err(
+        anyhow!("duration_ms must be greater than 0").into(),
+      );
+    }
+    if self.permission.level == PermissionLevel::None
+      && self.permission.specific.is_empty()
+    {
+      return Err(
+        anyhow!(
+          "Must request a permission level or specific permissions"
+        )
+        .into(),
+      );
+    }
+
+    let (variant, resource_id) =
+      extract_resource_target_with_validation(&self.target).await?;
+    if variant == ResourceTargetVariant::System {
+      return Err(
+        anyhow!("Access can only be requested on a resource").into(),
+      );
+    }
+
+    let pending = db_client()
+      .access_requests
+      .find_one(doc! {
+        "user_id": &user.id,
+        "target.type": variant.as_ref(),
+        "target.id": &resource_id,
+        "status": AccessRequestStatus::Pending.to_string(),
+      })
+      .await
+      .context("Failed to query db for access requests")?;
+    if pending.is_some() {
+      return Err(
+        anyhow!(
+          "There is already a pending access request on this resource"
+        )
+        .into(),
+      );
+    }
+
+    let mut request = AccessRequest {
+      id: Default::default(),
+      user_id: user.id.clone(),
+      username: user.username.clone(),
+      target: resource_target_from_variant(variant, resource_id),
+      permission: self.permission,
+      duration_ms: self.duration_ms,
+      reason: self.reason,
+      status: AccessRequestStatus::Pending,
+      requested_at: komodo_timestamp(),
+      resolved_by: Default::default(),
+      resolved_at: Default::default(),
+      comment: Default::default(),
+      expires_at: Default::default(),
+      previous: None,
+    };
+    request.id = db_client()
+      .access_requests
+      .insert_one(&request)
+      .await
+      .context("Failed to add access request to db")?
+      .inserted_id
+      .as_object_id()
+      .context("Inserted id is not ObjectId")?
+      .to_hex();
+
+    let mut update = make_update(
+      request.target.clone(),
+      Operation::RequestAccess,
+      user,
+    );
+    let mut log = format!(
+      "{} requested {} access for {}",
+      user.username,
+      request.permission.level,
+      format_duration(request.duration_ms)
+    );
+    if !request.reason.is_empty() {
+      log.push_str(&format!("\n\n{}", request.reason));
+    }
+    update.push_simple_log("Request Access", log);
+    update.finalize();
+    add_update(update).await?;
+
+    Ok(request)
+  }
+}
+
+impl Resolve<WriteArgs> for ApproveAccessRequest {
+  #[instrument(name = "ApproveAccessRequest", skip(admin))]
+  async fn resolve(
+    self,
+    WriteArgs { user: admin }: &WriteArgs,
+  ) -> serror::Result<ApproveAccessRequestResponse> {
+    if !admin.admin {
+      return Err(anyhow!("this method is admin only").into());
+    }
+    let request = get_pending_request(&self.id).await?;
+    let duration_ms = self.duration_ms.unwrap_or(request.duration_ms);
+    if duration_ms <= 0 {
+      return Err(
+        anyhow!("duration_ms must be greater than 0").into(),
+      );
+    }
+
+    let user = get_user(&request.user_id).await?;
+    if !user.enabled {
+      return Err(anyhow!("User is not enabled").into());
+    }
+
+    let (variant, resource_id) = request.target.extract_variant_id();
+    let permission_filter = doc! {
+      "user_target.type": "User",
+      "user_target.id": &request.user_id,
+      "resource_target.type": variant.as_ref(),
+      "resource_target.id": resource_id,
+    };
+
+    // Permanent permissions are restored when the access expires,
+    // expiring ones just lapse.
+    let previous = match db_client()
+      .permissions
+      .find_one(permission_filter.clone())
+      .await
+      .context("Failed to query db for permissions")?
+    {
+      Some(permission) if permission.expires_at == 0 => {
+        Some(PermissionLevelAndSpecifics::from(&permission))
+      }
+      // The permission may be from another approved request
+      // which is still active. This replaces it, so take over
+      // the permission it would have restored.
+      Some(permission) => db_client()
+        .access_requests
+        .find_one(doc! {
+          "user_id": &request.user_id,
+          "target.type": variant.as_ref(),
+          "target.id": resource_id,
+          "status": AccessRequestStatus::Approved.to_string(),
+          "expires_at": permission.expires_at,
+        })
+        .await
+        .context("Failed to query db for access requests")?
+        .and_then(|active| active.previous),
+      None => None,
+    };
+
+    // Don't take away anything the user already has.
+    let mut permission = request.permission.clone();
+    if let Some(previous) = &previous {
+      if previous.level > permission.level {
+        permission.level = previous.level;
+      }
+      permission
+        .specific
+        .extend(previous.specific.iter().cloned());
+    }
+
+    let now = komodo_timestamp();
+    let expires_at = now + duration_ms;
+    let object_id = ObjectId::parse_str(&request.id)
+      .context("Access request id is not valid ObjectId")?;
+    // Only matches if it is still pending,
+    // so it can't be approved and rejected at once.
+    let res = db_client()
+      .access_requests
+      .update_one(
+        doc! {
+          "_id": object_id,
+          "status": AccessRequestStatus::Pending.to_string(),
+        },
+        doc! { "$set": {
+          "status": AccessRequestStatus::Approved.to_string(),
+          "resolved_by": &admin.id,
+          "resolved_at": now,
+          "comment": &self.comment,
+          "duration_ms": duration_ms,
+          "expires_at": expires_at,
+          "previous": to_bson(&previous)
+            .context("Failed to serialize previous permission")?,
+        } },
+      )
+      .await
+      .context("Failed to update access request on db")?;
+    if res.matched_count == 0 {
+      return Err(
+        anyhow!("Only pending access requests can be approved")
+          .into(),
+      );
+    }
+
+    let specific = to_bson(&permission.specific)
+      .context("permission.specific is not valid Bson")?;
+    db_client()
+      .permissions
+      .update_one(
+        permission_filter,
+        doc! { "$set": {
+          "user_target.type": "User",
+          "user_target.id": &request.user_id,
+          "resource_target.type": variant.as_ref(),
+          "resource_target.id": resource_id,
+          "level": permission.level.as_ref(),
+          "specific": specific,
+          "expires_at": expires_at,
+        } },
+      )
+      .with_options(UpdateOptions::builder().upsert(true).build())
+      .await
+      .context("Failed to update permission on db")?;
+
+    schedule_access_request_expiry(request.id.clone());
+
+    let mut update = make_update(
+      request.target.clone(),
+      Operation::ApproveAccessRequest,
+      admin,
+    );
+    let mut log = format!(
+      "Approved {} access for {} for {}",
+      permission.level,
+      request.username,
+      format_duration(duration_ms)
+    );
+    if !self.comment.is_empty() {
+      log.push_str(&format!("\n\n{}", self.comment));
+    }
+    update.push_simple_log("Approve Access Request", log);
+    update.finalize();
+    add_update(update).await?;
+
+    Ok(get_access_request(&self.id).await?)
+  }
+}
+
+impl Resolve<WriteArgs> for RejectAccessRequest {
+  #[instrument(name = "RejectAccessRequest", skip(admin))]
+  async fn resolve(
+    self,
+    WriteArgs { user: admin }: &WriteArgs,
+  ) -> serror::Result<RejectAccessRequestResponse> {
+    if !admin.admin {
+      return Err(anyhow!("this method is admin only").into());
+    }
+    let request = get_pending_request(&self.id).await?;
+    let object_id = ObjectId::parse_str(&request.id)
+      .context("Access request id is not valid ObjectId")?;
+    let res = db_client()
+      .access_requests
+      .update_one(
+        doc! {
+          "_id": object_id,
+          "status": AccessRequestStatus::Pending.to_string(),
+        },
+        doc! { "$set": {
+          "status": AccessRequestStatus::Rejected.to_string(),
+          "resolved_by": &admin.id,
+          "resolved_at": komodo_timestamp(),
+          "comment": &self.comment,
+        } },
+      )
+      .await
+      .context("Failed to update access request on db")?;
+    if res.matched_count == 0 {
+      return Err(
+        anyhow!("Only pending access requests can be rejected")
+          .into(),
+      );
+    }
+
+    let mut update = make_update(
+      request.target.clone(),
+      Operation::RejectAccessRequest,
+      admin,
+    );
+    let mut log = format!(
+      "Rejected {} access for {}",
+      request.permission.level, request.username
+    );
+    if !self.comment.is_empty() {
+      log.push_str(&format!("\n\n{}", self.comment));
+    }
+    update.push_simple_log("Reject Access Request", log);
+    update.finalize();
+    add_update(update).await?;
+
+    Ok(get_access_request(&self.id).await?)
+  }
+}
+
+async fn get_access_request(
+  id: &str,
+) -> anyhow::Result<AccessRequest> {
+  find_one_by_id(&db_client().access_requests, id)
+    .await
+    .context("Failed to query db for access request")?
+    .context("No access request found with given id")
+}
+
+async fn get_pending_request(
+  id: &str,
+) -> anyhow::Result<AccessRequest> {
+  let request = get_access_request(id).await?;
+  if request.status != AccessRequestStatus::Pending {
+    return Err(anyhow!(
+      "Access request is not pending, it is {}",
+      request.status
+    ));
+  }
+  Ok(request)
+}
+
+fn format_duration(duration_ms: i64) -> String {
+  let minutes = duration_ms / 60_000;
+  if minutes >= 60 * 24 && minutes % (60 * 24) == 0 {
+    format!("{} day/s", minutes / (60 * 24))
+  } else if minutes >= 60 && minutes % 60 == 0 {
+    format!("{} hour/s", minutes / 60)
+  } else {
+    format!("{minutes} minute/s")
+  }
+}
@@ -42,7 +42,15 @@ impl Resolve<WriteArgs> for UpdateAction {
     self,
     WriteArgs { user }: &WriteArgs,
   ) -> serror::Result<Action> {
-    Ok(resource::update::<Action>(&self.id, self.config, user).await?)
+    Ok(
+      resource::update::<Action>(
+        &self.id,
+        self.config,
+        self.revision,
+        user,
+      )
+      .await?,
+    )
   }
 }
 
@@ -53,8 +53,13 @@ impl Resolve<WriteArgs> for UpdateAlerter {
     WriteArgs { user }: &WriteArgs,
   ) -> serror::Result<Alerter> {
     Ok(
-      resource::update::<Alerter>(&self.id, self.config, user)
-        .await?,
+      resource::update::<Alerter>(
+        &self.id,
+        self.config,
+        self.revision,
+        user,
+      )
+      .await?,
     )
   }
 }
@@ -33,7 +33,8 @@ use tokio::fs;
 use crate::{
   config::core_config,
   helpers::{
-    git_token, periphery_client,
+    TokenRequester, git_token, periphery_client,
+    periphery_signing_key,
     query::get_server_with_state,
     update::{add_update, make_update},
   },
@@ -85,7 +86,15 @@ impl Resolve<WriteArgs> for UpdateBuild {
     self,
     WriteArgs { user }: &WriteArgs,
   ) -> serror::Result<Build> {
-    Ok(resource::update::<Build>(&self.id, self.config, user).await?)
+    Ok(
+      resource::update::<Build>(
+        &self.id,
+        self.config,
+        self.revision,
+        user,
+      )
+      .await?,
+    )
   }
 }
 
@@ -180,15 +189,17 @@ async fn write_dockerfile_contents_git(
 ) -> serror::Result<Update> {
   let WriteBuildFileContents { build: _, contents } = req;
 
-  let mut repo_args: RepoExecutionArgs = if !build
-    .config
-    .files_on_host
+  let (mut repo_args, requester): (
+    RepoExecutionArgs,
+    TokenRequester,
+  ) = if !build.config.files_on_host
     && !build.config.linked_repo.is_empty()
   {
-    (&crate::resource::get::<Repo>(&build.config.linked_repo).await?)
-      .into()
+    let repo =
+      crate::resource::get::<Repo>(&build.config.linked_repo).await?;
+    ((&repo).into(), (&repo).into())
   } else {
-    (&build).into()
+    ((&build).into(), (&build).into())
   };
   let root = repo_args.unique_path(&core_config().repo_directory)?;
   repo_args.destination = Some(root.display().to_string());
@@ -215,7 +226,7 @@ async fn write_dockerfile_contents_git(
   }
 
   let access_token = if let Some(account) = &repo_args.account {
-    git_token(&repo_args.provider, account, |https| repo_args.https = https)
+    git_token(&repo_args.provider, account, &requester, |https| repo_args.https = https)
     .await
     .with_context(
       || format!("Failed to get git token in call to db. Stopping run. | {} | {account}", repo_args.provider),
@@ -364,14 +375,17 @@ impl Resolve<WriteArgs> for RefreshBuildCache {
         }
       }
     } else if let Some(repo) = &repo {
-      let Some(res) = get_git_remote(&build, repo.into()).await?
+      let Some(res) =
+        get_git_remote(&build, repo.into(), &repo.into()).await?
       else {
         // Nothing to do here
         return Ok(NoData {});
       };
       res
     } else if !build.config.repo.is_empty() {
-      let Some(res) = get_git_remote(&build, (&build).into()).await?
+      let Some(res) =
+        get_git_remote(&build, (&build).into(), &(&build).into())
+          .await?
       else {
         // Nothing to do here
         return Ok(NoData {});
@@ -389,6 +403,9 @@ impl Resolve<WriteArgs> for RefreshBuildCache {
       built_hash: build.info.built_hash,
       built_message: build.info.built_message,
       built_contents: build.info.built_contents,
+      built_digest: build.info.built_digest,
+      built_platforms: build.info.built_platforms,
+      image_scans: build.info.image_scans,
       remote_path,
       remote_contents,
       remote_error,
@@ -433,7 +450,8 @@ async fn get_on_host_periphery(
         config.passkey,
         config.headers,
         Duration::from_secs(3),
-      );
+      )
+      .with_signing_key(periphery_signing_key(&builder.id));
       periphery.health_check().await?;
       Ok(periphery)
     }
@@ -450,7 +468,7 @@ async fn get_on_host_periphery(
           "Builder server is disabled or not reachable"
         ));
       };
-      periphery_client(&server)
+      periphery_client(&server).await
     }
   }
 }
@@ -473,6 +491,7 @@ async fn get_on_host_dockerfile(
 async fn get_git_remote(
   build: &Build,
   mut clone_args: RepoExecutionArgs,
+  requester: &TokenRequester,
 ) -> anyhow::Result<
   Option<(
     Option<String>,
@@ -491,7 +510,7 @@ async fn get_git_remote(
   clone_args.destination = Some(repo_path.display().to_string());
 
   let access_token = if let Some(username) = &clone_args.account {
-    git_token(&clone_args.provider, username, |https| {
+    git_token(&clone_args.provider, username, requester, |https| {
           clone_args.https = https
         })
         .await
@@ -635,6 +654,7 @@ impl Resolve<WriteArgs> for CreateBuildWebhook {
           webhook_enabled: Some(true),
           ..Default::default()
         },
+        revision: None,
       }
       .resolve(args)
       .await
@@ -53,8 +53,13 @@ impl Resolve<WriteArgs> for UpdateBuilder {
     WriteArgs { user }: &WriteArgs,
   ) -> serror::Result<Builder> {
     Ok(
-      resource::update::<Builder>(&self.id, self.config, user)
-        .await?,
+      resource::update::<Builder>(
+        &self.id,
+        self.config,
+        self.revision,
+        user,
+      )
+      .await?,
     )
   }
 }
@@ -85,7 +85,8 @@ impl Resolve<WriteArgs> for CreateDeploymentFromContainer {
         .into(),
       );
     }
-    let container = periphery_client(&server)?
+    let container = periphery_client(&server)
+      .await?
       .request(InspectContainer {
         name: self.name.clone(),
       })
@@ -170,8 +171,13 @@ impl Resolve<WriteArgs> for UpdateDeployment {
     WriteArgs { user }: &WriteArgs,
   ) -> serror::Result<Deployment> {
     Ok(
-      resource::update::<Deployment>(&self.id, self.config, user)
-        .await?,
+      resource::update::<Deployment>(
+        &self.id,
+        self.config,
+        self.revision,
+        user,
+      )
+      .await?,
     )
   }
 }
@@ -231,7 +237,8 @@ impl Resolve<WriteArgs> for RenameDeployment {
     if container_state != DeploymentState::NotDeployed {
       let server =
         resource::get::<Server>(&deployment.config.server_id).await?;
-      let log = periphery_client(&server)?
+      let log = periphery_client(&server)
+        .await?
         .request(api::container::RenameContainer {
           curr_name: deployment.name.clone(),
           new_name: name.clone(),
@@ -0,0 +1,162 @@
+use anyhow::{Context, anyhow};
+use database::mungos::{
+  by_id::find_one_by_id,
+  mongodb::bson::{doc, oid::ObjectId},
+};
+use komodo_client::{
+  api::write::*,
+  entities::{
+    Operation, ResourceTarget,
+    execution_approval::{
+      ExecutionApproval, ExecutionApprovalStatus,
+    },
+    komodo_timestamp,
+    permission::PermissionLevel,
+    procedure::Procedure,
+    user::User,
+  },
+};
+use resolver_api::Resolve;
+
+use crate::{
+  helpers::update::{add_update, make_update},
+  permission::get_check_permissions,
+  state::db_client,
+};
+
+use super::WriteArgs;
+
+impl Resolve<WriteArgs> for ApproveExecution {
+  #[instrument(name = "ApproveExecution", skip(user))]
+  async fn resolve(
+    self,
+    WriteArgs { user }: &WriteArgs,
+  ) -> serror::Result<ApproveExecutionResponse> {
+    Ok(
+      resolve_approval(
+        &self.id,
+        ExecutionApprovalStatus::Approved,
+        self.comment,
+        user,
+      )
+      .await?,
+    )
+  }
+}
+
+impl Resolve<WriteArgs> for RejectExecution {
+  #[instrument(name = "RejectExecution", skip(user))]
+  async fn resolve(
+    self,
+    WriteArgs { user }: &WriteArgs,
+  ) -> serror::Result<RejectExecutionResponse> {
+    Ok(
+      resolve_approval(
+        &self.id,
+        ExecutionApprovalStatus::Rejected,
+        self.comment,
+        user,
+      )
+      .await?,
+    )
+  }
+}
+
+/// The waiting Procedure run picks up the new status
+/// on its next poll of the approval.
+async fn resolve_approval(
+  id: &str,
+  status: ExecutionApprovalStatus,
+  comment: String,
+  user: &User,
+) -> anyhow::Result<ExecutionApproval> {
+  let approval = find_one_by_id(&db_client().execution_approvals, id)
+    .await
+    .context("Failed to query db for execution approval")?
+    .context("No execution approval found with given id")?;
+
+  let procedure = get_check_permissions::<Procedure>(
+    &approval.procedure_id,
+    user,
+    PermissionLevel::Write.into(),
+  )
+  .await?;
+
+  // Rejecting is always allowed, to stop a run.
+  if status == ExecutionApprovalStatus::Approved {
+    check_approver(&approval, user)?;
+  }
+
+  let object_id = ObjectId::parse_str(&approval.id)
+    .context("Execution approval id is not valid ObjectId")?;
+  // Only matches if it is still pending,
+  // so it can't be approved and rejected at once.
+  let res = db_client()
+    .execution_approvals
+    .update_one(
+      doc! { "_id": object_id, "status": "Pending" },
+      doc! { "$set": {
+        "status": status.to_string(),
+        "resolved_by": &user.id,
+        "resolved_at": komodo_timestamp(),
+        "comment": &comment,
+      } },
+    )
+    .await
+    .context("Failed to update execution approval on db")?;
+  if res.matched_count == 0 {
+    return Err(anyhow!(
+      "Only pending approvals can be resolved, this one is {}",
+      approval.status
+    ));
+  }
+
+  let (operation, verb) = match status {
+    ExecutionApprovalStatus::Approved => {
+      (Operation::ApproveExecution, "Approved")
+    }
+    _ => (Operation::RejectExecution, "Rejected"),
+  };
+  let mut update = make_update(
+    ResourceTarget::Procedure(procedure.id),
+    operation,
+    user,
+  );
+  let mut log = format!(
+    "{verb} stage '{}' of Procedure run {}",
+    approval.stage, approval.update_id
+  );
+  if !comment.is_empty() {
+    log.push_str(&format!("\n\n{comment}"));
+  }
+  update.push_simple_log(&format!("{verb} Execution"), log);
+  update.finalize();
+  add_update(update).await?;
+
+  find_one_by_id(&db_client().execution_approvals, id)
+    .await
+    .context("Failed to query db for execution approval")?
+    .context("No execution approval found with given id")
+}
+
+fn check_approver(
+  approval: &ExecutionApproval,
+  user: &User,
+) -> anyhow::Result<()> {
+  if approval.forbid_self_approval && approval.requested_by == user.id
+  {
+    return Err(anyhow!(
+      "The user who ran the Procedure can't approve this stage"
+    ));
+  }
+  if !approval.approvers.is_empty()
+    && !approval.approvers.iter().any(|approver| {
+      approver == &user.id || approver == &user.username
+    })
+  {
+    return Err(anyhow!(
+      "User is not in the approvers for this stage"
+    ));
+  }
+  Ok(())
+}
@@ -0,0 +1,315 @@
+use anyhow::{Context, anyhow};
+use database::mungos::{
+  by_id::{delete_one_by_id, update_one_by_id},
+  mongodb::bson::{doc, to_bson},
+};
+use derive_variants::ExtractVariant;
+use komodo_client::{
+  api::{execute::Execution, write::*},
+  entities::{
+    Operation, ResourceTarget, ScheduleFormat,
+    execution_schedule::ExecutionSchedule,
+    permission::PermissionLevel, user::User,
+  },
+};
+use reqwest::StatusCode;
+use resolver_api::Resolve;
+use serror::AddStatusCodeError;
+
+use crate::{
+  helpers::{
+    execution_queue::execution_request,
+    query::{get_execution_schedule, get_user_permission_on_target},
+    update::{add_update, execution_operation_target, make_update},
+  },
+  schedule::{
+    cancel_execution_schedule, next_occurrence,
+    update_execution_schedule,
+  },
+  state::db_client,
+};
+
+use super::WriteArgs;
+
+impl Resolve<WriteArgs> for CreateExecutionSchedule {
+  #[instrument(name = "CreateExecutionSchedule", skip(user))]
+  async fn resolve(
+    self,
+    WriteArgs { user }: &WriteArgs,
+  ) -> serror::Result<CreateExecutionScheduleResponse> {
+    if self.name.is_empty() {
+      return Err(
+        anyhow!("Execution schedule name cannot be empty")
+          .status_code(StatusCode::BAD_REQUEST),
+      );
+    }
+
+    check_schedule(
+      self.schedule_format,
+      &self.schedule,
+      &self.schedule_timezone,
+    )?;
+    check_execution_permission(&self.execution, user).await?;
+
+    let mut schedule = ExecutionSchedule {
+      id: Default::default(),
+      name: self.name,
+      description: self.description,
+      execution: self.execution,
+      enabled: self.enabled,
+      schedule_format: self.schedule_format,
+      schedule: self.schedule,
+      schedule_timezone: self.schedule_timezone,
+      missed_runs: self.missed_runs,
+      created_by: user.id.clone(),
+      next_run_at: 0,
+      schedule_error: Default::default(),
+      last_run_at: 0,
+      last_update_id: Default::default(),
+      last_run_error: Default::default(),
+    };
+
+    schedule.id = db_client()
+      .execution_schedules
+      .insert_one(&schedule)
+      .await
+      .context("Failed to create execution schedule on db")?
+      .inserted_id
+      .as_object_id()
+      .context("inserted_id is not object id")?
+      .to_string();
+
+    update_execution_schedule(&schedule);
+
+    let mut update = make_update(
+      ResourceTarget::system(),
+      Operation::CreateExecutionSchedule,
+      user,
+    );
+    update.push_simple_log(
+      "Create Execution Schedule",
+      format!(
+        "Created execution schedule '{}' with id {}\n\n{}",
+        schedule.name,
+        schedule.id,
+        schedule_log(&schedule)?
+      ),
+    );
+    update.finalize();
+    add_update(update).await?;
+
+    Ok(schedule)
+  }
+}
+
+impl Resolve<WriteArgs> for UpdateExecutionSchedule {
+  #[instrument(name = "UpdateExecutionSchedule", skip(user))]
+  async fn resolve(
+    self,
+    WriteArgs { user }: &WriteArgs,
+  ) -> serror::Result<UpdateExecutionScheduleResponse> {
+    let schedule = get_execution_schedule(&self.id).await?;
+    check_owner(&schedule, user)?;
+
+    let mut set = doc! {};
+    if let Some(description) = &self.description {
+      set.insert("description", description);
+    }
+    if let Some(execution) = &self.execution {
+      check_execution_permission(execution, user).await?;
+      set.insert(
+        "execution",
+        to_bson(execution)
+          .context("Failed to serialize execution")?,
+      );
+    }
+    if let Some(enabled) = self.enabled {
+      set.insert("enabled", enabled);
+    }
+    if let Some(schedule_format) = self.schedule_format {
+      set.insert(
+        "schedule_format",
+        to_bson(&schedule_format)
+          .context("Failed to serialize schedule format")?,
+      );
+    }
+    if let Some(schedule) = &self.schedule {
+      set.insert("schedule", schedule);
+    }
+    if let Some(schedule_timezone) = &self.schedule_timezone {
+      set.insert("schedule_timezone", schedule_timezone);
+    }
+    if let Some(missed_runs) = self.missed_runs {
+      set.insert("missed_runs", missed_runs.to_string());
+    }
+    if set.is_empty() {
+      return Ok(schedule);
+    }
+
+    check_schedule(
+      self.schedule_format.unwrap_or(schedule.schedule_format),
+      self.schedule.as_deref().unwrap_or(&schedule.schedule),
+      self
+        .schedule_timezone
+        .as_deref()
+        .unwrap_or(&schedule.schedule_timezone),
+    )?;
+
+    update_one_by_id(
+      &db_client().execution_schedules,
+      &schedule.id,
+      doc! { "$set": set },
+      None,
+    )
+    .await
+    .context("Failed to update execution schedule on db")?;
+
+    let updated = get_execution_schedule(&schedule.id).await?;
+
+    update_execution_schedule(&updated);
+
+    let mut update = make_update(
+      ResourceTarget::system(),
+      Operation::UpdateExecutionSchedule,
+      user,
+    );
+    update.push_simple_log(
+      "Update Execution Schedule",
+      format!(
+        "Updated execution schedule '{}'\n\n{}",
+        updated.name,
+        schedule_log(&updated)?
+      ),
+    );
+    update.finalize();
+    add_update(update).await?;
+
+    Ok(updated)
+  }
+}
+
+impl Resolve<WriteArgs> for DeleteExecutionSchedule {
+  #[instrument(name = "DeleteExecutionSchedule", skip(user))]
+  async fn resolve(
+    self,
+    WriteArgs { user }: &WriteArgs,
+  ) -> serror::Result<DeleteExecutionScheduleResponse> {
+    let schedule = get_execution_schedule(&self.id).await?;
+    check_owner(&schedule, user)?;
+
+    delete_one_by_id(
+      &db_client().execution_schedules,
+      &schedule.id,
+      None,
+    )
+    .await
+    .context("Failed to delete execution schedule on db")?;
+
+    db_client()
+      .execution_schedule_runs
+      .delete_many(doc! { "schedule_id": &schedule.id })
+      .await
+      .context("Failed to delete execution schedule runs on db")?;
+
+    cancel_execution_schedule(&schedule.id);
+
+    let mut update = make_update(
+      ResourceTarget::system(),
+      Operation::DeleteExecutionSchedule,
+      user,
+    );
+    update.push_simple_log(
+      "Delete Execution Schedule",
+      format!(
+        "Deleted execution schedule '{}' with id {}",
+        schedule.name, schedule.id
+      ),
+    );
+    update.finalize();
+    add_update(update).await?;
+
+    Ok(schedule)
+  }
+}
+
+fn check_owner(
+  schedule: &ExecutionSchedule,
+  user: &User,
+) -> serror::Result<()> {
+  if user.admin || schedule.created_by == user.id {
+    Ok(())
+  } else {
+    Err(
+      anyhow!(
+        "Only the creator or an admin can manage this execution schedule"
+      )
+      .status_code(StatusCode::FORBIDDEN),
+    )
+  }
+}
+
+fn check_schedule(
+  format: ScheduleFormat,
+  schedule: &str,
+  timezone: &str,
+) -> serror::Result<()> {
+  if schedule.is_empty() {
+    return Err(
+      anyhow!("Schedule cannot be empty")
+        .status_code(StatusCode::BAD_REQUEST),
+    );
+  }
+  next_occurrence(format, schedule, timezone)
+    .map_err(|e| e.status_code(StatusCode::BAD_REQUEST))?;
+  Ok(())
+}
+
+/// The user needs Execute permission on the execution target.
+/// Executions on the Core system, such as
+/// BackupCoreDatabase, can only be scheduled by admins.
+async fn check_execution_permission(
+  execution: &Execution,
+  user: &User,
+) -> serror::Result<()> {
+  let request = execution_request(execution)
+    .map_err(|e| e.status_code(StatusCode::BAD_REQUEST))?;
+  // Batch executions only run on the matched resources
+  // the user has Execute permission on.
+  if user.admin
+    || execution.extract_variant().to_string().starts_with("Batch")
+  {
+    return Ok(());
+  }
+  let (_, target) = execution_operation_target(&request).await?;
+  if matches!(target, ResourceTarget::System(_)) {
+    return Err(
+      anyhow!("Only admins can schedule system executions")
+        .status_code(StatusCode::FORBIDDEN),
+    );
+  }
+  let permission =
+    get_user_permission_on_target(user, &target).await?;
+  if permission.level < PermissionLevel::Execute {
+    return Err(
+      anyhow!(
+        "User does not have Execute permission on the execution target"
+      )
+      .status_code(StatusCode::FORBIDDEN),
+    );
+  }
+  Ok(())
+}
+
+fn schedule_log(
+  schedule: &ExecutionSchedule,
+) -> anyhow::Result<String> {
+  Ok(format!(
+    "schedule: {} ({:?})\nenabled: {}\nmissed runs: {}\n\n{}",
+    schedule.schedule,
+    schedule.schedule_format,
+    schedule.enabled,
+    schedule.missed_runs,
+    serde_json::to_string_pretty(&schedule.execution)
+      .context("Failed to serialize execution")?
+  ))
+}
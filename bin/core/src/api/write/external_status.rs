@@ -0,0 +1,174 @@
+use anyhow::{Context, anyhow};
+use database::mungos::{
+  by_id::delete_one_by_id,
+  find::find_collect,
+  mongodb::{
+    bson::{doc, to_bson},
+    options::UpdateOptions,
+  },
+};
+use komodo_client::{
+  api::write::*,
+  entities::{
+    Operation, ResourceTarget,
+    alert::{AlertData, SeverityLevel},
+    komodo_timestamp,
+  },
+};
+use reqwest::StatusCode;
+use resolver_api::Resolve;
+use serror::AddStatusCodeError;
+
+use crate::{
+  alert::send_alerts,
+  helpers::{
+    query::get_external_status,
+    update::{add_update, make_update},
+  },
+  state::db_client,
+};
+
+use super::WriteArgs;
+
+impl Resolve<WriteArgs> for PushExternalStatus {
+  #[instrument(name = "PushExternalStatus", skip(user))]
+  async fn resolve(
+    self,
+    WriteArgs { user }: &WriteArgs,
+  ) -> serror::Result<PushExternalStatusResponse> {
+    if !user.admin {
+      return Err(
+        anyhow!("Only admins can push external statuses")
+          .status_code(StatusCode::FORBIDDEN),
+      );
+    }
+
+    if self.name.is_empty() {
+      return Err(
+        anyhow!("External status name cannot be empty")
+          .status_code(StatusCode::BAD_REQUEST),
+      );
+    }
+
+    let mut set = doc! {
+      "state": to_bson(&self.state)
+        .context("Failed to serialize external state")?,
+      "message": &self.message,
+      "ts": komodo_timestamp(),
+      "pushed_by": &user.id,
+    };
+    if let Some(description) = &self.description {
+      set.insert("description", description);
+    }
+    if let Some(stale_after) = self.stale_after {
+      set.insert("stale_after", stale_after);
+    }
+    if let Some(send_alerts) = self.send_alerts {
+      set.insert("send_alerts", send_alerts);
+    }
+    if let Some(maintenance_windows) = &self.maintenance_windows {
+      set.insert(
+        "maintenance_windows",
+        to_bson(maintenance_windows)
+          .context("Failed to serialize maintenance windows")?,
+      );
+    }
+    if let Some(links) = &self.links {
+      set.insert("links", links);
+    }
+
+    let mut update = doc! { "$set": set };
+    // New external statuses send alerts by default.
+    if self.send_alerts.is_none() {
+      update.insert("$setOnInsert", doc! { "send_alerts": true });
+    }
+
+    db_client()
+      .external_statuses
+      .update_one(doc! { "name": &self.name }, update)
+      .with_options(UpdateOptions::builder().upsert(true).build())
+      .await
+      .context("Failed to push external status to db")?;
+
+    Ok(get_external_status(&self.name).await?)
+  }
+}
+
+impl Resolve<WriteArgs> for DeleteExternalStatus {
+  #[instrument(name = "DeleteExternalStatus", skip(user))]
+  async fn resolve(
+    self,
+    WriteArgs { user }: &WriteArgs,
+  ) -> serror::Result<DeleteExternalStatusResponse> {
+    if !user.admin {
+      return Err(
+        anyhow!("Only admins can delete external statuses")
+          .status_code(StatusCode::FORBIDDEN),
+      );
+    }
+
+    let status = get_external_status(&self.name).await?;
+
+    delete_one_by_id(
+      &db_client().external_statuses,
+      &status.id,
+      None,
+    )
+    .await
+    .context("Failed to delete external status on db")?;
+
+    // Resolve the open alert, as it will no longer be checked.
+    let query = doc! {
+      "resolved": false,
+      "data.type": "ExternalStatus",
+      "data.data.name": &status.name,
+    };
+    let mut alerts =
+      find_collect(&db_client().alerts, query.clone(), None)
+        .await
+        .context(
+          "Failed to query db for open external status alerts",
+        )?;
+    if !alerts.is_empty() {
+      let ts = komodo_timestamp();
+      db_client()
+        .alerts
+        .update_many(
+          query,
+          doc! { "$set": { "resolved": true, "resolved_ts": ts } },
+        )
+        .await
+        .context("Failed to resolve external status alerts on db")?;
+      for alert in &mut alerts {
+        alert.resolved = true;
+        alert.resolved_ts = Some(ts);
+        alert.level = SeverityLevel::Ok;
+        if let AlertData::ExternalStatus { message, .. } =
+          &mut alert.data
+        {
+          *message = String::from("External status was deleted");
+        }
+      }
+      send_alerts(&alerts).await;
+    }
+
+    let mut update = make_update(
+      ResourceTarget::system(),
+      Operation::DeleteExternalStatus,
+      user,
+    );
+
+    update.push_simple_log(
+      "Delete External Status",
+      format!(
+        "Deleted external status '{}' with id {}",
+        status.name, status.id
+      ),
+    );
+    update.finalize();
+
+    add_update(update).await?;
+
+    Ok(status)
+  }
+}
@@ -0,0 +1,219 @@
+use anyhow::{Context, anyhow};
+use database::mungos::{
+  by_id::{delete_one_by_id, update_one_by_id},
+  mongodb::bson::doc,
+};
+use komodo_client::{
+  api::write::*,
+  entities::{Operation, ResourceTarget, federation::FederationPeer},
+};
+use reqwest::StatusCode;
+use resolver_api::Resolve;
+use serror::AddStatusCodeError;
+
+use crate::{
+  helpers::{
+    federation::{federation_cache, refresh_federation_peer},
+    query::get_federation_peer,
+    update::{add_update, make_update},
+  },
+  state::db_client,
+};
+
+use super::WriteArgs;
+
+impl Resolve<WriteArgs> for CreateFederationPeer {
+  #[instrument(name = "CreateFederationPeer", skip(user, self), fields(name = &self.name))]
+  async fn resolve(
+    self,
+    WriteArgs { user }: &WriteArgs,
+  ) -> serror::Result<CreateFederationPeerResponse> {
+    if !user.admin {
+      return Err(
+        anyhow!("Only admins can create federation peers")
+          .status_code(StatusCode::FORBIDDEN),
+      );
+    }
+
+    if self.name.is_empty() {
+      return Err(
+        anyhow!("Federation peer name cannot be empty")
+          .status_code(StatusCode::BAD_REQUEST),
+      );
+    }
+    if self.address.is_empty()
+      || self.api_key.is_empty()
+      || self.api_secret.is_empty()
+    {
+      return Err(
+        anyhow!(
+          "Federation peer address, api key and api secret must be provided"
+        )
+        .status_code(StatusCode::BAD_REQUEST),
+      );
+    }
+
+    let mut peer = FederationPeer {
+      id: Default::default(),
+      name: self.name,
+      description: self.description,
+      address: self.address,
+      api_key: self.api_key,
+      api_secret: self.api_secret,
+      enabled: self.enabled,
+    };
+
+    peer.id = db_client()
+      .federation_peers
+      .insert_one(&peer)
+      .await
+      .context("Failed to create federation peer on db")?
+      .inserted_id
+      .as_object_id()
+      .context("inserted_id is not object id")?
+      .to_string();
+
+    tokio::spawn(refresh_federation_peer(peer.clone()));
+
+    let mut update = make_update(
+      ResourceTarget::system(),
+      Operation::CreateFederationPeer,
+      user,
+    );
+
+    update.push_simple_log(
+      "Create Federation Peer",
+      format!(
+        "Created federation peer '{}' with id {} at {}",
+        peer.name, peer.id, peer.address
+      ),
+    );
+    update.finalize();
+
+    add_update(update).await?;
+
+    peer.api_secret.clear();
+    Ok(peer)
+  }
+}
+
+impl Resolve<WriteArgs> for UpdateFederationPeer {
+  #[instrument(name = "UpdateFederationPeer", skip(user, self), fields(id = &self.id))]
+  async fn resolve(
+    self,
+    WriteArgs { user }: &WriteArgs,
+  ) -> serror::Result<UpdateFederationPeerResponse> {
+    if !user.admin {
+      return Err(
+        anyhow!("Only admins can update federation peers")
+          .status_code(StatusCode::FORBIDDEN),
+      );
+    }
+
+    let mut peer = get_federation_peer(&self.id).await?;
+
+    let mut set = doc! {};
+    if let Some(description) = &self.description {
+      set.insert("description", description);
+    }
+    if let Some(address) = &self.address {
+      set.insert("address", address);
+    }
+    if let Some(api_key) = &self.api_key {
+      set.insert("api_key", api_key);
+    }
+    if let Some(api_secret) = &self.api_secret {
+      set.insert("api_secret", api_secret);
+    }
+    if let Some(enabled) = self.enabled {
+      set.insert("enabled", enabled);
+    }
+    if set.is_empty() {
+      peer.api_secret.clear();
+      return Ok(peer);
+    }
+
+    update_one_by_id(
+      &db_client().federation_peers,
+      &peer.id,
+      doc! { "$set": set },
+      None,
+    )
+    .await
+    .context("Failed to update federation peer on db")?;
+
+    let mut updated = get_federation_peer(&peer.id).await?;
+
+    tokio::spawn(refresh_federation_peer(updated.clone()));
+
+    let mut update = make_update(
+      ResourceTarget::system(),
+      Operation::UpdateFederationPeer,
+      user,
+    );
+
+    update.push_simple_log(
+      "Update Federation Peer",
+      format!(
+        "Updated federation peer '{}'\naddress: {}\nenabled: {}{}",
+        updated.name,
+        updated.address,
+        updated.enabled,
+        if self.api_key.is_some() || self.api_secret.is_some() {
+          "\nUpdated api credentials"
+        } else {
+          ""
+        }
+      ),
+    );
+    update.finalize();
+
+    add_update(update).await?;
+
+    updated.api_secret.clear();
+    Ok(updated)
+  }
+}
+
+impl Resolve<WriteArgs> for DeleteFederationPeer {
+  #[instrument(name = "DeleteFederationPeer", skip(user))]
+  async fn resolve(
+    self,
+    WriteArgs { user }: &WriteArgs,
+  ) -> serror::Result<DeleteFederationPeerResponse> {
+    if !user.admin {
+      return Err(
+        anyhow!("Only admins can delete federation peers")
+          .status_code(StatusCode::FORBIDDEN),
+      );
+    }
+
+    let mut peer = get_federation_peer(&self.id).await?;
+
+    delete_one_by_id(&db_client().federation_peers, &peer.id, None)
+      .await
+      .context("Failed to delete federation peer on db")?;
+
+    federation_cache().remove(&peer.id).await;
+
+    let mut update = make_update(
+      ResourceTarget::system(),
+      Operation::DeleteFederationPeer,
+      user,
+    );
+
+    update.push_simple_log(
+      "Delete Federation Peer",
+      format!(
+        "Deleted federation peer '{}' with id {}",
+        peer.name, peer.id
+      ),
+    );
+    update.finalize();
+
+    add_update(update).await?;
+
+    peer.api_secret.clear();
+    Ok(peer)
+  }
+}
@@ -6,6 +6,7 @@ use axum::{
 };
 use derive_variants::{EnumVariants, ExtractVariant};
 use komodo_client::{api::write::*, entities::user::User};
+use periphery_client::audit::AuditContext;
 use resolver_api::Resolve;
 use response::Response;
 use serde::{Deserialize, Serialize};
@@ -14,25 +15,39 @@ use serror::Json;
 use typeshare::typeshare;
 use uuid::Uuid;
 
-use crate::auth::auth_request;
+use crate::{
+  auth::auth_request,
+  helpers::audit::{RequestSource, audit_entry, record_audit},
+};
 
 use super::Variant;
 
+mod access_request;
 mod action;
 mod alerter;
 mod build;
 mod builder;
 mod deployment;
+mod execution_approval;
+mod execution_schedule;
+mod external_status;
+mod federation;
 mod permissions;
 mod procedure;
 mod provider;
+mod queued_execution;
 mod repo;
 mod resource;
 mod server;
+mod server_profile;
 mod service_user;
+mod silence;
+mod snapshot;
 mod stack;
+mod swarm;
 mod sync;
 mod tag;
+mod update;
 mod user;
 mod user_group;
 mod variable;
@@ -71,6 +86,8 @@ pub enum WriteRequest {
   RemoveUserFromUserGroup(RemoveUserFromUserGroup),
   SetUsersInUserGroup(SetUsersInUserGroup),
   SetEveryoneUserGroup(SetEveryoneUserGroup),
+  AddUserGroupToUserGroup(AddUserGroupToUserGroup),
+  RemoveUserGroupFromUserGroup(RemoveUserGroupFromUserGroup),
 
   // ==== PERMISSIONS ====
   UpdateUserAdmin(UpdateUserAdmin),
@@ -80,6 +97,7 @@ pub enum WriteRequest {
 
   // ==== RESOURCE ====
   UpdateResourceMeta(UpdateResourceMeta),
+  SetResourceProtected(SetResourceProtected),
 
   // ==== SERVER ====
   CreateServer(CreateServer),
@@ -92,6 +110,11 @@ pub enum WriteRequest {
   DeleteTerminal(DeleteTerminal),
   DeleteAllTerminals(DeleteAllTerminals),
 
+  // ==== SERVER PROFILE ====
+  CreateServerProfile(CreateServerProfile),
+  UpdateServerProfile(UpdateServerProfile),
+  DeleteServerProfile(DeleteServerProfile),
+
   // ==== STACK ====
   CreateStack(CreateStack),
   CopyStack(CopyStack),
@@ -100,6 +123,7 @@ pub enum WriteRequest {
   RenameStack(RenameStack),
   WriteStackFileContents(WriteStackFileContents),
   RefreshStackCache(RefreshStackCache),
+  TakeStackOwnership(TakeStackOwnership),
   CreateStackWebhook(CreateStackWebhook),
   DeleteStackWebhook(DeleteStackWebhook),
 
@@ -111,6 +135,13 @@ pub enum WriteRequest {
   UpdateDeployment(UpdateDeployment),
   RenameDeployment(RenameDeployment),
 
+  // ==== SWARM SERVICE ====
+  CreateSwarmService(CreateSwarmService),
+  CopySwarmService(CopySwarmService),
+  DeleteSwarmService(DeleteSwarmService),
+  UpdateSwarmService(UpdateSwarmService),
+  RenameSwarmService(RenameSwarmService),
+
   // ==== BUILD ====
   CreateBuild(CreateBuild),
   CopyBuild(CopyBuild),
@@ -185,6 +216,44 @@ pub enum WriteRequest {
   UpdateVariableIsSecret(UpdateVariableIsSecret),
   DeleteVariable(DeleteVariable),
 
+  // ==== EXTERNAL STATUS ====
+  PushExternalStatus(PushExternalStatus),
+  DeleteExternalStatus(DeleteExternalStatus),
+
+  // ==== FEDERATION ====
+  CreateFederationPeer(CreateFederationPeer),
+  UpdateFederationPeer(UpdateFederationPeer),
+  DeleteFederationPeer(DeleteFederationPeer),
+
+  // ==== QUEUED EXECUTION ====
+  EnqueueExecution(EnqueueExecution),
+  CancelQueuedExecution(CancelQueuedExecution),
+
+  // ==== EXECUTION APPROVAL ====
+  ApproveExecution(ApproveExecution),
+  RejectExecution(RejectExecution),
+
+  // ==== ACCESS REQUEST ====
+  RequestAccess(RequestAccess),
+  ApproveAccessRequest(ApproveAccessRequest),
+  RejectAccessRequest(RejectAccessRequest),
+
+  // ==== UPDATE ====
+  CancelUpdate(CancelUpdate),
+
+  // ==== EXECUTION SCHEDULE ====
+  CreateExecutionSchedule(CreateExecutionSchedule),
+  UpdateExecutionSchedule(UpdateExecutionSchedule),
+  DeleteExecutionSchedule(DeleteExecutionSchedule),
+
+  // ==== SILENCE ====
+  CreateSilence(CreateSilence),
+  DeleteSilence(DeleteSilence),
+
+  // ==== SNAPSHOT ====
+  SnapshotState(SnapshotState),
+  DeleteSnapshot(DeleteSnapshot),
+
   // ==== PROVIDERS ====
   CreateGitProviderAccount(CreateGitProviderAccount),
   UpdateGitProviderAccount(UpdateGitProviderAccount),
@@ -203,6 +272,7 @@ pub fn router() -> Router {
 
 async fn variant_handler(
   user: Extension<User>,
+  source: RequestSource,
   Path(Variant { variant }): Path<Variant>,
   Json(params): Json<serde_json::Value>,
 ) -> serror::Result<axum::response::Response> {
@@ -210,20 +280,29 @@ async fn variant_handler(
     "type": variant,
     "params": params,
   }))?;
-  handler(user, Json(req)).await
+  handler(user, source, Json(req)).await
 }
 
 async fn handler(
   Extension(user): Extension<User>,
+  source: RequestSource,
   Json(request): Json<WriteRequest>,
 ) -> serror::Result<axum::response::Response> {
   let req_id = Uuid::new_v4();
 
-  let res = tokio::spawn(task(req_id, request, user))
+  let entry = audit_entry("write", &source, &request);
+  let audit_user = user.clone();
+
+  let audit = AuditContext::new(&user.username, "");
+  let res = tokio::spawn(audit.scope(task(req_id, request, user)))
     .await
-    .context("failure in spawned task");
+    .context("failure in spawned task")
+    .map_err(serror::Error::from)
+    .and_then(|res| res);
+
+  record_audit(entry, Some(&audit_user), &res);
 
-  res?
+  res
 }
 
 #[instrument(
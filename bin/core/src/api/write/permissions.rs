@@ -198,6 +198,7 @@ impl Resolve<WriteArgs> for UpdatePermissionOnTarget {
       user_target,
       resource_target,
       permission,
+      expires_at,
     } = self;
 
     // Some extra checks relevant if user target is an actual User
@@ -244,7 +245,8 @@ impl Resolve<WriteArgs> for UpdatePermissionOnTarget {
             "resource_target.type": resource_variant,
             "resource_target.id": resource_id,
             "level": permission.level.as_ref(),
-            "specific": specific
+            "specific": specific,
+            "expires_at": expires_at,
           }
         },
       )
@@ -292,7 +294,7 @@ async fn extract_user_target_with_validation(
 }
 
 /// checks if inner id is actually a `name`, and replaces it with id if so.
-async fn extract_resource_target_with_validation(
+pub(super) async fn extract_resource_target_with_validation(
   resource_target: &ResourceTarget,
 ) -> serror::Result<(ResourceTargetVariant, String)> {
   match resource_target {
@@ -342,6 +344,20 @@ async fn extract_resource_target_with_validation(
         .id;
       Ok((ResourceTargetVariant::Deployment, id))
     }
+    ResourceTarget::SwarmService(ident) => {
+      let filter = match ObjectId::from_str(ident) {
+        Ok(id) => doc! { "_id": id },
+        Err(_) => doc! { "name": ident },
+      };
+      let id = db_client()
+        .swarm_services
+        .find_one(filter)
+        .await
+        .context("failed to query db for swarm services")?
+        .context("no matching swarm service found")?
+        .id;
+      Ok((ResourceTargetVariant::SwarmService, id))
+    }
     ResourceTarget::Server(ident) => {
       let filter = match ObjectId::from_str(ident) {
         Ok(id) => doc! { "_id": id },
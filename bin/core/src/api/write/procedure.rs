@@ -45,8 +45,13 @@ impl Resolve<WriteArgs> for UpdateProcedure {
     WriteArgs { user }: &WriteArgs,
   ) -> serror::Result<UpdateProcedureResponse> {
     Ok(
-      resource::update::<Procedure>(&self.id, self.config, user)
-        .await?,
+      resource::update::<Procedure>(
+        &self.id,
+        self.config,
+        self.revision,
+        user,
+      )
+      .await?,
     )
   }
 }
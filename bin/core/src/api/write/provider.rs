@@ -33,6 +33,9 @@ impl Resolve<WriteArgs> for CreateGitProviderAccount {
 
     let mut account: GitProviderAccount = self.account.into();
 
+    // Health is managed by Core
+    account.health = Default::default();
+
     if account.domain.is_empty() {
       return Err(anyhow!("domain cannot be empty string.").into());
     }
@@ -111,6 +114,11 @@ impl Resolve<WriteArgs> for UpdateGitProviderAccount {
     // Ensure update does not change id
     self.account.id = None;
 
+    // Health is managed by Core,
+    // it is reset when the token changes.
+    self.account.health =
+      self.account.token.as_ref().map(|_| Default::default());
+
     let mut update = make_update(
       ResourceTarget::system(),
       Operation::UpdateGitProviderAccount,
@@ -224,6 +232,10 @@ impl Resolve<WriteArgs> for CreateDockerRegistryAccount {
 
     let mut account: DockerRegistryAccount = self.account.into();
 
+    // Health and token rotation are managed by Core
+    account.health = Default::default();
+    account.token_rotated_at = 0;
+
     if account.domain.is_empty() {
       return Err(anyhow!("domain cannot be empty string.").into());
     }
@@ -307,6 +319,14 @@ impl Resolve<WriteArgs> for UpdateDockerRegistryAccount {
 
     self.account.id = None;
 
+    // Health is managed by Core,
+    // it is reset when the token changes.
+    self.account.health =
+      self.account.token.as_ref().map(|_| Default::default());
+    // A new token is rotated on next use, if rotation is enabled.
+    self.account.token_rotated_at =
+      self.account.token.as_ref().map(|_| 0);
+
     let mut update = make_update(
       ResourceTarget::system(),
       Operation::UpdateDockerRegistryAccount,
@@ -0,0 +1,321 @@
+use anyhow::{Context, anyhow};
+use database::mungos::{
+  by_id::find_one_by_id,
+  mongodb::bson::{doc, oid::ObjectId},
+};
+use derive_variants::ExtractVariant;
+use komodo_client::{
+  api::{execute::*, write::*},
+  entities::{
+    Operation, ResourceTarget,
+    deployment::Deployment,
+    komodo_timestamp,
+    permission::PermissionLevel,
+    queued_execution::{QueuedExecution, QueuedExecutionStatus},
+    repo::Repo,
+    server::Server,
+    stack::Stack,
+    swarm::SwarmService,
+  },
+};
+use reqwest::StatusCode;
+use resolver_api::Resolve;
+use serror::AddStatusCodeError;
+
+use crate::{
+  helpers::{
+    execution_queue::{execution_request, spawn_queued_executions},
+    update::{add_update, make_update},
+  },
+  permission::get_check_permissions,
+  resource,
+  state::db_client,
+};
+
+use super::WriteArgs;
+
+impl Resolve<WriteArgs> for EnqueueExecution {
+  #[instrument(name = "EnqueueExecution", skip(user))]
+  async fn resolve(
+    self,
+    WriteArgs { user }: &WriteArgs,
+  ) -> serror::Result<EnqueueExecutionResponse> {
+    let server = get_check_permissions::<Server>(
+      &self.server,
+      user,
+      PermissionLevel::Execute.into(),
+    )
+    .await?;
+
+    // The queue only runs once the queued Server is reachable,
+    // so the execution must run on that Server.
+    let target_server_id = execution_server_id(&self.execution)
+      .await?
+      .with_context(|| {
+        format!(
+          "{} doesn't run on a single Server, so it can't be queued",
+          self.execution.extract_variant()
+        )
+      })
+      .status_code(StatusCode::BAD_REQUEST)?;
+    if target_server_id != server.id {
+      return Err(
+        anyhow!(
+          "The execution target is not on Server {}",
+          server.name
+        )
+        .status_code(StatusCode::BAD_REQUEST),
+      );
+    }
+    // Fail early on executions which can't run later.
+    execution_request(&self.execution)?;
+
+    let ts = komodo_timestamp();
+    let mut queued = QueuedExecution {
+      id: Default::default(),
+      server_id: server.id.clone(),
+      execution: self.execution,
+      status: QueuedExecutionStatus::Queued,
+      queued_by: user.id.clone(),
+      queued_at: ts,
+      expires_at: self
+        .expires_after
+        .filter(|after| *after > 0)
+        .map(|after| ts + after * 1000)
+        .unwrap_or_default(),
+      started_at: 0,
+      finished_at: 0,
+      update_id: Default::default(),
+      error: Default::default(),
+    };
+
+    queued.id = db_client()
+      .queued_executions
+      .insert_one(&queued)
+      .await
+      .context("Failed to add queued execution to db")?
+      .inserted_id
+      .as_object_id()
+      .context("Inserted id is not ObjectId")?
+      .to_string();
+
+    let mut update = make_update(
+      ResourceTarget::Server(server.id.clone()),
+      Operation::EnqueueExecution,
+      user,
+    );
+    update.push_simple_log(
+      "Enqueue Execution",
+      format!(
+        "Queued execution {} to run once Server {} is reachable\n\n{}",
+        queued.id,
+        server.name,
+        serde_json::to_string_pretty(&queued.execution)
+          .context("Failed to serialize execution")?
+      ),
+    );
+    update.finalize();
+    add_update(update).await?;
+
+    // Runs right away if the Server is already reachable.
+    spawn_queued_executions(server.id);
+
+    Ok(queued)
+  }
+}
+
+impl Resolve<WriteArgs> for CancelQueuedExecution {
+  #[instrument(name = "CancelQueuedExecution", skip(user))]
+  async fn resolve(
+    self,
+    WriteArgs { user }: &WriteArgs,
+  ) -> serror::Result<CancelQueuedExecutionResponse> {
+    let queued =
+      find_one_by_id(&db_client().queued_executions, &self.id)
+        .await
+        .context("Failed to query db for queued execution")?
+        .context("No queued execution found with given id")?;
+
+    let server = get_check_permissions::<Server>(
+      &queued.server_id,
+      user,
+      PermissionLevel::Execute.into(),
+    )
+    .await?;
+
+    let id = ObjectId::parse_str(&queued.id)
+      .context("Queued execution id is not valid ObjectId")?;
+    // Only matches if it hasn't been claimed to run in the meantime.
+    let res = db_client()
+      .queued_executions
+      .update_one(
+        doc! { "_id": id, "status": "Queued" },
+        doc! { "$set": {
+          "status": "Cancelled",
+          "finished_at": komodo_timestamp(),
+        } },
+      )
+      .await
+      .context("Failed to cancel queued execution on db")?;
+    if res.matched_count == 0 {
+      return Err(
+        anyhow!(
+          "Only queued executions can be cancelled, this one is {}",
+          queued.status
+        )
+        .into(),
+      );
+    }
+
+    let mut update = make_update(
+      ResourceTarget::Server(server.id),
+      Operation::CancelQueuedExecution,
+      user,
+    );
+    update.push_simple_log(
+      "Cancel Queued Execution",
+      format!("Cancelled queued execution {}", queued.id),
+    );
+    update.finalize();
+    add_update(update).await?;
+
+    Ok(
+      find_one_by_id(&db_client().queued_executions, &self.id)
+        .await
+        .context("Failed to query db for queued execution")?
+        .context("No queued execution found with given id")?,
+    )
+  }
+}
+
+/// The id of the Server the execution runs on,
+/// or None if it doesn't run on a single Server.
+async fn execution_server_id(
+  execution: &Execution,
+) -> anyhow::Result<Option<String>> {
+  let server_id = match execution {
+    Execution::Deploy(Deploy { deployment, .. })
+    | Execution::PullDeployment(PullDeployment {
+      deployment, ..
+    })
+    | Execution::StartDeployment(StartDeployment {
+      deployment,
+      ..
+    })
+    | Execution::RestartDeployment(RestartDeployment {
+      deployment,
+      ..
+    })
+    | Execution::PauseDeployment(PauseDeployment {
+      deployment,
+      ..
+    })
+    | Execution::UnpauseDeployment(UnpauseDeployment {
+      deployment,
+      ..
+    })
+    | Execution::StopDeployment(StopDeployment {
+      deployment, ..
+    })
+    | Execution::DestroyDeployment(DestroyDeployment {
+      deployment,
+      ..
+    }) => {
+      resource::get::<Deployment>(deployment)
+        .await?
+        .config
+        .server_id
+    }
+    Execution::DeployStack(DeployStack { stack, .. })
+    | Execution::DeployStackIfChanged(DeployStackIfChanged {
+      stack,
+      ..
+    })
+    | Execution::PullStack(PullStack { stack, .. })
+    | Execution::StartStack(StartStack { stack, .. })
+    | Execution::RestartStack(RestartStack { stack, .. })
+    | Execution::PauseStack(PauseStack { stack, .. })
+    | Execution::UnpauseStack(UnpauseStack { stack, .. })
+    | Execution::StopStack(StopStack { stack, .. })
+    | Execution::DestroyStack(DestroyStack { stack, .. })
+    | Execution::RunStackService(RunStackService { stack, .. })
+    | Execution::ScaleStackService(ScaleStackService {
+      stack, ..
+    }) => resource::get::<Stack>(stack).await?.config.server_id,
+    Execution::DeploySwarmService(DeploySwarmService {
+      swarm_service,
+      ..
+    })
+    | Execution::ScaleSwarmService(ScaleSwarmService {
+      swarm_service,
+      ..
+    })
+    | Execution::RemoveSwarmService(RemoveSwarmService {
+      swarm_service,
+      ..
+    }) => {
+      resource::get::<SwarmService>(swarm_service)
+        .await?
+        .config
+        .server_id
+    }
+    Execution::CloneRepo(CloneRepo { repo, .. })
+    | Execution::PullRepo(PullRepo { repo, .. })
+    | Execution::RunRepoPipeline(RunRepoPipeline { repo, .. }) => {
+      resource::get::<Repo>(repo).await?.config.server_id
+    }
+    Execution::StartContainer(StartContainer { server, .. })
+    | Execution::RestartContainer(RestartContainer {
+      server, ..
+    })
+    | Execution::PauseContainer(PauseContainer { server, .. })
+    | Execution::UnpauseContainer(UnpauseContainer {
+      server, ..
+    })
+    | Execution::StopContainer(StopContainer { server, .. })
+    | Execution::DestroyContainer(DestroyContainer {
+      server, ..
+    })
+    | Execution::StartAllContainers(StartAllContainers {
+      server,
+      ..
+    })
+    | Execution::RestartAllContainers(RestartAllContainers {
+      server,
+      ..
+    })
+    | Execution::PauseAllContainers(PauseAllContainers {
+      server,
+      ..
+    })
+    | Execution::UnpauseAllContainers(UnpauseAllContainers {
+      server,
+      ..
+    })
+    | Execution::StopAllContainers(StopAllContainers {
+      server,
+      ..
+    })
+    | Execution::PruneContainers(PruneContainers {
+      server, ..
+    })
+    | Execution::DeleteNetwork(DeleteNetwork { server, .. })
+    | Execution::PruneNetworks(PruneNetworks { server, .. })
+    | Execution::DeleteImage(DeleteImage { server, .. })
+    | Execution::PruneImages(PruneImages { server, .. })
+    | Execution::DeleteVolume(DeleteVolume { server, .. })
+    | Execution::PruneVolumes(PruneVolumes { server, .. })
+    | Execution::PruneDockerBuilders(PruneDockerBuilders {
+      server,
+      ..
+    })
+    | Execution::PruneBuildx(PruneBuildx { server, .. })
+    | Execution::PruneSystem(PruneSystem { server, .. })
+    | Execution::RunScheduledCommand(RunScheduledCommand {
+      server,
+      ..
+    }) => resource::get::<Server>(server).await?.id,
+    _ => return Ok(None),
+  };
+  Ok(Some(server_id))
+}
@@ -75,7 +75,15 @@ impl Resolve<WriteArgs> for UpdateRepo {
     self,
     WriteArgs { user }: &WriteArgs,
   ) -> serror::Result<Repo> {
-    Ok(resource::update::<Repo>(&self.id, self.config, user).await?)
+    Ok(
+      resource::update::<Repo>(
+        &self.id,
+        self.config,
+        self.revision,
+        user,
+      )
+      .await?,
+    )
   }
 }
 
@@ -127,7 +135,8 @@ impl Resolve<WriteArgs> for RenameRepo {
     let server =
       resource::get::<Server>(&repo.config.server_id).await?;
 
-    let log = match periphery_client(&server)?
+    let log = match periphery_client(&server)
+      .await?
       .request(api::git::RenameRepo {
         curr_name: to_path_compatible_name(&repo.name),
         new_name: name.clone(),
@@ -187,7 +196,7 @@ impl Resolve<WriteArgs> for RefreshRepoCache {
     clone_args.destination = Some(repo_path.display().to_string());
 
     let access_token = if let Some(username) = &clone_args.account {
-      git_token(&clone_args.provider, username, |https| {
+      git_token(&clone_args.provider, username, &(&repo).into(), |https| {
           clone_args.https = https
         })
         .await
@@ -311,6 +320,9 @@ impl Resolve<WriteArgs> for CreateRepoWebhook {
       RepoWebhookAction::Build => {
         format!("{host}/listener/github/repo/{}/build", repo.id)
       }
+      RepoWebhookAction::Pipeline => {
+        format!("{host}/listener/github/repo/{}/pipeline", repo.id)
+      }
     };
 
     for webhook in webhooks {
@@ -345,6 +357,7 @@ impl Resolve<WriteArgs> for CreateRepoWebhook {
           webhook_enabled: Some(true),
           ..Default::default()
         },
+        revision: None,
       }
       .resolve(args)
       .await
@@ -433,6 +446,9 @@ impl Resolve<WriteArgs> for DeleteRepoWebhook {
       RepoWebhookAction::Build => {
         format!("{host}/listener/github/repo/{}/build", repo.id)
       }
+      RepoWebhookAction::Pipeline => {
+        format!("{host}/listener/github/repo/{}/pipeline", repo.id)
+      }
     };
 
     for webhook in webhooks {
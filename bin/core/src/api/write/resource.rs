@@ -1,10 +1,14 @@
 use anyhow::anyhow;
 use komodo_client::{
-  api::write::{UpdateResourceMeta, UpdateResourceMetaResponse},
+  api::write::{
+    SetResourceProtected, SetResourceProtectedResponse,
+    UpdateResourceMeta, UpdateResourceMetaResponse,
+  },
   entities::{
     ResourceTarget, action::Action, alerter::Alerter, build::Build,
     builder::Builder, deployment::Deployment, procedure::Procedure,
-    repo::Repo, server::Server, stack::Stack, sync::ResourceSync,
+    repo::Repo, server::Server, stack::Stack, swarm::SwarmService,
+    sync::ResourceSync,
   },
 };
 use resolver_api::Resolve;
@@ -23,6 +27,8 @@ impl Resolve<WriteArgs> for UpdateResourceMeta {
       description: self.description,
       template: self.template,
       tags: self.tags,
+      metadata: self.metadata,
+      concurrency: self.concurrency,
     };
     match self.target {
       ResourceTarget::System(_) => {
@@ -37,6 +43,10 @@ impl Resolve<WriteArgs> for UpdateResourceMeta {
       ResourceTarget::Deployment(id) => {
         resource::update_meta::<Deployment>(&id, meta, args).await?;
       }
+      ResourceTarget::SwarmService(id) => {
+        resource::update_meta::<SwarmService>(&id, meta, args)
+          .await?;
+      }
       ResourceTarget::Build(id) => {
         resource::update_meta::<Build>(&id, meta, args).await?;
       }
@@ -66,3 +76,62 @@ impl Resolve<WriteArgs> for UpdateResourceMeta {
     Ok(UpdateResourceMetaResponse {})
   }
 }
+
+impl Resolve<WriteArgs> for SetResourceProtected {
+  #[instrument(name = "SetResourceProtected", skip(user))]
+  async fn resolve(
+    self,
+    WriteArgs { user }: &WriteArgs,
+  ) -> serror::Result<SetResourceProtectedResponse> {
+    let protected = self.protected;
+    let update = match self.target {
+      ResourceTarget::System(_) => {
+        return Err(
+          anyhow!("cannot protect System resource target").into(),
+        );
+      }
+      ResourceTarget::Server(id) => {
+        resource::set_protected::<Server>(&id, protected, user)
+          .await?
+      }
+      ResourceTarget::Deployment(id) => {
+        resource::set_protected::<Deployment>(&id, protected, user)
+          .await?
+      }
+      ResourceTarget::SwarmService(id) => {
+        resource::set_protected::<SwarmService>(&id, protected, user)
+          .await?
+      }
+      ResourceTarget::Build(id) => {
+        resource::set_protected::<Build>(&id, protected, user).await?
+      }
+      ResourceTarget::Repo(id) => {
+        resource::set_protected::<Repo>(&id, protected, user).await?
+      }
+      ResourceTarget::Builder(id) => {
+        resource::set_protected::<Builder>(&id, protected, user)
+          .await?
+      }
+      ResourceTarget::Alerter(id) => {
+        resource::set_protected::<Alerter>(&id, protected, user)
+          .await?
+      }
+      ResourceTarget::Procedure(id) => {
+        resource::set_protected::<Procedure>(&id, protected, user)
+          .await?
+      }
+      ResourceTarget::Action(id) => {
+        resource::set_protected::<Action>(&id, protected, user)
+          .await?
+      }
+      ResourceTarget::ResourceSync(id) => {
+        resource::set_protected::<ResourceSync>(&id, protected, user)
+          .await?
+      }
+      ResourceTarget::Stack(id) => {
+        resource::set_protected::<Stack>(&id, protected, user).await?
+      }
+    };
+    Ok(update)
+  }
+}
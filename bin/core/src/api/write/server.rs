@@ -1,9 +1,11 @@
+use std::net::IpAddr;
+
 use anyhow::Context;
 use formatting::format_serror;
 use komodo_client::{
   api::write::*,
   entities::{
-    NoData, Operation,
+    NoData, Operation, environment_vars_from_str, optional_string,
     permission::PermissionLevel,
     server::Server,
     to_docker_compatible_name,
@@ -16,6 +18,10 @@ use resolver_api::Resolve;
 use crate::{
   helpers::{
     periphery_client,
+    query::get_user_user_groups,
+    terminal::{
+      check_unrestricted_terminal_access, get_terminal_profile,
+    },
     update::{add_update, make_update, update_update},
   },
   permission::get_check_permissions,
@@ -64,7 +70,15 @@ impl Resolve<WriteArgs> for UpdateServer {
     self,
     WriteArgs { user }: &WriteArgs,
   ) -> serror::Result<Server> {
-    Ok(resource::update::<Server>(&self.id, self.config, user).await?)
+    Ok(
+      resource::update::<Server>(
+        &self.id,
+        self.config,
+        self.revision,
+        user,
+      )
+      .await?,
+    )
   }
 }
 
@@ -91,7 +105,18 @@ impl Resolve<WriteArgs> for CreateNetwork {
     )
     .await?;
 
-    let periphery = periphery_client(&server)?;
+    for subnet in &self.subnets {
+      let (ip, prefix) = subnet
+        .split_once('/')
+        .with_context(|| format!("Invalid subnet {subnet}"))?;
+      ip.parse::<IpAddr>()
+        .with_context(|| format!("Invalid subnet {subnet}"))?;
+      prefix
+        .parse::<u8>()
+        .with_context(|| format!("Invalid subnet {subnet}"))?;
+    }
+
+    let periphery = periphery_client(&server).await?;
 
     let mut update =
       make_update(&server, Operation::CreateNetwork, user);
@@ -102,6 +127,8 @@ impl Resolve<WriteArgs> for CreateNetwork {
       .request(api::network::CreateNetwork {
         name: to_docker_compatible_name(&self.name),
         driver: None,
+        enable_ipv6: self.enable_ipv6,
+        subnets: self.subnets,
       })
       .await
     {
@@ -132,14 +159,43 @@ impl Resolve<WriteArgs> for CreateTerminal {
     )
     .await?;
 
-    let periphery = periphery_client(&server)?;
+    let request = match &self.profile {
+      Some(profile) => {
+        let profile =
+          get_terminal_profile(&server, profile, user).await?;
+        api::terminal::CreateTerminal {
+          name: self.name,
+          command: profile.command.clone(),
+          working_dir: optional_string(&profile.working_dir),
+          env: environment_vars_from_str(&profile.environment)
+            .context("Invalid terminal profile environment")?,
+          profile: Some(profile.name.clone()),
+          user_groups: get_user_user_groups(&user.id)
+            .await?
+            .into_iter()
+            .flat_map(|group| [group.id, group.name])
+            .collect(),
+          recreate: self.recreate,
+        }
+      }
+      None => {
+        check_unrestricted_terminal_access(&server, user)?;
+        api::terminal::CreateTerminal {
+          name: self.name,
+          command: self.command,
+          working_dir: None,
+          env: Vec::new(),
+          profile: None,
+          user_groups: Vec::new(),
+          recreate: self.recreate,
+        }
+      }
+    };
+
+    let periphery = periphery_client(&server).await?;
 
     periphery
-      .request(api::terminal::CreateTerminal {
-        name: self.name,
-        command: self.command,
-        recreate: self.recreate,
-      })
+      .request(request)
       .await
       .context("Failed to create terminal on periphery")?;
 
@@ -160,7 +216,7 @@ impl Resolve<WriteArgs> for DeleteTerminal {
     )
     .await?;
 
-    let periphery = periphery_client(&server)?;
+    let periphery = periphery_client(&server).await?;
 
     periphery
       .request(api::terminal::DeleteTerminal {
@@ -186,7 +242,7 @@ impl Resolve<WriteArgs> for DeleteAllTerminals {
     )
     .await?;
 
-    let periphery = periphery_client(&server)?;
+    let periphery = periphery_client(&server).await?;
 
     periphery
       .request(api::terminal::DeleteAllTerminals {})
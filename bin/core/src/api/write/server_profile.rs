@@ -0,0 +1,226 @@
+use anyhow::{Context, anyhow};
+use database::mungos::{
+  by_id::{delete_one_by_id, update_one_by_id},
+  find::find_collect,
+  mongodb::bson::{doc, to_bson},
+};
+use komodo_client::{
+  api::write::*,
+  entities::{
+    Operation, ResourceTarget, server_profile::ServerProfile,
+  },
+};
+use reqwest::StatusCode;
+use resolver_api::Resolve;
+use serror::AddStatusCodeError;
+
+use crate::{
+  helpers::{
+    query::get_server_profile,
+    update::{add_update, make_update},
+  },
+  monitor::update_cache_for_server,
+  state::db_client,
+  sync::toml::TOML_PRETTY_OPTIONS,
+};
+
+use super::WriteArgs;
+
+impl Resolve<WriteArgs> for CreateServerProfile {
+  #[instrument(name = "CreateServerProfile", skip(user))]
+  async fn resolve(
+    self,
+    WriteArgs { user }: &WriteArgs,
+  ) -> serror::Result<CreateServerProfileResponse> {
+    if !user.admin {
+      return Err(
+        anyhow!("Only admins can create server profiles")
+          .status_code(StatusCode::FORBIDDEN),
+      );
+    }
+
+    if self.name.is_empty() {
+      return Err(
+        anyhow!("Server profile name cannot be empty")
+          .status_code(StatusCode::BAD_REQUEST),
+      );
+    }
+
+    let mut profile = ServerProfile {
+      id: Default::default(),
+      name: self.name,
+      description: self.description,
+      config: self.config,
+    };
+
+    profile.id = db_client()
+      .server_profiles
+      .insert_one(&profile)
+      .await
+      .context("Failed to create server profile on db")?
+      .inserted_id
+      .as_object_id()
+      .context("inserted_id is not object id")?
+      .to_string();
+
+    let mut update = make_update(
+      ResourceTarget::system(),
+      Operation::CreateServerProfile,
+      user,
+    );
+
+    update.push_simple_log(
+      "Create Server Profile",
+      format!(
+        "Created server profile '{}' with id {}\n{}",
+        profile.name,
+        profile.id,
+        toml_pretty::to_string(&profile.config, TOML_PRETTY_OPTIONS)
+          .unwrap_or_default()
+      ),
+    );
+    update.finalize();
+
+    add_update(update).await?;
+
+    Ok(profile)
+  }
+}
+
+impl Resolve<WriteArgs> for UpdateServerProfile {
+  #[instrument(name = "UpdateServerProfile", skip(user))]
+  async fn resolve(
+    self,
+    WriteArgs { user }: &WriteArgs,
+  ) -> serror::Result<UpdateServerProfileResponse> {
+    if !user.admin {
+      return Err(
+        anyhow!("Only admins can update server profiles")
+          .status_code(StatusCode::FORBIDDEN),
+      );
+    }
+
+    let profile = get_server_profile(&self.id).await?;
+
+    let mut set = doc! {};
+    if let Some(description) = &self.description {
+      set.insert("description", description);
+    }
+    if let Some(config) = &self.config {
+      set.insert(
+        "config",
+        to_bson(config)
+          .context("Failed to serialize server profile config")?,
+      );
+    }
+    if set.is_empty() {
+      return Ok(profile);
+    }
+
+    update_one_by_id(
+      &db_client().server_profiles,
+      &profile.id,
+      doc! { "$set": set },
+      None,
+    )
+    .await
+    .context("Failed to update server profile on db")?;
+
+    let updated = get_server_profile(&profile.id).await?;
+
+    let mut update = make_update(
+      ResourceTarget::system(),
+      Operation::UpdateServerProfile,
+      user,
+    );
+
+    update.push_simple_log(
+      "Update Server Profile",
+      format!(
+        "Updated server profile '{}'\n{}",
+        updated.name,
+        toml_pretty::to_string(&updated.config, TOML_PRETTY_OPTIONS)
+          .unwrap_or_default()
+      ),
+    );
+    update.finalize();
+
+    add_update(update).await?;
+
+    // Refresh the health of the Servers using the profile,
+    // so changed thresholds take effect immediately.
+    let servers = find_collect(
+      &db_client().servers,
+      doc! {
+        "config.server_profile": { "$in": [&updated.id, &updated.name] }
+      },
+      None,
+    )
+    .await
+    .context("Failed to query db for servers using profile")?;
+    tokio::spawn(async move {
+      for server in servers {
+        update_cache_for_server(&server, true).await;
+      }
+    });
+
+    Ok(updated)
+  }
+}
+
+impl Resolve<WriteArgs> for DeleteServerProfile {
+  #[instrument(name = "DeleteServerProfile", skip(user))]
+  async fn resolve(
+    self,
+    WriteArgs { user }: &WriteArgs,
+  ) -> serror::Result<DeleteServerProfileResponse> {
+    if !user.admin {
+      return Err(
+        anyhow!("Only admins can delete server profiles")
+          .status_code(StatusCode::FORBIDDEN),
+      );
+    }
+
+    let profile = get_server_profile(&self.id).await?;
+
+    let in_use = db_client()
+      .servers
+      .count_documents(doc! {
+        "config.server_profile": { "$in": [&profile.id, &profile.name] }
+      })
+      .await
+      .context("Failed to query db for servers using profile")?;
+    if in_use > 0 {
+      return Err(
+        anyhow!(
+          "Server profile '{}' is still used by {in_use} servers",
+          profile.name
+        )
+        .status_code(StatusCode::BAD_REQUEST),
+      );
+    }
+
+    delete_one_by_id(&db_client().server_profiles, &profile.id, None)
+      .await
+      .context("Failed to delete server profile on db")?;
+
+    let mut update = make_update(
+      ResourceTarget::system(),
+      Operation::DeleteServerProfile,
+      user,
+    );
+
+    update.push_simple_log(
+      "Delete Server Profile",
+      format!(
+        "Deleted server profile '{}' with id {}",
+        profile.name, profile.id
+      ),
+    );
+    update.finalize();
+
+    add_update(update).await?;
+
+    Ok(profile)
+  }
+}
@@ -118,6 +118,7 @@ impl Resolve<WriteArgs> for CreateApiKeyForServiceUser {
     CreateApiKey {
       name: self.name,
       expires: self.expires,
+      scope: self.scope,
     }
     .resolve(&UserArgs { user: service_user })
     .await
@@ -0,0 +1,156 @@
+use anyhow::{Context, anyhow};
+use database::mungos::by_id::{delete_one_by_id, find_one_by_id};
+use komodo_client::{
+  api::write::*,
+  entities::{
+    Operation, ResourceTarget, komodo_timestamp,
+    silence::AlertSilence,
+  },
+};
+use reqwest::StatusCode;
+use resolver_api::Resolve;
+use serror::AddStatusCodeError;
+
+use crate::{
+  helpers::update::{add_update, make_update},
+  state::db_client,
+};
+
+use super::WriteArgs;
+
+impl Resolve<WriteArgs> for CreateSilence {
+  #[instrument(name = "CreateSilence", skip(user))]
+  async fn resolve(
+    self,
+    WriteArgs { user }: &WriteArgs,
+  ) -> serror::Result<CreateSilenceResponse> {
+    if !user.admin {
+      return Err(
+        anyhow!("Only admins can create silences")
+          .status_code(StatusCode::FORBIDDEN),
+      );
+    }
+
+    let start_ts = self.start_ts.unwrap_or_else(komodo_timestamp);
+    if self.end_ts <= start_ts {
+      return Err(
+        anyhow!("Silence end_ts must be after start_ts")
+          .status_code(StatusCode::BAD_REQUEST),
+      );
+    }
+
+    let mut silence = AlertSilence {
+      id: Default::default(),
+      targets: self.targets,
+      alert_types: self.alert_types,
+      start_ts,
+      end_ts: self.end_ts,
+      comment: self.comment,
+      created_by: user.id.clone(),
+    };
+
+    silence.id = db_client()
+      .silences
+      .insert_one(&silence)
+      .await
+      .context("Failed to create silence on db")?
+      .inserted_id
+      .as_object_id()
+      .context("inserted_id is not object id")?
+      .to_string();
+
+    let mut update = make_update(
+      ResourceTarget::system(),
+      Operation::CreateSilence,
+      user,
+    );
+
+    update.push_simple_log(
+      "Create Silence",
+      format!(
+        "Created silence with id {}\n{}",
+        silence.id,
+        describe_silence(&silence)
+      ),
+    );
+    update.finalize();
+
+    add_update(update).await?;
+
+    Ok(silence)
+  }
+}
+
+impl Resolve<WriteArgs> for DeleteSilence {
+  #[instrument(name = "DeleteSilence", skip(user))]
+  async fn resolve(
+    self,
+    WriteArgs { user }: &WriteArgs,
+  ) -> serror::Result<DeleteSilenceResponse> {
+    if !user.admin {
+      return Err(
+        anyhow!("Only admins can delete silences")
+          .status_code(StatusCode::FORBIDDEN),
+      );
+    }
+
+    let silence = find_one_by_id(&db_client().silences, &self.id)
+      .await
+      .context("Failed to query db for silence")?
+      .context("No silence found with given id")?;
+
+    delete_one_by_id(&db_client().silences, &silence.id, None)
+      .await
+      .context("Failed to delete silence on db")?;
+
+    let mut update = make_update(
+      ResourceTarget::system(),
+      Operation::DeleteSilence,
+      user,
+    );
+
+    update.push_simple_log(
+      "Delete Silence",
+      format!(
+        "Deleted silence with id {}\n{}",
+        silence.id,
+        describe_silence(&silence)
+      ),
+    );
+    update.finalize();
+
+    add_update(update).await?;
+
+    Ok(silence)
+  }
+}
+
+fn describe_silence(silence: &AlertSilence) -> String {
+  let targets = if silence.targets.is_empty() {
+    String::from("all")
+  } else {
+    silence
+      .targets
+      .iter()
+      .map(|target| {
+        let (variant, id) = target.extract_variant_id();
+        format!("{variant}({id})")
+      })
+      .collect::<Vec<_>>()
+      .join(", ")
+  };
+  let alert_types = if silence.alert_types.is_empty() {
+    String::from("all")
+  } else {
+    silence
+      .alert_types
+      .iter()
+      .map(|alert_type| format!("{alert_type:?}"))
+      .collect::<Vec<_>>()
+      .join(", ")
+  };
+  format!(
+    "targets: {targets}\nalert types: {alert_types}\nstart: {}\nend: {}\ncomment: {}",
+    silence.start_ts, silence.end_ts, silence.comment
+  )
+}
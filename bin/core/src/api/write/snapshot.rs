@@ -0,0 +1,115 @@
+use anyhow::{Context, anyhow};
+use database::mungos::by_id::{delete_one_by_id, find_one_by_id};
+use komodo_client::{
+  api::write::*,
+  entities::{
+    Operation, ResourceTarget, komodo_timestamp, snapshot::Snapshot,
+  },
+};
+use reqwest::StatusCode;
+use resolver_api::Resolve;
+use serror::AddStatusCodeError;
+
+use crate::{
+  helpers::update::{add_update, make_update},
+  state::db_client,
+  sync::snapshot::current_state_toml,
+};
+
+use super::WriteArgs;
+
+impl Resolve<WriteArgs> for SnapshotState {
+  #[instrument(name = "SnapshotState", skip(user))]
+  async fn resolve(
+    self,
+    WriteArgs { user }: &WriteArgs,
+  ) -> serror::Result<SnapshotStateResponse> {
+    if !user.admin {
+      return Err(
+        anyhow!("Only admins can take snapshots")
+          .status_code(StatusCode::FORBIDDEN),
+      );
+    }
+
+    let mut snapshot = Snapshot {
+      id: Default::default(),
+      name: self.name,
+      description: self.description,
+      ts: komodo_timestamp(),
+      created_by: user.id.clone(),
+      toml: current_state_toml(user).await?,
+    };
+
+    snapshot.id = db_client()
+      .snapshots
+      .insert_one(&snapshot)
+      .await
+      .context("Failed to create snapshot on db")?
+      .inserted_id
+      .as_object_id()
+      .context("inserted_id is not object id")?
+      .to_string();
+
+    let mut update = make_update(
+      ResourceTarget::system(),
+      Operation::CreateSnapshot,
+      user,
+    );
+
+    update.push_simple_log(
+      "Create Snapshot",
+      format!(
+        "Created snapshot '{}' with id {}",
+        snapshot.name, snapshot.id
+      ),
+    );
+    update.finalize();
+
+    add_update(update).await?;
+
+    Ok(snapshot)
+  }
+}
+
+impl Resolve<WriteArgs> for DeleteSnapshot {
+  #[instrument(name = "DeleteSnapshot", skip(user))]
+  async fn resolve(
+    self,
+    WriteArgs { user }: &WriteArgs,
+  ) -> serror::Result<DeleteSnapshotResponse> {
+    if !user.admin {
+      return Err(
+        anyhow!("Only admins can delete snapshots")
+          .status_code(StatusCode::FORBIDDEN),
+      );
+    }
+
+    let snapshot = find_one_by_id(&db_client().snapshots, &self.id)
+      .await
+      .context("Failed to query db for snapshot")?
+      .context("No snapshot found with given id")?;
+
+    delete_one_by_id(&db_client().snapshots, &snapshot.id, None)
+      .await
+      .context("Failed to delete snapshot on db")?;
+
+    let mut update = make_update(
+      ResourceTarget::system(),
+      Operation::DeleteSnapshot,
+      user,
+    );
+
+    update.push_simple_log(
+      "Delete Snapshot",
+      format!(
+        "Deleted snapshot '{}' with id {}",
+        snapshot.name, snapshot.id
+      ),
+    );
+    update.finalize();
+
+    add_update(update).await?;
+
+    Ok(snapshot)
+  }
+}
@@ -1,14 +1,17 @@
 use std::path::PathBuf;
 
 use anyhow::{Context, anyhow};
-use database::mungos::mongodb::bson::{doc, to_document};
+use database::mungos::{by_id::update_one_by_id, mongodb::bson::doc};
 use formatting::format_serror;
 use komodo_client::{
   api::write::*,
   entities::{
     FileContents, NoData, Operation, RepoExecutionArgs,
+    ResourceTarget,
+    alert::{Alert, AlertData, SeverityLevel},
     all_logs_success,
     config::core::CoreConfig,
+    komodo_timestamp,
     permission::PermissionLevel,
     repo::Repo,
     server::ServerState,
@@ -22,11 +25,12 @@ use octorust::types::{
 };
 use periphery_client::api::compose::{
   GetComposeContentsOnHost, GetComposeContentsOnHostResponse,
-  WriteComposeContentsToHost,
+  ListComposeProjects, WriteComposeContentsToHost,
 };
 use resolver_api::Resolve;
 
 use crate::{
+  alert::send_alerts,
   config::core_config,
   helpers::{
     periphery_client,
@@ -34,13 +38,19 @@ use crate::{
     stack_git_token,
     update::{add_update, make_update},
   },
+  monitor::update_cache_for_server,
   permission::get_check_permissions,
   resource,
   stack::{
-    remote::{RemoteComposeContents, get_repo_compose_contents},
+    get_project_name_conflicts, get_stack_and_server,
+    remote::{
+      RemoteComposeContents, get_oci_compose_contents,
+      get_repo_compose_contents,
+    },
     services::extract_services_into_res,
+    update_stack_info,
   },
-  state::{db_client, github_client},
+  state::{all_resources_cache, db_client, github_client},
 };
 
 use super::WriteArgs;
@@ -85,7 +95,15 @@ impl Resolve<WriteArgs> for UpdateStack {
     self,
     WriteArgs { user }: &WriteArgs,
   ) -> serror::Result<Stack> {
-    Ok(resource::update::<Stack>(&self.id, self.config, user).await?)
+    Ok(
+      resource::update::<Stack>(
+        &self.id,
+        self.config,
+        self.revision,
+        user,
+      )
+      .await?,
+    )
   }
 }
 
@@ -126,6 +144,14 @@ impl Resolve<WriteArgs> for WriteStackFileContents {
       ).into());
     }
 
+    if !stack.config.files_on_host
+      && !stack.config.oci_artifact.is_empty()
+    {
+      return Err(anyhow!(
+        "Stack sources files from an OCI artifact, can't write file contents. Publish a new artifact instead."
+      ).into());
+    }
+
     let mut update =
       make_update(&stack, Operation::WriteStackContents, user);
 
@@ -170,7 +196,8 @@ async fn write_stack_file_contents_on_host(
       .into(),
     );
   }
-  match periphery_client(&server)?
+  match periphery_client(&server)
+    .await?
     .request(WriteComposeContentsToHost {
       name: stack.name,
       run_directory: stack.config.run_directory,
@@ -392,10 +419,12 @@ impl Resolve<WriteArgs> for RefreshStackCache {
     let file_contents_empty = stack.config.file_contents.is_empty();
     let repo_empty =
       stack.config.repo.is_empty() && repo.as_ref().is_none();
+    let oci_empty = stack.config.oci_artifact.is_empty();
 
     if !stack.config.files_on_host
       && file_contents_empty
       && repo_empty
+      && oci_empty
     {
       // Nothing to do without one of these
       return Ok(NoData {});
@@ -424,7 +453,8 @@ impl Resolve<WriteArgs> for RefreshStackCache {
         (vec![], None, None, None, None)
       } else if let Some(server) = server {
         let GetComposeContentsOnHostResponse { contents, errors } =
-          match periphery_client(&server)?
+          match periphery_client(&server)
+            .await?
             .request(GetComposeContentsOnHost {
               file_paths: stack.all_file_dependencies(),
               name: stack.name.clone(),
@@ -468,22 +498,27 @@ impl Resolve<WriteArgs> for RefreshStackCache {
       } else {
         (vec![], None, None, None, None)
       }
-    } else if !repo_empty {
-      // ================
-      // REPO BASED STACK
-      // ================
+    } else if !oci_empty || !repo_empty {
+      // ======================
+      // OCI / REPO BASED STACK
+      // ======================
       let RemoteComposeContents {
         successful: remote_contents,
         errored: remote_errors,
         hash: latest_hash,
         message: latest_message,
         ..
-      } = get_repo_compose_contents(
-        &stack,
-        repo.as_ref(),
-        Some(&mut missing_files),
-      )
-      .await?;
+      } = if !oci_empty {
+        get_oci_compose_contents(&stack, Some(&mut missing_files))
+          .await?
+      } else {
+        get_repo_compose_contents(
+          &stack,
+          repo.as_ref(),
+          Some(&mut missing_files),
+        )
+        .await?
+      };
 
       let project_name = stack.project_name(true);
 
@@ -537,6 +572,7 @@ impl Resolve<WriteArgs> for RefreshStackCache {
       missing_files,
       deployed_services: stack.info.deployed_services.clone(),
       deployed_project_name: stack.info.deployed_project_name.clone(),
+      blue_green_color: stack.info.blue_green_color,
       deployed_contents: stack.info.deployed_contents.clone(),
       deployed_config: stack.info.deployed_config.clone(),
       deployed_hash: stack.info.deployed_hash.clone(),
@@ -546,21 +582,219 @@ impl Resolve<WriteArgs> for RefreshStackCache {
       remote_errors,
       latest_hash,
       latest_message,
+      stored_file_contents: stack.info.stored_file_contents.clone(),
     };
 
-    let info = to_document(&info)
-      .context("failed to serialize stack info to bson")?;
+    let drifted = stack_drifted(&info);
+
+    update_stack_info(&stack.name, &info).await?;
+
+    // check to update alert
+    tokio::task::spawn(async move {
+      let db = db_client();
+      let Some(existing) = db
+        .alerts
+        .find_one(doc! {
+          "resolved": false,
+          "target.type": "Stack",
+          "target.id": &stack.id,
+          "data.type": "StackDrifted",
+        })
+        .await
+        .context("failed to query db for alert")
+        .inspect_err(|e| warn!("{e:#}"))
+        .ok()
+      else {
+        return;
+      };
+      match (existing, drifted) {
+        // OPEN A NEW ALERT
+        (None, true) => {
+          let server_name = all_resources_cache()
+            .load()
+            .servers
+            .get(&stack.config.server_id)
+            .map(|server| server.name.clone())
+            .unwrap_or(String::from("unknown"));
+          let alert = Alert {
+            id: Default::default(),
+            ts: komodo_timestamp(),
+            resolved: false,
+            level: SeverityLevel::Warning,
+            target: ResourceTarget::Stack(stack.id.clone()),
+            data: AlertData::StackDrifted {
+              id: stack.id,
+              name: stack.name,
+              server_id: stack.config.server_id,
+              server_name,
+            },
+            resolved_ts: None,
+            notifications: Default::default(),
+          };
+          db.alerts
+            .insert_one(&alert)
+            .await
+            .context("failed to open stack drifted alert")
+            .inspect_err(|e| warn!("{e:#}"))
+            .ok();
+          if stack.config.send_drift_alerts {
+            send_alerts(&[alert]).await;
+          }
+        }
+        // CLOSE ALERT
+        (Some(existing), false) => {
+          update_one_by_id(
+            &db.alerts,
+            &existing.id,
+            doc! {
+              "$set": {
+                "resolved": true,
+                "resolved_ts": komodo_timestamp()
+              }
+            },
+            None,
+          )
+          .await
+          .context("failed to close stack drifted alert")
+          .inspect_err(|e| warn!("{e:#}"))
+          .ok();
+        }
+        // NOTHING TO DO
+        _ => {}
+      }
+    });
+
+    Ok(NoData {})
+  }
+}
 
-    db_client()
-      .stacks
-      .update_one(
-        doc! { "name": &stack.name },
-        doc! { "$set": { "info": info } },
-      )
+/// Whether the latest files no longer match the deployed files,
+/// compared by contents like DeployStackIfChanged.
+fn stack_drifted(info: &StackInfo) -> bool {
+  let (Some(deployed), Some(latest)) =
+    (&info.deployed_contents, &info.remote_contents)
+  else {
+    return false;
+  };
+  latest.iter().any(|latest| {
+    deployed
+      .iter()
+      .find(|deployed| deployed.path == latest.path)
+      .map(|deployed| deployed.contents != latest.contents)
+      .unwrap_or(true)
+  })
+}
+
+impl Resolve<WriteArgs> for TakeStackOwnership {
+  #[instrument(name = "TakeStackOwnership", skip(user))]
+  async fn resolve(
+    self,
+    WriteArgs { user }: &WriteArgs,
+  ) -> serror::Result<Update> {
+    // Like RefreshStackCache, this doesn't change any config,
+    // it only records the current state of the host.
+    let (stack, server) = get_stack_and_server(
+      &self.stack,
+      user,
+      PermissionLevel::Execute.into(),
+      true,
+    )
+    .await?;
+
+    // Ensures the latest contents / services are used below.
+    RefreshStackCache {
+      stack: stack.id.clone(),
+    }
+    .resolve(&WriteArgs { user: user.clone() })
+    .await
+    .map_err(|e| e.error)
+    .context("Failed to refresh stack cache")?;
+    let stack = resource::get::<Stack>(&stack.id).await?;
+
+    let project_name = stack.project_name(true);
+
+    let project = periphery_client(&server)
+      .await?
+      .request(ListComposeProjects {})
       .await
-      .context("failed to update stack info on db")?;
+      .context("Failed to list compose projects on server")?
+      .into_iter()
+      .find(|project| project.name == project_name)
+      .with_context(|| {
+        format!(
+          "Compose project '{project_name}' not found on Server {}",
+          server.name
+        )
+      })?;
 
-    Ok(NoData {})
+    let mut update =
+      make_update(&stack, Operation::TakeStackOwnership, user);
+
+    update.push_simple_log(
+      "Take Ownership",
+      format!(
+        "Adopted compose project '{project_name}' ({}) using files:\n\n{}",
+        project.status.as_deref().unwrap_or("unknown status"),
+        project.compose_files.join("\n")
+      ),
+    );
+
+    match get_project_name_conflicts(&stack, &project_name).await {
+      Ok(conflicts) if !conflicts.is_empty() => {
+        update.push_simple_log(
+          "Project Ownership",
+          format!(
+            "WARNING: Compose project '{project_name}' is also managed by Stack/s {} on the same Server. \
+            Deploying any of them will take over the containers.",
+            conflicts.join(", ")
+          ),
+        );
+      }
+      Ok(_) => {}
+      Err(e) => warn!(
+        "Failed to check project name conflicts for Stack {} | {e:#}",
+        stack.name
+      ),
+    }
+
+    let deployed_contents = match &stack.info.remote_contents {
+      Some(contents) => contents
+        .iter()
+        .map(|f| FileContents {
+          path: f.path.clone(),
+          contents: f.contents.clone(),
+        })
+        .collect(),
+      // UI defined stacks
+      None => vec![FileContents {
+        path: stack
+          .compose_file_paths()
+          .first()
+          .cloned()
+          .unwrap_or_default(),
+        contents: stack.config.file_contents.clone(),
+      }],
+    };
+
+    let info = StackInfo {
+      deployed_project_name: Some(project_name),
+      blue_green_color: None,
+      deployed_services: Some(stack.info.latest_services.clone()),
+      deployed_contents: Some(deployed_contents),
+      deployed_config: None,
+      deployed_hash: stack.info.latest_hash.clone(),
+      deployed_message: stack.info.latest_message.clone(),
+      ..stack.info
+    };
+    update_stack_info(&stack.name, &info).await?;
+
+    // Pick up the adopted containers immediately.
+    update_cache_for_server(&server, true).await;
+
+    update.finalize();
+    update.id = add_update(update.clone()).await?;
+
+    Ok(update)
   }
 }
 
@@ -675,6 +909,7 @@ impl Resolve<WriteArgs> for CreateStackWebhook {
           webhook_enabled: Some(true),
           ..Default::default()
         },
+        revision: None,
       }
       .resolve(args)
       .await
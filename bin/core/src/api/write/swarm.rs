@@ -0,0 +1,140 @@
+use anyhow::{Context, anyhow};
+use database::mungos::{by_id::update_one_by_id, mongodb::bson::doc};
+use komodo_client::{
+  api::write::*,
+  entities::{
+    Operation, komodo_timestamp,
+    permission::PermissionLevel,
+    swarm::{SwarmService, SwarmServiceState},
+    to_docker_compatible_name,
+    update::Update,
+  },
+};
+use resolver_api::Resolve;
+
+use crate::{
+  helpers::{
+    query::get_swarm_service_state,
+    update::{add_update, make_update},
+  },
+  permission::get_check_permissions,
+  resource,
+  state::db_client,
+};
+
+use super::WriteArgs;
+
+impl Resolve<WriteArgs> for CreateSwarmService {
+  #[instrument(name = "CreateSwarmService", skip(user))]
+  async fn resolve(
+    self,
+    WriteArgs { user }: &WriteArgs,
+  ) -> serror::Result<SwarmService> {
+    resource::create::<SwarmService>(&self.name, self.config, user)
+      .await
+  }
+}
+
+impl Resolve<WriteArgs> for CopySwarmService {
+  #[instrument(name = "CopySwarmService", skip(user))]
+  async fn resolve(
+    self,
+    WriteArgs { user }: &WriteArgs,
+  ) -> serror::Result<SwarmService> {
+    let SwarmService { config, .. } =
+      get_check_permissions::<SwarmService>(
+        &self.id,
+        user,
+        PermissionLevel::Read.into(),
+      )
+      .await?;
+    resource::create::<SwarmService>(&self.name, config.into(), user)
+      .await
+  }
+}
+
+impl Resolve<WriteArgs> for DeleteSwarmService {
+  #[instrument(name = "DeleteSwarmService", skip(args))]
+  async fn resolve(
+    self,
+    args: &WriteArgs,
+  ) -> serror::Result<SwarmService> {
+    Ok(resource::delete::<SwarmService>(&self.id, args).await?)
+  }
+}
+
+impl Resolve<WriteArgs> for UpdateSwarmService {
+  #[instrument(name = "UpdateSwarmService", skip(user))]
+  async fn resolve(
+    self,
+    WriteArgs { user }: &WriteArgs,
+  ) -> serror::Result<SwarmService> {
+    Ok(
+      resource::update::<SwarmService>(
+        &self.id,
+        self.config,
+        self.revision,
+        user,
+      )
+      .await?,
+    )
+  }
+}
+
+impl Resolve<WriteArgs> for RenameSwarmService {
+  #[instrument(name = "RenameSwarmService", skip(user))]
+  async fn resolve(
+    self,
+    WriteArgs { user }: &WriteArgs,
+  ) -> serror::Result<Update> {
+    let swarm_service = get_check_permissions::<SwarmService>(
+      &self.id,
+      user,
+      PermissionLevel::Write.into(),
+    )
+    .await?;
+
+    // The stack name can't be changed in place,
+    // so the services must be removed first.
+    let state = get_swarm_service_state(&swarm_service.id).await?;
+    if state != SwarmServiceState::NotDeployed {
+      return Err(
+        anyhow!(
+          "Cannot rename Swarm Service while it is {state}. Remove the services first."
+        )
+        .into(),
+      );
+    }
+
+    let name = to_docker_compatible_name(&self.name);
+
+    let mut update = make_update(
+      &swarm_service,
+      Operation::RenameSwarmService,
+      user,
+    );
+
+    update_one_by_id(
+      &db_client().swarm_services,
+      &swarm_service.id,
+      database::mungos::update::Update::Set(
+        doc! { "name": &name, "updated_at": komodo_timestamp() },
+      ),
+      None,
+    )
+    .await
+    .context("Failed to update Swarm Service name on db")?;
+
+    update.push_simple_log(
+      "Rename Swarm Service",
+      format!(
+        "Renamed Swarm Service from {} to {}",
+        swarm_service.name, name
+      ),
+    );
+    update.finalize();
+    update.id = add_update(update.clone()).await?;
+
+    Ok(update)
+  }
+}
@@ -46,6 +46,7 @@ use crate::{
   api::read::ReadArgs,
   config::core_config,
   helpers::{
+    TokenRequester,
     all_resources::AllResourcesById,
     git_token,
     query::get_id_to_tags,
@@ -108,8 +109,13 @@ impl Resolve<WriteArgs> for UpdateResourceSync {
     WriteArgs { user }: &WriteArgs,
   ) -> serror::Result<ResourceSync> {
     Ok(
-      resource::update::<ResourceSync>(&self.id, self.config, user)
-        .await?,
+      resource::update::<ResourceSync>(
+        &self.id,
+        self.config,
+        self.revision,
+        user,
+      )
+      .await?,
     )
   }
 }
@@ -257,16 +263,19 @@ async fn write_sync_file_contents_git(
     contents,
   } = req;
 
-  let mut repo_args: RepoExecutionArgs = if let Some(repo) = &repo {
-    repo.into()
+  let (mut repo_args, requester): (
+    RepoExecutionArgs,
+    TokenRequester,
+  ) = if let Some(repo) = &repo {
+    (repo.into(), repo.into())
   } else {
-    (&sync).into()
+    ((&sync).into(), (&sync).into())
   };
   let root = repo_args.unique_path(&core_config().repo_directory)?;
   repo_args.destination = Some(root.display().to_string());
 
   let git_token = if let Some(account) = &repo_args.account {
-    git_token(&repo_args.provider, account, |https| repo_args.https = https)
+    git_token(&repo_args.provider, account, &requester, |https| repo_args.https = https)
     .await
     .with_context(
       || format!("Failed to get git token in call to db. Stopping run. | {} | {account}", repo_args.provider),
@@ -415,6 +424,15 @@ impl Resolve<WriteArgs> for CommitSync {
       None
     };
 
+    if sync.config.artifact_source() {
+      return Err(
+        anyhow!(
+          "Cannot commit to OCI artifact or S3 based syncs. Publish the files to the source instead."
+        )
+        .into(),
+      );
+    }
+
     let file_contents_empty = sync.config.file_contents_empty();
 
     let fresh_sync = !sync.config.files_on_host
@@ -511,9 +529,14 @@ impl Resolve<WriteArgs> for CommitSync {
         unreachable!()
       };
       let args: RepoExecutionArgs = repo.into();
-      if let Err(e) =
-        commit_git_sync(args, &resource_path, &res.toml, &mut update)
-          .await
+      if let Err(e) = commit_git_sync(
+        args,
+        &repo.into(),
+        &resource_path,
+        &res.toml,
+        &mut update,
+      )
+      .await
       {
         update.push_error_log(
           "Write resource file",
@@ -529,9 +552,14 @@ impl Resolve<WriteArgs> for CommitSync {
         unreachable!()
       };
       let args: RepoExecutionArgs = (&sync).into();
-      if let Err(e) =
-        commit_git_sync(args, &resource_path, &res.toml, &mut update)
-          .await
+      if let Err(e) = commit_git_sync(
+        args,
+        &(&sync).into(),
+        &resource_path,
+        &res.toml,
+        &mut update,
+      )
+      .await
       {
         update.push_error_log(
           "Write resource file",
@@ -581,6 +609,7 @@ impl Resolve<WriteArgs> for CommitSync {
 
 async fn commit_git_sync(
   mut args: RepoExecutionArgs,
+  requester: &TokenRequester,
   resource_path: &Path,
   toml: &str,
   update: &mut Update,
@@ -589,7 +618,7 @@ async fn commit_git_sync(
   args.destination = Some(root.display().to_string());
 
   let access_token = if let Some(account) = &args.account {
-    git_token(&args.provider, account, |https| args.https = https)
+    git_token(&args.provider, account, requester, |https| args.https = https)
       .await
       .with_context(
         || format!("Failed to get git token in call to db. Stopping run. | {} | {account}", args.provider),
@@ -657,6 +686,7 @@ impl Resolve<WriteArgs> for RefreshResourceSyncPending {
       && sync.config.file_contents.is_empty()
       && sync.config.repo.is_empty()
       && sync.config.linked_repo.is_empty()
+      && !sync.config.artifact_source()
     {
       // Sync not configured, nothing to refresh
       return Ok(sync);
@@ -935,6 +965,7 @@ impl Resolve<WriteArgs> for RefreshResourceSyncPending {
             target: ResourceTarget::ResourceSync(id.clone()),
             data: AlertData::ResourceSyncPendingUpdates { id, name },
             resolved_ts: None,
+            notifications: Default::default(),
           };
           db.alerts
             .insert_one(&alert)
@@ -1083,6 +1114,7 @@ impl Resolve<WriteArgs> for CreateSyncWebhook {
           webhook_enabled: Some(true),
           ..Default::default()
         },
+        revision: None,
       }
       .resolve(args)
       .await
@@ -0,0 +1,134 @@
+use anyhow::{Context, anyhow};
+use database::mungos::{by_id::find_one_by_id, mongodb::bson::doc};
+use komodo_client::{
+  api::write::*,
+  entities::{
+    Operation, ResourceTarget, komodo_timestamp,
+    permission::PermissionLevel, update::UpdateStatus,
+  },
+};
+use reqwest::StatusCode;
+use resolver_api::Resolve;
+use serror::AddStatusCodeError;
+
+use crate::{
+  helpers::{
+    cancel::cancel_execution,
+    query::get_user_permission_on_target,
+    update::{add_update, make_update, update_update},
+  },
+  state::db_client,
+};
+
+use super::WriteArgs;
+
+impl Resolve<WriteArgs> for CancelUpdate {
+  #[instrument(name = "CancelUpdate", skip(user))]
+  async fn resolve(
+    self,
+    WriteArgs { user }: &WriteArgs,
+  ) -> serror::Result<CancelUpdateResponse> {
+    let mut cancelled =
+      find_one_by_id(&db_client().updates, &self.id)
+        .await
+        .context("Failed to query db for update")?
+        .context("No update found with given id")
+        .status_code(StatusCode::NOT_FOUND)?;
+
+    if cancelled.status != UpdateStatus::InProgress {
+      return Err(
+        anyhow!("Only in progress Updates can be cancelled")
+          .status_code(StatusCode::BAD_REQUEST),
+      );
+    }
+
+    if !user.admin && cancelled.operator != user.id {
+      if matches!(cancelled.target, ResourceTarget::System(_)) {
+        return Err(
+          anyhow!("Only admins can cancel system executions")
+            .status_code(StatusCode::FORBIDDEN),
+        );
+      }
+      let permission =
+        get_user_permission_on_target(user, &cancelled.target)
+          .await?;
+      if permission.level < PermissionLevel::Execute {
+        return Err(
+          anyhow!(
+            "User does not have Execute permission on the Update target"
+          )
+          .status_code(StatusCode::FORBIDDEN),
+        );
+      }
+    }
+
+    // The execution closes the Update once it has stopped.
+    if !cancel_execution(&cancelled.id).await
+      && !cancel_on_other_core(&cancelled.id, &user.id).await?
+    {
+      // Not running on any Core, such as after a restart.
+      cancelled.push_error_log(
+        "Cancelled",
+        format!(
+          "Cancelled by {}. The execution isn't running on this Core, so the Update is only marked as cancelled.",
+          user.username
+        ),
+      );
+      cancelled.finalize();
+      update_update(cancelled.clone()).await?;
+      // Release the resource for other executions.
+      // Only expired, as a live lock would be renewed.
+      db_client()
+        .resource_locks
+        .delete_many(doc! {
+          "update_id": &cancelled.id,
+          "expires_at": { "$lt": komodo_timestamp() },
+        })
+        .await
+        .context("Failed to release resource lock")?;
+    }
+
+    let mut update = make_update(
+      cancelled.target.clone(),
+      Operation::CancelUpdate,
+      user,
+    );
+    update.push_simple_log(
+      "Cancel Update",
+      format!(
+        "Cancelled {} (Update {})",
+        cancelled.operation, cancelled.id
+      ),
+    );
+    update.finalize();
+    add_update(update).await?;
+
+    find_one_by_id(&db_client().updates, &cancelled.id)
+      .await
+      .context("Failed to query db for update")?
+      .context("No update found with given id")
+      .map_err(Into::into)
+  }
+}
+
+/// Executions holding a live resource lock are running on another Core.
+/// Flags the lock as cancelled, so the holder cancels the execution
+/// the next time it renews the lock.
+/// Returns false if no live lock is held for the Update.
+async fn cancel_on_other_core(
+  update_id: &str,
+  user_id: &str,
+) -> anyhow::Result<bool> {
+  let res = db_client()
+    .resource_locks
+    .update_many(
+      doc! {
+        "update_id": update_id,
+        "expires_at": { "$gte": komodo_timestamp() },
+      },
+      doc! { "$set": { "cancelled_by": user_id } },
+    )
+    .await
+    .context("Failed to cancel execution through resource lock")?;
+  Ok(res.matched_count > 0)
+}
@@ -14,7 +14,9 @@ use reqwest::StatusCode;
 use resolver_api::Resolve;
 use serror::AddStatusCodeError;
 
-use crate::state::db_client;
+use crate::{
+  helpers::query::get_nested_user_group_ids, state::db_client,
+};
 
 use super::WriteArgs;
 
@@ -35,6 +37,7 @@ impl Resolve<WriteArgs> for CreateUserGroup {
       id: Default::default(),
       everyone: Default::default(),
       users: Default::default(),
+      groups: Default::default(),
       all: Default::default(),
       updated_at: komodo_timestamp(),
     };
@@ -109,6 +112,11 @@ impl Resolve<WriteArgs> for DeleteUserGroup {
       .await
       .context("failed to delete UserGroup from db")?;
 
+    db.user_groups
+      .update_many(doc! {}, doc! { "$pull": { "groups": &self.id } })
+      .await
+      .context("failed to remove UserGroup from parent groups. User Group has been deleted")?;
+
     db.permissions
       .delete_many(doc! {
         "user_target.type": "UserGroup",
@@ -300,3 +308,105 @@ impl Resolve<WriteArgs> for SetEveryoneUserGroup {
     Ok(res)
   }
 }
+
+impl Resolve<WriteArgs> for AddUserGroupToUserGroup {
+  #[instrument(name = "AddUserGroupToUserGroup", skip(admin), fields(admin = admin.username))]
+  async fn resolve(
+    self,
+    WriteArgs { user: admin }: &WriteArgs,
+  ) -> serror::Result<UserGroup> {
+    if !admin.admin {
+      return Err(
+        anyhow!("This call is admin-only")
+          .status_code(StatusCode::FORBIDDEN),
+      );
+    }
+
+    let db = db_client();
+
+    let user_group = find_user_group(&self.user_group).await?;
+    let nested = find_user_group(&self.nested).await?;
+
+    // The group can't be nested in itself, or in any group nested in it.
+    if get_nested_user_group_ids(&nested.id)
+      .await?
+      .contains(&user_group.id)
+    {
+      return Err(
+        anyhow!(
+          "Cannot nest {} in {}, as it would create a cycle",
+          nested.name,
+          user_group.name
+        )
+        .status_code(StatusCode::BAD_REQUEST),
+      );
+    }
+
+    update_one_by_id(
+      &db.user_groups,
+      &user_group.id,
+      doc! { "$addToSet": { "groups": &nested.id } },
+      None,
+    )
+    .await
+    .context("failed to add nested group to group on db")?;
+    let res = find_one_by_id(&db.user_groups, &user_group.id)
+      .await
+      .context("failed to query db for UserGroups")?
+      .context("no user group with given id")?;
+    Ok(res)
+  }
+}
+
+impl Resolve<WriteArgs> for RemoveUserGroupFromUserGroup {
+  #[instrument(name = "RemoveUserGroupFromUserGroup", skip(admin), fields(admin = admin.username))]
+  async fn resolve(
+    self,
+    WriteArgs { user: admin }: &WriteArgs,
+  ) -> serror::Result<UserGroup> {
+    if !admin.admin {
+      return Err(
+        anyhow!("This call is admin-only")
+          .status_code(StatusCode::FORBIDDEN),
+      );
+    }
+
+    let db = db_client();
+
+    let nested = find_user_group(&self.nested).await?;
+
+    let filter = match ObjectId::from_str(&self.user_group) {
+      Ok(id) => doc! { "_id": id },
+      Err(_) => doc! { "name": &self.user_group },
+    };
+    db.user_groups
+      .update_one(
+        filter.clone(),
+        doc! { "$pull": { "groups": &nested.id } },
+      )
+      .await
+      .context("failed to remove nested group from group on db")?;
+    let res = db
+      .user_groups
+      .find_one(filter)
+      .await
+      .context("failed to query db for UserGroups")?
+      .context("no user group with given id")?;
+    Ok(res)
+  }
+}
+
+async fn find_user_group(
+  id_or_name: &str,
+) -> anyhow::Result<UserGroup> {
+  let filter = match ObjectId::from_str(id_or_name) {
+    Ok(id) => doc! { "_id": id },
+    Err(_) => doc! { "name": id_or_name },
+  };
+  db_client()
+    .user_groups
+    .find_one(filter)
+    .await
+    .context("failed to query db for UserGroups")?
+    .with_context(|| format!("no user group matching {id_or_name}"))
+}
@@ -0,0 +1,53 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Context;
+use database::mungos::mongodb::bson::doc;
+
+use crate::state::db_client;
+
+/// Syncs the user's membership of the user groups in `mapping`
+/// with the groups given by the login provider.
+/// The user is added to the user groups mapped from `provider_groups`,
+/// and removed from the other user groups in the mapping.
+pub async fn sync_mapped_user_groups(
+  user_id: &str,
+  provider_groups: &[String],
+  mapping: &HashMap<String, String>,
+) -> anyhow::Result<()> {
+  if mapping.is_empty() {
+    return Ok(());
+  }
+
+  let member = provider_groups
+    .iter()
+    .filter_map(|group| mapping.get(group))
+    .cloned()
+    .collect::<HashSet<_>>();
+  let not_member = mapping
+    .values()
+    .filter(|user_group| !member.contains(*user_group))
+    .cloned()
+    .collect::<HashSet<_>>();
+
+  let db = db_client();
+  if !member.is_empty() {
+    db.user_groups
+      .update_many(
+        doc! { "name": { "$in": member.into_iter().collect::<Vec<_>>() } },
+        doc! { "$addToSet": { "users": user_id } },
+      )
+      .await
+      .context("Failed to add user to mapped user groups")?;
+  }
+  if !not_member.is_empty() {
+    db.user_groups
+      .update_many(
+        doc! { "name": { "$in": not_member.into_iter().collect::<Vec<_>>() } },
+        doc! { "$pull": { "users": user_id } },
+      )
+      .await
+      .context("Failed to remove user from mapped user groups")?;
+  }
+
+  Ok(())
+}
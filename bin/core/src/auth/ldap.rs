@@ -0,0 +1,226 @@
+use anyhow::{Context, anyhow};
+use database::mungos::mongodb::bson::{Document, doc};
+use komodo_client::{
+  api::auth::{LoginLdapUser, LoginLdapUserResponse},
+  entities::{
+    komodo_timestamp,
+    user::{User, UserConfig},
+  },
+};
+use ldap3::{
+  Ldap, LdapConnAsync, LdapConnSettings, Scope, SearchEntry,
+  ldap_escape,
+};
+use resolver_api::Resolve;
+
+use crate::{
+  api::auth::{AuthArgs, record_login},
+  auth::groups::sync_mapped_user_groups,
+  config::core_config,
+  helpers::random_string,
+  state::{db_client, jwt_client},
+};
+
+/// The user found in the directory.
+struct LdapUser {
+  dn: String,
+  groups: Vec<String>,
+}
+
+impl Resolve<AuthArgs> for LoginLdapUser {
+  #[instrument(name = "LoginLdapUser", level = "debug", skip(self))]
+  async fn resolve(
+    self,
+    args: &AuthArgs,
+  ) -> serror::Result<LoginLdapUserResponse> {
+    let username = self.username.clone();
+    let res: serror::Result<LoginLdapUserResponse> = async {
+      let config = core_config();
+      if !config.ldap_enabled {
+        return Err(anyhow!("LDAP auth is not enabled").into());
+      }
+      if self.username.is_empty() {
+        return Err(
+          anyhow!("Username cannot be empty string").into(),
+        );
+      }
+      // An empty password is an unauthenticated bind,
+      // which many servers accept as a success.
+      if self.password.is_empty() {
+        return Err(
+          anyhow!("Password cannot be empty string").into(),
+        );
+      }
+
+      let ldap_user =
+        authenticate_ldap_user(&self.username, &self.password)
+          .await?;
+
+      let user_id =
+        get_or_create_ldap_user(&self.username, ldap_user.dn).await?;
+
+      sync_mapped_user_groups(
+        &user_id,
+        &ldap_user.groups,
+        &config.ldap_group_mapping,
+      )
+      .await
+      .context("Failed to sync user groups from LDAP groups")?;
+
+      jwt_client()
+        .encode(user_id)
+        .context("failed at generating jwt for user")
+        .map_err(Into::into)
+    }
+    .await;
+    record_login(args, "LoginLdapUser", username, &res);
+    res
+  }
+}
+
+/// Finds the user in the directory, then binds as them to check the password.
+async fn authenticate_ldap_user(
+  username: &str,
+  password: &str,
+) -> anyhow::Result<LdapUser> {
+  let config = core_config();
+  let settings =
+    LdapConnSettings::new().set_starttls(config.ldap_start_tls);
+  let (conn, mut ldap) =
+    LdapConnAsync::with_settings(settings, &config.ldap_url)
+      .await
+      .with_context(|| {
+        format!(
+          "Failed to connect to LDAP server at {}",
+          config.ldap_url
+        )
+      })?;
+  ldap3::drive!(conn);
+
+  let res = authenticate_with(&mut ldap, username, password).await;
+  let _ = ldap.unbind().await;
+  res
+}
+
+async fn authenticate_with(
+  ldap: &mut Ldap,
+  username: &str,
+  password: &str,
+) -> anyhow::Result<LdapUser> {
+  let config = core_config();
+
+  if !config.ldap_bind_dn.is_empty() {
+    ldap
+      .simple_bind(&config.ldap_bind_dn, &config.ldap_bind_password)
+      .await
+      .context("Failed to bind to LDAP server")?
+      .success()
+      .context("Failed to bind to LDAP server with ldap_bind_dn")?;
+  }
+
+  let filter = config
+    .ldap_user_filter
+    .replace("{username}", &ldap_escape(username));
+  let (entries, _) = ldap
+    .search(
+      &config.ldap_user_base_dn,
+      Scope::Subtree,
+      &filter,
+      vec![config.ldap_group_attribute.as_str()],
+    )
+    .await
+    .context("Failed to search LDAP server for user")?
+    .success()
+    .context("LDAP user search failed")?;
+
+  let mut entries = entries.into_iter();
+  let (Some(entry), None) = (entries.next(), entries.next()) else {
+    return Err(anyhow!("invalid credentials"));
+  };
+  let SearchEntry { dn, attrs, .. } = SearchEntry::construct(entry);
+
+  ldap
+    .simple_bind(&dn, password)
+    .await
+    .context("Failed to bind to LDAP server as user")?
+    .success()
+    .map_err(|_| anyhow!("invalid credentials"))?;
+
+  // Attribute names are case insensitive.
+  let groups = attrs
+    .into_iter()
+    .find(|(attr, _)| {
+      attr.eq_ignore_ascii_case(&config.ldap_group_attribute)
+    })
+    .map(|(_, groups)| groups)
+    .unwrap_or_default();
+
+  Ok(LdapUser { dn, groups })
+}
+
+/// Returns the id of the user with the DN,
+/// creating the user on first login.
+async fn get_or_create_ldap_user(
+  username: &str,
+  dn: String,
+) -> anyhow::Result<String> {
+  let db_client = db_client();
+  let user = db_client
+    .users
+    .find_one(doc! {
+      "config.type": "Ldap",
+      "config.data.dn": &dn,
+    })
+    .await
+    .context("failed at find user query from database")?;
+  if let Some(user) = user {
+    return Ok(user.id);
+  }
+
+  let no_users_exist =
+    db_client.users.find_one(Document::new()).await?.is_none();
+  let core_config = core_config();
+  if !no_users_exist && core_config.disable_user_registration {
+    return Err(anyhow!("User registration is disabled"));
+  }
+
+  // Modify username if it already exists
+  let mut username = username.to_string();
+  if db_client
+    .users
+    .find_one(doc! { "username": &username })
+    .await
+    .context("Failed to query users collection")?
+    .is_some()
+  {
+    username += "-";
+    username += &random_string(5);
+  };
+
+  let user = User {
+    id: Default::default(),
+    username,
+    enabled: no_users_exist || core_config.enable_new_users,
+    admin: no_users_exist,
+    super_admin: no_users_exist,
+    create_server_permissions: no_users_exist,
+    create_build_permissions: no_users_exist,
+    updated_at: komodo_timestamp(),
+    last_update_view: 0,
+    recents: Default::default(),
+    all: Default::default(),
+    config: UserConfig::Ldap { dn },
+  };
+
+  let user_id = db_client
+    .users
+    .insert_one(user)
+    .await
+    .context("failed to create user on database")?
+    .inserted_id
+    .as_object_id()
+    .context("inserted_id is not ObjectId")?
+    .to_string();
+
+  Ok(user_id)
+}
@@ -5,7 +5,11 @@ use axum::{
   response::Response,
 };
 use database::mungos::mongodb::bson::doc;
-use komodo_client::entities::{komodo_timestamp, user::User};
+use komodo_client::entities::{
+  api_key::{ApiKey, ApiKeyScope},
+  komodo_timestamp,
+  user::User,
+};
 use reqwest::StatusCode;
 use serde::Deserialize;
 use serror::AddStatusCode;
@@ -17,12 +21,17 @@ use crate::{
 
 use self::jwt::JwtClaims;
 
+pub use self::scope::normalize_api_key_scope;
+
 pub mod github;
 pub mod google;
 pub mod jwt;
 pub mod oidc;
 
+mod groups;
+mod ldap;
 mod local;
+mod scope;
 
 const STATE_PREFIX_LENGTH: usize = 20;
 
@@ -37,9 +46,17 @@ pub async fn auth_request(
   mut req: Request,
   next: Next,
 ) -> serror::Result<Response> {
-  let user = authenticate_check_enabled(&headers)
+  let (user_id, scope) = authenticate_headers(&headers)
+    .await
+    .status_code(StatusCode::UNAUTHORIZED)?;
+  let user = check_enabled(user_id)
     .await
     .status_code(StatusCode::UNAUTHORIZED)?;
+  if let Some(scope) = scope.filter(|scope| !scope.is_empty()) {
+    req = scope::check_api_key_scope(&scope, req)
+      .await
+      .status_code(StatusCode::FORBIDDEN)?;
+  }
   req.extensions_mut().insert(user);
   Ok(next.run(req).await)
 }
@@ -48,6 +65,17 @@ pub async fn auth_request(
 pub async fn get_user_id_from_headers(
   headers: &HeaderMap,
 ) -> anyhow::Result<String> {
+  authenticate_headers(headers)
+    .await
+    .map(|(user_id, _)| user_id)
+}
+
+/// Returns the user id, and the api key scope
+/// if the request used an api key.
+#[instrument(level = "debug")]
+async fn authenticate_headers(
+  headers: &HeaderMap,
+) -> anyhow::Result<(String, Option<ApiKeyScope>)> {
   match (
     headers.get("authorization"),
     headers.get("x-api-key"),
@@ -58,14 +86,16 @@ pub async fn get_user_id_from_headers(
       let jwt = jwt.to_str().context("jwt is not str")?;
       auth_jwt_get_user_id(jwt)
         .await
+        .map(|user_id| (user_id, None))
         .context("failed to authenticate jwt")
     }
     (None, Some(key), Some(secret)) => {
       // USE API KEY / SECRET
       let key = key.to_str().context("key is not str")?;
       let secret = secret.to_str().context("secret is not str")?;
-      auth_api_key_get_user_id(key, secret)
+      auth_api_key(key, secret)
         .await
+        .map(|key| (key.user_id, Some(key.scope)))
         .context("failed to authenticate api key")
     }
     _ => {
@@ -77,19 +107,6 @@ pub async fn get_user_id_from_headers(
   }
 }
 
-#[instrument(level = "debug")]
-pub async fn authenticate_check_enabled(
-  headers: &HeaderMap,
-) -> anyhow::Result<User> {
-  let user_id = get_user_id_from_headers(headers).await?;
-  let user = get_user(&user_id).await?;
-  if user.enabled {
-    Ok(user)
-  } else {
-    Err(anyhow!("user not enabled"))
-  }
-}
-
 #[instrument(level = "debug")]
 pub async fn auth_jwt_get_user_id(
   jwt: &str,
@@ -111,10 +128,10 @@ pub async fn auth_jwt_check_enabled(
 }
 
 #[instrument(level = "debug")]
-pub async fn auth_api_key_get_user_id(
+async fn auth_api_key(
   key: &str,
   secret: &str,
-) -> anyhow::Result<String> {
+) -> anyhow::Result<ApiKey> {
   let key = db_client()
     .api_keys
     .find_one(doc! { "key": key })
@@ -128,7 +145,7 @@ pub async fn auth_api_key_get_user_id(
     .context("failed to verify secret hash")?
   {
     // secret matches
-    Ok(key.user_id)
+    Ok(key)
   } else {
     // secret mismatch
     Err(anyhow!("invalid api secret"))
@@ -140,8 +157,14 @@ pub async fn auth_api_key_check_enabled(
   key: &str,
   secret: &str,
 ) -> anyhow::Result<User> {
-  let user_id = auth_api_key_get_user_id(key, secret).await?;
-  check_enabled(user_id).await
+  let key = auth_api_key(key, secret).await?;
+  // Websocket requests can't be checked against the scope
+  if !key.scope.is_empty() {
+    return Err(anyhow!(
+      "scoped api keys can't be used on websockets"
+    ));
+  }
+  check_enabled(key.user_id).await
 }
 
 #[instrument(level = "debug")]
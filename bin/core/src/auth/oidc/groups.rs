@@ -0,0 +1,62 @@
+use anyhow::Context;
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use serde_json::Value;
+
+use crate::{
+  auth::groups::sync_mapped_user_groups, config::core_config,
+};
+
+/// Syncs the user's membership of the user groups in `oidc_group_mapping`
+/// with the provider groups in the ID token `oidc_groups_claim`.
+/// The ID token must already be verified.
+pub async fn sync_oidc_user_groups(
+  user_id: &str,
+  id_token: &str,
+) -> anyhow::Result<()> {
+  let config = core_config();
+  if config.oidc_groups_claim.is_empty()
+    || config.oidc_group_mapping.is_empty()
+  {
+    return Ok(());
+  }
+
+  let provider_groups =
+    groups_from_id_token(id_token, &config.oidc_groups_claim)?;
+
+  sync_mapped_user_groups(
+    user_id,
+    &provider_groups,
+    &config.oidc_group_mapping,
+  )
+  .await
+}
+
+/// Reads the groups claim from the ID token payload.
+/// A missing claim means the user has no groups.
+fn groups_from_id_token(
+  id_token: &str,
+  claim: &str,
+) -> anyhow::Result<Vec<String>> {
+  let payload = id_token
+    .split('.')
+    .nth(1)
+    .context("ID token is not a valid JWT")?;
+  let payload = URL_SAFE_NO_PAD
+    .decode(payload.trim_end_matches('='))
+    .context("Failed to decode ID token payload")?;
+  let payload = serde_json::from_slice::<Value>(&payload)
+    .context("Failed to parse ID token payload")?;
+  let value = claim
+    .split('.')
+    .try_fold(&payload, |value, key| value.get(key));
+  let groups = match value {
+    Some(Value::Array(groups)) => groups
+      .iter()
+      .filter_map(Value::as_str)
+      .map(str::to_string)
+      .collect(),
+    Some(Value::String(group)) => vec![group.clone()],
+    _ => Vec::new(),
+  };
+  Ok(groups)
+}
@@ -7,6 +7,7 @@ use axum::{
 use client::oidc_client;
 use dashmap::DashMap;
 use database::mungos::mongodb::bson::{Document, doc};
+use groups::sync_oidc_user_groups;
 use komodo_client::entities::{
   komodo_timestamp,
   user::{User, UserConfig},
@@ -30,6 +31,7 @@ use crate::{
 use super::RedirectQuery;
 
 pub mod client;
+mod groups;
 
 static APP_USER_AGENT: &str =
   concat!("Komodo/", env!("CARGO_PKG_VERSION"),);
@@ -222,10 +224,8 @@ async fn callback(
     .await
     .context("failed at find user query from database")?;
 
-  let jwt = match user {
-    Some(user) => jwt_client()
-      .encode(user.id)
-      .context("failed to generate jwt")?,
+  let id = match user {
+    Some(user) => user.id,
     None => {
       let ts = komodo_timestamp();
       let no_users_exist =
@@ -298,7 +298,7 @@ async fn callback(
         },
       };
 
-      let user_id = db_client
+      db_client
         .users
         .insert_one(user)
         .await
@@ -306,13 +306,16 @@ async fn callback(
         .inserted_id
         .as_object_id()
         .context("inserted_id is not ObjectId")?
-        .to_string();
-
-      jwt_client()
-        .encode(user_id)
-        .context("failed to generate jwt")?
+        .to_string()
     }
   };
+
+  sync_oidc_user_groups(&id, &id_token.to_string())
+    .await
+    .context("Failed to sync user groups from OIDC groups claim")?;
+
+  let jwt =
+    jwt_client().encode(id).context("failed to generate jwt")?;
   let exchange_token = jwt_client().create_exchange_token(jwt).await;
   let redirect_url = if let Some(redirect) = redirect {
     let splitter = if redirect.contains('?') { '&' } else { '?' };
@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, anyhow};
+use axum::{
+  body::{Body, to_bytes},
+  extract::{OriginalUri, Request},
+};
+use komodo_client::entities::{
+  ResourceTarget, ResourceTargetVariant,
+  api_key::{ApiKeyRequestClass, ApiKeyScope},
+  resource::Resource,
+};
+use serde_json::Value;
+
+use crate::{
+  helpers::all_resources::AllResourcesById,
+  state::all_resources_cache,
+};
+
+/// Request bodies larger than this are rejected for scoped keys,
+/// as the body must be buffered to find the request targets.
+const MAX_SCOPED_BODY_BYTES: usize = 20 * 1024 * 1024;
+
+/// Request params which name a resource of the given type.
+const TARGET_KEYS: &[(&str, ResourceTargetVariant)] = &[
+  ("server", ResourceTargetVariant::Server),
+  ("deployment", ResourceTargetVariant::Deployment),
+  ("stack", ResourceTargetVariant::Stack),
+  ("swarm_service", ResourceTargetVariant::SwarmService),
+  ("build", ResourceTargetVariant::Build),
+  ("repo", ResourceTargetVariant::Repo),
+  ("procedure", ResourceTargetVariant::Procedure),
+  ("action", ResourceTargetVariant::Action),
+  ("builder", ResourceTargetVariant::Builder),
+  ("alerter", ResourceTargetVariant::Alerter),
+  ("sync", ResourceTargetVariant::ResourceSync),
+];
+
+/// The resource type of `id` / `name` params is taken from
+/// the request type, eg `UpdateStack`.
+/// Ordered so the longer names match first.
+const REQUEST_TYPE_NAMES: &[(&str, ResourceTargetVariant)] = &[
+  ("SwarmService", ResourceTargetVariant::SwarmService),
+  ("ResourceSync", ResourceTargetVariant::ResourceSync),
+  ("Deployment", ResourceTargetVariant::Deployment),
+  ("Procedure", ResourceTargetVariant::Procedure),
+  ("Builder", ResourceTargetVariant::Builder),
+  ("Build", ResourceTargetVariant::Build),
+  ("Alerter", ResourceTargetVariant::Alerter),
+  ("Action", ResourceTargetVariant::Action),
+  ("Server", ResourceTargetVariant::Server),
+  ("Stack", ResourceTargetVariant::Stack),
+  ("Repo", ResourceTargetVariant::Repo),
+  ("Sync", ResourceTargetVariant::ResourceSync),
+];
+
+/// Checks the request against the api key scope.
+/// Returns the request to pass on, with the body restored
+/// if it had to be read to find the request targets.
+///
+/// Anything the scope can't be checked against is rejected.
+pub async fn check_api_key_scope(
+  scope: &ApiKeyScope,
+  req: Request,
+) -> anyhow::Result<Request> {
+  let path = req
+    .extensions()
+    .get::<OriginalUri>()
+    .map(|OriginalUri(uri)| uri.path())
+    .unwrap_or(req.uri().path())
+    .to_string();
+  let mut segments = path.split('/').filter(|s| !s.is_empty());
+  let api = segments.next().unwrap_or_default();
+  let class = match api {
+    "read" | "log" | "artifact" => ApiKeyRequestClass::Read,
+    "execute" | "terminal" => ApiKeyRequestClass::Execute,
+    "write" => ApiKeyRequestClass::Write,
+    api => {
+      return Err(anyhow!(
+        "Scoped api keys can't be used on the /{api} api"
+      ));
+    }
+  };
+  if !scope.allows(class) {
+    return Err(anyhow!(
+      "Api key scope does not allow {class:?} requests"
+    ));
+  }
+
+  if scope.targets.is_empty() {
+    return Ok(req);
+  }
+
+  let (parts, body) = req.into_parts();
+  let bytes = to_bytes(body, MAX_SCOPED_BODY_BYTES)
+    .await
+    .context("Failed to read request body")?;
+  let body = serde_json::from_slice::<Value>(&bytes)
+    .context("Failed to parse request body")?;
+
+  // The read / write / execute apis take either
+  // `/{api}/{type}` with the params as body,
+  // or `/{api}` with `{ "type": ..., "params": ... }` as body.
+  // The other apis only take the params as body.
+  let (request_type, params) = match (api, segments.next()) {
+    ("read" | "write" | "execute", Some(request_type)) => {
+      (request_type, &body)
+    }
+    ("read" | "write" | "execute", None) => (
+      body.get("type").and_then(Value::as_str).unwrap_or_default(),
+      body.get("params").unwrap_or(&Value::Null),
+    ),
+    _ => ("", &body),
+  };
+
+  let targets = request_targets(request_type, params);
+  if targets.is_empty() {
+    return Err(anyhow!(
+      "Scoped api keys can only make requests on a specific resource"
+    ));
+  }
+
+  let all = all_resources_cache().load();
+  for (variant, id_or_name) in &targets {
+    let in_scope = scope.targets.iter().any(|target| {
+      let (target_variant, id) = target.extract_variant_id();
+      target_variant == *variant
+        && (id == id_or_name
+          || all
+            .resource_meta(target)
+            .map(|meta| meta.name == id_or_name)
+            .unwrap_or_default())
+    });
+    if !in_scope {
+      return Err(anyhow!(
+        "{} {id_or_name} is not in the api key scope",
+        variant.as_ref()
+      ));
+    }
+  }
+
+  Ok(Request::from_parts(parts, Body::from(bytes)))
+}
+
+/// The resources named by the top level request params.
+fn request_targets(
+  request_type: &str,
+  params: &Value,
+) -> Vec<(ResourceTargetVariant, String)> {
+  let mut targets = Vec::new();
+  let str_param = |key: &str| {
+    params
+      .get(key)
+      .and_then(Value::as_str)
+      .filter(|value| !value.is_empty())
+      .map(str::to_string)
+  };
+  for (key, variant) in TARGET_KEYS {
+    if let Some(value) = str_param(key) {
+      targets.push((*variant, value));
+    }
+  }
+  if let Some(variant) = REQUEST_TYPE_NAMES
+    .iter()
+    .find(|(name, _)| request_type.contains(name))
+    .map(|(_, variant)| *variant)
+  {
+    for key in ["id", "name"] {
+      if let Some(value) = str_param(key) {
+        targets.push((variant, value));
+      }
+    }
+  }
+  if let Some(target) = params.get("target").and_then(|target| {
+    serde_json::from_value::<ResourceTarget>(target.clone()).ok()
+  }) {
+    let (variant, id) = target.extract_variant_id();
+    targets.push((variant, id.clone()));
+  }
+  targets
+}
+
+/// Resolves the scope targets given by name to their ids,
+/// so they still match if the resource is renamed.
+pub fn normalize_api_key_scope(
+  scope: &mut ApiKeyScope,
+) -> anyhow::Result<()> {
+  let all = all_resources_cache().load();
+  for target in &mut scope.targets {
+    let (variant, id_or_name) = target.extract_variant_id();
+    let resource_id = find_resource_id(&all, variant, id_or_name)
+      .with_context(|| {
+        format!("No {} found matching {id_or_name}", variant.as_ref())
+      })?;
+    match target {
+      ResourceTarget::System(_) => {
+        return Err(anyhow!(
+          "System can't be an api key scope target"
+        ));
+      }
+      ResourceTarget::Server(id)
+      | ResourceTarget::Stack(id)
+      | ResourceTarget::Deployment(id)
+      | ResourceTarget::SwarmService(id)
+      | ResourceTarget::Build(id)
+      | ResourceTarget::Repo(id)
+      | ResourceTarget::Procedure(id)
+      | ResourceTarget::Action(id)
+      | ResourceTarget::Builder(id)
+      | ResourceTarget::Alerter(id)
+      | ResourceTarget::ResourceSync(id) => *id = resource_id,
+    }
+  }
+  Ok(())
+}
+
+fn find_resource_id(
+  all: &AllResourcesById,
+  variant: ResourceTargetVariant,
+  id_or_name: &str,
+) -> Option<String> {
+  match variant {
+    ResourceTargetVariant::System => None,
+    ResourceTargetVariant::Server => {
+      find_id(&all.servers, id_or_name)
+    }
+    ResourceTargetVariant::Stack => find_id(&all.stacks, id_or_name),
+    ResourceTargetVariant::Deployment => {
+      find_id(&all.deployments, id_or_name)
+    }
+    ResourceTargetVariant::SwarmService => {
+      find_id(&all.swarm_services, id_or_name)
+    }
+    ResourceTargetVariant::Build => find_id(&all.builds, id_or_name),
+    ResourceTargetVariant::Repo => find_id(&all.repos, id_or_name),
+    ResourceTargetVariant::Procedure => {
+      find_id(&all.procedures, id_or_name)
+    }
+    ResourceTargetVariant::Action => {
+      find_id(&all.actions, id_or_name)
+    }
+    ResourceTargetVariant::Builder => {
+      find_id(&all.builders, id_or_name)
+    }
+    ResourceTargetVariant::Alerter => {
+      find_id(&all.alerters, id_or_name)
+    }
+    ResourceTargetVariant::ResourceSync => {
+      find_id(&all.syncs, id_or_name)
+    }
+  }
+}
+
+fn find_id<C: Default, I: Default>(
+  resources: &HashMap<String, Resource<C, I>>,
+  id_or_name: &str,
+) -> Option<String> {
+  resources
+    .values()
+    .find(|resource| {
+      resource.id == id_or_name || resource.name == id_or_name
+    })
+    .map(|resource| resource.id.clone())
+}
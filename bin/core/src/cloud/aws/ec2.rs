@@ -5,10 +5,12 @@ use aws_config::{BehaviorVersion, Region};
 use aws_sdk_ec2::{
   Client,
   types::{
-    BlockDeviceMapping, EbsBlockDevice,
+    BlockDeviceMapping, EbsBlockDevice, Filter,
+    InstanceInterruptionBehavior, InstanceMarketOptionsRequest,
     InstanceNetworkInterfaceSpecification, InstanceStateChange,
-    InstanceStateName, InstanceStatus, InstanceType, ResourceType,
-    Tag, TagSpecification,
+    InstanceStateName, InstanceStatus, InstanceType, MarketType,
+    ResourceType, SpotInstanceType, SpotMarketOptions, Tag,
+    TagSpecification,
   },
 };
 use base64::Engine;
@@ -19,7 +21,9 @@ use komodo_client::entities::{
   komodo_timestamp,
 };
 
-use crate::{alert::send_alerts, config::core_config};
+use crate::alert::send_alerts;
+
+use super::CredentialsFromConfig;
 
 const POLL_RATE_SECS: u64 = 2;
 const MAX_POLL_TRIES: usize = 30;
@@ -29,34 +33,6 @@ pub struct Ec2Instance {
   pub ip: String,
 }
 
-/// Provides credentials in the core config file to the AWS client
-#[derive(Debug)]
-struct CredentialsFromConfig;
-
-impl aws_credential_types::provider::ProvideCredentials
-  for CredentialsFromConfig
-{
-  fn provide_credentials<'a>(
-    &'a self,
-  ) -> aws_credential_types::provider::future::ProvideCredentials<'a>
-  where
-    Self: 'a,
-  {
-    aws_credential_types::provider::future::ProvideCredentials::new(
-      async {
-        let config = core_config();
-        Ok(aws_credential_types::Credentials::new(
-          &config.aws.access_key_id,
-          &config.aws.secret_access_key,
-          None,
-          None,
-          "komodo-config",
-        ))
-      },
-    )
-  }
-}
-
 #[instrument]
 async fn create_ec2_client(region: String) -> Client {
   let region = Region::new(region);
@@ -68,10 +44,13 @@ async fn create_ec2_client(region: String) -> Client {
   Client::new(&config)
 }
 
+/// Launches an instance with the given `Name` tag,
+/// and any `extra_tags` as (key, value).
 #[instrument]
 pub async fn launch_ec2_instance(
   name: &str,
   config: &AwsBuilderConfig,
+  extra_tags: &[(&str, &str)],
 ) -> anyhow::Result<Ec2Instance> {
   let AwsBuilderConfig {
     region,
@@ -89,11 +68,44 @@ pub async fn launch_ec2_instance(
     git_providers: _,
     docker_registries: _,
     secrets: _,
+    warm_pool_size: _,
+    warm_pool_hours: _,
+    warm_pool_idle_timeout: _,
+    use_spot,
+    spot_max_price,
+    spot_interruption_retries: _,
   } = config;
   let instance_type = handle_unknown_instance_type(
     InstanceType::from(instance_type.as_str()),
   )?;
   let client = create_ec2_client(region.clone()).await;
+  let tag_specification = extra_tags.iter().fold(
+    TagSpecification::builder()
+      .tags(Tag::builder().key("Name").value(name).build())
+      .resource_type(ResourceType::Instance),
+    |spec, (key, value)| {
+      spec.tags(Tag::builder().key(*key).value(*value).build())
+    },
+  );
+  // Spot instances are terminated when interrupted,
+  // the build is restarted on a new instance.
+  let market_options = use_spot.then(|| {
+    InstanceMarketOptionsRequest::builder()
+      .market_type(MarketType::Spot)
+      .spot_options(
+        SpotMarketOptions::builder()
+          .set_max_price(
+            (!spot_max_price.is_empty())
+              .then(|| spot_max_price.clone()),
+          )
+          .spot_instance_type(SpotInstanceType::OneTime)
+          .instance_interruption_behavior(
+            InstanceInterruptionBehavior::Terminate,
+          )
+          .build(),
+      )
+      .build()
+  });
   let req = client
     .run_instances()
     .image_id(ami_id)
@@ -107,12 +119,7 @@ pub async fn launch_ec2_instance(
         .build(),
     )
     .key_name(key_pair_name)
-    .tag_specifications(
-      TagSpecification::builder()
-        .tags(Tag::builder().key("Name").value(name).build())
-        .resource_type(ResourceType::Instance)
-        .build(),
-    )
+    .tag_specifications(tag_specification.build())
     .block_device_mappings(
       BlockDeviceMapping::builder()
         .set_device_name("/dev/sda1".to_string().into())
@@ -124,6 +131,7 @@ pub async fn launch_ec2_instance(
         )
         .build(),
     )
+    .set_instance_market_options(market_options)
     .min_count(1)
     .max_count(1)
     .user_data(
@@ -165,6 +173,59 @@ pub async fn launch_ec2_instance(
   Err(anyhow!("instance not running after polling"))
 }
 
+/// Lists the ids of pending / running instances
+/// which have the tag `key` with `value`.
+#[instrument]
+pub async fn list_ec2_instances_with_tag(
+  region: String,
+  key: &str,
+  value: &str,
+) -> anyhow::Result<Vec<String>> {
+  let client = create_ec2_client(region).await;
+  let res = client
+    .describe_instances()
+    .filters(
+      Filter::builder()
+        .name(format!("tag:{key}"))
+        .values(value)
+        .build(),
+    )
+    .filters(
+      Filter::builder()
+        .name("instance-state-name")
+        .values("pending")
+        .values("running")
+        .build(),
+    )
+    .send()
+    .await
+    .context("failed to describe instances from aws")?;
+  let instance_ids = res
+    .reservations()
+    .iter()
+    .flat_map(|reservation| reservation.instances())
+    .filter_map(|instance| instance.instance_id())
+    .map(str::to_string)
+    .collect();
+  Ok(instance_ids)
+}
+
+/// Whether the instance is still running.
+/// Used to detect builder instances reclaimed by AWS.
+#[instrument]
+pub async fn ec2_instance_is_running(
+  region: String,
+  instance_id: &str,
+) -> anyhow::Result<bool> {
+  let client = create_ec2_client(region).await;
+  let state =
+    get_ec2_instance_state_name(&client, instance_id).await?;
+  Ok(matches!(
+    state,
+    Some(InstanceStateName::Pending | InstanceStateName::Running)
+  ))
+}
+
 const MAX_TERMINATION_TRIES: usize = 5;
 const TERMINATION_WAIT_SECS: u64 = 15;
 
@@ -194,6 +255,7 @@ pub async fn terminate_ec2_instance_with_retry(
               message: format!("{e:#}"),
             },
             resolved_ts: None,
+            notifications: Default::default(),
           };
           send_alerts(&[alert]).await;
           return Err(e);
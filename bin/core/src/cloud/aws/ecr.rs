@@ -0,0 +1,50 @@
+use anyhow::{Context, anyhow};
+use aws_config::{BehaviorVersion, Region};
+use aws_sdk_ecr::Client;
+use base64::{Engine, engine::general_purpose::STANDARD};
+
+use super::CredentialsFromConfig;
+
+#[instrument]
+async fn create_ecr_client(region: String) -> Client {
+  let region = Region::new(region);
+  let config = aws_config::defaults(BehaviorVersion::latest())
+    .region(region)
+    .credentials_provider(CredentialsFromConfig)
+    .load()
+    .await;
+  Client::new(&config)
+}
+
+/// Returns the registry password for the `AWS` user,
+/// with the unix timestamp in ms it expires at.
+#[instrument]
+pub async fn get_ecr_login_password(
+  region: String,
+) -> anyhow::Result<(String, i64)> {
+  let data = create_ecr_client(region)
+    .await
+    .get_authorization_token()
+    .send()
+    .await
+    .context("Failed to get ECR authorization token")?
+    .authorization_data
+    .and_then(|data| data.into_iter().next())
+    .context("ECR returned no authorization data")?;
+  let token = data
+    .authorization_token
+    .context("ECR authorization data has no token")?;
+  let token = STANDARD
+    .decode(token)
+    .context("ECR authorization token is not valid base64")?;
+  let token = String::from_utf8(token)
+    .context("ECR authorization token is not valid utf8")?;
+  let (_, password) = token.split_once(':').ok_or_else(|| {
+    anyhow!("ECR authorization token is not in format 'AWS:password'")
+  })?;
+  let expires_at = data
+    .expires_at
+    .and_then(|expires_at| expires_at.to_millis().ok())
+    .context("ECR authorization data has no expiry")?;
+  Ok((password.to_string(), expires_at))
+}
@@ -1 +1,35 @@
+use crate::config::core_config;
+
 pub mod ec2;
+pub mod ecr;
+pub mod s3;
+pub mod secrets_manager;
+pub mod warm_pool;
+
+/// Provides credentials in the core config file to the AWS client
+#[derive(Debug)]
+pub struct CredentialsFromConfig;
+
+impl aws_credential_types::provider::ProvideCredentials
+  for CredentialsFromConfig
+{
+  fn provide_credentials<'a>(
+    &'a self,
+  ) -> aws_credential_types::provider::future::ProvideCredentials<'a>
+  where
+    Self: 'a,
+  {
+    aws_credential_types::provider::future::ProvideCredentials::new(
+      async {
+        let config = core_config();
+        Ok(aws_credential_types::Credentials::new(
+          &config.aws.access_key_id,
+          &config.aws.secret_access_key,
+          None,
+          None,
+          "komodo-config",
+        ))
+      },
+    )
+  }
+}
@@ -0,0 +1,213 @@
+use anyhow::Context;
+use aws_config::{BehaviorVersion, Region};
+use aws_sdk_s3::{Client, primitives::ByteStream};
+
+use super::CredentialsFromConfig;
+
+#[instrument]
+async fn create_s3_client(region: String) -> Client {
+  create_s3_client_with_endpoint(region, "").await
+}
+
+/// Uses the S3 compatible `endpoint`, such as MinIO, if not empty.
+#[instrument]
+async fn create_s3_client_with_endpoint(
+  region: String,
+  endpoint: &str,
+) -> Client {
+  let region = Region::new(region);
+  let config = aws_config::defaults(BehaviorVersion::latest())
+    .region(region)
+    .credentials_provider(CredentialsFromConfig)
+    .load()
+    .await;
+  if endpoint.is_empty() {
+    return Client::new(&config);
+  }
+  let config = aws_sdk_s3::config::Builder::from(&config)
+    .endpoint_url(endpoint)
+    // S3 compatible stores generally don't support bucket subdomains.
+    .force_path_style(true)
+    .build();
+  Client::from_conf(config)
+}
+
+pub struct S3Object {
+  pub key: String,
+  pub etag: String,
+  pub contents: Vec<u8>,
+}
+
+/// Gets all the objects under the prefix.
+#[instrument]
+pub async fn get_s3_objects_with_prefix(
+  region: String,
+  endpoint: &str,
+  bucket: &str,
+  prefix: &str,
+) -> anyhow::Result<Vec<S3Object>> {
+  let client = create_s3_client_with_endpoint(region, endpoint).await;
+  let mut objects = Vec::new();
+  let mut continuation_token = None;
+  loop {
+    let res = client
+      .list_objects_v2()
+      .bucket(bucket)
+      .prefix(prefix)
+      .set_continuation_token(continuation_token)
+      .send()
+      .await
+      .with_context(|| {
+        format!("Failed to list s3 objects with prefix {prefix}")
+      })?;
+    for object in res.contents() {
+      let Some(key) = object.key() else {
+        continue;
+      };
+      // Skip the folder placeholders
+      if key.ends_with('/') {
+        continue;
+      }
+      let contents = client
+        .get_object()
+        .bucket(bucket)
+        .key(key)
+        .send()
+        .await
+        .with_context(|| format!("Failed to get s3 object {key}"))?
+        .body
+        .collect()
+        .await
+        .with_context(|| format!("Failed to read s3 object {key}"))?
+        .into_bytes()
+        .to_vec();
+      objects.push(S3Object {
+        key: key.to_string(),
+        etag: object.e_tag().unwrap_or_default().to_string(),
+        contents,
+      });
+    }
+    continuation_token =
+      res.next_continuation_token().map(str::to_string);
+    if continuation_token.is_none() {
+      return Ok(objects);
+    }
+  }
+}
+
+#[instrument(skip(contents))]
+pub async fn put_s3_object(
+  region: String,
+  bucket: &str,
+  key: &str,
+  contents: Vec<u8>,
+) -> anyhow::Result<()> {
+  create_s3_client(region)
+    .await
+    .put_object()
+    .bucket(bucket)
+    .key(key)
+    .body(ByteStream::from(contents))
+    .send()
+    .await
+    .with_context(|| format!("Failed to put s3 object {key}"))?;
+  Ok(())
+}
+
+#[instrument]
+pub async fn get_s3_object(
+  region: String,
+  bucket: &str,
+  key: &str,
+) -> anyhow::Result<Vec<u8>> {
+  let res = create_s3_client(region)
+    .await
+    .get_object()
+    .bucket(bucket)
+    .key(key)
+    .send()
+    .await
+    .with_context(|| format!("Failed to get s3 object {key}"))?;
+  let contents = res
+    .body
+    .collect()
+    .await
+    .with_context(|| format!("Failed to read s3 object {key}"))?
+    .into_bytes()
+    .to_vec();
+  Ok(contents)
+}
+
+/// Gets the object body as a stream, without reading it into memory.
+#[instrument]
+pub async fn get_s3_object_stream(
+  region: String,
+  bucket: &str,
+  key: &str,
+) -> anyhow::Result<ByteStream> {
+  let res = create_s3_client(region)
+    .await
+    .get_object()
+    .bucket(bucket)
+    .key(key)
+    .send()
+    .await
+    .with_context(|| format!("Failed to get s3 object {key}"))?;
+  Ok(res.body)
+}
+
+#[instrument]
+pub async fn delete_s3_object(
+  region: String,
+  bucket: &str,
+  key: &str,
+) -> anyhow::Result<()> {
+  create_s3_client(region)
+    .await
+    .delete_object()
+    .bucket(bucket)
+    .key(key)
+    .send()
+    .await
+    .with_context(|| format!("Failed to delete s3 object {key}"))?;
+  Ok(())
+}
+
+#[instrument]
+pub async fn delete_s3_objects_with_prefix(
+  region: String,
+  bucket: &str,
+  prefix: &str,
+) -> anyhow::Result<()> {
+  let client = create_s3_client(region).await;
+  let mut continuation_token = None;
+  loop {
+    let res = client
+      .list_objects_v2()
+      .bucket(bucket)
+      .prefix(prefix)
+      .set_continuation_token(continuation_token)
+      .send()
+      .await
+      .with_context(|| {
+        format!("Failed to list s3 objects with prefix {prefix}")
+      })?;
+    for key in res.contents().iter().filter_map(|object| object.key())
+    {
+      client
+        .delete_object()
+        .bucket(bucket)
+        .key(key)
+        .send()
+        .await
+        .with_context(|| {
+          format!("Failed to delete s3 object {key}")
+        })?;
+    }
+    continuation_token =
+      res.next_continuation_token().map(str::to_string);
+    if continuation_token.is_none() {
+      return Ok(());
+    }
+  }
+}
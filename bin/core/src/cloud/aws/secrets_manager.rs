@@ -0,0 +1,42 @@
+use anyhow::Context;
+use aws_config::{BehaviorVersion, Region};
+use aws_sdk_secretsmanager::Client;
+
+use super::CredentialsFromConfig;
+
+#[instrument]
+async fn create_secrets_manager_client(region: String) -> Client {
+  let region = Region::new(region);
+  let config = aws_config::defaults(BehaviorVersion::latest())
+    .region(region)
+    .credentials_provider(CredentialsFromConfig)
+    .load()
+    .await;
+  Client::new(&config)
+}
+
+/// Returns the secret ids with their secret string.
+#[instrument]
+pub async fn get_secret_strings(
+  region: String,
+  secret_ids: &[String],
+) -> anyhow::Result<Vec<(String, String)>> {
+  let client = create_secrets_manager_client(region).await;
+  let mut secrets = Vec::with_capacity(secret_ids.len());
+  for secret_id in secret_ids {
+    let value = client
+      .get_secret_value()
+      .secret_id(secret_id)
+      .send()
+      .await
+      .with_context(|| {
+        format!("Failed to get AWS secret value for {secret_id}")
+      })?
+      .secret_string
+      .with_context(|| {
+        format!("AWS secret {secret_id} has no secret string")
+      })?;
+    secrets.push((secret_id.clone(), value));
+  }
+  Ok(secrets)
+}
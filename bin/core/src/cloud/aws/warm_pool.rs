@@ -0,0 +1,329 @@
+use std::{
+  collections::{HashMap, HashSet},
+  sync::{Mutex, OnceLock},
+  time::Duration,
+};
+
+use anyhow::Context;
+use async_timing_util::Timelength;
+use database::mungos::find::find_collect;
+use komodo_client::entities::{
+  builder::{AwsBuilderConfig, BuilderConfig},
+  komodo_timestamp,
+};
+use periphery_client::api;
+
+use crate::{
+  config::core_config,
+  helpers::{
+    builder::aws_builder_periphery, maintenance::is_in_maintenance,
+  },
+  state::db_client,
+};
+
+use super::ec2::{
+  Ec2Instance, launch_ec2_instance, list_ec2_instances_with_tag,
+  terminate_ec2_instance_with_retry,
+};
+
+/// Tag attached to instances which may be kept in a warm pool,
+/// with the Core host as value. Used to clean up
+/// instances left over after Core restarts.
+pub const WARM_POOL_TAG: &str = "komodo-warm-pool";
+
+const WARM_POOL_POLL_RATE_SECS: u64 = 2;
+const WARM_POOL_POLL_MAX_TRIES: usize = 60;
+
+/// An idle builder instance in a warm pool.
+#[derive(Debug, Clone)]
+pub struct WarmInstance {
+  pub instance_id: String,
+  pub ip: String,
+  pub region: String,
+  /// Unix timestamp in ms the instance became idle.
+  idle_since: i64,
+}
+
+impl WarmInstance {
+  pub fn new(
+    instance_id: String,
+    ip: String,
+    region: String,
+  ) -> Self {
+    Self {
+      instance_id,
+      ip,
+      region,
+      idle_since: komodo_timestamp(),
+    }
+  }
+}
+
+#[derive(Default)]
+struct WarmPool {
+  idle: Vec<WarmInstance>,
+  /// The number of instances being launched for the pool.
+  launching: usize,
+}
+
+/// Builder id -> WarmPool
+fn warm_pools() -> &'static Mutex<HashMap<String, WarmPool>> {
+  static WARM_POOLS: OnceLock<Mutex<HashMap<String, WarmPool>>> =
+    OnceLock::new();
+  WARM_POOLS.get_or_init(Default::default)
+}
+
+/// Takes an idle instance out of the Builder's warm pool, if one is available.
+pub fn take_warm_instance(builder_id: &str) -> Option<WarmInstance> {
+  warm_pools().lock().unwrap().get_mut(builder_id)?.idle.pop()
+}
+
+/// Returns an instance to the Builder's warm pool after use.
+/// If the pool doesn't need it, it will be terminated
+/// by the warm pool manager after the idle timeout.
+pub fn release_warm_instance(
+  builder_id: &str,
+  mut instance: WarmInstance,
+) {
+  instance.idle_since = komodo_timestamp();
+  warm_pools()
+    .lock()
+    .unwrap()
+    .entry(builder_id.to_string())
+    .or_default()
+    .idle
+    .push(instance);
+}
+
+pub fn spawn_warm_pool_manager() {
+  tokio::spawn(async move {
+    terminate_orphaned_warm_instances().await;
+    loop {
+      async_timing_util::wait_until_timelength(
+        Timelength::OneMinute,
+        0,
+      )
+      .await;
+      manage_warm_pools().await;
+    }
+  });
+}
+
+/// Keeps each AWS Builder's warm pool filled during
+/// the configured hours, and terminates idle instances
+/// which are no longer needed after the idle timeout.
+async fn manage_warm_pools() {
+  let builders = match find_collect(&db_client().builders, None, None)
+    .await
+    .context("failed to get builders from db")
+  {
+    Ok(builders) => builders,
+    Err(e) => {
+      error!("Failed to manage builder warm pools | {e:#}");
+      return;
+    }
+  };
+
+  let now = komodo_timestamp();
+  let mut to_terminate = Vec::new();
+  let mut to_launch = Vec::new();
+
+  {
+    let mut pools = warm_pools().lock().unwrap();
+
+    // Clear out pools for Builders which no longer use them.
+    pools.retain(|builder_id, pool| {
+      let enabled = builders.iter().any(|builder| {
+        &builder.id == builder_id
+          && matches!(
+            &builder.config,
+            BuilderConfig::Aws(config) if config.warm_pool_size > 0
+          )
+      });
+      if !enabled {
+        to_terminate.append(&mut pool.idle);
+      }
+      enabled || pool.launching > 0
+    });
+
+    for builder in builders {
+      let BuilderConfig::Aws(config) = builder.config else {
+        continue;
+      };
+      if config.warm_pool_size < 1 {
+        continue;
+      }
+      let target = if config.warm_pool_hours.is_empty()
+        || is_in_maintenance(&config.warm_pool_hours, now)
+      {
+        config.warm_pool_size as usize
+      } else {
+        0
+      };
+      let idle_timeout_ms =
+        config.warm_pool_idle_timeout.max(0) as i64 * 60_000;
+
+      let pool = pools.entry(builder.id.clone()).or_default();
+
+      // Oldest idle first. Builds take the most recently idle instance.
+      pool.idle.sort_by_key(|instance| instance.idle_since);
+      while pool.idle.len() > target
+        && now - pool.idle[0].idle_since >= idle_timeout_ms
+      {
+        to_terminate.push(pool.idle.remove(0));
+      }
+
+      let available = pool.idle.len() + pool.launching;
+      if available < target {
+        let count = target - available;
+        pool.launching += count;
+        for _ in 0..count {
+          to_launch.push((
+            builder.id.clone(),
+            builder.name.clone(),
+            config.clone(),
+          ));
+        }
+      }
+    }
+  }
+
+  for instance in to_terminate {
+    info!(
+      "terminating idle warm pool instance {}",
+      instance.instance_id
+    );
+    tokio::spawn(async move {
+      let _ = terminate_ec2_instance_with_retry(
+        instance.region,
+        &instance.instance_id,
+      )
+      .await;
+    });
+  }
+
+  for (builder_id, builder_name, config) in to_launch {
+    tokio::spawn(launch_warm_instance(
+      builder_id,
+      builder_name,
+      config,
+    ));
+  }
+}
+
+async fn launch_warm_instance(
+  builder_id: String,
+  builder_name: String,
+  config: AwsBuilderConfig,
+) {
+  let res = async {
+    let Ec2Instance { instance_id, ip } = launch_ec2_instance(
+      &format!("BUILDER-WARM-{builder_name}"),
+      &config,
+      &[(WARM_POOL_TAG, core_config().host.as_str())],
+    )
+    .await?;
+    if let Err(e) =
+      wait_for_periphery(&builder_id, &ip, &config).await
+    {
+      let region = config.region.clone();
+      tokio::spawn(async move {
+        let _ =
+          terminate_ec2_instance_with_retry(region, &instance_id)
+            .await;
+      });
+      return Err(e);
+    }
+    anyhow::Ok(WarmInstance::new(
+      instance_id,
+      ip,
+      config.region.clone(),
+    ))
+  }
+  .await;
+
+  let mut pools = warm_pools().lock().unwrap();
+  let pool = pools.entry(builder_id).or_default();
+  pool.launching = pool.launching.saturating_sub(1);
+  match res {
+    Ok(instance) => {
+      info!(
+        "launched warm pool instance {} for builder {builder_name}",
+        instance.instance_id
+      );
+      pool.idle.push(instance);
+    }
+    Err(e) => {
+      warn!(
+        "Failed to launch warm pool instance for builder {builder_name} | {e:#}"
+      );
+    }
+  }
+}
+
+async fn wait_for_periphery(
+  builder_id: &str,
+  ip: &str,
+  config: &AwsBuilderConfig,
+) -> anyhow::Result<()> {
+  let periphery = aws_builder_periphery(builder_id, ip, config);
+  let mut res = Ok(());
+  for _ in 0..WARM_POOL_POLL_MAX_TRIES {
+    match periphery.request(api::GetVersion {}).await {
+      Ok(_) => return Ok(()),
+      Err(e) => res = Err(e),
+    }
+    tokio::time::sleep(Duration::from_secs(WARM_POOL_POLL_RATE_SECS))
+      .await;
+  }
+  res.context("failed to reach periphery on warm pool instance")
+}
+
+/// The warm pools are only held in memory,
+/// so instances left running from before a Core restart
+/// are terminated on startup.
+async fn terminate_orphaned_warm_instances() {
+  let builders = match find_collect(&db_client().builders, None, None)
+    .await
+    .context("failed to get builders from db")
+  {
+    Ok(builders) => builders,
+    Err(e) => {
+      error!("Failed to clean up builder warm pools | {e:#}");
+      return;
+    }
+  };
+  let regions = builders
+    .into_iter()
+    .filter_map(|builder| match builder.config {
+      BuilderConfig::Aws(config) => Some(config.region),
+      _ => None,
+    })
+    .collect::<HashSet<_>>();
+  for region in regions {
+    let instance_ids = match list_ec2_instances_with_tag(
+      region.clone(),
+      WARM_POOL_TAG,
+      &core_config().host,
+    )
+    .await
+    {
+      Ok(instance_ids) => instance_ids,
+      Err(e) => {
+        warn!(
+          "Failed to list warm pool instances in region {region} | {e:#}"
+        );
+        continue;
+      }
+    };
+    for instance_id in instance_ids {
+      info!("terminating orphaned warm pool instance {instance_id}");
+      let region = region.clone();
+      tokio::spawn(async move {
+        let _ =
+          terminate_ec2_instance_with_retry(region, &instance_id)
+            .await;
+      });
+    }
+  }
+}
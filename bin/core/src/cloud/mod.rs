@@ -6,4 +6,9 @@ pub enum BuildCleanupData {
   Server,
   /// Clean up AWS instance
   Aws { instance_id: String, region: String },
+  /// Return AWS instance to the Builder's warm pool
+  AwsWarmPool {
+    builder_id: String,
+    instance: aws::warm_pool::WarmInstance,
+  },
 }
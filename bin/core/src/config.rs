@@ -91,8 +91,14 @@ pub fn core_config() -> &'static CoreConfig {
       jwt_secret: maybe_read_item_from_file(env.komodo_jwt_secret_file, env.komodo_jwt_secret).unwrap_or(config.jwt_secret),
       passkey: maybe_read_item_from_file(env.komodo_passkey_file, env.komodo_passkey)
         .unwrap_or(config.passkey),
+      periphery_signing_secret: maybe_read_item_from_file(env.komodo_periphery_signing_secret_file, env.komodo_periphery_signing_secret)
+        .unwrap_or(config.periphery_signing_secret),
       webhook_secret: maybe_read_item_from_file(env.komodo_webhook_secret_file, env.komodo_webhook_secret)
         .unwrap_or(config.webhook_secret),
+      metrics_token: maybe_read_item_from_file(env.komodo_metrics_token_file, env.komodo_metrics_token)
+        .unwrap_or(config.metrics_token),
+      alertmanager_token: maybe_read_item_from_file(env.komodo_alertmanager_token_file, env.komodo_alertmanager_token)
+        .unwrap_or(config.alertmanager_token),
       database: DatabaseConfig {
         uri: maybe_read_item_from_file(env.komodo_database_uri_file,env.komodo_database_uri).unwrap_or(config.database.uri),
         address: env.komodo_database_address.unwrap_or(config.database.address),
@@ -131,6 +137,21 @@ pub fn core_config() -> &'static CoreConfig {
       oidc_additional_audiences: maybe_read_list_from_file(env.komodo_oidc_additional_audiences_file,env
         .komodo_oidc_additional_audiences)
         .unwrap_or(config.oidc_additional_audiences),
+      oidc_groups_claim: env.komodo_oidc_groups_claim
+        .unwrap_or(config.oidc_groups_claim),
+      oidc_group_mapping: config.oidc_group_mapping,
+      ldap_enabled: env.komodo_ldap_enabled.unwrap_or(config.ldap_enabled),
+      ldap_url: env.komodo_ldap_url.unwrap_or(config.ldap_url),
+      ldap_start_tls: env.komodo_ldap_start_tls.unwrap_or(config.ldap_start_tls),
+      ldap_bind_dn: maybe_read_item_from_file(env.komodo_ldap_bind_dn_file, env.komodo_ldap_bind_dn)
+        .unwrap_or(config.ldap_bind_dn),
+      ldap_bind_password: maybe_read_item_from_file(env.komodo_ldap_bind_password_file, env.komodo_ldap_bind_password)
+        .unwrap_or(config.ldap_bind_password),
+      ldap_user_base_dn: env.komodo_ldap_user_base_dn.unwrap_or(config.ldap_user_base_dn),
+      ldap_user_filter: env.komodo_ldap_user_filter.unwrap_or(config.ldap_user_filter),
+      ldap_group_attribute: env.komodo_ldap_group_attribute
+        .unwrap_or(config.ldap_group_attribute),
+      ldap_group_mapping: config.ldap_group_mapping,
       google_oauth: OauthCredentials {
         enabled: env
           .komodo_google_oauth_enabled
@@ -192,6 +213,15 @@ pub fn core_config() -> &'static CoreConfig {
       action_directory: env
         .komodo_action_directory
         .unwrap_or(config.action_directory),
+      artifact_directory: env
+        .komodo_artifact_directory
+        .unwrap_or(config.artifact_directory),
+      compose_cache_directory: env
+        .komodo_compose_cache_directory
+        .unwrap_or(config.compose_cache_directory),
+      stack_file_directory: env
+        .komodo_stack_file_directory
+        .unwrap_or(config.stack_file_directory),
       resource_poll_interval: env
         .komodo_resource_poll_interval
         .unwrap_or(config.resource_poll_interval),
@@ -204,9 +234,42 @@ pub fn core_config() -> &'static CoreConfig {
       keep_alerts_for_days: env
         .komodo_keep_alerts_for_days
         .unwrap_or(config.keep_alerts_for_days),
+      keep_artifacts_for_days: env
+        .komodo_keep_artifacts_for_days
+        .unwrap_or(config.keep_artifacts_for_days),
+      keep_audit_log_for_days: env
+        .komodo_keep_audit_log_for_days
+        .unwrap_or(config.keep_audit_log_for_days),
+      resource_lock_wait_secs: env
+        .komodo_resource_lock_wait_secs
+        .unwrap_or(config.resource_lock_wait_secs),
+      artifact_s3_bucket: env
+        .komodo_artifact_s3_bucket
+        .unwrap_or(config.artifact_s3_bucket),
+      artifact_s3_region: env
+        .komodo_artifact_s3_region
+        .unwrap_or(config.artifact_s3_region),
+      stack_file_storage: env
+        .komodo_stack_file_storage
+        .unwrap_or(config.stack_file_storage),
+      stack_file_max_inline_bytes: env
+        .komodo_stack_file_max_inline_bytes
+        .unwrap_or(config.stack_file_max_inline_bytes),
+      stack_file_s3_bucket: env
+        .komodo_stack_file_s3_bucket
+        .unwrap_or(config.stack_file_s3_bucket),
+      stack_file_s3_region: env
+        .komodo_stack_file_s3_region
+        .unwrap_or(config.stack_file_s3_region),
       webhook_base_url: env
         .komodo_webhook_base_url
         .unwrap_or(config.webhook_base_url),
+      metrics_enabled: env
+        .komodo_metrics_enabled
+        .unwrap_or(config.metrics_enabled),
+      alertmanager_enabled: env
+        .komodo_alertmanager_enabled
+        .unwrap_or(config.alertmanager_enabled),
       transparent_mode: env
         .komodo_transparent_mode
         .unwrap_or(config.transparent_mode),
@@ -258,6 +321,9 @@ pub fn core_config() -> &'static CoreConfig {
 
       // These can't be overridden on env
       secrets: config.secrets,
+      secret_providers: config.secret_providers,
+      container_dns_defaults: config.container_dns_defaults,
+      alertmanager_target_labels: config.alertmanager_target_labels,
       git_providers: config.git_providers,
       docker_registries: config.docker_registries,
     }
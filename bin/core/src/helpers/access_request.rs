@@ -0,0 +1,198 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use async_timing_util::{Timelength, wait_until_timelength};
+use database::mungos::{
+  by_id::find_one_by_id,
+  find::find_collect,
+  mongodb::bson::{doc, oid::ObjectId, to_bson},
+};
+use komodo_client::entities::{
+  Operation,
+  access_request::{AccessRequest, AccessRequestStatus},
+  komodo_timestamp,
+  user::system_user,
+};
+
+use crate::state::db_client;
+
+use super::update::{add_update, make_update};
+
+pub fn spawn_access_expiry_loop() {
+  tokio::spawn(async move {
+    if let Err(e) = schedule_approved_access_expiry().await {
+      error!("Failed to schedule access request expiry | {e:#}");
+    }
+    loop {
+      wait_until_timelength(Timelength::OneMinute, 0).await;
+      if let Err(e) = expire_access().await {
+        error!("Failed to revoke expired access | {e:#}");
+      }
+    }
+  });
+}
+
+/// Expires the approved access request right when it expires.
+/// Permission queries stop applying the access at this time,
+/// so any previous permission needs to be restored right away
+/// rather than on the next run of the expiry loop.
+pub fn schedule_access_request_expiry(request_id: String) {
+  tokio::spawn(async move {
+    let request =
+      match find_one_by_id(&db_client().access_requests, &request_id)
+        .await
+        .context("Failed to query db for access request")
+      {
+        Ok(Some(request)) => request,
+        Ok(None) => return,
+        Err(e) => {
+          warn!("Failed to schedule access request expiry | {e:#}");
+          return;
+        }
+      };
+    let wait_ms = request.expires_at - komodo_timestamp();
+    if wait_ms > 0 {
+      tokio::time::sleep(Duration::from_millis(wait_ms as u64)).await;
+    }
+    if let Err(e) = expire_access_request(&request).await {
+      warn!(
+        "Failed to revoke access for {} from request {} | {e:#}",
+        request.username, request.id
+      );
+    }
+  });
+}
+
+/// The timers are lost on restart, so reschedule
+/// the access requests which are still active.
+async fn schedule_approved_access_expiry() -> anyhow::Result<()> {
+  let requests = find_collect(
+    &db_client().access_requests,
+    doc! {
+      "status": AccessRequestStatus::Approved.to_string(),
+      "expires_at": { "$gt": komodo_timestamp() },
+    },
+    None,
+  )
+  .await
+  .context("Failed to query db for approved access requests")?;
+  for request in requests {
+    schedule_access_request_expiry(request.id);
+  }
+  Ok(())
+}
+
+/// Revokes the access given by approved access requests
+/// and any other permissions which have expired.
+async fn expire_access() -> anyhow::Result<()> {
+  let now = komodo_timestamp();
+  let db = db_client();
+
+  let requests = find_collect(
+    &db.access_requests,
+    doc! {
+      "status": AccessRequestStatus::Approved.to_string(),
+      "expires_at": { "$lte": now },
+    },
+    None,
+  )
+  .await
+  .context("Failed to query db for expired access requests")?;
+  for request in requests {
+    if let Err(e) = expire_access_request(&request).await {
+      warn!(
+        "Failed to revoke access for {} from request {} | {e:#}",
+        request.username, request.id
+      );
+    }
+  }
+
+  // Permissions given directly with an expiry.
+  // Runs after the requests so restored permissions aren't removed.
+  db.permissions
+    .delete_many(doc! { "expires_at": { "$gt": 0, "$lte": now } })
+    .await
+    .context("Failed to delete expired permissions")?;
+
+  Ok(())
+}
+
+/// Restores the user's permission from before the request was approved,
+/// or removes the permission if there wasn't one.
+async fn expire_access_request(
+  request: &AccessRequest,
+) -> anyhow::Result<()> {
+  let db = db_client();
+  let (variant, resource_id) = request.target.extract_variant_id();
+  // Only matches if the permission wasn't changed since approval.
+  let filter = doc! {
+    "user_target.type": "User",
+    "user_target.id": &request.user_id,
+    "resource_target.type": variant.as_ref(),
+    "resource_target.id": resource_id,
+    "expires_at": request.expires_at,
+  };
+  let log = match &request.previous {
+    Some(previous) => {
+      let specific = to_bson(&previous.specific)
+        .context("permission.specific is not valid Bson")?;
+      db.permissions
+        .update_one(
+          filter,
+          doc! { "$set": {
+            "level": previous.level.as_ref(),
+            "specific": specific,
+            "expires_at": 0,
+          } },
+        )
+        .await
+        .context("Failed to restore permission")?;
+      format!(
+        "Access for {} expired, restored {} access",
+        request.username, previous.level
+      )
+    }
+    None => {
+      db.permissions
+        .delete_one(filter)
+        .await
+        .context("Failed to delete permission")?;
+      format!(
+        "Access for {} expired and was revoked",
+        request.username
+      )
+    }
+  };
+
+  // Only matches if it is still approved, so the timer
+  // and the expiry loop don't both log the expiry.
+  let object_id = ObjectId::parse_str(&request.id)
+    .context("Access request id is not valid ObjectId")?;
+  let res = db
+    .access_requests
+    .update_one(
+      doc! {
+        "_id": object_id,
+        "status": AccessRequestStatus::Approved.to_string(),
+      },
+      doc! { "$set": {
+        "status": AccessRequestStatus::Expired.to_string(),
+      } },
+    )
+    .await
+    .context("Failed to update access request status")?;
+  if res.matched_count == 0 {
+    return Ok(());
+  }
+
+  let mut update = make_update(
+    request.target.clone(),
+    Operation::ExpireAccess,
+    system_user(),
+  );
+  update.push_simple_log("Expire Access", log);
+  update.finalize();
+  add_update(update).await?;
+
+  Ok(())
+}
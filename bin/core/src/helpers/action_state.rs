@@ -8,7 +8,7 @@ use komodo_client::{
     deployment::DeploymentActionState,
     procedure::ProcedureActionState, repo::RepoActionState,
     server::ServerActionState, stack::StackActionState,
-    sync::ResourceSyncActionState,
+    swarm::SwarmServiceActionState, sync::ResourceSyncActionState,
   },
 };
 
@@ -20,6 +20,8 @@ pub struct ActionStates {
   pub stack: Cache<String, Arc<ActionState<StackActionState>>>,
   pub deployment:
     Cache<String, Arc<ActionState<DeploymentActionState>>>,
+  pub swarm_service:
+    Cache<String, Arc<ActionState<SwarmServiceActionState>>>,
   pub build: Cache<String, Arc<ActionState<BuildActionState>>>,
   pub repo: Cache<String, Arc<ActionState<RepoActionState>>>,
   pub procedure:
@@ -1,15 +1,17 @@
 use std::collections::HashMap;
 
 use komodo_client::entities::{
-  action::Action, alerter::Alerter, build::Build, builder::Builder,
-  deployment::Deployment, procedure::Procedure, repo::Repo,
-  server::Server, stack::Stack, sync::ResourceSync,
+  ResourceTarget, action::Action, alerter::Alerter, build::Build,
+  builder::Builder, deployment::Deployment, procedure::Procedure,
+  repo::Repo, resource::Resource, server::Server, stack::Stack,
+  swarm::SwarmService, sync::ResourceSync,
 };
 
 #[derive(Debug, Default)]
 pub struct AllResourcesById {
   pub servers: HashMap<String, Server>,
   pub deployments: HashMap<String, Deployment>,
+  pub swarm_services: HashMap<String, SwarmService>,
   pub stacks: HashMap<String, Stack>,
   pub builds: HashMap<String, Build>,
   pub repos: HashMap<String, Repo>,
@@ -35,6 +37,10 @@ impl AllResourcesById {
         Deployment,
       >(id_to_tags, match_tags)
       .await?,
+      swarm_services: crate::resource::get_id_to_resource_map::<
+        SwarmService,
+      >(id_to_tags, match_tags)
+      .await?,
       builds: crate::resource::get_id_to_resource_map::<Build>(
         id_to_tags, match_tags,
       )
@@ -70,4 +76,65 @@ impl AllResourcesById {
       .await?,
     })
   }
+
+  /// The name, tags and metadata of the target resource.
+  pub fn resource_meta(
+    &self,
+    target: &ResourceTarget,
+  ) -> Option<ResourceMeta<'_>> {
+    match target {
+      ResourceTarget::System(_) => None,
+      ResourceTarget::Server(id) => {
+        self.servers.get(id).map(ResourceMeta::from)
+      }
+      ResourceTarget::Stack(id) => {
+        self.stacks.get(id).map(ResourceMeta::from)
+      }
+      ResourceTarget::Deployment(id) => {
+        self.deployments.get(id).map(ResourceMeta::from)
+      }
+      ResourceTarget::SwarmService(id) => {
+        self.swarm_services.get(id).map(ResourceMeta::from)
+      }
+      ResourceTarget::Build(id) => {
+        self.builds.get(id).map(ResourceMeta::from)
+      }
+      ResourceTarget::Repo(id) => {
+        self.repos.get(id).map(ResourceMeta::from)
+      }
+      ResourceTarget::Procedure(id) => {
+        self.procedures.get(id).map(ResourceMeta::from)
+      }
+      ResourceTarget::Action(id) => {
+        self.actions.get(id).map(ResourceMeta::from)
+      }
+      ResourceTarget::Builder(id) => {
+        self.builders.get(id).map(ResourceMeta::from)
+      }
+      ResourceTarget::Alerter(id) => {
+        self.alerters.get(id).map(ResourceMeta::from)
+      }
+      ResourceTarget::ResourceSync(id) => {
+        self.syncs.get(id).map(ResourceMeta::from)
+      }
+    }
+  }
+}
+
+pub struct ResourceMeta<'a> {
+  pub name: &'a String,
+  pub tags: &'a Vec<String>,
+  pub metadata: &'a HashMap<String, String>,
+}
+
+impl<'a, C: Default, I: Default> From<&'a Resource<C, I>>
+  for ResourceMeta<'a>
+{
+  fn from(resource: &'a Resource<C, I>) -> Self {
+    ResourceMeta {
+      name: &resource.name,
+      tags: &resource.tags,
+      metadata: &resource.metadata,
+    }
+  }
 }
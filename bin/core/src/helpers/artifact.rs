@@ -0,0 +1,171 @@
+use anyhow::{Context, anyhow};
+use komodo_client::entities::{
+  komodo_timestamp,
+  update::{Update, UpdateArtifact},
+};
+use tokio::fs;
+
+use crate::{
+  cloud::aws::s3::{
+    delete_s3_objects_with_prefix, get_s3_object, put_s3_object,
+  },
+  config::core_config,
+};
+
+/// Log output larger than this is moved to an artifact,
+/// keeping Updates well under the database document size limit.
+const MAX_INLINE_LOG_BYTES: usize = 1024 * 1024;
+/// How much of the end of offloaded log output is kept on the Update.
+const OFFLOADED_LOG_TAIL_BYTES: usize = 64 * 1024;
+
+/// Stores the contents as an artifact of the Update,
+/// replacing any existing artifact with the same name.
+/// The Update must already exist in the database.
+pub async fn attach_artifact(
+  update: &mut Update,
+  name: &str,
+  contents: Vec<u8>,
+) -> anyhow::Result<()> {
+  if update.id.is_empty() {
+    return Err(anyhow!(
+      "Update must be created before attaching artifacts"
+    ));
+  }
+  let name = sanitize_artifact_name(name);
+  let size = contents.len() as i64;
+  let config = core_config();
+  if config.artifact_s3_bucket.is_empty() {
+    let dir = config.artifact_directory.join(&update.id);
+    fs::create_dir_all(&dir).await.with_context(|| {
+      format!("Failed to create artifact directory {dir:?}")
+    })?;
+    let path = dir.join(&name);
+    fs::write(&path, contents).await.with_context(|| {
+      format!("Failed to write artifact to {path:?}")
+    })?;
+  } else {
+    put_s3_object(
+      config.artifact_s3_region.clone(),
+      &config.artifact_s3_bucket,
+      &artifact_key(&update.id, &name),
+      contents,
+    )
+    .await?;
+  }
+  update.artifacts.retain(|artifact| artifact.name != name);
+  update.artifacts.push(UpdateArtifact {
+    name,
+    size,
+    created_at: komodo_timestamp(),
+  });
+  Ok(())
+}
+
+pub async fn read_artifact(
+  update_id: &str,
+  name: &str,
+) -> anyhow::Result<Vec<u8>> {
+  let name = sanitize_artifact_name(name);
+  let config = core_config();
+  if config.artifact_s3_bucket.is_empty() {
+    let path = config.artifact_directory.join(update_id).join(&name);
+    fs::read(&path)
+      .await
+      .with_context(|| format!("Failed to read artifact at {path:?}"))
+  } else {
+    get_s3_object(
+      config.artifact_s3_region.clone(),
+      &config.artifact_s3_bucket,
+      &artifact_key(update_id, &name),
+    )
+    .await
+  }
+}
+
+/// Deletes all the stored artifacts of the Update.
+pub async fn delete_artifacts(update_id: &str) -> anyhow::Result<()> {
+  let config = core_config();
+  if config.artifact_s3_bucket.is_empty() {
+    let dir = config.artifact_directory.join(update_id);
+    if dir.exists() {
+      fs::remove_dir_all(&dir).await.with_context(|| {
+        format!("Failed to delete artifacts at {dir:?}")
+      })?;
+    }
+    Ok(())
+  } else {
+    delete_s3_objects_with_prefix(
+      config.artifact_s3_region.clone(),
+      &config.artifact_s3_bucket,
+      &format!("{update_id}/"),
+    )
+    .await
+  }
+}
+
+/// Moves log output too large to store in the database
+/// to artifacts, keeping only the end of the output on the Update.
+pub async fn offload_large_logs(update: &mut Update) {
+  if update.id.is_empty() {
+    return;
+  }
+  for index in 0..update.logs.len() {
+    let log = &mut update.logs[index];
+    let stdout = (log.stdout.len() > MAX_INLINE_LOG_BYTES)
+      .then(|| std::mem::take(&mut log.stdout));
+    let stderr = (log.stderr.len() > MAX_INLINE_LOG_BYTES)
+      .then(|| std::mem::take(&mut log.stderr));
+    if let Some(stdout) = stdout {
+      update.logs[index].stdout =
+        offload_log_output(update, index, "stdout", stdout).await;
+    }
+    if let Some(stderr) = stderr {
+      update.logs[index].stderr =
+        offload_log_output(update, index, "stderr", stderr).await;
+    }
+  }
+}
+
+/// Returns the truncated output to keep on the log.
+async fn offload_log_output(
+  update: &mut Update,
+  index: usize,
+  stream: &str,
+  output: String,
+) -> String {
+  let mut start =
+    output.len().saturating_sub(OFFLOADED_LOG_TAIL_BYTES);
+  while !output.is_char_boundary(start) {
+    start += 1;
+  }
+  let tail = output[start..].to_string();
+  let name = format!("log-{index}-{stream}.txt");
+  match attach_artifact(update, &name, output.into_bytes()).await {
+    Ok(_) => format!(
+      "[Output truncated. The full output is stored in artifact '{name}']\n...{tail}"
+    ),
+    Err(e) => {
+      warn!(
+        "Failed to store log artifact for update {} | {e:#}",
+        update.id
+      );
+      format!(
+        "[Output truncated. Failed to store the full output | {e:#}]\n...{tail}"
+      )
+    }
+  }
+}
+
+fn artifact_key(update_id: &str, name: &str) -> String {
+  format!("{update_id}/{name}")
+}
+
+/// Artifacts are stored flat in the Update's directory.
+fn sanitize_artifact_name(name: &str) -> String {
+  let name = name.replace(['/', '\\'], "_");
+  if name.is_empty() || name == "." || name == ".." {
+    String::from("_")
+  } else {
+    name
+  }
+}
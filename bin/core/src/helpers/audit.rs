@@ -0,0 +1,156 @@
+use std::net::SocketAddr;
+
+use axum::{
+  extract::{ConnectInfo, FromRequestParts},
+  http::{HeaderMap, request::Parts},
+};
+use komodo_client::entities::{
+  ResourceTarget, audit::AuditLogEntry, komodo_timestamp, user::User,
+};
+use serde::Serialize;
+
+use crate::state::db_client;
+
+/// Request params which name the entity the request targets,
+/// in order of precedence.
+const TARGET_KEYS: &[&str] = &[
+  "id",
+  "name",
+  "server",
+  "deployment",
+  "stack",
+  "build",
+  "repo",
+  "procedure",
+  "action",
+  "alerter",
+  "builder",
+  "sync",
+  "swarm_service",
+  "user",
+  "user_group",
+  "username",
+  "key",
+];
+
+/// Where a request came from, for the audit log.
+#[derive(Debug, Clone, Default)]
+pub struct RequestSource {
+  pub ip: Option<String>,
+  /// The public api key, if the request used one.
+  pub api_key: Option<String>,
+}
+
+impl RequestSource {
+  pub fn new(
+    headers: &HeaderMap,
+    connect_info: Option<&ConnectInfo<SocketAddr>>,
+  ) -> RequestSource {
+    let header = |name: &str| {
+      headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+    };
+    // Prefer the client ip forwarded by a reverse proxy.
+    let ip = header("x-forwarded-for")
+      .and_then(|forwarded| forwarded.split(',').next())
+      .or_else(|| header("x-real-ip"))
+      .map(|ip| ip.trim().to_string())
+      .or_else(|| {
+        connect_info.map(|ConnectInfo(addr)| addr.ip().to_string())
+      });
+    let api_key = if header("authorization").is_none() {
+      header("x-api-key").map(str::to_string)
+    } else {
+      None
+    };
+    RequestSource { ip, api_key }
+  }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for RequestSource {
+  type Rejection = std::convert::Infallible;
+
+  async fn from_request_parts(
+    parts: &mut Parts,
+    _: &S,
+  ) -> Result<Self, Self::Rejection> {
+    Ok(RequestSource::new(
+      &parts.headers,
+      parts.extensions.get::<ConnectInfo<SocketAddr>>(),
+    ))
+  }
+}
+
+/// Starts an audit log entry for a request serialized like
+/// `{ "type": "UpdateVariableValue", "params": { ... } }`.
+///
+/// Only the request type and target are taken from the request,
+/// the params themselves are never recorded.
+pub fn audit_entry(
+  api: &str,
+  source: &RequestSource,
+  request: &impl Serialize,
+) -> AuditLogEntry {
+  let request = serde_json::to_value(request).unwrap_or_default();
+  let operation = request
+    .get("type")
+    .and_then(|ty| ty.as_str())
+    .unwrap_or_default()
+    .to_string();
+  let params = request.get("params");
+  let target = params
+    .and_then(|params| params.get("target"))
+    .and_then(|target| {
+      serde_json::from_value::<ResourceTarget>(target.clone()).ok()
+    });
+  let target_name = params.and_then(|params| {
+    TARGET_KEYS.iter().find_map(|key| {
+      params
+        .get(key)
+        .and_then(|value| value.as_str())
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+    })
+  });
+  AuditLogEntry {
+    ts: komodo_timestamp(),
+    api_key: source.api_key.clone(),
+    ip: source.ip.clone(),
+    api: api.to_string(),
+    operation,
+    target,
+    target_name,
+    ..Default::default()
+  }
+}
+
+/// Records the outcome of the request in the audit log.
+/// The insert runs in the background so it doesn't slow down the response.
+pub fn record_audit<T>(
+  mut entry: AuditLogEntry,
+  user: Option<&User>,
+  res: &serror::Result<T>,
+) {
+  if let Some(user) = user {
+    entry.user_id = user.id.clone();
+    entry.username = user.username.clone();
+  }
+  match res {
+    Ok(_) => entry.success = true,
+    Err(e) => {
+      entry.success = false;
+      entry.error = Some(format!("{:#}", e.error));
+    }
+  }
+  tokio::spawn(async move {
+    if let Err(e) = db_client().audit_log.insert_one(&entry).await {
+      warn!(
+        "Failed to record {} {} in audit log | {e:#}",
+        entry.api, entry.operation
+      );
+    }
+  });
+}
@@ -0,0 +1,387 @@
+use std::{
+  collections::{HashMap, HashSet},
+  sync::{Mutex, OnceLock},
+};
+
+use anyhow::Context;
+use async_timing_util::{Timelength, wait_until_timelength};
+use database::mungos::{
+  find::find_collect,
+  mongodb::{bson::doc, options::FindOneOptions},
+};
+use komodo_client::{
+  api::execute::{
+    DestroyDeployment, DestroyStack, StopDeployment, StopStack,
+  },
+  entities::{
+    AutoStopMode, ResourceTarget, ScheduleFormat,
+    alert::{Alert, AlertData, SeverityLevel},
+    deployment::DeploymentState,
+    komodo_timestamp,
+    stack::StackState,
+  },
+};
+
+use crate::{
+  alert::send_alerts,
+  api::execute::ExecuteRequest,
+  schedule::{
+    HasSchedule, find_next_occurrence, run_scheduler_execution,
+  },
+  state::{db_client, deployment_status_cache, stack_status_cache},
+};
+
+use super::uptime::{deployment_is_up, stack_is_up};
+
+/// The auto stop config of a Deployment or Stack.
+struct AutoStop {
+  target: ResourceTarget,
+  name: String,
+  /// Unix ms the resource was last updated,
+  /// used if there are no Updates on the resource.
+  updated_at: i64,
+  mode: AutoStopMode,
+  after: i64,
+  schedule_format: ScheduleFormat,
+  schedule: String,
+  schedule_timezone: String,
+  warning: i64,
+}
+
+impl HasSchedule for &AutoStop {
+  fn target(&self) -> ResourceTarget {
+    self.target.clone()
+  }
+  fn name(&self) -> &str {
+    &self.name
+  }
+  fn enabled(&self) -> bool {
+    true
+  }
+  fn format(&self) -> ScheduleFormat {
+    self.schedule_format
+  }
+  fn schedule(&self) -> &str {
+    &self.schedule
+  }
+  fn timezone(&self) -> &str {
+    &self.schedule_timezone
+  }
+}
+
+#[derive(Default)]
+struct AutoStopState {
+  /// The schedule which `next_scheduled` was found with,
+  /// used to recompute it when the schedule changes.
+  schedule: String,
+  /// The next scheduled stop in unix ms.
+  next_scheduled: Option<i64>,
+  /// The stop time the AutoStopPending alert was sent for.
+  warned: Option<i64>,
+}
+
+/// Resource target -> AutoStopState
+fn auto_stop_states()
+-> &'static Mutex<HashMap<ResourceTarget, AutoStopState>> {
+  static AUTO_STOP_STATES: OnceLock<
+    Mutex<HashMap<ResourceTarget, AutoStopState>>,
+  > = OnceLock::new();
+  AUTO_STOP_STATES.get_or_init(Default::default)
+}
+
+pub fn spawn_auto_stop_loop() {
+  tokio::spawn(async move {
+    loop {
+      wait_until_timelength(Timelength::OneMinute, 0).await;
+      if let Err(e) = check_auto_stops().await {
+        error!("Failed to check auto stops | {e:#}");
+      }
+    }
+  });
+}
+
+async fn check_auto_stops() -> anyhow::Result<()> {
+  let (deployments, stacks) = tokio::try_join!(
+    find_collect(
+      &db_client().deployments,
+      doc! { "config.auto_stop_enabled": true },
+      None,
+    ),
+    find_collect(
+      &db_client().stacks,
+      doc! { "config.auto_stop_enabled": true },
+      None,
+    ),
+  )
+  .context("Failed to get auto stop resources from db")?;
+
+  let mut auto_stops = Vec::new();
+
+  for deployment in deployments {
+    let state = deployment_status_cache()
+      .get(&deployment.id)
+      .await
+      .unwrap_or_default()
+      .curr
+      .state;
+    let active = match deployment.config.auto_stop_mode {
+      AutoStopMode::Stop => deployment_is_up(state),
+      AutoStopMode::Destroy => !matches!(
+        state,
+        DeploymentState::NotDeployed | DeploymentState::Unknown
+      ),
+    };
+    let config = deployment.config;
+    auto_stops.push((
+      AutoStop {
+        target: ResourceTarget::Deployment(deployment.id),
+        name: deployment.name,
+        updated_at: deployment.updated_at,
+        mode: config.auto_stop_mode,
+        after: config.auto_stop_after,
+        schedule_format: config.auto_stop_schedule_format,
+        schedule: config.auto_stop_schedule,
+        schedule_timezone: config.auto_stop_schedule_timezone,
+        warning: config.auto_stop_warning,
+      },
+      active,
+    ));
+  }
+
+  for stack in stacks {
+    let state = stack_status_cache()
+      .get(&stack.id)
+      .await
+      .unwrap_or_default()
+      .curr
+      .state;
+    let active = match stack.config.auto_stop_mode {
+      AutoStopMode::Stop => stack_is_up(state),
+      AutoStopMode::Destroy => {
+        !matches!(state, StackState::Down | StackState::Unknown)
+      }
+    };
+    let config = stack.config;
+    auto_stops.push((
+      AutoStop {
+        target: ResourceTarget::Stack(stack.id),
+        name: stack.name,
+        updated_at: stack.updated_at,
+        mode: config.auto_stop_mode,
+        after: config.auto_stop_after,
+        schedule_format: config.auto_stop_schedule_format,
+        schedule: config.auto_stop_schedule,
+        schedule_timezone: config.auto_stop_schedule_timezone,
+        warning: config.auto_stop_warning,
+      },
+      active,
+    ));
+  }
+
+  // Clear out state for resources which no longer use auto stop
+  let targets = auto_stops
+    .iter()
+    .map(|(auto_stop, _)| auto_stop.target.clone())
+    .collect::<HashSet<_>>();
+  auto_stop_states()
+    .lock()
+    .unwrap()
+    .retain(|target, _| targets.contains(target));
+
+  let now = komodo_timestamp();
+  for (auto_stop, active) in auto_stops {
+    if let Err(e) = check_auto_stop(auto_stop, active, now).await {
+      warn!("Failed to check auto stop | {e:#}");
+    }
+  }
+
+  Ok(())
+}
+
+async fn check_auto_stop(
+  auto_stop: AutoStop,
+  active: bool,
+  now: i64,
+) -> anyhow::Result<()> {
+  // The schedule is always advanced, so a stopped resource
+  // isn't stopped immediately after being started again.
+  let scheduled_due = advance_schedule(&auto_stop, now);
+
+  if !active {
+    return Ok(());
+  }
+
+  if scheduled_due {
+    run_auto_stop(&auto_stop).await;
+    return Ok(());
+  }
+
+  let inactive_at = if auto_stop.after > 0 {
+    Some(last_activity(&auto_stop).await? + auto_stop.after * 60_000)
+  } else {
+    None
+  };
+  let next_scheduled = auto_stop_states()
+    .lock()
+    .unwrap()
+    .get(&auto_stop.target)
+    .and_then(|state| state.next_scheduled);
+
+  let Some(stop_at) =
+    [inactive_at, next_scheduled].into_iter().flatten().min()
+  else {
+    return Ok(());
+  };
+
+  if now >= stop_at {
+    run_auto_stop(&auto_stop).await;
+    return Ok(());
+  }
+
+  if auto_stop.warning > 0
+    && stop_at - now <= auto_stop.warning * 60_000
+  {
+    send_auto_stop_warning(&auto_stop, stop_at, now).await;
+  }
+
+  Ok(())
+}
+
+/// Updates the next scheduled stop,
+/// returning whether the previous one is due.
+fn advance_schedule(auto_stop: &AutoStop, now: i64) -> bool {
+  let mut states = auto_stop_states().lock().unwrap();
+  let state = states.entry(auto_stop.target.clone()).or_default();
+
+  if auto_stop.schedule.is_empty() {
+    state.schedule.clear();
+    state.next_scheduled = None;
+    return false;
+  }
+
+  let schedule = format!(
+    "{:?}|{}|{}",
+    auto_stop.schedule_format,
+    auto_stop.schedule,
+    auto_stop.schedule_timezone
+  );
+  let due = state.schedule == schedule
+    && state.next_scheduled.is_some_and(|next| now >= next);
+
+  if state.schedule != schedule || due {
+    state.schedule = schedule;
+    state.next_scheduled = match find_next_occurrence(auto_stop) {
+      Ok(next) => Some(next),
+      Err(e) => {
+        warn!(
+          "Invalid auto stop schedule for {:?} | {e:#}",
+          auto_stop.target
+        );
+        None
+      }
+    };
+  }
+
+  due
+}
+
+/// The latest Update on the resource in unix ms.
+async fn last_activity(auto_stop: &AutoStop) -> anyhow::Result<i64> {
+  let (variant, id) = auto_stop.target.extract_variant_id();
+  let last_update = db_client()
+    .updates
+    .find_one(doc! {
+      "target.type": variant.as_ref(),
+      "target.id": id,
+    })
+    .with_options(
+      FindOneOptions::builder()
+        .sort(doc! { "start_ts": -1 })
+        .build(),
+    )
+    .await
+    .context("Failed to get latest update from db")?;
+  Ok(
+    last_update
+      .map(|update| update.start_ts)
+      .unwrap_or(auto_stop.updated_at),
+  )
+}
+
+async fn send_auto_stop_warning(
+  auto_stop: &AutoStop,
+  stop_at: i64,
+  now: i64,
+) {
+  {
+    let mut states = auto_stop_states().lock().unwrap();
+    let state = states.entry(auto_stop.target.clone()).or_default();
+    if state.warned == Some(stop_at) {
+      return;
+    }
+    state.warned = Some(stop_at);
+  }
+  let (resource_type, id) = auto_stop.target.extract_variant_id();
+  let alert = Alert {
+    id: Default::default(),
+    target: auto_stop.target.clone(),
+    ts: now,
+    resolved_ts: Some(now),
+    resolved: true,
+    level: SeverityLevel::Warning,
+    data: AlertData::AutoStopPending {
+      resource_type,
+      id: id.clone(),
+      name: auto_stop.name.clone(),
+      mode: auto_stop.mode,
+      // Round up to whole minutes
+      minutes: (stop_at - now + 59_999) / 60_000,
+    },
+    notifications: Default::default(),
+  };
+  send_alerts(&[alert]).await
+}
+
+async fn run_auto_stop(auto_stop: &AutoStop) {
+  let request = match (&auto_stop.target, auto_stop.mode) {
+    (ResourceTarget::Deployment(id), AutoStopMode::Stop) => {
+      ExecuteRequest::StopDeployment(StopDeployment {
+        deployment: id.clone(),
+        signal: None,
+        time: None,
+      })
+    }
+    (ResourceTarget::Deployment(id), AutoStopMode::Destroy) => {
+      ExecuteRequest::DestroyDeployment(DestroyDeployment {
+        deployment: id.clone(),
+        signal: None,
+        time: None,
+      })
+    }
+    (ResourceTarget::Stack(id), AutoStopMode::Stop) => {
+      ExecuteRequest::StopStack(StopStack {
+        stack: id.clone(),
+        stop_time: None,
+        services: Vec::new(),
+      })
+    }
+    (ResourceTarget::Stack(id), AutoStopMode::Destroy) => {
+      ExecuteRequest::DestroyStack(DestroyStack {
+        stack: id.clone(),
+        services: Vec::new(),
+        remove_orphans: false,
+        stop_time: None,
+      })
+    }
+    _ => unreachable!(),
+  };
+  info!(
+    "Auto stop ({}) triggered for {:?} {}",
+    auto_stop.mode, auto_stop.target, auto_stop.name
+  );
+  run_scheduler_execution(
+    request,
+    &format!("auto stop of {}", auto_stop.name),
+  )
+  .await
+}
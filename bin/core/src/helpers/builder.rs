@@ -11,15 +11,21 @@ use komodo_client::entities::{
 };
 use periphery_client::{
   PeripheryClient,
-  api::{self, GetVersionResponse},
+  api::{self, GetVersionResponse, SpotInterruption},
 };
 
 use crate::{
   cloud::{
     BuildCleanupData,
-    aws::ec2::{
-      Ec2Instance, launch_ec2_instance,
-      terminate_ec2_instance_with_retry,
+    aws::{
+      ec2::{
+        Ec2Instance, ec2_instance_is_running, launch_ec2_instance,
+        terminate_ec2_instance_with_retry,
+      },
+      warm_pool::{
+        WARM_POOL_TAG, WarmInstance, release_warm_instance,
+        take_warm_instance,
+      },
     },
   },
   config::core_config,
@@ -27,10 +33,11 @@ use crate::{
   resource,
 };
 
-use super::periphery_client;
+use super::{periphery_client, periphery_signing_key};
 
 const BUILDER_POLL_RATE_SECS: u64 = 2;
 const BUILDER_POLL_MAX_TRIES: usize = 60;
+const SPOT_INTERRUPTION_POLL_SECS: u64 = 5;
 
 #[instrument(skip_all, fields(builder_id = builder.id, update_id = update.id))]
 pub async fn get_builder_periphery(
@@ -56,7 +63,8 @@ pub async fn get_builder_periphery(
         },
         config.request,
         Duration::from_secs(3),
-      );
+      )
+      .with_signing_key(periphery_signing_key(&builder.id));
       periphery
         .health_check()
         .await
@@ -68,28 +76,68 @@ pub async fn get_builder_periphery(
         return Err(anyhow!("Builder has not configured a server"));
       }
       let server = resource::get::<Server>(&config.server_id).await?;
-      let periphery = periphery_client(&server)?;
+      let periphery = periphery_client(&server).await?;
       Ok((periphery, BuildCleanupData::Server))
     }
     BuilderConfig::Aws(config) => {
-      get_aws_builder(&resource_name, version, config, update).await
+      get_aws_builder(
+        &builder.id,
+        &resource_name,
+        version,
+        config,
+        update,
+      )
+      .await
     }
   }
 }
 
 #[instrument(skip_all, fields(resource_name, update_id = update.id))]
 async fn get_aws_builder(
+  builder_id: &str,
   resource_name: &str,
   version: Option<Version>,
   config: AwsBuilderConfig,
   update: &mut Update,
 ) -> anyhow::Result<(PeripheryClient, BuildCleanupData)> {
+  let warm_pool = config.warm_pool_size > 0;
+
+  if warm_pool
+    && let Some((periphery, instance)) =
+      get_warm_instance(builder_id, &config).await
+  {
+    update.push_simple_log(
+      "use warm pool instance",
+      format!(
+        "using instance {} from the builder warm pool\n{}: {}",
+        instance.instance_id,
+        muted("ip"),
+        instance.ip
+      ),
+    );
+    update_update(update.clone()).await?;
+    return Ok((
+      periphery,
+      BuildCleanupData::AwsWarmPool {
+        builder_id: builder_id.to_string(),
+        instance,
+      },
+    ));
+  }
+
   let start_create_ts = komodo_timestamp();
 
   let version = version.map(|v| format!("-v{v}")).unwrap_or_default();
   let instance_name = format!("BUILDER-{resource_name}{version}");
+  // Instances which will be returned to the warm pool
+  // are tagged so they can be cleaned up after Core restarts.
+  let extra_tags = if warm_pool {
+    vec![(WARM_POOL_TAG, core_config().host.as_str())]
+  } else {
+    Vec::new()
+  };
   let Ec2Instance { instance_id, ip } =
-    launch_ec2_instance(&instance_name, &config).await?;
+    launch_ec2_instance(&instance_name, &config, &extra_tags).await?;
 
   info!("ec2 instance launched");
 
@@ -106,15 +154,7 @@ async fn get_aws_builder(
 
   update_update(update.clone()).await?;
 
-  let protocol = if config.use_https { "https" } else { "http" };
-  let periphery_address =
-    format!("{protocol}://{ip}:{}", config.port);
-  let periphery = PeripheryClient::new(
-    &periphery_address,
-    &core_config().passkey,
-    [],
-    Duration::from_secs(3),
-  );
+  let periphery = aws_builder_periphery(builder_id, &ip, &config);
 
   let start_connect_ts = komodo_timestamp();
   let mut res = Ok(GetVersionResponse {
@@ -138,13 +178,18 @@ async fn get_aws_builder(
       };
       update.logs.push(connect_log);
       update_update(update.clone()).await?;
-      return Ok((
-        periphery,
+      let cleanup_data = if warm_pool {
+        BuildCleanupData::AwsWarmPool {
+          builder_id: builder_id.to_string(),
+          instance: WarmInstance::new(instance_id, ip, config.region),
+        }
+      } else {
         BuildCleanupData::Aws {
           instance_id,
           region: config.region,
-        },
-      ));
+        }
+      };
+      return Ok((periphery, cleanup_data));
     }
     res = version;
     tokio::time::sleep(Duration::from_secs(BUILDER_POLL_RATE_SECS))
@@ -166,6 +211,55 @@ async fn get_aws_builder(
   )
 }
 
+/// Takes a reachable instance from the Builder's warm pool.
+/// Unreachable instances are terminated.
+async fn get_warm_instance(
+  builder_id: &str,
+  config: &AwsBuilderConfig,
+) -> Option<(PeripheryClient, WarmInstance)> {
+  while let Some(instance) = take_warm_instance(builder_id) {
+    let periphery =
+      aws_builder_periphery(builder_id, &instance.ip, config);
+    match periphery.request(api::GetVersion {}).await {
+      Ok(_) => return Some((periphery, instance)),
+      Err(e) => {
+        warn!(
+          "warm pool instance {} is unreachable, terminating | {e:#}",
+          instance.instance_id
+        );
+        tokio::spawn(async move {
+          let _ = terminate_ec2_instance_with_retry(
+            instance.region,
+            &instance.instance_id,
+          )
+          .await;
+        });
+      }
+    }
+  }
+  None
+}
+
+pub fn aws_builder_periphery(
+  builder_id: &str,
+  ip: &str,
+  config: &AwsBuilderConfig,
+) -> PeripheryClient {
+  let protocol = if config.use_https { "https" } else { "http" };
+  let periphery_address = if ip.contains(':') {
+    format!("{protocol}://[{ip}]:{}", config.port)
+  } else {
+    format!("{protocol}://{ip}:{}", config.port)
+  };
+  PeripheryClient::new(
+    &periphery_address,
+    &core_config().passkey,
+    [],
+    Duration::from_secs(3),
+  )
+  .with_signing_key(periphery_signing_key(builder_id))
+}
+
 #[instrument(skip(update))]
 pub async fn cleanup_builder_instance(
   cleanup_data: BuildCleanupData,
@@ -190,9 +284,89 @@ pub async fn cleanup_builder_instance(
         format!("termination queued for instance id {instance_id}"),
       );
     }
+    BuildCleanupData::AwsWarmPool {
+      builder_id,
+      instance,
+    } => {
+      update.push_simple_log(
+        "release instance",
+        format!(
+          "instance id {} returned to the builder warm pool",
+          instance.instance_id
+        ),
+      );
+      release_warm_instance(&builder_id, instance);
+    }
   }
 }
 
+/// Resolves with a description of the interruption once the
+/// spot instance running the build receives an interruption notice.
+/// Never resolves if the builder is not a spot instance.
+pub async fn wait_for_spot_interruption(
+  periphery: &PeripheryClient,
+  spot: bool,
+) -> String {
+  if !spot {
+    return std::future::pending().await;
+  }
+  loop {
+    tokio::time::sleep(Duration::from_secs(
+      SPOT_INTERRUPTION_POLL_SECS,
+    ))
+    .await;
+    if let Ok(Some(interruption)) =
+      periphery.request(api::GetSpotInterruption {}).await
+    {
+      return spot_interruption_message(&interruption);
+    }
+  }
+}
+
+/// After a request to a spot builder fails, checks whether
+/// the failure was caused by the instance being interrupted.
+pub async fn check_spot_interruption(
+  periphery: &PeripheryClient,
+  cleanup_data: &BuildCleanupData,
+  spot: bool,
+) -> Option<String> {
+  if !spot {
+    return None;
+  }
+  if let Ok(interruption) =
+    periphery.request(api::GetSpotInterruption {}).await
+  {
+    return interruption.as_ref().map(spot_interruption_message);
+  }
+  // Periphery is unreachable, the instance may already be reclaimed.
+  let (instance_id, region) = match cleanup_data {
+    BuildCleanupData::Server => return None,
+    BuildCleanupData::Aws {
+      instance_id,
+      region,
+    } => (instance_id, region),
+    BuildCleanupData::AwsWarmPool { instance, .. } => {
+      (&instance.instance_id, &instance.region)
+    }
+  };
+  match ec2_instance_is_running(region.clone(), instance_id).await {
+    Ok(false) => Some(format!(
+      "spot instance {instance_id} was reclaimed by AWS"
+    )),
+    _ => None,
+  }
+}
+
+fn spot_interruption_message(
+  SpotInterruption { action, time }: &SpotInterruption,
+) -> String {
+  format!(
+    "received spot interruption notice\n{}: {action}\n{}: {time}",
+    muted("action"),
+    muted("time")
+  )
+}
+
 pub fn start_aws_builder_log(
   instance_id: &str,
   ip: &str,
@@ -207,6 +381,7 @@ pub fn start_aws_builder_log(
     security_group_ids,
     use_public_ip,
     use_https,
+    use_spot,
     ..
   } = config;
 
@@ -223,6 +398,7 @@ pub fn start_aws_builder_log(
     format!("{}: {assign_public_ip}", muted("assign public ip")),
     format!("{}: {use_public_ip}", muted("use public ip")),
     format!("{}: {use_https}", muted("use https")),
+    format!("{}: {use_spot}", muted("spot instance")),
   ]
   .join("\n")
 }
@@ -0,0 +1,91 @@
+//! Cancelling in progress executions with CancelUpdate.
+
+use std::{future::Future, sync::OnceLock};
+
+use anyhow::anyhow;
+use tokio_util::sync::CancellationToken;
+
+use super::cache::Cache;
+
+tokio::task_local! {
+  /// The cancellation token of the execution run by the task.
+  /// Procedure stages share the token of the Procedure.
+  static EXECUTION_CANCEL: CancellationToken;
+}
+
+/// Update id -> the cancellation token of the execution
+/// running on this Core.
+fn execution_cancels() -> &'static Cache<String, CancellationToken> {
+  static CANCELS: OnceLock<Cache<String, CancellationToken>> =
+    OnceLock::new();
+  CANCELS.get_or_init(Default::default)
+}
+
+/// Cancels the execution recorded by the Update.
+/// Returns false if it isn't running on this Core.
+pub async fn cancel_execution(update_id: &str) -> bool {
+  match execution_cancels().get(&update_id.to_string()).await {
+    Some(cancel) => {
+      cancel.cancel();
+      true
+    }
+    None => false,
+  }
+}
+
+/// Runs the execution with a cancellation token registered
+/// for its Update, so it can be cancelled with CancelUpdate.
+pub async fn run_cancellable<T>(
+  variant: &str,
+  update_id: &str,
+  execution: impl Future<Output = anyhow::Result<T>>,
+) -> anyhow::Result<T> {
+  let cancel = CancellationToken::new();
+  let _registration = if update_id.is_empty() {
+    None
+  } else {
+    execution_cancels()
+      .insert(update_id.to_string(), cancel.clone())
+      .await;
+    Some(CancelRegistration(update_id.to_string()))
+  };
+  EXECUTION_CANCEL
+    .scope(cancel, stop_on_cancel(variant, execution))
+    .await
+}
+
+/// Stops the execution where it is when cancelled, releasing
+/// its action state and resource lock as they are dropped.
+/// Executions which handle the cancel themselves run to completion.
+pub async fn stop_on_cancel<T>(
+  variant: &str,
+  execution: impl Future<Output = anyhow::Result<T>>,
+) -> anyhow::Result<T> {
+  // Builds clean up their builder, and Procedures
+  // cancel the executions of the running stage.
+  if matches!(variant, "RunBuild" | "BuildRepo" | "RunProcedure") {
+    return execution.await;
+  }
+  let cancel = execution_cancel_token();
+  tokio::select! {
+    res = execution => res,
+    _ = cancel.cancelled() => Err(anyhow!("Execution was cancelled")),
+  }
+}
+
+/// The cancellation token of the execution being run.
+/// Never cancelled outside of a cancellable execution.
+pub fn execution_cancel_token() -> CancellationToken {
+  EXECUTION_CANCEL.try_with(Clone::clone).unwrap_or_default()
+}
+
+struct CancelRegistration(String);
+
+impl Drop for CancelRegistration {
+  fn drop(&mut self) {
+    let update_id = std::mem::take(&mut self.0);
+    tokio::spawn(async move {
+      execution_cancels().remove(&update_id).await;
+    });
+  }
+}
@@ -0,0 +1,319 @@
+use std::collections::HashMap;
+
+use anyhow::Context;
+use interpolate::Interpolator;
+use komodo_client::{
+  api::read::ContainerConfigDiff,
+  entities::{
+    build::Build,
+    deployment::{
+      Deployment, DeploymentConfig, DeploymentImage, RestartMode,
+      conversions_from_str,
+    },
+    docker::container::{Container, RestartPolicyNameEnum},
+    environment_vars_from_str,
+    server::Server,
+    stack::Stack,
+  },
+  parsers::QUOTE_PATTERN,
+};
+
+use crate::resource;
+
+use super::query::{VariablesAndSecrets, get_variables_and_secrets};
+
+/// Compare the running Deployment container with
+/// what would be passed to `docker run` if it were deployed now.
+pub async fn diff_deployment_container(
+  mut deployment: Deployment,
+  container: &Container,
+) -> anyhow::Result<Vec<ContainerConfigDiff>> {
+  let mut secret_replacers = Vec::new();
+  if !deployment.config.skip_secret_interp {
+    let VariablesAndSecrets { variables, secrets } =
+      get_variables_and_secrets().await?;
+    let server =
+      resource::get::<Server>(&deployment.config.server_id).await?;
+    let mut interpolator =
+      Interpolator::new(Some(&variables), &secrets)
+        .with_server(&server)?;
+    interpolator.interpolate_deployment(&mut deployment)?;
+    secret_replacers.extend(interpolator.secret_replacers);
+  }
+
+  let DeploymentConfig {
+    image,
+    restart,
+    command,
+    ports,
+    volumes,
+    environment,
+    labels,
+    ..
+  } = &deployment.config;
+
+  let config = container.config.clone().unwrap_or_default();
+  let host_config = container.host_config.clone().unwrap_or_default();
+
+  let mut diffs = Vec::new();
+
+  push_diff(
+    &mut diffs,
+    "image",
+    config.image.unwrap_or_default(),
+    get_deployment_image(image).await?,
+  );
+
+  if !command.trim().is_empty() {
+    push_diff(
+      &mut diffs,
+      "command",
+      config.cmd.join(" "),
+      command.trim().to_string(),
+    );
+  }
+
+  push_diff(
+    &mut diffs,
+    "network",
+    host_config.network_mode.unwrap_or_default(),
+    deployment.config.network_name().to_string(),
+  );
+
+  let current_restart = match host_config
+    .restart_policy
+    .map(|policy| policy.name)
+    .unwrap_or_default()
+  {
+    RestartPolicyNameEnum::Empty | RestartPolicyNameEnum::No => {
+      RestartMode::NoRestart
+    }
+    RestartPolicyNameEnum::Always => RestartMode::Always,
+    RestartPolicyNameEnum::UnlessStopped => {
+      RestartMode::UnlessStopped
+    }
+    RestartPolicyNameEnum::OnFailure => RestartMode::OnFailure,
+  };
+  push_diff(
+    &mut diffs,
+    "restart",
+    current_restart.to_string(),
+    restart.to_string(),
+  );
+
+  let mut current_ports = host_config
+    .port_bindings
+    .iter()
+    .flat_map(|(container_port, bindings)| {
+      let container_port = container_port
+        .strip_suffix("/tcp")
+        .unwrap_or(container_port);
+      bindings.iter().map(move |binding| {
+        let host_port =
+          binding.host_port.as_deref().unwrap_or_default();
+        match binding.host_ip.as_deref() {
+          None | Some("") | Some("0.0.0.0") | Some("::") => {
+            format!("{host_port}:{container_port}")
+          }
+          Some(host_ip) => {
+            format!("{host_ip}:{host_port}:{container_port}")
+          }
+        }
+      })
+    })
+    .collect::<Vec<_>>();
+  current_ports.sort();
+  current_ports.dedup();
+  let mut expected_ports = conversions_from_str(ports)
+    .context("Invalid ports")?
+    .into_iter()
+    .map(|port| {
+      let container_port = port
+        .container
+        .strip_suffix("/tcp")
+        .unwrap_or(&port.container)
+        .to_string();
+      format!("{}:{container_port}", port.local)
+    })
+    .collect::<Vec<_>>();
+  expected_ports.sort();
+  push_diff(
+    &mut diffs,
+    "ports",
+    current_ports.join("\n"),
+    expected_ports.join("\n"),
+  );
+
+  let mut current_volumes = host_config.binds.clone();
+  current_volumes.sort();
+  let mut expected_volumes = conversions_from_str(volumes)
+    .context("Invalid volumes")?
+    .into_iter()
+    .map(|volume| format!("{}:{}", volume.local, volume.container))
+    .collect::<Vec<_>>();
+  expected_volumes.sort();
+  push_diff(
+    &mut diffs,
+    "volumes",
+    current_volumes.join("\n"),
+    expected_volumes.join("\n"),
+  );
+
+  let current_environment = config
+    .env
+    .iter()
+    .filter_map(|var| var.split_once('='))
+    .collect::<HashMap<_, _>>();
+  for var in environment_vars_from_str(environment)
+    .context("Invalid environment")?
+  {
+    push_diff(
+      &mut diffs,
+      &format!("environment.{}", var.variable),
+      current_environment
+        .get(var.variable.as_str())
+        .map(|value| value.to_string())
+        .unwrap_or_default(),
+      strip_quotes(&var.value),
+    );
+  }
+
+  for label in
+    environment_vars_from_str(labels).context("Invalid labels")?
+  {
+    push_diff(
+      &mut diffs,
+      &format!("labels.{}", label.variable),
+      config
+        .labels
+        .get(&label.variable)
+        .cloned()
+        .unwrap_or_default(),
+      strip_quotes(&label.value),
+    );
+  }
+
+  // Values which still reference a secret couldn't be resolved by Core,
+  // they are likely Periphery secrets.
+  diffs.retain(|diff| !diff.expected.contains("[["));
+
+  // Don't expose any secret values in the response
+  for diff in &mut diffs {
+    diff.current =
+      svi::replace_in_string(&diff.current, &secret_replacers);
+    diff.expected =
+      svi::replace_in_string(&diff.expected, &secret_replacers);
+  }
+
+  Ok(diffs)
+}
+
+/// Compare the running Stack service container with
+/// the latest Stack configuration.
+pub fn diff_stack_container(
+  stack: &Stack,
+  service: &str,
+  container: &Container,
+) -> Vec<ContainerConfigDiff> {
+  let mut diffs = Vec::new();
+
+  if let Some(latest) = stack
+    .info
+    .latest_services
+    .iter()
+    .find(|s| s.service_name == service)
+  {
+    push_diff(
+      &mut diffs,
+      "image",
+      container
+        .config
+        .as_ref()
+        .and_then(|config| config.image.clone())
+        .unwrap_or_default(),
+      latest.image.clone(),
+    );
+  }
+
+  // Compose files are compared by contents,
+  // matching the behavior of DeployStackIfChanged.
+  if let (Some(deployed), Some(latest)) =
+    (&stack.info.deployed_contents, &stack.info.remote_contents)
+  {
+    for file in latest.iter().filter(|file| {
+      file.services.is_empty()
+        || file.services.iter().any(|s| s == service)
+    }) {
+      let deployed = deployed
+        .iter()
+        .find(|deployed| deployed.path == file.path)
+        .map(|deployed| deployed.contents.clone())
+        .unwrap_or_default();
+      push_diff(
+        &mut diffs,
+        &format!("files.{}", file.path),
+        deployed,
+        file.contents.clone(),
+      );
+    }
+  }
+
+  diffs
+}
+
+/// Get the image the Deployment would be deployed with now.
+async fn get_deployment_image(
+  image: &DeploymentImage,
+) -> anyhow::Result<String> {
+  match image {
+    DeploymentImage::Image { image } => Ok(image.clone()),
+    DeploymentImage::Build { build_id, version } => {
+      let build = resource::get::<Build>(build_id).await?;
+      let image_names = build.get_image_names();
+      let image_name = image_names
+        .first()
+        .context("No image name could be created")?;
+      let version = if version.is_none() {
+        build.config.version.to_string()
+      } else {
+        version.to_string()
+      };
+      if build.config.image_tag.is_empty() {
+        Ok(format!("{image_name}:{version}"))
+      } else {
+        Ok(format!(
+          "{image_name}:{version}-{}",
+          build.config.image_tag
+        ))
+      }
+    }
+  }
+}
+
+fn push_diff(
+  diffs: &mut Vec<ContainerConfigDiff>,
+  field: &str,
+  current: String,
+  expected: String,
+) {
+  if current == expected {
+    return;
+  }
+  diffs.push(ContainerConfigDiff {
+    field: field.to_string(),
+    current,
+    expected,
+  });
+}
+
+/// Quotes are removed by the shell when passed to `docker run`.
+fn strip_quotes(value: &str) -> String {
+  if value.len() > 1
+    && value.starts_with(QUOTE_PATTERN)
+    && value.ends_with(QUOTE_PATTERN)
+  {
+    value[1..value.len() - 1].to_string()
+  } else {
+    value.to_string()
+  }
+}
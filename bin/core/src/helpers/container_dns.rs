@@ -0,0 +1,59 @@
+use komodo_client::entities::server::Server;
+
+use crate::config::core_config;
+
+use super::query::get_tag;
+
+/// Applies the Core `container_dns_defaults` matching the Server's tags.
+/// `dns` is only filled in if the resource doesn't configure any,
+/// and `extra_hosts` are added unless the hostname is already configured.
+pub async fn apply_container_dns_defaults(
+  server: &Server,
+  dns: &mut Vec<String>,
+  extra_hosts: &mut Vec<String>,
+) {
+  for defaults in &core_config().container_dns_defaults {
+    if !defaults.tags.is_empty()
+      && !server_has_any_tag(server, &defaults.tags).await
+    {
+      continue;
+    }
+    if dns.is_empty() {
+      dns.extend(defaults.dns.iter().cloned());
+    }
+    for host in &defaults.extra_hosts {
+      let hostname = host_entry_name(host);
+      if !extra_hosts
+        .iter()
+        .any(|existing| host_entry_name(existing) == hostname)
+      {
+        extra_hosts.push(host.clone());
+      }
+    }
+  }
+}
+
+async fn server_has_any_tag(
+  server: &Server,
+  tags: &[String],
+) -> bool {
+  for tag in tags {
+    match get_tag(tag).await {
+      Ok(tag) => {
+        if server.tags.contains(&tag.id) {
+          return true;
+        }
+      }
+      Err(e) => {
+        warn!("Failed to get container dns defaults tag | {e:#}")
+      }
+    }
+  }
+  false
+}
+
+/// Gets the hostname from a `hostname:ip` entry.
+/// The ip may be IPv6, so splits on the first ':'.
+fn host_entry_name(entry: &str) -> &str {
+  entry.split_once(':').map(|(name, _)| name).unwrap_or(entry)
+}
@@ -0,0 +1,834 @@
+use std::{
+  collections::HashMap,
+  path::PathBuf,
+  sync::{Arc, OnceLock},
+  time::Duration,
+};
+
+use anyhow::{Context, anyhow};
+use bollard::{
+  API_DEFAULT_VERSION, Docker,
+  auth::DockerCredentials,
+  models::{
+    ContainerCreateBody, EndpointIpamConfig, EndpointSettings,
+    HostConfig, NetworkingConfig, PortBinding, RestartPolicy,
+    RestartPolicyNameEnum,
+  },
+  query_parameters::{
+    CreateContainerOptions, CreateImageOptions,
+    ListContainersOptions, LogsOptions, PruneImagesOptions,
+    RemoveContainerOptions, RestartContainerOptions,
+    StartContainerOptions, StopContainerOptions,
+  },
+};
+use formatting::format_serror;
+use futures::{StreamExt, TryStreamExt};
+use komodo_client::{
+  entities::{
+    SearchCombinator, TerminationSignal,
+    deployment::{
+      Deployment, DeploymentImage, RestartMode, conversions_from_str,
+      extract_registry_domain,
+    },
+    docker::container::{ContainerListItem, HealthStatusEnum},
+    environment_vars_from_str, komodo_timestamp,
+    server::{Server, ServerMode},
+    update::Log,
+  },
+  parsers::QUOTE_PATTERN,
+};
+use periphery_client::{
+  PeripheryClient, RequestHandler,
+  api::{
+    self, GetDockerListsResponse,
+    container::{
+      Deploy, GetContainerLog, GetContainerLogSearch, PauseContainer,
+      RemoveContainer, RestartContainer, StartContainer,
+      StopContainer, UnpauseContainer,
+    },
+    image::PruneImages,
+  },
+};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use tokio::sync::Mutex;
+
+use crate::{config::core_config, helpers::write_private_file};
+
+/// Timeout for Docker API requests other than the health check.
+/// Deploy includes the image pull, so this is generous.
+const DOCKER_API_TIMEOUT_SECS: u64 = 600;
+
+/// What the Docker client was connected with.
+/// If any of these change, the client is reconnected.
+#[derive(PartialEq)]
+struct DockerSpec {
+  address: String,
+  ca: String,
+  cert: String,
+  key: String,
+}
+
+/// Server id -> (DockerSpec, Docker)
+fn docker_clients()
+-> &'static Mutex<HashMap<String, (DockerSpec, Docker)>> {
+  static DOCKER_CLIENTS: OnceLock<
+    Mutex<HashMap<String, (DockerSpec, Docker)>>,
+  > = OnceLock::new();
+  DOCKER_CLIENTS.get_or_init(Default::default)
+}
+
+/// Gets a client which handles Periphery requests
+/// using the Docker API at the Server address directly.
+/// Unsupported requests return an error.
+pub async fn docker_api_client(
+  server: &Server,
+  address: String,
+) -> anyhow::Result<PeripheryClient> {
+  let docker = docker(server, &address).await?;
+  let timeout =
+    Duration::from_secs(server.config.timeout_seconds as u64);
+  let handler: RequestHandler = Arc::new(move |req_type, params| {
+    let docker = docker.clone();
+    Box::pin(async move {
+      handle_request(&docker, timeout, &req_type, params).await
+    })
+  });
+  Ok(
+    PeripheryClient::new(address, "", Vec::new(), timeout)
+      .with_handler(handler),
+  )
+}
+
+async fn docker(
+  server: &Server,
+  address: &str,
+) -> anyhow::Result<Docker> {
+  let ServerMode::DockerApi {
+    ca_secret,
+    cert_secret,
+    key_secret,
+  } = &server.config.mode
+  else {
+    return Err(anyhow!("Server is not in Docker API mode"));
+  };
+
+  let secret = |name: &str| {
+    if name.is_empty() {
+      return anyhow::Ok(String::new());
+    }
+    core_config().secrets.get(name).cloned().with_context(|| {
+      format!("No Core secret named '{name}' for Docker API TLS")
+    })
+  };
+  let spec = DockerSpec {
+    address: address.to_string(),
+    ca: secret(ca_secret)?,
+    cert: secret(cert_secret)?,
+    key: secret(key_secret)?,
+  };
+
+  let mut clients = docker_clients().lock().await;
+
+  if let Some((existing, docker)) = clients.get(&server.id)
+    && *existing == spec
+  {
+    return Ok(docker.clone());
+  }
+
+  let docker =
+    connect(&server.id, &spec).await.with_context(|| {
+      format!(
+        "Failed to connect to Docker API at {address} for Server {}",
+        server.name
+      )
+    })?;
+  clients.insert(server.id.clone(), (spec, docker.clone()));
+
+  Ok(docker)
+}
+
+async fn connect(
+  server_id: &str,
+  spec: &DockerSpec,
+) -> anyhow::Result<Docker> {
+  if spec.cert.is_empty() && spec.key.is_empty() {
+    return Docker::connect_with_http(
+      &spec.address,
+      DOCKER_API_TIMEOUT_SECS,
+      API_DEFAULT_VERSION,
+    )
+    .context("Failed to create Docker client");
+  }
+
+  let dir =
+    std::env::temp_dir().join("komodo-docker").join(server_id);
+  let ca_path = write_pem(&dir, "ca.pem", &spec.ca).await?;
+  let cert_path = write_pem(&dir, "cert.pem", &spec.cert).await?;
+  let key_path = write_pem(&dir, "key.pem", &spec.key).await?;
+
+  Docker::connect_with_ssl(
+    &spec.address,
+    &key_path,
+    &cert_path,
+    &ca_path,
+    DOCKER_API_TIMEOUT_SECS,
+    API_DEFAULT_VERSION,
+  )
+  .context("Failed to create Docker TLS client")
+}
+
+async fn write_pem(
+  dir: &std::path::Path,
+  name: &str,
+  contents: &str,
+) -> anyhow::Result<PathBuf> {
+  let path = dir.join(name);
+  write_private_file(
+    &path,
+    format!("{}\n", contents.trim()).as_bytes(),
+  )
+  .await
+  .with_context(|| format!("Failed to write {name}"))?;
+  Ok(path)
+}
+
+async fn handle_request(
+  docker: &Docker,
+  timeout: Duration,
+  req_type: &str,
+  params: Value,
+) -> anyhow::Result<Value> {
+  match req_type {
+    "GetHealth" => {
+      tokio::time::timeout(timeout, docker.ping())
+        .await
+        .context("Timed out reaching Docker API")?
+        .context("Failed to reach Docker API")?;
+      respond(api::GetHealthResponse {})
+    }
+    "GetVersion" => {
+      let version = docker
+        .version()
+        .await
+        .context("Failed to get Docker version")?;
+      respond(api::GetVersionResponse {
+        version: format!(
+          "docker {}",
+          version.version.unwrap_or_default()
+        ),
+      })
+    }
+    "GetDockerLists" => {
+      let containers =
+        list_containers(docker).await.map_err(|e| (&e).into());
+      respond(GetDockerListsResponse {
+        containers,
+        networks: Ok(Vec::new()),
+        images: Ok(Vec::new()),
+        volumes: Ok(Vec::new()),
+        projects: Ok(Vec::new()),
+      })
+    }
+    "GetContainerLog" => {
+      let GetContainerLog {
+        name,
+        tail,
+        timestamps,
+      } = parse(params)?;
+      let command = format!("docker logs --tail {tail} {name}");
+      let (stdout, stderr) =
+        container_log(docker, &name, tail.to_string(), timestamps)
+          .await?;
+      respond(log_output(
+        "get container log",
+        command,
+        stdout,
+        stderr,
+      ))
+    }
+    "GetContainerLogSearch" => {
+      let GetContainerLogSearch {
+        name,
+        terms,
+        combinator,
+        invert,
+        timestamps,
+      } = parse(params)?;
+      let command = format!("docker logs {name} | grep ...");
+      let (stdout, stderr) =
+        container_log(docker, &name, String::from("all"), timestamps)
+          .await?;
+      respond(log_output(
+        "search container log",
+        command,
+        search_lines(&stdout, &terms, combinator, invert),
+        search_lines(&stderr, &terms, combinator, invert),
+      ))
+    }
+    "Deploy" => respond(deploy(docker, parse(params)?).await),
+    "StartContainer" => {
+      let StartContainer { name } = parse(params)?;
+      respond(
+        action_log(
+          "docker start",
+          format!("docker start {name}"),
+          async {
+            docker
+              .start_container(&name, None::<StartContainerOptions>)
+              .await?;
+            Ok(format!("Started container {name}"))
+          },
+        )
+        .await,
+      )
+    }
+    "RestartContainer" => {
+      let RestartContainer { name } = parse(params)?;
+      respond(
+        action_log(
+          "docker restart",
+          format!("docker restart {name}"),
+          async {
+            docker
+              .restart_container(
+                &name,
+                None::<RestartContainerOptions>,
+              )
+              .await?;
+            Ok(format!("Restarted container {name}"))
+          },
+        )
+        .await,
+      )
+    }
+    "PauseContainer" => {
+      let PauseContainer { name } = parse(params)?;
+      respond(
+        action_log(
+          "docker pause",
+          format!("docker pause {name}"),
+          async {
+            docker.pause_container(&name).await?;
+            Ok(format!("Paused container {name}"))
+          },
+        )
+        .await,
+      )
+    }
+    "UnpauseContainer" => {
+      let UnpauseContainer { name } = parse(params)?;
+      respond(
+        action_log(
+          "docker unpause",
+          format!("docker unpause {name}"),
+          async {
+            docker.unpause_container(&name).await?;
+            Ok(format!("Unpaused container {name}"))
+          },
+        )
+        .await,
+      )
+    }
+    "StopContainer" => {
+      let StopContainer { name, signal, time } = parse(params)?;
+      respond(
+        action_log(
+          "docker stop",
+          format!("docker stop {name}"),
+          async {
+            stop_container(docker, &name, signal, time).await?;
+            Ok(format!("Stopped container {name}"))
+          },
+        )
+        .await,
+      )
+    }
+    "RemoveContainer" => {
+      let RemoveContainer { name, signal, time } = parse(params)?;
+      respond(
+        action_log(
+          "docker stop and remove",
+          format!("docker stop {name} && docker container rm {name}"),
+          async {
+            stop_container(docker, &name, signal, time).await.ok();
+            remove_container(docker, &name).await?;
+            Ok(format!("Removed container {name}"))
+          },
+        )
+        .await,
+      )
+    }
+    "PruneImages" => {
+      let PruneImages {} = parse(params)?;
+      respond(
+        action_log(
+          "docker image prune",
+          String::from("docker image prune -a -f"),
+          async {
+            let res =
+              docker.prune_images(None::<PruneImagesOptions>).await?;
+            Ok(format!(
+              "Deleted {} images. Reclaimed {} bytes",
+              res.images_deleted.unwrap_or_default().len(),
+              res.space_reclaimed.unwrap_or_default()
+            ))
+          },
+        )
+        .await,
+      )
+    }
+    _ => Err(anyhow!(
+      "{req_type} is not supported on Docker API Servers. Install Periphery on the Server for full support."
+    )),
+  }
+}
+
+fn parse<T: DeserializeOwned>(params: Value) -> anyhow::Result<T> {
+  serde_json::from_value(params).context("Failed to parse request")
+}
+
+fn respond<T: Serialize>(res: T) -> anyhow::Result<Value> {
+  serde_json::to_value(res).context("Failed to serialize response")
+}
+
+/// Runs the action, returning a Log in the same form
+/// Periphery would for the equivalent command.
+async fn action_log(
+  stage: &str,
+  command: String,
+  action: impl Future<Output = anyhow::Result<String>>,
+) -> Log {
+  let start_ts = komodo_timestamp();
+  let mut log = match action.await {
+    Ok(stdout) => Log::simple(stage, stdout),
+    Err(e) => Log::error(stage, format_serror(&e.into())),
+  };
+  log.command = command;
+  log.start_ts = start_ts;
+  log
+}
+
+fn log_output(
+  stage: &str,
+  command: String,
+  stdout: String,
+  stderr: String,
+) -> Log {
+  let mut log = Log::simple(stage, stdout);
+  log.stderr = stderr;
+  log.command = command;
+  log
+}
+
+// ==========
+// CONTAINERS
+// ==========
+
+async fn list_containers(
+  docker: &Docker,
+) -> anyhow::Result<Vec<ContainerListItem>> {
+  let containers = docker
+    .list_containers(Some(ListContainersOptions {
+      all: true,
+      ..Default::default()
+    }))
+    .await
+    .context("Failed to list containers")?;
+  let mut containers = containers
+    .into_iter()
+    .filter_map(|container| {
+      let name = container.names?.pop()?.replace('/', "");
+      let mut networks = container
+        .network_settings
+        .and_then(|settings| settings.networks)
+        .map(|networks| networks.into_keys().collect::<Vec<_>>())
+        .unwrap_or_default();
+      networks.sort();
+      Some(ContainerListItem {
+        server_id: None,
+        name,
+        id: container.id,
+        image: container.image,
+        image_id: container.image_id,
+        created: container.created,
+        size_rw: container.size_rw,
+        size_root_fs: container.size_root_fs,
+        state: container
+          .state
+          .and_then(|state| state.to_string().parse().ok())
+          .unwrap_or_default(),
+        health: container
+          .status
+          .as_deref()
+          .and_then(HealthStatusEnum::from_container_status),
+        status: container.status,
+        network_mode: container
+          .host_config
+          .and_then(|config| config.network_mode),
+        networks,
+        labels: container.labels.unwrap_or_default(),
+        ..Default::default()
+      })
+    })
+    .collect::<Vec<_>>();
+  containers.sort_by(|a, b| a.name.cmp(&b.name));
+  Ok(containers)
+}
+
+/// Returns (stdout, stderr)
+async fn container_log(
+  docker: &Docker,
+  name: &str,
+  tail: String,
+  timestamps: bool,
+) -> anyhow::Result<(String, String)> {
+  let mut stream = docker.logs(
+    name,
+    Some(LogsOptions {
+      stdout: true,
+      stderr: true,
+      tail,
+      timestamps,
+      ..Default::default()
+    }),
+  );
+  let (mut stdout, mut stderr) = (String::new(), String::new());
+  while let Some(output) = stream.next().await {
+    match output.context("Failed to get container log")? {
+      bollard::container::LogOutput::StdErr { message } => {
+        stderr.push_str(&String::from_utf8_lossy(&message))
+      }
+      output => stdout.push_str(&output.to_string()),
+    }
+  }
+  Ok((stdout, stderr))
+}
+
+fn search_lines(
+  log: &str,
+  terms: &[String],
+  combinator: SearchCombinator,
+  invert: bool,
+) -> String {
+  log
+    .lines()
+    .filter(|line| {
+      let matches = match combinator {
+        SearchCombinator::Or => {
+          terms.iter().any(|term| line.contains(term.as_str()))
+        }
+        SearchCombinator::And => {
+          terms.iter().all(|term| line.contains(term.as_str()))
+        }
+      };
+      matches != invert
+    })
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+async fn stop_container(
+  docker: &Docker,
+  name: &str,
+  signal: Option<TerminationSignal>,
+  time: Option<i32>,
+) -> anyhow::Result<()> {
+  docker
+    .stop_container(
+      name,
+      Some(StopContainerOptions {
+        signal: signal.map(|signal| signal.to_string()),
+        t: time,
+      }),
+    )
+    .await
+    .with_context(|| format!("Failed to stop container {name}"))
+}
+
+async fn remove_container(
+  docker: &Docker,
+  name: &str,
+) -> anyhow::Result<()> {
+  docker
+    .remove_container(
+      name,
+      Some(RemoveContainerOptions {
+        force: true,
+        ..Default::default()
+      }),
+    )
+    .await
+    .with_context(|| format!("Failed to remove container {name}"))
+}
+
+// ======
+// DEPLOY
+// ======
+
+async fn deploy(docker: &Docker, req: Deploy) -> Log {
+  let Deploy {
+    deployment,
+    stop_signal,
+    stop_time,
+    registry_token,
+    replacers,
+  } = req;
+  let name = deployment.name.clone();
+  let mut log = action_log(
+    "Docker Run",
+    format!("docker run -d --name {name} ..."),
+    async {
+      let DeploymentImage::Image { image } = &deployment.config.image
+      else {
+        return Err(anyhow!(
+          "Deployment does not have image attached"
+        ));
+      };
+      if image.is_empty() {
+        return Err(anyhow!(
+          "Deployment does not have image attached"
+        ));
+      }
+      if !deployment.config.extra_args.is_empty() {
+        return Err(anyhow!(
+          "Extra args are not supported on Docker API Servers"
+        ));
+      }
+      let body = container_create_body(&deployment, image)?;
+
+      pull_image(
+        docker,
+        image,
+        &deployment.config.image_registry_account,
+        registry_token,
+      )
+      .await?;
+
+      stop_container(docker, &name, stop_signal, stop_time)
+        .await
+        .ok();
+      remove_container(docker, &name).await.ok();
+
+      let container = docker
+        .create_container(
+          Some(CreateContainerOptions {
+            name: Some(name.clone()),
+            ..Default::default()
+          }),
+          body,
+        )
+        .await
+        .context("Failed to create container")?;
+      docker
+        .start_container(&name, None::<StartContainerOptions>)
+        .await
+        .context("Failed to start container")?;
+
+      Ok(container.id)
+    },
+  )
+  .await;
+  // Errors may include interpolated secrets
+  log.stderr = svi::replace_in_string(&log.stderr, &replacers);
+  log
+}
+
+async fn pull_image(
+  docker: &Docker,
+  image: &str,
+  account: &str,
+  registry_token: Option<String>,
+) -> anyhow::Result<()> {
+  let credentials = match registry_token {
+    Some(token) if !account.is_empty() => Some(DockerCredentials {
+      username: Some(account.to_string()),
+      password: Some(token),
+      serveraddress: Some(extract_registry_domain(image)?),
+      ..Default::default()
+    }),
+    _ => None,
+  };
+  docker
+    .create_image(
+      Some(CreateImageOptions {
+        from_image: Some(image.to_string()),
+        ..Default::default()
+      }),
+      None,
+      credentials,
+    )
+    .try_collect::<Vec<_>>()
+    .await
+    .with_context(|| format!("Failed to pull image {image}"))?;
+  Ok(())
+}
+
+fn container_create_body(
+  deployment: &Deployment,
+  image: &str,
+) -> anyhow::Result<ContainerCreateBody> {
+  let config = &deployment.config;
+
+  let mut exposed_ports = HashMap::new();
+  let mut port_bindings = HashMap::new();
+  for port in
+    conversions_from_str(&config.ports).context("Invalid ports")?
+  {
+    let container_port = if port.container.contains('/') {
+      port.container
+    } else {
+      format!("{}/tcp", port.container)
+    };
+    let (host_ip, host_port) = match port.local.rsplit_once(':') {
+      Some((ip, port)) => (Some(ip.to_string()), port.to_string()),
+      None => (None, port.local),
+    };
+    exposed_ports.insert(container_port.clone(), HashMap::new());
+    port_bindings
+      .entry(container_port)
+      .or_insert_with(|| Some(Vec::new()))
+      .get_or_insert_with(Vec::new)
+      .push(PortBinding {
+        host_ip,
+        host_port: Some(host_port),
+      });
+  }
+
+  let binds = conversions_from_str(&config.volumes)
+    .context("Invalid volumes")?
+    .into_iter()
+    .map(|volume| format!("{}:{}", volume.local, volume.container))
+    .collect::<Vec<_>>();
+
+  let env = environment_vars_from_str(&config.environment)
+    .context("Invalid environment")?
+    .into_iter()
+    .map(|var| {
+      format!("{}={}", var.variable, strip_quotes(var.value))
+    })
+    .collect::<Vec<_>>();
+
+  let labels = environment_vars_from_str(&config.labels)
+    .context("Invalid labels")?
+    .into_iter()
+    .map(|label| (label.variable, strip_quotes(label.value)))
+    .collect::<HashMap<_, _>>();
+
+  let restart_policy = RestartPolicy {
+    name: Some(match config.restart {
+      RestartMode::NoRestart => RestartPolicyNameEnum::NO,
+      RestartMode::OnFailure => RestartPolicyNameEnum::ON_FAILURE,
+      RestartMode::Always => RestartPolicyNameEnum::ALWAYS,
+      RestartMode::UnlessStopped => {
+        RestartPolicyNameEnum::UNLESS_STOPPED
+      }
+    }),
+    maximum_retry_count: matches!(
+      config.restart,
+      RestartMode::OnFailure
+    )
+    .then_some(10),
+  };
+
+  let network = config.network_name().to_string();
+  let networking_config = if config.ip_address.is_empty()
+    && config.mac_address.is_empty()
+  {
+    None
+  } else {
+    let ipam_config = if config.ip_address.is_empty() {
+      None
+    } else if config.ip_address.contains(':') {
+      Some(EndpointIpamConfig {
+        ipv6_address: Some(config.ip_address.clone()),
+        ..Default::default()
+      })
+    } else {
+      Some(EndpointIpamConfig {
+        ipv4_address: Some(config.ip_address.clone()),
+        ..Default::default()
+      })
+    };
+    Some(NetworkingConfig {
+      endpoints_config: Some(HashMap::from([(
+        network.clone(),
+        EndpointSettings {
+          ipam_config,
+          mac_address: (!config.mac_address.is_empty())
+            .then(|| config.mac_address.clone()),
+          ..Default::default()
+        },
+      )])),
+    })
+  };
+
+  let cmd = split_command(&config.command);
+
+  Ok(ContainerCreateBody {
+    image: Some(image.to_string()),
+    cmd: (!cmd.is_empty()).then_some(cmd),
+    env: Some(env),
+    labels: Some(labels),
+    exposed_ports: Some(exposed_ports),
+    stop_signal: Some(config.termination_signal.to_string()),
+    stop_timeout: Some(config.termination_timeout as i64),
+    host_config: Some(HostConfig {
+      binds: Some(binds),
+      port_bindings: Some(port_bindings),
+      network_mode: Some(network),
+      restart_policy: Some(restart_policy),
+      dns: Some(config.dns.clone()),
+      extra_hosts: Some(config.extra_hosts.clone()),
+      ..Default::default()
+    }),
+    networking_config,
+    ..Default::default()
+  })
+}
+
+/// The Docker API takes the raw value,
+/// without the quotes needed by `docker run`.
+fn strip_quotes(value: String) -> String {
+  if value.len() >= 2
+    && value.starts_with(QUOTE_PATTERN)
+    && value.ends_with(QUOTE_PATTERN)
+  {
+    value[1..value.len() - 1].to_string()
+  } else {
+    value
+  }
+}
+
+/// Splits the command into args like a shell would,
+/// respecting single and double quotes.
+fn split_command(command: &str) -> Vec<String> {
+  let mut args = Vec::new();
+  let mut curr = String::new();
+  let mut in_arg = false;
+  let mut quote = None;
+  for char in command.chars() {
+    match (quote, char) {
+      (Some(q), c) if c == q => quote = None,
+      (Some(_), c) => curr.push(c),
+      (None, '\'' | '"') => {
+        quote = Some(char);
+        in_arg = true;
+      }
+      (None, c) if c.is_whitespace() => {
+        if in_arg {
+          args.push(std::mem::take(&mut curr));
+          in_arg = false;
+        }
+      }
+      (None, c) => {
+        curr.push(c);
+        in_arg = true;
+      }
+    }
+  }
+  if in_arg {
+    args.push(curr);
+  }
+  args
+}
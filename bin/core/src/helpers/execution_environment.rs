@@ -0,0 +1,86 @@
+use std::collections::BTreeSet;
+
+use komodo_client::entities::{
+  docker::image::Image,
+  update::{ExecutionEnvironment, ImageDigest},
+};
+use periphery_client::{PeripheryClient, api};
+use sha2::{Digest, Sha256};
+
+/// Captures the Periphery / Docker versions and the image digests
+/// from the Periphery which ran the build / deploy.
+/// Anything which can't be read is left empty,
+/// so this never fails the execution.
+pub async fn capture_execution_environment(
+  periphery: &PeripheryClient,
+  images: impl IntoIterator<Item = String>,
+  variables: Vec<String>,
+) -> ExecutionEnvironment {
+  let periphery_version = periphery
+    .request(api::GetVersion {})
+    .await
+    .map(|res| res.version)
+    .unwrap_or_default();
+  let docker_version = periphery
+    .request(api::stats::GetSystemInformation {})
+    .await
+    .ok()
+    .and_then(|info| info.docker_version)
+    .unwrap_or_default();
+
+  let mut digests = Vec::new();
+  for image in images
+    .into_iter()
+    .filter(|image| !image.is_empty())
+    .collect::<BTreeSet<_>>()
+  {
+    let digest = match periphery
+      .request(api::image::InspectImage {
+        name: image.clone(),
+      })
+      .await
+    {
+      Ok(inspect) => image_digest(&image, &inspect),
+      Err(e) => {
+        debug!("Failed to inspect image {image} | {e:#}");
+        String::new()
+      }
+    };
+    digests.push(ImageDigest { image, digest });
+  }
+
+  ExecutionEnvironment {
+    periphery_version,
+    docker_version,
+    images: digests,
+    compose_hash: String::new(),
+    services: Vec::new(),
+    variables,
+  }
+}
+
+/// The sha256 of the rendered compose config, in hex.
+pub fn compose_hash(compose_config: &str) -> String {
+  hex::encode(Sha256::digest(compose_config.as_bytes()))
+}
+
+/// Prefers the registry digest for the image repository,
+/// falling back to the local image id.
+fn image_digest(image: &str, inspect: &Image) -> String {
+  let repo = image.split('@').next().unwrap_or(image);
+  // Strip the tag, but not a registry port.
+  let repo = match repo.rsplit_once(':') {
+    Some((repo, tag)) if !tag.contains('/') => repo,
+    _ => repo,
+  };
+  let prefix = format!("{repo}@");
+  inspect
+    .repo_digests
+    .iter()
+    .find(|digest| digest.starts_with(&prefix))
+    .or(inspect.repo_digests.first())
+    .and_then(|digest| digest.split_once('@'))
+    .map(|(_, digest)| digest.to_string())
+    .or_else(|| inspect.id.clone())
+    .unwrap_or_default()
+}
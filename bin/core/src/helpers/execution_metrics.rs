@@ -0,0 +1,190 @@
+use std::{
+  collections::HashMap,
+  sync::{Mutex, OnceLock},
+};
+
+use anyhow::Context;
+use async_timing_util::{
+  ONE_DAY_MS, Timelength, wait_until_timelength,
+};
+use database::mungos::{
+  find::find_collect,
+  mongodb::{bson::doc, options::FindOptions},
+};
+use komodo_client::entities::{
+  Operation, ResourceTarget, komodo_timestamp,
+  update::{ExecutionMetrics, UpdateStatus},
+};
+use serde::Deserialize;
+
+use crate::state::db_client;
+
+/// The days aggregated for the list item metrics.
+const LIST_ITEM_METRICS_DAYS: u64 = 30;
+
+/// The operations which count as a deploy / run of the resource.
+const DEPLOY_OPERATIONS: &[Operation] = &[
+  Operation::Deploy,
+  Operation::DeployStack,
+  Operation::DeployStackService,
+  Operation::DeploySwarmService,
+  Operation::RunBuild,
+  Operation::BuildRepo,
+  Operation::RunProcedure,
+  Operation::RunAction,
+  Operation::RunSync,
+];
+
+/// Resource target -> ExecutionMetrics
+fn execution_metrics_cache()
+-> &'static Mutex<HashMap<ResourceTarget, ExecutionMetrics>> {
+  static EXECUTION_METRICS_CACHE: OnceLock<
+    Mutex<HashMap<ResourceTarget, ExecutionMetrics>>,
+  > = OnceLock::new();
+  EXECUTION_METRICS_CACHE.get_or_init(Default::default)
+}
+
+/// The cached metrics over the last 30 days,
+/// included on the resource list items.
+pub fn execution_metrics(
+  target: &ResourceTarget,
+) -> ExecutionMetrics {
+  execution_metrics_cache()
+    .lock()
+    .unwrap()
+    .get(target)
+    .cloned()
+    .unwrap_or_default()
+}
+
+pub fn spawn_execution_metrics_loop() {
+  tokio::spawn(async move {
+    loop {
+      if let Err(e) = refresh_execution_metrics().await {
+        error!("Failed to refresh execution metrics | {e:#}");
+      }
+      wait_until_timelength(Timelength::FifteenMinutes, 0).await;
+    }
+  });
+}
+
+async fn refresh_execution_metrics() -> anyhow::Result<()> {
+  let metrics =
+    aggregate_execution_metrics(None, LIST_ITEM_METRICS_DAYS).await?;
+  *execution_metrics_cache().lock().unwrap() = metrics;
+  Ok(())
+}
+
+/// The fields of the Updates needed for the metrics.
+#[derive(Deserialize)]
+struct DeployUpdate {
+  target: ResourceTarget,
+  success: bool,
+  start_ts: i64,
+  end_ts: Option<i64>,
+}
+
+/// Aggregates the completed deploys / runs of the last `days`,
+/// for one target or all of them.
+pub async fn aggregate_execution_metrics(
+  target: Option<&ResourceTarget>,
+  days: u64,
+) -> anyhow::Result<HashMap<ResourceTarget, ExecutionMetrics>> {
+  let now = komodo_timestamp();
+  let since = now - (days.max(1) as u128 * ONE_DAY_MS) as i64;
+  let mut filter = doc! {
+    "start_ts": { "$gte": since },
+    "status": UpdateStatus::Complete.to_string(),
+    "operation": {
+      "$in": DEPLOY_OPERATIONS
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+    },
+  };
+  if let Some(target) = target {
+    let (variant, id) = target.extract_variant_id();
+    filter.extend(doc! {
+      "target.type": variant.as_ref(),
+      "target.id": id,
+    });
+  }
+  let updates = find_collect(
+    &db_client().updates.clone_with_type::<DeployUpdate>(),
+    filter,
+    FindOptions::builder()
+      .projection(doc! {
+        "target": 1,
+        "success": 1,
+        "start_ts": 1,
+        "end_ts": 1,
+      })
+      .build(),
+  )
+  .await
+  .context("Failed to get deploy updates from db")?;
+
+  let mut by_target =
+    HashMap::<ResourceTarget, Vec<DeployUpdate>>::new();
+  for update in updates {
+    by_target
+      .entry(update.target.clone())
+      .or_default()
+      .push(update);
+  }
+  // Include the target even if it has no deploys
+  if let Some(target) = target {
+    by_target.entry(target.clone()).or_default();
+  }
+
+  let weeks = days.max(1) as f64 / 7.0;
+  Ok(
+    by_target
+      .into_iter()
+      .map(|(target, updates)| {
+        (target, metrics(&updates, since, weeks))
+      })
+      .collect(),
+  )
+}
+
+fn metrics(
+  updates: &[DeployUpdate],
+  since: i64,
+  weeks: f64,
+) -> ExecutionMetrics {
+  let deploys = updates.len();
+  let failures =
+    updates.iter().filter(|update| !update.success).count();
+  let mut durations = updates
+    .iter()
+    .filter_map(|update| {
+      update
+        .end_ts
+        .map(|end_ts| (end_ts - update.start_ts).max(0))
+    })
+    .collect::<Vec<_>>();
+  durations.sort_unstable();
+  ExecutionMetrics {
+    since,
+    deploys: deploys as i64,
+    deploys_per_week: deploys as f64 / weeks,
+    failures: failures as i64,
+    failure_rate: if deploys == 0 {
+      0.0
+    } else {
+      failures as f64 / deploys as f64
+    },
+    p50_duration_ms: percentile(&durations, 50),
+    p95_duration_ms: percentile(&durations, 95),
+  }
+}
+
+/// Nearest rank percentile of the sorted values.
+fn percentile(sorted: &[i64], percentile: usize) -> i64 {
+  if sorted.is_empty() {
+    return 0;
+  }
+  let rank = (percentile * sorted.len()).div_ceil(100);
+  sorted[rank.saturating_sub(1).min(sorted.len() - 1)]
+}
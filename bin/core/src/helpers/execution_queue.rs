@@ -0,0 +1,287 @@
+use std::sync::{Arc, OnceLock};
+
+use anyhow::{Context, anyhow};
+use async_timing_util::{Timelength, wait_until_timelength};
+use database::mungos::{
+  by_id::find_one_by_id,
+  mongodb::{
+    bson::{doc, oid::ObjectId},
+    options::{FindOneAndUpdateOptions, ReturnDocument},
+  },
+};
+use derive_variants::ExtractVariant;
+use formatting::format_serror;
+use komodo_client::{
+  api::execute::Execution,
+  entities::{
+    komodo_timestamp,
+    queued_execution::QueuedExecutionStatus,
+    server::ServerState,
+    update::{Log, Update},
+    user::User,
+  },
+};
+use periphery_client::audit::AuditContext;
+use resolver_api::Resolve;
+use response::JsonString;
+use tokio::sync::Mutex;
+
+use crate::{
+  api::execute::{ExecuteArgs, ExecuteRequest},
+  helpers::{
+    cache::Cache,
+    cancel::run_cancellable,
+    update::{init_execution_update, update_update},
+  },
+  state::{db_client, server_status_cache},
+};
+
+/// Converts the queued or scheduled execution into the request to run.
+pub fn execution_request(
+  execution: &Execution,
+) -> anyhow::Result<ExecuteRequest> {
+  let variant = execution.extract_variant().to_string();
+  // Execution and ExecuteRequest share the same serialized form.
+  let execution = serde_json::to_value(execution)
+    .context("Failed to serialize execution")?;
+  serde_json::from_value(execution)
+    .map_err(|_| anyhow!("{variant} can't be queued or scheduled"))
+}
+
+/// The queued executions are run when the Server reconnects,
+/// see [spawn_queued_executions]. This only expires them,
+/// after failing the ones left running by a Core restart.
+pub fn spawn_execution_queue_loop() {
+  let started_at = komodo_timestamp();
+  tokio::spawn(async move {
+    if let Err(e) = fail_interrupted_executions(started_at).await {
+      error!("Failed to fail interrupted queued executions | {e:#}");
+    }
+    loop {
+      wait_until_timelength(Timelength::OneMinute, 0).await;
+      if let Err(e) = expire_queued_executions().await {
+        error!("Failed to expire queued executions | {e:#}");
+      }
+    }
+  });
+}
+
+/// Executions claimed before Core started were interrupted
+/// by the restart. They may have partially run,
+/// so they are failed rather than run again.
+async fn fail_interrupted_executions(
+  started_at: i64,
+) -> anyhow::Result<()> {
+  db_client()
+    .queued_executions
+    .update_many(
+      doc! {
+        "status": "Running",
+        "started_at": { "$lt": started_at },
+      },
+      doc! { "$set": {
+        "status": "Failed",
+        "finished_at": komodo_timestamp(),
+        "error": "Core restarted while the execution was running",
+      } },
+    )
+    .await
+    .context("Failed to update interrupted queued executions")?;
+  Ok(())
+}
+
+async fn expire_queued_executions() -> anyhow::Result<()> {
+  let ts = komodo_timestamp();
+  db_client()
+    .queued_executions
+    .update_many(
+      doc! {
+        "status": "Queued",
+        "expires_at": { "$gt": 0, "$lte": ts },
+      },
+      doc! { "$set": { "status": "Expired", "finished_at": ts } },
+    )
+    .await
+    .context("Failed to expire queued executions")?;
+  Ok(())
+}
+
+fn queue_locks() -> &'static Cache<String, Arc<Mutex<()>>> {
+  static LOCKS: OnceLock<Cache<String, Arc<Mutex<()>>>> =
+    OnceLock::new();
+  LOCKS.get_or_init(Default::default)
+}
+
+/// Runs the Server's queued executions in order, if the Server
+/// is reachable and they aren't already being run.
+/// Called when the Server reconnects, and when an execution is queued.
+pub fn spawn_queued_executions(server_id: String) {
+  tokio::spawn(async move {
+    let lock = queue_locks().get_or_insert_default(&server_id).await;
+    let Ok(_lock) = lock.try_lock() else {
+      return;
+    };
+    if let Err(e) = run_queued_executions(&server_id).await {
+      warn!(
+        "Failed to run queued executions for server {server_id} | {e:#}"
+      );
+    }
+  });
+}
+
+async fn run_queued_executions(
+  server_id: &str,
+) -> anyhow::Result<()> {
+  loop {
+    // Stop if the Server becomes unreachable, the rest stay queued.
+    let reachable = server_status_cache()
+      .get(server_id)
+      .await
+      .map(|status| status.state == ServerState::Ok)
+      .unwrap_or_default();
+    if !reachable {
+      return Ok(());
+    }
+
+    let ts = komodo_timestamp();
+    // Claim the next execution, so a concurrent cancel can't race it.
+    let Some(queued) = db_client()
+      .queued_executions
+      .find_one_and_update(
+        doc! {
+          "server_id": server_id,
+          "status": "Queued",
+          "$or": [
+            { "expires_at": { "$lte": 0 } },
+            { "expires_at": { "$gt": ts } },
+          ],
+        },
+        doc! { "$set": { "status": "Running", "started_at": ts } },
+      )
+      .with_options(
+        FindOneAndUpdateOptions::builder()
+          .sort(doc! { "queued_at": 1 })
+          .return_document(ReturnDocument::After)
+          .build(),
+      )
+      .await
+      .context("Failed to claim queued execution")?
+    else {
+      return Ok(());
+    };
+
+    let (status, update_id, error) = match run_execution_as_user(
+      &queued.execution,
+      &queued.queued_by,
+      "Queued Execution",
+    )
+    .await
+    {
+      Ok(update) if update.success => {
+        (QueuedExecutionStatus::Complete, update.id, String::new())
+      }
+      Ok(update) => {
+        (QueuedExecutionStatus::Failed, update.id, String::new())
+      }
+      Err(e) => (
+        QueuedExecutionStatus::Failed,
+        String::new(),
+        format!("{e:#}"),
+      ),
+    };
+
+    let id = ObjectId::parse_str(&queued.id)
+      .context("Queued execution id is not valid ObjectId")?;
+    db_client()
+      .queued_executions
+      .update_one(
+        doc! { "_id": id },
+        doc! { "$set": {
+          "status": status.to_string(),
+          "finished_at": komodo_timestamp(),
+          "update_id": update_id,
+          "error": error,
+        } },
+      )
+      .await
+      .context("Failed to update queued execution status")?;
+  }
+}
+
+/// Runs the execution as the user who queued or scheduled it,
+/// so their permissions are checked when it runs.
+pub async fn run_execution_as_user(
+  execution: &Execution,
+  user_id: &str,
+  stage: &str,
+) -> anyhow::Result<Update> {
+  let user = find_one_by_id(&db_client().users, user_id)
+    .await
+    .context("Failed to query db for user")?
+    .context("The user to run the execution as no longer exists")?;
+  if !user.enabled {
+    return Err(anyhow!(
+      "The user to run the execution as is disabled"
+    ));
+  }
+
+  run_execution(execution_request(execution)?, user, stage).await
+}
+
+/// Runs the execution to completion, returning its final Update.
+/// Errors are logged to the Update rather than returned,
+/// as long as the Update could be created.
+pub async fn run_execution(
+  request: ExecuteRequest,
+  user: User,
+  stage: &str,
+) -> anyhow::Result<Update> {
+  let update = init_execution_update(&request, &user).await?;
+  let update_id = update.id.clone();
+
+  let variant = format!("{:?}", request.extract_variant());
+  let audit = AuditContext::new(&user.username, &update_id);
+  let res = run_cancellable(&variant, &update_id, async {
+    audit
+      .scope(request.resolve(&ExecuteArgs { user, update }))
+      .await
+      .map_err(|e| e.error)
+  })
+  .await;
+  let update = match res {
+    Ok(JsonString::Ok(update)) => {
+      serde_json::from_str::<Update>(&update)
+        .context("Failed to parse execution Update")?
+    }
+    Ok(JsonString::Err(e)) => {
+      return Err(
+        anyhow::Error::from(e)
+          .context("Failed to serialize execution Update"),
+      );
+    }
+    Err(e) => {
+      // The update may not be closed if resolve returns Err.
+      let mut update =
+        find_one_by_id(&db_client().updates, &update_id)
+          .await
+          .context("Failed to query db for update")?
+          .context("No update exists with given id")?;
+      update
+        .logs
+        .push(Log::error(stage, format_serror(&e.into())));
+      update.finalize();
+      update_update(update.clone()).await?;
+      return Ok(update);
+    }
+  };
+
+  // Some executions, like DeployStackIfChanged,
+  // only create the Update in the database if needed.
+  if update.id.is_empty() {
+    return Ok(update);
+  }
+  find_one_by_id(&db_client().updates, &update.id)
+    .await
+    .context("Failed to query db for update")?
+    .context("No update exists with given id")
+}
@@ -0,0 +1,235 @@
+//! Reading the resources and alerts of the federation peers,
+//! for the read only federated view.
+
+use std::{collections::HashSet, sync::OnceLock, time::Duration};
+
+use anyhow::Context;
+use async_timing_util::{Timelength, wait_until_timelength};
+use database::mungos::{find::find_collect, mongodb::bson::doc};
+use futures::future::join_all;
+use komodo_client::{
+  KomodoClient,
+  api::read::*,
+  entities::{
+    federation::{
+      FederatedAlert, FederatedResource, FederationPeer,
+      FederationPeerStatus,
+    },
+    komodo_timestamp,
+    resource::ResourceListItem,
+  },
+};
+
+use crate::state::db_client;
+
+use super::cache::Cache;
+
+/// The federation peer as last read by this Core.
+#[derive(Debug, Clone, Default)]
+pub struct FederatedPeer {
+  pub status: FederationPeerStatus,
+  pub resources: Vec<FederatedResource>,
+  pub alerts: Vec<FederatedAlert>,
+}
+
+/// Peer id -> the peer as last read.
+pub fn federation_cache() -> &'static Cache<String, FederatedPeer> {
+  static CACHE: OnceLock<Cache<String, FederatedPeer>> =
+    OnceLock::new();
+  CACHE.get_or_init(Default::default)
+}
+
+fn http_client() -> &'static reqwest::Client {
+  static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+  CLIENT.get_or_init(|| {
+    reqwest::Client::builder()
+      .timeout(Duration::from_secs(15))
+      .build()
+      .expect("Invalid federation reqwest client")
+  })
+}
+
+pub fn spawn_federation_refresh_loop() {
+  tokio::spawn(async move {
+    loop {
+      refresh_federation_peers().await;
+      wait_until_timelength(Timelength::OneMinute, 0).await;
+    }
+  });
+}
+
+async fn refresh_federation_peers() {
+  let peers =
+    match find_collect(&db_client().federation_peers, None, None)
+      .await
+    {
+      Ok(peers) => peers,
+      Err(e) => {
+        error!("Failed to get federation peers from db | {e:#}");
+        return;
+      }
+    };
+
+  // Remove the peers deleted by another Core using the database.
+  let ids = peers.iter().map(|peer| &peer.id).collect::<HashSet<_>>();
+  for cached in federation_cache().get_list().await {
+    if !ids.contains(&cached.status.id) {
+      federation_cache().remove(&cached.status.id).await;
+    }
+  }
+
+  join_all(peers.into_iter().map(refresh_federation_peer)).await;
+}
+
+/// Reads the peer's resources and open alerts into the cache.
+/// If the peer can't be reached, the last read is kept
+/// along with the error.
+pub async fn refresh_federation_peer(peer: FederationPeer) {
+  let mut status = FederationPeerStatus {
+    id: peer.id.clone(),
+    name: peer.name.clone(),
+    address: peer.address.clone(),
+    enabled: peer.enabled,
+    ..Default::default()
+  };
+
+  if !peer.enabled {
+    federation_cache()
+      .insert(
+        peer.id.clone(),
+        FederatedPeer {
+          status,
+          ..Default::default()
+        },
+      )
+      .await;
+    return;
+  }
+
+  let federated = match read_peer(&peer).await {
+    Ok((version, resources, alerts)) => {
+      status.version = version;
+      status.refreshed_at = komodo_timestamp();
+      FederatedPeer {
+        status,
+        resources,
+        alerts,
+      }
+    }
+    Err(e) => {
+      let previous =
+        federation_cache().get(&peer.id).await.unwrap_or_default();
+      status.version = previous.status.version;
+      status.refreshed_at = previous.status.refreshed_at;
+      status.error = format!("{e:#}");
+      FederatedPeer {
+        status,
+        resources: previous.resources,
+        alerts: previous.alerts,
+      }
+    }
+  };
+
+  federation_cache().insert(peer.id, federated).await;
+}
+
+async fn read_peer(
+  peer: &FederationPeer,
+) -> anyhow::Result<(
+  String,
+  Vec<FederatedResource>,
+  Vec<FederatedAlert>,
+)> {
+  let komodo = KomodoClient::new(
+    peer.address.trim_end_matches('/'),
+    &peer.api_key,
+    &peer.api_secret,
+  )
+  .set_reqwest(http_client().clone());
+
+  let (
+    version,
+    servers,
+    stacks,
+    deployments,
+    builds,
+    repos,
+    procedures,
+    actions,
+    syncs,
+    alerts,
+  ) = tokio::try_join!(
+    komodo.core_version(),
+    komodo.read(ListServers::default()),
+    komodo.read(ListStacks::default()),
+    komodo.read(ListDeployments::default()),
+    komodo.read(ListBuilds::default()),
+    komodo.read(ListRepos::default()),
+    komodo.read(ListProcedures::default()),
+    komodo.read(ListActions::default()),
+    komodo.read(ListResourceSyncs::default()),
+    komodo.read(ListAlerts {
+      query: Some(doc! { "resolved": false }),
+      page: 0,
+    }),
+  )
+  .with_context(|| {
+    format!("Failed to read from federation peer {}", peer.name)
+  })?;
+
+  let mut resources = Vec::new();
+  resources.extend(federated(peer, servers, |i| i.state.to_string()));
+  resources.extend(federated(peer, stacks, |i| i.state.to_string()));
+  resources
+    .extend(federated(peer, deployments, |i| i.state.to_string()));
+  resources.extend(federated(peer, builds, |i| i.state.to_string()));
+  resources.extend(federated(peer, repos, |i| i.state.to_string()));
+  resources
+    .extend(federated(peer, procedures, |i| i.state.to_string()));
+  resources.extend(federated(peer, actions, |i| i.state.to_string()));
+  resources.extend(federated(peer, syncs, |i| i.state.to_string()));
+
+  let alerts = alerts
+    .alerts
+    .into_iter()
+    .map(|alert| FederatedAlert::new(peer, alert))
+    .collect();
+
+  Ok((version, resources, alerts))
+}
+
+fn federated<'a, Info: 'a>(
+  peer: &'a FederationPeer,
+  items: Vec<ResourceListItem<Info>>,
+  state: impl Fn(&Info) -> String + 'a,
+) -> impl Iterator<Item = FederatedResource> + 'a {
+  items.into_iter().map(move |item| {
+    let state = state(&item.info);
+    FederatedResource::new(
+      peer,
+      item.resource_type,
+      item.id,
+      item.name,
+      state,
+    )
+  })
+}
+
+/// The peers matching the optional id or name.
+pub async fn federated_peers(
+  peer: Option<&str>,
+) -> Vec<FederatedPeer> {
+  let mut peers = federation_cache()
+    .get_list()
+    .await
+    .into_iter()
+    .filter(|federated| federated.status.enabled)
+    .filter(|federated| {
+      peer.is_none_or(|peer| {
+        federated.status.id == peer || federated.status.name == peer
+      })
+    })
+    .collect::<Vec<_>>();
+  peers.sort_by(|a, b| a.status.name.cmp(&b.status.name));
+  peers
+}
@@ -0,0 +1,502 @@
+//! Deletes stale version tags of Build images from the registries,
+//! following the Build [ImageRetentionPolicy].
+//!
+//! Supports Docker Hub, GHCR, and Harbor (any other domain).
+
+use std::{collections::HashSet, sync::OnceLock, time::Duration};
+
+use anyhow::{Context, anyhow};
+use komodo_client::entities::{
+  Version,
+  build::{Build, ImageRegistryConfig, ImageRetentionPolicy},
+  komodo_timestamp,
+  update::Log,
+};
+use serde::Deserialize;
+
+pub const IMAGE_RETENTION_LOG_STAGE: &str = "Image Retention";
+
+const ONE_DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+fn http_client() -> &'static reqwest::Client {
+  static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+  CLIENT.get_or_init(|| {
+    reqwest::Client::builder()
+      .timeout(Duration::from_secs(30))
+      .user_agent("komodo")
+      .build()
+      .expect("Invalid image retention reqwest client")
+  })
+}
+
+/// Deletes the stale version tags from each of the Build registries.
+/// Returns None if the policy is disabled or the image isn't pushed.
+///
+/// Failures don't fail the build, they are added to the stderr
+/// of the log, which stays successful.
+pub async fn prune_registry_images(
+  build: &Build,
+  // (domain, account, token)
+  registry_tokens: &[(String, String, String)],
+) -> Option<Log> {
+  let policy = &build.config.image_retention;
+  if !policy.enabled() {
+    return None;
+  }
+  let start_ts = komodo_timestamp();
+  let repo = if build.config.image_name.is_empty() {
+    &build.name
+  } else {
+    &build.config.image_name
+  };
+  let postfixes = version_tag_postfixes(build);
+
+  let mut stdout = Vec::new();
+  let mut stderr = Vec::new();
+
+  for registry in &build.config.image_registry {
+    let ImageRegistryConfig {
+      domain,
+      account,
+      organization,
+    } = registry;
+    if domain.is_empty() || account.is_empty() {
+      continue;
+    }
+    let namespace = if organization.is_empty() {
+      account
+    } else {
+      organization
+    };
+    let Some((_, _, token)) = registry_tokens
+      .iter()
+      .find(|(d, a, _)| d == domain && a == account)
+    else {
+      stderr.push(format!(
+        "{domain}/{namespace}/{repo}: No token for account {account}"
+      ));
+      continue;
+    };
+    let registry = Registry {
+      api: RegistryApi::from_domain(domain),
+      domain,
+      account,
+      token,
+      namespace,
+      organization,
+      repo,
+    };
+    match registry
+      .prune(policy, &build.config.version, &postfixes)
+      .await
+    {
+      Ok(deleted) if deleted.is_empty() => stdout
+        .push(format!("{domain}/{namespace}/{repo}: No stale tags")),
+      Ok(deleted) => stdout.push(format!(
+        "{domain}/{namespace}/{repo}: Deleted {}",
+        deleted.join(", ")
+      )),
+      Err(e) => {
+        stderr.push(format!("{domain}/{namespace}/{repo}: {e:#}"))
+      }
+    }
+  }
+
+  if stdout.is_empty() && stderr.is_empty() {
+    return None;
+  }
+
+  Some(Log {
+    stage: IMAGE_RETENTION_LOG_STAGE.to_string(),
+    stdout: stdout.join("\n"),
+    stderr: stderr.join("\n"),
+    success: true,
+    start_ts,
+    end_ts: komodo_timestamp(),
+    ..Default::default()
+  })
+}
+
+/// The postfix after the version in the full version tags,
+/// one for each matrix variant.
+fn version_tag_postfixes(build: &Build) -> Vec<String> {
+  let postfix = |image_tag: &str| {
+    if image_tag.is_empty() {
+      String::new()
+    } else {
+      format!("-{image_tag}")
+    }
+  };
+  if build.config.matrix.is_empty() {
+    return vec![postfix(&build.config.image_tag)];
+  }
+  build
+    .config
+    .matrix
+    .iter()
+    .map(|variant| {
+      postfix(&build.matrix_variant(variant).config.image_tag)
+    })
+    .collect()
+}
+
+/// Parses full version tags, eg `1.19.5-postfix`.
+/// Partial version tags like `1.19` are not matched.
+fn parse_version_tag(tag: &str, postfix: &str) -> Option<Version> {
+  let version = tag.strip_suffix(postfix)?;
+  if version.split('.').count() != 3 {
+    return None;
+  }
+  Version::try_from(version).ok()
+}
+
+fn version_key(version: &Version) -> (i32, i32, i32) {
+  (version.major, version.minor, version.patch)
+}
+
+/// Finds the stale tags among the (tag, pushed at) pairs.
+/// The current version, and any newer version, are always kept.
+fn stale_tags(
+  policy: &ImageRetentionPolicy,
+  current: &Version,
+  postfixes: &[String],
+  tags: &[(String, i64)],
+) -> HashSet<String> {
+  let keep_after = komodo_timestamp() - policy.keep_days * ONE_DAY_MS;
+  let mut stale = HashSet::new();
+  for postfix in postfixes {
+    let mut versions = tags
+      .iter()
+      .filter_map(|(tag, pushed_at)| {
+        parse_version_tag(tag, postfix)
+          .map(|version| (version, tag, *pushed_at))
+      })
+      .collect::<Vec<_>>();
+    versions.sort_by(|(a, ..), (b, ..)| {
+      version_key(b).cmp(&version_key(a))
+    });
+    for (i, (version, tag, pushed_at)) in
+      versions.into_iter().enumerate()
+    {
+      let keep = version_key(&version) >= version_key(current)
+        || (policy.keep_last > 0 && (i as i64) < policy.keep_last)
+        || (policy.keep_days > 0 && pushed_at >= keep_after);
+      if !keep {
+        stale.insert(tag.clone());
+      }
+    }
+  }
+  stale
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum RegistryApi {
+  DockerHub,
+  Ghcr,
+  Harbor,
+}
+
+impl RegistryApi {
+  fn from_domain(domain: &str) -> RegistryApi {
+    match domain {
+      "docker.io" | "index.docker.io" | "registry-1.docker.io" => {
+        RegistryApi::DockerHub
+      }
+      "ghcr.io" => RegistryApi::Ghcr,
+      _ => RegistryApi::Harbor,
+    }
+  }
+}
+
+/// An image in the registry with its tags.
+/// For Docker Hub, each tag is listed separately.
+struct RegistryImage {
+  /// Docker Hub: the tag, GHCR: the package version id,
+  /// Harbor: the artifact digest.
+  id: String,
+  tags: Vec<String>,
+  pushed_at: i64,
+}
+
+struct Registry<'a> {
+  api: RegistryApi,
+  domain: &'a str,
+  account: &'a str,
+  token: &'a str,
+  namespace: &'a str,
+  organization: &'a str,
+  repo: &'a str,
+}
+
+impl Registry<'_> {
+  /// Returns the deleted tags.
+  async fn prune(
+    &self,
+    policy: &ImageRetentionPolicy,
+    current: &Version,
+    postfixes: &[String],
+  ) -> anyhow::Result<Vec<String>> {
+    let auth = self.auth().await?;
+    let images = self.list_images(&auth).await?;
+    let tags = images
+      .iter()
+      .flat_map(|image| {
+        image.tags.iter().map(|tag| (tag.clone(), image.pushed_at))
+      })
+      .collect::<Vec<_>>();
+    let stale = stale_tags(policy, current, postfixes, &tags);
+
+    let mut deleted = Vec::new();
+    for image in images {
+      let image_stale = image
+        .tags
+        .iter()
+        .filter(|tag| stale.contains(*tag))
+        .collect::<Vec<_>>();
+      if image_stale.is_empty() {
+        continue;
+      }
+      // GHCR deletes whole package versions, so the version is only
+      // deleted if it isn't also tagged with a tag to keep, eg `latest`.
+      if self.api == RegistryApi::Ghcr {
+        if image_stale.len() == image.tags.len() {
+          self.delete(&auth, &image.id, None).await?;
+          deleted.extend(image.tags);
+        }
+        continue;
+      }
+      for tag in image_stale {
+        self.delete(&auth, &image.id, Some(tag)).await?;
+        deleted.push(tag.clone());
+      }
+    }
+    deleted.sort();
+    Ok(deleted)
+  }
+
+  /// The Authorization header value.
+  async fn auth(&self) -> anyhow::Result<String> {
+    match self.api {
+      RegistryApi::DockerHub => {
+        #[derive(Deserialize)]
+        struct LoginResponse {
+          token: String,
+        }
+        let res = http_client()
+          .post("https://hub.docker.com/v2/users/login")
+          .json(&serde_json::json!({
+            "username": self.account,
+            "password": self.token,
+          }))
+          .send()
+          .await
+          .context("Failed to reach Docker Hub")?;
+        let LoginResponse { token } = json_response(res)
+          .await
+          .context("Failed to login to Docker Hub")?;
+        Ok(format!("Bearer {token}"))
+      }
+      RegistryApi::Ghcr => Ok(format!("Bearer {}", self.token)),
+      RegistryApi::Harbor => {
+        use base64::{Engine, engine::general_purpose::STANDARD};
+        Ok(format!(
+          "Basic {}",
+          STANDARD.encode(format!("{}:{}", self.account, self.token))
+        ))
+      }
+    }
+  }
+
+  fn images_url(&self) -> String {
+    let repo = urlencoding::encode(self.repo);
+    match self.api {
+      RegistryApi::DockerHub => format!(
+        "https://hub.docker.com/v2/namespaces/{}/repositories/{repo}/tags",
+        self.namespace
+      ),
+      RegistryApi::Ghcr if self.organization.is_empty() => format!(
+        "https://api.github.com/user/packages/container/{repo}/versions"
+      ),
+      RegistryApi::Ghcr => format!(
+        "https://api.github.com/orgs/{}/packages/container/{repo}/versions",
+        self.organization
+      ),
+      // Harbor requires repository names with '/' to be double encoded.
+      RegistryApi::Harbor => format!(
+        "https://{}/api/v2.0/projects/{}/repositories/{}/artifacts",
+        self.domain,
+        self.namespace,
+        urlencoding::encode(&repo)
+      ),
+    }
+  }
+
+  async fn list_images(
+    &self,
+    auth: &str,
+  ) -> anyhow::Result<Vec<RegistryImage>> {
+    let url = self.images_url();
+    let mut images = Vec::new();
+    for page in 1.. {
+      let req = http_client().get(&url).header("authorization", auth);
+      let before = images.len();
+      match self.api {
+        RegistryApi::DockerHub => {
+          #[derive(Deserialize)]
+          struct TagsResponse {
+            next: Option<String>,
+            results: Vec<Tag>,
+          }
+          #[derive(Deserialize)]
+          struct Tag {
+            name: String,
+            tag_last_pushed: Option<String>,
+            last_updated: Option<String>,
+          }
+          let res = req
+            .query(&[("page_size", 100), ("page", page)])
+            .send()
+            .await
+            .context("Failed to reach Docker Hub")?;
+          let TagsResponse { next, results } = json_response(res)
+            .await
+            .context("Failed to list Docker Hub tags")?;
+          images.extend(results.into_iter().map(|tag| {
+            RegistryImage {
+              pushed_at: parse_ts(
+                tag.tag_last_pushed.or(tag.last_updated).as_deref(),
+              ),
+              id: tag.name.clone(),
+              tags: vec![tag.name],
+            }
+          }));
+          if next.is_none() {
+            break;
+          }
+        }
+        RegistryApi::Ghcr => {
+          #[derive(Deserialize)]
+          struct PackageVersion {
+            id: i64,
+            created_at: String,
+            metadata: Metadata,
+          }
+          #[derive(Deserialize)]
+          struct Metadata {
+            container: Container,
+          }
+          #[derive(Deserialize)]
+          struct Container {
+            tags: Vec<String>,
+          }
+          let res = req
+            .header("accept", "application/vnd.github+json")
+            .query(&[("per_page", 100), ("page", page)])
+            .send()
+            .await
+            .context("Failed to reach GitHub")?;
+          let versions: Vec<PackageVersion> = json_response(res)
+            .await
+            .context("Failed to list GHCR package versions")?;
+          images.extend(versions.into_iter().map(|version| {
+            RegistryImage {
+              id: version.id.to_string(),
+              tags: version.metadata.container.tags,
+              pushed_at: parse_ts(Some(&version.created_at)),
+            }
+          }));
+        }
+        RegistryApi::Harbor => {
+          #[derive(Deserialize)]
+          struct Artifact {
+            digest: String,
+            push_time: Option<String>,
+            #[serde(default)]
+            tags: Option<Vec<Tag>>,
+          }
+          #[derive(Deserialize)]
+          struct Tag {
+            name: String,
+          }
+          let res = req
+            .query(&[("with_tag", "true")])
+            .query(&[("page_size", 100), ("page", page)])
+            .send()
+            .await
+            .context("Failed to reach Harbor")?;
+          let artifacts: Vec<Artifact> = json_response(res)
+            .await
+            .context("Failed to list Harbor artifacts")?;
+          images.extend(artifacts.into_iter().map(|artifact| {
+            RegistryImage {
+              id: artifact.digest,
+              tags: artifact
+                .tags
+                .unwrap_or_default()
+                .into_iter()
+                .map(|tag| tag.name)
+                .collect(),
+              pushed_at: parse_ts(artifact.push_time.as_deref()),
+            }
+          }));
+        }
+      }
+      if images.len() == before {
+        break;
+      }
+    }
+    Ok(images)
+  }
+
+  /// Deletes the tag, or the whole image when the tag is None.
+  async fn delete(
+    &self,
+    auth: &str,
+    id: &str,
+    tag: Option<&String>,
+  ) -> anyhow::Result<()> {
+    let url = match (self.api, tag) {
+      (RegistryApi::Harbor, Some(tag)) => {
+        format!("{}/{id}/tags/{tag}", self.images_url())
+      }
+      _ => format!("{}/{id}", self.images_url()),
+    };
+    let res = http_client()
+      .delete(&url)
+      .header("authorization", auth)
+      .header("accept", "application/vnd.github+json")
+      .send()
+      .await
+      .with_context(|| format!("Failed to reach {}", self.domain))?;
+    let status = res.status();
+    if status.is_success() {
+      Ok(())
+    } else {
+      let text = res.text().await.unwrap_or_default();
+      Err(anyhow!(
+        "Failed to delete {} | {status} | {text}",
+        tag.map(String::as_str).unwrap_or(id)
+      ))
+    }
+  }
+}
+
+async fn json_response<T: serde::de::DeserializeOwned>(
+  res: reqwest::Response,
+) -> anyhow::Result<T> {
+  let status = res.status();
+  if !status.is_success() {
+    let text = res.text().await.unwrap_or_default();
+    return Err(anyhow!("{status} | {text}"));
+  }
+  res.json().await.context("Failed to parse response")
+}
+
+/// Parses RFC 3339 timestamps to unix ms.
+/// Unknown timestamps are treated as recent,
+/// so `keep_days` doesn't delete them.
+fn parse_ts(ts: Option<&str>) -> i64 {
+  ts.and_then(|ts| chrono::DateTime::parse_from_rfc3339(ts).ok())
+    .map(|ts| ts.timestamp_millis())
+    .unwrap_or_else(komodo_timestamp)
+}
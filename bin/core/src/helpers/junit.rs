@@ -0,0 +1,154 @@
+use anyhow::Context;
+use komodo_client::entities::update::{
+  TestCaseFailure, TestReport, Update,
+};
+use periphery_client::{PeripheryClient, api::build::GetJunitReport};
+use serde::Deserialize;
+
+use super::artifact::attach_artifact;
+
+/// A `<testsuites>` or `<testsuite>` element.
+/// Both may contain nested test suites.
+#[derive(Deserialize)]
+struct JunitTestSuite {
+  #[serde(default, rename = "testsuite")]
+  test_suites: Vec<JunitTestSuite>,
+  #[serde(default, rename = "testcase")]
+  test_cases: Vec<JunitTestCase>,
+}
+
+#[derive(Deserialize)]
+struct JunitTestCase {
+  #[serde(default, rename = "@name")]
+  name: String,
+  #[serde(default, rename = "@classname")]
+  class_name: String,
+  #[serde(default, rename = "@time")]
+  time: String,
+  #[serde(default)]
+  failure: Vec<JunitMessage>,
+  #[serde(default)]
+  error: Vec<JunitMessage>,
+  #[serde(default)]
+  skipped: Vec<JunitMessage>,
+}
+
+#[derive(Deserialize)]
+struct JunitMessage {
+  #[serde(default, rename = "@message")]
+  message: String,
+  #[serde(default, rename = "$text")]
+  text: String,
+}
+
+pub fn parse_junit_report(
+  name: String,
+  contents: &str,
+) -> anyhow::Result<TestReport> {
+  let root = quick_xml::de::from_str::<JunitTestSuite>(contents)
+    .context("Invalid JUnit XML")?;
+  let mut report = TestReport {
+    name,
+    ..Default::default()
+  };
+  add_test_suite(&mut report, root);
+  Ok(report)
+}
+
+fn add_test_suite(report: &mut TestReport, suite: JunitTestSuite) {
+  for case in suite.test_cases {
+    report.tests += 1;
+    // Some reporters format the time with thousands separators
+    report.time += case
+      .time
+      .replace(',', "")
+      .parse::<f64>()
+      .unwrap_or_default();
+    if let Some(failure) =
+      case.failure.into_iter().chain(case.error).next()
+    {
+      report.failed += 1;
+      let name = if case.class_name.is_empty() {
+        case.name
+      } else {
+        format!("{}::{}", case.class_name, case.name)
+      };
+      let message = if failure.message.is_empty() {
+        failure.text.trim().to_string()
+      } else {
+        failure.message
+      };
+      report.failures.push(TestCaseFailure { name, message });
+    } else if !case.skipped.is_empty() {
+      report.skipped += 1;
+    } else {
+      report.passed += 1;
+    }
+  }
+  for suite in suite.test_suites {
+    add_test_suite(report, suite);
+  }
+}
+
+/// Gets the JUnit report from Periphery and attaches
+/// the parsed results to the Update.
+///
+/// A missing or invalid report is logged, but does not
+/// fail the Update.
+pub async fn add_junit_report(
+  periphery: &PeripheryClient,
+  name: String,
+  request: GetJunitReport,
+  update: &mut Update,
+) {
+  let path = request.path.clone();
+  let contents = match periphery.request(request).await {
+    Ok(Some(contents)) => contents,
+    Ok(None) => {
+      update.push_simple_log(
+        "Test Report",
+        format!("No JUnit report for {name} found at {path}"),
+      );
+      return;
+    }
+    Err(e) => {
+      update.push_simple_log(
+        "Test Report",
+        format!("Failed to get JUnit report for {name} | {e:#}"),
+      );
+      return;
+    }
+  };
+  let report = match parse_junit_report(name, &contents) {
+    Ok(report) => report,
+    Err(e) => {
+      update.push_simple_log(
+        "Test Report",
+        format!("Failed to parse JUnit report at {path} | {e:#}"),
+      );
+      return;
+    }
+  };
+  let mut msg = format!(
+    "{}: {} passed, {} failed, {} skipped ({:.2}s)",
+    report.name,
+    report.passed,
+    report.failed,
+    report.skipped,
+    report.time
+  );
+  for failure in &report.failures {
+    msg.push_str(&format!("\n- {}", failure.name));
+  }
+  update.push_simple_log("Test Report", msg);
+  if let Err(e) = attach_artifact(
+    update,
+    &format!("{}.junit.xml", report.name),
+    contents.into_bytes(),
+  )
+  .await
+  {
+    warn!("Failed to attach JUnit report artifact | {e:#}");
+  }
+  update.test_reports.push(report);
+}
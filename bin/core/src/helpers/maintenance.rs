@@ -101,7 +101,7 @@ fn is_time_in_window(
   }
 }
 
-fn convert_day_of_week(value: chrono::Weekday) -> DayOfWeek {
+pub fn convert_day_of_week(value: chrono::Weekday) -> DayOfWeek {
   match value {
     chrono::Weekday::Mon => DayOfWeek::Monday,
     chrono::Weekday::Tue => DayOfWeek::Tuesday,
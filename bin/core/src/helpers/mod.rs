@@ -1,8 +1,15 @@
-use std::{fmt::Write, time::Duration};
+use std::{
+  fmt::Write, os::unix::fs::PermissionsExt, path::Path, str::FromStr,
+  time::Duration,
+};
 
 use anyhow::{Context, anyhow};
 use database::mongo_indexed::Document;
-use database::mungos::mongodb::bson::{Bson, doc};
+use database::mungos::{
+  find::find_collect,
+  mongodb::bson::{Bson, doc, oid::ObjectId},
+};
+use hmac::{Hmac, Mac};
 use indexmap::IndexSet;
 use komodo_client::entities::{
   ResourceTarget,
@@ -10,27 +17,58 @@ use komodo_client::entities::{
   permission::{
     Permission, PermissionLevel, SpecificPermission, UserTarget,
   },
+  provider::ProviderAccountType,
   repo::Repo,
-  server::Server,
+  resource::Resource,
+  server::{Server, ServerMode},
   stack::Stack,
   user::User,
 };
-use periphery_client::PeripheryClient;
+use periphery_client::{PeripheryClient, signing::SigningKey};
 use rand::Rng;
+use sha2::Sha256;
+use tokio::io::AsyncWriteExt;
 
-use crate::{config::core_config, state::db_client};
+use crate::{
+  config::core_config, permission::unexpired_permission_filter,
+  state::db_client,
+};
 
+pub mod access_request;
 pub mod action_state;
 pub mod all_resources;
+pub mod artifact;
+pub mod audit;
+pub mod auto_stop;
 pub mod builder;
 pub mod cache;
+pub mod cancel;
 pub mod channel;
+pub mod container_diff;
+pub mod container_dns;
+pub mod docker_api;
+pub mod execution_environment;
+pub mod execution_metrics;
+pub mod execution_queue;
+pub mod federation;
+pub mod image_retention;
+pub mod junit;
 pub mod maintenance;
 pub mod matcher;
 pub mod procedure;
+pub mod provider_health;
 pub mod prune;
 pub mod query;
+pub mod registry_auth;
+pub mod rerun;
+pub mod resource_lock;
+pub mod runtime_inputs;
+pub mod secret_providers;
+pub mod ssh_tunnel;
+pub mod stage_outputs;
+pub mod terminal;
 pub mod update;
+pub mod uptime;
 
 // pub mod resource;
 
@@ -54,12 +92,159 @@ pub fn random_string(length: usize) -> String {
     .collect()
 }
 
+/// Compares secrets in constant time,
+/// so response timing doesn't leak how much of the secret matched.
+pub fn secrets_match(a: &str, b: &str) -> bool {
+  let (a, b) = (a.as_bytes(), b.as_bytes());
+  a.len() == b.len()
+    && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Writes sensitive contents, like private keys, to a file only
+/// readable by the Core user. The directory is created 0700 and the
+/// file is created 0600 before anything is written to it.
+pub async fn write_private_file(
+  path: &Path,
+  contents: &[u8],
+) -> anyhow::Result<()> {
+  let dir = path
+    .parent()
+    .with_context(|| format!("No parent directory for {path:?}"))?;
+  tokio::fs::DirBuilder::new()
+    .recursive(true)
+    .mode(0o700)
+    .create(dir)
+    .await
+    .with_context(|| format!("Failed to create directory {dir:?}"))?;
+  // The directory may already exist with looser permissions.
+  tokio::fs::set_permissions(
+    dir,
+    std::fs::Permissions::from_mode(0o700),
+  )
+  .await
+  .with_context(|| format!("Failed to set permissions on {dir:?}"))?;
+  // Replace any existing file rather than writing through it,
+  // it may have looser permissions or be a symlink.
+  if let Err(e) = tokio::fs::remove_file(path).await
+    && e.kind() != std::io::ErrorKind::NotFound
+  {
+    return Err(e).with_context(|| {
+      format!("Failed to remove existing {path:?}")
+    });
+  }
+  let mut file = tokio::fs::OpenOptions::new()
+    .write(true)
+    .create_new(true)
+    .mode(0o600)
+    .open(path)
+    .await
+    .with_context(|| format!("Failed to create {path:?}"))?;
+  file
+    .write_all(contents)
+    .await
+    .with_context(|| format!("Failed to write {path:?}"))?;
+  file
+    .flush()
+    .await
+    .with_context(|| format!("Failed to flush {path:?}"))
+}
+
+/// The resource which will use a provider account token.
+#[derive(Debug, Clone)]
+pub struct TokenRequester {
+  pub target: ResourceTarget,
+  pub tags: Vec<String>,
+}
+
+impl<C, I> From<&Resource<C, I>> for TokenRequester
+where
+  for<'a> &'a Resource<C, I>: Into<ResourceTarget>,
+{
+  fn from(resource: &Resource<C, I>) -> Self {
+    TokenRequester {
+      target: resource.into(),
+      tags: resource.tags.clone(),
+    }
+  }
+}
+
+/// Scoped provider accounts can only be used by resources
+/// with an allowed tag, or which an allowed user group
+/// has Write permission on.
+async fn check_account_scope(
+  provider_type: ProviderAccountType,
+  domain: &str,
+  username: &str,
+  allowed_tags: &[String],
+  allowed_user_groups: &[String],
+  requester: &TokenRequester,
+) -> anyhow::Result<()> {
+  if allowed_tags.is_empty() && allowed_user_groups.is_empty() {
+    return Ok(());
+  }
+  if requester.tags.iter().any(|tag| allowed_tags.contains(tag)) {
+    return Ok(());
+  }
+  let (variant, id) = requester.target.extract_variant_id();
+  if !allowed_user_groups.is_empty() {
+    let db = db_client();
+    let user_groups = find_collect(
+      &db.user_groups,
+      doc! { "_id": { "$in": object_ids(allowed_user_groups) } },
+      None,
+    )
+    .await
+    .context("failed to query db for user groups")?;
+    let all_write = user_groups.iter().any(|group| {
+      group.all.get(&variant).is_some_and(|permission| {
+        permission.level >= PermissionLevel::Write
+      })
+    });
+    if all_write {
+      return Ok(());
+    }
+    let permissions = find_collect(
+      &db.permissions,
+      doc! {
+        "user_target.type": "UserGroup",
+        "user_target.id": { "$in": allowed_user_groups },
+        "resource_target.type": variant.as_ref(),
+        "resource_target.id": id,
+        "expires_at": unexpired_permission_filter(),
+      },
+      None,
+    )
+    .await
+    .context("failed to query db for permissions")?;
+    if permissions
+      .iter()
+      .any(|permission| permission.level >= PermissionLevel::Write)
+    {
+      return Ok(());
+    }
+  }
+  Err(anyhow!(
+    "{variant} {id} is not allowed to use {provider_type} account {domain} | {username}. \
+    It must have one of the account's allowed tags, \
+    or one of the account's allowed user groups must have Write permission on it."
+  ))
+}
+
+fn object_ids(ids: &[String]) -> Vec<ObjectId> {
+  ids
+    .iter()
+    .filter_map(|id| ObjectId::from_str(id).ok())
+    .collect()
+}
+
 /// First checks db for token, then checks core config.
-/// Only errors if db call errors.
+/// Errors if db call errors, or if the db account
+/// is scoped and does not allow the requester.
 /// Returns (token, use_https)
 pub async fn git_token(
   provider_domain: &str,
   account_username: &str,
+  requester: &TokenRequester,
   mut on_https_found: impl FnMut(bool),
 ) -> anyhow::Result<Option<String>> {
   if provider_domain.is_empty() || account_username.is_empty() {
@@ -71,6 +256,15 @@ pub async fn git_token(
     .await
     .context("failed to query db for git provider accounts")?;
   if let Some(provider) = db_provider {
+    check_account_scope(
+      ProviderAccountType::Git,
+      &provider.domain,
+      &provider.username,
+      &provider.allowed_tags,
+      &provider.allowed_user_groups,
+      requester,
+    )
+    .await?;
     on_https_found(provider.https);
     return Ok(Some(provider.token));
   }
@@ -98,6 +292,7 @@ pub async fn stack_git_token(
     return git_token(
       &repo.config.git_provider,
       &repo.config.git_account,
+      &(&*repo).into(),
       |https| repo.config.git_https = https,
     )
     .await
@@ -111,6 +306,7 @@ pub async fn stack_git_token(
   git_token(
     &stack.config.git_provider,
     &stack.config.git_account,
+    &(&*stack).into(),
     |https| stack.config.git_https = https,
   )
   .await
@@ -122,6 +318,26 @@ pub async fn stack_git_token(
   })
 }
 
+/// The registry token used by Periphery to pull the Stack OCI artifact.
+pub async fn stack_oci_token(
+  stack: &Stack,
+) -> anyhow::Result<Option<String>> {
+  if stack.config.oci_artifact.is_empty()
+    || stack.config.oci_account.is_empty()
+  {
+    return Ok(None);
+  }
+  let domain = oci::registry_domain(&stack.config.oci_artifact)?;
+  registry_token(&domain, &stack.config.oci_account, &stack.into())
+    .await
+    .with_context(|| {
+      format!(
+        "Failed to get OCI registry token. Stopping run. | {domain} | {}",
+        stack.config.oci_account
+      )
+    })
+}
+
 pub async fn build_git_token(
   build: &mut Build,
   repo: Option<&mut Repo>,
@@ -130,6 +346,7 @@ pub async fn build_git_token(
     return git_token(
       &repo.config.git_provider,
       &repo.config.git_account,
+      &(&*repo).into(),
       |https| repo.config.git_https = https,
     )
     .await
@@ -143,6 +360,7 @@ pub async fn build_git_token(
   git_token(
     &build.config.git_provider,
     &build.config.git_account,
+    &(&*build).into(),
     |https| build.config.git_https = https,
   )
   .await
@@ -155,10 +373,12 @@ pub async fn build_git_token(
 }
 
 /// First checks db for token, then checks core config.
-/// Only errors if db call errors.
+/// Errors if db call errors, or if the db account
+/// is scoped and does not allow the requester.
 pub async fn registry_token(
   provider_domain: &str,
   account_username: &str,
+  requester: &TokenRequester,
 ) -> anyhow::Result<Option<String>> {
   let provider = db_client()
     .registry_accounts
@@ -166,7 +386,19 @@ pub async fn registry_token(
     .await
     .context("failed to query db for docker registry accounts")?;
   if let Some(provider) = provider {
-    return Ok(Some(provider.token));
+    check_account_scope(
+      ProviderAccountType::Registry,
+      &provider.domain,
+      &provider.username,
+      &provider.allowed_tags,
+      &provider.allowed_user_groups,
+      requester,
+    )
+    .await?;
+    // The account may get the token through a provider auth flow.
+    return registry_auth::registry_account_token(&provider)
+      .await
+      .map(Some);
   }
   Ok(
     core_config()
@@ -185,15 +417,50 @@ pub async fn registry_token(
 
 //
 
-pub fn periphery_client(
+/// Periphery addresses are used as urls, so IPv6 literals
+/// must be wrapped in brackets, eg `https://[fd00::1]:8120`.
+pub fn validate_periphery_address(
+  address: &str,
+) -> anyhow::Result<()> {
+  let host_port = address
+    .split_once("://")
+    .map(|(_, rest)| rest)
+    .unwrap_or(address);
+  let host_port = host_port.split('/').next().unwrap_or_default();
+  if let Some(host) = host_port.strip_prefix('[') {
+    let (ip, _) = host.split_once(']').with_context(|| {
+      format!("Invalid address {address}: missing closing ']'")
+    })?;
+    ip.parse::<std::net::Ipv6Addr>().with_context(|| {
+      format!("Invalid address {address}: invalid IPv6 address {ip}")
+    })?;
+  } else if host_port.matches(':').count() > 1 {
+    return Err(anyhow!(
+      "Invalid address {address}: IPv6 addresses must be wrapped in brackets, eg https://[fd00::1]:8120"
+    ));
+  }
+  Ok(())
+}
+
+/// For SSH connections, this opens the tunnel
+/// if it isn't open already.
+/// For Docker API servers, requests are handled by Core
+/// using the Docker API directly.
+pub async fn periphery_client(
   server: &Server,
 ) -> anyhow::Result<PeripheryClient> {
   if !server.config.enabled {
     return Err(anyhow!("server not enabled"));
   }
 
+  let address = ssh_tunnel::periphery_address(server).await?;
+
+  if let ServerMode::DockerApi { .. } = &server.config.mode {
+    return docker_api::docker_api_client(server, address).await;
+  }
+
   let client = PeripheryClient::new(
-    &server.config.address,
+    address,
     if server.config.passkey.is_empty() {
       &core_config().passkey
     } else {
@@ -201,11 +468,29 @@ pub fn periphery_client(
     },
     &server.config.request_headers,
     Duration::from_secs(server.config.timeout_seconds as u64),
-  );
+  )
+  .with_container_runtime(server.config.container_runtime)
+  .with_signing_key(periphery_signing_key(&server.id));
 
   Ok(client)
 }
 
+/// The key requests to periphery are signed with.
+/// Each Server / Builder gets its own key, derived from
+/// the `periphery_signing_secret` and the resource id.
+/// Returns None if no signing secret is configured.
+pub fn periphery_signing_key(id: &str) -> Option<SigningKey> {
+  let secret = &core_config().periphery_signing_secret;
+  if secret.is_empty() {
+    return None;
+  }
+  let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+    .expect("HMAC can take key of any size");
+  mac.update(id.as_bytes());
+  let seed: [u8; 32] = mac.finalize().into_bytes().into();
+  Some(SigningKey::from_bytes(&seed))
+}
+
 #[instrument]
 pub async fn create_permission<T>(
   user: &User,
@@ -228,6 +513,7 @@ pub async fn create_permission<T>(
       resource_target: target.clone(),
       level,
       specific,
+      expires_at: 0,
     })
     .await
   {
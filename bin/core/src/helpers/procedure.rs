@@ -1,17 +1,32 @@
-use std::time::{Duration, Instant};
+use std::{
+  collections::HashMap,
+  time::{Duration, Instant},
+};
 
 use anyhow::{Context, anyhow};
-use database::mungos::by_id::find_one_by_id;
+use database::mungos::{
+  by_id::find_one_by_id,
+  mongodb::bson::{doc, oid::ObjectId},
+};
+use derive_variants::ExtractVariant;
 use formatting::{Color, bold, colored, format_serror, muted};
 use futures::future::join_all;
 use komodo_client::{
   api::execute::*,
   entities::{
+    ResourceTarget,
     action::Action,
+    alert::{Alert, AlertData, SeverityLevel},
     build::Build,
     deployment::Deployment,
+    execution_approval::{
+      ExecutionApproval, ExecutionApprovalStatus,
+    },
+    komodo_timestamp,
     permission::PermissionLevel,
-    procedure::Procedure,
+    procedure::{
+      Procedure, RequireApproval, StageCondition, StageFailureHandler,
+    },
     repo::Repo,
     stack::Stack,
     update::{Log, Update},
@@ -22,6 +37,7 @@ use resolver_api::Resolve;
 use tokio::sync::Mutex;
 
 use crate::{
+  alert::send_alerts,
   api::{
     execute::{ExecuteArgs, ExecuteRequest},
     write::WriteArgs,
@@ -30,17 +46,59 @@ use crate::{
   state::db_client,
 };
 
-use super::update::{init_execution_update, update_update};
+use super::{
+  cancel::{execution_cancel_token, stop_on_cancel},
+  query::get_variable,
+  runtime_inputs::RuntimeInputs,
+  stage_outputs::{StageOutputs, execution_outputs},
+  update::{init_execution_update, update_update},
+};
+
+/// Pending approvals are stored in the database, so they can be
+/// approved through any Core instance. Checked at this interval.
+const APPROVAL_POLL_INTERVAL: Duration = Duration::from_secs(3);
 
 #[instrument(skip_all)]
 pub async fn execute_procedure(
   procedure: &Procedure,
+  inputs: &RuntimeInputs,
   update: &Mutex<Update>,
 ) -> anyhow::Result<()> {
+  // Whether each stage which has run succeeded, by stage name.
+  let mut results = HashMap::<&str, bool>::new();
+  // The first failure of a stage which continued on failure.
+  let mut continued_failure = None;
+  let mut outputs = StageOutputs::default();
+  let cancel = execution_cancel_token();
+
   for stage in &procedure.config.stages {
     if !stage.enabled {
       continue;
     }
+    if cancel.is_cancelled() {
+      return Err(anyhow!("Procedure was cancelled"));
+    }
+    let run = stage_condition_met(&stage.condition, &results, inputs)
+      .await
+      .with_context(|| {
+        format!(
+          "Failed to evaluate condition of stage '{}'",
+          bold(&stage.name)
+        )
+      })?;
+    if !run {
+      add_line_to_update(
+        update,
+        &format!(
+          "{}: Skipping stage '{}', condition not met: {:?}",
+          muted("INFO"),
+          bold(&stage.name),
+          stage.condition
+        ),
+      )
+      .await;
+      continue;
+    }
     add_line_to_update(
       update,
       &format!(
@@ -50,8 +108,24 @@ pub async fn execute_procedure(
       ),
     )
     .await;
+    // A rejected or expired approval always stops the procedure,
+    // so on_failure can't be used to skip past the gate.
+    if let Some(approval) = &stage.require_approval {
+      wait_for_approval(
+        procedure,
+        &stage.name,
+        approval,
+        inputs,
+        &outputs,
+        update,
+      )
+      .await
+      .with_context(|| {
+        format!("Stage '{}' was not approved", bold(&stage.name))
+      })?;
+    }
     let timer = Instant::now();
-    execute_stage(
+    let res = execute_stage(
       stage
         .executions
         .iter()
@@ -60,6 +134,8 @@ pub async fn execute_procedure(
         .collect(),
       &procedure.id,
       &procedure.name,
+      inputs,
+      &outputs,
       update,
     )
     .await
@@ -69,7 +145,45 @@ pub async fn execute_procedure(
         bold(&stage.name),
         timer.elapsed(),
       )
-    })?;
+    });
+    // Failure handlers don't run for a cancelled stage.
+    if cancel.is_cancelled() {
+      let e =
+        res.err().unwrap_or_else(|| anyhow!("Stage was cancelled"));
+      return Err(e.context("Procedure was cancelled"));
+    }
+    results.insert(&stage.name, res.is_ok());
+    let stage_outputs = match res {
+      Ok(stage_outputs) => stage_outputs,
+      Err(e) => {
+        if !handle_stage_failure(
+          &stage.on_failure,
+          &procedure.id,
+          &procedure.name,
+          inputs,
+          &outputs,
+          update,
+        )
+        .await
+        {
+          return Err(e);
+        }
+        add_line_to_update(
+          update,
+          &format!(
+            "{}: Stage '{}' failed, continuing to next stage\n{}",
+            colored("ERROR", Color::Red),
+            bold(&stage.name),
+            format_serror(&e.into())
+          ),
+        )
+        .await;
+        if continued_failure.is_none() {
+          continued_failure = Some(stage.name.clone());
+        }
+        continue;
+      }
+    };
     add_line_to_update(
       update,
       &format!(
@@ -81,19 +195,330 @@ pub async fn execute_procedure(
       ),
     )
     .await;
+    if !stage_outputs.is_empty() {
+      let mut lines = stage_outputs
+        .iter()
+        .map(|(name, value)| {
+          format!(
+            "{} => {value}",
+            muted(format!("stages.{}.{name}", stage.name))
+          )
+        })
+        .collect::<Vec<_>>();
+      lines.sort();
+      add_line_to_update(
+        update,
+        &format!(
+          "{}: Stage '{}' outputs:\n{}",
+          muted("INFO"),
+          bold(&stage.name),
+          lines.join("\n")
+        ),
+      )
+      .await;
+    }
+    outputs.insert(&stage.name, stage_outputs);
   }
 
-  Ok(())
+  match continued_failure {
+    Some(stage) => Err(anyhow!(
+      "Stage '{}' failed, the procedure continued after the failure",
+      bold(&stage)
+    )),
+    None => Ok(()),
+  }
 }
 
+/// Checks whether the stage should run,
+/// given the results of the stages run before it.
+/// Pauses the procedure until a user approves or rejects
+/// the stage with ApproveExecution / RejectExecution.
+/// Rejection or expiry fail the procedure, regardless of on_failure.
+async fn wait_for_approval(
+  procedure: &Procedure,
+  stage: &str,
+  approval: &RequireApproval,
+  inputs: &RuntimeInputs,
+  outputs: &StageOutputs,
+  update: &Mutex<Update>,
+) -> anyhow::Result<()> {
+  // Secret inputs are not interpolated, as the message
+  // is sent in alerts and shown to approvers.
+  let mut values = inputs.variables.clone();
+  values.extend(
+    outputs
+      .values()
+      .iter()
+      .map(|(name, value)| (name.clone(), value.clone())),
+  );
+  let (message, _) = svi::interpolate_variables(
+    &approval.message,
+    &values,
+    svi::Interpolator::DoubleBrackets,
+    false,
+  )
+  .context("Failed to interpolate approval message")?;
+
+  let (update_id, requested_by) = {
+    let update = update.lock().await;
+    (update.id.clone(), update.operator.clone())
+  };
+  let ts = komodo_timestamp();
+  let mut pending = ExecutionApproval {
+    id: Default::default(),
+    procedure_id: procedure.id.clone(),
+    procedure_name: procedure.name.clone(),
+    stage: stage.to_string(),
+    update_id,
+    message: message.clone(),
+    status: ExecutionApprovalStatus::Pending,
+    requested_by,
+    requested_at: ts,
+    expires_at: if approval.timeout_minutes > 0 {
+      ts + approval.timeout_minutes * 60_000
+    } else {
+      0
+    },
+    forbid_self_approval: approval.forbid_self_approval,
+    approvers: approval.approvers.clone(),
+    resolved_by: Default::default(),
+    resolved_at: 0,
+    comment: Default::default(),
+  };
+  pending.id = db_client()
+    .execution_approvals
+    .insert_one(&pending)
+    .await
+    .context("Failed to add execution approval to db")?
+    .inserted_id
+    .as_object_id()
+    .context("Inserted id is not ObjectId")?
+    .to_string();
+
+  add_line_to_update(
+    update,
+    &format!(
+      "{}: Stage '{}' is {} (approval {})",
+      muted("INFO"),
+      bold(stage),
+      colored("waiting for approval", Color::Blue),
+      pending.id
+    ),
+  )
+  .await;
+
+  let alert = Alert {
+    id: Default::default(),
+    target: ResourceTarget::Procedure(procedure.id.clone()),
+    ts,
+    resolved_ts: Some(ts),
+    resolved: true,
+    level: SeverityLevel::Warning,
+    data: AlertData::ProcedureApprovalRequired {
+      id: procedure.id.clone(),
+      name: procedure.name.clone(),
+      stage: stage.to_string(),
+      approval_id: pending.id.clone(),
+      message,
+    },
+    notifications: Default::default(),
+  };
+  tokio::spawn(async move { send_alerts(&[alert]).await });
+
+  let cancel = execution_cancel_token();
+  loop {
+    tokio::select! {
+      _ = tokio::time::sleep(APPROVAL_POLL_INTERVAL) => {}
+      _ = cancel.cancelled() => {
+        let id = ObjectId::parse_str(&pending.id)
+          .context("Execution approval id is not valid ObjectId")?;
+        db_client()
+          .execution_approvals
+          .update_one(
+            doc! { "_id": id, "status": "Pending" },
+            doc! { "$set": {
+              "status": "Cancelled",
+              "resolved_at": komodo_timestamp(),
+            } },
+          )
+          .await
+          .context("Failed to cancel execution approval")?;
+        return Err(anyhow!("Procedure was cancelled"));
+      }
+    }
+    let approval =
+      find_one_by_id(&db_client().execution_approvals, &pending.id)
+        .await
+        .context("Failed to query db for execution approval")?
+        .context("Execution approval no longer exists")?;
+    match approval.status {
+      ExecutionApprovalStatus::Approved => {
+        add_line_to_update(
+          update,
+          &format!(
+            "{}: Stage '{}' {} by {}{}",
+            muted("INFO"),
+            bold(stage),
+            colored("approved", Color::Green),
+            approval_username(&approval.resolved_by).await,
+            approval_comment(&approval.comment)
+          ),
+        )
+        .await;
+        return Ok(());
+      }
+      ExecutionApprovalStatus::Rejected => {
+        return Err(anyhow!(
+          "Stage was rejected by {}{}",
+          approval_username(&approval.resolved_by).await,
+          approval_comment(&approval.comment)
+        ));
+      }
+      ExecutionApprovalStatus::Expired
+      | ExecutionApprovalStatus::Cancelled => {
+        return Err(anyhow!("Stage approval is {}", approval.status));
+      }
+      ExecutionApprovalStatus::Pending => {}
+    }
+    if approval.expires_at > 0
+      && komodo_timestamp() >= approval.expires_at
+    {
+      let id = ObjectId::parse_str(&approval.id)
+        .context("Execution approval id is not valid ObjectId")?;
+      // Only matches if it hasn't been resolved in the meantime,
+      // otherwise the next poll picks up the resolution.
+      let res = db_client()
+        .execution_approvals
+        .update_one(
+          doc! { "_id": id, "status": "Pending" },
+          doc! { "$set": {
+            "status": "Expired",
+            "resolved_at": komodo_timestamp(),
+          } },
+        )
+        .await
+        .context("Failed to expire execution approval")?;
+      if res.matched_count > 0 {
+        return Err(anyhow!(
+          "Stage was not approved within {} minutes",
+          (approval.expires_at - approval.requested_at) / 60_000
+        ));
+      }
+    }
+  }
+}
+
+async fn approval_username(user_id: &str) -> String {
+  find_one_by_id(&db_client().users, user_id)
+    .await
+    .ok()
+    .flatten()
+    .map(|user| user.username)
+    .unwrap_or_else(|| user_id.to_string())
+}
+
+fn approval_comment(comment: &str) -> String {
+  if comment.is_empty() {
+    String::new()
+  } else {
+    format!(" | {comment}")
+  }
+}
+
+async fn stage_condition_met(
+  condition: &StageCondition,
+  results: &HashMap<&str, bool>,
+  inputs: &RuntimeInputs,
+) -> anyhow::Result<bool> {
+  let met = match condition {
+    StageCondition::Always => true,
+    StageCondition::PreviousSucceeded => {
+      results.values().all(|success| *success)
+    }
+    StageCondition::PreviousFailed => {
+      results.values().any(|success| !success)
+    }
+    StageCondition::StageSucceeded { stage } => {
+      results.get(stage.as_str()) == Some(&true)
+    }
+    StageCondition::StageFailed { stage } => {
+      results.get(stage.as_str()) == Some(&false)
+    }
+    StageCondition::VariableEquals { variable, value } => {
+      match inputs
+        .variables
+        .get(variable)
+        .or_else(|| inputs.secrets.get(variable))
+      {
+        Some(input) => input == value,
+        None => get_variable(variable).await?.value == *value,
+      }
+    }
+  };
+  Ok(met)
+}
+
+/// Runs the failure handler of a failed stage.
+/// Returns whether the procedure should continue.
+async fn handle_stage_failure(
+  handler: &StageFailureHandler,
+  parent_id: &str,
+  parent_name: &str,
+  inputs: &RuntimeInputs,
+  outputs: &StageOutputs,
+  update: &Mutex<Update>,
+) -> bool {
+  let (execution, continue_after) = match handler {
+    StageFailureHandler::Abort => return false,
+    StageFailureHandler::Continue => return true,
+    StageFailureHandler::RunExecution {
+      execution,
+      continue_after,
+    } => (execution, *continue_after),
+  };
+  add_line_to_update(
+    update,
+    &format!(
+      "{}: Running stage failure handler: {execution:?}",
+      muted("INFO"),
+    ),
+  )
+  .await;
+  let res = execute_stage(
+    vec![execution.clone()],
+    parent_id,
+    parent_name,
+    inputs,
+    outputs,
+    update,
+  )
+  .await;
+  if let Err(e) = res {
+    add_line_to_update(
+      update,
+      &format!(
+        "{}: Stage failure handler failed\n{}",
+        colored("ERROR", Color::Red),
+        format_serror(&e.into())
+      ),
+    )
+    .await;
+  }
+  continue_after
+}
+
+/// Runs the stage executions in parallel,
+/// returning the outputs they publish.
 #[allow(dependency_on_unit_never_type_fallback)]
-#[instrument(skip(update))]
+#[instrument(skip(inputs, outputs, update))]
 async fn execute_stage(
   _executions: Vec<Execution>,
   parent_id: &str,
   parent_name: &str,
+  inputs: &RuntimeInputs,
+  outputs: &StageOutputs,
   update: &Mutex<Update>,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<HashMap<String, String>> {
   let mut executions = Vec::with_capacity(_executions.capacity());
   for execution in _executions {
     match execution {
@@ -195,10 +620,20 @@ async fn execute_stage(
       "{}: Failed on {execution:?}",
       colored("ERROR", Color::Red)
     );
-    let res =
-      execute_execution(execution.clone(), parent_id, parent_name)
-        .await
-        .context(fail_log);
+    // Interpolate runtime inputs only after logging the execution,
+    // so secret input values are not written to the update.
+    let res = async {
+      let execution = inputs.interpolate_execution(&execution)?;
+      let execution = outputs.interpolate_execution(&execution)?;
+      let update = stop_on_cancel(
+        &execution.extract_variant().to_string(),
+        execute_execution(execution.clone(), parent_id, parent_name),
+      )
+      .await?;
+      anyhow::Ok(execution_outputs(&execution, &update).await)
+    }
+    .await
+    .context(fail_log);
     add_line_to_update(
       update,
       &format!(
@@ -211,11 +646,15 @@ async fn execute_stage(
     .await;
     res
   });
-  join_all(futures)
+  // When executions publish the same output, the last one is kept.
+  let outputs = join_all(futures)
     .await
     .into_iter()
-    .collect::<anyhow::Result<Vec<_>>>()?;
-  Ok(())
+    .collect::<anyhow::Result<Vec<_>>>()?
+    .into_iter()
+    .flatten()
+    .collect();
+  Ok(outputs)
 }
 
 async fn execute_execution(
@@ -223,10 +662,10 @@ async fn execute_execution(
   // used to prevent recursive procedure
   parent_id: &str,
   parent_name: &str,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<Update> {
   let user = procedure_user().to_owned();
   let update = match execution {
-    Execution::None(_) => return Ok(()),
+    Execution::None(_) => return Ok(Default::default()),
     Execution::RunProcedure(req) => {
       if req.procedure == parent_id || req.procedure == parent_name {
         return Err(anyhow!("Self referential procedure detected"));
@@ -464,6 +903,57 @@ async fn execute_execution(
         "Batch method BatchDestroyDeployment not implemented correctly"
       ));
     }
+    Execution::DeploySwarmService(req) => {
+      let req = ExecuteRequest::DeploySwarmService(req);
+      let update = init_execution_update(&req, &user).await?;
+      let ExecuteRequest::DeploySwarmService(req) = req else {
+        unreachable!()
+      };
+      let update_id = update.id.clone();
+      handle_resolve_result(
+        req
+          .resolve(&ExecuteArgs { user, update })
+          .await
+          .map_err(|e| e.error)
+          .context("Failed at DeploySwarmService"),
+        &update_id,
+      )
+      .await?
+    }
+    Execution::ScaleSwarmService(req) => {
+      let req = ExecuteRequest::ScaleSwarmService(req);
+      let update = init_execution_update(&req, &user).await?;
+      let ExecuteRequest::ScaleSwarmService(req) = req else {
+        unreachable!()
+      };
+      let update_id = update.id.clone();
+      handle_resolve_result(
+        req
+          .resolve(&ExecuteArgs { user, update })
+          .await
+          .map_err(|e| e.error)
+          .context("Failed at ScaleSwarmService"),
+        &update_id,
+      )
+      .await?
+    }
+    Execution::RemoveSwarmService(req) => {
+      let req = ExecuteRequest::RemoveSwarmService(req);
+      let update = init_execution_update(&req, &user).await?;
+      let ExecuteRequest::RemoveSwarmService(req) = req else {
+        unreachable!()
+      };
+      let update_id = update.id.clone();
+      handle_resolve_result(
+        req
+          .resolve(&ExecuteArgs { user, update })
+          .await
+          .map_err(|e| e.error)
+          .context("Failed at RemoveSwarmService"),
+        &update_id,
+      )
+      .await?
+    }
     Execution::CloneRepo(req) => {
       let req = ExecuteRequest::CloneRepo(req);
       let update = init_execution_update(&req, &user).await?;
@@ -510,6 +1000,23 @@ async fn execute_execution(
         "Batch method BatchPullRepo not implemented correctly"
       ));
     }
+    Execution::RunRepoPipeline(req) => {
+      let req = ExecuteRequest::RunRepoPipeline(req);
+      let update = init_execution_update(&req, &user).await?;
+      let ExecuteRequest::RunRepoPipeline(req) = req else {
+        unreachable!()
+      };
+      let update_id = update.id.clone();
+      handle_resolve_result(
+        req
+          .resolve(&ExecuteArgs { user, update })
+          .await
+          .map_err(|e| e.error)
+          .context("Failed at RunRepoPipeline"),
+        &update_id,
+      )
+      .await?
+    }
     Execution::BuildRepo(req) => {
       let req = ExecuteRequest::BuildRepo(req);
       let update = init_execution_update(&req, &user).await?;
@@ -907,6 +1414,23 @@ async fn execute_execution(
       )
       .await?
     }
+    Execution::RunScheduledCommand(req) => {
+      let req = ExecuteRequest::RunScheduledCommand(req);
+      let update = init_execution_update(&req, &user).await?;
+      let ExecuteRequest::RunScheduledCommand(req) = req else {
+        unreachable!()
+      };
+      let update_id = update.id.clone();
+      handle_resolve_result(
+        req
+          .resolve(&ExecuteArgs { user, update })
+          .await
+          .map_err(|e| e.error)
+          .context("Failed at RunScheduledCommand"),
+        &update_id,
+      )
+      .await?
+    }
     Execution::RunSync(req) => {
       let req = ExecuteRequest::RunSync(req);
       let update = init_execution_update(&req, &user).await?;
@@ -1118,6 +1642,23 @@ async fn execute_execution(
       )
       .await?
     }
+    Execution::ScaleStackService(req) => {
+      let req = ExecuteRequest::ScaleStackService(req);
+      let update = init_execution_update(&req, &user).await?;
+      let ExecuteRequest::ScaleStackService(req) = req else {
+        unreachable!()
+      };
+      let update_id = update.id.clone();
+      handle_resolve_result(
+        req
+          .resolve(&ExecuteArgs { user, update })
+          .await
+          .map_err(|e| e.error)
+          .context("Failed at ScaleStackService"),
+        &update_id,
+      )
+      .await?
+    }
     Execution::BatchDestroyStack(_) => {
       // All batch executions must be expanded in `execute_stage`
       return Err(anyhow!(
@@ -1219,7 +1760,7 @@ async fn execute_execution(
     }
   };
   if update.success {
-    Ok(())
+    Ok(update)
   } else {
     Err(anyhow!(
       "{}: execution not successful. see update '{}'",
@@ -1293,7 +1834,10 @@ trait ExtendBatch {
 impl ExtendBatch for BatchRunProcedure {
   type Resource = Procedure;
   fn single_execution(procedure: String) -> Execution {
-    Execution::RunProcedure(RunProcedure { procedure })
+    Execution::RunProcedure(RunProcedure {
+      procedure,
+      inputs: None,
+    })
   }
 }
 
@@ -1342,6 +1886,7 @@ impl ExtendBatch for BatchDeploy {
       deployment,
       stop_signal: None,
       stop_time: None,
+      inputs: None,
     })
   }
 }
@@ -0,0 +1,445 @@
+use std::{sync::OnceLock, time::Duration};
+
+use anyhow::{Context, anyhow};
+use async_timing_util::{Timelength, wait_until_timelength};
+use database::mungos::{
+  by_id::update_one_by_id,
+  find::find_collect,
+  mongodb::bson::{doc, to_bson},
+};
+use komodo_client::entities::{
+  ResourceTarget,
+  alert::{Alert, AlertData, SeverityLevel},
+  komodo_timestamp,
+  provider::{
+    DockerRegistryAccount, GitProviderAccount, ProviderAccountHealth,
+    ProviderAccountType,
+  },
+};
+use reqwest::{StatusCode, header};
+use serde::Deserialize;
+
+use crate::{
+  alert::send_alerts, helpers::registry_auth::registry_account_token,
+  state::db_client,
+};
+
+static APP_USER_AGENT: &str =
+  concat!("Komodo/", env!("CARGO_PKG_VERSION"),);
+
+const ONE_DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// Alerts are sent as the token expiry crosses each of these
+/// thresholds (in days).
+const EXPIRY_WARNING_DAYS: [i64; 4] = [14, 7, 3, 1];
+
+/// At or below this many days until expiry, the alert is critical.
+const EXPIRY_CRITICAL_DAYS: i64 = 3;
+
+fn http_client() -> &'static reqwest::Client {
+  static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+  CLIENT.get_or_init(|| {
+    reqwest::Client::builder()
+      .user_agent(APP_USER_AGENT)
+      .timeout(Duration::from_secs(15))
+      .build()
+      .expect("Invalid provider health reqwest client")
+  })
+}
+
+pub fn spawn_provider_health_loop() {
+  tokio::spawn(async move {
+    loop {
+      wait_until_timelength(Timelength::OneHour, 0).await;
+      if let Err(e) = check_provider_accounts().await {
+        error!("Failed to check provider accounts | {e:#}");
+      }
+    }
+  });
+}
+
+/// Validates the git provider and docker registry account
+/// tokens stored on the db, records the results on the accounts,
+/// and alerts on failures / upcoming token expiry.
+async fn check_provider_accounts() -> anyhow::Result<()> {
+  let db = db_client();
+  let (git_accounts, registry_accounts) = tokio::try_join!(
+    find_collect(&db.git_accounts, None, None),
+    find_collect(&db.registry_accounts, None, None),
+  )
+  .context("Failed to get provider accounts from db")?;
+
+  let mut alerts = Vec::new();
+
+  for account in git_accounts {
+    let health =
+      to_health(&account.health, check_git_account(&account).await);
+    alerts.extend(health_alerts(
+      ProviderAccountType::Git,
+      &account.id,
+      &account.domain,
+      &account.username,
+      &account.health,
+      &health,
+    ));
+    if let Err(e) = update_one_by_id(
+      &db.git_accounts,
+      &account.id,
+      doc! { "$set": { "health": to_bson(&health)? } },
+      None,
+    )
+    .await
+    {
+      warn!(
+        "Failed to update git provider account health for {} | {} | {e:#}",
+        account.domain, account.username
+      );
+    }
+  }
+
+  for account in registry_accounts {
+    let health = to_health(
+      &account.health,
+      check_registry_account(&account).await,
+    );
+    alerts.extend(health_alerts(
+      ProviderAccountType::Registry,
+      &account.id,
+      &account.domain,
+      &account.username,
+      &account.health,
+      &health,
+    ));
+    if let Err(e) = update_one_by_id(
+      &db.registry_accounts,
+      &account.id,
+      doc! { "$set": { "health": to_bson(&health)? } },
+      None,
+    )
+    .await
+    {
+      warn!(
+        "Failed to update docker registry account health for {} | {} | {e:#}",
+        account.domain, account.username
+      );
+    }
+  }
+
+  send_alerts(&alerts).await;
+
+  Ok(())
+}
+
+fn to_health(
+  prev: &ProviderAccountHealth,
+  res: anyhow::Result<Option<i64>>,
+) -> ProviderAccountHealth {
+  let now = komodo_timestamp();
+  match res {
+    Ok(expires_at) => ProviderAccountHealth {
+      last_check_ts: now,
+      last_success_ts: now,
+      error: String::new(),
+      expires_at,
+    },
+    Err(e) => ProviderAccountHealth {
+      last_check_ts: now,
+      last_success_ts: prev.last_success_ts,
+      error: format!("{e:#}"),
+      // Keep the last known expiry
+      expires_at: prev.expires_at,
+    },
+  }
+}
+
+fn health_alerts(
+  provider_type: ProviderAccountType,
+  id: &str,
+  domain: &str,
+  username: &str,
+  prev: &ProviderAccountHealth,
+  curr: &ProviderAccountHealth,
+) -> Option<Alert> {
+  let (level, data) = if !curr.error.is_empty() {
+    // Only alert when the account starts failing
+    if !prev.error.is_empty() {
+      return None;
+    }
+    (
+      SeverityLevel::Critical,
+      AlertData::ProviderAccountCheckFailed {
+        provider_type,
+        id: id.to_string(),
+        domain: domain.to_string(),
+        username: username.to_string(),
+        message: curr.error.clone(),
+      },
+    )
+  } else {
+    let expires_at = curr.expires_at?;
+    let days =
+      (expires_at - curr.last_check_ts).div_euclid(ONE_DAY_MS);
+    let prev_stage = match prev.expires_at {
+      Some(prev_expires_at) if prev.last_check_ts > 0 => {
+        expiry_stage(
+          (prev_expires_at - prev.last_check_ts)
+            .div_euclid(ONE_DAY_MS),
+        )
+      }
+      _ => 0,
+    };
+    // Only alert when crossing into the next threshold
+    if expiry_stage(days) <= prev_stage {
+      return None;
+    }
+    let level = if days <= EXPIRY_CRITICAL_DAYS {
+      SeverityLevel::Critical
+    } else {
+      SeverityLevel::Warning
+    };
+    (
+      level,
+      AlertData::ProviderTokenExpiring {
+        provider_type,
+        id: id.to_string(),
+        domain: domain.to_string(),
+        username: username.to_string(),
+        expires_at,
+        days,
+      },
+    )
+  };
+  let ts = komodo_timestamp();
+  Some(Alert {
+    id: Default::default(),
+    target: ResourceTarget::system(),
+    ts,
+    resolved_ts: Some(ts),
+    resolved: true,
+    level,
+    data,
+    notifications: Default::default(),
+  })
+}
+
+/// The number of warning thresholds crossed.
+fn expiry_stage(days: i64) -> usize {
+  EXPIRY_WARNING_DAYS
+    .iter()
+    .filter(|threshold| days <= **threshold)
+    .count()
+}
+
+// =======
+//   GIT
+// =======
+
+/// Returns the token expiry, if the provider exposes it.
+async fn check_git_account(
+  account: &GitProviderAccount,
+) -> anyhow::Result<Option<i64>> {
+  if account.domain == "github.com" {
+    return github_token_expiry(
+      "https://api.github.com/user",
+      &account.token,
+    )
+    .await;
+  }
+
+  let protocol = if account.https { "https" } else { "http" };
+  let base = format!("{protocol}://{}", account.domain);
+
+  // Gitlab
+  let res = http_client()
+    .get(format!("{base}/api/v4/personal_access_tokens/self"))
+    .header("PRIVATE-TOKEN", &account.token)
+    .send()
+    .await
+    .context("Failed to reach git provider")?;
+  if res.status() != StatusCode::NOT_FOUND {
+    let res = error_for_status(res).await?;
+    #[derive(Deserialize)]
+    struct GitlabToken {
+      expires_at: Option<String>,
+    }
+    let token = res
+      .json::<GitlabToken>()
+      .await
+      .context("Failed to parse Gitlab token response")?;
+    return Ok(token.expires_at.as_deref().and_then(parse_date));
+  }
+
+  // Gitea / Forgejo
+  let res = http_client()
+    .get(format!("{base}/api/v1/user"))
+    .header(header::AUTHORIZATION, format!("token {}", account.token))
+    .send()
+    .await
+    .context("Failed to reach git provider")?;
+  if res.status() != StatusCode::NOT_FOUND {
+    error_for_status(res).await?;
+    return Ok(None);
+  }
+
+  // Github Enterprise
+  github_token_expiry(&format!("{base}/api/v3/user"), &account.token)
+    .await
+    .with_context(|| {
+      format!("Did not find a supported git provider api at {base}")
+    })
+}
+
+async fn github_token_expiry(
+  url: &str,
+  token: &str,
+) -> anyhow::Result<Option<i64>> {
+  let res = http_client()
+    .get(url)
+    .bearer_auth(token)
+    .header(header::ACCEPT, "application/vnd.github+json")
+    .send()
+    .await
+    .context("Failed to reach Github api")?;
+  let res = error_for_status(res).await?;
+  Ok(
+    res
+      .headers()
+      .get("github-authentication-token-expiration")
+      .and_then(|expiry| expiry.to_str().ok())
+      .and_then(parse_github_expiration),
+  )
+}
+
+/// Github gives the expiration like `2025-01-01 00:00:00 UTC`
+fn parse_github_expiration(expiry: &str) -> Option<i64> {
+  let expiry = expiry.trim();
+  chrono::DateTime::parse_from_str(expiry, "%Y-%m-%d %H:%M:%S %z")
+    .map(|date| date.timestamp_millis())
+    .or_else(|_| {
+      chrono::NaiveDateTime::parse_from_str(
+        expiry.trim_end_matches("UTC").trim(),
+        "%Y-%m-%d %H:%M:%S",
+      )
+      .map(|date| date.and_utc().timestamp_millis())
+    })
+    .ok()
+}
+
+/// Parses `YYYY-MM-DD` as the start of the day in UTC.
+fn parse_date(date: &str) -> Option<i64> {
+  chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+    .ok()?
+    .and_hms_opt(0, 0, 0)
+    .map(|date| date.and_utc().timestamp_millis())
+}
+
+// ============
+//   REGISTRY
+// ============
+
+/// Returns the token expiry, if the provider exposes it.
+async fn check_registry_account(
+  account: &DockerRegistryAccount,
+) -> anyhow::Result<Option<i64>> {
+  let token = registry_account_token(account).await?;
+  let base = match account.domain.as_str() {
+    "docker.io" => String::from("https://registry-1.docker.io"),
+    domain
+      if domain.starts_with("http://")
+        || domain.starts_with("https://") =>
+    {
+      domain.trim_end_matches('/').to_string()
+    }
+    domain => format!("https://{domain}"),
+  };
+
+  let res = http_client()
+    .get(format!("{base}/v2/"))
+    .send()
+    .await
+    .context("Failed to reach registry")?;
+
+  if res.status() == StatusCode::UNAUTHORIZED {
+    let challenge = res
+      .headers()
+      .get(header::WWW_AUTHENTICATE)
+      .and_then(|challenge| challenge.to_str().ok())
+      .unwrap_or_default()
+      .to_string();
+    let res = if let Some(params) = challenge.strip_prefix("Bearer ")
+    {
+      let params = parse_challenge_params(params);
+      let realm = params
+        .iter()
+        .find(|(key, _)| key == "realm")
+        .map(|(_, value)| value.as_str())
+        .context("Registry auth challenge is missing realm")?;
+      let mut query = vec![("account", account.username.as_str())];
+      if let Some((_, service)) =
+        params.iter().find(|(key, _)| key == "service")
+      {
+        query.push(("service", service));
+      }
+      http_client()
+        .get(realm)
+        .query(&query)
+        .basic_auth(&account.username, Some(&token))
+        .send()
+        .await
+        .context("Failed to reach registry auth")?
+    } else {
+      http_client()
+        .get(format!("{base}/v2/"))
+        .basic_auth(&account.username, Some(&token))
+        .send()
+        .await
+        .context("Failed to reach registry")?
+    };
+    error_for_status(res).await?;
+  } else {
+    error_for_status(res).await?;
+  }
+
+  // Github container registry uses Github tokens,
+  // which expose the expiry on the Github api.
+  if account.domain == "ghcr.io" {
+    return Ok(
+      github_token_expiry("https://api.github.com/user", &token)
+        .await
+        .ok()
+        .flatten(),
+    );
+  }
+
+  Ok(None)
+}
+
+/// Parses `key="value",key2="value2"` from a `WWW-Authenticate` header.
+fn parse_challenge_params(params: &str) -> Vec<(String, String)> {
+  params
+    .split(',')
+    .filter_map(|param| {
+      let (key, value) = param.split_once('=')?;
+      Some((
+        key.trim().to_string(),
+        value.trim().trim_matches('"').to_string(),
+      ))
+    })
+    .collect()
+}
+
+async fn error_for_status(
+  res: reqwest::Response,
+) -> anyhow::Result<reqwest::Response> {
+  let status = res.status();
+  if status.is_success() {
+    return Ok(res);
+  }
+  let body = res.text().await.unwrap_or_default();
+  let body = body.trim();
+  if body.is_empty() {
+    Err(anyhow!("Request failed with {status}"))
+  } else {
+    Err(anyhow!("Request failed with {status} | {body}"))
+  }
+}
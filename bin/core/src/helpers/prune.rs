@@ -8,14 +8,28 @@ use periphery_client::api::image::PruneImages;
 
 use crate::{config::core_config, state::db_client};
 
-use super::periphery_client;
+use super::{
+  artifact::delete_artifacts, periphery_client,
+  query::inherit_server_profiles,
+};
 
 pub fn spawn_prune_loop() {
   tokio::spawn(async move {
     loop {
       wait_until_timelength(Timelength::OneDay, 5000).await;
-      let (images_res, stats_res, alerts_res) =
-        tokio::join!(prune_images(), prune_stats(), prune_alerts());
+      let (
+        images_res,
+        stats_res,
+        alerts_res,
+        artifacts_res,
+        audit_log_res,
+      ) = tokio::join!(
+        prune_images(),
+        prune_stats(),
+        prune_alerts(),
+        prune_artifacts(),
+        prune_audit_log()
+      );
       if let Err(e) = images_res {
         error!("error in pruning images | {e:#}");
       }
@@ -25,29 +39,44 @@ pub fn spawn_prune_loop() {
       if let Err(e) = alerts_res {
         error!("error in pruning alerts | {e:#}");
       }
+      if let Err(e) = artifacts_res {
+        error!("error in pruning artifacts | {e:#}");
+      }
+      if let Err(e) = audit_log_res {
+        error!("error in pruning audit log | {e:#}");
+      }
     }
   });
 }
 
 async fn prune_images() -> anyhow::Result<()> {
-  let mut futures = find_collect(
+  let mut servers = find_collect(
     &db_client().servers,
-    doc! { "config.enabled": true, "config.auto_prune": true },
+    doc! { "config.enabled": true },
     None,
   )
   .await
-  .context("failed to get servers from db")?
-  .into_iter()
-  .map(|server| async move {
-    (
-      async {
-        periphery_client(&server)?.request(PruneImages {}).await
-      }
-      .await,
-      server,
-    )
-  })
-  .collect::<FuturesUnordered<_>>();
+  .context("failed to get servers from db")?;
+
+  // auto_prune may be inherited from the Server Profile
+  inherit_server_profiles(&mut servers).await?;
+
+  let mut futures = servers
+    .into_iter()
+    .filter(|server| server.config.auto_prune)
+    .map(|server| async move {
+      (
+        async {
+          periphery_client(&server)
+            .await?
+            .request(PruneImages {})
+            .await
+        }
+        .await,
+        server,
+      )
+    })
+    .collect::<FuturesUnordered<_>>();
 
   while let Some((res, server)) = futures.next().await {
     if let Err(e) = res {
@@ -98,3 +127,58 @@ async fn prune_alerts() -> anyhow::Result<()> {
   }
   Ok(())
 }
+
+async fn prune_audit_log() -> anyhow::Result<()> {
+  if core_config().keep_audit_log_for_days == 0 {
+    return Ok(());
+  }
+  let delete_before_ts = (unix_timestamp_ms()
+    - core_config().keep_audit_log_for_days as u128 * ONE_DAY_MS)
+    as i64;
+  let res = db_client()
+    .audit_log
+    .delete_many(doc! {
+      "ts": { "$lt": delete_before_ts }
+    })
+    .await?;
+  if res.deleted_count > 0 {
+    info!("deleted {} audit log entries from db", res.deleted_count);
+  }
+  Ok(())
+}
+
+async fn prune_artifacts() -> anyhow::Result<()> {
+  if core_config().keep_artifacts_for_days == 0 {
+    return Ok(());
+  }
+  let delete_before_ts = (unix_timestamp_ms()
+    - core_config().keep_artifacts_for_days as u128 * ONE_DAY_MS)
+    as i64;
+  let filter = doc! {
+    "start_ts": { "$lt": delete_before_ts },
+    "artifacts.0": { "$exists": true },
+  };
+  let updates = db_client().updates;
+  let ids = updates
+    .distinct("_id", filter.clone())
+    .await
+    .context("failed to get updates with artifacts from db")?;
+  let mut deleted = 0;
+  for id in ids.iter().filter_map(|id| id.as_object_id()) {
+    let id = id.to_hex();
+    match delete_artifacts(&id).await {
+      Ok(_) => deleted += 1,
+      Err(e) => {
+        warn!("failed to delete artifacts for update {id} | {e:#}")
+      }
+    }
+  }
+  updates
+    .update_many(filter, doc! { "$unset": { "artifacts": "" } })
+    .await
+    .context("failed to remove artifacts from updates on db")?;
+  if deleted > 0 {
+    info!("deleted artifacts of {deleted} updates");
+  }
+  Ok(())
+}
@@ -1,5 +1,5 @@
 use std::{
-  collections::HashMap,
+  collections::{HashMap, HashSet},
   str::FromStr,
   sync::{Arc, OnceLock},
 };
@@ -23,14 +23,19 @@ use komodo_client::{
     builder::Builder,
     deployment::{Deployment, DeploymentState},
     docker::container::{
-      ContainerListItem, ContainerStateStatusEnum,
+      ContainerListItem, ContainerStateStatusEnum, HealthStatusEnum,
     },
+    execution_schedule::ExecutionSchedule,
+    external_status::ExternalStatus,
+    federation::FederationPeer,
     permission::{PermissionLevel, PermissionLevelAndSpecifics},
     procedure::{Procedure, ProcedureState},
     repo::Repo,
     server::{Server, ServerState},
+    server_profile::ServerProfile,
     stack::{Stack, StackServiceNames, StackState},
     stats::SystemInformation,
+    swarm::{SwarmService, SwarmServiceState},
     sync::ResourceSync,
     tag::Tag,
     update::Update,
@@ -50,11 +55,13 @@ use crate::{
   state::{
     action_state_cache, action_states, db_client,
     deployment_status_cache, procedure_state_cache,
-    stack_status_cache,
+    stack_status_cache, swarm_service_status_cache,
   },
 };
 
-use super::periphery_client;
+use super::{
+  periphery_client, secret_providers::get_provider_secrets,
+};
 
 // user: Id or username
 #[instrument(level = "debug")]
@@ -84,12 +91,10 @@ pub async fn get_server_state(server: &Server) -> ServerState {
   if !server.config.enabled {
     return ServerState::Disabled;
   }
-  // Unwrap ok: Server disabled check above
-  match super::periphery_client(server)
-    .unwrap()
-    .request(periphery_client::api::GetHealth {})
-    .await
-  {
+  let Ok(periphery) = super::periphery_client(server).await else {
+    return ServerState::NotOk;
+  };
+  match periphery.request(periphery_client::api::GetHealth {}).await {
     Ok(_) => ServerState::Ok,
     Err(_) => ServerState::NotOk,
   }
@@ -120,6 +125,30 @@ pub async fn get_deployment_state(
   Ok(state)
 }
 
+pub async fn get_swarm_service_state(
+  id: &String,
+) -> anyhow::Result<SwarmServiceState> {
+  if action_states()
+    .swarm_service
+    .get(id)
+    .await
+    .map(|s| s.get().map(|s| s.deploying))
+    .transpose()
+    .ok()
+    .flatten()
+    .unwrap_or_default()
+  {
+    return Ok(SwarmServiceState::Deploying);
+  }
+  let state = swarm_service_status_cache()
+    .get(id)
+    .await
+    .unwrap_or_default()
+    .curr
+    .state;
+  Ok(state)
+}
+
 /// Can pass all the containers from the same server
 pub fn get_stack_state_from_containers(
   ignore_services: &[String],
@@ -156,7 +185,14 @@ pub fn get_stack_state_from_containers(
     container.state == ContainerStateStatusEnum::Running
   });
   if running {
-    return StackState::Running;
+    let unhealthy = containers.iter().any(|container| {
+      container.health == Some(HealthStatusEnum::Unhealthy)
+    });
+    return if unhealthy {
+      StackState::Unhealthy
+    } else {
+      StackState::Running
+    };
   }
   let paused = containers.iter().all(|container| {
     container.state == ContainerStateStatusEnum::Paused
@@ -241,6 +277,121 @@ pub async fn get_all_tags(
     .context("failed to query db for tags")
 }
 
+#[instrument(level = "debug")]
+pub async fn get_server_profile(
+  id_or_name: &str,
+) -> anyhow::Result<ServerProfile> {
+  let query = match ObjectId::from_str(id_or_name) {
+    Ok(id) => doc! { "_id": id },
+    Err(_) => doc! { "name": id_or_name },
+  };
+  db_client()
+    .server_profiles
+    .find_one(query)
+    .await
+    .context("failed to query mongo for server profile")?
+    .with_context(|| {
+      format!("no server profile found matching {id_or_name}")
+    })
+}
+
+pub async fn get_execution_schedule(
+  id_or_name: &str,
+) -> anyhow::Result<ExecutionSchedule> {
+  let query = match ObjectId::from_str(id_or_name) {
+    Ok(id) => doc! { "_id": id },
+    Err(_) => doc! { "name": id_or_name },
+  };
+  db_client()
+    .execution_schedules
+    .find_one(query)
+    .await
+    .context("failed to query mongo for execution schedule")?
+    .with_context(|| {
+      format!("no execution schedule found matching {id_or_name}")
+    })
+}
+
+pub async fn get_federation_peer(
+  id_or_name: &str,
+) -> anyhow::Result<FederationPeer> {
+  let query = match ObjectId::from_str(id_or_name) {
+    Ok(id) => doc! { "_id": id },
+    Err(_) => doc! { "name": id_or_name },
+  };
+  db_client()
+    .federation_peers
+    .find_one(query)
+    .await
+    .context("failed to query mongo for federation peer")?
+    .with_context(|| {
+      format!("no federation peer found matching {id_or_name}")
+    })
+}
+
+pub async fn get_external_status(
+  name: &str,
+) -> anyhow::Result<ExternalStatus> {
+  db_client()
+    .external_statuses
+    .find_one(doc! { "name": name })
+    .await
+    .context("failed to query mongo for external status")?
+    .with_context(|| {
+      format!("no external status found with name {name}")
+    })
+}
+
+/// Applies the Server Profile onto the Server config, if one is attached.
+/// If the profile can't be found, the Server's own config is used.
+pub async fn inherit_server_profile(mut server: Server) -> Server {
+  if server.config.server_profile.is_empty() {
+    return server;
+  }
+  match get_server_profile(&server.config.server_profile).await {
+    Ok(profile) => profile.config.apply(&mut server.config),
+    Err(e) => warn!(
+      "Failed to get profile for server {} ({}) | {e:#}",
+      server.name, server.id
+    ),
+  }
+  server
+}
+
+/// Applies the Server Profiles onto the configs
+/// of all the Servers which have one attached.
+pub async fn inherit_server_profiles(
+  servers: &mut [Server],
+) -> anyhow::Result<()> {
+  if servers
+    .iter()
+    .all(|server| server.config.server_profile.is_empty())
+  {
+    return Ok(());
+  }
+  let profiles =
+    find_collect(&db_client().server_profiles, None, None)
+      .await
+      .context("failed to query db for server profiles")?;
+  for server in servers {
+    if server.config.server_profile.is_empty() {
+      continue;
+    }
+    let Some(profile) = profiles.iter().find(|profile| {
+      profile.id == server.config.server_profile
+        || profile.name == server.config.server_profile
+    }) else {
+      warn!(
+        "No profile found for server {} ({}) matching {}",
+        server.name, server.id, server.config.server_profile
+      );
+      continue;
+    };
+    profile.config.apply(&mut server.config);
+  }
+  Ok(())
+}
+
 pub async fn get_id_to_tags(
   filter: impl Into<Option<Document>>,
 ) -> anyhow::Result<HashMap<String, Tag>> {
@@ -253,11 +404,13 @@ pub async fn get_id_to_tags(
   Ok(res)
 }
 
+/// The user groups the user is a member of,
+/// including the groups containing them through nesting.
 #[instrument(level = "debug")]
 pub async fn get_user_user_groups(
   user_id: &str,
 ) -> anyhow::Result<Vec<UserGroup>> {
-  find_collect(
+  let mut groups = find_collect(
     &db_client().user_groups,
     doc! {
       "$or": [
@@ -268,7 +421,58 @@ pub async fn get_user_user_groups(
     None,
   )
   .await
-  .context("failed to query db for user groups")
+  .context("failed to query db for user groups")?;
+  let mut seen = groups
+    .iter()
+    .map(|ug| ug.id.clone())
+    .collect::<HashSet<_>>();
+  let mut search = seen.iter().cloned().collect::<Vec<_>>();
+  // Walk up the nesting until no new parent groups are found.
+  // The seen ids guard against cycles.
+  while !search.is_empty() {
+    let parents = find_collect(
+      &db_client().user_groups,
+      doc! { "groups": { "$in": &search } },
+      None,
+    )
+    .await
+    .context("failed to query db for parent user groups")?;
+    search = Vec::new();
+    for parent in parents {
+      if seen.insert(parent.id.clone()) {
+        search.push(parent.id.clone());
+        groups.push(parent);
+      }
+    }
+  }
+  Ok(groups)
+}
+
+/// The ids of the user group and all groups nested in it, at any depth.
+pub async fn get_nested_user_group_ids(
+  user_group_id: &str,
+) -> anyhow::Result<HashSet<String>> {
+  let mut seen = HashSet::from([user_group_id.to_string()]);
+  let mut search = vec![user_group_id.to_string()];
+  while !search.is_empty() {
+    let ids = search
+      .iter()
+      .filter_map(|id| ObjectId::from_str(id).ok())
+      .collect::<Vec<_>>();
+    let groups = find_collect(
+      &db_client().user_groups,
+      doc! { "_id": { "$in": ids } },
+      None,
+    )
+    .await
+    .context("failed to query db for nested user groups")?;
+    search = groups
+      .into_iter()
+      .flat_map(|ug| ug.groups)
+      .filter(|id| seen.insert(id.clone()))
+      .collect();
+  }
+  Ok(seen)
 }
 
 #[instrument(level = "debug")]
@@ -314,6 +518,9 @@ pub async fn get_user_permission_on_target(
     ResourceTarget::Deployment(id) => {
       get_user_permission_on_resource::<Deployment>(user, id).await
     }
+    ResourceTarget::SwarmService(id) => {
+      get_user_permission_on_resource::<SwarmService>(user, id).await
+    }
     ResourceTarget::Server(id) => {
       get_user_permission_on_resource::<Server>(user, id).await
     }
@@ -394,7 +601,9 @@ pub async fn get_variables_and_secrets()
   let variables = find_collect(&db_client().variables, None, None)
     .await
     .context("failed to get all variables from db")?;
-  let mut secrets = core_config().secrets.clone();
+  // Secret providers have the lowest precedence
+  let mut secrets = get_provider_secrets().await?;
+  secrets.extend(core_config().secrets.clone());
 
   // extend secrets with secret variables
   secrets.extend(
@@ -432,7 +641,8 @@ pub async fn get_system_info(
       cached.0.clone()
     }
     _ => {
-      let stats = periphery_client(server)?
+      let stats = periphery_client(server)
+        .await?
         .request(stats::GetSystemInformation {})
         .await?;
       lock.insert(
@@ -0,0 +1,176 @@
+//! Provider specific auth flows for docker registry accounts,
+//! so tokens don't need to be rotated manually.
+
+use std::{
+  collections::HashMap,
+  sync::{Arc, OnceLock},
+};
+
+use anyhow::{Context, anyhow};
+use database::mungos::{
+  by_id::{find_one_by_id, update_one_by_id},
+  mongodb::bson::doc,
+};
+use komodo_client::entities::{
+  komodo_timestamp,
+  provider::{DockerRegistryAccount, RegistryAccountAuth},
+};
+use tokio::sync::Mutex;
+
+use crate::{
+  cloud::aws::ecr::get_ecr_login_password, helpers::random_string,
+  state::db_client,
+};
+
+use super::cache::Cache;
+
+const ONE_DAY_MS: i64 = 24 * 60 * 60 * 1000;
+/// ECR tokens are refreshed when they expire within this time.
+const ECR_REFRESH_MARGIN_MS: i64 = 60 * 60 * 1000;
+
+/// Gets the token to login to the registry with,
+/// using the auth flow configured on the account.
+pub async fn registry_account_token(
+  account: &DockerRegistryAccount,
+) -> anyhow::Result<String> {
+  match &account.auth {
+    RegistryAccountAuth::Token => Ok(account.token.clone()),
+    RegistryAccountAuth::Ecr { region } => {
+      if account.username != "AWS" {
+        return Err(anyhow!(
+          "ECR accounts must use the username 'AWS'"
+        ));
+      }
+      let region = if region.is_empty() {
+        ecr_region(&account.domain)?
+      } else {
+        region.clone()
+      };
+      ecr_token(region).await
+    }
+    RegistryAccountAuth::HarborRobot {
+      robot_id,
+      rotate_days,
+    } => harbor_robot_token(account, *robot_id, *rotate_days).await,
+  }
+}
+
+/// Parses the region from `123456789012.dkr.ecr.us-east-1.amazonaws.com`.
+fn ecr_region(domain: &str) -> anyhow::Result<String> {
+  domain
+    .split_once(".dkr.ecr.")
+    .and_then(|(_, rest)| rest.split_once('.'))
+    .map(|(region, _)| region.to_string())
+    .with_context(|| {
+      format!(
+        "Failed to parse ECR region from domain {domain}. Set the region on the account."
+      )
+    })
+}
+
+/// ECR tokens are valid for 12 hours, and shared by all
+/// registries in the region, so they are cached by region.
+async fn ecr_token(region: String) -> anyhow::Result<String> {
+  static TOKENS: OnceLock<Mutex<HashMap<String, (String, i64)>>> =
+    OnceLock::new();
+  let mut tokens = TOKENS.get_or_init(Default::default).lock().await;
+  if let Some((token, expires_at)) = tokens.get(&region)
+    && *expires_at - ECR_REFRESH_MARGIN_MS > komodo_timestamp()
+  {
+    return Ok(token.clone());
+  }
+  let (token, expires_at) =
+    get_ecr_login_password(region.clone()).await?;
+  tokens.insert(region, (token.clone(), expires_at));
+  Ok(token)
+}
+
+fn harbor_robot_locks() -> &'static Cache<String, Arc<Mutex<()>>> {
+  static LOCKS: OnceLock<Cache<String, Arc<Mutex<()>>>> =
+    OnceLock::new();
+  LOCKS.get_or_init(Default::default)
+}
+
+/// Rotates the robot secret when it is due,
+/// using the current secret to authenticate as the robot.
+/// The new secret is stored on the account.
+async fn harbor_robot_token(
+  account: &DockerRegistryAccount,
+  robot_id: i64,
+  rotate_days: i64,
+) -> anyhow::Result<String> {
+  let due = |account: &DockerRegistryAccount| {
+    rotate_days > 0
+      && komodo_timestamp() - account.token_rotated_at
+        > rotate_days * ONE_DAY_MS
+  };
+  if !due(account) {
+    return Ok(account.token.clone());
+  }
+
+  // Only one rotation at a time, as the old secret stops working.
+  let lock = harbor_robot_locks()
+    .get_or_insert_default(&account.id)
+    .await;
+  let _lock = lock.lock().await;
+
+  // Another task may have rotated the secret while waiting.
+  let account =
+    find_one_by_id(&db_client().registry_accounts, &account.id)
+      .await
+      .context("Failed to query db for registry account")?
+      .context("Registry account no longer exists")?;
+  if !due(&account) {
+    return Ok(account.token);
+  }
+
+  let secret = harbor_robot_secret();
+  let base = if account.domain.starts_with("http://")
+    || account.domain.starts_with("https://")
+  {
+    account.domain.trim_end_matches('/').to_string()
+  } else {
+    format!("https://{}", account.domain)
+  };
+  let res = reqwest::Client::new()
+    .patch(format!("{base}/api/v2.0/robots/{robot_id}"))
+    .basic_auth(&account.username, Some(&account.token))
+    .json(&serde_json::json!({ "secret": secret }))
+    .send()
+    .await
+    .context("Failed to reach Harbor")?;
+  let status = res.status();
+  if !status.is_success() {
+    let text = res.text().await.unwrap_or_default();
+    return Err(anyhow!(
+      "Failed to rotate Harbor robot secret | {status} | {text}"
+    ));
+  }
+
+  update_one_by_id(
+    &db_client().registry_accounts,
+    &account.id,
+    doc! { "$set": {
+      "token": &secret,
+      "token_rotated_at": komodo_timestamp(),
+    } },
+    None,
+  )
+  .await
+  .context(
+    "Rotated Harbor robot secret, but failed to store it on the account. The account must be updated with a new secret.",
+  )?;
+
+  info!(
+    "Rotated Harbor robot secret for registry account {} | {}",
+    account.domain, account.username
+  );
+
+  Ok(secret)
+}
+
+/// Harbor requires the secret to include
+/// an uppercase letter, a lowercase letter, and a number.
+fn harbor_robot_secret() -> String {
+  format!("Kr0{}", random_string(40))
+}
@@ -0,0 +1,87 @@
+//! Rerunning executions with the inputs recorded on a past Update.
+
+use std::future::Future;
+
+use anyhow::{Context, anyhow};
+use database::mungos::by_id::find_one_by_id;
+use komodo_client::entities::{
+  Operation, Version,
+  update::{ExecutionEnvironment, Update},
+};
+
+use crate::state::db_client;
+
+tokio::task_local! {
+  /// The inputs of the Update being rerun by the task.
+  static RERUN: RerunInputs;
+}
+
+#[derive(Clone)]
+pub struct RerunInputs {
+  /// The id of the Update being rerun.
+  pub update_id: String,
+  /// The resolved commit of the Update being rerun.
+  pub commit_hash: String,
+  /// The version of the Update being rerun.
+  pub version: Version,
+  pub environment: ExecutionEnvironment,
+}
+
+/// Gets the Update to rerun,
+/// checking its operation can be rerun.
+pub async fn get_rerun_update(id: &str) -> anyhow::Result<Update> {
+  let update = find_one_by_id(&db_client().updates, id)
+    .await
+    .context("Failed to query db for Update")?
+    .context("No Update found with given id")?;
+  if !matches!(
+    update.operation,
+    Operation::Deploy | Operation::DeployStack | Operation::RunBuild
+  ) {
+    return Err(anyhow!(
+      "Only Deploy, DeployStack and RunBuild Updates can be rerun, got {}",
+      update.operation
+    ));
+  }
+  if update.environment.is_none() {
+    return Err(anyhow!(
+      "Update {id} has no recorded execution environment to rerun"
+    ));
+  }
+  Ok(update)
+}
+
+/// Runs the execution with the inputs of the Update being rerun.
+pub async fn run_rerun<T>(
+  inputs: RerunInputs,
+  execution: impl Future<Output = T>,
+) -> T {
+  RERUN.scope(inputs, execution).await
+}
+
+/// The inputs to pin, if the task is rerunning an Update.
+pub fn rerun_inputs() -> Option<RerunInputs> {
+  RERUN.try_with(Clone::clone).ok()
+}
+
+/// Pins the commit to the resolved commit of the Update being rerun.
+pub fn pin_rerun_commit(commit: &mut String, update: &mut Update) {
+  let Some(rerun) = rerun_inputs() else {
+    return;
+  };
+  if rerun.commit_hash.is_empty() {
+    return;
+  }
+  *commit = rerun.commit_hash;
+  update.push_simple_log(
+    "Rerun",
+    format!("Using commit {commit} of Update {}", rerun.update_id),
+  );
+}
+
+/// Pins the image to the digest, keeping the tag for readability.
+/// Docker uses the digest when both are given.
+pub fn pinned_image(image: &str, digest: &str) -> String {
+  let image = image.split('@').next().unwrap_or(image);
+  format!("{image}@{digest}")
+}
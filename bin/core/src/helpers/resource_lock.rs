@@ -0,0 +1,374 @@
+use std::time::Duration;
+
+use anyhow::{Context, anyhow};
+use database::mungos::{
+  by_id::{delete_one_by_id, find_one_by_id, update_one_by_id},
+  mongodb::{bson::doc, options::FindOneOptions},
+};
+use komodo_client::entities::{
+  ResourceTarget,
+  build::Build,
+  komodo_timestamp,
+  resource::Resource,
+  resource_lock::{
+    ConcurrencyPolicy, ResourceLock, ResourceLockWaiter,
+  },
+  update::Update,
+};
+use reqwest::StatusCode;
+use serror::AddStatusCodeError;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+  config::core_config, helpers::cancel::cancel_execution,
+  state::db_client,
+};
+
+/// Locks expire if not renewed within this time,
+/// so a lock held by a stopped Core is released.
+const LOCK_TTL_MS: i64 = 60_000;
+/// Also how long it can take for a cancel
+/// to reach an execution on another Core.
+const LOCK_RENEW_INTERVAL: Duration = Duration::from_secs(20);
+const LOCK_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Acquires the lock on the resource's concurrency group for the execution,
+/// which is shared between all Core instances using the database.
+/// The group defaults to the resource itself.
+///
+/// If another execution holds the lock, the resource concurrency policy applies:
+/// - Reject: Waits up to `resource_lock_wait_secs` for it to be released,
+///   then fails with "operation in progress".
+/// - Queue: Waits in line behind the other waiting executions.
+/// - CancelPrevious: Cancels the other waiting executions, then waits.
+///
+/// The lock is held until the returned guard is dropped.
+/// Acquire it before the action state, so waiting
+/// executions don't find the resource busy.
+pub async fn acquire_resource_lock<Config: Default, Info: Default>(
+  resource: &Resource<Config, Info>,
+  update: &Update,
+) -> serror::Result<ResourceLockGuard>
+where
+  for<'a> &'a Resource<Config, Info>: Into<ResourceTarget>,
+{
+  let target: ResourceTarget = resource.into();
+  let (variant, id) = target.extract_variant_id();
+  let lock_id = if resource.concurrency.group.is_empty() {
+    format!("{variant}:{id}")
+  } else {
+    format!("group:{}", resource.concurrency.group)
+  };
+  let lock = LockRequest {
+    lock_id: &lock_id,
+    target: &target,
+    update,
+  };
+  match resource.concurrency.policy {
+    ConcurrencyPolicy::Reject => lock.acquire_or_reject().await,
+    ConcurrencyPolicy::Queue => {
+      lock.acquire_in_line(Supersede::None).await
+    }
+    ConcurrencyPolicy::CancelPrevious => {
+      lock.acquire_in_line(Supersede::Group).await
+    }
+  }
+}
+
+/// Builds with a `concurrency_group` wait in line on the group,
+/// and a newer run of the Build cancels its runs still waiting,
+/// so rapid successive webhooks only build the latest commit.
+/// Otherwise the same as [acquire_resource_lock].
+pub async fn acquire_build_lock(
+  build: &Build,
+  update: &Update,
+) -> serror::Result<ResourceLockGuard> {
+  if build.config.concurrency_group.is_empty() {
+    return acquire_resource_lock(build, update).await;
+  }
+  let lock_id = format!("group:{}", build.config.concurrency_group);
+  LockRequest {
+    lock_id: &lock_id,
+    target: &ResourceTarget::Build(build.id.clone()),
+    update,
+  }
+  .acquire_in_line(Supersede::Target)
+  .await
+}
+
+/// Which of the executions waiting in line a new one cancels.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Supersede {
+  None,
+  /// All the waiting executions in the group.
+  Group,
+  /// Only those on the same resource.
+  Target,
+}
+
+struct LockRequest<'a> {
+  lock_id: &'a str,
+  target: &'a ResourceTarget,
+  update: &'a Update,
+}
+
+impl LockRequest<'_> {
+  async fn acquire_or_reject(
+    &self,
+  ) -> serror::Result<ResourceLockGuard> {
+    let deadline = komodo_timestamp()
+      + core_config().resource_lock_wait_secs as i64 * 1000;
+    loop {
+      let holder = match self.try_acquire().await? {
+        Ok(guard) => return Ok(guard),
+        Err(holder) => holder,
+      };
+      if komodo_timestamp() >= deadline {
+        return Err(
+          anyhow!(
+            "Operation in progress | {} is running on this {} (Update {}), try again once it completes",
+            holder.operation,
+            self.concurrency_target(),
+            holder.update_id
+          )
+          .status_code(StatusCode::CONFLICT),
+        );
+      }
+      tokio::time::sleep(LOCK_POLL_INTERVAL).await;
+    }
+  }
+
+  /// Waits in line with the other executions in the group,
+  /// acquiring the lock once first in line.
+  async fn acquire_in_line(
+    &self,
+    supersede: Supersede,
+  ) -> serror::Result<ResourceLockGuard> {
+    let waiters = &db_client().resource_lock_waiters;
+    let mut filter =
+      doc! { "lock_id": self.lock_id, "superseded_by": "" };
+    if supersede == Supersede::Target {
+      let (variant, id) = self.target.extract_variant_id();
+      filter.insert("target.type", variant.to_string());
+      filter.insert("target.id", id.to_string());
+    }
+    if supersede != Supersede::None {
+      waiters
+        .update_many(
+          filter,
+          doc! { "$set": { "superseded_by": &self.update.id } },
+        )
+        .await
+        .context("Failed to cancel queued executions")?;
+    }
+
+    let now = komodo_timestamp();
+    let waiter = ResourceLockWaiter {
+      id: Default::default(),
+      lock_id: self.lock_id.to_string(),
+      target: self.target.clone(),
+      operation: self.update.operation,
+      update_id: self.update.id.clone(),
+      queued_at: now,
+      expires_at: now + LOCK_TTL_MS,
+      superseded_by: Default::default(),
+    };
+    let waiter_id = waiters
+      .insert_one(&waiter)
+      .await
+      .context("Failed to add execution to resource lock queue")?
+      .inserted_id
+      .as_object_id()
+      .context("Inserted id is not ObjectId")?
+      .to_string();
+    // Leaves the line when this returns, or the execution is dropped.
+    let _waiter = WaiterGuard(waiter_id.clone());
+
+    loop {
+      let waiter = find_one_by_id(waiters, &waiter_id)
+        .await
+        .context("Failed to query db for resource lock waiter")?
+        .context(
+          "Execution was removed from the resource lock queue",
+        )?;
+      if !waiter.superseded_by.is_empty() {
+        return Err(
+          anyhow!(
+            "Cancelled | A newer execution on this {} (Update {}) cancelled this queued one",
+            self.concurrency_target(),
+            waiter.superseded_by
+          )
+          .status_code(StatusCode::CONFLICT),
+        );
+      }
+
+      let now = komodo_timestamp();
+      update_one_by_id(
+        waiters,
+        &waiter_id,
+        doc! { "$set": { "expires_at": now + LOCK_TTL_MS } },
+        None,
+      )
+      .await
+      .context("Failed to renew resource lock waiter")?;
+      // Remove the waiters of a stopped Core.
+      waiters
+        .delete_many(doc! {
+          "lock_id": self.lock_id,
+          "expires_at": { "$lt": now },
+        })
+        .await
+        .context("Failed to clear expired resource lock waiters")?;
+
+      let first = waiters
+        .find_one(
+          doc! { "lock_id": self.lock_id, "superseded_by": "" },
+        )
+        .with_options(
+          FindOneOptions::builder()
+            .sort(doc! { "queued_at": 1, "_id": 1 })
+            .build(),
+        )
+        .await
+        .context("Failed to query db for resource lock queue")?;
+      if first.is_some_and(|first| first.id == waiter_id)
+        && let Ok(guard) = self.try_acquire().await?
+      {
+        return Ok(guard);
+      }
+
+      tokio::time::sleep(LOCK_POLL_INTERVAL).await;
+    }
+  }
+
+  /// Returns the current holder if the lock is already held.
+  async fn try_acquire(
+    &self,
+  ) -> anyhow::Result<Result<ResourceLockGuard, ResourceLock>> {
+    let locks = &db_client().resource_locks;
+    let now = komodo_timestamp();
+    // Release a lock left by a stopped Core.
+    locks
+      .delete_one(
+        doc! { "_id": self.lock_id, "expires_at": { "$lt": now } },
+      )
+      .await
+      .context("Failed to clear expired resource lock")?;
+
+    let lock = ResourceLock {
+      id: self.lock_id.to_string(),
+      target: self.target.clone(),
+      operation: self.update.operation,
+      update_id: self.update.id.clone(),
+      operator: self.update.operator.clone(),
+      locked_at: now,
+      expires_at: now + LOCK_TTL_MS,
+      cancelled_by: Default::default(),
+    };
+    let insert_error = match locks.insert_one(&lock).await {
+      Ok(_) => return Ok(Ok(ResourceLockGuard::new(lock))),
+      Err(e) => e,
+    };
+
+    // The insert fails if another execution holds the lock.
+    match locks
+      .find_one(doc! { "_id": self.lock_id })
+      .await
+      .context("Failed to query db for resource lock")?
+    {
+      Some(holder) => Ok(Err(holder)),
+      None => Err(
+        anyhow::Error::from(insert_error)
+          .context("Failed to acquire resource lock"),
+      ),
+    }
+  }
+
+  fn concurrency_target(&self) -> String {
+    match self.lock_id.strip_prefix("group:") {
+      Some(group) => format!("concurrency group '{group}'"),
+      None => self.target.extract_variant_id().0.to_string(),
+    }
+  }
+}
+
+struct WaiterGuard(String);
+
+impl Drop for WaiterGuard {
+  fn drop(&mut self) {
+    let id = std::mem::take(&mut self.0);
+    tokio::spawn(async move {
+      if let Err(e) = delete_one_by_id(
+        &db_client().resource_lock_waiters,
+        &id,
+        None,
+      )
+      .await
+      {
+        warn!("Failed to remove resource lock waiter {id} | {e:#}");
+      }
+    });
+  }
+}
+
+/// Renews the lock while alive, and releases it when dropped.
+pub struct ResourceLockGuard {
+  lock: ResourceLock,
+  cancel: CancellationToken,
+}
+
+impl ResourceLockGuard {
+  fn new(lock: ResourceLock) -> ResourceLockGuard {
+    let cancel = CancellationToken::new();
+    let renew_cancel = cancel.clone();
+    let id = lock.id.clone();
+    let locked_at = lock.locked_at;
+    let update_id = lock.update_id.clone();
+    tokio::spawn(async move {
+      loop {
+        tokio::select! {
+          _ = renew_cancel.cancelled() => return,
+          _ = tokio::time::sleep(LOCK_RENEW_INTERVAL) => {}
+        }
+        let res = db_client()
+          .resource_locks
+          .find_one_and_update(
+            doc! { "_id": &id, "locked_at": locked_at },
+            doc! { "$set": {
+              "expires_at": komodo_timestamp() + LOCK_TTL_MS
+            } },
+          )
+          .await;
+        match res {
+          // Cancelled with CancelUpdate on another Core.
+          Ok(Some(lock)) if !lock.cancelled_by.is_empty() => {
+            cancel_execution(&update_id).await;
+          }
+          Ok(_) => {}
+          Err(e) => {
+            warn!("Failed to renew resource lock {id} | {e:#}")
+          }
+        }
+      }
+    });
+    ResourceLockGuard { lock, cancel }
+  }
+}
+
+impl Drop for ResourceLockGuard {
+  fn drop(&mut self) {
+    self.cancel.cancel();
+    let id = std::mem::take(&mut self.lock.id);
+    let locked_at = self.lock.locked_at;
+    tokio::spawn(async move {
+      // Only release the lock if it is still this one.
+      let res = db_client()
+        .resource_locks
+        .delete_one(doc! { "_id": &id, "locked_at": locked_at })
+        .await;
+      if let Err(e) = res {
+        warn!("Failed to release resource lock {id} | {e:#}");
+      }
+    });
+  }
+}
@@ -0,0 +1,134 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Context, anyhow};
+use komodo_client::{
+  api::execute::Execution,
+  entities::{RuntimeInput, update::Log},
+};
+
+/// The runtime input values resolved for a single run.
+/// These are never persisted, only a sanitized log of them
+/// is added to the Update.
+#[derive(Default)]
+pub struct RuntimeInputs {
+  pub variables: HashMap<String, String>,
+  pub secrets: HashMap<String, String>,
+}
+
+impl RuntimeInputs {
+  /// Validates the supplied values against the declared inputs,
+  /// using the defaults for any which are not supplied.
+  pub fn resolve(
+    declared: &[RuntimeInput],
+    supplied: Option<HashMap<String, String>>,
+  ) -> anyhow::Result<RuntimeInputs> {
+    let mut supplied = supplied.unwrap_or_default();
+    let mut names = HashSet::new();
+    let mut inputs = RuntimeInputs::default();
+
+    for input in declared {
+      if input.name.is_empty() {
+        return Err(anyhow!(
+          "Runtime input is declared without a name"
+        ));
+      }
+      if !names.insert(input.name.as_str()) {
+        return Err(anyhow!(
+          "Runtime input '{}' is declared more than once",
+          input.name
+        ));
+      }
+      let value = supplied
+        .remove(&input.name)
+        .or_else(|| input.default.clone())
+        .with_context(|| {
+          format!("Missing required runtime input '{}'", input.name)
+        })?;
+      if input.secret {
+        inputs.secrets.insert(input.name.clone(), value);
+      } else {
+        inputs.variables.insert(input.name.clone(), value);
+      }
+    }
+
+    if !supplied.is_empty() {
+      let mut unknown = supplied.into_keys().collect::<Vec<_>>();
+      unknown.sort();
+      return Err(anyhow!(
+        "Unknown runtime inputs: {}",
+        unknown.join(", ")
+      ));
+    }
+
+    Ok(inputs)
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.variables.is_empty() && self.secrets.is_empty()
+  }
+
+  /// Show the input values, only showing names of secret inputs.
+  pub fn push_log(&self, logs: &mut Vec<Log>) {
+    if self.is_empty() {
+      return;
+    }
+    let mut variables = self.variables.iter().collect::<Vec<_>>();
+    variables.sort();
+    let mut secrets = self.secrets.keys().collect::<Vec<_>>();
+    secrets.sort();
+    let lines = variables
+      .into_iter()
+      .map(|(name, value)| format!("<span class=\"text-muted-foreground\">{name} =></span> {value}"))
+      .chain(secrets.into_iter().map(|name| {
+        format!("<span class=\"text-muted-foreground\">secret:</span> {name}")
+      }))
+      .collect::<Vec<_>>();
+    logs.push(Log::simple("Runtime Inputs", lines.join("\n")));
+  }
+
+  /// Interpolates the input values into a Procedure stage execution.
+  pub fn interpolate_execution(
+    &self,
+    execution: &Execution,
+  ) -> anyhow::Result<Execution> {
+    if self.is_empty() {
+      return Ok(execution.clone());
+    }
+    let serialized = serde_json::to_string(execution)
+      .context("Failed to serialize execution")?;
+    let (res, _) = svi::interpolate_variables(
+      &serialized,
+      &json_escaped(&self.variables)?,
+      svi::Interpolator::DoubleBrackets,
+      false,
+    )
+    .context("Failed to interpolate runtime inputs")?;
+    let (res, _) = svi::interpolate_variables(
+      &res,
+      &json_escaped(&self.secrets)?,
+      svi::Interpolator::DoubleBrackets,
+      false,
+    )
+    .context("Failed to interpolate secret runtime inputs")?;
+    serde_json::from_str(&res).context(
+      "Failed to parse execution after interpolating runtime inputs",
+    )
+  }
+}
+
+/// The values are interpolated into serialized JSON strings,
+/// so they need to be escaped first.
+pub fn json_escaped(
+  values: &HashMap<String, String>,
+) -> anyhow::Result<HashMap<String, String>> {
+  values
+    .iter()
+    .map(|(name, value)| {
+      let escaped = serde_json::to_string(value)
+        .context("Failed to escape runtime input")?;
+      // Remove the surrounding quotes
+      let escaped = escaped[1..escaped.len() - 1].to_string();
+      Ok((name.clone(), escaped))
+    })
+    .collect()
+}
@@ -0,0 +1,227 @@
+use std::{collections::HashMap, sync::OnceLock, time::Duration};
+
+use anyhow::{Context, anyhow};
+use async_timing_util::{ONE_MIN_MS, unix_timestamp_ms};
+use komodo_client::entities::config::core::{
+  AwsSecretsManagerSecretProvider, EnvFileSecretProvider,
+  SecretProvider, VaultSecretProvider,
+};
+use serde::Deserialize;
+use tokio::sync::Mutex;
+
+use crate::{
+  cloud::aws::secrets_manager::get_secret_strings,
+  config::core_config,
+};
+
+fn http_client() -> &'static reqwest::Client {
+  static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+  CLIENT.get_or_init(|| {
+    reqwest::Client::builder()
+      .timeout(Duration::from_secs(10))
+      .build()
+      .expect("Invalid secret provider reqwest client")
+  })
+}
+
+// This protects the secret providers from spam requests
+const PROVIDER_SECRETS_EXPIRY: u128 = ONE_MIN_MS;
+
+#[derive(Default)]
+struct ProviderSecretsCache {
+  /// The secrets from the last successful read,
+  /// with the timestamp they expire at.
+  secrets: Option<(HashMap<String, String>, u128)>,
+  /// The secret names from the last successful read.
+  /// These don't expire, so listing secrets doesn't
+  /// need to read the values again.
+  names: Option<Vec<String>>,
+}
+
+fn provider_secrets_cache() -> &'static Mutex<ProviderSecretsCache> {
+  static PROVIDER_SECRETS_CACHE: OnceLock<
+    Mutex<ProviderSecretsCache>,
+  > = OnceLock::new();
+  PROVIDER_SECRETS_CACHE.get_or_init(Default::default)
+}
+
+/// Reads the secrets from all the configured secret providers.
+/// Later providers take precedence over earlier ones.
+///
+/// The secrets are cached for a minute. A provider which fails
+/// fails the whole read, so an unreachable provider is reported
+/// as such rather than as missing variables.
+pub async fn get_provider_secrets()
+-> anyhow::Result<HashMap<String, String>> {
+  if core_config().secret_providers.is_empty() {
+    return Ok(HashMap::new());
+  }
+  let mut cache = provider_secrets_cache().lock().await;
+  if let Some((secrets, expiry)) = &cache.secrets
+    && unix_timestamp_ms() < *expiry
+  {
+    return Ok(secrets.clone());
+  }
+  let secrets = read_provider_secrets().await?;
+  cache.names = Some(secrets.keys().cloned().collect());
+  cache.secrets = Some((
+    secrets.clone(),
+    unix_timestamp_ms() + PROVIDER_SECRETS_EXPIRY,
+  ));
+  Ok(secrets)
+}
+
+/// Lists the names of the secret provider secrets.
+/// Uses the names from the last successful read,
+/// only reading the providers if they haven't been read yet.
+pub async fn get_provider_secret_names() -> anyhow::Result<Vec<String>>
+{
+  if let Some(names) = &provider_secrets_cache().lock().await.names {
+    return Ok(names.clone());
+  }
+  get_provider_secrets()
+    .await
+    .map(|secrets| secrets.into_keys().collect())
+}
+
+async fn read_provider_secrets()
+-> anyhow::Result<HashMap<String, String>> {
+  let mut secrets = HashMap::new();
+  for provider in &core_config().secret_providers {
+    let provider_secrets = match provider {
+      SecretProvider::Vault(vault) => {
+        vault_secrets(vault).await.with_context(|| {
+          format!(
+            "Failed to read secrets from Vault secret provider at {}",
+            vault.address
+          )
+        })?
+      }
+      SecretProvider::AwsSecretsManager(aws) => {
+        aws_secrets(aws).await.with_context(|| {
+          format!(
+            "Failed to read secrets from AWS Secrets Manager secret provider in {}",
+            aws.region
+          )
+        })?
+      }
+      SecretProvider::EnvFile(env_file) => {
+        env_file_secrets(env_file).await.with_context(|| {
+          format!(
+            "Failed to read secrets from env file secret provider at {:?}",
+            env_file.path
+          )
+        })?
+      }
+    };
+    secrets.extend(provider_secrets);
+  }
+  Ok(secrets)
+}
+
+async fn vault_secrets(
+  VaultSecretProvider {
+    address,
+    token,
+    namespace,
+    mount,
+    path,
+    prefix,
+  }: &VaultSecretProvider,
+) -> anyhow::Result<Vec<(String, String)>> {
+  #[derive(Deserialize)]
+  struct VaultResponse {
+    data: VaultData,
+  }
+  #[derive(Deserialize)]
+  struct VaultData {
+    data: HashMap<String, serde_json::Value>,
+  }
+
+  let url = format!(
+    "{}/v1/{}/data/{}",
+    address.trim_end_matches('/'),
+    mount.trim_matches('/'),
+    path.trim_matches('/')
+  );
+  let mut req =
+    http_client().get(&url).header("X-Vault-Token", token);
+  if !namespace.is_empty() {
+    req = req.header("X-Vault-Namespace", namespace);
+  }
+  let res = req
+    .send()
+    .await
+    .with_context(|| format!("Failed to reach Vault at {address}"))?;
+  let status = res.status();
+  if !status.is_success() {
+    let text = res.text().await.unwrap_or_default();
+    return Err(anyhow!(
+      "Vault returned {status} for {mount}/{path} | {text}"
+    ));
+  }
+  let res = res
+    .json::<VaultResponse>()
+    .await
+    .context("Failed to parse Vault KV v2 response")?;
+  Ok(
+    res
+      .data
+      .data
+      .into_iter()
+      .map(|(name, value)| {
+        (format!("{prefix}{name}"), value_to_string(value))
+      })
+      .collect(),
+  )
+}
+
+async fn aws_secrets(
+  AwsSecretsManagerSecretProvider {
+    region,
+    secret_ids,
+    prefix,
+  }: &AwsSecretsManagerSecretProvider,
+) -> anyhow::Result<Vec<(String, String)>> {
+  let mut secrets = Vec::new();
+  for (secret_id, value) in
+    get_secret_strings(region.clone(), secret_ids).await?
+  {
+    match serde_json::from_str::<HashMap<String, serde_json::Value>>(
+      &value,
+    ) {
+      Ok(values) => {
+        secrets.extend(values.into_iter().map(|(name, value)| {
+          (format!("{prefix}{name}"), value_to_string(value))
+        }))
+      }
+      Err(_) => secrets.push((format!("{prefix}{secret_id}"), value)),
+    }
+  }
+  Ok(secrets)
+}
+
+async fn env_file_secrets(
+  EnvFileSecretProvider { path, prefix }: &EnvFileSecretProvider,
+) -> anyhow::Result<Vec<(String, String)>> {
+  let contents =
+    tokio::fs::read_to_string(path).await.with_context(|| {
+      format!("Failed to read secrets env file at {path:?}")
+    })?;
+  dotenvy::from_read_iter(contents.as_bytes())
+    .map(|item| {
+      let (name, value) = item.with_context(|| {
+        format!("Failed to parse secrets env file at {path:?}")
+      })?;
+      Ok((format!("{prefix}{name}"), value))
+    })
+    .collect()
+}
+
+/// Uses strings as is, other JSON values are stringified.
+fn value_to_string(value: serde_json::Value) -> String {
+  match value {
+    serde_json::Value::String(value) => value,
+    value => value.to_string(),
+  }
+}
@@ -0,0 +1,258 @@
+use std::{
+  collections::HashMap,
+  path::PathBuf,
+  process::Stdio,
+  sync::{Arc, OnceLock},
+  time::Duration,
+};
+
+use anyhow::{Context, anyhow};
+use komodo_client::entities::server::{PeripheryConnection, Server};
+use tokio::{
+  io::AsyncReadExt,
+  net::{TcpListener, TcpStream},
+  process::{Child, Command},
+  sync::Mutex,
+};
+
+use crate::{config::core_config, helpers::write_private_file};
+
+/// How long to wait for a new tunnel to accept connections.
+const TUNNEL_READY_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// What the tunnel was opened with.
+/// If any of these change, the tunnel is reopened.
+#[derive(PartialEq)]
+struct TunnelSpec {
+  host: String,
+  port: u16,
+  user: String,
+  key: String,
+  remote: String,
+}
+
+struct SshTunnel {
+  spec: TunnelSpec,
+  local_port: u16,
+  child: Child,
+}
+
+type SshTunnelEntry = Arc<Mutex<Option<SshTunnel>>>;
+
+/// Server id -> SshTunnel.
+///
+/// All requests to a Server share the same tunnel,
+/// each connection is multiplexed over the single SSH session.
+/// Each Server has its own lock, so a slow SSH server
+/// doesn't hold up requests to the other Servers.
+fn ssh_tunnels() -> &'static Mutex<HashMap<String, SshTunnelEntry>> {
+  static SSH_TUNNELS: OnceLock<
+    Mutex<HashMap<String, SshTunnelEntry>>,
+  > = OnceLock::new();
+  SSH_TUNNELS.get_or_init(Default::default)
+}
+
+/// Gets the address to reach Periphery at for the Server.
+/// For SSH connections, opens the tunnel if it isn't open already.
+pub async fn periphery_address(
+  server: &Server,
+) -> anyhow::Result<String> {
+  let PeripheryConnection::Ssh {
+    host,
+    port,
+    user,
+    key_secret,
+  } = &server.config.connection
+  else {
+    return Ok(server.config.address.clone());
+  };
+
+  let (protocol, remote) = split_address(&server.config.address)?;
+  let key = core_config()
+    .secrets
+    .get(key_secret)
+    .with_context(|| {
+      format!("No Core secret named '{key_secret}' for the SSH key")
+    })?
+    .clone();
+  let spec = TunnelSpec {
+    host: host.clone(),
+    port: *port,
+    user: user.clone(),
+    key,
+    remote,
+  };
+
+  let entry = ssh_tunnels()
+    .lock()
+    .await
+    .entry(server.id.clone())
+    .or_default()
+    .clone();
+
+  // Hold the Server lock while opening the tunnel,
+  // so concurrent requests wait for the same tunnel.
+  let mut tunnel = entry.lock().await;
+
+  if let Some(existing) = tunnel.as_mut() {
+    let alive = matches!(existing.child.try_wait(), Ok(None));
+    if alive && existing.spec == spec {
+      return Ok(format!(
+        "{protocol}://127.0.0.1:{}",
+        existing.local_port
+      ));
+    }
+    if alive {
+      existing.child.kill().await.ok();
+    }
+    *tunnel = None;
+  }
+
+  let opened =
+    open_tunnel(&server.id, spec).await.with_context(|| {
+      format!(
+        "Failed to open SSH tunnel to {user}@{host}:{port} for Server {}",
+        server.name
+      )
+    })?;
+  let address =
+    format!("{protocol}://127.0.0.1:{}", opened.local_port);
+  *tunnel = Some(opened);
+
+  Ok(address)
+}
+
+/// Closes the Server SSH tunnel, if one is open.
+pub async fn close_ssh_tunnel(server_id: &str) {
+  let Some(entry) = ssh_tunnels().lock().await.remove(server_id)
+  else {
+    return;
+  };
+  if let Some(mut tunnel) = entry.lock().await.take() {
+    tunnel.child.kill().await.ok();
+  }
+}
+
+async fn open_tunnel(
+  server_id: &str,
+  spec: TunnelSpec,
+) -> anyhow::Result<SshTunnel> {
+  let dir = ssh_directory();
+  let key_path = dir.join(format!("{server_id}.key"));
+  // Private keys must end with a newline
+  let key = format!("{}\n", spec.key.trim());
+  write_private_file(&key_path, key.as_bytes())
+    .await
+    .context("Failed to write SSH key")?;
+
+  let local_port = free_local_port().await?;
+
+  let mut child = Command::new("ssh")
+    .arg("-N")
+    .args(["-o", "BatchMode=yes"])
+    .args(["-o", "LogLevel=ERROR"])
+    .args(["-o", "ExitOnForwardFailure=yes"])
+    .args(["-o", "ServerAliveInterval=15"])
+    .args(["-o", "ServerAliveCountMax=3"])
+    .args(["-o", "StrictHostKeyChecking=accept-new"])
+    .arg("-o")
+    .arg(format!(
+      "UserKnownHostsFile={}",
+      dir.join("known_hosts").display()
+    ))
+    .arg("-i")
+    .arg(&key_path)
+    .arg("-p")
+    .arg(spec.port.to_string())
+    .arg("-L")
+    .arg(format!("127.0.0.1:{local_port}:{}", spec.remote))
+    .arg(format!("{}@{}", spec.user, spec.host))
+    .stdin(Stdio::null())
+    .stdout(Stdio::null())
+    .stderr(Stdio::piped())
+    .kill_on_drop(true)
+    .spawn()
+    .context("Failed to spawn ssh. Is the ssh client installed?")?;
+
+  let start = tokio::time::Instant::now();
+  loop {
+    if let Some(status) =
+      child.try_wait().context("Failed to get ssh status")?
+    {
+      let mut stderr = String::new();
+      if let Some(mut pipe) = child.stderr.take() {
+        pipe.read_to_string(&mut stderr).await.ok();
+      }
+      return Err(anyhow!(
+        "ssh exited with {status} | {}",
+        stderr.trim()
+      ));
+    }
+    if TcpStream::connect(("127.0.0.1", local_port)).await.is_ok() {
+      break;
+    }
+    if start.elapsed() > TUNNEL_READY_TIMEOUT {
+      child.kill().await.ok();
+      return Err(anyhow!(
+        "Timed out waiting for the tunnel to accept connections"
+      ));
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+  }
+
+  info!(
+    "Opened SSH tunnel to {}@{}:{} for Server {server_id}",
+    spec.user, spec.host, spec.port
+  );
+
+  Ok(SshTunnel {
+    spec,
+    local_port,
+    child,
+  })
+}
+
+fn ssh_directory() -> PathBuf {
+  std::env::temp_dir().join("komodo-ssh")
+}
+
+async fn free_local_port() -> anyhow::Result<u16> {
+  let listener = TcpListener::bind(("127.0.0.1", 0))
+    .await
+    .context("Failed to find free local port")?;
+  Ok(listener.local_addr()?.port())
+}
+
+/// Splits eg. `https://localhost:8120` into (`https`, `localhost:8120`).
+/// The port defaults to 8120, or 2375 for Docker `tcp://` addresses.
+///
+/// Remote unix sockets, eg. `unix:///var/run/docker.sock`,
+/// are forwarded as `tcp` to the socket path.
+fn split_address(address: &str) -> anyhow::Result<(&str, String)> {
+  if let Some(path) = address.strip_prefix("unix://") {
+    if path.is_empty() {
+      return Err(anyhow!("Invalid socket address: {address}"));
+    }
+    return Ok(("tcp", path.to_string()));
+  }
+  let (protocol, rest) =
+    address.split_once("://").unwrap_or(("http", address));
+  let host = rest.split('/').next().unwrap_or_default();
+  if host.is_empty() {
+    return Err(anyhow!("Invalid periphery address: {address}"));
+  }
+  let has_port = match host.rsplit_once(':') {
+    // IPv6 without port, eg. `[::1]`
+    Some((_, port)) if port.ends_with(']') => false,
+    Some(_) => true,
+    None => false,
+  };
+  let remote = if has_port {
+    host.to_string()
+  } else if protocol == "tcp" {
+    format!("{host}:2375")
+  } else {
+    format!("{host}:8120")
+  };
+  Ok((protocol, remote))
+}
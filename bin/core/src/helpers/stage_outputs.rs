@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+
+use anyhow::Context;
+use komodo_client::{
+  api::execute::Execution,
+  entities::{build::Build, update::Update},
+};
+
+use crate::resource;
+
+use super::runtime_inputs::json_escaped;
+
+/// Actions publish outputs by logging lines with this prefix,
+/// followed by the JSON `{ "name": string, "value": string }`.
+/// The Action `OUTPUT(name, value)` function does this.
+pub const ACTION_OUTPUT_PREFIX: &str = "__KOMODO_OUTPUT__ ";
+
+/// The outputs published by the stages of an in-flight
+/// procedure run. Later stages reference them
+/// with `[[stages.<stage>.<name>]]`.
+#[derive(Default)]
+pub struct StageOutputs {
+  values: HashMap<String, String>,
+}
+
+impl StageOutputs {
+  pub fn insert(
+    &mut self,
+    stage: &str,
+    outputs: HashMap<String, String>,
+  ) {
+    for (name, value) in outputs {
+      self.values.insert(format!("stages.{stage}.{name}"), value);
+    }
+  }
+
+  pub fn values(&self) -> &HashMap<String, String> {
+    &self.values
+  }
+
+  /// Interpolates the outputs of earlier stages into an execution.
+  pub fn interpolate_execution(
+    &self,
+    execution: &Execution,
+  ) -> anyhow::Result<Execution> {
+    if self.values.is_empty() {
+      return Ok(execution.clone());
+    }
+    let serialized = serde_json::to_string(execution)
+      .context("Failed to serialize execution")?;
+    let (res, _) = svi::interpolate_variables(
+      &serialized,
+      &json_escaped(&self.values)?,
+      svi::Interpolator::DoubleBrackets,
+      false,
+    )
+    .context("Failed to interpolate stage outputs")?;
+    serde_json::from_str(&res).context(
+      "Failed to parse execution after interpolating stage outputs",
+    )
+  }
+}
+
+/// Gets the outputs published by a finished execution.
+/// All executions publish their `update_id`.
+pub async fn execution_outputs(
+  execution: &Execution,
+  update: &Update,
+) -> HashMap<String, String> {
+  let mut outputs = HashMap::new();
+  if !update.id.is_empty() {
+    outputs.insert(String::from("update_id"), update.id.clone());
+  }
+  match execution {
+    Execution::RunBuild(req) => {
+      let version = update.version.to_string();
+      if !update.commit_hash.is_empty() {
+        outputs.insert(
+          String::from("commit_hash"),
+          update.commit_hash.clone(),
+        );
+      }
+      match resource::get::<Build>(&req.build).await {
+        Ok(build) => {
+          let image_tag = if build.config.image_tag.is_empty() {
+            version.clone()
+          } else {
+            format!("{version}-{}", build.config.image_tag)
+          };
+          if let Some(image_name) =
+            build.get_image_names().into_iter().next()
+          {
+            outputs.insert(
+              String::from("image"),
+              format!("{image_name}:{image_tag}"),
+            );
+          }
+          outputs.insert(String::from("image_tag"), image_tag);
+        }
+        Err(e) => {
+          warn!("Failed to get Build for stage outputs | {e:#}")
+        }
+      }
+      outputs.insert(String::from("version"), version);
+    }
+    Execution::RunAction(_) => {
+      outputs.extend(action_outputs(update));
+    }
+    _ => {}
+  }
+  outputs
+}
+
+/// Parses the outputs the Action published with `OUTPUT(name, value)`.
+fn action_outputs(
+  update: &Update,
+) -> impl Iterator<Item = (String, String)> + '_ {
+  #[derive(serde::Deserialize)]
+  struct ActionOutput {
+    name: String,
+    value: String,
+  }
+  update
+    .logs
+    .iter()
+    .flat_map(|log| log.stdout.lines())
+    .filter_map(|line| line.strip_prefix(ACTION_OUTPUT_PREFIX))
+    .filter_map(|output| {
+      serde_json::from_str::<ActionOutput>(output)
+        .inspect_err(|e| {
+          warn!("Failed to parse Action output | {e:?}")
+        })
+        .ok()
+    })
+    .map(|ActionOutput { name, value }| (name, value))
+}
@@ -0,0 +1,130 @@
+use anyhow::{Context, anyhow};
+use komodo_client::entities::{
+  permission::PermissionLevel,
+  server::{Server, TerminalProfile},
+  user::User,
+};
+
+use crate::{
+  permission::get_user_permission_on_resource,
+  resource::KomodoResource,
+};
+
+use super::{
+  periphery_client,
+  query::{get_system_info, get_user_user_groups},
+};
+
+/// Get the [TerminalProfile] on the Server by name,
+/// checking the user is allowed to use it.
+pub async fn get_terminal_profile<'a>(
+  server: &'a Server,
+  profile: &str,
+  user: &User,
+) -> anyhow::Result<&'a TerminalProfile> {
+  let profile = server
+    .config
+    .terminal_profiles
+    .iter()
+    .find(|p| p.name == profile)
+    .with_context(|| {
+      format!(
+        "No terminal profile '{profile}' on Server {}",
+        server.name
+      )
+    })?;
+  if user.admin || profile.user_groups.is_empty() {
+    return Ok(profile);
+  }
+  let allowed = get_user_user_groups(&user.id)
+    .await?
+    .into_iter()
+    .any(|group| {
+      profile
+        .user_groups
+        .iter()
+        .any(|allowed| allowed == &group.id || allowed == &group.name)
+    });
+  if allowed {
+    Ok(profile)
+  } else {
+    Err(anyhow!(
+      "User is not allowed to use terminal profile '{}'",
+      profile.name
+    ))
+  }
+}
+
+/// Ensures the user may open arbitrary terminals / container exec
+/// sessions on the Server, outside of the configured terminal profiles.
+pub fn check_unrestricted_terminal_access(
+  server: &Server,
+  user: &User,
+) -> anyhow::Result<()> {
+  if user.admin || !server.config.restrict_terminals {
+    Ok(())
+  } else {
+    Err(anyhow!(
+      "Terminals on Server {} are restricted to terminal profiles",
+      server.name
+    ))
+  }
+}
+
+/// Ensures container exec is not disabled on the Server's Periphery,
+/// failing early with a clear error before any session is opened.
+pub async fn check_container_exec_enabled(
+  server: &Server,
+) -> anyhow::Result<()> {
+  if get_system_info(server).await?.container_exec_disabled {
+    Err(anyhow!(
+      "Container exec is disabled on Server {}",
+      server.name
+    ))
+  } else {
+    Ok(())
+  }
+}
+
+/// Ensures the user may connect to an existing terminal on the Server.
+/// On restricted Servers, non-admin users may only connect to
+/// terminals created from a profile they are allowed to use.
+pub async fn check_terminal_access(
+  server: &Server,
+  user: &User,
+  terminal: &str,
+) -> anyhow::Result<()> {
+  if check_unrestricted_terminal_access(server, user).is_ok() {
+    return Ok(());
+  }
+  let profile = periphery_client(server).await?
+    .request(periphery_client::api::terminal::ListTerminals {})
+    .await
+    .context("Failed to list terminals on periphery")?
+    .into_iter()
+    .find(|t| t.name == terminal)
+    .with_context(|| format!("No terminal at {terminal}"))?
+    .profile
+    .context(
+      "Terminal was not created from a terminal profile, and terminals on this Server are restricted",
+    )?;
+  get_terminal_profile(server, &profile, user)
+    .await
+    .map(|_| ())
+}
+
+/// Whether the user must be attached to the terminal read-only.
+/// Users without at least Execute permissions on the resource
+/// can only observe the session, regardless of what they request.
+pub async fn terminal_read_only<T: KomodoResource>(
+  user: &User,
+  resource_id: &str,
+  requested: bool,
+) -> anyhow::Result<bool> {
+  if requested {
+    return Ok(true);
+  }
+  let permission =
+    get_user_permission_on_resource::<T>(user, resource_id).await?;
+  Ok(permission.level < PermissionLevel::Execute)
+}
@@ -3,27 +3,34 @@ use database::mungos::{
   by_id::{find_one_by_id, update_one_by_id},
   mongodb::bson::to_document,
 };
-use komodo_client::entities::{
-  Operation, ResourceTarget,
-  action::Action,
-  alerter::Alerter,
-  build::Build,
-  deployment::Deployment,
-  komodo_timestamp,
-  procedure::Procedure,
-  repo::Repo,
-  server::Server,
-  stack::Stack,
-  sync::ResourceSync,
-  update::{Update, UpdateListItem},
-  user::User,
+use komodo_client::{
+  api::execute::PlanExecution,
+  entities::{
+    Operation, ResourceTarget,
+    action::Action,
+    alerter::Alerter,
+    build::Build,
+    deployment::Deployment,
+    komodo_timestamp,
+    procedure::Procedure,
+    repo::Repo,
+    server::Server,
+    stack::Stack,
+    swarm::SwarmService,
+    sync::ResourceSync,
+    update::{Update, UpdateListItem},
+    user::User,
+  },
 };
 
 use crate::{
   api::execute::ExecuteRequest, resource, state::db_client,
 };
 
-use super::channel::update_channel;
+use super::{
+  artifact::offload_large_logs, channel::update_channel,
+  rerun::get_rerun_update,
+};
 
 pub fn make_update(
   target: impl Into<ResourceTarget>,
@@ -76,7 +83,8 @@ pub async fn add_update_without_send(
 }
 
 #[instrument(level = "debug")]
-pub async fn update_update(update: Update) -> anyhow::Result<()> {
+pub async fn update_update(mut update: Update) -> anyhow::Result<()> {
+  offload_large_logs(&mut update).await;
   update_one_by_id(&db_client().updates, &update.id, database::mungos::update::Update::Set(to_document(&update)?), None)
     .await
     .context("failed to update the update on db. the update build process was deleted")?;
@@ -110,6 +118,7 @@ async fn update_list_item(
     status: update.status,
     version: update.version,
     other_data: update.other_data,
+    rerun_of: update.rerun_of,
     username,
   };
   Ok(update)
@@ -125,7 +134,31 @@ pub async fn init_execution_update(
   request: &ExecuteRequest,
   user: &User,
 ) -> anyhow::Result<Update> {
-  let (operation, target) = match &request {
+  let (operation, target) =
+    execution_operation_target(request).await?;
+
+  let mut update = make_update(target, operation, user);
+  if let ExecuteRequest::RerunUpdate(data) = request {
+    update.rerun_of = data.update.clone();
+  }
+  update.in_progress();
+
+  // Hold off on even adding update for DeployStackIfChanged
+  if !matches!(&request, ExecuteRequest::DeployStackIfChanged(_)) {
+    // Don't actually send it here, let the handlers send it after they can set action state.
+    update.id = add_update_without_send(&update).await?;
+  }
+
+  Ok(update)
+}
+
+/// Gets the operation and target of the execution.
+/// Batch executions target the Core system, and make
+/// an Update for each execution in addition to their own.
+pub async fn execution_operation_target(
+  request: &ExecuteRequest,
+) -> anyhow::Result<(Operation, ResourceTarget)> {
+  let res = match &request {
     // Server
     ExecuteRequest::StartContainer(data) => (
       Operation::StartContainer,
@@ -253,6 +286,12 @@ pub async fn init_execution_update(
         resource::get::<Server>(&data.server).await?.id,
       ),
     ),
+    ExecuteRequest::RunScheduledCommand(data) => (
+      Operation::RunScheduledCommand,
+      ResourceTarget::Server(
+        resource::get::<Server>(&data.server).await?.id,
+      ),
+    ),
 
     // Deployment
     ExecuteRequest::Deploy(data) => (
@@ -261,8 +300,8 @@ pub async fn init_execution_update(
         resource::get::<Deployment>(&data.deployment).await?.id,
       ),
     ),
-    ExecuteRequest::BatchDeploy(_data) => {
-      return Ok(Default::default());
+    ExecuteRequest::BatchDeploy(_) => {
+      (Operation::BatchDeploy, ResourceTarget::system())
     }
     ExecuteRequest::PullDeployment(data) => (
       Operation::PullDeployment,
@@ -306,10 +345,30 @@ pub async fn init_execution_update(
         resource::get::<Deployment>(&data.deployment).await?.id,
       ),
     ),
-    ExecuteRequest::BatchDestroyDeployment(_data) => {
-      return Ok(Default::default());
+    ExecuteRequest::BatchDestroyDeployment(_) => {
+      (Operation::BatchDestroyDeployment, ResourceTarget::system())
     }
 
+    // Swarm Service
+    ExecuteRequest::DeploySwarmService(data) => (
+      Operation::DeploySwarmService,
+      ResourceTarget::SwarmService(
+        resource::get::<SwarmService>(&data.swarm_service).await?.id,
+      ),
+    ),
+    ExecuteRequest::ScaleSwarmService(data) => (
+      Operation::ScaleSwarmService,
+      ResourceTarget::SwarmService(
+        resource::get::<SwarmService>(&data.swarm_service).await?.id,
+      ),
+    ),
+    ExecuteRequest::RemoveSwarmService(data) => (
+      Operation::RemoveSwarmService,
+      ResourceTarget::SwarmService(
+        resource::get::<SwarmService>(&data.swarm_service).await?.id,
+      ),
+    ),
+
     // Build
     ExecuteRequest::RunBuild(data) => (
       Operation::RunBuild,
@@ -317,8 +376,8 @@ pub async fn init_execution_update(
         resource::get::<Build>(&data.build).await?.id,
       ),
     ),
-    ExecuteRequest::BatchRunBuild(_data) => {
-      return Ok(Default::default());
+    ExecuteRequest::BatchRunBuild(_) => {
+      (Operation::BatchRunBuild, ResourceTarget::system())
     }
     ExecuteRequest::CancelBuild(data) => (
       Operation::CancelBuild,
@@ -334,8 +393,8 @@ pub async fn init_execution_update(
         resource::get::<Repo>(&data.repo).await?.id,
       ),
     ),
-    ExecuteRequest::BatchCloneRepo(_data) => {
-      return Ok(Default::default());
+    ExecuteRequest::BatchCloneRepo(_) => {
+      (Operation::BatchCloneRepo, ResourceTarget::system())
     }
     ExecuteRequest::PullRepo(data) => (
       Operation::PullRepo,
@@ -343,17 +402,23 @@ pub async fn init_execution_update(
         resource::get::<Repo>(&data.repo).await?.id,
       ),
     ),
-    ExecuteRequest::BatchPullRepo(_data) => {
-      return Ok(Default::default());
+    ExecuteRequest::BatchPullRepo(_) => {
+      (Operation::BatchPullRepo, ResourceTarget::system())
     }
+    ExecuteRequest::RunRepoPipeline(data) => (
+      Operation::RunRepoPipeline,
+      ResourceTarget::Repo(
+        resource::get::<Repo>(&data.repo).await?.id,
+      ),
+    ),
     ExecuteRequest::BuildRepo(data) => (
       Operation::BuildRepo,
       ResourceTarget::Repo(
         resource::get::<Repo>(&data.repo).await?.id,
       ),
     ),
-    ExecuteRequest::BatchBuildRepo(_data) => {
-      return Ok(Default::default());
+    ExecuteRequest::BatchBuildRepo(_) => {
+      (Operation::BatchBuildRepo, ResourceTarget::system())
     }
     ExecuteRequest::CancelRepoBuild(data) => (
       Operation::CancelRepoBuild,
@@ -370,7 +435,7 @@ pub async fn init_execution_update(
       ),
     ),
     ExecuteRequest::BatchRunProcedure(_) => {
-      return Ok(Default::default());
+      (Operation::BatchRunProcedure, ResourceTarget::system())
     }
 
     // Action
@@ -381,7 +446,7 @@ pub async fn init_execution_update(
       ),
     ),
     ExecuteRequest::BatchRunAction(_) => {
-      return Ok(Default::default());
+      (Operation::BatchRunAction, ResourceTarget::system())
     }
 
     // Resource Sync
@@ -392,6 +457,25 @@ pub async fn init_execution_update(
       ),
     ),
 
+    // Rerun, recorded as the original operation
+    ExecuteRequest::RerunUpdate(data) => {
+      let update = get_rerun_update(&data.update).await?;
+      (update.operation, update.target)
+    }
+
+    // Plan
+    ExecuteRequest::Plan(data) => (
+      Operation::Plan,
+      match &data.execution {
+        PlanExecution::RunSync(data) => ResourceTarget::ResourceSync(
+          resource::get::<ResourceSync>(&data.sync).await?.id,
+        ),
+        PlanExecution::DeployStack(data) => ResourceTarget::Stack(
+          resource::get::<Stack>(&data.stack).await?.id,
+        ),
+      },
+    ),
+
     // Stack
     ExecuteRequest::DeployStack(data) => (
       if !data.services.is_empty() {
@@ -403,8 +487,8 @@ pub async fn init_execution_update(
         resource::get::<Stack>(&data.stack).await?.id,
       ),
     ),
-    ExecuteRequest::BatchDeployStack(_data) => {
-      return Ok(Default::default());
+    ExecuteRequest::BatchDeployStack(_) => {
+      (Operation::BatchDeployStack, ResourceTarget::system())
     }
     ExecuteRequest::DeployStackIfChanged(data) => (
       Operation::DeployStack,
@@ -412,9 +496,10 @@ pub async fn init_execution_update(
         resource::get::<Stack>(&data.stack).await?.id,
       ),
     ),
-    ExecuteRequest::BatchDeployStackIfChanged(_data) => {
-      return Ok(Default::default());
-    }
+    ExecuteRequest::BatchDeployStackIfChanged(_) => (
+      Operation::BatchDeployStackIfChanged,
+      ResourceTarget::system(),
+    ),
     ExecuteRequest::StartStack(data) => (
       if !data.services.is_empty() {
         Operation::StartStackService
@@ -435,8 +520,8 @@ pub async fn init_execution_update(
         resource::get::<Stack>(&data.stack).await?.id,
       ),
     ),
-    ExecuteRequest::BatchPullStack(_data) => {
-      return Ok(Default::default());
+    ExecuteRequest::BatchPullStack(_) => {
+      (Operation::BatchPullStack, ResourceTarget::system())
     }
     ExecuteRequest::RestartStack(data) => (
       if !data.services.is_empty() {
@@ -488,8 +573,8 @@ pub async fn init_execution_update(
         resource::get::<Stack>(&data.stack).await?.id,
       ),
     ),
-    ExecuteRequest::BatchDestroyStack(_data) => {
-      return Ok(Default::default());
+    ExecuteRequest::BatchDestroyStack(_) => {
+      (Operation::BatchDestroyStack, ResourceTarget::system())
     }
 
     ExecuteRequest::RunStackService(data) => (
@@ -498,6 +583,12 @@ pub async fn init_execution_update(
         resource::get::<Stack>(&data.stack).await?.id,
       ),
     ),
+    ExecuteRequest::ScaleStackService(data) => (
+      Operation::ScaleStackService,
+      ResourceTarget::Stack(
+        resource::get::<Stack>(&data.stack).await?.id,
+      ),
+    ),
 
     // Alerter
     ExecuteRequest::TestAlerter(data) => (
@@ -521,15 +612,5 @@ pub async fn init_execution_update(
       (Operation::GlobalAutoUpdate, ResourceTarget::system())
     }
   };
-
-  let mut update = make_update(target, operation, user);
-  update.in_progress();
-
-  // Hold off on even adding update for DeployStackIfChanged
-  if !matches!(&request, ExecuteRequest::DeployStackIfChanged(_)) {
-    // Don't actually send it here, let the handlers send it after they can set action state.
-    update.id = add_update_without_send(&update).await?;
-  }
-
-  Ok(update)
+  Ok(res)
 }
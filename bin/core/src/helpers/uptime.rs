@@ -0,0 +1,252 @@
+use std::{
+  collections::{HashMap, HashSet},
+  sync::{Mutex, OnceLock},
+};
+
+use anyhow::Context;
+use async_timing_util::{Timelength, wait_until_timelength};
+use database::mungos::{find::find_collect, mongodb::bson::doc};
+use komodo_client::{
+  api::execute::{
+    Deploy, DeployStack, StartDeployment, StartStack, StopDeployment,
+    StopStack,
+  },
+  entities::{
+    MaintenanceWindow, ResourceTarget, deployment::DeploymentState,
+    komodo_timestamp, stack::StackState,
+  },
+};
+
+use crate::{
+  api::execute::ExecuteRequest,
+  schedule::run_scheduler_execution,
+  state::{db_client, deployment_status_cache, stack_status_cache},
+};
+
+use super::maintenance::is_in_maintenance;
+
+/// State changes made by the scheduler take some time to be
+/// reflected in the status cache. Mismatches are only counted as
+/// manual overrides after this long.
+const OVERRIDE_GRACE_MS: i64 = 2 * 60_000;
+
+#[derive(Default)]
+struct UptimeState {
+  /// Whether the resource was in its uptime hours at the last check.
+  in_window: bool,
+  /// Unix ms of the last window transition.
+  transitioned_at: i64,
+  /// Whether the resource was manually started / stopped
+  /// against its uptime hours since the last transition.
+  overridden: bool,
+}
+
+/// Resource target -> UptimeState
+fn uptime_states()
+-> &'static Mutex<HashMap<ResourceTarget, UptimeState>> {
+  static UPTIME_STATES: OnceLock<
+    Mutex<HashMap<ResourceTarget, UptimeState>>,
+  > = OnceLock::new();
+  UPTIME_STATES.get_or_init(Default::default)
+}
+
+/// Whether the resource was manually started / stopped
+/// against its `uptime_hours`.
+pub fn uptime_override(target: &ResourceTarget) -> bool {
+  uptime_states()
+    .lock()
+    .unwrap()
+    .get(target)
+    .map(|state| state.overridden)
+    .unwrap_or_default()
+}
+
+pub fn deployment_is_up(state: DeploymentState) -> bool {
+  matches!(
+    state,
+    DeploymentState::Running
+      | DeploymentState::Unhealthy
+      | DeploymentState::Restarting
+      | DeploymentState::Paused
+  )
+}
+
+pub fn stack_is_up(state: StackState) -> bool {
+  matches!(
+    state,
+    StackState::Running
+      | StackState::Restarting
+      | StackState::Paused
+      | StackState::Unhealthy
+  )
+}
+
+pub fn spawn_uptime_loop() {
+  tokio::spawn(async move {
+    loop {
+      wait_until_timelength(Timelength::OneMinute, 0).await;
+      if let Err(e) = enforce_uptime_hours().await {
+        error!("Failed to enforce uptime hours | {e:#}");
+      }
+    }
+  });
+}
+
+async fn enforce_uptime_hours() -> anyhow::Result<()> {
+  let (deployments, stacks) = tokio::try_join!(
+    find_collect(
+      &db_client().deployments,
+      doc! { "config.uptime_hours.0": { "$exists": true } },
+      None,
+    ),
+    find_collect(
+      &db_client().stacks,
+      doc! { "config.uptime_hours.0": { "$exists": true } },
+      None,
+    ),
+  )
+  .context("Failed to get uptime managed resources from db")?;
+
+  let now = komodo_timestamp();
+  let mut targets = HashSet::new();
+
+  for deployment in deployments {
+    let target = ResourceTarget::Deployment(deployment.id.clone());
+    targets.insert(target.clone());
+    let state = deployment_status_cache()
+      .get(&deployment.id)
+      .await
+      .unwrap_or_default()
+      .curr
+      .state;
+    let Some(start) = check_uptime(
+      target,
+      &deployment.config.uptime_hours,
+      deployment_is_up(state),
+      now,
+    ) else {
+      continue;
+    };
+    let request = match (start, state) {
+      (true, DeploymentState::NotDeployed) => {
+        ExecuteRequest::Deploy(Deploy {
+          deployment: deployment.id,
+          stop_signal: None,
+          stop_time: None,
+          inputs: None,
+        })
+      }
+      (true, _) => ExecuteRequest::StartDeployment(StartDeployment {
+        deployment: deployment.id,
+      }),
+      (false, _) => ExecuteRequest::StopDeployment(StopDeployment {
+        deployment: deployment.id,
+        signal: None,
+        time: None,
+      }),
+    };
+    tokio::spawn(run_uptime_execution(
+      request,
+      deployment.name,
+      start,
+    ));
+  }
+
+  for stack in stacks {
+    let target = ResourceTarget::Stack(stack.id.clone());
+    targets.insert(target.clone());
+    let state = stack_status_cache()
+      .get(&stack.id)
+      .await
+      .unwrap_or_default()
+      .curr
+      .state;
+    let Some(start) = check_uptime(
+      target,
+      &stack.config.uptime_hours,
+      stack_is_up(state),
+      now,
+    ) else {
+      continue;
+    };
+    let request = match (start, state) {
+      (true, StackState::Down) => {
+        ExecuteRequest::DeployStack(DeployStack {
+          stack: stack.id,
+          services: Vec::new(),
+          stop_time: None,
+        })
+      }
+      (true, _) => ExecuteRequest::StartStack(StartStack {
+        stack: stack.id,
+        services: Vec::new(),
+      }),
+      (false, _) => ExecuteRequest::StopStack(StopStack {
+        stack: stack.id,
+        stop_time: None,
+        services: Vec::new(),
+      }),
+    };
+    tokio::spawn(run_uptime_execution(request, stack.name, start));
+  }
+
+  // Clear out state for resources which no longer use uptime hours
+  uptime_states()
+    .lock()
+    .unwrap()
+    .retain(|target, _| targets.contains(target));
+
+  Ok(())
+}
+
+/// Returns Some(true) if the resource should be started,
+/// Some(false) if it should be stopped, or None to leave it alone.
+///
+/// Resources are only started / stopped when a window begins or ends,
+/// so manual changes in between are left in place until the next one.
+fn check_uptime(
+  target: ResourceTarget,
+  hours: &[MaintenanceWindow],
+  up: bool,
+  now: i64,
+) -> Option<bool> {
+  let in_window = is_in_maintenance(hours, now);
+  let mut states = uptime_states().lock().unwrap();
+  let Some(state) = states.get_mut(&target) else {
+    // Don't act on the first check, as the transition
+    // may have been missed while Core was down.
+    states.insert(
+      target,
+      UptimeState {
+        in_window,
+        transitioned_at: now,
+        overridden: false,
+      },
+    );
+    return None;
+  };
+  if state.in_window != in_window {
+    state.in_window = in_window;
+    state.transitioned_at = now;
+    state.overridden = false;
+    return (in_window != up).then_some(in_window);
+  }
+  if now - state.transitioned_at >= OVERRIDE_GRACE_MS {
+    state.overridden = in_window != up;
+  }
+  None
+}
+
+async fn run_uptime_execution(
+  request: ExecuteRequest,
+  name: String,
+  start: bool,
+) {
+  let action = if start { "start" } else { "stop" };
+  info!("Uptime hours {action} triggered for {name}");
+  run_scheduler_execution(
+    request,
+    &format!("uptime hours {action} of {name}"),
+  )
+  .await
+}
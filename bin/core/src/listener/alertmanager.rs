@@ -0,0 +1,308 @@
+use std::{collections::HashMap, str::FromStr};
+
+use anyhow::{Context, anyhow};
+use axum::http::{HeaderMap, header};
+use database::mungos::{
+  find::find_collect,
+  mongodb::bson::{doc, oid::ObjectId},
+};
+use komodo_client::entities::{
+  ResourceTarget, ResourceTargetVariant,
+  alert::{Alert, AlertData, SeverityLevel},
+  komodo_timestamp,
+  resource::Resource,
+};
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serror::AddStatusCodeError;
+
+use crate::{
+  alert::send_alerts,
+  config::core_config,
+  helpers::secrets_match,
+  state::{all_resources_cache, db_client},
+};
+
+/// The Alertmanager webhook payload.
+/// Only the fields used by Komodo are parsed.
+/// https://prometheus.io/docs/alerting/latest/configuration/#webhook_config
+#[derive(Deserialize)]
+struct AlertmanagerPayload {
+  #[serde(default)]
+  alerts: Vec<AlertmanagerAlert>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AlertmanagerAlert {
+  /// firing | resolved
+  status: String,
+  #[serde(default)]
+  labels: HashMap<String, String>,
+  #[serde(default)]
+  annotations: HashMap<String, String>,
+  #[serde(default, rename = "generatorURL")]
+  generator_url: String,
+  #[serde(default)]
+  fingerprint: String,
+}
+
+pub fn auth_alertmanager_webhook(
+  headers: &HeaderMap,
+) -> serror::Result<()> {
+  let config = core_config();
+  if !config.alertmanager_enabled {
+    return Err(
+      anyhow!("Alertmanager webhooks are not enabled")
+        .status_code(StatusCode::NOT_FOUND),
+    );
+  }
+  let expected = if config.alertmanager_token.is_empty() {
+    config.webhook_secret.as_str()
+  } else {
+    config.alertmanager_token.as_str()
+  };
+  if expected.is_empty() {
+    return Err(
+      anyhow!(
+        "Alertmanager webhooks are enabled, but no alertmanager_token or webhook_secret is configured"
+      )
+      .status_code(StatusCode::UNAUTHORIZED),
+    );
+  }
+  let authorized = headers
+    .get(header::AUTHORIZATION)
+    .and_then(|value| value.to_str().ok())
+    .and_then(|value| value.strip_prefix("Bearer "))
+    .map(|token| secrets_match(token.trim(), expected))
+    .unwrap_or_default();
+  if authorized {
+    Ok(())
+  } else {
+    Err(
+      anyhow!("Invalid Alertmanager token")
+        .status_code(StatusCode::UNAUTHORIZED),
+    )
+  }
+}
+
+/// Opens alerts which are firing, and resolves the
+/// open alerts Alertmanager reports as resolved.
+pub async fn handle_alertmanager_webhook(
+  body: String,
+) -> anyhow::Result<()> {
+  let payload = serde_json::from_str::<AlertmanagerPayload>(&body)
+    .context("Failed to parse Alertmanager payload")?;
+
+  let fingerprints = payload
+    .alerts
+    .iter()
+    .map(|alert| alert.fingerprint.as_str())
+    .collect::<Vec<_>>();
+  let open = find_collect(
+    &db_client().alerts,
+    doc! {
+      "resolved": false,
+      "data.type": "Prometheus",
+      "data.data.fingerprint": { "$in": fingerprints },
+    },
+    None,
+  )
+  .await
+  .context("Failed to query db for open Prometheus alerts")?
+  .into_iter()
+  .filter_map(|alert| match &alert.data {
+    AlertData::Prometheus { fingerprint, .. } => {
+      Some((fingerprint.clone(), alert))
+    }
+    _ => None,
+  })
+  .collect::<HashMap<_, _>>();
+
+  let ts = komodo_timestamp();
+  let mut to_open = Vec::new();
+  let mut to_resolve = Vec::new();
+
+  for alert in payload.alerts {
+    if alert.fingerprint.is_empty() {
+      warn!("Alertmanager alert is missing fingerprint, skipping");
+      continue;
+    }
+    match (alert.status.as_str(), open.get(&alert.fingerprint)) {
+      // Already open, Alertmanager resends firing alerts on repeat_interval
+      ("firing", Some(_)) => {}
+      ("firing", None) => to_open.push(Alert {
+        id: Default::default(),
+        ts,
+        resolved: false,
+        level: severity_level(&alert.labels),
+        target: match_target(&alert.labels),
+        data: AlertData::Prometheus {
+          name: alert
+            .labels
+            .get("alertname")
+            .cloned()
+            .unwrap_or_default(),
+          summary: alert
+            .annotations
+            .get("summary")
+            .cloned()
+            .unwrap_or_default(),
+          description: alert
+            .annotations
+            .get("description")
+            .cloned()
+            .unwrap_or_default(),
+          fingerprint: alert.fingerprint,
+          labels: alert.labels,
+          generator_url: alert.generator_url,
+        },
+        resolved_ts: None,
+        notifications: Default::default(),
+      }),
+      ("resolved", Some(open)) => to_resolve.push(open.clone()),
+      ("resolved", None) => {}
+      (status, _) => {
+        warn!(
+          "Unknown Alertmanager alert status '{status}', skipping"
+        )
+      }
+    }
+  }
+
+  open_alerts(to_open).await?;
+  resolve_alerts(to_resolve, ts).await
+}
+
+async fn open_alerts(mut alerts: Vec<Alert>) -> anyhow::Result<()> {
+  if alerts.is_empty() {
+    return Ok(());
+  }
+  let ids = db_client()
+    .alerts
+    .insert_many(&alerts)
+    .await
+    .context("Failed to open Prometheus alerts on db")?
+    .inserted_ids;
+  for (index, id) in ids {
+    if let (Some(alert), Some(id)) =
+      (alerts.get_mut(index), id.as_object_id())
+    {
+      alert.id = id.to_string();
+    }
+  }
+  send_alerts(&alerts).await;
+  Ok(())
+}
+
+async fn resolve_alerts(
+  mut alerts: Vec<Alert>,
+  ts: i64,
+) -> anyhow::Result<()> {
+  if alerts.is_empty() {
+    return Ok(());
+  }
+  let ids = alerts
+    .iter()
+    .map(|alert| {
+      ObjectId::from_str(&alert.id)
+        .context("Failed to convert alert id to ObjectId")
+    })
+    .collect::<anyhow::Result<Vec<_>>>()?;
+  db_client()
+    .alerts
+    .update_many(
+      doc! { "_id": { "$in": ids } },
+      doc! { "$set": { "resolved": true, "resolved_ts": ts } },
+    )
+    .await
+    .context("Failed to resolve Prometheus alerts on db")?;
+  for alert in &mut alerts {
+    alert.resolved = true;
+    alert.resolved_ts = Some(ts);
+    alert.level = SeverityLevel::Ok;
+  }
+  send_alerts(&alerts).await;
+  Ok(())
+}
+
+/// Uses the common `severity` label convention.
+fn severity_level(labels: &HashMap<String, String>) -> SeverityLevel {
+  match labels
+    .get("severity")
+    .map(|severity| severity.to_lowercase())
+    .as_deref()
+  {
+    Some("critical" | "error" | "page") => SeverityLevel::Critical,
+    _ => SeverityLevel::Warning,
+  }
+}
+
+/// Finds the resource identified by the configured target labels,
+/// otherwise attaches the alert to System.
+fn match_target(labels: &HashMap<String, String>) -> ResourceTarget {
+  let resources = all_resources_cache().load();
+  for (label, variant) in &core_config().alertmanager_target_labels {
+    let Some(value) = labels.get(label) else {
+      continue;
+    };
+    let target = match variant {
+      ResourceTargetVariant::System => None,
+      ResourceTargetVariant::Server => {
+        find_id(&resources.servers, value).map(ResourceTarget::Server)
+      }
+      ResourceTargetVariant::Stack => {
+        find_id(&resources.stacks, value).map(ResourceTarget::Stack)
+      }
+      ResourceTargetVariant::Deployment => {
+        find_id(&resources.deployments, value)
+          .map(ResourceTarget::Deployment)
+      }
+      ResourceTargetVariant::SwarmService => {
+        find_id(&resources.swarm_services, value)
+          .map(ResourceTarget::SwarmService)
+      }
+      ResourceTargetVariant::Build => {
+        find_id(&resources.builds, value).map(ResourceTarget::Build)
+      }
+      ResourceTargetVariant::Repo => {
+        find_id(&resources.repos, value).map(ResourceTarget::Repo)
+      }
+      ResourceTargetVariant::Procedure => {
+        find_id(&resources.procedures, value)
+          .map(ResourceTarget::Procedure)
+      }
+      ResourceTargetVariant::Action => {
+        find_id(&resources.actions, value).map(ResourceTarget::Action)
+      }
+      ResourceTargetVariant::Builder => {
+        find_id(&resources.builders, value)
+          .map(ResourceTarget::Builder)
+      }
+      ResourceTargetVariant::Alerter => {
+        find_id(&resources.alerters, value)
+          .map(ResourceTarget::Alerter)
+      }
+      ResourceTargetVariant::ResourceSync => {
+        find_id(&resources.syncs, value)
+          .map(ResourceTarget::ResourceSync)
+      }
+    };
+    if let Some(target) = target {
+      return target;
+    }
+  }
+  ResourceTarget::system()
+}
+
+fn find_id<Config: Default, Info: Default>(
+  resources: &HashMap<String, Resource<Config, Info>>,
+  id_or_name: &str,
+) -> Option<String> {
+  resources
+    .values()
+    .find(|resource| {
+      resource.id == id_or_name || resource.name == id_or_name
+    })
+    .map(|resource| resource.id.clone())
+}
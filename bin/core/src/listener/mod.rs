@@ -1,12 +1,13 @@
 use std::sync::Arc;
 
 use anyhow::anyhow;
-use axum::{Router, http::HeaderMap};
+use axum::{Router, http::HeaderMap, routing::post};
 use komodo_client::entities::resource::Resource;
 use tokio::sync::Mutex;
 
 use crate::{helpers::cache::Cache, resource::KomodoResource};
 
+mod alertmanager;
 mod integrations;
 mod resources;
 mod router;
@@ -17,6 +18,15 @@ pub fn router() -> Router {
   Router::new()
     .nest("/github", router::router::<github::Github>())
     .nest("/gitlab", router::router::<gitlab::Gitlab>())
+    .route(
+      "/alertmanager",
+      post(|headers: HeaderMap, body: String| async move {
+        alertmanager::auth_alertmanager_webhook(&headers)?;
+        alertmanager::handle_alertmanager_webhook(body)
+          .await
+          .map_err(serror::Error::from)
+      }),
+    )
 }
 
 type ListenerLockCache = Cache<String, Arc<Mutex<()>>>;
@@ -51,8 +51,15 @@ pub async fn handle_build_webhook<B: super::ExtractBranch>(
   // Acquire and hold lock to make a task queue for
   // subsequent listener calls on same resource.
   // It would fail if we let it go through from action state busy.
+  // Builds in a concurrency group wait in line on the group's
+  // resource lock instead, so waiting runs can be superseded
+  // by newer pushes.
   let lock = build_locks().get_or_insert_default(&build.id).await;
-  let _lock = lock.lock().await;
+  let _lock = if build.config.concurrency_group.is_empty() {
+    Some(lock.lock().await)
+  } else {
+    None
+  };
 
   B::verify_branch(&body, &build.config.branch)?;
 
@@ -148,12 +155,33 @@ impl RepoExecution for BuildRepo {
   }
 }
 
+impl RepoExecution for RunRepoPipeline {
+  async fn resolve(repo: Repo) -> anyhow::Result<()> {
+    let user = git_webhook_user().to_owned();
+    let req = crate::api::execute::ExecuteRequest::RunRepoPipeline(
+      RunRepoPipeline { repo: repo.id },
+    );
+    let update = init_execution_update(&req, &user).await?;
+    let crate::api::execute::ExecuteRequest::RunRepoPipeline(req) =
+      req
+    else {
+      unreachable!()
+    };
+    req
+      .resolve(&ExecuteArgs { user, update })
+      .await
+      .map_err(|e| e.error)?;
+    Ok(())
+  }
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum RepoWebhookOption {
   Clone,
   Pull,
   Build,
+  Pipeline,
 }
 
 pub async fn handle_repo_webhook<B: super::ExtractBranch>(
@@ -171,6 +199,10 @@ pub async fn handle_repo_webhook<B: super::ExtractBranch>(
     RepoWebhookOption::Build => {
       handle_repo_webhook_inner::<B, BuildRepo>(repo, body).await
     }
+    RepoWebhookOption::Pipeline => {
+      handle_repo_webhook_inner::<B, RunRepoPipeline>(repo, body)
+        .await
+    }
   }
 }
 
@@ -445,6 +477,7 @@ pub async fn handle_procedure_webhook<B: super::ExtractBranch>(
   let user = git_webhook_user().to_owned();
   let req = ExecuteRequest::RunProcedure(RunProcedure {
     procedure: procedure.id,
+    inputs: None,
   });
   let update = init_execution_update(&req, &user).await?;
   let ExecuteRequest::RunProcedure(req) = req else {
@@ -1,10 +1,13 @@
 #[macro_use]
 extern crate tracing;
 
-use std::{net::SocketAddr, str::FromStr};
+use std::{
+  net::{IpAddr, SocketAddr},
+  str::FromStr,
+};
 
 use anyhow::Context;
-use axum::Router;
+use axum::{Router, middleware};
 use axum_server::{Handle, tls_rustls::RustlsConfig};
 use tower_http::{
   cors::{Any, CorsLayer},
@@ -20,6 +23,7 @@ mod cloud;
 mod config;
 mod helpers;
 mod listener;
+mod metrics;
 mod monitor;
 mod network;
 mod permission;
@@ -75,7 +79,18 @@ async fn app() -> anyhow::Result<()> {
   resource::spawn_procedure_state_refresh_loop();
   resource::spawn_action_state_refresh_loop();
   schedule::spawn_schedule_executor();
+  cloud::aws::warm_pool::spawn_warm_pool_manager();
   helpers::prune::spawn_prune_loop();
+  helpers::provider_health::spawn_provider_health_loop();
+  alert::spawn_digest_loop();
+  helpers::auto_stop::spawn_auto_stop_loop();
+  helpers::execution_queue::spawn_execution_queue_loop();
+  helpers::uptime::spawn_uptime_loop();
+  helpers::execution_metrics::spawn_execution_metrics_loop();
+  helpers::federation::spawn_federation_refresh_loop();
+  helpers::access_request::spawn_access_expiry_loop();
+  stack::health::spawn_stack_health_loop();
+  stack::runtime_drift::spawn_stack_runtime_drift_loop();
 
   // Setup static frontend services
   let frontend_path = &config.frontend_path;
@@ -86,27 +101,39 @@ async fn app() -> anyhow::Result<()> {
 
   let app = Router::new()
     .nest("/auth", api::auth::router())
+    .nest("/artifact", api::artifact::router())
+    .nest("/stack_file", api::stack_file::router())
     .nest("/user", api::user::router())
     .nest("/read", api::read::router())
     .nest("/write", api::write::router())
     .nest("/execute", api::execute::router())
     .nest("/terminal", api::terminal::router())
+    .nest("/log", api::log::router())
     .nest("/listener", listener::router())
     .nest("/ws", ws::router())
     .nest("/client", ts_client::router())
+    .nest("/metrics", metrics::router())
     .fallback_service(serve_frontend)
+    .layer(middleware::map_response(api::core_version_header))
     .layer(
       CorsLayer::new()
         .allow_origin(Any)
         .allow_methods(Any)
         .allow_headers(Any),
     )
-    .into_make_service();
+    // Client ip is recorded in the audit log
+    .into_make_service_with_connect_info::<SocketAddr>();
 
-  let addr =
-    format!("{}:{}", core_config().bind_ip, core_config().port);
-  let socket_addr = SocketAddr::from_str(&addr)
-    .context("failed to parse listen address")?;
+  // Accepts IPv6 bind ips with or without brackets
+  let bind_ip = core_config()
+    .bind_ip
+    .trim_start_matches('[')
+    .trim_end_matches(']');
+  let socket_addr = SocketAddr::new(
+    IpAddr::from_str(bind_ip)
+      .with_context(|| format!("invalid bind ip: {bind_ip}"))?,
+    core_config().port,
+  );
 
   let handle = Handle::new();
   tokio::spawn({
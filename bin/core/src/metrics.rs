@@ -0,0 +1,521 @@
+use std::{collections::HashMap, fmt::Write};
+
+use anyhow::{Context, anyhow};
+use axum::{
+  Router,
+  http::{HeaderMap, HeaderValue, header},
+  routing::get,
+};
+use database::mungos::{
+  find::find_collect,
+  mongodb::{bson::doc, options::FindOneOptions},
+};
+use derive_variants::ExtractVariant;
+use komodo_client::entities::{
+  Operation,
+  alert::{Alert, SeverityLevel},
+  update::UpdateStatus,
+};
+use reqwest::StatusCode;
+use serror::AddStatusCodeError;
+
+use crate::{
+  config::core_config,
+  helpers::secrets_match,
+  state::{
+    all_resources_cache, db_client, deployment_status_cache,
+    server_status_cache, stack_status_cache,
+  },
+};
+
+pub fn router() -> Router {
+  Router::new().route("/", get(serve_metrics))
+}
+
+async fn serve_metrics(
+  headers: HeaderMap,
+) -> serror::Result<(HeaderMap, String)> {
+  let config = core_config();
+  if !config.metrics_enabled {
+    return Err(
+      anyhow!("Metrics are not enabled")
+        .status_code(StatusCode::NOT_FOUND),
+    );
+  }
+  if config.metrics_token.is_empty() {
+    return Err(
+      anyhow!(
+        "Metrics are enabled, but no metrics_token is configured"
+      )
+      .status_code(StatusCode::UNAUTHORIZED),
+    );
+  }
+  let authorized = headers
+    .get(header::AUTHORIZATION)
+    .and_then(|value| value.to_str().ok())
+    .and_then(|value| value.strip_prefix("Bearer "))
+    .map(|token| secrets_match(token.trim(), &config.metrics_token))
+    .unwrap_or_default();
+  if !authorized {
+    return Err(
+      anyhow!("Invalid metrics token")
+        .status_code(StatusCode::UNAUTHORIZED),
+    );
+  }
+
+  let mut metrics = Metrics::default();
+  write_server_metrics(&mut metrics).await;
+  write_deployment_metrics(&mut metrics).await;
+  write_stack_metrics(&mut metrics).await;
+  write_build_metrics(&mut metrics).await?;
+  write_alert_metrics(&mut metrics).await?;
+  write_update_metrics(&mut metrics).await?;
+
+  let mut headers = HeaderMap::new();
+  headers.insert(
+    header::CONTENT_TYPE,
+    HeaderValue::from_static("text/plain; version=0.0.4"),
+  );
+  Ok((headers, metrics.0))
+}
+
+async fn write_server_metrics(metrics: &mut Metrics) {
+  let resources = all_resources_cache().load_full();
+  let mut statuses = server_status_cache().get_list().await;
+  statuses.sort_by(|a, b| a.id.cmp(&b.id));
+
+  metrics.header(
+    "komodo_server_state",
+    "gauge",
+    "The current state of the Server.",
+  );
+  for status in &statuses {
+    let Some(server) = resources.servers.get(&status.id) else {
+      continue;
+    };
+    metrics.sample(
+      "komodo_server_state",
+      &[
+        ("id", &server.id),
+        ("name", &server.name),
+        ("state", &status.state.to_string()),
+      ],
+      1.0,
+    );
+  }
+
+  metrics.header(
+    "komodo_server_health",
+    "gauge",
+    "The Server health level. 0 = ok, 1 = warning, 2 = critical.",
+  );
+  for status in &statuses {
+    let (Some(server), Some(health)) =
+      (resources.servers.get(&status.id), &status.health)
+    else {
+      continue;
+    };
+    let mut disks = health.disks.iter().collect::<Vec<_>>();
+    disks.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let components = [
+      ("cpu", &health.cpu, None),
+      ("mem", &health.mem, None),
+      ("network", &health.network, None),
+      ("disk_io", &health.disk_io, None),
+    ]
+    .into_iter()
+    .chain(disks.into_iter().map(|(mount, health)| {
+      ("disk", health, Some(mount.display().to_string()))
+    }));
+    for (component, health, mount) in components {
+      let mut labels = vec![
+        ("id", server.id.as_str()),
+        ("name", server.name.as_str()),
+        ("component", component),
+      ];
+      if let Some(mount) = &mount {
+        labels.push(("mount", mount));
+      }
+      metrics.sample(
+        "komodo_server_health",
+        &labels,
+        severity_value(health.level),
+      );
+    }
+  }
+
+  let stats = statuses
+    .iter()
+    .filter_map(|status| {
+      Some((
+        resources.servers.get(&status.id)?,
+        status.stats.as_ref()?,
+      ))
+    })
+    .collect::<Vec<_>>();
+
+  metrics.header(
+    "komodo_server_cpu_percent",
+    "gauge",
+    "The Server CPU usage in percent.",
+  );
+  for (server, stats) in &stats {
+    metrics.sample(
+      "komodo_server_cpu_percent",
+      &[("id", &server.id), ("name", &server.name)],
+      stats.cpu_perc as f64,
+    );
+  }
+
+  metrics.header(
+    "komodo_server_memory_used_gigabytes",
+    "gauge",
+    "The Server memory usage in GB.",
+  );
+  for (server, stats) in &stats {
+    metrics.sample(
+      "komodo_server_memory_used_gigabytes",
+      &[("id", &server.id), ("name", &server.name)],
+      stats.mem_used_gb,
+    );
+  }
+
+  metrics.header(
+    "komodo_server_memory_total_gigabytes",
+    "gauge",
+    "The Server total memory in GB.",
+  );
+  for (server, stats) in &stats {
+    metrics.sample(
+      "komodo_server_memory_total_gigabytes",
+      &[("id", &server.id), ("name", &server.name)],
+      stats.mem_total_gb,
+    );
+  }
+
+  metrics.header(
+    "komodo_server_disk_used_gigabytes",
+    "gauge",
+    "The Server disk usage in GB, per mount.",
+  );
+  for (server, stats) in &stats {
+    for disk in &stats.disks {
+      metrics.sample(
+        "komodo_server_disk_used_gigabytes",
+        &[
+          ("id", &server.id),
+          ("name", &server.name),
+          ("mount", &disk.mount.display().to_string()),
+        ],
+        disk.used_gb,
+      );
+    }
+  }
+
+  metrics.header(
+    "komodo_server_disk_total_gigabytes",
+    "gauge",
+    "The Server disk size in GB, per mount.",
+  );
+  for (server, stats) in &stats {
+    for disk in &stats.disks {
+      metrics.sample(
+        "komodo_server_disk_total_gigabytes",
+        &[
+          ("id", &server.id),
+          ("name", &server.name),
+          ("mount", &disk.mount.display().to_string()),
+        ],
+        disk.total_gb,
+      );
+    }
+  }
+
+  metrics.header(
+    "komodo_server_network_ingress_bytes",
+    "gauge",
+    "The Server network ingress over the last polling interval.",
+  );
+  for (server, stats) in &stats {
+    metrics.sample(
+      "komodo_server_network_ingress_bytes",
+      &[("id", &server.id), ("name", &server.name)],
+      stats.network_ingress_bytes,
+    );
+  }
+
+  metrics.header(
+    "komodo_server_network_egress_bytes",
+    "gauge",
+    "The Server network egress over the last polling interval.",
+  );
+  for (server, stats) in &stats {
+    metrics.sample(
+      "komodo_server_network_egress_bytes",
+      &[("id", &server.id), ("name", &server.name)],
+      stats.network_egress_bytes,
+    );
+  }
+
+  metrics.header(
+    "komodo_server_containers",
+    "gauge",
+    "The number of containers on the Server, per container state.",
+  );
+  for status in &statuses {
+    let (Some(server), Some(containers)) =
+      (resources.servers.get(&status.id), &status.containers)
+    else {
+      continue;
+    };
+    let mut counts = HashMap::<String, usize>::new();
+    for container in containers {
+      *counts.entry(container.state.to_string()).or_default() += 1;
+    }
+    let mut counts = counts.into_iter().collect::<Vec<_>>();
+    counts.sort();
+    for (state, count) in counts {
+      metrics.sample(
+        "komodo_server_containers",
+        &[
+          ("id", &server.id),
+          ("name", &server.name),
+          ("state", &state),
+        ],
+        count as f64,
+      );
+    }
+  }
+}
+
+async fn write_deployment_metrics(metrics: &mut Metrics) {
+  let resources = all_resources_cache().load_full();
+  let mut statuses = deployment_status_cache().get_list().await;
+  statuses.sort_by(|a, b| a.curr.id.cmp(&b.curr.id));
+  metrics.header(
+    "komodo_deployment_state",
+    "gauge",
+    "The current state of the Deployment container.",
+  );
+  for status in statuses {
+    let Some(deployment) = resources.deployments.get(&status.curr.id)
+    else {
+      continue;
+    };
+    metrics.sample(
+      "komodo_deployment_state",
+      &[
+        ("id", &deployment.id),
+        ("name", &deployment.name),
+        ("server_id", &deployment.config.server_id),
+        ("state", &status.curr.state.to_string()),
+      ],
+      1.0,
+    );
+  }
+}
+
+async fn write_stack_metrics(metrics: &mut Metrics) {
+  let resources = all_resources_cache().load_full();
+  let mut statuses = stack_status_cache().get_list().await;
+  statuses.sort_by(|a, b| a.curr.id.cmp(&b.curr.id));
+  metrics.header(
+    "komodo_stack_state",
+    "gauge",
+    "The current state of the Stack.",
+  );
+  for status in statuses {
+    let Some(stack) = resources.stacks.get(&status.curr.id) else {
+      continue;
+    };
+    metrics.sample(
+      "komodo_stack_state",
+      &[
+        ("id", &stack.id),
+        ("name", &stack.name),
+        ("server_id", &stack.config.server_id),
+        ("state", &status.curr.state.to_string()),
+      ],
+      1.0,
+    );
+  }
+}
+
+async fn write_build_metrics(
+  metrics: &mut Metrics,
+) -> anyhow::Result<()> {
+  let resources = all_resources_cache().load_full();
+  let mut builds = resources.builds.values().collect::<Vec<_>>();
+  builds.sort_by(|a, b| a.id.cmp(&b.id));
+
+  let mut latest = Vec::with_capacity(builds.len());
+  for build in builds {
+    let update = db_client()
+      .updates
+      .find_one(doc! {
+        "target.type": "Build",
+        "target.id": &build.id,
+        "operation": Operation::RunBuild.to_string(),
+        "status": UpdateStatus::Complete.to_string(),
+      })
+      .with_options(
+        FindOneOptions::builder()
+          .sort(doc! { "start_ts": -1 })
+          .build(),
+      )
+      .await
+      .context("Failed to query latest build update")?;
+    if let Some(update) = update {
+      latest.push((build, update));
+    }
+  }
+
+  metrics.header(
+    "komodo_build_last_duration_seconds",
+    "gauge",
+    "The duration of the latest completed run of the Build.",
+  );
+  for (build, update) in &latest {
+    let Some(end_ts) = update.end_ts else {
+      continue;
+    };
+    metrics.sample(
+      "komodo_build_last_duration_seconds",
+      &[("id", &build.id), ("name", &build.name)],
+      (end_ts - update.start_ts) as f64 / 1000.0,
+    );
+  }
+
+  metrics.header(
+    "komodo_build_last_success",
+    "gauge",
+    "Whether the latest completed run of the Build succeeded.",
+  );
+  for (build, update) in &latest {
+    metrics.sample(
+      "komodo_build_last_success",
+      &[("id", &build.id), ("name", &build.name)],
+      if update.success { 1.0 } else { 0.0 },
+    );
+  }
+
+  metrics.header(
+    "komodo_build_last_timestamp_seconds",
+    "gauge",
+    "The start time of the latest completed run of the Build.",
+  );
+  for (build, update) in &latest {
+    metrics.sample(
+      "komodo_build_last_timestamp_seconds",
+      &[("id", &build.id), ("name", &build.name)],
+      update.start_ts as f64 / 1000.0,
+    );
+  }
+
+  Ok(())
+}
+
+async fn write_alert_metrics(
+  metrics: &mut Metrics,
+) -> anyhow::Result<()> {
+  let alerts = find_collect(
+    &db_client().alerts,
+    doc! { "resolved": false },
+    None,
+  )
+  .await
+  .context("Failed to query open alerts")?;
+  let mut counts = HashMap::<(String, String), usize>::new();
+  for Alert { level, data, .. } in alerts {
+    *counts
+      .entry((
+        level.to_string().to_lowercase(),
+        format!("{:?}", data.extract_variant()),
+      ))
+      .or_default() += 1;
+  }
+  let mut counts = counts.into_iter().collect::<Vec<_>>();
+  counts.sort();
+  metrics.header(
+    "komodo_alerts_open",
+    "gauge",
+    "The number of unresolved alerts, per level and alert type.",
+  );
+  for ((level, alert_type), count) in counts {
+    metrics.sample(
+      "komodo_alerts_open",
+      &[("level", &level), ("type", &alert_type)],
+      count as f64,
+    );
+  }
+  Ok(())
+}
+
+async fn write_update_metrics(
+  metrics: &mut Metrics,
+) -> anyhow::Result<()> {
+  metrics.header(
+    "komodo_updates",
+    "gauge",
+    "The number of queued and in progress updates.",
+  );
+  for (status, label) in [
+    (UpdateStatus::Queued, "queued"),
+    (UpdateStatus::InProgress, "in_progress"),
+  ] {
+    let count = db_client()
+      .updates
+      .count_documents(doc! { "status": status.to_string() })
+      .await
+      .context("Failed to count updates")?;
+    metrics.sample(
+      "komodo_updates",
+      &[("status", label)],
+      count as f64,
+    );
+  }
+  Ok(())
+}
+
+fn severity_value(level: SeverityLevel) -> f64 {
+  match level {
+    SeverityLevel::Ok => 0.0,
+    SeverityLevel::Warning => 1.0,
+    SeverityLevel::Critical => 2.0,
+  }
+}
+
+/// Writes the Prometheus text exposition format.
+#[derive(Default)]
+struct Metrics(String);
+
+impl Metrics {
+  fn header(&mut self, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(self.0, "# HELP {name} {help}");
+    let _ = writeln!(self.0, "# TYPE {name} {kind}");
+  }
+
+  fn sample(
+    &mut self,
+    name: &str,
+    labels: &[(&str, &str)],
+    value: f64,
+  ) {
+    self.0.push_str(name);
+    if !labels.is_empty() {
+      self.0.push('{');
+      for (i, (key, value)) in labels.iter().enumerate() {
+        if i > 0 {
+          self.0.push(',');
+        }
+        let value = value
+          .replace('\\', "\\\\")
+          .replace('"', "\\\"")
+          .replace('\n', "\\n");
+        let _ = write!(self.0, "{key}=\"{value}\"");
+      }
+      self.0.push('}');
+    }
+    let _ = writeln!(self.0, " {value}");
+  }
+}
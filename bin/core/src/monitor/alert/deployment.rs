@@ -59,29 +59,56 @@ pub async fn alert_deployments(
       else {
         continue;
       };
-      if !deployment.config.send_alerts {
-        continue;
-      }
       let target: ResourceTarget = (&deployment).into();
-      let data = AlertData::ContainerStateChange {
-        id: status.curr.id.clone(),
-        name: deployment.name,
-        server_name: server_names
-          .get(&deployment.config.server_id)
-          .cloned()
-          .unwrap_or(String::from("unknown")),
-        server_id: deployment.config.server_id,
-        from: prev,
-        to: status.curr.state,
+      let server_name = server_names
+        .get(&deployment.config.server_id)
+        .cloned()
+        .unwrap_or(String::from("unknown"));
+      let (level, data) = if status.curr.state
+        == DeploymentState::Unhealthy
+      {
+        if !deployment.config.send_unhealthy_alerts {
+          continue;
+        }
+        let data = AlertData::ContainerUnhealthy {
+          id: status.curr.id.clone(),
+          name: deployment.name,
+          server_name,
+          server_id: deployment.config.server_id,
+        };
+        (SeverityLevel::Critical, data)
+      } else if prev.is_running() && status.curr.state.is_running() {
+        // Recovered from Unhealthy, the container was running throughout.
+        continue;
+      } else {
+        if !deployment.config.send_alerts
+          || (!deployment.config.alert_states.is_empty()
+            && !deployment
+              .config
+              .alert_states
+              .contains(&status.curr.state))
+        {
+          continue;
+        }
+        let data = AlertData::ContainerStateChange {
+          id: status.curr.id.clone(),
+          name: deployment.name,
+          server_name,
+          server_id: deployment.config.server_id,
+          from: prev,
+          to: status.curr.state,
+        };
+        (SeverityLevel::Warning, data)
       };
       let alert = Alert {
         id: Default::default(),
-        level: SeverityLevel::Warning,
+        level,
         resolved: true,
         resolved_ts: ts.into(),
         target,
         data,
         ts,
+        notifications: Default::default(),
       };
       alerts.push(alert);
     }
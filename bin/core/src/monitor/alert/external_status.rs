@@ -0,0 +1,173 @@
+use std::{collections::HashMap, str::FromStr};
+
+use anyhow::Context;
+use database::mungos::{
+  find::find_collect,
+  mongodb::bson::{doc, oid::ObjectId, to_bson},
+};
+use komodo_client::entities::{
+  ResourceTarget,
+  alert::{Alert, AlertData, SeverityLevel},
+  external_status::ExternalState,
+};
+
+use crate::{
+  alert::send_alerts, helpers::maintenance::is_in_maintenance,
+  state::db_client,
+};
+
+#[instrument(level = "debug")]
+pub async fn alert_external_statuses(ts: i64) {
+  if let Err(e) = alert_external_statuses_inner(ts).await {
+    error!("Failed to check external status alerts | {e:#}");
+  }
+}
+
+async fn alert_external_statuses_inner(
+  ts: i64,
+) -> anyhow::Result<()> {
+  let statuses =
+    find_collect(&db_client().external_statuses, None, None)
+      .await
+      .context("Failed to query db for external statuses")?;
+
+  let mut open_alerts = find_collect(
+    &db_client().alerts,
+    doc! { "resolved": false, "data.type": "ExternalStatus" },
+    None,
+  )
+  .await
+  .context("Failed to query db for open external status alerts")?
+  .into_iter()
+  .filter_map(|alert| match &alert.data {
+    AlertData::ExternalStatus { name, .. } => {
+      Some((name.clone(), alert))
+    }
+    _ => None,
+  })
+  .collect::<HashMap<_, _>>();
+
+  let mut alerts_to_open = Vec::new();
+  let mut alerts_to_update = Vec::new();
+  let mut alerts_to_close = Vec::new();
+
+  for status in statuses {
+    let state = status.current_state(ts);
+    let level = match state {
+      ExternalState::Ok => None,
+      ExternalState::Warning | ExternalState::Unknown => {
+        Some(SeverityLevel::Warning)
+      }
+      ExternalState::Critical => Some(SeverityLevel::Critical),
+    };
+    let data = AlertData::ExternalStatus {
+      name: status.name.clone(),
+      state,
+      message: status.message.clone(),
+    };
+    match (level, open_alerts.remove(&status.name)) {
+      (Some(level), None) => {
+        if !status.send_alerts
+          || is_in_maintenance(&status.maintenance_windows, ts)
+        {
+          continue;
+        }
+        alerts_to_open.push(Alert {
+          id: Default::default(),
+          ts,
+          resolved: false,
+          resolved_ts: None,
+          level,
+          target: ResourceTarget::system(),
+          data,
+          notifications: Default::default(),
+        });
+      }
+      (Some(level), Some(mut alert)) => {
+        // Only send the update if the severity changed
+        let send = alert.level != level;
+        if send
+          || !matches!(
+            &alert.data,
+            AlertData::ExternalStatus { state: prev, message, .. }
+              if *prev == state && *message == status.message
+          )
+        {
+          alert.level = level;
+          alert.data = data;
+          alerts_to_update.push((alert, send));
+        }
+      }
+      (None, Some(mut alert)) => {
+        alert.resolved = true;
+        alert.resolved_ts = Some(ts);
+        alert.level = SeverityLevel::Ok;
+        alert.data = data;
+        alerts_to_close.push(alert);
+      }
+      (None, None) => {}
+    }
+  }
+
+  if !alerts_to_open.is_empty() {
+    let ids = db_client()
+      .alerts
+      .insert_many(&alerts_to_open)
+      .await
+      .context("Failed to open external status alerts on db")?
+      .inserted_ids;
+    for (index, id) in ids {
+      if let (Some(alert), Some(id)) =
+        (alerts_to_open.get_mut(index), id.as_object_id())
+      {
+        alert.id = id.to_string();
+      }
+    }
+    send_alerts(&alerts_to_open).await;
+  }
+
+  let mut to_send = Vec::new();
+  for (alert, send) in alerts_to_update {
+    let id = ObjectId::from_str(&alert.id)
+      .context("Failed to convert alert id to ObjectId")?;
+    db_client()
+      .alerts
+      .update_one(
+        doc! { "_id": id },
+        doc! { "$set": {
+          "level": to_bson(&alert.level)
+            .context("Failed to serialize alert level")?,
+          "data": to_bson(&alert.data)
+            .context("Failed to serialize alert data")?,
+        } },
+      )
+      .await
+      .context("Failed to update external status alert on db")?;
+    if send {
+      to_send.push(alert);
+    }
+  }
+
+  if !alerts_to_close.is_empty() {
+    let ids = alerts_to_close
+      .iter()
+      .map(|alert| {
+        ObjectId::from_str(&alert.id)
+          .context("Failed to convert alert id to ObjectId")
+      })
+      .collect::<anyhow::Result<Vec<_>>>()?;
+    db_client()
+      .alerts
+      .update_many(
+        doc! { "_id": { "$in": ids } },
+        doc! { "$set": { "resolved": true, "resolved_ts": ts } },
+      )
+      .await
+      .context("Failed to resolve external status alerts on db")?;
+    to_send.extend(alerts_to_close);
+  }
+
+  send_alerts(&to_send).await;
+
+  Ok(())
+}
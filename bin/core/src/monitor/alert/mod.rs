@@ -6,9 +6,10 @@ use komodo_client::entities::{
   server::Server, user::User,
 };
 
-use crate::resource;
+use crate::{helpers::query::inherit_server_profiles, resource};
 
 mod deployment;
+mod external_status;
 mod server;
 mod stack;
 
@@ -26,7 +27,9 @@ pub async fn check_alerts(ts: i64) {
   tokio::join!(
     server::alert_servers(ts, servers),
     deployment::alert_deployments(ts, &server_names),
-    stack::alert_stacks(ts, &server_names)
+    stack::alert_stacks(ts, &server_names),
+    external_status::alert_external_statuses(ts),
+    crate::alert::escalate_open_alerts(ts)
   );
 }
 
@@ -34,7 +37,7 @@ pub async fn check_alerts(ts: i64) {
 async fn get_all_servers_map()
 -> anyhow::Result<(HashMap<String, Server>, HashMap<String, String>)>
 {
-  let servers = resource::list_full_for_user::<Server>(
+  let mut servers = resource::list_full_for_user::<Server>(
     ResourceQuery::default(),
     &User {
       admin: true,
@@ -46,6 +49,8 @@ async fn get_all_servers_map()
   .await
   .context("failed to get servers from db (in alert_servers)")?;
 
+  inherit_server_profiles(&mut servers).await?;
+
   let servers = servers
     .into_iter()
     .map(|server| (server.id.clone(), server))
@@ -18,6 +18,7 @@ use komodo_client::entities::{
   alert::{Alert, AlertData, AlertDataVariant, SeverityLevel},
   komodo_timestamp, optional_string,
   server::{Server, ServerState},
+  stats::bytes_to_mb_per_sec,
 };
 
 use crate::{
@@ -134,6 +135,7 @@ pub async fn alert_servers(
               region: optional_string(&server.config.region),
               err: server_status.err.clone(),
             },
+            notifications: Default::default(),
           };
           alerts_to_open
             .push((alert, server.config.send_unreachable_alerts))
@@ -214,6 +216,7 @@ pub async fn alert_servers(
               server_version: server_status.version.clone(),
               core_version: core_version.to_string(),
             },
+            notifications: Default::default(),
           };
           // Use send_unreachable_alerts as a proxy for general server alerts
           alerts_to_open
@@ -287,6 +290,7 @@ pub async fn alert_servers(
                 .map(|s| s.cpu_perc as f64)
                 .unwrap_or(0.0),
             },
+            notifications: Default::default(),
           };
           alerts_to_open.push((alert, server.config.send_cpu_alerts));
         }
@@ -371,6 +375,7 @@ pub async fn alert_servers(
                 .map(|s| s.mem_used_gb)
                 .unwrap_or(0.0),
             },
+            notifications: Default::default(),
           };
           alerts_to_open.push((alert, server.config.send_mem_alerts));
         }
@@ -426,6 +431,161 @@ pub async fn alert_servers(
         .reset(server_status.id.clone(), AlertDataVariant::ServerMem),
     }
 
+    // ===================
+    // SERVER NETWORK
+    // ===================
+    let network_data = || {
+      let (ingress, egress, polling_rate) = server_status
+        .stats
+        .as_ref()
+        .map(|s| {
+          (
+            s.network_ingress_bytes,
+            s.network_egress_bytes,
+            s.polling_rate,
+          )
+        })
+        .unwrap_or_default();
+      AlertData::ServerNetwork {
+        id: server_status.id.clone(),
+        name: server.name.clone(),
+        region: optional_string(&server.config.region),
+        ingress_mb_per_sec: bytes_to_mb_per_sec(
+          ingress,
+          polling_rate,
+        ),
+        egress_mb_per_sec: bytes_to_mb_per_sec(egress, polling_rate),
+      }
+    };
+    let network_alert = server_alerts
+      .as_ref()
+      .and_then(|alerts| alerts.get(&AlertDataVariant::ServerNetwork))
+      .cloned();
+    match (
+      health.network.level,
+      network_alert,
+      health.network.should_close_alert,
+    ) {
+      (SeverityLevel::Warning | SeverityLevel::Critical, None, _) => {
+        // Only open network alert if not in maintenance and buffer is ready
+        if !in_maintenance
+          && buffer.ready_to_open(
+            server_status.id.clone(),
+            AlertDataVariant::ServerNetwork,
+          )
+        {
+          let alert = Alert {
+            id: Default::default(),
+            ts,
+            resolved: false,
+            resolved_ts: None,
+            level: health.network.level,
+            target: ResourceTarget::Server(server_status.id.clone()),
+            data: network_data(),
+            notifications: Default::default(),
+          };
+          alerts_to_open
+            .push((alert, server.config.send_network_alerts));
+        }
+      }
+      (
+        SeverityLevel::Warning | SeverityLevel::Critical,
+        Some(mut alert),
+        _,
+      ) => {
+        // modify alert level only if it has increased and not in maintenance
+        if !in_maintenance && alert.level < health.network.level {
+          alert.level = health.network.level;
+          alert.data = network_data();
+          alerts_to_update
+            .push((alert, server.config.send_network_alerts));
+        }
+      }
+      (SeverityLevel::Ok, Some(mut alert), true) => {
+        alert.data = network_data();
+        alert_ids_to_close
+          .push((alert, server.config.send_network_alerts))
+      }
+      (SeverityLevel::Ok, _, _) => buffer.reset(
+        server_status.id.clone(),
+        AlertDataVariant::ServerNetwork,
+      ),
+    }
+
+    // ===================
+    // SERVER DISK IO
+    // ===================
+    let disk_io_data = || {
+      let (read, write, polling_rate) = server_status
+        .stats
+        .as_ref()
+        .map(|s| {
+          (s.disk_read_bytes, s.disk_write_bytes, s.polling_rate)
+        })
+        .unwrap_or_default();
+      AlertData::ServerDiskIo {
+        id: server_status.id.clone(),
+        name: server.name.clone(),
+        region: optional_string(&server.config.region),
+        read_mb_per_sec: bytes_to_mb_per_sec(read, polling_rate),
+        write_mb_per_sec: bytes_to_mb_per_sec(write, polling_rate),
+      }
+    };
+    let disk_io_alert = server_alerts
+      .as_ref()
+      .and_then(|alerts| alerts.get(&AlertDataVariant::ServerDiskIo))
+      .cloned();
+    match (
+      health.disk_io.level,
+      disk_io_alert,
+      health.disk_io.should_close_alert,
+    ) {
+      (SeverityLevel::Warning | SeverityLevel::Critical, None, _) => {
+        // Only open disk IO alert if not in maintenance and buffer is ready
+        if !in_maintenance
+          && buffer.ready_to_open(
+            server_status.id.clone(),
+            AlertDataVariant::ServerDiskIo,
+          )
+        {
+          let alert = Alert {
+            id: Default::default(),
+            ts,
+            resolved: false,
+            resolved_ts: None,
+            level: health.disk_io.level,
+            target: ResourceTarget::Server(server_status.id.clone()),
+            data: disk_io_data(),
+            notifications: Default::default(),
+          };
+          alerts_to_open
+            .push((alert, server.config.send_disk_io_alerts));
+        }
+      }
+      (
+        SeverityLevel::Warning | SeverityLevel::Critical,
+        Some(mut alert),
+        _,
+      ) => {
+        // modify alert level only if it has increased and not in maintenance
+        if !in_maintenance && alert.level < health.disk_io.level {
+          alert.level = health.disk_io.level;
+          alert.data = disk_io_data();
+          alerts_to_update
+            .push((alert, server.config.send_disk_io_alerts));
+        }
+      }
+      (SeverityLevel::Ok, Some(mut alert), true) => {
+        alert.data = disk_io_data();
+        alert_ids_to_close
+          .push((alert, server.config.send_disk_io_alerts))
+      }
+      (SeverityLevel::Ok, _, _) => buffer.reset(
+        server_status.id.clone(),
+        AlertDataVariant::ServerDiskIo,
+      ),
+    }
+
     // ===================
     // SERVER DISK
     // ===================
@@ -474,6 +634,7 @@ pub async fn alert_servers(
                   .unwrap_or_default(),
                 used_gb: disk.map(|d| d.used_gb).unwrap_or_default(),
               },
+              notifications: Default::default(),
             };
             alerts_to_open
               .push((alert, server.config.send_disk_alerts));
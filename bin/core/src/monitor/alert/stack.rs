@@ -56,7 +56,10 @@ pub async fn alert_stacks(
       else {
         continue;
       };
-      if !stack.config.send_alerts {
+      if !stack.config.send_alerts
+        || (!stack.config.alert_states.is_empty()
+          && !stack.config.alert_states.contains(&status.curr.state))
+      {
         continue;
       }
       let target: ResourceTarget = (&stack).into();
@@ -79,6 +82,7 @@ pub async fn alert_stacks(
         target,
         data,
         ts,
+        notifications: Default::default(),
       };
       alerts.push(alert);
     }
@@ -1,3 +1,5 @@
+use std::path::Path;
+
 use komodo_client::entities::{
   alert::SeverityLevel,
   deployment::{Deployment, DeploymentState},
@@ -11,18 +13,22 @@ use komodo_client::entities::{
     ServerState,
   },
   stack::{ComposeProject, Stack, StackState},
-  stats::{SingleDiskUsage, SystemStats},
+  stats::{SingleDiskUsage, SystemStats, bytes_to_mb_per_sec},
+  swarm::{SwarmService, SwarmServiceState},
 };
 use serror::Serror;
 
-use crate::state::{
-  deployment_status_cache, repo_status_cache, server_status_cache,
-  stack_status_cache,
+use crate::{
+  helpers::execution_queue::spawn_queued_executions,
+  state::{
+    deployment_status_cache, repo_status_cache, server_status_cache,
+    stack_status_cache, swarm_service_status_cache,
+  },
 };
 
 use super::{
   CachedDeploymentStatus, CachedRepoStatus, CachedServerStatus,
-  CachedStackStatus, History,
+  CachedStackStatus, CachedSwarmServiceStatus, History,
 };
 
 #[instrument(level = "debug", skip_all)]
@@ -91,6 +97,33 @@ pub async fn insert_stacks_status_unknown(stacks: Vec<Stack>) {
   }
 }
 
+#[instrument(level = "debug", skip_all)]
+pub async fn insert_swarm_services_status_unknown(
+  swarm_services: Vec<SwarmService>,
+) {
+  let status_cache = swarm_service_status_cache();
+  for swarm_service in swarm_services {
+    let prev = status_cache
+      .get(&swarm_service.id)
+      .await
+      .map(|s| s.curr.state);
+    status_cache
+      .insert(
+        swarm_service.id.clone(),
+        History {
+          curr: CachedSwarmServiceStatus {
+            id: swarm_service.id,
+            state: SwarmServiceState::Unknown,
+            services: Vec::new(),
+          },
+          prev,
+        }
+        .into(),
+      )
+      .await;
+  }
+}
+
 type DockerLists = (
   Option<Vec<ContainerListItem>>,
   Option<Vec<NetworkListItem>>,
@@ -109,6 +142,11 @@ pub async fn insert_server_status(
   err: impl Into<Option<Serror>>,
 ) {
   let health = stats.as_ref().map(|s| get_server_health(server, s));
+  let reconnected = state == ServerState::Ok
+    && server_status_cache()
+      .get(&server.id)
+      .await
+      .is_none_or(|status| status.state != ServerState::Ok);
   server_status_cache()
     .insert(
       server.id.clone(),
@@ -128,6 +166,10 @@ pub async fn insert_server_status(
       .into(),
     )
     .await;
+  // Run anything queued while the Server was unreachable.
+  if reconnected {
+    spawn_queued_executions(server.id.clone());
+  }
 }
 
 const ALERT_PERCENTAGE_THRESHOLD: f32 = 5.0;
@@ -139,6 +181,11 @@ fn get_server_health(
     mem_used_gb,
     mem_total_gb,
     disks,
+    network_ingress_bytes,
+    network_egress_bytes,
+    disk_read_bytes,
+    disk_write_bytes,
+    polling_rate,
     ..
   }: &SystemStats,
 ) -> ServerHealth {
@@ -149,6 +196,11 @@ fn get_server_health(
     mem_critical,
     disk_warning,
     disk_critical,
+    disk_thresholds,
+    network_warning,
+    network_critical,
+    disk_io_warning,
+    disk_io_critical,
     ..
   } = &server.config;
   let mut health = ServerHealth::default();
@@ -180,6 +232,12 @@ fn get_server_health(
   } in disks
   {
     let perc = 100.0 * used_gb / total_gb;
+    // Mount specific thresholds override the server-wide ones
+    let (disk_warning, disk_critical) = disk_thresholds
+      .iter()
+      .find(|threshold| Path::new(&threshold.mount) == mount)
+      .map(|threshold| (&threshold.warning, &threshold.critical))
+      .unwrap_or((disk_warning, disk_critical));
     let mut state = ServerHealthState::default();
     if perc >= *disk_critical {
       state.level = SeverityLevel::Critical;
@@ -193,5 +251,40 @@ fn get_server_health(
     health.disks.insert(mount.clone(), state);
   }
 
+  let network = bytes_to_mb_per_sec(
+    network_ingress_bytes.max(*network_egress_bytes),
+    *polling_rate,
+  );
+  health.network =
+    throughput_health(network, *network_warning, *network_critical);
+
+  let disk_io = bytes_to_mb_per_sec(
+    disk_read_bytes.max(*disk_write_bytes),
+    *polling_rate,
+  );
+  health.disk_io =
+    throughput_health(disk_io, *disk_io_warning, *disk_io_critical);
+
   health
 }
+
+/// Thresholds of 0 are disabled.
+fn throughput_health(
+  mb_per_sec: f64,
+  warning: f64,
+  critical: f64,
+) -> ServerHealthState {
+  let mut state = ServerHealthState::default();
+  let lowest = if warning > 0.0 { warning } else { critical };
+  if critical > 0.0 && mb_per_sec >= critical {
+    state.level = SeverityLevel::Critical;
+  } else if warning > 0.0 && mb_per_sec >= warning {
+    state.level = SeverityLevel::Warning;
+  } else if lowest == 0.0
+    || mb_per_sec
+      < lowest * (1.0 - (ALERT_PERCENTAGE_THRESHOLD as f64) / 100.0)
+  {
+    state.should_close_alert = true;
+  }
+  state
+}
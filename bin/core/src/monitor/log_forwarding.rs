@@ -0,0 +1,129 @@
+use komodo_client::{
+  entities::{
+    deployment::Deployment,
+    docker::container::ContainerListItem,
+    log_forwarding::LogForwardingConfig,
+    server::Server,
+    stack::{Stack, StackServiceNames},
+  },
+  parsers::parse_key_value_list,
+};
+use periphery_client::{
+  PeripheryClient,
+  api::log_forwarding::{LogForward, SetLogForwarding},
+};
+
+use crate::stack::{
+  compose_container_match_regex,
+  services::extract_services_from_stack,
+};
+
+/// The container logs to forward from the server,
+/// from the `log_forwarding` config of its Deployments and Stacks.
+pub fn log_forwards(
+  server: &Server,
+  deployments: &[Deployment],
+  stacks: &[Stack],
+  containers: &[ContainerListItem],
+) -> Vec<LogForward> {
+  let mut forwards = Vec::new();
+
+  for deployment in deployments {
+    let config = &deployment.config.log_forwarding;
+    if !config.enabled
+      || !containers
+        .iter()
+        .any(|container| container.name == deployment.name)
+    {
+      continue;
+    }
+    let labels = vec![
+      ("komodo_server".to_string(), server.name.clone()),
+      ("komodo_deployment".to_string(), deployment.name.clone()),
+    ];
+    if let Some(forward) = log_forward(
+      config,
+      deployment.name.clone(),
+      labels,
+      &deployment.name,
+    ) {
+      forwards.push(forward);
+    }
+  }
+
+  for stack in stacks {
+    let config = &stack.config.log_forwarding;
+    if !config.enabled {
+      continue;
+    }
+    for StackServiceNames {
+      service_name,
+      container_name,
+      ..
+    } in extract_services_from_stack(stack)
+    {
+      let Ok(regex) = compose_container_match_regex(&container_name)
+      else {
+        continue;
+      };
+      for container in containers
+        .iter()
+        .filter(|container| regex.is_match(&container.name))
+      {
+        let labels = vec![
+          ("komodo_server".to_string(), server.name.clone()),
+          ("komodo_stack".to_string(), stack.name.clone()),
+          ("komodo_service".to_string(), service_name.clone()),
+        ];
+        if let Some(forward) = log_forward(
+          config,
+          container.name.clone(),
+          labels,
+          &stack.name,
+        ) {
+          forwards.push(forward);
+        }
+      }
+    }
+  }
+
+  forwards
+}
+
+fn log_forward(
+  config: &LogForwardingConfig,
+  container: String,
+  mut labels: Vec<(String, String)>,
+  resource: &str,
+) -> Option<LogForward> {
+  match parse_key_value_list(&config.labels) {
+    Ok(extra) => labels.extend(extra),
+    Err(e) => {
+      warn!("Invalid log forwarding labels on {resource} | {e:#}");
+      return None;
+    }
+  }
+  Some(LogForward {
+    container,
+    labels,
+    sink: config.sink.clone(),
+  })
+}
+
+/// Periphery forwards the given container logs,
+/// and stops forwarding any others.
+pub async fn set_log_forwarding(
+  periphery: &PeripheryClient,
+  server: &Server,
+  forwards: Vec<LogForward>,
+) {
+  if let Err(e) =
+    periphery.request(SetLogForwarding { forwards }).await
+  {
+    // Older Periphery versions don't support forwarding.
+    debug!(
+      "Failed to set log forwarding on server {} | {e:#}",
+      server.name
+    );
+  }
+}
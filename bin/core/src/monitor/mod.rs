@@ -11,29 +11,35 @@ use komodo_client::entities::{
     network::NetworkListItem, volume::VolumeListItem,
   },
   komodo_timestamp, optional_string,
-  server::{Server, ServerHealth, ServerState},
+  server::{Server, ServerHealth, ServerMode, ServerState},
   stack::{ComposeProject, StackService, StackState},
   stats::SystemStats,
+  swarm::{SwarmServiceState, SwarmServiceSummary},
+};
+use periphery_client::api::{
+  self, git::GetLatestCommit, swarm::ListSwarmStackServices,
 };
-use periphery_client::api::{self, git::GetLatestCommit};
 use serror::Serror;
 use tokio::sync::Mutex;
 
 use crate::{
   config::core_config,
-  helpers::{cache::Cache, periphery_client},
+  helpers::{
+    cache::Cache, periphery_client, query::inherit_server_profile,
+  },
   monitor::{alert::check_alerts, record::record_server_stats},
   state::{db_client, deployment_status_cache, repo_status_cache},
 };
 
 use self::helpers::{
   insert_deployments_status_unknown, insert_repos_status_unknown,
-  insert_server_status,
+  insert_server_status, insert_swarm_services_status_unknown,
 };
 
 mod alert;
 mod helpers;
 mod lists;
+mod log_forwarding;
 mod record;
 mod resources;
 
@@ -84,6 +90,16 @@ pub struct CachedStackStatus {
   pub services: Vec<StackService>,
 }
 
+#[derive(Default, Clone, Debug)]
+pub struct CachedSwarmServiceStatus {
+  /// The swarm service id
+  pub id: String,
+  /// The swarm service state
+  pub state: SwarmServiceState,
+  /// The services in the Swarm stack
+  pub services: Vec<SwarmServiceSummary>,
+}
+
 const ADDITIONAL_MS: u128 = 500;
 
 pub fn spawn_monitor_loop() {
@@ -154,7 +170,10 @@ pub async fn update_cache_for_server(server: &Server, force: bool) {
 
   *lock = now;
 
-  let (deployments, builds, repos, stacks) = tokio::join!(
+  // Health is checked against the thresholds inherited from the profile.
+  let server = &inherit_server_profile(server.clone()).await;
+
+  let (deployments, builds, repos, stacks, swarm_services) = tokio::join!(
     find_collect(
       &db_client().deployments,
       doc! { "config.server_id": &server.id },
@@ -170,6 +189,11 @@ pub async fn update_cache_for_server(server: &Server, force: bool) {
       &db_client().stacks,
       doc! { "config.server_id": &server.id },
       None,
+    ),
+    find_collect(
+      &db_client().swarm_services,
+      doc! { "config.server_id": &server.id },
+      None,
     )
   );
 
@@ -177,12 +201,14 @@ pub async fn update_cache_for_server(server: &Server, force: bool) {
   let builds =  builds.inspect_err(|e| error!("failed to get builds list from db (update status cache) | server : {} | {e:#}", server.name)).unwrap_or_default();
   let repos = repos.inspect_err(|e|  error!("failed to get repos list from db (update status cache) | server: {} | {e:#}", server.name)).unwrap_or_default();
   let stacks = stacks.inspect_err(|e|  error!("failed to get stacks list from db (update status cache) | server: {} | {e:#}", server.name)).unwrap_or_default();
+  let swarm_services = swarm_services.inspect_err(|e|  error!("failed to get swarm services list from db (update status cache) | server: {} | {e:#}", server.name)).unwrap_or_default();
 
   // Handle server disabled
   if !server.config.enabled {
     insert_deployments_status_unknown(deployments).await;
     insert_stacks_status_unknown(stacks).await;
     insert_repos_status_unknown(repos).await;
+    insert_swarm_services_status_unknown(swarm_services).await;
     insert_server_status(
       server,
       ServerState::Disabled,
@@ -195,19 +221,19 @@ pub async fn update_cache_for_server(server: &Server, force: bool) {
     return;
   }
 
-  let Ok(periphery) = periphery_client(server) else {
-    error!(
-      "somehow periphery not ok to create. should not be reached."
-    );
-    return;
-  };
-
-  let version = match periphery.request(api::GetVersion {}).await {
-    Ok(version) => version.version,
+  let (periphery, version) = match async {
+    let periphery = periphery_client(server).await?;
+    let version = periphery.request(api::GetVersion {}).await?;
+    anyhow::Ok((periphery, version.version))
+  }
+  .await
+  {
+    Ok(res) => res,
     Err(e) => {
       insert_deployments_status_unknown(deployments).await;
       insert_stacks_status_unknown(stacks).await;
       insert_repos_status_unknown(repos).await;
+      insert_swarm_services_status_unknown(swarm_services).await;
       insert_server_status(
         server,
         ServerState::NotOk,
@@ -221,13 +247,17 @@ pub async fn update_cache_for_server(server: &Server, force: bool) {
     }
   };
 
-  let stats = if server.config.stats_monitoring {
+  // System stats are only available through Periphery
+  let stats = if server.config.stats_monitoring
+    && matches!(server.config.mode, ServerMode::Periphery {})
+  {
     match periphery.request(api::stats::GetSystemStats {}).await {
       Ok(stats) => Some(filter_volumes(server, stats)),
       Err(e) => {
         insert_deployments_status_unknown(deployments).await;
         insert_stacks_status_unknown(stacks).await;
         insert_repos_status_unknown(repos).await;
+        insert_swarm_services_status_unknown(swarm_services).await;
         insert_server_status(
           server,
           ServerState::NotOk,
@@ -249,6 +279,18 @@ pub async fn update_cache_for_server(server: &Server, force: bool) {
       containers.iter_mut().for_each(|container| {
         container.server_id = Some(server.id.clone())
       });
+      // Docker API servers don't run Periphery to forward logs.
+      let log_forwards =
+        matches!(server.config.mode, ServerMode::Periphery {}).then(
+          || {
+            log_forwarding::log_forwards(
+              server,
+              &deployments,
+              &stacks,
+              &containers,
+            )
+          },
+        );
       tokio::join!(
         resources::update_deployment_cache(
           server.name.clone(),
@@ -279,6 +321,12 @@ pub async fn update_cache_for_server(server: &Server, force: bool) {
         None,
       )
       .await;
+      if let Some(forwards) = log_forwards {
+        log_forwarding::set_log_forwarding(
+          &periphery, server, forwards,
+        )
+        .await;
+      }
     }
     Err(e) => {
       insert_deployments_status_unknown(deployments).await;
@@ -295,6 +343,26 @@ pub async fn update_cache_for_server(server: &Server, force: bool) {
     }
   }
 
+  if !swarm_services.is_empty() {
+    let stacks = swarm_services
+      .iter()
+      .map(|swarm_service| swarm_service.name.clone())
+      .collect();
+    match periphery.request(ListSwarmStackServices { stacks }).await {
+      Ok(stacks) => {
+        resources::update_swarm_service_cache(swarm_services, stacks)
+          .await
+      }
+      Err(e) => {
+        warn!(
+          "Failed to list swarm services on server {} | {e:#}",
+          server.name
+        );
+        insert_swarm_services_status_unknown(swarm_services).await;
+      }
+    }
+  }
+
   let status_cache = repo_status_cache();
   for repo in repos {
     let (latest_hash, latest_message) = periphery
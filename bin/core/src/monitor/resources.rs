@@ -1,5 +1,5 @@
 use std::{
-  collections::HashSet,
+  collections::{HashMap, HashSet},
   sync::{Mutex, OnceLock},
 };
 
@@ -17,6 +17,7 @@ use komodo_client::{
     },
     komodo_timestamp,
     stack::{Stack, StackService, StackServiceNames, StackState},
+    swarm::{SwarmService, SwarmServiceState, SwarmServiceSummary},
     user::auto_redeploy_user,
   },
 };
@@ -26,16 +27,20 @@ use crate::{
   api::execute::{self, ExecuteRequest},
   helpers::query::get_stack_state_from_containers,
   stack::{
-    compose_container_match_regex,
+    compose_container_match_regex, health::stack_service_health,
+    runtime_drift::stack_service_drift,
     services::extract_services_from_stack,
   },
   state::{
     action_states, db_client, deployment_status_cache,
-    stack_status_cache,
+    stack_status_cache, swarm_service_status_cache,
   },
 };
 
-use super::{CachedDeploymentStatus, CachedStackStatus, History};
+use super::{
+  CachedDeploymentStatus, CachedStackStatus,
+  CachedSwarmServiceStatus, History,
+};
 
 fn deployment_alert_sent_cache() -> &'static Mutex<HashSet<String>> {
   static CACHE: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
@@ -61,7 +66,7 @@ pub async fn update_deployment_cache(
       .map(|s| s.curr.state);
     let state = container
       .as_ref()
-      .map(|c| c.state.into())
+      .map(DeploymentState::from)
       .unwrap_or(DeploymentState::NotDeployed);
     let image = match deployment.config.image {
       DeploymentImage::Build { build_id, version } => {
@@ -106,7 +111,7 @@ pub async fn update_deployment_cache(
 
     if update_available {
       if deployment.config.auto_update {
-        if state == DeploymentState::Running
+        if state.is_running()
           && !action_states()
             .deployment
             .get_or_insert_default(&deployment.id)
@@ -122,6 +127,7 @@ pub async fn update_deployment_cache(
                 deployment: deployment.name.clone(),
                 stop_time: None,
                 stop_signal: None,
+                inputs: None,
               }),
               auto_redeploy_user().to_owned(),
             )
@@ -143,6 +149,7 @@ pub async fn update_deployment_cache(
                     server_id: deployment.config.server_id,
                     image,
                   },
+                  notifications: Default::default(),
                 };
                 let res = db_client().alerts.insert_one(&alert).await;
                 if let Err(e) = res {
@@ -161,7 +168,7 @@ pub async fn update_deployment_cache(
             }
           });
         }
-      } else if state == DeploymentState::Running
+      } else if state.is_running()
         && deployment.config.send_alerts
         && !deployment_alert_sent_cache()
           .lock()
@@ -188,6 +195,7 @@ pub async fn update_deployment_cache(
             server_id: deployment.config.server_id,
             image,
           },
+          notifications: Default::default(),
         };
         let res = db_client().alerts.insert_one(&alert).await;
         if let Err(e) = res {
@@ -232,6 +240,14 @@ fn stack_alert_sent_cache()
   CACHE.get_or_init(Default::default)
 }
 
+/// (StackId, Service)
+fn stack_replicas_alert_sent_cache()
+-> &'static Mutex<HashSet<(String, String)>> {
+  static CACHE: OnceLock<Mutex<HashSet<(String, String)>>> =
+    OnceLock::new();
+  CACHE.get_or_init(Default::default)
+}
+
 pub async fn update_stack_cache(
   server_name: String,
   stacks: Vec<Stack>,
@@ -242,17 +258,18 @@ pub async fn update_stack_cache(
   for stack in stacks {
     let services = extract_services_from_stack(&stack);
     let mut services_with_containers = services.iter().map(|StackServiceNames { service_name, container_name, image }| {
-      let container = containers.iter().find(|container| {
-        match compose_container_match_regex(container_name)
-          .with_context(|| format!("failed to construct container name matching regex for service {service_name}")) 
-        {
-          Ok(regex) => regex,
-          Err(e) => {
-            warn!("{e:#}");
-            return false
-          }
-        }.is_match(&container.name)
-      }).cloned();
+      let regex = compose_container_match_regex(container_name)
+        .with_context(|| format!("failed to construct container name matching regex for service {service_name}"))
+        .inspect_err(|e| warn!("{e:#}"))
+        .ok();
+      let service_containers = containers.iter().filter(|container| {
+        regex.as_ref().map(|regex| regex.is_match(&container.name)).unwrap_or_default()
+      }).collect::<Vec<_>>();
+      let container = service_containers.first().cloned().cloned();
+      let running_replicas = service_containers
+        .iter()
+        .filter(|container| container.state == ContainerStateStatusEnum::Running)
+        .count() as i64;
       let image = if image.contains(':') {
         image.to_string()
       } else {
@@ -301,6 +318,7 @@ pub async fn update_stack_cache(
               service: service_name.clone(),
               image: image.clone(),
             },
+            notifications: Default::default(),
           };
           tokio::spawn(async move {
             let res = db_client().alerts.insert_one(&alert).await;
@@ -323,6 +341,10 @@ pub async fn update_stack_cache(
         image: image.clone(),
         container,
         update_available,
+        replicas: stack.config.service_replicas(service_name),
+        running_replicas,
+        health: stack_service_health(&stack, service_name),
+        drift: stack_service_drift(&stack.id, service_name),
       }
     }).collect::<Vec<_>>();
 
@@ -345,11 +367,23 @@ pub async fn update_stack_cache(
       }
     }
 
-    let state = get_stack_state_from_containers(
+    let mut state = get_stack_state_from_containers(
       &stack.config.ignore_services,
       &services,
       containers,
     );
+    // Failing application health checks make a running stack unhealthy.
+    if state == StackState::Running
+      && services_with_containers.iter().any(|service| {
+        service
+          .health
+          .as_ref()
+          .map(|health| !health.healthy)
+          .unwrap_or_default()
+      })
+    {
+      state = StackState::Unhealthy;
+    }
     if !services_to_update.is_empty()
       && stack.config.auto_update
       && state == StackState::Running
@@ -394,6 +428,7 @@ pub async fn update_stack_cache(
                 server_id: stack.config.server_id,
                 images: images_with_update,
               },
+              notifications: Default::default(),
             };
             let res = db_client().alerts.insert_one(&alert).await;
             if let Err(e) = res {
@@ -409,6 +444,21 @@ pub async fn update_stack_cache(
         }
       });
     }
+    if stack.config.send_alerts
+      && matches!(state, StackState::Running | StackState::Unhealthy)
+      && !action_states()
+        .stack
+        .get_or_insert_default(&stack.id)
+        .await
+        .busy()
+        .unwrap_or(true)
+    {
+      check_stack_replicas(
+        &stack,
+        &server_name,
+        &services_with_containers,
+      );
+    }
     services_with_containers
       .sort_by(|a, b| a.service.cmp(&b.service));
     let prev = stack_status_cache
@@ -425,3 +475,103 @@ pub async fn update_stack_cache(
       .await;
   }
 }
+
+/// Sends an alert when a service has fewer running replicas
+/// than configured. Only one alert is sent until it recovers.
+fn check_stack_replicas(
+  stack: &Stack,
+  server_name: &str,
+  services: &[StackService],
+) {
+  for service in services {
+    let Some(desired) = service.replicas else {
+      continue;
+    };
+    let key = (stack.id.clone(), service.service.clone());
+    if service.running_replicas >= desired {
+      stack_replicas_alert_sent_cache()
+        .lock()
+        .unwrap()
+        .remove(&key);
+      continue;
+    }
+    // Returns false if already sent
+    if !stack_replicas_alert_sent_cache()
+      .lock()
+      .unwrap()
+      .insert(key)
+    {
+      continue;
+    }
+    let ts = komodo_timestamp();
+    let alert = Alert {
+      id: Default::default(),
+      ts,
+      resolved: true,
+      resolved_ts: ts.into(),
+      level: SeverityLevel::Warning,
+      target: ResourceTarget::Stack(stack.id.clone()),
+      data: AlertData::StackServiceReplicasLow {
+        id: stack.id.clone(),
+        name: stack.name.clone(),
+        server_id: stack.config.server_id.clone(),
+        server_name: server_name.to_string(),
+        service: service.service.clone(),
+        running: service.running_replicas,
+        desired,
+      },
+      notifications: Default::default(),
+    };
+    tokio::spawn(async move {
+      let res = db_client().alerts.insert_one(&alert).await;
+      if let Err(e) = res {
+        error!(
+          "Failed to record StackServiceReplicasLow to db | {e:#}"
+        );
+      }
+      send_alerts(&[alert]).await;
+    });
+  }
+}
+
+pub async fn update_swarm_service_cache(
+  swarm_services: Vec<SwarmService>,
+  mut stacks: HashMap<String, Vec<SwarmServiceSummary>>,
+) {
+  let status_cache = swarm_service_status_cache();
+  for swarm_service in swarm_services {
+    let mut services =
+      stacks.remove(&swarm_service.name).unwrap_or_default();
+    services.sort_by(|a, b| a.name.cmp(&b.name));
+    let prev = status_cache
+      .get(&swarm_service.id)
+      .await
+      .map(|s| s.curr.state);
+    let status = CachedSwarmServiceStatus {
+      id: swarm_service.id.clone(),
+      state: get_swarm_service_state(&services),
+      services,
+    };
+    status_cache
+      .insert(swarm_service.id, History { curr: status, prev }.into())
+      .await;
+  }
+}
+
+fn get_swarm_service_state(
+  services: &[SwarmServiceSummary],
+) -> SwarmServiceState {
+  if services.is_empty() {
+    return SwarmServiceState::NotDeployed;
+  }
+  if services.iter().all(|service| service.desired == 0) {
+    return SwarmServiceState::Stopped;
+  }
+  if services
+    .iter()
+    .any(|service| service.running < service.desired)
+  {
+    return SwarmServiceState::Degraded;
+  }
+  SwarmServiceState::Running
+}
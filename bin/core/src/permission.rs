@@ -1,13 +1,14 @@
 use std::collections::HashSet;
 
 use anyhow::{Context, anyhow};
-use database::mongo_indexed::doc;
+use database::mongo_indexed::{Document, doc};
 use database::mungos::find::find_collect;
 use futures::{FutureExt, future::BoxFuture};
 use indexmap::IndexSet;
 use komodo_client::{
   api::read::GetPermission,
   entities::{
+    komodo_timestamp,
     permission::{PermissionLevel, PermissionLevelAndSpecifics},
     resource::Resource,
     user::User,
@@ -29,7 +30,16 @@ pub async fn get_check_permissions<T: KomodoResource>(
   required_permissions: PermissionLevelAndSpecifics,
 ) -> anyhow::Result<Resource<T::Config, T::Info>> {
   let resource = get::<T>(id_or_name).await?;
+  check_permissions::<T>(resource, user, required_permissions).await
+}
 
+/// Checks the user permissions on an already fetched resource,
+/// returning it back if they are sufficient.
+pub async fn check_permissions<T: KomodoResource>(
+  resource: Resource<T::Config, T::Info>,
+  user: &User,
+  required_permissions: PermissionLevelAndSpecifics,
+) -> anyhow::Result<Resource<T::Config, T::Info>> {
   // Allow all if admin
   if user.admin {
     return Ok(resource);
@@ -144,7 +154,8 @@ pub fn get_user_permission_on_resource<'a, T: KomodoResource>(
       doc! {
         "$or": user_target_query(&user.id, &groups)?,
         "resource_target.type": resource_type.as_ref(),
-        "resource_target.id": resource_id
+        "resource_target.id": resource_id,
+        "expires_at": unexpired_permission_filter(),
       },
       None,
     )
@@ -211,7 +222,8 @@ pub async fn get_resource_ids_for_user<T: KomodoResource>(
       doc! {
         "$or": user_target_query(&user.id, &groups)?,
         "resource_target.type": resource_type.as_ref(),
-        "level": { "$in": ["Read", "Execute", "Write"] }
+        "level": { "$in": ["Read", "Execute", "Write"] },
+        "expires_at": unexpired_permission_filter(),
       },
       None,
     )
@@ -229,3 +241,9 @@ pub async fn get_resource_ids_for_user<T: KomodoResource>(
 
   Ok(Some(ids.into_iter().collect()))
 }
+
+/// Filter on the permission `expires_at` which excludes expired permissions,
+/// so they stop applying before the expiry task revokes them.
+pub fn unexpired_permission_filter() -> Document {
+  doc! { "$not": { "$gt": 0, "$lte": komodo_timestamp() } }
+}
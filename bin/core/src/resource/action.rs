@@ -18,7 +18,10 @@ use komodo_client::entities::{
 };
 
 use crate::{
-  helpers::query::{get_action_state, get_last_run_at},
+  helpers::{
+    execution_metrics::execution_metrics,
+    query::{get_action_state, get_last_run_at},
+  },
   schedule::{
     cancel_schedule, get_schedule_item_info, update_schedule,
   },
@@ -56,10 +59,13 @@ impl super::KomodoResource for Action {
     let (next_scheduled_run, schedule_error) = get_schedule_item_info(
       &ResourceTarget::Action(action.id.clone()),
     );
+    let metrics =
+      execution_metrics(&ResourceTarget::Action(action.id.clone()));
     ActionListItem {
       name: action.name,
       id: action.id,
       template: action.template,
+      protected: action.protected,
       tags: action.tags,
       resource_type: ResourceTargetVariant::Action,
       info: ActionListItemInfo {
@@ -67,6 +73,7 @@ impl super::KomodoResource for Action {
         last_run_at: last_run_at.unwrap_or(None),
         next_scheduled_run,
         schedule_error,
+        metrics,
       },
     }
   }
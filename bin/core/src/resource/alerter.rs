@@ -41,6 +41,7 @@ impl super::KomodoResource for Alerter {
       name: alerter.name,
       id: alerter.id,
       template: alerter.template,
+      protected: alerter.protected,
       tags: alerter.tags,
       resource_type: ResourceTargetVariant::Alerter,
       info: AlerterListItemInfo {
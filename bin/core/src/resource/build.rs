@@ -31,9 +31,13 @@ use crate::{
   api::write::WriteArgs,
   config::core_config,
   helpers::{
-    empty_or_only_spaces, query::get_latest_update, repo_link,
+    empty_or_only_spaces, execution_metrics::execution_metrics,
+    query::get_latest_update, repo_link,
   },
   permission::get_check_permissions,
+  schedule::{
+    cancel_schedule, get_schedule_item_info, update_schedule,
+  },
   state::{
     action_states, all_resources_cache, build_state_cache, db_client,
   },
@@ -68,6 +72,9 @@ impl super::KomodoResource for Build {
     build: Resource<Self::Config, Self::Info>,
   ) -> Self::ListItem {
     let state = get_build_state(&build.id).await;
+    let (next_scheduled_run, schedule_error) = get_schedule_item_info(
+      &ResourceTarget::Build(build.id.clone()),
+    );
 
     let default_git = (
       build.config.git_provider,
@@ -94,10 +101,13 @@ impl super::KomodoResource for Build {
           .unwrap_or(default_git)
       };
 
+    let metrics =
+      execution_metrics(&ResourceTarget::Build(build.id.clone()));
     BuildListItem {
       name: build.name,
       id: build.id,
       template: build.template,
+      protected: build.protected,
       tags: build.tags,
       resource_type: ResourceTargetVariant::Build,
       info: BuildListItemInfo {
@@ -124,6 +134,9 @@ impl super::KomodoResource for Build {
         built_hash: build.info.built_hash,
         latest_hash: build.info.latest_hash,
         state,
+        next_scheduled_run,
+        schedule_error,
+        metrics,
       },
     }
   }
@@ -153,6 +166,11 @@ impl super::KomodoResource for Build {
     config: &mut Self::PartialConfig,
     user: &User,
   ) -> anyhow::Result<()> {
+    if let Some(group) = &config.concurrency_group
+      && !group.is_empty()
+    {
+      super::check_concurrency_group(group, user).await?;
+    }
     validate_config(config, user).await
   }
 
@@ -160,6 +178,7 @@ impl super::KomodoResource for Build {
     created: &Resource<Self::Config, Self::Info>,
     update: &mut Update,
   ) -> anyhow::Result<()> {
+    update_schedule(created);
     refresh_build_state_cache().await;
     if let Err(e) = (RefreshBuildCache {
       build: created.name.clone(),
@@ -184,10 +203,17 @@ impl super::KomodoResource for Build {
   }
 
   async fn validate_update_config(
-    _id: &str,
+    id: &str,
     config: &mut Self::PartialConfig,
     user: &User,
   ) -> anyhow::Result<()> {
+    if let Some(group) = &config.concurrency_group
+      && !group.is_empty()
+      && super::get::<Build>(id).await?.config.concurrency_group
+        != *group
+    {
+      super::check_concurrency_group(group, user).await?;
+    }
     validate_config(config, user).await
   }
 
@@ -221,6 +247,7 @@ impl super::KomodoResource for Build {
     resource: &Resource<Self::Config, Self::Info>,
     _update: &mut Update,
   ) -> anyhow::Result<()> {
+    cancel_schedule(&ResourceTarget::Build(resource.id.clone()));
     build_state_cache().remove(&resource.id).await;
     Ok(())
   }
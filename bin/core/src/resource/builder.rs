@@ -10,6 +10,7 @@ use komodo_client::entities::{
     Builder, BuilderConfig, BuilderConfigDiff, BuilderConfigVariant,
     BuilderListItem, BuilderListItemInfo, BuilderQuerySpecifics,
     PartialBuilderConfig, PartialServerBuilderConfig,
+    PartialUrlBuilderConfig,
   },
   permission::{PermissionLevel, SpecificPermission},
   resource::Resource,
@@ -18,7 +19,7 @@ use komodo_client::entities::{
   user::User,
 };
 
-use crate::state::db_client;
+use crate::{helpers::validate_periphery_address, state::db_client};
 
 impl super::KomodoResource for Builder {
   type Config = BuilderConfig;
@@ -65,6 +66,7 @@ impl super::KomodoResource for Builder {
       name: builder.name,
       id: builder.id,
       template: builder.template,
+      protected: builder.protected,
       tags: builder.tags,
       resource_type: ResourceTargetVariant::Builder,
       info: BuilderListItemInfo {
@@ -196,6 +198,12 @@ async fn validate_config(
       .await?;
       *server_id = server.id;
     }
+    PartialBuilderConfig::Url(PartialUrlBuilderConfig {
+      address: Some(address),
+      ..
+    }) if !address.is_empty() => {
+      validate_periphery_address(address)?;
+    }
     _ => {}
   }
   Ok(())
@@ -1,4 +1,6 @@
-use anyhow::Context;
+use std::net::IpAddr;
+
+use anyhow::{Context, anyhow};
 use database::mungos::mongodb::Collection;
 use formatting::format_serror;
 use indexmap::IndexSet;
@@ -24,8 +26,9 @@ use periphery_client::api::container::RemoveContainer;
 use crate::{
   config::core_config,
   helpers::{
-    empty_or_only_spaces, periphery_client,
-    query::get_deployment_state,
+    empty_or_only_spaces, execution_metrics::execution_metrics,
+    periphery_client, query::get_deployment_state,
+    uptime::uptime_override,
   },
   monitor::update_cache_for_server,
   state::{action_states, db_client, deployment_status_cache},
@@ -125,10 +128,17 @@ impl super::KomodoResource for Deployment {
         })
       })
       .unwrap_or((build_image, false));
+    let uptime_override = uptime_override(
+      &ResourceTarget::Deployment(deployment.id.clone()),
+    );
+    let metrics = execution_metrics(&ResourceTarget::Deployment(
+      deployment.id.clone(),
+    ));
     DeploymentListItem {
       name: deployment.name,
       id: deployment.id,
       template: deployment.template,
+      protected: deployment.protected,
       tags: deployment.tags,
       resource_type: ResourceTargetVariant::Deployment,
       info: DeploymentListItemInfo {
@@ -140,6 +150,8 @@ impl super::KomodoResource for Deployment {
         update_available,
         server_id: deployment.config.server_id,
         build_id,
+        uptime_override,
+        metrics,
       },
     }
   }
@@ -167,7 +179,7 @@ impl super::KomodoResource for Deployment {
     config: &mut Self::PartialConfig,
     user: &User,
   ) -> anyhow::Result<()> {
-    validate_config(config, user).await
+    validate_config(None, config, user).await
   }
 
   async fn post_create(
@@ -199,11 +211,11 @@ impl super::KomodoResource for Deployment {
   }
 
   async fn validate_update_config(
-    _id: &str,
+    id: &str,
     config: &mut Self::PartialConfig,
     user: &User,
   ) -> anyhow::Result<()> {
-    validate_config(config, user).await
+    validate_config(Some(id), config, user).await
   }
 
   async fn post_update(
@@ -267,7 +279,7 @@ impl super::KomodoResource for Deployment {
       );
       return Ok(());
     }
-    let periphery = match periphery_client(&server) {
+    let periphery = match periphery_client(&server).await {
       Ok(periphery) => periphery,
       Err(e) => {
         // This case won't ever happen, as periphery_client only fallible if the server is disabled.
@@ -311,6 +323,7 @@ impl super::KomodoResource for Deployment {
 
 #[instrument(skip(user))]
 async fn validate_config(
+  id: Option<&str>,
   config: &mut PartialDeploymentConfig,
   user: &User,
 ) -> anyhow::Result<()> {
@@ -342,6 +355,44 @@ async fn validate_config(
       version: *version,
     });
   }
+  if let Some(restart_after) = &config.restart_after {
+    // Dependents are only restarted on the same Server.
+    let server_id = match (&config.server_id, id) {
+      (Some(server_id), _) => server_id.clone(),
+      (None, Some(id)) => {
+        super::get::<Deployment>(id).await?.config.server_id
+      }
+      (None, None) => String::new(),
+    };
+    let mut ids = IndexSet::new();
+    for dependency in restart_after {
+      if empty_or_only_spaces(dependency) {
+        continue;
+      }
+      let dependency = get_check_permissions::<Deployment>(
+        dependency,
+        user,
+        PermissionLevel::Read.attach(),
+      )
+      .await
+      .with_context(|| {
+        format!("Cannot restart after Deployment {dependency}")
+      })?;
+      if Some(dependency.id.as_str()) == id {
+        return Err(anyhow!(
+          "Deployment cannot restart after itself"
+        ));
+      }
+      if dependency.config.server_id != server_id {
+        return Err(anyhow!(
+          "Deployment cannot restart after Deployment {} on a different Server",
+          dependency.name
+        ));
+      }
+      ids.insert(dependency.id);
+    }
+    config.restart_after = Some(ids.into_iter().collect());
+  }
   if let Some(volumes) = &config.volumes {
     conversions_from_str(volumes).context("Invalid volumes")?;
   }
@@ -352,6 +403,24 @@ async fn validate_config(
     environment_vars_from_str(environment)
       .context("Invalid environment")?;
   }
+  if let Some(ip_address) = &config.ip_address
+    && !ip_address.is_empty()
+  {
+    ip_address
+      .parse::<IpAddr>()
+      .with_context(|| format!("Invalid IP address: {ip_address}"))?;
+  }
+  if let Some(mac_address) = &config.mac_address
+    && !mac_address.is_empty()
+  {
+    let valid = mac_address.split(':').count() == 6
+      && mac_address.split(':').all(|part| {
+        part.len() == 2 && part.chars().all(|c| c.is_ascii_hexdigit())
+      });
+    if !valid {
+      return Err(anyhow!("Invalid MAC address: {mac_address}"));
+    }
+  }
   if let Some(extra_args) = &mut config.extra_args {
     extra_args.retain(|v| !empty_or_only_spaces(v))
   }
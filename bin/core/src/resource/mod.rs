@@ -1,7 +1,4 @@
-use std::{
-  collections::{HashMap, HashSet},
-  str::FromStr,
-};
+use std::{collections::HashMap, str::FromStr};
 
 use anyhow::{Context, anyhow};
 use database::mungos::{
@@ -9,7 +6,7 @@ use database::mungos::{
   find::find_collect,
   mongodb::{
     Collection,
-    bson::{Document, doc, oid::ObjectId, to_document},
+    bson::{Document, bson, doc, oid::ObjectId, to_document},
     options::FindOptions,
   },
 };
@@ -20,18 +17,29 @@ use komodo_client::{
   api::{read::ExportResourcesToToml, write::CreateTag},
   entities::{
     Operation, ResourceTarget, ResourceTargetVariant,
+    action::Action,
+    alerter::Alerter,
+    build::Build,
+    builder::Builder,
+    deployment::Deployment,
     komodo_timestamp,
     permission::{
       PermissionLevel, PermissionLevelAndSpecifics,
       SpecificPermission,
     },
+    procedure::Procedure,
+    repo::Repo,
     resource::{AddFilters, Resource, ResourceQuery},
+    resource_lock::ResourceConcurrency,
+    server::Server,
+    stack::Stack,
+    swarm::SwarmService,
+    sync::ResourceSync,
     tag::Tag,
     to_general_name,
     update::Update,
     user::{User, system_user},
   },
-  parsers::parse_string_list,
 };
 use partial_derive2::{Diff, MaybeNone, PartialDiff};
 use reqwest::StatusCode;
@@ -46,7 +54,10 @@ use crate::{
     query::{get_tag, id_or_name_filter},
     update::{add_update, make_update},
   },
-  permission::{get_check_permissions, get_resource_ids_for_user},
+  permission::{
+    check_permissions, get_check_permissions,
+    get_resource_ids_for_user,
+  },
   state::db_client,
 };
 
@@ -55,11 +66,13 @@ mod alerter;
 mod build;
 mod builder;
 mod deployment;
+mod pattern;
 mod procedure;
 mod refresh;
 mod repo;
 mod server;
 mod stack;
+mod swarm;
 mod sync;
 
 pub use action::{
@@ -79,6 +92,7 @@ pub use refresh::{
 pub use repo::{
   refresh_repo_state_cache, spawn_repo_state_refresh_loop,
 };
+pub use server::inherit_scheduled_command_creators;
 
 /// Implement on each Komodo resource for common methods
 pub trait KomodoResource {
@@ -124,6 +138,14 @@ pub trait KomodoResource {
   #[allow(clippy::ptr_arg)]
   async fn busy(id: &String) -> anyhow::Result<bool>;
 
+  /// Loads any config stored outside the resource document,
+  /// such as large Stack file contents.
+  async fn load_stored_config(
+    _resource: &mut Resource<Self::Config, Self::Info>,
+  ) -> anyhow::Result<()> {
+    Ok(())
+  }
+
   /// Some resource types have restrictions on the allowed formatting for names.
   /// Stacks, Builds, and Deployments all require names to be "docker compatible",
   /// which means all lowercase, and no spaces or dots.
@@ -230,6 +252,16 @@ pub trait KomodoResource {
 
 pub async fn get<T: KomodoResource>(
   id_or_name: &str,
+) -> anyhow::Result<Resource<T::Config, T::Info>> {
+  let mut resource = get_unloaded::<T>(id_or_name).await?;
+  T::load_stored_config(&mut resource).await?;
+  Ok(resource)
+}
+
+/// Gets the resource as stored in the database,
+/// without loading any config stored outside the document.
+pub async fn get_unloaded<T: KomodoResource>(
+  id_or_name: &str,
 ) -> anyhow::Result<Resource<T::Config, T::Info>> {
   if id_or_name.is_empty() {
     return Err(anyhow!(
@@ -316,12 +348,15 @@ pub async fn list_for_user_using_document<T: KomodoResource>(
 }
 
 /// Lists full resource matching wildcard syntax,
-/// or regex if wrapped with "\\"
+/// or regex if wrapped with "\\", or tag expressions.
+/// Terms prefixed with "!" exclude their matches.
+/// See [pattern] for the syntax.
 ///
 /// ## Example
 /// ```
 /// let items = list_full_for_user_using_match_string::<Build>("foo-*", Default::default(), user, all_tags).await?;
 /// let items = list_full_for_user_using_match_string::<Build>("\\^foo-.*$\\", Default::default(), user, all_tags).await?;
+/// let items = list_full_for_user_using_match_string::<Build>("tag:edge AND NOT tag:frozen, !foo-test", Default::default(), user, all_tags).await?;
 /// ```
 #[instrument(level = "debug")]
 pub async fn list_full_for_user_using_pattern<T: KomodoResource>(
@@ -335,28 +370,8 @@ pub async fn list_full_for_user_using_pattern<T: KomodoResource>(
     list_full_for_user::<T>(query, user, permissions, all_tags)
       .await?;
 
-  let patterns = parse_string_list(pattern);
-  let mut names = HashSet::<String>::new();
-
-  for pattern in patterns {
-    if pattern.starts_with('\\') && pattern.ends_with('\\') {
-      let regex = regex::Regex::new(&pattern[1..(pattern.len() - 1)])
-        .context("Regex matching string invalid")?;
-      for resource in &resources {
-        if regex.is_match(&resource.name) {
-          names.insert(resource.name.clone());
-        }
-      }
-    } else {
-      let wildcard = wildcard::Wildcard::new(pattern.as_bytes())
-        .context("Wildcard matching string invalid")?;
-      for resource in &resources {
-        if wildcard.is_match(resource.name.as_bytes()) {
-          names.insert(resource.name.clone());
-        }
-      }
-    };
-  }
+  let names =
+    pattern::matching_names(pattern, &resources, all_tags).await?;
 
   Ok(
     resources
@@ -413,7 +428,7 @@ pub async fn get_id_to_resource_map<T: KomodoResource>(
   id_to_tags: &HashMap<String, Tag>,
   match_tags: &[String],
 ) -> anyhow::Result<IdResourceMap<T>> {
-  let res = find_collect(T::coll(), None, None)
+  let mut res: IdResourceMap<T> = find_collect(T::coll(), None, None)
     .await
     .with_context(|| {
       format!("failed to pull {}s from mongo", T::resource_type())
@@ -449,6 +464,9 @@ pub async fn get_id_to_resource_map<T: KomodoResource>(
     })
     .map(|r| (r.id.clone(), r))
     .collect();
+  for resource in res.values_mut() {
+    T::load_stored_config(resource).await?;
+  }
   Ok(res)
 }
 
@@ -515,11 +533,15 @@ pub async fn create<T: KomodoResource>(
     name,
     description: Default::default(),
     template: Default::default(),
+    protected: Default::default(),
     tags: Default::default(),
+    metadata: Default::default(),
     config: config.into(),
     info: T::default_info().await?,
     base_permission: PermissionLevel::None.into(),
+    concurrency: Default::default(),
     updated_at: start_ts,
+    revision: 0,
   };
 
   let resource_id = T::coll()
@@ -575,11 +597,14 @@ pub async fn create<T: KomodoResource>(
 // UPDATE
 // =======
 
+/// Pass the `revision` the update is based on
+/// to reject it if the resource was changed since.
 pub async fn update<T: KomodoResource>(
   id_or_name: &str,
   mut config: T::PartialConfig,
+  revision: Option<i64>,
   user: &User,
-) -> anyhow::Result<Resource<T::Config, T::Info>> {
+) -> serror::Result<Resource<T::Config, T::Info>> {
   let resource = get_check_permissions::<T>(
     id_or_name,
     user,
@@ -587,8 +612,14 @@ pub async fn update<T: KomodoResource>(
   )
   .await?;
 
+  if let Some(revision) = revision
+    && revision != resource.revision
+  {
+    return Err(revision_conflict(resource));
+  }
+
   if T::busy(&resource.id).await? {
-    return Err(anyhow!("{} busy", T::resource_type()));
+    return Err(anyhow!("{} busy", T::resource_type()).into());
   }
 
   T::validate_update_config(&resource.id, &mut config, user).await?;
@@ -616,16 +647,40 @@ pub async fn update<T: KomodoResource>(
   let config: T::PartialConfig = diff.into();
 
   let id = resource.id.clone();
+  let prev_revision = resource.revision;
 
   let config_doc = T::update_document(resource, config)
     .context("failed to serialize config to bson document")?;
 
   let update_doc = flatten_document(doc! { "config": config_doc });
 
-  update_one_by_id(T::coll(), &id, doc! { "$set": update_doc }, None)
+  // Only update if the revision is unchanged,
+  // so concurrent updates can't overwrite each other.
+  let revision_filter = if prev_revision == 0 {
+    // Resources created before revisions don't have the field.
+    bson!({ "$in": [0, null] })
+  } else {
+    bson!(prev_revision)
+  };
+  let res = T::coll()
+    .update_one(
+      doc! {
+        "_id": ObjectId::from_str(&id)
+          .context("Resource id is not valid ObjectId")?,
+        "revision": revision_filter,
+      },
+      doc! {
+        "$set": update_doc,
+        "$inc": { "revision": 1 },
+      },
+    )
     .await
     .context("failed to update resource on database")?;
 
+  if res.matched_count == 0 {
+    return Err(revision_conflict(get::<T>(&id).await?));
+  }
+
   let curr_toml = ExportResourcesToToml {
     targets: vec![T::resource_target(&id)],
     ..Default::default()
@@ -668,8 +723,35 @@ pub async fn update<T: KomodoResource>(
   Ok(updated)
 }
 
+/// The error includes the current resource as JSON,
+/// so the client can rebase the update on it.
+fn revision_conflict<
+  C: Serialize + Default,
+  I: Serialize + Default,
+>(
+  current: Resource<C, I>,
+) -> serror::Error {
+  let json = match serde_json::to_string(&current) {
+    Ok(json) => json,
+    Err(e) => format!("Failed to serialize current resource | {e:?}"),
+  };
+  anyhow!(json)
+    .context(format!(
+      "Revision conflict | The resource was updated since it was read (current revision {}). Reload it and apply the changes again.",
+      current.revision
+    ))
+    .status_code(StatusCode::CONFLICT)
+}
+
 fn resource_target<T: KomodoResource>(id: String) -> ResourceTarget {
-  match T::resource_type() {
+  resource_target_from_variant(T::resource_type(), id)
+}
+
+pub fn resource_target_from_variant(
+  variant: ResourceTargetVariant,
+  id: String,
+) -> ResourceTarget {
+  match variant {
     ResourceTargetVariant::System => ResourceTarget::System(id),
     ResourceTargetVariant::Build => ResourceTarget::Build(id),
     ResourceTargetVariant::Builder => ResourceTarget::Builder(id),
@@ -685,6 +767,9 @@ fn resource_target<T: KomodoResource>(id: String) -> ResourceTarget {
     }
     ResourceTargetVariant::Stack => ResourceTarget::Stack(id),
     ResourceTargetVariant::Action => ResourceTarget::Action(id),
+    ResourceTargetVariant::SwarmService => {
+      ResourceTarget::SwarmService(id)
+    }
   }
 }
 
@@ -692,6 +777,8 @@ pub struct ResourceMetaUpdate {
   pub description: Option<String>,
   pub template: Option<bool>,
   pub tags: Option<Vec<String>>,
+  pub metadata: Option<HashMap<String, String>>,
+  pub concurrency: Option<ResourceConcurrency>,
 }
 
 impl ResourceMetaUpdate {
@@ -699,6 +786,8 @@ impl ResourceMetaUpdate {
     self.description.is_none()
       && self.template.is_none()
       && self.tags.is_none()
+      && self.metadata.is_none()
+      && self.concurrency.is_none()
   }
 }
 
@@ -707,7 +796,7 @@ pub async fn update_meta<T: KomodoResource>(
   meta: ResourceMetaUpdate,
   args: &WriteArgs,
 ) -> anyhow::Result<()> {
-  get_check_permissions::<T>(
+  let resource = get_check_permissions::<T>(
     id_or_name,
     &args.user,
     PermissionLevel::Write.into(),
@@ -741,6 +830,25 @@ pub async fn update_meta<T: KomodoResource>(
       .collect::<Vec<_>>();
     set.insert("tags", tags);
   }
+  if let Some(metadata) = meta.metadata {
+    set.insert(
+      "metadata",
+      to_document(&metadata)
+        .context("Failed to serialize metadata")?,
+    );
+  }
+  if let Some(concurrency) = meta.concurrency {
+    if !concurrency.group.is_empty()
+      && concurrency.group != resource.concurrency.group
+    {
+      check_concurrency_group(&concurrency.group, &args.user).await?;
+    }
+    set.insert(
+      "concurrency",
+      to_document(&concurrency)
+        .context("Failed to serialize concurrency")?,
+    );
+  }
   T::coll()
     .update_one(id_or_name_filter(id_or_name), doc! { "$set": set })
     .await?;
@@ -748,6 +856,145 @@ pub async fn update_meta<T: KomodoResource>(
   Ok(())
 }
 
+/// Joining a concurrency group lets the resource hold up,
+/// or with CancelPrevious cancel, executions of the other resources
+/// in the group. So it requires Execute on all of them.
+pub(super) async fn check_concurrency_group(
+  group: &str,
+  user: &User,
+) -> anyhow::Result<()> {
+  if user.admin {
+    return Ok(());
+  }
+  tokio::try_join!(
+    check_concurrency_group_members::<Server>(group, user),
+    check_concurrency_group_members::<Deployment>(group, user),
+    check_concurrency_group_members::<SwarmService>(group, user),
+    check_concurrency_group_members::<Build>(group, user),
+    check_concurrency_group_members::<Repo>(group, user),
+    check_concurrency_group_members::<Builder>(group, user),
+    check_concurrency_group_members::<Alerter>(group, user),
+    check_concurrency_group_members::<Procedure>(group, user),
+    check_concurrency_group_members::<Action>(group, user),
+    check_concurrency_group_members::<ResourceSync>(group, user),
+    check_concurrency_group_members::<Stack>(group, user),
+  )?;
+  Ok(())
+}
+
+async fn check_concurrency_group_members<T: KomodoResource>(
+  group: &str,
+  user: &User,
+) -> anyhow::Result<()> {
+  // Builds also join with `concurrency_group` in their config.
+  let members = find_collect(
+    T::coll(),
+    doc! { "$or": [
+      { "concurrency.group": group },
+      { "config.concurrency_group": group },
+    ] },
+    None,
+  )
+  .await
+  .with_context(|| {
+    format!("failed to pull {}s from mongo", T::resource_type())
+  })?;
+  for member in members {
+    let name = member.name.clone();
+    check_permissions::<T>(
+      member,
+      user,
+      PermissionLevel::Execute.into(),
+    )
+    .await
+    .with_context(|| {
+      format!(
+        "Concurrency group '{group}' includes {} '{name}'",
+        T::resource_type()
+      )
+    })?;
+  }
+  Ok(())
+}
+
+// ==========
+// PROTECTION
+// ==========
+
+/// Any user with Write permission can protect the resource,
+/// only admins can remove the protection.
+pub async fn set_protected<T: KomodoResource>(
+  id_or_name: &str,
+  protected: bool,
+  user: &User,
+) -> anyhow::Result<Update> {
+  if !protected && !user.admin {
+    return Err(anyhow!(
+      "Only admins can remove resource protection"
+    ));
+  }
+  let resource = get_check_permissions::<T>(
+    id_or_name,
+    user,
+    PermissionLevel::Write.into(),
+  )
+  .await?;
+
+  let mut update = make_update(
+    resource_target::<T>(resource.id.clone()),
+    Operation::SetResourceProtected,
+    user,
+  );
+
+  update_one_by_id(
+    T::coll(),
+    &resource.id,
+    database::mungos::update::Update::Set(
+      doc! { "protected": protected },
+    ),
+    None,
+  )
+  .await
+  .with_context(|| {
+    format!("Failed to update {} on db", T::resource_type())
+  })?;
+
+  update.push_simple_log(
+    "Set Protected",
+    format!(
+      "{} {} {}",
+      if protected {
+        "Protected"
+      } else {
+        "Removed protection from"
+      },
+      T::resource_type(),
+      resource.name
+    ),
+  );
+
+  refresh_all_resources_cache().await;
+
+  update.finalize();
+  update.id = add_update(update.clone()).await?;
+
+  Ok(update)
+}
+
+/// Rejects Delete / Destroy / Prune on protected resources.
+pub fn check_not_protected<T: KomodoResource>(
+  resource: &Resource<T::Config, T::Info>,
+) -> anyhow::Result<()> {
+  if resource.protected {
+    return Err(anyhow!(
+      "{} {} is protected. An admin must remove the protection first.",
+      T::resource_type(),
+      resource.name
+    ));
+  }
+  Ok(())
+}
+
 pub async fn remove_tag_from_all<T: KomodoResource>(
   tag_id: &str,
 ) -> anyhow::Result<()> {
@@ -831,6 +1078,8 @@ pub async fn delete<T: KomodoResource>(
   )
   .await?;
 
+  check_not_protected::<T>(&resource)?;
+
   if T::busy(&resource.id).await? {
     return Err(anyhow!("{} busy", T::resource_type()));
   }
@@ -958,6 +1207,7 @@ where
   let (recent_field, id) = match resource {
     ResourceTarget::Server(id) => ("recents.Server", id),
     ResourceTarget::Deployment(id) => ("recents.Deployment", id),
+    ResourceTarget::SwarmService(id) => ("recents.SwarmService", id),
     ResourceTarget::Build(id) => ("recents.Build", id),
     ResourceTarget::Repo(id) => ("recents.Repo", id),
     ResourceTarget::Procedure(id) => ("recents.Procedure", id),
@@ -0,0 +1,242 @@
+//! Matching resources with the patterns used by batch executions.
+//!
+//! Patterns are multiline and / or comma separated terms:
+//! - `foo-*`: Wildcard match on the resource name.
+//! - `\^foo-.*$\`: Regex match on the resource name.
+//! - `tag:edge AND NOT (tag:frozen OR tag:legacy)`: Match on resource tags.
+//! - `!staging-*`: Excludes the resources matching the term,
+//!   even if matched by another term. If all the terms
+//!   are exclusions, all other resources are matched.
+
+use std::collections::HashSet;
+
+use anyhow::{Context, anyhow};
+use database::mungos::find::find_collect;
+use komodo_client::{
+  entities::{resource::Resource, tag::Tag},
+  parsers::parse_string_list,
+};
+
+use crate::state::db_client;
+
+/// Gets the names of the resources matching the pattern.
+pub async fn matching_names<Config: Default, Info: Default>(
+  pattern: &str,
+  resources: &[Resource<Config, Info>],
+  all_tags: &[Tag],
+) -> anyhow::Result<HashSet<String>> {
+  let terms = parse_string_list(pattern);
+
+  // Batch executions don't pass the tags, only query them if needed.
+  let queried_tags;
+  let all_tags = if all_tags.is_empty()
+    && terms.iter().any(|term| is_tag_expression(term))
+  {
+    queried_tags = find_collect(&db_client().tags, None, None)
+      .await
+      .context("Failed to query db for tags")?;
+    &queried_tags
+  } else {
+    all_tags
+  };
+
+  let mut include = HashSet::<&str>::new();
+  let mut exclude = HashSet::<&str>::new();
+  let mut has_include = false;
+
+  for term in &terms {
+    let (term, names) = match term.strip_prefix('!') {
+      Some(term) => (term.trim(), &mut exclude),
+      None => {
+        has_include = true;
+        (term.as_str(), &mut include)
+      }
+    };
+    let matcher = TermMatcher::parse(term, all_tags)?;
+    names.extend(
+      resources
+        .iter()
+        .filter(|resource| matcher.is_match(resource))
+        .map(|resource| resource.name.as_str()),
+    );
+  }
+
+  Ok(
+    resources
+      .iter()
+      .map(|resource| resource.name.as_str())
+      .filter(|name| !has_include || include.contains(name))
+      .filter(|name| !exclude.contains(name))
+      .map(str::to_string)
+      .collect(),
+  )
+}
+
+fn is_tag_expression(term: &str) -> bool {
+  let term = term.strip_prefix('!').unwrap_or(term).trim();
+  term.starts_with("tag:")
+    || term.starts_with("NOT ")
+    || term.starts_with('(')
+}
+
+enum TermMatcher<'a> {
+  Regex(regex::Regex),
+  Wildcard(wildcard::Wildcard<'a>),
+  Tags(TagExpression),
+}
+
+impl<'a> TermMatcher<'a> {
+  fn parse(
+    term: &'a str,
+    all_tags: &[Tag],
+  ) -> anyhow::Result<TermMatcher<'a>> {
+    if is_tag_expression(term) {
+      TagExpression::parse(term, all_tags)
+        .map(TermMatcher::Tags)
+        .with_context(|| format!("Invalid tag expression '{term}'"))
+    } else if term.len() > 1
+      && term.starts_with('\\')
+      && term.ends_with('\\')
+    {
+      regex::Regex::new(&term[1..(term.len() - 1)])
+        .map(TermMatcher::Regex)
+        .context("Regex matching string invalid")
+    } else {
+      wildcard::Wildcard::new(term.as_bytes())
+        .map(TermMatcher::Wildcard)
+        .context("Wildcard matching string invalid")
+    }
+  }
+
+  fn is_match<Config: Default, Info: Default>(
+    &self,
+    resource: &Resource<Config, Info>,
+  ) -> bool {
+    match self {
+      TermMatcher::Regex(regex) => regex.is_match(&resource.name),
+      TermMatcher::Wildcard(wildcard) => {
+        wildcard.is_match(resource.name.as_bytes())
+      }
+      TermMatcher::Tags(expression) => {
+        expression.is_match(&resource.tags)
+      }
+    }
+  }
+}
+
+/// A boolean expression on resource tags, eg.
+/// `tag:edge AND NOT (tag:frozen OR tag:legacy)`.
+/// NOT binds tighter than AND, which binds tighter than OR.
+enum TagExpression {
+  /// Matches resources with the tag id.
+  Tag(String),
+  Not(Box<TagExpression>),
+  And(Box<TagExpression>, Box<TagExpression>),
+  Or(Box<TagExpression>, Box<TagExpression>),
+}
+
+impl TagExpression {
+  fn parse(
+    expression: &str,
+    all_tags: &[Tag],
+  ) -> anyhow::Result<TagExpression> {
+    let expression =
+      expression.replace('(', " ( ").replace(')', " ) ");
+    let tokens = expression.split_whitespace().collect::<Vec<_>>();
+    let mut parser = TagParser {
+      tokens: &tokens,
+      position: 0,
+      all_tags,
+    };
+    let res = parser.or()?;
+    match parser.next() {
+      None => Ok(res),
+      Some(token) => Err(anyhow!("Unexpected '{token}'")),
+    }
+  }
+
+  fn is_match(&self, tags: &[String]) -> bool {
+    match self {
+      TagExpression::Tag(id) => tags.contains(id),
+      TagExpression::Not(expression) => !expression.is_match(tags),
+      TagExpression::And(left, right) => {
+        left.is_match(tags) && right.is_match(tags)
+      }
+      TagExpression::Or(left, right) => {
+        left.is_match(tags) || right.is_match(tags)
+      }
+    }
+  }
+}
+
+struct TagParser<'a> {
+  tokens: &'a [&'a str],
+  position: usize,
+  all_tags: &'a [Tag],
+}
+
+impl<'a> TagParser<'a> {
+  fn next(&mut self) -> Option<&'a str> {
+    let token = self.tokens.get(self.position).copied();
+    self.position += 1;
+    token
+  }
+
+  fn peek(&self) -> Option<&'a str> {
+    self.tokens.get(self.position).copied()
+  }
+
+  fn or(&mut self) -> anyhow::Result<TagExpression> {
+    let mut res = self.and()?;
+    while self.peek() == Some("OR") {
+      self.position += 1;
+      res = TagExpression::Or(Box::new(res), Box::new(self.and()?));
+    }
+    Ok(res)
+  }
+
+  fn and(&mut self) -> anyhow::Result<TagExpression> {
+    let mut res = self.not()?;
+    while self.peek() == Some("AND") {
+      self.position += 1;
+      res = TagExpression::And(Box::new(res), Box::new(self.not()?));
+    }
+    Ok(res)
+  }
+
+  fn not(&mut self) -> anyhow::Result<TagExpression> {
+    if self.peek() == Some("NOT") {
+      self.position += 1;
+      return Ok(TagExpression::Not(Box::new(self.not()?)));
+    }
+    self.atom()
+  }
+
+  fn atom(&mut self) -> anyhow::Result<TagExpression> {
+    match self.next() {
+      Some("(") => {
+        let res = self.or()?;
+        match self.next() {
+          Some(")") => Ok(res),
+          _ => Err(anyhow!("Missing closing ')'")),
+        }
+      }
+      Some(token) => {
+        let name = token.strip_prefix("tag:").with_context(|| {
+          format!("Expected 'tag:<name>', found '{token}'")
+        })?;
+        // Unknown tags are an error rather than matching nothing,
+        // so a typo in an exclusion can't match more than intended.
+        let tag = self
+          .all_tags
+          .iter()
+          .find(|tag| tag.name == name || tag.id == name)
+          .with_context(|| {
+            format!("No tag found matching '{name}'")
+          })?;
+        Ok(TagExpression::Tag(tag.id.clone()))
+      }
+      None => Err(anyhow!("Unexpected end of expression")),
+    }
+  }
+}
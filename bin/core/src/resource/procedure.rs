@@ -18,12 +18,13 @@ use komodo_client::{
     procedure::{
       PartialProcedureConfig, Procedure, ProcedureConfig,
       ProcedureConfigDiff, ProcedureListItem, ProcedureListItemInfo,
-      ProcedureQuerySpecifics, ProcedureState,
+      ProcedureQuerySpecifics, ProcedureState, StageFailureHandler,
     },
     repo::Repo,
     resource::Resource,
     server::Server,
     stack::Stack,
+    swarm::SwarmService,
     sync::ResourceSync,
     update::Update,
     user::User,
@@ -32,7 +33,10 @@ use komodo_client::{
 
 use crate::{
   config::core_config,
-  helpers::query::{get_last_run_at, get_procedure_state},
+  helpers::{
+    execution_metrics::execution_metrics,
+    query::{get_last_run_at, get_procedure_state},
+  },
   schedule::{
     cancel_schedule, get_schedule_item_info, update_schedule,
   },
@@ -70,10 +74,14 @@ impl super::KomodoResource for Procedure {
     let (next_scheduled_run, schedule_error) = get_schedule_item_info(
       &ResourceTarget::Procedure(procedure.id.clone()),
     );
+    let metrics = execution_metrics(&ResourceTarget::Procedure(
+      procedure.id.clone(),
+    ));
     ProcedureListItem {
       name: procedure.name,
       id: procedure.id,
       template: procedure.template,
+      protected: procedure.protected,
       tags: procedure.tags,
       resource_type: ResourceTargetVariant::Procedure,
       info: ProcedureListItemInfo {
@@ -82,6 +90,7 @@ impl super::KomodoResource for Procedure {
         last_run_at: last_run_at.unwrap_or(None),
         next_scheduled_run,
         schedule_error,
+        metrics,
       },
     }
   }
@@ -182,601 +191,665 @@ async fn validate_config(
   };
   for stage in stages {
     for exec in &mut stage.executions {
-      match &mut exec.execution {
-        Execution::None(_) => {}
-        Execution::RunProcedure(params) => {
-          let procedure = super::get_check_permissions::<Procedure>(
-            &params.procedure,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          match id {
-            Some(id) if procedure.id == id => {
-              return Err(anyhow!(
-                "Cannot have self-referential procedure"
-              ));
-            }
-            _ => {}
-          }
-          params.procedure = procedure.id;
-        }
-        Execution::BatchRunProcedure(_params) => {
-          if !user.admin {
-            return Err(anyhow!(
-              "Non admin user cannot configure Batch executions"
-            ));
-          }
-        }
-        Execution::RunAction(params) => {
-          let action = super::get_check_permissions::<Action>(
-            &params.action,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.action = action.id;
-        }
-        Execution::BatchRunAction(_params) => {
-          if !user.admin {
-            return Err(anyhow!(
-              "Non admin user cannot configure Batch executions"
-            ));
-          }
-        }
-        Execution::RunBuild(params) => {
-          let build = super::get_check_permissions::<Build>(
-            &params.build,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.build = build.id;
-        }
-        Execution::BatchRunBuild(_params) => {
-          if !user.admin {
-            return Err(anyhow!(
-              "Non admin user cannot configure Batch executions"
-            ));
-          }
-        }
-        Execution::CancelBuild(params) => {
-          let build = super::get_check_permissions::<Build>(
-            &params.build,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.build = build.id;
-        }
-        Execution::Deploy(params) => {
-          let deployment =
-            super::get_check_permissions::<Deployment>(
-              &params.deployment,
-              user,
-              PermissionLevel::Execute.into(),
-            )
-            .await?;
-          params.deployment = deployment.id;
-        }
-        Execution::BatchDeploy(_params) => {
-          if !user.admin {
-            return Err(anyhow!(
-              "Non admin user cannot configure Batch executions"
-            ));
-          }
-        }
-        Execution::PullDeployment(params) => {
-          let deployment =
-            super::get_check_permissions::<Deployment>(
-              &params.deployment,
-              user,
-              PermissionLevel::Execute.into(),
-            )
-            .await?;
-          params.deployment = deployment.id;
-        }
-        Execution::StartDeployment(params) => {
-          let deployment =
-            super::get_check_permissions::<Deployment>(
-              &params.deployment,
-              user,
-              PermissionLevel::Execute.into(),
-            )
-            .await?;
-          params.deployment = deployment.id;
-        }
-        Execution::RestartDeployment(params) => {
-          let deployment =
-            super::get_check_permissions::<Deployment>(
-              &params.deployment,
-              user,
-              PermissionLevel::Execute.into(),
-            )
-            .await?;
-          params.deployment = deployment.id;
-        }
-        Execution::PauseDeployment(params) => {
-          let deployment =
-            super::get_check_permissions::<Deployment>(
-              &params.deployment,
-              user,
-              PermissionLevel::Execute.into(),
-            )
-            .await?;
-          params.deployment = deployment.id;
-        }
-        Execution::UnpauseDeployment(params) => {
-          let deployment =
-            super::get_check_permissions::<Deployment>(
-              &params.deployment,
-              user,
-              PermissionLevel::Execute.into(),
-            )
-            .await?;
-          params.deployment = deployment.id;
-        }
-        Execution::StopDeployment(params) => {
-          let deployment =
-            super::get_check_permissions::<Deployment>(
-              &params.deployment,
-              user,
-              PermissionLevel::Execute.into(),
-            )
-            .await?;
-          params.deployment = deployment.id;
-        }
-        Execution::DestroyDeployment(params) => {
-          let deployment =
-            super::get_check_permissions::<Deployment>(
-              &params.deployment,
-              user,
-              PermissionLevel::Execute.into(),
-            )
-            .await?;
-          params.deployment = deployment.id;
-        }
-        Execution::BatchDestroyDeployment(_params) => {
-          if !user.admin {
-            return Err(anyhow!(
-              "Non admin user cannot configure Batch executions"
-            ));
-          }
-        }
-        Execution::CloneRepo(params) => {
-          let repo = super::get_check_permissions::<Repo>(
-            &params.repo,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.repo = repo.id;
-        }
-        Execution::BatchCloneRepo(_params) => {
-          if !user.admin {
-            return Err(anyhow!(
-              "Non admin user cannot configure Batch executions"
-            ));
-          }
-        }
-        Execution::PullRepo(params) => {
-          let repo = super::get_check_permissions::<Repo>(
-            &params.repo,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.repo = repo.id;
-        }
-        Execution::BatchPullRepo(_params) => {
-          if !user.admin {
-            return Err(anyhow!(
-              "Non admin user cannot configure Batch executions"
-            ));
-          }
-        }
-        Execution::BuildRepo(params) => {
-          let repo = super::get_check_permissions::<Repo>(
-            &params.repo,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.repo = repo.id;
-        }
-        Execution::BatchBuildRepo(_params) => {
-          if !user.admin {
-            return Err(anyhow!(
-              "Non admin user cannot configure Batch executions"
-            ));
-          }
-        }
-        Execution::CancelRepoBuild(params) => {
-          let repo = super::get_check_permissions::<Repo>(
-            &params.repo,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.repo = repo.id;
-        }
-        Execution::StartContainer(params) => {
-          let server = super::get_check_permissions::<Server>(
-            &params.server,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.server = server.id;
-        }
-        Execution::RestartContainer(params) => {
-          let server = super::get_check_permissions::<Server>(
-            &params.server,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.server = server.id;
-        }
-        Execution::PauseContainer(params) => {
-          let server = super::get_check_permissions::<Server>(
-            &params.server,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.server = server.id;
-        }
-        Execution::UnpauseContainer(params) => {
-          let server = super::get_check_permissions::<Server>(
-            &params.server,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.server = server.id;
-        }
-        Execution::StopContainer(params) => {
-          let server = super::get_check_permissions::<Server>(
-            &params.server,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.server = server.id;
-        }
-        Execution::DestroyContainer(params) => {
-          let server = super::get_check_permissions::<Server>(
-            &params.server,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.server = server.id;
-        }
-        Execution::StartAllContainers(params) => {
-          let server = super::get_check_permissions::<Server>(
-            &params.server,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.server = server.id;
-        }
-        Execution::RestartAllContainers(params) => {
-          let server = super::get_check_permissions::<Server>(
-            &params.server,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.server = server.id;
-        }
-        Execution::PauseAllContainers(params) => {
-          let server = super::get_check_permissions::<Server>(
-            &params.server,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.server = server.id;
-        }
-        Execution::UnpauseAllContainers(params) => {
-          let server = super::get_check_permissions::<Server>(
-            &params.server,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.server = server.id;
-        }
-        Execution::StopAllContainers(params) => {
-          let server = super::get_check_permissions::<Server>(
-            &params.server,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.server = server.id;
-        }
-        Execution::PruneContainers(params) => {
-          let server = super::get_check_permissions::<Server>(
-            &params.server,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.server = server.id;
-        }
-        Execution::DeleteNetwork(params) => {
-          let server = super::get_check_permissions::<Server>(
-            &params.server,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.server = server.id;
-        }
-        Execution::PruneNetworks(params) => {
-          let server = super::get_check_permissions::<Server>(
-            &params.server,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.server = server.id;
-        }
-        Execution::DeleteImage(params) => {
-          let server = super::get_check_permissions::<Server>(
-            &params.server,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.server = server.id;
-        }
-        Execution::PruneImages(params) => {
-          let server = super::get_check_permissions::<Server>(
-            &params.server,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.server = server.id;
-        }
-        Execution::DeleteVolume(params) => {
-          let server = super::get_check_permissions::<Server>(
-            &params.server,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.server = server.id;
-        }
-        Execution::PruneVolumes(params) => {
-          let server = super::get_check_permissions::<Server>(
-            &params.server,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.server = server.id;
-        }
-        Execution::PruneDockerBuilders(params) => {
-          let server = super::get_check_permissions::<Server>(
-            &params.server,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.server = server.id;
-        }
-        Execution::PruneBuildx(params) => {
-          let server = super::get_check_permissions::<Server>(
-            &params.server,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.server = server.id;
-        }
-        Execution::PruneSystem(params) => {
-          let server = super::get_check_permissions::<Server>(
-            &params.server,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.server = server.id;
-        }
-        Execution::RunSync(params) => {
-          let sync = super::get_check_permissions::<ResourceSync>(
-            &params.sync,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.sync = sync.id;
-        }
-        Execution::CommitSync(params) => {
-          // This one is actually a write operation.
-          let sync = super::get_check_permissions::<ResourceSync>(
-            &params.sync,
-            user,
-            PermissionLevel::Write.into(),
-          )
-          .await?;
-          params.sync = sync.id;
-        }
-        Execution::DeployStack(params) => {
-          let stack = super::get_check_permissions::<Stack>(
-            &params.stack,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.stack = stack.id;
-        }
-        Execution::BatchDeployStack(_params) => {
-          if !user.admin {
-            return Err(anyhow!(
-              "Non admin user cannot configure Batch executions"
-            ));
-          }
-        }
-        Execution::DeployStackIfChanged(params) => {
-          let stack = super::get_check_permissions::<Stack>(
-            &params.stack,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.stack = stack.id;
-        }
-        Execution::BatchDeployStackIfChanged(_params) => {
-          if !user.admin {
-            return Err(anyhow!(
-              "Non admin user cannot configure Batch executions"
-            ));
-          }
-        }
-        Execution::PullStack(params) => {
-          let stack = super::get_check_permissions::<Stack>(
-            &params.stack,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.stack = stack.id;
-        }
-        Execution::BatchPullStack(_params) => {
-          if !user.admin {
-            return Err(anyhow!(
-              "Non admin user cannot configure Batch executions"
-            ));
-          }
-        }
-        Execution::StartStack(params) => {
-          let stack = super::get_check_permissions::<Stack>(
-            &params.stack,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.stack = stack.id;
-        }
-        Execution::RestartStack(params) => {
-          let stack = super::get_check_permissions::<Stack>(
-            &params.stack,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.stack = stack.id;
-        }
-        Execution::PauseStack(params) => {
-          let stack = super::get_check_permissions::<Stack>(
-            &params.stack,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.stack = stack.id;
-        }
-        Execution::UnpauseStack(params) => {
-          let stack = super::get_check_permissions::<Stack>(
-            &params.stack,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.stack = stack.id;
-        }
-        Execution::StopStack(params) => {
-          let stack = super::get_check_permissions::<Stack>(
-            &params.stack,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.stack = stack.id;
-        }
-        Execution::DestroyStack(params) => {
-          let stack = super::get_check_permissions::<Stack>(
-            &params.stack,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.stack = stack.id;
-        }
-        Execution::RunStackService(params) => {
-          let stack = super::get_check_permissions::<Stack>(
-            &params.stack,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.stack = stack.id;
-        }
-        Execution::BatchDestroyStack(_params) => {
-          if !user.admin {
-            return Err(anyhow!(
-              "Non admin user cannot configure Batch executions"
-            ));
-          }
-        }
-        Execution::TestAlerter(params) => {
-          let alerter = super::get_check_permissions::<Alerter>(
-            &params.alerter,
-            user,
-            PermissionLevel::Execute.into(),
-          )
-          .await?;
-          params.alerter = alerter.id;
-        }
-        Execution::SendAlert(params) => {
-          params.alerters = params
-            .alerters
-            .iter()
-            .map(async |alerter| {
-              let id = super::get_check_permissions::<Alerter>(
-                alerter,
-                user,
-                PermissionLevel::Execute.into(),
-              )
-              .await?
-              .id;
-              anyhow::Ok(id)
-            })
-            .collect::<FuturesUnordered<_>>()
-            .try_collect::<Vec<_>>()
-            .await?;
-        }
-        Execution::ClearRepoCache(_params) => {
-          if !user.admin {
-            return Err(anyhow!(
-              "Non admin user cannot clear repo cache"
-            ));
-          }
-        }
-        Execution::BackupCoreDatabase(_params) => {
-          if !user.admin {
-            return Err(anyhow!(
-              "Non admin user cannot trigger core database backup"
-            ));
-          }
-        }
-        Execution::GlobalAutoUpdate(_params) => {
-          if !user.admin {
-            return Err(anyhow!(
-              "Non admin user cannot trigger global auto update"
-            ));
-          }
-        }
-        Execution::Sleep(_) => {}
+      validate_execution(&mut exec.execution, user, id).await?;
+    }
+    if let StageFailureHandler::RunExecution { execution, .. } =
+      &mut stage.on_failure
+    {
+      validate_execution(execution, user, id).await?;
+    }
+  }
+
+  Ok(())
+}
+
+async fn validate_execution(
+  execution: &mut Execution,
+  user: &User,
+  id: Option<&str>,
+) -> anyhow::Result<()> {
+  match execution {
+    Execution::None(_) => {}
+    Execution::RunProcedure(params) => {
+      let procedure = super::get_check_permissions::<Procedure>(
+        &params.procedure,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      match id {
+        Some(id) if procedure.id == id => {
+          return Err(anyhow!(
+            "Cannot have self-referential procedure"
+          ));
+        }
+        _ => {}
+      }
+      params.procedure = procedure.id;
+    }
+    Execution::BatchRunProcedure(_params) => {
+      if !user.admin {
+        return Err(anyhow!(
+          "Non admin user cannot configure Batch executions"
+        ));
+      }
+    }
+    Execution::RunAction(params) => {
+      let action = super::get_check_permissions::<Action>(
+        &params.action,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.action = action.id;
+    }
+    Execution::BatchRunAction(_params) => {
+      if !user.admin {
+        return Err(anyhow!(
+          "Non admin user cannot configure Batch executions"
+        ));
+      }
+    }
+    Execution::RunBuild(params) => {
+      let build = super::get_check_permissions::<Build>(
+        &params.build,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.build = build.id;
+    }
+    Execution::BatchRunBuild(_params) => {
+      if !user.admin {
+        return Err(anyhow!(
+          "Non admin user cannot configure Batch executions"
+        ));
+      }
+    }
+    Execution::CancelBuild(params) => {
+      let build = super::get_check_permissions::<Build>(
+        &params.build,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.build = build.id;
+    }
+    Execution::Deploy(params) => {
+      let deployment = super::get_check_permissions::<Deployment>(
+        &params.deployment,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.deployment = deployment.id;
+    }
+    Execution::BatchDeploy(_params) => {
+      if !user.admin {
+        return Err(anyhow!(
+          "Non admin user cannot configure Batch executions"
+        ));
+      }
+    }
+    Execution::PullDeployment(params) => {
+      let deployment = super::get_check_permissions::<Deployment>(
+        &params.deployment,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.deployment = deployment.id;
+    }
+    Execution::StartDeployment(params) => {
+      let deployment = super::get_check_permissions::<Deployment>(
+        &params.deployment,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.deployment = deployment.id;
+    }
+    Execution::RestartDeployment(params) => {
+      let deployment = super::get_check_permissions::<Deployment>(
+        &params.deployment,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.deployment = deployment.id;
+    }
+    Execution::PauseDeployment(params) => {
+      let deployment = super::get_check_permissions::<Deployment>(
+        &params.deployment,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.deployment = deployment.id;
+    }
+    Execution::UnpauseDeployment(params) => {
+      let deployment = super::get_check_permissions::<Deployment>(
+        &params.deployment,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.deployment = deployment.id;
+    }
+    Execution::StopDeployment(params) => {
+      let deployment = super::get_check_permissions::<Deployment>(
+        &params.deployment,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.deployment = deployment.id;
+    }
+    Execution::DestroyDeployment(params) => {
+      let deployment = super::get_check_permissions::<Deployment>(
+        &params.deployment,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.deployment = deployment.id;
+    }
+    Execution::BatchDestroyDeployment(_params) => {
+      if !user.admin {
+        return Err(anyhow!(
+          "Non admin user cannot configure Batch executions"
+        ));
+      }
+    }
+    Execution::DeploySwarmService(params) => {
+      let swarm_service =
+        super::get_check_permissions::<SwarmService>(
+          &params.swarm_service,
+          user,
+          PermissionLevel::Execute.into(),
+        )
+        .await?;
+      params.swarm_service = swarm_service.id;
+    }
+    Execution::ScaleSwarmService(params) => {
+      let swarm_service =
+        super::get_check_permissions::<SwarmService>(
+          &params.swarm_service,
+          user,
+          PermissionLevel::Execute.into(),
+        )
+        .await?;
+      params.swarm_service = swarm_service.id;
+    }
+    Execution::RemoveSwarmService(params) => {
+      let swarm_service =
+        super::get_check_permissions::<SwarmService>(
+          &params.swarm_service,
+          user,
+          PermissionLevel::Execute.into(),
+        )
+        .await?;
+      params.swarm_service = swarm_service.id;
+    }
+    Execution::CloneRepo(params) => {
+      let repo = super::get_check_permissions::<Repo>(
+        &params.repo,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.repo = repo.id;
+    }
+    Execution::BatchCloneRepo(_params) => {
+      if !user.admin {
+        return Err(anyhow!(
+          "Non admin user cannot configure Batch executions"
+        ));
+      }
+    }
+    Execution::PullRepo(params) => {
+      let repo = super::get_check_permissions::<Repo>(
+        &params.repo,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.repo = repo.id;
+    }
+    Execution::BatchPullRepo(_params) => {
+      if !user.admin {
+        return Err(anyhow!(
+          "Non admin user cannot configure Batch executions"
+        ));
+      }
+    }
+    Execution::RunRepoPipeline(params) => {
+      let repo = super::get_check_permissions::<Repo>(
+        &params.repo,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.repo = repo.id;
+    }
+    Execution::BuildRepo(params) => {
+      let repo = super::get_check_permissions::<Repo>(
+        &params.repo,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.repo = repo.id;
+    }
+    Execution::BatchBuildRepo(_params) => {
+      if !user.admin {
+        return Err(anyhow!(
+          "Non admin user cannot configure Batch executions"
+        ));
+      }
+    }
+    Execution::CancelRepoBuild(params) => {
+      let repo = super::get_check_permissions::<Repo>(
+        &params.repo,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.repo = repo.id;
+    }
+    Execution::StartContainer(params) => {
+      let server = super::get_check_permissions::<Server>(
+        &params.server,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.server = server.id;
+    }
+    Execution::RestartContainer(params) => {
+      let server = super::get_check_permissions::<Server>(
+        &params.server,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.server = server.id;
+    }
+    Execution::PauseContainer(params) => {
+      let server = super::get_check_permissions::<Server>(
+        &params.server,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.server = server.id;
+    }
+    Execution::UnpauseContainer(params) => {
+      let server = super::get_check_permissions::<Server>(
+        &params.server,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.server = server.id;
+    }
+    Execution::StopContainer(params) => {
+      let server = super::get_check_permissions::<Server>(
+        &params.server,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.server = server.id;
+    }
+    Execution::DestroyContainer(params) => {
+      let server = super::get_check_permissions::<Server>(
+        &params.server,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.server = server.id;
+    }
+    Execution::StartAllContainers(params) => {
+      let server = super::get_check_permissions::<Server>(
+        &params.server,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.server = server.id;
+    }
+    Execution::RestartAllContainers(params) => {
+      let server = super::get_check_permissions::<Server>(
+        &params.server,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.server = server.id;
+    }
+    Execution::PauseAllContainers(params) => {
+      let server = super::get_check_permissions::<Server>(
+        &params.server,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.server = server.id;
+    }
+    Execution::UnpauseAllContainers(params) => {
+      let server = super::get_check_permissions::<Server>(
+        &params.server,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.server = server.id;
+    }
+    Execution::StopAllContainers(params) => {
+      let server = super::get_check_permissions::<Server>(
+        &params.server,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.server = server.id;
+    }
+    Execution::PruneContainers(params) => {
+      let server = super::get_check_permissions::<Server>(
+        &params.server,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.server = server.id;
+    }
+    Execution::DeleteNetwork(params) => {
+      let server = super::get_check_permissions::<Server>(
+        &params.server,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.server = server.id;
+    }
+    Execution::PruneNetworks(params) => {
+      let server = super::get_check_permissions::<Server>(
+        &params.server,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.server = server.id;
+    }
+    Execution::DeleteImage(params) => {
+      let server = super::get_check_permissions::<Server>(
+        &params.server,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.server = server.id;
+    }
+    Execution::PruneImages(params) => {
+      let server = super::get_check_permissions::<Server>(
+        &params.server,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.server = server.id;
+    }
+    Execution::DeleteVolume(params) => {
+      let server = super::get_check_permissions::<Server>(
+        &params.server,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.server = server.id;
+    }
+    Execution::PruneVolumes(params) => {
+      let server = super::get_check_permissions::<Server>(
+        &params.server,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.server = server.id;
+    }
+    Execution::PruneDockerBuilders(params) => {
+      let server = super::get_check_permissions::<Server>(
+        &params.server,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.server = server.id;
+    }
+    Execution::PruneBuildx(params) => {
+      let server = super::get_check_permissions::<Server>(
+        &params.server,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.server = server.id;
+    }
+    Execution::PruneSystem(params) => {
+      let server = super::get_check_permissions::<Server>(
+        &params.server,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.server = server.id;
+    }
+    Execution::RunScheduledCommand(params) => {
+      let server = super::get_check_permissions::<Server>(
+        &params.server,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.server = server.id;
+    }
+    Execution::RunSync(params) => {
+      let sync = super::get_check_permissions::<ResourceSync>(
+        &params.sync,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.sync = sync.id;
+    }
+    Execution::CommitSync(params) => {
+      // This one is actually a write operation.
+      let sync = super::get_check_permissions::<ResourceSync>(
+        &params.sync,
+        user,
+        PermissionLevel::Write.into(),
+      )
+      .await?;
+      params.sync = sync.id;
+    }
+    Execution::DeployStack(params) => {
+      let stack = super::get_check_permissions::<Stack>(
+        &params.stack,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.stack = stack.id;
+    }
+    Execution::BatchDeployStack(_params) => {
+      if !user.admin {
+        return Err(anyhow!(
+          "Non admin user cannot configure Batch executions"
+        ));
+      }
+    }
+    Execution::DeployStackIfChanged(params) => {
+      let stack = super::get_check_permissions::<Stack>(
+        &params.stack,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.stack = stack.id;
+    }
+    Execution::BatchDeployStackIfChanged(_params) => {
+      if !user.admin {
+        return Err(anyhow!(
+          "Non admin user cannot configure Batch executions"
+        ));
+      }
+    }
+    Execution::PullStack(params) => {
+      let stack = super::get_check_permissions::<Stack>(
+        &params.stack,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.stack = stack.id;
+    }
+    Execution::BatchPullStack(_params) => {
+      if !user.admin {
+        return Err(anyhow!(
+          "Non admin user cannot configure Batch executions"
+        ));
+      }
+    }
+    Execution::StartStack(params) => {
+      let stack = super::get_check_permissions::<Stack>(
+        &params.stack,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.stack = stack.id;
+    }
+    Execution::RestartStack(params) => {
+      let stack = super::get_check_permissions::<Stack>(
+        &params.stack,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.stack = stack.id;
+    }
+    Execution::PauseStack(params) => {
+      let stack = super::get_check_permissions::<Stack>(
+        &params.stack,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.stack = stack.id;
+    }
+    Execution::UnpauseStack(params) => {
+      let stack = super::get_check_permissions::<Stack>(
+        &params.stack,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.stack = stack.id;
+    }
+    Execution::StopStack(params) => {
+      let stack = super::get_check_permissions::<Stack>(
+        &params.stack,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.stack = stack.id;
+    }
+    Execution::DestroyStack(params) => {
+      let stack = super::get_check_permissions::<Stack>(
+        &params.stack,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.stack = stack.id;
+    }
+    Execution::RunStackService(params) => {
+      let stack = super::get_check_permissions::<Stack>(
+        &params.stack,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.stack = stack.id;
+    }
+    Execution::ScaleStackService(params) => {
+      let stack = super::get_check_permissions::<Stack>(
+        &params.stack,
+        user,
+        PermissionLevel::Write.into(),
+      )
+      .await?;
+      params.stack = stack.id;
+    }
+    Execution::BatchDestroyStack(_params) => {
+      if !user.admin {
+        return Err(anyhow!(
+          "Non admin user cannot configure Batch executions"
+        ));
+      }
+    }
+    Execution::TestAlerter(params) => {
+      let alerter = super::get_check_permissions::<Alerter>(
+        &params.alerter,
+        user,
+        PermissionLevel::Execute.into(),
+      )
+      .await?;
+      params.alerter = alerter.id;
+    }
+    Execution::SendAlert(params) => {
+      params.alerters = params
+        .alerters
+        .iter()
+        .map(async |alerter| {
+          let id = super::get_check_permissions::<Alerter>(
+            alerter,
+            user,
+            PermissionLevel::Execute.into(),
+          )
+          .await?
+          .id;
+          anyhow::Ok(id)
+        })
+        .collect::<FuturesUnordered<_>>()
+        .try_collect::<Vec<_>>()
+        .await?;
+    }
+    Execution::ClearRepoCache(_params) => {
+      if !user.admin {
+        return Err(anyhow!(
+          "Non admin user cannot clear repo cache"
+        ));
+      }
+    }
+    Execution::BackupCoreDatabase(_params) => {
+      if !user.admin {
+        return Err(anyhow!(
+          "Non admin user cannot trigger core database backup"
+        ));
+      }
+    }
+    Execution::GlobalAutoUpdate(_params) => {
+      if !user.admin {
+        return Err(anyhow!(
+          "Non admin user cannot trigger global auto update"
+        ));
       }
     }
+    Execution::Sleep(_) => {}
   }
 
   Ok(())
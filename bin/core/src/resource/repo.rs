@@ -67,6 +67,7 @@ impl super::KomodoResource for Repo {
       name: repo.name,
       id: repo.id,
       template: repo.template,
+      protected: repo.protected,
       tags: repo.tags,
       resource_type: ResourceTargetVariant::Repo,
       info: RepoListItemInfo {
@@ -169,7 +170,7 @@ impl super::KomodoResource for Repo {
     }
 
     let server = super::get::<Server>(&repo.config.server_id).await?;
-    let periphery = periphery_client(&server)?;
+    let periphery = periphery_client(&server).await?;
 
     match periphery
       .request(DeleteRepo {
@@ -277,6 +278,8 @@ async fn get_repo_state(id: &String) -> RepoState {
             Some(RepoState::Pulling)
           } else if s.building {
             Some(RepoState::Building)
+          } else if s.running_pipeline {
+            Some(RepoState::RunningPipeline)
           } else {
             None
           }
@@ -301,6 +304,7 @@ async fn get_repo_state_from_db(id: &str) -> RepoState {
           { "operation": "CloneRepo" },
           { "operation": "PullRepo" },
           { "operation": "BuildRepo" },
+          { "operation": "RunRepoPipeline" },
         ],
       })
       .with_options(
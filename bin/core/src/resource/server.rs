@@ -1,22 +1,30 @@
-use anyhow::Context;
+use anyhow::{Context, anyhow};
 use database::mungos::mongodb::{Collection, bson::doc};
 use indexmap::IndexSet;
 use komodo_client::entities::{
   Operation, ResourceTarget, ResourceTargetVariant, komodo_timestamp,
-  permission::SpecificPermission,
+  permission::{PermissionLevel, SpecificPermission},
   resource::Resource,
   server::{
-    PartialServerConfig, Server, ServerConfig, ServerConfigDiff,
-    ServerListItem, ServerListItemInfo, ServerQuerySpecifics,
+    PartialServerConfig, PeripheryConnection, ScheduledCommand,
+    Server, ServerConfig, ServerConfigDiff, ServerListItem,
+    ServerListItemInfo, ServerMode, ServerQuerySpecifics,
   },
+  server_profile::ServerProfileConfig,
   update::Update,
   user::User,
 };
 
 use crate::{
   config::core_config,
-  helpers::query::get_system_info,
+  helpers::{
+    query::{get_server_profile, get_system_info},
+    ssh_tunnel::close_ssh_tunnel,
+    validate_periphery_address,
+  },
   monitor::update_cache_for_server,
+  permission::check_permissions,
+  schedule::{cancel_scheduled_commands, update_scheduled_commands},
   state::{action_states, db_client, server_status_cache},
 };
 
@@ -66,6 +74,7 @@ impl super::KomodoResource for Server {
       name: server.name,
       id: server.id,
       template: server.template,
+      protected: server.protected,
       tags: server.tags,
       resource_type: ResourceTargetVariant::Server,
       info: ServerListItemInfo {
@@ -113,10 +122,13 @@ impl super::KomodoResource for Server {
   }
 
   async fn validate_create_config(
-    _config: &mut Self::PartialConfig,
-    _user: &User,
+    config: &mut Self::PartialConfig,
+    user: &User,
   ) -> anyhow::Result<()> {
-    Ok(())
+    if let Some(commands) = &mut config.scheduled_commands {
+      inherit_scheduled_command_creators(&[], commands, &user.id);
+    }
+    validate_config(config).await
   }
 
   async fn post_create(
@@ -124,6 +136,7 @@ impl super::KomodoResource for Server {
     _update: &mut Update,
   ) -> anyhow::Result<()> {
     update_cache_for_server(created, true).await;
+    update_scheduled_commands(created);
     Ok(())
   }
 
@@ -134,11 +147,34 @@ impl super::KomodoResource for Server {
   }
 
   async fn validate_update_config(
-    _id: &str,
-    _config: &mut Self::PartialConfig,
-    _user: &User,
+    id: &str,
+    config: &mut Self::PartialConfig,
+    user: &User,
   ) -> anyhow::Result<()> {
-    Ok(())
+    if let Some(commands) = &mut config.scheduled_commands {
+      let server = super::get::<Server>(id).await?;
+      let previous = &server.config.scheduled_commands;
+      // Scheduled commands run arbitrary commands on the host,
+      // so adding or changing them requires terminal access.
+      let changed = commands.iter().any(|command| {
+        !previous
+          .iter()
+          .any(|previous| previous.same_command(command))
+      });
+      if changed {
+        check_permissions::<Server>(
+          server.clone(),
+          user,
+          PermissionLevel::Write.terminal(),
+        )
+        .await
+        .context("Adding or changing scheduled commands requires Terminal permission on the Server")?;
+      }
+      inherit_scheduled_command_creators(
+        previous, commands, &user.id,
+      );
+    }
+    validate_config(config).await
   }
 
   async fn post_update(
@@ -146,6 +182,7 @@ impl super::KomodoResource for Server {
     _update: &mut Update,
   ) -> anyhow::Result<()> {
     update_cache_for_server(updated, true).await;
+    update_scheduled_commands(updated);
     Ok(())
   }
 
@@ -220,6 +257,87 @@ impl super::KomodoResource for Server {
     _update: &mut Update,
   ) -> anyhow::Result<()> {
     server_status_cache().remove(&resource.id).await;
+    cancel_scheduled_commands(&resource.id);
+    close_ssh_tunnel(&resource.id).await;
     Ok(())
   }
 }
+
+async fn validate_config(
+  config: &PartialServerConfig,
+) -> anyhow::Result<()> {
+  if let Some(address) = &config.address
+    && !address.is_empty()
+  {
+    validate_periphery_address(address)?;
+  }
+  if let Some(PeripheryConnection::Ssh {
+    host,
+    user,
+    key_secret,
+    ..
+  }) = &config.connection
+  {
+    if host.is_empty() || user.is_empty() {
+      return Err(anyhow!("SSH connection requires host and user"));
+    }
+    if !core_config().secrets.contains_key(key_secret) {
+      return Err(anyhow!(
+        "SSH key secret '{key_secret}' is not defined in Core config"
+      ));
+    }
+  }
+  if let Some(ServerMode::DockerApi {
+    ca_secret,
+    cert_secret,
+    key_secret,
+  }) = &config.mode
+  {
+    if cert_secret.is_empty() != key_secret.is_empty() {
+      return Err(anyhow!(
+        "Docker API TLS requires both the cert and key secrets"
+      ));
+    }
+    for secret in [ca_secret, cert_secret, key_secret] {
+      if !secret.is_empty()
+        && !core_config().secrets.contains_key(secret)
+      {
+        return Err(anyhow!(
+          "Docker API TLS secret '{secret}' is not defined in Core config"
+        ));
+      }
+    }
+  }
+  if let Some(server_profile) = &config.server_profile
+    && !server_profile.is_empty()
+  {
+    get_server_profile(server_profile).await?;
+  }
+  if let Some(profile_overrides) = &config.profile_overrides {
+    for field in profile_overrides {
+      if !ServerProfileConfig::FIELDS.contains(&field.as_str()) {
+        return Err(anyhow!(
+          "Profile override '{field}' is not a field a Server Profile can set"
+        ));
+      }
+    }
+  }
+  Ok(())
+}
+
+/// Scheduled commands run with the permissions of the user who
+/// created or last changed them. Unchanged commands keep their
+/// previous creator, new or changed commands get `creator`.
+pub fn inherit_scheduled_command_creators(
+  previous: &[ScheduledCommand],
+  commands: &mut [ScheduledCommand],
+  creator: &str,
+) {
+  for command in commands {
+    command.created_by = previous
+      .iter()
+      .find(|previous| previous.same_command(command))
+      .map(|previous| previous.created_by.clone())
+      .unwrap_or_else(|| creator.to_string());
+  }
+}
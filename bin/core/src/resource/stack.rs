@@ -26,8 +26,18 @@ use resolver_api::Resolve;
 use crate::{
   api::write::WriteArgs,
   config::core_config,
-  helpers::{periphery_client, query::get_stack_state, repo_link},
+  helpers::{
+    execution_metrics::execution_metrics, periphery_client,
+    query::get_stack_state, repo_link, uptime::uptime_override,
+  },
   monitor::update_cache_for_server,
+  stack::{
+    runtime_drift::stack_has_runtime_drift,
+    storage::{
+      clear_stored_file_contents, delete_stored_file,
+      load_stored_file_contents, sync_stored_file_contents,
+    },
+  },
   state::{
     action_states, all_resources_cache, db_client,
     server_status_cache, stack_status_cache,
@@ -164,10 +174,15 @@ impl super::KomodoResource for Stack {
         (false, None)
       };
 
+    let uptime_override =
+      uptime_override(&ResourceTarget::Stack(stack.id.clone()));
+    let metrics =
+      execution_metrics(&ResourceTarget::Stack(stack.id.clone()));
     StackListItem {
       name: stack.name,
       id: stack.id,
       template: stack.template,
+      protected: stack.protected,
       tags: stack.tags,
       resource_type: ResourceTargetVariant::Stack,
       info: StackListItemInfo {
@@ -175,7 +190,9 @@ impl super::KomodoResource for Stack {
         status,
         services,
         project_missing,
-        file_contents: !stack.config.file_contents.is_empty(),
+        runtime_drift: stack_has_runtime_drift(&stack.id),
+        file_contents: !stack.config.file_contents.is_empty()
+          || stack.info.stored_file_contents.is_some(),
         server_id: stack.config.server_id,
         linked_repo: stack.config.linked_repo,
         missing_files: stack.info.missing_files,
@@ -191,6 +208,8 @@ impl super::KomodoResource for Stack {
         branch,
         latest_hash: stack.info.latest_hash,
         deployed_hash: stack.info.deployed_hash,
+        uptime_override,
+        metrics,
       },
     }
   }
@@ -204,6 +223,12 @@ impl super::KomodoResource for Stack {
       .busy()
   }
 
+  async fn load_stored_config(
+    stack: &mut Resource<Self::Config, Self::Info>,
+  ) -> anyhow::Result<()> {
+    load_stored_file_contents(stack).await
+  }
+
   // CREATE
 
   fn create_operation() -> Operation {
@@ -225,6 +250,15 @@ impl super::KomodoResource for Stack {
     created: &Resource<Self::Config, Self::Info>,
     update: &mut Update,
   ) -> anyhow::Result<()> {
+    // Before the refresh, which reads the Stack back
+    if let Err(e) = sync_stored_file_contents(&created.id).await {
+      update.push_error_log(
+        "Store file contents",
+        format_serror(
+          &e.context("Failed to move the file contents to the Stack file storage. They are kept in the database.").into(),
+        ),
+      );
+    }
     if let Err(e) = (RefreshStackCache {
       stack: created.name.clone(),
     })
@@ -263,11 +297,20 @@ impl super::KomodoResource for Stack {
   }
 
   async fn validate_update_config(
-    _id: &str,
+    id: &str,
     config: &mut Self::PartialConfig,
     user: &User,
   ) -> anyhow::Result<()> {
-    validate_config(config, user).await
+    validate_config(config, user).await?;
+    // Otherwise the stored contents would be loaded back in.
+    if config
+      .file_contents
+      .as_ref()
+      .is_some_and(|contents| contents.is_empty())
+    {
+      clear_stored_file_contents(id).await?;
+    }
+    Ok(())
   }
 
   async fn post_update(
@@ -327,7 +370,7 @@ impl super::KomodoResource for Stack {
       return Ok(());
     }
 
-    let periphery = match periphery_client(&server) {
+    let periphery = match periphery_client(&server).await {
       Ok(periphery) => periphery,
       Err(e) => {
         // This case won't ever happen, as periphery_client only fallible if the server is disabled.
@@ -366,9 +409,17 @@ impl super::KomodoResource for Stack {
 
   async fn post_delete(
     resource: &Resource<Self::Config, Self::Info>,
-    _update: &mut Update,
+    update: &mut Update,
   ) -> anyhow::Result<()> {
     stack_status_cache().remove(&resource.id).await;
+    if let Some(stored) = &resource.info.stored_file_contents
+      && let Err(e) = delete_stored_file(stored).await
+    {
+      update.push_error_log(
+        "Delete stored file contents",
+        format_serror(&e.into()),
+      );
+    }
     Ok(())
   }
 }
@@ -404,5 +455,21 @@ async fn validate_config(
     // in case it comes in as name
     config.linked_repo = Some(repo.id);
   }
+  for compose_override in
+    config.compose_overrides.iter_mut().flatten()
+  {
+    if compose_override.repo.is_empty() {
+      continue;
+    }
+    let repo = get_check_permissions::<Repo>(
+      &compose_override.repo,
+      user,
+      PermissionLevel::Read.attach(),
+    )
+    .await
+    .context("Cannot attach compose override Repo to this Stack")?;
+    // in case it comes in as name
+    compose_override.repo = repo.id;
+  }
   Ok(())
 }
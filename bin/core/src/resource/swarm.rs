@@ -0,0 +1,323 @@
+use anyhow::{Context, anyhow};
+use database::mungos::mongodb::Collection;
+use formatting::format_serror;
+use indexmap::IndexSet;
+use komodo_client::entities::{
+  Operation, ResourceTarget, ResourceTargetVariant,
+  deployment::conversions_from_str,
+  environment_vars_from_str,
+  permission::{PermissionLevel, SpecificPermission},
+  resource::Resource,
+  server::Server,
+  swarm::{
+    PartialSwarmServiceConfig, SwarmService, SwarmServiceConfig,
+    SwarmServiceConfigDiff, SwarmServiceListItem,
+    SwarmServiceListItemInfo, SwarmServiceQuerySpecifics,
+    SwarmServiceState,
+  },
+  to_docker_compatible_name,
+  update::Update,
+  user::User,
+};
+use periphery_client::api::swarm::RemoveSwarmStack;
+
+use crate::{
+  config::core_config,
+  helpers::{
+    empty_or_only_spaces, periphery_client,
+    query::get_swarm_service_state,
+  },
+  monitor::update_cache_for_server,
+  state::{action_states, db_client, swarm_service_status_cache},
+};
+
+use super::get_check_permissions;
+
+impl super::KomodoResource for SwarmService {
+  type Config = SwarmServiceConfig;
+  type PartialConfig = PartialSwarmServiceConfig;
+  type ConfigDiff = SwarmServiceConfigDiff;
+  type Info = ();
+  type ListItem = SwarmServiceListItem;
+  type QuerySpecifics = SwarmServiceQuerySpecifics;
+
+  fn resource_type() -> ResourceTargetVariant {
+    ResourceTargetVariant::SwarmService
+  }
+
+  fn resource_target(id: impl Into<String>) -> ResourceTarget {
+    ResourceTarget::SwarmService(id.into())
+  }
+
+  /// The name is used as the docker stack name.
+  fn validated_name(name: &str) -> String {
+    to_docker_compatible_name(name)
+  }
+
+  fn creator_specific_permissions() -> IndexSet<SpecificPermission> {
+    [SpecificPermission::Inspect, SpecificPermission::Logs]
+      .into_iter()
+      .collect()
+  }
+
+  fn inherit_specific_permissions_from(
+    _self: &Resource<Self::Config, Self::Info>,
+  ) -> Option<ResourceTarget> {
+    ResourceTarget::Server(_self.config.server_id.clone()).into()
+  }
+
+  fn coll() -> &'static Collection<Resource<Self::Config, Self::Info>>
+  {
+    &db_client().swarm_services
+  }
+
+  async fn to_list_item(
+    swarm_service: Resource<Self::Config, Self::Info>,
+  ) -> Self::ListItem {
+    let status =
+      swarm_service_status_cache().get(&swarm_service.id).await;
+    let state = if action_states()
+      .swarm_service
+      .get(&swarm_service.id)
+      .await
+      .map(|s| s.get().map(|s| s.deploying))
+      .transpose()
+      .ok()
+      .flatten()
+      .unwrap_or_default()
+    {
+      SwarmServiceState::Deploying
+    } else {
+      status.as_ref().map(|s| s.curr.state).unwrap_or_default()
+    };
+    let (running, desired) = status
+      .as_ref()
+      .map(|s| {
+        s.curr.services.iter().fold(
+          (0, 0),
+          |(running, desired), service| {
+            (running + service.running, desired + service.desired)
+          },
+        )
+      })
+      .unwrap_or_default();
+    let image =
+      if swarm_service.config.file_contents.trim().is_empty() {
+        swarm_service.config.image
+      } else {
+        String::new()
+      };
+    SwarmServiceListItem {
+      name: swarm_service.name,
+      id: swarm_service.id,
+      template: swarm_service.template,
+      protected: swarm_service.protected,
+      tags: swarm_service.tags,
+      resource_type: ResourceTargetVariant::SwarmService,
+      info: SwarmServiceListItemInfo {
+        state,
+        server_id: swarm_service.config.server_id,
+        image,
+        running,
+        desired,
+      },
+    }
+  }
+
+  async fn busy(id: &String) -> anyhow::Result<bool> {
+    action_states()
+      .swarm_service
+      .get(id)
+      .await
+      .unwrap_or_default()
+      .busy()
+  }
+
+  // CREATE
+
+  fn create_operation() -> Operation {
+    Operation::CreateSwarmService
+  }
+
+  fn user_can_create(user: &User) -> bool {
+    user.admin || !core_config().disable_non_admin_create
+  }
+
+  async fn validate_create_config(
+    config: &mut Self::PartialConfig,
+    user: &User,
+  ) -> anyhow::Result<()> {
+    validate_config(config, user).await
+  }
+
+  async fn post_create(
+    created: &Resource<Self::Config, Self::Info>,
+    _update: &mut Update,
+  ) -> anyhow::Result<()> {
+    if created.config.server_id.is_empty() {
+      return Ok(());
+    }
+    let Ok(server) = super::get::<Server>(&created.config.server_id)
+      .await
+      .inspect_err(|e| {
+        warn!(
+          "Failed to get Server for Swarm Service {} | {e:#}",
+          created.name
+        )
+      })
+    else {
+      return Ok(());
+    };
+    update_cache_for_server(&server, true).await;
+    Ok(())
+  }
+
+  // UPDATE
+
+  fn update_operation() -> Operation {
+    Operation::UpdateSwarmService
+  }
+
+  async fn validate_update_config(
+    _id: &str,
+    config: &mut Self::PartialConfig,
+    user: &User,
+  ) -> anyhow::Result<()> {
+    validate_config(config, user).await
+  }
+
+  async fn post_update(
+    updated: &Self,
+    update: &mut Update,
+  ) -> anyhow::Result<()> {
+    Self::post_create(updated, update).await
+  }
+
+  // RENAME
+
+  fn rename_operation() -> Operation {
+    Operation::RenameSwarmService
+  }
+
+  // DELETE
+
+  fn delete_operation() -> Operation {
+    Operation::DeleteSwarmService
+  }
+
+  async fn pre_delete(
+    swarm_service: &Resource<Self::Config, Self::Info>,
+    update: &mut Update,
+  ) -> anyhow::Result<()> {
+    let state = get_swarm_service_state(&swarm_service.id)
+      .await
+      .context("Failed to get swarm service state")?;
+    if matches!(
+      state,
+      SwarmServiceState::NotDeployed | SwarmServiceState::Unknown
+    ) {
+      return Ok(());
+    }
+    // stack needs to be removed
+    let server =
+      match super::get::<Server>(&swarm_service.config.server_id)
+        .await
+      {
+        Ok(server) => server,
+        Err(e) => {
+          update.push_error_log(
+            "Remove Stack",
+            format_serror(
+              &e.context(format!(
+                "failed to retrieve server at {} from db.",
+                swarm_service.config.server_id
+              ))
+              .into(),
+            ),
+          );
+          return Ok(());
+        }
+      };
+    if !server.config.enabled {
+      update.push_simple_log(
+        "Remove Stack",
+        "Skipping stack removal, server is disabled.",
+      );
+      return Ok(());
+    }
+    let periphery = match periphery_client(&server).await {
+      Ok(periphery) => periphery,
+      Err(e) => {
+        update.push_error_log(
+          "Remove Stack",
+          format_serror(
+            &e.context("Failed to get periphery client").into(),
+          ),
+        );
+        return Ok(());
+      }
+    };
+    match periphery
+      .request(RemoveSwarmStack {
+        stack: swarm_service.name.clone(),
+      })
+      .await
+    {
+      Ok(log) => update.logs.push(log),
+      Err(e) => update.push_error_log(
+        "Remove Stack",
+        format_serror(&e.context("Failed to remove stack").into()),
+      ),
+    };
+    Ok(())
+  }
+
+  async fn post_delete(
+    resource: &Resource<Self::Config, Self::Info>,
+    _update: &mut Update,
+  ) -> anyhow::Result<()> {
+    swarm_service_status_cache().remove(&resource.id).await;
+    Ok(())
+  }
+}
+
+#[instrument(skip(user))]
+async fn validate_config(
+  config: &mut PartialSwarmServiceConfig,
+  user: &User,
+) -> anyhow::Result<()> {
+  if let Some(server_id) = &config.server_id
+    && !server_id.is_empty()
+  {
+    let server = get_check_permissions::<Server>(
+      server_id,
+      user,
+      PermissionLevel::Read.attach(),
+    )
+    .await
+    .context("Cannot attach Swarm Service to this Server")?;
+    config.server_id = Some(server.id);
+  }
+  if let Some(replicas) = config.replicas
+    && replicas < 0
+  {
+    return Err(anyhow!("Replicas cannot be negative"));
+  }
+  if let Some(ports) = &config.ports {
+    conversions_from_str(ports).context("Invalid ports")?;
+  }
+  if let Some(environment) = &config.environment {
+    environment_vars_from_str(environment)
+      .context("Invalid environment")?;
+  }
+  if let Some(labels) = &config.labels {
+    environment_vars_from_str(labels).context("Invalid labels")?;
+  }
+  if let Some(networks) = &mut config.networks {
+    networks.retain(|v| !empty_or_only_spaces(v))
+  }
+  if let Some(extra_args) = &mut config.extra_args {
+    extra_args.retain(|v| !empty_or_only_spaces(v))
+  }
+  Ok(())
+}
@@ -82,15 +82,30 @@ impl super::KomodoResource for ResourceSync {
           .unwrap_or(default_git)
       };
 
+    let artifact_source =
+      if !resource_sync.config.oci_artifact.is_empty() {
+        resource_sync.config.oci_artifact
+      } else if !resource_sync.config.s3_bucket.is_empty() {
+        format!(
+          "s3://{}/{}",
+          resource_sync.config.s3_bucket,
+          resource_sync.config.s3_prefix
+        )
+      } else {
+        String::new()
+      };
+
     ResourceSyncListItem {
       name: resource_sync.name,
       id: resource_sync.id,
       template: resource_sync.template,
+      protected: resource_sync.protected,
       tags: resource_sync.tags,
       resource_type: ResourceTargetVariant::ResourceSync,
       info: ResourceSyncListItemInfo {
         file_contents: !resource_sync.config.file_contents.is_empty(),
         files_on_host: resource_sync.config.files_on_host,
+        artifact_source,
         managed: resource_sync.config.managed,
         linked_repo: resource_sync.config.linked_repo,
         repo_link: repo_link(
@@ -7,29 +7,62 @@ use anyhow::{Context, anyhow};
 use async_timing_util::Timelength;
 use chrono::Local;
 use croner::parser::CronParser;
-use database::mungos::find::find_collect;
+use database::mungos::{
+  by_id::update_one_by_id,
+  find::find_collect,
+  mongodb::{
+    bson::{doc, oid::ObjectId},
+    options::{FindOptions, ReplaceOptions},
+  },
+};
 use formatting::format_serror;
 use komodo_client::{
-  api::execute::{RunAction, RunProcedure},
+  api::{
+    execute::{
+      Execution, RunAction, RunBuild, RunProcedure,
+      RunScheduledCommand,
+    },
+    write::RefreshBuildCache,
+  },
   entities::{
     ResourceTarget, ResourceTargetVariant, ScheduleFormat,
     action::Action,
     alert::{Alert, AlertData, SeverityLevel},
+    build::Build,
+    execution_schedule::{
+      ExecutionSchedule, ExecutionScheduleRun, MissedRunPolicy,
+      ResourceSchedule,
+    },
     komodo_timestamp,
     procedure::Procedure,
-    user::{action_user, procedure_user},
+    server::{ScheduledCommand, Server},
+    user::{action_user, procedure_user, scheduler_user},
   },
 };
 use resolver_api::Resolve;
 
 use crate::{
   alert::send_alerts,
-  api::execute::{ExecuteArgs, ExecuteRequest},
+  api::{
+    execute::{ExecuteArgs, ExecuteRequest},
+    write::WriteArgs,
+  },
   config::core_config,
-  helpers::update::init_execution_update,
+  helpers::{
+    execution_queue::run_execution_as_user,
+    query::get_execution_schedule,
+    update::{init_execution_update, update_update},
+  },
   state::db_client,
 };
 
+/// The number of runs kept in each execution schedule's run history.
+const EXECUTION_SCHEDULE_RUN_HISTORY: u64 = 100;
+
+/// Scheduled runs starting later than this are alerted as missed,
+/// as Core was down or the scheduler fell behind.
+const SCHEDULE_LATE_THRESHOLD_MS: i64 = 60_000;
+
 pub fn spawn_schedule_executor() {
   // Executor thread
   tokio::spawn(async move {
@@ -40,11 +73,15 @@ pub fn spawn_schedule_executor() {
         0,
       )
       .await as i64;
+      run_due_scheduled_commands(current_time);
+      run_due_execution_schedules(current_time);
       let mut lock = schedules().write().unwrap();
       let drained = lock.drain().collect::<Vec<_>>();
       for (target, next_run) in drained {
         match next_run {
           Ok(next_run_time) if current_time >= next_run_time => {
+            // Clear the stored next run, so the run isn't seen as missed.
+            store_next_run(&target, 0);
             tokio::spawn(async move {
               match &target {
                 ResourceTarget::Action(id) => {
@@ -61,6 +98,7 @@ pub fn spawn_schedule_executor() {
                       return;
                     }
                   };
+                  check_run_late(&action, next_run_time);
                   let request =
                     ExecuteRequest::RunAction(RunAction {
                       action: id.clone(),
@@ -109,6 +147,7 @@ pub fn spawn_schedule_executor() {
                         id: action.id,
                         name: action.name,
                       },
+                      notifications: Default::default(),
                     };
                     send_alerts(&[alert]).await
                   }
@@ -127,9 +166,11 @@ pub fn spawn_schedule_executor() {
                       return;
                     }
                   };
+                  check_run_late(&procedure, next_run_time);
                   let request =
                     ExecuteRequest::RunProcedure(RunProcedure {
                       procedure: id.clone(),
+                      inputs: None,
                     });
                   let update = match init_execution_update(
                     &request,
@@ -175,10 +216,14 @@ pub fn spawn_schedule_executor() {
                         id: procedure.id,
                         name: procedure.name,
                       },
+                      notifications: Default::default(),
                     };
                     send_alerts(&[alert]).await
                   }
                 }
+                ResourceTarget::Build(id) => {
+                  run_scheduled_build(id, next_run_time).await
+                }
                 _ => unreachable!(),
               }
             });
@@ -202,6 +247,28 @@ fn schedules() -> &'static RwLock<Schedules> {
   SCHEDULES.get_or_init(Default::default)
 }
 
+/// (Server id, ScheduledCommand name)
+type ScheduledCommandKey = (String, String);
+type ScheduledCommandSchedules =
+  HashMap<ScheduledCommandKey, Result<UnixTimestampMs, String>>;
+
+fn scheduled_commands() -> &'static RwLock<ScheduledCommandSchedules>
+{
+  static SCHEDULED_COMMANDS: OnceLock<
+    RwLock<ScheduledCommandSchedules>,
+  > = OnceLock::new();
+  SCHEDULED_COMMANDS.get_or_init(Default::default)
+}
+
+type ExecutionSchedules =
+  HashMap<String, Result<UnixTimestampMs, String>>;
+
+fn execution_schedules() -> &'static RwLock<ExecutionSchedules> {
+  static EXECUTION_SCHEDULES: OnceLock<RwLock<ExecutionSchedules>> =
+    OnceLock::new();
+  EXECUTION_SCHEDULES.get_or_init(Default::default)
+}
+
 pub fn get_schedule_item_info(
   target: &ResourceTarget,
 ) -> (Option<i64>, Option<String>) {
@@ -214,12 +281,24 @@ pub fn get_schedule_item_info(
 
 pub fn cancel_schedule(target: &ResourceTarget) {
   schedules().write().unwrap().remove(target);
+  store_next_run(target, 0);
 }
 
 pub async fn update_schedules() {
-  let (procedures, actions) = tokio::join!(
+  let (
+    procedures,
+    actions,
+    builds,
+    servers,
+    execution_schedules,
+    resource_schedules,
+  ) = tokio::join!(
     find_collect(&db_client().procedures, None, None),
     find_collect(&db_client().actions, None, None),
+    find_collect(&db_client().builds, None, None),
+    find_collect(&db_client().servers, None, None),
+    find_collect(&db_client().execution_schedules, None, None),
+    find_collect(&db_client().resource_schedules, None, None),
   );
   let procedures = match procedures
     .context("failed to get all procedures from db")
@@ -238,6 +317,22 @@ pub async fn update_schedules() {
         Vec::new()
       }
     };
+  let builds =
+    match builds.context("failed to get all builds from db") {
+      Ok(builds) => builds,
+      Err(e) => {
+        error!("failed to get builds for schedule update | {e:#}");
+        Vec::new()
+      }
+    };
+  let servers =
+    match servers.context("failed to get all servers from db") {
+      Ok(servers) => servers,
+      Err(e) => {
+        error!("failed to get servers for schedule update | {e:#}");
+        Vec::new()
+      }
+    };
   // clear out any schedules which don't match to existing resources
   {
     let mut lock = schedules().write().unwrap();
@@ -248,33 +343,677 @@ pub async fn update_schedules() {
       ResourceTarget::Procedure(id) => {
         procedures.iter().any(|procedure| &procedure.id == id)
       }
+      ResourceTarget::Build(id) => {
+        builds.iter().any(|build| &build.id == id)
+      }
       _ => unreachable!(),
     });
   }
+  let stored_next_runs = match resource_schedules
+    .context("failed to get all resource schedules from db")
+  {
+    Ok(schedules) => schedules
+      .into_iter()
+      .map(|schedule| (schedule.id, schedule.next_run_at))
+      .collect::<HashMap<_, _>>(),
+    Err(e) => {
+      error!(
+        "failed to get stored next runs for schedule update | {e:#}"
+      );
+      HashMap::new()
+    }
+  };
   for procedure in procedures {
-    update_schedule(&procedure);
+    restore_schedule(&procedure, &stored_next_runs);
   }
   for action in actions {
-    update_schedule(&action);
+    restore_schedule(&action, &stored_next_runs);
+  }
+  for build in builds {
+    restore_schedule(&build, &stored_next_runs);
+  }
+  scheduled_commands().write().unwrap().retain(
+    |(server_id, _), _| {
+      servers.iter().any(|server| &server.id == server_id)
+    },
+  );
+  for server in servers {
+    update_scheduled_commands(&server);
+  }
+  match execution_schedules
+    .context("failed to get all execution schedules from db")
+  {
+    Ok(schedules) => {
+      for schedule in schedules {
+        update_execution_schedule(&schedule);
+      }
+    }
+    Err(e) => {
+      error!(
+        "failed to get execution schedules for schedule update | {e:#}"
+      );
+    }
   }
 }
 
 /// Re/spawns the schedule for the given procedure
 pub fn update_schedule(schedule: impl HasSchedule) {
+  let target = schedule.target();
   // Cancel any existing schedule for the procedure
-  cancel_schedule(&schedule.target());
+  schedules().write().unwrap().remove(&target);
+
+  if !schedule.enabled() || schedule.schedule().is_empty() {
+    store_next_run(&target, 0);
+    return;
+  }
+
+  let next_run = find_next_occurrence(schedule)
+    .map_err(|e| format_serror(&e.into()));
+  store_next_run(&target, *next_run.as_ref().unwrap_or(&0));
+  schedules().write().unwrap().insert(target, next_run);
+}
 
+/// Spawns the schedule when Core starts. If Core was down at the
+/// stored next run, the missed run is alerted, then skipped or
+/// run right away depending on `schedule_missed_runs`.
+fn restore_schedule(
+  schedule: impl HasSchedule,
+  stored_next_runs: &HashMap<String, i64>,
+) {
+  let target = schedule.target();
+  let missed_at = stored_next_runs
+    .get(&resource_schedule_id(&target))
+    .copied()
+    .filter(|next_run_at| {
+      *next_run_at > 0 && *next_run_at < komodo_timestamp()
+    });
+  let Some(missed_at) = missed_at else {
+    return update_schedule(schedule);
+  };
   if !schedule.enabled() || schedule.schedule().is_empty() {
+    return update_schedule(schedule);
+  }
+  match schedule.missed_runs() {
+    MissedRunPolicy::RunOnce => {
+      info!(
+        "Schedule for {} missed a run, running it now",
+        schedule.name()
+      );
+      // Alerted as late when it runs.
+      schedules().write().unwrap().insert(target, Ok(missed_at));
+    }
+    MissedRunPolicy::Skip => {
+      info!(
+        "Schedule for {} missed a run, skipping it",
+        schedule.name()
+      );
+      let id = target.extract_variant_id().1.clone();
+      spawn_schedule_missed_alert(
+        target,
+        id,
+        schedule.name().to_string(),
+        missed_at,
+        false,
+      );
+      update_schedule(schedule);
+    }
+  }
+}
+
+fn resource_schedule_id(target: &ResourceTarget) -> String {
+  let (variant, id) = target.extract_variant_id();
+  format!("{variant}:{id}")
+}
+
+/// Stores the next run of the resource schedule,
+/// so runs missed while Core was down can be detected.
+fn store_next_run(target: &ResourceTarget, next_run_at: i64) {
+  let schedule = ResourceSchedule {
+    id: resource_schedule_id(target),
+    target: target.clone(),
+    next_run_at,
+  };
+  tokio::spawn(async move {
+    let schedules = &db_client().resource_schedules;
+    let res = if next_run_at == 0 {
+      schedules
+        .delete_one(doc! { "_id": &schedule.id })
+        .await
+        .map(|_| ())
+    } else {
+      schedules
+        .replace_one(doc! { "_id": &schedule.id }, &schedule)
+        .with_options(ReplaceOptions::builder().upsert(true).build())
+        .await
+        .map(|_| ())
+    };
+    if let Err(e) = res {
+      warn!(
+        "Failed to store next run of schedule {} | {e:#}",
+        schedule.id
+      );
+    }
+  });
+}
+
+/// Alerts when the scheduled run starts late,
+/// because Core was down or the scheduler fell behind.
+fn check_run_late(schedule: impl HasSchedule, scheduled_at: i64) {
+  let late_ms = komodo_timestamp() - scheduled_at;
+  if late_ms < SCHEDULE_LATE_THRESHOLD_MS {
     return;
   }
+  warn!(
+    "Scheduled run for {} started {}s late",
+    schedule.name(),
+    late_ms / 1000
+  );
+  let target = schedule.target();
+  let id = target.extract_variant_id().1.clone();
+  spawn_schedule_missed_alert(
+    target,
+    id,
+    schedule.name().to_string(),
+    scheduled_at,
+    true,
+  );
+}
+
+/// Execution Schedules aren't resources, so they
+/// are alerted with the System target and the schedule id.
+fn spawn_schedule_missed_alert(
+  target: ResourceTarget,
+  id: String,
+  name: String,
+  scheduled_at: i64,
+  run_late: bool,
+) {
+  let resource_type = target.extract_variant_id().0;
+  let ts = komodo_timestamp();
+  let alert = Alert {
+    id: Default::default(),
+    target,
+    ts,
+    resolved_ts: Some(ts),
+    resolved: true,
+    level: SeverityLevel::Warning,
+    data: AlertData::ScheduleMissed {
+      resource_type,
+      id,
+      name,
+      scheduled_at,
+      run_late,
+    },
+    notifications: Default::default(),
+  };
+  tokio::spawn(async move { send_alerts(&[alert]).await });
+}
+
+/// Re/spawns the schedules for all of the Server's scheduled commands
+pub fn update_scheduled_commands(server: &Server) {
+  cancel_scheduled_commands(&server.id);
+  for command in &server.config.scheduled_commands {
+    update_scheduled_command(server, &command.name);
+  }
+}
 
-  schedules().write().unwrap().insert(
-    schedule.target(),
-    find_next_occurrence(schedule)
+/// Re/spawns the schedule for a single Server scheduled command
+fn update_scheduled_command(server: &Server, name: &str) {
+  let key = (server.id.clone(), name.to_string());
+  let mut lock = scheduled_commands().write().unwrap();
+  lock.remove(&key);
+  let Some(command) = server
+    .config
+    .scheduled_commands
+    .iter()
+    .find(|command| command.name == name)
+  else {
+    return;
+  };
+  if !command.enabled
+    || command.schedule.is_empty()
+    || command.command.is_empty()
+  {
+    return;
+  }
+  lock.insert(
+    key,
+    find_next_occurrence(ServerScheduledCommand { server, command })
       .map_err(|e| format_serror(&e.into())),
   );
 }
 
+pub fn cancel_scheduled_commands(server_id: &str) {
+  scheduled_commands()
+    .write()
+    .unwrap()
+    .retain(|(id, _), _| id != server_id);
+}
+
+fn run_due_scheduled_commands(current_time: i64) {
+  let mut lock = scheduled_commands().write().unwrap();
+  let drained = lock.drain().collect::<Vec<_>>();
+  for ((server_id, name), next_run) in drained {
+    match next_run {
+      Ok(next_run_time) if current_time >= next_run_time => {
+        tokio::spawn(run_scheduled_command(server_id, name));
+      }
+      other => {
+        lock.insert((server_id, name), other);
+      }
+    }
+  }
+}
+
+async fn run_scheduled_command(server_id: String, name: String) {
+  if let Err(e) =
+    run_scheduled_command_as_creator(&server_id, &name).await
+  {
+    warn!(
+      "Scheduled command {name} on server {server_id} failed | {e:#}"
+    );
+  }
+  // Schedule the next run using the latest Server config
+  match crate::resource::get::<Server>(&server_id).await {
+    Ok(server) => update_scheduled_command(&server, &name),
+    Err(e) => {
+      warn!(
+        "Failed to get server {server_id} to reschedule command {name} | {e:#}"
+      );
+    }
+  }
+}
+
+/// Re/spawns the schedule for the Execution Schedule.
+/// If Core was down at the stored next run, the missed run
+/// is skipped or run right away, depending on `missed_runs`.
+pub fn update_execution_schedule(schedule: &ExecutionSchedule) {
+  let mut lock = execution_schedules().write().unwrap();
+  lock.remove(&schedule.id);
+
+  let next_run = if !schedule.enabled || schedule.schedule.is_empty()
+  {
+    None
+  } else if schedule.next_run_at > 0
+    && schedule.next_run_at < komodo_timestamp()
+  {
+    match schedule.missed_runs {
+      MissedRunPolicy::RunOnce => {
+        info!(
+          "Execution schedule {} missed a run, running it now",
+          schedule.name
+        );
+        Some(Ok(schedule.next_run_at))
+      }
+      MissedRunPolicy::Skip => {
+        info!(
+          "Execution schedule {} missed a run, skipping it",
+          schedule.name
+        );
+        spawn_schedule_missed_alert(
+          ResourceTarget::system(),
+          schedule.id.clone(),
+          schedule.name.clone(),
+          schedule.next_run_at,
+          false,
+        );
+        Some(execution_schedule_next_occurrence(schedule))
+      }
+    }
+  } else {
+    Some(execution_schedule_next_occurrence(schedule))
+  };
+
+  let (next_run_at, schedule_error) = match &next_run {
+    Some(Ok(next_run_at)) => (*next_run_at, String::new()),
+    Some(Err(e)) => (0, e.clone()),
+    None => (0, String::new()),
+  };
+  if let Some(next_run) = next_run {
+    lock.insert(schedule.id.clone(), next_run);
+  }
+
+  // Store the next run, so missed runs are detected after restart.
+  if next_run_at != schedule.next_run_at
+    || schedule_error != schedule.schedule_error
+  {
+    let id = schedule.id.clone();
+    tokio::spawn(async move {
+      let res = update_one_by_id(
+        &db_client().execution_schedules,
+        &id,
+        doc! { "$set": {
+          "next_run_at": next_run_at,
+          "schedule_error": schedule_error,
+        } },
+        None,
+      )
+      .await;
+      if let Err(e) = res {
+        warn!(
+          "Failed to store next run of execution schedule {id} | {e:#}"
+        );
+      }
+    });
+  }
+}
+
+fn execution_schedule_next_occurrence(
+  schedule: &ExecutionSchedule,
+) -> Result<UnixTimestampMs, String> {
+  next_occurrence(
+    schedule.schedule_format,
+    &schedule.schedule,
+    &schedule.schedule_timezone,
+  )
+  .map_err(|e| format_serror(&e.into()))
+}
+
+pub fn cancel_execution_schedule(id: &str) {
+  execution_schedules().write().unwrap().remove(id);
+}
+
+fn run_due_execution_schedules(current_time: i64) {
+  let mut lock = execution_schedules().write().unwrap();
+  let drained = lock.drain().collect::<Vec<_>>();
+  for (id, next_run) in drained {
+    match next_run {
+      Ok(next_run_time) if current_time >= next_run_time => {
+        tokio::spawn(run_execution_schedule(id, next_run_time));
+      }
+      other => {
+        lock.insert(id, other);
+      }
+    }
+  }
+}
+
+async fn run_execution_schedule(id: String, scheduled_at: i64) {
+  let schedule = match get_execution_schedule(&id).await {
+    Ok(schedule) => schedule,
+    Err(e) => {
+      warn!("Failed to get execution schedule {id} to run | {e:#}");
+      return;
+    }
+  };
+
+  if komodo_timestamp() - scheduled_at >= SCHEDULE_LATE_THRESHOLD_MS {
+    warn!(
+      "Execution schedule {} started {}s late",
+      schedule.name,
+      (komodo_timestamp() - scheduled_at) / 1000
+    );
+    spawn_schedule_missed_alert(
+      ResourceTarget::system(),
+      schedule.id.clone(),
+      schedule.name.clone(),
+      scheduled_at,
+      true,
+    );
+  }
+
+  let last_run_at = komodo_timestamp();
+  let (last_update_id, success, last_run_error) =
+    match run_execution_as_user(
+      &schedule.execution,
+      &schedule.created_by,
+      "Execution Schedule",
+    )
+    .await
+    {
+      Ok(update) => (update.id, update.success, String::new()),
+      Err(e) => {
+        warn!(
+          "Execution schedule {} failed to run | {e:#}",
+          schedule.name
+        );
+        (String::new(), false, format!("{e:#}"))
+      }
+    };
+
+  let run = ExecutionScheduleRun {
+    id: Default::default(),
+    schedule_id: id.clone(),
+    run_at: last_run_at,
+    finished_at: komodo_timestamp(),
+    update_id: last_update_id.clone(),
+    success,
+    error: last_run_error.clone(),
+  };
+  if let Err(e) = add_execution_schedule_run(&run).await {
+    warn!(
+      "Failed to store run history of execution schedule {id} | {e:#}"
+    );
+  }
+
+  // Clear the next run, so the run isn't seen as missed.
+  let res = update_one_by_id(
+    &db_client().execution_schedules,
+    &id,
+    doc! { "$set": {
+      "next_run_at": 0,
+      "last_run_at": last_run_at,
+      "last_update_id": last_update_id,
+      "last_run_error": last_run_error,
+    } },
+    None,
+  )
+  .await;
+  if let Err(e) = res {
+    warn!("Failed to store run of execution schedule {id} | {e:#}");
+  }
+
+  // Schedule the next run using the latest config
+  match get_execution_schedule(&id).await {
+    Ok(schedule) => update_execution_schedule(&schedule),
+    Err(e) => {
+      warn!(
+        "Failed to get execution schedule {id} to reschedule | {e:#}"
+      );
+    }
+  }
+}
+
+/// Adds the run to the schedule run history,
+/// removing the oldest runs past the history limit.
+async fn add_execution_schedule_run(
+  run: &ExecutionScheduleRun,
+) -> anyhow::Result<()> {
+  let runs = &db_client().execution_schedule_runs;
+  runs
+    .insert_one(run)
+    .await
+    .context("Failed to add execution schedule run to db")?;
+  let expired = find_collect(
+    runs,
+    doc! { "schedule_id": &run.schedule_id },
+    FindOptions::builder()
+      .sort(doc! { "run_at": -1 })
+      .skip(EXECUTION_SCHEDULE_RUN_HISTORY)
+      .build(),
+  )
+  .await
+  .context("Failed to query db for execution schedule runs")?
+  .into_iter()
+  .filter_map(|run| ObjectId::parse_str(&run.id).ok())
+  .collect::<Vec<_>>();
+  if !expired.is_empty() {
+    runs
+      .delete_many(doc! { "_id": { "$in": expired } })
+      .await
+      .context("Failed to prune execution schedule runs")?;
+  }
+  Ok(())
+}
+
+/// Runs an execution triggered by resource config
+/// as the Scheduler user.
+pub async fn run_scheduler_execution(
+  request: ExecuteRequest,
+  description: &str,
+) {
+  let update = match init_execution_update(&request, scheduler_user())
+    .await
+  {
+    Ok(update) => update,
+    Err(e) => {
+      error!(
+        "Failed to make update for {description}, it is not being run | {e:#}"
+      );
+      return;
+    }
+  };
+  if let Err(e) = request
+    .resolve(&ExecuteArgs {
+      user: scheduler_user().to_owned(),
+      update,
+    })
+    .await
+  {
+    warn!("{description} failed | {:#}", e.error);
+  }
+}
+
+async fn run_scheduled_build(id: &str, scheduled_at: i64) {
+  let build = match crate::resource::get::<Build>(id).await {
+    Ok(build) => build,
+    Err(e) => {
+      warn!(
+        "Scheduled build run on {id} failed | failed to get build | {e:?}"
+      );
+      return;
+    }
+  };
+  check_run_late(&build, scheduled_at);
+  let request = ExecuteRequest::RunBuild(RunBuild {
+    build: id.to_string(),
+  });
+  let mut update = match init_execution_update(
+    &request,
+    scheduler_user(),
+  )
+  .await
+  {
+    Ok(update) => update,
+    Err(e) => {
+      error!(
+        "Failed to make update for scheduled build run, build {id} is not being run | {e:#}"
+      );
+      return;
+    }
+  };
+  let unchanged_hash = if build.config.schedule_skip_unchanged {
+    get_unchanged_build_hash(&build).await
+  } else {
+    None
+  };
+  if let Some(hash) = unchanged_hash {
+    update.push_simple_log(
+      "Skip Build",
+      format!(
+        "Latest commit {hash} was already built successfully, skipping scheduled build."
+      ),
+    );
+    update.finalize();
+    if let Err(e) = update_update(update).await {
+      warn!(
+        "Failed to update skipped scheduled build Update for {id} | {e:#}"
+      );
+    }
+  } else {
+    let ExecuteRequest::RunBuild(request) = request else {
+      unreachable!()
+    };
+    if let Err(e) = request
+      .resolve(&ExecuteArgs {
+        user: scheduler_user().to_owned(),
+        update,
+      })
+      .await
+    {
+      warn!("Scheduled build run on {id} failed | {e:?}");
+    }
+  }
+  update_schedule(&build);
+  if build.config.schedule_alert {
+    let alert = Alert {
+      id: Default::default(),
+      target: ResourceTarget::Build(build.id.clone()),
+      ts: komodo_timestamp(),
+      resolved_ts: Some(komodo_timestamp()),
+      resolved: true,
+      level: SeverityLevel::Ok,
+      data: AlertData::ScheduleRun {
+        resource_type: ResourceTargetVariant::Build,
+        id: build.id,
+        name: build.name,
+      },
+      notifications: Default::default(),
+    };
+    send_alerts(&[alert]).await
+  }
+}
+
+/// Refreshes the build cache, and returns the latest commit hash
+/// on the branch if it was already built successfully.
+async fn get_unchanged_build_hash(build: &Build) -> Option<String> {
+  if build.config.files_on_host
+    || (build.config.repo.is_empty()
+      && build.config.linked_repo.is_empty())
+  {
+    return None;
+  }
+  if let Err(e) = (RefreshBuildCache {
+    build: build.id.clone(),
+  })
+  .resolve(&WriteArgs {
+    user: scheduler_user().to_owned(),
+  })
+  .await
+  {
+    warn!(
+      "Failed to refresh build cache for scheduled build {} | {:#}",
+      build.name, e.error
+    );
+    return None;
+  }
+  let build = crate::resource::get::<Build>(&build.id).await.ok()?;
+  let latest_hash = build.info.latest_hash?;
+  (build.info.built_hash.as_ref() == Some(&latest_hash))
+    .then_some(latest_hash)
+}
+
+/// Runs the scheduled command as the user who created it,
+/// so it can't be used to gain the Scheduler's admin permissions.
+async fn run_scheduled_command_as_creator(
+  server_id: &str,
+  name: &str,
+) -> anyhow::Result<()> {
+  let server = crate::resource::get::<Server>(server_id).await?;
+  let created_by = server
+    .config
+    .scheduled_commands
+    .iter()
+    .find(|command| command.name == name)
+    .map(|command| command.created_by.as_str())
+    .unwrap_or_default();
+  if created_by.is_empty() {
+    return Err(anyhow!(
+      "Scheduled command has no creator, update the command to run it"
+    ));
+  }
+  run_execution_as_user(
+    &Execution::RunScheduledCommand(RunScheduledCommand {
+      server: server.id,
+      command: name.to_string(),
+    }),
+    created_by,
+    "Scheduled Command",
+  )
+  .await
+  .map(|_| ())
+}
+
 fn cron_parser() -> &'static CronParser {
   static CRON_PARSER: OnceLock<CronParser> = OnceLock::new();
   CRON_PARSER.get_or_init(|| {
@@ -286,63 +1025,84 @@ fn cron_parser() -> &'static CronParser {
 }
 
 /// Finds the next run occurence in UTC ms.
-fn find_next_occurrence(
+pub fn find_next_occurrence(
   schedule: impl HasSchedule,
 ) -> anyhow::Result<i64> {
-  let cron = match schedule.format() {
+  next_occurrence(
+    schedule.format(),
+    schedule.schedule(),
+    schedule.timezone(),
+  )
+}
+
+/// Finds the next occurence of the schedule in UTC ms.
+/// Also used to validate schedules.
+pub fn next_occurrence(
+  format: ScheduleFormat,
+  schedule: &str,
+  timezone: &str,
+) -> anyhow::Result<i64> {
+  let cron = match format {
     ScheduleFormat::Cron => cron_parser()
-      .parse(schedule.schedule())
+      .parse(schedule)
       .context("Invalid CRON schedule")?,
     ScheduleFormat::English => {
-      let cron =
-        english_to_cron::str_cron_syntax(schedule.schedule())
-          .map_err(|e| {
-            anyhow!("Failed to parse english to cron | {e:?}")
-          })?
-          .split(' ')
-          // croner does not accept year
-          .take(6)
-          .collect::<Vec<_>>()
-          .join(" ");
+      let cron = english_to_cron::str_cron_syntax(schedule)
+        .map_err(|e| {
+          anyhow!("Failed to parse english to cron | {e:?}")
+        })?
+        .split(' ')
+        // croner does not accept year
+        .take(6)
+        .collect::<Vec<_>>()
+        .join(" ");
       cron_parser()
         .parse(&cron)
         .with_context(|| format!("English expression produced invalid CRON schedule | produced: {cron}"))?
     }
   };
-  let next =
-    match (schedule.timezone(), core_config().timezone.as_str()) {
-      ("", "") => {
-        let tz_time = chrono::Local::now().with_timezone(&Local);
-        cron
-          .find_next_occurrence(&tz_time, false)
-          .context("Failed to find next run time")?
-          .timestamp_millis()
-      }
-      ("", timezone) | (timezone, _) => {
-        let tz: chrono_tz::Tz =
-          timezone.parse().context("Failed to parse timezone")?;
-        let tz_time = chrono::Local::now().with_timezone(&tz);
-        cron
-          .find_next_occurrence(&tz_time, false)
-          .context("Failed to find next run time")?
-          .timestamp_millis()
-      }
-    };
+  let next = match (timezone, core_config().timezone.as_str()) {
+    ("", "") => {
+      let tz_time = chrono::Local::now().with_timezone(&Local);
+      cron
+        .find_next_occurrence(&tz_time, false)
+        .context("Failed to find next run time")?
+        .timestamp_millis()
+    }
+    ("", timezone) | (timezone, _) => {
+      let tz: chrono_tz::Tz =
+        timezone.parse().context("Failed to parse timezone")?;
+      let tz_time = chrono::Local::now().with_timezone(&tz);
+      cron
+        .find_next_occurrence(&tz_time, false)
+        .context("Failed to find next run time")?
+        .timestamp_millis()
+    }
+  };
   Ok(next)
 }
 
 pub trait HasSchedule {
   fn target(&self) -> ResourceTarget;
+  fn name(&self) -> &str;
   fn enabled(&self) -> bool;
   fn format(&self) -> ScheduleFormat;
   fn schedule(&self) -> &str;
   fn timezone(&self) -> &str;
+  /// Only Procedure, Action and Build schedules
+  /// detect runs missed while Core was down.
+  fn missed_runs(&self) -> MissedRunPolicy {
+    MissedRunPolicy::Skip
+  }
 }
 
 impl HasSchedule for &Procedure {
   fn target(&self) -> ResourceTarget {
     ResourceTarget::Procedure(self.id.clone())
   }
+  fn name(&self) -> &str {
+    &self.name
+  }
   fn enabled(&self) -> bool {
     self.config.schedule_enabled
   }
@@ -355,12 +1115,18 @@ impl HasSchedule for &Procedure {
   fn timezone(&self) -> &str {
     &self.config.schedule_timezone
   }
+  fn missed_runs(&self) -> MissedRunPolicy {
+    self.config.schedule_missed_runs
+  }
 }
 
 impl HasSchedule for &Action {
   fn target(&self) -> ResourceTarget {
     ResourceTarget::Action(self.id.clone())
   }
+  fn name(&self) -> &str {
+    &self.name
+  }
   fn enabled(&self) -> bool {
     self.config.schedule_enabled
   }
@@ -373,4 +1139,57 @@ impl HasSchedule for &Action {
   fn timezone(&self) -> &str {
     &self.config.schedule_timezone
   }
+  fn missed_runs(&self) -> MissedRunPolicy {
+    self.config.schedule_missed_runs
+  }
+}
+
+impl HasSchedule for &Build {
+  fn target(&self) -> ResourceTarget {
+    ResourceTarget::Build(self.id.clone())
+  }
+  fn name(&self) -> &str {
+    &self.name
+  }
+  fn enabled(&self) -> bool {
+    self.config.schedule_enabled
+  }
+  fn format(&self) -> ScheduleFormat {
+    self.config.schedule_format
+  }
+  fn schedule(&self) -> &str {
+    &self.config.schedule
+  }
+  fn timezone(&self) -> &str {
+    &self.config.schedule_timezone
+  }
+  fn missed_runs(&self) -> MissedRunPolicy {
+    self.config.schedule_missed_runs
+  }
+}
+
+struct ServerScheduledCommand<'a> {
+  server: &'a Server,
+  command: &'a ScheduledCommand,
+}
+
+impl HasSchedule for ServerScheduledCommand<'_> {
+  fn target(&self) -> ResourceTarget {
+    ResourceTarget::Server(self.server.id.clone())
+  }
+  fn name(&self) -> &str {
+    &self.command.name
+  }
+  fn enabled(&self) -> bool {
+    self.command.enabled
+  }
+  fn format(&self) -> ScheduleFormat {
+    self.command.schedule_format
+  }
+  fn schedule(&self) -> &str {
+    &self.command.schedule
+  }
+  fn timezone(&self) -> &str {
+    &self.command.schedule_timezone
+  }
 }
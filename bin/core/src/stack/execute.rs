@@ -10,8 +10,12 @@ use komodo_client::{
 use periphery_client::{PeripheryClient, api::compose::*};
 
 use crate::{
-  helpers::{periphery_client, update::update_update},
+  helpers::{
+    periphery_client, resource_lock::acquire_resource_lock,
+    update::update_update,
+  },
   monitor::update_cache_for_server,
+  resource::check_not_protected,
   state::action_states,
 };
 
@@ -20,6 +24,9 @@ use super::get_stack_and_server;
 pub trait ExecuteCompose {
   type Extras;
 
+  /// Destructive executions are rejected on protected Stacks.
+  const DESTRUCTIVE: bool = false;
+
   async fn execute(
     periphery: PeripheryClient,
     stack: Stack,
@@ -44,6 +51,14 @@ pub async fn execute_compose<T: ExecuteCompose>(
   )
   .await?;
 
+  if T::DESTRUCTIVE {
+    check_not_protected::<Stack>(&stack)?;
+  }
+
+  let _resource_lock = acquire_resource_lock(&stack, &update)
+    .await
+    .map_err(|e| e.error)?;
+
   // get the action state for the stack (or insert default).
   let action_state =
     action_states().stack.get_or_insert_default(&stack.id).await;
@@ -55,7 +70,7 @@ pub async fn execute_compose<T: ExecuteCompose>(
   // Send update here for frontend to recheck action state
   update_update(update.clone()).await?;
 
-  let periphery = periphery_client(&server)?;
+  let periphery = periphery_client(&server).await?;
 
   if !services.is_empty() {
     update.logs.push(Log::simple(
@@ -181,6 +196,7 @@ impl ExecuteCompose for StopStack {
 
 impl ExecuteCompose for DestroyStack {
   type Extras = (Option<i32>, bool);
+  const DESTRUCTIVE: bool = true;
   async fn execute(
     periphery: PeripheryClient,
     stack: Stack,
@@ -0,0 +1,232 @@
+use std::{
+  collections::HashMap,
+  sync::{Mutex, OnceLock},
+  time::Duration,
+};
+
+use anyhow::{Context, anyhow};
+use async_timing_util::{Timelength, wait_until_timelength};
+use futures::future::join_all;
+use komodo_client::entities::{
+  komodo_timestamp,
+  stack::{Stack, StackServiceHealth, StackServiceHealthCheck},
+};
+
+use crate::state::{all_resources_cache, stack_status_cache};
+
+static APP_USER_AGENT: &str =
+  concat!("Komodo/", env!("CARGO_PKG_VERSION"),);
+
+fn http_client() -> &'static reqwest::Client {
+  static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+  CLIENT.get_or_init(|| {
+    reqwest::Client::builder()
+      .user_agent(APP_USER_AGENT)
+      .timeout(Duration::from_secs(10))
+      .build()
+      .expect("Invalid stack health reqwest client")
+  })
+}
+
+/// (StackId, Service) -> latest probe result
+fn stack_health_cache()
+-> &'static Mutex<HashMap<(String, String), StackServiceHealth>> {
+  static CACHE: OnceLock<
+    Mutex<HashMap<(String, String), StackServiceHealth>>,
+  > = OnceLock::new();
+  CACHE.get_or_init(Default::default)
+}
+
+/// The latest health check result for the Stack service,
+/// if it has a health check configured.
+pub fn stack_service_health(
+  stack: &Stack,
+  service: &str,
+) -> Option<StackServiceHealth> {
+  if !stack
+    .config
+    .health_checks
+    .iter()
+    .any(|check| check.service == service)
+  {
+    return None;
+  }
+  stack_health_cache()
+    .lock()
+    .unwrap()
+    .get(&(stack.id.clone(), service.to_string()))
+    .cloned()
+}
+
+pub fn spawn_stack_health_loop() {
+  tokio::spawn(async move {
+    loop {
+      wait_until_timelength(Timelength::OneMinute, 0).await;
+      // Collected up front so the resources cache isn't held across awaits.
+      let stacks = all_resources_cache()
+        .load()
+        .stacks
+        .values()
+        .filter(|stack| {
+          !stack.config.server_id.is_empty()
+            && !stack.config.health_checks.is_empty()
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+      join_all(stacks.iter().map(probe_stack_health)).await;
+    }
+  });
+}
+
+/// Probes each of the Stack health checks and caches the results.
+pub async fn probe_stack_health(stack: &Stack) {
+  // Clear results for health checks which were removed.
+  stack_health_cache()
+    .lock()
+    .unwrap()
+    .retain(|(id, service), _| {
+      id != &stack.id
+        || stack
+          .config
+          .health_checks
+          .iter()
+          .any(|check| &check.service == service)
+    });
+  let results = join_all(
+    stack
+      .config
+      .health_checks
+      .iter()
+      .map(|check| probe_health_check(stack, check)),
+  )
+  .await;
+  let mut cache = stack_health_cache().lock().unwrap();
+  for (check, health) in
+    stack.config.health_checks.iter().zip(results)
+  {
+    if !health.healthy {
+      warn!(
+        "Stack {} service {} health check failed | {}",
+        stack.name,
+        check.service,
+        health.error.as_deref().unwrap_or_default()
+      );
+    }
+    cache.insert((stack.id.clone(), check.service.clone()), health);
+  }
+}
+
+async fn probe_health_check(
+  stack: &Stack,
+  check: &StackServiceHealthCheck,
+) -> StackServiceHealth {
+  let ts = komodo_timestamp();
+  let url = match health_check_url(stack, check).await {
+    Ok(url) => url,
+    Err(e) => {
+      return StackServiceHealth {
+        healthy: false,
+        url: check.url.clone(),
+        status: None,
+        error: Some(format!("{e:#}")),
+        ts,
+      };
+    }
+  };
+  match http_client().get(&url).send().await {
+    Ok(res) => {
+      let status = res.status();
+      let healthy = status.is_success() || status.is_redirection();
+      StackServiceHealth {
+        healthy,
+        url,
+        status: Some(status.as_u16()),
+        error: (!healthy)
+          .then(|| format!("Health check returned {status}")),
+        ts,
+      }
+    }
+    Err(e) => StackServiceHealth {
+      healthy: false,
+      url,
+      status: None,
+      error: Some(format!("Failed to reach health check | {e:#}")),
+      ts,
+    },
+  }
+}
+
+/// Uses the configured url, or builds it from the Server
+/// external address and the host port published for the container port.
+async fn health_check_url(
+  stack: &Stack,
+  check: &StackServiceHealthCheck,
+) -> anyhow::Result<String> {
+  if !check.url.is_empty() {
+    return Ok(check.url.clone());
+  }
+  if check.port == 0 {
+    return Err(anyhow!(
+      "Health check has no url or port configured"
+    ));
+  }
+  let external_address = all_resources_cache()
+    .load()
+    .servers
+    .get(&stack.config.server_id)
+    .map(|server| server.config.external_address.clone())
+    .unwrap_or_default();
+  if external_address.is_empty() {
+    return Err(anyhow!(
+      "Server has no external address configured for the health check url"
+    ));
+  }
+  let public_port = stack_status_cache()
+    .get(&stack.id)
+    .await
+    .and_then(|status| {
+      status
+        .curr
+        .services
+        .iter()
+        .find(|service| service.service == check.service)?
+        .container
+        .as_ref()?
+        .ports
+        .iter()
+        .find(|port| port.private_port == check.port)?
+        .public_port
+    })
+    .with_context(|| {
+      format!(
+        "Service {} has no host port published for container port {}",
+        check.service, check.port
+      )
+    })?;
+  Ok(build_url(&external_address, public_port, &check.path))
+}
+
+/// The external address may be a plain host or include the scheme / port.
+fn build_url(
+  external_address: &str,
+  port: u16,
+  path: &str,
+) -> String {
+  let (scheme, rest) = external_address
+    .split_once("://")
+    .unwrap_or(("http", external_address));
+  let host = rest.split('/').next().unwrap_or(rest);
+  // Drop any port on the address, the published port is used instead.
+  let host = match host.rsplit_once(':') {
+    Some((host, port))
+      if !host.is_empty()
+        && port.chars().all(|c| c.is_ascii_digit())
+        && !host.ends_with(':') =>
+    {
+      host
+    }
+    _ => host,
+  };
+  let path = path.trim_start_matches('/');
+  format!("{scheme}://{host}:{port}/{path}")
+}
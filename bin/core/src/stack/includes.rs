@@ -0,0 +1,251 @@
+use std::{path::Path, sync::OnceLock, time::Duration};
+
+use anyhow::{Context, anyhow};
+use komodo_client::entities::{
+  FileContents, stack::Stack, update::Log,
+};
+use serde_yaml_ng::Value;
+use sha2::{Digest, Sha256};
+use tokio::fs;
+
+use crate::config::core_config;
+
+/// The directory the remote includes are written to,
+/// relative to the Stack run directory.
+const INCLUDE_DIRECTORY: &str = ".komodo.includes";
+
+static APP_USER_AGENT: &str =
+  concat!("Komodo/", env!("CARGO_PKG_VERSION"),);
+
+fn http_client() -> &'static reqwest::Client {
+  static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+  CLIENT.get_or_init(|| {
+    reqwest::Client::builder()
+      .user_agent(APP_USER_AGENT)
+      .timeout(Duration::from_secs(30))
+      .build()
+      .expect("Invalid compose include reqwest client")
+  })
+}
+
+/// A remote include fetched by Core.
+pub struct RemoteInclude {
+  /// The include url, as written in the compose file.
+  pub url: String,
+  /// The local copy, relative to the run directory.
+  pub file: FileContents,
+}
+
+/// Whether the compose file is defined in the UI,
+/// so the includes can be rewritten before sending to Periphery.
+pub fn stack_is_ui_defined(stack: &Stack) -> bool {
+  !stack.config.files_on_host
+    && stack.config.oci_artifact.is_empty()
+    && stack.config.linked_repo.is_empty()
+    && stack.config.repo.is_empty()
+}
+
+/// Fetches the remote compose `include` files of a UI defined Stack,
+/// and points the includes at local copies in the run directory.
+pub async fn resolve_remote_includes(
+  stack: &mut Stack,
+  logs: &mut Vec<Log>,
+) -> anyhow::Result<Vec<RemoteInclude>> {
+  let mut urls = remote_include_urls(&stack.config.file_contents);
+  if urls.is_empty() {
+    return Ok(Vec::new());
+  }
+  // Longer urls are replaced first, in case one is a prefix of another.
+  urls.sort_by_key(|url| std::cmp::Reverse(url.len()));
+
+  let mut includes = Vec::with_capacity(urls.len());
+  let mut log = Vec::with_capacity(urls.len());
+  for url in urls {
+    let checksum = stack
+      .config
+      .include_checksums
+      .iter()
+      .find(|pin| pin.url == url)
+      .map(|pin| pin.sha256.to_lowercase());
+    let (contents, source) =
+      get_remote_include(&url, checksum.as_deref())
+        .await
+        .with_context(|| {
+          format!("Failed to get remote include {url}")
+        })?;
+    let url_hash = sha256_hex(url.as_bytes());
+    let path =
+      format!("{INCLUDE_DIRECTORY}/{}.compose.yaml", &url_hash[..16]);
+    stack.config.file_contents =
+      stack.config.file_contents.replace(&url, &path);
+    log.push(format!("{url} -> {path} | {source}"));
+    includes.push(RemoteInclude {
+      url,
+      file: FileContents { path, contents },
+    });
+  }
+  logs.push(Log::simple("Remote Includes", log.join("\n")));
+
+  Ok(includes)
+}
+
+/// Points the includes in the deployed contents back at the urls,
+/// so they can be compared with the Stack file contents.
+pub fn restore_include_urls(
+  contents: &str,
+  includes: &[RemoteInclude],
+) -> String {
+  includes
+    .iter()
+    .fold(contents.to_string(), |contents, include| {
+      contents.replace(&include.file.path, &include.url)
+    })
+}
+
+/// Fetches the latest contents, falling back to the cached contents
+/// if the url can't be reached or no longer matches the checksum.
+/// Returns (contents, source)
+pub async fn get_remote_include(
+  url: &str,
+  checksum: Option<&str>,
+) -> anyhow::Result<(String, String)> {
+  let cache_path = core_config()
+    .compose_cache_directory
+    .join(sha256_hex(url.as_bytes()));
+
+  let fetched =
+    fetch_remote_include(url).await.and_then(|contents| {
+      check_checksum(&contents, checksum)?;
+      Ok(contents)
+    });
+  let e = match fetched {
+    Ok(contents) => {
+      // Only contents matching the checksum are cached.
+      if let Err(e) = write_cache(&cache_path, &contents).await {
+        warn!("Failed to cache remote include {url} | {e:#}");
+      }
+      return Ok((contents, String::from("fetched")));
+    }
+    Err(e) => e,
+  };
+
+  let Ok(contents) = fs::read_to_string(&cache_path).await else {
+    return Err(e.context("No cached contents to fall back to"));
+  };
+  check_checksum(&contents, checksum)
+    .context("Cached contents don't match the checksum either")?;
+  warn!("Using cached remote include {url} | {e:#}");
+  Ok((contents, format!("cached | {e:#}")))
+}
+
+async fn fetch_remote_include(url: &str) -> anyhow::Result<String> {
+  http_client()
+    .get(url)
+    .send()
+    .await
+    .context("Failed to reach url")?
+    .error_for_status()
+    .context("Url returned error status")?
+    .text()
+    .await
+    .context("Failed to read response body")
+}
+
+async fn write_cache(
+  path: &Path,
+  contents: &str,
+) -> anyhow::Result<()> {
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent).await.with_context(|| {
+      format!(
+        "Failed to create compose cache directory at {parent:?}"
+      )
+    })?;
+  }
+  fs::write(path, contents).await.with_context(|| {
+    format!("Failed to write cache file at {path:?}")
+  })
+}
+
+fn check_checksum(
+  contents: &str,
+  checksum: Option<&str>,
+) -> anyhow::Result<()> {
+  let Some(checksum) = checksum else {
+    return Ok(());
+  };
+  let actual = sha256_hex(contents.as_bytes());
+  if actual != checksum {
+    return Err(anyhow!(
+      "Checksum mismatch | expected {checksum} | got {actual}"
+    ));
+  }
+  Ok(())
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+  hex::encode(Sha256::digest(bytes))
+}
+
+/// The http / https urls under the top level `include`,
+/// given either directly or as the include `path`.
+/// Git repo includes are left for compose to handle.
+fn remote_include_urls(file_contents: &str) -> Vec<String> {
+  // Invalid files are reported by compose on deploy.
+  let Ok(file) = serde_yaml_ng::from_str::<Value>(file_contents)
+  else {
+    return Vec::new();
+  };
+  let Some(includes) =
+    file.get("include").and_then(Value::as_sequence)
+  else {
+    return Vec::new();
+  };
+  let mut urls = Vec::new();
+  for include in includes {
+    let paths = match include {
+      Value::String(path) => vec![path.as_str()],
+      Value::Mapping(_) => match include.get("path") {
+        Some(Value::String(path)) => vec![path.as_str()],
+        Some(Value::Sequence(paths)) => {
+          paths.iter().filter_map(Value::as_str).collect()
+        }
+        _ => Vec::new(),
+      },
+      _ => Vec::new(),
+    };
+    for path in paths {
+      if is_remote_file(path) && !urls.iter().any(|url| url == path) {
+        urls.push(path.to_string());
+      }
+    }
+  }
+  urls
+}
+
+pub fn is_remote_file(path: &str) -> bool {
+  (path.starts_with("https://") || path.starts_with("http://"))
+    && !path.ends_with(".git")
+    && !path.contains(".git#")
+}
+
+/// Checks the include checksum pin is a http / https url
+/// and a sha256 checksum in hex.
+pub fn check_include_checksum(
+  url: &str,
+  sha256: &str,
+) -> anyhow::Result<()> {
+  if !is_remote_file(url) {
+    return Err(anyhow!(
+      "Include checksum url must be a http / https file url | {url}"
+    ));
+  }
+  if sha256.len() != 64
+    || !sha256.chars().all(|c| c.is_ascii_hexdigit())
+  {
+    return Err(anyhow!(
+      "Include checksum for {url} must be a sha256 checksum in hex"
+    ));
+  }
+  Ok(())
+}
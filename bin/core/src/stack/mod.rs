@@ -1,20 +1,33 @@
 use anyhow::{Context, anyhow};
+use database::mungos::{
+  find::find_collect,
+  mongodb::bson::{Document, doc, oid::ObjectId, to_document},
+};
 use komodo_client::entities::{
+  FileContents,
+  docker::container::ContainerStateStatusEnum,
   permission::PermissionLevelAndSpecifics,
   server::{Server, ServerState},
-  stack::Stack,
+  stack::{Stack, StackInfo},
   user::User,
 };
+use periphery_client::api::GetDockerLists;
 use regex::Regex;
 
 use crate::{
-  helpers::query::get_server_with_state,
+  helpers::{periphery_client, query::get_server_with_state},
   permission::get_check_permissions,
+  state::{db_client, stack_status_cache},
 };
 
 pub mod execute;
+pub mod health;
+pub mod includes;
+pub mod overrides;
 pub mod remote;
+pub mod runtime_drift;
 pub mod services;
+pub mod storage;
 
 pub async fn get_stack_and_server(
   stack: &str,
@@ -40,6 +53,100 @@ pub async fn get_stack_and_server(
   Ok((stack, server))
 }
 
+/// The latest contents of the Stack files.
+/// UI defined contents are written to the first compose file path.
+pub fn latest_stack_files(stack: &Stack) -> Vec<FileContents> {
+  match &stack.info.remote_contents {
+    Some(contents) => contents
+      .iter()
+      .map(|file| FileContents {
+        path: file.path.clone(),
+        contents: file.contents.clone(),
+      })
+      .collect(),
+    None => vec![FileContents {
+      path: stack
+        .compose_file_paths()
+        .first()
+        .cloned()
+        .unwrap_or_else(|| String::from("compose.yaml")),
+      contents: stack.config.file_contents.clone(),
+    }],
+  }
+}
+
+/// Sets the Stack info, except for `stored_file_contents`.
+/// That is only changed along with the file contents,
+/// which may have been moved since the Stack was read.
+pub async fn update_stack_info(
+  stack_name: &str,
+  info: &StackInfo,
+) -> anyhow::Result<()> {
+  let mut info = to_document(info)
+    .context("Failed to serialize stack info to bson")?;
+  info.remove("stored_file_contents");
+  let info = info
+    .into_iter()
+    .map(|(field, value)| (format!("info.{field}"), value))
+    .collect::<Document>();
+  db_client()
+    .stacks
+    .update_one(doc! { "name": stack_name }, doc! { "$set": info })
+    .await
+    .context("Failed to update stack info on db")?;
+  Ok(())
+}
+
+/// Get the name of the running container for the Stack service,
+/// so callers don't have to know the compose container naming.
+/// Uses the cached Stack status, and falls back to listing the
+/// containers on the Server if the cached container isn't running.
+pub async fn get_stack_service_container(
+  stack: &Stack,
+  server: &Server,
+  service: &str,
+) -> anyhow::Result<String> {
+  let services = services::extract_services_from_stack(stack);
+  let service_names = services
+    .iter()
+    .find(|s| s.service_name == service)
+    .with_context(|| {
+      format!("Service {service} not found on Stack {}", stack.name)
+    })?;
+
+  if let Some(status) = stack_status_cache().get(&stack.id).await
+    && let Some(container) = status
+      .curr
+      .services
+      .iter()
+      .find(|s| s.service == service)
+      .and_then(|s| s.container.as_ref())
+    && container.state == ContainerStateStatusEnum::Running
+  {
+    return Ok(container.name.clone());
+  }
+
+  let regex =
+    compose_container_match_regex(&service_names.container_name)?;
+  let containers = periphery_client(server)
+    .await?
+    .request(GetDockerLists {})
+    .await
+    .context("Failed to list containers on Server")?
+    .containers
+    .unwrap_or_default();
+  containers
+    .into_iter()
+    .find(|container| {
+      container.state == ContainerStateStatusEnum::Running
+        && regex.is_match(&container.name)
+    })
+    .map(|container| container.name)
+    .with_context(|| {
+      format!("No running container found for service {service}")
+    })
+}
+
 pub fn compose_container_match_regex(
   container_name: &str,
 ) -> anyhow::Result<Regex> {
@@ -48,3 +155,34 @@ pub fn compose_container_match_regex(
     format!("failed to construct valid regex from {regex}")
   })
 }
+
+/// Get the names of the other Stacks on the same Server
+/// which manage the given compose project name.
+/// Two Stacks using the same project will take over each other's containers.
+pub async fn get_project_name_conflicts(
+  stack: &Stack,
+  project_name: &str,
+) -> anyhow::Result<Vec<String>> {
+  let id = ObjectId::parse_str(&stack.id)
+    .context("Stack id is not valid ObjectId")?;
+  let stacks = find_collect(
+    &db_client().stacks,
+    doc! {
+      "_id": { "$ne": id },
+      "config.server_id": &stack.config.server_id,
+    },
+    None,
+  )
+  .await
+  .context("Failed to query db for stacks on server")?;
+  Ok(
+    stacks
+      .into_iter()
+      .filter(|other| {
+        other.project_name(false) == project_name
+          || other.project_name(true) == project_name
+      })
+      .map(|other| other.name)
+      .collect(),
+  )
+}
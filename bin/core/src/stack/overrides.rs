@@ -0,0 +1,156 @@
+use std::path::{Component, Path};
+
+use anyhow::{Context, anyhow};
+use komodo_client::entities::{
+  FileContents,
+  repo::Repo,
+  stack::{Stack, StackComposeOverride},
+  update::Log,
+};
+use tokio::fs;
+
+use crate::resource;
+
+use super::{
+  includes::{get_remote_include, is_remote_file},
+  remote::ensure_remote_repo,
+};
+
+/// The directory the compose overrides are written to,
+/// relative to the Stack run directory.
+const OVERRIDE_DIRECTORY: &str = ".komodo.overrides";
+
+/// Fetches the Stack compose overrides, and adds them to the
+/// compose file paths after the Stack compose files, in order.
+/// The returned files must be written relative to the run directory.
+pub async fn resolve_compose_overrides(
+  stack: &mut Stack,
+  logs: &mut Vec<Log>,
+) -> anyhow::Result<Vec<FileContents>> {
+  if stack.config.compose_overrides.is_empty() {
+    return Ok(Vec::new());
+  }
+  // Otherwise the default compose file is replaced, rather than overridden.
+  stack.config.file_paths = stack.compose_file_paths().to_vec();
+
+  let mut files = Vec::new();
+  let mut log = Vec::new();
+  for (i, compose_override) in
+    stack.config.compose_overrides.iter().enumerate()
+  {
+    let (contents, source) =
+      get_compose_override(stack, compose_override).await?;
+    let path = format!("{OVERRIDE_DIRECTORY}/{i}.compose.yaml");
+    log.push(format!("{source} -> {path}"));
+    files.push(FileContents { path, contents });
+  }
+  stack
+    .config
+    .file_paths
+    .extend(files.iter().map(|file| file.path.clone()));
+  logs.push(Log::simple("Compose Overrides", log.join("\n")));
+
+  Ok(files)
+}
+
+/// Whether the file is one of the compose overrides written by Core,
+/// rather than one of the Stack files.
+pub fn is_compose_override_path(path: &str) -> bool {
+  path.starts_with(OVERRIDE_DIRECTORY)
+}
+
+/// Returns (contents, source)
+async fn get_compose_override(
+  stack: &Stack,
+  compose_override: &StackComposeOverride,
+) -> anyhow::Result<(String, String)> {
+  check_compose_override(compose_override)?;
+  let StackComposeOverride { url, repo, path } = compose_override;
+
+  if !url.is_empty() {
+    let checksum = stack
+      .config
+      .include_checksums
+      .iter()
+      .find(|pin| &pin.url == url)
+      .map(|pin| pin.sha256.to_lowercase());
+    let (contents, source) =
+      get_remote_include(url, checksum.as_deref())
+        .await
+        .with_context(|| {
+          format!("Failed to get compose override {url}")
+        })?;
+    return Ok((contents, format!("{url} | {source}")));
+  }
+
+  let repo = resource::get::<Repo>(repo).await?;
+  let (repo_path, _, hash, _) =
+    ensure_remote_repo((&repo).into(), &(&repo).into())
+      .await
+      .with_context(|| {
+        format!("Failed to clone compose override Repo {}", repo.name)
+      })?;
+  // Resolve symlinks, which could otherwise point out of the repo.
+  let repo_path =
+    fs::canonicalize(&repo_path).await.with_context(|| {
+      format!("Failed to resolve Repo {} path", repo.name)
+    })?;
+  let file_path = fs::canonicalize(repo_path.join(path))
+    .await
+    .with_context(|| {
+      format!(
+        "Failed to resolve compose override {path} in Repo {}",
+        repo.name
+      )
+    })?;
+  if !file_path.starts_with(&repo_path) {
+    return Err(anyhow!(
+      "Compose override path must stay inside Repo {} | {path}",
+      repo.name
+    ));
+  }
+  let contents =
+    fs::read_to_string(&file_path).await.with_context(|| {
+      format!(
+        "Failed to read compose override {path} from Repo {}",
+        repo.name
+      )
+    })?;
+  let source = match hash {
+    Some(hash) => format!("{}: {path} @ {hash}", repo.name),
+    None => format!("{}: {path}", repo.name),
+  };
+  Ok((contents, source))
+}
+
+/// Checks the compose override has either a http / https
+/// file url, or a repo with the path to the file.
+pub fn check_compose_override(
+  compose_override: &StackComposeOverride,
+) -> anyhow::Result<()> {
+  let StackComposeOverride { url, repo, path } = compose_override;
+  match (url.is_empty(), repo.is_empty()) {
+    (false, true) if is_remote_file(url) => Ok(()),
+    (false, true) => Err(anyhow!(
+      "Compose override url must be a http / https file url | {url}"
+    )),
+    (true, false) if path.is_empty() => Err(anyhow!(
+      "Compose override from Repo {repo} must have the path to the file"
+    )),
+    // The file is read on Core, so it must stay in the repo.
+    (true, false)
+      if Path::new(path).is_absolute()
+        || Path::new(path)
+          .components()
+          .any(|c| c == Component::ParentDir) =>
+    {
+      Err(anyhow!(
+        "Compose override path must be relative to the repo root | {path}"
+      ))
+    }
+    (true, false) => Ok(()),
+    _ => Err(anyhow!(
+      "Compose override must have either a url, or a repo and path"
+    )),
+  }
+}
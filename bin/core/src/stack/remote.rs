@@ -1,4 +1,7 @@
-use std::{fs, path::PathBuf};
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
 
 use anyhow::Context;
 use formatting::format_serror;
@@ -6,10 +9,14 @@ use komodo_client::entities::{
   FileContents, RepoExecutionArgs,
   repo::Repo,
   stack::{Stack, StackRemoteFileContents},
+  to_path_compatible_name,
   update::Log,
 };
 
-use crate::{config::core_config, helpers::git_token};
+use crate::{
+  config::core_config,
+  helpers::{TokenRequester, git_token, stack_oci_token},
+};
 
 pub struct RemoteComposeContents {
   pub successful: Vec<StackRemoteFileContents>,
@@ -24,16 +31,78 @@ pub async fn get_repo_compose_contents(
   stack: &Stack,
   repo: Option<&Repo>,
   // Collect any files which are missing in the repo.
-  mut missing_files: Option<&mut Vec<String>>,
+  missing_files: Option<&mut Vec<String>>,
 ) -> anyhow::Result<RemoteComposeContents> {
   let clone_args: RepoExecutionArgs =
     repo.map(Into::into).unwrap_or(stack.into());
+  let requester: TokenRequester =
+    repo.map(Into::into).unwrap_or(stack.into());
   let (repo_path, _logs, hash, message) =
-    ensure_remote_repo(clone_args)
+    ensure_remote_repo(clone_args, &requester)
       .await
       .context("Failed to clone stack repo")?;
 
-  let run_directory = repo_path.join(&stack.config.run_directory);
+  let (successful, errored) =
+    read_compose_contents(stack, &repo_path, missing_files);
+
+  Ok(RemoteComposeContents {
+    successful,
+    errored,
+    hash,
+    message,
+    _logs,
+  })
+}
+
+/// Pulls the Stack OCI artifact into the core repo directory
+/// and reads the compose files.
+/// Returns Result<(read paths, error paths, logs, short digest, revision)>
+pub async fn get_oci_compose_contents(
+  stack: &Stack,
+  // Collect any files which are missing in the artifact.
+  missing_files: Option<&mut Vec<String>>,
+) -> anyhow::Result<RemoteComposeContents> {
+  let artifact = &stack.config.oci_artifact;
+  let token = stack_oci_token(stack).await?;
+  let root = core_config()
+    .repo_directory
+    .join("stack-sources")
+    .join(to_path_compatible_name(&stack.name));
+  let pulled = oci::pull_oci_artifact(
+    artifact,
+    token
+      .as_deref()
+      .map(|token| (stack.config.oci_account.as_str(), token)),
+    &root,
+  )
+  .await
+  .with_context(|| {
+    format!("Failed to pull OCI artifact {artifact}")
+  })?;
+
+  let (successful, errored) =
+    read_compose_contents(stack, &root, missing_files);
+
+  Ok(RemoteComposeContents {
+    successful,
+    errored,
+    hash: Some(oci::short_digest(&pulled.digest)),
+    message: Some(
+      pulled
+        .revision
+        .unwrap_or_else(|| format!("{artifact}@{}", pulled.digest)),
+    ),
+    _logs: Vec::new(),
+  })
+}
+
+fn read_compose_contents(
+  stack: &Stack,
+  root: &Path,
+  // Collect any files which are missing.
+  mut missing_files: Option<&mut Vec<String>>,
+) -> (Vec<StackRemoteFileContents>, Vec<FileContents>) {
+  let run_directory = root.join(&stack.config.run_directory);
   // This will remove any intermediate '/./' which can be a problem for some OS.
   let run_directory = run_directory.components().collect::<PathBuf>();
 
@@ -64,24 +133,19 @@ pub async fn get_repo_compose_contents(
     }
   }
 
-  Ok(RemoteComposeContents {
-    successful,
-    errored,
-    hash,
-    message,
-    _logs,
-  })
+  (successful, errored)
 }
 
 /// Returns (destination, logs, hash, message)
 pub async fn ensure_remote_repo(
   mut clone_args: RepoExecutionArgs,
+  requester: &TokenRequester,
 ) -> anyhow::Result<(PathBuf, Vec<Log>, Option<String>, Option<String>)>
 {
   let config = core_config();
 
   let access_token = if let Some(username) = &clone_args.account {
-    git_token(&clone_args.provider, username, |https| {
+    git_token(&clone_args.provider, username, requester, |https| {
         clone_args.https = https
       })
       .await
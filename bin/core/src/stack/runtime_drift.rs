@@ -0,0 +1,237 @@
+use std::{
+  collections::HashMap,
+  sync::{Mutex, OnceLock},
+};
+
+use anyhow::{Context, anyhow};
+use async_timing_util::{Timelength, wait_until_timelength};
+use database::mungos::by_id::update_one_by_id;
+use database::mungos::mongodb::bson::doc;
+use futures::future::join_all;
+use komodo_client::entities::{
+  ResourceTarget,
+  alert::{Alert, AlertData, SeverityLevel},
+  komodo_timestamp,
+  server::ServerState,
+  stack::{Stack, StackServiceDrift, StackState},
+};
+use periphery_client::api::compose::GetComposeRuntimeDrift;
+
+use crate::{
+  alert::send_alerts,
+  helpers::{periphery_client, query::get_server_with_state},
+  resource::KomodoResource,
+  state::{all_resources_cache, db_client, stack_status_cache},
+};
+
+/// StackId -> latest drift check result
+fn stack_runtime_drift_cache()
+-> &'static Mutex<HashMap<String, Vec<StackServiceDrift>>> {
+  static CACHE: OnceLock<
+    Mutex<HashMap<String, Vec<StackServiceDrift>>>,
+  > = OnceLock::new();
+  CACHE.get_or_init(Default::default)
+}
+
+/// Whether the latest drift check found any
+/// differences from the deployed compose file.
+pub fn stack_has_runtime_drift(stack_id: &str) -> bool {
+  stack_runtime_drift_cache()
+    .lock()
+    .unwrap()
+    .get(stack_id)
+    .is_some_and(|drift| !drift.is_empty())
+}
+
+/// The differences found for the Stack service
+/// in the latest drift check.
+pub fn stack_service_drift(
+  stack_id: &str,
+  service: &str,
+) -> Vec<String> {
+  stack_runtime_drift_cache()
+    .lock()
+    .unwrap()
+    .get(stack_id)
+    .and_then(|drift| {
+      drift.iter().find(|drift| drift.service == service)
+    })
+    .map(|drift| drift.differences.clone())
+    .unwrap_or_default()
+}
+
+/// Clears the drift check result, as after a redeploy.
+pub fn clear_stack_runtime_drift(stack_id: &str) {
+  stack_runtime_drift_cache().lock().unwrap().remove(stack_id);
+}
+
+pub fn spawn_stack_runtime_drift_loop() {
+  tokio::spawn(async move {
+    loop {
+      wait_until_timelength(Timelength::FiveMinutes, 0).await;
+      // Collected up front so the resources cache isn't held across awaits.
+      let stacks = all_resources_cache()
+        .load()
+        .stacks
+        .values()
+        .filter(|stack| {
+          !stack.config.server_id.is_empty()
+            && stack.info.deployed_config.is_some()
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+      join_all(stacks.iter().map(check_stack_runtime_drift)).await;
+    }
+  });
+}
+
+/// Compares the running Stack containers with the deployed
+/// compose file on Periphery, and caches the result.
+pub async fn check_stack_runtime_drift(stack: &Stack) {
+  let drift = match get_stack_runtime_drift(stack).await {
+    Ok(Some(drift)) => drift,
+    // There is nothing running to compare.
+    Ok(None) => Vec::new(),
+    // Keeps the previous result, the check can be retried.
+    Err(e) => {
+      debug!(
+        "Failed to check Stack {} for runtime drift | {e:#}",
+        stack.name
+      );
+      return;
+    }
+  };
+  if !drift.is_empty() {
+    warn!(
+      "Stack {} containers have drifted from the deployed compose file | {}",
+      stack.name,
+      drift
+        .iter()
+        .map(|drift| format!(
+          "{}: {}",
+          drift.service,
+          drift.differences.join(", ")
+        ))
+        .collect::<Vec<_>>()
+        .join(" | ")
+    );
+  }
+  let services = drift
+    .iter()
+    .map(|drift| drift.service.clone())
+    .collect::<Vec<_>>();
+  stack_runtime_drift_cache()
+    .lock()
+    .unwrap()
+    .insert(stack.id.clone(), drift);
+  if let Err(e) = update_runtime_drift_alert(stack, services).await {
+    warn!(
+      "Failed to update StackRuntimeDrift alert for Stack {} | {e:#}",
+      stack.name
+    );
+  }
+}
+
+async fn get_stack_runtime_drift(
+  stack: &Stack,
+) -> anyhow::Result<Option<Vec<StackServiceDrift>>> {
+  let Some(compose_config) = &stack.info.deployed_config else {
+    return Ok(None);
+  };
+  let state = stack_status_cache()
+    .get(&stack.id)
+    .await
+    .map(|status| status.curr.state)
+    .unwrap_or_default();
+  if matches!(state, StackState::Down | StackState::Unknown)
+    || Stack::busy(&stack.id).await?
+  {
+    return Ok(None);
+  }
+  let (server, server_state) =
+    get_server_with_state(&stack.config.server_id).await?;
+  if server_state != ServerState::Ok {
+    return Err(anyhow!("Server is not reachable"));
+  }
+  let drift = periphery_client(&server)
+    .await?
+    .request(GetComposeRuntimeDrift {
+      project: stack.project_name(false),
+      compose_config: compose_config.clone(),
+    })
+    .await
+    .context("Failed to get runtime drift from Periphery")?;
+  Ok(Some(drift))
+}
+
+/// Opens the alert when drift is found,
+/// and resolves it once the containers match again.
+async fn update_runtime_drift_alert(
+  stack: &Stack,
+  services: Vec<String>,
+) -> anyhow::Result<()> {
+  let db = db_client();
+  let existing = db
+    .alerts
+    .find_one(doc! {
+      "resolved": false,
+      "target.type": "Stack",
+      "target.id": &stack.id,
+      "data.type": "StackRuntimeDrift",
+    })
+    .await
+    .context("failed to query db for alert")?;
+  match (existing, services.is_empty()) {
+    // OPEN A NEW ALERT
+    (None, false) => {
+      let server_name = all_resources_cache()
+        .load()
+        .servers
+        .get(&stack.config.server_id)
+        .map(|server| server.name.clone())
+        .unwrap_or(String::from("unknown"));
+      let alert = Alert {
+        id: Default::default(),
+        ts: komodo_timestamp(),
+        resolved: false,
+        level: SeverityLevel::Warning,
+        target: ResourceTarget::Stack(stack.id.clone()),
+        data: AlertData::StackRuntimeDrift {
+          id: stack.id.clone(),
+          name: stack.name.clone(),
+          server_id: stack.config.server_id.clone(),
+          server_name,
+          services,
+        },
+        resolved_ts: None,
+        notifications: Default::default(),
+      };
+      db.alerts
+        .insert_one(&alert)
+        .await
+        .context("failed to open stack runtime drift alert")?;
+      if stack.config.send_runtime_drift_alerts {
+        send_alerts(&[alert]).await;
+      }
+    }
+    // CLOSE ALERT
+    (Some(existing), true) => {
+      update_one_by_id(
+        &db.alerts,
+        &existing.id,
+        doc! {
+          "$set": {
+            "resolved": true,
+            "resolved_ts": komodo_timestamp()
+          }
+        },
+        None,
+      )
+      .await
+      .context("failed to close stack runtime drift alert")?;
+    }
+    // NOTHING TO DO
+    _ => {}
+  }
+  Ok(())
+}
@@ -0,0 +1,461 @@
+use std::pin::Pin;
+
+use anyhow::{Context, anyhow};
+use database::mungos::{
+  by_id::find_one_by_id,
+  find::find_collect,
+  mongodb::{
+    bson::{Bson, doc, oid::ObjectId, to_bson},
+    gridfs::GridFsBucket,
+    options::GridFsBucketOptions,
+  },
+};
+use futures::TryStreamExt;
+use komodo_client::entities::stack::{
+  Stack, StackFileStorage, StoredFileContents,
+};
+use sha2::{Digest, Sha256};
+use tokio::{
+  fs,
+  io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+};
+use tokio_util::compat::{
+  FuturesAsyncReadCompatExt, FuturesAsyncWriteCompatExt,
+};
+
+use crate::{
+  cloud::aws::s3::{
+    delete_s3_object, get_s3_object_stream, put_s3_object,
+  },
+  config::core_config,
+  state::db_client,
+};
+
+/// The GridFS bucket holding the Stack files.
+const GRIDFS_BUCKET: &str = "StackFiles";
+const CHUNK_BYTES: usize = 64 * 1024;
+
+pub type StoredFileReader = Pin<Box<dyn AsyncRead + Send>>;
+
+/// Loads the file contents moved to the Stack file storage
+/// back into the Stack config.
+/// Contents in the config take precedence,
+/// as they are written there first.
+pub async fn load_stored_file_contents(
+  stack: &mut Stack,
+) -> anyhow::Result<()> {
+  if !stack.config.file_contents.is_empty() {
+    return Ok(());
+  }
+  let Some(stored) = &stack.info.stored_file_contents else {
+    return Ok(());
+  };
+  stack.config.file_contents =
+    read_stored_file_to_string(stored).await.with_context(|| {
+      format!("Failed to load file contents for Stack {}", stack.name)
+    })?;
+  Ok(())
+}
+
+/// Moves the Stack file contents into the configured storage
+/// if they are larger than `stack_file_max_inline_bytes`,
+/// or back into the database if the storage is `database`.
+/// Contents in a previously configured storage are moved over.
+pub async fn sync_stored_file_contents(
+  stack_id: &str,
+) -> anyhow::Result<()> {
+  // Uses the Stack as stored, without loading the contents.
+  let stack = find_one_by_id(&db_client().stacks, stack_id)
+    .await
+    .context("Failed to query db for Stack")?
+    .context("No Stack found with given id")?;
+  let config = core_config();
+  let storage = config.stack_file_storage;
+  let previous = stack.info.stored_file_contents;
+  let object_id = ObjectId::parse_str(&stack.id)
+    .context("Stack id is not valid ObjectId")?;
+
+  let contents = if !stack.config.file_contents.is_empty() {
+    stack.config.file_contents.clone()
+  } else if let Some(previous) = &previous
+    && previous.storage != storage
+  {
+    read_stored_file_to_string(previous).await?
+  } else {
+    // Nothing stored, or already in the configured storage.
+    return Ok(());
+  };
+
+  let fits_inline = storage == StackFileStorage::Database
+    || contents.len() as u64 <= config.stack_file_max_inline_bytes;
+  if fits_inline
+    && previous.is_none()
+    && !stack.config.file_contents.is_empty()
+  {
+    return Ok(());
+  }
+
+  let res = if fits_inline {
+    db_client()
+      .stacks
+      .update_one(
+        doc! { "_id": object_id },
+        doc! { "$set": {
+          "config.file_contents": &contents,
+          "info.stored_file_contents": Bson::Null,
+        } },
+      )
+      .await
+  } else {
+    let stored = write_stored_file(
+      &stored_file_key(&stack.id),
+      contents.as_bytes(),
+    )
+    .await?;
+    let stored = to_bson(&stored)
+      .context("Failed to serialize stored file contents")?;
+    // Only clear the contents if they weren't changed in the meantime.
+    db_client()
+      .stacks
+      .update_one(
+        doc! {
+          "_id": object_id,
+          "config.file_contents": {
+            "$in": [&contents, ""]
+          },
+        },
+        doc! { "$set": {
+          "config.file_contents": "",
+          "info.stored_file_contents": stored,
+        } },
+      )
+      .await
+  }
+  .context("Failed to update Stack file contents on db")?;
+
+  if res.matched_count == 0 {
+    return Err(anyhow!(
+      "Stack {} file contents changed while being stored",
+      stack.name
+    ));
+  }
+
+  // The contents in the configured storage were just replaced.
+  if let Some(previous) = previous
+    && (fits_inline
+      || previous.storage != storage
+      || previous.key != stored_file_key(&stack.id))
+  {
+    delete_stored_file(&previous).await?;
+  }
+
+  Ok(())
+}
+
+/// Removes the file contents from the storage,
+/// for when they are cleared or the Stack is deleted.
+pub async fn clear_stored_file_contents(
+  stack_id: &str,
+) -> anyhow::Result<()> {
+  let Some(stack) = find_one_by_id(&db_client().stacks, stack_id)
+    .await
+    .context("Failed to query db for Stack")?
+  else {
+    return Ok(());
+  };
+  let Some(stored) = stack.info.stored_file_contents else {
+    return Ok(());
+  };
+  let object_id = ObjectId::parse_str(&stack.id)
+    .context("Stack id is not valid ObjectId")?;
+  db_client()
+    .stacks
+    .update_one(
+      doc! { "_id": object_id },
+      doc! { "$set": { "info.stored_file_contents": Bson::Null } },
+    )
+    .await
+    .context("Failed to update Stack file contents on db")?;
+  delete_stored_file(&stored).await
+}
+
+/// Moves any Stack file contents not matching
+/// the configured `stack_file_storage` on startup.
+pub async fn migrate_stored_file_contents() {
+  let config = core_config();
+  let filter = if config.stack_file_storage
+    == StackFileStorage::Database
+  {
+    doc! { "info.stored_file_contents": { "$type": "object" } }
+  } else {
+    doc! { "$or": [
+      {
+        "info.stored_file_contents.storage": {
+          "$ne": config.stack_file_storage.to_string()
+        },
+        "info.stored_file_contents": { "$type": "object" },
+      },
+      { "$expr": { "$gt": [
+        { "$strLenBytes": { "$ifNull": ["$config.file_contents", ""] } },
+        config.stack_file_max_inline_bytes as i64,
+      ] } },
+    ] }
+  };
+  let stacks = match find_collect(&db_client().stacks, filter, None)
+    .await
+  {
+    Ok(stacks) => stacks,
+    Err(e) => {
+      error!(
+        "Failed to query db for Stacks to migrate file contents | {e:#}"
+      );
+      return;
+    }
+  };
+  for stack in stacks {
+    match sync_stored_file_contents(&stack.id).await {
+      Ok(_) => info!(
+        "Moved Stack {} file contents to {} storage",
+        stack.name, config.stack_file_storage
+      ),
+      Err(e) => warn!(
+        "Failed to move Stack {} file contents to {} storage | {e:#}",
+        stack.name, config.stack_file_storage
+      ),
+    }
+  }
+}
+
+/// Streams the contents into the configured storage,
+/// replacing any existing contents under the key.
+pub async fn write_stored_file(
+  key: &str,
+  mut reader: impl AsyncRead + Unpin + Send,
+) -> anyhow::Result<StoredFileContents> {
+  let config = core_config();
+  let storage = config.stack_file_storage;
+  let (size, sha256) = match storage {
+    StackFileStorage::Database => {
+      return Err(anyhow!(
+        "Core 'stack_file_storage' is not configured"
+      ));
+    }
+    StackFileStorage::Filesystem => {
+      let dir = &config.stack_file_directory;
+      fs::create_dir_all(dir).await.with_context(|| {
+        format!("Failed to create Stack file directory {dir:?}")
+      })?;
+      // Written next to the file first, so a failed write
+      // doesn't leave partial contents behind.
+      let path = dir.join(key);
+      let tmp_path = dir.join(format!("{key}.tmp"));
+      let mut file =
+        fs::File::create(&tmp_path).await.with_context(|| {
+          format!("Failed to create Stack file at {tmp_path:?}")
+        })?;
+      let res = copy_hashed(&mut reader, &mut file).await?;
+      file.sync_all().await.with_context(|| {
+        format!("Failed to write Stack file at {tmp_path:?}")
+      })?;
+      fs::rename(&tmp_path, &path).await.with_context(|| {
+        format!("Failed to move Stack file to {path:?}")
+      })?;
+      res
+    }
+    StackFileStorage::S3 => {
+      check_s3_bucket()?;
+      // S3 needs the full body to put the object.
+      let mut contents = Vec::new();
+      let res = copy_hashed(&mut reader, &mut contents).await?;
+      put_s3_object(
+        config.stack_file_s3_region.clone(),
+        &config.stack_file_s3_bucket,
+        key,
+        contents,
+      )
+      .await?;
+      res
+    }
+    StackFileStorage::GridFs => {
+      let bucket = gridfs_bucket();
+      let upload = bucket
+        .open_upload_stream(key)
+        .await
+        .context("Failed to open GridFS upload stream")?;
+      let id = upload.id().clone();
+      let mut upload = upload.compat_write();
+      let res = copy_hashed(&mut reader, &mut upload).await?;
+      upload
+        .shutdown()
+        .await
+        .context("Failed to finish GridFS upload")?;
+      // Remove the older revisions of the file.
+      let previous = bucket
+        .find(doc! { "filename": key, "_id": { "$ne": id } })
+        .await
+        .context("Failed to query GridFS for Stack files")?
+        .try_collect::<Vec<_>>()
+        .await
+        .context("Failed to query GridFS for Stack files")?;
+      for file in previous {
+        if let Err(e) = bucket.delete(file.id).await {
+          warn!(
+            "Failed to delete old GridFS Stack file {key} | {e:#}"
+          );
+        }
+      }
+      res
+    }
+  };
+  Ok(StoredFileContents {
+    storage,
+    key: key.to_string(),
+    size: size as i64,
+    sha256,
+  })
+}
+
+/// Streams the contents out of the storage they were written to.
+pub async fn read_stored_file(
+  stored: &StoredFileContents,
+) -> anyhow::Result<StoredFileReader> {
+  let config = core_config();
+  match stored.storage {
+    StackFileStorage::Database => {
+      Err(anyhow!("Stack file contents are stored in the database"))
+    }
+    StackFileStorage::Filesystem => {
+      let path = config.stack_file_directory.join(&stored.key);
+      let file = fs::File::open(&path).await.with_context(|| {
+        format!("Failed to open Stack file at {path:?}")
+      })?;
+      Ok(Box::pin(file))
+    }
+    StackFileStorage::S3 => {
+      check_s3_bucket()?;
+      let body = get_s3_object_stream(
+        config.stack_file_s3_region.clone(),
+        &config.stack_file_s3_bucket,
+        &stored.key,
+      )
+      .await?;
+      Ok(Box::pin(body.into_async_read()))
+    }
+    StackFileStorage::GridFs => {
+      let download = gridfs_bucket()
+        .open_download_stream_by_name(&stored.key)
+        .await
+        .with_context(|| {
+          format!("Failed to open GridFS Stack file {}", stored.key)
+        })?;
+      Ok(Box::pin(download.compat()))
+    }
+  }
+}
+
+pub async fn delete_stored_file(
+  stored: &StoredFileContents,
+) -> anyhow::Result<()> {
+  let config = core_config();
+  match stored.storage {
+    StackFileStorage::Database => Ok(()),
+    StackFileStorage::Filesystem => {
+      let path = config.stack_file_directory.join(&stored.key);
+      if path.exists() {
+        fs::remove_file(&path).await.with_context(|| {
+          format!("Failed to delete Stack file at {path:?}")
+        })?;
+      }
+      Ok(())
+    }
+    StackFileStorage::S3 => {
+      check_s3_bucket()?;
+      delete_s3_object(
+        config.stack_file_s3_region.clone(),
+        &config.stack_file_s3_bucket,
+        &stored.key,
+      )
+      .await
+    }
+    StackFileStorage::GridFs => {
+      let bucket = gridfs_bucket();
+      let files = bucket
+        .find(doc! { "filename": &stored.key })
+        .await
+        .context("Failed to query GridFS for Stack files")?
+        .try_collect::<Vec<_>>()
+        .await
+        .context("Failed to query GridFS for Stack files")?;
+      for file in files {
+        bucket.delete(file.id).await.with_context(|| {
+          format!("Failed to delete GridFS Stack file {}", stored.key)
+        })?;
+      }
+      Ok(())
+    }
+  }
+}
+
+/// Each Stack stores a single file under its id.
+pub fn stored_file_key(stack_id: &str) -> String {
+  format!("{stack_id}.compose.yaml")
+}
+
+async fn read_stored_file_to_string(
+  stored: &StoredFileContents,
+) -> anyhow::Result<String> {
+  let mut contents = String::with_capacity(stored.size as usize);
+  read_stored_file(stored)
+    .await?
+    .read_to_string(&mut contents)
+    .await
+    .context("Failed to read stored Stack file contents")?;
+  Ok(contents)
+}
+
+/// Returns the number of bytes copied and their sha256 in hex.
+async fn copy_hashed(
+  reader: &mut (impl AsyncRead + Unpin),
+  writer: &mut (impl AsyncWrite + Unpin),
+) -> anyhow::Result<(usize, String)> {
+  let mut hasher = Sha256::new();
+  let mut size = 0;
+  let mut buf = vec![0; CHUNK_BYTES];
+  loop {
+    let read = reader
+      .read(&mut buf)
+      .await
+      .context("Failed to read Stack file contents")?;
+    if read == 0 {
+      break;
+    }
+    hasher.update(&buf[..read]);
+    writer
+      .write_all(&buf[..read])
+      .await
+      .context("Failed to write Stack file contents")?;
+    size += read;
+  }
+  writer
+    .flush()
+    .await
+    .context("Failed to write Stack file contents")?;
+  Ok((size, hex::encode(hasher.finalize())))
+}
+
+fn check_s3_bucket() -> anyhow::Result<()> {
+  if core_config().stack_file_s3_bucket.is_empty() {
+    return Err(anyhow!(
+      "Core 'stack_file_s3_bucket' must be configured to store Stack files in S3"
+    ));
+  }
+  Ok(())
+}
+
+fn gridfs_bucket() -> GridFsBucket {
+  db_client().db.gridfs_bucket(
+    GridFsBucketOptions::builder()
+      .bucket_name(String::from(GRIDFS_BUCKET))
+      .build(),
+  )
+}
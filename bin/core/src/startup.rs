@@ -40,6 +40,7 @@ use crate::{
   config::core_config,
   helpers::update::init_execution_update,
   network, resource,
+  stack::storage::migrate_stored_file_contents,
   state::db_client,
 };
 
@@ -105,10 +106,12 @@ pub async fn on_startup() {
 
   tokio::join!(
     in_progress_update_cleanup(),
+    pending_approval_cleanup(),
     open_alert_cleanup(),
     clean_up_server_templates(),
     ensure_first_server_and_builder(),
     ensure_init_user_and_resources(),
+    migrate_stored_file_contents(),
   );
 }
 
@@ -141,6 +144,23 @@ async fn in_progress_update_cleanup() {
   }
 }
 
+/// Procedure runs waiting for approval were stopped by the shutdown.
+async fn pending_approval_cleanup() {
+  if let Err(e) = db_client()
+    .execution_approvals
+    .update_many(
+      doc! { "status": "Pending" },
+      doc! { "$set": {
+        "status": "Cancelled",
+        "resolved_at": komodo_timestamp(),
+      } },
+    )
+    .await
+  {
+    error!("failed to cleanup pending approvals on startup | {e:#}")
+  }
+}
+
 /// Run on startup, ensure open alerts pointing to invalid resources are closed.
 async fn open_alert_cleanup() {
   let db = db_client();
@@ -330,7 +350,10 @@ async fn ensure_init_user_and_resources() {
             execution: Execution::BackupCoreDatabase(BackupCoreDatabase {}),
             enabled: true
           }
-        ]
+        ],
+        condition: Default::default(),
+        on_failure: Default::default(),
+        require_approval: None,
       }])
       .schedule(String::from("Every day at 01:00"))
       .build()
@@ -373,7 +396,10 @@ async fn ensure_init_user_and_resources() {
             execution: Execution::GlobalAutoUpdate(GlobalAutoUpdate {}),
             enabled: true
           }
-        ]
+        ],
+        condition: Default::default(),
+        on_failure: Default::default(),
+        require_approval: None,
       }])
       .schedule(String::from("Every day at 03:00"))
       .build()
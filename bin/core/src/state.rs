@@ -14,6 +14,7 @@ use komodo_client::entities::{
   procedure::ProcedureState,
   repo::RepoState,
   stack::StackState,
+  swarm::SwarmServiceState,
 };
 use octorust::auth::{
   Credentials, InstallationTokenGenerator, JWTCredentials,
@@ -28,7 +29,7 @@ use crate::{
   },
   monitor::{
     CachedDeploymentStatus, CachedRepoStatus, CachedServerStatus,
-    CachedStackStatus, History,
+    CachedStackStatus, CachedSwarmServiceStatus, History,
   },
 };
 
@@ -157,6 +158,19 @@ pub fn stack_status_cache() -> &'static StackStatusCache {
   STACK_STATUS_CACHE.get_or_init(Default::default)
 }
 
+pub type SwarmServiceStatusCache = Cache<
+  String,
+  Arc<History<CachedSwarmServiceStatus, SwarmServiceState>>,
+>;
+
+pub fn swarm_service_status_cache() -> &'static SwarmServiceStatusCache
+{
+  static SWARM_SERVICE_STATUS_CACHE: OnceLock<
+    SwarmServiceStatusCache,
+  > = OnceLock::new();
+  SWARM_SERVICE_STATUS_CACHE.get_or_init(Default::default)
+}
+
 pub type ServerStatusCache = Cache<String, Arc<CachedServerStatus>>;
 
 pub fn server_status_cache() -> &'static ServerStatusCache {
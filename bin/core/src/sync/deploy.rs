@@ -91,6 +91,7 @@ pub async fn deploy_from_cache(
                 deployment: name.to_string(),
                 stop_signal: None,
                 stop_time: None,
+                inputs: None,
               });
 
               let update = init_execution_update(&req, user).await?;
@@ -357,7 +358,7 @@ fn build_cache_for_deployment<'a>(
         cache.insert(target, None);
         return Ok(());
       }
-      DeploymentState::Running => {
+      DeploymentState::Running | DeploymentState::Unhealthy => {
         // Here can diff the changes, to see if they merit a redeploy.
 
         // First merge toml resource config (partial) onto default resource config.
@@ -377,7 +378,12 @@ fn build_cache_for_deployment<'a>(
           || diff.image.is_some()
           || diff.image_registry_account.is_some()
           || diff.skip_secret_interp.is_some()
+          || diff.network_mode.is_some()
           || diff.network.is_some()
+          || diff.ip_address.is_some()
+          || diff.mac_address.is_some()
+          || diff.dns.is_some()
+          || diff.extra_hosts.is_some()
           || diff.restart.is_some()
           || diff.command.is_some()
           || diff.extra_args.is_some()
@@ -614,6 +620,8 @@ fn build_cache_for_stack<'a>(
           || diff.file_contents.is_some()
           || diff.skip_secret_interp.is_some()
           || diff.extra_args.is_some()
+          || diff.dns.is_some()
+          || diff.extra_hosts.is_some()
           || diff.environment.is_some()
           || diff.env_file_path.is_some()
           || diff.repo.is_some()
@@ -168,6 +168,8 @@ pub trait ExecuteResourceSync: ResourceSyncTrait {
           description: Some(resource.description),
           template: Some(resource.template),
           tags: Some(resource.tags),
+          metadata: None,
+          concurrency: None,
         },
         &mut log,
         &mut has_error,
@@ -197,6 +199,8 @@ pub trait ExecuteResourceSync: ResourceSyncTrait {
           .then(|| resource.description.clone()),
         template: update_template.then_some(resource.template),
         tags: update_tags.then(|| resource.tags.clone()),
+        metadata: None,
+        concurrency: None,
       };
 
       if !meta.is_none() {
@@ -214,9 +218,11 @@ pub trait ExecuteResourceSync: ResourceSyncTrait {
         if let Err(e) = crate::resource::update::<Self>(
           &id,
           resource.config,
+          None,
           sync_user(),
         )
         .await
+        .map_err(|e| e.error)
         {
           has_error = true;
           log.push_str(&format!(
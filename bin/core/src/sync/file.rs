@@ -1,6 +1,6 @@
 use std::{
   fs,
-  path::{Path, PathBuf},
+  path::{Component, Path, PathBuf},
 };
 
 use anyhow::{Context, anyhow};
@@ -238,6 +238,42 @@ fn read_resources_directory(
   Ok(())
 }
 
+/// Replaces the directory contents with the files.
+/// The file paths must be relative and stay in the directory.
+pub async fn write_source_files(
+  dir: &Path,
+  files: Vec<(String, Vec<u8>)>,
+) -> anyhow::Result<()> {
+  for (path, _) in &files {
+    let path = Path::new(path);
+    if !path
+      .components()
+      .all(|component| matches!(component, Component::Normal(_)))
+    {
+      return Err(anyhow!(
+        "Invalid sync file path {path:?}. Must be relative, and not contain '..'"
+      ));
+    }
+  }
+  if tokio::fs::try_exists(dir).await.unwrap_or_default() {
+    tokio::fs::remove_dir_all(dir).await.with_context(|| {
+      format!("Failed to clear sync files at {dir:?}")
+    })?;
+  }
+  for (path, contents) in files {
+    let path = dir.join(path);
+    if let Some(parent) = path.parent() {
+      tokio::fs::create_dir_all(parent).await.with_context(|| {
+        format!("Failed to create directory {parent:?}")
+      })?;
+    }
+    tokio::fs::write(&path, contents).await.with_context(|| {
+      format!("Failed to write sync file {path:?}")
+    })?;
+  }
+  Ok(())
+}
+
 pub fn extend_resources(
   resources: &mut ResourcesToml,
   more: ResourcesToml,
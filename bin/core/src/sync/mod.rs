@@ -16,6 +16,7 @@ pub mod execute;
 pub mod file;
 pub mod remote;
 pub mod resources;
+pub mod snapshot;
 pub mod toml;
 pub mod user_groups;
 pub mod variables;
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use anyhow::Context;
 use komodo_client::entities::{
   RepoExecutionArgs, RepoExecutionResponse,
@@ -8,9 +10,16 @@ use komodo_client::entities::{
   update::Log,
 };
 
-use crate::{config::core_config, helpers::git_token};
+use oci::{PulledArtifact, pull_oci_artifact, short_digest};
+use sha2::{Digest, Sha256};
+
+use crate::{
+  cloud::aws::s3::get_s3_objects_with_prefix,
+  config::core_config,
+  helpers::{TokenRequester, git_token, registry_token},
+};
 
-use super::file::extend_resources;
+use super::file::{extend_resources, write_source_files};
 
 pub struct RemoteResources {
   pub resources: anyhow::Result<ResourcesToml>,
@@ -28,10 +37,14 @@ pub async fn get_remote_resources(
 ) -> anyhow::Result<RemoteResources> {
   if sync.config.files_on_host {
     get_files_on_host(sync).await
+  } else if !sync.config.oci_artifact.is_empty() {
+    get_oci_artifact(sync).await
+  } else if !sync.config.s3_bucket.is_empty() {
+    get_s3_objects(sync).await
   } else if let Some(repo) = repo {
-    get_repo(sync, repo.into()).await
+    get_repo(sync, repo.into(), &repo.into()).await
   } else if !sync.config.repo.is_empty() {
-    get_repo(sync, sync.into()).await
+    get_repo(sync, sync.into(), &sync.into()).await
   } else {
     get_ui_defined(sync).await
   }
@@ -63,12 +76,137 @@ async fn get_files_on_host(
   })
 }
 
+/// The directory the OCI artifact or S3 files are downloaded to.
+fn artifact_source_directory(sync: &ResourceSync) -> PathBuf {
+  core_config()
+    .repo_directory
+    .join("sync-sources")
+    .join(to_path_compatible_name(&sync.name))
+}
+
+async fn get_oci_artifact(
+  sync: &ResourceSync,
+) -> anyhow::Result<RemoteResources> {
+  let artifact = &sync.config.oci_artifact;
+  let token = if sync.config.oci_account.is_empty() {
+    None
+  } else {
+    let domain = oci::registry_domain(artifact)?;
+    registry_token(&domain, &sync.config.oci_account, &sync.into())
+      .await
+      .with_context(|| {
+        format!(
+          "Failed to get registry token for {domain} | {}",
+          sync.config.oci_account
+        )
+      })?
+  };
+  let credentials = token
+    .as_deref()
+    .map(|token| (sync.config.oci_account.as_str(), token));
+
+  let dir = artifact_source_directory(sync);
+  let PulledArtifact { digest, revision } =
+    pull_oci_artifact(artifact, credentials, &dir).await?;
+
+  let mut logs = vec![Log::simple(
+    "Pull OCI artifact",
+    format!("Pulled {artifact} at {digest}"),
+  )];
+  let (mut files, mut file_errors) = (Vec::new(), Vec::new());
+  let resources = super::file::read_resources(
+    &dir,
+    &sync.config.resource_path,
+    &sync.config.match_tags,
+    &mut logs,
+    &mut files,
+    &mut file_errors,
+  );
+
+  Ok(RemoteResources {
+    resources,
+    files,
+    file_errors,
+    logs,
+    hash: Some(short_digest(&digest)),
+    message: Some(revision.unwrap_or(digest)),
+  })
+}
+
+async fn get_s3_objects(
+  sync: &ResourceSync,
+) -> anyhow::Result<RemoteResources> {
+  let config = &sync.config;
+  let region = if config.s3_region.is_empty() {
+    String::from("us-east-1")
+  } else {
+    config.s3_region.clone()
+  };
+  let objects = get_s3_objects_with_prefix(
+    region,
+    &config.s3_endpoint,
+    &config.s3_bucket,
+    &config.s3_prefix,
+  )
+  .await?;
+
+  // The hash changes when any of the objects change.
+  let mut hasher = Sha256::new();
+  for object in &objects {
+    hasher.update(&object.key);
+    hasher.update(&object.etag);
+  }
+  let hash = hex::encode(hasher.finalize());
+
+  let count = objects.len();
+  let files = objects
+    .into_iter()
+    .map(|object| {
+      let path = object
+        .key
+        .strip_prefix(&config.s3_prefix)
+        .unwrap_or(&object.key)
+        .trim_start_matches('/')
+        .to_string();
+      (path, object.contents)
+    })
+    .collect();
+  let dir = artifact_source_directory(sync);
+  write_source_files(&dir, files).await?;
+
+  let source =
+    format!("s3://{}/{}", config.s3_bucket, config.s3_prefix);
+  let mut logs = vec![Log::simple(
+    "Get S3 objects",
+    format!("Got {count} objects from {source}"),
+  )];
+  let (mut files, mut file_errors) = (Vec::new(), Vec::new());
+  let resources = super::file::read_resources(
+    &dir,
+    &config.resource_path,
+    &config.match_tags,
+    &mut logs,
+    &mut files,
+    &mut file_errors,
+  );
+
+  Ok(RemoteResources {
+    resources,
+    files,
+    file_errors,
+    logs,
+    hash: Some(short_digest(&hash)),
+    message: Some(format!("{count} objects in {source}")),
+  })
+}
+
 async fn get_repo(
   sync: &ResourceSync,
   mut clone_args: RepoExecutionArgs,
+  requester: &TokenRequester,
 ) -> anyhow::Result<RemoteResources> {
   let access_token = if let Some(account) = &clone_args.account {
-    git_token(&clone_args.provider, account, |https| clone_args.https = https)
+    git_token(&clone_args.provider, account, requester, |https| clone_args.https = https)
       .await
       .with_context(
         || format!("Failed to get git token in call to db. Stopping run. | {} | {account}", clone_args.provider),
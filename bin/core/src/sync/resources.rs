@@ -24,7 +24,10 @@ use partial_derive2::{MaybeNone, PartialDiff};
 
 use crate::{
   api::write::WriteArgs,
-  resource::{KomodoResource, ResourceMetaUpdate},
+  resource::{
+    KomodoResource, ResourceMetaUpdate,
+    inherit_scheduled_command_creators,
+  },
   state::all_resources_cache,
   sync::{ToUpdateItem, execute::run_update_meta},
 };
@@ -38,8 +41,16 @@ use super::{
 impl ResourceSyncTrait for Server {
   fn get_diff(
     original: Self::Config,
-    update: Self::PartialConfig,
+    mut update: Self::PartialConfig,
   ) -> anyhow::Result<Self::ConfigDiff> {
+    // created_by isn't included in the resource files.
+    if let Some(commands) = &mut update.scheduled_commands {
+      inherit_scheduled_command_creators(
+        &original.scheduled_commands,
+        commands,
+        "",
+      );
+    }
     Ok(original.partial_diff(update))
   }
 }
@@ -73,6 +84,19 @@ impl ResourceSyncTrait for Deployment {
       };
     }
 
+    // need to replace the restart after ids with names
+    original.restart_after = original
+      .restart_after
+      .iter()
+      .map(|id| {
+        resources
+          .deployments
+          .get(id)
+          .map(|d| d.name.clone())
+          .unwrap_or_default()
+      })
+      .collect();
+
     Ok(original.partial_diff(update))
   }
 }
@@ -97,6 +121,13 @@ impl ResourceSyncTrait for Stack {
       .get(&original.linked_repo)
       .map(|r| r.name.clone())
       .unwrap_or_default();
+    // Replace compose override repos with name
+    for compose_override in &mut original.compose_overrides {
+      if let Some(repo) = resources.repos.get(&compose_override.repo)
+      {
+        compose_override.repo.clone_from(&repo.name);
+      }
+    }
 
     Ok(original.partial_diff(update))
   }
@@ -234,13 +265,14 @@ impl ResourceSyncTrait for ResourceSync {
       && !config.files_on_host
       && config.repo.is_empty()
       && config.linked_repo.is_empty()
+      && !config.artifact_source()
     {
       return false;
     }
     // The file contents MUST be empty
     contents_empty &&
-    // The sync must be files on host mode OR git repo mode
-    (config.files_on_host || !config.repo.is_empty() || !config.linked_repo.is_empty())
+    // The sync must be files on host mode OR git repo mode OR artifact mode
+    (config.files_on_host || !config.repo.is_empty() || !config.linked_repo.is_empty() || config.artifact_source())
   }
 
   fn include_resource_partial(
@@ -270,6 +302,12 @@ impl ResourceSyncTrait for ResourceSync {
       .map(String::is_empty)
       .unwrap_or(true);
     let files_on_host = config.files_on_host.unwrap_or_default();
+    let artifact_source = !config
+      .oci_artifact
+      .as_deref()
+      .unwrap_or_default()
+      .is_empty()
+      || !config.s3_bucket.as_deref().unwrap_or_default().is_empty();
     if contents_empty
       && !files_on_host
       && config.repo.as_ref().map(String::is_empty).unwrap_or(true)
@@ -278,13 +316,14 @@ impl ResourceSyncTrait for ResourceSync {
         .as_ref()
         .map(String::is_empty)
         .unwrap_or(true)
+      && !artifact_source
     {
       return false;
     }
     // The file contents MUST be empty
     contents_empty &&
-    // The sync must be files on host mode OR git repo mode
-    (files_on_host || !config.repo.as_deref().unwrap_or_default().is_empty() || !config.linked_repo.as_deref().unwrap_or_default().is_empty())
+    // The sync must be files on host mode OR git repo mode OR artifact mode
+    (files_on_host || !config.repo.as_deref().unwrap_or_default().is_empty() || !config.linked_repo.as_deref().unwrap_or_default().is_empty() || artifact_source)
   }
 
   fn get_diff(
@@ -403,6 +442,27 @@ impl ResourceSyncTrait for Procedure {
               .unwrap_or_default();
           }
           Execution::BatchDestroyDeployment(_config) => {}
+          Execution::DeploySwarmService(config) => {
+            config.swarm_service = resources
+              .swarm_services
+              .get(&config.swarm_service)
+              .map(|d| d.name.clone())
+              .unwrap_or_default();
+          }
+          Execution::ScaleSwarmService(config) => {
+            config.swarm_service = resources
+              .swarm_services
+              .get(&config.swarm_service)
+              .map(|d| d.name.clone())
+              .unwrap_or_default();
+          }
+          Execution::RemoveSwarmService(config) => {
+            config.swarm_service = resources
+              .swarm_services
+              .get(&config.swarm_service)
+              .map(|d| d.name.clone())
+              .unwrap_or_default();
+          }
           Execution::CloneRepo(config) => {
             config.repo = resources
               .repos
@@ -419,6 +479,13 @@ impl ResourceSyncTrait for Procedure {
               .unwrap_or_default();
           }
           Execution::BatchPullRepo(_config) => {}
+          Execution::RunRepoPipeline(config) => {
+            config.repo = resources
+              .repos
+              .get(&config.repo)
+              .map(|d| d.name.clone())
+              .unwrap_or_default();
+          }
           Execution::BuildRepo(config) => {
             config.repo = resources
               .repos
@@ -581,6 +648,13 @@ impl ResourceSyncTrait for Procedure {
               .map(|d| d.name.clone())
               .unwrap_or_default();
           }
+          Execution::RunScheduledCommand(config) => {
+            config.server = resources
+              .servers
+              .get(&config.server)
+              .map(|d| d.name.clone())
+              .unwrap_or_default();
+          }
           Execution::RunSync(config) => {
             config.sync = resources
               .syncs
@@ -668,6 +742,13 @@ impl ResourceSyncTrait for Procedure {
               .map(|s| s.name.clone())
               .unwrap_or_default();
           }
+          Execution::ScaleStackService(config) => {
+            config.stack = resources
+              .stacks
+              .get(&config.stack)
+              .map(|s| s.name.clone())
+              .unwrap_or_default();
+          }
           Execution::BatchDestroyStack(_config) => {}
           Execution::TestAlerter(config) => {
             config.alerter = resources
@@ -772,6 +853,8 @@ impl ExecuteResourceSync for Procedure {
             .then(|| resource.description.clone()),
           template: update_template.then(|| resource.template),
           tags: update_tags.then(|| resource.tags.clone()),
+          metadata: None,
+          concurrency: None,
         };
 
         if !meta.is_none() {
@@ -788,9 +871,11 @@ impl ExecuteResourceSync for Procedure {
           && let Err(e) = crate::resource::update::<Procedure>(
             id,
             resource.config.clone(),
+            None,
             sync_user(),
           )
           .await
+          .map_err(|e| e.error)
         {
           if i == 9 {
             has_error = true;
@@ -848,6 +933,8 @@ impl ExecuteResourceSync for Procedure {
             description: Some(resource.description.clone()),
             template: Some(resource.template),
             tags: Some(resource.tags.clone()),
+            metadata: None,
+            concurrency: None,
           },
           &mut log,
           &mut has_error,
@@ -0,0 +1,177 @@
+use anyhow::Context;
+use indexmap::IndexMap;
+use komodo_client::{
+  api::read::ExportAllResourcesToToml,
+  entities::{
+    action::Action,
+    alerter::Alerter,
+    build::Build,
+    builder::Builder,
+    deployment::Deployment,
+    procedure::Procedure,
+    repo::Repo,
+    server::Server,
+    stack::Stack,
+    sync::{DiffData, ResourceDiff, ResourceSync},
+    toml::{ResourceToml, ResourcesToml},
+    user::User,
+  },
+};
+use resolver_api::Resolve;
+
+use crate::api::read::ReadArgs;
+
+use super::{
+  deserialize_resources_toml,
+  toml::{ToToml, resource_toml_to_toml_string},
+  user_groups::user_group_to_toml,
+  variables::variable_to_toml,
+};
+
+/// Exports all resources, variables, and user groups
+/// the user can see as TOML.
+pub async fn current_state_toml(
+  user: &User,
+) -> anyhow::Result<String> {
+  let toml = ExportAllResourcesToToml {
+    include_resources: true,
+    tags: Vec::new(),
+    include_variables: true,
+    include_user_groups: true,
+  }
+  .resolve(&ReadArgs { user: user.clone() })
+  .await
+  .map_err(|e| e.error)?
+  .toml;
+  Ok(toml)
+}
+
+#[derive(Default)]
+pub struct SnapshotDiff {
+  pub resource_updates: Vec<ResourceDiff>,
+  pub variable_updates: Vec<DiffData>,
+  pub user_group_updates: Vec<DiffData>,
+}
+
+/// Diffs two snapshots in the exported TOML format.
+/// In each diff, `current` is from `from`, and `proposed` is from `to`.
+pub fn diff_snapshots(
+  from: &str,
+  to: &str,
+) -> anyhow::Result<SnapshotDiff> {
+  let from = deserialize_resources_toml(from)
+    .context("Failed to parse 'from' snapshot")?;
+  let to = deserialize_resources_toml(to)
+    .context("Failed to parse 'to' snapshot")?;
+
+  let ResourcesToml {
+    servers,
+    deployments,
+    stacks,
+    builds,
+    repos,
+    procedures,
+    actions,
+    alerters,
+    builders,
+    resource_syncs,
+    user_groups,
+    variables,
+  } = from;
+
+  let mut diff = SnapshotDiff::default();
+  let updates = &mut diff.resource_updates;
+
+  diff_resources::<Server>(servers, to.servers, updates)?;
+  diff_resources::<Stack>(stacks, to.stacks, updates)?;
+  diff_resources::<Deployment>(deployments, to.deployments, updates)?;
+  diff_resources::<Build>(builds, to.builds, updates)?;
+  diff_resources::<Repo>(repos, to.repos, updates)?;
+  diff_resources::<Procedure>(procedures, to.procedures, updates)?;
+  diff_resources::<Action>(actions, to.actions, updates)?;
+  diff_resources::<Builder>(builders, to.builders, updates)?;
+  diff_resources::<Alerter>(alerters, to.alerters, updates)?;
+  diff_resources::<ResourceSync>(
+    resource_syncs,
+    to.resource_syncs,
+    updates,
+  )?;
+
+  diff.variable_updates = diff_by_name(
+    variables.into_iter().map(|v| (v.name.clone(), v)),
+    to.variables.into_iter().map(|v| (v.name.clone(), v)),
+    |variable| variable_to_toml(&variable),
+  )?
+  .into_iter()
+  .map(|(_, data)| data)
+  .collect();
+
+  diff.user_group_updates = diff_by_name(
+    user_groups.into_iter().map(|ug| (ug.name.clone(), ug)),
+    to.user_groups.into_iter().map(|ug| (ug.name.clone(), ug)),
+    user_group_to_toml,
+  )?
+  .into_iter()
+  .map(|(_, data)| data)
+  .collect();
+
+  Ok(diff)
+}
+
+fn diff_resources<R: ToToml>(
+  from: Vec<ResourceToml<R::PartialConfig>>,
+  to: Vec<ResourceToml<R::PartialConfig>>,
+  diffs: &mut Vec<ResourceDiff>,
+) -> anyhow::Result<()> {
+  let updates = diff_by_name(
+    from.into_iter().map(|r| (r.name.clone(), r)),
+    to.into_iter().map(|r| (r.name.clone(), r)),
+    resource_toml_to_toml_string::<R>,
+  )?;
+  diffs.extend(updates.into_iter().map(|(name, data)| {
+    ResourceDiff {
+      // Snapshots may include deleted resources, so use the name.
+      target: R::resource_target(name),
+      data,
+    }
+  }));
+  Ok(())
+}
+
+/// Matches items by name, and compares them using the TOML
+/// they would be exported as.
+fn diff_by_name<T>(
+  from: impl Iterator<Item = (String, T)>,
+  to: impl Iterator<Item = (String, T)>,
+  to_toml: impl Fn(T) -> anyhow::Result<String>,
+) -> anyhow::Result<Vec<(String, DiffData)>> {
+  let mut from = from.collect::<IndexMap<_, _>>();
+  let mut diffs = Vec::new();
+
+  for (name, item) in to {
+    let proposed = to_toml(item)?;
+    match from.shift_remove(&name) {
+      Some(current) => {
+        let current = to_toml(current)?;
+        if current != proposed {
+          diffs.push((name, DiffData::Update { proposed, current }));
+        }
+      }
+      None => {
+        diffs
+          .push((name.clone(), DiffData::Create { name, proposed }));
+      }
+    }
+  }
+
+  for (name, current) in from {
+    diffs.push((
+      name,
+      DiffData::Delete {
+        current: to_toml(current)?,
+      },
+    ));
+  }
+
+  Ok(diffs)
+}
@@ -158,7 +158,14 @@ pub fn convert_resource<R: KomodoResource>(
 
 // These have no linked resource ids to replace
 impl ToToml for Alerter {}
-impl ToToml for Server {}
+impl ToToml for Server {
+  fn replace_ids(resource: &mut Resource<Self::Config, Self::Info>) {
+    // Set by Core from the user making the change
+    for command in &mut resource.config.scheduled_commands {
+      command.created_by.clear();
+    }
+  }
+}
 impl ToToml for Action {}
 
 impl ToToml for ResourceSync {
@@ -191,6 +198,11 @@ impl ToToml for Stack {
         .map(|r| &r.name)
         .unwrap_or(&String::new()),
     );
+    for compose_override in &mut resource.config.compose_overrides {
+      if let Some(repo) = all.repos.get(&compose_override.repo) {
+        compose_override.repo.clone_from(&repo.name);
+      }
+    }
   }
 
   fn edit_config_object(
@@ -232,6 +244,15 @@ impl ToToml for Deployment {
           .unwrap_or(&String::new()),
       );
     }
+    for dependency in &mut resource.config.restart_after {
+      dependency.clone_from(
+        all
+          .deployments
+          .get(dependency)
+          .map(|d| &d.name)
+          .unwrap_or(&String::new()),
+      );
+    }
   }
 
   fn edit_config_object(
@@ -502,6 +523,33 @@ impl ToToml for Procedure {
             )
           }
           Execution::BatchDestroyDeployment(_exec) => {}
+          Execution::DeploySwarmService(exec) => {
+            exec.swarm_service.clone_from(
+              all
+                .swarm_services
+                .get(&exec.swarm_service)
+                .map(|r| &r.name)
+                .unwrap_or(&String::new()),
+            )
+          }
+          Execution::ScaleSwarmService(exec) => {
+            exec.swarm_service.clone_from(
+              all
+                .swarm_services
+                .get(&exec.swarm_service)
+                .map(|r| &r.name)
+                .unwrap_or(&String::new()),
+            )
+          }
+          Execution::RemoveSwarmService(exec) => {
+            exec.swarm_service.clone_from(
+              all
+                .swarm_services
+                .get(&exec.swarm_service)
+                .map(|r| &r.name)
+                .unwrap_or(&String::new()),
+            )
+          }
           Execution::CloneRepo(exec) => exec.repo.clone_from(
             all
               .repos
@@ -518,6 +566,13 @@ impl ToToml for Procedure {
               .unwrap_or(&String::new()),
           ),
           Execution::BatchPullRepo(_exec) => {}
+          Execution::RunRepoPipeline(exec) => exec.repo.clone_from(
+            all
+              .repos
+              .get(&exec.repo)
+              .map(|r| &r.name)
+              .unwrap_or(&String::new()),
+          ),
           Execution::BuildRepo(exec) => exec.repo.clone_from(
             all
               .repos
@@ -698,6 +753,15 @@ impl ToToml for Procedure {
               .map(|r| &r.name)
               .unwrap_or(&String::new()),
           ),
+          Execution::RunScheduledCommand(exec) => {
+            exec.server.clone_from(
+              all
+                .servers
+                .get(&exec.server)
+                .map(|r| &r.name)
+                .unwrap_or(&String::new()),
+            )
+          }
           Execution::RunSync(exec) => exec.sync.clone_from(
             all
               .syncs
@@ -759,6 +823,15 @@ impl ToToml for Procedure {
               .map(|r| &r.name)
               .unwrap_or(&String::new()),
           ),
+          Execution::ScaleStackService(exec) => {
+            exec.stack.clone_from(
+              all
+                .stacks
+                .get(&exec.stack)
+                .map(|r| &r.name)
+                .unwrap_or(&String::new()),
+            )
+          }
           Execution::PauseStack(exec) => exec.stack.clone_from(
             all
               .stacks
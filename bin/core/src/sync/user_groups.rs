@@ -344,6 +344,13 @@ pub async fn get_updates_for_execution(
             .map(|b| b.name.clone())
             .unwrap_or_default()
         }
+        ResourceTarget::SwarmService(id) => {
+          *id = all_resources
+            .swarm_services
+            .get(id)
+            .map(|b| b.name.clone())
+            .unwrap_or_default()
+        }
         ResourceTarget::Server(id) => {
           *id = all_resources
             .servers
@@ -753,6 +760,7 @@ async fn run_update_permissions(
       user_target: UserTarget::UserGroup(user_group.clone()),
       resource_target: target.clone(),
       permission: level.specifics(specific.clone()),
+      expires_at: 0,
     })
     .resolve(&WriteArgs {
       user: sync_user().to_owned(),
@@ -832,6 +840,20 @@ async fn expand_user_group_permissions(
           });
         expanded.extend(permissions);
       }
+      ResourceTargetVariant::SwarmService => {
+        let permissions = all_resources
+          .swarm_services
+          .values()
+          .filter(|resource| matcher.is_match(&resource.name))
+          .map(|resource| PermissionToml {
+            target: ResourceTarget::SwarmService(
+              resource.name.clone(),
+            ),
+            level: permission.level,
+            specific: permission.specific.clone(),
+          });
+        expanded.extend(permissions);
+      }
       ResourceTargetVariant::Server => {
         let permissions = all_resources
           .servers
@@ -1061,6 +1083,13 @@ pub async fn convert_user_groups(
             .map(|r| r.name.clone())
             .unwrap_or_default()
         }
+        ResourceTarget::SwarmService(id) => {
+          *id = all
+            .swarm_services
+            .get(id)
+            .map(|r| r.name.clone())
+            .unwrap_or_default()
+        }
         ResourceTarget::Server(id) => {
           *id = all
             .servers
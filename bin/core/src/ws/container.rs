@@ -8,7 +8,10 @@ use komodo_client::{
   entities::{permission::PermissionLevel, server::Server},
 };
 
-use crate::permission::get_check_permissions;
+use crate::{
+  helpers::terminal::terminal_read_only,
+  permission::get_check_permissions,
+};
 
 #[instrument(name = "ConnectContainerExec", skip(ws))]
 pub async fn terminal(
@@ -16,6 +19,7 @@ pub async fn terminal(
     server,
     container,
     shell,
+    read_only,
   }): Query<ConnectContainerExecQuery>,
   ws: WebSocketUpgrade,
 ) -> impl IntoResponse {
@@ -44,11 +48,29 @@ pub async fn terminal(
       }
     };
 
+    let read_only = match terminal_read_only::<Server>(
+      &user, &server.id, read_only,
+    )
+    .await
+    {
+      Ok(read_only) => read_only,
+      Err(e) => {
+        debug!("could not get user permission on server | {e:#}");
+        let _ = client_socket
+          .send(Message::text(format!("ERROR: {e:#}")))
+          .await;
+        let _ = client_socket.close().await;
+        return;
+      }
+    };
+
     super::handle_container_terminal(
       client_socket,
       &server,
       container,
       shell,
+      &user,
+      read_only,
     )
     .await
   })
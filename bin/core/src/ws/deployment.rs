@@ -11,13 +11,18 @@ use komodo_client::{
   },
 };
 
-use crate::{permission::get_check_permissions, resource::get};
+use crate::{
+  helpers::terminal::terminal_read_only,
+  permission::get_check_permissions, resource::get,
+};
 
 #[instrument(name = "ConnectDeploymentExec", skip(ws))]
 pub async fn terminal(
-  Query(ConnectDeploymentExecQuery { deployment, shell }): Query<
-    ConnectDeploymentExecQuery,
-  >,
+  Query(ConnectDeploymentExecQuery {
+    deployment,
+    shell,
+    read_only,
+  }): Query<ConnectDeploymentExecQuery>,
   ws: WebSocketUpgrade,
 ) -> impl IntoResponse {
   ws.on_upgrade(|socket| async move {
@@ -45,6 +50,24 @@ pub async fn terminal(
       }
     };
 
+    let read_only = match terminal_read_only::<Deployment>(
+      &user,
+      &deployment.id,
+      read_only,
+    )
+    .await
+    {
+      Ok(read_only) => read_only,
+      Err(e) => {
+        debug!("could not get user permission on deployment | {e:#}");
+        let _ = client_socket
+          .send(Message::text(format!("ERROR: {e:#}")))
+          .await;
+        let _ = client_socket.close().await;
+        return;
+      }
+    };
+
     let server =
       match get::<Server>(&deployment.config.server_id).await {
         Ok(server) => server,
@@ -63,6 +86,8 @@ pub async fn terminal(
       &server,
       deployment.name,
       shell,
+      &user,
+      read_only,
     )
     .await
   })
@@ -1,6 +1,12 @@
 use crate::{
   auth::{auth_api_key_check_enabled, auth_jwt_check_enabled},
-  helpers::query::get_user,
+  helpers::{
+    query::get_user,
+    terminal::{
+      check_container_exec_enabled,
+      check_unrestricted_terminal_access,
+    },
+  },
 };
 use anyhow::anyhow;
 use axum::{
@@ -22,12 +28,14 @@ use tokio_util::sync::CancellationToken;
 mod container;
 mod deployment;
 mod stack;
+mod subscribe;
 mod terminal;
 mod update;
 
 pub fn router() -> Router {
   Router::new()
     .route("/update", get(update::handler))
+    .route("/subscribe", get(subscribe::handler))
     .route("/terminal", get(terminal::handler))
     .route("/container/terminal", get(container::terminal))
     .route("/deployment/terminal", get(deployment::terminal))
@@ -130,8 +138,11 @@ async fn handle_container_terminal(
   server: &Server,
   container: String,
   shell: String,
+  user: &User,
+  read_only: bool,
 ) {
-  let periphery = match crate::helpers::periphery_client(server) {
+  let periphery = match crate::helpers::periphery_client(server).await
+  {
     Ok(periphery) => periphery,
     Err(e) => {
       debug!("couldn't get periphery | {e:#}");
@@ -143,10 +154,33 @@ async fn handle_container_terminal(
     }
   };
 
+  if let Err(e) = check_unrestricted_terminal_access(server, user) {
+    debug!("user not allowed to access container exec | {e:#}");
+    let _ = client_socket
+      .send(Message::text(format!("ERROR: {e:#}")))
+      .await;
+    let _ = client_socket.close().await;
+    return;
+  }
+
+  if let Err(e) = check_container_exec_enabled(server).await {
+    debug!("container exec disabled | {e:#}");
+    let _ = client_socket
+      .send(Message::text(format!("ERROR: {e:#}")))
+      .await;
+    let _ = client_socket.close().await;
+    return;
+  }
+
   trace!("connecting to periphery container exec websocket");
 
   let periphery_socket = match periphery
-    .connect_container_exec(container, shell)
+    .connect_container_exec(
+      container,
+      shell,
+      user.username.clone(),
+      read_only,
+    )
     .await
   {
     Ok(ws) => ws,
@@ -11,8 +11,9 @@ use komodo_client::{
 };
 
 use crate::{
+  helpers::terminal::terminal_read_only,
   permission::get_check_permissions, resource::get,
-  state::stack_status_cache,
+  stack::get_stack_service_container,
 };
 
 #[instrument(name = "ConnectStackExec", skip(ws))]
@@ -21,6 +22,7 @@ pub async fn terminal(
     stack,
     service,
     shell,
+    read_only,
   }): Query<ConnectStackExecQuery>,
   ws: WebSocketUpgrade,
 ) -> impl IntoResponse {
@@ -49,6 +51,21 @@ pub async fn terminal(
       }
     };
 
+    let read_only =
+      match terminal_read_only::<Stack>(&user, &stack.id, read_only)
+        .await
+      {
+        Ok(read_only) => read_only,
+        Err(e) => {
+          debug!("could not get user permission on stack | {e:#}");
+          let _ = client_socket
+            .send(Message::text(format!("ERROR: {e:#}")))
+            .await;
+          let _ = client_socket.close().await;
+          return;
+        }
+      };
+
     let server = match get::<Server>(&stack.config.server_id).await {
       Ok(server) => server,
       Err(e) => {
@@ -61,51 +78,28 @@ pub async fn terminal(
       }
     };
 
-    let Some(status) = stack_status_cache().get(&stack.id).await
-    else {
-      debug!("could not get stack status");
-      let _ = client_socket
-        .send(Message::text(String::from(
-          "ERROR: could not get stack status",
-        )))
-        .await;
-      let _ = client_socket.close().await;
-      return;
-    };
-
-    let container = match status
-      .curr
-      .services
-      .iter()
-      .find(|s| s.service == service)
-      .map(|s| s.container.as_ref())
-    {
-      Some(Some(container)) => container.name.clone(),
-      Some(None) => {
-        let _ = client_socket
-          .send(Message::text(format!(
-            "ERROR: Service {service} container could not be found"
-          )))
-          .await;
-        let _ = client_socket.close().await;
-        return;
-      }
-      None => {
-        let _ = client_socket
-          .send(Message::text(format!(
-            "ERROR: Service {service} could not be found"
-          )))
-          .await;
-        let _ = client_socket.close().await;
-        return;
-      }
-    };
+    let container =
+      match get_stack_service_container(&stack, &server, &service)
+        .await
+      {
+        Ok(container) => container,
+        Err(e) => {
+          debug!("could not get stack service container | {e:#}");
+          let _ = client_socket
+            .send(Message::text(format!("ERROR: {e:#}")))
+            .await;
+          let _ = client_socket.close().await;
+          return;
+        }
+      };
 
     super::handle_container_terminal(
       client_socket,
       &server,
       container,
       shell,
+      &user,
+      read_only,
     )
     .await
   })
@@ -0,0 +1,341 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use anyhow::{Context, anyhow};
+use axum::{
+  extract::{
+    WebSocketUpgrade,
+    ws::{Message, WebSocket},
+  },
+  response::IntoResponse,
+};
+use komodo_client::{
+  busy::Busy,
+  entities::{
+    JsonValue, ResourceTarget, action::Action, alerter::Alerter,
+    build::Build, builder::Builder, deployment::Deployment,
+    permission::PermissionLevel, procedure::Procedure, repo::Repo,
+    resource::Resource, server::Server, stack::Stack,
+    swarm::SwarmService, sync::ResourceSync, user::User,
+  },
+  ws::{WsSubscribeMessage, WsSubscriptionMessage},
+};
+use serde::Serialize;
+use serde_json::json;
+use serror::serialize_error;
+
+use crate::{
+  helpers::{
+    action_state::ActionState, all_resources::AllResourcesById,
+    cache::Cache, query::get_user_permission_on_target,
+  },
+  permission::get_check_permissions,
+  resource::{self, KomodoResource},
+  state::{action_states, all_resources_cache, server_status_cache},
+};
+
+/// How often the subscriptions are checked for changes.
+const SUBSCRIPTION_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_SUBSCRIPTIONS: usize = 100;
+
+enum Subscription {
+  Resource(ResourceTarget),
+  ContainerStats(String),
+}
+
+struct ActiveSubscription {
+  subscription: Subscription,
+  /// The last message sent, so only changes are sent.
+  last: String,
+}
+
+#[instrument(level = "debug")]
+pub async fn handler(ws: WebSocketUpgrade) -> impl IntoResponse {
+  ws.on_upgrade(|socket| async move {
+    let Some((socket, user)) = super::ws_login(socket).await else {
+      return;
+    };
+    handle_subscriptions(socket, user).await
+  })
+}
+
+async fn handle_subscriptions(mut socket: WebSocket, user: User) {
+  let mut subscriptions =
+    HashMap::<String, ActiveSubscription>::new();
+  let mut interval = tokio::time::interval(SUBSCRIPTION_INTERVAL);
+
+  loop {
+    tokio::select! {
+      msg = socket.recv() => {
+        let text = match msg {
+          Some(Ok(Message::Text(text))) => text,
+          Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+          Some(Ok(_)) => continue,
+        };
+        let res = match serde_json::from_str::<WsSubscribeMessage>(&text) {
+          Ok(msg) => {
+            handle_subscribe_message(msg, &user, &mut subscriptions).await
+          }
+          Err(e) => Err(
+            anyhow::Error::from(e)
+              .context("Failed to parse subscription message"),
+          ),
+        };
+        let msg = match res {
+          Ok(msg) => msg,
+          Err(e) => WsSubscriptionMessage::Error {
+            id: None,
+            message: format!("{e:#}"),
+          },
+        };
+        if send(&mut socket, &msg).await.is_err() {
+          return;
+        }
+        // Send the current state of new subscriptions right away.
+        if matches!(msg, WsSubscriptionMessage::Subscribed { .. }) {
+          interval.reset_immediately();
+        }
+      }
+      _ = interval.tick() => {
+        if subscriptions.is_empty() {
+          continue;
+        }
+        // Verify user is still valid, kill the connection if not.
+        if let Err(e) = super::check_user_valid(&user.id).await {
+          let _ = socket
+            .send(Message::text(json!({ "type": "INVALID_USER", "msg": serialize_error(&e) }).to_string()))
+            .await;
+          let _ = socket.close().await;
+          return;
+        }
+        if send_changes(&mut socket, &mut subscriptions).await.is_err() {
+          return;
+        }
+      }
+    }
+  }
+}
+
+async fn handle_subscribe_message(
+  msg: WsSubscribeMessage,
+  user: &User,
+  subscriptions: &mut HashMap<String, ActiveSubscription>,
+) -> anyhow::Result<WsSubscriptionMessage> {
+  let (id, subscription) = match msg {
+    WsSubscribeMessage::SubscribeResource { target } => {
+      let (variant, resource_id) = target.extract_variant_id();
+      if matches!(target, ResourceTarget::System(_)) {
+        return Err(anyhow!("Cannot subscribe to System target"));
+      }
+      if !user.admin {
+        let permission =
+          get_user_permission_on_target(user, &target).await?;
+        if permission.level < PermissionLevel::Read {
+          return Err(anyhow!(
+            "User does not have Read permission on {variant} {resource_id}"
+          ));
+        }
+      }
+      (
+        format!("Resource:{variant}:{resource_id}"),
+        Subscription::Resource(target),
+      )
+    }
+    WsSubscribeMessage::SubscribeContainerStats { server } => {
+      let server = get_check_permissions::<Server>(
+        &server,
+        user,
+        PermissionLevel::Read.into(),
+      )
+      .await?;
+      (
+        format!("ContainerStats:{}", server.id),
+        Subscription::ContainerStats(server.id),
+      )
+    }
+    WsSubscribeMessage::Unsubscribe { id } => {
+      subscriptions
+        .remove(&id)
+        .with_context(|| format!("No subscription with id {id}"))?;
+      return Ok(WsSubscriptionMessage::Unsubscribed { id });
+    }
+  };
+  if !subscriptions.contains_key(&id)
+    && subscriptions.len() >= MAX_SUBSCRIPTIONS
+  {
+    return Err(anyhow!(
+      "Cannot have more than {MAX_SUBSCRIPTIONS} subscriptions on one connection"
+    ));
+  }
+  subscriptions.insert(
+    id.clone(),
+    ActiveSubscription {
+      subscription,
+      last: String::new(),
+    },
+  );
+  Ok(WsSubscriptionMessage::Subscribed { id })
+}
+
+/// Sends the subscriptions which changed since last sent.
+async fn send_changes(
+  socket: &mut WebSocket,
+  subscriptions: &mut HashMap<String, ActiveSubscription>,
+) -> anyhow::Result<()> {
+  let mut failed = Vec::new();
+  for (id, active) in subscriptions.iter_mut() {
+    let msg =
+      match subscription_message(id, &active.subscription).await {
+        Ok(msg) => msg,
+        Err(e) => {
+          // Eg. the resource was deleted.
+          send(
+            socket,
+            &WsSubscriptionMessage::Error {
+              id: Some(id.clone()),
+              message: format!("{e:#}"),
+            },
+          )
+          .await?;
+          failed.push(id.clone());
+          continue;
+        }
+      };
+    let msg = serde_json::to_string(&msg)
+      .context("Failed to serialize subscription message")?;
+    if msg != active.last {
+      socket
+        .send(Message::text(msg.clone()))
+        .await
+        .context("Failed to send subscription message")?;
+      active.last = msg;
+    }
+  }
+  for id in failed {
+    subscriptions.remove(&id);
+  }
+  Ok(())
+}
+
+async fn send(
+  socket: &mut WebSocket,
+  msg: &WsSubscriptionMessage,
+) -> anyhow::Result<()> {
+  let msg = serde_json::to_string(msg)
+    .context("Failed to serialize subscription message")?;
+  socket
+    .send(Message::text(msg))
+    .await
+    .context("Failed to send subscription message")
+}
+
+async fn subscription_message(
+  id: &str,
+  subscription: &Subscription,
+) -> anyhow::Result<WsSubscriptionMessage> {
+  match subscription {
+    Subscription::Resource(target) => {
+      resource_message(id, target).await
+    }
+    Subscription::ContainerStats(server) => {
+      let status =
+        server_status_cache().get(server).await.unwrap_or_default();
+      Ok(WsSubscriptionMessage::ContainerStats {
+        id: id.to_string(),
+        server: server.clone(),
+        containers: status.containers.clone().unwrap_or_default(),
+        stats: status.stats.clone(),
+      })
+    }
+  }
+}
+
+async fn resource_message(
+  subscription_id: &str,
+  target: &ResourceTarget,
+) -> anyhow::Result<WsSubscriptionMessage> {
+  let states = action_states();
+  let (item, action_state) = match target {
+    ResourceTarget::System(_) => {
+      return Err(anyhow!("Cannot subscribe to System target"));
+    }
+    ResourceTarget::Server(id) => (
+      list_item::<Server>(id, |all| &all.servers).await?,
+      action_state(&states.server, id).await?,
+    ),
+    ResourceTarget::Stack(id) => (
+      list_item::<Stack>(id, |all| &all.stacks).await?,
+      action_state(&states.stack, id).await?,
+    ),
+    ResourceTarget::Deployment(id) => (
+      list_item::<Deployment>(id, |all| &all.deployments).await?,
+      action_state(&states.deployment, id).await?,
+    ),
+    ResourceTarget::SwarmService(id) => (
+      list_item::<SwarmService>(id, |all| &all.swarm_services)
+        .await?,
+      action_state(&states.swarm_service, id).await?,
+    ),
+    ResourceTarget::Build(id) => (
+      list_item::<Build>(id, |all| &all.builds).await?,
+      action_state(&states.build, id).await?,
+    ),
+    ResourceTarget::Repo(id) => (
+      list_item::<Repo>(id, |all| &all.repos).await?,
+      action_state(&states.repo, id).await?,
+    ),
+    ResourceTarget::Procedure(id) => (
+      list_item::<Procedure>(id, |all| &all.procedures).await?,
+      action_state(&states.procedure, id).await?,
+    ),
+    ResourceTarget::Action(id) => (
+      list_item::<Action>(id, |all| &all.actions).await?,
+      action_state(&states.action, id).await?,
+    ),
+    ResourceTarget::ResourceSync(id) => (
+      list_item::<ResourceSync>(id, |all| &all.syncs).await?,
+      action_state(&states.sync, id).await?,
+    ),
+    ResourceTarget::Builder(id) => (
+      list_item::<Builder>(id, |all| &all.builders).await?,
+      JsonValue::Null,
+    ),
+    ResourceTarget::Alerter(id) => (
+      list_item::<Alerter>(id, |all| &all.alerters).await?,
+      JsonValue::Null,
+    ),
+  };
+  Ok(WsSubscriptionMessage::Resource {
+    id: subscription_id.to_string(),
+    target: target.clone(),
+    item,
+    action_state,
+  })
+}
+
+/// Uses the cached resource when available, to avoid
+/// querying the db every interval.
+async fn list_item<T: KomodoResource>(
+  id: &String,
+  cached: fn(
+    &AllResourcesById,
+  ) -> &HashMap<String, Resource<T::Config, T::Info>>,
+) -> anyhow::Result<JsonValue> {
+  let cached = cached(&all_resources_cache().load()).get(id).cloned();
+  let resource = match cached {
+    Some(resource) => resource,
+    None => resource::get::<T>(id).await?,
+  };
+  serde_json::to_value(T::to_list_item(resource).await)
+    .context("Failed to serialize resource list item")
+}
+
+async fn action_state<
+  States: Default + Busy + Copy + Send + Serialize + 'static,
+>(
+  cache: &Cache<String, Arc<ActionState<States>>>,
+  id: &String,
+) -> anyhow::Result<JsonValue> {
+  let state = cache.get(id).await.unwrap_or_default().get()?;
+  serde_json::to_value(state)
+    .context("Failed to serialize resource action state")
+}
@@ -9,15 +9,21 @@ use komodo_client::{
 };
 
 use crate::{
-  helpers::periphery_client, permission::get_check_permissions,
+  helpers::{
+    periphery_client,
+    terminal::{check_terminal_access, terminal_read_only},
+  },
+  permission::get_check_permissions,
   ws::core_periphery_forward_ws,
 };
 
 #[instrument(name = "ConnectTerminal", skip(ws))]
 pub async fn handler(
-  Query(ConnectTerminalQuery { server, terminal }): Query<
-    ConnectTerminalQuery,
-  >,
+  Query(ConnectTerminalQuery {
+    server,
+    terminal,
+    read_only,
+  }): Query<ConnectTerminalQuery>,
   ws: WebSocketUpgrade,
 ) -> impl IntoResponse {
   ws.on_upgrade(|socket| async move {
@@ -45,7 +51,34 @@ pub async fn handler(
       }
     };
 
-    let periphery = match periphery_client(&server) {
+    let read_only = match terminal_read_only::<Server>(
+      &user, &server.id, read_only,
+    )
+    .await
+    {
+      Ok(read_only) => read_only,
+      Err(e) => {
+        debug!("could not get user permission on server | {e:#}");
+        let _ = client_socket
+          .send(Message::text(format!("ERROR: {e:#}")))
+          .await;
+        let _ = client_socket.close().await;
+        return;
+      }
+    };
+
+    if let Err(e) =
+      check_terminal_access(&server, &user, &terminal).await
+    {
+      debug!("user not allowed to access terminal | {e:#}");
+      let _ = client_socket
+        .send(Message::text(format!("ERROR: {e:#}")))
+        .await;
+      let _ = client_socket.close().await;
+      return;
+    }
+
+    let periphery = match periphery_client(&server).await {
       Ok(periphery) => periphery,
       Err(e) => {
         debug!("couldn't get periphery | {e:#}");
@@ -59,18 +92,20 @@ pub async fn handler(
 
     trace!("connecting to periphery terminal websocket");
 
-    let periphery_socket =
-      match periphery.connect_terminal(terminal).await {
-        Ok(ws) => ws,
-        Err(e) => {
-          debug!("Failed connect to periphery terminal | {e:#}");
-          let _ = client_socket
-            .send(Message::text(format!("ERROR: {e:#}")))
-            .await;
-          let _ = client_socket.close().await;
-          return;
-        }
-      };
+    let periphery_socket = match periphery
+      .connect_terminal(terminal, user.username.clone(), read_only)
+      .await
+    {
+      Ok(ws) => ws,
+      Err(e) => {
+        debug!("Failed connect to periphery terminal | {e:#}");
+        let _ = client_socket
+          .send(Message::text(format!("ERROR: {e:#}")))
+          .await;
+        let _ = client_socket.close().await;
+        return;
+      }
+    };
 
     trace!("connected to periphery terminal websocket");
 
@@ -54,8 +54,11 @@ pub async fn handler(ws: WebSocketUpgrade) -> impl IntoResponse {
           Ok(user) => user,
         };
 
-        // Only send if user has permission on the target resource.
-        if user_can_see_update(&user, &update.target).await.is_ok() {
+        // Only send if user has permission on the target resource,
+        // or started the update themselves.
+        if update.operator == user.id
+          || user_can_see_update(&user, &update.target).await.is_ok()
+        {
           let _ = ws_sender
             .send(Message::text(serde_json::to_string(&update).unwrap()))
             .await;
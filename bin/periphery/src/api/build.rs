@@ -5,30 +5,41 @@ use std::{
 
 use anyhow::{Context, anyhow};
 use command::{
-  run_komodo_command, run_komodo_command_with_sanitization,
+  run_komodo_command, run_komodo_command_cancellable,
+  run_komodo_command_with_sanitization,
 };
 use formatting::format_serror;
 use interpolate::Interpolator;
 use komodo_client::entities::{
   EnvironmentVar, all_logs_success,
-  build::{Build, BuildConfig},
+  build::{Build, BuildConfig, MANIFEST_DIGEST_LOG_STAGE},
+  deployment::conversions_from_str,
   environment_vars_from_str, optional_string,
+  server::ContainerRuntime,
   to_path_compatible_name,
   update::Log,
 };
 use periphery_client::api::build::{
-  self, GetDockerfileContentsOnHost,
-  GetDockerfileContentsOnHostResponse, PruneBuilders, PruneBuildx,
-  WriteDockerfileContentsToHost,
+  self, BuildStep, GetDockerfileContentsOnHost,
+  GetDockerfileContentsOnHostResponse, GetJunitReport,
+  GetJunitReportResponse, PruneBuilders, PruneBuildx, ScanImage,
+  ScanImageResponse, WriteDockerfileContentsToHost,
 };
 use resolver_api::Resolve;
 use tokio::fs;
 
 use crate::{
-  build::{parse_build_args, parse_secret_args, write_dockerfile},
+  build::{
+    MULTI_PLATFORM_BUILDER, build_cache_log, build_cancel,
+    kill_build, parse_build_args, parse_cache_args, parse_platforms,
+    parse_secret_args, parse_trivy_report, prune_cache_volumes,
+    read_manifest_digest, scan_image_command, setup_multi_platform,
+    write_cache_volumes_dockerfile, write_dockerfile,
+  },
   config::periphery_config,
-  docker::docker_login,
+  docker::{container_cli, container_runtime, docker_login},
   helpers::{parse_extra_args, parse_labels},
+  hooks::prune_with_hooks,
 };
 
 impl Resolve<super::Args> for GetDockerfileContentsOnHost {
@@ -72,6 +83,43 @@ impl Resolve<super::Args> for GetDockerfileContentsOnHost {
   }
 }
 
+impl Resolve<super::Args> for GetJunitReport {
+  #[instrument(name = "GetJunitReport", level = "debug")]
+  async fn resolve(
+    self,
+    _: &super::Args,
+  ) -> serror::Result<GetJunitReportResponse> {
+    let GetJunitReport {
+      path,
+      name,
+      linked_repo,
+    } = self;
+
+    let root = if linked_repo {
+      periphery_config().repo_dir()
+    } else {
+      periphery_config().build_dir()
+    };
+    // Absolute paths will replace the root on join.
+    let full_path = root
+      .join(to_path_compatible_name(&name))
+      .join(&path)
+      .components()
+      .collect::<PathBuf>();
+
+    if !full_path.is_file() {
+      return Ok(None);
+    }
+
+    let contents =
+      fs::read_to_string(&full_path).await.with_context(|| {
+        format!("Failed to read JUnit report at {full_path:?}")
+      })?;
+
+    Ok(Some(contents))
+  }
+}
+
 impl Resolve<super::Args> for WriteDockerfileContentsToHost {
   #[instrument(
     name = "WriteDockerfileContentsToHost",
@@ -127,6 +175,7 @@ impl Resolve<super::Args> for build::Build {
       mut replacers,
       commit_hash,
       additional_tags,
+      step,
     } = self;
 
     let mut logs = Vec::new();
@@ -156,6 +205,12 @@ impl Resolve<super::Args> for build::Build {
           files_on_host,
           dockerfile,
           pre_build,
+          junit_report,
+          cache_volumes,
+          cache_volumes_max_size,
+          cache_from,
+          cache_to,
+          platforms,
           ..
         },
       ..
@@ -176,10 +231,22 @@ impl Resolve<super::Args> for build::Build {
       })
       .collect::<HashMap<_, _>>();
 
-    // Maybe docker login
-    let mut should_push = false;
+    // The shared steps run once before the matrix variants.
+    let (prepare, variant) = match &step {
+      BuildStep::All | BuildStep::Prepare => (true, None),
+      BuildStep::Variant(variant) => {
+        (false, Some(to_path_compatible_name(variant)))
+      }
+    };
+
+    // Maybe docker login. Matrix variants were logged in by Prepare.
+    let mut should_push = !prepare
+      && image_registry
+        .iter()
+        .any(|r| !r.domain.is_empty() && !r.account.is_empty());
     for (domain, account) in image_registry
       .iter()
+      .filter(|_| prepare)
       .map(|r| (r.domain.as_str(), r.account.as_str()))
       // This ensures uniqueness / prevents redundant logins
       .collect::<HashSet<_>>()
@@ -223,7 +290,8 @@ impl Resolve<super::Args> for build::Build {
       .unwrap_or("Dockerfile".to_owned());
 
     // Write UI defined Dockerfile to host
-    if !*files_on_host
+    if prepare
+      && !*files_on_host
       && repo.is_empty()
       && linked_repo.is_none()
       && !dockerfile.is_empty()
@@ -240,8 +308,13 @@ impl Resolve<super::Args> for build::Build {
       }
     };
 
+    // Remove any report left from a previous build
+    if prepare && !junit_report.is_empty() {
+      let _ = fs::remove_file(build_path.join(junit_report)).await;
+    }
+
     // Pre Build
-    if !pre_build.is_none() {
+    if prepare && !pre_build.is_none() {
       let pre_build_path = build_path.join(&pre_build.path);
       if let Some(log) = run_komodo_command_with_sanitization(
         "Pre Build",
@@ -260,6 +333,58 @@ impl Resolve<super::Args> for build::Build {
       }
     }
 
+    let multi_platform = !platforms.is_empty();
+
+    if let BuildStep::Prepare = step {
+      // Set up the multi-platform builder once for all the variants.
+      if multi_platform {
+        setup_multi_platform(&parse_platforms(platforms)?, &mut logs)
+          .await;
+      }
+      return Ok(logs);
+    }
+
+    // Mount the cache volumes into the RUN instructions
+    let cache_volumes = conversions_from_str(cache_volumes)
+      .context("Invalid cache_volumes")?;
+    let cache_dockerfile = if cache_volumes.is_empty() {
+      None
+    } else {
+      match write_cache_volumes_dockerfile(
+        &build_path,
+        &dockerfile_path,
+        &cache_volumes,
+        variant.as_deref(),
+      )
+      .await
+      {
+        Ok(cache_dockerfile) => {
+          logs.push(Log::simple(
+            "Cache Volumes",
+            format!(
+              "Building with cache volumes mounted using {}",
+              cache_dockerfile.path
+            ),
+          ));
+          Some(cache_dockerfile)
+        }
+        Err(e) => {
+          logs.push(Log::error(
+            "Cache Volumes",
+            format_serror(
+              &e.context("Failed to add cache volumes to dockerfile")
+                .into(),
+            ),
+          ));
+          return Ok(logs);
+        }
+      }
+    };
+    let dockerfile_path = cache_dockerfile
+      .as_ref()
+      .map(|cache_dockerfile| cache_dockerfile.path.as_str())
+      .unwrap_or(dockerfile_path.as_str());
+
     // Get command parts
 
     // Add VERSION to build args (if not already there)
@@ -284,7 +409,56 @@ impl Resolve<super::Args> for build::Build {
 
     let extra_args = parse_extra_args(extra_args);
 
-    let buildx = if *use_buildx { " buildx" } else { "" };
+    // Remote caches and multi-platform builds require buildx
+    let use_remote_cache =
+      !cache_from.is_empty() || !cache_to.is_empty();
+    let buildx = if *use_buildx || use_remote_cache || multi_platform
+    {
+      " buildx"
+    } else {
+      ""
+    };
+    let cache_args = parse_cache_args(cache_from, cache_to);
+
+    // The manifest digest is read from the metadata file after the build.
+    let metadata_path = periphery_config().build_dir().join(format!(
+      ".{}{}.metadata.json",
+      to_path_compatible_name(name),
+      variant
+        .as_ref()
+        .map(|v| format!("-{v}"))
+        .unwrap_or_default()
+    ));
+    let platform_args = if multi_platform {
+      let platforms = parse_platforms(platforms)?;
+      if prepare {
+        setup_multi_platform(&platforms, &mut logs).await;
+        if !all_logs_success(&logs) {
+          return Ok(logs);
+        }
+      }
+      if !should_push {
+        logs.push(Log::simple(
+          "Multi-Platform",
+          String::from(
+            "No image registry is configured, the multi-platform image stays in the build cache.",
+          ),
+        ));
+      }
+      let _ = fs::remove_file(&metadata_path).await;
+      // Respect a builder passed in extra args
+      let builder = if extra_args.contains("--builder") {
+        String::new()
+      } else {
+        format!(" --builder {MULTI_PLATFORM_BUILDER}")
+      };
+      format!(
+        "{builder} --platform {platforms} --metadata-file {}",
+        metadata_path.display()
+      )
+    } else {
+      String::new()
+    };
 
     let image_tags = build
       .get_image_tags_as_arg(commit_hash.as_deref(), &additional_tags)
@@ -294,20 +468,48 @@ impl Resolve<super::Args> for build::Build {
 
     // Construct command
     let command = format!(
-      "docker{buildx} build{build_args}{command_secret_args}{extra_args}{labels}{image_tags}{maybe_push} -f {dockerfile_path} .",
+      "{}{buildx} build{platform_args}{build_args}{command_secret_args}{cache_args}{extra_args}{labels}{image_tags}{maybe_push} -f {dockerfile_path} .",
+      container_cli()
     );
 
-    if let Some(build_log) = run_komodo_command_with_sanitization(
+    // Killed with KillBuild
+    let cancel = build_cancel(name);
+    let build_log = run_komodo_command_cancellable(
       "Docker Build",
-      build_path.as_ref(),
+      &build_path,
       command,
-      false,
       &replacers,
+      cancel.cancelled(),
     )
-    .await
-    {
-      logs.push(build_log);
-    };
+    .await;
+    let cache_log = (use_remote_cache && build_log.success)
+      .then(|| build_cache_log(&build_log));
+    let success = build_log.success;
+    logs.push(build_log);
+    logs.extend(cache_log);
+    if multi_platform && success && should_push {
+      match read_manifest_digest(&metadata_path).await {
+        Ok(digest) => {
+          logs.push(Log::simple(MANIFEST_DIGEST_LOG_STAGE, digest))
+        }
+        // The images are already pushed, don't fail the build.
+        Err(e) => logs.push(Log::simple(
+          "Multi-Platform",
+          format!("WARNING: Failed to read manifest digest | {e:#}"),
+        )),
+      }
+    }
+
+    if multi_platform {
+      let _ = fs::remove_file(&metadata_path).await;
+    }
+
+    if cache_dockerfile.is_some() {
+      drop(cache_dockerfile);
+      if !cache_volumes_max_size.is_empty() {
+        logs.push(prune_cache_volumes(cache_volumes_max_size).await);
+      }
+    }
 
     Ok(logs)
   }
@@ -315,11 +517,85 @@ impl Resolve<super::Args> for build::Build {
 
 //
 
+impl Resolve<super::Args> for build::KillBuild {
+  #[instrument(name = "KillBuild", skip_all, fields(build = self.name.to_string()))]
+  async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
+    let log = if kill_build(&self.name) {
+      Log::simple(
+        "Kill Build",
+        format!("Killed the running docker build of {}", self.name),
+      )
+    } else {
+      Log::simple(
+        "Kill Build",
+        format!("No docker build of {} is running", self.name),
+      )
+    };
+    Ok(log)
+  }
+}
+
+impl Resolve<super::Args> for ScanImage {
+  #[instrument(name = "ScanImage", skip_all, fields(image = self.image))]
+  async fn resolve(
+    self,
+    _: &super::Args,
+  ) -> serror::Result<ScanImageResponse> {
+    let mut log = run_komodo_command(
+      "Image Scan",
+      None,
+      scan_image_command(&self.image),
+    )
+    .await;
+    if !log.success {
+      return Ok(ScanImageResponse { log, scan: None });
+    }
+    let scan = match parse_trivy_report(self.image, &log.stdout) {
+      Ok(scan) => scan,
+      Err(e) => {
+        log.success = false;
+        log.stderr = format_serror(&e.into());
+        return Ok(ScanImageResponse { log, scan: None });
+      }
+    };
+    // Replace the full JSON report with a summary.
+    log.stdout = format!(
+      "Scanned {}\nCritical: {} | High: {} | Medium: {} | Low: {} | Unknown: {}",
+      scan.image,
+      scan.critical,
+      scan.high,
+      scan.medium,
+      scan.low,
+      scan.unknown
+    );
+    for vulnerability in &scan.vulnerabilities {
+      log.stdout.push_str(&format!(
+        "\n{} {} | {} {} -> {}",
+        vulnerability.severity,
+        vulnerability.id,
+        vulnerability.package,
+        vulnerability.installed_version,
+        if vulnerability.fixed_version.is_empty() {
+          "no fix"
+        } else {
+          &vulnerability.fixed_version
+        }
+      ));
+    }
+    Ok(ScanImageResponse {
+      log,
+      scan: Some(scan),
+    })
+  }
+}
+
+//
+
 impl Resolve<super::Args> for PruneBuilders {
   #[instrument(name = "PruneBuilders", skip_all)]
   async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
-    let command = String::from("docker builder prune -a -f");
-    Ok(run_komodo_command("Prune Builders", None, command).await)
+    let command = prune_build_cache_command("builder");
+    Ok(prune_with_hooks("builders", "Prune Builders", command).await)
   }
 }
 
@@ -328,7 +604,20 @@ impl Resolve<super::Args> for PruneBuilders {
 impl Resolve<super::Args> for PruneBuildx {
   #[instrument(name = "PruneBuildx", skip_all)]
   async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
-    let command = String::from("docker buildx prune -a -f");
-    Ok(run_komodo_command("Prune Buildx", None, command).await)
+    let command = prune_build_cache_command("buildx");
+    Ok(prune_with_hooks("buildx", "Prune Buildx", command).await)
+  }
+}
+
+/// Podman has no separate builder,
+/// the build cache is pruned along with images.
+fn prune_build_cache_command(builder: &str) -> String {
+  match container_runtime() {
+    ContainerRuntime::Docker => {
+      format!("docker {builder} prune -a -f")
+    }
+    ContainerRuntime::Podman => {
+      String::from("podman image prune --build-cache -f")
+    }
   }
 }
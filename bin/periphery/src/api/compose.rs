@@ -7,9 +7,12 @@ use git::write_commit_file;
 use interpolate::Interpolator;
 use komodo_client::entities::{
   FileContents, RepoExecutionResponse, all_logs_success,
+  config::periphery::PeripheryHookEvent,
+  komodo_timestamp,
   stack::{
     ComposeFile, ComposeProject, ComposeService,
-    ComposeServiceDeploy, StackRemoteFileContents, StackServiceNames,
+    ComposeServiceDeploy, StackRemoteFileContents, StackServiceDrift,
+    StackServiceNames,
   },
   to_path_compatible_name,
   update::Log,
@@ -17,18 +20,33 @@ use komodo_client::entities::{
 use periphery_client::api::compose::*;
 use resolver_api::Resolve;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
 use shell_escape::unix::escape;
-use std::{borrow::Cow, path::PathBuf};
+use std::{
+  borrow::Cow,
+  path::{Path, PathBuf},
+  time::{Duration, Instant},
+};
 use tokio::fs;
+use uuid::Uuid;
 
 use crate::{
   compose::{
-    docker_compose, env_file_args, pull_or_clone_stack,
-    up::{maybe_login_registry, validate_files},
-    write::write_stack,
+    docker_compose,
+    drift::get_compose_runtime_drift,
+    env_file_args, pull_or_clone_stack,
+    up::{
+      check_project_ownership, maybe_login_registry, validate_files,
+    },
+    write::{
+      write_dns_override, write_include_files, write_stack,
+      write_stack_to_render_dir,
+    },
   },
   config::periphery_config,
   helpers::{log_grep, parse_extra_args},
+  hooks::run_hooks,
 };
 
 impl Resolve<super::Args> for ListComposeProjects {
@@ -320,7 +338,9 @@ impl Resolve<super::Args> for ComposePull {
       services,
       git_token,
       registry_token,
+      oci_token,
       mut replacers,
+      include_files,
     } = self;
 
     let mut res = ComposePullResponse::default();
@@ -338,6 +358,7 @@ impl Resolve<super::Args> for ComposePull {
       &stack,
       repo.as_ref(),
       git_token,
+      oci_token,
       replacers.clone(),
       &mut res,
     )
@@ -357,6 +378,15 @@ impl Resolve<super::Args> for ComposePull {
       "Failed to validate run directory on host after stack write (canonicalize error)",
     )?;
 
+    if let Err(e) =
+      write_include_files(&run_directory, &include_files).await
+    {
+      res
+        .logs
+        .push(Log::error("Write Includes", format_serror(&e.into())));
+      return Ok(res);
+    }
+
     let file_paths = stack
       .all_file_paths()
       .into_iter()
@@ -434,7 +464,10 @@ impl Resolve<super::Args> for ComposeUp {
       services,
       git_token,
       registry_token,
+      oci_token,
       mut replacers,
+      include_files,
+      expected_compose_hash,
     } = self;
 
     let mut res = ComposeUpResponse::default();
@@ -452,6 +485,7 @@ impl Resolve<super::Args> for ComposeUp {
       &stack,
       repo.as_ref(),
       git_token,
+      oci_token,
       replacers.clone(),
       &mut res,
     )
@@ -471,6 +505,15 @@ impl Resolve<super::Args> for ComposeUp {
       "Failed to validate run directory on host after stack write (canonicalize error)",
     )?;
 
+    if let Err(e) =
+      write_include_files(&run_directory, &include_files).await
+    {
+      res
+        .logs
+        .push(Log::error("Write Includes", format_serror(&e.into())));
+      return Ok(res);
+    }
+
     validate_files(&stack, &run_directory, &mut res).await;
     if !all_logs_success(&res.logs) {
       return Ok(res);
@@ -481,6 +524,20 @@ impl Resolve<super::Args> for ComposeUp {
       return Ok(res);
     }
 
+    let hook_fields = json!({
+      "stack": stack.name,
+      "project": stack.project_name(true),
+      "run_directory": run_directory,
+      "services": services,
+    });
+    res.logs.extend(
+      run_hooks(PeripheryHookEvent::PreDeploy, hook_fields.clone())
+        .await,
+    );
+    if !all_logs_success(&res.logs) {
+      return Ok(res);
+    }
+
     // Pre deploy
     if !stack.config.pre_deploy.is_none() {
       let pre_deploy_path =
@@ -509,18 +566,27 @@ impl Resolve<super::Args> for ComposeUp {
       format!(" {}", services.join(" "))
     };
 
-    let file_args = stack.compose_file_paths().join(" -f ");
+    let mut file_args = stack.compose_file_paths().join(" -f ");
 
     // This will be the last project name, which is the one that needs to be destroyed.
     // Might be different from the current project name, if user renames stack / changes to custom project name.
     let last_project_name = stack.project_name(false);
     let project_name = stack.project_name(true);
 
+    check_project_ownership(
+      &project_name,
+      &run_directory,
+      &mut res.logs,
+    )
+    .await;
+
     let env_file_args = env_file_args(
       env_file_path,
       &stack.config.additional_env_files,
     )?;
 
+    let mut service_names = Vec::new();
+
     // Uses 'docker compose config' command to extract services (including image)
     // after performing interpolation
     {
@@ -544,11 +610,27 @@ impl Resolve<super::Args> for ComposeUp {
         res.logs.push(config_log);
         return Ok(res);
       }
+      if let Some(expected) = &expected_compose_hash {
+        let hash =
+          hex::encode(Sha256::digest(config_log.stdout.as_bytes()));
+        if &hash != expected {
+          res.logs.push(config_log);
+          res.logs.push(Log::error(
+            "Rerun",
+            String::from(
+              "The rendered compose config differs from the Update being rerun, stopping run.",
+            ),
+          ));
+          return Ok(res);
+        }
+      }
       let compose =
         serde_yaml_ng::from_str::<ComposeFile>(&config_log.stdout)
           .context("Failed to parse compose contents")?;
       // Record sanitized compose config output
       res.compose_config = Some(config_log.stdout);
+      service_names = compose.services.keys().cloned().collect();
+      service_names.sort();
       for (
         service_name,
         ComposeService {
@@ -586,6 +668,28 @@ impl Resolve<super::Args> for ComposeUp {
       }
     }
 
+    if !stack.config.dns.is_empty()
+      || !stack.config.extra_hosts.is_empty()
+    {
+      match write_dns_override(
+        &run_directory,
+        &service_names,
+        &stack.config.dns,
+        &stack.config.extra_hosts,
+      )
+      .await
+      {
+        Ok(path) => file_args.push_str(&format!(" -f {path}")),
+        Err(e) => {
+          res.logs.push(Log::error(
+            "Write DNS Override",
+            format_serror(&e.into()),
+          ));
+          return Ok(res);
+        }
+      }
+    }
+
     if stack.config.run_build {
       let build_extra_args =
         parse_extra_args(&stack.config.build_extra_args);
@@ -641,8 +745,17 @@ impl Resolve<super::Args> for ComposeUp {
 
     // Run compose up
     let extra_args = parse_extra_args(&stack.config.extra_args);
+    let scale_args = stack
+      .config
+      .replicas
+      .iter()
+      .filter(|r| {
+        services.is_empty() || services.contains(&r.service)
+      })
+      .map(|r| format!(" --scale {}={}", r.service, r.replicas))
+      .collect::<String>();
     let command = format!(
-      "{docker_compose} -p {project_name} -f {file_args}{env_file_args} up -d{extra_args}{service_args}",
+      "{docker_compose} -p {project_name} -f {file_args}{env_file_args} up -d{extra_args}{scale_args}{service_args}",
     );
 
     let Some(log) = run_komodo_command_with_sanitization(
@@ -676,12 +789,203 @@ impl Resolve<super::Args> for ComposeUp {
       };
     }
 
+    let mut hook_fields = hook_fields;
+    hook_fields["success"] = res.deployed.into();
+    res.logs.extend(
+      run_hooks(PeripheryHookEvent::PostDeploy, hook_fields).await,
+    );
+
     Ok(res)
   }
 }
 
 //
 
+impl Resolve<super::Args> for ComposeRender {
+  #[instrument(
+    name = "ComposeRender",
+    level = "debug",
+    skip_all,
+    fields(
+      stack = &self.stack.name,
+      services = format!("{:?}", self.services),
+    )
+  )]
+  async fn resolve(
+    self,
+    _: &super::Args,
+  ) -> serror::Result<ComposeRenderResponse> {
+    // Rendered in a temporary directory, so the Stack's run directory
+    // is left as it was deployed for the next execution.
+    let render_dir = periphery_config()
+      .root_directory
+      .join(".render")
+      .join(Uuid::new_v4().to_string());
+    let res = render_compose(self, &render_dir).await;
+    if let Err(e) = fs::remove_dir_all(&render_dir).await
+      && e.kind() != std::io::ErrorKind::NotFound
+    {
+      warn!(
+        "Failed to remove render directory {render_dir:?} | {e:#}"
+      );
+    }
+    res
+  }
+}
+
+async fn render_compose(
+  req: ComposeRender,
+  render_dir: &Path,
+) -> serror::Result<ComposeRenderResponse> {
+  let ComposeRender {
+    mut stack,
+    services,
+    repo,
+    git_token,
+    oci_token,
+    mut replacers,
+    include_files,
+  } = req;
+
+  let mut res = ComposeRenderResponse::default();
+
+  let mut interpolator =
+    Interpolator::new(None, &periphery_config().secrets);
+  interpolator
+    .interpolate_stack(&mut stack)?
+    .push_logs(&mut res.logs);
+  replacers.extend(interpolator.secret_replacers);
+
+  fs::create_dir_all(render_dir).await.with_context(|| {
+    format!("Failed to create render directory at {render_dir:?}")
+  })?;
+  // Files on host are read in place, so the env file and
+  // compose overrides are written to the render directory instead.
+  if stack.config.files_on_host {
+    if !stack.config.env_vars()?.is_empty() {
+      stack.config.env_file_path =
+        render_dir.join(".env").display().to_string();
+    }
+    for path in &mut stack.config.file_paths {
+      if include_files.iter().any(|file| &file.path == path) {
+        *path = render_dir.join(&*path).display().to_string();
+      }
+    }
+  }
+
+  let (run_directory, env_file_path) =
+    match write_stack_to_render_dir(
+      &stack,
+      repo.as_ref(),
+      git_token,
+      oci_token,
+      replacers.clone(),
+      &mut res,
+      render_dir,
+    )
+    .await
+    {
+      Ok(res) => res,
+      Err(e) => {
+        res
+          .logs
+          .push(Log::error("Write Stack", format_serror(&e.into())));
+        return Ok(res);
+      }
+    };
+
+  let run_directory = run_directory.canonicalize().context(
+      "Failed to validate run directory on host after stack write (canonicalize error)",
+    )?;
+
+  let include_directory = if stack.config.files_on_host {
+    render_dir
+  } else {
+    run_directory.as_path()
+  };
+  if let Err(e) =
+    write_include_files(include_directory, &include_files).await
+  {
+    res
+      .logs
+      .push(Log::error("Write Includes", format_serror(&e.into())));
+    return Ok(res);
+  }
+
+  let docker_compose = docker_compose();
+
+  let service_args = if services.is_empty() {
+    String::new()
+  } else {
+    format!(" {}", services.join(" "))
+  };
+
+  let file_args = stack.compose_file_paths().join(" -f ");
+  let project_name = stack.project_name(true);
+
+  let env_file_args =
+    env_file_args(env_file_path, &stack.config.additional_env_files)?;
+
+  // The same as the deploy command, so secrets must be sanitized.
+  let extra_args = parse_extra_args(&stack.config.extra_args);
+  let scale_args = stack
+    .config
+    .replicas
+    .iter()
+    .filter(|r| services.is_empty() || services.contains(&r.service))
+    .map(|r| format!(" --scale {}={}", r.service, r.replicas))
+    .collect::<String>();
+  res.command = svi::replace_in_string(
+    &format!(
+      "{docker_compose} -p {project_name} -f {file_args}{env_file_args} up -d{extra_args}{scale_args}{service_args}",
+    ),
+    &replacers,
+  );
+
+  let Some(config_log) = run_komodo_command_with_sanitization(
+      "Compose Config",
+      run_directory.as_path(),
+      format!(
+        "{docker_compose} -p {project_name} -f {file_args}{env_file_args} config{service_args}",
+      ),
+      false,
+      &replacers,
+    )
+    .await
+    else {
+      // Only reachable if command is empty,
+      // not the case since it is provided above.
+      unreachable!()
+    };
+  if config_log.success {
+    res.compose_config = Some(config_log.stdout.clone());
+  }
+  res.logs.push(config_log);
+
+  Ok(res)
+}
+
+//
+
+impl Resolve<super::Args> for GetComposeRuntimeDrift {
+  #[instrument(
+    name = "GetComposeRuntimeDrift",
+    level = "debug",
+    skip(self),
+    fields(project = &self.project)
+  )]
+  async fn resolve(
+    self,
+    _: &super::Args,
+  ) -> serror::Result<Vec<StackServiceDrift>> {
+    get_compose_runtime_drift(&self.project, &self.compose_config)
+      .await
+      .map_err(Into::into)
+  }
+}
+
+//
+
 impl Resolve<super::Args> for ComposeExecution {
   #[instrument(name = "ComposeExecution")]
   async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
@@ -699,6 +1003,224 @@ impl Resolve<super::Args> for ComposeExecution {
 
 //
 
+/// A container from `docker compose ps --format json`.
+/// Only the fields used by Komodo are parsed.
+#[derive(Deserialize)]
+struct DockerComposePsItem {
+  #[serde(default, alias = "Name")]
+  name: String,
+  #[serde(default, alias = "Service")]
+  service: String,
+  #[serde(default, alias = "State")]
+  state: String,
+  /// Empty if the container has no healthcheck.
+  #[serde(default, alias = "Health")]
+  health: String,
+}
+
+impl Resolve<super::Args> for ComposeWaitHealthy {
+  #[instrument(name = "ComposeWaitHealthy", level = "debug")]
+  async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
+    let ComposeWaitHealthy {
+      project,
+      ignore_services,
+      timeout,
+    } = self;
+    let docker_compose = docker_compose();
+    let command =
+      format!("{docker_compose} -p {project} ps --all --format json");
+    let start_ts = komodo_timestamp();
+    let deadline =
+      Instant::now() + Duration::from_secs(timeout.max(0) as u64);
+
+    loop {
+      let log =
+        run_komodo_command("Wait Healthy", None, &command).await;
+      if !log.success {
+        return Ok(log);
+      }
+      let containers = parse_compose_ps(&log.stdout)?
+        .into_iter()
+        .filter(|c| !ignore_services.contains(&c.service))
+        .collect::<Vec<_>>();
+
+      let failed = containers
+        .iter()
+        .filter(|c| {
+          c.health == "unhealthy"
+            || matches!(c.state.as_str(), "exited" | "dead")
+        })
+        .map(|c| format!("{} ({} {})", c.name, c.state, c.health))
+        .collect::<Vec<_>>();
+      if !failed.is_empty() {
+        let mut log = Log::error(
+          "Wait Healthy",
+          format!("Containers failed: {}", failed.join(", ")),
+        );
+        log.command = command;
+        log.start_ts = start_ts;
+        return Ok(log);
+      }
+
+      let pending = containers
+        .iter()
+        .filter(|c| {
+          c.state != "running"
+            || !matches!(c.health.as_str(), "" | "healthy")
+        })
+        .map(|c| c.name.as_str())
+        .collect::<Vec<_>>();
+      if !containers.is_empty() && pending.is_empty() {
+        let mut log = Log::simple(
+          "Wait Healthy",
+          format!("All {} containers are healthy", containers.len()),
+        );
+        log.command = command;
+        log.start_ts = start_ts;
+        return Ok(log);
+      }
+
+      if Instant::now() >= deadline {
+        let mut log = Log::error(
+          "Wait Healthy",
+          format!(
+            "Timed out after {timeout}s waiting on: {}",
+            pending.join(", ")
+          ),
+        );
+        log.command = command;
+        log.start_ts = start_ts;
+        return Ok(log);
+      }
+
+      tokio::time::sleep(Duration::from_secs(2)).await;
+    }
+  }
+}
+
+/// Newer compose versions output one JSON object per line,
+/// older ones output a JSON array.
+fn parse_compose_ps(
+  stdout: &str,
+) -> anyhow::Result<Vec<DockerComposePsItem>> {
+  let stdout = stdout.trim();
+  if stdout.starts_with('[') {
+    return serde_json::from_str(stdout)
+      .context("Failed to parse compose ps output");
+  }
+  stdout
+    .lines()
+    .filter(|line| !line.trim().is_empty())
+    .map(|line| {
+      serde_json::from_str(line)
+        .context("Failed to parse compose ps output")
+    })
+    .collect()
+}
+
+//
+
+impl Resolve<super::Args> for ComposeSwapNetworkAlias {
+  #[instrument(name = "ComposeSwapNetworkAlias")]
+  async fn resolve(
+    self,
+    _: &super::Args,
+  ) -> serror::Result<Vec<Log>> {
+    let ComposeSwapNetworkAlias {
+      project,
+      previous_project,
+      service,
+      network,
+      alias,
+    } = self;
+    let network = escape(Cow::Borrowed(&network)).into_owned();
+    let alias = escape(Cow::Borrowed(&alias)).into_owned();
+    let mut logs = Vec::new();
+
+    let containers = match service_container_ids(
+      &project, &service, &mut logs,
+    )
+    .await
+    {
+      Some(containers) if !containers.is_empty() => containers,
+      Some(_) => {
+        logs.push(Log::error(
+            "Connect Alias",
+            format!(
+              "No containers found for service '{service}' in project '{project}'"
+            ),
+          ));
+        return Ok(logs);
+      }
+      None => return Ok(logs),
+    };
+    let command = containers
+      .iter()
+      .map(|id| {
+        format!(
+          "docker network connect --alias {alias} {network} {id}"
+        )
+      })
+      .collect::<Vec<_>>()
+      .join(" && ");
+    let log =
+      run_komodo_command("Connect Alias", None, command).await;
+    let success = log.success;
+    logs.push(log);
+    if !success {
+      return Ok(logs);
+    }
+
+    let Some(previous_project) = previous_project else {
+      return Ok(logs);
+    };
+    let Some(previous) =
+      service_container_ids(&previous_project, &service, &mut logs)
+        .await
+    else {
+      return Ok(logs);
+    };
+    if previous.is_empty() {
+      return Ok(logs);
+    }
+    let command = previous
+      .iter()
+      .map(|id| format!("docker network disconnect {network} {id}"))
+      .collect::<Vec<_>>()
+      .join(" && ");
+    logs.push(
+      run_komodo_command("Disconnect Alias", None, command).await,
+    );
+
+    Ok(logs)
+  }
+}
+
+/// Returns None if the lookup fails, after pushing the error log.
+async fn service_container_ids(
+  project: &str,
+  service: &str,
+  logs: &mut Vec<Log>,
+) -> Option<Vec<String>> {
+  let project = escape(Cow::Borrowed(project));
+  let service = escape(Cow::Borrowed(service));
+  let log = run_komodo_command(
+    "List Containers",
+    None,
+    format!(
+      "docker ps -q --filter label=com.docker.compose.project={project} --filter label=com.docker.compose.service={service}"
+    ),
+  )
+  .await;
+  if !log.success {
+    logs.push(log);
+    return None;
+  }
+  Some(log.stdout.split_whitespace().map(str::to_string).collect())
+}
+
+//
+
 impl Resolve<super::Args> for ComposeRun {
   #[instrument(name = "ComposeRun", level = "debug", skip_all, fields(stack = &self.stack.name, service = &self.service))]
   async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
@@ -707,6 +1229,7 @@ impl Resolve<super::Args> for ComposeRun {
       repo,
       git_token,
       registry_token,
+      oci_token,
       mut replacers,
       service,
       command,
@@ -733,6 +1256,7 @@ impl Resolve<super::Args> for ComposeRun {
       &stack,
       repo.as_ref(),
       git_token,
+      oci_token,
       replacers.clone(),
       &mut res,
     )
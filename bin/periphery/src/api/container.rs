@@ -1,6 +1,10 @@
-use anyhow::Context;
+use std::{pin::Pin, process::Stdio, task::Poll};
+
+use anyhow::{Context, anyhow};
+use axum::http::{HeaderMap, StatusCode};
+use bytes::BytesMut;
 use command::run_komodo_command;
-use futures::future::join_all;
+use futures::{Stream, TryStreamExt, future::join_all};
 use komodo_client::entities::{
   docker::{
     container::{Container, ContainerListItem, ContainerStats},
@@ -9,13 +13,21 @@ use komodo_client::entities::{
   update::Log,
 };
 use periphery_client::api::container::*;
+use pin_project_lite::pin_project;
 use resolver_api::Resolve;
+use serror::{AddStatusCodeError, Json};
+use tokio_util::codec::{
+  Decoder, FramedRead, LinesCodec, LinesCodecError,
+};
 
 use crate::{
+  api::router::audit_context,
   docker::{
-    docker_client, stats::get_container_stats, stop_container_command,
+    container_cli, docker_client, stats::get_container_stats,
+    stop_container_command,
   },
   helpers::log_grep,
+  hooks::prune_with_hooks,
 };
 
 // ======
@@ -49,14 +61,157 @@ impl Resolve<super::Args> for GetContainerLog {
     } else {
       Default::default()
     };
-    let command =
-      format!("docker logs {name} --tail {tail}{timestamps}");
+    let command = format!(
+      "{} logs {name} --tail {tail}{timestamps}",
+      container_cli()
+    );
     Ok(run_komodo_command("Get container log", None, command).await)
   }
 }
 
 //
 
+pub async fn follow_container_log(
+  headers: HeaderMap,
+  Json(FollowContainerLogBody {
+    name,
+    tail,
+    timestamps,
+    filter,
+  }): Json<FollowContainerLogBody>,
+) -> serror::Result<axum::body::Body> {
+  let filter = if filter.is_empty() {
+    None
+  } else {
+    Some(
+      regex::Regex::new(&filter)
+        .context("Invalid log filter regex")
+        .status_code(StatusCode::BAD_REQUEST)?,
+    )
+  };
+
+  match audit_context(&headers) {
+    Some(audit) => info!(
+      "user {} following log of container {name} | update: {}",
+      audit.user, audit.update_id
+    ),
+    None => debug!("following log of container {name}"),
+  }
+
+  let mut command = tokio::process::Command::new(container_cli());
+  command.args(["logs", "--follow", "--tail", &tail.to_string()]);
+  if timestamps {
+    command.arg("--timestamps");
+  }
+  let mut child = command
+    .arg(&name)
+    .stdin(Stdio::null())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .kill_on_drop(true)
+    .spawn()
+    .context("Failed to spawn container log command")?;
+  let (Some(stdout), Some(stderr)) =
+    (child.stdout.take(), child.stderr.take())
+  else {
+    return Err(
+      anyhow!("Failed to read container log output").into(),
+    );
+  };
+
+  // The container stderr is written to stderr.
+  let lines = futures::stream::select(
+    FramedRead::new(stdout, LogLinesCodec::default()),
+    FramedRead::new(stderr, LogLinesCodec::default()),
+  )
+  .try_filter(move |line| {
+    std::future::ready(
+      filter.as_ref().is_none_or(|filter| filter.is_match(line)),
+    )
+  })
+  .map_ok(|line| line + "\n");
+
+  Ok(axum::body::Body::from_stream(FollowLogStream {
+    lines,
+    _child: child,
+  }))
+}
+
+/// Longer lines are skipped, so a container writing without
+/// newlines can't make the log stream buffer without bound.
+const MAX_LOG_LINE_BYTES: usize = 64 * 1024;
+
+/// [LinesCodec] with a max line length, which emits a notice
+/// in place of a line over the limit rather than failing the stream.
+struct LogLinesCodec(LinesCodec);
+
+impl Default for LogLinesCodec {
+  fn default() -> Self {
+    Self(LinesCodec::new_with_max_length(MAX_LOG_LINE_BYTES))
+  }
+}
+
+impl LogLinesCodec {
+  fn skip_long_line(
+    res: Result<Option<String>, LinesCodecError>,
+  ) -> Result<Option<String>, LinesCodecError> {
+    match res {
+      Err(LinesCodecError::MaxLineLengthExceeded) => {
+        Ok(Some(format!(
+          "[Komodo] Skipped a log line longer than {MAX_LOG_LINE_BYTES} bytes"
+        )))
+      }
+      res => res,
+    }
+  }
+}
+
+impl Decoder for LogLinesCodec {
+  type Item = String;
+  type Error = LinesCodecError;
+
+  fn decode(
+    &mut self,
+    buf: &mut BytesMut,
+  ) -> Result<Option<String>, LinesCodecError> {
+    Self::skip_long_line(self.0.decode(buf))
+  }
+
+  fn decode_eof(
+    &mut self,
+    buf: &mut BytesMut,
+  ) -> Result<Option<String>, LinesCodecError> {
+    Self::skip_long_line(self.0.decode_eof(buf))
+  }
+}
+
+pin_project! {
+  /// Lines are only read as the response is sent,
+  /// so a slow client slows the log command rather than
+  /// buffering. The command is killed when the response is dropped.
+  struct FollowLogStream<S> {
+    #[pin]
+    lines: S,
+    _child: tokio::process::Child,
+  }
+}
+
+impl<S> Stream for FollowLogStream<S>
+where
+  S: Stream<Item = Result<String, LinesCodecError>>,
+{
+  type Item = Result<String, LinesCodecError>;
+
+  fn poll_next(
+    self: Pin<&mut Self>,
+    cx: &mut std::task::Context<'_>,
+  ) -> Poll<Option<Self::Item>> {
+    self.project().lines.poll_next(cx)
+  }
+}
+
+//
+
 impl Resolve<super::Args> for GetContainerLogSearch {
   #[instrument(name = "GetContainerLogSearch", level = "debug")]
   async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
@@ -74,7 +229,8 @@ impl Resolve<super::Args> for GetContainerLogSearch {
       Default::default()
     };
     let command = format!(
-      "docker logs {name} --tail 5000{timestamps} 2>&1 | {grep}"
+      "{} logs {name} --tail 5000{timestamps} 2>&1 | {grep}",
+      container_cli()
     );
     Ok(
       run_komodo_command("Get container log grep", None, command)
@@ -136,7 +292,7 @@ impl Resolve<super::Args> for StartContainer {
       run_komodo_command(
         "Docker Start",
         None,
-        format!("docker start {}", self.name),
+        format!("{} start {}", container_cli(), self.name),
       )
       .await,
     )
@@ -152,7 +308,7 @@ impl Resolve<super::Args> for RestartContainer {
       run_komodo_command(
         "Docker Restart",
         None,
-        format!("docker restart {}", self.name),
+        format!("{} restart {}", container_cli(), self.name),
       )
       .await,
     )
@@ -168,7 +324,7 @@ impl Resolve<super::Args> for PauseContainer {
       run_komodo_command(
         "Docker Pause",
         None,
-        format!("docker pause {}", self.name),
+        format!("{} pause {}", container_cli(), self.name),
       )
       .await,
     )
@@ -182,7 +338,7 @@ impl Resolve<super::Args> for UnpauseContainer {
       run_komodo_command(
         "Docker Unpause",
         None,
-        format!("docker unpause {}", self.name),
+        format!("{} unpause {}", container_cli(), self.name),
       )
       .await,
     )
@@ -259,7 +415,8 @@ impl Resolve<super::Args> for RenameContainer {
       curr_name,
       new_name,
     } = self;
-    let command = format!("docker rename {curr_name} {new_name}");
+    let command =
+      format!("{} rename {curr_name} {new_name}", container_cli());
     Ok(run_komodo_command("Docker Rename", None, command).await)
   }
 }
@@ -269,8 +426,11 @@ impl Resolve<super::Args> for RenameContainer {
 impl Resolve<super::Args> for PruneContainers {
   #[instrument(name = "PruneContainers", skip_all)]
   async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
-    let command = String::from("docker container prune -f");
-    Ok(run_komodo_command("Prune Containers", None, command).await)
+    let command = format!("{} container prune -f", container_cli());
+    Ok(
+      prune_with_hooks("containers", "Prune Containers", command)
+        .await,
+    )
   }
 }
 
@@ -291,7 +451,7 @@ impl Resolve<super::Args> for StartAllContainers {
         if labels.contains_key("komodo.skip") {
           return None;
         }
-        let command = format!("docker start {name}");
+        let command = format!("{} start {name}", container_cli());
         Some(async move {
           run_komodo_command(&command.clone(), None, command).await
         })
@@ -318,7 +478,7 @@ impl Resolve<super::Args> for RestartAllContainers {
         if labels.contains_key("komodo.skip") {
           return None;
         }
-        let command = format!("docker restart {name}");
+        let command = format!("{} restart {name}", container_cli());
         Some(async move {
           run_komodo_command(&command.clone(), None, command).await
         })
@@ -345,7 +505,7 @@ impl Resolve<super::Args> for PauseAllContainers {
         if labels.contains_key("komodo.skip") {
           return None;
         }
-        let command = format!("docker pause {name}");
+        let command = format!("{} pause {name}", container_cli());
         Some(async move {
           run_komodo_command(&command.clone(), None, command).await
         })
@@ -372,7 +532,7 @@ impl Resolve<super::Args> for UnpauseAllContainers {
         if labels.contains_key("komodo.skip") {
           return None;
         }
-        let command = format!("docker unpause {name}");
+        let command = format!("{} unpause {name}", container_cli());
         Some(async move {
           run_komodo_command(&command.clone(), None, command).await
         })
@@ -401,7 +561,7 @@ impl Resolve<super::Args> for StopAllContainers {
         }
         Some(async move {
           run_komodo_command(
-            &format!("docker stop {name}"),
+            &format!("{} stop {name}", container_cli()),
             None,
             stop_container_command(name, None, None),
           )
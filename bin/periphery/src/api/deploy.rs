@@ -1,13 +1,17 @@
-use anyhow::Context;
+use std::path::Path;
+
+use anyhow::{Context, anyhow};
 use command::run_komodo_command_with_sanitization;
 use formatting::format_serror;
 use interpolate::Interpolator;
 use komodo_client::{
   entities::{
     EnvironmentVar,
+    config::periphery::PeripheryHookEvent,
     deployment::{
       Conversion, Deployment, DeploymentConfig, DeploymentImage,
-      RestartMode, conversions_from_str, extract_registry_domain,
+      DeploymentNetworkMode, RestartMode, conversions_from_str,
+      extract_registry_domain,
     },
     environment_vars_from_str,
     update::Log,
@@ -16,13 +20,17 @@ use komodo_client::{
 };
 use periphery_client::api::container::{Deploy, RemoveContainer};
 use resolver_api::Resolve;
+use serde_json::json;
 
 use crate::{
   config::periphery_config,
-  docker::{docker_login, pull_image},
+  docker::{container_cli, docker_client, docker_login, pull_image},
   helpers::{parse_extra_args, parse_labels},
+  hooks::run_hooks,
 };
 
+const DOCKERENV_FILE: &str = "/.dockerenv";
+
 impl Resolve<super::Args> for Deploy {
   #[instrument(
     name = "Deploy",
@@ -82,6 +90,28 @@ impl Resolve<super::Args> for Deploy {
     let _ = pull_image(image).await;
     debug!("image pulled");
 
+    if let Err(e) = validate_network(&deployment.config).await {
+      return Ok(Log::error(
+        "Validate Network",
+        format_serror(
+          &e.context("Invalid network configuration").into(),
+        ),
+      ));
+    }
+
+    let hook_fields = json!({
+      "deployment": deployment.name,
+      "image": image,
+    });
+    if let Some(failed) =
+      run_hooks(PeripheryHookEvent::PreDeploy, hook_fields.clone())
+        .await
+        .into_iter()
+        .find(|log| !log.success)
+    {
+      return Ok(failed);
+    }
+
     let _ = (RemoveContainer {
       name: deployment.name.clone(),
       signal: stop_signal,
@@ -94,7 +124,7 @@ impl Resolve<super::Args> for Deploy {
     let command = docker_run_command(&deployment, image)
       .context("Unable to generate valid docker run command")?;
 
-    let Some(log) = run_komodo_command_with_sanitization(
+    let Some(mut log) = run_komodo_command_with_sanitization(
       "Docker Run",
       None,
       command,
@@ -108,18 +138,40 @@ impl Resolve<super::Args> for Deploy {
       unreachable!()
     };
 
+    let mut hook_fields = hook_fields;
+    hook_fields["success"] = log.success.into();
+    // Deploy only returns the one log, so failures are added to it.
+    for failed in
+      run_hooks(PeripheryHookEvent::PostDeploy, hook_fields)
+        .await
+        .into_iter()
+        .filter(|log| !log.success)
+    {
+      log.stderr.push_str(&format!(
+        "\n\n{} failed:\n{}",
+        failed.stage,
+        failed.stderr.trim()
+      ));
+    }
+
     Ok(log)
   }
 }
 
 fn docker_run_command(
-  Deployment {
+  deployment: &Deployment,
+  image: &str,
+) -> anyhow::Result<String> {
+  let Deployment {
     name,
     config:
       DeploymentConfig {
         volumes,
         ports,
-        network,
+        ip_address,
+        mac_address,
+        dns,
+        extra_hosts,
         command,
         restart,
         environment,
@@ -128,9 +180,7 @@ fn docker_run_command(
         ..
       },
     ..
-  }: &Deployment,
-  image: &str,
-) -> anyhow::Result<String> {
+  } = deployment;
   let ports = parse_conversions(
     &conversions_from_str(ports).context("Invalid ports")?,
     "-p",
@@ -139,7 +189,11 @@ fn docker_run_command(
     &conversions_from_str(volumes).context("Invalid volumes")?,
     "-v",
   );
-  let network = parse_network(network);
+  let network = parse_network(
+    deployment.config.network_name(),
+    ip_address,
+    mac_address,
+  );
   let restart = parse_restart(restart);
   let environment = parse_environment(
     &environment_vars_from_str(environment)
@@ -148,10 +202,12 @@ fn docker_run_command(
   let labels = parse_labels(
     &environment_vars_from_str(labels).context("Invalid labels")?,
   );
+  let dns = parse_dns(dns, extra_hosts);
   let command = parse_command(command);
   let extra_args = parse_extra_args(extra_args);
   let command = format!(
-    "docker run -d --name {name}{ports}{volumes}{network}{restart}{environment}{labels}{extra_args} {image}{command}"
+    "{} run -d --name {name}{ports}{volumes}{network}{dns}{restart}{environment}{labels}{extra_args} {image}{command}",
+    container_cli()
   );
   Ok(command)
 }
@@ -184,8 +240,103 @@ fn parse_environment(environment: &[EnvironmentVar]) -> String {
     .join("")
 }
 
-fn parse_network(network: &str) -> String {
-  format!(" --network {network}")
+fn parse_network(
+  network: &str,
+  ip_address: &str,
+  mac_address: &str,
+) -> String {
+  let mut res = format!(" --network {network}");
+  if !ip_address.is_empty() {
+    if ip_address.contains(':') {
+      res.push_str(&format!(" --ip6 {ip_address}"));
+    } else {
+      res.push_str(&format!(" --ip {ip_address}"));
+    }
+  }
+  if !mac_address.is_empty() {
+    res.push_str(&format!(" --mac-address {mac_address}"));
+  }
+  res
+}
+
+fn parse_dns(dns: &[String], extra_hosts: &[String]) -> String {
+  let dns = dns.iter().map(|dns| format!(" --dns {dns}"));
+  let extra_hosts =
+    extra_hosts.iter().map(|host| format!(" --add-host {host}"));
+  dns.chain(extra_hosts).collect()
+}
+
+/// Checks the Deployment network configuration against
+/// the networks on the host, before the existing container is removed.
+async fn validate_network(
+  config: &DeploymentConfig,
+) -> anyhow::Result<()> {
+  let network = config.network_name();
+  let static_address =
+    !config.ip_address.is_empty() || !config.mac_address.is_empty();
+  match config.network_mode {
+    DeploymentNetworkMode::Bridge | DeploymentNetworkMode::Host => {
+      if static_address {
+        return Err(anyhow!(
+          "Static IP / MAC addresses can only be used with Custom or Macvlan network mode"
+        ));
+      }
+      Ok(())
+    }
+    DeploymentNetworkMode::Custom => {
+      if network.is_empty() {
+        return Err(anyhow!("No network configured"));
+      }
+      // The builtin networks don't need to be checked
+      if ["host", "bridge", "none"].contains(&network) {
+        if static_address {
+          return Err(anyhow!(
+            "Static IP / MAC addresses cannot be used with the builtin {network} network"
+          ));
+        }
+        return Ok(());
+      }
+      docker_client()
+        .inspect_network(network)
+        .await
+        .with_context(|| {
+          format!("Network {network} does not exist on the host")
+        })?;
+      Ok(())
+    }
+    DeploymentNetworkMode::Macvlan => {
+      if network.is_empty() {
+        return Err(anyhow!("No macvlan network configured"));
+      }
+      let network = docker_client()
+        .inspect_network(network)
+        .await
+        .with_context(|| {
+          format!("Macvlan network {network} does not exist on the host. Create it with 'docker network create -d macvlan -o parent=<interface> ...'")
+        })?;
+      let name = network.name.unwrap_or_default();
+      let driver = network.driver.unwrap_or_default();
+      if driver != "macvlan" {
+        return Err(anyhow!(
+          "Network {name} uses driver '{driver}', not 'macvlan'"
+        ));
+      }
+      let parent =
+        network.options.get("parent").with_context(|| {
+          format!("Macvlan network {name} has no parent interface")
+        })?;
+      // The host interfaces are not visible to containerized Periphery
+      // without host networking, so it can only be checked outside docker.
+      if !Path::new(DOCKERENV_FILE).exists()
+        && !Path::new("/sys/class/net").join(parent).exists()
+      {
+        return Err(anyhow!(
+          "Parent interface {parent} of macvlan network {name} does not exist on the host"
+        ));
+      }
+      Ok(())
+    }
+  }
 }
 
 fn parse_restart(restart: &RestartMode) -> String {
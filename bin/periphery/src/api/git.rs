@@ -1,21 +1,33 @@
 use anyhow::{Context, anyhow};
 use axum::http::StatusCode;
+use command::{
+  run_komodo_command, run_komodo_command_with_sanitization,
+};
 use formatting::format_serror;
-use komodo_client::entities::{
-  DefaultRepoFolder, LatestCommit, update::Log,
+use komodo_client::{
+  entities::{
+    DefaultRepoFolder, LatestCommit, repo::RepoPipelineStep,
+    to_path_compatible_name, update::Log,
+  },
+  parsers::parse_multiline_command,
 };
 use periphery_client::api::git::{
   CloneRepo, DeleteRepo, GetLatestCommit,
   PeripheryRepoExecutionResponse, PullOrCloneRepo, PullRepo,
-  RenameRepo,
+  RenameRepo, RunRepoPipeline,
 };
 use resolver_api::Resolve;
 use serror::AddStatusCodeError;
-use std::path::PathBuf;
+use shell_escape::unix::escape;
+use std::{
+  borrow::Cow,
+  path::{Path, PathBuf},
+};
 use tokio::fs;
 
 use crate::{
-  config::periphery_config, git::handle_post_repo_execution,
+  config::periphery_config, docker::container_cli,
+  git::handle_post_repo_execution,
 };
 
 impl Resolve<super::Args> for GetLatestCommit {
@@ -171,6 +183,147 @@ impl Resolve<super::Args> for PullOrCloneRepo {
 
 //
 
+impl Resolve<super::Args> for RunRepoPipeline {
+  #[instrument(
+    name = "RunRepoPipeline",
+    skip_all,
+    fields(name = self.name, path = format!("{:?}", self.path))
+  )]
+  async fn resolve(
+    self,
+    _: &super::Args,
+  ) -> serror::Result<Vec<Log>> {
+    let RunRepoPipeline {
+      name,
+      path,
+      steps,
+      env_file_path,
+      replacers,
+    } = self;
+
+    let artifacts_dir = periphery_config()
+      .root_directory
+      .join("artifacts")
+      .join(to_path_compatible_name(&name));
+    // Clear the artifacts of the previous run
+    if artifacts_dir.exists() {
+      fs::remove_dir_all(&artifacts_dir).await.with_context(
+        || format!("Failed to clear artifacts at {artifacts_dir:?}"),
+      )?;
+    }
+
+    let env_file = path.join(&env_file_path);
+    let env_file = env_file.is_file().then_some(env_file);
+
+    let mut logs = Vec::new();
+    for step in steps {
+      // Remove any report left from a previous run
+      if !step.junit_report.is_empty() {
+        let _ = fs::remove_file(
+          path.join(&step.path).join(&step.junit_report),
+        )
+        .await;
+      }
+      let Some(log) = run_pipeline_step(
+        &path,
+        env_file.as_deref(),
+        &step,
+        &replacers,
+      )
+      .await
+      else {
+        continue;
+      };
+      let success = log.success;
+      logs.push(log);
+      if !success {
+        break;
+      }
+      if step.artifacts.is_empty() {
+        continue;
+      }
+      let log = collect_artifacts(&path, &artifacts_dir, &step).await;
+      let success = log.success;
+      logs.push(log);
+      if !success {
+        break;
+      }
+    }
+
+    Ok(logs)
+  }
+}
+
+/// Returns None if the step has no command.
+async fn run_pipeline_step(
+  repo_path: &Path,
+  env_file: Option<&Path>,
+  step: &RepoPipelineStep,
+  replacers: &[(String, String)],
+) -> Option<Log> {
+  if step.image.is_empty() {
+    return run_komodo_command_with_sanitization(
+      &step.name,
+      repo_path.join(&step.path).as_path(),
+      &step.command,
+      true,
+      replacers,
+    )
+    .await;
+  }
+
+  let command = parse_multiline_command(&step.command);
+  if command.is_empty() {
+    return None;
+  }
+  let workdir = Path::new("/repo").join(&step.path);
+  let env_file = env_file
+    .map(|env_file| format!(" --env-file {}", escape_path(env_file)))
+    .unwrap_or_default();
+  let command = format!(
+    "{} run --rm -v {}:/repo -w {}{env_file} {} sh -c {}",
+    container_cli(),
+    escape_path(repo_path),
+    escape_path(&workdir),
+    escape(Cow::Borrowed(&step.image)),
+    escape(Cow::Owned(command)),
+  );
+  run_komodo_command_with_sanitization(
+    &step.name, None, command, false, replacers,
+  )
+  .await
+}
+
+/// Copies the step artifacts into the artifacts directory,
+/// keeping their paths relative to the repo root.
+async fn collect_artifacts(
+  repo_path: &Path,
+  artifacts_dir: &Path,
+  step: &RepoPipelineStep,
+) -> Log {
+  let artifacts = step
+    .artifacts
+    .iter()
+    .map(|artifact| escape(Cow::Borrowed(artifact)).into_owned())
+    .collect::<Vec<_>>()
+    .join(" ");
+  let artifacts_dir = escape_path(artifacts_dir);
+  run_komodo_command(
+    &format!("{} Artifacts", step.name),
+    repo_path,
+    format!(
+      "mkdir -p {artifacts_dir} && cp -r --parents {artifacts} {artifacts_dir}"
+    ),
+  )
+  .await
+}
+
+fn escape_path(path: &Path) -> String {
+  escape(path.to_string_lossy()).into_owned()
+}
+
+//
+
 impl Resolve<super::Args> for RenameRepo {
   #[instrument(name = "RenameRepo")]
   async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
@@ -11,7 +11,10 @@ use komodo_client::entities::{
 use periphery_client::api::image::*;
 use resolver_api::Resolve;
 
-use crate::docker::{docker_client, docker_login};
+use crate::{
+  docker::{container_cli, docker_client, docker_login},
+  hooks::prune_with_hooks,
+};
 
 //
 
@@ -77,7 +80,7 @@ impl Resolve<super::Args> for PullImage {
         run_komodo_command(
           "Docker Pull",
           None,
-          format!("docker pull {name}"),
+          format!("{} pull {name}", container_cli()),
         )
         .await,
       )
@@ -97,7 +100,8 @@ impl Resolve<super::Args> for PullImage {
 impl Resolve<super::Args> for DeleteImage {
   #[instrument(name = "DeleteImage")]
   async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
-    let command = format!("docker image rm {}", self.name);
+    let command =
+      format!("{} image rm {}", container_cli(), self.name);
     Ok(run_komodo_command("Delete Image", None, command).await)
   }
 }
@@ -107,7 +111,7 @@ impl Resolve<super::Args> for DeleteImage {
 impl Resolve<super::Args> for PruneImages {
   #[instrument(name = "PruneImages")]
   async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
-    let command = String::from("docker image prune -a -f");
-    Ok(run_komodo_command("Prune Images", None, command).await)
+    let command = format!("{} image prune -a -f", container_cli());
+    Ok(prune_with_hooks("images", "Prune Images", command).await)
   }
 }
@@ -0,0 +1,11 @@
+use komodo_client::entities::NoData;
+use periphery_client::api::log_forwarding::*;
+use resolver_api::Resolve;
+
+impl Resolve<super::Args> for SetLogForwarding {
+  #[instrument(name = "SetLogForwarding", level = "debug", skip_all)]
+  async fn resolve(self, _: &super::Args) -> serror::Result<NoData> {
+    crate::log_forwarding::set_log_forwards(self.forwards);
+    Ok(NoData {})
+  }
+}
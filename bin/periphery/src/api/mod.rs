@@ -1,21 +1,34 @@
-use anyhow::Context;
+use std::{path::PathBuf, sync::OnceLock, time::Duration};
+
+use anyhow::{Context, anyhow};
 use command::run_komodo_command;
 use derive_variants::EnumVariants;
 use futures::TryFutureExt;
-use komodo_client::entities::{
-  SystemCommand,
-  config::{DockerRegistry, GitProvider},
-  update::Log,
+use komodo_client::{
+  entities::{
+    SystemCommand,
+    config::{
+      DockerRegistry, GitProvider, periphery::PeripheryHookEvent,
+    },
+    update::Log,
+  },
+  parsers::parse_multiline_command,
 };
 use periphery_client::api::{
-  build::*, compose::*, container::*, git::*, image::*, network::*,
-  stats::*, terminal::*, volume::*, *,
+  build::*, compose::*, container::*, git::*, image::*,
+  log_forwarding::*, network::*, stats::*, swarm::*, terminal::*,
+  volume::*, *,
 };
 use resolver_api::Resolve;
 use response::Response;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 
-use crate::{config::periphery_config, docker::docker_client};
+use crate::{
+  config::periphery_config,
+  docker::{container_cli, docker_client},
+  hooks::{prune_with_hooks, run_hooks},
+};
 
 mod build;
 mod compose;
@@ -23,9 +36,11 @@ mod container;
 mod deploy;
 mod git;
 mod image;
+mod log_forwarding;
 mod network;
 mod router;
 mod stats;
+mod swarm;
 mod terminal;
 mod volume;
 
@@ -45,6 +60,7 @@ pub struct Args;
 pub enum PeripheryRequest {
   GetVersion(GetVersion),
   GetHealth(GetHealth),
+  GetSpotInterruption(GetSpotInterruption),
 
   // Config (Read)
   ListGitProviders(ListGitProviders),
@@ -59,18 +75,24 @@ pub enum PeripheryRequest {
 
   // Generic shell execution
   RunCommand(RunCommand),
+  RunScheduledCommand(RunScheduledCommand),
+  RunAlertHooks(RunAlertHooks),
 
   // Repo (Write)
   CloneRepo(CloneRepo),
   PullRepo(PullRepo),
   PullOrCloneRepo(PullOrCloneRepo),
+  RunRepoPipeline(RunRepoPipeline),
   RenameRepo(RenameRepo),
   DeleteRepo(DeleteRepo),
 
   // Build
   GetDockerfileContentsOnHost(GetDockerfileContentsOnHost),
+  GetJunitReport(GetJunitReport),
   WriteDockerfileContentsToHost(WriteDockerfileContentsToHost),
   Build(Build),
+  KillBuild(KillBuild),
+  ScanImage(ScanImage),
   PruneBuilders(PruneBuilders),
   PruneBuildx(PruneBuildx),
 
@@ -78,6 +100,8 @@ pub enum PeripheryRequest {
   GetComposeContentsOnHost(GetComposeContentsOnHost),
   GetComposeLog(GetComposeLog),
   GetComposeLogSearch(GetComposeLogSearch),
+  GetComposeRuntimeDrift(GetComposeRuntimeDrift),
+  ComposeRender(ComposeRender),
 
   // Compose (Write)
   WriteComposeContentsToHost(WriteComposeContentsToHost),
@@ -85,6 +109,8 @@ pub enum PeripheryRequest {
   ComposePull(ComposePull),
   ComposeUp(ComposeUp),
   ComposeExecution(ComposeExecution),
+  ComposeWaitHealthy(ComposeWaitHealthy),
+  ComposeSwapNetworkAlias(ComposeSwapNetworkAlias),
   ComposeRun(ComposeRun),
 
   // Container (Read)
@@ -111,6 +137,14 @@ pub enum PeripheryRequest {
   RenameContainer(RenameContainer),
   PruneContainers(PruneContainers),
 
+  // Swarm (Read)
+  ListSwarmStackServices(ListSwarmStackServices),
+
+  // Swarm (Write)
+  DeploySwarmService(DeploySwarmService),
+  ScaleSwarmServices(ScaleSwarmServices),
+  RemoveSwarmStack(RemoveSwarmStack),
+
   // Networks (Read)
   InspectNetwork(InspectNetwork),
 
@@ -138,6 +172,9 @@ pub enum PeripheryRequest {
   // All in one (Read)
   GetDockerLists(GetDockerLists),
 
+  // Log forwarding
+  SetLogForwarding(SetLogForwarding),
+
   // All in one (Write)
   PruneSystem(PruneSystem),
 
@@ -177,6 +214,66 @@ impl Resolve<Args> for GetVersion {
 
 //
 
+const EC2_METADATA_URL: &str = "http://169.254.169.254/latest";
+
+fn ec2_metadata_client() -> &'static reqwest::Client {
+  static EC2_METADATA_CLIENT: OnceLock<reqwest::Client> =
+    OnceLock::new();
+  EC2_METADATA_CLIENT.get_or_init(|| {
+    reqwest::Client::builder()
+      .timeout(Duration::from_secs(2))
+      .build()
+      .expect("failed to build ec2 metadata client")
+  })
+}
+
+impl Resolve<Args> for GetSpotInterruption {
+  #[instrument(
+    name = "GetSpotInterruption",
+    level = "debug",
+    skip_all
+  )]
+  async fn resolve(
+    self,
+    _: &Args,
+  ) -> serror::Result<Option<SpotInterruption>> {
+    let client = ec2_metadata_client();
+    // IMDSv2 requires a session token
+    let token = client
+      .put(format!("{EC2_METADATA_URL}/api/token"))
+      .header("X-aws-ec2-metadata-token-ttl-seconds", "60")
+      .send()
+      .await
+      .context("Failed to reach EC2 instance metadata")?
+      .error_for_status()
+      .context("Failed to get EC2 instance metadata token")?
+      .text()
+      .await
+      .context("Failed to read EC2 instance metadata token")?;
+    let res = client
+      .get(format!(
+        "{EC2_METADATA_URL}/meta-data/spot/instance-action"
+      ))
+      .header("X-aws-ec2-metadata-token", token)
+      .send()
+      .await
+      .context("Failed to reach EC2 instance metadata")?;
+    // The instance-action is only present once interruption is scheduled
+    if res.status() == reqwest::StatusCode::NOT_FOUND {
+      return Ok(None);
+    }
+    let interruption = res
+      .error_for_status()
+      .context("Failed to get spot instance-action")?
+      .json()
+      .await
+      .context("Failed to parse spot instance-action")?;
+    Ok(Some(interruption))
+  }
+}
+
+//
+
 impl Resolve<Args> for ListGitProviders {
   #[instrument(name = "ListGitProviders", level = "debug", skip_all)]
   async fn resolve(
@@ -268,10 +365,71 @@ impl Resolve<Args> for RunCommand {
   }
 }
 
+impl Resolve<Args> for RunScheduledCommand {
+  #[instrument(name = "RunScheduledCommand", skip(self), fields(name = self.name, user = self.user))]
+  async fn resolve(self, _: &Args) -> serror::Result<Log> {
+    let RunScheduledCommand {
+      name,
+      command,
+      path,
+      user,
+    } = self;
+    if periphery_config().disable_terminals {
+      return Err(
+        anyhow!(
+          "Scheduled commands are disabled along with terminals in the periphery config"
+        )
+        .into(),
+      );
+    }
+    let command = parse_multiline_command(command);
+    if command.is_empty() {
+      return Err(
+        anyhow!("Scheduled command {name} is empty").into(),
+      );
+    }
+    let command = if user.is_empty() {
+      command
+    } else {
+      if !user
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "_-.".contains(c))
+      {
+        return Err(anyhow!("Invalid user: {user}").into());
+      }
+      format!(
+        "sudo -n -u {user} -- sh -c '{}'",
+        command.replace('\'', "'\\''")
+      )
+    };
+    let path = (!path.is_empty()).then(|| PathBuf::from(path));
+    let res = tokio::spawn(async move {
+      run_komodo_command(&name, path.as_deref(), command).await
+    })
+    .await
+    .context("failure in spawned task")?;
+    Ok(res)
+  }
+}
+
+impl Resolve<Args> for RunAlertHooks {
+  #[instrument(name = "RunAlertHooks", level = "debug", skip_all)]
+  async fn resolve(self, _: &Args) -> serror::Result<Vec<Log>> {
+    Ok(
+      run_hooks(
+        PeripheryHookEvent::AlertCondition,
+        json!({ "alert": self.alert }),
+      )
+      .await,
+    )
+  }
+}
+
 impl Resolve<Args> for PruneSystem {
   #[instrument(name = "PruneSystem", skip_all)]
   async fn resolve(self, _: &Args) -> serror::Result<Log> {
-    let command = String::from("docker system prune -a -f --volumes");
-    Ok(run_komodo_command("Prune System", None, command).await)
+    let command =
+      format!("{} system prune -a -f --volumes", container_cli());
+    Ok(prune_with_hooks("system", "Prune System", command).await)
   }
 }
@@ -5,7 +5,10 @@ use komodo_client::entities::{
 use periphery_client::api::network::*;
 use resolver_api::Resolve;
 
-use crate::docker::docker_client;
+use crate::{
+  docker::{container_cli, docker_client},
+  hooks::prune_with_hooks,
+};
 
 //
 
@@ -21,12 +24,25 @@ impl Resolve<super::Args> for InspectNetwork {
 impl Resolve<super::Args> for CreateNetwork {
   #[instrument(name = "CreateNetwork", skip(self))]
   async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
-    let CreateNetwork { name, driver } = self;
+    let CreateNetwork {
+      name,
+      driver,
+      enable_ipv6,
+      subnets,
+    } = self;
     let driver = match driver {
       Some(driver) => format!(" -d {driver}"),
       None => String::new(),
     };
-    let command = format!("docker network create{driver} {name}");
+    let ipv6 = if enable_ipv6 { " --ipv6" } else { "" };
+    let subnets = subnets
+      .iter()
+      .map(|subnet| format!(" --subnet {subnet}"))
+      .collect::<String>();
+    let command = format!(
+      "{} network create{driver}{ipv6}{subnets} {name}",
+      container_cli()
+    );
     Ok(run_komodo_command("Create Network", None, command).await)
   }
 }
@@ -36,7 +52,8 @@ impl Resolve<super::Args> for CreateNetwork {
 impl Resolve<super::Args> for DeleteNetwork {
   #[instrument(name = "DeleteNetwork", skip(self))]
   async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
-    let command = format!("docker network rm {}", self.name);
+    let command =
+      format!("{} network rm {}", container_cli(), self.name);
     Ok(run_komodo_command("Delete Network", None, command).await)
   }
 }
@@ -46,7 +63,7 @@ impl Resolve<super::Args> for DeleteNetwork {
 impl Resolve<super::Args> for PruneNetworks {
   #[instrument(name = "PruneNetworks", skip(self))]
   async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
-    let command = String::from("docker network prune -f");
-    Ok(run_komodo_command("Prune Networks", None, command).await)
+    let command = format!("{} network prune -f", container_cli());
+    Ok(prune_with_hooks("networks", "Prune Networks", command).await)
   }
 }
@@ -2,25 +2,57 @@ use anyhow::{Context, anyhow};
 use axum::{
   Router,
   body::Body,
-  extract::ConnectInfo,
-  http::{Request, StatusCode},
+  extract::{ConnectInfo, OriginalUri},
+  http::{HeaderMap, Method, Request, StatusCode},
   middleware::{self, Next},
   response::Response,
   routing::{get, post},
 };
 use derive_variants::ExtractVariant;
+use komodo_client::entities::{
+  komodo_timestamp, server::ContainerRuntime,
+};
+use periphery_client::{
+  CONTAINER_RUNTIME_HEADER,
+  audit::{AuditContext, UPDATE_ID_HEADER, USER_HEADER},
+  signing::{
+    MAX_SIGNATURE_AGE_MS, NONCE_HEADER, SIGNATURE_HEADER,
+    SignedRequest, TIMESTAMP_HEADER, VerifyingKey, decode_public_key,
+    verify_signature,
+  },
+};
 use resolver_api::Resolve;
 use serror::{AddStatusCode, AddStatusCodeError, Json};
-use std::net::{IpAddr, SocketAddr};
+use std::{
+  collections::HashMap,
+  net::{IpAddr, SocketAddr},
+  str::FromStr,
+  sync::{Mutex, OnceLock},
+};
 use uuid::Uuid;
 
-use crate::config::periphery_config;
+use crate::{
+  config::periphery_config, docker::set_container_runtime,
+};
 
 pub fn router() -> Router {
   Router::new()
     .merge(
       Router::new()
         .route("/", post(handler))
+        .layer(middleware::from_fn(set_request_container_runtime))
+        .layer(middleware::from_fn(guard_request_by_signature))
+        .layer(middleware::from_fn(guard_request_by_passkey)),
+    )
+    .nest(
+      "/log",
+      Router::new()
+        .route(
+          "/follow",
+          post(super::container::follow_container_log),
+        )
+        .layer(middleware::from_fn(set_request_container_runtime))
+        .layer(middleware::from_fn(guard_request_by_signature))
         .layer(middleware::from_fn(guard_request_by_passkey)),
     )
     .nest(
@@ -38,21 +70,28 @@ pub fn router() -> Router {
             .route(
               "/container",
               post(super::terminal::execute_container_exec),
-            )
-            .layer(middleware::from_fn(guard_request_by_passkey)),
-        ),
+            ),
+        )
+        .layer(middleware::from_fn(set_request_container_runtime))
+        .layer(middleware::from_fn(guard_request_by_signature))
+        .layer(middleware::from_fn(guard_request_by_passkey)),
     )
     .layer(middleware::from_fn(guard_request_by_ip))
 }
 
 async fn handler(
+  headers: HeaderMap,
   Json(request): Json<crate::api::PeripheryRequest>,
 ) -> serror::Result<axum::response::Response> {
   let req_id = Uuid::new_v4();
 
-  let res = tokio::spawn(task(req_id, request))
-    .await
-    .context("task handler spawn error");
+  let res = tokio::spawn(audited_task(
+    req_id,
+    request,
+    audit_context(&headers),
+  ))
+  .await
+  .context("task handler spawn error");
 
   if let Err(e) = &res {
     warn!("request {req_id} spawn error: {e:#}");
@@ -61,6 +100,38 @@ async fn handler(
   res?
 }
 
+/// Requests made on behalf of a Komodo user are logged at info level,
+/// along with each command they run.
+/// Background requests, like polling, are only logged at debug level.
+async fn audited_task(
+  req_id: Uuid,
+  request: crate::api::PeripheryRequest,
+  audit: Option<AuditContext>,
+) -> serror::Result<axum::response::Response> {
+  let Some(AuditContext { user, update_id }) = audit else {
+    debug!(
+      "request {req_id} | type: {:?}",
+      request.extract_variant()
+    );
+    return task(req_id, request).await;
+  };
+  let label =
+    format!("request {req_id} | user: {user} | update: {update_id}");
+  info!("{label} | type: {:?}", request.extract_variant());
+  command::with_audit_label(label, task(req_id, request)).await
+}
+
+/// Reads the Komodo user and Update the request was made on behalf of.
+pub fn audit_context(headers: &HeaderMap) -> Option<AuditContext> {
+  let user = headers.get(USER_HEADER)?.to_str().ok()?;
+  let user = urlencoding::decode(user).ok()?;
+  let update_id = headers
+    .get(UPDATE_ID_HEADER)
+    .and_then(|update_id| update_id.to_str().ok())
+    .unwrap_or_default();
+  Some(AuditContext::new(user, update_id))
+}
+
 async fn task(
   req_id: Uuid,
   request: crate::api::PeripheryRequest,
@@ -79,6 +150,27 @@ async fn task(
   res
 }
 
+/// Core sends the container runtime configured on the Server
+/// with each request. Only applied after the passkey is checked.
+async fn set_request_container_runtime(
+  req: Request<Body>,
+  next: Next,
+) -> Response {
+  if let Some(runtime) = req.headers().get(CONTAINER_RUNTIME_HEADER) {
+    match runtime
+      .to_str()
+      .ok()
+      .and_then(|runtime| ContainerRuntime::from_str(runtime).ok())
+    {
+      Some(runtime) => set_container_runtime(runtime),
+      None => {
+        warn!("Got invalid container runtime header: {runtime:?}")
+      }
+    }
+  }
+  next.run(req).await
+}
+
 async fn guard_request_by_passkey(
   req: Request<Body>,
   next: Next,
@@ -110,6 +202,110 @@ async fn guard_request_by_passkey(
   }
 }
 
+/// When `core_public_keys` are configured, requests must be
+/// signed by one of the keys, and each nonce is only accepted once.
+/// The signature covers the method, path, body and Komodo headers.
+/// Websocket connections have no body, so their query is signed instead.
+/// Only applied after the passkey is checked.
+async fn guard_request_by_signature(
+  req: Request<Body>,
+  next: Next,
+) -> serror::Result<Response> {
+  if periphery_config().core_public_keys.is_empty() {
+    return Ok(next.run(req).await);
+  }
+  let (parts, body) = req.into_parts();
+  let header = |name: &str| {
+    parts
+      .headers
+      .get(name)
+      .and_then(|value| value.to_str().ok())
+      .with_context(|| {
+        format!("request was not signed, missing {name} header")
+      })
+      .status_code(StatusCode::UNAUTHORIZED)
+  };
+  let signature = header(SIGNATURE_HEADER)?;
+  let timestamp = header(TIMESTAMP_HEADER)?;
+  let nonce = header(NONCE_HEADER)?;
+  let header_or_empty = |name: &str| {
+    parts
+      .headers
+      .get(name)
+      .and_then(|value| value.to_str().ok())
+      .unwrap_or_default()
+  };
+  // Nested routers only see the path after the nest prefix.
+  let uri = parts
+    .extensions
+    .get::<OriginalUri>()
+    .map(|uri| &uri.0)
+    .unwrap_or(&parts.uri);
+  let body = axum::body::to_bytes(body, usize::MAX)
+    .await
+    .context("failed to read request body")
+    .status_code(StatusCode::BAD_REQUEST)?;
+  let signed_body = if parts.method == Method::GET {
+    uri.query().unwrap_or_default().as_bytes()
+  } else {
+    &body[..]
+  };
+  verify_signature(
+    core_public_keys(),
+    signature,
+    timestamp,
+    nonce,
+    &SignedRequest {
+      method: parts.method.as_str(),
+      path: uri.path(),
+      container_runtime: header_or_empty(CONTAINER_RUNTIME_HEADER),
+      user: header_or_empty(USER_HEADER),
+      update_id: header_or_empty(UPDATE_ID_HEADER),
+      body: signed_body,
+    },
+  )
+  .status_code(StatusCode::UNAUTHORIZED)?;
+  check_nonce(nonce).status_code(StatusCode::UNAUTHORIZED)?;
+  Ok(next.run(Request::from_parts(parts, Body::from(body))).await)
+}
+
+/// Invalid keys are skipped with an error,
+/// so requests are still required to be signed.
+fn core_public_keys() -> &'static [VerifyingKey] {
+  static CORE_PUBLIC_KEYS: OnceLock<Vec<VerifyingKey>> =
+    OnceLock::new();
+  CORE_PUBLIC_KEYS.get_or_init(|| {
+    periphery_config()
+      .core_public_keys
+      .iter()
+      .filter_map(|key| match decode_public_key(key) {
+        Ok(key) => Some(key),
+        Err(e) => {
+          error!("Invalid core public key {key} | {e:#}");
+          None
+        }
+      })
+      .collect()
+  })
+}
+
+/// Nonces are remembered for longer than a signature is valid,
+/// so a captured request can't be replayed.
+fn check_nonce(nonce: &str) -> anyhow::Result<()> {
+  static NONCES: OnceLock<Mutex<HashMap<String, i64>>> =
+    OnceLock::new();
+  let now = komodo_timestamp();
+  let mut nonces = NONCES
+    .get_or_init(Default::default)
+    .lock()
+    .map_err(|_| anyhow!("nonce lock poisoned"))?;
+  nonces.retain(|_, ts| now - *ts <= 2 * MAX_SIGNATURE_AGE_MS);
+  if nonces.insert(nonce.to_string(), now).is_some() {
+    return Err(anyhow!("request nonce was already used"));
+  }
+  Ok(())
+}
+
 async fn guard_request_by_ip(
   req: Request<Body>,
   next: Next,
@@ -6,7 +6,7 @@ use periphery_client::api::stats::{
 };
 use resolver_api::Resolve;
 
-use crate::stats::stats_client;
+use crate::{docker::docker_client, stats::stats_client};
 
 impl Resolve<super::Args> for GetSystemInformation {
   #[instrument(
@@ -18,7 +18,13 @@ impl Resolve<super::Args> for GetSystemInformation {
     self,
     _: &super::Args,
   ) -> serror::Result<SystemInformation> {
-    Ok(stats_client().read().await.info.clone())
+    let mut info = stats_client().read().await.info.clone();
+    info.docker_version = docker_client()
+      .version()
+      .await
+      .inspect_err(|e| debug!("Failed to get docker version | {e:#}"))
+      .ok();
+    Ok(info)
   }
 }
 
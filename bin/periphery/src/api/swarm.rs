@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, anyhow};
+use command::{
+  run_komodo_command, run_komodo_command_with_sanitization,
+};
+use formatting::format_serror;
+use interpolate::Interpolator;
+use komodo_client::{
+  entities::{
+    deployment::{conversions_from_str, extract_registry_domain},
+    environment_vars_from_str,
+    server::ContainerRuntime,
+    swarm::{
+      SwarmService, SwarmServiceConfig, SwarmServiceMode,
+      SwarmServiceSummary,
+    },
+    update::Log,
+  },
+  parsers::QUOTE_PATTERN,
+};
+use periphery_client::api::swarm::*;
+use resolver_api::Resolve;
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+use tokio::fs;
+
+use crate::{
+  config::periphery_config,
+  docker::{container_runtime, docker_login},
+  helpers::parse_extra_args,
+};
+
+const SWARM_COMPOSE_FILE: &str = "compose.yaml";
+
+impl Resolve<super::Args> for ListSwarmStackServices {
+  #[instrument(
+    name = "ListSwarmStackServices",
+    level = "debug",
+    skip_all
+  )]
+  async fn resolve(
+    self,
+    _: &super::Args,
+  ) -> serror::Result<ListSwarmStackServicesResponse> {
+    ensure_swarm_runtime()?;
+    let mut res = HashMap::with_capacity(self.stacks.len());
+    for stack in self.stacks {
+      let services = list_stack_services(&stack).await?;
+      res.insert(stack, services);
+    }
+    Ok(res)
+  }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct DockerStackServicesItem {
+  #[serde(rename = "ID")]
+  id: String,
+  name: String,
+  mode: String,
+  image: String,
+  ports: String,
+  /// Eg. `2/3`, or `1/1 (max 1 per node)`
+  replicas: String,
+}
+
+async fn list_stack_services(
+  stack: &str,
+) -> anyhow::Result<Vec<SwarmServiceSummary>> {
+  let log = run_komodo_command(
+    "List Stack Services",
+    None,
+    format!(
+      "docker stack services {stack} --format '{{{{json .}}}}'"
+    ),
+  )
+  .await;
+  if !log.success {
+    return Err(anyhow!("{}", log.combined()).context(format!(
+      "Failed to list services in stack {stack}. Is the Server a Swarm manager?"
+    )));
+  }
+  // Stacks which are not deployed only print to stderr
+  log
+    .stdout
+    .lines()
+    .filter(|line| !line.trim().is_empty())
+    .map(|line| {
+      let item =
+        serde_json::from_str::<DockerStackServicesItem>(line)
+          .with_context(|| line.to_string())
+          .context("Failed to parse docker stack services output")?;
+      let (running, desired) = item
+        .replicas
+        .split_whitespace()
+        .next()
+        .and_then(|replicas| replicas.split_once('/'))
+        .map(|(running, desired)| {
+          (
+            running.parse().unwrap_or_default(),
+            desired.parse().unwrap_or_default(),
+          )
+        })
+        .unwrap_or_default();
+      anyhow::Ok(SwarmServiceSummary {
+        id: item.id,
+        name: item.name,
+        mode: item.mode,
+        image: item.image,
+        ports: item.ports,
+        running,
+        desired,
+      })
+    })
+    .collect()
+}
+
+//
+
+impl Resolve<super::Args> for DeploySwarmService {
+  #[instrument(
+    name = "DeploySwarmService",
+    skip_all,
+    fields(swarm_service = &self.swarm_service.name)
+  )]
+  async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
+    ensure_swarm_runtime()?;
+
+    let DeploySwarmService {
+      mut swarm_service,
+      registry_token,
+      mut replacers,
+    } = self;
+
+    let mut interpolator =
+      Interpolator::new(None, &periphery_config().secrets);
+    interpolator.interpolate_swarm_service(&mut swarm_service)?;
+    replacers.extend(interpolator.secret_replacers);
+
+    let SwarmService { name, config, .. } = &swarm_service;
+
+    let contents = if config.file_contents.trim().is_empty() {
+      if config.image.is_empty() {
+        return Ok(Log::error(
+          "Get Image",
+          String::from(
+            "Swarm service does not have a compose file or image configured",
+          ),
+        ));
+      }
+      if let Err(e) = docker_login(
+        &extract_registry_domain(&config.image)?,
+        &config.image_registry_account,
+        registry_token.as_deref(),
+      )
+      .await
+      {
+        return Ok(Log::error(
+          "Docker Login",
+          format_serror(
+            &e.context("Failed to login to docker registry").into(),
+          ),
+        ));
+      }
+      generate_compose_file(name, config)
+        .context("Failed to generate compose file")?
+    } else {
+      config.file_contents.clone()
+    };
+
+    let run_directory =
+      periphery_config().root_directory.join("swarm").join(name);
+    fs::create_dir_all(&run_directory).await.with_context(|| {
+      format!("Failed to create directory {run_directory:?}")
+    })?;
+    let file_path = run_directory.join(SWARM_COMPOSE_FILE);
+    fs::write(&file_path, contents).await.with_context(|| {
+      format!("Failed to write compose file to {file_path:?}")
+    })?;
+
+    let extra_args = parse_extra_args(&config.extra_args);
+    let command = format!(
+      "docker stack deploy --compose-file {SWARM_COMPOSE_FILE} --with-registry-auth --prune{extra_args} {name}"
+    );
+
+    let Some(log) = run_komodo_command_with_sanitization(
+      "Docker Stack Deploy",
+      run_directory.as_path(),
+      command,
+      false,
+      &replacers,
+    )
+    .await
+    else {
+      // The none case is only for empty command,
+      // this won't be the case given it is populated above.
+      unreachable!()
+    };
+
+    Ok(log)
+  }
+}
+
+/// Generates a compose file with a single service,
+/// named after the swarm service, from the service config.
+fn generate_compose_file(
+  name: &str,
+  config: &SwarmServiceConfig,
+) -> anyhow::Result<String> {
+  let mut deploy = Map::new();
+  deploy.insert(String::from("mode"), json!(config.mode.to_string()));
+  if config.mode == SwarmServiceMode::Replicated {
+    deploy.insert(String::from("replicas"), json!(config.replicas));
+  }
+  let labels = environment_vars_from_str(&config.labels)
+    .context("Invalid labels")?
+    .into_iter()
+    .map(|label| (label.variable, json!(strip_quotes(&label.value))))
+    .collect::<Map<_, _>>();
+  if !labels.is_empty() {
+    deploy.insert(String::from("labels"), Value::Object(labels));
+  }
+
+  let mut service = Map::new();
+  service.insert(String::from("image"), json!(config.image));
+  if !config.command.trim().is_empty() {
+    service
+      .insert(String::from("command"), json!(config.command.trim()));
+  }
+  let environment = environment_vars_from_str(&config.environment)
+    .context("Invalid environment")?
+    .into_iter()
+    .map(|var| (var.variable, json!(strip_quotes(&var.value))))
+    .collect::<Map<_, _>>();
+  if !environment.is_empty() {
+    service.insert(
+      String::from("environment"),
+      Value::Object(environment),
+    );
+  }
+  let ports = conversions_from_str(&config.ports)
+    .context("Invalid ports")?
+    .into_iter()
+    .map(|port| format!("{}:{}", port.local, port.container))
+    .collect::<Vec<_>>();
+  if !ports.is_empty() {
+    service.insert(String::from("ports"), json!(ports));
+  }
+  if !config.networks.is_empty() {
+    service.insert(String::from("networks"), json!(config.networks));
+  }
+  service.insert(String::from("deploy"), Value::Object(deploy));
+
+  let mut compose = json!({
+    "version": "3.8",
+    "services": { name: service },
+  });
+  if !config.networks.is_empty() {
+    // The networks must already exist in the Swarm
+    let networks = config
+      .networks
+      .iter()
+      .map(|network| (network.clone(), json!({ "external": true })))
+      .collect::<Map<_, _>>();
+    compose["networks"] = Value::Object(networks);
+  }
+
+  serde_yaml_ng::to_string(&compose)
+    .context("Failed to serialize compose file")
+}
+
+/// Quotes are only needed when passing values through the shell.
+fn strip_quotes(value: &str) -> &str {
+  if value.len() > 1
+    && value.starts_with(QUOTE_PATTERN)
+    && value.ends_with(QUOTE_PATTERN)
+  {
+    &value[1..value.len() - 1]
+  } else {
+    value
+  }
+}
+
+//
+
+impl Resolve<super::Args> for ScaleSwarmServices {
+  #[instrument(name = "ScaleSwarmServices", skip(self))]
+  async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
+    ensure_swarm_runtime()?;
+    if self.services.is_empty() {
+      return Err(anyhow!("No services to scale").into());
+    }
+    let services = self
+      .services
+      .iter()
+      .map(|service| format!(" {service}={}", self.replicas))
+      .collect::<String>();
+    Ok(
+      run_komodo_command(
+        "Docker Service Scale",
+        None,
+        format!("docker service scale --detach{services}"),
+      )
+      .await,
+    )
+  }
+}
+
+//
+
+impl Resolve<super::Args> for RemoveSwarmStack {
+  #[instrument(name = "RemoveSwarmStack", skip(self))]
+  async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
+    ensure_swarm_runtime()?;
+    Ok(
+      run_komodo_command(
+        "Docker Stack Remove",
+        None,
+        format!("docker stack rm {}", self.stack),
+      )
+      .await,
+    )
+  }
+}
+
+/// Swarm mode is only available with the Docker runtime.
+fn ensure_swarm_runtime() -> anyhow::Result<()> {
+  if container_runtime() == ContainerRuntime::Podman {
+    return Err(anyhow!(
+      "Docker Swarm is not supported with the Podman container runtime"
+    ));
+  }
+  Ok(())
+}
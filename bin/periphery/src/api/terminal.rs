@@ -4,21 +4,36 @@ use axum::{
     Query, WebSocketUpgrade,
     ws::{Message, Utf8Bytes},
   },
-  http::StatusCode,
+  http::{HeaderMap, StatusCode},
   response::Response,
 };
 use bytes::Bytes;
 use futures::{SinkExt, StreamExt, TryStreamExt};
 use komodo_client::{
-  api::write::TerminalRecreateMode,
-  entities::{KOMODO_EXIT_CODE, NoData, server::TerminalInfo},
+  api::{
+    terminal::{
+      TERMINAL_FILE_DOWNLOAD_PREFIX, TERMINAL_FILE_MESSAGE_PREFIX,
+      TERMINAL_FILE_UPLOAD_PREFIX, TerminalFileDownload,
+      TerminalFileMessage, TerminalFileUpload,
+    },
+    write::TerminalRecreateMode,
+  },
+  entities::{
+    KOMODO_EXIT_CODE, NoData, environment_vars_from_str,
+    optional_string,
+    server::{TerminalInfo, TerminalProfile},
+  },
 };
 use periphery_client::api::terminal::*;
 use resolver_api::Resolve;
 use serror::{AddStatusCodeError, Json};
+use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
 
-use crate::{config::periphery_config, terminal::*};
+use crate::{
+  api::router::audit_context, config::periphery_config,
+  docker::container_cli, terminal::*,
+};
 
 impl Resolve<super::Args> for ListTerminals {
   #[instrument(name = "ListTerminals", level = "debug")]
@@ -40,13 +55,86 @@ impl Resolve<super::Args> for CreateTerminal {
           .status_code(StatusCode::FORBIDDEN),
       );
     }
-    create_terminal(self.name, self.command, self.recreate)
+    let (command, options) = match self.profile {
+      Some(profile)
+        if !periphery_config().terminal_profiles.is_empty() =>
+      {
+        let profile =
+          get_terminal_profile(&profile, &self.user_groups)?;
+        (
+          profile.command.clone(),
+          TerminalOptions {
+            working_dir: optional_string(&profile.working_dir),
+            env: environment_vars_from_str(&profile.environment)
+              .context("Invalid terminal profile environment")?,
+            profile: Some(profile.name.clone()),
+          },
+        )
+      }
+      profile => (
+        self.command,
+        TerminalOptions {
+          working_dir: self.working_dir,
+          env: self.env,
+          profile,
+        },
+      ),
+    };
+    check_terminal_command_allowed(&command)?;
+    create_terminal(self.name, command, options, self.recreate)
       .await
       .map(|_| NoData {})
       .map_err(Into::into)
   }
 }
 
+/// Gets the profile from the Periphery `terminal_profiles`,
+/// checking the user is in one of its User Groups.
+fn get_terminal_profile(
+  name: &str,
+  user_groups: &[String],
+) -> serror::Result<&'static TerminalProfile> {
+  let profile = periphery_config()
+    .terminal_profiles
+    .iter()
+    .find(|profile| profile.name == name)
+    .with_context(|| {
+      format!("No terminal profile '{name}' in the periphery config")
+    })
+    .status_code(StatusCode::FORBIDDEN)?;
+  if profile.user_groups.is_empty()
+    || profile
+      .user_groups
+      .iter()
+      .any(|allowed| user_groups.contains(allowed))
+  {
+    return Ok(profile);
+  }
+  Err(
+    anyhow!(
+      "User is not allowed to use terminal profile '{name}' in the periphery config"
+    )
+    .status_code(StatusCode::FORBIDDEN),
+  )
+}
+
+/// Ensures the terminal command (or container exec shell)
+/// is included in `allowed_terminal_commands`, if configured.
+fn check_terminal_command_allowed(
+  command: &str,
+) -> serror::Result<()> {
+  let allowed = &periphery_config().allowed_terminal_commands;
+  if allowed.is_empty() || allowed.iter().any(|c| c == command) {
+    return Ok(());
+  }
+  Err(
+    anyhow!(
+      "Terminal command '{command}' is not allowed in the periphery config"
+    )
+    .status_code(StatusCode::FORBIDDEN),
+  )
+}
+
 impl Resolve<super::Args> for DeleteTerminal {
   #[instrument(name = "DeleteTerminal", level = "debug")]
   async fn resolve(self, _: &super::Args) -> serror::Result<NoData> {
@@ -93,6 +181,8 @@ pub async fn connect_container_exec(
     token,
     container,
     shell,
+    user,
+    read_only,
   }): Query<ConnectContainerExecQuery>,
   ws: WebSocketUpgrade,
 ) -> serror::Result<Response> {
@@ -110,10 +200,15 @@ pub async fn connect_container_exec(
       .into(),
     );
   }
+  check_terminal_command_allowed(&shell)?;
   // Create (recreate if shell changed)
   create_terminal(
     container.clone(),
-    format!("docker exec -it {container} {shell}"),
+    format!("{} exec -it {container} {shell}", container_cli()),
+    TerminalOptions {
+      container: Some(container.clone()),
+      ..Default::default()
+    },
     TerminalRecreateMode::DifferentCommand,
   )
   .await
@@ -123,6 +218,8 @@ pub async fn connect_container_exec(
     ConnectTerminalQuery {
       token,
       terminal: container,
+      user,
+      read_only,
     },
     ws,
   )
@@ -130,14 +227,19 @@ pub async fn connect_container_exec(
 }
 
 async fn handle_terminal_websocket(
-  ConnectTerminalQuery { token, terminal }: ConnectTerminalQuery,
+  ConnectTerminalQuery {
+    token,
+    terminal: terminal_name,
+    user,
+    read_only,
+  }: ConnectTerminalQuery,
   ws: WebSocketUpgrade,
 ) -> serror::Result<Response> {
   // Auth the connection with single use token
   auth_tokens().check_token(token)?;
 
   clean_up_terminals().await;
-  let terminal = get_terminal(&terminal).await?;
+  let terminal = get_terminal(&terminal_name).await?;
 
   Ok(ws.on_upgrade(|mut socket| async move {
     let init_res = async {
@@ -157,10 +259,23 @@ async fn handle_terminal_websocket(
       return;
     }
 
+    info!(
+      "user {user} connected to terminal {terminal_name} | read only: {read_only}"
+    );
+
+    // Listed in the terminal participants until the connection ends.
+    let _participant =
+      terminal.join(terminal_name.clone(), user.clone(), read_only);
+
     let (mut ws_write, mut ws_read) = socket.split();
 
     let cancel = CancellationToken::new();
 
+    // File transfer responses are sent through the ws write task,
+    // so they aren't interleaved with terminal output.
+    let (file_send, mut file_receive) =
+      mpsc::channel::<(TerminalFileMessage, Option<Bytes>)>(8);
+
     let ws_read = async {
       loop {
         let res = tokio::select! {
@@ -175,10 +290,32 @@ async fn handle_terminal_websocket(
           }
         };
         match res {
+          Some(Ok(Message::Binary(bytes)))
+            if bytes.first() == Some(&TERMINAL_FILE_UPLOAD_PREFIX) =>
+          {
+            let message =
+              handle_file_upload(&terminal, read_only, &bytes[1..]).await;
+            if file_send.send((message, None)).await.is_err() {
+              break;
+            }
+          }
+          Some(Ok(Message::Binary(bytes)))
+            if bytes.first() == Some(&TERMINAL_FILE_DOWNLOAD_PREFIX) =>
+          {
+            let response =
+              handle_file_download(&terminal, read_only, &bytes[1..]).await;
+            if file_send.send(response).await.is_err() {
+              break;
+            }
+          }
+          // Read only participants can't write to stdin or resize.
+          Some(Ok(Message::Binary(_)) | Ok(Message::Text(_)))
+            if read_only => {}
           Some(Ok(Message::Binary(bytes)))
             if bytes.first() == Some(&0x00) =>
           {
             // println!("Got ws read bytes - for stdin");
+            terminal.touch();
             if let Err(e) = terminal.stdin.send(StdinMsg::Bytes(
               Bytes::copy_from_slice(&bytes[1..]),
             )).await {
@@ -202,6 +339,7 @@ async fn handle_terminal_websocket(
           }
           Some(Ok(Message::Text(text))) => {
             trace!("Got ws read text");
+            terminal.touch();
             if let Err(e) =
               terminal.stdin.send(StdinMsg::Bytes(Bytes::from(text))).await
             {
@@ -237,6 +375,14 @@ async fn handle_terminal_websocket(
       loop {
         let res = tokio::select! {
           res = stdout.recv() => res.context("Failed to get message over stdout receiver"),
+          Some((message, contents)) = file_receive.recv() => {
+            if let Err(e) = send_file_message(&mut ws_write, message, contents).await {
+              debug!("Failed to send file message to WS: {e:?}");
+              cancel.cancel();
+              break;
+            }
+            continue;
+          },
           _ = terminal.cancel.cancelled() => {
             trace!("ws write: cancelled from outside");
             let _ = ws_write.send(Message::Text(Utf8Bytes::from_static("PTY KILLED"))).await;
@@ -276,11 +422,126 @@ async fn handle_terminal_websocket(
 
     tokio::join!(ws_read, ws_write);
 
+    info!("user {user} disconnected from terminal {terminal_name}");
+
     clean_up_terminals().await;
   }))
 }
 
+async fn handle_file_upload(
+  terminal: &Terminal,
+  read_only: bool,
+  body: &[u8],
+) -> TerminalFileMessage {
+  let Some(split) = body.iter().position(|byte| *byte == b'\n')
+  else {
+    return TerminalFileMessage::Error {
+      path: String::new(),
+      message: String::from("File upload is missing the header"),
+    };
+  };
+  let TerminalFileUpload { path } =
+    match serde_json::from_slice(&body[..split]) {
+      Ok(upload) => upload,
+      Err(e) => {
+        return TerminalFileMessage::Error {
+          path: String::new(),
+          message: format!("Invalid file upload header | {e:?}"),
+        };
+      }
+    };
+  let contents = &body[split + 1..];
+  let res = if read_only {
+    Err(anyhow!("Read only participants cannot transfer files"))
+  } else {
+    terminal.upload_file(&path, contents).await
+  };
+  match res {
+    Ok(_) => {
+      info!("Uploaded file to {path} through terminal");
+      TerminalFileMessage::Uploaded {
+        path,
+        size: contents.len() as u64,
+      }
+    }
+    Err(e) => TerminalFileMessage::Error {
+      path,
+      message: format!("{e:#}"),
+    },
+  }
+}
+
+async fn handle_file_download(
+  terminal: &Terminal,
+  read_only: bool,
+  body: &[u8],
+) -> (TerminalFileMessage, Option<Bytes>) {
+  let TerminalFileDownload { path } =
+    match serde_json::from_slice(body) {
+      Ok(download) => download,
+      Err(e) => {
+        return (
+          TerminalFileMessage::Error {
+            path: String::new(),
+            message: format!("Invalid file download request | {e:?}"),
+          },
+          None,
+        );
+      }
+    };
+  let res = if read_only {
+    Err(anyhow!("Read only participants cannot transfer files"))
+  } else {
+    terminal.download_file(&path).await
+  };
+  match res {
+    Ok(contents) => {
+      info!("Downloaded file at {path} through terminal");
+      (
+        TerminalFileMessage::Download {
+          path,
+          size: contents.len() as u64,
+        },
+        Some(contents),
+      )
+    }
+    Err(e) => (
+      TerminalFileMessage::Error {
+        path,
+        message: format!("{e:#}"),
+      },
+      None,
+    ),
+  }
+}
+
+async fn send_file_message<S>(
+  ws_write: &mut S,
+  message: TerminalFileMessage,
+  contents: Option<Bytes>,
+) -> anyhow::Result<()>
+where
+  S: futures::Sink<Message, Error = axum::Error> + Unpin,
+{
+  let message = serde_json::to_string(&message)
+    .context("Failed to serialize file message")?;
+  ws_write
+    .send(Message::Text(Utf8Bytes::from(format!(
+      "{TERMINAL_FILE_MESSAGE_PREFIX}{message}"
+    ))))
+    .await
+    .context("Failed to send file message")?;
+  if let Some(contents) = contents {
+    ws_write
+      .send(Message::Binary(contents))
+      .await
+      .context("Failed to send file contents")?;
+  }
+  Ok(())
+}
+
 pub async fn execute_terminal(
+  headers: HeaderMap,
   Json(ExecuteTerminalBody { terminal, command }): Json<
     ExecuteTerminalBody,
   >,
@@ -292,10 +553,12 @@ pub async fn execute_terminal(
     );
   }
 
+  log_terminal_execution(&headers, &terminal);
   execute_command_on_terminal(&terminal, &command).await
 }
 
 pub async fn execute_container_exec(
+  headers: HeaderMap,
   Json(ExecuteContainerExecBody {
     container,
     shell,
@@ -316,18 +579,35 @@ pub async fn execute_container_exec(
       .into(),
     );
   }
+  check_terminal_command_allowed(&shell)?;
   // Create terminal (recreate if shell changed)
   create_terminal(
     container.clone(),
-    format!("docker exec -it {container} {shell}"),
+    format!("{} exec -it {container} {shell}", container_cli()),
+    TerminalOptions {
+      container: Some(container.clone()),
+      ..Default::default()
+    },
     TerminalRecreateMode::DifferentCommand,
   )
   .await
   .context("Failed to create terminal for container exec")?;
 
+  log_terminal_execution(&headers, &container);
   execute_command_on_terminal(&container, &command).await
 }
 
+/// The command isn't logged, as it may contain secrets.
+fn log_terminal_execution(headers: &HeaderMap, terminal: &str) {
+  match audit_context(headers) {
+    Some(audit) => info!(
+      "user {} executing command on terminal {terminal} | update: {}",
+      audit.user, audit.update_id
+    ),
+    None => debug!("executing command on terminal {terminal}"),
+  }
+}
+
 async fn execute_command_on_terminal(
   terminal_name: &str,
   command: &str,
@@ -350,6 +630,7 @@ async fn execute_command_on_terminal(
     "printf '\n{START_OF_OUTPUT}\n\n'; {command}; rc=$?; printf '\n{KOMODO_EXIT_CODE}%d\n{END_OF_OUTPUT}\n' \"$rc\"\n"
   );
 
+  terminal.touch();
   terminal
     .stdin
     .send(StdinMsg::Bytes(Bytes::from(full_command)))
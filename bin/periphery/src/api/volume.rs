@@ -1,9 +1,14 @@
 use command::run_komodo_command;
-use komodo_client::entities::{docker::volume::Volume, update::Log};
+use komodo_client::entities::{
+  docker::volume::Volume, server::ContainerRuntime, update::Log,
+};
 use periphery_client::api::volume::*;
 use resolver_api::Resolve;
 
-use crate::docker::docker_client;
+use crate::{
+  docker::{container_cli, container_runtime, docker_client},
+  hooks::prune_with_hooks,
+};
 
 //
 
@@ -19,7 +24,8 @@ impl Resolve<super::Args> for InspectVolume {
 impl Resolve<super::Args> for DeleteVolume {
   #[instrument(name = "DeleteVolume")]
   async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
-    let command = format!("docker volume rm {}", self.name);
+    let command =
+      format!("{} volume rm {}", container_cli(), self.name);
     Ok(run_komodo_command("Delete Volume", None, command).await)
   }
 }
@@ -29,7 +35,12 @@ impl Resolve<super::Args> for DeleteVolume {
 impl Resolve<super::Args> for PruneVolumes {
   #[instrument(name = "PruneVolumes")]
   async fn resolve(self, _: &super::Args) -> serror::Result<Log> {
-    let command = String::from("docker volume prune -a -f");
-    Ok(run_komodo_command("Prune Volumes", None, command).await)
+    // Podman prunes all unused volumes without '-a'
+    let all = match container_runtime() {
+      ContainerRuntime::Docker => " -a",
+      ContainerRuntime::Podman => "",
+    };
+    let command = format!("{} volume prune{all} -f", container_cli());
+    Ok(prune_with_hooks("volumes", "Prune Volumes", command).await)
   }
 }
@@ -1,14 +1,66 @@
 use std::{
+  borrow::Cow,
+  collections::{HashMap, HashSet},
   fmt::Write,
   path::{Path, PathBuf},
+  sync::{Mutex, OnceLock},
 };
 
 use anyhow::{Context, anyhow};
+use async_timing_util::wait_until_timelength;
+use command::run_komodo_command;
 use formatting::format_serror;
 use komodo_client::{
-  entities::{EnvironmentVar, update::Log},
+  entities::{
+    EnvironmentVar, I64,
+    build::{ImageScan, ImageVulnerability},
+    deployment::Conversion,
+    komodo_timestamp,
+    server::ContainerRuntime,
+    update::Log,
+  },
   parsers::QUOTE_PATTERN,
 };
+use serde::Deserialize;
+use shell_escape::unix::escape;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+  config::periphery_config,
+  docker::{container_cli, container_runtime, runtime_socket_path},
+};
+
+/// Build name -> the cancellation token of its running docker builds.
+fn build_cancels()
+-> &'static Mutex<HashMap<String, CancellationToken>> {
+  static BUILD_CANCELS: OnceLock<
+    Mutex<HashMap<String, CancellationToken>>,
+  > = OnceLock::new();
+  BUILD_CANCELS.get_or_init(Default::default)
+}
+
+/// Gets the token which kills the docker builds of the Build
+/// when cancelled. The matrix variants of the Build share it.
+pub fn build_cancel(name: &str) -> CancellationToken {
+  build_cancels()
+    .lock()
+    .unwrap()
+    .entry(name.to_string())
+    .or_default()
+    .clone()
+}
+
+/// Kills the running docker builds of the Build.
+/// Returns false if none are running.
+pub fn kill_build(name: &str) -> bool {
+  match build_cancels().lock().unwrap().remove(name) {
+    Some(cancel) => {
+      cancel.cancel();
+      true
+    }
+    None => false,
+  }
+}
 
 pub async fn write_dockerfile(
   build_path: &Path,
@@ -106,3 +158,416 @@ pub async fn parse_secret_args(
   }
   Ok(res)
 }
+
+/// Writes a copy of the Dockerfile with the cache volumes
+/// mounted into every `RUN` instruction, next to the original.
+/// Matrix variants each write their own copy.
+/// The copy is removed again when the returned
+/// [CacheDockerfile] is dropped.
+pub async fn write_cache_volumes_dockerfile(
+  build_path: &Path,
+  dockerfile_path: &str,
+  cache_volumes: &[Conversion],
+  variant: Option<&str>,
+) -> anyhow::Result<CacheDockerfile> {
+  let mut mounts = String::new();
+  for Conversion { local, container } in cache_volumes {
+    if local.contains([',', ' ']) || container.contains([',', ' ']) {
+      return Err(anyhow!(
+        "Invalid cache volume {local}: {container}. Cannot contain ',' or spaces."
+      ));
+    }
+    write!(
+      &mut mounts,
+      " --mount=type=cache,id={local},target={container}"
+    )
+    .context("Failed to format cache volume mount")?;
+  }
+
+  let full_dockerfile_path = build_path
+    .join(dockerfile_path)
+    .components()
+    .collect::<PathBuf>();
+  let dockerfile = tokio::fs::read_to_string(&full_dockerfile_path)
+    .await
+    .with_context(|| {
+      format!("Failed to read dockerfile at {full_dockerfile_path:?}")
+    })?;
+
+  let cache_dockerfile_path = match variant {
+    Some(variant) => {
+      format!("{dockerfile_path}.{variant}.komodo-cache")
+    }
+    None => format!("{dockerfile_path}.komodo-cache"),
+  };
+  let full_cache_dockerfile_path = build_path
+    .join(&cache_dockerfile_path)
+    .components()
+    .collect::<PathBuf>();
+  tokio::fs::write(
+    &full_cache_dockerfile_path,
+    add_cache_mounts(&dockerfile, &mounts),
+  )
+  .await
+  .with_context(|| {
+    format!(
+      "Failed to write dockerfile with cache volumes to {full_cache_dockerfile_path:?}"
+    )
+  })?;
+
+  Ok(CacheDockerfile {
+    path: cache_dockerfile_path,
+    full_path: full_cache_dockerfile_path,
+  })
+}
+
+/// A Dockerfile copy with cache volumes mounted,
+/// removed from the build context on drop so it isn't left
+/// behind when the build returns early.
+pub struct CacheDockerfile {
+  /// The path relative to the build path
+  pub path: String,
+  full_path: PathBuf,
+}
+
+impl Drop for CacheDockerfile {
+  fn drop(&mut self) {
+    let _ = std::fs::remove_file(&self.full_path);
+  }
+}
+
+/// Adds the mounts to every `RUN` instruction.
+/// Lines continuing a previous instruction are left alone.
+fn add_cache_mounts(dockerfile: &str, mounts: &str) -> String {
+  let mut continuation = false;
+  let mut res = String::with_capacity(dockerfile.len());
+  for line in dockerfile.lines() {
+    let trimmed = line.trim_start();
+    // Comments and empty lines don't end a continuation.
+    if trimmed.is_empty() || trimmed.starts_with('#') {
+      res.push_str(line);
+      res.push('\n');
+      continue;
+    }
+    let is_run = !continuation
+      && trimmed.get(..3).is_some_and(|instruction| {
+        instruction.eq_ignore_ascii_case("RUN")
+      })
+      && trimmed[3..].starts_with(char::is_whitespace);
+    if is_run {
+      let indent = &line[..line.len() - trimmed.len()];
+      res.push_str(indent);
+      res.push_str(&trimmed[..3]);
+      res.push_str(mounts);
+      res.push_str(&trimmed[3..]);
+    } else {
+      res.push_str(line);
+    }
+    res.push('\n');
+    continuation = trimmed.trim_end().ends_with('\\');
+  }
+  res
+}
+
+/// <https://docs.docker.com/build/cache/backends/>
+///
+/// Plain image references are used as registry caches.
+pub fn parse_cache_args(
+  cache_from: &[String],
+  cache_to: &[String],
+) -> String {
+  let cache_spec = |spec: &str, extra: &str| {
+    let spec = if spec.contains('=') {
+      spec.to_string()
+    } else {
+      format!("type=registry,ref={spec}{extra}")
+    };
+    escape(Cow::Owned(spec)).into_owned()
+  };
+  let mut res = String::new();
+  for spec in cache_from {
+    let _ = write!(res, " --cache-from {}", cache_spec(spec, ""));
+  }
+  for spec in cache_to {
+    let _ =
+      write!(res, " --cache-to {}", cache_spec(spec, ",mode=max"));
+  }
+  res
+}
+
+/// Summarizes how many of the build steps were cached,
+/// using the buildx plain progress output.
+pub fn build_cache_log(build_log: &Log) -> Log {
+  let mut steps = HashSet::new();
+  let mut cached = HashSet::new();
+  let mut imported = Vec::new();
+  for line in build_log.stdout.lines().chain(build_log.stderr.lines())
+  {
+    let Some((step, rest)) =
+      line.strip_prefix('#').and_then(|line| line.split_once(' '))
+    else {
+      continue;
+    };
+    if rest.starts_with('[') && !rest.starts_with("[internal]") {
+      steps.insert(step);
+    } else if rest.trim() == "CACHED" {
+      cached.insert(step);
+    } else if let Some(source) =
+      rest.strip_prefix("importing cache manifest from ")
+    {
+      imported.push(source.trim());
+    }
+  }
+  let cached = steps.intersection(&cached).count();
+  let mut msg =
+    format!("{cached} / {} build steps were cached", steps.len());
+  if !imported.is_empty() {
+    imported.sort();
+    imported.dedup();
+    let _ =
+      write!(msg, "\nImported cache from: {}", imported.join(", "));
+  }
+  Log::simple("Build Cache", msg)
+}
+
+/// The buildx builder used for multi-platform builds.
+pub const MULTI_PLATFORM_BUILDER: &str = "komodo-multi-platform";
+
+/// Validates the platforms and joins them
+/// into the `--platform` argument value.
+pub fn parse_platforms(
+  platforms: &[String],
+) -> anyhow::Result<String> {
+  for platform in platforms {
+    if platform.is_empty()
+      || !platform.chars().all(|c| {
+        c.is_ascii_alphanumeric()
+          || matches!(c, '/' | '-' | '_' | '.')
+      })
+    {
+      return Err(anyhow!("Invalid platform: {platform}"));
+    }
+  }
+  Ok(platforms.join(","))
+}
+
+/// Prepares the builder to build the given platforms:
+///   1. Installs the QEMU emulators for the platforms using `tonistiigi/binfmt`.
+///   2. Ensures the multi-platform buildx builder exists.
+///      The default `docker` driver can't produce multi-platform manifests,
+///      so it uses the `docker-container` driver.
+pub async fn setup_multi_platform(
+  platforms: &str,
+  logs: &mut Vec<Log>,
+) {
+  if container_runtime() == ContainerRuntime::Podman {
+    logs.push(Log::error(
+      "Multi-Platform Setup",
+      String::from("Multi-platform builds require Docker buildx"),
+    ));
+    return;
+  }
+  let log = run_komodo_command(
+    "Setup QEMU",
+    None,
+    format!(
+      "docker run --privileged --rm tonistiigi/binfmt --install {platforms}"
+    ),
+  )
+  .await;
+  let success = log.success;
+  logs.push(log);
+  if !success {
+    return;
+  }
+  logs.push(
+    run_komodo_command(
+      "Setup Buildx Builder",
+      None,
+      format!(
+        "docker buildx inspect {MULTI_PLATFORM_BUILDER} > /dev/null 2>&1 \
+        || docker buildx create --name {MULTI_PLATFORM_BUILDER} --driver docker-container --bootstrap"
+      ),
+    )
+    .await,
+  );
+}
+
+/// Reads the pushed manifest digest out of the
+/// buildx `--metadata-file` written by the build.
+pub async fn read_manifest_digest(
+  metadata_path: &Path,
+) -> anyhow::Result<String> {
+  let contents = tokio::fs::read_to_string(metadata_path)
+    .await
+    .with_context(|| {
+      format!("Failed to read build metadata at {metadata_path:?}")
+    })?;
+  let metadata = serde_json::from_str::<serde_json::Value>(&contents)
+    .context("Failed to parse build metadata")?;
+  metadata
+    .get("containerimage.digest")
+    .and_then(|digest| digest.as_str())
+    .map(str::to_string)
+    .context("Build metadata is missing the image digest")
+}
+
+/// Prunes the build cache volumes down to `max_size`.
+/// If `max_size` is empty, all unused cache volumes are removed.
+pub async fn prune_cache_volumes(max_size: &str) -> Log {
+  if !max_size
+    .chars()
+    .all(|c| c.is_ascii_alphanumeric() || c == '.')
+  {
+    return Log::error(
+      "Prune Cache Volumes",
+      format!("Invalid cache volumes max size: {max_size}"),
+    );
+  }
+  let keep_storage = if max_size.is_empty() {
+    String::new()
+  } else {
+    format!(" --keep-storage {max_size}")
+  };
+  if container_runtime() == ContainerRuntime::Podman {
+    return Log::simple(
+      "Prune Cache Volumes",
+      String::from(
+        "Cache volume pruning is not supported with Podman, skipping",
+      ),
+    );
+  }
+  let command = format!(
+    "docker builder prune -f --filter type=exec.cachemount{keep_storage}"
+  );
+  run_komodo_command("Prune Cache Volumes", None, command).await
+}
+
+/// Prunes the build cache volumes on the
+/// configured `cache_volumes_prune_interval`.
+pub fn spawn_cache_volumes_prune_loop() {
+  let Some(interval) =
+    periphery_config().cache_volumes_prune_interval
+  else {
+    return;
+  };
+  tokio::spawn(async move {
+    let interval = interval
+      .to_string()
+      .parse()
+      .expect("invalid cache volumes prune interval");
+    loop {
+      wait_until_timelength(interval, 0).await;
+      let log = prune_cache_volumes(
+        &periphery_config().cache_volumes_max_size,
+      )
+      .await;
+      if log.success {
+        info!("pruned build cache volumes");
+      } else {
+        warn!("Failed to prune build cache volumes | {}", log.stderr);
+      }
+    }
+  });
+}
+
+/// Uses the `trivy` binary on the host if available.
+/// Otherwise runs Trivy in a container against the runtime socket,
+/// keeping the vulnerability database in a volume between scans.
+pub fn scan_image_command(image: &str) -> String {
+  let image = escape(image.into());
+  let args =
+    format!("image --quiet --scanners vuln --format json {image}");
+  let socket = escape(runtime_socket_path().into());
+  // SELinux otherwise blocks Podman containers from the socket.
+  let security_opt = match container_runtime() {
+    ContainerRuntime::Docker => "",
+    ContainerRuntime::Podman => " --security-opt label=disable",
+  };
+  format!(
+    "if command -v trivy > /dev/null 2>&1; then trivy {args}; \
+    else {} run --rm{security_opt} -v {socket}:/var/run/docker.sock \
+    -v komodo-trivy-cache:/root/.cache aquasec/trivy {args}; fi",
+    container_cli()
+  )
+}
+
+/// Parses the Trivy JSON report into the scan summary.
+/// Only the Critical and High vulnerabilities are kept.
+pub fn parse_trivy_report(
+  image: String,
+  report: &str,
+) -> anyhow::Result<ImageScan> {
+  #[derive(Deserialize)]
+  #[serde(rename_all = "PascalCase")]
+  struct Report {
+    #[serde(default)]
+    results: Vec<ReportResult>,
+  }
+  #[derive(Deserialize)]
+  #[serde(rename_all = "PascalCase")]
+  struct ReportResult {
+    #[serde(default)]
+    vulnerabilities: Option<Vec<Vulnerability>>,
+  }
+  #[derive(Deserialize)]
+  #[serde(rename_all = "PascalCase")]
+  struct Vulnerability {
+    #[serde(rename = "VulnerabilityID")]
+    vulnerability_id: String,
+    #[serde(default)]
+    severity: String,
+    #[serde(default)]
+    pkg_name: String,
+    #[serde(default)]
+    installed_version: String,
+    #[serde(default)]
+    fixed_version: String,
+    #[serde(default)]
+    title: String,
+  }
+
+  let report = serde_json::from_str::<Report>(report)
+    .context("Failed to parse Trivy report")?;
+
+  let mut scan = ImageScan {
+    image,
+    ts: komodo_timestamp(),
+    ..Default::default()
+  };
+  // The same vulnerability may be reported for multiple targets.
+  let mut seen = HashSet::new();
+  for vulnerability in report
+    .results
+    .into_iter()
+    .flat_map(|result| result.vulnerabilities.unwrap_or_default())
+  {
+    if !seen.insert((
+      vulnerability.vulnerability_id.clone(),
+      vulnerability.pkg_name.clone(),
+      vulnerability.installed_version.clone(),
+    )) {
+      continue;
+    }
+    let count: &mut I64 = match vulnerability.severity.as_str() {
+      "CRITICAL" => &mut scan.critical,
+      "HIGH" => &mut scan.high,
+      "MEDIUM" => &mut scan.medium,
+      "LOW" => &mut scan.low,
+      _ => &mut scan.unknown,
+    };
+    *count += 1;
+    if matches!(vulnerability.severity.as_str(), "CRITICAL" | "HIGH")
+    {
+      scan.vulnerabilities.push(ImageVulnerability {
+        id: vulnerability.vulnerability_id,
+        severity: vulnerability.severity,
+        package: vulnerability.pkg_name,
+        installed_version: vulnerability.installed_version,
+        fixed_version: vulnerability.fixed_version,
+        title: vulnerability.title,
+      });
+    }
+  }
+  Ok(scan)
+}
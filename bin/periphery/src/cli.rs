@@ -0,0 +1,288 @@
+//! Local commands run directly on the host,
+//! for debugging on-site when Core is unreachable.
+
+use std::{
+  net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+  path::Path,
+  str::FromStr,
+  time::Duration,
+};
+
+use anyhow::{Context, anyhow};
+use colored::Colorize;
+use comfy_table::{Attribute, Cell, Color, Table};
+use command::run_komodo_command;
+use komodo_client::entities::{
+  config::periphery::PeripheryCommand,
+  docker::container::ContainerStateStatusEnum,
+  server::ContainerRuntime,
+};
+use tokio::net::TcpStream;
+
+use crate::{
+  config::periphery_config,
+  docker::{container_cli, docker_client, set_container_runtime},
+};
+
+pub async fn handle(command: PeripheryCommand) -> anyhow::Result<()> {
+  match command {
+    PeripheryCommand::Containers { all, podman } => {
+      use_podman(podman);
+      list_containers(all).await
+    }
+    PeripheryCommand::Config { unsanitized } => {
+      print_config(unsanitized);
+      Ok(())
+    }
+    PeripheryCommand::Logs {
+      tail,
+      follow,
+      unit,
+      user,
+      podman,
+    } => {
+      use_podman(podman);
+      tail_logs(tail, follow, &unit, user)
+    }
+    PeripheryCommand::SelfTest { core, podman } => {
+      use_podman(podman);
+      self_test(core.as_deref()).await
+    }
+  }
+}
+
+fn use_podman(podman: bool) {
+  if podman {
+    set_container_runtime(ContainerRuntime::Podman);
+  }
+}
+
+async fn list_containers(all: bool) -> anyhow::Result<()> {
+  let mut containers = docker_client()
+    .list_containers()
+    .await
+    .context("Failed to list containers")?
+    .into_iter()
+    .filter(|container| {
+      all || container.state == ContainerStateStatusEnum::Running
+    })
+    .collect::<Vec<_>>();
+  containers
+    .sort_by(|a, b| a.state.cmp(&b.state).then(a.name.cmp(&b.name)));
+
+  let mut table = Table::new();
+  table.load_preset(comfy_table::presets::UTF8_FULL_CONDENSED);
+  table.set_header(
+    ["Container", "State", "Image", "Networks", "Status"]
+      .into_iter()
+      .map(|header| Cell::new(header).add_attribute(Attribute::Bold)),
+  );
+  for container in containers {
+    let color = match container.state {
+      ContainerStateStatusEnum::Running => Color::Green,
+      ContainerStateStatusEnum::Paused => Color::DarkYellow,
+      ContainerStateStatusEnum::Empty => Color::Grey,
+      _ => Color::Red,
+    };
+    let networks = if container.networks.is_empty() {
+      container.network_mode.unwrap_or_default()
+    } else {
+      container.networks.join(", ")
+    };
+    table.add_row([
+      Cell::new(container.name).add_attribute(Attribute::Bold),
+      Cell::new(container.state.to_string()).fg(color),
+      Cell::new(container.image.unwrap_or_default()),
+      Cell::new(networks),
+      Cell::new(container.status.unwrap_or_default()),
+    ]);
+  }
+  println!("{table}");
+  Ok(())
+}
+
+fn print_config(unsanitized: bool) {
+  let config = if unsanitized {
+    periphery_config().clone()
+  } else {
+    periphery_config().sanitized()
+  };
+  println!("{config:#?}");
+}
+
+/// Systemd installs write to the journal.
+/// When running in a container, the logs are read from the runtime,
+/// using the hostname which defaults to the container id.
+fn tail_logs(
+  tail: u64,
+  follow: bool,
+  unit: &str,
+  user: bool,
+) -> anyhow::Result<()> {
+  let follow = if follow { " -f" } else { "" };
+  let in_container = Path::new("/.dockerenv").exists()
+    || Path::new("/run/.containerenv").exists();
+  let command = if in_container {
+    format!(
+      "{} logs --tail {tail}{follow} \"$(hostname)\"",
+      container_cli()
+    )
+  } else {
+    let user = if user { " --user" } else { "" };
+    let unit = shell_escape::unix::escape(unit.into());
+    format!("journalctl{user} -u {unit} -n {tail}{follow} --no-pager")
+  };
+  // Inherit stdio so following streams straight to the terminal.
+  let status = std::process::Command::new("sh")
+    .arg("-c")
+    .arg(&command)
+    .status()
+    .with_context(|| format!("Failed to run '{command}'"))?;
+  if status.success() {
+    Ok(())
+  } else {
+    Err(anyhow!("'{command}' exited with {status}"))
+  }
+}
+
+async fn self_test(core: Option<&str>) -> anyhow::Result<()> {
+  let mut failed = false;
+
+  let log = run_komodo_command(
+    "Container Runtime",
+    None,
+    format!("{} version", container_cli()),
+  )
+  .await;
+  failed |= !report(
+    &format!("Container runtime ({})", container_cli()),
+    if log.success {
+      Ok(String::from("daemon is reachable"))
+    } else {
+      Err(anyhow!("{}", log.combined().trim()))
+    },
+  );
+
+  failed |= !report(
+    "Container listing",
+    async {
+      docker_client()
+        .list_containers()
+        .await
+        .map(|containers| format!("{} containers", containers.len()))
+    }
+    .await,
+  );
+
+  let config = periphery_config();
+  failed |= !report(
+    "Agent listener",
+    async {
+      let address = listener_address()?;
+      tokio::time::timeout(
+        Duration::from_secs(5),
+        TcpStream::connect(address),
+      )
+      .await
+      .context("Timed out connecting")?
+      .with_context(|| {
+        format!("Nothing is listening on {address}")
+      })?;
+      let protocol =
+        if config.ssl_enabled { "https" } else { "http" };
+      anyhow::Ok(format!("listening on {protocol}://{address}"))
+    }
+    .await,
+  );
+
+  if let Some(core) = core {
+    failed |= !report("Core", check_core(core).await);
+  } else {
+    println!(
+      "{}: Pass '--core <ADDRESS>' to also check reaching Komodo Core",
+      "INFO".dimmed()
+    );
+  }
+
+  if config.passkeys.is_empty() {
+    println!(
+      "{}: No passkeys are configured, any Core which can reach the agent is accepted",
+      "WARN".yellow()
+    );
+  }
+  if !config.allowed_ips.is_empty() {
+    println!(
+      "{}: Core must connect from one of the allowed ips: {}",
+      "INFO".dimmed(),
+      config
+        .allowed_ips
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+    );
+  }
+
+  if failed {
+    Err(anyhow!("Self test failed"))
+  } else {
+    Ok(())
+  }
+}
+
+/// Connects to the agent's own port, using loopback
+/// when bound on the unspecified address.
+fn listener_address() -> anyhow::Result<SocketAddr> {
+  let config = periphery_config();
+  let bind_ip =
+    config.bind_ip.trim_start_matches('[').trim_end_matches(']');
+  let ip = IpAddr::from_str(bind_ip)
+    .with_context(|| format!("invalid bind ip: {bind_ip}"))?;
+  let ip = match ip {
+    IpAddr::V4(ip) if ip.is_unspecified() => {
+      IpAddr::V4(Ipv4Addr::LOCALHOST)
+    }
+    IpAddr::V6(ip) if ip.is_unspecified() => {
+      IpAddr::V6(Ipv6Addr::LOCALHOST)
+    }
+    ip => ip,
+  };
+  Ok(SocketAddr::new(ip, config.port))
+}
+
+/// Core doesn't need to be reachable from the agent,
+/// as Core connects to Periphery. This checks the network
+/// path between the hosts, using an unauthenticated Core endpoint.
+async fn check_core(core: &str) -> anyhow::Result<String> {
+  let url =
+    format!("{}/auth/GetLoginOptions", core.trim_end_matches('/'));
+  let res = reqwest::Client::builder()
+    .timeout(Duration::from_secs(10))
+    .build()
+    .context("Failed to build http client")?
+    .post(&url)
+    .json(&serde_json::json!({}))
+    .send()
+    .await
+    .with_context(|| format!("Failed to reach Core at {url}"))?;
+  let status = res.status();
+  if status.is_success() {
+    Ok(format!("reachable at {core}"))
+  } else {
+    Err(anyhow!("Core at {url} responded with {status}"))
+  }
+}
+
+/// Prints the check result, returning whether it passed.
+fn report(check: &str, res: anyhow::Result<String>) -> bool {
+  match res {
+    Ok(msg) => {
+      println!("{} {}: {msg}", "OK".green().bold(), check.bold());
+      true
+    }
+    Err(e) => {
+      println!("{} {}: {e:#}", "FAILED".red().bold(), check.bold());
+      false
+    }
+  }
+}
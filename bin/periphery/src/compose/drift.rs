@@ -0,0 +1,231 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::Context;
+use komodo_client::entities::{
+  docker::container::Container, stack::StackServiceDrift,
+};
+use serde::Deserialize;
+use serde_yaml_ng::Value;
+
+use crate::docker::docker_client;
+
+const PROJECT_LABEL: &str = "com.docker.compose.project";
+const SERVICE_LABEL: &str = "com.docker.compose.service";
+/// Set on containers from `docker compose run`.
+const ONEOFF_LABEL: &str = "com.docker.compose.oneoff";
+
+/// The parts of the `docker compose config` output
+/// which are compared with the running containers.
+#[derive(Deserialize)]
+struct DriftComposeFile {
+  #[serde(default)]
+  services: HashMap<String, DriftComposeService>,
+}
+
+#[derive(Deserialize)]
+struct DriftComposeService {
+  image: Option<String>,
+  #[serde(default)]
+  environment: Value,
+  #[serde(default)]
+  labels: Value,
+  #[serde(default)]
+  deploy: Value,
+}
+
+/// Compares the containers of the compose project
+/// with the services in the deployed compose config.
+pub async fn get_compose_runtime_drift(
+  project: &str,
+  compose_config: &str,
+) -> anyhow::Result<Vec<StackServiceDrift>> {
+  let compose =
+    serde_yaml_ng::from_str::<DriftComposeFile>(compose_config)
+      .context("Failed to parse deployed compose config")?;
+
+  let docker = docker_client();
+  let mut containers = HashMap::<String, Vec<Container>>::new();
+  for container in docker
+    .list_containers()
+    .await
+    .context("Failed to list containers")?
+    .into_iter()
+    .filter(|container| {
+      container.labels.get(PROJECT_LABEL).map(String::as_str)
+        == Some(project)
+        && container.labels.get(ONEOFF_LABEL).map(String::as_str)
+          != Some("True")
+    })
+  {
+    let service = container
+      .labels
+      .get(SERVICE_LABEL)
+      .cloned()
+      .unwrap_or_default();
+    let container = docker
+      .inspect_container(&container.name)
+      .await
+      .with_context(|| {
+      format!("Failed to inspect container {}", container.name)
+    })?;
+    containers.entry(service).or_default().push(container);
+  }
+
+  let mut drift = Vec::new();
+
+  for (service, config) in &compose.services {
+    let mut differences = Vec::new();
+    let Some(service_containers) = containers.remove(service) else {
+      // Scaled to zero on purpose.
+      if config
+        .deploy
+        .get("replicas")
+        .and_then(Value::as_u64)
+        .is_some_and(|replicas| replicas == 0)
+      {
+        continue;
+      }
+      drift.push(StackServiceDrift {
+        service: service.clone(),
+        differences: vec![String::from("no container")],
+      });
+      continue;
+    };
+    let expected_image_id = match &config.image {
+      Some(image) => docker
+        .inspect_image(image)
+        .await
+        .ok()
+        .and_then(|image| image.id),
+      None => None,
+    };
+    for container in &service_containers {
+      for difference in container_drift(
+        container,
+        config,
+        expected_image_id.as_deref(),
+      ) {
+        if !differences.contains(&difference) {
+          differences.push(difference);
+        }
+      }
+    }
+    if !differences.is_empty() {
+      drift.push(StackServiceDrift {
+        service: service.clone(),
+        differences,
+      });
+    }
+  }
+
+  // Containers labeled with the project,
+  // but not defined in the compose file.
+  for service in containers.into_keys() {
+    drift.push(StackServiceDrift {
+      service,
+      differences: vec![String::from(
+        "not defined in the compose file",
+      )],
+    });
+  }
+
+  drift.sort_by(|a, b| a.service.cmp(&b.service));
+  Ok(drift)
+}
+
+fn container_drift(
+  container: &Container,
+  service: &DriftComposeService,
+  expected_image_id: Option<&str>,
+) -> Vec<String> {
+  let mut differences = Vec::new();
+  let container_config = container.config.as_ref();
+
+  if let Some(image) = &service.image {
+    let running = container_config
+      .and_then(|config| config.image.as_deref())
+      .unwrap_or_default();
+    if running != image {
+      differences
+        .push(format!("image: {running} (expected {image})"));
+    } else if let (Some(expected), Some(running)) =
+      (expected_image_id, container.image.as_deref())
+      && expected != running
+    {
+      differences.push(String::from(
+        "image digest: the container runs an older pull of the image",
+      ));
+    }
+  }
+
+  let env = container_config
+    .map(|config| {
+      config
+        .env
+        .iter()
+        .filter_map(|entry| entry.split_once('='))
+        .collect::<HashMap<_, _>>()
+    })
+    .unwrap_or_default();
+  // Only the keys are included, the values may be secret.
+  let env_keys = key_values(&service.environment)
+    .into_iter()
+    .filter(|(key, value)| {
+      value.as_deref().is_some_and(|value| {
+        env.get(key.as_str()).copied() != Some(value)
+      })
+    })
+    .map(|(key, _)| key)
+    .collect::<Vec<_>>();
+  if !env_keys.is_empty() {
+    differences.push(format!("environment: {}", env_keys.join(", ")));
+  }
+
+  let labels = container_config.map(|config| &config.labels);
+  let label_keys = key_values(&service.labels)
+    .into_iter()
+    .filter(|(key, value)| {
+      value.as_ref().is_some_and(|value| {
+        labels.and_then(|labels| labels.get(key)) != Some(value)
+      })
+    })
+    .map(|(key, _)| key)
+    .collect::<Vec<_>>();
+  if !label_keys.is_empty() {
+    differences.push(format!("labels: {}", label_keys.join(", ")));
+  }
+
+  differences
+}
+
+/// Reads a compose `environment` / `labels` field,
+/// in either the mapping or `KEY=value` list syntax.
+fn key_values(value: &Value) -> BTreeMap<String, Option<String>> {
+  match value {
+    Value::Mapping(mapping) => mapping
+      .iter()
+      .filter_map(|(key, value)| {
+        let key = key.as_str()?.to_string();
+        let value = match value {
+          Value::Null => None,
+          Value::String(value) => Some(value.clone()),
+          Value::Bool(value) => Some(value.to_string()),
+          Value::Number(value) => Some(value.to_string()),
+          _ => return None,
+        };
+        Some((key, value))
+      })
+      .collect(),
+    Value::Sequence(entries) => entries
+      .iter()
+      .filter_map(Value::as_str)
+      .map(|entry| match entry.split_once('=') {
+        Some((key, value)) => {
+          (key.to_string(), Some(value.to_string()))
+        }
+        None => (entry.to_string(), None),
+      })
+      .collect(),
+    _ => BTreeMap::new(),
+  }
+}
@@ -3,21 +3,24 @@ use std::{fmt::Write, path::PathBuf};
 use anyhow::{Context, anyhow};
 use command::run_komodo_command;
 use komodo_client::entities::{
-  RepoExecutionArgs, repo::Repo, stack::Stack,
-  to_path_compatible_name,
+  RepoExecutionArgs, repo::Repo, server::ContainerRuntime,
+  stack::Stack, to_path_compatible_name,
 };
 use periphery_client::api::{
   compose::ComposeUpResponse, git::PullOrCloneRepo,
 };
 use resolver_api::Resolve;
 
-use crate::config::periphery_config;
+use crate::{config::periphery_config, docker::container_runtime};
 
+pub mod drift;
 pub mod up;
 pub mod write;
 
 pub fn docker_compose() -> &'static str {
-  if periphery_config().legacy_compose_cli {
+  if container_runtime() == ContainerRuntime::Podman {
+    "podman compose"
+  } else if periphery_config().legacy_compose_cli {
     "docker-compose"
   } else {
     "docker compose"
@@ -7,7 +7,10 @@ use komodo_client::entities::{
   stack::{Stack, StackRemoteFileContents},
   update::Log,
 };
-use periphery_client::api::compose::ComposeUpResponse;
+use periphery_client::api::compose::{
+  ComposeUpResponse, ListComposeProjects,
+};
+use resolver_api::Resolve;
 use tokio::fs;
 
 use crate::docker::docker_login;
@@ -108,3 +111,50 @@ pub async fn maybe_login_registry(
     ));
   }
 }
+
+/// Warns if a compose project with the same name is already
+/// running on the host from files outside this stack's run directory.
+/// This happens when another Stack (possibly on another Komodo instance),
+/// or manual `docker compose` usage, manages the same project name.
+/// `docker compose up` would silently take over those containers.
+pub async fn check_project_ownership(
+  project_name: &str,
+  run_directory: &Path,
+  logs: &mut Vec<Log>,
+) {
+  // Not critical to the deploy, skip the check if listing fails.
+  let Ok(projects) =
+    ListComposeProjects {}.resolve(&crate::api::Args).await
+  else {
+    return;
+  };
+  let Some(project) = projects
+    .into_iter()
+    .find(|project| project.name == project_name)
+  else {
+    return;
+  };
+  let config_files = project
+    .compose_files
+    .iter()
+    .map(|file| file.trim())
+    .filter(|file| !file.is_empty())
+    .collect::<Vec<_>>();
+  if config_files.is_empty()
+    || config_files
+      .iter()
+      .any(|file| Path::new(file).starts_with(run_directory))
+  {
+    return;
+  }
+  logs.push(Log::simple(
+    "Project Ownership",
+    format!(
+      "WARNING: Compose project '{project_name}' already exists on this host \
+      using files outside of the run directory {run_directory:?}:\n\n{}\n\n\
+      Another Stack or manual 'docker compose' usage may be managing the same project. \
+      Deploying will take over its containers.",
+      config_files.join("\n")
+    ),
+  ));
+}
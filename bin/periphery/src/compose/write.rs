@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 
 use anyhow::{Context, anyhow};
 use formatting::format_serror;
@@ -8,11 +8,13 @@ use komodo_client::entities::{
 };
 use periphery_client::api::{
   compose::{
-    ComposePullResponse, ComposeRunResponse, ComposeUpResponse,
+    ComposePullResponse, ComposeRenderResponse, ComposeRunResponse,
+    ComposeUpResponse,
   },
   git::{CloneRepo, PullOrCloneRepo},
 };
 use resolver_api::Resolve;
+use serde_json::{Value, json};
 use tokio::fs;
 
 use crate::{config::periphery_config, helpers};
@@ -45,6 +47,12 @@ impl WriteStackRes for &mut ComposePullResponse {
   }
 }
 
+impl WriteStackRes for &mut ComposeRenderResponse {
+  fn logs(&mut self) -> &mut Vec<Log> {
+    &mut self.logs
+  }
+}
+
 impl WriteStackRes for &mut ComposeRunResponse {
   fn logs(&mut self) -> &mut Vec<Log> {
     &mut self.logs
@@ -58,8 +66,62 @@ pub async fn write_stack<'a>(
   stack: &'a Stack,
   repo: Option<&Repo>,
   git_token: Option<String>,
+  oci_token: Option<String>,
+  replacers: Vec<(String, String)>,
+  res: impl WriteStackRes,
+) -> anyhow::Result<(
+  // run_directory
+  PathBuf,
+  // env_file_path
+  Option<&'a str>,
+)> {
+  write_stack_in(
+    stack, repo, git_token, oci_token, replacers, res, None,
+  )
+  .await
+}
+
+/// Like [write_stack], but writes the files, clone or artifact
+/// to the `render_dir` instead of the Stack's run directory,
+/// so rendering doesn't change what the next execution runs.
+/// Repo on_clone / on_pull are not run.
+///
+/// Files on host stacks are still read from the host, only the
+/// env file should be redirected, by making its path absolute.
+pub async fn write_stack_to_render_dir<'a>(
+  stack: &'a Stack,
+  repo: Option<&Repo>,
+  git_token: Option<String>,
+  oci_token: Option<String>,
+  replacers: Vec<(String, String)>,
+  res: impl WriteStackRes,
+  render_dir: &Path,
+) -> anyhow::Result<(
+  // run_directory
+  PathBuf,
+  // env_file_path
+  Option<&'a str>,
+)> {
+  write_stack_in(
+    stack,
+    repo,
+    git_token,
+    oci_token,
+    replacers,
+    res,
+    Some(render_dir),
+  )
+  .await
+}
+
+async fn write_stack_in<'a>(
+  stack: &'a Stack,
+  repo: Option<&Repo>,
+  git_token: Option<String>,
+  oci_token: Option<String>,
   replacers: Vec<(String, String)>,
   res: impl WriteStackRes,
+  render_dir: Option<&Path>,
 ) -> anyhow::Result<(
   // run_directory
   PathBuf,
@@ -68,13 +130,17 @@ pub async fn write_stack<'a>(
 )> {
   if stack.config.files_on_host {
     write_stack_files_on_host(stack, res).await
+  } else if !stack.config.oci_artifact.is_empty() {
+    write_stack_oci_artifact(stack, oci_token, res, render_dir).await
   } else if let Some(repo) = repo {
-    write_stack_linked_repo(stack, repo, git_token, replacers, res)
-      .await
+    write_stack_linked_repo(
+      stack, repo, git_token, replacers, res, render_dir,
+    )
+    .await
   } else if !stack.config.repo.is_empty() {
-    write_stack_inline_repo(stack, git_token, res).await
+    write_stack_inline_repo(stack, git_token, res, render_dir).await
   } else {
-    write_stack_ui_defined(stack, res).await
+    write_stack_ui_defined(stack, res, render_dir).await
   }
 }
 
@@ -120,18 +186,22 @@ async fn write_stack_linked_repo<'a>(
   git_token: Option<String>,
   replacers: Vec<(String, String)>,
   mut res: impl WriteStackRes,
+  render_dir: Option<&Path>,
 ) -> anyhow::Result<(
   // run_directory
   PathBuf,
   // env_file_path
   Option<&'a str>,
 )> {
-  let root = periphery_config()
-    .repo_dir()
-    .join(to_path_compatible_name(&repo.name))
-    .join(&repo.config.path)
-    .components()
-    .collect::<PathBuf>();
+  let root = match render_dir {
+    Some(render_dir) => render_dir.to_path_buf(),
+    None => periphery_config()
+      .repo_dir()
+      .join(to_path_compatible_name(&repo.name))
+      .join(&repo.config.path)
+      .components()
+      .collect::<PathBuf>(),
+  };
 
   let mut args: RepoExecutionArgs = repo.into();
   // Set the clone destination to the one created for this run
@@ -146,10 +216,12 @@ async fn write_stack_linked_repo<'a>(
     .display()
     .to_string();
 
-  let on_clone = (!repo.config.on_clone.is_none())
-    .then_some(repo.config.on_clone.clone());
-  let on_pull = (!repo.config.on_pull.is_none())
-    .then_some(repo.config.on_pull.clone());
+  let on_clone = (render_dir.is_none()
+    && !repo.config.on_clone.is_none())
+  .then_some(repo.config.on_clone.clone());
+  let on_pull = (render_dir.is_none()
+    && !repo.config.on_pull.is_none())
+  .then_some(repo.config.on_pull.clone());
 
   let clone_res = if stack.config.reclone {
     CloneRepo {
@@ -217,18 +289,22 @@ async fn write_stack_inline_repo(
   stack: &Stack,
   git_token: Option<String>,
   mut res: impl WriteStackRes,
+  render_dir: Option<&Path>,
 ) -> anyhow::Result<(
   // run_directory
   PathBuf,
   // env_file_path
   Option<&str>,
 )> {
-  let root = periphery_config()
-    .stack_dir()
-    .join(to_path_compatible_name(&stack.name))
-    .join(&stack.config.clone_path)
-    .components()
-    .collect::<PathBuf>();
+  let root = match render_dir {
+    Some(render_dir) => render_dir.to_path_buf(),
+    None => periphery_config()
+      .stack_dir()
+      .join(to_path_compatible_name(&stack.name))
+      .join(&stack.config.clone_path)
+      .components()
+      .collect::<PathBuf>(),
+  };
 
   let mut args: RepoExecutionArgs = stack.into();
   // Set the clone destination to the one created for this run
@@ -298,9 +374,117 @@ async fn write_stack_inline_repo(
   ))
 }
 
+async fn write_stack_oci_artifact(
+  stack: &Stack,
+  oci_token: Option<String>,
+  mut res: impl WriteStackRes,
+  render_dir: Option<&Path>,
+) -> anyhow::Result<(
+  // run_directory
+  PathBuf,
+  // env_file_path
+  Option<&str>,
+)> {
+  let root = match render_dir {
+    Some(render_dir) => render_dir.to_path_buf(),
+    None => periphery_config()
+      .stack_dir()
+      .join(to_path_compatible_name(&stack.name))
+      .components()
+      .collect::<PathBuf>(),
+  };
+
+  let artifact = &stack.config.oci_artifact;
+  let account = &stack.config.oci_account;
+
+  let token = if account.is_empty() {
+    None
+  } else if let Some(token) = oci_token {
+    Some(token)
+  } else {
+    match oci::registry_domain(artifact).and_then(|domain| {
+      helpers::registry_token(&domain, account).map(String::from)
+    }) {
+      Ok(token) => Some(token),
+      Err(e) => {
+        let error = format_serror(&e.into());
+        res
+          .logs()
+          .push(Log::error("Missing registry token", error.clone()));
+        res.add_remote_error(FileContents {
+          path: Default::default(),
+          contents: error,
+        });
+        return Err(anyhow!(
+          "failed to find required registry token, stopping run"
+        ));
+      }
+    }
+  };
+
+  let pulled = match oci::pull_oci_artifact(
+    artifact,
+    token.as_deref().map(|token| (account.as_str(), token)),
+    &root,
+  )
+  .await
+  {
+    Ok(pulled) => pulled,
+    Err(e) => {
+      let error = format_serror(
+        &e.context(format!("Failed to pull OCI artifact {artifact}"))
+          .into(),
+      );
+      res
+        .logs()
+        .push(Log::error("Pull OCI Artifact", error.clone()));
+      res.add_remote_error(FileContents {
+        path: Default::default(),
+        contents: error,
+      });
+      return Ok((root, None));
+    }
+  };
+
+  res.logs().push(Log::simple(
+    "Pull OCI Artifact",
+    format!("Pulled {artifact} | digest: {}", pulled.digest),
+  ));
+  res.set_commit_hash(Some(oci::short_digest(&pulled.digest)));
+  res.set_commit_message(Some(
+    pulled
+      .revision
+      .unwrap_or_else(|| format!("{artifact}@{}", pulled.digest)),
+  ));
+
+  let run_directory = root
+    .join(&stack.config.run_directory)
+    .components()
+    .collect::<PathBuf>();
+
+  let env_file_path = environment::write_env_file(
+    &stack.config.env_vars()?,
+    run_directory.as_path(),
+    &stack.config.env_file_path,
+    res.logs(),
+  )
+  .await;
+  if !all_logs_success(res.logs()) {
+    return Err(anyhow!("Failed to write env file, stopping run"));
+  }
+
+  Ok((
+    run_directory,
+    env_file_path
+      .is_some()
+      .then_some(&stack.config.env_file_path),
+  ))
+}
+
 async fn write_stack_ui_defined(
   stack: &Stack,
   mut res: impl WriteStackRes,
+  render_dir: Option<&Path>,
 ) -> anyhow::Result<(
   // run_directory
   PathBuf,
@@ -313,11 +497,14 @@ async fn write_stack_ui_defined(
     ));
   }
 
-  let run_directory = periphery_config()
-    .stack_dir()
-    .join(to_path_compatible_name(&stack.name))
-    .components()
-    .collect::<PathBuf>();
+  let run_directory = match render_dir {
+    Some(render_dir) => render_dir.to_path_buf(),
+    None => periphery_config()
+      .stack_dir()
+      .join(to_path_compatible_name(&stack.name))
+      .components()
+      .collect::<PathBuf>(),
+  };
 
   // Ensure run directory exists
   fs::create_dir_all(&run_directory).await.with_context(|| {
@@ -363,6 +550,69 @@ async fn write_stack_ui_defined(
   ))
 }
 
+/// Writes the remote include files fetched by Core.
+/// The paths must stay inside the run directory.
+pub async fn write_include_files(
+  run_directory: &Path,
+  files: &[FileContents],
+) -> anyhow::Result<()> {
+  for file in files {
+    let relative = Path::new(&file.path);
+    if !relative
+      .components()
+      .all(|component| matches!(component, Component::Normal(_)))
+    {
+      return Err(anyhow!(
+        "Include file path {} must be relative to the run directory",
+        file.path
+      ));
+    }
+    let path = run_directory.join(relative);
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent).await.with_context(|| {
+        format!("Failed to create include directory at {parent:?}")
+      })?;
+    }
+    fs::write(&path, &file.contents).await.with_context(|| {
+      format!("Failed to write include file to {path:?}")
+    })?;
+  }
+  Ok(())
+}
+
+/// The compose override file adding the Stack `dns` and `extra_hosts`
+/// to every service, written to the run directory.
+pub const DNS_OVERRIDE_FILE: &str = ".komodo.dns.compose.yaml";
+
+/// Writes the compose override file applying `dns` and `extra_hosts`
+/// to all the services, and returns its path relative to the run directory.
+pub async fn write_dns_override(
+  run_directory: &Path,
+  services: &[String],
+  dns: &[String],
+  extra_hosts: &[String],
+) -> anyhow::Result<&'static str> {
+  let mut service = serde_json::Map::new();
+  if !dns.is_empty() {
+    service.insert(String::from("dns"), json!(dns));
+  }
+  if !extra_hosts.is_empty() {
+    service.insert(String::from("extra_hosts"), json!(extra_hosts));
+  }
+  let services = services
+    .iter()
+    .map(|name| (name.clone(), Value::Object(service.clone())))
+    .collect::<serde_json::Map<_, _>>();
+  let contents =
+    serde_yaml_ng::to_string(&json!({ "services": services }))
+      .context("Failed to serialize dns override")?;
+  let path = run_directory.join(DNS_OVERRIDE_FILE);
+  fs::write(&path, contents).await.with_context(|| {
+    format!("Failed to write dns override to {path:?}")
+  })?;
+  Ok(DNS_OVERRIDE_FILE)
+}
+
 fn stack_git_token<R: WriteStackRes>(
   core_token: Option<String>,
   args: &RepoExecutionArgs,
@@ -71,6 +71,22 @@ pub fn periphery_config() -> &'static PeripheryConfig {
       disable_container_exec: env
         .periphery_disable_container_exec
         .unwrap_or(config.disable_container_exec),
+      allowed_terminal_commands: env
+        .periphery_allowed_terminal_commands
+        .unwrap_or(config.allowed_terminal_commands),
+      terminal_profiles: config.terminal_profiles,
+      terminal_idle_timeout: env
+        .periphery_terminal_idle_timeout
+        .or(config.terminal_idle_timeout),
+      terminal_max_lifetime: env
+        .periphery_terminal_max_lifetime
+        .or(config.terminal_max_lifetime),
+      max_terminals: env
+        .periphery_max_terminals
+        .unwrap_or(config.max_terminals),
+      terminal_file_root: env
+        .periphery_terminal_file_root
+        .or(config.terminal_file_root),
       stats_polling_rate: env
         .periphery_stats_polling_rate
         .unwrap_or(config.stats_polling_rate),
@@ -80,6 +96,12 @@ pub fn periphery_config() -> &'static PeripheryConfig {
       legacy_compose_cli: env
         .periphery_legacy_compose_cli
         .unwrap_or(config.legacy_compose_cli),
+      cache_volumes_prune_interval: env
+        .periphery_cache_volumes_prune_interval
+        .or(config.cache_volumes_prune_interval),
+      cache_volumes_max_size: env
+        .periphery_cache_volumes_max_size
+        .unwrap_or(config.cache_volumes_max_size),
       logging: LogConfig {
         level: args
           .log_level
@@ -113,6 +135,11 @@ pub fn periphery_config() -> &'static PeripheryConfig {
         env.periphery_passkeys,
       )
       .unwrap_or(config.passkeys),
+      core_public_keys: maybe_read_list_from_file(
+        env.periphery_core_public_keys_file,
+        env.periphery_core_public_keys,
+      )
+      .unwrap_or(config.core_public_keys),
       include_disk_mounts: env
         .periphery_include_disk_mounts
         .unwrap_or(config.include_disk_mounts),
@@ -131,6 +158,7 @@ pub fn periphery_config() -> &'static PeripheryConfig {
       secrets: config.secrets,
       git_providers: config.git_providers,
       docker_registries: config.docker_registries,
+      hooks: config.hooks,
     }
   })
 }
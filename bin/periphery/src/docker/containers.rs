@@ -46,21 +46,43 @@ impl DockerClient {
           state: convert_summary_container_state(
             container.state.context("no container state")?,
           ),
+          health: container
+            .status
+            .as_deref()
+            .and_then(HealthStatusEnum::from_container_status),
           status: container.status,
           network_mode: container
             .host_config
             .and_then(|config| config.network_mode),
           networks: container
             .network_settings
+            .as_ref()
             .and_then(|settings| {
-              settings.networks.map(|networks| {
+              settings.networks.as_ref().map(|networks| {
                 let mut keys =
-                  networks.into_keys().collect::<Vec<_>>();
+                  networks.keys().cloned().collect::<Vec<_>>();
                 keys.sort();
                 keys
               })
             })
             .unwrap_or_default(),
+          ip_addresses: container
+            .network_settings
+            .and_then(|settings| settings.networks)
+            .map(|networks| {
+              let mut networks =
+                networks.into_iter().collect::<Vec<_>>();
+              networks.sort_by(|a, b| a.0.cmp(&b.0));
+              networks
+                .into_iter()
+                .flat_map(|(_, network)| {
+                  [network.ip_address, network.global_ipv6_address]
+                })
+                .flatten()
+                .filter(|ip| !ip.is_empty())
+                .collect()
+            })
+            .unwrap_or_default(),
           ports: container
             .ports
             .map(|ports| {
@@ -1,9 +1,14 @@
-use std::sync::OnceLock;
+use std::{
+  path::PathBuf,
+  sync::{OnceLock, RwLock},
+};
 
-use anyhow::anyhow;
+use anyhow::{Context, anyhow};
 use bollard::Docker;
 use command::run_komodo_command;
-use komodo_client::entities::{TerminationSignal, update::Log};
+use komodo_client::entities::{
+  TerminationSignal, server::ContainerRuntime, update::Log,
+};
 use run_command::async_run_command;
 
 pub mod stats;
@@ -13,22 +18,123 @@ mod images;
 mod networks;
 mod volumes;
 
+fn container_runtime_state() -> &'static RwLock<ContainerRuntime> {
+  static CONTAINER_RUNTIME: OnceLock<RwLock<ContainerRuntime>> =
+    OnceLock::new();
+  CONTAINER_RUNTIME.get_or_init(Default::default)
+}
+
+/// The container runtime configured on the Server in Core.
+/// This is sent along with each request from Core.
+pub fn container_runtime() -> ContainerRuntime {
+  *container_runtime_state().read().unwrap()
+}
+
+pub fn set_container_runtime(runtime: ContainerRuntime) {
+  let mut current = container_runtime_state().write().unwrap();
+  if *current != runtime {
+    info!("Using container runtime: {runtime}");
+    *current = runtime;
+  }
+}
+
+/// The CLI used to run container commands.
+pub fn container_cli() -> &'static str {
+  match container_runtime() {
+    ContainerRuntime::Docker => "docker",
+    ContainerRuntime::Podman => "podman",
+  }
+}
+
 pub fn docker_client() -> &'static DockerClient {
   static DOCKER_CLIENT: OnceLock<DockerClient> = OnceLock::new();
-  DOCKER_CLIENT.get_or_init(Default::default)
+  static PODMAN_CLIENT: OnceLock<DockerClient> = OnceLock::new();
+  match container_runtime() {
+    ContainerRuntime::Docker => {
+      DOCKER_CLIENT.get_or_init(DockerClient::docker)
+    }
+    ContainerRuntime::Podman => {
+      PODMAN_CLIENT.get_or_init(DockerClient::podman)
+    }
+  }
 }
 
 pub struct DockerClient {
   docker: Docker,
 }
 
-impl Default for DockerClient {
-  fn default() -> DockerClient {
+impl DockerClient {
+  fn docker() -> DockerClient {
     DockerClient {
       docker: Docker::connect_with_defaults()
         .expect("failed to connect to docker daemon"),
     }
   }
+
+  /// Podman serves a Docker compatible API on its socket.
+  fn podman() -> DockerClient {
+    DockerClient {
+      docker: Docker::connect_with_unix(
+        &podman_socket(),
+        120,
+        bollard::API_DEFAULT_VERSION,
+      )
+      .expect("failed to connect to podman socket"),
+    }
+  }
+
+  /// The version of the engine serving the API.
+  pub async fn version(&self) -> anyhow::Result<String> {
+    self
+      .docker
+      .version()
+      .await
+      .context("Failed to get docker version")?
+      .version
+      .context("Docker did not return a version")
+  }
+}
+
+/// Uses `CONTAINER_HOST` if set, otherwise the rootless socket
+/// for the user running Periphery if it exists, falling back
+/// to the rootful socket.
+fn podman_socket() -> String {
+  if let Ok(host) = std::env::var("CONTAINER_HOST")
+    && !host.is_empty()
+  {
+    return host;
+  }
+  if let Ok(runtime_dir) = std::env::var("XDG_RUNTIME_DIR") {
+    let path = PathBuf::from(runtime_dir).join("podman/podman.sock");
+    if path.exists() {
+      return format!("unix://{}", path.display());
+    }
+  }
+  String::from("unix:///run/podman/podman.sock")
+}
+
+/// The host path of the container runtime socket,
+/// for mounting into containers which use the runtime API.
+pub fn runtime_socket_path() -> String {
+  let host = match container_runtime() {
+    ContainerRuntime::Docker => std::env::var("DOCKER_HOST")
+      .unwrap_or_else(|_| {
+        String::from("unix:///var/run/docker.sock")
+      }),
+    ContainerRuntime::Podman => podman_socket(),
+  };
+  match host.strip_prefix("unix://") {
+    Some(path) => path.to_string(),
+    // Remote hosts can't be mounted, use the default local socket.
+    None => match container_runtime() {
+      ContainerRuntime::Docker => {
+        String::from("/var/run/docker.sock")
+      }
+      ContainerRuntime::Podman => {
+        String::from("/run/podman/podman.sock")
+      }
+    },
+  }
 }
 
 /// Returns whether build result should be pushed after build
@@ -47,7 +153,8 @@ pub async fn docker_login(
     None => crate::helpers::registry_token(domain, account)?,
   };
   let log = async_run_command(&format!(
-    "echo {registry_token} | docker login {domain} --username '{account}' --password-stdin",
+    "echo {registry_token} | {} login {domain} --username '{account}' --password-stdin",
+    container_cli()
   ))
   .await;
   if log.success() {
@@ -70,7 +177,7 @@ pub async fn docker_login(
 
 #[instrument]
 pub async fn pull_image(image: &str) -> Log {
-  let command = format!("docker pull {image}");
+  let command = format!("{} pull {image}", container_cli());
   run_komodo_command("Docker Pull", None, command).await
 }
 
@@ -79,11 +186,14 @@ pub fn stop_container_command(
   signal: Option<TerminationSignal>,
   time: Option<i32>,
 ) -> String {
+  // Podman stop doesn't support choosing the signal,
+  // it uses the container's configured stop signal.
   let signal = signal
+    .filter(|_| container_runtime() == ContainerRuntime::Docker)
     .map(|signal| format!(" --signal {signal}"))
     .unwrap_or_default();
   let time = time
     .map(|time| format!(" --time {time}"))
     .unwrap_or_default();
-  format!("docker stop{signal}{time} {container_name}")
+  format!("{} stop{signal}{time} {container_name}", container_cli())
 }
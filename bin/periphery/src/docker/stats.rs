@@ -19,7 +19,10 @@ use komodo_client::entities::docker::{
 };
 use run_command::async_run_command;
 
-use crate::{config::periphery_config, docker::DockerClient};
+use crate::{
+  config::periphery_config,
+  docker::{DockerClient, container_cli},
+};
 
 pub type ContainerStatsMap = HashMap<String, ContainerStats>;
 
@@ -72,8 +75,10 @@ pub async fn get_container_stats(
     Some(name) => format!(" {name}"),
     None => "".to_string(),
   };
-  let command =
-    format!("docker stats{container_name} --no-stream {format}");
+  let command = format!(
+    "{} stats{container_name} --no-stream {format}",
+    container_cli()
+  );
   let output = async_run_command(&command).await;
   if output.success() {
     output
@@ -0,0 +1,128 @@
+//! Runs the executables configured in `hooks` at hook points,
+//! so sites can add custom host behavior.
+
+use std::{process::Stdio, time::Duration};
+
+use anyhow::Context;
+use command::run_komodo_command;
+use komodo_client::entities::{
+  config::periphery::{PeripheryHook, PeripheryHookEvent},
+  komodo_timestamp,
+  update::Log,
+};
+use serde_json::{Value, json};
+use tokio::io::AsyncWriteExt;
+
+use crate::config::periphery_config;
+
+/// Runs the hooks for the event in order, with the event
+/// and the `fields` passed as JSON on stdin.
+/// Stops after the first hook which fails.
+pub async fn run_hooks(
+  event: PeripheryHookEvent,
+  fields: Value,
+) -> Vec<Log> {
+  let mut input = json!({ "event": event });
+  if let (Some(input), Value::Object(fields)) =
+    (input.as_object_mut(), fields)
+  {
+    input.extend(fields);
+  }
+  let input = input.to_string();
+
+  let mut logs = Vec::new();
+  for hook in periphery_config()
+    .hooks
+    .iter()
+    .filter(|hook| hook.runs_on(event))
+  {
+    let log = run_hook(hook, event, &input).await;
+    let success = log.success;
+    logs.push(log);
+    if !success {
+      break;
+    }
+  }
+  logs
+}
+
+/// Runs the prune command, unless a `pre_prune` hook fails.
+/// Then the log of the failed hook is returned instead.
+pub async fn prune_with_hooks(
+  prune: &str,
+  stage: &str,
+  command: String,
+) -> Log {
+  if let Some(failed) =
+    run_hooks(PeripheryHookEvent::PrePrune, json!({ "prune": prune }))
+      .await
+      .into_iter()
+      .find(|log| !log.success)
+  {
+    return failed;
+  }
+  run_komodo_command(stage, None, command).await
+}
+
+async fn run_hook(
+  hook: &PeripheryHook,
+  event: PeripheryHookEvent,
+  input: &str,
+) -> Log {
+  let start_ts = komodo_timestamp();
+  let command = std::iter::once(hook.path.display().to_string())
+    .chain(hook.args.iter().cloned())
+    .collect::<Vec<_>>()
+    .join(" ");
+  let (stdout, stderr, success) = match spawn_hook(hook, input).await
+  {
+    Ok(output) => (
+      String::from_utf8_lossy(&output.stdout).to_string(),
+      String::from_utf8_lossy(&output.stderr).to_string(),
+      output.status.success(),
+    ),
+    Err(e) => (String::new(), format!("{e:#}"), false),
+  };
+  if !success {
+    warn!("Hook {} failed on {event} | {}", hook.name, stderr.trim());
+  }
+  Log {
+    stage: format!("Hook {} ({event})", hook.name),
+    command,
+    stdout,
+    stderr,
+    success,
+    start_ts,
+    end_ts: komodo_timestamp(),
+  }
+}
+
+async fn spawn_hook(
+  hook: &PeripheryHook,
+  input: &str,
+) -> anyhow::Result<std::process::Output> {
+  let mut child = tokio::process::Command::new(&hook.path)
+    .args(&hook.args)
+    .stdin(Stdio::piped())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    // Dropped on timeout, which kills the hook.
+    .kill_on_drop(true)
+    .spawn()
+    .with_context(|| {
+      format!("Failed to spawn hook at {:?}", hook.path)
+    })?;
+  let run = async {
+    if let Some(mut stdin) = child.stdin.take() {
+      // The hook may exit without reading stdin.
+      let _ = stdin.write_all(input.as_bytes()).await;
+    }
+    child.wait_with_output().await
+  };
+  tokio::time::timeout(Duration::from_secs(hook.timeout_seconds), run)
+    .await
+    .with_context(|| {
+      format!("Hook timed out after {} seconds", hook.timeout_seconds)
+    })?
+    .context("Failed to get hook output")
+}
@@ -0,0 +1,476 @@
+//! Forwards container logs to external sinks,
+//! so the host doesn't need a separate logging agent.
+
+use std::{
+  collections::HashMap,
+  process::Stdio,
+  sync::{Mutex, OnceLock},
+  time::Duration,
+};
+
+use anyhow::{Context, anyhow};
+use aws_config::{BehaviorVersion, Region};
+use aws_sdk_s3::primitives::ByteStream;
+use chrono::{DateTime, SecondsFormat, Utc};
+use futures::{StreamExt, TryStreamExt};
+use komodo_client::entities::log_forwarding::{
+  LogSink, SyslogProtocol,
+};
+use periphery_client::api::log_forwarding::LogForward;
+use serde_json::json;
+use tokio::{
+  io::AsyncWriteExt,
+  net::{TcpStream, UdpSocket},
+  task::JoinHandle,
+};
+use tokio_util::codec::{FramedRead, LinesCodec};
+
+use crate::docker::container_cli;
+
+/// Wait before following the log again,
+/// eg. after the container stops.
+const RETRY_INTERVAL: Duration = Duration::from_secs(10);
+/// Send the batch every this many lines,
+/// without waiting for the flush interval.
+const MAX_BATCH_LINES: usize = 1_000;
+/// Lines kept while the sink is unreachable. Older lines are dropped.
+const MAX_BUFFERED_LINES: usize = 10_000;
+
+struct Forwarder {
+  forward: LogForward,
+  handle: JoinHandle<()>,
+}
+
+fn forwarders() -> &'static Mutex<HashMap<String, Forwarder>> {
+  static FORWARDERS: OnceLock<Mutex<HashMap<String, Forwarder>>> =
+    OnceLock::new();
+  FORWARDERS.get_or_init(Default::default)
+}
+
+/// Starts forwarding the new containers, and stops
+/// forwarding the containers which are removed or changed.
+pub fn set_log_forwards(forwards: Vec<LogForward>) {
+  let mut desired = forwards
+    .into_iter()
+    .map(|forward| (forward.container.clone(), forward))
+    .collect::<HashMap<_, _>>();
+  let mut forwarders = forwarders().lock().unwrap();
+  forwarders.retain(|container, forwarder| {
+    let keep = desired.get(container) == Some(&forwarder.forward);
+    if keep {
+      desired.remove(container);
+    } else {
+      forwarder.handle.abort();
+    }
+    keep
+  });
+  for (container, forward) in desired {
+    info!("Forwarding logs of container {container}");
+    let handle = tokio::spawn(forward_logs(forward.clone()));
+    forwarders.insert(container, Forwarder { forward, handle });
+  }
+}
+
+struct LogLine {
+  ts: DateTime<Utc>,
+  stderr: bool,
+  message: String,
+}
+
+async fn forward_logs(forward: LogForward) {
+  let sink = Sink::new(&forward).await;
+  // Only forward the lines written after forwarding starts,
+  // and continue from the last line after the log is followed again.
+  let mut since = Utc::now();
+  let mut batch = Vec::new();
+  loop {
+    if let Err(e) =
+      follow_log(&forward, &sink, &mut since, &mut batch).await
+    {
+      warn!(
+        "Failed to forward logs of container {} | {e:#}",
+        forward.container
+      );
+    }
+    tokio::time::sleep(RETRY_INTERVAL).await;
+  }
+}
+
+async fn follow_log(
+  forward: &LogForward,
+  sink: &Sink,
+  since: &mut DateTime<Utc>,
+  batch: &mut Vec<LogLine>,
+) -> anyhow::Result<()> {
+  let mut child = tokio::process::Command::new(container_cli())
+    .args(["logs", "--follow", "--timestamps", "--since"])
+    .arg(since.to_rfc3339_opts(SecondsFormat::Nanos, true))
+    .arg(&forward.container)
+    .stdin(Stdio::null())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .kill_on_drop(true)
+    .spawn()
+    .context("Failed to spawn container log command")?;
+  let (Some(stdout), Some(stderr)) =
+    (child.stdout.take(), child.stderr.take())
+  else {
+    return Err(anyhow!("Failed to read container log output"));
+  };
+
+  // The container stderr is written to stderr.
+  let mut lines = futures::stream::select(
+    FramedRead::new(stdout, LinesCodec::new())
+      .map_ok(|line| (false, line)),
+    FramedRead::new(stderr, LinesCodec::new())
+      .map_ok(|line| (true, line)),
+  );
+  let mut interval = tokio::time::interval(sink.flush_interval());
+
+  loop {
+    tokio::select! {
+      line = lines.next() => {
+        let Some(line) = line else {
+          // The log ends when the container stops.
+          flush(forward, sink, batch).await;
+          return Ok(());
+        };
+        let (stderr, line) = line.context("Failed to read container log line")?;
+        let (ts, message) = line
+          .split_once(' ')
+          .and_then(|(ts, message)| {
+            let ts = DateTime::parse_from_rfc3339(ts).ok()?;
+            Some((ts.with_timezone(&Utc), message.to_string()))
+          })
+          .unwrap_or_else(|| (Utc::now(), line));
+        // '--since' includes lines at the timestamp.
+        *since = ts + chrono::Duration::nanoseconds(1);
+        batch.push(LogLine { ts, stderr, message });
+        if batch.len() % MAX_BATCH_LINES == 0 {
+          flush(forward, sink, batch).await;
+        }
+      }
+      _ = interval.tick() => flush(forward, sink, batch).await,
+    }
+  }
+}
+
+/// Sends the batch, keeping it to retry if the sink fails.
+async fn flush(
+  forward: &LogForward,
+  sink: &Sink,
+  batch: &mut Vec<LogLine>,
+) {
+  if batch.is_empty() {
+    return;
+  }
+  match sink.send(forward, batch).await {
+    Ok(_) => batch.clear(),
+    Err(e) => {
+      warn!(
+        "Failed to send logs of container {} | {e:#}",
+        forward.container
+      );
+      if batch.len() > MAX_BUFFERED_LINES {
+        batch.drain(..batch.len() - MAX_BUFFERED_LINES);
+      }
+    }
+  }
+}
+
+enum Sink {
+  Loki {
+    url: String,
+    tenant: String,
+  },
+  Syslog {
+    address: String,
+    protocol: SyslogProtocol,
+  },
+  S3 {
+    client: aws_sdk_s3::Client,
+    bucket: String,
+    prefix: String,
+  },
+}
+
+impl Sink {
+  async fn new(forward: &LogForward) -> Sink {
+    match &forward.sink {
+      LogSink::Loki { url, tenant } => Sink::Loki {
+        url: format!(
+          "{}/loki/api/v1/push",
+          url.trim_end_matches('/')
+        ),
+        tenant: tenant.clone(),
+      },
+      LogSink::Syslog { address, protocol } => Sink::Syslog {
+        address: address.clone(),
+        protocol: *protocol,
+      },
+      LogSink::S3 {
+        bucket,
+        prefix,
+        region,
+        endpoint,
+      } => Sink::S3 {
+        client: s3_client(region, endpoint).await,
+        bucket: bucket.clone(),
+        prefix: prefix.trim_matches('/').to_string(),
+      },
+    }
+  }
+
+  /// S3 batches for longer to avoid many small objects.
+  fn flush_interval(&self) -> Duration {
+    match self {
+      Sink::S3 { .. } => Duration::from_secs(60),
+      _ => Duration::from_secs(2),
+    }
+  }
+
+  async fn send(
+    &self,
+    forward: &LogForward,
+    lines: &[LogLine],
+  ) -> anyhow::Result<()> {
+    match self {
+      Sink::Loki { url, tenant } => {
+        send_loki(url, tenant, forward, lines).await
+      }
+      Sink::Syslog { address, protocol } => {
+        send_syslog(address, *protocol, forward, lines).await
+      }
+      Sink::S3 {
+        client,
+        bucket,
+        prefix,
+      } => send_s3(client, bucket, prefix, forward, lines).await,
+    }
+  }
+}
+
+fn http_client() -> &'static reqwest::Client {
+  static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+  CLIENT.get_or_init(|| {
+    reqwest::Client::builder()
+      .timeout(Duration::from_secs(15))
+      .build()
+      .expect("Invalid log forwarding reqwest client")
+  })
+}
+
+async fn send_loki(
+  url: &str,
+  tenant: &str,
+  forward: &LogForward,
+  lines: &[LogLine],
+) -> anyhow::Result<()> {
+  let streams = [("stdout", false), ("stderr", true)]
+    .into_iter()
+    .filter_map(|(name, stderr)| {
+      let values = lines
+        .iter()
+        .filter(|line| line.stderr == stderr)
+        .map(|line| {
+          [
+            line
+              .ts
+              .timestamp_nanos_opt()
+              .unwrap_or_default()
+              .to_string(),
+            line.message.clone(),
+          ]
+        })
+        .collect::<Vec<_>>();
+      if values.is_empty() {
+        return None;
+      }
+      let mut labels = forward
+        .labels
+        .iter()
+        .map(|(key, value)| (key.clone(), json!(value)))
+        .collect::<serde_json::Map<_, _>>();
+      labels
+        .insert("container".to_string(), json!(forward.container));
+      labels.insert("stream".to_string(), json!(name));
+      Some(json!({ "stream": labels, "values": values }))
+    })
+    .collect::<Vec<_>>();
+  let mut req =
+    http_client().post(url).json(&json!({ "streams": streams }));
+  if !tenant.is_empty() {
+    req = req.header("X-Scope-OrgID", tenant);
+  }
+  let res = req.send().await.context("Failed to reach Loki")?;
+  let status = res.status();
+  if !status.is_success() {
+    let body = res.text().await.unwrap_or_default();
+    return Err(anyhow!("Loki push failed with {status} | {body}"));
+  }
+  Ok(())
+}
+
+async fn send_syslog(
+  address: &str,
+  protocol: SyslogProtocol,
+  forward: &LogForward,
+  lines: &[LogLine],
+) -> anyhow::Result<()> {
+  let messages = lines
+    .iter()
+    .map(|line| syslog_message(forward, line))
+    .collect::<Vec<_>>();
+  match protocol {
+    SyslogProtocol::Udp => {
+      let socket = UdpSocket::bind("0.0.0.0:0")
+        .await
+        .context("Failed to bind syslog socket")?;
+      socket
+        .connect(address)
+        .await
+        .with_context(|| format!("Failed to connect to {address}"))?;
+      for message in messages {
+        socket
+          .send(message.as_bytes())
+          .await
+          .context("Failed to send syslog message")?;
+      }
+    }
+    SyslogProtocol::Tcp => {
+      let mut stream = TcpStream::connect(address)
+        .await
+        .with_context(|| format!("Failed to connect to {address}"))?;
+      // RFC 6587 octet counting framing
+      let framed = messages
+        .into_iter()
+        .map(|message| format!("{} {message}", message.len()))
+        .collect::<String>();
+      stream
+        .write_all(framed.as_bytes())
+        .await
+        .context("Failed to send syslog messages")?;
+      stream
+        .flush()
+        .await
+        .context("Failed to send syslog messages")?;
+    }
+  }
+  Ok(())
+}
+
+/// Formats the line as RFC 5424, with the labels as structured data.
+fn syslog_message(forward: &LogForward, line: &LogLine) -> String {
+  // Facility user, severity error or informational
+  let priority = if line.stderr { 11 } else { 14 };
+  let hostname = forward
+    .labels
+    .iter()
+    .find(|(key, _)| key == "komodo_server")
+    .map(|(_, server)| syslog_name(server, 255))
+    .unwrap_or_else(|| String::from("-"));
+  let app = syslog_name(&forward.container, 48);
+  let params = forward
+    .labels
+    .iter()
+    .map(|(key, value)| {
+      let value = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace(']', "\\]");
+      format!(" {}=\"{value}\"", syslog_name(key, 32))
+    })
+    .collect::<String>();
+  let data = if params.is_empty() {
+    String::from("-")
+  } else {
+    format!("[komodo@32473{params}]")
+  };
+  format!(
+    "<{priority}>1 {} {hostname} {app} - - {data} {}",
+    line.ts.to_rfc3339_opts(SecondsFormat::Micros, true),
+    line.message
+  )
+}
+
+/// Syslog header fields are printable ascii without spaces.
+fn syslog_name(name: &str, max: usize) -> String {
+  name
+    .chars()
+    .filter(|c| c.is_ascii_graphic() && !matches!(c, '=' | ']' | '"'))
+    .take(max)
+    .collect()
+}
+
+async fn s3_client(
+  region: &str,
+  endpoint: &str,
+) -> aws_sdk_s3::Client {
+  let region = if region.is_empty() {
+    String::from("us-east-1")
+  } else {
+    region.to_string()
+  };
+  let config = aws_config::defaults(BehaviorVersion::latest())
+    .region(Region::new(region))
+    .load()
+    .await;
+  if endpoint.is_empty() {
+    return aws_sdk_s3::Client::new(&config);
+  }
+  let config = aws_sdk_s3::config::Builder::from(&config)
+    .endpoint_url(endpoint)
+    // S3 compatible stores generally don't support bucket subdomains.
+    .force_path_style(true)
+    .build();
+  aws_sdk_s3::Client::from_conf(config)
+}
+
+async fn send_s3(
+  client: &aws_sdk_s3::Client,
+  bucket: &str,
+  prefix: &str,
+  forward: &LogForward,
+  lines: &[LogLine],
+) -> anyhow::Result<()> {
+  let resource = forward
+    .labels
+    .iter()
+    .find(|(key, _)| {
+      key == "komodo_deployment" || key == "komodo_stack"
+    })
+    .map(|(_, name)| name.as_str())
+    .unwrap_or("unknown");
+  let first =
+    lines.first().map(|line| line.ts).unwrap_or_else(Utc::now);
+  let key = [
+    prefix,
+    resource,
+    &forward.container,
+    &format!("{}.log", first.timestamp_millis()),
+  ]
+  .into_iter()
+  .filter(|part| !part.is_empty())
+  .collect::<Vec<_>>()
+  .join("/");
+  let contents = lines
+    .iter()
+    .map(|line| {
+      format!(
+        "{} {}\n",
+        line.ts.to_rfc3339_opts(SecondsFormat::Nanos, true),
+        line.message
+      )
+    })
+    .collect::<String>();
+  client
+    .put_object()
+    .bucket(bucket)
+    .key(&key)
+    .content_type("text/plain")
+    .body(ByteStream::from(contents.into_bytes()))
+    .send()
+    .await
+    .with_context(|| format!("Failed to put s3 object {key}"))?;
+  Ok(())
+}
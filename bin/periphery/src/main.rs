@@ -2,19 +2,27 @@
 extern crate tracing;
 
 //
-use std::{net::SocketAddr, str::FromStr};
+use std::{
+  net::{IpAddr, SocketAddr},
+  str::FromStr,
+};
 
 use anyhow::Context;
 use axum_server::tls_rustls::RustlsConfig;
+use clap::Parser;
 use config::periphery_config;
+use komodo_client::entities::config::periphery::CliArgs;
 
 mod api;
 mod build;
+mod cli;
 mod compose;
 mod config;
 mod docker;
 mod git;
 mod helpers;
+mod hooks;
+mod log_forwarding;
 mod ssl;
 mod stats;
 mod terminal;
@@ -34,16 +42,20 @@ async fn app() -> anyhow::Result<()> {
 
   stats::spawn_polling_thread();
   docker::stats::spawn_polling_thread();
-
-  let addr = format!(
-    "{}:{}",
-    config::periphery_config().bind_ip,
-    config::periphery_config().port
+  terminal::spawn_terminal_policy_loop();
+  build::spawn_cache_volumes_prune_loop();
+
+  // Accepts IPv6 bind ips with or without brackets
+  let bind_ip = config::periphery_config()
+    .bind_ip
+    .trim_start_matches('[')
+    .trim_end_matches(']');
+  let socket_addr = SocketAddr::new(
+    IpAddr::from_str(bind_ip)
+      .with_context(|| format!("invalid bind ip: {bind_ip}"))?,
+    config::periphery_config().port,
   );
 
-  let socket_addr = SocketAddr::from_str(&addr)
-    .context("failed to parse listen address")?;
-
   let app =
     api::router().into_make_service_with_connect_info::<SocketAddr>();
 
@@ -74,6 +86,12 @@ async fn app() -> anyhow::Result<()> {
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+  // Local commands run instead of the agent
+  if let Some(command) = CliArgs::parse().command {
+    dotenvy::dotenv().ok();
+    return cli::handle(command).await;
+  }
+
   let mut term_signal = tokio::signal::unix::signal(
     tokio::signal::unix::SignalKind::terminate(),
   )?;
@@ -2,8 +2,8 @@ use std::{cmp::Ordering, sync::OnceLock};
 
 use async_timing_util::wait_until_timelength;
 use komodo_client::entities::stats::{
-  SingleDiskUsage, SystemInformation, SystemLoadAverage,
-  SystemProcess, SystemStats,
+  SingleDiskUsage, SingleNetworkInterfaceUsage, SystemInformation,
+  SystemLoadAverage, SystemProcess, SystemStats,
 };
 use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System};
 use tokio::sync::RwLock;
@@ -90,11 +90,24 @@ impl StatsClient {
 
     let mut network_ingress_bytes: u64 = 0;
     let mut network_egress_bytes: u64 = 0;
+    let mut network_usage_interface = Vec::new();
 
-    for (_, network) in self.networks.iter() {
+    for (name, network) in self.networks.iter() {
       network_ingress_bytes += network.received();
       network_egress_bytes += network.transmitted();
+      network_usage_interface.push(SingleNetworkInterfaceUsage {
+        name: name.clone(),
+        ingress_bytes: network.received() as f64,
+        egress_bytes: network.transmitted() as f64,
+      });
     }
+    network_usage_interface.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let disks = self.get_disks();
+    let (disk_read_bytes, disk_write_bytes) =
+      disks.iter().fold((0.0, 0.0), |(read, write), disk| {
+        (read + disk.read_bytes, write + disk.write_bytes)
+      });
 
     let load_avg = System::load_average();
 
@@ -110,7 +123,10 @@ impl StatsClient {
       mem_total_gb: total_mem as f64 / BYTES_PER_GB,
       network_ingress_bytes: network_ingress_bytes as f64,
       network_egress_bytes: network_egress_bytes as f64,
-      disks: self.get_disks(),
+      network_usage_interface,
+      disk_read_bytes,
+      disk_write_bytes,
+      disks,
       polling_rate: self.stats.polling_rate,
       refresh_ts: self.stats.refresh_ts,
       refresh_list_ts: self.stats.refresh_list_ts,
@@ -148,11 +164,14 @@ impl StatsClient {
           disk.file_system().to_string_lossy().to_string();
         let disk_total = disk.total_space() as f64 / BYTES_PER_GB;
         let disk_free = disk.available_space() as f64 / BYTES_PER_GB;
+        let usage = disk.usage();
         SingleDiskUsage {
           mount: disk.mount_point().to_owned(),
           used_gb: disk_total - disk_free,
           total_gb: disk_total,
           file_system,
+          read_bytes: usage.read_bytes as f64,
+          write_bytes: usage.written_bytes as f64,
         }
       })
       .collect()
@@ -216,5 +235,7 @@ fn get_system_information(
       .unwrap_or_default(),
     terminals_disabled: config.disable_terminals,
     container_exec_disabled: config.disable_container_exec,
+    // Filled in on request, as it needs to query the docker daemon.
+    docker_version: None,
   }
 }
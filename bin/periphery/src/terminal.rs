@@ -1,34 +1,64 @@
 use std::{
   collections::{HashMap, VecDeque},
+  path::{Path, PathBuf},
   pin::Pin,
-  sync::{Arc, OnceLock},
+  process::Stdio,
+  sync::{
+    Arc, OnceLock,
+    atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering},
+  },
   task::Poll,
   time::Duration,
 };
 
 use anyhow::{Context, anyhow};
+use async_timing_util::get_timelength_in_ms;
 use axum::http::StatusCode;
 use bytes::Bytes;
 use futures::Stream;
 use komodo_client::{
-  api::write::TerminalRecreateMode,
-  entities::{komodo_timestamp, server::TerminalInfo},
+  api::{
+    terminal::MAX_TERMINAL_FILE_BYTES, write::TerminalRecreateMode,
+  },
+  entities::{
+    EnvironmentVar, Timelength, komodo_timestamp,
+    server::{TerminalInfo, TerminalParticipant},
+  },
 };
 use pin_project_lite::pin_project;
 use portable_pty::{CommandBuilder, PtySize, native_pty_system};
 use rand::Rng;
 use serror::AddStatusCodeError;
-use tokio::sync::{broadcast, mpsc};
+use shell_escape::unix::escape;
+use tokio::{
+  io::AsyncWriteExt,
+  sync::{broadcast, mpsc},
+};
 use tokio_util::sync::CancellationToken;
 
+use crate::{config::periphery_config, docker::container_cli};
+
 type PtyName = String;
 type PtyMap = tokio::sync::RwLock<HashMap<PtyName, Arc<Terminal>>>;
 type StdinSender = mpsc::Sender<StdinMsg>;
 type StdoutReceiver = broadcast::Receiver<Bytes>;
 
+/// Options to further configure the terminal process.
+#[derive(Debug, Default)]
+pub struct TerminalOptions {
+  pub working_dir: Option<String>,
+  pub env: Vec<EnvironmentVar>,
+  /// The name of the profile the terminal was created with, if any.
+  pub profile: Option<String>,
+  /// The container, for container exec terminals.
+  /// Used to transfer files in / out of the container.
+  pub container: Option<String>,
+}
+
 pub async fn create_terminal(
   name: String,
   command: String,
+  options: TerminalOptions,
   recreate: TerminalRecreateMode,
 ) -> anyhow::Result<()> {
   trace!(
@@ -48,9 +78,22 @@ pub async fn create_terminal(
       ));
     }
   }
+  let max_terminals = periphery_config().max_terminals;
+  if max_terminals > 0
+    && !terminals.contains_key(&name)
+    && terminals
+      .values()
+      .filter(|terminal| !terminal.cancel.is_cancelled())
+      .count()
+      >= max_terminals
+  {
+    return Err(anyhow!(
+      "Cannot create terminal {name}, the maximum of {max_terminals} terminals are already open"
+    ));
+  }
   if let Some(prev) = terminals.insert(
     name,
-    Terminal::new(command)
+    Terminal::new(command, options)
       .await
       .context("Failed to init terminal")?
       .into(),
@@ -75,6 +118,8 @@ pub async fn list_terminals() -> Vec<TerminalInfo> {
       name: name.to_string(),
       command: terminal.command.clone(),
       stored_size_kb: terminal.history.size_kb(),
+      profile: terminal.profile.clone(),
+      participants: terminal.participants(),
     })
     .collect::<Vec<_>>();
   terminals.sort_by(|a, b| a.name.cmp(&b.name));
@@ -131,16 +176,46 @@ pub struct Terminal {
   /// The command that was used as the root command, eg `shell`
   command: String,
 
+  /// The profile used to create the terminal, if any.
+  profile: Option<String>,
+
+  /// The working directory, used to resolve relative file transfer paths.
+  working_dir: Option<String>,
+  /// The container, for container exec terminals.
+  container: Option<String>,
+
   pub cancel: CancellationToken,
 
   pub stdin: StdinSender,
   pub stdout: StdoutReceiver,
 
   pub history: Arc<History>,
+
+  /// Used to send notices to connected users,
+  /// without recording them in the history.
+  notify: broadcast::Sender<Bytes>,
+  /// Unix timestamp (ms) the terminal was created.
+  created_at: i64,
+  /// Unix timestamp (ms) of the latest input.
+  last_activity: AtomicI64,
+  idle_warned: AtomicBool,
+  lifetime_warned: AtomicBool,
+
+  /// The users currently connected, keyed by connection id.
+  participants: std::sync::Mutex<HashMap<u64, TerminalParticipant>>,
+  next_participant_id: AtomicU64,
 }
 
 impl Terminal {
-  async fn new(command: String) -> anyhow::Result<Terminal> {
+  async fn new(
+    command: String,
+    TerminalOptions {
+      working_dir,
+      env,
+      profile,
+      container,
+    }: TerminalOptions,
+  ) -> anyhow::Result<Terminal> {
     trace!("Creating terminal with command: {command}");
 
     let terminal = native_pty_system()
@@ -157,6 +232,14 @@ impl Terminal {
       cmd.arg(arg);
     }
 
+    if let Some(working_dir) = &working_dir {
+      cmd.cwd(working_dir);
+    }
+
+    for EnvironmentVar { variable, value } in env {
+      cmd.env(variable, value);
+    }
+
     cmd.env("TERM", "xterm-256color");
     cmd.env("COLORTERM", "truecolor");
 
@@ -263,6 +346,7 @@ impl Terminal {
     // Uses broadcast to output to multiple client simultaneously
     let (write, stdout) =
       tokio::sync::broadcast::channel::<Bytes>(8192);
+    let notify = write.clone();
     let _cancel = cancel.clone();
     let _history = history.clone();
     tokio::task::spawn_blocking(move || {
@@ -302,17 +386,343 @@ impl Terminal {
 
     Ok(Terminal {
       command,
+      profile,
+      working_dir,
+      container,
       cancel,
       stdin,
       stdout,
       history,
+      notify,
+      created_at: komodo_timestamp(),
+      last_activity: AtomicI64::new(komodo_timestamp()),
+      idle_warned: AtomicBool::new(false),
+      lifetime_warned: AtomicBool::new(false),
+      participants: Default::default(),
+      next_participant_id: AtomicU64::new(0),
     })
   }
 
+  /// Record input activity, resetting the idle timeout.
+  pub fn touch(&self) {
+    self
+      .last_activity
+      .store(komodo_timestamp(), Ordering::Relaxed);
+    self.idle_warned.store(false, Ordering::Relaxed);
+  }
+
+  fn resolve_path(&self, path: &str) -> PathBuf {
+    match &self.working_dir {
+      // Joining an absolute path replaces the working dir
+      Some(working_dir) => Path::new(working_dir).join(path),
+      None => PathBuf::from(path),
+    }
+  }
+
+  /// Restricted terminals are limited to their command,
+  /// so they may not be used to read or write arbitrary files.
+  fn check_file_transfer_allowed(&self) -> anyhow::Result<()> {
+    if let Some(profile) = &self.profile {
+      return Err(anyhow!(
+        "File transfer is disabled for terminals created from a profile ({profile})"
+      ));
+    }
+    if !periphery_config().allowed_terminal_commands.is_empty() {
+      return Err(anyhow!(
+        "File transfer is disabled when allowed_terminal_commands is configured"
+      ));
+    }
+    Ok(())
+  }
+
+  /// Resolves the path on the host, following any symlinks,
+  /// and ensures it is inside the configured `terminal_file_root`.
+  async fn resolve_host_path(
+    &self,
+    path: &str,
+  ) -> anyhow::Result<PathBuf> {
+    let root = periphery_config()
+      .terminal_file_root
+      .as_ref()
+      .context("Host file transfer is disabled, terminal_file_root is not configured")?;
+    let root =
+      tokio::fs::canonicalize(root).await.with_context(|| {
+        format!("Failed to resolve terminal_file_root {root:?}")
+      })?;
+    let path = self.resolve_path(path);
+    let file_name = path
+      .file_name()
+      .with_context(|| format!("Invalid file path {path:?}"))?;
+    // The file may not exist yet when uploading,
+    // so the parent directory is resolved instead.
+    let parent = path
+      .parent()
+      .filter(|parent| !parent.as_os_str().is_empty())
+      .unwrap_or(Path::new("."));
+    let mut resolved = tokio::fs::canonicalize(parent)
+      .await
+      .with_context(|| format!("Failed to resolve {parent:?}"))?
+      .join(file_name);
+    if tokio::fs::symlink_metadata(&resolved).await.is_ok() {
+      resolved = tokio::fs::canonicalize(&resolved)
+        .await
+        .with_context(|| format!("Failed to resolve {resolved:?}"))?;
+    }
+    if resolved.starts_with(&root) {
+      Ok(resolved)
+    } else {
+      Err(anyhow!(
+        "File path {path:?} is outside of terminal_file_root {root:?}"
+      ))
+    }
+  }
+
+  /// Write a file on the host, or inside the container
+  /// for container exec terminals.
+  pub async fn upload_file(
+    &self,
+    path: &str,
+    contents: &[u8],
+  ) -> anyhow::Result<()> {
+    if contents.len() > MAX_TERMINAL_FILE_BYTES {
+      return Err(anyhow!(
+        "File exceeds the maximum transfer size of {MAX_TERMINAL_FILE_BYTES} bytes"
+      ));
+    }
+    self.check_file_transfer_allowed()?;
+    let Some(container) = &self.container else {
+      let path = self.resolve_host_path(path).await?;
+      return tokio::fs::write(&path, contents).await.with_context(
+        || format!("Failed to write file at {path:?}"),
+      );
+    };
+    let path = self.resolve_path(path);
+    let mut child = tokio::process::Command::new(container_cli())
+      .args(["exec", "-i", container, "sh", "-c"])
+      .arg(format!("cat > {}", escape(path.to_string_lossy())))
+      .stdin(Stdio::piped())
+      .stdout(Stdio::null())
+      .stderr(Stdio::piped())
+      .spawn()
+      .context("Failed to spawn docker exec")?;
+    let mut stdin = child
+      .stdin
+      .take()
+      .context("Failed to take docker exec stdin")?;
+    stdin
+      .write_all(contents)
+      .await
+      .context("Failed to write file to docker exec stdin")?;
+    drop(stdin);
+    let output = child
+      .wait_with_output()
+      .await
+      .context("Failed to wait for docker exec")?;
+    if output.status.success() {
+      Ok(())
+    } else {
+      Err(anyhow!(
+        "Failed to write file at {path:?} in container {container} | {}",
+        String::from_utf8_lossy(&output.stderr).trim()
+      ))
+    }
+  }
+
+  /// Read a file on the host, or inside the container
+  /// for container exec terminals.
+  pub async fn download_file(
+    &self,
+    path: &str,
+  ) -> anyhow::Result<Bytes> {
+    self.check_file_transfer_allowed()?;
+    let contents = if let Some(container) = &self.container {
+      let path = self.resolve_path(path);
+      // Reads at most one byte over the limit, so the output stays
+      // bounded and a larger file is still detected below.
+      let output = tokio::process::Command::new(container_cli())
+        .args(["exec", container, "head", "-c"])
+        .arg((MAX_TERMINAL_FILE_BYTES + 1).to_string())
+        .arg("--")
+        .arg(&path)
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .context("Failed to run docker exec")?;
+      if !output.status.success() {
+        return Err(anyhow!(
+          "Failed to read file at {path:?} in container {container} | {}",
+          String::from_utf8_lossy(&output.stderr).trim()
+        ));
+      }
+      output.stdout
+    } else {
+      let path = self.resolve_host_path(path).await?;
+      let metadata = tokio::fs::metadata(&path)
+        .await
+        .with_context(|| format!("No file at {path:?}"))?;
+      if metadata.len() as usize > MAX_TERMINAL_FILE_BYTES {
+        return Err(anyhow!(
+          "File exceeds the maximum transfer size of {MAX_TERMINAL_FILE_BYTES} bytes"
+        ));
+      }
+      tokio::fs::read(&path)
+        .await
+        .with_context(|| format!("Failed to read file at {path:?}"))?
+    };
+    if contents.len() > MAX_TERMINAL_FILE_BYTES {
+      return Err(anyhow!(
+        "File exceeds the maximum transfer size of {MAX_TERMINAL_FILE_BYTES} bytes"
+      ));
+    }
+    Ok(contents.into())
+  }
+
+  fn notice(&self, message: &str) {
+    let _ = self.notify.send(Bytes::from(format!(
+      "\r\n\x1b[33m[Komodo] {message}\x1b[0m\r\n"
+    )));
+  }
+
   pub fn cancel(&self) {
     trace!("Cancel called");
     self.cancel.cancel();
   }
+
+  /// Register a user connected to the terminal.
+  /// They are removed again when the returned guard is dropped.
+  pub fn join(
+    self: &Arc<Self>,
+    terminal: String,
+    username: String,
+    read_only: bool,
+  ) -> ParticipantGuard {
+    let id = self.next_participant_id.fetch_add(1, Ordering::Relaxed);
+    info!(
+      "User {username} joined terminal {terminal} | read only: {read_only}"
+    );
+    self.participants.lock().unwrap().insert(
+      id,
+      TerminalParticipant {
+        username,
+        read_only,
+        joined_at: komodo_timestamp(),
+      },
+    );
+    ParticipantGuard {
+      terminal_name: terminal,
+      terminal: self.clone(),
+      id,
+    }
+  }
+
+  pub fn participants(&self) -> Vec<TerminalParticipant> {
+    let mut participants = self
+      .participants
+      .lock()
+      .unwrap()
+      .values()
+      .cloned()
+      .collect::<Vec<_>>();
+    participants.sort_by_key(|p| p.joined_at);
+    participants
+  }
+}
+
+/// Removes the participant from the terminal on drop.
+pub struct ParticipantGuard {
+  terminal_name: String,
+  terminal: Arc<Terminal>,
+  id: u64,
+}
+
+impl Drop for ParticipantGuard {
+  fn drop(&mut self) {
+    if let Some(participant) =
+      self.terminal.participants.lock().unwrap().remove(&self.id)
+    {
+      info!(
+        "User {} left terminal {}",
+        participant.username, self.terminal_name
+      );
+    }
+  }
+}
+
+/// Users are warned this long before a terminal is closed by policy.
+const POLICY_WARNING_MS: i64 = 60_000;
+
+/// This should be called before starting the server in main.rs.
+/// Closes terminals exceeding the configured
+/// `terminal_idle_timeout` or `terminal_max_lifetime`.
+pub fn spawn_terminal_policy_loop() {
+  let config = periphery_config();
+  let idle_timeout = config.terminal_idle_timeout.map(timelength_ms);
+  let max_lifetime = config.terminal_max_lifetime.map(timelength_ms);
+  if idle_timeout.is_none() && max_lifetime.is_none() {
+    return;
+  }
+  tokio::spawn(async move {
+    let mut interval = tokio::time::interval(Duration::from_secs(5));
+    loop {
+      interval.tick().await;
+      let ts = komodo_timestamp();
+      for (name, terminal) in terminals().read().await.iter() {
+        if terminal.cancel.is_cancelled() {
+          continue;
+        }
+        if let Some(idle_timeout) = idle_timeout {
+          let last_activity =
+            terminal.last_activity.load(Ordering::Relaxed);
+          enforce_terminal_policy(
+            name,
+            terminal,
+            last_activity + idle_timeout - ts,
+            &terminal.idle_warned,
+            "it has been idle for too long",
+          );
+        }
+        if let Some(max_lifetime) = max_lifetime {
+          enforce_terminal_policy(
+            name,
+            terminal,
+            terminal.created_at + max_lifetime - ts,
+            &terminal.lifetime_warned,
+            "it has reached the maximum lifetime",
+          );
+        }
+      }
+      clean_up_terminals().await;
+    }
+  });
+}
+
+fn enforce_terminal_policy(
+  name: &str,
+  terminal: &Terminal,
+  remaining_ms: i64,
+  warned: &AtomicBool,
+  reason: &str,
+) {
+  if remaining_ms <= 0 {
+    info!("Closing terminal {name} because {reason}");
+    terminal.cancel();
+  } else if remaining_ms <= POLICY_WARNING_MS
+    && !warned.swap(true, Ordering::Relaxed)
+  {
+    debug!("Warning terminal {name} users before closing");
+    terminal.notice(&format!(
+      "This terminal will be closed in {} seconds because {reason}.",
+      remaining_ms / 1_000
+    ));
+  }
+}
+
+fn timelength_ms(timelength: Timelength) -> i64 {
+  get_timelength_in_ms(
+    timelength
+      .try_into()
+      .expect("Invalid terminal policy timelength"),
+  ) as i64
 }
 
 /// 1 MiB rolling max history size per terminal
@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+/// Download an artifact attached to an Update.
+/// Responds with the raw artifact bytes.
+///
+/// POST `/artifact/download` with this JSON body.
+/// Requires read permissions on the Update.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DownloadArtifactBody {
+  /// The Update id
+  pub update: String,
+  /// The artifact name, matching one of the Update's `artifacts`.
+  pub name: String,
+}
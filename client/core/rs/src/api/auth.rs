@@ -43,6 +43,8 @@ pub struct GetLoginOptionsResponse {
   pub google: bool,
   /// Whether OIDC login is enabled.
   pub oidc: bool,
+  /// Whether LDAP login is enabled.
+  pub ldap: bool,
   /// Whether user registration (Sign Up) has been disabled
   pub registration_disabled: bool,
 }
@@ -100,6 +102,30 @@ pub type LoginLocalUserResponse = JwtResponse;
 
 //
 
+/// Login with LDAP credentials. The user is created on first login.
+/// Response: [LoginLdapUserResponse].
+///
+/// Note. This method is only available if the core api has `ldap_enabled`.
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoAuthRequest)]
+#[response(LoginLdapUserResponse)]
+#[error(serror::Error)]
+pub struct LoginLdapUser {
+  /// The user's LDAP username
+  pub username: String,
+  /// The user's LDAP password
+  pub password: String,
+}
+
+/// The response for [LoginLdapUser]
+#[typeshare]
+pub type LoginLdapUserResponse = JwtResponse;
+
+//
+
 /// Exchange a single use exchange token (safe for transport in url query)
 /// for a jwt.
 /// Response: [ExchangeForJwtResponse].
@@ -1,5 +1,5 @@
 use anyhow::Context;
-use clap::Parser;
+use clap::{ArgAction::SetTrue, Parser};
 use derive_empty_traits::EmptyTraits;
 use resolver_api::Resolve;
 use serde::{Deserialize, Serialize};
@@ -7,7 +7,7 @@ use typeshare::typeshare;
 
 use crate::entities::{JsonObject, update::Update};
 
-use super::{BatchExecutionResponse, KomodoExecuteRequest};
+use super::KomodoExecuteRequest;
 
 /// Runs the target Action. Response: [Update]
 #[typeshare]
@@ -40,7 +40,7 @@ fn args_parser(args: &str) -> anyhow::Result<JsonObject> {
   serde_qs::from_str(args).context("Failed to parse args")
 }
 
-/// Runs multiple Actions in parallel that match pattern. Response: [BatchExecutionResponse]
+/// Runs multiple Actions in parallel that match pattern. Response: [Update]
 #[typeshare]
 #[derive(
   Debug,
@@ -53,11 +53,12 @@ fn args_parser(args: &str) -> anyhow::Result<JsonObject> {
   Parser,
 )]
 #[empty_traits(KomodoExecuteRequest)]
-#[response(BatchExecutionResponse)]
+#[response(Update)]
 #[error(serror::Error)]
 pub struct BatchRunAction {
-  /// Id or name or wildcard pattern or regex.
+  /// Id or name or wildcard pattern or regex or tag expression.
   /// Supports multiline and comma delineated combinations of the above.
+  /// Terms prefixed with `!` exclude their matches.
   ///
   /// Example:
   /// ```text
@@ -67,4 +68,11 @@ pub struct BatchRunAction {
   /// extra-action-1, extra-action-2
   /// ```
   pub pattern: String,
+  /// The max number of executions to run at once.
+  /// If not given, all of them run at once.
+  #[arg(long)]
+  pub parallelism: Option<i32>,
+  /// Don't start any remaining executions after one fails.
+  #[arg(long = "abort-on-failure", action = SetTrue)]
+  pub abort_on_failure: Option<bool>,
 }
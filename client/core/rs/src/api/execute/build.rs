@@ -1,4 +1,4 @@
-use clap::Parser;
+use clap::{ArgAction::SetTrue, Parser};
 use derive_empty_traits::EmptyTraits;
 use resolver_api::Resolve;
 use serde::{Deserialize, Serialize};
@@ -6,7 +6,7 @@ use typeshare::typeshare;
 
 use crate::entities::update::Update;
 
-use super::{BatchExecutionResponse, KomodoExecuteRequest};
+use super::KomodoExecuteRequest;
 
 //
 
@@ -44,7 +44,7 @@ pub struct RunBuild {
 
 //
 
-/// Runs multiple builds in parallel that match pattern. Response: [BatchExecutionResponse].
+/// Runs multiple builds in parallel that match pattern. Response: [Update].
 #[typeshare]
 #[derive(
   Debug,
@@ -57,11 +57,12 @@ pub struct RunBuild {
   Parser,
 )]
 #[empty_traits(KomodoExecuteRequest)]
-#[response(BatchExecutionResponse)]
+#[response(Update)]
 #[error(serror::Error)]
 pub struct BatchRunBuild {
-  /// Id or name or wildcard pattern or regex.
+  /// Id or name or wildcard pattern or regex or tag expression.
   /// Supports multiline and comma delineated combinations of the above.
+  /// Terms prefixed with `!` exclude their matches.
   ///
   /// Example:
   /// ```text
@@ -71,6 +72,13 @@ pub struct BatchRunBuild {
   /// extra-build-1, extra-build-2
   /// ```
   pub pattern: String,
+  /// The max number of executions to run at once.
+  /// If not given, all of them run at once.
+  #[arg(long)]
+  pub parallelism: Option<i32>,
+  /// Don't start any remaining executions after one fails.
+  #[arg(long = "abort-on-failure", action = SetTrue)]
+  pub abort_on_failure: Option<bool>,
 }
 
 //
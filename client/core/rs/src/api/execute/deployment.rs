@@ -1,4 +1,7 @@
-use clap::Parser;
+use std::collections::HashMap;
+
+use anyhow::Context;
+use clap::{ArgAction::SetTrue, Parser};
 use derive_empty_traits::EmptyTraits;
 use resolver_api::Resolve;
 use serde::{Deserialize, Serialize};
@@ -6,7 +9,7 @@ use typeshare::typeshare;
 
 use crate::entities::{TerminationSignal, update::Update};
 
-use super::{BatchExecutionResponse, KomodoExecuteRequest};
+use super::KomodoExecuteRequest;
 
 /// Deploys the container for the target deployment. Response: [Update].
 ///
@@ -38,11 +41,24 @@ pub struct Deploy {
   /// Override the default termination max time.
   /// Only used when deployment needs to be taken down before redeploy.
   pub stop_time: Option<i32>,
+  /// Values for the runtime inputs declared on the deployment.
+  /// They are interpolated for this deploy only, and never persisted.
+  ///
+  /// CLI Format: `"INPUT1=val1&INPUT2=val2"`
+  #[serde(default)]
+  #[clap(long, value_parser = inputs_parser)]
+  pub inputs: Option<HashMap<String, String>>,
+}
+
+fn inputs_parser(
+  inputs: &str,
+) -> anyhow::Result<HashMap<String, String>> {
+  serde_qs::from_str(inputs).context("Failed to parse inputs")
 }
 
 //
 
-/// Deploys multiple Deployments in parallel that match pattern. Response: [BatchExecutionResponse].
+/// Deploys multiple Deployments in parallel that match pattern. Response: [Update].
 #[typeshare]
 #[derive(
   Serialize,
@@ -55,11 +71,12 @@ pub struct Deploy {
   Parser,
 )]
 #[empty_traits(KomodoExecuteRequest)]
-#[response(BatchExecutionResponse)]
+#[response(Update)]
 #[error(serror::Error)]
 pub struct BatchDeploy {
-  /// Id or name or wildcard pattern or regex.
+  /// Id or name or wildcard pattern or regex or tag expression.
   /// Supports multiline and comma delineated combinations of the above.
+  /// Terms prefixed with `!` exclude their matches.
   ///
   /// Example:
   /// ```text
@@ -69,6 +86,13 @@ pub struct BatchDeploy {
   /// extra-deployment-1, extra-deployment-2
   /// ```
   pub pattern: String,
+  /// The max number of executions to run at once.
+  /// If not given, all of them run at once.
+  #[arg(long)]
+  pub parallelism: Option<i32>,
+  /// Don't start any remaining executions after one fails.
+  #[arg(long = "abort-on-failure", action = SetTrue)]
+  pub abort_on_failure: Option<bool>,
 }
 
 //
@@ -250,7 +274,7 @@ pub struct DestroyDeployment {
 
 //
 
-/// Destroys multiple Deployments in parallel that match pattern. Response: [BatchExecutionResponse].
+/// Destroys multiple Deployments in parallel that match pattern. Response: [Update].
 #[typeshare]
 #[derive(
   Serialize,
@@ -263,11 +287,12 @@ pub struct DestroyDeployment {
   Parser,
 )]
 #[empty_traits(KomodoExecuteRequest)]
-#[response(BatchExecutionResponse)]
+#[response(Update)]
 #[error(serror::Error)]
 pub struct BatchDestroyDeployment {
-  /// Id or name or wildcard pattern or regex.
+  /// Id or name or wildcard pattern or regex or tag expression.
   /// Supports multiline and comma delineated combinations of the above.
+  /// Terms prefixed with `!` exclude their matches.
   ///
   /// Example:
   /// ```text
@@ -277,4 +302,11 @@ pub struct BatchDestroyDeployment {
   /// extra-deployment-1, extra-deployment-2
   /// ```
   pub pattern: String,
+  /// The max number of executions to run at once.
+  /// If not given, all of them run at once.
+  #[arg(long)]
+  pub parallelism: Option<i32>,
+  /// Don't start any remaining executions after one fails.
+  #[arg(long = "abort-on-failure", action = SetTrue)]
+  pub abort_on_failure: Option<bool>,
 }
@@ -10,10 +10,13 @@ mod alerter;
 mod build;
 mod deployment;
 mod maintenance;
+mod plan;
 mod procedure;
 mod repo;
+mod rerun;
 mod server;
 mod stack;
+mod swarm;
 mod sync;
 
 pub use action::*;
@@ -21,15 +24,18 @@ pub use alerter::*;
 pub use build::*;
 pub use deployment::*;
 pub use maintenance::*;
+pub use plan::*;
 pub use procedure::*;
 pub use repo::*;
+pub use rerun::*;
 pub use server::*;
 pub use stack::*;
+pub use swarm::*;
 pub use sync::*;
 
 use crate::{
   api::write::CommitSync,
-  entities::{_Serror, I64, NoData, update::Update},
+  entities::{I64, NoData},
 };
 
 pub trait KomodoExecuteRequest: HasResponse {}
@@ -92,6 +98,13 @@ pub enum Execution {
   DestroyDeployment(DestroyDeployment),
   BatchDestroyDeployment(BatchDestroyDeployment),
 
+  // SWARM SERVICE
+  /// Deploy the target swarm service. (alias: `swarm`)
+  #[clap(alias = "swarm")]
+  DeploySwarmService(DeploySwarmService),
+  ScaleSwarmService(ScaleSwarmService),
+  RemoveSwarmService(RemoveSwarmService),
+
   // REPO
   /// Clone the target repo
   #[clap(alias = "clone")]
@@ -99,6 +112,7 @@ pub enum Execution {
   BatchCloneRepo(BatchCloneRepo),
   PullRepo(PullRepo),
   BatchPullRepo(BatchPullRepo),
+  RunRepoPipeline(RunRepoPipeline),
   BuildRepo(BuildRepo),
   BatchBuildRepo(BatchBuildRepo),
   CancelRepoBuild(CancelRepoBuild),
@@ -127,6 +141,7 @@ pub enum Execution {
   PruneDockerBuilders(PruneDockerBuilders),
   PruneBuildx(PruneBuildx),
   PruneSystem(PruneSystem),
+  RunScheduledCommand(RunScheduledCommand),
 
   // SYNC
   /// Execute a Resource Sync. (alias: `sync`)
@@ -153,6 +168,7 @@ pub enum Execution {
   DestroyStack(DestroyStack),
   BatchDestroyStack(BatchDestroyStack),
   RunStackService(RunStackService),
+  ScaleStackService(ScaleStackService),
 
   // ALERTER
   TestAlerter(TestAlerter),
@@ -175,34 +191,3 @@ pub struct Sleep {
   #[serde(default)]
   pub duration_ms: I64,
 }
-
-#[typeshare]
-pub type BatchExecutionResponse = Vec<BatchExecutionResponseItem>;
-
-#[typeshare]
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "status", content = "data")]
-pub enum BatchExecutionResponseItem {
-  Ok(Update),
-  Err(BatchExecutionResponseItemErr),
-}
-
-impl From<Result<Box<Update>, BatchExecutionResponseItemErr>>
-  for BatchExecutionResponseItem
-{
-  fn from(
-    value: Result<Box<Update>, BatchExecutionResponseItemErr>,
-  ) -> Self {
-    match value {
-      Ok(update) => Self::Ok(*update),
-      Err(e) => Self::Err(e),
-    }
-  }
-}
-
-#[typeshare]
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BatchExecutionResponseItemErr {
-  pub name: String,
-  pub error: _Serror,
-}
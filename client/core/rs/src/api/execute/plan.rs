@@ -0,0 +1,41 @@
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::update::Update;
+
+use super::{DeployStack, KomodoExecuteRequest, RunSync};
+
+/// Computes what an execution would change without applying
+/// anything, like `terraform plan`. Response: [Update]
+///
+/// The changes are stored on the Update as
+/// [ExecutionPlan][crate::entities::plan::ExecutionPlan],
+/// and summarized in the logs.
+#[typeshare]
+#[derive(
+  Debug,
+  Clone,
+  PartialEq,
+  Serialize,
+  Deserialize,
+  Resolve,
+  EmptyTraits,
+)]
+#[empty_traits(KomodoExecuteRequest)]
+#[response(Update)]
+#[error(serror::Error)]
+pub struct Plan {
+  /// The execution to plan.
+  pub execution: PlanExecution,
+}
+
+/// The executions which can be planned.
+#[typeshare]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "params")]
+pub enum PlanExecution {
+  RunSync(RunSync),
+  DeployStack(DeployStack),
+}
@@ -1,4 +1,7 @@
-use clap::Parser;
+use std::collections::HashMap;
+
+use anyhow::Context;
+use clap::{ArgAction::SetTrue, Parser};
 use derive_empty_traits::EmptyTraits;
 use resolver_api::Resolve;
 use serde::{Deserialize, Serialize};
@@ -6,7 +9,7 @@ use typeshare::typeshare;
 
 use crate::entities::update::Update;
 
-use super::{BatchExecutionResponse, KomodoExecuteRequest};
+use super::KomodoExecuteRequest;
 
 /// Runs the target Procedure. Response: [Update]
 #[typeshare]
@@ -26,9 +29,23 @@ use super::{BatchExecutionResponse, KomodoExecuteRequest};
 pub struct RunProcedure {
   /// Id or name
   pub procedure: String,
+  /// Values for the runtime inputs declared on the procedure.
+  /// They are interpolated into the stage executions for this run only,
+  /// and never persisted.
+  ///
+  /// CLI Format: `"INPUT1=val1&INPUT2=val2"`
+  #[serde(default)]
+  #[clap(long, value_parser = inputs_parser)]
+  pub inputs: Option<HashMap<String, String>>,
 }
 
-/// Runs multiple Procedures in parallel that match pattern. Response: [BatchExecutionResponse].
+fn inputs_parser(
+  inputs: &str,
+) -> anyhow::Result<HashMap<String, String>> {
+  serde_qs::from_str(inputs).context("Failed to parse inputs")
+}
+
+/// Runs multiple Procedures in parallel that match pattern. Response: [Update].
 #[typeshare]
 #[derive(
   Debug,
@@ -41,11 +58,12 @@ pub struct RunProcedure {
   Parser,
 )]
 #[empty_traits(KomodoExecuteRequest)]
-#[response(BatchExecutionResponse)]
+#[response(Update)]
 #[error(serror::Error)]
 pub struct BatchRunProcedure {
-  /// Id or name or wildcard pattern or regex.
+  /// Id or name or wildcard pattern or regex or tag expression.
   /// Supports multiline and comma delineated combinations of the above.
+  /// Terms prefixed with `!` exclude their matches.
   ///
   /// Example:
   /// ```text
@@ -55,4 +73,11 @@ pub struct BatchRunProcedure {
   /// extra-procedure-1, extra-procedure-2
   /// ```
   pub pattern: String,
+  /// The max number of executions to run at once.
+  /// If not given, all of them run at once.
+  #[arg(long)]
+  pub parallelism: Option<i32>,
+  /// Don't start any remaining executions after one fails.
+  #[arg(long = "abort-on-failure", action = SetTrue)]
+  pub abort_on_failure: Option<bool>,
 }
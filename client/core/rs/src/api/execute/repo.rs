@@ -1,4 +1,4 @@
-use clap::Parser;
+use clap::{ArgAction::SetTrue, Parser};
 use derive_empty_traits::EmptyTraits;
 use resolver_api::Resolve;
 use serde::{Deserialize, Serialize};
@@ -6,7 +6,7 @@ use typeshare::typeshare;
 
 use crate::entities::update::Update;
 
-use super::{BatchExecutionResponse, KomodoExecuteRequest};
+use super::KomodoExecuteRequest;
 
 //
 
@@ -40,7 +40,7 @@ pub struct CloneRepo {
 
 //
 
-/// Clones multiple Repos in parallel that match pattern. Response: [BatchExecutionResponse].
+/// Clones multiple Repos in parallel that match pattern. Response: [Update].
 #[typeshare]
 #[derive(
   Debug,
@@ -53,11 +53,12 @@ pub struct CloneRepo {
   Parser,
 )]
 #[empty_traits(KomodoExecuteRequest)]
-#[response(BatchExecutionResponse)]
+#[response(Update)]
 #[error(serror::Error)]
 pub struct BatchCloneRepo {
-  /// Id or name or wildcard pattern or regex.
+  /// Id or name or wildcard pattern or regex or tag expression.
   /// Supports multiline and comma delineated combinations of the above.
+  /// Terms prefixed with `!` exclude their matches.
   ///
   /// Example:
   /// ```text
@@ -67,6 +68,13 @@ pub struct BatchCloneRepo {
   /// extra-repo-1, extra-repo-2
   /// ```
   pub pattern: String,
+  /// The max number of executions to run at once.
+  /// If not given, all of them run at once.
+  #[arg(long)]
+  pub parallelism: Option<i32>,
+  /// Don't start any remaining executions after one fails.
+  #[arg(long = "abort-on-failure", action = SetTrue)]
+  pub abort_on_failure: Option<bool>,
 }
 
 //
@@ -98,7 +106,7 @@ pub struct PullRepo {
 
 //
 
-/// Pulls multiple Repos in parallel that match pattern. Response: [BatchExecutionResponse].
+/// Pulls multiple Repos in parallel that match pattern. Response: [Update].
 #[typeshare]
 #[derive(
   Debug,
@@ -111,11 +119,12 @@ pub struct PullRepo {
   Parser,
 )]
 #[empty_traits(KomodoExecuteRequest)]
-#[response(BatchExecutionResponse)]
+#[response(Update)]
 #[error(serror::Error)]
 pub struct BatchPullRepo {
-  /// Id or name or wildcard pattern or regex.
+  /// Id or name or wildcard pattern or regex or tag expression.
   /// Supports multiline and comma delineated combinations of the above.
+  /// Terms prefixed with `!` exclude their matches.
   ///
   /// Example:
   /// ```text
@@ -125,6 +134,43 @@ pub struct BatchPullRepo {
   /// extra-repo-1, extra-repo-2
   /// ```
   pub pattern: String,
+  /// The max number of executions to run at once.
+  /// If not given, all of them run at once.
+  #[arg(long)]
+  pub parallelism: Option<i32>,
+  /// Don't start any remaining executions after one fails.
+  #[arg(long = "abort-on-failure", action = SetTrue)]
+  pub abort_on_failure: Option<bool>,
+}
+
+//
+
+/// Runs the target repo pipeline. Response: [Update].
+///
+/// Note. Repo must have server attached at `server_id`.
+///
+/// 1. Pulls the repo on the target server, or clones it if it doesn't exist.
+/// 2. Runs the pipeline steps in order, either on the host
+/// or inside a container using the step image.
+/// Stops at the first failed step.
+/// 3. Copies the step artifacts to `${root_directory}/artifacts/${repo}`.
+#[typeshare]
+#[derive(
+  Serialize,
+  Deserialize,
+  Debug,
+  Clone,
+  PartialEq,
+  Resolve,
+  EmptyTraits,
+  Parser,
+)]
+#[empty_traits(KomodoExecuteRequest)]
+#[response(Update)]
+#[error(serror::Error)]
+pub struct RunRepoPipeline {
+  /// Id or name
+  pub repo: String,
 }
 
 //
@@ -160,7 +206,7 @@ pub struct BuildRepo {
 
 //
 
-/// Builds multiple Repos in parallel that match pattern. Response: [BatchExecutionResponse].
+/// Builds multiple Repos in parallel that match pattern. Response: [Update].
 #[typeshare]
 #[derive(
   Debug,
@@ -173,11 +219,12 @@ pub struct BuildRepo {
   Parser,
 )]
 #[empty_traits(KomodoExecuteRequest)]
-#[response(BatchExecutionResponse)]
+#[response(Update)]
 #[error(serror::Error)]
 pub struct BatchBuildRepo {
-  /// Id or name or wildcard pattern or regex.
+  /// Id or name or wildcard pattern or regex or tag expression.
   /// Supports multiline and comma delineated combinations of the above.
+  /// Terms prefixed with `!` exclude their matches.
   ///
   /// Example:
   /// ```text
@@ -187,6 +234,13 @@ pub struct BatchBuildRepo {
   /// extra-repo-1, extra-repo-2
   /// ```
   pub pattern: String,
+  /// The max number of executions to run at once.
+  /// If not given, all of them run at once.
+  #[arg(long)]
+  pub parallelism: Option<i32>,
+  /// Don't start any remaining executions after one fails.
+  #[arg(long = "abort-on-failure", action = SetTrue)]
+  pub abort_on_failure: Option<bool>,
 }
 
 //
@@ -0,0 +1,44 @@
+use std::collections::HashMap;
+
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::update::Update;
+
+use super::KomodoExecuteRequest;
+
+/// Reruns the execution recorded by a past Update with the inputs
+/// captured on it. Response: [Update]
+///
+/// - Deploy: Deploys the recorded image, pinned to the recorded digest.
+/// - DeployStack: Deploys the recorded commit and services,
+///   only if the rendered compose config matches the original.
+/// - RunBuild: Builds the recorded commit.
+///
+/// The Update must have a recorded
+/// [ExecutionEnvironment][crate::entities::update::ExecutionEnvironment].
+/// The new Update links the original with `rerun_of`,
+/// and the current permissions on the resource apply.
+#[typeshare]
+#[derive(
+  Debug,
+  Clone,
+  PartialEq,
+  Serialize,
+  Deserialize,
+  Resolve,
+  EmptyTraits,
+)]
+#[empty_traits(KomodoExecuteRequest)]
+#[response(Update)]
+#[error(serror::Error)]
+pub struct RerunUpdate {
+  /// The id of the Update to rerun.
+  pub update: String,
+  /// Values for the runtime inputs of a Deploy,
+  /// which aren't recorded on the Update.
+  #[serde(default)]
+  pub inputs: Option<HashMap<String, String>>,
+}
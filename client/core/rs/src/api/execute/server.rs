@@ -531,3 +531,30 @@ pub struct PruneSystem {
   /// Id or name
   pub server: String,
 }
+
+//
+
+/// Runs one of the Server's scheduled commands now. Response: [Update].
+///
+/// Scheduled commands are also run automatically by Core on their schedule.
+/// Requires Execute and Terminal permissions on the Server.
+#[typeshare]
+#[derive(
+  Serialize,
+  Deserialize,
+  Debug,
+  Clone,
+  PartialEq,
+  Resolve,
+  EmptyTraits,
+  Parser,
+)]
+#[empty_traits(KomodoExecuteRequest)]
+#[response(Update)]
+#[error(serror::Error)]
+pub struct RunScheduledCommand {
+  /// Id or name
+  pub server: String,
+  /// The name of the scheduled command
+  pub command: String,
+}
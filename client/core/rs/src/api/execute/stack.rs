@@ -8,7 +8,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use typeshare::typeshare;
 
-use super::{BatchExecutionResponse, KomodoExecuteRequest};
+use super::KomodoExecuteRequest;
 
 /// Deploys the target stack. `docker compose up`. Response: [Update]
 #[typeshare]
@@ -39,7 +39,7 @@ pub struct DeployStack {
 
 //
 
-/// Deploys multiple Stacks in parallel that match pattern. Response: [BatchExecutionResponse].
+/// Deploys multiple Stacks in parallel that match pattern. Response: [Update].
 #[typeshare]
 #[derive(
   Serialize,
@@ -52,11 +52,12 @@ pub struct DeployStack {
   Parser,
 )]
 #[empty_traits(KomodoExecuteRequest)]
-#[response(BatchExecutionResponse)]
+#[response(Update)]
 #[error(serror::Error)]
 pub struct BatchDeployStack {
-  /// Id or name or wildcard pattern or regex.
+  /// Id or name or wildcard pattern or regex or tag expression.
   /// Supports multiline and comma delineated combinations of the above.
+  /// Terms prefixed with `!` exclude their matches.
   ///
   /// Example:
   /// ```text
@@ -66,6 +67,13 @@ pub struct BatchDeployStack {
   /// extra-stack-1, extra-stack-2
   /// ```
   pub pattern: String,
+  /// The max number of executions to run at once.
+  /// If not given, all of them run at once.
+  #[arg(long)]
+  pub parallelism: Option<i32>,
+  /// Don't start any remaining executions after one fails.
+  #[arg(long = "abort-on-failure", action = SetTrue)]
+  pub abort_on_failure: Option<bool>,
 }
 
 //
@@ -97,7 +105,7 @@ pub struct DeployStackIfChanged {
 
 //
 
-/// Deploys multiple Stacks if changed in parallel that match pattern. Response: [BatchExecutionResponse].
+/// Deploys multiple Stacks if changed in parallel that match pattern. Response: [Update].
 #[typeshare]
 #[derive(
   Serialize,
@@ -110,11 +118,12 @@ pub struct DeployStackIfChanged {
   Parser,
 )]
 #[empty_traits(KomodoExecuteRequest)]
-#[response(BatchExecutionResponse)]
+#[response(Update)]
 #[error(serror::Error)]
 pub struct BatchDeployStackIfChanged {
-  /// Id or name or wildcard pattern or regex.
+  /// Id or name or wildcard pattern or regex or tag expression.
   /// Supports multiline and comma delineated combinations of the above.
+  /// Terms prefixed with `!` exclude their matches.
   ///
   /// Example:
   /// ```text
@@ -124,6 +133,13 @@ pub struct BatchDeployStackIfChanged {
   /// extra-stack-1, extra-stack-2
   /// ```
   pub pattern: String,
+  /// The max number of executions to run at once.
+  /// If not given, all of them run at once.
+  #[arg(long)]
+  pub parallelism: Option<i32>,
+  /// Don't start any remaining executions after one fails.
+  #[arg(long = "abort-on-failure", action = SetTrue)]
+  pub abort_on_failure: Option<bool>,
 }
 
 //
@@ -154,7 +170,7 @@ pub struct PullStack {
 
 //
 
-/// Pulls multiple Stacks in parallel that match pattern. Response: [BatchExecutionResponse].
+/// Pulls multiple Stacks in parallel that match pattern. Response: [Update].
 #[typeshare]
 #[derive(
   Serialize,
@@ -167,11 +183,12 @@ pub struct PullStack {
   Parser,
 )]
 #[empty_traits(KomodoExecuteRequest)]
-#[response(BatchExecutionResponse)]
+#[response(Update)]
 #[error(serror::Error)]
 pub struct BatchPullStack {
-  /// Id or name or wildcard pattern or regex.
+  /// Id or name or wildcard pattern or regex or tag expression.
   /// Supports multiline and comma delineated combinations of the above.
+  /// Terms prefixed with `!` exclude their matches.
   ///
   /// Example:
   /// ```text
@@ -181,6 +198,13 @@ pub struct BatchPullStack {
   /// extra-stack-1, extra-stack-2
   /// ```
   pub pattern: String,
+  /// The max number of executions to run at once.
+  /// If not given, all of them run at once.
+  #[arg(long)]
+  pub parallelism: Option<i32>,
+  /// Don't start any remaining executions after one fails.
+  #[arg(long = "abort-on-failure", action = SetTrue)]
+  pub abort_on_failure: Option<bool>,
 }
 
 //
@@ -408,7 +432,38 @@ fn env_parser(args: &str) -> anyhow::Result<HashMap<String, String>> {
 
 //
 
-/// Destroys multiple Stacks in parallel that match pattern. Response: [BatchExecutionResponse].
+/// Sets the desired replicas for a Stack service, and redeploys
+/// the service with `docker compose up --scale`. Response: [Update]
+///
+/// The replica count is persisted to the Stack config,
+/// and will be used on subsequent deploys.
+/// Requires Write permissions on the Stack.
+#[typeshare]
+#[derive(
+  Debug,
+  Clone,
+  PartialEq,
+  Serialize,
+  Deserialize,
+  Resolve,
+  EmptyTraits,
+  Parser,
+)]
+#[empty_traits(KomodoExecuteRequest)]
+#[response(Update)]
+#[error(serror::Error)]
+pub struct ScaleStackService {
+  /// Id or name
+  pub stack: String,
+  /// The service to scale
+  pub service: String,
+  /// The desired number of replicas
+  pub replicas: i64,
+}
+
+//
+
+/// Destroys multiple Stacks in parallel that match pattern. Response: [Update].
 #[typeshare]
 #[derive(
   Serialize,
@@ -421,11 +476,12 @@ fn env_parser(args: &str) -> anyhow::Result<HashMap<String, String>> {
   Parser,
 )]
 #[empty_traits(KomodoExecuteRequest)]
-#[response(BatchExecutionResponse)]
+#[response(Update)]
 #[error(serror::Error)]
 pub struct BatchDestroyStack {
-  /// Id or name or wildcard pattern or regex.
+  /// Id or name or wildcard pattern or regex or tag expression.
   /// Supports multiline and comma delineated combinations of the above.
+  /// Terms prefixed with `!` exclude their matches.
   ///d
   /// Example:
   /// ```text
@@ -435,4 +491,11 @@ pub struct BatchDestroyStack {
   /// extra-stack-1, extra-stack-2
   /// ```
   pub pattern: String,
+  /// The max number of executions to run at once.
+  /// If not given, all of them run at once.
+  #[arg(long)]
+  pub parallelism: Option<i32>,
+  /// Don't start any remaining executions after one fails.
+  #[arg(long = "abort-on-failure", action = SetTrue)]
+  pub abort_on_failure: Option<bool>,
 }
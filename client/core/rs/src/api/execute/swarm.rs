@@ -0,0 +1,89 @@
+use clap::Parser;
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::update::Update;
+
+use super::KomodoExecuteRequest;
+
+/// Deploys the target swarm service to the Swarm
+/// with `docker stack deploy`. Response: [Update].
+///
+/// If the stack is already deployed, the services are updated in place,
+/// using the Swarm rolling update configuration.
+#[typeshare]
+#[derive(
+  Serialize,
+  Deserialize,
+  Debug,
+  Clone,
+  PartialEq,
+  Resolve,
+  EmptyTraits,
+  Parser,
+)]
+#[empty_traits(KomodoExecuteRequest)]
+#[response(Update)]
+#[error(serror::Error)]
+pub struct DeploySwarmService {
+  /// Id or name
+  pub swarm_service: String,
+}
+
+//
+
+/// Scales the services of the target swarm service
+/// with `docker service scale`. Response: [Update].
+///
+/// Only applies to services in `replicated` mode.
+/// The replica count is not persisted to the swarm service config,
+/// so it will be reset on the next deploy.
+#[typeshare]
+#[derive(
+  Serialize,
+  Deserialize,
+  Debug,
+  Clone,
+  PartialEq,
+  Resolve,
+  EmptyTraits,
+  Parser,
+)]
+#[empty_traits(KomodoExecuteRequest)]
+#[response(Update)]
+#[error(serror::Error)]
+pub struct ScaleSwarmService {
+  /// Id or name
+  pub swarm_service: String,
+  /// The desired number of replicas
+  pub replicas: i64,
+  /// Only scale this service in the stack.
+  /// If empty, all services in the stack are scaled.
+  #[serde(default)]
+  pub service: Option<String>,
+}
+
+//
+
+/// Removes the services of the target swarm service
+/// from the Swarm with `docker stack rm`. Response: [Update].
+#[typeshare]
+#[derive(
+  Serialize,
+  Deserialize,
+  Debug,
+  Clone,
+  PartialEq,
+  Resolve,
+  EmptyTraits,
+  Parser,
+)]
+#[empty_traits(KomodoExecuteRequest)]
+#[response(Update)]
+#[error(serror::Error)]
+pub struct RemoveSwarmService {
+  /// Id or name
+  pub swarm_service: String,
+}
@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::U64;
+
+/// Follow a container log on the given Server as it is written,
+/// like `docker logs -f`. Responds with a stream of log lines,
+/// which only ends when the container stops.
+///
+/// POST `/log/follow/container` with this JSON body.
+/// Requires Read permission with Logs on the Server.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FollowContainerLogBody {
+  /// Server Id or name
+  pub server: String,
+  /// The container name
+  pub container: String,
+  /// The number of lines to include from before following. Default: 50
+  #[serde(default = "default_tail")]
+  pub tail: U64,
+  /// Enable `--timestamps`
+  #[serde(default)]
+  pub timestamps: bool,
+  /// Only stream the lines matching this regex.
+  /// Default: "" (all lines)
+  #[serde(default)]
+  pub filter: String,
+}
+
+/// Follow the Deployment container log as it is written.
+/// Responds with a stream of log lines.
+///
+/// POST `/log/follow/deployment` with this JSON body.
+/// Requires Read permission with Logs on the Deployment.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FollowDeploymentLogBody {
+  /// Deployment Id or name
+  pub deployment: String,
+  /// The number of lines to include from before following. Default: 50
+  #[serde(default = "default_tail")]
+  pub tail: U64,
+  /// Enable `--timestamps`
+  #[serde(default)]
+  pub timestamps: bool,
+  /// Only stream the lines matching this regex.
+  /// Default: "" (all lines)
+  #[serde(default)]
+  pub filter: String,
+}
+
+/// Follow a Stack service container log as it is written.
+/// Responds with a stream of log lines.
+///
+/// POST `/log/follow/stack` with this JSON body.
+/// Requires Read permission with Logs on the Stack.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FollowStackLogBody {
+  /// Stack Id or name
+  pub stack: String,
+  /// The service name.
+  /// The service's current container is resolved automatically.
+  pub service: String,
+  /// The number of lines to include from before following. Default: 50
+  #[serde(default = "default_tail")]
+  pub tail: U64,
+  /// Enable `--timestamps`
+  #[serde(default)]
+  pub timestamps: bool,
+  /// Only stream the lines matching this regex.
+  /// Default: "" (all lines)
+  #[serde(default)]
+  pub filter: String,
+}
+
+fn default_tail() -> u64 {
+  50
+}
@@ -51,6 +51,7 @@
 //! - [read]: Read only requests which retrieve data from Komodo.
 //! - [execute]: Run actions on Komodo resources, eg [execute::RunBuild].
 //! - [mod@write]: Requests which alter data, like create / update / delete resources.
+//! - [artifact]: Download artifacts attached to Updates.
 //!
 //! ## Errors
 //!
@@ -66,9 +67,12 @@
 //! }
 //! ```
 
+pub mod artifact;
 pub mod auth;
 pub mod execute;
+pub mod log;
 pub mod read;
+pub mod stack_file;
 pub mod terminal;
 pub mod user;
 pub mod write;
@@ -0,0 +1,27 @@
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::access_request::AccessRequest;
+
+use super::KomodoReadRequest;
+
+/// List access requests, newest first.
+/// Admins see all requests, other users only their own.
+/// Response: [ListAccessRequestsResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ListAccessRequestsResponse)]
+#[error(serror::Error)]
+pub struct ListAccessRequests {
+  /// Also include requests which are no longer pending.
+  #[serde(default)]
+  pub include_resolved: bool,
+}
+
+#[typeshare]
+pub type ListAccessRequestsResponse = Vec<AccessRequest>;
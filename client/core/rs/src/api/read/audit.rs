@@ -0,0 +1,50 @@
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::{
+  I64, MongoDocument, U64, audit::AuditLogEntry,
+};
+
+use super::KomodoReadRequest;
+
+/// Get a paginated list of audit log entries sorted by timestamp descending.
+/// Admin only.
+/// Response: [ListAuditLogResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ListAuditLogResponse)]
+#[error(serror::Error)]
+pub struct ListAuditLog {
+  /// Pass a custom mongo query to filter the entries.
+  ///
+  /// ## Example JSON
+  /// ```json
+  /// {
+  ///   "success": false,
+  ///   "operation": "LoginLocalUser",
+  ///   "ts": { "$gt": 1735689600000 }
+  /// }
+  /// ```
+  /// This will filter to only include failed local logins
+  /// since the given timestamp.
+  pub query: Option<MongoDocument>,
+  /// Retrieve older results by incrementing the page.
+  /// `page: 0` is default, and returns the most recent results.
+  #[serde(default)]
+  pub page: U64,
+}
+
+/// Response for [ListAuditLog].
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ListAuditLogResponse {
+  pub entries: Vec<AuditLogEntry>,
+  /// If more entries exist, the next page will be given here.
+  /// Otherwise it will be `null`
+  pub next_page: Option<I64>,
+}
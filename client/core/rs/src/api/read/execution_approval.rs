@@ -0,0 +1,48 @@
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::execution_approval::ExecutionApproval;
+
+use super::KomodoReadRequest;
+
+/// Get a specific Execution Approval. Response: [ExecutionApproval].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(GetExecutionApprovalResponse)]
+#[error(serror::Error)]
+pub struct GetExecutionApproval {
+  /// The id of the execution approval.
+  pub id: String,
+}
+
+#[typeshare]
+pub type GetExecutionApprovalResponse = ExecutionApproval;
+
+//
+
+/// List the Execution Approvals the user can see, newest first.
+/// Only includes approvals for Procedures the user has Read permission on.
+/// Response: [ListExecutionApprovalsResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ListExecutionApprovalsResponse)]
+#[error(serror::Error)]
+pub struct ListExecutionApprovals {
+  /// Only list approvals for this Procedure (id or name).
+  #[serde(default)]
+  pub procedure: Option<String>,
+  /// Also include approvals which are no longer pending.
+  #[serde(default)]
+  pub include_resolved: bool,
+}
+
+#[typeshare]
+pub type ListExecutionApprovalsResponse = Vec<ExecutionApproval>;
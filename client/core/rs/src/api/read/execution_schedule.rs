@@ -0,0 +1,71 @@
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::{
+  I64,
+  execution_schedule::{ExecutionSchedule, ExecutionScheduleRun},
+};
+
+use super::KomodoReadRequest;
+
+/// Get a specific Execution Schedule.
+/// Non admins can only get the schedules they created.
+/// Response: [ExecutionSchedule].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(GetExecutionScheduleResponse)]
+#[error(serror::Error)]
+pub struct GetExecutionSchedule {
+  /// Id or name
+  pub schedule: String,
+}
+
+#[typeshare]
+pub type GetExecutionScheduleResponse = ExecutionSchedule;
+
+//
+
+/// List the Execution Schedules, sorted by name.
+/// Non admins only see the schedules they created.
+/// Response: [ListExecutionSchedulesResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ListExecutionSchedulesResponse)]
+#[error(serror::Error)]
+pub struct ListExecutionSchedules {}
+
+#[typeshare]
+pub type ListExecutionSchedulesResponse = Vec<ExecutionSchedule>;
+
+//
+
+/// List the run history of an Execution Schedule, newest first.
+/// Non admins can only list the runs of schedules they created.
+/// Response: [ListExecutionScheduleRunsResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ListExecutionScheduleRunsResponse)]
+#[error(serror::Error)]
+pub struct ListExecutionScheduleRuns {
+  /// Id or name
+  pub schedule: String,
+  /// The max number of runs to return.
+  /// The last 100 runs of each schedule are kept.
+  #[serde(default)]
+  pub limit: Option<I64>,
+}
+
+#[typeshare]
+pub type ListExecutionScheduleRunsResponse =
+  Vec<ExecutionScheduleRun>;
@@ -0,0 +1,107 @@
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use super::KomodoReadRequest;
+
+//
+
+/// Render a Deployment as the equivalent `docker run` command.
+/// Variables and secrets are left uninterpolated.
+/// Response: [ExportDeploymentToDockerRunResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ExportDeploymentToDockerRunResponse)]
+#[error(serror::Error)]
+pub struct ExportDeploymentToDockerRun {
+  /// Id or name
+  #[serde(alias = "id", alias = "name")]
+  pub deployment: String,
+}
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExportDeploymentToDockerRunResponse {
+  /// The `docker run` command.
+  pub command: String,
+}
+
+//
+
+/// Render a Deployment as an equivalent compose file,
+/// with a single service named after the Deployment.
+/// Variables and secrets are left uninterpolated.
+/// Response: [ComposeExportResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ExportDeploymentToComposeResponse)]
+#[error(serror::Error)]
+pub struct ExportDeploymentToCompose {
+  /// Id or name
+  #[serde(alias = "id", alias = "name")]
+  pub deployment: String,
+}
+
+#[typeshare]
+pub type ExportDeploymentToComposeResponse = ComposeExportResponse;
+
+//
+
+/// Render the resources managed on a Server as a compose bundle.
+///
+/// All Deployments on the Server the user has permission to view are
+/// rendered as services in a single compose file. The compose files of
+/// Stacks defined in the UI are included as is. Stacks using files
+/// on the host or in a git repo are not included,
+/// as their files are not stored by Core.
+///
+/// Variables and secrets are left uninterpolated.
+/// Response: [ComposeExportResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ExportServerToComposeResponse)]
+#[error(serror::Error)]
+pub struct ExportServerToCompose {
+  /// Id or name
+  #[serde(alias = "id", alias = "name")]
+  pub server: String,
+}
+
+#[typeshare]
+pub type ExportServerToComposeResponse = ComposeExportResponse;
+
+//
+
+/// Response containing compose file contents.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ComposeExportResponse {
+  /// The compose file rendered from the Deployments.
+  /// Empty if there are no Deployments to export.
+  pub compose: String,
+  /// The compose files of the exported Stacks.
+  #[serde(default)]
+  pub stacks: Vec<StackComposeExport>,
+}
+
+/// The compose file of an exported Stack.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StackComposeExport {
+  /// The Stack name.
+  pub name: String,
+  /// The compose project name the Stack is deployed with.
+  pub project_name: String,
+  /// The compose file contents.
+  pub contents: String,
+}
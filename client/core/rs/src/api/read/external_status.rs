@@ -0,0 +1,70 @@
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::external_status::ExternalStatus;
+
+use super::KomodoReadRequest;
+
+/// Get a specific External Status. Response: [ExternalStatus].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(GetExternalStatusResponse)]
+#[error(serror::Error)]
+pub struct GetExternalStatus {
+  /// The name of the external resource.
+  pub name: String,
+}
+
+#[typeshare]
+pub type GetExternalStatusResponse = ExternalStatus;
+
+//
+
+/// List the External Statuses, sorted by name.
+/// Response: [ListExternalStatusesResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ListExternalStatusesResponse)]
+#[error(serror::Error)]
+pub struct ListExternalStatuses {}
+
+#[typeshare]
+pub type ListExternalStatusesResponse = Vec<ExternalStatus>;
+
+//
+
+/// Gets a summary of data relating to all External Statuses.
+/// Stale reports are counted as unknown.
+/// Response: [GetExternalStatusesSummaryResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(GetExternalStatusesSummaryResponse)]
+#[error(serror::Error)]
+pub struct GetExternalStatusesSummary {}
+
+/// Response for [GetExternalStatusesSummary]
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct GetExternalStatusesSummaryResponse {
+  /// The total number of external statuses
+  pub total: u32,
+  /// The number of external statuses with Ok state.
+  pub ok: u32,
+  /// The number of external statuses with Warning state.
+  pub warning: u32,
+  /// The number of external statuses with Critical state.
+  pub critical: u32,
+  /// The number of external statuses with Unknown state.
+  pub unknown: u32,
+}
@@ -0,0 +1,72 @@
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::{
+  ResourceTargetVariant,
+  federation::{
+    FederatedAlert, FederatedResource, FederationPeerStatus,
+  },
+};
+
+use super::KomodoReadRequest;
+
+/// **Admin only.** List the federation peers with their status,
+/// sorted by name. Response: [ListFederationPeersResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ListFederationPeersResponse)]
+#[error(serror::Error)]
+pub struct ListFederationPeers {}
+
+#[typeshare]
+pub type ListFederationPeersResponse = Vec<FederationPeerStatus>;
+
+//
+
+/// **Admin only.** List the resources on the enabled federation peers,
+/// as last read from the peers. Read only, each resource links to the peer.
+/// Response: [ListFederatedResourcesResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ListFederatedResourcesResponse)]
+#[error(serror::Error)]
+pub struct ListFederatedResources {
+  /// Only include the resources on this peer (id or name).
+  #[serde(default)]
+  pub peer: Option<String>,
+  /// Only include resources of this type.
+  #[serde(default)]
+  pub resource_type: Option<ResourceTargetVariant>,
+}
+
+#[typeshare]
+pub type ListFederatedResourcesResponse = Vec<FederatedResource>;
+
+//
+
+/// **Admin only.** List the open alerts on the enabled federation peers,
+/// as last read from the peers, sorted by timestamp descending.
+/// Response: [ListFederatedAlertsResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ListFederatedAlertsResponse)]
+#[error(serror::Error)]
+pub struct ListFederatedAlerts {
+  /// Only include the alerts on this peer (id or name).
+  #[serde(default)]
+  pub peer: Option<String>,
+}
+
+#[typeshare]
+pub type ListFederatedAlertsResponse = Vec<FederatedAlert>;
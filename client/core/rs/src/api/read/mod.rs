@@ -3,50 +3,80 @@ use resolver_api::{HasResponse, Resolve};
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
+mod access_request;
 mod action;
 mod alert;
 mod alerter;
+mod audit;
 mod build;
 mod builder;
 mod deployment;
+mod execution_approval;
+mod execution_schedule;
+mod export;
+mod external_status;
+mod federation;
+mod pattern;
 mod permission;
 mod procedure;
 mod provider;
+mod queued_execution;
 mod repo;
 mod schedule;
+mod schema;
 mod server;
+mod server_profile;
+mod silence;
+mod snapshot;
 mod stack;
+mod swarm;
 mod sync;
 mod tag;
 mod toml;
 mod update;
 mod user;
 mod user_group;
+mod validate;
 mod variable;
 
+pub use access_request::*;
 pub use action::*;
 pub use alert::*;
 pub use alerter::*;
+pub use audit::*;
 pub use build::*;
 pub use builder::*;
 pub use deployment::*;
+pub use execution_approval::*;
+pub use execution_schedule::*;
+pub use export::*;
+pub use external_status::*;
+pub use federation::*;
+pub use pattern::*;
 pub use permission::*;
 pub use procedure::*;
 pub use provider::*;
+pub use queued_execution::*;
 pub use repo::*;
 pub use schedule::*;
+pub use schema::*;
 pub use server::*;
+pub use server_profile::*;
+pub use silence::*;
+pub use snapshot::*;
 pub use stack::*;
+pub use swarm::*;
 pub use sync::*;
 pub use tag::*;
 pub use toml::*;
 pub use update::*;
 pub use user::*;
 pub use user_group::*;
+pub use validate::*;
 pub use variable::*;
 
 use crate::entities::{
-  ResourceTarget, Timelength,
+  ResourceTarget, Timelength, Version,
   config::{DockerRegistry, GitProvider},
 };
 
@@ -75,6 +105,56 @@ pub struct GetVersionResponse {
 
 //
 
+/// Check whether the client version is supported by Core,
+/// and get the endpoints which are deprecated.
+/// Response: [NegotiateVersionResponse].
+///
+/// Also see [compatibility][crate::compatibility].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(NegotiateVersionResponse)]
+#[error(serror::Error)]
+pub struct NegotiateVersion {
+  /// The version of the client, eg `1.19.5`.
+  pub client_version: String,
+}
+
+/// Response for [NegotiateVersion].
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NegotiateVersionResponse {
+  /// The version of Komodo Core.
+  pub core_version: Version,
+  /// The oldest client version supported by Core.
+  pub min_client_version: Version,
+  /// The first newer client version which is not supported.
+  /// Clients must be older than this version.
+  pub max_client_version: Version,
+  /// Whether the client version is supported.
+  pub compatible: bool,
+  /// Describes the version mismatch if not compatible.
+  pub message: Option<String>,
+  /// Endpoints which still work, but will be removed
+  /// in a future version.
+  pub deprecations: Vec<DeprecatedEndpoint>,
+}
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DeprecatedEndpoint {
+  /// The endpoint, eg `/read/GetVersion`.
+  pub endpoint: String,
+  /// The Core version the endpoint was deprecated in.
+  pub since: Version,
+  /// The endpoint to use instead, if any.
+  pub replacement: Option<String>,
+}
+
+//
+
 /// Get info about the core api configuration.
 /// Response: [GetCoreInfoResponse].
 #[typeshare]
@@ -0,0 +1,51 @@
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::ResourceTargetVariant;
+
+use super::KomodoReadRequest;
+
+/// Preview which resources a batch execution pattern matches,
+/// without running anything. Matches exactly the resources
+/// a batch execution by the user would run on.
+/// Response: [PreviewMatchesResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(PreviewMatchesResponse)]
+#[error(serror::Error)]
+pub struct PreviewMatches {
+  /// The resource type to match, eg. `Stack`.
+  pub resource_type: ResourceTargetVariant,
+  /// Id or name or wildcard pattern or regex or tag expression.
+  /// Supports multiline and comma delineated combinations of the above.
+  /// Terms prefixed with `!` exclude their matches.
+  ///
+  /// Example:
+  /// ```text
+  /// # match all foo-* stacks
+  /// foo-*
+  /// # edge stacks which aren't frozen
+  /// tag:edge AND NOT tag:frozen
+  /// # except staging
+  /// !staging-*
+  /// ```
+  pub pattern: String,
+}
+
+#[typeshare]
+pub type PreviewMatchesResponse = Vec<PatternMatch>;
+
+/// A resource matched by a pattern.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PatternMatch {
+  /// The resource id.
+  pub id: String,
+  /// The resource name.
+  pub name: String,
+}
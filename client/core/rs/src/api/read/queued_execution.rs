@@ -0,0 +1,48 @@
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::queued_execution::QueuedExecution;
+
+use super::KomodoReadRequest;
+
+/// Get a specific Queued Execution. Response: [QueuedExecution].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(GetQueuedExecutionResponse)]
+#[error(serror::Error)]
+pub struct GetQueuedExecution {
+  /// The id of the queued execution.
+  pub id: String,
+}
+
+#[typeshare]
+pub type GetQueuedExecutionResponse = QueuedExecution;
+
+//
+
+/// List the Queued Executions the user can see, in queue order.
+/// Only includes executions on Servers the user has Read permission on.
+/// Response: [ListQueuedExecutionsResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ListQueuedExecutionsResponse)]
+#[error(serror::Error)]
+pub struct ListQueuedExecutions {
+  /// Only list executions queued for this Server (id or name).
+  #[serde(default)]
+  pub server: Option<String>,
+  /// Also include executions which are no longer queued or running.
+  #[serde(default)]
+  pub include_finished: bool,
+}
+
+#[typeshare]
+pub type ListQueuedExecutionsResponse = Vec<QueuedExecution>;
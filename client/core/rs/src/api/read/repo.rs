@@ -112,6 +112,8 @@ pub struct GetReposSummaryResponse {
   pub pulling: u32,
   /// The number of repos currently building.
   pub building: u32,
+  /// The number of repos currently running the pipeline.
+  pub running_pipeline: u32,
   /// The number of repos with failed state.
   pub failed: u32,
   /// The number of repos with unknown state.
@@ -147,4 +149,6 @@ pub struct GetRepoWebhooksEnabledResponse {
   pub pull_enabled: bool,
   /// Whether pushes to branch trigger build. Will always be false if managed is false.
   pub build_enabled: bool,
+  /// Whether pushes to branch trigger the pipeline. Will always be false if managed is false.
+  pub pipeline_enabled: bool,
 }
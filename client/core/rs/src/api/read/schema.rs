@@ -0,0 +1,58 @@
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::{JsonValue, ResourceTargetVariant};
+
+use super::KomodoReadRequest;
+
+/// The JSON Schema of a resource type config.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResourceConfigSchema {
+  /// The resource type.
+  pub resource_type: ResourceTargetVariant,
+  /// The Core version the schema was generated from.
+  pub version: String,
+  /// The JSON Schema (draft 7) of the config,
+  /// including the field defaults and docs.
+  pub schema: JsonValue,
+}
+
+//
+
+/// Get the JSON Schema of a resource type config,
+/// generated from the config types of the running Core.
+/// Response: [ResourceConfigSchema].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(GetResourceConfigSchemaResponse)]
+#[error(serror::Error)]
+pub struct GetResourceConfigSchema {
+  /// The resource type, eg. `Stack`.
+  pub resource_type: ResourceTargetVariant,
+}
+
+#[typeshare]
+pub type GetResourceConfigSchemaResponse = ResourceConfigSchema;
+
+//
+
+/// List the JSON Schemas of all the resource type configs.
+/// Response: [ListResourceConfigSchemasResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ListResourceConfigSchemasResponse)]
+#[error(serror::Error)]
+pub struct ListResourceConfigSchemas {}
+
+#[typeshare]
+pub type ListResourceConfigSchemasResponse =
+  Vec<ResourceConfigSchema>;
@@ -151,6 +151,32 @@ pub struct GetPeripheryVersionResponse {
 
 //
 
+/// Get the public key Core signs periphery requests with,
+/// for a Server, Url Builder or Aws Builder. Add it to the periphery
+/// `core_public_keys` to require signed requests.
+/// Response: [GetPeripheryPublicKeyResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(GetPeripheryPublicKeyResponse)]
+#[error(serror::Error)]
+pub struct GetPeripheryPublicKey {
+  /// The Server or Builder target. The id can also be the name.
+  pub target: ResourceTarget,
+}
+
+/// Response for [GetPeripheryPublicKey].
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GetPeripheryPublicKeyResponse {
+  /// The base64 encoded ed25519 public key.
+  pub public_key: String,
+}
+
+//
+
 /// List the docker networks on the server. Response: [ListDockerNetworksResponse].
 #[typeshare]
 #[derive(
@@ -443,6 +469,49 @@ pub struct GetResourceMatchingContainerResponse {
 
 //
 
+/// Compare the running container of a Deployment or Stack service
+/// with what Komodo would create if it were deployed now,
+/// listing the fields which differ.
+/// Response: [DiffContainerConfigResponse].
+///
+/// Note. Only fields Komodo configures are compared, so extra
+/// environment variables / labels coming from the image are ignored.
+/// Values using Periphery secrets can't be resolved by Core, and are skipped.
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(DiffContainerConfigResponse)]
+#[error(serror::Error)]
+pub struct DiffContainerConfig {
+  /// The Deployment or Stack target.
+  pub target: ResourceTarget,
+  /// The service name. Required for Stack targets.
+  #[serde(default)]
+  pub service: Option<String>,
+}
+
+#[typeshare]
+pub type DiffContainerConfigResponse = Vec<ContainerConfigDiff>;
+
+/// A field which differs between the running container
+/// and the current resource configuration.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ContainerConfigDiff {
+  /// The compared field, eg. `image` or `environment.PORT`.
+  pub field: String,
+  /// The value on the running container.
+  /// Empty if it isn't set on the container.
+  pub current: String,
+  /// The value Komodo would use now.
+  /// Empty if it wouldn't be set.
+  pub expected: String,
+}
+
+//
+
 /// List all docker volumes on the target server.
 /// Response: [ListDockerVolumesResponse].
 #[typeshare]
@@ -0,0 +1,40 @@
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::server_profile::ServerProfile;
+
+use super::KomodoReadRequest;
+
+/// Get a specific Server Profile. Response: [ServerProfile].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(GetServerProfileResponse)]
+#[error(serror::Error)]
+pub struct GetServerProfile {
+  /// Id or name
+  pub profile: String,
+}
+
+#[typeshare]
+pub type GetServerProfileResponse = ServerProfile;
+
+//
+
+/// List the Server Profiles, sorted by name.
+/// Response: [ListServerProfilesResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ListServerProfilesResponse)]
+#[error(serror::Error)]
+pub struct ListServerProfiles {}
+
+#[typeshare]
+pub type ListServerProfilesResponse = Vec<ServerProfile>;
@@ -0,0 +1,26 @@
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::silence::AlertSilence;
+
+use super::KomodoReadRequest;
+
+/// List the alert silences, sorted by start timestamp descending.
+/// Response: [ListSilencesResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ListSilencesResponse)]
+#[error(serror::Error)]
+pub struct ListSilences {
+  /// Also include the silences which have already ended.
+  #[serde(default)]
+  pub include_expired: bool,
+}
+
+#[typeshare]
+pub type ListSilencesResponse = Vec<AlertSilence>;
@@ -0,0 +1,89 @@
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::{
+  I64,
+  snapshot::{Snapshot, SnapshotListItem},
+  sync::{DiffData, ResourceDiff},
+};
+
+use super::KomodoReadRequest;
+
+/// **Admin only.** List the snapshots, sorted by timestamp descending.
+/// Response: [ListSnapshotsResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ListSnapshotsResponse)]
+#[error(serror::Error)]
+pub struct ListSnapshots {}
+
+#[typeshare]
+pub type ListSnapshotsResponse = Vec<SnapshotListItem>;
+
+//
+
+/// **Admin only.** Get a snapshot, including the TOML contents.
+/// Response: [Snapshot].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(GetSnapshotResponse)]
+#[error(serror::Error)]
+pub struct GetSnapshot {
+  /// The snapshot id.
+  pub id: String,
+}
+
+#[typeshare]
+pub type GetSnapshotResponse = Snapshot;
+
+//
+
+/// **Admin only.** Get the changes between two snapshots.
+/// Response: [DiffSnapshotsResponse].
+///
+/// Resources, variables, and user groups are matched by name,
+/// so a renamed resource shows up as one deleted and one created.
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(DiffSnapshotsResponse)]
+#[error(serror::Error)]
+pub struct DiffSnapshots {
+  /// The id of the earlier snapshot.
+  pub from: String,
+  /// The id of the later snapshot.
+  /// If not provided, diffs against the current state.
+  #[serde(default)]
+  pub to: Option<String>,
+}
+
+/// Response for [DiffSnapshots].
+///
+/// In each diff, `current` is the state at `from`,
+/// and `proposed` is the state at `to`.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct DiffSnapshotsResponse {
+  /// Unix timestamp in milliseconds of the `from` snapshot.
+  pub from_ts: I64,
+  /// Unix timestamp in milliseconds of the `to` snapshot,
+  /// or now if diffing against the current state.
+  pub to_ts: I64,
+  /// The changed resources.
+  /// The target id is the resource name.
+  pub resource_updates: Vec<ResourceDiff>,
+  /// The changed variables.
+  pub variable_updates: Vec<DiffData>,
+  /// The changed user groups.
+  pub user_group_updates: Vec<DiffData>,
+}
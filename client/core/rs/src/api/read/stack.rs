@@ -151,6 +151,90 @@ pub type SearchStackLogResponse = Log;
 
 //
 
+/// Render the fully interpolated compose config for the stack,
+/// and validate it with `docker compose config`, without deploying.
+/// Variables, secrets, the environment and the extra args
+/// are all resolved as they would be for a deploy.
+/// Response: [RenderStackComposeResponse].
+///
+/// Note. The stack files are written to a temporary directory
+/// on the server, so repo based stacks render the latest commit,
+/// without changing the deployed files.
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(RenderStackComposeResponse)]
+#[error(serror::Error)]
+pub struct RenderStackCompose {
+  /// Id or name
+  #[serde(alias = "id", alias = "name")]
+  pub stack: String,
+  /// Filter to only render specific services.
+  /// If empty, will render all services.
+  #[serde(default)]
+  pub services: Vec<String>,
+}
+
+/// Response for [RenderStackCompose].
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct RenderStackComposeResponse {
+  /// Whether the compose config is valid.
+  pub valid: bool,
+  /// The output of `docker compose config`, or null if it is not valid.
+  /// Secret values are sanitized.
+  pub compose_config: Option<String>,
+  /// The `docker compose up` command a deploy would run,
+  /// including the extra args. Secret values are sanitized.
+  pub command: String,
+  /// The errors from writing the stack and `docker compose config`.
+  pub errors: Vec<String>,
+  /// The logs from interpolation, writing the stack and `docker compose config`.
+  pub logs: Vec<Log>,
+}
+
+//
+
+/// Get a unified diff of the stack files between the contents
+/// captured at the last deploy, and the latest contents.
+/// Use this to review the pending changes before deploying.
+/// Response: [DiffStackFileResponse].
+///
+/// The latest contents of repo / files on host stacks are
+/// as of the last stack cache refresh. Only changed files are included.
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(DiffStackFileResponse)]
+#[error(serror::Error)]
+pub struct DiffStackFile {
+  /// Id or name
+  #[serde(alias = "id", alias = "name")]
+  pub stack: String,
+  /// Only diff the file at this path.
+  /// If not provided, will diff all the stack files.
+  pub path: Option<String>,
+}
+
+#[typeshare]
+pub type DiffStackFileResponse = Vec<StackFileUnifiedDiff>;
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct StackFileUnifiedDiff {
+  /// The file path relative to the run directory.
+  pub path: String,
+  /// The unified diff, from the deployed contents (`a/`)
+  /// to the latest contents (`b/`). Missing files use `/dev/null`.
+  pub diff: String,
+}
+
+//
+
 /// Gets a list of existing values used as extra args across other stacks.
 /// Useful to offer suggestions. Response: [ListCommonStackExtraArgsResponse]
 #[typeshare]
@@ -0,0 +1,111 @@
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::swarm::{
+  SwarmService, SwarmServiceActionState, SwarmServiceListItem,
+  SwarmServiceQuery, SwarmServiceSummary,
+};
+
+use super::KomodoReadRequest;
+
+//
+
+/// Get a specific swarm service. Response: [SwarmService].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(GetSwarmServiceResponse)]
+#[error(serror::Error)]
+pub struct GetSwarmService {
+  /// Id or name
+  #[serde(alias = "id", alias = "name")]
+  pub swarm_service: String,
+}
+
+#[typeshare]
+pub type GetSwarmServiceResponse = SwarmService;
+
+//
+
+/// List swarm services matching optional query.
+/// Response: [ListSwarmServicesResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ListSwarmServicesResponse)]
+#[error(serror::Error)]
+pub struct ListSwarmServices {
+  /// optional structured query to filter swarm services.
+  #[serde(default)]
+  pub query: SwarmServiceQuery,
+}
+
+#[typeshare]
+pub type ListSwarmServicesResponse = Vec<SwarmServiceListItem>;
+
+//
+
+/// List swarm services matching optional query.
+/// Response: [ListFullSwarmServicesResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ListFullSwarmServicesResponse)]
+#[error(serror::Error)]
+pub struct ListFullSwarmServices {
+  /// optional structured query to filter swarm services.
+  #[serde(default)]
+  pub query: SwarmServiceQuery,
+}
+
+#[typeshare]
+pub type ListFullSwarmServicesResponse = Vec<SwarmService>;
+
+//
+
+/// Get current action state for the swarm service.
+/// Response: [SwarmServiceActionState].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(GetSwarmServiceActionStateResponse)]
+#[error(serror::Error)]
+pub struct GetSwarmServiceActionState {
+  /// Id or name
+  #[serde(alias = "id", alias = "name")]
+  pub swarm_service: String,
+}
+
+#[typeshare]
+pub type GetSwarmServiceActionStateResponse = SwarmServiceActionState;
+
+//
+
+/// Lists the services in the Swarm stack deployed by the swarm service,
+/// with their running / desired tasks.
+/// Response: [ListSwarmStackServicesResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ListSwarmStackServicesResponse)]
+#[error(serror::Error)]
+pub struct ListSwarmStackServices {
+  /// Id or name
+  #[serde(alias = "id", alias = "name")]
+  pub swarm_service: String,
+}
+
+#[typeshare]
+pub type ListSwarmStackServicesResponse = Vec<SwarmServiceSummary>;
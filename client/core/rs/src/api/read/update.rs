@@ -4,8 +4,8 @@ use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
 use crate::entities::{
-  MongoDocument,
-  update::{Update, UpdateListItem},
+  I64, MongoDocument, Operation, ResourceTarget, U64,
+  update::{ExecutionMetrics, TestReport, Update, UpdateListItem},
 };
 
 use super::KomodoReadRequest;
@@ -56,3 +56,91 @@ pub struct ListUpdatesResponse {
   /// If there is a next page of data, pass this to `page` to get it.
   pub next_page: Option<u32>,
 }
+
+//
+
+/// List the test reports attached to the target resource's
+/// recent Updates, and the test cases which are flaky across them.
+/// Only Builds and Repos produce test reports.
+/// Response: [ListTestReportsResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ListTestReportsResponse)]
+#[error(serror::Error)]
+pub struct ListTestReports {
+  /// The target Build or Repo.
+  pub target: ResourceTarget,
+  /// The number of most recent runs to include. Default: 20
+  pub limit: Option<I64>,
+}
+
+/// Response for [ListTestReports].
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ListTestReportsResponse {
+  /// The runs with test reports, sorted by timestamp descending.
+  pub runs: Vec<TestReportRun>,
+  /// Test cases which failed in some runs of their report, but not all.
+  pub flaky: Vec<FlakyTestCase>,
+}
+
+/// The test reports of a single Update.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TestReportRun {
+  /// The id of the update
+  pub update_id: String,
+  /// Which operation was run
+  pub operation: Operation,
+  /// The starting time of the operation
+  pub start_ts: I64,
+  /// The commit hash the tests were run on
+  pub commit_hash: String,
+  /// The test reports produced by the run
+  pub reports: Vec<TestReport>,
+}
+
+/// A test case with inconsistent results across runs.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FlakyTestCase {
+  /// The name of the report the test case is in
+  pub report: String,
+  /// The test case name
+  pub name: String,
+  /// The number of runs the test case failed
+  pub failures: I64,
+  /// The number of runs which produced the report
+  pub runs: I64,
+}
+
+//
+
+/// Get the deploy / run metrics of a resource,
+/// aggregated from its Update history.
+/// Response: [ExecutionMetrics].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(GetResourceMetricsResponse)]
+#[error(serror::Error)]
+pub struct GetResourceMetrics {
+  /// The target resource.
+  pub target: ResourceTarget,
+  /// The number of days to aggregate.
+  /// Default: 30
+  #[serde(default = "default_metrics_days")]
+  pub days: U64,
+}
+
+fn default_metrics_days() -> U64 {
+  30
+}
+
+#[typeshare]
+pub type GetResourceMetricsResponse = ExecutionMetrics;
@@ -0,0 +1,267 @@
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::{
+  action::_PartialActionConfig, alerter::_PartialAlerterConfig,
+  build::_PartialBuildConfig, builder::_PartialBuilderConfig,
+  deployment::_PartialDeploymentConfig,
+  procedure::_PartialProcedureConfig, repo::_PartialRepoConfig,
+  server::_PartialServerConfig, stack::_PartialStackConfig,
+  swarm::_PartialSwarmServiceConfig,
+  sync::_PartialResourceSyncConfig,
+};
+
+use super::KomodoReadRequest;
+
+/// An error with one field of a partial config.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConfigFieldError {
+  /// The config field, eg. `server_id`.
+  /// For Builder configs, this is the field inside `params`.
+  pub field: String,
+  /// The error message.
+  pub error: String,
+}
+
+/// Response for the ValidateConfig requests.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ValidateConfigResponse {
+  /// The errors found in the config.
+  /// Empty if the config is valid.
+  pub errors: Vec<ConfigFieldError>,
+}
+
+//
+
+/// Validates a partial Server config without saving it.
+/// Checks referenced resources exist and can be attached,
+/// and the fields parse. Response: [ValidateConfigResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ValidateConfigResponse)]
+#[error(serror::Error)]
+pub struct ValidateServerConfig {
+  /// Validate as an update to this Server (id or name).
+  /// If empty, validates as the config of a new Server.
+  #[serde(default)]
+  pub id: String,
+  /// The partial config to validate.
+  pub config: _PartialServerConfig,
+}
+
+//
+
+/// Validates a partial Stack config without saving it.
+/// Checks referenced resources exist and can be attached,
+/// and the fields parse. Response: [ValidateConfigResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ValidateConfigResponse)]
+#[error(serror::Error)]
+pub struct ValidateStackConfig {
+  /// Validate as an update to this Stack (id or name).
+  /// If empty, validates as the config of a new Stack.
+  #[serde(default)]
+  pub id: String,
+  /// The partial config to validate.
+  pub config: _PartialStackConfig,
+}
+
+//
+
+/// Validates a partial Deployment config without saving it.
+/// Checks referenced resources exist and can be attached,
+/// and the fields parse. Response: [ValidateConfigResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ValidateConfigResponse)]
+#[error(serror::Error)]
+pub struct ValidateDeploymentConfig {
+  /// Validate as an update to this Deployment (id or name).
+  /// If empty, validates as the config of a new Deployment.
+  #[serde(default)]
+  pub id: String,
+  /// The partial config to validate.
+  pub config: _PartialDeploymentConfig,
+}
+
+//
+
+/// Validates a partial Swarm Service config without saving it.
+/// Checks referenced resources exist and can be attached,
+/// and the fields parse. Response: [ValidateConfigResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ValidateConfigResponse)]
+#[error(serror::Error)]
+pub struct ValidateSwarmServiceConfig {
+  /// Validate as an update to this Swarm Service (id or name).
+  /// If empty, validates as the config of a new Swarm Service.
+  #[serde(default)]
+  pub id: String,
+  /// The partial config to validate.
+  pub config: _PartialSwarmServiceConfig,
+}
+
+//
+
+/// Validates a partial Build config without saving it.
+/// Checks referenced resources exist and can be attached,
+/// and the fields parse. Response: [ValidateConfigResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ValidateConfigResponse)]
+#[error(serror::Error)]
+pub struct ValidateBuildConfig {
+  /// Validate as an update to this Build (id or name).
+  /// If empty, validates as the config of a new Build.
+  #[serde(default)]
+  pub id: String,
+  /// The partial config to validate.
+  pub config: _PartialBuildConfig,
+}
+
+//
+
+/// Validates a partial Repo config without saving it.
+/// Checks referenced resources exist and can be attached,
+/// and the fields parse. Response: [ValidateConfigResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ValidateConfigResponse)]
+#[error(serror::Error)]
+pub struct ValidateRepoConfig {
+  /// Validate as an update to this Repo (id or name).
+  /// If empty, validates as the config of a new Repo.
+  #[serde(default)]
+  pub id: String,
+  /// The partial config to validate.
+  pub config: _PartialRepoConfig,
+}
+
+//
+
+/// Validates a partial Procedure config without saving it.
+/// Checks referenced resources exist and can be attached,
+/// and the fields parse. Response: [ValidateConfigResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ValidateConfigResponse)]
+#[error(serror::Error)]
+pub struct ValidateProcedureConfig {
+  /// Validate as an update to this Procedure (id or name).
+  /// If empty, validates as the config of a new Procedure.
+  #[serde(default)]
+  pub id: String,
+  /// The partial config to validate.
+  pub config: _PartialProcedureConfig,
+}
+
+//
+
+/// Validates a partial Action config without saving it.
+/// Checks referenced resources exist and can be attached,
+/// and the fields parse. Response: [ValidateConfigResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ValidateConfigResponse)]
+#[error(serror::Error)]
+pub struct ValidateActionConfig {
+  /// Validate as an update to this Action (id or name).
+  /// If empty, validates as the config of a new Action.
+  #[serde(default)]
+  pub id: String,
+  /// The partial config to validate.
+  pub config: _PartialActionConfig,
+}
+
+//
+
+/// Validates a partial Resource Sync config without saving it.
+/// Checks referenced resources exist and can be attached,
+/// and the fields parse. Response: [ValidateConfigResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ValidateConfigResponse)]
+#[error(serror::Error)]
+pub struct ValidateResourceSyncConfig {
+  /// Validate as an update to this Resource Sync (id or name).
+  /// If empty, validates as the config of a new Resource Sync.
+  #[serde(default)]
+  pub id: String,
+  /// The partial config to validate.
+  pub config: _PartialResourceSyncConfig,
+}
+
+//
+
+/// Validates a partial Builder config without saving it.
+/// Checks referenced resources exist and can be attached,
+/// and the fields parse. Response: [ValidateConfigResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ValidateConfigResponse)]
+#[error(serror::Error)]
+pub struct ValidateBuilderConfig {
+  /// Validate as an update to this Builder (id or name).
+  /// If empty, validates as the config of a new Builder.
+  #[serde(default)]
+  pub id: String,
+  /// The partial config to validate.
+  pub config: _PartialBuilderConfig,
+}
+
+//
+
+/// Validates a partial Alerter config without saving it.
+/// Checks referenced resources exist and can be attached,
+/// and the fields parse. Response: [ValidateConfigResponse].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoReadRequest)]
+#[response(ValidateConfigResponse)]
+#[error(serror::Error)]
+pub struct ValidateAlerterConfig {
+  /// Validate as an update to this Alerter (id or name).
+  /// If empty, validates as the config of a new Alerter.
+  #[serde(default)]
+  pub id: String,
+  /// The partial config to validate.
+  pub config: _PartialAlerterConfig,
+}
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+/// Download the UI defined file contents of a Stack,
+/// including contents moved to the Core Stack file storage.
+/// Responds with a stream of the raw file contents.
+///
+/// POST `/stack_file/download` with this JSON body.
+/// Requires Read permission on the Stack.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DownloadStackFileBody {
+  /// Stack Id or name
+  pub stack: String,
+}
+
+/// Replace the UI defined file contents of a Stack,
+/// streaming them directly into the Core Stack file storage.
+/// Use this for files too large to send with `UpdateStack`.
+///
+/// POST `/stack_file/upload?stack=<stack>` with the raw file contents
+/// as the body. Responds with the [Update][crate::entities::update::Update].
+/// Requires Write permission on the Stack,
+/// and Core `stack_file_storage` to be configured.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UploadStackFileQuery {
+  /// Stack Id or name
+  pub stack: String,
+}
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
+use crate::entities::U64;
+
 /// Query to connect to a terminal (interactive shell over websocket) on the given server.
 /// TODO: Document calling.
 #[typeshare]
@@ -13,9 +15,16 @@ pub struct ConnectTerminalQuery {
   /// the call will fail.
   /// Create a terminal using [CreateTerminal][super::write::server::CreateTerminal]
   pub terminal: String,
+  /// Attach to the session without writing to stdin,
+  /// for example to observe another user's session.
+  /// Users without Execute permissions are always attached read-only.
+  /// Default: false
+  #[serde(default)]
+  pub read_only: bool,
 }
 
 /// Execute a terminal command on the given server.
+/// Requires Execute and Terminal permissions on the Server.
 /// TODO: Document calling.
 #[typeshare]
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -42,9 +51,16 @@ pub struct ConnectContainerExecQuery {
   pub container: String,
   /// The shell to use (eg. `sh` or `bash`)
   pub shell: String,
+  /// Attach to the session without writing to stdin,
+  /// for example to observe another user's session.
+  /// Users without Execute permissions are always attached read-only.
+  /// Default: false
+  #[serde(default)]
+  pub read_only: bool,
 }
 
 /// Execute a command in the given containers shell.
+/// Requires Execute and Terminal permissions on the resource.
 /// TODO: Document calling.
 #[typeshare]
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -69,9 +85,16 @@ pub struct ConnectDeploymentExecQuery {
   pub deployment: String,
   /// The shell to use (eg. `sh` or `bash`)
   pub shell: String,
+  /// Attach to the session without writing to stdin,
+  /// for example to observe another user's session.
+  /// Users without Execute permissions are always attached read-only.
+  /// Default: false
+  #[serde(default)]
+  pub read_only: bool,
 }
 
 /// Execute a command in the given containers shell.
+/// Requires Execute and Terminal permissions on the resource.
 /// TODO: Document calling.
 #[typeshare]
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -86,6 +109,7 @@ pub struct ExecuteDeploymentExecBody {
 
 /// Query to connect to a container exec session (interactive shell over websocket) on the given Stack / service.
 /// This call will use access to the Stack Terminal to permission the call.
+/// The service's current container is resolved automatically.
 /// TODO: Document calling.
 #[typeshare]
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -96,19 +120,85 @@ pub struct ConnectStackExecQuery {
   pub service: String,
   /// The shell to use (eg. `sh` or `bash`)
   pub shell: String,
+  /// Attach to the session without writing to stdin,
+  /// for example to observe another user's session.
+  /// Users without Execute permissions are always attached read-only.
+  /// Default: false
+  #[serde(default)]
+  pub read_only: bool,
 }
 
 /// Execute a command in the given containers shell.
+/// Requires Execute and Terminal permissions on the resource.
 /// TODO: Document calling.
 #[typeshare]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ExecuteStackExecBody {
   /// Stack Id or name
   pub stack: String,
-  /// The service name to connect to
+  /// The service name to connect to.
+  /// The service's current container is resolved automatically.
   pub service: String,
   /// The shell to use (eg. `sh` or `bash`)
   pub shell: String,
   /// The command to execute.
   pub command: String,
 }
+
+// =====================
+// = TERMINAL TRANSFER =
+// =====================
+
+/// Binary terminal websocket messages starting with this byte
+/// upload a file. The byte is followed by a JSON encoded
+/// [TerminalFileUpload], a newline (`\n`), and then the raw file contents.
+pub const TERMINAL_FILE_UPLOAD_PREFIX: u8 = 0x01;
+
+/// Binary terminal websocket messages starting with this byte
+/// request a file download. The byte is followed by a JSON
+/// encoded [TerminalFileDownload].
+pub const TERMINAL_FILE_DOWNLOAD_PREFIX: u8 = 0x02;
+
+/// Text terminal websocket messages starting with this
+/// are followed by a JSON encoded [TerminalFileMessage].
+pub const TERMINAL_FILE_MESSAGE_PREFIX: &str = "__KOMODO_FILE__";
+
+/// The maximum size of a file which can be transferred
+/// over the terminal websocket. 10 MiB.
+pub const MAX_TERMINAL_FILE_BYTES: usize = 10 * 1024 * 1024;
+
+/// Upload a file through an open terminal websocket.
+/// For container exec sessions, the file is written inside the container.
+/// Host paths must be inside the Periphery `terminal_file_root`.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TerminalFileUpload {
+  /// The path to write the file to.
+  /// Relative paths are relative to the terminal working directory.
+  pub path: String,
+}
+
+/// Download a file through an open terminal websocket.
+/// For container exec sessions, the file is read from inside the container.
+/// Host paths must be inside the Periphery `terminal_file_root`.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TerminalFileDownload {
+  /// The path of the file to download.
+  /// Relative paths are relative to the terminal working directory.
+  pub path: String,
+}
+
+/// Sent back over the terminal websocket
+/// in response to file uploads / downloads.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type", content = "params")]
+pub enum TerminalFileMessage {
+  /// The file was uploaded successfully.
+  Uploaded { path: String, size: U64 },
+  /// The next binary message contains the file contents.
+  Download { path: String, size: U64 },
+  /// The transfer failed.
+  Error { path: String, message: String },
+}
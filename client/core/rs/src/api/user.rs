@@ -3,7 +3,9 @@ use resolver_api::{HasResponse, Resolve};
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
-use crate::entities::{I64, NoData, ResourceTarget};
+use crate::entities::{
+  I64, NoData, ResourceTarget, api_key::ApiKeyScope,
+};
 
 pub trait KomodoUserRequest: HasResponse {}
 
@@ -65,6 +67,12 @@ pub struct CreateApiKey {
   /// Default is 0, which means no expiry.
   #[serde(default)]
   pub expires: I64,
+
+  /// Restrict what the api key can be used for.
+  /// Resource targets can be given by name or id.
+  /// Default is no restrictions.
+  #[serde(default)]
+  pub scope: ApiKeyScope,
 }
 
 /// Response for [CreateApiKey].
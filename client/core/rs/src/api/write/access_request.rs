@@ -0,0 +1,85 @@
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::{
+  I64, ResourceTarget, access_request::AccessRequest,
+  permission::PermissionLevelAndSpecifics,
+};
+
+use super::KomodoWriteRequest;
+
+/// Request temporary elevated access to a resource.
+/// An admin must approve the request with [ApproveAccessRequest],
+/// after which the access lasts for `duration_ms`.
+/// Response: [AccessRequest].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(RequestAccessResponse)]
+#[error(serror::Error)]
+pub struct RequestAccess {
+  /// The resource to request access on. Accepts name or id.
+  pub target: ResourceTarget,
+  /// The requested permission on the resource.
+  pub permission: PermissionLevelAndSpecifics,
+  /// How long the access should last once approved, in milliseconds.
+  pub duration_ms: I64,
+  /// Why the access is needed, shown to approvers.
+  #[serde(default)]
+  pub reason: String,
+}
+
+#[typeshare]
+pub type RequestAccessResponse = AccessRequest;
+
+//
+
+/// **Admin only.** Approve a pending access request,
+/// giving the user the requested permission until it expires.
+/// Response: [AccessRequest].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(ApproveAccessRequestResponse)]
+#[error(serror::Error)]
+pub struct ApproveAccessRequest {
+  /// The id of the access request.
+  pub id: String,
+  /// Override the requested duration, in milliseconds.
+  #[serde(default)]
+  pub duration_ms: Option<I64>,
+  /// An optional comment, added to the Update.
+  #[serde(default)]
+  pub comment: String,
+}
+
+#[typeshare]
+pub type ApproveAccessRequestResponse = AccessRequest;
+
+//
+
+/// **Admin only.** Reject a pending access request.
+/// Response: [AccessRequest].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(RejectAccessRequestResponse)]
+#[error(serror::Error)]
+pub struct RejectAccessRequest {
+  /// The id of the access request.
+  pub id: String,
+  /// An optional comment, added to the Update.
+  #[serde(default)]
+  pub comment: String,
+}
+
+#[typeshare]
+pub type RejectAccessRequestResponse = AccessRequest;
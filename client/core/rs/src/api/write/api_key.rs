@@ -5,7 +5,7 @@ use typeshare::typeshare;
 
 use crate::{
   api::user::CreateApiKeyResponse,
-  entities::{I64, NoData},
+  entities::{I64, NoData, api_key::ApiKeyScope},
 };
 
 use super::KomodoWriteRequest;
@@ -30,6 +30,11 @@ pub struct CreateApiKeyForServiceUser {
   /// Default is 0, which means no expiry.
   #[serde(default)]
   pub expires: I64,
+  /// Restrict what the api key can be used for.
+  /// Resource targets can be given by name or id.
+  /// Default is no restrictions.
+  #[serde(default)]
+  pub scope: ApiKeyScope,
 }
 
 #[typeshare]
@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
 use crate::entities::{
-  NoData,
+  I64, NoData,
   build::{_PartialBuildConfig, Build},
   update::Update,
 };
@@ -85,6 +85,12 @@ pub struct UpdateBuild {
   pub id: String,
   /// The partial config update to apply.
   pub config: _PartialBuildConfig,
+  /// The resource revision the update is based on.
+  /// If provided and the resource has been updated since,
+  /// the update is rejected with status 409 (Conflict),
+  /// with the current resource as JSON in the error trace.
+  #[serde(default)]
+  pub revision: Option<I64>,
 }
 
 //
@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
 use crate::entities::{
+  I64,
   builder::{Builder, PartialBuilderConfig},
   update::Update,
 };
@@ -84,6 +85,12 @@ pub struct UpdateBuilder {
   pub id: String,
   /// The partial config update to apply.
   pub config: PartialBuilderConfig,
+  /// The resource revision the update is based on.
+  /// If provided and the resource has been updated since,
+  /// the update is rejected with status 409 (Conflict),
+  /// with the current resource as JSON in the error trace.
+  #[serde(default)]
+  pub revision: Option<I64>,
 }
 
 //
@@ -0,0 +1,54 @@
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::execution_approval::ExecutionApproval;
+
+use super::KomodoWriteRequest;
+
+/// Approve a Procedure run waiting at a stage which requires approval,
+/// so the run continues. Requires Write permission on the Procedure,
+/// and the user must be allowed by the stage `approvers` /
+/// `forbid_self_approval`.
+/// Response: [ExecutionApproval].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(ApproveExecutionResponse)]
+#[error(serror::Error)]
+pub struct ApproveExecution {
+  /// The id of the execution approval.
+  pub id: String,
+  /// An optional comment, added to the Procedure Update.
+  #[serde(default)]
+  pub comment: String,
+}
+
+#[typeshare]
+pub type ApproveExecutionResponse = ExecutionApproval;
+
+//
+
+/// Reject a Procedure run waiting at a stage which requires approval,
+/// failing the stage. Requires Write permission on the Procedure.
+/// Response: [ExecutionApproval].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(RejectExecutionResponse)]
+#[error(serror::Error)]
+pub struct RejectExecution {
+  /// The id of the execution approval.
+  pub id: String,
+  /// An optional comment, added to the Procedure Update.
+  #[serde(default)]
+  pub comment: String,
+}
+
+#[typeshare]
+pub type RejectExecutionResponse = ExecutionApproval;
@@ -0,0 +1,111 @@
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{
+  api::execute::Execution,
+  entities::{
+    ScheduleFormat,
+    execution_schedule::{ExecutionSchedule, MissedRunPolicy},
+  },
+};
+
+use super::KomodoWriteRequest;
+
+/// Create an Execution Schedule, which runs the execution
+/// on a schedule as the user who created it.
+/// Requires Execute permission on the execution target.
+/// Response: [ExecutionSchedule].
+#[typeshare]
+#[derive(
+  Debug, Clone, Serialize, Deserialize, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(CreateExecutionScheduleResponse)]
+#[error(serror::Error)]
+pub struct CreateExecutionSchedule {
+  /// The name of the schedule.
+  pub name: String,
+  /// A description of the schedule. default: "".
+  #[serde(default)]
+  pub description: String,
+  /// The execution to run.
+  pub execution: Execution,
+  /// Whether the schedule is enabled. default: true.
+  #[serde(default = "default_enabled")]
+  pub enabled: bool,
+  /// The format of the schedule. default: English.
+  #[serde(default)]
+  pub schedule_format: ScheduleFormat,
+  /// The schedule for the execution.
+  pub schedule: String,
+  /// The schedule timezone. If empty, uses the Core timezone.
+  #[serde(default)]
+  pub schedule_timezone: String,
+  /// What to do with runs missed while Core was down. default: Skip.
+  #[serde(default)]
+  pub missed_runs: MissedRunPolicy,
+}
+
+fn default_enabled() -> bool {
+  true
+}
+
+#[typeshare]
+pub type CreateExecutionScheduleResponse = ExecutionSchedule;
+
+//
+
+/// Update an Execution Schedule.
+/// Only the creator or an admin can update the schedule.
+/// Fields which are not passed keep their current values.
+/// Response: [ExecutionSchedule].
+#[typeshare]
+#[derive(
+  Debug, Clone, Serialize, Deserialize, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(UpdateExecutionScheduleResponse)]
+#[error(serror::Error)]
+pub struct UpdateExecutionSchedule {
+  /// Id or name
+  pub id: String,
+  /// Update the description.
+  pub description: Option<String>,
+  /// Update the execution.
+  pub execution: Option<Execution>,
+  /// Update whether the schedule is enabled.
+  pub enabled: Option<bool>,
+  /// Update the schedule format.
+  pub schedule_format: Option<ScheduleFormat>,
+  /// Update the schedule.
+  pub schedule: Option<String>,
+  /// Update the schedule timezone.
+  pub schedule_timezone: Option<String>,
+  /// Update what to do with missed runs.
+  pub missed_runs: Option<MissedRunPolicy>,
+}
+
+#[typeshare]
+pub type UpdateExecutionScheduleResponse = ExecutionSchedule;
+
+//
+
+/// Delete an Execution Schedule.
+/// Only the creator or an admin can delete the schedule.
+/// Response: [ExecutionSchedule].
+#[typeshare]
+#[derive(
+  Debug, Clone, Serialize, Deserialize, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(DeleteExecutionScheduleResponse)]
+#[error(serror::Error)]
+pub struct DeleteExecutionSchedule {
+  /// Id or name
+  pub id: String,
+}
+
+#[typeshare]
+pub type DeleteExecutionScheduleResponse = ExecutionSchedule;
@@ -0,0 +1,65 @@
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::{
+  I64, MaintenanceWindow,
+  external_status::{ExternalState, ExternalStatus},
+};
+
+use super::KomodoWriteRequest;
+
+/// **Admin only.** Report the state of a resource managed outside
+/// of Komodo, such as a Kubernetes operator or a backup script.
+/// The External Status is created on the first report.
+/// Fields which are not passed keep their current values.
+/// Response: [ExternalStatus].
+#[typeshare]
+#[derive(
+  Debug, Clone, Serialize, Deserialize, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(PushExternalStatusResponse)]
+#[error(serror::Error)]
+pub struct PushExternalStatus {
+  /// The name of the external resource.
+  pub name: String,
+  /// The current state.
+  pub state: ExternalState,
+  /// A message describing the state. default: "".
+  #[serde(default)]
+  pub message: String,
+  /// Update the description.
+  pub description: Option<String>,
+  /// Update the seconds without a report before the state becomes Unknown.
+  pub stale_after: Option<I64>,
+  /// Update whether to send alerts.
+  pub send_alerts: Option<bool>,
+  /// Update the maintenance windows.
+  pub maintenance_windows: Option<Vec<MaintenanceWindow>>,
+  /// Update the quick links.
+  pub links: Option<Vec<String>>,
+}
+
+#[typeshare]
+pub type PushExternalStatusResponse = ExternalStatus;
+
+//
+
+/// **Admin only.** Delete an External Status,
+/// and resolve any open alert for it. Response: [ExternalStatus].
+#[typeshare]
+#[derive(
+  Debug, Clone, Serialize, Deserialize, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(DeleteExternalStatusResponse)]
+#[error(serror::Error)]
+pub struct DeleteExternalStatus {
+  /// The name of the external resource.
+  pub name: String,
+}
+
+#[typeshare]
+pub type DeleteExternalStatusResponse = ExternalStatus;
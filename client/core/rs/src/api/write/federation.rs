@@ -0,0 +1,91 @@
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::federation::FederationPeer;
+
+use super::KomodoWriteRequest;
+
+/// **Admin only.** Register a peer Komodo Core,
+/// to include its resources and alerts in the federated view.
+/// Response: [FederationPeer].
+#[typeshare]
+#[derive(
+  Debug, Clone, Serialize, Deserialize, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(CreateFederationPeerResponse)]
+#[error(serror::Error)]
+pub struct CreateFederationPeer {
+  /// The name of the peer.
+  pub name: String,
+  /// A description of the peer. default: "".
+  #[serde(default)]
+  pub description: String,
+  /// The address of the peer Core.
+  pub address: String,
+  /// The api key used to read from the peer.
+  pub api_key: String,
+  /// The api secret used to read from the peer.
+  pub api_secret: String,
+  /// Whether the peer is included in the federated view. default: true.
+  #[serde(default = "default_enabled")]
+  pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+  true
+}
+
+#[typeshare]
+pub type CreateFederationPeerResponse = FederationPeer;
+
+//
+
+/// **Admin only.** Update a federation peer.
+/// Fields which are not passed keep their current values.
+/// Response: [FederationPeer].
+#[typeshare]
+#[derive(
+  Debug, Clone, Serialize, Deserialize, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(UpdateFederationPeerResponse)]
+#[error(serror::Error)]
+pub struct UpdateFederationPeer {
+  /// Id or name
+  pub id: String,
+  /// Update the description.
+  pub description: Option<String>,
+  /// Update the address.
+  pub address: Option<String>,
+  /// Update the api key.
+  pub api_key: Option<String>,
+  /// Update the api secret.
+  pub api_secret: Option<String>,
+  /// Update whether the peer is included in the federated view.
+  pub enabled: Option<bool>,
+}
+
+#[typeshare]
+pub type UpdateFederationPeerResponse = FederationPeer;
+
+//
+
+/// **Admin only.** Delete a federation peer.
+/// Response: [FederationPeer].
+#[typeshare]
+#[derive(
+  Debug, Clone, Serialize, Deserialize, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(DeleteFederationPeerResponse)]
+#[error(serror::Error)]
+pub struct DeleteFederationPeer {
+  /// Id or name
+  pub id: String,
+}
+
+#[typeshare]
+pub type DeleteFederationPeerResponse = FederationPeer;
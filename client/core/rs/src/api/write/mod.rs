@@ -1,37 +1,59 @@
+mod access_request;
 mod action;
 mod alerter;
 mod api_key;
 mod build;
 mod builder;
 mod deployment;
+mod execution_approval;
+mod execution_schedule;
+mod external_status;
+mod federation;
 mod permissions;
 mod procedure;
 mod provider;
+mod queued_execution;
 mod repo;
 mod resource;
 mod server;
+mod server_profile;
+mod silence;
+mod snapshot;
 mod stack;
+mod swarm;
 mod sync;
 mod tags;
+mod update;
 mod user;
 mod user_group;
 mod variable;
 
+pub use access_request::*;
 pub use action::*;
 pub use alerter::*;
 pub use api_key::*;
 pub use build::*;
 pub use builder::*;
 pub use deployment::*;
+pub use execution_approval::*;
+pub use execution_schedule::*;
+pub use external_status::*;
+pub use federation::*;
 pub use permissions::*;
 pub use procedure::*;
 pub use provider::*;
+pub use queued_execution::*;
 pub use repo::*;
 pub use resource::*;
 pub use server::*;
+pub use server_profile::*;
+pub use silence::*;
+pub use snapshot::*;
 pub use stack::*;
+pub use swarm::*;
 pub use sync::*;
 pub use tags::*;
+pub use update::*;
 pub use user::*;
 pub use user_group::*;
 pub use variable::*;
@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
 use crate::entities::{
-  NoData, ResourceTarget, ResourceTargetVariant,
+  I64, NoData, ResourceTarget, ResourceTargetVariant,
   permission::{PermissionLevelAndSpecifics, UserTarget},
 };
 
@@ -26,6 +26,10 @@ pub struct UpdatePermissionOnTarget {
   pub resource_target: ResourceTarget,
   /// Specify the permission level.
   pub permission: PermissionLevelAndSpecifics,
+  /// Unix timestamp in milliseconds the permission expires,
+  /// after which it is revoked. Default is 0, which never expires.
+  #[serde(default)]
+  pub expires_at: I64,
 }
 
 #[typeshare]
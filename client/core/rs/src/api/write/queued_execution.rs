@@ -0,0 +1,57 @@
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::{
+  api::execute::Execution,
+  entities::{I64, queued_execution::QueuedExecution},
+};
+
+use super::KomodoWriteRequest;
+
+/// Queue an execution to run once Core can reach the Server.
+/// Queued executions run in order, as the user who queued them.
+/// Requires Execute permission on the Server.
+/// Response: [QueuedExecution].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(EnqueueExecutionResponse)]
+#[error(serror::Error)]
+pub struct EnqueueExecution {
+  /// The Server to wait for (id or name).
+  pub server: String,
+  /// The execution to run, eg DeployStack.
+  /// It must run on the Server, eg. on a Stack attached to it.
+  pub execution: Execution,
+  /// Expire the execution if the Server isn't reachable
+  /// within this many seconds. If not provided, never expires.
+  #[serde(default)]
+  pub expires_after: Option<I64>,
+}
+
+#[typeshare]
+pub type EnqueueExecutionResponse = QueuedExecution;
+
+//
+
+/// Cancel a Queued Execution which hasn't started running.
+/// Requires Execute permission on the Server.
+/// Response: [QueuedExecution].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(CancelQueuedExecutionResponse)]
+#[error(serror::Error)]
+pub struct CancelQueuedExecution {
+  /// The id of the queued execution.
+  pub id: String,
+}
+
+#[typeshare]
+pub type CancelQueuedExecutionResponse = QueuedExecution;
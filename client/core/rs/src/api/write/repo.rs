@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
 use crate::entities::{
-  NoData,
+  I64, NoData,
   repo::{_PartialRepoConfig, Repo},
   update::Update,
 };
@@ -88,6 +88,12 @@ pub struct UpdateRepo {
   pub id: String,
   /// The partial config update to apply.
   pub config: _PartialRepoConfig,
+  /// The resource revision the update is based on.
+  /// If provided and the resource has been updated since,
+  /// the update is rejected with status 409 (Conflict),
+  /// with the current resource as JSON in the error trace.
+  #[serde(default)]
+  pub revision: Option<I64>,
 }
 
 //
@@ -132,6 +138,7 @@ pub enum RepoWebhookAction {
   Clone,
   Pull,
   Build,
+  Pipeline,
 }
 
 /// Create a webhook on the github repo attached to the (Komodo) Repo resource.
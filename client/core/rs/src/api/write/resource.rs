@@ -1,9 +1,14 @@
+use std::collections::HashMap;
+
 use derive_empty_traits::EmptyTraits;
 use resolver_api::Resolve;
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
-use crate::entities::{NoData, ResourceTarget};
+use crate::entities::{
+  NoData, ResourceTarget, resource_lock::ResourceConcurrency,
+  update::Update,
+};
 
 use super::KomodoWriteRequest;
 
@@ -11,6 +16,8 @@ use super::KomodoWriteRequest;
 /// - description
 /// - template
 /// - tags
+/// - metadata
+/// - concurrency
 /// Response: [NoData].
 #[typeshare]
 #[derive(
@@ -31,7 +38,40 @@ pub struct UpdateResourceMeta {
   /// The exact tags to set,
   /// or null for no update
   pub tags: Option<Vec<String>>,
+  /// The exact metadata to set,
+  /// or null for no update
+  #[serde(default)]
+  pub metadata: Option<HashMap<String, String>>,
+  /// New concurrency group and policy to set,
+  /// or null for no update
+  #[serde(default)]
+  pub concurrency: Option<ResourceConcurrency>,
 }
 
 #[typeshare]
 pub type UpdateResourceMetaResponse = NoData;
+
+//
+
+/// Set whether the resource is protected from
+/// Delete / Destroy / Prune executions.
+/// Response: [Update].
+///
+/// Note. Any user with Write permission can protect a resource,
+/// but only admins can remove the protection.
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(SetResourceProtectedResponse)]
+#[error(serror::Error)]
+pub struct SetResourceProtected {
+  /// The target resource.
+  pub target: ResourceTarget,
+  /// Whether the resource is protected.
+  pub protected: bool,
+}
+
+#[typeshare]
+pub type SetResourceProtectedResponse = Update;
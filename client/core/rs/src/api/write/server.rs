@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
 use crate::entities::{
-  NoData,
+  I64, NoData,
   server::{_PartialServerConfig, Server},
   update::Update,
 };
@@ -85,6 +85,12 @@ pub struct UpdateServer {
   pub id: String,
   /// The partial config update to apply.
   pub config: _PartialServerConfig,
+  /// The resource revision the update is based on.
+  /// If provided and the resource has been updated since,
+  /// the update is rejected with status 409 (Conflict),
+  /// with the current resource as JSON in the error trace.
+  #[serde(default)]
+  pub revision: Option<I64>,
 }
 
 //
@@ -110,7 +116,7 @@ pub struct RenameServer {
 /// Create a docker network on the server.
 /// Response: [Update]
 ///
-/// `docker network create {name}`
+/// `docker network create [--ipv6] [--subnet {subnet}] {name}`
 #[typeshare]
 #[derive(
   Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
@@ -123,6 +129,13 @@ pub struct CreateNetwork {
   pub server: String,
   /// The name of the network to create.
   pub name: String,
+  /// Enable IPv6 on the network, making it dual-stack.
+  #[serde(default)]
+  pub enable_ipv6: bool,
+  /// Subnets to use for the network, eg `172.28.0.0/16`
+  /// and / or `fd00:28::/64`. Otherwise docker assigns them.
+  #[serde(default)]
+  pub subnets: Vec<String>,
 }
 
 //
@@ -164,6 +177,11 @@ pub struct CreateTerminal {
   /// Default: `bash`
   #[serde(default = "default_command")]
   pub command: String,
+  /// Create the terminal using one of the Server's
+  /// [TerminalProfile][crate::entities::server::TerminalProfile]s.
+  /// If provided, `command` is ignored in favor of the profile command.
+  #[serde(default)]
+  pub profile: Option<String>,
   /// Default: `Never`
   #[serde(default)]
   pub recreate: TerminalRecreateMode,
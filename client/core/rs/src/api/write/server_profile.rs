@@ -0,0 +1,78 @@
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::server_profile::{
+  ServerProfile, ServerProfileConfig,
+};
+
+use super::KomodoWriteRequest;
+
+/// **Admin only.** Create a Server Profile, which Servers can
+/// inherit shared config defaults from. Response: [ServerProfile].
+#[typeshare]
+#[derive(
+  Debug, Clone, Serialize, Deserialize, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(CreateServerProfileResponse)]
+#[error(serror::Error)]
+pub struct CreateServerProfile {
+  /// The name of the profile.
+  pub name: String,
+  /// A description of the profile. default: "".
+  #[serde(default)]
+  pub description: String,
+  /// The config defaults inherited by the Servers.
+  #[serde(default)]
+  pub config: ServerProfileConfig,
+}
+
+#[typeshare]
+pub type CreateServerProfileResponse = ServerProfile;
+
+//
+
+/// **Admin only.** Update a Server Profile.
+/// The changes apply to all the Servers using the profile.
+/// Response: [ServerProfile].
+#[typeshare]
+#[derive(
+  Debug, Clone, Serialize, Deserialize, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(UpdateServerProfileResponse)]
+#[error(serror::Error)]
+pub struct UpdateServerProfile {
+  /// The id of the profile to update.
+  pub id: String,
+  /// Update the description.
+  pub description: Option<String>,
+  /// Replace the profile config.
+  /// Fields which are not set are no longer inherited.
+  pub config: Option<ServerProfileConfig>,
+}
+
+#[typeshare]
+pub type UpdateServerProfileResponse = ServerProfile;
+
+//
+
+/// **Admin only.** Delete a Server Profile.
+/// Fails if any Servers are still using the profile.
+/// Response: [ServerProfile].
+#[typeshare]
+#[derive(
+  Debug, Clone, Serialize, Deserialize, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(DeleteServerProfileResponse)]
+#[error(serror::Error)]
+pub struct DeleteServerProfile {
+  /// The id of the profile to delete.
+  pub id: String,
+}
+
+#[typeshare]
+pub type DeleteServerProfileResponse = ServerProfile;
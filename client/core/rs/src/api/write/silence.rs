@@ -0,0 +1,61 @@
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::{
+  I64, ResourceTarget, alert::AlertDataVariant, silence::AlertSilence,
+};
+
+use super::KomodoWriteRequest;
+
+/// **Admin only.** Mute the alerts matching the targets and alert types
+/// between `start_ts` and `end_ts`, across all Alerters.
+/// Response: [AlertSilence].
+#[typeshare]
+#[derive(
+  Debug, Clone, Serialize, Deserialize, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(CreateSilenceResponse)]
+#[error(serror::Error)]
+pub struct CreateSilence {
+  /// Only silence alerts on these targets.
+  /// If empty, alerts on all targets are silenced.
+  #[serde(default)]
+  pub targets: Vec<ResourceTarget>,
+  /// Only silence these alert types.
+  /// If empty, all alert types are silenced.
+  #[serde(default)]
+  pub alert_types: Vec<AlertDataVariant>,
+  /// Unix timestamp in milliseconds the silence starts.
+  /// If not provided, the silence starts immediately.
+  pub start_ts: Option<I64>,
+  /// Unix timestamp in milliseconds the silence ends.
+  pub end_ts: I64,
+  /// Why the alerts are silenced. default: "".
+  #[serde(default)]
+  pub comment: String,
+}
+
+#[typeshare]
+pub type CreateSilenceResponse = AlertSilence;
+
+//
+
+/// **Admin only.** Delete an alert silence, unmuting the alerts
+/// it matches. Response: [AlertSilence].
+#[typeshare]
+#[derive(
+  Debug, Clone, Serialize, Deserialize, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(DeleteSilenceResponse)]
+#[error(serror::Error)]
+pub struct DeleteSilence {
+  /// The id of the silence to delete.
+  pub id: String,
+}
+
+#[typeshare]
+pub type DeleteSilenceResponse = AlertSilence;
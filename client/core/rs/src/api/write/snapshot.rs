@@ -0,0 +1,47 @@
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::snapshot::Snapshot;
+
+use super::KomodoWriteRequest;
+
+/// **Admin only.** Take a snapshot of all resources, variables,
+/// and user groups as they are now. Response: [Snapshot].
+#[typeshare]
+#[derive(
+  Debug, Clone, Serialize, Deserialize, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(SnapshotStateResponse)]
+#[error(serror::Error)]
+pub struct SnapshotState {
+  /// A name for the snapshot, eg. `before-sync`.
+  #[serde(default)]
+  pub name: String,
+  /// A description for the snapshot. default: "".
+  #[serde(default)]
+  pub description: String,
+}
+
+#[typeshare]
+pub type SnapshotStateResponse = Snapshot;
+
+//
+
+/// **Admin only.** Delete a snapshot. Response: [Snapshot].
+#[typeshare]
+#[derive(
+  Debug, Clone, Serialize, Deserialize, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(DeleteSnapshotResponse)]
+#[error(serror::Error)]
+pub struct DeleteSnapshot {
+  /// The id of the snapshot to delete.
+  pub id: String,
+}
+
+#[typeshare]
+pub type DeleteSnapshotResponse = Snapshot;
@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
 use crate::entities::{
-  NoData,
+  I64, NoData,
   stack::{_PartialStackConfig, Stack},
   update::Update,
 };
@@ -88,6 +88,12 @@ pub struct UpdateStack {
   pub id: String,
   /// The partial config update to apply.
   pub config: _PartialStackConfig,
+  /// The resource revision the update is based on.
+  /// If provided and the resource has been updated since,
+  /// the update is rejected with status 409 (Conflict),
+  /// with the current resource as JSON in the error trace.
+  #[serde(default)]
+  pub revision: Option<I64>,
 }
 
 //
@@ -149,6 +155,29 @@ pub struct RefreshStackCache {
 
 //
 
+/// Adopt the compose project already running on the Stack's server
+/// as the deployed state of the Stack, without recreating the containers.
+/// Use this when the project was brought up manually with `docker compose`,
+/// or by another Komodo instance.
+///
+/// The project must exist on the server. The latest compose file contents
+/// are recorded as deployed, so `DeployStackIfChanged` will only redeploy
+/// after further changes. Response: [Update].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(Update)]
+#[error(serror::Error)]
+pub struct TakeStackOwnership {
+  /// Id or name
+  #[serde(alias = "id", alias = "name")]
+  pub stack: String,
+}
+
+//
+
 #[typeshare]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum StackWebhookAction {
@@ -0,0 +1,118 @@
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::{
+  I64,
+  swarm::{_PartialSwarmServiceConfig, SwarmService},
+  update::Update,
+};
+
+use super::KomodoWriteRequest;
+
+//
+
+/// Create a swarm service. Response: [SwarmService].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(SwarmService)]
+#[error(serror::Error)]
+pub struct CreateSwarmService {
+  /// The name given to newly created swarm service.
+  pub name: String,
+  /// Optional partial config to initialize the swarm service with.
+  #[serde(default)]
+  pub config: _PartialSwarmServiceConfig,
+}
+
+//
+
+/// Creates a new swarm service with given `name` and the configuration
+/// of the swarm service at the given `id`. Response: [SwarmService].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(SwarmService)]
+#[error(serror::Error)]
+pub struct CopySwarmService {
+  /// The name of the new swarm service.
+  pub name: String,
+  /// The id of the swarm service to copy.
+  pub id: String,
+}
+
+//
+
+/// Deletes the swarm service at the given id, and returns the deleted swarm service.
+/// If the services are deployed, they will be removed with `docker stack rm`.
+/// Response: [SwarmService]
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(SwarmService)]
+#[error(serror::Error)]
+pub struct DeleteSwarmService {
+  /// The id or name of the swarm service to delete.
+  pub id: String,
+}
+
+//
+
+/// Update the swarm service at the given id, and return the updated swarm service.
+/// Response: [SwarmService].
+///
+/// Note. If the attached server for the swarm service changes,
+/// services already deployed are not removed from the old Swarm.
+/// Use [RemoveSwarmService][crate::api::execute::RemoveSwarmService] first.
+///
+/// Note. This method updates only the fields which are set in the [_PartialSwarmServiceConfig],
+/// effectively merging diffs into the final document.
+/// This is helpful when multiple users are using
+/// the same resources concurrently by ensuring no unintentional
+/// field changes occur from out of date local state.
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(SwarmService)]
+#[error(serror::Error)]
+pub struct UpdateSwarmService {
+  /// The id of the swarm service to update.
+  pub id: String,
+  /// The partial config update to apply.
+  pub config: _PartialSwarmServiceConfig,
+  /// The resource revision the update is based on.
+  /// If provided and the resource has been updated since,
+  /// the update is rejected with status 409 (Conflict),
+  /// with the current resource as JSON in the error trace.
+  #[serde(default)]
+  pub revision: Option<I64>,
+}
+
+//
+
+/// Rename the SwarmService at id to the given name.
+/// The services must be removed first, as the name is used
+/// as the Swarm stack name. Response: [Update].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(Update)]
+#[error(serror::Error)]
+pub struct RenameSwarmService {
+  /// The id or name of the SwarmService to rename.
+  pub id: String,
+  /// The new name.
+  pub name: String,
+}
@@ -0,0 +1,33 @@
+use derive_empty_traits::EmptyTraits;
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use crate::entities::update::Update;
+
+use super::KomodoWriteRequest;
+
+/// Cancel an in progress execution, such as a build, repo clone
+/// or Procedure run, releasing the resource for other executions.
+/// Requires Execute permission on the Update target,
+/// or to be the user who started the execution.
+/// Response: [Update].
+///
+/// Builds clean up after themselves, killing the build
+/// on the builder. Other executions are stopped where they are.
+/// If the execution isn't running on this Core, such as after
+/// a restart, the Update is only marked as cancelled.
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(CancelUpdateResponse)]
+#[error(serror::Error)]
+pub struct CancelUpdate {
+  /// The id of the in progress Update.
+  pub id: String,
+}
+
+#[typeshare]
+pub type CancelUpdateResponse = Update;
@@ -121,3 +121,40 @@ pub struct SetEveryoneUserGroup {
   /// Whether this user group applies to everyone.
   pub everyone: bool,
 }
+
+//
+
+/// **Admin only.** Nest a user group in another user group.
+/// The members of the nested group become members of the user group.
+/// Response: [UserGroup]
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(UserGroup)]
+#[error(serror::Error)]
+pub struct AddUserGroupToUserGroup {
+  /// The name or id of the UserGroup to nest the group in.
+  pub user_group: String,
+  /// The name or id of the UserGroup to nest.
+  pub nested: String,
+}
+
+//
+
+/// **Admin only.** Remove a nested user group from a user group.
+/// Response: [UserGroup]
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Resolve, EmptyTraits,
+)]
+#[empty_traits(KomodoWriteRequest)]
+#[response(UserGroup)]
+#[error(serror::Error)]
+pub struct RemoveUserGroupFromUserGroup {
+  /// The name or id of the UserGroup to remove the nested group from.
+  pub user_group: String,
+  /// The name or id of the nested UserGroup to remove.
+  pub nested: String,
+}
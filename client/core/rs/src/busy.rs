@@ -2,7 +2,8 @@ use crate::entities::{
   action::ActionActionState, build::BuildActionState,
   deployment::DeploymentActionState, procedure::ProcedureActionState,
   repo::RepoActionState, server::ServerActionState,
-  stack::StackActionState, sync::ResourceSyncActionState,
+  stack::StackActionState, swarm::SwarmServiceActionState,
+  sync::ResourceSyncActionState,
 };
 
 pub trait Busy {
@@ -36,6 +37,12 @@ impl Busy for DeploymentActionState {
   }
 }
 
+impl Busy for SwarmServiceActionState {
+  fn busy(&self) -> bool {
+    self.deploying || self.scaling || self.removing
+  }
+}
+
 impl Busy for StackActionState {
   fn busy(&self) -> bool {
     self.deploying
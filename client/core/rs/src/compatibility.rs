@@ -0,0 +1,67 @@
+//! Version compatibility between Komodo Core and API clients.
+//!
+//! Clients send their version with each request in the
+//! [CLIENT_VERSION_HEADER], and Core responds with its version
+//! in the [CORE_VERSION_HEADER]. Core and clients are compatible
+//! when they share the same major and minor version,
+//! as breaking API changes are only made in minor releases.
+
+use anyhow::{Context, anyhow};
+
+use crate::entities::Version;
+
+/// Header containing the version of the client making the request.
+pub const CLIENT_VERSION_HEADER: &str = "x-komodo-client-version";
+/// Header containing the version of Komodo Core.
+pub const CORE_VERSION_HEADER: &str = "x-komodo-core-version";
+
+/// The version of this client.
+pub const CLIENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The range of client versions supported by the Core version.
+/// Returns the oldest supported version,
+/// and the first unsupported newer version.
+pub fn supported_client_versions(
+  core: Version,
+) -> (Version, Version) {
+  (
+    Version {
+      major: core.major,
+      minor: core.minor,
+      patch: 0,
+    },
+    Version {
+      major: core.major,
+      minor: core.minor + 1,
+      patch: 0,
+    },
+  )
+}
+
+/// Checks the client version is supported by the Core version.
+/// The error describes the mismatch.
+pub fn check_compatible(
+  core_version: &str,
+  client_version: &str,
+) -> anyhow::Result<()> {
+  let core = Version::try_from(core_version).with_context(|| {
+    format!("Invalid Core version: {core_version}")
+  })?;
+  let client =
+    Version::try_from(client_version).with_context(|| {
+      format!("Invalid client version: {client_version}")
+    })?;
+  let (min, below) = supported_client_versions(core);
+  if (client.major, client.minor) == (min.major, min.minor) {
+    return Ok(());
+  }
+  let upgrade =
+    if (client.major, client.minor) < (below.major, below.minor) {
+      "client"
+    } else {
+      "Core"
+    };
+  Err(anyhow!(
+    "Komodo Core {core} does not support client {client}. Core supports clients {min} up to (not including) {below}. Upgrade the {upgrade} to match."
+  ))
+}
@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+use strum::Display;
+use typeshare::typeshare;
+
+use super::{
+  I64, MongoId, ResourceTarget,
+  permission::PermissionLevelAndSpecifics,
+};
+
+/// A user's request for temporary elevated access to a resource.
+/// Once an admin approves it, the user is given the permission
+/// until `expires_at`, after which it is revoked automatically.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(
+  feature = "mongo",
+  derive(mongo_indexed::derive::MongoIndexed)
+)]
+#[cfg_attr(feature = "mongo", doc_index({ "status": 1, "expires_at": 1 }))]
+pub struct AccessRequest {
+  /// The Mongo ID of the access request.
+  /// This field is de/serialized from/to JSON as
+  /// `{ "_id": { "$oid": "..." }, ...(rest of serialized AccessRequest) }`
+  #[serde(
+    default,
+    rename = "_id",
+    skip_serializing_if = "String::is_empty",
+    with = "bson::serde_helpers::hex_string_as_object_id"
+  )]
+  pub id: MongoId,
+
+  /// The id of the user requesting access.
+  #[cfg_attr(feature = "mongo", index)]
+  pub user_id: String,
+
+  /// The username of the user requesting access.
+  #[serde(default)]
+  pub username: String,
+
+  /// The resource access is requested on.
+  pub target: ResourceTarget,
+
+  /// The requested permission on the resource.
+  #[serde(default)]
+  pub permission: PermissionLevelAndSpecifics,
+
+  /// How long the access lasts once approved, in milliseconds.
+  #[serde(default)]
+  pub duration_ms: I64,
+
+  /// Why the access is needed, shown to approvers.
+  #[serde(default)]
+  pub reason: String,
+
+  /// The status of the access request.
+  #[serde(default)]
+  pub status: AccessRequestStatus,
+
+  /// When the access was requested.
+  #[serde(default)]
+  pub requested_at: I64,
+
+  /// The id of the admin who approved or rejected the request.
+  #[serde(default)]
+  pub resolved_by: String,
+
+  /// When the request was approved / rejected.
+  #[serde(default)]
+  pub resolved_at: I64,
+
+  /// An optional comment given with the approval or rejection.
+  #[serde(default)]
+  pub comment: String,
+
+  /// When the approved access is revoked.
+  #[serde(default)]
+  pub expires_at: I64,
+
+  /// The user's permission on the resource before the request was approved,
+  /// which is restored when the access expires. None if there was none.
+  #[serde(default)]
+  pub previous: Option<PermissionLevelAndSpecifics>,
+}
+
+#[typeshare]
+#[derive(
+  Serialize,
+  Deserialize,
+  Debug,
+  Clone,
+  Copy,
+  Default,
+  PartialEq,
+  Eq,
+  Hash,
+  Display,
+)]
+pub enum AccessRequestStatus {
+  /// Waiting for an admin to approve or reject the request.
+  #[default]
+  Pending,
+  /// The access was given, and is revoked at `expires_at`.
+  Approved,
+  /// The request was rejected.
+  Rejected,
+  /// The approved access has expired and was revoked.
+  Expired,
+}
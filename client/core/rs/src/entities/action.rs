@@ -15,7 +15,9 @@ use crate::{
 
 use super::{
   ScheduleFormat,
+  execution_schedule::MissedRunPolicy,
   resource::{Resource, ResourceListItem, ResourceQuery},
+  update::ExecutionMetrics,
 };
 
 #[typeshare]
@@ -34,6 +36,9 @@ pub struct ActionListItemInfo {
   /// If there is an error parsing schedule expression,
   /// it will be given here.
   pub schedule_error: Option<String>,
+  /// Run metrics over the last 30 days.
+  #[serde(default)]
+  pub metrics: ExecutionMetrics,
 }
 
 #[typeshare]
@@ -70,6 +75,7 @@ pub type _PartialActionConfig = PartialActionConfig;
 
 #[typeshare]
 #[derive(Serialize, Deserialize, Debug, Clone, Builder, Partial)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[partial_derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[partial(skip_serializing_none, from, diff)]
 pub struct ActionConfig {
@@ -123,6 +129,12 @@ pub struct ActionConfig {
   #[partial_default(default_schedule_alert())]
   pub schedule_alert: bool,
 
+  /// What to do with scheduled runs missed while Core was down.
+  /// An alert is sent for each missed run either way.
+  #[serde(default)]
+  #[builder(default)]
+  pub schedule_missed_runs: MissedRunPolicy,
+
   /// Whether to send alerts when this action fails.
   #[serde(default = "default_failure_alert")]
   #[builder(default = "default_failure_alert()")]
@@ -208,6 +220,7 @@ impl Default for ActionConfig {
       schedule_timezone: Default::default(),
       run_at_startup: default_run_at_startup(),
       schedule_alert: default_schedule_alert(),
+      schedule_missed_runs: Default::default(),
       failure_alert: default_failure_alert(),
       webhook_enabled: default_webhook_enabled(),
       webhook_secret: Default::default(),
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf};
 
 use derive_variants::EnumVariants;
 use serde::{Deserialize, Serialize};
@@ -8,8 +8,10 @@ use typeshare::typeshare;
 use crate::entities::{I64, MongoId};
 
 use super::{
-  _Serror, ResourceTarget, ResourceTargetVariant, Version,
-  deployment::DeploymentState, stack::StackState,
+  _Serror, AutoStopMode, Operation, ResourceTarget,
+  ResourceTargetVariant, Version, deployment::DeploymentState,
+  external_status::ExternalState, provider::ProviderAccountType,
+  stack::StackState,
 };
 
 /// Representation of an alert in the system.
@@ -54,6 +56,34 @@ pub struct Alert {
 
   /// The timestamp of alert resolution
   pub resolved_ts: Option<I64>,
+
+  /// The repeat / escalation notifications
+  /// sent while the alert was open.
+  #[serde(default)]
+  pub notifications: Vec<AlertNotification>,
+}
+
+/// A repeat / escalation notification of an open alert.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AlertNotification {
+  /// The id of the Alerter with the escalation policy.
+  pub alerter_id: String,
+  /// Unix timestamp in milliseconds the notification was sent.
+  pub ts: I64,
+  /// Whether this was a repeat, or an escalation.
+  pub kind: AlertNotificationKind,
+}
+
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq,
+)]
+pub enum AlertNotificationKind {
+  /// The alert was sent again to the same Alerter.
+  Repeat,
+  /// The alert was sent to the escalation Alerter.
+  Escalation,
 }
 
 /// The variants of data related to the alert.
@@ -137,6 +167,34 @@ pub enum AlertData {
     total_gb: f64,
   },
 
+  /// A server has high network throughput.
+  ServerNetwork {
+    /// The id of the server
+    id: String,
+    /// The name of the server
+    name: String,
+    /// The region of the server
+    region: Option<String>,
+    /// The network ingress in MB/s
+    ingress_mb_per_sec: f64,
+    /// The network egress in MB/s
+    egress_mb_per_sec: f64,
+  },
+
+  /// A server has high disk IO.
+  ServerDiskIo {
+    /// The id of the server
+    id: String,
+    /// The name of the server
+    name: String,
+    /// The region of the server
+    region: Option<String>,
+    /// The disk read in MB/s
+    read_mb_per_sec: f64,
+    /// The disk write in MB/s
+    write_mb_per_sec: f64,
+  },
+
   /// A server has a version mismatch with the core.
   ServerVersionMismatch {
     /// The id of the server
@@ -167,6 +225,18 @@ pub enum AlertData {
     to: DeploymentState,
   },
 
+  /// A Deployment container healthcheck has started failing.
+  ContainerUnhealthy {
+    /// The id of the deployment
+    id: String,
+    /// The name of the deployment
+    name: String,
+    /// The server id of server that the deployment is on
+    server_id: String,
+    /// The server name
+    server_name: String,
+  },
+
   /// A Deployment has an image update available
   DeploymentImageUpdateAvailable {
     /// The id of the deployment
@@ -211,6 +281,32 @@ pub enum AlertData {
     to: StackState,
   },
 
+  /// The latest Stack files no longer match the deployed files
+  StackDrifted {
+    /// The id of the stack
+    id: String,
+    /// The name of the stack
+    name: String,
+    /// The server id of server that the stack is on
+    server_id: String,
+    /// The server name
+    server_name: String,
+  },
+
+  /// The running Stack containers no longer match the deployed compose file
+  StackRuntimeDrift {
+    /// The id of the stack
+    id: String,
+    /// The name of the stack
+    name: String,
+    /// The server id of server that the stack is on
+    server_id: String,
+    /// The server name
+    server_name: String,
+    /// The drifted services
+    services: Vec<String>,
+  },
+
   /// A Stack has an image update available
   StackImageUpdateAvailable {
     /// The id of the stack
@@ -241,6 +337,24 @@ pub enum AlertData {
     images: Vec<String>,
   },
 
+  /// A Stack service has fewer running replicas than desired
+  StackServiceReplicasLow {
+    /// The id of the stack
+    id: String,
+    /// The name of the stack
+    name: String,
+    /// The server id of server that the stack is on
+    server_id: String,
+    /// The server name
+    server_name: String,
+    /// The service name
+    service: String,
+    /// The number of running replicas
+    running: I64,
+    /// The desired number of replicas
+    desired: I64,
+  },
+
   /// An AWS builder failed to terminate.
   AwsBuilderTerminationFailed {
     /// The id of the aws instance which failed to terminate
@@ -283,6 +397,20 @@ pub enum AlertData {
     name: String,
   },
 
+  /// A procedure is waiting for approval to continue
+  ProcedureApprovalRequired {
+    /// The id of the procedure
+    id: String,
+    /// The name of the procedure
+    name: String,
+    /// The name of the stage requiring approval
+    stage: String,
+    /// The id of the approval, used to approve / reject
+    approval_id: String,
+    /// The message for approvers
+    message: String,
+  },
+
   /// An action has failed
   ActionFailed {
     /// The id of the action
@@ -291,6 +419,46 @@ pub enum AlertData {
     name: String,
   },
 
+  /// An execution on a resource has failed, like Deploy or RunSync.
+  /// Builds, Repo builds, Procedures, and Actions
+  /// send their own failure alerts instead.
+  UpdateFailed {
+    /// The type of the resource
+    resource_type: ResourceTargetVariant,
+    /// The id of the resource
+    id: String,
+    /// The name of the resource
+    name: String,
+    /// The operation which failed
+    operation: Operation,
+    /// The id of the failed Update
+    update_id: String,
+  },
+
+  /// A Server scheduled command has failed
+  ScheduledCommandFailed {
+    /// The id of the server
+    id: String,
+    /// The name of the server
+    name: String,
+    /// The name of the scheduled command
+    command: String,
+  },
+
+  /// A Deployment or Stack will be auto stopped soon
+  AutoStopPending {
+    /// Deployment or Stack
+    resource_type: ResourceTargetVariant,
+    /// The resource id
+    id: String,
+    /// The resource name
+    name: String,
+    /// Whether the resource will be stopped or destroyed
+    mode: AutoStopMode,
+    /// The minutes until the resource is auto stopped
+    minutes: I64,
+  },
+
   /// A schedule was run
   ScheduleRun {
     /// Procedure or Action
@@ -301,6 +469,100 @@ pub enum AlertData {
     name: String,
   },
 
+  /// A scheduled run didn't start on time,
+  /// because Core was down or the scheduler fell behind.
+  ScheduleMissed {
+    /// Procedure, Action or Build.
+    /// System for Execution Schedules.
+    resource_type: ResourceTargetVariant,
+    /// The resource or Execution Schedule id
+    id: String,
+    /// The resource or Execution Schedule name
+    name: String,
+    /// When the run was scheduled in unix ms
+    scheduled_at: I64,
+    /// Whether the run was started late,
+    /// otherwise it was skipped.
+    run_late: bool,
+  },
+
+  /// A git provider or docker registry account
+  /// failed its token health check.
+  ProviderAccountCheckFailed {
+    /// Git provider or docker registry
+    provider_type: ProviderAccountType,
+    /// The account id
+    id: String,
+    /// The provider domain
+    domain: String,
+    /// The account username
+    username: String,
+    /// The error message
+    message: String,
+  },
+
+  /// A git provider or docker registry account
+  /// token is about to expire.
+  ProviderTokenExpiring {
+    /// Git provider or docker registry
+    provider_type: ProviderAccountType,
+    /// The account id
+    id: String,
+    /// The provider domain
+    domain: String,
+    /// The account username
+    username: String,
+    /// Unix timestamp in ms when the token expires
+    expires_at: I64,
+    /// The days until the token expires
+    days: I64,
+  },
+
+  /// An external resource reported a state other than Ok,
+  /// or stopped reporting. Reported using `PushExternalStatus`.
+  ExternalStatus {
+    /// The name of the external resource
+    name: String,
+    /// The current state
+    state: ExternalState,
+    /// The last reported message. May be empty string.
+    #[serde(default)]
+    message: String,
+  },
+
+  /// An alert received from Prometheus Alertmanager.
+  /// Produced using `/listener/alertmanager`
+  Prometheus {
+    /// The Alertmanager fingerprint, used to resolve the alert.
+    fingerprint: String,
+    /// The `alertname` label
+    name: String,
+    /// The `summary` annotation. May be empty string.
+    #[serde(default)]
+    summary: String,
+    /// The `description` annotation. May be empty string.
+    #[serde(default)]
+    description: String,
+    /// The alert labels
+    #[serde(default)]
+    labels: HashMap<String, String>,
+    /// Link to the alert source in Prometheus. May be empty string.
+    #[serde(default)]
+    generator_url: String,
+  },
+
+  /// The weekly digest of an Alerter with `digest` enabled.
+  WeeklyDigest {
+    /// The id of the alerter
+    id: String,
+    /// The name of the alerter
+    name: String,
+    /// The start of the digest period in unix ms
+    since: I64,
+    /// The summary for each configured tag
+    sections: Vec<DigestSection>,
+  },
+
   /// Custom header / body.
   /// Produced using `/execute/SendAlert`
   Custom {
@@ -312,6 +574,43 @@ pub enum AlertData {
   },
 }
 
+/// Weekly digest summary of the resources with a tag.
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, PartialEq, Default,
+)]
+pub struct DigestSection {
+  /// The tag name. Empty if the section covers all resources.
+  pub tag: String,
+  /// The number of Deployment, Stack and Swarm Service deploys.
+  pub deploys: I64,
+  /// The number of failed Updates.
+  pub failures: I64,
+  /// The number of alerts which are still open.
+  pub open_alerts: I64,
+  /// The number of Deployment containers / Stack services
+  /// with a newer image available.
+  pub stale_images: I64,
+  /// The disk usage of the Servers, and how it changed over the week.
+  pub disks: Vec<DigestDiskUsage>,
+}
+
+/// Server disk usage trend in a [DigestSection].
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, PartialEq, Default,
+)]
+pub struct DigestDiskUsage {
+  /// The server id
+  pub server_id: String,
+  /// The server name
+  pub server_name: String,
+  /// The current disk usage percentage
+  pub used_perc: f64,
+  /// The change in disk usage percentage since the start of the week
+  pub change_perc: f64,
+}
+
 impl Default for AlertData {
   fn default() -> Self {
     AlertData::None {}
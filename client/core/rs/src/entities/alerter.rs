@@ -10,8 +10,8 @@ use typeshare::typeshare;
 use crate::entities::MaintenanceWindow;
 
 use super::{
-  ResourceTarget,
-  alert::AlertDataVariant,
+  ResourceTarget, ResourceTargetVariant,
+  alert::{AlertDataVariant, SeverityLevel},
   resource::{Resource, ResourceListItem, ResourceQuery},
 };
 
@@ -35,6 +35,7 @@ pub type _PartialAlerterConfig = PartialAlerterConfig;
 
 #[typeshare]
 #[derive(Serialize, Deserialize, Debug, Clone, Builder, Partial)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[partial_derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[partial(skip_serializing_none, from, diff)]
 pub struct AlerterConfig {
@@ -67,6 +68,24 @@ pub struct AlerterConfig {
   #[builder(default)]
   pub except_resources: Vec<ResourceTarget>,
 
+  /// Route alerts by the tags, type and server of the resource,
+  /// and by the alert severity.
+  #[serde(default)]
+  #[builder(default)]
+  pub routing: AlertRouting,
+
+  /// Re-send alerts which stay open,
+  /// and escalate them to another Alerter.
+  #[serde(default)]
+  #[builder(default)]
+  pub escalation: AlertEscalation,
+
+  /// Send a weekly digest summarizing deploys, failures,
+  /// open alerts, stale images and disk usage per tag.
+  #[serde(default)]
+  #[builder(default)]
+  pub digest: AlertDigest,
+
   /// Scheduled maintenance windows during which alerts will be suppressed.
   #[serde(default)]
   #[builder(default)]
@@ -88,17 +107,125 @@ impl Default for AlerterConfig {
       alert_types: Default::default(),
       resources: Default::default(),
       except_resources: Default::default(),
+      routing: Default::default(),
+      escalation: Default::default(),
+      digest: Default::default(),
       maintenance_windows: Default::default(),
     }
   }
 }
 
+/// Routing rules for an Alerter.
+/// For each non empty include list, the alert must match one of the entries.
+/// Alerts matching any entry of an except list are not sent.
+#[typeshare]
+#[derive(
+  Debug, Clone, Default, PartialEq, Serialize, Deserialize,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct AlertRouting {
+  /// Only send alerts on resources with one of these tags (name or id).
+  /// Entries can also be prefixed like `tag:team-a`.
+  #[serde(default)]
+  pub tags: Vec<String>,
+  /// DON'T send alerts on resources with any of these tags (name or id).
+  #[serde(default)]
+  pub except_tags: Vec<String>,
+  /// Only send alerts on these resource types.
+  #[serde(default)]
+  pub resource_types: Vec<ResourceTargetVariant>,
+  /// DON'T send alerts on these resource types.
+  #[serde(default)]
+  pub except_resource_types: Vec<ResourceTargetVariant>,
+  /// Only send alerts on these Servers (name or id),
+  /// including the Deployments and Stacks on them.
+  #[serde(default)]
+  pub servers: Vec<String>,
+  /// DON'T send alerts on these Servers (name or id),
+  /// including the Deployments and Stacks on them.
+  #[serde(default)]
+  pub except_servers: Vec<String>,
+  /// Only send alerts with these severity levels.
+  /// Note that resolved alerts are sent with the `OK` level.
+  #[serde(default)]
+  pub levels: Vec<SeverityLevel>,
+  /// DON'T send alerts with these severity levels.
+  #[serde(default)]
+  pub except_levels: Vec<SeverityLevel>,
+}
+
+impl AlertRouting {
+  pub fn is_empty(&self) -> bool {
+    self == &AlertRouting::default()
+  }
+}
+
+/// Repeat / escalation policy for alerts which stay open,
+/// like a Server CPU alert. Alerts which are resolved immediately,
+/// like a failed Build, are only sent once.
+#[typeshare]
+#[derive(
+  Debug, Clone, Default, PartialEq, Serialize, Deserialize,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct AlertEscalation {
+  /// Re-send the open alert to this Alerter every N minutes.
+  /// 0 disables repeats.
+  #[serde(default)]
+  pub repeat_interval_minutes: u64,
+  /// Send the alert to the `escalate_to` Alerter once
+  /// it has been open for N minutes. 0 disables escalation.
+  #[serde(default)]
+  pub escalate_after_minutes: u64,
+  /// The Alerter (name or id) to escalate to.
+  #[serde(default)]
+  pub escalate_to: String,
+}
+
+impl AlertEscalation {
+  pub fn is_empty(&self) -> bool {
+    self.repeat_interval_minutes == 0
+      && (self.escalate_after_minutes == 0
+        || self.escalate_to.is_empty())
+  }
+}
+
+/// Weekly digest sent through the Alerter.
+/// It is sent regardless of the Alerter filters and routing rules.
+#[typeshare]
+#[derive(
+  Debug, Clone, Default, PartialEq, Serialize, Deserialize,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct AlertDigest {
+  /// Whether to send the weekly digest.
+  #[serde(default)]
+  pub enabled: bool,
+  /// The day of the week to send the digest (Monday, Tuesday, etc.).
+  /// Default: Monday
+  #[serde(default)]
+  pub day_of_week: String,
+  /// The hour to send the digest in 24-hour format (0-23).
+  #[serde(default)]
+  pub hour: u8,
+  /// Timezone for the digest schedule.
+  /// If empty, will use Core timezone.
+  #[serde(default)]
+  pub timezone: String,
+  /// Summarize the resources with each of these tags (name or id)
+  /// in their own section. If empty, all resources
+  /// are summarized together.
+  #[serde(default)]
+  pub tags: Vec<String>,
+}
+
 // ENDPOINTS
 
 #[typeshare]
 #[derive(
   Debug, Clone, PartialEq, Serialize, Deserialize, EnumVariants,
 )]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[variant_derive(
   Debug,
   Clone,
@@ -129,6 +256,15 @@ pub enum AlerterEndpoint {
 
   /// Send alert to Pushover
   Pushover(PushoverAlerterEndpoint),
+
+  /// Send alert to PagerDuty using the Events API v2
+  PagerDuty(PagerDutyAlerterEndpoint),
+
+  /// Send alert to Opsgenie
+  Opsgenie(OpsgenieAlerterEndpoint),
+
+  /// Send alert as email over SMTP
+  Email(EmailAlerterEndpoint),
 }
 
 impl Default for AlerterEndpoint {
@@ -142,6 +278,7 @@ impl Default for AlerterEndpoint {
 #[derive(
   Debug, Clone, PartialEq, Serialize, Deserialize, Builder,
 )]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct CustomAlerterEndpoint {
   /// The http/s endpoint to send the POST to
   #[serde(default = "default_custom_url")]
@@ -166,6 +303,7 @@ fn default_custom_url() -> String {
 #[derive(
   Debug, Clone, PartialEq, Serialize, Deserialize, Builder,
 )]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SlackAlerterEndpoint {
   /// The Slack app webhook url
   #[serde(default = "default_slack_url")]
@@ -192,6 +330,7 @@ fn default_slack_url() -> String {
 #[derive(
   Debug, Clone, PartialEq, Serialize, Deserialize, Builder,
 )]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct DiscordAlerterEndpoint {
   /// The Discord webhook url
   #[serde(default = "default_discord_url")]
@@ -218,6 +357,7 @@ fn default_discord_url() -> String {
 #[derive(
   Debug, Clone, PartialEq, Serialize, Deserialize, Builder,
 )]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct NtfyAlerterEndpoint {
   /// The ntfy topic URL
   #[serde(default = "default_ntfy_url")]
@@ -247,6 +387,7 @@ fn default_ntfy_url() -> String {
 #[derive(
   Debug, Clone, PartialEq, Serialize, Deserialize, Builder,
 )]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PushoverAlerterEndpoint {
   /// The pushover URL including application and user tokens in parameters.
   #[serde(default = "default_pushover_url")]
@@ -268,6 +409,157 @@ fn default_pushover_url() -> String {
   )
 }
 
+/// Configuration for a PagerDuty alerter.
+#[typeshare]
+#[derive(
+  Debug, Clone, PartialEq, Serialize, Deserialize, Builder,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PagerDutyAlerterEndpoint {
+  /// The PagerDuty Events API v2 url
+  #[serde(default = "default_pagerduty_url")]
+  #[builder(default = "default_pagerduty_url()")]
+  pub url: String,
+
+  /// The integration / routing key of the PagerDuty service.
+  /// Supports Variable / Secret interpolation.
+  #[serde(default)]
+  #[builder(default)]
+  pub routing_key: String,
+}
+
+impl Default for PagerDutyAlerterEndpoint {
+  fn default() -> Self {
+    Self {
+      url: default_pagerduty_url(),
+      routing_key: Default::default(),
+    }
+  }
+}
+
+fn default_pagerduty_url() -> String {
+  String::from("https://events.pagerduty.com/v2/enqueue")
+}
+
+/// Configuration for an Opsgenie alerter.
+#[typeshare]
+#[derive(
+  Debug, Clone, PartialEq, Serialize, Deserialize, Builder,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct OpsgenieAlerterEndpoint {
+  /// The Opsgenie API url.
+  /// Use `https://api.eu.opsgenie.com` for the EU instance.
+  #[serde(default = "default_opsgenie_url")]
+  #[builder(default = "default_opsgenie_url()")]
+  pub url: String,
+
+  /// The API key of the Opsgenie integration.
+  /// Supports Variable / Secret interpolation.
+  #[serde(default)]
+  #[builder(default)]
+  pub api_key: String,
+}
+
+impl Default for OpsgenieAlerterEndpoint {
+  fn default() -> Self {
+    Self {
+      url: default_opsgenie_url(),
+      api_key: Default::default(),
+    }
+  }
+}
+
+fn default_opsgenie_url() -> String {
+  String::from("https://api.opsgenie.com")
+}
+
+/// Configuration for an Email alerter.
+#[typeshare]
+#[derive(
+  Debug, Clone, PartialEq, Serialize, Deserialize, Builder,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct EmailAlerterEndpoint {
+  /// The SMTP server host, eg. `smtp.example.com`
+  #[serde(default)]
+  #[builder(default)]
+  pub host: String,
+
+  /// The SMTP server port
+  #[serde(default = "default_smtp_port")]
+  #[builder(default = "default_smtp_port()")]
+  pub port: u16,
+
+  /// How to secure the SMTP connection.
+  #[serde(default)]
+  #[builder(default)]
+  pub security: SmtpSecurity,
+
+  /// The SMTP username. Leave empty to send without authentication.
+  #[serde(default)]
+  #[builder(default)]
+  pub username: String,
+
+  /// The SMTP password.
+  /// Supports Variable / Secret interpolation.
+  #[serde(default)]
+  #[builder(default)]
+  pub password: String,
+
+  /// The sender address, eg. `Komodo <komodo@example.com>`
+  #[serde(default)]
+  #[builder(default)]
+  pub from: String,
+
+  /// The recipient addresses
+  #[serde(default)]
+  #[builder(default)]
+  pub to: Vec<String>,
+}
+
+impl Default for EmailAlerterEndpoint {
+  fn default() -> Self {
+    Self {
+      host: Default::default(),
+      port: default_smtp_port(),
+      security: Default::default(),
+      username: Default::default(),
+      password: Default::default(),
+      from: Default::default(),
+      to: Default::default(),
+    }
+  }
+}
+
+fn default_smtp_port() -> u16 {
+  587
+}
+
+/// How to secure the SMTP connection.
+#[typeshare]
+#[derive(
+  Debug,
+  Clone,
+  Copy,
+  Default,
+  PartialEq,
+  Eq,
+  Serialize,
+  Deserialize,
+  Display,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum SmtpSecurity {
+  /// Upgrade the connection with STARTTLS (usually port 587).
+  #[default]
+  StartTls,
+  /// Connect using implicit TLS (usually port 465).
+  Tls,
+  /// Don't use TLS. Only for trusted local relays.
+  None,
+}
+
 // QUERY
 #[typeshare]
 pub type AlerterQuery = ResourceQuery<AlerterQuerySpecifics>;
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
-use super::I64;
+use super::{I64, ResourceTarget, permission::PermissionLevel};
 
 /// An api key used to authenticate requests via request headers.
 #[typeshare]
@@ -30,6 +30,12 @@ pub struct ApiKey {
 
   /// Expiry of key, or 0 if never expires
   pub expires: I64,
+
+  /// Restrict what the key can be used for.
+  /// The default scope places no restrictions
+  /// beyond the user's own permissions.
+  #[serde(default)]
+  pub scope: ApiKeyScope,
 }
 
 impl ApiKey {
@@ -37,3 +43,73 @@ impl ApiKey {
     self.secret.clear()
   }
 }
+
+/// Restricts an api key to specific request classes,
+/// permission level, and resources. Each restriction
+/// is applied on top of the user's own permissions.
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, PartialEq,
+)]
+pub struct ApiKeyScope {
+  /// The classes of request the key can make.
+  /// Empty allows all classes.
+  #[serde(default)]
+  pub requests: Vec<ApiKeyRequestClass>,
+
+  /// The highest permission level the key can use.
+  /// `Read` only allows read requests,
+  /// `Execute` allows read and execute requests.
+  /// None places no limit.
+  #[serde(default)]
+  pub level: Option<PermissionLevel>,
+
+  /// The resources the key can be used on.
+  /// Requests which don't target one of these resources are rejected.
+  /// Empty allows all resources.
+  #[serde(default)]
+  pub targets: Vec<ResourceTarget>,
+}
+
+impl ApiKeyScope {
+  /// Whether the scope places no restrictions on the key.
+  pub fn is_empty(&self) -> bool {
+    self.requests.is_empty()
+      && self.level.is_none()
+      && self.targets.is_empty()
+  }
+
+  /// Whether the key can make requests of the given class.
+  pub fn allows(&self, class: ApiKeyRequestClass) -> bool {
+    (self.requests.is_empty() || self.requests.contains(&class))
+      && self
+        .level
+        .map(|level| level >= class.level())
+        .unwrap_or(true)
+  }
+}
+
+/// The class of request, by the api it is made on.
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq,
+)]
+pub enum ApiKeyRequestClass {
+  /// The `/read`, `/log`, and `/artifact` apis.
+  Read,
+  /// The `/execute` and `/terminal` apis.
+  Execute,
+  /// The `/write` api.
+  Write,
+}
+
+impl ApiKeyRequestClass {
+  /// The permission level needed to make requests of this class.
+  pub fn level(self) -> PermissionLevel {
+    match self {
+      ApiKeyRequestClass::Read => PermissionLevel::Read,
+      ApiKeyRequestClass::Execute => PermissionLevel::Execute,
+      ApiKeyRequestClass::Write => PermissionLevel::Write,
+    }
+  }
+}
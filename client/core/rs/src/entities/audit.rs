@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use super::{I64, MongoId, ResourceTarget};
+
+/// A record of a write or auth request made to Core:
+/// who made it, from where, against what, and whether it succeeded.
+///
+/// Request parameters are never recorded, as they may contain secrets.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(
+  feature = "mongo",
+  derive(mongo_indexed::derive::MongoIndexed)
+)]
+pub struct AuditLogEntry {
+  /// The Mongo ID of the entry.
+  /// This field is de/serialized from/to JSON as
+  /// `{ "_id": { "$oid": "..." }, ...(rest of serialized AuditLogEntry) }`
+  #[serde(
+    default,
+    rename = "_id",
+    skip_serializing_if = "String::is_empty",
+    with = "bson::serde_helpers::hex_string_as_object_id"
+  )]
+  pub id: MongoId,
+
+  /// Unix timestamp in milliseconds the request was made.
+  #[cfg_attr(feature = "mongo", index)]
+  pub ts: I64,
+
+  /// The id of the user who made the request.
+  /// Empty for failed logins.
+  #[cfg_attr(feature = "mongo", index)]
+  #[serde(default)]
+  pub user_id: String,
+
+  /// The username of the user who made the request,
+  /// or the username attempted for failed logins.
+  #[serde(default)]
+  pub username: String,
+
+  /// The api key used to authenticate the request, if any.
+  /// Only the public key is recorded.
+  #[serde(default)]
+  pub api_key: Option<String>,
+
+  /// The ip address the request came from, if known.
+  #[serde(default)]
+  pub ip: Option<String>,
+
+  /// The api the request was made on,
+  /// eg `write`, `user`, `auth`.
+  #[serde(default)]
+  pub api: String,
+
+  /// The request type, eg `UpdateVariableValue`.
+  #[cfg_attr(feature = "mongo", index)]
+  #[serde(default)]
+  pub operation: String,
+
+  /// The resource the request targeted, if it could be determined.
+  #[serde(default)]
+  pub target: Option<ResourceTarget>,
+
+  /// The name or id of the entity the request targeted,
+  /// when it isn't a resource (eg a variable or user).
+  #[serde(default)]
+  pub target_name: Option<String>,
+
+  /// Whether the request succeeded.
+  #[cfg_attr(feature = "mongo", index)]
+  pub success: bool,
+
+  /// The error, if the request failed.
+  #[serde(default)]
+  pub error: Option<String>,
+}
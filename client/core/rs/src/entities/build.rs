@@ -10,8 +10,9 @@ use typeshare::typeshare;
 
 use crate::{
   deserializers::{
-    env_vars_deserializer, item_or_vec_deserializer,
-    labels_deserializer, option_env_vars_deserializer,
+    conversions_deserializer, env_vars_deserializer,
+    item_or_vec_deserializer, labels_deserializer,
+    option_conversions_deserializer, option_env_vars_deserializer,
     option_item_or_vec_deserializer, option_labels_deserializer,
     option_string_list_deserializer, string_list_deserializer,
   },
@@ -19,8 +20,10 @@ use crate::{
 };
 
 use super::{
-  SystemCommand, Version,
+  ScheduleFormat, SystemCommand, Version,
+  execution_schedule::MissedRunPolicy,
   resource::{Resource, ResourceListItem, ResourceQuery},
+  update::ExecutionMetrics,
 };
 
 #[typeshare]
@@ -130,6 +133,33 @@ impl Build {
     tags
   }
 
+  /// The Build for one variant of the matrix.
+  /// The variant name is added to the image tag.
+  pub fn matrix_variant(
+    &self,
+    variant: &BuildMatrixVariant,
+  ) -> Build {
+    let mut build = self.clone();
+    let config = &mut build.config;
+    config.image_tag = if config.image_tag.is_empty() {
+      variant.name.clone()
+    } else {
+      format!("{}-{}", config.image_tag, variant.name)
+    };
+    if !variant.dockerfile_path.is_empty() {
+      config.dockerfile_path = variant.dockerfile_path.clone();
+    }
+    if !variant.build_args.is_empty() {
+      config.build_args = if config.build_args.trim().is_empty() {
+        variant.build_args.clone()
+      } else {
+        format!("{}\n{}", config.build_args, variant.build_args)
+      };
+    }
+    config.matrix = Vec::new();
+    build
+  }
+
   pub fn get_image_tags_as_arg(
     &self,
     commit_hash: Option<&str>,
@@ -185,6 +215,16 @@ pub struct BuildListItemInfo {
 
   /// The first listed image registry domain
   pub image_registry_domain: Option<String>,
+
+  /// If the build has schedule enabled, this is the
+  /// next scheduled run time in unix ms.
+  pub next_scheduled_run: Option<I64>,
+  /// If there is an error parsing schedule expression,
+  /// it will be given here.
+  pub schedule_error: Option<String>,
+  /// Build metrics over the last 30 days.
+  #[serde(default)]
+  pub metrics: ExecutionMetrics,
 }
 
 #[typeshare]
@@ -226,6 +266,16 @@ pub struct BuildInfo {
   /// The last built dockerfile contents.
   /// This is updated whenever Komodo successfully runs the build.
   pub built_contents: Option<String>,
+  /// The digest of the last pushed multi-platform manifest, or null.
+  /// All the image tags of the build point to this manifest.
+  pub built_digest: Option<String>,
+  /// The platforms included in the last built manifest.
+  #[serde(default)]
+  pub built_platforms: Vec<String>,
+  /// The vulnerability scans of the last built images,
+  /// one for each matrix variant.
+  #[serde(default)]
+  pub image_scans: Vec<ImageScan>,
 
   /// The absolute path to the file
   pub remote_path: Option<String>,
@@ -248,6 +298,7 @@ pub type _PartialBuildConfig = PartialBuildConfig;
 /// The build configuration.
 #[typeshare]
 #[derive(Debug, Clone, Serialize, Deserialize, Builder, Partial)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[partial_derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[partial(skip_serializing_none, from, diff)]
 pub struct BuildConfig {
@@ -257,6 +308,19 @@ pub struct BuildConfig {
   #[builder(default)]
   pub builder_id: String,
 
+  /// Builds with the same concurrency group run one at a time.
+  /// While a build in the group is running, a newer run of this Build
+  /// supersedes (cancels) any run of it still waiting on the group,
+  /// so rapid successive webhooks only build the latest commit.
+  /// Shares the resource lock of the concurrency group with the same
+  /// name, so it is queued across Core instances. Overrides the
+  /// Build's resource concurrency. Joining requires Execute on the
+  /// resources already in the group.
+  /// If empty, the build is not part of a concurrency group.
+  #[serde(default)]
+  #[builder(default)]
+  pub concurrency_group: String,
+
   /// The current version of the build.
   #[serde(default)]
   #[builder(default)]
@@ -373,6 +437,70 @@ pub struct BuildConfig {
   #[builder(default)]
   pub webhook_secret: String,
 
+  /// Choose whether to specify schedule as regular CRON, or using the english to CRON parser.
+  #[serde(default)]
+  #[builder(default)]
+  pub schedule_format: ScheduleFormat,
+
+  /// Optionally provide a schedule for the build to run on.
+  ///
+  /// There are 2 ways to specify a schedule:
+  ///
+  /// 1. Regular CRON expression:
+  ///
+  /// (second, minute, hour, day, month, day-of-week)
+  /// ```text
+  /// 0 0 0 1,15 * ?
+  /// ```
+  ///
+  /// 2. "English" expression via [english-to-cron](https://crates.io/crates/english-to-cron):
+  ///
+  /// ```text
+  /// at midnight on the 1st and 15th of the month
+  /// ```
+  #[serde(default)]
+  #[builder(default)]
+  pub schedule: String,
+
+  /// Whether schedule is enabled if one is provided.
+  /// Can be used to temporarily disable the schedule.
+  #[serde(default = "default_schedule_enabled")]
+  #[builder(default = "default_schedule_enabled()")]
+  #[partial_default(default_schedule_enabled())]
+  pub schedule_enabled: bool,
+
+  /// Optional. A TZ Identifier. If not provided, will use Core local timezone.
+  /// https://en.wikipedia.org/wiki/List_of_tz_database_time_zones.
+  #[serde(default)]
+  #[builder(default)]
+  pub schedule_timezone: String,
+
+  /// Whether to send alerts when the schedule was run.
+  #[serde(default = "default_schedule_alert")]
+  #[builder(default = "default_schedule_alert()")]
+  #[partial_default(default_schedule_alert())]
+  pub schedule_alert: bool,
+
+  /// What to do with scheduled runs missed while Core was down.
+  /// An alert is sent for each missed run either way.
+  #[serde(default)]
+  #[builder(default)]
+  pub schedule_missed_runs: MissedRunPolicy,
+
+  /// Skip scheduled runs if the latest commit on the branch
+  /// was already built successfully. The skipped run is
+  /// still recorded as an Update.
+  /// Only applies to repo based builds.
+  #[serde(default)]
+  #[builder(default)]
+  pub schedule_skip_unchanged: bool,
+
+  /// Whether to send BuildFailed alerts when the build fails.
+  #[serde(default = "default_send_failure_alerts")]
+  #[builder(default = "default_send_failure_alerts()")]
+  #[partial_default(default_send_failure_alerts())]
+  pub send_failure_alerts: bool,
+
   /// If this is checked, the build will source the files on the host.
   /// Use `build_path` and `dockerfile_path` to specify the path on the host.
   /// This is useful for those who wish to setup their files on the host,
@@ -428,6 +556,13 @@ pub struct BuildConfig {
   #[builder(default)]
   pub pre_build: SystemCommand,
 
+  /// Path to a JUnit XML report written by the pre build command,
+  /// relative to the build path.
+  /// The test results are attached to the Update.
+  #[serde(default)]
+  #[builder(default)]
+  pub junit_report: String,
+
   /// UI defined dockerfile contents.
   /// Supports variable / secret interpolation.
   #[serde(default)]
@@ -471,8 +606,114 @@ pub struct BuildConfig {
   ))]
   #[builder(default)]
   pub labels: String,
+
+  /// Persistent cache volumes mounted into every `RUN` instruction
+  /// of the Dockerfile, as `id: /path/in/build` per line, eg:
+  /// ```text
+  /// cargo-registry: /usr/local/cargo/registry
+  /// npm: /root/.npm
+  /// go-mod: /go/pkg/mod
+  /// ```
+  /// These are BuildKit cache mounts stored on the builder,
+  /// and are shared between all builds using the same id.
+  /// The Dockerfile doesn't need to be changed to use them.
+  #[serde(default, deserialize_with = "conversions_deserializer")]
+  #[partial_attr(serde(
+    default,
+    deserialize_with = "option_conversions_deserializer"
+  ))]
+  #[builder(default)]
+  pub cache_volumes: String,
+
+  /// After the build, prune the cache volumes on the builder
+  /// down to this total size, eg `20GB`.
+  /// The least recently used caches are removed first.
+  /// If empty, cache volumes aren't pruned after the build.
+  #[serde(default)]
+  #[builder(default)]
+  pub cache_volumes_max_size: String,
+
+  /// Import the build cache from these remote caches,
+  /// passed to `docker buildx build` as `--cache-from`.
+  /// Accepts a full cache spec, eg `type=registry,ref=ghcr.io/org/app:cache`,
+  /// or just an image reference, which is used as a registry cache.
+  /// Setting this implies `use_buildx`.
+  #[serde(default, deserialize_with = "string_list_deserializer")]
+  #[partial_attr(serde(
+    default,
+    deserialize_with = "option_string_list_deserializer"
+  ))]
+  #[builder(default)]
+  pub cache_from: Vec<String>,
+
+  /// Export the build cache to these remote caches,
+  /// passed to `docker buildx build` as `--cache-to`.
+  /// Accepts a full cache spec, or just an image reference,
+  /// which is exported as a registry cache with `mode=max`.
+  /// Exporting requires a buildx builder using the `docker-container` driver.
+  /// Setting this implies `use_buildx`.
+  #[serde(default, deserialize_with = "string_list_deserializer")]
+  #[partial_attr(serde(
+    default,
+    deserialize_with = "option_string_list_deserializer"
+  ))]
+  #[builder(default)]
+  pub cache_to: Vec<String>,
+
+  /// Build a multi-platform image for these platforms,
+  /// eg `linux/amd64` and `linux/arm64`, passed to
+  /// `docker buildx build` as `--platform`.
+  /// QEMU emulation is set up on the builder for the platforms,
+  /// and a single manifest covering all of them is pushed.
+  /// Setting this implies `use_buildx`.
+  #[serde(default, deserialize_with = "string_list_deserializer")]
+  #[partial_attr(serde(
+    default,
+    deserialize_with = "option_string_list_deserializer"
+  ))]
+  #[builder(default)]
+  pub platforms: Vec<String>,
+
+  /// Build multiple variants of the image from this Build.
+  /// After the shared steps (clone, pre build) run once,
+  /// the variants are built in parallel on the builder,
+  /// and the results are collected in the same Update.
+  /// Each variant's image tags get the variant name as a suffix,
+  /// eg `:1.19.5-alpine`.
+  /// If empty, a single image is built.
+  #[serde(default)]
+  #[builder(default)]
+  pub matrix: Vec<BuildMatrixVariant>,
+
+  /// Scan the built image for vulnerabilities after the build,
+  /// using Trivy on the builder.
+  #[serde(default)]
+  #[builder(default)]
+  pub scan_image: bool,
+
+  /// Fail the build if the scan finds any vulnerability
+  /// at or above this severity.
+  /// The image is already pushed when the scan runs.
+  #[serde(default)]
+  #[builder(default)]
+  pub scan_fail_severity: ImageScanThreshold,
+
+  /// Block deploying the image if its latest scan
+  /// found any Critical vulnerabilities.
+  #[serde(default)]
+  #[builder(default)]
+  pub scan_block_deploy: bool,
+
+  /// Delete stale version tags from the image registries
+  /// after each successful build.
+  #[serde(default)]
+  #[builder(default)]
+  pub image_retention: ImageRetentionPolicy,
 }
 
+/// The stage of the build log containing the pushed manifest digest.
+pub const MANIFEST_DIGEST_LOG_STAGE: &str = "Manifest Digest";
+
 impl BuildConfig {
   pub fn builder() -> BuildConfigBuilder {
     BuildConfigBuilder::default()
@@ -511,10 +752,23 @@ fn default_webhook_enabled() -> bool {
   true
 }
 
+fn default_schedule_enabled() -> bool {
+  true
+}
+
+fn default_schedule_alert() -> bool {
+  true
+}
+
+fn default_send_failure_alerts() -> bool {
+  true
+}
+
 impl Default for BuildConfig {
   fn default() -> Self {
     Self {
       builder_id: Default::default(),
+      concurrency_group: Default::default(),
       skip_secret_interp: Default::default(),
       version: Default::default(),
       auto_increment_version: default_auto_increment_version(),
@@ -532,27 +786,179 @@ impl Default for BuildConfig {
       commit: Default::default(),
       git_account: Default::default(),
       pre_build: Default::default(),
+      junit_report: Default::default(),
       build_path: default_build_path(),
       dockerfile_path: default_dockerfile_path(),
       build_args: Default::default(),
       secret_args: Default::default(),
       labels: Default::default(),
+      cache_volumes: Default::default(),
+      cache_volumes_max_size: Default::default(),
+      cache_from: Default::default(),
+      cache_to: Default::default(),
+      platforms: Default::default(),
+      matrix: Default::default(),
+      scan_image: Default::default(),
+      scan_fail_severity: Default::default(),
+      scan_block_deploy: Default::default(),
+      image_retention: Default::default(),
       extra_args: Default::default(),
       use_buildx: Default::default(),
       image_registry: Default::default(),
       webhook_enabled: default_webhook_enabled(),
       webhook_secret: Default::default(),
+      schedule_format: Default::default(),
+      schedule: Default::default(),
+      schedule_enabled: default_schedule_enabled(),
+      schedule_timezone: Default::default(),
+      schedule_alert: default_schedule_alert(),
+      schedule_missed_runs: Default::default(),
+      schedule_skip_unchanged: Default::default(),
+      send_failure_alerts: default_send_failure_alerts(),
       dockerfile: Default::default(),
       files_on_host: Default::default(),
     }
   }
 }
 
+/// A variant of a matrix build.
+#[typeshare]
+#[derive(
+  Debug, Clone, Default, PartialEq, Serialize, Deserialize,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct BuildMatrixVariant {
+  /// The name of the variant, eg `alpine`.
+  /// It is added to the image tags of the variant.
+  pub name: String,
+
+  /// Build the variant with this dockerfile,
+  /// relative to the build path.
+  /// If empty, uses the Build `dockerfile_path`.
+  #[serde(default)]
+  pub dockerfile_path: String,
+
+  /// Extra build args for the variant, in the same format
+  /// as the Build `build_args`. These are passed after the
+  /// Build `build_args`, so they take precedence.
+  #[serde(default)]
+  pub build_args: String,
+}
+
+/// Which version tags of the Build image to keep in the registries.
+/// Only full version tags, eg `:1.19.5` / `:1.19.5-image_tag`,
+/// are deleted. A tag is kept if either rule keeps it,
+/// and the current version is always kept.
+#[typeshare]
+#[derive(
+  Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ImageRetentionPolicy {
+  /// Keep the latest N versions. 0 disables this rule.
+  #[serde(default)]
+  pub keep_last: I64,
+
+  /// Keep versions pushed within this many days.
+  /// 0 disables this rule.
+  #[serde(default)]
+  pub keep_days: I64,
+}
+
+impl ImageRetentionPolicy {
+  /// Stale tags are only deleted when at least one rule is set.
+  pub fn enabled(&self) -> bool {
+    self.keep_last > 0 || self.keep_days > 0
+  }
+}
+
+/// The severity at which an image scan fails the build.
+#[typeshare]
+#[derive(
+  Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum ImageScanThreshold {
+  /// The scan never fails the build.
+  #[default]
+  None,
+  Low,
+  Medium,
+  High,
+  Critical,
+}
+
+impl ImageScanThreshold {
+  /// The number of vulnerabilities in the scan
+  /// at or above the threshold.
+  pub fn count(&self, scan: &ImageScan) -> I64 {
+    match self {
+      ImageScanThreshold::None => 0,
+      ImageScanThreshold::Low => {
+        scan.low + scan.medium + scan.high + scan.critical
+      }
+      ImageScanThreshold::Medium => {
+        scan.medium + scan.high + scan.critical
+      }
+      ImageScanThreshold::High => scan.high + scan.critical,
+      ImageScanThreshold::Critical => scan.critical,
+    }
+  }
+}
+
+/// The result of scanning a built image for vulnerabilities.
+#[typeshare]
+#[derive(
+  Debug, Clone, Default, PartialEq, Serialize, Deserialize,
+)]
+pub struct ImageScan {
+  /// The scanned image, eg `ghcr.io/org/app:1.2.3`.
+  pub image: String,
+  /// The timestamp of the scan.
+  pub ts: I64,
+  /// The number of Critical vulnerabilities.
+  pub critical: I64,
+  /// The number of High vulnerabilities.
+  pub high: I64,
+  /// The number of Medium vulnerabilities.
+  pub medium: I64,
+  /// The number of Low vulnerabilities.
+  pub low: I64,
+  /// The number of vulnerabilities of unknown severity.
+  pub unknown: I64,
+  /// The Critical and High vulnerabilities found.
+  #[serde(default)]
+  pub vulnerabilities: Vec<ImageVulnerability>,
+}
+
+/// A vulnerability found by an image scan.
+#[typeshare]
+#[derive(
+  Debug, Clone, Default, PartialEq, Serialize, Deserialize,
+)]
+pub struct ImageVulnerability {
+  /// The vulnerability id, eg `CVE-2024-12345`.
+  pub id: String,
+  /// The severity reported by the scanner, eg `CRITICAL`.
+  pub severity: String,
+  /// The affected package.
+  pub package: String,
+  /// The installed version of the package.
+  pub installed_version: String,
+  /// The version fixing the vulnerability, if any.
+  #[serde(default)]
+  pub fixed_version: String,
+  /// A short description of the vulnerability.
+  #[serde(default)]
+  pub title: String,
+}
+
 /// Configuration for an image registry
 #[typeshare]
 #[derive(
   Debug, Clone, Default, PartialEq, Serialize, Deserialize,
 )]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ImageRegistryConfig {
   /// Specify the registry provider domain, eg `docker.io`.
   /// If not provided, will not push to any registry.
@@ -10,7 +10,7 @@ use crate::deserializers::{
 };
 
 use super::{
-  MergePartial,
+  MaintenanceWindow, MergePartial,
   config::{DockerRegistry, GitProvider},
   resource::{AddFilters, Resource, ResourceListItem, ResourceQuery},
 };
@@ -37,6 +37,7 @@ pub struct BuilderListItemInfo {
 
 #[typeshare]
 #[derive(Serialize, Deserialize, Debug, Clone, EnumVariants)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[variant_derive(
   Serialize,
   Deserialize,
@@ -294,6 +295,22 @@ impl MergePartial for BuilderConfig {
               .docker_registries
               .unwrap_or(config.docker_registries),
             secrets: partial.secrets.unwrap_or(config.secrets),
+            warm_pool_size: partial
+              .warm_pool_size
+              .unwrap_or(config.warm_pool_size),
+            warm_pool_hours: partial
+              .warm_pool_hours
+              .unwrap_or(config.warm_pool_hours),
+            warm_pool_idle_timeout: partial
+              .warm_pool_idle_timeout
+              .unwrap_or(config.warm_pool_idle_timeout),
+            use_spot: partial.use_spot.unwrap_or(config.use_spot),
+            spot_max_price: partial
+              .spot_max_price
+              .unwrap_or(config.spot_max_price),
+            spot_interruption_retries: partial
+              .spot_interruption_retries
+              .unwrap_or(config.spot_interruption_retries),
           };
           BuilderConfig::Aws(config)
         }
@@ -309,6 +326,7 @@ pub type _PartialUrlBuilderConfig = PartialUrlBuilderConfig;
 /// Configuration for a Komodo Url Builder.
 #[typeshare]
 #[derive(Serialize, Deserialize, Debug, Clone, Builder, Partial)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[partial_derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[partial(skip_serializing_none, from, diff)]
 pub struct UrlBuilderConfig {
@@ -347,6 +365,7 @@ pub type _PartialServerBuilderConfig = PartialServerBuilderConfig;
 #[derive(
   Serialize, Deserialize, Debug, Clone, Default, Builder, Partial,
 )]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[partial_derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[partial(skip_serializing_none, from, diff)]
 pub struct ServerBuilderConfig {
@@ -368,6 +387,7 @@ pub type _PartialAwsBuilderConfig = PartialAwsBuilderConfig;
 /// Configuration for an AWS builder.
 #[typeshare]
 #[derive(Debug, Clone, Serialize, Deserialize, Builder, Partial)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[partial_derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[partial(skip_serializing_none, from, diff)]
 pub struct AwsBuilderConfig {
@@ -455,6 +475,45 @@ pub struct AwsBuilderConfig {
   ))]
   #[builder(default)]
   pub secrets: Vec<String>,
+
+  /// The number of idle instances to keep running,
+  /// so builds don't have to wait for a new instance to start.
+  /// Instances are reused between builds.
+  /// Default: `0` (disabled)
+  #[serde(default)]
+  #[builder(default)]
+  pub warm_pool_size: i32,
+  /// The hours during which the warm pool is kept filled.
+  /// If empty, the warm pool is always kept filled.
+  #[serde(default)]
+  #[builder(default)]
+  pub warm_pool_hours: Vec<MaintenanceWindow>,
+  /// Minutes an idle instance is kept alive after it is
+  /// no longer needed in the warm pool, before it is terminated.
+  /// Default: `15`
+  #[serde(default = "aws_default_warm_pool_idle_timeout")]
+  #[builder(default = "aws_default_warm_pool_idle_timeout()")]
+  #[partial_default(aws_default_warm_pool_idle_timeout())]
+  pub warm_pool_idle_timeout: i32,
+
+  /// Launch the builder as a spot instance.
+  /// If the instance is interrupted during a build,
+  /// the build is restarted on a new instance.
+  #[serde(default)]
+  #[builder(default)]
+  pub use_spot: bool,
+  /// The maximum hourly price to pay for the spot instance, eg `0.25`.
+  /// If empty, the on-demand price is used as the maximum.
+  #[serde(default)]
+  #[builder(default)]
+  pub spot_max_price: String,
+  /// The number of times to restart a build on a new instance
+  /// after the spot instance is interrupted.
+  /// Default: `2`
+  #[serde(default = "aws_default_spot_interruption_retries")]
+  #[builder(default = "aws_default_spot_interruption_retries()")]
+  #[partial_default(aws_default_spot_interruption_retries())]
+  pub spot_interruption_retries: i32,
 }
 
 impl Default for AwsBuilderConfig {
@@ -475,6 +534,13 @@ impl Default for AwsBuilderConfig {
       git_providers: Default::default(),
       docker_registries: Default::default(),
       secrets: Default::default(),
+      warm_pool_size: Default::default(),
+      warm_pool_hours: Default::default(),
+      warm_pool_idle_timeout: aws_default_warm_pool_idle_timeout(),
+      use_spot: Default::default(),
+      spot_max_price: Default::default(),
+      spot_interruption_retries:
+        aws_default_spot_interruption_retries(),
     }
   }
 }
@@ -497,6 +563,14 @@ fn aws_default_volume_gb() -> i32 {
   20
 }
 
+fn aws_default_warm_pool_idle_timeout() -> i32 {
+  15
+}
+
+fn aws_default_spot_interruption_retries() -> i32 {
+  2
+}
+
 fn default_port() -> i32 {
   8120
 }
@@ -70,3 +70,22 @@ pub struct InspectContainer {
   #[arg(long, short = 'n')]
   pub network_settings: bool,
 }
+
+#[derive(Debug, Clone, clap::Parser)]
+pub struct FollowLog {
+  /// The container name.
+  pub container: String,
+  /// Select the server the container is on.
+  /// Required if containers on multiple servers have the name.
+  #[arg(long, short = 's')]
+  pub server: Option<String>,
+  /// The number of lines to print from before following.
+  #[arg(long, short = 'n', default_value_t = 50)]
+  pub tail: u64,
+  /// Include timestamps.
+  #[arg(long, short = 't')]
+  pub timestamps: bool,
+  /// Only print the lines matching this regex.
+  #[arg(long, short = 'g')]
+  pub filter: Option<String>,
+}
@@ -0,0 +1,60 @@
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, clap::Subcommand)]
+pub enum ImportCommand {
+  /// Import a directory tree of compose projects as Stacks. (alias: `dc`)
+  ///
+  /// Every directory containing a compose file becomes a Stack,
+  /// named after the directory. A `.env` file next to the
+  /// compose file is imported as the Stack environment.
+  #[clap(alias = "dc")]
+  Compose {
+    /// The root directory to search for compose projects.
+    path: PathBuf,
+    /// The Server (name or id) to attach the Stacks to.
+    #[arg(long, short = 's')]
+    server: String,
+    /// Only print the import report, without creating anything.
+    #[arg(long, short = 'n', default_value_t = false)]
+    dry_run: bool,
+    /// Choose the report output format.
+    #[arg(long, short = 'f', default_value_t = super::CliFormat::Table)]
+    format: super::CliFormat,
+    /// Always continue on user confirmation prompts.
+    #[arg(long, short = 'y', default_value_t = false)]
+    yes: bool,
+  },
+  /// Import the Stacks from a Portainer data directory
+  /// or extracted Portainer backup. (alias: `pt`)
+  ///
+  /// The compose files are read from `<path>/compose/<stack id>`.
+  /// Pass the Portainer API stack and endpoint lists to
+  /// also import the Stack names, environment, git config and Servers.
+  #[clap(alias = "pt")]
+  Portainer {
+    /// The Portainer data directory, containing the `compose` directory.
+    path: PathBuf,
+    /// The output of the Portainer API `GET /api/stacks`, saved as a json file.
+    /// If not provided, the Stacks will be named `portainer-<stack id>`.
+    #[arg(long)]
+    stacks: Option<PathBuf>,
+    /// The output of the Portainer API `GET /api/endpoints`, saved as a json file.
+    /// Each Portainer environment is mapped to the Server with the same name,
+    /// which is created (disabled) if it does not exist.
+    #[arg(long)]
+    endpoints: Option<PathBuf>,
+    /// The Server (name or id) to attach Stacks to
+    /// when their Portainer environment can't be mapped.
+    #[arg(long, short = 's')]
+    server: Option<String>,
+    /// Only print the import report, without creating anything.
+    #[arg(long, short = 'n', default_value_t = false)]
+    dry_run: bool,
+    /// Choose the report output format.
+    #[arg(long, short = 'f', default_value_t = super::CliFormat::Table)]
+    format: super::CliFormat,
+    /// Always continue on user confirmation prompts.
+    #[arg(long, short = 'y', default_value_t = false)]
+    yes: bool,
+  },
+}
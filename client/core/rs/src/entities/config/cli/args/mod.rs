@@ -6,6 +6,7 @@ use crate::api::execute::Execution;
 
 pub mod container;
 pub mod database;
+pub mod import;
 pub mod list;
 pub mod update;
 
@@ -59,6 +60,10 @@ pub enum Command {
   #[clap(alias = "i")]
   Inspect(container::InspectContainer),
 
+  /// Follow a container log as it is written. (alias: `log`)
+  #[clap(alias = "log")]
+  Logs(container::FollowLog),
+
   /// List Komodo resources (aliases: `ls`, `resources`)
   #[clap(alias = "ls", alias = "resources")]
   List(list::List),
@@ -86,6 +91,13 @@ pub enum Command {
     #[command(subcommand)]
     command: database::DatabaseCommand,
   },
+
+  /// Import resources from Portainer or compose project directories. (alias: `im`)
+  #[clap(alias = "im")]
+  Import {
+    #[command(subcommand)]
+    command: import::ImportCommand,
+  },
 }
 
 #[derive(Debug, Clone, clap::Parser)]
@@ -11,12 +11,14 @@
 
 use std::{collections::HashMap, path::PathBuf, str::FromStr};
 
+use indexmap::IndexMap;
 use serde::Deserialize;
 
 use crate::entities::{
-  Timelength,
+  ResourceTargetVariant, Timelength,
   config::DatabaseConfig,
   logger::{LogConfig, LogLevel, StdioLogMode},
+  stack::StackFileStorage,
 };
 
 use super::{DockerRegistry, GitProvider, empty_or_redacted};
@@ -75,6 +77,10 @@ pub struct Env {
   pub komodo_passkey: Option<String>,
   /// Override `passkey` with file
   pub komodo_passkey_file: Option<PathBuf>,
+  /// Override `periphery_signing_secret`
+  pub komodo_periphery_signing_secret: Option<String>,
+  /// Override `periphery_signing_secret` with file
+  pub komodo_periphery_signing_secret_file: Option<PathBuf>,
   /// Override `timezone`
   #[serde(alias = "tz", alias = "TZ")]
   pub komodo_timezone: Option<String>,
@@ -96,6 +102,12 @@ pub struct Env {
   pub komodo_repo_directory: Option<PathBuf>,
   /// Override `action_directory`
   pub komodo_action_directory: Option<PathBuf>,
+  /// Override `artifact_directory`
+  pub komodo_artifact_directory: Option<PathBuf>,
+  /// Override `compose_cache_directory`
+  pub komodo_compose_cache_directory: Option<PathBuf>,
+  /// Override `stack_file_directory`
+  pub komodo_stack_file_directory: Option<PathBuf>,
   /// Override `resource_poll_interval`
   pub komodo_resource_poll_interval: Option<Timelength>,
   /// Override `monitoring_interval`
@@ -104,12 +116,30 @@ pub struct Env {
   pub komodo_keep_stats_for_days: Option<u64>,
   /// Override `keep_alerts_for_days`
   pub komodo_keep_alerts_for_days: Option<u64>,
+  /// Override `keep_artifacts_for_days`
+  pub komodo_keep_artifacts_for_days: Option<u64>,
+  /// Override `keep_audit_log_for_days`
+  pub komodo_keep_audit_log_for_days: Option<u64>,
+  /// Override `resource_lock_wait_secs`
+  pub komodo_resource_lock_wait_secs: Option<u64>,
   /// Override `webhook_secret`
   pub komodo_webhook_secret: Option<String>,
   /// Override `webhook_secret` with file
   pub komodo_webhook_secret_file: Option<PathBuf>,
   /// Override `webhook_base_url`
   pub komodo_webhook_base_url: Option<String>,
+  /// Override `metrics_enabled`
+  pub komodo_metrics_enabled: Option<bool>,
+  /// Override `metrics_token`
+  pub komodo_metrics_token: Option<String>,
+  /// Override `metrics_token` with file
+  pub komodo_metrics_token_file: Option<PathBuf>,
+  /// Override `alertmanager_enabled`
+  pub komodo_alertmanager_enabled: Option<bool>,
+  /// Override `alertmanager_token`
+  pub komodo_alertmanager_token: Option<String>,
+  /// Override `alertmanager_token` with file
+  pub komodo_alertmanager_token_file: Option<PathBuf>,
 
   /// Override `logging.level`
   pub komodo_logging_level: Option<LogLevel>,
@@ -180,6 +210,29 @@ pub struct Env {
   pub komodo_oidc_additional_audiences: Option<Vec<String>>,
   /// Override `oidc_additional_audiences` from file
   pub komodo_oidc_additional_audiences_file: Option<PathBuf>,
+  /// Override `oidc_groups_claim`
+  pub komodo_oidc_groups_claim: Option<String>,
+
+  /// Override `ldap_enabled`
+  pub komodo_ldap_enabled: Option<bool>,
+  /// Override `ldap_url`
+  pub komodo_ldap_url: Option<String>,
+  /// Override `ldap_start_tls`
+  pub komodo_ldap_start_tls: Option<bool>,
+  /// Override `ldap_bind_dn`
+  pub komodo_ldap_bind_dn: Option<String>,
+  /// Override `ldap_bind_dn` from file
+  pub komodo_ldap_bind_dn_file: Option<PathBuf>,
+  /// Override `ldap_bind_password`
+  pub komodo_ldap_bind_password: Option<String>,
+  /// Override `ldap_bind_password` from file
+  pub komodo_ldap_bind_password_file: Option<PathBuf>,
+  /// Override `ldap_user_base_dn`
+  pub komodo_ldap_user_base_dn: Option<String>,
+  /// Override `ldap_user_filter`
+  pub komodo_ldap_user_filter: Option<String>,
+  /// Override `ldap_group_attribute`
+  pub komodo_ldap_group_attribute: Option<String>,
 
   /// Override `google_oauth.enabled`
   pub komodo_google_oauth_enabled: Option<bool>,
@@ -258,6 +311,18 @@ pub struct Env {
   pub komodo_aws_secret_access_key: Option<String>,
   /// Override `aws.secret_access_key` with file
   pub komodo_aws_secret_access_key_file: Option<PathBuf>,
+  /// Override `artifact_s3_bucket`
+  pub komodo_artifact_s3_bucket: Option<String>,
+  /// Override `artifact_s3_region`
+  pub komodo_artifact_s3_region: Option<String>,
+  /// Override `stack_file_storage`
+  pub komodo_stack_file_storage: Option<StackFileStorage>,
+  /// Override `stack_file_max_inline_bytes`
+  pub komodo_stack_file_max_inline_bytes: Option<u64>,
+  /// Override `stack_file_s3_bucket`
+  pub komodo_stack_file_s3_bucket: Option<String>,
+  /// Override `stack_file_s3_region`
+  pub komodo_stack_file_s3_region: Option<String>,
 
   /// Override `internet_interface`
   pub komodo_internet_interface: Option<String>,
@@ -322,6 +387,12 @@ pub struct CoreConfig {
   #[serde(default = "default_passkey")]
   pub passkey: String,
 
+  /// Sign requests to periphery with ed25519 keys derived
+  /// from this secret, one key for each Server / Url Builder.
+  /// If empty, requests are not signed.
+  #[serde(default)]
+  pub periphery_signing_secret: String,
+
   /// A TZ Identifier. If not provided, will use Core local timezone.
   /// https://en.wikipedia.org/wiki/List_of_tz_database_time_zones.
   /// This will be populated by TZ env variable in addition to KOMODO_TIMEZONE.
@@ -465,6 +536,70 @@ pub struct CoreConfig {
   #[serde(default, skip_serializing_if = "Vec::is_empty")]
   pub oidc_additional_audiences: Vec<String>,
 
+  /// The ID token claim listing the groups of the user, eg `groups`.
+  /// Nested claims are given as a path, eg `realm_access.roles`.
+  /// Used with `oidc_group_mapping` to sync user group membership on login.
+  #[serde(default, skip_serializing_if = "String::is_empty")]
+  pub oidc_groups_claim: String,
+
+  /// Maps the groups in `oidc_groups_claim` to Komodo user group names.
+  /// On each OIDC login, the user is added to the user groups mapped from
+  /// their groups, and removed from the other user groups in the mapping.
+  /// User groups not in the mapping are left alone.
+  #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+  pub oidc_group_mapping: HashMap<String, String>,
+
+  // ========
+  // = LDAP =
+  // ========
+  /// Enable login with the configured LDAP / Active Directory server.
+  #[serde(default)]
+  pub ldap_enabled: bool,
+
+  /// The LDAP server address.
+  /// Use `ldaps://` for TLS, or `ldap://` with `ldap_start_tls`.
+  ///
+  /// `ldap://ldap.example.internal:389`
+  #[serde(default)]
+  pub ldap_url: String,
+
+  /// Upgrade the `ldap://` connection to TLS with StartTLS.
+  #[serde(default)]
+  pub ldap_start_tls: bool,
+
+  /// The DN of the account used to search for users.
+  /// If empty, users are searched for anonymously.
+  #[serde(default)]
+  pub ldap_bind_dn: String,
+
+  /// The password of `ldap_bind_dn`.
+  #[serde(default)]
+  pub ldap_bind_password: String,
+
+  /// The base DN users are searched for under.
+  /// Eg. `ou=users,dc=example,dc=com`
+  #[serde(default)]
+  pub ldap_user_base_dn: String,
+
+  /// The filter to find the user logging in,
+  /// with `{username}` replaced by the escaped username.
+  /// Default: `(uid={username})`.
+  /// For Active Directory use `(sAMAccountName={username})`.
+  #[serde(default = "default_ldap_user_filter")]
+  pub ldap_user_filter: String,
+
+  /// The user attribute listing the DNs of the user's groups.
+  /// Default: `memberOf`.
+  #[serde(default = "default_ldap_group_attribute")]
+  pub ldap_group_attribute: String,
+
+  /// Maps the group DNs in `ldap_group_attribute` to Komodo user group names.
+  /// On each LDAP login, the user is added to the user groups mapped from
+  /// their groups, and removed from the other user groups in the mapping.
+  /// User groups not in the mapping are left alone.
+  #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+  pub ldap_group_mapping: HashMap<String, String>,
+
   // =========
   // = Oauth =
   // =========
@@ -499,6 +634,40 @@ pub struct CoreConfig {
   #[serde(default)]
   pub github_webhook_app: GithubWebhookAppConfig,
 
+  // ===========
+  // = Metrics =
+  // ===========
+  /// Expose the Prometheus metrics endpoint at `/metrics`.
+  #[serde(default)]
+  pub metrics_enabled: bool,
+
+  /// The metrics endpoint requires `Authorization: Bearer <metrics_token>`.
+  /// Required when metrics are enabled.
+  #[serde(default)]
+  pub metrics_token: String,
+
+  // ================
+  // = Alertmanager =
+  // ================
+  /// Accept Prometheus Alertmanager webhooks at `/listener/alertmanager`,
+  /// and open / resolve them as Komodo alerts.
+  #[serde(default)]
+  pub alertmanager_enabled: bool,
+
+  /// The Alertmanager webhook requires
+  /// `Authorization: Bearer <alertmanager_token>`.
+  /// If empty, the `webhook_secret` is used instead.
+  #[serde(default)]
+  pub alertmanager_token: String,
+
+  /// Map alert labels to the type of resource they identify.
+  /// The label values are matched against the resource names and ids,
+  /// checking the labels in order. Alerts which match no resource
+  /// are attached to System.
+  #[serde(default = "default_target_labels")]
+  pub alertmanager_target_labels:
+    IndexMap<String, ResourceTargetVariant>,
+
   // ===========
   // = Logging =
   // ===========
@@ -532,6 +701,26 @@ pub struct CoreConfig {
   #[serde(default = "default_prune_days")]
   pub keep_alerts_for_days: u64,
 
+  /// Number of days to keep Update artifacts, or 0 to disable pruning.
+  /// Artifacts of Updates older than this number of days are deleted on a daily cycle
+  /// Default: 14
+  #[serde(default = "default_prune_days")]
+  pub keep_artifacts_for_days: u64,
+
+  /// Number of days to keep audit log entries, or 0 to disable pruning.
+  /// Entries older than this number of days are deleted on a daily cycle
+  /// Default: 90
+  #[serde(default = "default_audit_log_prune_days")]
+  pub keep_audit_log_for_days: u64,
+
+  /// When an execution is started on a resource which another
+  /// execution is already running on, wait up to this many seconds
+  /// for it to finish. 0 fails the execution immediately.
+  /// Only applies to resources using the Reject concurrency policy.
+  /// Default: 0
+  #[serde(default)]
+  pub resource_lock_wait_secs: u64,
+
   // ==================
   // = Poll Intervals =
   // ==================
@@ -553,6 +742,51 @@ pub struct CoreConfig {
   #[serde(default)]
   pub aws: AwsCredentials,
 
+  /// Store Update artifacts in this S3 bucket, using the `aws` credentials.
+  /// If empty, artifacts are stored in `artifact_directory`.
+  #[serde(default)]
+  pub artifact_s3_bucket: String,
+
+  /// The region of `artifact_s3_bucket`.
+  /// Default: `us-east-1`
+  #[serde(default = "default_artifact_s3_region")]
+  pub artifact_s3_region: String,
+
+  /// Where to store UI defined Stack file contents
+  /// larger than `stack_file_max_inline_bytes`.
+  /// Existing contents are moved over on startup.
+  /// Default: `database`
+  #[serde(default)]
+  pub stack_file_storage: StackFileStorage,
+
+  /// Stack file contents larger than this are moved
+  /// to the `stack_file_storage`.
+  /// Default: `1048576` (1 MiB)
+  #[serde(default = "default_stack_file_max_inline_bytes")]
+  pub stack_file_max_inline_bytes: u64,
+
+  /// Store Stack file contents in this S3 bucket,
+  /// using the `aws` credentials, with `stack_file_storage = "s3"`.
+  #[serde(default)]
+  pub stack_file_s3_bucket: String,
+
+  /// The region of `stack_file_s3_bucket`.
+  /// Default: `us-east-1`
+  #[serde(default = "default_artifact_s3_region")]
+  pub stack_file_s3_region: String,
+
+  // ==========================
+  // = Container DNS Defaults =
+  // ==========================
+  /// Default `dns` and `extra_hosts` for the Deployment and Stack
+  /// containers on matching Servers.
+  #[serde(
+    default,
+    alias = "container_dns_default",
+    skip_serializing_if = "Vec::is_empty"
+  )]
+  pub container_dns_defaults: Vec<ContainerDnsDefaults>,
+
   // =================
   // = Git Providers =
   // =================
@@ -586,6 +820,16 @@ pub struct CoreConfig {
   #[serde(default, skip_serializing_if = "HashMap::is_empty")]
   pub secrets: HashMap<String, String>,
 
+  /// Configure external secret providers, such as HashiCorp Vault,
+  /// AWS Secrets Manager, or env files. Their secrets are cached
+  /// in memory for a minute, and are never persisted by Komodo.
+  #[serde(
+    default,
+    alias = "secret_provider",
+    skip_serializing_if = "Vec::is_empty"
+  )]
+  pub secret_providers: Vec<SecretProvider>,
+
   // =======
   // = SSL =
   // =======
@@ -621,6 +865,24 @@ pub struct CoreConfig {
   /// Default: `/action-cache`
   #[serde(default = "default_action_directory")]
   pub action_directory: PathBuf,
+
+  /// Specify the directory used to store Update artifacts,
+  /// if `artifact_s3_bucket` is not configured.
+  /// Default: `/artifacts`
+  #[serde(default = "default_artifact_directory")]
+  pub artifact_directory: PathBuf,
+
+  /// Specify the directory used to cache remote compose includes
+  /// fetched for Stacks with `cache_remote_includes` enabled.
+  /// Default: `/compose-cache`
+  #[serde(default = "default_compose_cache_directory")]
+  pub compose_cache_directory: PathBuf,
+
+  /// Specify the directory used to store Stack file contents
+  /// with `stack_file_storage = "filesystem"`.
+  /// Default: `/stack-files`
+  #[serde(default = "default_stack_file_directory")]
+  pub stack_file_directory: PathBuf,
 }
 
 fn default_title() -> String {
@@ -659,6 +921,14 @@ fn default_init_admin_password() -> String {
   String::from("changeme")
 }
 
+fn default_ldap_user_filter() -> String {
+  String::from("(uid={username})")
+}
+
+fn default_ldap_group_attribute() -> String {
+  String::from("memberOf")
+}
+
 fn default_sync_directory() -> PathBuf {
   // unwrap ok: `/syncs` will always be valid path
   PathBuf::from_str("/syncs").unwrap()
@@ -674,10 +944,37 @@ fn default_action_directory() -> PathBuf {
   PathBuf::from_str("/action-cache").unwrap()
 }
 
+fn default_artifact_directory() -> PathBuf {
+  // unwrap ok: `/artifacts` will always be valid path
+  PathBuf::from_str("/artifacts").unwrap()
+}
+
+fn default_compose_cache_directory() -> PathBuf {
+  // unwrap ok: `/compose-cache` will always be valid path
+  PathBuf::from_str("/compose-cache").unwrap()
+}
+
+fn default_stack_file_directory() -> PathBuf {
+  // unwrap ok: `/stack-files` will always be valid path
+  PathBuf::from_str("/stack-files").unwrap()
+}
+
+fn default_artifact_s3_region() -> String {
+  String::from("us-east-1")
+}
+
+fn default_stack_file_max_inline_bytes() -> u64 {
+  1024 * 1024
+}
+
 fn default_prune_days() -> u64 {
   14
 }
 
+fn default_audit_log_prune_days() -> u64 {
+  90
+}
+
 fn default_poll_interval() -> Timelength {
   Timelength::OneHour
 }
@@ -694,6 +991,18 @@ fn default_ssl_cert_file() -> PathBuf {
   "/config/ssl/cert.pem".parse().unwrap()
 }
 
+fn default_target_labels() -> IndexMap<String, ResourceTargetVariant>
+{
+  IndexMap::from([
+    (String::from("komodo_server"), ResourceTargetVariant::Server),
+    (String::from("komodo_stack"), ResourceTargetVariant::Stack),
+    (
+      String::from("komodo_deployment"),
+      ResourceTargetVariant::Deployment,
+    ),
+  ])
+}
+
 impl Default for CoreConfig {
   fn default() -> Self {
     Self {
@@ -703,6 +1012,7 @@ impl Default for CoreConfig {
       bind_ip: default_core_bind_ip(),
       internet_interface: Default::default(),
       passkey: default_passkey(),
+      periphery_signing_secret: Default::default(),
       timezone: Default::default(),
       ui_write_disabled: Default::default(),
       disable_confirm_dialog: Default::default(),
@@ -730,19 +1040,47 @@ impl Default for CoreConfig {
       oidc_client_secret: Default::default(),
       oidc_use_full_email: Default::default(),
       oidc_additional_audiences: Default::default(),
+      oidc_groups_claim: Default::default(),
+      oidc_group_mapping: Default::default(),
+      ldap_enabled: Default::default(),
+      ldap_url: Default::default(),
+      ldap_start_tls: Default::default(),
+      ldap_bind_dn: Default::default(),
+      ldap_bind_password: Default::default(),
+      ldap_user_base_dn: Default::default(),
+      ldap_user_filter: default_ldap_user_filter(),
+      ldap_group_attribute: default_ldap_group_attribute(),
+      ldap_group_mapping: Default::default(),
       google_oauth: Default::default(),
       github_oauth: Default::default(),
       webhook_secret: Default::default(),
       webhook_base_url: Default::default(),
       github_webhook_app: Default::default(),
+      metrics_enabled: Default::default(),
+      metrics_token: Default::default(),
+      alertmanager_enabled: Default::default(),
+      alertmanager_token: Default::default(),
+      alertmanager_target_labels: default_target_labels(),
       logging: Default::default(),
       pretty_startup_config: Default::default(),
       unsafe_unsanitized_startup_config: Default::default(),
       keep_stats_for_days: default_prune_days(),
       keep_alerts_for_days: default_prune_days(),
+      keep_artifacts_for_days: default_prune_days(),
+      keep_audit_log_for_days: default_audit_log_prune_days(),
+      resource_lock_wait_secs: Default::default(),
       resource_poll_interval: default_poll_interval(),
       monitoring_interval: default_monitoring_interval(),
       aws: Default::default(),
+      artifact_s3_bucket: Default::default(),
+      artifact_s3_region: default_artifact_s3_region(),
+      stack_file_storage: Default::default(),
+      stack_file_max_inline_bytes:
+        default_stack_file_max_inline_bytes(),
+      stack_file_s3_bucket: Default::default(),
+      stack_file_s3_region: default_artifact_s3_region(),
+      container_dns_defaults: Default::default(),
+      secret_providers: Default::default(),
       git_providers: Default::default(),
       docker_registries: Default::default(),
       secrets: Default::default(),
@@ -752,6 +1090,9 @@ impl Default for CoreConfig {
       sync_directory: default_sync_directory(),
       repo_directory: default_repo_directory(),
       action_directory: default_action_directory(),
+      artifact_directory: default_artifact_directory(),
+      compose_cache_directory: default_compose_cache_directory(),
+      stack_file_directory: default_stack_file_directory(),
     }
   }
 }
@@ -765,6 +1106,9 @@ impl CoreConfig {
       port: config.port,
       bind_ip: config.bind_ip,
       passkey: empty_or_redacted(&config.passkey),
+      periphery_signing_secret: empty_or_redacted(
+        &config.periphery_signing_secret,
+      ),
       timezone: config.timezone,
       first_server: config.first_server,
       first_server_name: config.first_server_name,
@@ -773,12 +1117,18 @@ impl CoreConfig {
       jwt_ttl: config.jwt_ttl,
       repo_directory: config.repo_directory,
       action_directory: config.action_directory,
+      artifact_directory: config.artifact_directory,
+      compose_cache_directory: config.compose_cache_directory,
+      stack_file_directory: config.stack_file_directory,
       sync_directory: config.sync_directory,
       internet_interface: config.internet_interface,
       resource_poll_interval: config.resource_poll_interval,
       monitoring_interval: config.monitoring_interval,
       keep_stats_for_days: config.keep_stats_for_days,
       keep_alerts_for_days: config.keep_alerts_for_days,
+      keep_artifacts_for_days: config.keep_artifacts_for_days,
+      keep_audit_log_for_days: config.keep_audit_log_for_days,
+      resource_lock_wait_secs: config.resource_lock_wait_secs,
       logging: config.logging,
       pretty_startup_config: config.pretty_startup_config,
       unsafe_unsanitized_startup_config: config
@@ -813,6 +1163,19 @@ impl CoreConfig {
         .iter()
         .map(|aud| empty_or_redacted(aud))
         .collect(),
+      oidc_groups_claim: config.oidc_groups_claim,
+      oidc_group_mapping: config.oidc_group_mapping,
+      ldap_enabled: config.ldap_enabled,
+      ldap_url: config.ldap_url,
+      ldap_start_tls: config.ldap_start_tls,
+      ldap_bind_dn: empty_or_redacted(&config.ldap_bind_dn),
+      ldap_bind_password: empty_or_redacted(
+        &config.ldap_bind_password,
+      ),
+      ldap_user_base_dn: config.ldap_user_base_dn,
+      ldap_user_filter: config.ldap_user_filter,
+      ldap_group_attribute: config.ldap_group_attribute,
+      ldap_group_mapping: config.ldap_group_mapping,
       google_oauth: OauthCredentials {
         enabled: config.google_oauth.enabled,
         id: empty_or_redacted(&config.google_oauth.id),
@@ -826,6 +1189,13 @@ impl CoreConfig {
       webhook_secret: empty_or_redacted(&config.webhook_secret),
       webhook_base_url: config.webhook_base_url,
       github_webhook_app: config.github_webhook_app,
+      metrics_enabled: config.metrics_enabled,
+      metrics_token: empty_or_redacted(&config.metrics_token),
+      alertmanager_enabled: config.alertmanager_enabled,
+      alertmanager_token: empty_or_redacted(
+        &config.alertmanager_token,
+      ),
+      alertmanager_target_labels: config.alertmanager_target_labels,
       database: config.database.sanitized(),
       aws: AwsCredentials {
         access_key_id: empty_or_redacted(&config.aws.access_key_id),
@@ -833,11 +1203,28 @@ impl CoreConfig {
           &config.aws.secret_access_key,
         ),
       },
+      artifact_s3_bucket: config.artifact_s3_bucket,
+      artifact_s3_region: config.artifact_s3_region,
+      stack_file_storage: config.stack_file_storage,
+      stack_file_max_inline_bytes: config.stack_file_max_inline_bytes,
+      stack_file_s3_bucket: config.stack_file_s3_bucket,
+      stack_file_s3_region: config.stack_file_s3_region,
       secrets: config
         .secrets
         .into_iter()
         .map(|(id, secret)| (id, empty_or_redacted(&secret)))
         .collect(),
+      secret_providers: config
+        .secret_providers
+        .into_iter()
+        .map(|mut provider| {
+          if let SecretProvider::Vault(vault) = &mut provider {
+            vault.token = empty_or_redacted(&vault.token);
+          }
+          provider
+        })
+        .collect(),
+      container_dns_defaults: config.container_dns_defaults,
       git_providers: config
         .git_providers
         .into_iter()
@@ -915,6 +1302,91 @@ impl Default for GithubWebhookAppConfig {
   }
 }
 
+/// Default DNS configuration for the containers
+/// deployed on a group of Servers.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ContainerDnsDefaults {
+  /// Apply to Servers with any of these tags (name or id).
+  /// Empty applies to all Servers.
+  #[serde(default)]
+  pub tags: Vec<String>,
+  /// DNS servers used by the containers.
+  /// Only used when the Deployment / Stack doesn't configure `dns`.
+  #[serde(default)]
+  pub dns: Vec<String>,
+  /// Extra `hostname:ip` entries added to the containers `/etc/hosts`.
+  /// Entries for hostnames the Deployment / Stack already
+  /// configures are skipped.
+  #[serde(default)]
+  pub extra_hosts: Vec<String>,
+}
+
+/// An external source of secrets for interpolation.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type")]
+pub enum SecretProvider {
+  /// Read the keys of a HashiCorp Vault KV v2 secret.
+  Vault(VaultSecretProvider),
+  /// Read AWS Secrets Manager secrets,
+  /// using the `aws` credentials.
+  AwsSecretsManager(AwsSecretsManagerSecretProvider),
+  /// Read a `KEY=value` env file on the Core host.
+  EnvFile(EnvFileSecretProvider),
+}
+
+/// Reads `{address}/v1/{mount}/data/{path}`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct VaultSecretProvider {
+  /// The Vault address, eg. `https://vault.example.com`.
+  pub address: String,
+  /// The Vault token.
+  pub token: String,
+  /// The Vault namespace. Only for Vault Enterprise.
+  #[serde(default)]
+  pub namespace: String,
+  /// The KV v2 mount.
+  /// Default: `secret`
+  #[serde(default = "default_vault_mount")]
+  pub mount: String,
+  /// The path of the secret in the mount.
+  pub path: String,
+  /// Prefixed to the secret names.
+  #[serde(default)]
+  pub prefix: String,
+}
+
+fn default_vault_mount() -> String {
+  String::from("secret")
+}
+
+/// Secrets storing a JSON object provide each of its keys,
+/// other secrets are provided using the secret id as the name.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AwsSecretsManagerSecretProvider {
+  /// The AWS region.
+  /// Default: `us-east-1`
+  #[serde(default = "default_aws_region")]
+  pub region: String,
+  /// The secret names / ARNs to read.
+  pub secret_ids: Vec<String>,
+  /// Prefixed to the secret names.
+  #[serde(default)]
+  pub prefix: String,
+}
+
+fn default_aws_region() -> String {
+  String::from("us-east-1")
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EnvFileSecretProvider {
+  /// The path to the env file.
+  pub path: PathBuf,
+  /// Prefixed to the secret names.
+  #[serde(default)]
+  pub prefix: String,
+}
+
 /// Provide configuration for a Github Webhook app installation.
 #[derive(Debug, Clone, Deserialize)]
 pub struct GithubWebhookAppInstallationConfig {
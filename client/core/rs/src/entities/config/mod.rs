@@ -112,6 +112,7 @@ impl DatabaseConfig {
   Serialize,
   Deserialize,
 )]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct GitProvider {
   /// The git provider domain. Default: `github.com`.
   #[serde(default = "default_git_provider")]
@@ -144,6 +145,7 @@ fn default_git_https() -> bool {
   Serialize,
   Deserialize,
 )]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct DockerRegistry {
   /// The docker provider domain. Default: `docker.io`.
   #[serde(default = "default_docker_provider")]
@@ -173,6 +175,7 @@ fn default_docker_provider() -> String {
   Serialize,
   Deserialize,
 )]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ProviderAccount {
   /// The account username. Required.
   #[serde(alias = "account")]
@@ -14,14 +14,16 @@
 
 use clap::Parser;
 use ipnetwork::IpNetwork;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, path::PathBuf};
+use strum::Display;
 
 use crate::{
   deserializers::ForgivingVec,
   entities::{
     Timelength,
     logger::{LogConfig, LogLevel, StdioLogMode},
+    server::TerminalProfile,
   },
 };
 
@@ -46,6 +48,11 @@ use super::{
 ///   --extend-config-arrays false \
 ///   --log-level info
 /// ```
+///
+/// Passing a [PeripheryCommand] runs it locally instead of starting the agent:
+/// ```sh
+/// periphery --config-path /path/to/periphery.config.toml self-test
+/// ```
 #[derive(Parser)]
 #[command(name = "periphery", author, about, version)]
 pub struct CliArgs {
@@ -77,6 +84,77 @@ pub struct CliArgs {
   /// If passed, will override any other log_level set.
   #[arg(long)]
   pub log_level: Option<tracing::Level>,
+
+  /// Run a local command instead of starting the agent.
+  #[command(subcommand)]
+  pub command: Option<PeripheryCommand>,
+}
+
+/// # Periphery Local Commands
+///
+/// These run directly on the host, for debugging on-site
+/// when Core is unreachable. They use the same config as the agent.
+#[derive(Debug, Clone, clap::Subcommand)]
+pub enum PeripheryCommand {
+  /// List the containers on the host, as seen by the agent. (aliases: `ps`, `cn`)
+  #[clap(alias = "ps", alias = "cn")]
+  Containers {
+    /// Include containers which aren't running.
+    #[arg(long, short = 'a', default_value_t = false)]
+    all: bool,
+
+    /// Use the Podman socket instead of Docker.
+    #[arg(long, default_value_t = false)]
+    podman: bool,
+  },
+
+  /// Print the agent's config, after combining the
+  /// args, environment and config files. (aliases: `cfg`, `cf`)
+  #[clap(alias = "cfg", alias = "cf")]
+  Config {
+    /// Whether to print unsanitized config,
+    /// including passkeys and secrets.
+    #[arg(long, action)]
+    unsanitized: bool,
+  },
+
+  /// Tail the agent's own logs. Reads from systemd,
+  /// or the container runtime when the agent runs in a container.
+  Logs {
+    /// The number of lines to show.
+    #[arg(long, short = 'n', default_value_t = 100)]
+    tail: u64,
+
+    /// Keep following the logs.
+    #[arg(long, short = 'f', default_value_t = false)]
+    follow: bool,
+
+    /// The systemd unit running the agent.
+    #[arg(long, default_value = "periphery")]
+    unit: String,
+
+    /// The systemd unit is a user unit.
+    #[arg(long, default_value_t = false)]
+    user: bool,
+
+    /// Use podman instead of docker for container logs.
+    #[arg(long, default_value_t = false)]
+    podman: bool,
+  },
+
+  /// Check the container runtime, that the agent is listening,
+  /// and optionally that Core is reachable. (alias: `test`)
+  #[clap(alias = "test")]
+  SelfTest {
+    /// The Komodo Core address to test reaching.
+    /// Eg. "https://komodo.example.com"
+    #[arg(long, short = 'a')]
+    core: Option<String>,
+
+    /// Use the Podman socket instead of Docker.
+    #[arg(long, default_value_t = false)]
+    podman: bool,
+  },
 }
 
 /// # Periphery Environment Variables
@@ -136,12 +214,26 @@ pub struct Env {
   pub periphery_disable_terminals: Option<bool>,
   /// Override `disable_container_exec`
   pub periphery_disable_container_exec: Option<bool>,
+  /// Override `allowed_terminal_commands`
+  pub periphery_allowed_terminal_commands: Option<Vec<String>>,
+  /// Override `terminal_idle_timeout`
+  pub periphery_terminal_idle_timeout: Option<Timelength>,
+  /// Override `terminal_max_lifetime`
+  pub periphery_terminal_max_lifetime: Option<Timelength>,
+  /// Override `max_terminals`
+  pub periphery_max_terminals: Option<usize>,
+  /// Override `terminal_file_root`
+  pub periphery_terminal_file_root: Option<PathBuf>,
   /// Override `stats_polling_rate`
   pub periphery_stats_polling_rate: Option<Timelength>,
   /// Override `container_stats_polling_rate`
   pub periphery_container_stats_polling_rate: Option<Timelength>,
   /// Override `legacy_compose_cli`
   pub periphery_legacy_compose_cli: Option<bool>,
+  /// Override `cache_volumes_prune_interval`
+  pub periphery_cache_volumes_prune_interval: Option<Timelength>,
+  /// Override `cache_volumes_max_size`
+  pub periphery_cache_volumes_max_size: Option<String>,
 
   // LOGGING
   /// Override `logging.level`
@@ -165,6 +257,10 @@ pub struct Env {
   pub periphery_passkeys: Option<Vec<String>>,
   /// Override `passkeys` from file
   pub periphery_passkeys_file: Option<PathBuf>,
+  /// Override `core_public_keys`
+  pub periphery_core_public_keys: Option<Vec<String>>,
+  /// Override `core_public_keys` from file
+  pub periphery_core_public_keys_file: Option<PathBuf>,
   /// Override `include_disk_mounts`
   pub periphery_include_disk_mounts: Option<ForgivingVec<PathBuf>>,
   /// Override `exclude_disk_mounts`
@@ -223,6 +319,7 @@ pub struct PeripheryConfig {
 
   /// Whether to disable the create terminal
   /// and disallow direct remote shell access.
+  /// This also refuses to run Server scheduled commands.
   /// Default: false
   #[serde(default)]
   pub disable_terminals: bool,
@@ -233,6 +330,50 @@ pub struct PeripheryConfig {
   #[serde(default)]
   pub disable_container_exec: bool,
 
+  /// If non-empty, terminals can only be created
+  /// using one of these commands (eg. `bash`, `docker exec -it postgres psql`).
+  /// Container exec shells must also be in the list.
+  /// This is enforced regardless of what Core requests.
+  /// Default: none
+  #[serde(default)]
+  pub allowed_terminal_commands: Vec<String>,
+
+  /// Terminal profiles enforced by Periphery. If non-empty,
+  /// terminals created from a profile must use one of these by name,
+  /// and are started with its command, working dir and environment,
+  /// regardless of what Core sends. If the profile has `user_groups`,
+  /// the user must be in one of them.
+  /// Default: none
+  #[serde(default)]
+  pub terminal_profiles: Vec<TerminalProfile>,
+
+  /// Close terminals which haven't received any input for this long.
+  /// Connected users are warned before the terminal is closed.
+  /// Options: https://docs.rs/komodo_client/latest/komodo_client/entities/enum.Timelength.html
+  /// Default: none (never closed for inactivity)
+  pub terminal_idle_timeout: Option<Timelength>,
+
+  /// Close terminals which have been open for this long,
+  /// regardless of activity.
+  /// Connected users are warned before the terminal is closed.
+  /// Options: https://docs.rs/komodo_client/latest/komodo_client/entities/enum.Timelength.html
+  /// Default: none (no maximum lifetime)
+  pub terminal_max_lifetime: Option<Timelength>,
+
+  /// The maximum number of terminals (including container exec sessions)
+  /// which can be open at once. 0 means no limit.
+  /// Default: 0
+  #[serde(default)]
+  pub max_terminals: usize,
+
+  /// Files transferred through (non container) terminals
+  /// must be inside this directory. If not set, host file
+  /// transfer is disabled. File transfer is always disabled
+  /// for terminals created from a profile, or when
+  /// `allowed_terminal_commands` is set.
+  /// Default: none
+  pub terminal_file_root: Option<PathBuf>,
+
   /// The rate at which the system stats will be polled to update the cache.
   /// Options: https://docs.rs/komodo_client/latest/komodo_client/entities/enum.Timelength.html
   /// Default: `5-sec`
@@ -251,6 +392,18 @@ pub struct PeripheryConfig {
   #[serde(default)]
   pub legacy_compose_cli: bool,
 
+  /// How often to prune the build cache volumes on the host.
+  /// Options: https://docs.rs/komodo_client/latest/komodo_client/entities/enum.Timelength.html
+  /// Default: None (disabled)
+  pub cache_volumes_prune_interval: Option<Timelength>,
+
+  /// When pruning on the interval, keep the build cache volumes
+  /// up to this total size, eg `50GB`.
+  /// If empty, all unused cache volumes are removed.
+  /// Default: empty
+  #[serde(default)]
+  pub cache_volumes_max_size: String,
+
   /// Logging configuration
   #[serde(default)]
   pub logging: LogConfig,
@@ -274,6 +427,14 @@ pub struct PeripheryConfig {
   #[serde(default)]
   pub passkeys: Vec<String>,
 
+  /// The base64 ed25519 public keys of the Cores allowed
+  /// to send requests. When set, requests must be signed by
+  /// one of the keys, and stale or replayed requests are rejected.
+  /// Get the key for the Server with `GetPeripheryPublicKey`.
+  /// Default: none
+  #[serde(default)]
+  pub core_public_keys: Vec<String>,
+
   /// If non-empty, only includes specific mount paths in the disk report.
   #[serde(default)]
   pub include_disk_mounts: ForgivingVec<PathBuf>,
@@ -297,6 +458,12 @@ pub struct PeripheryConfig {
   #[serde(default, alias = "docker_registry")]
   pub docker_registries: ForgivingVec<DockerRegistry>,
 
+  /// Executables run on the host at hook points,
+  /// to add custom behavior to Periphery.
+  /// Default: none
+  #[serde(default, alias = "hook")]
+  pub hooks: ForgivingVec<PeripheryHook>,
+
   /// Whether to enable ssl.
   /// Default: true
   #[serde(default = "default_ssl_enabled")]
@@ -311,6 +478,61 @@ pub struct PeripheryConfig {
   pub ssl_cert_file: Option<PathBuf>,
 }
 
+/// An executable Periphery runs at hook points.
+///
+/// The event is passed to the executable as JSON on stdin,
+/// eg. `{"event": "pre_deploy", "stack": "my-stack", ...}`.
+/// A non-zero exit code, or reaching the timeout, fails the hook.
+/// When a `pre_deploy` or `pre_prune` hook fails, the action is not run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeripheryHook {
+  /// A name for the hook, used in the logs.
+  pub name: String,
+  /// The path to the executable.
+  pub path: PathBuf,
+  /// Args passed to the executable.
+  #[serde(default)]
+  pub args: Vec<String>,
+  /// The hook points to run at.
+  /// If empty, runs at all of them.
+  #[serde(default)]
+  pub events: Vec<PeripheryHookEvent>,
+  /// Kill the executable after this many seconds.
+  /// Default: `30`
+  #[serde(default = "default_hook_timeout_seconds")]
+  pub timeout_seconds: u64,
+}
+
+fn default_hook_timeout_seconds() -> u64 {
+  30
+}
+
+impl PeripheryHook {
+  pub fn runs_on(&self, event: PeripheryHookEvent) -> bool {
+    self.events.is_empty() || self.events.contains(&event)
+  }
+}
+
+/// The points where Periphery runs the [PeripheryHook]s.
+#[derive(
+  Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Display,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum PeripheryHookEvent {
+  /// Before a Stack or Deployment is deployed.
+  PreDeploy,
+  /// After a Stack or Deployment is deployed.
+  /// Also runs if the deploy fails.
+  PostDeploy,
+  /// Before containers, images, networks, volumes
+  /// or the build cache are pruned.
+  PrePrune,
+  /// When Core sends an alert for the Server,
+  /// or a Stack / Deployment on it.
+  AlertCondition,
+}
+
 fn default_periphery_port() -> u16 {
   8120
 }
@@ -346,19 +568,29 @@ impl Default for PeripheryConfig {
       build_dir: None,
       disable_terminals: Default::default(),
       disable_container_exec: Default::default(),
+      allowed_terminal_commands: Default::default(),
+      terminal_profiles: Default::default(),
+      terminal_idle_timeout: None,
+      terminal_max_lifetime: None,
+      max_terminals: Default::default(),
+      terminal_file_root: None,
       stats_polling_rate: default_stats_polling_rate(),
       container_stats_polling_rate:
         default_container_stats_polling_rate(),
       legacy_compose_cli: Default::default(),
+      cache_volumes_prune_interval: None,
+      cache_volumes_max_size: Default::default(),
       logging: Default::default(),
       pretty_startup_config: Default::default(),
       allowed_ips: Default::default(),
       passkeys: Default::default(),
+      core_public_keys: Default::default(),
       include_disk_mounts: Default::default(),
       exclude_disk_mounts: Default::default(),
       secrets: Default::default(),
       git_providers: Default::default(),
       docker_registries: Default::default(),
+      hooks: Default::default(),
       ssl_enabled: default_ssl_enabled(),
       ssl_key_file: None,
       ssl_cert_file: None,
@@ -377,9 +609,19 @@ impl PeripheryConfig {
       build_dir: self.build_dir.clone(),
       disable_terminals: self.disable_terminals,
       disable_container_exec: self.disable_container_exec,
+      allowed_terminal_commands: self
+        .allowed_terminal_commands
+        .clone(),
+      terminal_profiles: self.terminal_profiles.clone(),
+      terminal_idle_timeout: self.terminal_idle_timeout,
+      terminal_max_lifetime: self.terminal_max_lifetime,
+      max_terminals: self.max_terminals,
+      terminal_file_root: self.terminal_file_root.clone(),
       stats_polling_rate: self.stats_polling_rate,
       container_stats_polling_rate: self.container_stats_polling_rate,
       legacy_compose_cli: self.legacy_compose_cli,
+      cache_volumes_prune_interval: self.cache_volumes_prune_interval,
+      cache_volumes_max_size: self.cache_volumes_max_size.clone(),
       logging: self.logging.clone(),
       pretty_startup_config: self.pretty_startup_config,
       allowed_ips: self.allowed_ips.clone(),
@@ -388,6 +630,7 @@ impl PeripheryConfig {
         .iter()
         .map(|passkey| empty_or_redacted(passkey))
         .collect(),
+      core_public_keys: self.core_public_keys.clone(),
       include_disk_mounts: self.include_disk_mounts.clone(),
       exclude_disk_mounts: self.exclude_disk_mounts.clone(),
       secrets: self
@@ -429,6 +672,7 @@ impl PeripheryConfig {
             .collect(),
         })
         .collect(),
+      hooks: self.hooks.clone(),
       ssl_enabled: self.ssl_enabled,
       ssl_key_file: self.ssl_key_file.clone(),
       ssl_cert_file: self.ssl_cert_file.clone(),
@@ -21,9 +21,14 @@ use crate::{
 };
 
 use super::{
-  TerminationSignal, Version,
-  docker::container::ContainerStateStatusEnum,
+  AutoStopMode, I64, MaintenanceWindow, RuntimeInput,
+  ScheduleFormat, TerminationSignal, Version,
+  docker::container::{
+    ContainerListItem, ContainerStateStatusEnum, HealthStatusEnum,
+  },
+  log_forwarding::LogForwardingConfig,
   resource::{Resource, ResourceListItem, ResourceQuery},
+  update::ExecutionMetrics,
 };
 
 #[typeshare]
@@ -48,6 +53,12 @@ pub struct DeploymentListItemInfo {
   pub server_id: String,
   /// An attached Komodo Build, if it exists.
   pub build_id: Option<String>,
+  /// Whether the deployment was manually started / stopped
+  /// against its `uptime_hours`.
+  pub uptime_override: bool,
+  /// Deploy metrics over the last 30 days.
+  #[serde(default)]
+  pub metrics: ExecutionMetrics,
 }
 
 #[typeshare(serialized_as = "Partial<DeploymentConfig>")]
@@ -55,6 +66,7 @@ pub type _PartialDeploymentConfig = PartialDeploymentConfig;
 
 #[typeshare]
 #[derive(Serialize, Deserialize, Debug, Clone, Builder, Partial)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[partial_derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[partial(skip_serializing_none, from, diff)]
 pub struct DeploymentConfig {
@@ -85,6 +97,13 @@ pub struct DeploymentConfig {
   #[builder(default)]
   pub skip_secret_interp: bool,
 
+  /// Inputs which can be supplied when deploying.
+  /// They are interpolated for that deploy only as `[[NAME]]`, and never persisted.
+  /// Not interpolated when `skip_secret_interp` is enabled.
+  #[serde(default)]
+  #[builder(default)]
+  pub inputs: Vec<RuntimeInput>,
+
   /// Whether to redeploy the deployment whenever the attached build finishes.
   #[serde(default)]
   #[builder(default)]
@@ -103,24 +122,156 @@ pub struct DeploymentConfig {
   #[builder(default)]
   pub auto_update: bool,
 
+  /// Other Deployments on the same Server which this Deployment depends on,
+  /// for example a shared reverse proxy. Accepts Deployment name or id.
+  ///
+  /// After one of them is redeployed, this Deployment will be restarted
+  /// (if it is running). Restarts happen in dependency order, so
+  /// Deployments further down the chain restart after this one.
+  #[serde(default, deserialize_with = "string_list_deserializer")]
+  #[partial_attr(serde(
+    default,
+    deserialize_with = "option_string_list_deserializer"
+  ))]
+  #[builder(default)]
+  pub restart_after: Vec<String>,
+
+  /// Whether to automatically stop the Deployment after a period of
+  /// inactivity (`auto_stop_after`) or on a schedule (`auto_stop_schedule`).
+  /// Useful for dev / preview environments.
+  #[serde(default)]
+  #[builder(default)]
+  pub auto_stop_enabled: bool,
+
+  /// Whether auto stop will stop or destroy the Deployment.
+  #[serde(default)]
+  #[builder(default)]
+  pub auto_stop_mode: AutoStopMode,
+
+  /// Stop the Deployment after this many minutes without any
+  /// activity. Activity is any Update on the Deployment,
+  /// such as a deploy or restart.
+  /// 0 disables inactivity based auto stop.
+  #[serde(default)]
+  #[builder(default)]
+  pub auto_stop_after: I64,
+
+  /// Choose whether to specify the auto stop schedule as regular CRON,
+  /// or using the english to CRON parser.
+  #[serde(default)]
+  #[builder(default)]
+  pub auto_stop_schedule_format: ScheduleFormat,
+
+  /// Optionally stop the Deployment at fixed times, for example
+  /// `at 2am every day`. Uses the same format as Procedure schedules.
+  #[serde(default)]
+  #[builder(default)]
+  pub auto_stop_schedule: String,
+
+  /// Optional. A TZ Identifier. If not provided, will use Core local timezone.
+  /// https://en.wikipedia.org/wiki/List_of_tz_database_time_zones.
+  #[serde(default)]
+  #[builder(default)]
+  pub auto_stop_schedule_timezone: String,
+
+  /// Send an AutoStopPending alert this many minutes
+  /// before the Deployment is auto stopped. 0 disables the alert.
+  /// Default: 15
+  #[serde(default = "default_auto_stop_warning")]
+  #[builder(default = "default_auto_stop_warning()")]
+  #[partial_default(default_auto_stop_warning())]
+  pub auto_stop_warning: I64,
+
+  /// The hours the Deployment should be up, for example
+  /// weekdays 08:00 - 19:00. The Core scheduler stops the Deployment
+  /// when a window ends, and starts it again when the next one begins.
+  /// If empty, uptime is not managed.
+  ///
+  /// Manually starting or stopping the Deployment overrides
+  /// the schedule until the next window begins or ends.
+  #[serde(default)]
+  #[builder(default)]
+  pub uptime_hours: Vec<MaintenanceWindow>,
+
   /// Whether to send ContainerStateChange alerts for this deployment.
   #[serde(default = "default_send_alerts")]
   #[builder(default = "default_send_alerts()")]
   #[partial_default(default_send_alerts())]
   pub send_alerts: bool,
 
+  /// Whether to send ContainerUnhealthy alerts when the container
+  /// healthcheck starts failing.
+  #[serde(default = "default_send_alerts")]
+  #[builder(default = "default_send_alerts()")]
+  #[partial_default(default_send_alerts())]
+  pub send_unhealthy_alerts: bool,
+
+  /// Only send ContainerStateChange alerts when the container
+  /// enters one of these states, eg. `exited`.
+  /// Empty alerts on every state change.
+  #[serde(default)]
+  #[builder(default)]
+  pub alert_states: Vec<DeploymentState>,
+
+  /// Forward the container logs from Periphery to Loki,
+  /// syslog, or an S3 bucket.
+  #[serde(default)]
+  #[builder(default)]
+  pub log_forwarding: LogForwardingConfig,
+
   /// Configure quick links that are displayed in the resource header
   #[serde(default)]
   #[builder(default)]
   pub links: Vec<String>,
 
-  /// The network attached to the container.
+  /// How the container is attached to the network.
+  /// Default is `Custom`, which attaches to `network`.
+  #[serde(default)]
+  #[builder(default)]
+  pub network_mode: DeploymentNetworkMode,
+
+  /// The network attached to the container,
+  /// when `network_mode` is `Custom` or `Macvlan`.
+  /// Must exist on the Server before deploying.
   /// Default is `host`.
   #[serde(default = "default_network")]
   #[builder(default = "default_network()")]
   #[partial_default(default_network())]
   pub network: String,
 
+  /// A static IP address to give the container on the network.
+  /// Only available when `network_mode` is `Custom` or `Macvlan`.
+  /// Empty lets docker assign the address.
+  #[serde(default)]
+  #[builder(default)]
+  pub ip_address: String,
+
+  /// A static MAC address to give the container on the network.
+  /// Only available when `network_mode` is `Custom` or `Macvlan`.
+  /// Empty lets docker assign the address.
+  #[serde(default)]
+  #[builder(default)]
+  pub mac_address: String,
+
+  /// DNS servers used by the container, eg `10.0.0.53`.
+  /// If empty, uses any matching Core `container_dns_defaults`.
+  #[serde(default, deserialize_with = "string_list_deserializer")]
+  #[partial_attr(serde(
+    default,
+    deserialize_with = "option_string_list_deserializer"
+  ))]
+  #[builder(default)]
+  pub dns: Vec<String>,
+
+  /// Extra `hostname:ip` entries added to the container `/etc/hosts`.
+  #[serde(default, deserialize_with = "string_list_deserializer")]
+  #[partial_attr(serde(
+    default,
+    deserialize_with = "option_string_list_deserializer"
+  ))]
+  #[builder(default)]
+  pub extra_hosts: Vec<String>,
+
   /// The restart mode given to the container.
   #[serde(default)]
   #[builder(default)]
@@ -214,12 +365,26 @@ impl DeploymentConfig {
     environment_vars_from_str(&self.environment)
       .context("Invalid environment")
   }
+
+  /// The network passed to `docker run --network`.
+  pub fn network_name(&self) -> &str {
+    match self.network_mode {
+      DeploymentNetworkMode::Bridge => "bridge",
+      DeploymentNetworkMode::Host => "host",
+      DeploymentNetworkMode::Custom
+      | DeploymentNetworkMode::Macvlan => &self.network,
+    }
+  }
 }
 
 fn default_send_alerts() -> bool {
   true
 }
 
+fn default_auto_stop_warning() -> i64 {
+  15
+}
+
 fn default_termination_timeout() -> i32 {
   10
 }
@@ -233,13 +398,26 @@ impl Default for DeploymentConfig {
     Self {
       server_id: Default::default(),
       send_alerts: default_send_alerts(),
+      send_unhealthy_alerts: default_send_alerts(),
+      alert_states: Default::default(),
+      log_forwarding: Default::default(),
       links: Default::default(),
       image: Default::default(),
       image_registry_account: Default::default(),
       skip_secret_interp: Default::default(),
+      inputs: Default::default(),
       redeploy_on_build: Default::default(),
       poll_for_updates: Default::default(),
       auto_update: Default::default(),
+      restart_after: Default::default(),
+      auto_stop_enabled: Default::default(),
+      auto_stop_mode: Default::default(),
+      auto_stop_after: Default::default(),
+      auto_stop_schedule_format: Default::default(),
+      auto_stop_schedule: Default::default(),
+      auto_stop_schedule_timezone: Default::default(),
+      auto_stop_warning: default_auto_stop_warning(),
+      uptime_hours: Default::default(),
       term_signal_labels: Default::default(),
       termination_signal: Default::default(),
       termination_timeout: default_termination_timeout(),
@@ -247,7 +425,12 @@ impl Default for DeploymentConfig {
       volumes: Default::default(),
       environment: Default::default(),
       labels: Default::default(),
+      network_mode: Default::default(),
       network: default_network(),
+      ip_address: Default::default(),
+      mac_address: Default::default(),
+      dns: Default::default(),
+      extra_hosts: Default::default(),
       restart: Default::default(),
       command: Default::default(),
       extra_args: Default::default(),
@@ -259,6 +442,7 @@ impl Default for DeploymentConfig {
 #[derive(
   Serialize, Deserialize, Debug, Clone, PartialEq, EnumVariants,
 )]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[variant_derive(
   Serialize,
   Deserialize,
@@ -343,6 +527,7 @@ pub fn conversions_from_str(
   Serialize,
   Deserialize,
 )]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 #[strum(serialize_all = "snake_case")]
 pub enum DeploymentState {
@@ -350,6 +535,8 @@ pub enum DeploymentState {
   Deploying,
   /// Container is running
   Running,
+  /// Container is running, but its healthcheck is failing
+  Unhealthy,
   /// Container is created but not running
   Created,
   /// Container is in restart loop
@@ -386,6 +573,58 @@ impl From<ContainerStateStatusEnum> for DeploymentState {
   }
 }
 
+impl From<&ContainerListItem> for DeploymentState {
+  fn from(container: &ContainerListItem) -> Self {
+    match (container.state, container.health) {
+      (
+        ContainerStateStatusEnum::Running,
+        Some(HealthStatusEnum::Unhealthy),
+      ) => DeploymentState::Unhealthy,
+      (state, _) => state.into(),
+    }
+  }
+}
+
+impl DeploymentState {
+  /// Whether the container is running, regardless of its healthcheck.
+  pub fn is_running(&self) -> bool {
+    matches!(
+      self,
+      DeploymentState::Running | DeploymentState::Unhealthy
+    )
+  }
+}
+
+/// How a Deployment container is attached to the network.
+#[typeshare]
+#[derive(
+  Serialize,
+  Deserialize,
+  Debug,
+  PartialEq,
+  Hash,
+  Eq,
+  Clone,
+  Copy,
+  Default,
+  Display,
+  EnumString,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum DeploymentNetworkMode {
+  /// Attach to the docker network given in `network`.
+  #[default]
+  Custom,
+  /// Attach to the default docker `bridge` network.
+  Bridge,
+  /// Share the host's network stack. Ports are not published.
+  Host,
+  /// Attach to the macvlan network given in `network`,
+  /// giving the container its own address on the parent interface's LAN.
+  /// The network and its parent interface must exist on the Server.
+  Macvlan,
+}
+
 #[typeshare]
 #[derive(
   Serialize,
@@ -400,6 +639,7 @@ impl From<ContainerStateStatusEnum> for DeploymentState {
   Display,
   EnumString,
 )]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum RestartMode {
   #[default]
   #[serde(rename = "no")]
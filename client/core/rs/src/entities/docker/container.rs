@@ -43,12 +43,19 @@ pub struct ContainerListItem {
   /// Additional human-readable status of this container (e.g. `Exit 0`)
   #[serde(skip_serializing_if = "Option::is_none")]
   pub status: Option<String>,
+  /// The healthcheck status, if the container has a healthcheck configured.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub health: Option<HealthStatusEnum>,
   /// The network mode
   #[serde(skip_serializing_if = "Option::is_none")]
   pub network_mode: Option<String>,
   /// The network names attached to container
   #[serde(default, skip_serializing_if = "Vec::is_empty")]
   pub networks: Vec<String>,
+  /// The IPv4 and global IPv6 addresses of the container
+  /// on its attached networks.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub ip_addresses: Vec<String>,
   /// Port mappings for the container
   #[serde(default, skip_serializing_if = "Vec::is_empty")]
   pub ports: Vec<Port>,
@@ -355,6 +362,23 @@ pub enum HealthStatusEnum {
   Unhealthy,
 }
 
+impl HealthStatusEnum {
+  /// Parses the health from the container list status,
+  /// eg. `Up 5 minutes (unhealthy)`.
+  /// Returns None if the container has no healthcheck.
+  pub fn from_container_status(status: &str) -> Option<Self> {
+    if status.ends_with("(healthy)") {
+      Some(HealthStatusEnum::Healthy)
+    } else if status.ends_with("(unhealthy)") {
+      Some(HealthStatusEnum::Unhealthy)
+    } else if status.ends_with("(health: starting)") {
+      Some(HealthStatusEnum::Starting)
+    } else {
+      None
+    }
+  }
+}
+
 /// HealthcheckResult stores information about a single run of a healthcheck probe
 #[typeshare]
 #[derive(
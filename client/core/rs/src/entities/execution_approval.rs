@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+use strum::Display;
+use typeshare::typeshare;
+
+use super::{I64, MongoId};
+
+/// A Procedure run paused at a stage which requires approval.
+/// The run continues once a user approves it,
+/// and fails the stage if it is rejected or expires.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(
+  feature = "mongo",
+  derive(mongo_indexed::derive::MongoIndexed)
+)]
+#[cfg_attr(feature = "mongo", doc_index({ "procedure_id": 1, "status": 1 }))]
+pub struct ExecutionApproval {
+  /// The Mongo ID of the approval.
+  /// This field is de/serialized from/to JSON as
+  /// `{ "_id": { "$oid": "..." }, ...(rest of serialized ExecutionApproval) }`
+  #[serde(
+    default,
+    rename = "_id",
+    skip_serializing_if = "String::is_empty",
+    with = "bson::serde_helpers::hex_string_as_object_id"
+  )]
+  pub id: MongoId,
+
+  /// The id of the Procedure waiting for approval.
+  pub procedure_id: String,
+
+  /// The name of the Procedure waiting for approval.
+  #[serde(default)]
+  pub procedure_name: String,
+
+  /// The name of the stage requiring approval.
+  #[serde(default)]
+  pub stage: String,
+
+  /// The id of the Update recording the Procedure run.
+  #[serde(default)]
+  pub update_id: String,
+
+  /// The message shown to approvers.
+  #[serde(default)]
+  pub message: String,
+
+  /// The status of the approval.
+  #[serde(default)]
+  pub status: ExecutionApprovalStatus,
+
+  /// The id of the user who ran the Procedure.
+  #[serde(default)]
+  pub requested_by: String,
+
+  /// When the Procedure reached the stage.
+  #[serde(default)]
+  pub requested_at: I64,
+
+  /// If not approved by this timestamp, the approval expires
+  /// and the stage fails. 0 never expires.
+  #[serde(default)]
+  pub expires_at: I64,
+
+  /// Whether the user who ran the Procedure can't approve it.
+  #[serde(default)]
+  pub forbid_self_approval: bool,
+
+  /// Only these users (by username or id) can approve.
+  /// Empty allows any user with Write permission on the Procedure.
+  #[serde(default)]
+  pub approvers: Vec<String>,
+
+  /// The id of the user who approved or rejected the run.
+  #[serde(default)]
+  pub resolved_by: String,
+
+  /// When the run was approved / rejected / expired.
+  #[serde(default)]
+  pub resolved_at: I64,
+
+  /// An optional comment given with the approval or rejection.
+  #[serde(default)]
+  pub comment: String,
+}
+
+#[typeshare]
+#[derive(
+  Serialize,
+  Deserialize,
+  Debug,
+  Clone,
+  Copy,
+  Default,
+  PartialEq,
+  Eq,
+  Hash,
+  Display,
+)]
+pub enum ExecutionApprovalStatus {
+  /// Waiting for a user to approve or reject the run.
+  #[default]
+  Pending,
+  /// The run was approved and continues.
+  Approved,
+  /// The run was rejected, failing the stage.
+  Rejected,
+  /// The run wasn't approved in time, failing the stage.
+  Expired,
+  /// The run stopped before it was approved,
+  /// for example because Core restarted.
+  Cancelled,
+}
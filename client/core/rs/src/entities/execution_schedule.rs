@@ -0,0 +1,182 @@
+use serde::{Deserialize, Serialize};
+use strum::Display;
+use typeshare::typeshare;
+
+use crate::api::execute::Execution;
+
+use super::{I64, MongoId, ResourceTarget, ScheduleFormat};
+
+/// Runs any execution on a schedule, such as `Deploy`,
+/// `RunBuild` or `DeployStack`, without wrapping it in a Procedure.
+/// Batch executions can be scheduled too, for recurring operations
+/// on all resources matching a pattern.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(
+  feature = "mongo",
+  derive(mongo_indexed::derive::MongoIndexed)
+)]
+pub struct ExecutionSchedule {
+  /// The Mongo ID of the execution schedule.
+  /// This field is de/serialized from/to JSON as
+  /// `{ "_id": { "$oid": "..." }, ...(rest of serialized ExecutionSchedule) }`
+  #[serde(
+    default,
+    rename = "_id",
+    skip_serializing_if = "String::is_empty",
+    with = "bson::serde_helpers::hex_string_as_object_id"
+  )]
+  pub id: MongoId,
+
+  /// The name of the schedule.
+  #[cfg_attr(feature = "mongo", unique_index)]
+  pub name: String,
+
+  /// A description of the schedule.
+  #[serde(default)]
+  pub description: String,
+
+  /// The execution to run.
+  pub execution: Execution,
+
+  /// Whether the schedule is enabled.
+  #[serde(default)]
+  pub enabled: bool,
+
+  /// Choose whether to specify schedule as regular CRON, or using the english to CRON parser.
+  #[serde(default)]
+  pub schedule_format: ScheduleFormat,
+
+  /// The schedule for the execution.
+  ///
+  /// - CRON: `0 0 3 * * *` (seconds are required)
+  /// - English: `Every day at 3am`
+  #[serde(default)]
+  pub schedule: String,
+
+  /// Optionally provide a timezone for the schedule.
+  /// If empty, will use the Core timezone.
+  #[serde(default)]
+  pub schedule_timezone: String,
+
+  /// What to do with runs missed while Core was down.
+  #[serde(default)]
+  pub missed_runs: MissedRunPolicy,
+
+  /// The id of the user who created the schedule.
+  /// The execution runs as this user.
+  #[serde(default)]
+  pub created_by: String,
+
+  /// The next scheduled run in unix ms. 0 if not scheduled.
+  /// Stored so runs missed while Core was down can be detected.
+  #[serde(default)]
+  pub next_run_at: I64,
+
+  /// If there is an error parsing the schedule,
+  /// it will be given here.
+  #[serde(default)]
+  pub schedule_error: String,
+
+  /// The last run in unix ms.
+  #[serde(default)]
+  pub last_run_at: I64,
+
+  /// The id of the Update recording the last run.
+  #[serde(default)]
+  pub last_update_id: String,
+
+  /// An error if the last run couldn't start,
+  /// such as the user lacking permission. Empty if none.
+  #[serde(default)]
+  pub last_run_error: String,
+}
+
+/// A run of an [ExecutionSchedule], kept as its run history.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(
+  feature = "mongo",
+  derive(mongo_indexed::derive::MongoIndexed)
+)]
+#[cfg_attr(feature = "mongo", doc_index({ "schedule_id": 1, "run_at": -1 }))]
+pub struct ExecutionScheduleRun {
+  /// The Mongo ID of the run.
+  #[serde(
+    default,
+    rename = "_id",
+    skip_serializing_if = "String::is_empty",
+    with = "bson::serde_helpers::hex_string_as_object_id"
+  )]
+  pub id: MongoId,
+
+  /// The id of the schedule which ran.
+  pub schedule_id: String,
+
+  /// When the run started in unix ms.
+  #[serde(default)]
+  pub run_at: I64,
+
+  /// When the run finished in unix ms.
+  #[serde(default)]
+  pub finished_at: I64,
+
+  /// The id of the Update recording the run.
+  /// Empty if the run couldn't start.
+  #[serde(default)]
+  pub update_id: String,
+
+  /// Whether the execution was successful.
+  #[serde(default)]
+  pub success: bool,
+
+  /// An error if the run couldn't start. Empty if none.
+  #[serde(default)]
+  pub error: String,
+}
+
+/// The next scheduled run of a Procedure, Action or Build,
+/// stored so runs missed while Core was down can be detected.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(
+  feature = "mongo",
+  derive(mongo_indexed::derive::MongoIndexed)
+)]
+pub struct ResourceSchedule {
+  /// The scheduled resource, eg `Procedure:<id>`.
+  #[serde(rename = "_id")]
+  pub id: String,
+
+  /// The scheduled resource.
+  pub target: ResourceTarget,
+
+  /// The next scheduled run in unix ms. 0 if not scheduled,
+  /// or the scheduled run has started.
+  #[serde(default)]
+  pub next_run_at: I64,
+}
+
+/// What to do with scheduled runs missed while Core was down.
+/// An alert is sent for each missed run either way.
+#[typeshare]
+#[derive(
+  Serialize,
+  Deserialize,
+  Debug,
+  Clone,
+  Copy,
+  Default,
+  PartialEq,
+  Eq,
+  Hash,
+  Display,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum MissedRunPolicy {
+  /// Skip the missed runs, and wait for the next scheduled run.
+  #[default]
+  Skip,
+  /// Run once when Core starts, no matter how many runs were missed.
+  RunOnce,
+}
@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+use strum::Display;
+use typeshare::typeshare;
+
+use super::{I64, MaintenanceWindow, MongoId};
+
+/// The health of a resource managed outside of Komodo,
+/// such as a Kubernetes operator or a backup script.
+/// The status is reported using `PushExternalStatus`.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(
+  feature = "mongo",
+  derive(mongo_indexed::derive::MongoIndexed)
+)]
+pub struct ExternalStatus {
+  /// The Mongo ID of the external status.
+  /// This field is de/serialized from/to JSON as
+  /// `{ "_id": { "$oid": "..." }, ...(rest of serialized ExternalStatus) }`
+  #[serde(
+    default,
+    rename = "_id",
+    skip_serializing_if = "String::is_empty",
+    with = "bson::serde_helpers::hex_string_as_object_id"
+  )]
+  pub id: MongoId,
+
+  /// The name of the external resource.
+  #[cfg_attr(feature = "mongo", unique_index)]
+  pub name: String,
+
+  /// A description of the external resource.
+  #[serde(default)]
+  pub description: String,
+
+  /// The last reported state.
+  #[serde(default)]
+  pub state: ExternalState,
+
+  /// The last reported message. May be empty string.
+  #[serde(default)]
+  pub message: String,
+
+  /// The timestamp of the last report.
+  #[serde(default)]
+  pub ts: I64,
+
+  /// The id of the user which made the last report.
+  #[serde(default)]
+  pub pushed_by: String,
+
+  /// If no report is received for this many seconds,
+  /// the state becomes Unknown. 0 disables.
+  #[serde(default)]
+  pub stale_after: I64,
+
+  /// Whether to send alerts when the state is not Ok.
+  #[serde(default)]
+  pub send_alerts: bool,
+
+  /// Alerts are not sent during maintenance windows.
+  #[serde(default)]
+  pub maintenance_windows: Vec<MaintenanceWindow>,
+
+  /// Quick links to the external resource.
+  #[serde(default)]
+  pub links: Vec<String>,
+}
+
+impl ExternalStatus {
+  /// The reported state, or Unknown if the report is stale.
+  pub fn current_state(&self, ts: I64) -> ExternalState {
+    if self.stale_after > 0 && ts - self.ts > self.stale_after * 1000
+    {
+      ExternalState::Unknown
+    } else {
+      self.state
+    }
+  }
+}
+
+#[typeshare]
+#[derive(
+  Serialize,
+  Deserialize,
+  Debug,
+  Clone,
+  Copy,
+  Default,
+  PartialEq,
+  Eq,
+  Hash,
+  Display,
+)]
+pub enum ExternalState {
+  /// No report has been received, or the last report is stale.
+  #[default]
+  Unknown,
+  /// The resource is healthy.
+  Ok,
+  /// The resource is degraded.
+  Warning,
+  /// The resource is down.
+  Critical,
+}
@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use super::{
+  I64, MongoId, ResourceTargetVariant, alert::Alert, resource_link,
+};
+
+/// Another Komodo Core registered with this one,
+/// so its resources and alerts show in the federated view.
+/// Peers are only read from, their databases stay separate.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(
+  feature = "mongo",
+  derive(mongo_indexed::derive::MongoIndexed)
+)]
+pub struct FederationPeer {
+  /// The Mongo ID of the peer.
+  /// This field is de/serialized from/to JSON as
+  /// `{ "_id": { "$oid": "..." }, ...(rest of serialized FederationPeer) }`
+  #[serde(
+    default,
+    rename = "_id",
+    skip_serializing_if = "String::is_empty",
+    with = "bson::serde_helpers::hex_string_as_object_id"
+  )]
+  pub id: MongoId,
+
+  /// The name of the peer, eg. the region it manages.
+  #[cfg_attr(feature = "mongo", unique_index)]
+  pub name: String,
+
+  /// A description of the peer.
+  #[serde(default)]
+  pub description: String,
+
+  /// The address of the peer Core, eg. `https://komodo.eu.example.com`.
+  /// Also used for the deep links into the peer.
+  pub address: String,
+
+  /// The api key used to read from the peer.
+  /// Use a key of a service user with read permissions on the peer.
+  pub api_key: String,
+
+  /// The api secret used to read from the peer.
+  /// Never returned by the API.
+  #[serde(default)]
+  pub api_secret: String,
+
+  /// Whether the peer is included in the federated view.
+  #[serde(default)]
+  pub enabled: bool,
+}
+
+/// The latest state of a [FederationPeer], as read by this Core.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct FederationPeerStatus {
+  /// The peer id.
+  pub id: String,
+  /// The peer name.
+  pub name: String,
+  /// The peer address.
+  pub address: String,
+  /// Whether the peer is included in the federated view.
+  pub enabled: bool,
+  /// The Core version of the peer. Empty if it hasn't been reached.
+  pub version: String,
+  /// When the peer was last read successfully in unix ms.
+  /// 0 if it hasn't been reached.
+  pub refreshed_at: I64,
+  /// The error reading from the peer. Empty if none.
+  pub error: String,
+}
+
+/// A resource on a [FederationPeer].
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FederatedResource {
+  /// The name of the peer the resource is on.
+  pub peer: String,
+  /// The resource type.
+  #[serde(rename = "type")]
+  pub resource_type: ResourceTargetVariant,
+  /// The resource id on the peer.
+  pub id: String,
+  /// The resource name.
+  pub name: String,
+  /// The resource state, eg `Ok` for Servers or `running` for Stacks.
+  pub state: String,
+  /// Link to the resource on the peer.
+  pub link: String,
+}
+
+impl FederatedResource {
+  pub fn new(
+    peer: &FederationPeer,
+    resource_type: ResourceTargetVariant,
+    id: String,
+    name: String,
+    state: String,
+  ) -> FederatedResource {
+    let link = resource_link(
+      peer.address.trim_end_matches('/'),
+      resource_type,
+      &id,
+    );
+    FederatedResource {
+      peer: peer.name.clone(),
+      resource_type,
+      id,
+      name,
+      state,
+      link,
+    }
+  }
+}
+
+/// An open alert on a [FederationPeer].
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FederatedAlert {
+  /// The name of the peer the alert is on.
+  pub peer: String,
+  /// The alert, as given by the peer.
+  pub alert: Alert,
+  /// Link to the alert target on the peer,
+  /// or the peer alerts page for System alerts.
+  pub link: String,
+}
+
+impl FederatedAlert {
+  pub fn new(peer: &FederationPeer, alert: Alert) -> FederatedAlert {
+    let address = peer.address.trim_end_matches('/');
+    let (resource_type, id) = alert.target.extract_variant_id();
+    let link = match resource_type {
+      ResourceTargetVariant::System => format!("{address}/alerts"),
+      _ => resource_link(address, resource_type, id),
+    };
+    FederatedAlert {
+      peer: peer.name.clone(),
+      alert,
+      link,
+    }
+  }
+}
@@ -21,6 +21,8 @@ use crate::{
   parsers::parse_key_value_list,
 };
 
+/// Subtypes of [AccessRequest][access_request::AccessRequest].
+pub mod access_request;
 /// Subtypes of [Action][action::Action].
 pub mod action;
 /// Subtypes of [Alert][alert::Alert].
@@ -29,6 +31,8 @@ pub mod alert;
 pub mod alerter;
 /// Subtypes of [ApiKey][api_key::ApiKey].
 pub mod api_key;
+/// Subtypes of [AuditLogEntry][audit::AuditLogEntry].
+pub mod audit;
 /// Subtypes of [Build][build::Build].
 pub mod build;
 /// Subtypes of [Builder][builder::Builder].
@@ -39,26 +43,50 @@ pub mod config;
 pub mod deployment;
 /// Networks, Images, Containers.
 pub mod docker;
+/// Subtypes of [ExecutionApproval][execution_approval::ExecutionApproval].
+pub mod execution_approval;
+/// Subtypes of [ExecutionSchedule][execution_schedule::ExecutionSchedule].
+pub mod execution_schedule;
+/// Subtypes of [ExternalStatus][external_status::ExternalStatus].
+pub mod external_status;
+/// Subtypes of [FederationPeer][federation::FederationPeer].
+pub mod federation;
+/// Subtypes of [LogForwardingConfig][log_forwarding::LogForwardingConfig].
+pub mod log_forwarding;
 /// Subtypes of [LogConfig][logger::LogConfig].
 pub mod logger;
 /// Subtypes of [Permission][permission::Permission].
 pub mod permission;
+/// Subtypes of [ExecutionPlan][plan::ExecutionPlan].
+pub mod plan;
 /// Subtypes of [Procedure][procedure::Procedure].
 pub mod procedure;
 /// Subtypes of [GitProviderAccount][provider::GitProviderAccount] and [DockerRegistryAccount][provider::DockerRegistryAccount]
 pub mod provider;
+/// Subtypes of [QueuedExecution][queued_execution::QueuedExecution].
+pub mod queued_execution;
 /// Subtypes of [Repo][repo::Repo].
 pub mod repo;
 /// Subtypes of [Resource][resource::Resource].
 pub mod resource;
+/// Subtypes of [ResourceLock][resource_lock::ResourceLock].
+pub mod resource_lock;
 /// Subtypes of [Schedule][schedule::Schedule]
 pub mod schedule;
 /// Subtypes of [Server][server::Server].
 pub mod server;
+/// Subtypes of [ServerProfile][server_profile::ServerProfile].
+pub mod server_profile;
+/// Subtypes of [AlertSilence][silence::AlertSilence].
+pub mod silence;
+/// Subtypes of [Snapshot][snapshot::Snapshot].
+pub mod snapshot;
 /// Subtypes of [Stack][stack::Stack]
 pub mod stack;
 /// Subtypes for server stats reporting.
 pub mod stats;
+/// Subtypes of [SwarmService][swarm::SwarmService]
+pub mod swarm;
 /// Subtypes of [ResourceSync][sync::ResourceSync]
 pub mod sync;
 /// Subtypes of [Tag][tag::Tag].
@@ -145,6 +173,7 @@ pub fn to_container_compatible_name(name: &str) -> String {
 /// Enforce common docker naming rules, such as only lowercase, and no '.'.
 /// These apply to:
 ///   - Stacks (docker project name)
+///   - Swarm Services (docker stack name)
 ///   - Builds (docker image name)
 ///   - Networks
 ///   - Volumes
@@ -179,6 +208,7 @@ pub struct __Serror {
 #[derive(
   Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq,
 )]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SystemCommand {
   #[serde(default)]
   pub path: String,
@@ -206,6 +236,7 @@ impl SystemCommand {
 
 #[typeshare]
 #[derive(Serialize, Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Version {
   pub major: i32,
   pub minor: i32,
@@ -362,9 +393,34 @@ pub struct FileContents {
   pub contents: String,
 }
 
+/// A runtime input declared by a Deployment or Procedure.
+/// Values are supplied with the execution request, interpolated
+/// as `[[NAME]]` for that run only, and never persisted.
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, PartialEq,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RuntimeInput {
+  /// The name used to reference the input, eg `[[MIGRATE]]`.
+  pub name: String,
+  /// Describe what the input is used for.
+  #[serde(default)]
+  pub description: String,
+  /// A default value used when the input is not supplied.
+  /// If not provided, the input is required.
+  #[serde(default)]
+  pub default: Option<String>,
+  /// Treat the value as a secret.
+  /// It will be sanitized from logs, same as Secret Variables.
+  #[serde(default)]
+  pub secret: bool,
+}
+
 /// Represents a scheduled maintenance window
 #[typeshare]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct MaintenanceWindow {
   /// Name for the maintenance window (required)
   pub name: String,
@@ -770,6 +826,7 @@ pub enum DayOfWeek {
   Serialize,
   Deserialize,
 )]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum MaintenanceScheduleType {
   /// Daily at the specified time
   #[default]
@@ -1036,6 +1093,7 @@ pub enum Operation {
   PruneDockerBuilders,
   PruneBuildx,
   PruneSystem,
+  RunScheduledCommand,
 
   // stack
   CreateStack,
@@ -1044,6 +1102,7 @@ pub enum Operation {
   DeleteStack,
   WriteStackContents,
   RefreshStackCache,
+  TakeStackOwnership,
   PullStack,
   DeployStack,
   StartStack,
@@ -1053,6 +1112,7 @@ pub enum Operation {
   StopStack,
   DestroyStack,
   RunStackService,
+  ScaleStackService,
 
   // stack (service)
   DeployStackService,
@@ -1078,6 +1138,15 @@ pub enum Operation {
   StopDeployment,
   DestroyDeployment,
 
+  // swarm service
+  CreateSwarmService,
+  UpdateSwarmService,
+  RenameSwarmService,
+  DeleteSwarmService,
+  DeploySwarmService,
+  ScaleSwarmService,
+  RemoveSwarmService,
+
   // build
   CreateBuild,
   UpdateBuild,
@@ -1094,6 +1163,7 @@ pub enum Operation {
   DeleteRepo,
   CloneRepo,
   PullRepo,
+  RunRepoPipeline,
   BuildRepo,
   CancelRepoBuild,
 
@@ -1111,6 +1181,20 @@ pub enum Operation {
   DeleteAction,
   RunAction,
 
+  // batch
+  BatchDeployStack,
+  BatchDeployStackIfChanged,
+  BatchPullStack,
+  BatchDestroyStack,
+  BatchDeploy,
+  BatchDestroyDeployment,
+  BatchRunBuild,
+  BatchCloneRepo,
+  BatchPullRepo,
+  BatchBuildRepo,
+  BatchRunProcedure,
+  BatchRunAction,
+
   // builder
   CreateBuilder,
   UpdateBuilder,
@@ -1134,6 +1218,9 @@ pub enum Operation {
   CommitSync,
   RunSync,
 
+  // plan
+  Plan,
+
   // maintenance
   ClearRepoCache,
   BackupCoreDatabase,
@@ -1144,6 +1231,49 @@ pub enum Operation {
   UpdateVariableValue,
   DeleteVariable,
 
+  // snapshot
+  CreateSnapshot,
+  DeleteSnapshot,
+
+  // silence
+  CreateSilence,
+  DeleteSilence,
+
+  // server profile
+  CreateServerProfile,
+  UpdateServerProfile,
+  DeleteServerProfile,
+
+  // external status
+  DeleteExternalStatus,
+
+  // execution queue
+  EnqueueExecution,
+  CancelQueuedExecution,
+
+  // execution approval
+  ApproveExecution,
+  RejectExecution,
+
+  // access request
+  RequestAccess,
+  ApproveAccessRequest,
+  RejectAccessRequest,
+  ExpireAccess,
+
+  // update
+  CancelUpdate,
+
+  // execution schedule
+  CreateExecutionSchedule,
+  UpdateExecutionSchedule,
+  DeleteExecutionSchedule,
+
+  // federation
+  CreateFederationPeer,
+  UpdateFederationPeer,
+  DeleteFederationPeer,
+
   // git provider
   CreateGitProviderAccount,
   UpdateGitProviderAccount,
@@ -1153,6 +1283,9 @@ pub enum Operation {
   CreateDockerRegistryAccount,
   UpdateDockerRegistryAccount,
   DeleteDockerRegistryAccount,
+
+  // resource protection
+  SetResourceProtected,
 }
 
 #[typeshare]
@@ -1189,6 +1322,7 @@ pub enum SearchCombinator {
   Display,
   EnumString,
 )]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "UPPERCASE")]
 #[strum(serialize_all = "UPPERCASE")]
 pub enum TerminationSignal {
@@ -1215,6 +1349,7 @@ pub enum TerminationSignal {
   Deserialize,
   EnumVariants,
 )]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[variant_derive(
   Debug,
   Clone,
@@ -1236,6 +1371,7 @@ pub enum ResourceTarget {
   Server(String),
   Stack(String),
   Deployment(String),
+  SwarmService(String),
   Build(String),
   Repo(String),
   Procedure(String),
@@ -1264,6 +1400,7 @@ impl ResourceTarget {
       ResourceTarget::Server(id) => id.is_empty(),
       ResourceTarget::Stack(id) => id.is_empty(),
       ResourceTarget::Deployment(id) => id.is_empty(),
+      ResourceTarget::SwarmService(id) => id.is_empty(),
       ResourceTarget::Build(id) => id.is_empty(),
       ResourceTarget::Repo(id) => id.is_empty(),
       ResourceTarget::Procedure(id) => id.is_empty(),
@@ -1284,6 +1421,7 @@ impl ResourceTarget {
       ResourceTarget::Build(id) => id,
       ResourceTarget::Builder(id) => id,
       ResourceTarget::Deployment(id) => id,
+      ResourceTarget::SwarmService(id) => id,
       ResourceTarget::Repo(id) => id,
       ResourceTarget::Alerter(id) => id,
       ResourceTarget::Procedure(id) => id,
@@ -1306,6 +1444,12 @@ impl From<&deployment::Deployment> for ResourceTarget {
   }
 }
 
+impl From<&swarm::SwarmService> for ResourceTarget {
+  fn from(swarm_service: &swarm::SwarmService) -> Self {
+    Self::SwarmService(swarm_service.id.clone())
+  }
+}
+
 impl From<&server::Server> for ResourceTarget {
   fn from(server: &server::Server) -> Self {
     Self::Server(server.id.clone())
@@ -1362,6 +1506,7 @@ impl ResourceTargetVariant {
       ResourceTargetVariant::Build => "build",
       ResourceTargetVariant::Builder => "builder",
       ResourceTargetVariant::Deployment => "deployment",
+      ResourceTargetVariant::SwarmService => "swarm_service",
       ResourceTargetVariant::Server => "server",
       ResourceTargetVariant::Repo => "repo",
       ResourceTargetVariant::Alerter => "alerter",
@@ -1377,16 +1522,40 @@ impl ResourceTargetVariant {
 #[derive(
   Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize,
 )]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum ScheduleFormat {
   #[default]
   English,
   Cron,
 }
 
+/// What happens to a Deployment or Stack when it is auto stopped.
+#[typeshare]
+#[derive(
+  Debug,
+  Clone,
+  Copy,
+  PartialEq,
+  Eq,
+  Default,
+  Display,
+  Serialize,
+  Deserialize,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum AutoStopMode {
+  /// Stop the containers, they can be started again.
+  #[default]
+  Stop,
+  /// Destroy the containers.
+  Destroy,
+}
+
 #[typeshare]
 #[derive(
   Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize,
 )]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum FileFormat {
   #[default]
@@ -1416,6 +1585,9 @@ pub fn resource_link(
     ResourceTargetVariant::Stack => {
       format!("/stacks/{id}")
     }
+    ResourceTargetVariant::SwarmService => {
+      format!("/swarm-services/{id}")
+    }
     ResourceTargetVariant::Server => {
       format!("/servers/{id}")
     }
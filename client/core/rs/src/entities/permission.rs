@@ -8,7 +8,7 @@ use strum::{
 };
 use typeshare::typeshare;
 
-use super::{MongoId, ResourceTarget};
+use super::{I64, MongoId, ResourceTarget};
 
 /// Representation of a User or UserGroups permission on a resource.
 #[typeshare]
@@ -47,6 +47,10 @@ pub struct Permission {
   /// Any specific permissions for the [user_target] on the [resource_target].
   #[serde(default)]
   pub specific: IndexSet<SpecificPermission>,
+  /// Unix timestamp in milliseconds the permission expires,
+  /// after which it is revoked. 0 never expires.
+  #[serde(default)]
+  pub expires_at: I64,
 }
 
 #[typeshare]
@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use super::sync::{DiffData, ResourceDiff, SyncDeployUpdate};
+
+/// The changes an execution would make,
+/// computed by [Plan][crate::api::execute::Plan]
+/// without applying anything.
+#[typeshare]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum ExecutionPlan {
+  RunSync(SyncPlan),
+  DeployStack(StackPlan),
+}
+
+impl ExecutionPlan {
+  pub fn has_changes(&self) -> bool {
+    match self {
+      ExecutionPlan::RunSync(plan) => {
+        !plan.resource_updates.is_empty()
+          || plan.deploy_updates.to_deploy > 0
+          || !plan.variable_updates.is_empty()
+          || !plan.user_group_updates.is_empty()
+      }
+      ExecutionPlan::DeployStack(plan) => {
+        !plan.files.is_empty() || !plan.services.is_empty()
+      }
+    }
+  }
+}
+
+/// The changes `RunSync` would make.
+#[typeshare]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncPlan {
+  /// Resources to create, update, or delete.
+  #[serde(default)]
+  pub resource_updates: Vec<ResourceDiff>,
+  /// Deployments and Stacks to deploy.
+  #[serde(default)]
+  pub deploy_updates: SyncDeployUpdate,
+  /// Variables to create, update, or delete.
+  #[serde(default)]
+  pub variable_updates: Vec<DiffData>,
+  /// User groups to create, update, or delete.
+  #[serde(default)]
+  pub user_group_updates: Vec<DiffData>,
+}
+
+/// The changes `DeployStack` would make,
+/// compared to the last successful deploy.
+#[typeshare]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StackPlan {
+  /// Whether the existing containers would be taken down
+  /// before deploying, due to `destroy_before_deploy`
+  /// or a changed compose project name.
+  #[serde(default)]
+  pub destroy_first: bool,
+  /// Compose and additional files which changed.
+  #[serde(default)]
+  pub files: Vec<StackFileDiff>,
+  /// Compose services which changed.
+  /// The service definitions are given as YAML.
+  #[serde(default)]
+  pub services: Vec<StackServiceDiff>,
+}
+
+/// A changed file in a [StackPlan].
+#[typeshare]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StackFileDiff {
+  /// The path to the file
+  pub path: String,
+  /// The file contents before and after
+  pub data: DiffData,
+}
+
+/// A changed compose service in a [StackPlan].
+#[typeshare]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StackServiceDiff {
+  /// The service name
+  pub service: String,
+  /// The service definition before and after
+  pub data: DiffData,
+}
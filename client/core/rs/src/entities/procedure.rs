@@ -9,8 +9,10 @@ use typeshare::typeshare;
 use crate::api::execute::Execution;
 
 use super::{
-  I64, ScheduleFormat,
+  I64, RuntimeInput, ScheduleFormat,
+  execution_schedule::MissedRunPolicy,
   resource::{Resource, ResourceListItem, ResourceQuery},
+  update::ExecutionMetrics,
 };
 
 #[typeshare]
@@ -31,6 +33,9 @@ pub struct ProcedureListItemInfo {
   /// If there is an error parsing schedule expression,
   /// it will be given here.
   pub schedule_error: Option<String>,
+  /// Run metrics over the last 30 days.
+  #[serde(default)]
+  pub metrics: ExecutionMetrics,
 }
 
 #[typeshare]
@@ -70,6 +75,7 @@ pub type _PartialProcedureConfig = PartialProcedureConfig;
 /// Config for the [Procedure]
 #[typeshare]
 #[derive(Debug, Clone, Serialize, Deserialize, Partial, Builder)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[partial_derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[partial(skip_serializing_none, from, diff)]
 pub struct ProcedureConfig {
@@ -79,6 +85,12 @@ pub struct ProcedureConfig {
   #[builder(default)]
   pub stages: Vec<ProcedureStage>,
 
+  /// Inputs which can be supplied when running the procedure.
+  /// They are available for interpolation into the stage executions as `[[NAME]]`.
+  #[serde(default)]
+  #[builder(default)]
+  pub inputs: Vec<RuntimeInput>,
+
   /// Choose whether to specify schedule as regular CRON, or using the english to CRON parser.
   #[serde(default)]
   #[builder(default)]
@@ -123,6 +135,12 @@ pub struct ProcedureConfig {
   #[partial_default(default_schedule_alert())]
   pub schedule_alert: bool,
 
+  /// What to do with scheduled runs missed while Core was down.
+  /// An alert is sent for each missed run either way.
+  #[serde(default)]
+  #[builder(default)]
+  pub schedule_missed_runs: MissedRunPolicy,
+
   /// Whether to send alerts when this procedure fails.
   #[serde(default = "default_failure_alert")]
   #[builder(default = "default_failure_alert()")]
@@ -168,11 +186,13 @@ impl Default for ProcedureConfig {
   fn default() -> Self {
     Self {
       stages: Default::default(),
+      inputs: Default::default(),
       schedule_format: Default::default(),
       schedule: Default::default(),
       schedule_enabled: default_schedule_enabled(),
       schedule_timezone: Default::default(),
       schedule_alert: default_schedule_alert(),
+      schedule_missed_runs: Default::default(),
       failure_alert: default_failure_alert(),
       webhook_enabled: default_webhook_enabled(),
       webhook_secret: Default::default(),
@@ -183,6 +203,7 @@ impl Default for ProcedureConfig {
 /// A single stage of a procedure. Runs a list of executions in parallel.
 #[typeshare]
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ProcedureStage {
   /// A name for the procedure
   pub name: String,
@@ -192,11 +213,98 @@ pub struct ProcedureStage {
   /// The executions in the stage
   #[serde(default, alias = "execution")]
   pub executions: Vec<EnabledExecution>,
+  /// Only run the stage when the condition is met.
+  /// Otherwise the stage is skipped.
+  #[serde(default)]
+  pub condition: StageCondition,
+  /// What to do when the stage fails.
+  #[serde(default)]
+  pub on_failure: StageFailureHandler,
+  /// Pause the procedure before running the stage,
+  /// until a user approves it. A stage with no executions
+  /// acts as an approval gate for the following stages.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub require_approval: Option<RequireApproval>,
+}
+
+/// Configures the approval a procedure stage waits for.
+/// Approving requires Write permission on the procedure.
+#[typeshare]
+#[derive(
+  Debug, Clone, Default, PartialEq, Serialize, Deserialize,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RequireApproval {
+  /// A message shown to approvers, eg. what will be deployed.
+  /// Stage outputs and non secret runtime inputs are interpolated.
+  #[serde(default)]
+  pub message: String,
+  /// Fail the stage if not approved within this many minutes.
+  /// 0 waits indefinitely.
+  #[serde(default)]
+  pub timeout_minutes: I64,
+  /// Don't let the user who ran the Procedure approve the stage.
+  #[serde(default)]
+  pub forbid_self_approval: bool,
+  /// Only these users (by username or id) can approve the stage.
+  /// Empty allows any user with Write permission on the Procedure.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub approvers: Vec<String>,
+}
+
+/// Condition for a procedure stage to run,
+/// evaluated before the stage starts.
+#[typeshare]
+#[derive(
+  Debug, Clone, Default, PartialEq, Serialize, Deserialize,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "type", content = "params")]
+pub enum StageCondition {
+  /// Always run the stage.
+  #[default]
+  Always,
+  /// Only run if none of the stages run before it failed.
+  PreviousSucceeded,
+  /// Only run if any of the stages run before it failed.
+  /// Useful for cleanup and notification stages.
+  PreviousFailed,
+  /// Only run if the stage with the given name ran and succeeded.
+  StageSucceeded { stage: String },
+  /// Only run if the stage with the given name ran and failed.
+  StageFailed { stage: String },
+  /// Only run if the variable equals the value.
+  /// Runtime inputs are checked first, then Komodo Variables.
+  VariableEquals { variable: String, value: String },
+}
+
+/// What a procedure does when a stage fails.
+#[typeshare]
+#[derive(
+  Debug, Clone, Default, PartialEq, Serialize, Deserialize,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "type", content = "params")]
+pub enum StageFailureHandler {
+  /// Abort the procedure, failing the run.
+  #[default]
+  Abort,
+  /// Continue on to the next stage.
+  /// The procedure run is still marked as failed.
+  Continue,
+  /// Run another execution, then abort the procedure,
+  /// or continue if `continue_after` is true.
+  RunExecution {
+    execution: Execution,
+    #[serde(default)]
+    continue_after: bool,
+  },
 }
 
 /// Allows to enable / disabled procedures in the sequence / parallel vec on the fly
 #[typeshare]
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct EnabledExecution {
   /// The execution request to run.
   pub execution: Execution,
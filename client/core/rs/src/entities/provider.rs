@@ -1,8 +1,9 @@
 use partial_derive2::Partial;
 use serde::{Deserialize, Serialize};
+use strum::Display;
 use typeshare::typeshare;
 
-use super::MongoId;
+use super::{I64, MongoId};
 
 #[typeshare(serialized_as = "Partial<GitProviderAccount>")]
 pub type _PartialGitProviderAccount = PartialGitProviderAccount;
@@ -49,6 +50,21 @@ pub struct GitProviderAccount {
   /// If the database / host can be accessed this is insecure.
   #[serde(default)]
   pub token: String,
+  /// Restrict the account to resources with any of these tags (ids).
+  /// If both this and `allowed_user_groups` are empty,
+  /// the account can be used by any resource.
+  #[serde(default)]
+  pub allowed_tags: Vec<String>,
+  /// Restrict the account to resources which any of these
+  /// user groups (ids) have Write permission on.
+  /// If both this and `allowed_tags` are empty,
+  /// the account can be used by any resource.
+  #[serde(default)]
+  pub allowed_user_groups: Vec<String>,
+  /// The result of the latest token health check.
+  /// Managed by Core, ignored on create / update.
+  #[serde(default)]
+  pub health: ProviderAccountHealth,
 }
 
 fn default_git_domain() -> String {
@@ -100,8 +116,113 @@ pub struct DockerRegistryAccount {
   /// If the database / host can be accessed this is insecure.
   #[serde(default)]
   pub token: String,
+  /// How Core gets the token used to login to the registry.
+  #[serde(default)]
+  pub auth: RegistryAccountAuth,
+  /// Unix timestamp in ms when Core last rotated the token.
+  /// Only used with Harbor robot accounts.
+  /// Managed by Core, reset when the token is updated.
+  #[serde(default)]
+  pub token_rotated_at: I64,
+  /// Restrict the account to resources with any of these tags (ids).
+  /// If both this and `allowed_user_groups` are empty,
+  /// the account can be used by any resource.
+  #[serde(default)]
+  pub allowed_tags: Vec<String>,
+  /// Restrict the account to resources which any of these
+  /// user groups (ids) have Write permission on.
+  /// If both this and `allowed_tags` are empty,
+  /// the account can be used by any resource.
+  #[serde(default)]
+  pub allowed_user_groups: Vec<String>,
+  /// The result of the latest token health check.
+  /// Managed by Core, ignored on create / update.
+  #[serde(default)]
+  pub health: ProviderAccountHealth,
+}
+
+/// How Core gets the token for a docker registry account.
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, PartialEq,
+)]
+#[serde(tag = "type", content = "params")]
+pub enum RegistryAccountAuth {
+  /// Use the static `token`.
+  #[default]
+  Token,
+  /// Amazon ECR. The token is derived from the Core AWS credentials,
+  /// and refreshed before it expires after 12 hours.
+  /// The account username must be `AWS`, and `token` is unused.
+  Ecr {
+    /// The AWS region of the registry.
+    /// If empty, it is parsed from the domain,
+    /// eg `123456789012.dkr.ecr.us-east-1.amazonaws.com`.
+    #[serde(default)]
+    region: String,
+  },
+  /// Harbor robot account. The `token` is the robot secret,
+  /// which Core replaces with a new random secret periodically.
+  HarborRobot {
+    /// The id of the robot account in Harbor.
+    robot_id: I64,
+    /// Rotate the secret after this many days.
+    /// 0 disables rotation.
+    #[serde(default = "default_harbor_rotate_days")]
+    rotate_days: I64,
+  },
+}
+
+fn default_harbor_rotate_days() -> I64 {
+  7
 }
 
 fn default_registry_domain() -> String {
   String::from("docker.io")
 }
+
+/// The result of the periodic token health check
+/// on a git provider or docker registry account.
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, PartialEq,
+)]
+pub struct ProviderAccountHealth {
+  /// Unix timestamp in ms of the latest check.
+  /// Zero if the account has not been checked yet.
+  #[serde(default)]
+  pub last_check_ts: I64,
+  /// Unix timestamp in ms of the latest successful check.
+  #[serde(default)]
+  pub last_success_ts: I64,
+  /// The error of the latest check.
+  /// Empty if the latest check was successful.
+  #[serde(default)]
+  pub error: String,
+  /// Unix timestamp in ms when the token expires,
+  /// if the provider exposes it.
+  #[serde(default)]
+  pub expires_at: Option<I64>,
+}
+
+/// The type of provider account.
+#[typeshare]
+#[derive(
+  Serialize,
+  Deserialize,
+  Debug,
+  Clone,
+  Copy,
+  PartialEq,
+  Eq,
+  Hash,
+  Display,
+)]
+pub enum ProviderAccountType {
+  /// A git provider account
+  #[strum(serialize = "git provider")]
+  Git,
+  /// A docker registry account
+  #[strum(serialize = "docker registry")]
+  Registry,
+}
@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+use strum::Display;
+use typeshare::typeshare;
+
+use crate::api::execute::Execution;
+
+use super::{I64, MongoId};
+
+/// An execution waiting for a Server to be reachable.
+/// For edge Servers with intermittent connectivity, Core runs
+/// the queued executions in order once it reconnects to the Server.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(
+  feature = "mongo",
+  derive(mongo_indexed::derive::MongoIndexed)
+)]
+#[cfg_attr(feature = "mongo", doc_index({ "server_id": 1, "status": 1, "queued_at": 1 }))]
+pub struct QueuedExecution {
+  /// The Mongo ID of the queued execution.
+  /// This field is de/serialized from/to JSON as
+  /// `{ "_id": { "$oid": "..." }, ...(rest of serialized QueuedExecution) }`
+  #[serde(
+    default,
+    rename = "_id",
+    skip_serializing_if = "String::is_empty",
+    with = "bson::serde_helpers::hex_string_as_object_id"
+  )]
+  pub id: MongoId,
+
+  /// The id of the Server the execution waits for.
+  pub server_id: String,
+
+  /// The execution to run.
+  pub execution: Execution,
+
+  /// The status of the queued execution.
+  #[serde(default)]
+  pub status: QueuedExecutionStatus,
+
+  /// The id of the user who queued the execution.
+  /// The execution runs as this user.
+  #[serde(default)]
+  pub queued_by: String,
+
+  /// When the execution was queued.
+  #[serde(default)]
+  pub queued_at: I64,
+
+  /// If the Server isn't reachable by this timestamp,
+  /// the execution expires instead of running. 0 never expires.
+  #[serde(default)]
+  pub expires_at: I64,
+
+  /// When the execution started running.
+  #[serde(default)]
+  pub started_at: I64,
+
+  /// When the execution finished, or was cancelled / expired.
+  #[serde(default)]
+  pub finished_at: I64,
+
+  /// The id of the Update recording the execution, once it has run.
+  #[serde(default)]
+  pub update_id: String,
+
+  /// An error message if the execution couldn't run.
+  #[serde(default)]
+  pub error: String,
+}
+
+#[typeshare]
+#[derive(
+  Serialize,
+  Deserialize,
+  Debug,
+  Clone,
+  Copy,
+  Default,
+  PartialEq,
+  Eq,
+  Hash,
+  Display,
+)]
+pub enum QueuedExecutionStatus {
+  /// Waiting for the Server to be reachable.
+  #[default]
+  Queued,
+  /// The Server is reachable and the execution is running.
+  Running,
+  /// The execution ran successfully.
+  Complete,
+  /// The execution failed, see the Update or error.
+  Failed,
+  /// The execution was cancelled before it ran.
+  Cancelled,
+  /// The Server wasn't reachable before the execution expired.
+  Expired,
+}
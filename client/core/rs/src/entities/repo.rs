@@ -9,8 +9,9 @@ use typeshare::typeshare;
 
 use crate::{
   deserializers::{
-    env_vars_deserializer, option_env_vars_deserializer,
-    option_string_list_deserializer, string_list_deserializer,
+    env_vars_deserializer, file_contents_deserializer,
+    option_env_vars_deserializer, option_string_list_deserializer,
+    string_list_deserializer,
   },
   entities::I64,
 };
@@ -72,6 +73,8 @@ pub enum RepoState {
   Pulling,
   /// Currently building
   Building,
+  /// Currently running the pipeline
+  RunningPipeline,
 }
 
 #[typeshare]
@@ -101,6 +104,7 @@ pub type _PartialRepoConfig = PartialRepoConfig;
 
 #[typeshare]
 #[derive(Serialize, Deserialize, Debug, Clone, Builder, Partial)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[partial_derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[partial(skip_serializing_none, from, diff)]
 pub struct RepoConfig {
@@ -188,6 +192,13 @@ pub struct RepoConfig {
   #[builder(default)]
   pub on_pull: SystemCommand,
 
+  /// Steps run in order by RunRepoPipeline, after the repo is pulled.
+  /// The pipeline stops at the first failed step.
+  #[serde(default, alias = "step")]
+  #[partial_attr(serde(alias = "step"))]
+  #[builder(default)]
+  pub pipeline: Vec<RepoPipelineStep>,
+
   /// Configure quick links that are displayed in the resource header
   #[serde(default, deserialize_with = "string_list_deserializer")]
   #[partial_attr(serde(
@@ -269,6 +280,7 @@ impl Default for RepoConfig {
       path: Default::default(),
       on_clone: Default::default(),
       on_pull: Default::default(),
+      pipeline: Default::default(),
       links: Default::default(),
       environment: Default::default(),
       env_file_path: default_env_file_path(),
@@ -279,6 +291,38 @@ impl Default for RepoConfig {
   }
 }
 
+/// A single step of the Repo pipeline.
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, PartialEq,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RepoPipelineStep {
+  /// A name for the step
+  pub name: String,
+  /// Run the step inside a container using this image,
+  /// with the repo mounted at `/repo`.
+  /// If empty, the step runs directly on the host.
+  #[serde(default)]
+  pub image: String,
+  /// The path to run the command in, relative to the repo root.
+  #[serde(default)]
+  pub path: String,
+  /// The command to run. Supports multiple lines.
+  #[serde(default, deserialize_with = "file_contents_deserializer")]
+  pub command: String,
+  /// Paths relative to the repo root to keep after a successful step.
+  /// They are copied to `${root_directory}/artifacts/${repo}`,
+  /// which is cleared at the start of each run.
+  #[serde(default, deserialize_with = "string_list_deserializer")]
+  pub artifacts: Vec<String>,
+  /// Path to a JUnit XML report written by the step,
+  /// relative to the step path.
+  /// The test results are attached to the Update.
+  #[serde(default)]
+  pub junit_report: String,
+}
+
 #[typeshare]
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
 pub struct RepoActionState {
@@ -290,6 +334,8 @@ pub struct RepoActionState {
   pub building: bool,
   /// Whether Repo currently renaming.
   pub renaming: bool,
+  /// Whether Repo currently running the pipeline.
+  pub running_pipeline: bool,
 }
 
 #[typeshare]
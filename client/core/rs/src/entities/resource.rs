@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use bson::{Document, doc};
 use clap::ValueEnum;
 use derive_builder::Builder;
@@ -13,6 +15,7 @@ use crate::{
 
 use super::{
   ResourceTargetVariant, permission::PermissionLevelAndSpecifics,
+  resource_lock::ResourceConcurrency,
 };
 
 #[typeshare]
@@ -44,11 +47,23 @@ pub struct Resource<Config: Default, Info: Default = ()> {
   #[builder(default)]
   pub template: bool,
 
+  /// Reject Delete / Destroy / Prune executions on the resource.
+  /// Only admins can remove the protection,
+  /// using [SetResourceProtected][crate::api::write::SetResourceProtected].
+  #[serde(default)]
+  #[builder(default)]
+  pub protected: bool,
+
   /// Tag Ids
   #[serde(default, deserialize_with = "string_list_deserializer")]
   #[builder(default)]
   pub tags: Vec<String>,
 
+  /// Custom key / value metadata, eg. rack, owner, or asset tag.
+  #[serde(default)]
+  #[builder(default)]
+  pub metadata: HashMap<String, String>,
+
   /// Resource-specific information (not user configurable).
   #[serde(default)]
   #[builder(setter(skip))]
@@ -65,10 +80,23 @@ pub struct Resource<Config: Default, Info: Default = ()> {
   #[builder(default)]
   pub base_permission: PermissionLevelAndSpecifics,
 
+  /// How executions on the resource run while
+  /// another execution in its concurrency group is running.
+  #[serde(default)]
+  #[builder(default)]
+  pub concurrency: ResourceConcurrency,
+
   /// When description last updated
   #[serde(default)]
   #[builder(setter(skip))]
   pub updated_at: I64,
+
+  /// Incremented on every config update.
+  /// Pass it with the config update to reject the update
+  /// if the resource was changed since it was read.
+  #[serde(default)]
+  #[builder(setter(skip))]
+  pub revision: I64,
 }
 
 impl<C: Default, I: Default> Default for Resource<C, I> {
@@ -78,11 +106,15 @@ impl<C: Default, I: Default> Default for Resource<C, I> {
       name: String::from("temp-resource"),
       description: String::new(),
       template: Default::default(),
+      protected: Default::default(),
       tags: Vec::new(),
+      metadata: Default::default(),
       info: I::default(),
       config: C::default(),
       base_permission: Default::default(),
+      concurrency: Default::default(),
       updated_at: 0,
+      revision: 0,
     }
   }
 }
@@ -99,6 +131,9 @@ pub struct ResourceListItem<Info> {
   pub name: String,
   /// Whether resource is a template
   pub template: bool,
+  /// Whether resource is protected from Delete / Destroy / Prune
+  #[serde(default)]
+  pub protected: bool,
   /// Tag Ids
   pub tags: Vec<String>,
   /// Resource specific info
@@ -121,6 +156,9 @@ pub struct ResourceQuery<T: Default> {
   /// 'All' or 'Any'
   #[serde(default)]
   pub tag_behavior: TagQueryBehavior,
+  /// Only return resources with all of these metadata values.
+  #[serde(default)]
+  pub metadata: HashMap<String, String>,
   #[serde(default)]
   pub specific: T,
 }
@@ -195,6 +233,9 @@ impl<T: AddFilters + Default> AddFilters for ResourceQuery<T> {
         }
       }
     }
+    for (key, value) in &self.metadata {
+      filters.insert(format!("metadata.{key}"), value.as_str());
+    }
     self.specific.add_filters(filters);
   }
 }
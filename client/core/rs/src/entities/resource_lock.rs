@@ -0,0 +1,145 @@
+use serde::{Deserialize, Serialize};
+use strum::Display;
+use typeshare::typeshare;
+
+use super::{I64, MongoId, Operation, ResourceTarget};
+
+/// A lock held on a resource while an execution runs on it.
+/// Locks are stored on the database, so they are shared
+/// between all the Core instances using it.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(
+  feature = "mongo",
+  derive(mongo_indexed::derive::MongoIndexed)
+)]
+pub struct ResourceLock {
+  /// The locked resource, eg `Stack:<id>`.
+  /// Only one lock can exist per resource.
+  #[serde(rename = "_id")]
+  pub id: String,
+
+  /// The locked resource.
+  pub target: ResourceTarget,
+
+  /// The operation holding the lock.
+  pub operation: Operation,
+
+  /// The id of the Update of the operation holding the lock.
+  #[serde(default)]
+  pub update_id: String,
+
+  /// The id of the user who started the operation.
+  #[serde(default)]
+  pub operator: String,
+
+  /// When the lock was acquired.
+  #[serde(default)]
+  pub locked_at: I64,
+
+  /// The holder renews the lock while the operation runs.
+  /// If the holding Core stops, the lock is released
+  /// once it isn't renewed by this time.
+  #[serde(default)]
+  pub expires_at: I64,
+
+  /// The id of the user who cancelled the operation, if any.
+  /// Lets CancelUpdate cancel operations running on another Core,
+  /// which checks for it when it renews the lock.
+  #[serde(default)]
+  pub cancelled_by: String,
+}
+
+/// An execution waiting in line for a resource lock,
+/// for resources using the Queue or CancelPrevious concurrency policy.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(
+  feature = "mongo",
+  derive(mongo_indexed::derive::MongoIndexed)
+)]
+#[cfg_attr(feature = "mongo", doc_index({ "lock_id": 1, "queued_at": 1 }))]
+pub struct ResourceLockWaiter {
+  /// The Mongo ID of the waiter.
+  #[serde(
+    default,
+    rename = "_id",
+    skip_serializing_if = "String::is_empty",
+    with = "bson::serde_helpers::hex_string_as_object_id"
+  )]
+  pub id: MongoId,
+
+  /// The id of the lock being waited for.
+  pub lock_id: String,
+
+  /// The resource the execution runs on.
+  pub target: ResourceTarget,
+
+  /// The waiting operation.
+  pub operation: Operation,
+
+  /// The id of the Update of the waiting operation.
+  #[serde(default)]
+  pub update_id: String,
+
+  /// When the execution started waiting.
+  #[serde(default)]
+  pub queued_at: I64,
+
+  /// The waiter renews this while waiting,
+  /// so waiters of a stopped Core don't block the line.
+  #[serde(default)]
+  pub expires_at: I64,
+
+  /// If a newer execution with the CancelPrevious policy
+  /// cancelled this one, the id of its Update.
+  #[serde(default)]
+  pub superseded_by: String,
+}
+
+/// Controls how an execution on a resource runs
+/// while another execution in its concurrency group is running.
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, PartialEq,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ResourceConcurrency {
+  /// Executions in the same concurrency group run one at a time.
+  /// Resources can share a group, eg. all the Stacks deploying to
+  /// the same Server. If empty, the group is just the resource.
+  /// Joining a group requires Execute on the resources already in it.
+  #[serde(default)]
+  pub group: String,
+  /// What happens when an execution starts while
+  /// another in the group is running.
+  #[serde(default)]
+  pub policy: ConcurrencyPolicy,
+}
+
+#[typeshare]
+#[derive(
+  Serialize,
+  Deserialize,
+  Debug,
+  Clone,
+  Copy,
+  Default,
+  PartialEq,
+  Eq,
+  Display,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum ConcurrencyPolicy {
+  /// Fail with "Operation in progress", after waiting
+  /// up to `resource_lock_wait_secs` from the Core config.
+  #[default]
+  Reject,
+  /// Wait for the running and earlier queued executions,
+  /// then run in the order they were started.
+  Queue,
+  /// Cancel any executions queued in the group,
+  /// then run once the running execution finishes.
+  /// Running executions are never interrupted.
+  CancelPrevious,
+}
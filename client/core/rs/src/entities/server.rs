@@ -3,14 +3,16 @@ use std::{collections::HashMap, path::PathBuf};
 use derive_builder::Builder;
 use partial_derive2::Partial;
 use serde::{Deserialize, Serialize};
-use strum::Display;
+use strum::{Display, EnumString};
 use typeshare::typeshare;
 
 use crate::{
   deserializers::{
-    option_string_list_deserializer, string_list_deserializer,
+    env_vars_deserializer, file_contents_deserializer,
+    option_env_vars_deserializer, option_string_list_deserializer,
+    string_list_deserializer,
   },
-  entities::MaintenanceWindow,
+  entities::{MaintenanceWindow, ScheduleFormat},
 };
 
 use super::{
@@ -62,6 +64,7 @@ pub type _PartialServerConfig = PartialServerConfig;
 /// Server configuration.
 #[typeshare]
 #[derive(Serialize, Deserialize, Debug, Clone, Builder, Partial)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[partial_derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[partial(skip_serializing_none, from, diff)]
 pub struct ServerConfig {
@@ -93,6 +96,17 @@ pub struct ServerConfig {
   #[builder(default)]
   pub region: String,
 
+  /// Custom key / value attributes of the server, one `KEY=value` per line.
+  /// Deployments and Stacks on the server can interpolate them
+  /// into their config with `${server.KEY}`.
+  #[serde(default, deserialize_with = "env_vars_deserializer")]
+  #[partial_attr(serde(
+    default,
+    deserialize_with = "option_env_vars_deserializer"
+  ))]
+  #[builder(default)]
+  pub attributes: String,
+
   /// Whether a server is enabled.
   /// If a server is disabled,
   /// you won't be able to perform any actions on it or see deployment's status.
@@ -116,6 +130,26 @@ pub struct ServerConfig {
   #[builder(default)]
   pub passkey: String,
 
+  /// How Core connects to the periphery `address`.
+  /// Default: Direct
+  #[serde(default)]
+  #[builder(default)]
+  pub connection: PeripheryConnection,
+
+  /// Whether Core manages the server through Periphery,
+  /// or talks to the Docker API at `address` directly.
+  /// Default: Periphery
+  #[serde(default)]
+  #[builder(default)]
+  pub mode: ServerMode,
+
+  /// The container runtime Periphery uses on the server
+  /// for deployments, stacks, prune operations, and stats.
+  /// Default: docker
+  #[serde(default)]
+  #[builder(default)]
+  pub container_runtime: ContainerRuntime,
+
   /// Sometimes the system stats reports a mount path that is not desired.
   /// Use this field to filter it out from the report.
   #[serde(default, deserialize_with = "string_list_deserializer")]
@@ -149,6 +183,19 @@ pub struct ServerConfig {
   #[builder(default)]
   pub links: Vec<String>,
 
+  /// Inherit shared config defaults from a Server Profile (name or id).
+  /// The fields set on the profile replace this Server's own values,
+  /// except for those listed in `profile_overrides`.
+  #[serde(default)]
+  #[builder(default)]
+  pub server_profile: String,
+
+  /// The config fields which keep this Server's own value
+  /// instead of inheriting from the profile, eg. `cpu_warning`.
+  #[serde(default)]
+  #[builder(default)]
+  pub profile_overrides: Vec<String>,
+
   /// Whether to send alerts about the servers reachability
   #[serde(default = "default_send_alerts")]
   #[builder(default = "default_send_alerts()")]
@@ -173,6 +220,18 @@ pub struct ServerConfig {
   #[partial_default(default_send_alerts())]
   pub send_disk_alerts: bool,
 
+  /// Whether to send alerts about the servers network throughput
+  #[serde(default = "default_send_alerts")]
+  #[builder(default = "default_send_alerts()")]
+  #[partial_default(default_send_alerts())]
+  pub send_network_alerts: bool,
+
+  /// Whether to send alerts about the servers disk IO
+  #[serde(default = "default_send_alerts")]
+  #[builder(default = "default_send_alerts()")]
+  #[partial_default(default_send_alerts())]
+  pub send_disk_io_alerts: bool,
+
   /// Whether to send alerts about the servers version mismatch with core
   #[serde(default = "default_send_alerts")]
   #[builder(default = "default_send_alerts()")]
@@ -215,10 +274,62 @@ pub struct ServerConfig {
   #[partial_default(default_disk_critical())]
   pub disk_critical: f64,
 
+  /// Per mount thresholds for DISK, which override
+  /// `disk_warning` and `disk_critical` for matching mounts.
+  #[serde(default)]
+  #[builder(default)]
+  pub disk_thresholds: Vec<DiskThreshold>,
+
+  /// The network throughput in MB/s, in either direction,
+  /// which triggers WARNING state for NETWORK. 0 is disabled.
+  #[serde(default)]
+  #[builder(default)]
+  pub network_warning: f64,
+
+  /// The network throughput in MB/s, in either direction,
+  /// which triggers CRITICAL state for NETWORK. 0 is disabled.
+  #[serde(default)]
+  #[builder(default)]
+  pub network_critical: f64,
+
+  /// The disk read or write throughput in MB/s
+  /// which triggers WARNING state for DISK IO. 0 is disabled.
+  #[serde(default)]
+  #[builder(default)]
+  pub disk_io_warning: f64,
+
+  /// The disk read or write throughput in MB/s
+  /// which triggers CRITICAL state for DISK IO. 0 is disabled.
+  #[serde(default)]
+  #[builder(default)]
+  pub disk_io_critical: f64,
+
   /// Scheduled maintenance windows during which alerts will be suppressed.
   #[serde(default)]
   #[builder(default)]
   pub maintenance_windows: Vec<MaintenanceWindow>,
+
+  /// Named terminal configurations which can be used to open terminals
+  /// on the server, for example a restricted shell or a specific container's console.
+  #[serde(default)]
+  #[builder(default)]
+  pub terminal_profiles: Vec<TerminalProfile>,
+
+  /// Whether non-admin users may only open terminals
+  /// using one of the configured `terminal_profiles`.
+  /// Default: false
+  #[serde(default)]
+  #[builder(default)]
+  pub restrict_terminals: bool,
+
+  /// Commands to run on the host on a schedule, managed by Core.
+  /// Each run is recorded as an Update on the Server,
+  /// and failed runs will send an alert. Commands run with the
+  /// permissions of the user who created or last changed them.
+  /// Adding or changing them requires Terminal permission on the Server.
+  #[serde(default)]
+  #[builder(default)]
+  pub scheduled_commands: Vec<ScheduledCommand>,
 }
 
 impl ServerConfig {
@@ -283,28 +394,269 @@ impl Default for ServerConfig {
       external_address: Default::default(),
       enabled: default_enabled(),
       timeout_seconds: default_timeout_seconds(),
+      connection: Default::default(),
+      mode: Default::default(),
       ignore_mounts: Default::default(),
       stats_monitoring: default_stats_monitoring(),
       auto_prune: default_auto_prune(),
       links: Default::default(),
+      server_profile: Default::default(),
+      profile_overrides: Default::default(),
       send_unreachable_alerts: default_send_alerts(),
       send_cpu_alerts: default_send_alerts(),
       send_mem_alerts: default_send_alerts(),
       send_disk_alerts: default_send_alerts(),
+      send_network_alerts: default_send_alerts(),
+      send_disk_io_alerts: default_send_alerts(),
       send_version_mismatch_alerts: default_send_alerts(),
       region: Default::default(),
+      attributes: Default::default(),
       passkey: Default::default(),
+      container_runtime: Default::default(),
       cpu_warning: default_cpu_warning(),
       cpu_critical: default_cpu_critical(),
       mem_warning: default_mem_warning(),
       mem_critical: default_mem_critical(),
       disk_warning: default_disk_warning(),
       disk_critical: default_disk_critical(),
+      disk_thresholds: Default::default(),
+      network_warning: Default::default(),
+      network_critical: Default::default(),
+      disk_io_warning: Default::default(),
+      disk_io_critical: Default::default(),
       maintenance_windows: Default::default(),
+      terminal_profiles: Default::default(),
+      restrict_terminals: Default::default(),
+      scheduled_commands: Default::default(),
     }
   }
 }
 
+/// The container runtime used by Periphery.
+#[typeshare]
+#[derive(
+  Debug,
+  Clone,
+  Copy,
+  PartialEq,
+  Eq,
+  Default,
+  Display,
+  EnumString,
+  Serialize,
+  Deserialize,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum ContainerRuntime {
+  /// Use the Docker CLI and daemon.
+  #[default]
+  Docker,
+  /// Use the Podman CLI and API socket.
+  /// Rootless Podman is supported.
+  Podman,
+}
+
+/// A named terminal configuration on a Server.
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, PartialEq,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TerminalProfile {
+  /// The name of the profile. Must be unique on the Server.
+  pub name: String,
+  /// The command used to start the terminal.
+  ///
+  /// This can also include args:
+  /// `docker exec -it postgres psql -U postgres`
+  ///
+  /// Default: `bash`
+  #[serde(default = "default_terminal_command")]
+  pub command: String,
+  /// The working directory of the terminal process.
+  /// If empty, will use the Periphery working directory.
+  #[serde(default)]
+  pub working_dir: String,
+  /// Extra environment variables passed to the terminal process.
+  #[serde(default, deserialize_with = "env_vars_deserializer")]
+  pub environment: String,
+  /// The User Groups (name or id) allowed to use the profile.
+  /// If empty, all users with Terminal permission on the Server may use it.
+  /// Periphery also enforces the profiles in its own config,
+  /// see `terminal_profiles` in the Periphery config.
+  #[serde(default, deserialize_with = "string_list_deserializer")]
+  pub user_groups: Vec<String>,
+}
+
+fn default_terminal_command() -> String {
+  String::from("bash")
+}
+
+/// A command run on the Server host on a schedule.
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, PartialEq,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ScheduledCommand {
+  /// The name of the command. Must be unique on the Server.
+  pub name: String,
+  /// Whether the scheduled command is enabled.
+  /// Default: true
+  #[serde(default = "default_scheduled_command_enabled")]
+  pub enabled: bool,
+  /// Choose whether to specify schedule as regular CRON, or using the english to CRON parser.
+  #[serde(default)]
+  pub schedule_format: ScheduleFormat,
+  /// Optionally provide a timezone for the schedule.
+  /// If empty, will use the Core timezone.
+  #[serde(default)]
+  pub schedule_timezone: String,
+  /// The schedule for the command.
+  ///
+  /// - CRON: `0 0 3 * * *` (seconds are required)
+  /// - English: `Every day at 3am`
+  #[serde(default)]
+  pub schedule: String,
+  /// The command to run. Supports multiline commands,
+  /// which will be chained together with '&&'.
+  #[serde(default, deserialize_with = "file_contents_deserializer")]
+  pub command: String,
+  /// The directory to run the command in.
+  /// If empty, will use the Periphery working directory.
+  #[serde(default)]
+  pub path: String,
+  /// Run the command as this host user (using `sudo -u`).
+  /// If empty, runs as the Periphery user.
+  #[serde(default)]
+  pub user: String,
+  /// Whether to send an alert when the command fails.
+  /// Default: true
+  #[serde(default = "default_scheduled_command_alert")]
+  pub alert: bool,
+  /// The id of the user who created or last changed the command.
+  /// The command runs with this user's permissions. Set by Core.
+  #[serde(default)]
+  pub created_by: String,
+}
+
+impl ScheduledCommand {
+  /// Whether the commands are the same, ignoring `created_by`.
+  pub fn same_command(&self, other: &ScheduledCommand) -> bool {
+    self.name == other.name
+      && self.enabled == other.enabled
+      && self.schedule_format == other.schedule_format
+      && self.schedule_timezone == other.schedule_timezone
+      && self.schedule == other.schedule
+      && self.command == other.command
+      && self.path == other.path
+      && self.user == other.user
+      && self.alert == other.alert
+  }
+}
+
+fn default_scheduled_command_enabled() -> bool {
+  true
+}
+
+fn default_scheduled_command_alert() -> bool {
+  true
+}
+
+/// How Core connects to Periphery.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "type", content = "params")]
+pub enum PeripheryConnection {
+  /// Connect to the periphery `address` directly.
+  Direct {},
+
+  /// Connect to the periphery `address` through an SSH tunnel.
+  /// The `address` is resolved from the SSH host,
+  /// so Periphery only needs to listen on eg. `localhost`.
+  Ssh {
+    /// The SSH host, eg. `server.example.com`.
+    host: String,
+    /// The SSH port.
+    /// Default: 22
+    #[serde(default = "default_ssh_port")]
+    port: u16,
+    /// The SSH user.
+    user: String,
+    /// The name of the Core secret containing the private key.
+    /// See `secrets` in the Core config.
+    key_secret: String,
+  },
+}
+
+impl Default for PeripheryConnection {
+  fn default() -> Self {
+    Self::Direct {}
+  }
+}
+
+fn default_ssh_port() -> u16 {
+  22
+}
+
+/// How Core manages the Server.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "type", content = "params")]
+pub enum ServerMode {
+  /// Core sends requests to the Periphery agent at `address`.
+  Periphery {},
+
+  /// Core talks to the Docker API at `address` directly,
+  /// for hosts where Periphery can't be installed.
+  ///
+  /// The `address` is the Docker host, eg. `tcp://10.0.0.5:2376`.
+  /// With an SSH `connection`, it can be the remote socket,
+  /// eg. `unix:///var/run/docker.sock`.
+  ///
+  /// Only container listing, logs, and Deployment actions
+  /// (deploy, start, stop, restart, pause, destroy) are supported.
+  DockerApi {
+    /// The name of the Core secret containing the CA certificate (PEM).
+    /// If the cert / key secrets are empty, connects without TLS.
+    #[serde(default)]
+    ca_secret: String,
+    /// The name of the Core secret containing the client certificate (PEM).
+    #[serde(default)]
+    cert_secret: String,
+    /// The name of the Core secret containing the client key (PEM).
+    #[serde(default)]
+    key_secret: String,
+  },
+}
+
+impl Default for ServerMode {
+  fn default() -> Self {
+    Self::Periphery {}
+  }
+}
+
+/// DISK alert thresholds for a specific mount on the Server.
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, PartialEq,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct DiskThreshold {
+  /// The mount path, eg. `/var/lib/docker`.
+  pub mount: String,
+  /// The percentage threshhold which triggers WARNING state for the mount.
+  #[serde(default = "default_disk_warning")]
+  pub warning: f64,
+  /// The percentage threshhold which triggers CRITICAL state for the mount.
+  #[serde(default = "default_disk_critical")]
+  pub critical: f64,
+}
+
 /// The health of a part of the server.
 #[typeshare]
 #[derive(Serialize, Deserialize, Default, Debug, Clone)]
@@ -321,6 +673,10 @@ pub struct ServerHealth {
   pub cpu: ServerHealthState,
   pub mem: ServerHealthState,
   pub disks: HashMap<PathBuf, ServerHealthState>,
+  #[serde(default)]
+  pub network: ServerHealthState,
+  #[serde(default)]
+  pub disk_io: ServerHealthState,
 }
 
 /// Info about an active terminal on a server.
@@ -334,6 +690,25 @@ pub struct TerminalInfo {
   pub command: String,
   /// The size of the terminal history in memory.
   pub stored_size_kb: f64,
+  /// The [TerminalProfile] used to create the terminal, if any.
+  #[serde(default)]
+  pub profile: Option<String>,
+  /// The users currently connected to the terminal.
+  #[serde(default)]
+  pub participants: Vec<TerminalParticipant>,
+}
+
+/// A user connected to a terminal session.
+#[typeshare]
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct TerminalParticipant {
+  /// The username of the connected user.
+  pub username: String,
+  /// Whether the user is attached read-only,
+  /// meaning they can see output but not write to stdin.
+  pub read_only: bool,
+  /// Unix timestamp (ms) when the user joined.
+  pub joined_at: I64,
 }
 
 /// Current pending actions on the server.
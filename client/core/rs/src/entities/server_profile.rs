@@ -0,0 +1,223 @@
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use super::{
+  MaintenanceWindow, MongoId,
+  server::{DiskThreshold, ServerConfig},
+};
+
+/// Shared config defaults which Servers can inherit from,
+/// by setting the profile on the Server config.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(
+  feature = "mongo",
+  derive(mongo_indexed::derive::MongoIndexed)
+)]
+pub struct ServerProfile {
+  /// The Mongo ID of the profile.
+  /// This field is de/serialized from/to JSON as
+  /// `{ "_id": { "$oid": "..." }, ...(rest of serialized ServerProfile) }`
+  #[serde(
+    default,
+    rename = "_id",
+    skip_serializing_if = "String::is_empty",
+    with = "bson::serde_helpers::hex_string_as_object_id"
+  )]
+  pub id: MongoId,
+
+  /// The name of the profile.
+  #[cfg_attr(feature = "mongo", unique_index)]
+  pub name: String,
+
+  /// A description of the profile.
+  #[serde(default)]
+  pub description: String,
+
+  /// The config defaults inherited by the Servers.
+  #[serde(default)]
+  pub config: ServerProfileConfig,
+}
+
+/// The Server config fields which can be inherited from a [ServerProfile].
+/// Only the fields which are set are applied.
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, PartialEq,
+)]
+pub struct ServerProfileConfig {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub auto_prune: Option<bool>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub send_unreachable_alerts: Option<bool>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub send_cpu_alerts: Option<bool>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub send_mem_alerts: Option<bool>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub send_disk_alerts: Option<bool>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub send_network_alerts: Option<bool>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub send_disk_io_alerts: Option<bool>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub send_version_mismatch_alerts: Option<bool>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub cpu_warning: Option<f32>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub cpu_critical: Option<f32>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub mem_warning: Option<f64>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub mem_critical: Option<f64>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub disk_warning: Option<f64>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub disk_critical: Option<f64>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub disk_thresholds: Option<Vec<DiskThreshold>>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub network_warning: Option<f64>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub network_critical: Option<f64>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub disk_io_warning: Option<f64>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub disk_io_critical: Option<f64>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub maintenance_windows: Option<Vec<MaintenanceWindow>>,
+}
+
+impl ServerProfileConfig {
+  /// The names of the Server config fields a profile can set,
+  /// and which can be listed in `profile_overrides`.
+  pub const FIELDS: &[&str] = &[
+    "auto_prune",
+    "send_unreachable_alerts",
+    "send_cpu_alerts",
+    "send_mem_alerts",
+    "send_disk_alerts",
+    "send_network_alerts",
+    "send_disk_io_alerts",
+    "send_version_mismatch_alerts",
+    "cpu_warning",
+    "cpu_critical",
+    "mem_warning",
+    "mem_critical",
+    "disk_warning",
+    "disk_critical",
+    "disk_thresholds",
+    "network_warning",
+    "network_critical",
+    "disk_io_warning",
+    "disk_io_critical",
+    "maintenance_windows",
+  ];
+
+  /// Applies the fields set on the profile onto the Server config,
+  /// skipping any the Server lists in `profile_overrides`.
+  pub fn apply(&self, config: &mut ServerConfig) {
+    let overrides = &config.profile_overrides;
+    let inherit = |field: &str| !overrides.iter().any(|f| f == field);
+    if let Some(value) = self.auto_prune
+      && inherit("auto_prune")
+    {
+      config.auto_prune = value;
+    }
+    if let Some(value) = self.send_unreachable_alerts
+      && inherit("send_unreachable_alerts")
+    {
+      config.send_unreachable_alerts = value;
+    }
+    if let Some(value) = self.send_cpu_alerts
+      && inherit("send_cpu_alerts")
+    {
+      config.send_cpu_alerts = value;
+    }
+    if let Some(value) = self.send_mem_alerts
+      && inherit("send_mem_alerts")
+    {
+      config.send_mem_alerts = value;
+    }
+    if let Some(value) = self.send_disk_alerts
+      && inherit("send_disk_alerts")
+    {
+      config.send_disk_alerts = value;
+    }
+    if let Some(value) = self.send_network_alerts
+      && inherit("send_network_alerts")
+    {
+      config.send_network_alerts = value;
+    }
+    if let Some(value) = self.send_disk_io_alerts
+      && inherit("send_disk_io_alerts")
+    {
+      config.send_disk_io_alerts = value;
+    }
+    if let Some(value) = self.send_version_mismatch_alerts
+      && inherit("send_version_mismatch_alerts")
+    {
+      config.send_version_mismatch_alerts = value;
+    }
+    if let Some(value) = self.cpu_warning
+      && inherit("cpu_warning")
+    {
+      config.cpu_warning = value;
+    }
+    if let Some(value) = self.cpu_critical
+      && inherit("cpu_critical")
+    {
+      config.cpu_critical = value;
+    }
+    if let Some(value) = self.mem_warning
+      && inherit("mem_warning")
+    {
+      config.mem_warning = value;
+    }
+    if let Some(value) = self.mem_critical
+      && inherit("mem_critical")
+    {
+      config.mem_critical = value;
+    }
+    if let Some(value) = self.disk_warning
+      && inherit("disk_warning")
+    {
+      config.disk_warning = value;
+    }
+    if let Some(value) = self.disk_critical
+      && inherit("disk_critical")
+    {
+      config.disk_critical = value;
+    }
+    if let Some(value) = &self.disk_thresholds
+      && inherit("disk_thresholds")
+    {
+      config.disk_thresholds = value.clone();
+    }
+    if let Some(value) = self.network_warning
+      && inherit("network_warning")
+    {
+      config.network_warning = value;
+    }
+    if let Some(value) = self.network_critical
+      && inherit("network_critical")
+    {
+      config.network_critical = value;
+    }
+    if let Some(value) = self.disk_io_warning
+      && inherit("disk_io_warning")
+    {
+      config.disk_io_warning = value;
+    }
+    if let Some(value) = self.disk_io_critical
+      && inherit("disk_io_critical")
+    {
+      config.disk_io_critical = value;
+    }
+    if let Some(value) = &self.maintenance_windows
+      && inherit("maintenance_windows")
+    {
+      config.maintenance_windows = value.clone();
+    }
+  }
+}
@@ -0,0 +1,72 @@
+use derive_variants::ExtractVariant;
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use super::{
+  I64, MongoId, ResourceTarget,
+  alert::{Alert, AlertDataVariant},
+};
+
+/// Mutes the alerts matching the targets and alert types
+/// between `start_ts` and `end_ts`, across all Alerters.
+///
+/// Silenced alerts are still recorded, they are just not sent.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(
+  feature = "mongo",
+  derive(mongo_indexed::derive::MongoIndexed)
+)]
+pub struct AlertSilence {
+  /// The Mongo ID of the silence.
+  /// This field is de/serialized from/to JSON as
+  /// `{ "_id": { "$oid": "..." }, ...(rest of serialized AlertSilence) }`
+  #[serde(
+    default,
+    rename = "_id",
+    skip_serializing_if = "String::is_empty",
+    with = "bson::serde_helpers::hex_string_as_object_id"
+  )]
+  pub id: MongoId,
+
+  /// Only silence alerts on these targets.
+  /// If empty, alerts on all targets are silenced.
+  #[serde(default)]
+  pub targets: Vec<ResourceTarget>,
+
+  /// Only silence these alert types.
+  /// If empty, all alert types are silenced.
+  #[serde(default)]
+  pub alert_types: Vec<AlertDataVariant>,
+
+  /// Unix timestamp in milliseconds the silence starts.
+  #[cfg_attr(feature = "mongo", index)]
+  pub start_ts: I64,
+
+  /// Unix timestamp in milliseconds the silence ends.
+  #[cfg_attr(feature = "mongo", index)]
+  pub end_ts: I64,
+
+  /// Why the alerts are silenced.
+  #[serde(default)]
+  pub comment: String,
+
+  /// The id of the user who created the silence.
+  #[serde(default)]
+  pub created_by: String,
+}
+
+impl AlertSilence {
+  /// Whether the silence is active at `ts`.
+  pub fn active(&self, ts: I64) -> bool {
+    self.start_ts <= ts && ts < self.end_ts
+  }
+
+  /// Whether the silence mutes the alert.
+  /// Does not check if the silence is active.
+  pub fn matches(&self, alert: &Alert) -> bool {
+    (self.targets.is_empty() || self.targets.contains(&alert.target))
+      && (self.alert_types.is_empty()
+        || self.alert_types.contains(&alert.data.extract_variant()))
+  }
+}
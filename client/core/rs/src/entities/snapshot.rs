@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use super::{I64, MongoId};
+
+/// A snapshot of the Komodo state at a point in time.
+///
+/// Contains the resources, variables, and user groups
+/// in the same TOML format used by Resource Syncs,
+/// so two snapshots can be compared with [DiffSnapshots][crate::api::read::DiffSnapshots].
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(
+  feature = "mongo",
+  derive(mongo_indexed::derive::MongoIndexed)
+)]
+pub struct Snapshot {
+  /// The Mongo ID of the snapshot.
+  /// This field is de/serialized from/to JSON as
+  /// `{ "_id": { "$oid": "..." }, ...(rest of serialized Snapshot) }`
+  #[serde(
+    default,
+    rename = "_id",
+    skip_serializing_if = "String::is_empty",
+    with = "bson::serde_helpers::hex_string_as_object_id"
+  )]
+  pub id: MongoId,
+
+  /// A name for the snapshot, eg. `before-sync`.
+  #[serde(default)]
+  pub name: String,
+
+  /// A description for the snapshot.
+  #[serde(default, skip_serializing_if = "String::is_empty")]
+  pub description: String,
+
+  /// Unix timestamp in milliseconds the snapshot was taken.
+  #[cfg_attr(feature = "mongo", index)]
+  pub ts: I64,
+
+  /// The id of the user who took the snapshot.
+  #[serde(default)]
+  pub created_by: String,
+
+  /// The state at `ts` in TOML.
+  #[serde(default)]
+  pub toml: String,
+}
+
+/// A snapshot without the TOML contents.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SnapshotListItem {
+  /// The snapshot id
+  pub id: String,
+  /// The snapshot name
+  pub name: String,
+  /// The snapshot description
+  pub description: String,
+  /// Unix timestamp in milliseconds the snapshot was taken.
+  pub ts: I64,
+  /// The id of the user who took the snapshot.
+  pub created_by: String,
+}
+
+impl From<Snapshot> for SnapshotListItem {
+  fn from(snapshot: Snapshot) -> Self {
+    Self {
+      id: snapshot.id,
+      name: snapshot.name,
+      description: snapshot.description,
+      ts: snapshot.ts,
+      created_by: snapshot.created_by,
+    }
+  }
+}
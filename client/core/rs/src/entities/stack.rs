@@ -24,9 +24,12 @@ use crate::{
 };
 
 use super::{
-  FileContents, SystemCommand,
+  AutoStopMode, FileContents, I64, MaintenanceWindow, ScheduleFormat,
+  SystemCommand,
   docker::container::ContainerListItem,
+  log_forwarding::LogForwardingConfig,
   resource::{Resource, ResourceListItem, ResourceQuery},
+  update::ExecutionMetrics,
 };
 
 #[typeshare]
@@ -117,7 +120,12 @@ pub struct StackListItemInfo {
   /// Whether stack is using files on host mode
   pub files_on_host: bool,
   /// Whether stack has file contents defined.
+  /// Includes contents moved to the file storage.
   pub file_contents: bool,
+  /// Whether any running containers no longer match
+  /// the deployed compose file.
+  #[serde(default)]
+  pub runtime_drift: bool,
   /// Linked repo, if one is attached.
   pub linked_repo: String,
   /// The git provider domain
@@ -147,6 +155,12 @@ pub struct StackListItemInfo {
   pub deployed_hash: Option<String>,
   /// Latest short commit hash, or null. Only for repo based stacks
   pub latest_hash: Option<String>,
+  /// Whether the stack was manually started / stopped
+  /// against its `uptime_hours`.
+  pub uptime_override: bool,
+  /// Deploy metrics over the last 30 days.
+  #[serde(default)]
+  pub metrics: ExecutionMetrics,
 }
 
 #[typeshare]
@@ -173,6 +187,7 @@ pub struct StackServiceWithUpdate {
   Deserialize,
   Display,
 )]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 // Do this one snake_case in line with DeploymentState.
 // Also in line with docker terminology.
 #[serde(rename_all = "snake_case")]
@@ -194,7 +209,8 @@ pub enum StackState {
   Dead,
   /// All containers are removing
   Removing,
-  /// The containers are in a mix of states
+  /// The containers are in a mix of states,
+  /// or a container healthcheck is failing
   Unhealthy,
   /// The stack is not deployed
   Down,
@@ -217,6 +233,11 @@ pub struct StackInfo {
   /// to ensure control is maintained after changing the project name (there is no rename compose project api).
   pub deployed_project_name: Option<String>,
 
+  /// The active color of a blue / green Stack.
+  /// This is updated whenever a blue / green deploy succeeds.
+  #[serde(default)]
+  pub blue_green_color: Option<BlueGreenColor>,
+
   /// Deployed short commit hash, or null. Only for repo based stacks.
   pub deployed_hash: Option<String>,
   /// Deployed commit message, or null. Only for repo based stacks
@@ -246,6 +267,83 @@ pub struct StackInfo {
   pub latest_hash: Option<String>,
   /// Latest commit message, or null
   pub latest_message: Option<String>,
+
+  /// If the UI defined file contents are stored outside the database,
+  /// where to find them. The `file_contents` in the config are left empty.
+  #[serde(default)]
+  pub stored_file_contents: Option<StoredFileContents>,
+}
+
+/// Stack file contents stored outside the Stack document.
+#[typeshare]
+#[derive(
+  Debug, Clone, Default, PartialEq, Serialize, Deserialize,
+)]
+pub struct StoredFileContents {
+  /// The storage holding the contents.
+  pub storage: StackFileStorage,
+  /// The key of the contents in the storage.
+  pub key: String,
+  /// The size of the contents in bytes.
+  pub size: I64,
+  /// The sha256 of the contents, in hex.
+  pub sha256: String,
+}
+
+/// Where Core stores Stack file contents
+/// too large to keep in the database.
+#[typeshare]
+#[derive(
+  Debug,
+  Clone,
+  Copy,
+  Default,
+  PartialEq,
+  Eq,
+  Serialize,
+  Deserialize,
+  Display,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum StackFileStorage {
+  /// Keep the contents in the Stack document.
+  #[default]
+  Database,
+  /// Files in Core `stack_file_directory`.
+  Filesystem,
+  /// Objects in Core `stack_file_s3_bucket`.
+  S3,
+  /// MongoDB GridFS, in the Komodo database.
+  GridFs,
+}
+
+/// The color of the active project of a blue / green Stack.
+#[typeshare]
+#[derive(
+  Debug,
+  Clone,
+  Copy,
+  PartialEq,
+  Eq,
+  Serialize,
+  Deserialize,
+  Display,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum BlueGreenColor {
+  Blue,
+  Green,
+}
+
+impl BlueGreenColor {
+  pub fn next(self) -> BlueGreenColor {
+    match self {
+      BlueGreenColor::Blue => BlueGreenColor::Green,
+      BlueGreenColor::Green => BlueGreenColor::Blue,
+    }
+  }
 }
 
 #[typeshare(serialized_as = "Partial<StackConfig>")]
@@ -254,6 +352,7 @@ pub type _PartialStackConfig = PartialStackConfig;
 /// The compose file configuration.
 #[typeshare]
 #[derive(Debug, Clone, Serialize, Deserialize, Builder, Partial)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[partial_derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[partial(skip_serializing_none, from, diff)]
 pub struct StackConfig {
@@ -321,6 +420,99 @@ pub struct StackConfig {
   #[builder(default)]
   pub destroy_before_deploy: bool,
 
+  /// Deploy the Stack blue / green. A full deploy brings up the
+  /// new project as `{project_name}-blue` or `{project_name}-green`,
+  /// waits for the services to be healthy, moves the network alias
+  /// over, and then takes down the previous project.
+  ///
+  /// Services should not use `container_name` or publish host ports,
+  /// as both projects run side by side during the deploy.
+  #[serde(default)]
+  #[builder(default)]
+  pub blue_green: bool,
+
+  /// The service which receives traffic from the reverse proxy.
+  /// If empty, no network alias is moved.
+  #[serde(default)]
+  #[builder(default)]
+  pub blue_green_service: String,
+
+  /// The network shared with the reverse proxy.
+  /// Komodo connects the service to it with `blue_green_alias`,
+  /// so it should not be attached to the service in the compose file.
+  #[serde(default)]
+  #[builder(default)]
+  pub blue_green_network: String,
+
+  /// The network alias the reverse proxy routes to.
+  #[serde(default)]
+  #[builder(default)]
+  pub blue_green_alias: String,
+
+  /// How long to wait for the new project to be healthy,
+  /// in seconds, before it is taken down and the deploy fails.
+  #[serde(default = "default_blue_green_timeout")]
+  #[builder(default = "default_blue_green_timeout()")]
+  #[partial_default(default_blue_green_timeout())]
+  pub blue_green_timeout: I64,
+
+  /// Whether to automatically stop the Stack after a period of
+  /// inactivity (`auto_stop_after`) or on a schedule (`auto_stop_schedule`).
+  /// Useful for dev / preview environments.
+  #[serde(default)]
+  #[builder(default)]
+  pub auto_stop_enabled: bool,
+
+  /// Whether auto stop will stop or destroy the Stack.
+  #[serde(default)]
+  #[builder(default)]
+  pub auto_stop_mode: AutoStopMode,
+
+  /// Stop the Stack after this many minutes without any
+  /// activity. Activity is any Update on the Stack,
+  /// such as a deploy or restart.
+  /// 0 disables inactivity based auto stop.
+  #[serde(default)]
+  #[builder(default)]
+  pub auto_stop_after: I64,
+
+  /// Choose whether to specify the auto stop schedule as regular CRON,
+  /// or using the english to CRON parser.
+  #[serde(default)]
+  #[builder(default)]
+  pub auto_stop_schedule_format: ScheduleFormat,
+
+  /// Optionally stop the Stack at fixed times, for example
+  /// `at 2am every day`. Uses the same format as Procedure schedules.
+  #[serde(default)]
+  #[builder(default)]
+  pub auto_stop_schedule: String,
+
+  /// Optional. A TZ Identifier. If not provided, will use Core local timezone.
+  /// https://en.wikipedia.org/wiki/List_of_tz_database_time_zones.
+  #[serde(default)]
+  #[builder(default)]
+  pub auto_stop_schedule_timezone: String,
+
+  /// Send an AutoStopPending alert this many minutes
+  /// before the Stack is auto stopped. 0 disables the alert.
+  /// Default: 15
+  #[serde(default = "default_auto_stop_warning")]
+  #[builder(default = "default_auto_stop_warning()")]
+  #[partial_default(default_auto_stop_warning())]
+  pub auto_stop_warning: I64,
+
+  /// The hours the Stack should be up, for example
+  /// weekdays 08:00 - 19:00. The Core scheduler stops the Stack
+  /// when a window ends, and starts it again when the next one begins.
+  /// If empty, uptime is not managed.
+  ///
+  /// Manually starting or stopping the Stack overrides
+  /// the schedule until the next window begins or ends.
+  #[serde(default)]
+  #[builder(default)]
+  pub uptime_hours: Vec<MaintenanceWindow>,
+
   /// Whether to skip secret interpolation into the stack environment variables.
   #[serde(default)]
   #[builder(default)]
@@ -408,6 +600,20 @@ pub struct StackConfig {
   #[builder(default)]
   pub files_on_host: bool,
 
+  /// Source the compose files from an OCI artifact,
+  /// eg `ghcr.io/org/app-compose:1.2`.
+  /// Periphery pulls the artifact at deploy time and verifies each layer by digest.
+  /// Pin a specific version with `@sha256:...`.
+  #[serde(default)]
+  #[builder(default)]
+  pub oci_artifact: String,
+
+  /// The registry account used to pull the OCI artifact.
+  /// Passing empty string can only pull public artifacts.
+  #[serde(default)]
+  #[builder(default)]
+  pub oci_account: String,
+
   /// Directory to change to (`cd`) before running `docker compose up -d`.
   #[serde(default)]
   #[builder(default)]
@@ -462,6 +668,33 @@ pub struct StackConfig {
   #[partial_default(default_send_alerts())]
   pub send_alerts: bool,
 
+  /// Only send StackStateChange alerts when the stack
+  /// enters one of these states, eg. `down`.
+  /// Empty alerts on every state change.
+  #[serde(default)]
+  #[builder(default)]
+  pub alert_states: Vec<StackState>,
+
+  /// Whether to send a StackDrifted alert when the latest files
+  /// no longer match the deployed files, eg. after a new commit.
+  /// Only applies to files on host, repo, and OCI based stacks.
+  #[serde(default)]
+  #[builder(default)]
+  pub send_drift_alerts: bool,
+
+  /// Whether to send a StackRuntimeDrift alert when the running
+  /// containers no longer match the deployed compose file,
+  /// eg. after manual `docker` changes on the host.
+  #[serde(default)]
+  #[builder(default)]
+  pub send_runtime_drift_alerts: bool,
+
+  /// Forward the container logs from Periphery to Loki,
+  /// syslog, or an S3 bucket.
+  #[serde(default)]
+  #[builder(default)]
+  pub log_forwarding: LogForwardingConfig,
+
   /// Used with `registry_account` to login to a registry before docker compose up.
   #[serde(default)]
   #[builder(default)]
@@ -503,6 +736,25 @@ pub struct StackConfig {
   #[builder(default)]
   pub build_extra_args: Vec<String>,
 
+  /// DNS servers used by the Stack service containers, eg `10.0.0.53`.
+  /// If empty, uses any matching Core `container_dns_defaults`.
+  #[serde(default, deserialize_with = "string_list_deserializer")]
+  #[partial_attr(serde(
+    default,
+    deserialize_with = "option_string_list_deserializer"
+  ))]
+  #[builder(default)]
+  pub dns: Vec<String>,
+
+  /// Extra `hostname:ip` entries added to the Stack service containers `/etc/hosts`.
+  #[serde(default, deserialize_with = "string_list_deserializer")]
+  #[partial_attr(serde(
+    default,
+    deserialize_with = "option_string_list_deserializer"
+  ))]
+  #[builder(default)]
+  pub extra_hosts: Vec<String>,
+
   /// Ignore certain services declared in the compose file when checking
   /// the stack status. For example, an init service might be exited, but the
   /// stack should be healthy. This init service should be in `ignore_services`
@@ -514,10 +766,50 @@ pub struct StackConfig {
   #[builder(default)]
   pub ignore_services: Vec<String>,
 
+  /// The desired number of replicas for specific services.
+  /// Passed to `docker compose up` as `--scale {service}={replicas}`.
+  /// Services not listed use the replicas declared in the compose file.
+  /// If `send_alerts` is enabled, an alert is sent when fewer replicas are running.
+  #[serde(default)]
+  #[builder(default)]
+  pub replicas: Vec<StackServiceReplicas>,
+
+  /// Application level health checks for specific services.
+  /// Core probes each url after deploy and every minute,
+  /// and the stack is Unhealthy while any probe fails.
+  #[serde(default)]
+  #[builder(default)]
+  pub health_checks: Vec<StackServiceHealthCheck>,
+
+  /// Have Core fetch the remote (http / https) compose `include` files,
+  /// rather than the Server at deploy time. Core caches the latest contents,
+  /// and deploys using the cached contents if the url can't be reached.
+  /// Only applies to Stacks with the compose file defined in the UI.
+  #[serde(default)]
+  #[builder(default)]
+  pub cache_remote_includes: bool,
+
+  /// Pin remote include urls to the sha256 checksum of their contents.
+  /// The deploy fails if neither the fetched or cached contents match.
+  #[serde(default)]
+  #[builder(default)]
+  pub include_checksums: Vec<StackIncludeChecksum>,
+
+  /// Compose files fetched by Core, and passed to compose after
+  /// `file_paths` with `-f`, in order. Later files override earlier ones
+  /// by the standard compose merge rules, eg. a shared base compose file
+  /// with per environment overrides. Urls are also pinned by `include_checksums`.
+  #[serde(default)]
+  #[builder(default)]
+  pub compose_overrides: Vec<StackComposeOverride>,
+
   /// The contents of the file directly, for management in the UI.
   /// If this is empty, it will fall back to checking git config for
   /// repo based compose file.
   /// Supports variable / secret interpolation.
+  /// Contents larger than Core `stack_file_max_inline_bytes`
+  /// are moved to the configured `stack_file_storage`,
+  /// and loaded back in when the Stack is read.
   #[serde(default, deserialize_with = "file_contents_deserializer")]
   #[partial_attr(serde(
     default,
@@ -549,6 +841,15 @@ impl StackConfig {
     environment_vars_from_str(&self.environment)
       .context("Invalid environment")
   }
+
+  /// Get the configured replicas for the service, if any.
+  pub fn service_replicas(&self, service: &str) -> Option<I64> {
+    self
+      .replicas
+      .iter()
+      .find(|r| r.service == service)
+      .map(|r| r.replicas)
+  }
 }
 
 fn default_env_file_path() -> String {
@@ -579,6 +880,14 @@ fn default_send_alerts() -> bool {
   true
 }
 
+fn default_auto_stop_warning() -> i64 {
+  15
+}
+
+fn default_blue_green_timeout() -> i64 {
+  120
+}
+
 impl Default for StackConfig {
   fn default() -> Self {
     Self {
@@ -587,6 +896,8 @@ impl Default for StackConfig {
       run_directory: Default::default(),
       file_paths: Default::default(),
       files_on_host: Default::default(),
+      oci_artifact: Default::default(),
+      oci_account: Default::default(),
       registry_provider: Default::default(),
       registry_account: Default::default(),
       file_contents: Default::default(),
@@ -595,6 +906,11 @@ impl Default for StackConfig {
       auto_update: Default::default(),
       auto_update_all_services: Default::default(),
       ignore_services: Default::default(),
+      replicas: Default::default(),
+      health_checks: Default::default(),
+      cache_remote_includes: Default::default(),
+      include_checksums: Default::default(),
+      compose_overrides: Default::default(),
       pre_deploy: Default::default(),
       post_deploy: Default::default(),
       extra_args: Default::default(),
@@ -604,7 +920,22 @@ impl Default for StackConfig {
       config_files: Default::default(),
       run_build: Default::default(),
       destroy_before_deploy: Default::default(),
+      blue_green: Default::default(),
+      blue_green_service: Default::default(),
+      blue_green_network: Default::default(),
+      blue_green_alias: Default::default(),
+      blue_green_timeout: default_blue_green_timeout(),
+      auto_stop_enabled: Default::default(),
+      auto_stop_mode: Default::default(),
+      auto_stop_after: Default::default(),
+      auto_stop_schedule_format: Default::default(),
+      auto_stop_schedule: Default::default(),
+      auto_stop_schedule_timezone: Default::default(),
+      auto_stop_warning: default_auto_stop_warning(),
+      uptime_hours: Default::default(),
       build_extra_args: Default::default(),
+      dns: Default::default(),
+      extra_hosts: Default::default(),
       skip_secret_interp: Default::default(),
       linked_repo: Default::default(),
       git_provider: default_git_provider(),
@@ -619,11 +950,100 @@ impl Default for StackConfig {
       webhook_secret: Default::default(),
       webhook_force_deploy: Default::default(),
       send_alerts: default_send_alerts(),
+      alert_states: Default::default(),
+      send_drift_alerts: Default::default(),
+      send_runtime_drift_alerts: Default::default(),
+      log_forwarding: Default::default(),
       links: Default::default(),
     }
   }
 }
 
+/// The desired number of replicas for a Stack service.
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct StackServiceReplicas {
+  /// The service name
+  pub service: String,
+  /// The desired number of replicas
+  pub replicas: I64,
+}
+
+/// An HTTP health check for a Stack service.
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct StackServiceHealthCheck {
+  /// The service name
+  pub service: String,
+  /// The full url to probe, eg `http://10.0.0.2:8080/health`.
+  /// If empty, the url is built from the Server `external_address`,
+  /// the host port published for `port`, and `path`.
+  #[serde(default)]
+  pub url: String,
+  /// The container port the health endpoint listens on.
+  /// Used when `url` is empty.
+  #[serde(default)]
+  pub port: u16,
+  /// The path of the health endpoint, eg `/health`.
+  /// Used when `url` is empty.
+  #[serde(default)]
+  pub path: String,
+}
+
+/// A checksum pin for a remote compose include.
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct StackIncludeChecksum {
+  /// The include url, as written in the compose file.
+  pub url: String,
+  /// The expected sha256 checksum of the contents, in hex.
+  pub sha256: String,
+}
+
+/// A compose file from outside the Stack files,
+/// given either as a `url` or a `repo` and `path`.
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct StackComposeOverride {
+  /// A http / https url to fetch the compose file from.
+  #[serde(default)]
+  pub url: String,
+  /// The Komodo Repo (id or name) to read the compose file from.
+  #[serde(default)]
+  pub repo: String,
+  /// The path to the compose file in the repo.
+  #[serde(default)]
+  pub path: String,
+}
+
+/// The result of the latest health check probe for a Stack service.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct StackServiceHealth {
+  /// Whether the probe got a success (2xx / 3xx) response.
+  pub healthy: bool,
+  /// The url which was probed.
+  pub url: String,
+  /// The response status code, if a response was received.
+  pub status: Option<u16>,
+  /// The error, if the probe failed.
+  pub error: Option<String>,
+  /// Unix timestamp in milliseconds of the probe.
+  pub ts: I64,
+}
+
 #[typeshare]
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ComposeProject {
@@ -673,6 +1093,31 @@ pub struct StackService {
   pub container: Option<ContainerListItem>,
   /// Whether there is an update available for this services image.
   pub update_available: bool,
+  /// The desired replicas configured for the service, if any.
+  #[serde(default)]
+  pub replicas: Option<I64>,
+  /// The number of running containers for the service.
+  #[serde(default)]
+  pub running_replicas: I64,
+  /// The latest health check result, if the service has a health check.
+  #[serde(default)]
+  pub health: Option<StackServiceHealth>,
+  /// How the running containers differ from the deployed compose file,
+  /// as of the latest drift check. Empty if they match.
+  #[serde(default)]
+  pub drift: Vec<String>,
+}
+
+/// How the containers of a Stack service differ
+/// from the service defined in the deployed compose file.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct StackServiceDrift {
+  /// The service name
+  pub service: String,
+  /// The differences, eg. `image`, or `environment: FOO, BAR`.
+  /// Environment values are never included.
+  pub differences: Vec<String>,
 }
 
 #[typeshare]
@@ -791,6 +1236,7 @@ pub struct StackRemoteFileContents {
   Serialize,
   Deserialize,
 )]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum StackFileRequires {
   /// Diff requires service redeploy.
   #[serde(alias = "redeploy")]
@@ -807,6 +1253,7 @@ pub enum StackFileRequires {
 /// Configure additional file dependencies of the Stack.
 #[typeshare]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct StackFileDependency {
   /// Specify the file
   pub path: String,
@@ -1,5 +1,6 @@
 use std::path::PathBuf;
 
+use async_timing_util::get_timelength_in_ms;
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
@@ -25,6 +26,9 @@ pub struct SystemInformation {
   pub terminals_disabled: bool,
   /// Whether container exec is disabled on this Periphery server
   pub container_exec_disabled: bool,
+  /// The Docker (or Podman) engine version
+  #[serde(default)]
+  pub docker_version: Option<String>,
 }
 
 /// System stats stored on the database.
@@ -64,9 +68,15 @@ pub struct SystemStatsRecord {
   /// Total network egress in bytes
   #[serde(default)]
   pub network_egress_bytes: f64,
-  // /// Network usage by interface name (ingress, egress in bytes)
-  // #[serde(default)]
-  // pub network_usage_interface: Vec<SingleNetworkInterfaceUsage>, // interface -> (ingress, egress)
+  /// Network usage by interface name (ingress, egress in bytes)
+  #[serde(default)]
+  pub network_usage_interface: Vec<SingleNetworkInterfaceUsage>,
+  /// Total disk read in bytes
+  #[serde(default)]
+  pub disk_read_bytes: f64,
+  /// Total disk write in bytes
+  #[serde(default)]
+  pub disk_write_bytes: f64,
 }
 
 /// Realtime system stats data.
@@ -96,9 +106,15 @@ pub struct SystemStats {
   /// Network egress usage in MB
   #[serde(default)]
   pub network_egress_bytes: f64,
-  // /// Network usage by interface name (ingress, egress in bytes)
-  // #[serde(default)]
-  // pub network_usage_interface: Vec<SingleNetworkInterfaceUsage>, // interface -> (ingress, egress)
+  /// Network usage by interface name (ingress, egress in bytes)
+  #[serde(default)]
+  pub network_usage_interface: Vec<SingleNetworkInterfaceUsage>,
+  /// Disk read across all disks in bytes since the last poll
+  #[serde(default)]
+  pub disk_read_bytes: f64,
+  /// Disk write across all disks in bytes since the last poll
+  #[serde(default)]
+  pub disk_write_bytes: f64,
   // metadata
   /// The rate the system stats are being polled from the system
   pub polling_rate: Timelength,
@@ -120,6 +136,12 @@ pub struct SingleDiskUsage {
   pub used_gb: f64,
   /// Total size of the disk in GB
   pub total_gb: f64,
+  /// Disk read in bytes since the last poll
+  #[serde(default)]
+  pub read_bytes: f64,
+  /// Disk write in bytes since the last poll
+  #[serde(default)]
+  pub write_bytes: f64,
 }
 
 /// Info for network interface usage.
@@ -134,6 +156,23 @@ pub struct SingleNetworkInterfaceUsage {
   pub egress_bytes: f64,
 }
 
+/// Converts bytes since the last poll into MB per second.
+pub fn bytes_to_mb_per_sec(
+  bytes: f64,
+  polling_rate: Timelength,
+) -> f64 {
+  let Ok(polling_rate) = polling_rate.try_into() else {
+    return 0.0;
+  };
+  let ms = get_timelength_in_ms(polling_rate);
+  if ms == 0 {
+    return 0.0;
+  }
+  bytes / BYTES_PER_MB / (ms as f64 / 1000.0)
+}
+
+const BYTES_PER_MB: f64 = 1048576.0;
+
 pub fn sum_disk_usage(disks: &[SingleDiskUsage]) -> TotalDiskUsage {
   disks
     .iter()
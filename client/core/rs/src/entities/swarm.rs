@@ -0,0 +1,319 @@
+use bson::{Document, doc};
+use derive_builder::Builder;
+use derive_default_builder::DefaultBuilder;
+use partial_derive2::Partial;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+use typeshare::typeshare;
+
+use crate::deserializers::{
+  conversions_deserializer, env_vars_deserializer,
+  file_contents_deserializer, labels_deserializer,
+  option_conversions_deserializer, option_env_vars_deserializer,
+  option_file_contents_deserializer, option_labels_deserializer,
+  option_string_list_deserializer, string_list_deserializer,
+};
+
+use super::{
+  I64,
+  resource::{Resource, ResourceListItem, ResourceQuery},
+};
+
+#[typeshare]
+pub type SwarmService = Resource<SwarmServiceConfig, ()>;
+
+#[typeshare]
+pub type SwarmServiceListItem =
+  ResourceListItem<SwarmServiceListItemInfo>;
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SwarmServiceListItemInfo {
+  /// The state of the swarm services.
+  pub state: SwarmServiceState,
+  /// The Swarm manager Server the services are deployed with.
+  pub server_id: String,
+  /// The image attached to the swarm service.
+  /// Empty when deploying a compose file.
+  pub image: String,
+  /// The number of running tasks across all services.
+  pub running: I64,
+  /// The number of desired tasks across all services.
+  pub desired: I64,
+}
+
+#[typeshare(serialized_as = "Partial<SwarmServiceConfig>")]
+pub type _PartialSwarmServiceConfig = PartialSwarmServiceConfig;
+
+/// The SwarmService config.
+///
+/// The services are always deployed with `docker stack deploy`,
+/// using the resource name as the stack name. If `file_contents` is empty,
+/// a compose file with a single service is generated from `image` and
+/// the other service fields.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Builder, Partial)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[partial_derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[partial(skip_serializing_none, from, diff)]
+pub struct SwarmServiceConfig {
+  /// The id of the Server the services are deployed with.
+  /// This Server must be a Swarm manager node.
+  #[serde(default, alias = "server")]
+  #[partial_attr(serde(alias = "server"))]
+  #[builder(default)]
+  pub server_id: String,
+
+  /// Configure quick links that are displayed in the resource header
+  #[serde(default, deserialize_with = "string_list_deserializer")]
+  #[partial_attr(serde(
+    default,
+    deserialize_with = "option_string_list_deserializer"
+  ))]
+  #[builder(default)]
+  pub links: Vec<String>,
+
+  /// Whether to skip secret interpolation into the compose file / service config.
+  #[serde(default)]
+  #[builder(default)]
+  pub skip_secret_interp: bool,
+
+  /// A compose file deployed with `docker stack deploy`.
+  /// If provided, the service fields below (image, replicas, ...) are ignored.
+  /// Supports variable / secret interpolation.
+  #[serde(default, deserialize_with = "file_contents_deserializer")]
+  #[partial_attr(serde(
+    default,
+    deserialize_with = "option_file_contents_deserializer"
+  ))]
+  #[builder(default)]
+  pub file_contents: String,
+
+  /// The image deployed as a single service,
+  /// when no `file_contents` are provided.
+  #[serde(default)]
+  #[builder(default)]
+  pub image: String,
+
+  /// Configure the account used to pull the image from the registry.
+  /// Used with `docker login`, and passed to the Swarm nodes
+  /// with `--with-registry-auth`.
+  /// The registry domain is taken from `image`, so this
+  /// is not used with `file_contents`.
+  #[serde(default)]
+  #[builder(default)]
+  pub image_registry_account: String,
+
+  /// Whether the service runs a set number of replicas,
+  /// or one task on every node.
+  #[serde(default)]
+  #[builder(default)]
+  pub mode: SwarmServiceMode,
+
+  /// The number of replicas for `Replicated` mode.
+  #[serde(default = "default_replicas")]
+  #[builder(default = "default_replicas()")]
+  #[partial_default(default_replicas())]
+  pub replicas: I64,
+
+  /// Replaces the command of the image. Empty is no command.
+  #[serde(default)]
+  #[builder(default)]
+  pub command: String,
+
+  /// The overlay networks the service is attached to.
+  /// They must already exist in the Swarm.
+  #[serde(default, deserialize_with = "string_list_deserializer")]
+  #[partial_attr(serde(
+    default,
+    deserialize_with = "option_string_list_deserializer"
+  ))]
+  #[builder(default)]
+  pub networks: Vec<String>,
+
+  /// The service port mapping.
+  /// Maps ports published on the Swarm routing mesh to ports on the service.
+  #[serde(default, deserialize_with = "conversions_deserializer")]
+  #[partial_attr(serde(
+    default,
+    deserialize_with = "option_conversions_deserializer"
+  ))]
+  #[builder(default)]
+  pub ports: String,
+
+  /// The environment variables passed to the service.
+  #[serde(default, deserialize_with = "env_vars_deserializer")]
+  #[partial_attr(serde(
+    default,
+    deserialize_with = "option_env_vars_deserializer"
+  ))]
+  #[builder(default)]
+  pub environment: String,
+
+  /// The docker labels given to the service.
+  #[serde(default, deserialize_with = "labels_deserializer")]
+  #[partial_attr(serde(
+    default,
+    deserialize_with = "option_labels_deserializer"
+  ))]
+  #[builder(default)]
+  pub labels: String,
+
+  /// Extra args which are interpolated into the `docker stack deploy` command.
+  #[serde(default, deserialize_with = "string_list_deserializer")]
+  #[partial_attr(serde(
+    default,
+    deserialize_with = "option_string_list_deserializer"
+  ))]
+  #[builder(default)]
+  pub extra_args: Vec<String>,
+}
+
+impl SwarmServiceConfig {
+  pub fn builder() -> SwarmServiceConfigBuilder {
+    SwarmServiceConfigBuilder::default()
+  }
+}
+
+fn default_replicas() -> I64 {
+  1
+}
+
+impl Default for SwarmServiceConfig {
+  fn default() -> Self {
+    Self {
+      server_id: Default::default(),
+      links: Default::default(),
+      skip_secret_interp: Default::default(),
+      file_contents: Default::default(),
+      image: Default::default(),
+      image_registry_account: Default::default(),
+      mode: Default::default(),
+      replicas: default_replicas(),
+      command: Default::default(),
+      networks: Default::default(),
+      ports: Default::default(),
+      environment: Default::default(),
+      labels: Default::default(),
+      extra_args: Default::default(),
+    }
+  }
+}
+
+/// The Swarm service mode.
+#[typeshare]
+#[derive(
+  Serialize,
+  Deserialize,
+  Debug,
+  PartialEq,
+  Hash,
+  Eq,
+  Clone,
+  Copy,
+  Default,
+  Display,
+  EnumString,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum SwarmServiceMode {
+  /// Run `replicas` tasks across the Swarm.
+  #[default]
+  Replicated,
+  /// Run one task on every node in the Swarm.
+  Global,
+}
+
+/// Variants de/serialized from/to snake_case.
+///
+/// Eg.
+/// - NotDeployed -> not_deployed
+/// - Running -> running
+#[typeshare]
+#[derive(
+  Debug,
+  Clone,
+  Copy,
+  PartialEq,
+  Eq,
+  Hash,
+  PartialOrd,
+  Ord,
+  Default,
+  Display,
+  EnumString,
+  Serialize,
+  Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum SwarmServiceState {
+  /// The services are currently being deployed
+  Deploying,
+  /// All services are running their desired tasks
+  Running,
+  /// Some services are running less than their desired tasks
+  Degraded,
+  /// All services are scaled to 0 tasks
+  Stopped,
+  /// There are no services deployed in the stack
+  NotDeployed,
+  /// Server not reachable for status
+  #[default]
+  Unknown,
+}
+
+/// A service in a Swarm stack, from `docker stack services`.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SwarmServiceSummary {
+  /// The service id
+  pub id: String,
+  /// The service name, `<stack>_<service>`.
+  pub name: String,
+  /// Either `replicated` or `global`
+  pub mode: String,
+  /// The image the service is running
+  pub image: String,
+  /// The published ports
+  pub ports: String,
+  /// The number of running tasks
+  pub running: I64,
+  /// The number of desired tasks
+  pub desired: I64,
+}
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct SwarmServiceActionState {
+  pub deploying: bool,
+  pub scaling: bool,
+  pub removing: bool,
+}
+
+#[typeshare]
+pub type SwarmServiceQuery =
+  ResourceQuery<SwarmServiceQuerySpecifics>;
+
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Default, DefaultBuilder,
+)]
+pub struct SwarmServiceQuerySpecifics {
+  /// Query only for SwarmServices on these Servers.
+  /// If empty, does not filter by Server.
+  /// Only accepts Server id (not name).
+  #[serde(default)]
+  pub server_ids: Vec<String>,
+}
+
+impl super::resource::AddFilters for SwarmServiceQuerySpecifics {
+  fn add_filters(&self, filters: &mut Document) {
+    if !self.server_ids.is_empty() {
+      filters
+        .insert("config.server_id", doc! { "$in": &self.server_ids });
+    }
+  }
+}
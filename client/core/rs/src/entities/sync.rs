@@ -29,6 +29,8 @@ pub struct ResourceSyncListItemInfo {
   pub files_on_host: bool,
   /// Whether sync has file contents defined.
   pub file_contents: bool,
+  /// The OCI artifact or `s3://bucket/prefix` source, if one is configured.
+  pub artifact_source: String,
   /// Whether sync has `managed` mode enabled.
   pub managed: bool,
   /// Resource paths to the files.
@@ -170,6 +172,7 @@ pub type _PartialResourceSyncConfig = PartialResourceSyncConfig;
 /// The sync configuration.
 #[typeshare]
 #[derive(Debug, Clone, Serialize, Deserialize, Builder, Partial)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[partial_derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[partial(skip_serializing_none, from, diff)]
 pub struct ResourceSyncConfig {
@@ -235,9 +238,46 @@ pub struct ResourceSyncConfig {
   #[builder(default)]
   pub files_on_host: bool,
 
+  /// Source the sync files from an OCI artifact,
+  /// eg. `ghcr.io/org/config:main`, as pushed by `oras push`.
+  /// Pin the artifact with a digest,
+  /// eg. `ghcr.io/org/config@sha256:...`.
+  #[serde(default)]
+  #[builder(default)]
+  pub oci_artifact: String,
+
+  /// The registry account used to pull private OCI artifacts.
+  /// Passing empty string can only pull public artifacts.
+  #[serde(default)]
+  #[builder(default)]
+  pub oci_account: String,
+
+  /// Source the sync files from the objects in this S3 bucket.
+  /// Uses the `aws` credentials in the core config.
+  #[serde(default)]
+  #[builder(default)]
+  pub s3_bucket: String,
+
+  /// Only use the objects under this prefix in the bucket.
+  #[serde(default)]
+  #[builder(default)]
+  pub s3_prefix: String,
+
+  /// The region of the bucket. Default: `us-east-1`
+  #[serde(default)]
+  #[builder(default)]
+  pub s3_region: String,
+
+  /// Use an S3 compatible endpoint, eg. `http://minio:9000` for MinIO.
+  /// Empty uses AWS S3.
+  #[serde(default)]
+  #[builder(default)]
+  pub s3_endpoint: String,
+
   /// The path of the resource file(s) to sync.
   ///  - If Files on Host, this is relative to the configured `sync_directory` in core config.
   ///  - If Git Repo based, this is relative to the root of the repo.
+  ///  - If OCI artifact or S3 based, this is relative to the artifact files or bucket prefix.
   /// Can be a specific file, or a directory containing multiple files / folders.
   /// See [https://komo.do/docs/sync-resources](https://komo.do/docs/sync-resources) for more information.
   #[serde(default, deserialize_with = "string_list_deserializer")]
@@ -322,6 +362,11 @@ impl ResourceSyncConfig {
       .count()
       == 0
   }
+
+  /// Whether the sync files come from an OCI artifact or S3 bucket.
+  pub fn artifact_source(&self) -> bool {
+    !self.oci_artifact.is_empty() || !self.s3_bucket.is_empty()
+  }
 }
 
 fn default_git_provider() -> String {
@@ -360,6 +405,12 @@ impl Default for ResourceSyncConfig {
       git_account: Default::default(),
       resource_path: Default::default(),
       files_on_host: Default::default(),
+      oci_artifact: Default::default(),
+      oci_account: Default::default(),
+      s3_bucket: Default::default(),
+      s3_prefix: Default::default(),
+      s3_region: Default::default(),
+      s3_endpoint: Default::default(),
       file_contents: Default::default(),
       managed: Default::default(),
       include_resources: default_include_resources(),
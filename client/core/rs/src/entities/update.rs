@@ -7,7 +7,7 @@ use crate::entities::{
   I64, MongoId, Operation, all_logs_success, komodo_timestamp,
 };
 
-use super::{ResourceTarget, Version};
+use super::{ResourceTarget, Version, plan::ExecutionPlan};
 
 /// Represents an action performed by Komodo.
 #[typeshare]
@@ -78,6 +78,26 @@ pub struct Update {
   /// If the update is for resource config update, give the current (at time of Update) toml contents
   #[serde(default, skip_serializing_if = "String::is_empty")]
   pub current_toml: String,
+  /// Test results parsed from JUnit reports produced during the operation.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub test_reports: Vec<TestReport>,
+  /// Files attached to the update which are stored outside the database,
+  /// such as logs too large to store inline.
+  /// Download them with `/artifact/download`.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub artifacts: Vec<UpdateArtifact>,
+  /// The changes the execution would make,
+  /// if the update is for a [Plan][crate::api::execute::Plan].
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub plan: Option<ExecutionPlan>,
+  /// The exact inputs used, if the update is for a successful build or deploy.
+  /// The resolved commit is given by `commit_hash`.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub environment: Option<ExecutionEnvironment>,
+  /// The id of the Update this execution reran,
+  /// if it was run with [RerunUpdate][crate::api::execute::RerunUpdate].
+  #[serde(default, skip_serializing_if = "String::is_empty")]
+  pub rerun_of: String,
 }
 
 impl Update {
@@ -142,6 +162,9 @@ pub struct UpdateListItem {
   /// Some unstructured, operation specific data. Not for general usage.
   #[serde(default, skip_serializing_if = "String::is_empty")]
   pub other_data: String,
+  /// The id of the Update this execution reran, if it is a rerun.
+  #[serde(default, skip_serializing_if = "String::is_empty")]
+  pub rerun_of: String,
 }
 
 /// Represents the output of some command being run
@@ -202,6 +225,88 @@ impl Log {
   }
 }
 
+/// A file attached to an [Update].
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct UpdateArtifact {
+  /// The file name, unique within the update.
+  pub name: String,
+  /// The size of the file in bytes
+  pub size: I64,
+  /// The time the artifact was stored
+  pub created_at: I64,
+}
+
+/// The inputs a build or deploy ran with,
+/// so it can be reproduced exactly later.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ExecutionEnvironment {
+  /// The Komodo Periphery version which ran the execution.
+  #[serde(default)]
+  pub periphery_version: String,
+  /// The Docker (or Podman) engine version which ran the execution.
+  #[serde(default)]
+  pub docker_version: String,
+  /// The images built / deployed, with the digests they resolved to.
+  #[serde(default)]
+  pub images: Vec<ImageDigest>,
+  /// The sha256 of the rendered compose config, for Stack deploys.
+  #[serde(default, skip_serializing_if = "String::is_empty")]
+  pub compose_hash: String,
+  /// The services a Stack deploy was filtered to.
+  /// Empty if all the services were deployed.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub services: Vec<String>,
+  /// The names of the variables and secrets which were interpolated.
+  /// The values are never recorded.
+  #[serde(default)]
+  pub variables: Vec<String>,
+}
+
+/// An image reference and the digest it resolved to.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ImageDigest {
+  /// The image, as given in the config, ie `nginx:latest`.
+  pub image: String,
+  /// The registry digest, ie `sha256:...`.
+  /// Falls back to the local image id for images which were never pushed or pulled.
+  /// Empty if the image couldn't be inspected.
+  pub digest: String,
+}
+
+/// The results of a JUnit XML test report.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct TestReport {
+  /// A label for the report, ie the pipeline step name.
+  pub name: String,
+  /// The total number of test cases
+  pub tests: I64,
+  /// The number of test cases which passed
+  pub passed: I64,
+  /// The number of test cases which failed or errored
+  pub failed: I64,
+  /// The number of test cases which were skipped
+  pub skipped: I64,
+  /// The total run time of the test cases in seconds
+  pub time: f64,
+  /// The test cases which failed or errored
+  #[serde(default)]
+  pub failures: Vec<TestCaseFailure>,
+}
+
+/// A failed test case in a [TestReport].
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct TestCaseFailure {
+  /// The test case name, prefixed with the class name if it exists.
+  pub name: String,
+  /// The failure message
+  pub message: String,
+}
+
 /// An update's status
 #[typeshare]
 #[derive(
@@ -226,3 +331,26 @@ pub enum UpdateStatus {
   #[default]
   Complete,
 }
+
+/// Metrics aggregated from the deploys / runs of a resource,
+/// like `Deploy`, `DeployStack`, `RunBuild` or `RunProcedure`.
+#[typeshare]
+#[derive(
+  Serialize, Deserialize, Debug, Clone, PartialEq, Default,
+)]
+pub struct ExecutionMetrics {
+  /// The start of the aggregated period in unix ms.
+  pub since: I64,
+  /// The number of completed deploys / runs.
+  pub deploys: I64,
+  /// The average number of deploys / runs per week.
+  pub deploys_per_week: f64,
+  /// The number of failed deploys / runs.
+  pub failures: I64,
+  /// The share of deploys / runs which failed, from 0.0 to 1.0.
+  pub failure_rate: f64,
+  /// The median deploy / run duration in ms.
+  pub p50_duration_ms: I64,
+  /// The 95th percentile deploy / run duration in ms.
+  pub p95_duration_ms: I64,
+}
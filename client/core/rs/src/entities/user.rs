@@ -108,6 +108,8 @@ impl User {
         | "000000000000000000000007"
         | "Repo Manager"
         | "000000000000000000000008"
+        | "Scheduler"
+        | "000000000000000000000009"
     )
   }
 }
@@ -141,6 +143,9 @@ pub fn admin_service_user(user_id: &str) -> Option<User> {
     "000000000000000000000008" | "Repo Manager" => {
       repo_user().to_owned().into()
     }
+    "000000000000000000000009" | "Scheduler" => {
+      scheduler_user().to_owned().into()
+    }
     _ => None,
   }
 }
@@ -271,6 +276,20 @@ pub fn repo_user() -> &'static User {
   })
 }
 
+pub fn scheduler_user() -> &'static User {
+  static SCHEDULER_USER: OnceLock<User> = OnceLock::new();
+  SCHEDULER_USER.get_or_init(|| {
+    let id_name = String::from("Scheduler");
+    User {
+      id: "000000000000000000000009".to_string(),
+      username: id_name,
+      enabled: true,
+      admin: true,
+      ..Default::default()
+    }
+  })
+}
+
 #[typeshare]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", content = "data")]
@@ -287,6 +306,9 @@ pub enum UserConfig {
   /// User that logs in via Oidc provider
   Oidc { provider: String, user_id: String },
 
+  /// User that logs in via LDAP
+  Ldap { dn: String },
+
   /// Non-human managed user, can have it's own permissions / api keys
   Service { description: String },
 }
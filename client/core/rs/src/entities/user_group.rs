@@ -15,6 +15,9 @@ use super::{
 /// A user can be a part of multiple groups. A user's permission on a particular resource
 /// will be resolved to be the maximum permission level between the user's own permissions and
 /// any groups they are a part of.
+///
+/// Groups can also contain other groups. The members of a nested group
+/// are members of all the groups containing it, and inherit their permissions.
 #[typeshare]
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[cfg_attr(
@@ -47,6 +50,12 @@ pub struct UserGroup {
   #[serde(default, deserialize_with = "string_list_deserializer")]
   pub users: Vec<String>,
 
+  /// Ids of the user groups nested in this group.
+  /// Their members are also members of this group.
+  #[cfg_attr(feature = "mongo", index)]
+  #[serde(default, deserialize_with = "string_list_deserializer")]
+  pub groups: Vec<String>,
+
   /// Give the user group elevated permissions on all resources of a certain type
   #[serde(default)]
   pub all:
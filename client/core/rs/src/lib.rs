@@ -31,14 +31,19 @@
 use std::{sync::OnceLock, time::Duration};
 
 use anyhow::Context;
-use api::read::GetVersion;
+use api::read::{
+  GetVersion, NegotiateVersion, NegotiateVersionResponse,
+};
 use serde::Deserialize;
 
 pub mod api;
 pub mod busy;
+pub mod compatibility;
 pub mod deserializers;
 pub mod entities;
 pub mod parsers;
+#[cfg(feature = "schema")]
+pub mod schema;
 pub mod terminal;
 pub mod ws;
 
@@ -143,6 +148,30 @@ impl KomodoClient {
     self.read(GetVersion {}).map(|r| r.version)
   }
 
+  /// Check this client version is supported by Core,
+  /// and get the deprecated endpoints.
+  #[cfg(not(feature = "blocking"))]
+  pub async fn negotiate_version(
+    &self,
+  ) -> anyhow::Result<NegotiateVersionResponse> {
+    self
+      .read(NegotiateVersion {
+        client_version: compatibility::CLIENT_VERSION.to_string(),
+      })
+      .await
+  }
+
+  /// Check this client version is supported by Core,
+  /// and get the deprecated endpoints.
+  #[cfg(feature = "blocking")]
+  pub fn negotiate_version(
+    &self,
+  ) -> anyhow::Result<NegotiateVersionResponse> {
+    self.read(NegotiateVersion {
+      client_version: compatibility::CLIENT_VERSION.to_string(),
+    })
+  }
+
   /// Send a health check.
   #[cfg(not(feature = "blocking"))]
   pub async fn health_check(&self) -> anyhow::Result<()> {
@@ -10,6 +10,10 @@ use crate::{
     read::KomodoReadRequest, user::KomodoUserRequest,
     write::KomodoWriteRequest,
   },
+  compatibility::{
+    CLIENT_VERSION, CLIENT_VERSION_HEADER, CORE_VERSION_HEADER,
+    check_compatible,
+  },
 };
 
 impl KomodoClient {
@@ -188,6 +192,64 @@ impl KomodoClient {
     )
   }
 
+  /// Follow a container log as it is written.
+  /// Read the lines with [crate::terminal::TerminalStreamResponse::into_line_stream].
+  #[cfg(not(feature = "blocking"))]
+  pub async fn follow_container_log(
+    &self,
+    body: crate::api::log::FollowContainerLogBody,
+  ) -> anyhow::Result<crate::terminal::TerminalStreamResponse> {
+    self.post_stream("/log/follow/container", body).await
+  }
+
+  /// Follow the Deployment container log as it is written.
+  /// Read the lines with [crate::terminal::TerminalStreamResponse::into_line_stream].
+  #[cfg(not(feature = "blocking"))]
+  pub async fn follow_deployment_log(
+    &self,
+    body: crate::api::log::FollowDeploymentLogBody,
+  ) -> anyhow::Result<crate::terminal::TerminalStreamResponse> {
+    self.post_stream("/log/follow/deployment", body).await
+  }
+
+  /// Follow a Stack service container log as it is written.
+  /// Read the lines with [crate::terminal::TerminalStreamResponse::into_line_stream].
+  #[cfg(not(feature = "blocking"))]
+  pub async fn follow_stack_log(
+    &self,
+    body: crate::api::log::FollowStackLogBody,
+  ) -> anyhow::Result<crate::terminal::TerminalStreamResponse> {
+    self.post_stream("/log/follow/stack", body).await
+  }
+
+  #[cfg(not(feature = "blocking"))]
+  async fn post_stream<B: Serialize + std::fmt::Debug>(
+    &self,
+    endpoint: &str,
+    body: B,
+  ) -> anyhow::Result<crate::terminal::TerminalStreamResponse> {
+    let req = self
+      .reqwest
+      .post(format!("{}{endpoint}", self.address))
+      .header("x-api-key", &self.key)
+      .header("x-api-secret", &self.secret)
+      .header(CLIENT_VERSION_HEADER, CLIENT_VERSION)
+      .header("content-type", "application/json")
+      .json(&body);
+    let res =
+      req.send().await.context("failed to reach Komodo API")?;
+    let status = res.status();
+    if status.is_success() {
+      return Ok(crate::terminal::TerminalStreamResponse(res));
+    }
+    let core_version = core_version(res.headers());
+    let e = match res.text().await {
+      Ok(res) => deserialize_error(res).context(status),
+      Err(e) => anyhow!("{e:?}").context(status),
+    };
+    Err(with_version_context(e, core_version))
+  }
+
   #[cfg(not(feature = "blocking"))]
   async fn post<
     B: Serialize + std::fmt::Debug,
@@ -202,12 +264,14 @@ impl KomodoClient {
       .post(format!("{}{endpoint}", self.address))
       .header("x-api-key", &self.key)
       .header("x-api-secret", &self.secret)
+      .header(CLIENT_VERSION_HEADER, CLIENT_VERSION)
       .header("content-type", "application/json")
       .json(&body);
     let res =
       req.send().await.context("failed to reach Komodo API")?;
     let status = res.status();
-    if status.is_success() {
+    let core_version = core_version(res.headers());
+    let res = if status.is_success() {
       match res.json().await {
         Ok(res) => Ok(res),
         Err(e) => Err(anyhow!("{e:#?}").context(status)),
@@ -217,7 +281,8 @@ impl KomodoClient {
         Ok(res) => Err(deserialize_error(res).context(status)),
         Err(e) => Err(anyhow!("{e:?}").context(status)),
       }
-    }
+    };
+    res.map_err(|e| with_version_context(e, core_version))
   }
 
   #[cfg(feature = "blocking")]
@@ -231,11 +296,13 @@ impl KomodoClient {
       .post(format!("{}{endpoint}", self.address))
       .header("x-api-key", &self.key)
       .header("x-api-secret", &self.secret)
+      .header(CLIENT_VERSION_HEADER, CLIENT_VERSION)
       .header("content-type", "application/json")
       .json(&body);
     let res = req.send().context("failed to reach Komodo API")?;
     let status = res.status();
-    if status.is_success() {
+    let core_version = core_version(res.headers());
+    let res = if status.is_success() {
       match res.json() {
         Ok(res) => Ok(res),
         Err(e) => Err(anyhow!("{e:#?}").context(status)),
@@ -245,6 +312,32 @@ impl KomodoClient {
         Ok(res) => Err(deserialize_error(res).context(status)),
         Err(e) => Err(anyhow!("{e:?}").context(status)),
       }
-    }
+    };
+    res.map_err(|e| with_version_context(e, core_version))
+  }
+}
+
+fn core_version(
+  headers: &reqwest::header::HeaderMap,
+) -> Option<String> {
+  headers
+    .get(CORE_VERSION_HEADER)
+    .and_then(|version| version.to_str().ok())
+    .map(str::to_string)
+}
+
+/// Mixed version Core and clients can fail with opaque
+/// deserialization errors, so point out when the versions
+/// are not compatible.
+fn with_version_context(
+  e: anyhow::Error,
+  core_version: Option<String>,
+) -> anyhow::Error {
+  let Some(core_version) = core_version else {
+    return e;
+  };
+  match check_compatible(&core_version, CLIENT_VERSION) {
+    Ok(_) => e,
+    Err(mismatch) => e.context(format!("{mismatch:#}")),
   }
 }
@@ -0,0 +1,115 @@
+//! JSON Schemas for the resource configs, generated from the
+//! config types along with their defaults and docs.
+//!
+//! Requires the `schema` feature.
+
+use anyhow::{Context, anyhow};
+use schemars::{
+  JsonSchema,
+  r#gen::{SchemaGenerator, SchemaSettings},
+  schema::{InstanceType, ObjectValidation, Schema, SchemaObject},
+};
+
+use crate::{
+  api::execute::Execution,
+  entities::{
+    JsonValue, ResourceTargetVariant, action::ActionConfig,
+    alert::AlertDataVariant, alerter::AlerterConfig,
+    build::BuildConfig, builder::BuilderConfig,
+    deployment::DeploymentConfig, procedure::ProcedureConfig,
+    repo::RepoConfig, server::ServerConfig, stack::StackConfig,
+    swarm::SwarmServiceConfig, sync::ResourceSyncConfig,
+  },
+};
+
+/// All the resource types with a config.
+pub const RESOURCE_TYPES: [ResourceTargetVariant; 11] = [
+  ResourceTargetVariant::Server,
+  ResourceTargetVariant::Stack,
+  ResourceTargetVariant::Deployment,
+  ResourceTargetVariant::SwarmService,
+  ResourceTargetVariant::Build,
+  ResourceTargetVariant::Repo,
+  ResourceTargetVariant::Procedure,
+  ResourceTargetVariant::Action,
+  ResourceTargetVariant::ResourceSync,
+  ResourceTargetVariant::Builder,
+  ResourceTargetVariant::Alerter,
+];
+
+/// Generates the JSON Schema (draft 7) for the resource type config.
+pub fn resource_config_schema(
+  resource_type: ResourceTargetVariant,
+) -> anyhow::Result<JsonValue> {
+  let schema = match resource_type {
+    ResourceTargetVariant::System => {
+      return Err(anyhow!("System has no config"));
+    }
+    ResourceTargetVariant::Server => root_schema::<ServerConfig>(),
+    ResourceTargetVariant::Stack => root_schema::<StackConfig>(),
+    ResourceTargetVariant::Deployment => {
+      root_schema::<DeploymentConfig>()
+    }
+    ResourceTargetVariant::SwarmService => {
+      root_schema::<SwarmServiceConfig>()
+    }
+    ResourceTargetVariant::Build => root_schema::<BuildConfig>(),
+    ResourceTargetVariant::Repo => root_schema::<RepoConfig>(),
+    ResourceTargetVariant::Procedure => {
+      root_schema::<ProcedureConfig>()
+    }
+    ResourceTargetVariant::Action => root_schema::<ActionConfig>(),
+    ResourceTargetVariant::ResourceSync => {
+      root_schema::<ResourceSyncConfig>()
+    }
+    ResourceTargetVariant::Builder => root_schema::<BuilderConfig>(),
+    ResourceTargetVariant::Alerter => root_schema::<AlerterConfig>(),
+  };
+  schema.context("Failed to serialize config schema")
+}
+
+fn root_schema<T: JsonSchema>() -> serde_json::Result<JsonValue> {
+  let schema = SchemaSettings::draft07()
+    .into_generator()
+    .into_root_schema_for::<T>();
+  serde_json::to_value(schema)
+}
+
+/// The executions are only described by their type,
+/// see the Execute API for the params of each execution.
+impl JsonSchema for Execution {
+  fn schema_name() -> String {
+    String::from("Execution")
+  }
+
+  fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+    SchemaObject {
+      instance_type: Some(InstanceType::Object.into()),
+      object: Some(Box::new(ObjectValidation {
+        required: [String::from("type")].into(),
+        properties: [
+          (String::from("type"), generator.subschema_for::<String>()),
+          (
+            String::from("params"),
+            generator.subschema_for::<JsonValue>(),
+          ),
+        ]
+        .into(),
+        ..Default::default()
+      })),
+      ..Default::default()
+    }
+    .into()
+  }
+}
+
+/// The alert type, eg. `ServerUnreachable`.
+impl JsonSchema for AlertDataVariant {
+  fn schema_name() -> String {
+    String::from("AlertDataVariant")
+  }
+
+  fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+    String::json_schema(generator)
+  }
+}
@@ -12,7 +12,13 @@ use tracing::{Instrument, debug, info, info_span, warn};
 use typeshare::typeshare;
 use uuid::Uuid;
 
-use crate::{KomodoClient, entities::update::UpdateListItem};
+use crate::{
+  KomodoClient,
+  entities::{
+    JsonValue, ResourceTarget, docker::container::ContainerListItem,
+    stats::SystemStats, update::UpdateListItem,
+  },
+};
 
 #[typeshare]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +40,75 @@ impl WsLoginMessage {
   }
 }
 
+/// Sent by the client on the `/ws/subscribe` websocket after login,
+/// to receive push updates instead of polling the read API.
+#[typeshare]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "params")]
+pub enum WsSubscribeMessage {
+  /// Receive the resource list item, including its state,
+  /// and its action state whenever they change.
+  /// Requires Read permission on the resource.
+  SubscribeResource { target: ResourceTarget },
+  /// Receive the Server container list, including container stats,
+  /// and the Server system stats whenever they are refreshed.
+  /// Requires Read permission on the Server.
+  SubscribeContainerStats {
+    /// Id or name
+    server: String,
+  },
+  /// Stop the subscription with the id given in `Subscribed`.
+  Unsubscribe { id: String },
+}
+
+/// Sent by Core on the `/ws/subscribe` websocket.
+#[typeshare]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum WsSubscriptionMessage {
+  /// The subscription was started.
+  /// The current state is sent right after.
+  Subscribed { id: String },
+  /// The subscription was stopped.
+  Unsubscribed { id: String },
+  /// The latest state of a subscribed resource.
+  Resource {
+    /// The subscription id.
+    id: String,
+    target: ResourceTarget,
+    /// The resource list item, eg. a `StackListItem` for Stacks.
+    item: JsonValue,
+    /// The resource action state, eg. a `StackActionState` for Stacks.
+    /// Null for resources without action states.
+    action_state: JsonValue,
+  },
+  /// The latest containers and stats of a subscribed Server.
+  ContainerStats {
+    /// The subscription id.
+    id: String,
+    /// The Server id.
+    server: String,
+    containers: Vec<ContainerListItem>,
+    /// The Server system stats, if the Server is reachable.
+    stats: Option<SystemStats>,
+  },
+  /// A subscription request failed, or a subscription
+  /// was stopped because it could no longer be read.
+  Error {
+    /// The subscription id, if the error is for a subscription.
+    id: Option<String>,
+    message: String,
+  },
+}
+
+impl WsSubscribeMessage {
+  pub fn to_json_string(&self) -> anyhow::Result<String> {
+    serde_json::to_string(self).context(
+      "failed to serialize WsSubscribeMessage to json string",
+    )
+  }
+}
+
 #[derive(Debug, Clone)]
 pub enum UpdateWsMessage {
   Update(UpdateListItem),
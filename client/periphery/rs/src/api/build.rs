@@ -1,5 +1,5 @@
 use komodo_client::entities::{
-  FileContents, repo::Repo, update::Log,
+  FileContents, build::ImageScan, repo::Repo, update::Log,
 };
 use resolver_api::Resolve;
 use serde::{Deserialize, Serialize};
@@ -23,12 +23,63 @@ pub struct Build {
   /// Add more tags for this build in addition to the version tags.
   #[serde(default)]
   pub additional_tags: Vec<String>,
+  /// Which part of the build to run.
+  #[serde(default)]
+  pub step: BuildStep,
 }
 
 pub type BuildResponse = Vec<Log>;
 
 //
 
+/// Kills the running docker builds of the Build, if any.
+/// The Build request then returns with the failed build log.
+#[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
+#[response(Log)]
+#[error(serror::Error)]
+pub struct KillBuild {
+  /// The name of the Build.
+  pub name: String,
+}
+
+/// Matrix builds run the shared steps once with `Prepare`,
+/// then build each variant in parallel with `Variant`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub enum BuildStep {
+  /// Run the full build.
+  #[default]
+  All,
+  /// Only run the steps shared by the matrix variants:
+  /// registry login, writing the dockerfile, and the pre build.
+  Prepare,
+  /// Only build the image for the named matrix variant.
+  /// The shared steps must already have run.
+  Variant(String),
+}
+
+//
+
+/// Scan an image for vulnerabilities using Trivy.
+/// Uses the `trivy` binary on the host if available,
+/// otherwise runs Trivy in a container.
+#[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
+#[response(ScanImageResponse)]
+#[error(serror::Error)]
+pub struct ScanImage {
+  /// The image to scan, eg `ghcr.io/org/app:1.2.3`.
+  pub image: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScanImageResponse {
+  /// The log of the scan, with a summary of the results.
+  pub log: Log,
+  /// The scan results, if the scan succeeded.
+  pub scan: Option<ImageScan>,
+}
+
+//
+
 /// Get the dockerfile contents on the host, for builds using
 /// `files_on_host`.
 #[derive(Debug, Clone, Serialize, Deserialize, Resolve)]
@@ -47,6 +98,28 @@ pub type GetDockerfileContentsOnHostResponse = FileContents;
 
 //
 
+/// Get the contents of a JUnit XML report on the host.
+/// Returns `null` if there is no report at the path.
+#[derive(Debug, Clone, Serialize, Deserialize, Resolve)]
+#[response(GetJunitReportResponse)]
+#[error(serror::Error)]
+pub struct GetJunitReport {
+  /// The path to the report. If relative, it is taken relative to
+  /// the directory of the Build `name`.
+  pub path: String,
+  /// The name of the build. Empty for absolute paths.
+  #[serde(default)]
+  pub name: String,
+  /// Whether `name` is the Build's linked Repo,
+  /// which is cloned in the Periphery `repo_dir`.
+  #[serde(default)]
+  pub linked_repo: bool,
+}
+
+pub type GetJunitReportResponse = Option<String>;
+
+//
+
 /// Write the dockerfile contents to the file on the host, for build using
 /// `files_on_host`.
 #[derive(Debug, Clone, Serialize, Deserialize, Resolve)]
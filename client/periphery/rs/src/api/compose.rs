@@ -3,7 +3,7 @@ use komodo_client::entities::{
   repo::Repo,
   stack::{
     ComposeProject, Stack, StackFileDependency,
-    StackRemoteFileContents, StackServiceNames,
+    StackRemoteFileContents, StackServiceDrift, StackServiceNames,
   },
   update::Log,
 };
@@ -155,9 +155,17 @@ pub struct ComposePull {
   pub git_token: Option<String>,
   /// If provided, use it to login in. Otherwise check periphery local registry providers.
   pub registry_token: Option<String>,
+  /// If provided, use it to pull the stack OCI artifact.
+  /// Otherwise check periphery local registries.
+  #[serde(default)]
+  pub oci_token: Option<String>,
   /// Propogate any secret replacers from core interpolation.
   #[serde(default)]
   pub replacers: Vec<(String, String)>,
+  /// Compose override files fetched by core,
+  /// to write relative to the run directory.
+  #[serde(default)]
+  pub include_files: Vec<FileContents>,
 }
 
 /// Response for [ComposePull]
@@ -190,9 +198,22 @@ pub struct ComposeUp {
   pub git_token: Option<String>,
   /// If provided, use it to login in. Otherwise check periphery local git providers.
   pub registry_token: Option<String>,
+  /// If provided, use it to pull the stack OCI artifact.
+  /// Otherwise check periphery local registries.
+  #[serde(default)]
+  pub oci_token: Option<String>,
   /// Propogate any secret replacers from core interpolation.
   #[serde(default)]
   pub replacers: Vec<(String, String)>,
+  /// Remote include files fetched by core,
+  /// to write relative to the run directory.
+  #[serde(default)]
+  pub include_files: Vec<FileContents>,
+  /// For reruns, the sha256 (hex) of the sanitized compose config
+  /// of the original deploy. If the rendered config doesn't match,
+  /// the Stack is not deployed.
+  #[serde(default)]
+  pub expected_compose_hash: Option<String>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -222,6 +243,47 @@ pub struct ComposeUpResponse {
 
 //
 
+/// Writes the stack files like [ComposeUp], but to a temporary
+/// directory, and runs `docker compose config` without deploying.
+#[derive(Debug, Clone, Serialize, Deserialize, Resolve)]
+#[response(ComposeRenderResponse)]
+#[error(serror::Error)]
+pub struct ComposeRender {
+  /// The stack to render
+  pub stack: Stack,
+  /// Filter to only render specific services.
+  /// If empty, will render all services.
+  #[serde(default)]
+  pub services: Vec<String>,
+  /// The linked repo, if it exists.
+  pub repo: Option<Repo>,
+  /// If provided, use it to login in. Otherwise check periphery local git providers.
+  pub git_token: Option<String>,
+  /// If provided, use it to pull the stack OCI artifact.
+  /// Otherwise check periphery local registries.
+  #[serde(default)]
+  pub oci_token: Option<String>,
+  /// Propogate any secret replacers from core interpolation.
+  #[serde(default)]
+  pub replacers: Vec<(String, String)>,
+  /// Remote include files fetched by core,
+  /// to write relative to the run directory.
+  #[serde(default)]
+  pub include_files: Vec<FileContents>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ComposeRenderResponse {
+  /// The logs produced writing the stack and running `docker compose config`
+  pub logs: Vec<Log>,
+  /// The output of `docker compose config`, if it succeeded.
+  pub compose_config: Option<String>,
+  /// The `docker compose up` command a deploy would run.
+  pub command: String,
+}
+
+//
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ComposeRunResponse {
   /// Logs produced during stack write/prepare for the run
@@ -244,6 +306,63 @@ pub struct ComposeExecution {
 
 //
 
+/// Waits for all the containers in the compose project to be running,
+/// and healthy if they define a healthcheck.
+/// Used by blue / green Stack deploys.
+#[derive(Debug, Clone, Serialize, Deserialize, Resolve)]
+#[response(Log)]
+#[error(serror::Error)]
+pub struct ComposeWaitHealthy {
+  /// The compose project name to wait on.
+  pub project: String,
+  /// Services which are not waited on,
+  /// for example init services which exit.
+  #[serde(default)]
+  pub ignore_services: Vec<String>,
+  /// How long to wait, in seconds, before failing.
+  pub timeout: i64,
+}
+
+//
+
+/// Connects the service containers of the compose project to
+/// the network with the alias, then disconnects the containers
+/// of the previous project from the network.
+/// Used by blue / green Stack deploys.
+#[derive(Debug, Clone, Serialize, Deserialize, Resolve)]
+#[response(Vec<Log>)]
+#[error(serror::Error)]
+pub struct ComposeSwapNetworkAlias {
+  /// The newly deployed compose project.
+  pub project: String,
+  /// The compose project to move the alias from, if any.
+  pub previous_project: Option<String>,
+  /// The service which receives the alias.
+  pub service: String,
+  /// The network shared with the reverse proxy.
+  pub network: String,
+  /// The network alias.
+  pub alias: String,
+}
+
+//
+
+/// Compares the containers of the compose project with the
+/// deployed compose config: the image and image digest,
+/// and the environment and labels defined in the compose file.
+/// Only services with differences are returned.
+#[derive(Debug, Clone, Serialize, Deserialize, Resolve)]
+#[response(Vec<StackServiceDrift>)]
+#[error(serror::Error)]
+pub struct GetComposeRuntimeDrift {
+  /// The compose project name.
+  pub project: String,
+  /// The output of `docker compose config` from the deploy.
+  pub compose_config: String,
+}
+
+//
+
 /// docker compose run one-time service execution.
 #[derive(Debug, Clone, Serialize, Deserialize, Resolve)]
 #[response(Log)]
@@ -257,6 +376,10 @@ pub struct ComposeRun {
   pub git_token: Option<String>,
   /// If provided, use it to login in. Otherwise check periphery local git providers.
   pub registry_token: Option<String>,
+  /// If provided, use it to pull the stack OCI artifact.
+  /// Otherwise check periphery local registries.
+  #[serde(default)]
+  pub oci_token: Option<String>,
   /// Propogate any secret replacers from core interpolation.
   #[serde(default)]
   pub replacers: Vec<(String, String)>,
@@ -37,6 +37,23 @@ fn default_tail() -> u64 {
   50
 }
 
+/// Follow the container log as it is written, like `docker logs -f`.
+/// Not a resolver, it is sent to `/log/follow`
+/// and the response streams the log lines.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FollowContainerLogBody {
+  pub name: String,
+  /// The number of lines to include from before following.
+  #[serde(default = "default_tail")]
+  pub tail: u64,
+  /// Enable `--timestamps`
+  #[serde(default)]
+  pub timestamps: bool,
+  /// Only stream the lines matching this regex. Empty streams all lines.
+  #[serde(default)]
+  pub filter: String,
+}
+
 //
 
 #[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
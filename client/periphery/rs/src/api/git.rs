@@ -2,7 +2,8 @@ use std::path::PathBuf;
 
 use komodo_client::entities::{
   EnvironmentVar, LatestCommit, RepoExecutionArgs,
-  RepoExecutionResponse, SystemCommand, update::Log,
+  RepoExecutionResponse, SystemCommand, repo::RepoPipelineStep,
+  update::Log,
 };
 use resolver_api::Resolve;
 use serde::{Deserialize, Serialize};
@@ -97,6 +98,26 @@ pub struct PeripheryRepoExecutionResponse {
 
 //
 
+/// Runs the Repo pipeline steps in order on an already cloned repo.
+/// Returns the logs of each step, stopping at the first failure.
+#[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
+#[response(Vec<Log>)]
+#[error(serror::Error)]
+pub struct RunRepoPipeline {
+  /// The Repo name, used for the artifacts directory.
+  pub name: String,
+  /// Absolute path to the repo root on the host.
+  pub path: PathBuf,
+  pub steps: Vec<RepoPipelineStep>,
+  /// Relative to repo root.
+  /// Passed to container steps with `--env-file` if it exists.
+  #[serde(default = "default_env_file_path")]
+  pub env_file_path: String,
+  /// Propogate any secret replacers from core interpolation.
+  #[serde(default)]
+  pub replacers: Vec<(String, String)>,
+}
+
 //
 
 #[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
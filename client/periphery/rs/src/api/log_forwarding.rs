@@ -0,0 +1,24 @@
+use komodo_client::entities::{NoData, log_forwarding::LogSink};
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+
+/// Sets the container logs Periphery forwards, replacing the previous set.
+/// Core sends the full set every monitoring interval,
+/// so forwarding stops once it is disabled or the container is removed.
+#[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
+#[response(NoData)]
+#[error(serror::Error)]
+pub struct SetLogForwarding {
+  pub forwards: Vec<LogForward>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LogForward {
+  /// The container to forward the logs of.
+  pub container: String,
+  /// Identifies the logs on the sink,
+  /// eg. `komodo_server`, `komodo_stack`, `komodo_service`.
+  pub labels: Vec<(String, String)>,
+  /// Where to forward the logs.
+  pub sink: LogSink,
+}
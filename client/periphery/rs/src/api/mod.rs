@@ -1,5 +1,6 @@
 use komodo_client::entities::{
   SystemCommand,
+  alert::Alert,
   config::{DockerRegistry, GitProvider},
   docker::{
     container::ContainerListItem, image::ImageListItem,
@@ -17,8 +18,10 @@ pub mod compose;
 pub mod container;
 pub mod git;
 pub mod image;
+pub mod log_forwarding;
 pub mod network;
 pub mod stats;
+pub mod swarm;
 pub mod terminal;
 pub mod volume;
 
@@ -44,6 +47,25 @@ pub struct GetVersionResponse {
   pub version: String,
 }
 
+/// Checks the EC2 instance metadata for a spot interruption notice.
+/// Returns `None` if the instance is not being interrupted.
+/// Fails if Periphery is not running on an EC2 instance.
+#[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
+#[response(Option<SpotInterruption>)]
+#[error(serror::Error)]
+pub struct GetSpotInterruption {}
+
+/// The spot `instance-action` from the EC2 instance metadata.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SpotInterruption {
+  /// The action which will be taken, eg `terminate`.
+  pub action: String,
+  /// The time the action will be taken, in UTC.
+  pub time: String,
+}
+
+//
+
 /// Returns all containers, networks, images, compose projects
 #[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
 #[response(GetDockerListsResponse)]
@@ -99,3 +121,34 @@ pub struct PruneSystem {}
 pub struct RunCommand {
   pub command: SystemCommand,
 }
+
+//
+
+/// Runs a Server scheduled command on the host.
+/// The command may be multiline.
+#[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
+#[response(Log)]
+#[error(serror::Error)]
+pub struct RunScheduledCommand {
+  /// The name of the scheduled command, used as the log stage.
+  pub name: String,
+  /// The command to run.
+  pub command: String,
+  /// The directory to run the command in.
+  pub path: String,
+  /// Run the command as this host user.
+  /// If empty, runs as the Periphery user.
+  pub user: String,
+}
+
+//
+
+/// Runs the `alert_condition` hooks configured on Periphery
+/// with the alert. Returns the hook logs, which is empty
+/// if no hooks are configured.
+#[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
+#[response(Vec<Log>)]
+#[error(serror::Error)]
+pub struct RunAlertHooks {
+  pub alert: Alert,
+}
@@ -21,6 +21,10 @@ pub struct InspectNetwork {
 pub struct CreateNetwork {
   pub name: String,
   pub driver: Option<String>,
+  #[serde(default)]
+  pub enable_ipv6: bool,
+  #[serde(default)]
+  pub subnets: Vec<String>,
 }
 
 //
@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+
+use komodo_client::entities::{
+  swarm::{SwarmService, SwarmServiceSummary},
+  update::Log,
+};
+use resolver_api::Resolve;
+use serde::{Deserialize, Serialize};
+
+/// Lists the services in each of the given Swarm stacks,
+/// using `docker stack services`.
+/// Stacks which are not deployed map to an empty list.
+#[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
+#[response(ListSwarmStackServicesResponse)]
+#[error(serror::Error)]
+pub struct ListSwarmStackServices {
+  pub stacks: Vec<String>,
+}
+
+/// Stack name -> services
+pub type ListSwarmStackServicesResponse =
+  HashMap<String, Vec<SwarmServiceSummary>>;
+
+//
+
+/// Deploys the swarm service with `docker stack deploy`,
+/// using the swarm service name as the stack name.
+#[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
+#[response(Log)]
+#[error(serror::Error)]
+pub struct DeploySwarmService {
+  pub swarm_service: SwarmService,
+  /// Override registry token with one sent from core.
+  pub registry_token: Option<String>,
+  /// Propogate any secret replacers from core interpolation.
+  #[serde(default)]
+  pub replacers: Vec<(String, String)>,
+}
+
+//
+
+/// Scales services in the Swarm with `docker service scale`.
+#[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
+#[response(Log)]
+#[error(serror::Error)]
+pub struct ScaleSwarmServices {
+  /// The full service names, `<stack>_<service>`.
+  pub services: Vec<String>,
+  pub replicas: i64,
+}
+
+//
+
+/// Removes a stack from the Swarm with `docker stack rm`.
+#[derive(Serialize, Deserialize, Debug, Clone, Resolve)]
+#[response(Log)]
+#[error(serror::Error)]
+pub struct RemoveSwarmStack {
+  pub stack: String,
+}
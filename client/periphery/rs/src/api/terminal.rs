@@ -1,6 +1,6 @@
 use komodo_client::{
   api::write::TerminalRecreateMode,
-  entities::{NoData, server::TerminalInfo},
+  entities::{EnvironmentVar, NoData, server::TerminalInfo},
 };
 use resolver_api::Resolve;
 use serde::{Deserialize, Serialize};
@@ -22,6 +22,19 @@ pub struct CreateTerminal {
   /// `docker exec -it container sh`
   #[serde(default = "default_command")]
   pub command: String,
+  /// The working directory of the terminal process.
+  #[serde(default)]
+  pub working_dir: Option<String>,
+  /// Extra environment variables passed to the terminal process.
+  #[serde(default)]
+  pub env: Vec<EnvironmentVar>,
+  /// The name of the terminal profile used, if any.
+  #[serde(default)]
+  pub profile: Option<String>,
+  /// The User Groups (names and ids) of the user creating
+  /// the terminal, checked against the Periphery `terminal_profiles`.
+  #[serde(default)]
+  pub user_groups: Vec<String>,
   /// Default: `Never`
   #[serde(default)]
   pub recreate: TerminalRecreateMode,
@@ -72,6 +85,13 @@ pub struct ConnectTerminalQuery {
   /// If a terminal with the specified name already exists,
   /// it will be attached to. Otherwise, it will fail.
   pub terminal: String,
+  /// The username of the connecting user,
+  /// listed in the terminal participants.
+  #[serde(default)]
+  pub user: String,
+  /// Attach without writing to stdin.
+  #[serde(default)]
+  pub read_only: bool,
 }
 
 //
@@ -98,6 +118,13 @@ pub struct ConnectContainerExecQuery {
   /// Default: `sh`
   #[serde(default = "default_container_shell")]
   pub shell: String,
+  /// The username of the connecting user,
+  /// listed in the terminal participants.
+  #[serde(default)]
+  pub user: String,
+  /// Attach without writing to stdin.
+  #[serde(default)]
+  pub read_only: bool,
 }
 
 //
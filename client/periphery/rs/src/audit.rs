@@ -0,0 +1,63 @@
+//! Identifies the Komodo user and Update behind each request to Periphery,
+//! so host-level audit logs can be correlated with Komodo users.
+
+use std::future::Future;
+
+/// Header containing the username of the user who initiated the request.
+/// The username is url encoded, as it may not be valid as a header value.
+pub const USER_HEADER: &str = "x-komodo-user";
+/// Header containing the id of the Update the request is part of.
+pub const UPDATE_ID_HEADER: &str = "x-komodo-update-id";
+
+tokio::task_local! {
+  static AUDIT_CONTEXT: AuditContext;
+}
+
+/// The user and Update a request to Periphery is made on behalf of.
+/// Requests made inside [AuditContext::scope] carry the context
+/// to Periphery as headers.
+#[derive(Debug, Clone, Default)]
+pub struct AuditContext {
+  pub user: String,
+  /// May be empty when the request isn't part of an Update.
+  pub update_id: String,
+}
+
+impl AuditContext {
+  pub fn new(
+    user: impl Into<String>,
+    update_id: impl Into<String>,
+  ) -> AuditContext {
+    AuditContext {
+      user: user.into(),
+      update_id: update_id.into(),
+    }
+  }
+
+  /// Runs the future with this context attached to
+  /// the Periphery requests it makes.
+  /// Note the context isn't inherited by spawned tasks.
+  pub async fn scope<F: Future>(self, fut: F) -> F::Output {
+    AUDIT_CONTEXT.scope(self, fut).await
+  }
+
+  /// The context of the current task, if any.
+  pub fn current() -> Option<AuditContext> {
+    AUDIT_CONTEXT.try_with(Clone::clone).ok()
+  }
+}
+
+/// The current audit context headers, as (name, value).
+pub fn audit_headers() -> Vec<(&'static str, String)> {
+  let Some(AuditContext { user, update_id }) =
+    AuditContext::current()
+  else {
+    return Vec::new();
+  };
+  let mut headers =
+    vec![(USER_HEADER, urlencoding::encode(&user).into_owned())];
+  if !update_id.is_empty() {
+    headers.push((UPDATE_ID_HEADER, update_id));
+  }
+  headers
+}
@@ -1,15 +1,29 @@
-use std::{sync::OnceLock, time::Duration};
+use std::{
+  future::Future,
+  pin::Pin,
+  sync::{Arc, OnceLock},
+  time::Duration,
+};
 
 use anyhow::Context;
+use komodo_client::entities::server::ContainerRuntime;
 use reqwest::StatusCode;
 use resolver_api::HasResponse;
 use serde::{Serialize, de::DeserializeOwned};
 use serde_json::json;
+use signing::{SigningKey, with_signed_body};
 
 pub mod api;
+pub mod audit;
+pub mod signing;
 
+mod log;
 mod terminal;
 
+/// Header used to tell Periphery which container runtime to use.
+pub const CONTAINER_RUNTIME_HEADER: &str =
+  "x-komodo-container-runtime";
+
 fn periphery_http_client() -> &'static reqwest::Client {
   static PERIPHERY_HTTP_CLIENT: OnceLock<reqwest::Client> =
     OnceLock::new();
@@ -22,11 +36,28 @@ fn periphery_http_client() -> &'static reqwest::Client {
   })
 }
 
+/// Handles requests in process, instead of sending them to Periphery.
+/// Receives the request type and params, and returns the json response.
+pub type RequestHandler = Arc<
+  dyn Fn(
+      String,
+      serde_json::Value,
+    ) -> Pin<
+      Box<
+        dyn Future<Output = anyhow::Result<serde_json::Value>> + Send,
+      >,
+    > + Send
+    + Sync,
+>;
+
 pub struct PeripheryClient {
   address: String,
   passkey: String,
   headers: Vec<String>,
   timeout: Duration,
+  container_runtime: ContainerRuntime,
+  handler: Option<RequestHandler>,
+  signing_key: Option<Arc<SigningKey>>,
 }
 
 impl PeripheryClient {
@@ -41,9 +72,42 @@ impl PeripheryClient {
       passkey: passkey.into(),
       headers: headers.into(),
       timeout: timeout.into(),
+      container_runtime: ContainerRuntime::default(),
+      handler: None,
+      signing_key: None,
     }
   }
 
+  /// Handle requests in process using the given handler,
+  /// rather than sending them to Periphery.
+  pub fn with_handler(
+    mut self,
+    handler: RequestHandler,
+  ) -> PeripheryClient {
+    self.handler = Some(handler);
+    self
+  }
+
+  /// Sets the container runtime Periphery should use
+  /// to handle requests from this client.
+  pub fn with_container_runtime(
+    mut self,
+    container_runtime: ContainerRuntime,
+  ) -> PeripheryClient {
+    self.container_runtime = container_runtime;
+    self
+  }
+
+  /// Sign the requests to Periphery with the key,
+  /// if one is provided.
+  pub fn with_signing_key(
+    mut self,
+    signing_key: Option<SigningKey>,
+  ) -> PeripheryClient {
+    self.signing_key = signing_key.map(Arc::new);
+    self
+  }
+
   // tracing will skip self, to avoid including passkey in traces
   #[tracing::instrument(
     name = "PeripheryRequest",
@@ -83,16 +147,35 @@ impl PeripheryClient {
     T::Response: DeserializeOwned,
   {
     let req_type = T::req_type();
+    if let Some(handler) = &self.handler {
+      tracing::trace!(
+        "handling request | type: {req_type} | body: {request:?}"
+      );
+      let params = serde_json::to_value(&request)
+        .context("failed to serialize request")?;
+      let res = handler(req_type.to_string(), params).await?;
+      return serde_json::from_value(res).with_context(|| format!(
+        "failed to parse response | type: {req_type} | request: {request:?}"
+      ));
+    }
     tracing::trace!(
       "sending request | type: {req_type} | body: {request:?}"
     );
-    let mut req = periphery_http_client()
+    let body = serde_json::to_vec(&json!({
+      "type": req_type,
+      "params": request
+    }))
+    .context("failed to serialize request")?;
+    let req = periphery_http_client()
       .post(&self.address)
-      .json(&json!({
-        "type": req_type,
-        "params": request
-      }))
       .header("authorization", &self.passkey);
+    let mut req = with_signed_body(
+      req,
+      self.signing_key.as_deref(),
+      "/",
+      &self.container_runtime.to_string(),
+      body,
+    );
     if let Some(timeout) = timeout {
       req = req.timeout(timeout);
     }
@@ -0,0 +1,41 @@
+use anyhow::{Context, anyhow};
+use komodo_client::terminal::TerminalStreamResponse;
+
+use crate::{
+  PeripheryClient, api::container::FollowContainerLogBody,
+  signing::with_signed_body, terminal::terminal_stream_response,
+};
+
+impl PeripheryClient {
+  /// Follows the container log, streaming the lines as they are written.
+  /// The stream only ends when the container stops,
+  /// drop it to stop following.
+  #[tracing::instrument(level = "debug", skip(self))]
+  pub async fn follow_container_log(
+    &self,
+    body: FollowContainerLogBody,
+  ) -> anyhow::Result<TerminalStreamResponse> {
+    tracing::trace!(
+      "sending request | type: FollowContainerLog | container: {}",
+      body.name
+    );
+    if self.handler.is_some() {
+      return Err(anyhow!(
+        "Following logs is only supported through Periphery"
+      ));
+    }
+    let body = serde_json::to_vec(&body)
+      .context("Failed to serialize request body")?;
+    let req = crate::periphery_http_client()
+      .post(format!("{}/log/follow", self.address))
+      .header("authorization", &self.passkey);
+    terminal_stream_response(with_signed_body(
+      req,
+      self.signing_key.as_deref(),
+      "/log/follow",
+      &self.container_runtime.to_string(),
+      body,
+    ))
+    .await
+  }
+}
@@ -0,0 +1,198 @@
+//! Signed request envelope between Core and Periphery.
+//!
+//! Core signs the method, path, body, container runtime and audit
+//! headers of each request with an ed25519 key, along with
+//! a timestamp and a random nonce.
+//! Websocket connections have no body, so the query is signed instead.
+//! The websocket messages after the upgrade are not signed.
+//! Periphery verifies the signature against the configured
+//! Core public keys, and rejects stale or replayed requests.
+//! This protects requests even when TLS terminates at a proxy
+//! between Core and Periphery.
+
+use anyhow::{Context, anyhow};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use ed25519_dalek::{Signature, Signer, Verifier};
+use komodo_client::entities::komodo_timestamp;
+use reqwest::RequestBuilder;
+
+use crate::{
+  CONTAINER_RUNTIME_HEADER,
+  audit::{UPDATE_ID_HEADER, USER_HEADER, audit_headers},
+};
+
+pub use ed25519_dalek::{SigningKey, VerifyingKey};
+
+/// Header containing the base64 encoded request signature.
+pub const SIGNATURE_HEADER: &str = "x-komodo-signature";
+/// Header containing the unix timestamp in ms the request was signed at.
+pub const TIMESTAMP_HEADER: &str = "x-komodo-timestamp";
+/// Header containing the random nonce of the request.
+pub const NONCE_HEADER: &str = "x-komodo-nonce";
+
+/// Signed requests are rejected if the timestamp is further
+/// than this from the Periphery time.
+pub const MAX_SIGNATURE_AGE_MS: i64 = 60_000;
+
+/// The parts of a request covered by the signature.
+/// Headers which aren't sent are signed as empty.
+pub struct SignedRequest<'a> {
+  pub method: &'a str,
+  /// The route path on Periphery, eg. `/terminal/execute`.
+  pub path: &'a str,
+  pub container_runtime: &'a str,
+  /// The [USER_HEADER] value, as sent.
+  pub user: &'a str,
+  /// The [UPDATE_ID_HEADER] value.
+  pub update_id: &'a str,
+  /// The request body, or the query for websocket connections.
+  pub body: &'a [u8],
+}
+
+impl SignedRequest<'_> {
+  fn message(&self, timestamp: i64, nonce: &str) -> Vec<u8> {
+    let SignedRequest {
+      method,
+      path,
+      container_runtime,
+      user,
+      update_id,
+      body,
+    } = self;
+    let mut message = format!(
+      "{timestamp}\n{nonce}\n{method}\n{path}\n{container_runtime}\n{user}\n{update_id}\n"
+    )
+    .into_bytes();
+    message.extend_from_slice(body);
+    message
+  }
+}
+
+/// The signature headers for the request, as (name, value).
+pub fn signature_headers(
+  signing_key: &SigningKey,
+  request: &SignedRequest,
+) -> [(&'static str, String); 3] {
+  let timestamp = komodo_timestamp();
+  let nonce = STANDARD.encode(rand::random::<[u8; 16]>());
+  let signature = signing_key
+    .sign(&request.message(timestamp, &nonce))
+    .to_bytes();
+  [
+    (SIGNATURE_HEADER, STANDARD.encode(signature)),
+    (TIMESTAMP_HEADER, timestamp.to_string()),
+    (NONCE_HEADER, nonce),
+  ]
+}
+
+/// The headers sent with every request to Periphery, as (name, value).
+/// The container runtime and audit headers are signed along
+/// with the method, path and body if a key is provided.
+pub fn request_headers(
+  signing_key: Option<&SigningKey>,
+  method: &str,
+  path: &str,
+  container_runtime: &str,
+  body: &[u8],
+) -> Vec<(&'static str, String)> {
+  let mut headers = audit_headers();
+  let header = |name: &str| {
+    headers
+      .iter()
+      .find(|(header, _)| *header == name)
+      .map(|(_, value)| value.clone())
+      .unwrap_or_default()
+  };
+  let signature = signing_key.map(|signing_key| {
+    signature_headers(
+      signing_key,
+      &SignedRequest {
+        method,
+        path,
+        container_runtime,
+        user: &header(USER_HEADER),
+        update_id: &header(UPDATE_ID_HEADER),
+        body,
+      },
+    )
+  });
+  headers
+    .push((CONTAINER_RUNTIME_HEADER, container_runtime.to_string()));
+  headers.extend(signature.into_iter().flatten());
+  headers
+}
+
+/// POSTs the body to the Periphery route, signed with the key if provided.
+pub fn with_signed_body(
+  req: RequestBuilder,
+  signing_key: Option<&SigningKey>,
+  path: &str,
+  container_runtime: &str,
+  body: Vec<u8>,
+) -> RequestBuilder {
+  let mut req = req.header("content-type", "application/json");
+  for (name, value) in request_headers(
+    signing_key,
+    "POST",
+    path,
+    container_runtime,
+    &body,
+  ) {
+    req = req.header(name, value);
+  }
+  req.body(body)
+}
+
+/// Verifies the signature was made by one of the public keys,
+/// and the timestamp is recent.
+/// Checking the nonce hasn't been seen before is left to the caller.
+pub fn verify_signature(
+  public_keys: &[VerifyingKey],
+  signature: &str,
+  timestamp: &str,
+  nonce: &str,
+  request: &SignedRequest,
+) -> anyhow::Result<()> {
+  let timestamp = timestamp
+    .parse::<i64>()
+    .context("Invalid signature timestamp")?;
+  if (komodo_timestamp() - timestamp).abs() > MAX_SIGNATURE_AGE_MS {
+    return Err(anyhow!(
+      "Signature timestamp is too old. Check the clocks of Core and Periphery are in sync."
+    ));
+  }
+  if nonce.is_empty() {
+    return Err(anyhow!("Signature nonce is empty"));
+  }
+  let signature = STANDARD
+    .decode(signature)
+    .context("Signature is not valid base64")?;
+  let signature = Signature::from_slice(&signature)
+    .context("Signature is not a valid ed25519 signature")?;
+  let message = request.message(timestamp, nonce);
+  if public_keys
+    .iter()
+    .any(|key| key.verify(&message, &signature).is_ok())
+  {
+    Ok(())
+  } else {
+    Err(anyhow!("Request signature is invalid"))
+  }
+}
+
+/// Encodes the public key as base64,
+/// the format used in the Periphery config.
+pub fn encode_public_key(key: &VerifyingKey) -> String {
+  STANDARD.encode(key.as_bytes())
+}
+
+pub fn decode_public_key(key: &str) -> anyhow::Result<VerifyingKey> {
+  let bytes = STANDARD
+    .decode(key.trim())
+    .context("Public key is not valid base64")?;
+  let bytes: [u8; 32] = bytes
+    .try_into()
+    .map_err(|_| anyhow!("Public key must be 32 bytes"))?;
+  VerifyingKey::from_bytes(&bytes)
+    .context("Public key is not a valid ed25519 key")
+}
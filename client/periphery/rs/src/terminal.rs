@@ -1,13 +1,23 @@
 use std::sync::Arc;
 
-use anyhow::Context;
+use anyhow::{Context, anyhow};
 use komodo_client::terminal::TerminalStreamResponse;
 use reqwest::RequestBuilder;
 use rustls::{ClientConfig, client::danger::ServerCertVerifier};
 use tokio::net::TcpStream;
-use tokio_tungstenite::{Connector, MaybeTlsStream, WebSocketStream};
+use tokio_tungstenite::{
+  Connector, MaybeTlsStream, WebSocketStream,
+  tungstenite::{
+    client::IntoClientRequest, handshake::client::Request,
+    http::HeaderValue,
+  },
+};
 
-use crate::{PeripheryClient, api::terminal::*};
+use crate::{
+  PeripheryClient,
+  api::terminal::*,
+  signing::{request_headers, with_signed_body},
+};
 
 impl PeripheryClient {
   /// Handles ws connect and login.
@@ -15,6 +25,8 @@ impl PeripheryClient {
   pub async fn connect_terminal(
     &self,
     terminal: String,
+    user: String,
+    read_only: bool,
   ) -> anyhow::Result<WebSocketStream<MaybeTlsStream<TcpStream>>> {
     tracing::trace!(
       "request | type: ConnectTerminal | terminal name: {terminal}",
@@ -28,15 +40,12 @@ impl PeripheryClient {
     let query_str = serde_qs::to_string(&ConnectTerminalQuery {
       token: token.token,
       terminal,
+      user,
+      read_only,
     })
     .context("Failed to serialize query string")?;
 
-    let url = format!(
-      "{}/terminal?{query_str}",
-      self.address.replacen("http", "ws", 1)
-    );
-
-    connect_websocket(&url).await
+    self.connect_websocket("/terminal", query_str).await
   }
 
   /// Executes command on specified terminal,
@@ -61,11 +70,25 @@ impl PeripheryClient {
     tracing::trace!(
       "sending request | type: ExecuteTerminal | terminal name: {terminal} | command: {command}",
     );
+    if self.handler.is_some() {
+      return Err(anyhow!(
+        "Terminals are only supported through Periphery"
+      ));
+    }
+    let body =
+      serde_json::to_vec(&ExecuteTerminalBody { terminal, command })
+        .context("Failed to serialize request body")?;
     let req = crate::periphery_http_client()
       .post(format!("{}/terminal/execute", self.address))
-      .json(&ExecuteTerminalBody { terminal, command })
       .header("authorization", &self.passkey);
-    terminal_stream_response(req).await
+    terminal_stream_response(with_signed_body(
+      req,
+      self.signing_key.as_deref(),
+      "/terminal/execute",
+      &self.container_runtime.to_string(),
+      body,
+    ))
+    .await
   }
 
   /// Handles ws connect and login.
@@ -74,6 +97,8 @@ impl PeripheryClient {
     &self,
     container: String,
     shell: String,
+    user: String,
+    read_only: bool,
   ) -> anyhow::Result<WebSocketStream<MaybeTlsStream<TcpStream>>> {
     tracing::trace!(
       "request | type: ConnectContainerExec | container name: {container} | shell: {shell}",
@@ -88,15 +113,14 @@ impl PeripheryClient {
       token: token.token,
       container,
       shell,
+      user,
+      read_only,
     })
     .context("Failed to serialize query string")?;
 
-    let url = format!(
-      "{}/terminal/container?{query_str}",
-      self.address.replacen("http", "ws", 1)
-    );
-
-    connect_websocket(&url).await
+    self
+      .connect_websocket("/terminal/container", query_str)
+      .await
   }
 
   /// Executes command on specified container,
@@ -122,24 +146,74 @@ impl PeripheryClient {
     tracing::trace!(
       "sending request | type: ExecuteContainerExec | container: {container} | shell: {shell} | command: {command}",
     );
+    if self.handler.is_some() {
+      return Err(anyhow!(
+        "Terminals are only supported through Periphery"
+      ));
+    }
+    let body = serde_json::to_vec(&ExecuteContainerExecBody {
+      container,
+      shell,
+      command,
+    })
+    .context("Failed to serialize request body")?;
     let req = crate::periphery_http_client()
       .post(format!("{}/terminal/execute/container", self.address))
-      .json(&ExecuteContainerExecBody {
-        container,
-        shell,
-        command,
-      })
       .header("authorization", &self.passkey);
-    terminal_stream_response(req).await
+    terminal_stream_response(with_signed_body(
+      req,
+      self.signing_key.as_deref(),
+      "/terminal/execute/container",
+      &self.container_runtime.to_string(),
+      body,
+    ))
+    .await
+  }
+
+  /// Connects to the websocket with the same authorization
+  /// headers as the other requests. There is no body to sign,
+  /// so the query is signed instead. The websocket messages
+  /// after the upgrade are not signed.
+  async fn connect_websocket(
+    &self,
+    path: &str,
+    query_str: String,
+  ) -> anyhow::Result<WebSocketStream<MaybeTlsStream<TcpStream>>> {
+    let url = format!(
+      "{}{path}?{query_str}",
+      self.address.replacen("http", "ws", 1)
+    );
+    let mut request =
+      url.as_str().into_client_request().with_context(|| {
+        format!("Invalid websocket url | url: {url}")
+      })?;
+    let mut headers = vec![("authorization", self.passkey.clone())];
+    headers.extend(request_headers(
+      self.signing_key.as_deref(),
+      "GET",
+      path,
+      &self.container_runtime.to_string(),
+      query_str.as_bytes(),
+    ));
+    for (name, value) in headers {
+      request.headers_mut().insert(
+        name,
+        HeaderValue::from_str(&value).with_context(|| {
+          format!("Invalid value for websocket header {name}")
+        })?,
+      );
+    }
+    connect_websocket(&url, request).await
   }
 }
 
 async fn connect_websocket(
   url: &str,
+  request: Request,
 ) -> anyhow::Result<WebSocketStream<MaybeTlsStream<TcpStream>>> {
   let (stream, _) = if url.starts_with("wss") {
     tokio_tungstenite::connect_async_tls_with_config(
-      url,
+      request,
       None,
       false,
       Some(Connector::Rustls(Arc::new(
@@ -156,15 +230,17 @@ async fn connect_websocket(
       format!("failed to connect to websocket | url: {url}")
     })?
   } else {
-    tokio_tungstenite::connect_async(url).await.with_context(
-      || format!("failed to connect to websocket | url: {url}"),
-    )?
+    tokio_tungstenite::connect_async(request)
+      .await
+      .with_context(|| {
+        format!("failed to connect to websocket | url: {url}")
+      })?
   };
 
   Ok(stream)
 }
 
-async fn terminal_stream_response(
+pub(crate) async fn terminal_stream_response(
   req: RequestBuilder,
 ) -> anyhow::Result<TerminalStreamResponse> {
   let res =
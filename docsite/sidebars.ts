@@ -64,7 +64,9 @@ const sidebars: SidebarsConfig = {
           ],
         },
         "resources/docker-compose",
+        "resources/swarm-service",
         "resources/auto-update",
+        "resources/auto-stop",
         "resources/variables",
         "resources/procedures",
         "resources/sync-resources",
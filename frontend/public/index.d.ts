@@ -763,6 +763,11 @@ declare global {
     WEBHOOK_BRANCH?: string;
     WEBHOOK_BODY?: any;
   } & Record<string, any>;
+  /**
+   * Publish an output for later Procedure stages to reference
+   * with `[[stages.<stage>.<name>]]`.
+   */
+  var OUTPUT: (name: string, value: string | number | boolean) => void;
   /** YAML parsing utilities */
   var YAML: {
     /**
@@ -263,6 +263,38 @@ export const ActionConfig = ({ id }: { id: string }) => {
               schedule_alert: {
                 description: "Send an alert when the scheduled run occurs",
               },
+              schedule_missed_runs: (schedule_missed_runs, set) => (
+                <ConfigItem
+                  label="Missed Runs"
+                  description="What to do with scheduled runs missed while Core was down. An alert is sent either way."
+                >
+                  <Select
+                    value={schedule_missed_runs}
+                    onValueChange={(schedule_missed_runs) =>
+                      set({
+                        schedule_missed_runs:
+                          schedule_missed_runs as Types.MissedRunPolicy,
+                      })
+                    }
+                    disabled={disabled}
+                  >
+                    <SelectTrigger className="w-[200px]" disabled={disabled}>
+                      <SelectValue placeholder="Select Policy" />
+                    </SelectTrigger>
+                    <SelectContent>
+                      {Object.values(Types.MissedRunPolicy).map((policy) => (
+                        <SelectItem
+                          key={policy}
+                          value={policy!}
+                          className="cursor-pointer"
+                        >
+                          {policy}
+                        </SelectItem>
+                      ))}
+                    </SelectContent>
+                  </Select>
+                </ConfigItem>
+              ),
             },
           },
           {
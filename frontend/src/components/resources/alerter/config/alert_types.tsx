@@ -21,11 +21,13 @@ const ALERT_TYPES: Types.AlertData["type"][] = [
   "DeploymentAutoUpdated",
   // Misc
   "ScheduleRun",
+  "ScheduleMissed",
   "BuildFailed",
   "ResourceSyncPendingUpdates",
   "RepoBuildFailed",
   "ActionFailed",
   "ProcedureFailed",
+  "ProcedureApprovalRequired",
   "AwsBuilderTerminationFailed",
   "Custom",
 ];
@@ -1220,7 +1220,12 @@ export const useServerAddress = (
     try {
       return new URL(base);
     } catch {
-      return new URL("http://" + base);
+      // Bare IPv6 literals must be wrapped in brackets
+      const host =
+        base.split(":").length > 2 && !base.startsWith("[")
+          ? `[${base}]`
+          : base;
+      return new URL("http://" + host);
     }
   })();
 
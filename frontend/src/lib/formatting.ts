@@ -187,5 +187,7 @@ export function fmt_utc_offset(tz: Types.IanaTimezone): string {
 }
 
 export function fmt_port_mount(port: Types.Port) {
-  return `${port.IP ? port.IP + ":" : ""}${port.PublicPort ?? "NONE"}:${port.PrivatePort ?? "NONE"}${port.Type ? "/" + port.Type : ""}`;
+  // IPv6 host ips are wrapped in brackets, eg [::]:8080
+  const ip = port.IP?.includes(":") ? `[${port.IP}]` : port.IP;
+  return `${ip ? ip + ":" : ""}${port.PublicPort ?? "NONE"}:${port.PrivatePort ?? "NONE"}${port.Type ? "/" + port.Type : ""}`;
 }
@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::{future::Future, path::Path};
 
 use komodo_client::{
   entities::{komodo_timestamp, update::Log},
@@ -6,6 +6,21 @@ use komodo_client::{
 };
 use run_command::{CommandOutput, async_run_command};
 
+tokio::task_local! {
+  static AUDIT_LABEL: String;
+}
+
+/// Runs the future with the audit label attached to the commands
+/// it runs. Each command is then logged at info level with the label,
+/// so host-level audit logs can be correlated with Komodo users.
+/// The command itself isn't logged, as it may contain secrets.
+pub async fn with_audit_label<F: Future>(
+  label: String,
+  fut: F,
+) -> F::Output {
+  AUDIT_LABEL.scope(label, fut).await
+}
+
 pub async fn run_komodo_command(
   stage: &str,
   path: impl Into<Option<&Path>>,
@@ -18,6 +33,12 @@ pub async fn run_komodo_command(
   };
   let start_ts = komodo_timestamp();
   let output = async_run_command(&command).await;
+  let _ = AUDIT_LABEL.try_with(|label| {
+    tracing::info!(
+      "{label} | ran command | stage: {stage} | success: {}",
+      output.success()
+    )
+  });
   output_into_log(stage, command, start_ts, output)
 }
 
@@ -69,6 +90,58 @@ pub async fn run_komodo_command_with_sanitization(
   Some(log)
 }
 
+/// Runs the command like [run_komodo_command_with_sanitization],
+/// killing it if `cancel` completes first. The shell is replaced by
+/// the command so the kill reaches it, so it must be a single command.
+pub async fn run_komodo_command_cancellable(
+  stage: &str,
+  path: &Path,
+  command: impl AsRef<str>,
+  replacers: &[(String, String)],
+  cancel: impl Future<Output = ()>,
+) -> Log {
+  let command = command.as_ref();
+  let start_ts = komodo_timestamp();
+  let output = tokio::process::Command::new("sh")
+    .arg("-c")
+    .arg(format!("exec {command}"))
+    .current_dir(path)
+    .kill_on_drop(true)
+    .output();
+  let (stdout, stderr, success) = tokio::select! {
+    output = output => match output {
+      Ok(output) => (
+        String::from_utf8_lossy(&output.stdout).to_string(),
+        String::from_utf8_lossy(&output.stderr).to_string(),
+        output.status.success(),
+      ),
+      Err(e) => {
+        (String::new(), format!("Failed to run command | {e:?}"), false)
+      }
+    },
+    _ = cancel => {
+      (String::new(), String::from("The command was cancelled"), false)
+    }
+  };
+  let _ = AUDIT_LABEL.try_with(|label| {
+    tracing::info!(
+      "{label} | ran command | stage: {stage} | success: {success}"
+    )
+  });
+  Log {
+    stage: stage.to_string(),
+    command: svi::replace_in_string(
+      &format!("cd {} && {command}", path.display()),
+      replacers,
+    ),
+    stdout: svi::replace_in_string(&stdout, replacers),
+    stderr: svi::replace_in_string(&stderr, replacers),
+    success,
+    start_ts,
+    end_ts: komodo_timestamp(),
+  }
+}
+
 pub fn output_into_log(
   stage: &str,
   command: String,
@@ -2,21 +2,35 @@ use std::str::FromStr;
 
 use anyhow::{Context, anyhow};
 use komodo_client::entities::{
+  access_request::AccessRequest,
   action::Action,
   alert::Alert,
   alerter::Alerter,
   api_key::ApiKey,
+  audit::AuditLogEntry,
   build::Build,
   builder::Builder,
   config::DatabaseConfig,
   deployment::Deployment,
+  execution_approval::ExecutionApproval,
+  execution_schedule::{
+    ExecutionSchedule, ExecutionScheduleRun, ResourceSchedule,
+  },
+  external_status::ExternalStatus,
+  federation::FederationPeer,
   permission::Permission,
   procedure::Procedure,
   provider::{DockerRegistryAccount, GitProviderAccount},
+  queued_execution::QueuedExecution,
   repo::Repo,
+  resource_lock::{ResourceLock, ResourceLockWaiter},
   server::Server,
+  server_profile::ServerProfile,
+  silence::AlertSilence,
+  snapshot::Snapshot,
   stack::Stack,
   stats::SystemStatsRecord,
+  swarm::SwarmService,
   sync::ResourceSync,
   tag::Tag,
   update::Update,
@@ -51,6 +65,20 @@ pub struct Client {
   pub updates: Collection<Update>,
   pub alerts: Collection<Alert>,
   pub stats: Collection<SystemStatsRecord>,
+  pub snapshots: Collection<Snapshot>,
+  pub silences: Collection<AlertSilence>,
+  pub server_profiles: Collection<ServerProfile>,
+  pub external_statuses: Collection<ExternalStatus>,
+  pub queued_executions: Collection<QueuedExecution>,
+  pub execution_schedules: Collection<ExecutionSchedule>,
+  pub execution_schedule_runs: Collection<ExecutionScheduleRun>,
+  pub resource_schedules: Collection<ResourceSchedule>,
+  pub resource_locks: Collection<ResourceLock>,
+  pub resource_lock_waiters: Collection<ResourceLockWaiter>,
+  pub execution_approvals: Collection<ExecutionApproval>,
+  pub federation_peers: Collection<FederationPeer>,
+  pub audit_log: Collection<AuditLogEntry>,
+  pub access_requests: Collection<AccessRequest>,
   // RESOURCES
   pub servers: Collection<Server>,
   pub deployments: Collection<Deployment>,
@@ -62,6 +90,7 @@ pub struct Client {
   pub alerters: Collection<Alerter>,
   pub resource_syncs: Collection<ResourceSync>,
   pub stacks: Collection<Stack>,
+  pub swarm_services: Collection<SwarmService>,
   //
   pub db: Database,
 }
@@ -87,6 +116,25 @@ impl Client {
       updates: mongo_indexed::collection(&db, true).await?,
       alerts: mongo_indexed::collection(&db, true).await?,
       stats: mongo_indexed::collection(&db, true).await?,
+      snapshots: mongo_indexed::collection(&db, true).await?,
+      silences: mongo_indexed::collection(&db, true).await?,
+      server_profiles: mongo_indexed::collection(&db, true).await?,
+      external_statuses: mongo_indexed::collection(&db, true).await?,
+      queued_executions: mongo_indexed::collection(&db, true).await?,
+      execution_schedules: mongo_indexed::collection(&db, true)
+        .await?,
+      execution_schedule_runs: mongo_indexed::collection(&db, true)
+        .await?,
+      resource_schedules: mongo_indexed::collection(&db, true)
+        .await?,
+      resource_locks: mongo_indexed::collection(&db, true).await?,
+      resource_lock_waiters: mongo_indexed::collection(&db, true)
+        .await?,
+      execution_approvals: mongo_indexed::collection(&db, true)
+        .await?,
+      federation_peers: mongo_indexed::collection(&db, true).await?,
+      audit_log: mongo_indexed::collection(&db, true).await?,
+      access_requests: mongo_indexed::collection(&db, true).await?,
       // RESOURCES
       servers: resource_collection(&db, "Server").await?,
       deployments: resource_collection(&db, "Deployment").await?,
@@ -99,6 +147,8 @@ impl Client {
       resource_syncs: resource_collection(&db, "ResourceSync")
         .await?,
       stacks: resource_collection(&db, "Stack").await?,
+      swarm_services: resource_collection(&db, "SwarmService")
+        .await?,
       //
       db,
     };
@@ -1,14 +1,19 @@
 use std::collections::{HashMap, HashSet};
 
 use anyhow::Context;
-use komodo_client::entities::{
-  EnvironmentVar, build::Build, deployment::Deployment, repo::Repo,
-  stack::Stack, update::Log,
+use komodo_client::{
+  entities::{
+    EnvironmentVar, build::Build, deployment::Deployment, repo::Repo,
+    server::Server, stack::Stack, swarm::SwarmService, update::Log,
+  },
+  parsers::parse_key_value_list,
 };
 
 pub struct Interpolator<'a> {
   variables: Option<&'a HashMap<String, String>>,
   secrets: &'a HashMap<String, String>,
+  server_attributes: Option<HashMap<String, String>>,
+  resource_metadata: Option<HashMap<String, String>>,
   variable_replacers: HashSet<(String, String)>,
   pub secret_replacers: HashSet<(String, String)>,
 }
@@ -21,11 +26,50 @@ impl<'a> Interpolator<'a> {
     Interpolator {
       variables,
       secrets,
+      server_attributes: None,
+      resource_metadata: None,
       variable_replacers: Default::default(),
       secret_replacers: Default::default(),
     }
   }
 
+  /// Also interpolate the attributes of the target Server,
+  /// using `${server.name}`, `${server.address}`, `${server.external_address}`,
+  /// `${server.region}`, or the custom `${server.KEY}` attributes / metadata.
+  pub fn with_server(
+    mut self,
+    server: &Server,
+  ) -> anyhow::Result<Self> {
+    let mut attributes = server.metadata.clone();
+    attributes.extend(
+      parse_key_value_list(&server.config.attributes)
+        .context("Invalid server attributes")?,
+    );
+    // The built in attributes take precedence
+    attributes.extend([
+      (String::from("id"), server.id.clone()),
+      (String::from("name"), server.name.clone()),
+      (String::from("address"), server.config.address.clone()),
+      (
+        String::from("external_address"),
+        server.config.external_address.clone(),
+      ),
+      (String::from("region"), server.config.region.clone()),
+    ]);
+    self.server_attributes = Some(attributes);
+    Ok(self)
+  }
+
+  /// Also interpolate the metadata of a resource,
+  /// using `${resource.KEY}`.
+  pub fn with_resource_metadata(
+    mut self,
+    metadata: &HashMap<String, String>,
+  ) -> Self {
+    self.resource_metadata = Some(metadata.clone());
+    self
+  }
+
   pub fn interpolate_stack(
     &mut self,
     stack: &mut Stack,
@@ -52,7 +96,11 @@ impl<'a> Interpolator<'a> {
     self
       .interpolate_string(&mut repo.config.environment)?
       .interpolate_string(&mut repo.config.on_clone.command)?
-      .interpolate_string(&mut repo.config.on_pull.command)
+      .interpolate_string(&mut repo.config.on_pull.command)?;
+    for step in &mut repo.config.pipeline {
+      self.interpolate_string(&mut step.command)?;
+    }
+    Ok(self)
   }
 
   pub fn interpolate_build(
@@ -68,7 +116,11 @@ impl<'a> Interpolator<'a> {
       .interpolate_string(&mut build.config.labels)?
       .interpolate_string(&mut build.config.pre_build.command)?
       .interpolate_string(&mut build.config.dockerfile)?
-      .interpolate_extra_args(&mut build.config.extra_args)
+      .interpolate_extra_args(&mut build.config.extra_args)?;
+    for variant in &mut build.config.matrix {
+      self.interpolate_string(&mut variant.build_args)?;
+    }
+    Ok(self)
   }
 
   pub fn interpolate_deployment(
@@ -87,6 +139,22 @@ impl<'a> Interpolator<'a> {
       .interpolate_extra_args(&mut deployment.config.extra_args)
   }
 
+  pub fn interpolate_swarm_service(
+    &mut self,
+    swarm_service: &mut SwarmService,
+  ) -> anyhow::Result<&mut Self> {
+    if swarm_service.config.skip_secret_interp {
+      return Ok(self);
+    }
+    self
+      .interpolate_string(&mut swarm_service.config.file_contents)?
+      .interpolate_string(&mut swarm_service.config.environment)?
+      .interpolate_string(&mut swarm_service.config.ports)?
+      .interpolate_string(&mut swarm_service.config.labels)?
+      .interpolate_string(&mut swarm_service.config.command)?
+      .interpolate_extra_args(&mut swarm_service.config.extra_args)
+  }
+
   pub fn interpolate_string(
     &mut self,
     target: &mut String,
@@ -95,6 +163,24 @@ impl<'a> Interpolator<'a> {
       return Ok(self);
     }
 
+    // server attributes / resource metadata pass
+    if let Some(attributes) = &self.server_attributes {
+      *target = interpolate_attributes(
+        target,
+        "server",
+        attributes,
+        &mut self.variable_replacers,
+      )?;
+    }
+    if let Some(metadata) = &self.resource_metadata {
+      *target = interpolate_attributes(
+        target,
+        "resource",
+        metadata,
+        &mut self.variable_replacers,
+      )?;
+    }
+
     // first pass - variables
     let res = if let Some(variables) = self.variables {
       let (res, more_replacers) = svi::interpolate_variables(
@@ -156,6 +242,20 @@ impl<'a> Interpolator<'a> {
     Ok(self)
   }
 
+  /// The names of the interpolated variables, secrets and attributes,
+  /// without their values.
+  pub fn interpolated_names(&self) -> Vec<String> {
+    let mut names = self
+      .variable_replacers
+      .iter()
+      .chain(&self.secret_replacers)
+      .map(|(_, name)| name.clone())
+      .collect::<Vec<_>>();
+    names.sort();
+    names.dedup();
+    names
+  }
+
   pub fn push_logs(&self, logs: &mut Vec<Log>) {
     // Show which variables / values were interpolated
     if !self.variable_replacers.is_empty() {
@@ -179,3 +279,32 @@ impl<'a> Interpolator<'a> {
     }
   }
 }
+
+/// Replaces `${NAMESPACE.KEY}` with the attribute,
+/// failing if there is no such attribute.
+fn interpolate_attributes(
+  target: &str,
+  namespace: &str,
+  attributes: &HashMap<String, String>,
+  replacers: &mut HashSet<(String, String)>,
+) -> anyhow::Result<String> {
+  let prefix = format!("${{{namespace}.");
+  let mut res = String::with_capacity(target.len());
+  let mut rest = target;
+  while let Some(start) = rest.find(&prefix) {
+    res.push_str(&rest[..start]);
+    let after = &rest[start + prefix.len()..];
+    let end = after.find('}').with_context(|| {
+      format!("Unclosed {namespace} attribute in target '{target}'")
+    })?;
+    let key = &after[..end];
+    let value = attributes.get(key).with_context(|| {
+      format!("No {namespace} attribute '{key}' to interpolate")
+    })?;
+    replacers.insert((value.clone(), format!("{namespace}.{key}")));
+    res.push_str(value);
+    rest = &after[end + 1..];
+  }
+  res.push_str(rest);
+  Ok(res)
+}
@@ -0,0 +1,409 @@
+//! Pulls files from OCI artifacts, as pushed by `oras push`.
+//! Each file is a layer, named by its title annotation.
+//! Used for Resource Sync and Stack files.
+
+use std::{
+  collections::{HashMap, HashSet},
+  path::{Component, Path},
+  sync::OnceLock,
+  time::Duration,
+};
+
+use anyhow::{Context, anyhow};
+use reqwest::{StatusCode, header};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use tokio::fs;
+
+static APP_USER_AGENT: &str =
+  concat!("Komodo/", env!("CARGO_PKG_VERSION"),);
+
+const MANIFEST_ACCEPT: &str = "application/vnd.oci.image.manifest.v1+json, application/vnd.docker.distribution.manifest.v2+json";
+const TITLE_ANNOTATION: &str = "org.opencontainers.image.title";
+const REVISION_ANNOTATION: &str = "org.opencontainers.image.revision";
+const UNPACK_ANNOTATION: &str = "io.deis.oras.content.unpack";
+
+fn http_client() -> &'static reqwest::Client {
+  static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+  CLIENT.get_or_init(|| {
+    reqwest::Client::builder()
+      .user_agent(APP_USER_AGENT)
+      .timeout(Duration::from_secs(60))
+      .build()
+      .expect("Invalid OCI reqwest client")
+  })
+}
+
+pub struct PulledArtifact {
+  /// The manifest digest, eg. `sha256:...`
+  pub digest: String,
+  /// The `org.opencontainers.image.revision` annotation, if set.
+  pub revision: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Manifest {
+  #[serde(default)]
+  layers: Vec<Descriptor>,
+  #[serde(default)]
+  annotations: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct Descriptor {
+  #[serde(default, rename = "mediaType")]
+  media_type: String,
+  digest: String,
+  #[serde(default)]
+  annotations: HashMap<String, String>,
+}
+
+/// Pulls the artifact files into the directory.
+/// The files are only downloaded again when the artifact digest changes.
+/// Other files in the directory are left alone, except those
+/// written by the previous artifact which are no longer in it.
+pub async fn pull_oci_artifact(
+  artifact: &str,
+  credentials: Option<(&str, &str)>,
+  dir: &Path,
+) -> anyhow::Result<PulledArtifact> {
+  let reference = ArtifactReference::parse(artifact)?;
+  let mut registry = RegistryClient {
+    reference: &reference,
+    credentials,
+    bearer: None,
+  };
+
+  let manifest_bytes = registry
+    .get(
+      &format!("manifests/{}", reference.reference),
+      MANIFEST_ACCEPT,
+    )
+    .await
+    .with_context(|| {
+      format!("Failed to get manifest for {artifact}")
+    })?;
+  let digest = sha256_digest(&manifest_bytes);
+  if reference.pinned && digest != reference.reference {
+    return Err(anyhow!(
+      "Manifest digest {digest} does not match pinned digest {}",
+      reference.reference
+    ));
+  }
+  let manifest: Manifest = serde_json::from_slice(&manifest_bytes)
+    .context("Failed to parse artifact manifest. Image indexes are not supported.")?;
+  let revision =
+    manifest.annotations.get(REVISION_ANNOTATION).cloned();
+
+  // Skip the download if the files are already at this digest.
+  // The first line of the marker is the digest, followed by the files.
+  let marker_path = dir.with_extension("digest");
+  let marker =
+    fs::read_to_string(&marker_path).await.unwrap_or_default();
+  let mut marker = marker.lines();
+  if marker.next() == Some(digest.as_str())
+    && fs::try_exists(dir).await.unwrap_or_default()
+  {
+    return Ok(PulledArtifact { digest, revision });
+  }
+  let previous_files = marker.map(str::to_string).collect::<Vec<_>>();
+
+  if manifest.layers.is_empty() {
+    return Err(anyhow!("Artifact {artifact} has no files"));
+  }
+
+  let mut files = Vec::with_capacity(manifest.layers.len());
+  for layer in manifest.layers {
+    let title =
+      layer.annotations.get(TITLE_ANNOTATION).with_context(|| {
+        format!(
+          "Artifact layer {} is missing the {TITLE_ANNOTATION} annotation",
+          layer.digest
+        )
+      })?;
+    if layer.annotations.contains_key(UNPACK_ANNOTATION)
+      || layer.media_type.contains(".tar")
+    {
+      return Err(anyhow!(
+        "Artifact layer {title} is a directory archive, which is not supported. Push the files individually."
+      ));
+    }
+    let contents = registry
+      .get(&format!("blobs/{}", layer.digest), "*/*")
+      .await
+      .with_context(|| {
+        format!("Failed to get artifact file {title}")
+      })?;
+    if sha256_digest(&contents) != layer.digest {
+      return Err(anyhow!(
+        "Artifact file {title} does not match its digest {}",
+        layer.digest
+      ));
+    }
+    files.push((title.clone(), contents));
+  }
+
+  write_files(dir, &files, &previous_files).await?;
+  let marker = std::iter::once(digest.as_str())
+    .chain(files.iter().map(|(path, _)| path.as_str()))
+    .collect::<Vec<_>>()
+    .join("\n");
+  fs::write(&marker_path, marker).await.with_context(|| {
+    format!("Failed to write artifact digest to {marker_path:?}")
+  })?;
+
+  Ok(PulledArtifact { digest, revision })
+}
+
+/// Writes the files, and removes the previous files not among them.
+/// The file paths must be relative and stay in the directory.
+async fn write_files(
+  dir: &Path,
+  files: &[(String, Vec<u8>)],
+  previous_files: &[String],
+) -> anyhow::Result<()> {
+  for (path, _) in files {
+    let path = Path::new(path);
+    if !path
+      .components()
+      .all(|component| matches!(component, Component::Normal(_)))
+    {
+      return Err(anyhow!(
+        "Invalid artifact file path {path:?}. Must be relative, and not contain '..'"
+      ));
+    }
+  }
+  let current = files
+    .iter()
+    .map(|(path, _)| path.as_str())
+    .collect::<HashSet<_>>();
+  for path in previous_files {
+    let stale = Path::new(path);
+    if current.contains(path.as_str())
+      || !stale
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+      continue;
+    }
+    let _ = fs::remove_file(dir.join(stale)).await;
+  }
+  for (path, contents) in files {
+    let path = dir.join(path);
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent).await.with_context(|| {
+        format!("Failed to create directory {parent:?}")
+      })?;
+    }
+    fs::write(&path, contents).await.with_context(|| {
+      format!("Failed to write artifact file {path:?}")
+    })?;
+  }
+  Ok(())
+}
+
+/// The first 12 characters of the digest, without the algorithm.
+pub fn short_digest(digest: &str) -> String {
+  let digest = digest.trim_start_matches("sha256:");
+  digest[..digest.len().min(12)].to_string()
+}
+
+/// The registry domain of the artifact, eg. `ghcr.io`,
+/// used to find the registry account.
+pub fn registry_domain(artifact: &str) -> anyhow::Result<String> {
+  ArtifactReference::parse(artifact).map(|reference| reference.domain)
+}
+
+fn sha256_digest(contents: &[u8]) -> String {
+  format!("sha256:{}", hex::encode(Sha256::digest(contents)))
+}
+
+struct ArtifactReference {
+  /// eg. `ghcr.io`
+  domain: String,
+  /// eg. `https://ghcr.io`
+  base: String,
+  /// eg. `org/config`
+  repository: String,
+  /// The tag or digest
+  reference: String,
+  /// Whether the reference is a digest
+  pinned: bool,
+}
+
+impl ArtifactReference {
+  fn parse(artifact: &str) -> anyhow::Result<ArtifactReference> {
+    let artifact = artifact.trim().trim_start_matches("oci://");
+    let (name, reference, pinned) = if let Some((name, digest)) =
+      artifact.split_once('@')
+    {
+      if !digest.starts_with("sha256:") {
+        return Err(anyhow!(
+          "Only sha256 digests are supported, got {digest}"
+        ));
+      }
+      (name, digest, true)
+    } else {
+      match artifact.rsplit_once(':') {
+        // Make sure the ':' isn't a registry port
+        Some((name, tag)) if !tag.contains('/') => (name, tag, false),
+        _ => (artifact, "latest", false),
+      }
+    };
+    let (domain, repository) = match name.split_once('/') {
+      Some((domain, repository))
+        if domain.contains('.')
+          || domain.contains(':')
+          || domain == "localhost" =>
+      {
+        (domain, repository.to_string())
+      }
+      Some(_) => ("docker.io", name.to_string()),
+      None => ("docker.io", format!("library/{name}")),
+    };
+    if repository.is_empty() {
+      return Err(anyhow!(
+        "Invalid OCI artifact reference {artifact}"
+      ));
+    }
+    let base = match domain {
+      "docker.io" => String::from("https://registry-1.docker.io"),
+      domain => format!("https://{domain}"),
+    };
+    Ok(ArtifactReference {
+      domain: domain.to_string(),
+      base,
+      repository,
+      reference: reference.to_string(),
+      pinned,
+    })
+  }
+}
+
+struct RegistryClient<'a> {
+  reference: &'a ArtifactReference,
+  /// Username and token
+  credentials: Option<(&'a str, &'a str)>,
+  /// The token from the registry auth challenge
+  bearer: Option<String>,
+}
+
+impl RegistryClient<'_> {
+  /// Gets `/v2/<repository>/<path>`, answering the auth challenge if needed.
+  async fn get(
+    &mut self,
+    path: &str,
+    accept: &str,
+  ) -> anyhow::Result<Vec<u8>> {
+    let url = format!(
+      "{}/v2/{}/{path}",
+      self.reference.base, self.reference.repository
+    );
+    let res = self.request(&url, accept).send().await?;
+    let res = if res.status() == StatusCode::UNAUTHORIZED
+      && self.bearer.is_none()
+    {
+      let challenge = res
+        .headers()
+        .get(header::WWW_AUTHENTICATE)
+        .and_then(|challenge| challenge.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+      self.authenticate(&challenge).await?;
+      self.request(&url, accept).send().await?
+    } else {
+      res
+    };
+    let status = res.status();
+    if !status.is_success() {
+      let body = res.text().await.unwrap_or_default();
+      return Err(anyhow!("Request failed with {status} | {body}"));
+    }
+    Ok(res.bytes().await?.to_vec())
+  }
+
+  fn request(
+    &self,
+    url: &str,
+    accept: &str,
+  ) -> reqwest::RequestBuilder {
+    let req = http_client().get(url).header(header::ACCEPT, accept);
+    match (&self.bearer, self.credentials) {
+      (Some(bearer), _) => req.bearer_auth(bearer),
+      (None, Some((username, token))) => {
+        req.basic_auth(username, Some(token))
+      }
+      (None, None) => req,
+    }
+  }
+
+  async fn authenticate(
+    &mut self,
+    challenge: &str,
+  ) -> anyhow::Result<()> {
+    let Some(params) = challenge.strip_prefix("Bearer ") else {
+      return Err(anyhow!(
+        "Registry requires authentication. Configure the OCI account."
+      ));
+    };
+    let params = parse_challenge_params(params);
+    let realm = params
+      .iter()
+      .find(|(key, _)| key == "realm")
+      .map(|(_, value)| value.as_str())
+      .context("Registry auth challenge is missing realm")?;
+    let scope =
+      format!("repository:{}:pull", self.reference.repository);
+    let mut query = vec![("scope", scope.as_str())];
+    if let Some((_, service)) =
+      params.iter().find(|(key, _)| key == "service")
+    {
+      query.push(("service", service));
+    }
+    let mut req = http_client().get(realm).query(&query);
+    if let Some((username, token)) = self.credentials {
+      req = req.basic_auth(username, Some(token));
+    }
+    let res =
+      req.send().await.context("Failed to reach registry auth")?;
+    let status = res.status();
+    if !status.is_success() {
+      let body = res.text().await.unwrap_or_default();
+      return Err(anyhow!(
+        "Registry auth failed with {status} | {body}"
+      ));
+    }
+    #[derive(Deserialize)]
+    struct TokenResponse {
+      token: Option<String>,
+      access_token: Option<String>,
+    }
+    let TokenResponse {
+      token,
+      access_token,
+    } = res
+      .json()
+      .await
+      .context("Failed to parse registry auth response")?;
+    self.bearer = Some(
+      token
+        .or(access_token)
+        .context("Registry auth response is missing token")?,
+    );
+    Ok(())
+  }
+}
+
+/// Parses `key="value",key2="value2"` from a `WWW-Authenticate` header.
+fn parse_challenge_params(params: &str) -> Vec<(String, String)> {
+  params
+    .split(',')
+    .filter_map(|param| {
+      let (key, value) = param.split_once('=')?;
+      Some((
+        key.trim().to_string(),
+        value.trim().trim_matches('"').to_string(),
+      ))
+    })
+    .collect()
+}